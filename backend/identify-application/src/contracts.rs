@@ -1 +1,34 @@
+pub mod authorization_policy;
+pub mod blob_storage;
+pub mod consent;
+pub mod device;
+pub mod email_alias;
+pub mod email_change;
+pub mod email_sender;
+pub mod email_verification;
+pub mod event_publisher;
+pub mod geo_resolver;
+pub mod invitation;
+pub mod login_recorder;
+pub mod membership;
+pub mod operation;
+pub mod organization;
+pub mod organization_quota;
+pub mod password_hasher;
+pub mod phone_verification;
+pub mod reverification;
+pub mod role;
+pub mod session_store;
+pub mod signing_key;
+pub mod signing_key_generator;
+pub mod sms_sender;
+pub mod token_generator;
+pub mod unit_of_work;
+pub mod use_case_metrics;
+pub mod use_case_retries;
 pub mod user;
+pub mod user_attribute;
+pub mod user_event;
+pub mod user_summary;
+pub mod webhook_delivery;
+pub mod webhook_endpoint;
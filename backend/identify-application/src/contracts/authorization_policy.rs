@@ -0,0 +1,20 @@
+use crate::Result;
+use async_trait::async_trait;
+use identify_domain::AuthorizationPolicy;
+
+/// Implementors of this contract are able to list all the
+/// [AuthorizationPolicies](AuthorizationPolicy) kept in the underlying persistent
+/// storage, which are expected to be few.
+#[async_trait]
+pub trait ListAll {
+    /// List all the policies, ordered by their names.
+    async fn list_all(&mut self) -> Result<Vec<AuthorizationPolicy>>;
+}
+
+/// Implementors of this contract are able to insert new
+/// [AuthorizationPolicies](AuthorizationPolicy) into the underlying persistent storage.
+#[async_trait]
+pub trait Insert {
+    /// Insert a new policy.
+    async fn insert(&mut self, entity: &AuthorizationPolicy) -> Result<()>;
+}
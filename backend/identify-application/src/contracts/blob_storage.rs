@@ -0,0 +1,43 @@
+use std::sync::Arc;
+
+use crate::Result;
+use async_trait::async_trait;
+
+/// Binary content stored under a key, along with its content type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Blob {
+    pub content_type: String,
+    pub bytes: Vec<u8>,
+}
+
+/// Implementors of this contract store [Blobs](Blob) outside of the database, e.g. on the
+/// filesystem or in an S3-compatible bucket.
+///
+/// Keys are made of `/`-separated segments of ASCII letters, digits, `.`, `_` and `-` that don't
+/// start with `.`, e.g. `avatars/<user ID>`.
+#[async_trait]
+pub trait BlobStorage: Send + Sync {
+    /// Stores the blob, replacing the one already stored under the key.
+    async fn put(&self, key: &str, blob: &Blob) -> Result<()>;
+
+    /// Returns the blob stored under the key, if any.
+    async fn get(&self, key: &str) -> Result<Option<Blob>>;
+
+    /// Deletes the blob stored under the key. Succeeds if there is none.
+    async fn delete(&self, key: &str) -> Result<()>;
+}
+
+#[async_trait]
+impl<S: BlobStorage + ?Sized> BlobStorage for Arc<S> {
+    async fn put(&self, key: &str, blob: &Blob) -> Result<()> {
+        (**self).put(key, blob).await
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Blob>> {
+        (**self).get(key).await
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        (**self).delete(key).await
+    }
+}
@@ -0,0 +1,28 @@
+use crate::Result;
+use async_trait::async_trait;
+use identify_domain::Consent;
+use uuid::Uuid;
+
+/// Implementors of this contract are able to list the [Consents](Consent) of a user.
+#[async_trait]
+pub trait ListByUser {
+    /// List all the consents of the user, including the ones to outdated versions of
+    /// policies, the most recent first.
+    async fn list_by_user(&mut self, user_id: Uuid) -> Result<Vec<Consent>>;
+}
+
+/// Implementors of this contract are able to persist new [Consents](Consent).
+#[async_trait]
+pub trait Insert {
+    /// Insert the consent. Fails if the user already accepted the same version of the policy.
+    async fn insert(&mut self, entity: &Consent) -> Result<()>;
+}
+
+/// Implementors of this contract are able to move the [Consents](Consent) of a user to another
+/// one, e.g. when merging duplicate users.
+#[async_trait]
+pub trait Reassign {
+    /// Move the consents of `from` to `to`, except to versions of policies `to` has already
+    /// accepted, which are left to `from`. Returns how many consents were moved.
+    async fn reassign(&mut self, from: Uuid, to: Uuid) -> Result<u64>;
+}
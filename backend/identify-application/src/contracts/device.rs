@@ -0,0 +1,63 @@
+use crate::Result;
+use async_trait::async_trait;
+use identify_domain::Device;
+use uuid::Uuid;
+
+/// Implementors of this contract are able to retrieve a [Device] of a user.
+#[async_trait]
+pub trait Get {
+    /// Get the device of the user with the ID. Fails if the user has no such device.
+    async fn get(&mut self, user_id: Uuid, id: Uuid) -> Result<Device>;
+}
+
+/// Implementors of this contract are able to recognize the [Devices](Device) users log
+/// in from.
+#[async_trait]
+pub trait GetByFingerprint {
+    /// Get the device of the user with the fingerprint that isn't revoked, if any.
+    async fn get_by_fingerprint(
+        &mut self,
+        user_id: Uuid,
+        fingerprint: &str,
+    ) -> Result<Option<Device>>;
+}
+
+/// Implementors of this contract are able to list the [Devices](Device) of a user.
+#[async_trait]
+pub trait ListByUser {
+    /// List all the devices of the user, including the revoked ones, the most recently seen
+    /// first.
+    async fn list_by_user(&mut self, user_id: Uuid) -> Result<Vec<Device>>;
+}
+
+/// Implementors of this contract are able to persist new [Devices](Device).
+#[async_trait]
+pub trait Insert {
+    /// Insert the device.
+    async fn insert(&mut self, entity: &Device) -> Result<()>;
+}
+
+/// Implementors of this contract are able to persist changes of [Devices](Device).
+#[async_trait]
+pub trait Update {
+    /// Update the device.
+    async fn update(&mut self, entity: &Device) -> Result<()>;
+}
+
+/// Implementors of this contract are able to delete all the [Devices](Device) of a user
+/// at once.
+#[async_trait]
+pub trait DeleteAll {
+    /// Delete all the devices of the user, returning how many there were.
+    async fn delete_all(&mut self, user_id: Uuid) -> Result<u64>;
+}
+
+/// Implementors of this contract are able to move the [Devices](Device) of a user to another
+/// one, e.g. when merging duplicate users.
+#[async_trait]
+pub trait Reassign {
+    /// Move the devices of `from` to `to`. Devices of `from` whose fingerprints `to` already
+    /// trusts are revoked, since a user trusts at most one device with the same fingerprint.
+    /// Returns how many devices were moved.
+    async fn reassign(&mut self, from: Uuid, to: Uuid) -> Result<u64>;
+}
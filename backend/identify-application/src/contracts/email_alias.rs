@@ -0,0 +1,33 @@
+use crate::Result;
+use async_trait::async_trait;
+use identify_domain::EmailAlias;
+use uuid::Uuid;
+
+/// Implementors of this contract are able to list the [EmailAliases](EmailAlias) of a user.
+#[async_trait]
+pub trait ListByUser {
+    /// List all the aliases of the user, the oldest first.
+    async fn list_by_user(&mut self, user_id: Uuid) -> Result<Vec<EmailAlias>>;
+}
+
+/// Implementors of this contract are able to persist new [EmailAliases](EmailAlias).
+#[async_trait]
+pub trait Insert {
+    /// Insert the alias. Fails if the email is already an alias of any user.
+    async fn insert(&mut self, entity: &EmailAlias) -> Result<()>;
+}
+
+/// Implementors of this contract are able to delete an [EmailAlias] of a user.
+#[async_trait]
+pub trait Delete {
+    /// Delete the alias of the user with the ID. Fails if the user has no such alias.
+    async fn delete(&mut self, user_id: Uuid, id: Uuid) -> Result<()>;
+}
+
+/// Implementors of this contract are able to delete all the [EmailAliases](EmailAlias) of a
+/// user at once.
+#[async_trait]
+pub trait DeleteAll {
+    /// Delete all the aliases of the user, returning how many there were.
+    async fn delete_all(&mut self, user_id: Uuid) -> Result<u64>;
+}
@@ -0,0 +1,33 @@
+use crate::Result;
+use async_trait::async_trait;
+use identify_domain::EmailChange;
+use uuid::Uuid;
+
+/// Implementors of this contract are able to retrieve an [EmailChange].
+#[async_trait]
+pub trait Get {
+    /// Get the change with the ID. Fails if there's no such change.
+    async fn get(&mut self, id: Uuid) -> Result<EmailChange>;
+}
+
+/// Implementors of this contract are able to persist new [EmailChanges](EmailChange).
+#[async_trait]
+pub trait Insert {
+    /// Insert the change.
+    async fn insert(&mut self, entity: &EmailChange) -> Result<()>;
+}
+
+/// Implementors of this contract are able to persist changes of
+/// [EmailChanges](EmailChange).
+#[async_trait]
+pub trait Update {
+    /// Update the change.
+    async fn update(&mut self, entity: &EmailChange) -> Result<()>;
+}
+
+/// Implementors of this contract are able to delete the [EmailChanges](EmailChange) of a user.
+#[async_trait]
+pub trait DeleteAll {
+    /// Delete all the changes the user requested, returning how many there were.
+    async fn delete_all(&mut self, user_id: Uuid) -> Result<u64>;
+}
@@ -0,0 +1,29 @@
+use std::sync::Arc;
+
+use crate::Result;
+use async_trait::async_trait;
+
+/// An email to a single recipient, with a plain-text and optionally an HTML part, see
+/// [EmailTemplates](crate::EmailTemplates).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Email {
+    pub to: String,
+    pub subject: String,
+    pub text: String,
+    pub html: Option<String>,
+}
+
+/// Implementors of this contract are able to send [Emails](Email) to users, e.g. through an SMTP
+/// server.
+#[async_trait]
+pub trait EmailSender: Send + Sync {
+    /// Send the email, returning once it has been accepted for delivery.
+    async fn send(&self, email: &Email) -> Result<()>;
+}
+
+#[async_trait]
+impl<S: EmailSender + ?Sized> EmailSender for Arc<S> {
+    async fn send(&self, email: &Email) -> Result<()> {
+        (**self).send(email).await
+    }
+}
@@ -0,0 +1,27 @@
+use crate::Result;
+use async_trait::async_trait;
+use identify_domain::EmailVerification;
+use uuid::Uuid;
+
+/// Implementors of this contract are able to retrieve an [EmailVerification].
+#[async_trait]
+pub trait Get {
+    /// Get the verification with the ID. Fails if there's no such verification.
+    async fn get(&mut self, id: Uuid) -> Result<EmailVerification>;
+}
+
+/// Implementors of this contract are able to persist new
+/// [EmailVerifications](EmailVerification).
+#[async_trait]
+pub trait Insert {
+    /// Insert the verification.
+    async fn insert(&mut self, entity: &EmailVerification) -> Result<()>;
+}
+
+/// Implementors of this contract are able to persist changes of
+/// [EmailVerifications](EmailVerification).
+#[async_trait]
+pub trait Update {
+    /// Update the verification.
+    async fn update(&mut self, entity: &EmailVerification) -> Result<()>;
+}
@@ -0,0 +1,41 @@
+use std::sync::Arc;
+
+use crate::Result;
+use async_trait::async_trait;
+use identify_domain::UserEvent;
+
+/// Implementors of this contract are able to stream [UserEvents](identify_domain::UserEvent)
+/// drained from the outbox to other services, e.g. through a message broker.
+#[async_trait]
+pub trait EventPublisher: Send + Sync {
+    /// Publish the event.
+    ///
+    /// An event can be published more than once, e.g. if the outbox is drained again after a
+    /// crash, so the consumers should deduplicate events by their ID.
+    async fn publish(&self, event: &UserEvent) -> Result<()>;
+}
+
+#[async_trait]
+impl<P: EventPublisher + ?Sized> EventPublisher for Box<P> {
+    async fn publish(&self, event: &UserEvent) -> Result<()> {
+        (**self).publish(event).await
+    }
+}
+
+#[async_trait]
+impl<P: EventPublisher + ?Sized> EventPublisher for Arc<P> {
+    async fn publish(&self, event: &UserEvent) -> Result<()> {
+        (**self).publish(event).await
+    }
+}
+
+/// Publishes nothing if there is no publisher, e.g. if streaming isn't configured.
+#[async_trait]
+impl<P: EventPublisher> EventPublisher for Option<P> {
+    async fn publish(&self, event: &UserEvent) -> Result<()> {
+        match self {
+            Some(publisher) => publisher.publish(event).await,
+            None => Ok(()),
+        }
+    }
+}
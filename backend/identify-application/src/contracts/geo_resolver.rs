@@ -0,0 +1,19 @@
+use std::{net::IpAddr, sync::Arc};
+
+use crate::Result;
+use async_trait::async_trait;
+
+/// Implementors of this contract are able to tell the approximate location of IP addresses,
+/// e.g. from a GeoIP database.
+#[async_trait]
+pub trait GeoResolver: Send + Sync {
+    /// Returns where the address is located, e.g. `Paris, France`, if it's known.
+    async fn locate(&self, ip: IpAddr) -> Result<Option<String>>;
+}
+
+#[async_trait]
+impl<G: GeoResolver + ?Sized> GeoResolver for Arc<G> {
+    async fn locate(&self, ip: IpAddr) -> Result<Option<String>> {
+        (**self).locate(ip).await
+    }
+}
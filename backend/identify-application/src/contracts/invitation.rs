@@ -0,0 +1,33 @@
+use crate::Result;
+use async_trait::async_trait;
+use identify_domain::Invitation;
+use uuid::Uuid;
+
+/// Implementors of this contract are able to retrieve an [Invitation].
+#[async_trait]
+pub trait Get {
+    /// Get the invitation with the ID. Fails if there's no such invitation.
+    async fn get(&mut self, id: Uuid) -> Result<Invitation>;
+}
+
+/// Implementors of this contract are able to persist new [Invitations](Invitation).
+#[async_trait]
+pub trait Insert {
+    /// Insert the invitation.
+    async fn insert(&mut self, entity: &Invitation) -> Result<()>;
+}
+
+/// Implementors of this contract are able to persist changes of [Invitations](Invitation).
+#[async_trait]
+pub trait Update {
+    /// Update the invitation.
+    async fn update(&mut self, entity: &Invitation) -> Result<()>;
+}
+
+/// Implementors of this contract are able to delete all the [Invitations](Invitation) a user
+/// accepted at once.
+#[async_trait]
+pub trait DeleteAll {
+    /// Delete all the invitations accepted by the user, returning how many there were.
+    async fn delete_all(&mut self, user_id: Uuid) -> Result<u64>;
+}
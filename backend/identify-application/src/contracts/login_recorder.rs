@@ -0,0 +1,21 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+/// Implementors of this contract count the logins of users towards their statistics, see
+/// [User::login_count](identify_domain::User::login_count).
+///
+/// Logins are meant to be buffered and added in batches, e.g. with
+/// [RecordLogins](crate::user_contracts::RecordLogins), so recording one is cheap and can't
+/// fail. The statistics of users may lag behind their latest logins.
+pub trait LoginRecorder: Send + Sync {
+    /// Counts a login of the user at the time.
+    fn record(&self, user_id: Uuid, logged_in_at: DateTime<Utc>);
+}
+
+impl<L: LoginRecorder + ?Sized> LoginRecorder for Arc<L> {
+    fn record(&self, user_id: Uuid, logged_in_at: DateTime<Utc>) {
+        (**self).record(user_id, logged_in_at)
+    }
+}
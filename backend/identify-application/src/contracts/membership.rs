@@ -0,0 +1,67 @@
+use crate::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use identify_domain::{Membership, MembershipRole};
+use uuid::Uuid;
+
+/// Implementors of this contract are able retrieve existing [Memberships](crate::Membership) from
+/// the underlying persistent storage.
+#[async_trait]
+pub trait Get {
+    /// Get the membership of the user in the organization.
+    async fn get(
+        &mut self,
+        organization_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Membership>;
+}
+
+/// Implementors of this contract are able to count the [Memberships](crate::Membership) of an
+/// organization without loading them.
+#[async_trait]
+pub trait CountByRole {
+    /// Count the members of the organization with the role.
+    async fn count_by_role(
+        &mut self,
+        organization_id: Uuid,
+        role: MembershipRole,
+    ) -> Result<u64>;
+}
+
+/// Implementors of this contract are able to count the [Memberships](crate::Membership) that
+/// made users admins.
+#[async_trait]
+pub trait CountNewAdmins {
+    /// Count the memberships that were created or updated with the admin or owner role within
+    /// `[since, until)`.
+    async fn count_new_admins(
+        &mut self,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> Result<u64>;
+}
+
+/// Implementors of this contract are able to insert new [Memberships](crate::Membership) into
+/// the underlying persistent storage.
+#[async_trait]
+pub trait Insert {
+    /// Insert a new membership.
+    async fn insert(&mut self, entity: &Membership) -> Result<()>;
+}
+
+/// Implementors of this contract are able to persist changes of existing
+/// [Memberships](crate::Membership).
+#[async_trait]
+pub trait Update {
+    /// Update the state of an existing membership.
+    async fn update(&mut self, entity: &Membership) -> Result<()>;
+}
+
+/// Implementors of this contract are able to move the [Memberships](crate::Membership) of a user
+/// to another one, e.g. when merging duplicate users.
+#[async_trait]
+pub trait Reassign {
+    /// Move the memberships of `from` to `to`, and delete the ones in organizations `to` is
+    /// already a member of. Returns how many memberships were moved.
+    async fn reassign(&mut self, from: Uuid, to: Uuid) -> Result<u64>;
+}
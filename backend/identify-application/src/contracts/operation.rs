@@ -0,0 +1,40 @@
+use crate::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use identify_domain::Operation;
+use uuid::Uuid;
+
+/// Implementors of this contract are able retrieve existing [Operations](crate::Operation)
+/// from the underlying persistent storage.
+#[async_trait]
+pub trait Get {
+    /// Get an operation by its UUID.
+    async fn get(&mut self, id: Uuid) -> Result<Operation>;
+}
+
+/// Implementors of this contract are able to insert new [Operations](crate::Operation) into
+/// the underlying persistent storage.
+#[async_trait]
+pub trait Insert {
+    /// Insert a new operation.
+    async fn insert(&mut self, entity: &Operation) -> Result<()>;
+}
+
+/// Implementors of this contract are able to persist changes of existing
+/// [Operations](crate::Operation).
+#[async_trait]
+pub trait Update {
+    /// Update the state of an existing operation.
+    async fn update(&mut self, entity: &Operation) -> Result<()>;
+}
+
+/// Implementors of this contract are able to count existing [Operations](crate::Operation).
+#[async_trait]
+pub trait CountFailed {
+    /// Count the operations that failed within `[since, until)`.
+    async fn count_failed(
+        &mut self,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> Result<u64>;
+}
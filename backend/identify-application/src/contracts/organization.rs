@@ -0,0 +1,58 @@
+use crate::Result;
+use async_trait::async_trait;
+use identify_domain::{Organization, Slug};
+use uuid::Uuid;
+
+/// Implementors of this contract are able retrieve existing [Organizations](crate::Organization)
+/// from the underlying persistent storage.
+#[async_trait]
+pub trait Get {
+    /// Get an organization by its UUID.
+    async fn get(&mut self, id: Uuid) -> Result<Organization>;
+}
+
+/// Implementors of this contract are able to look up [Organizations](crate::Organization) by the
+/// IDs external tools have assigned to them.
+#[async_trait]
+pub trait GetByExternalId {
+    /// Get an organization by its external ID, if there is one.
+    async fn get_by_external_id(
+        &mut self,
+        external_id: &str,
+    ) -> Result<Option<Organization>>;
+}
+
+/// Implementors of this contract are able to look up [Organizations](crate::Organization) by
+/// their current or previous slugs.
+#[async_trait]
+pub trait GetBySlug {
+    /// Get the organization that uses or used the slug, if there is one.
+    async fn get_by_slug(
+        &mut self,
+        slug: &Slug,
+    ) -> Result<Option<Organization>>;
+}
+
+/// Implementors of this contract are able to tell which of the
+/// [Organizations](crate::Organization) has ever used a slug.
+#[async_trait]
+pub trait SlugOwner {
+    /// Get the ID of the organization that uses or used the slug, if there is one.
+    async fn slug_owner(&mut self, slug: &Slug) -> Result<Option<Uuid>>;
+}
+
+/// Implementors of this contract are able to insert new [Organizations](crate::Organization)
+/// into the underlying persistent storage.
+#[async_trait]
+pub trait Insert {
+    /// Insert a new organization and record its slug.
+    async fn insert(&mut self, entity: &Organization) -> Result<()>;
+}
+
+/// Implementors of this contract are able to persist changes of existing
+/// [Organizations](crate::Organization).
+#[async_trait]
+pub trait Update {
+    /// Update the state of an existing organization, and record its slug if it has changed.
+    async fn update(&mut self, entity: &Organization) -> Result<()>;
+}
@@ -0,0 +1,51 @@
+use crate::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use identify_domain::OrganizationQuota;
+use uuid::Uuid;
+
+/// Implementors of this contract are able to retrieve the
+/// [OrganizationQuotas](crate::OrganizationQuota) organizations have of their own.
+#[async_trait]
+pub trait Find {
+    /// Get the quota of the organization, if it has one.
+    async fn find(
+        &mut self,
+        organization_id: Uuid,
+    ) -> Result<Option<OrganizationQuota>>;
+}
+
+/// Implementors of this contract are able to persist
+/// [OrganizationQuotas](crate::OrganizationQuota).
+#[async_trait]
+pub trait Put {
+    /// Insert the quota, or replace the one the organization has.
+    async fn put(&mut self, entity: &OrganizationQuota) -> Result<()>;
+}
+
+/// Implementors of this contract are able to delete
+/// [OrganizationQuotas](crate::OrganizationQuota), so that their organizations are limited by
+/// the default again.
+#[async_trait]
+pub trait Delete {
+    /// Delete the quota of the organization. Returns whether it had one.
+    async fn delete(&mut self, organization_id: Uuid) -> Result<bool>;
+}
+
+/// Implementors of this contract are able to find the organizations that reached their
+/// [OrganizationQuotas](crate::OrganizationQuota).
+#[async_trait]
+pub trait CountReached {
+    /// Count the organizations whose number of users reached their maximum within
+    /// `[since, until)`, i.e. the ones in which the member that filled the last place joined
+    /// then.
+    ///
+    /// Organizations without a quota of their own are limited by `default_max_users`, `None`
+    /// if there's no default limit.
+    async fn count_reached(
+        &mut self,
+        default_max_users: Option<u32>,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> Result<u64>;
+}
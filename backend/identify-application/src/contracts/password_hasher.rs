@@ -0,0 +1,15 @@
+use crate::Result;
+use identify_domain::Password;
+
+/// Implementors of this contract are able to turn [Passwords](identify_domain::Password) into
+/// hashes that can be stored, and to check passwords against them.
+///
+/// Hashes are self-describing strings, e.g. in the PHC format, so that the parameters of the
+/// hashing can change without invalidating the existing hashes.
+pub trait PasswordHasher: Send + Sync {
+    /// Hash the password with a fresh salt.
+    fn hash(&self, password: &Password) -> Result<String>;
+
+    /// Check whether the password matches the hash.
+    fn verify(&self, password: &str, hash: &str) -> Result<bool>;
+}
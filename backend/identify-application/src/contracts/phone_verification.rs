@@ -0,0 +1,35 @@
+use crate::Result;
+use async_trait::async_trait;
+use identify_domain::PhoneVerification;
+use uuid::Uuid;
+
+/// Implementors of this contract are able to retrieve a [PhoneVerification].
+#[async_trait]
+pub trait Get {
+    /// Get the verification with the ID. Fails if there's no such verification.
+    async fn get(&mut self, id: Uuid) -> Result<PhoneVerification>;
+}
+
+/// Implementors of this contract are able to persist new
+/// [PhoneVerifications](PhoneVerification).
+#[async_trait]
+pub trait Insert {
+    /// Insert the verification.
+    async fn insert(&mut self, entity: &PhoneVerification) -> Result<()>;
+}
+
+/// Implementors of this contract are able to persist changes of
+/// [PhoneVerifications](PhoneVerification).
+#[async_trait]
+pub trait Update {
+    /// Update the verification.
+    async fn update(&mut self, entity: &PhoneVerification) -> Result<()>;
+}
+
+/// Implementors of this contract are able to delete the
+/// [PhoneVerifications](PhoneVerification) of a user.
+#[async_trait]
+pub trait DeleteAll {
+    /// Delete all the verifications of the user, returning how many there were.
+    async fn delete_all(&mut self, user_id: Uuid) -> Result<u64>;
+}
@@ -0,0 +1,93 @@
+use crate::Result;
+use async_trait::async_trait;
+use identify_domain::{ReverificationCampaign, User};
+use uuid::Uuid;
+
+/// How far a [ReverificationCampaign] has got.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReverificationProgress {
+    /// Users flagged as requiring re-verification.
+    pub flagged: u64,
+    /// Flagged users that have been notified.
+    pub notified: u64,
+}
+
+impl ReverificationProgress {
+    /// Whether every flagged user has been notified.
+    pub fn is_complete(&self) -> bool {
+        self.notified >= self.flagged
+    }
+
+    /// Share of the flagged users that have been notified, in percents.
+    pub fn percent(&self) -> u8 {
+        if self.is_complete() {
+            return 100;
+        }
+
+        // Never reports 100% before the last user is notified.
+        (self.notified * 100 / self.flagged).min(99) as u8
+    }
+}
+
+/// Implementors of this contract are able retrieve existing
+/// [ReverificationCampaigns](crate::ReverificationCampaign) from the underlying persistent
+/// storage.
+#[async_trait]
+pub trait Get {
+    /// Get a campaign by its UUID.
+    async fn get(&mut self, id: Uuid) -> Result<ReverificationCampaign>;
+}
+
+/// Implementors of this contract are able to insert new
+/// [ReverificationCampaigns](crate::ReverificationCampaign) into the underlying persistent
+/// storage.
+#[async_trait]
+pub trait Insert {
+    /// Insert a new campaign.
+    async fn insert(&mut self, entity: &ReverificationCampaign) -> Result<()>;
+}
+
+/// Implementors of this contract are able to flag the cohorts of
+/// [ReverificationCampaigns](crate::ReverificationCampaign).
+#[async_trait]
+pub trait FlagCohort {
+    /// Flag the users of the cohort of the campaign as requiring re-verification, and return
+    /// how many there are.
+    async fn flag_cohort(
+        &mut self,
+        campaign: &ReverificationCampaign,
+    ) -> Result<u64>;
+}
+
+/// Implementors of this contract are able to find the flagged users that haven't been notified
+/// yet.
+#[async_trait]
+pub trait ListPending {
+    /// List at most `limit` users of the campaign that haven't been notified, oldest first.
+    async fn list_pending(
+        &mut self,
+        campaign_id: Uuid,
+        limit: u32,
+    ) -> Result<Vec<User>>;
+}
+
+/// Implementors of this contract are able to record the notified users.
+#[async_trait]
+pub trait MarkNotified {
+    /// Record that the user has been notified as part of the campaign.
+    async fn mark_notified(
+        &mut self,
+        campaign_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<()>;
+}
+
+/// Implementors of this contract are able to tell how far a campaign has got.
+#[async_trait]
+pub trait Progress {
+    /// Count the flagged and the notified users of the campaign.
+    async fn progress(
+        &mut self,
+        campaign_id: Uuid,
+    ) -> Result<ReverificationProgress>;
+}
@@ -0,0 +1,109 @@
+use crate::Result;
+use async_trait::async_trait;
+use identify_domain::{Role, RoleAssignment, RoleCohort, User};
+use uuid::Uuid;
+
+/// Implementors of this contract are able retrieve existing [Roles](Role) from the underlying
+/// persistent storage.
+#[async_trait]
+pub trait Get {
+    /// Get a role by its UUID.
+    async fn get(&mut self, id: Uuid) -> Result<Role>;
+}
+
+/// Implementors of this contract are able to look up [Roles](Role) by the IDs external tools
+/// have assigned to them.
+#[async_trait]
+pub trait GetByExternalId {
+    /// Get a role by its external ID, if there is one.
+    async fn get_by_external_id(
+        &mut self,
+        external_id: &str,
+    ) -> Result<Option<Role>>;
+}
+
+/// Implementors of this contract are able to list all the [Roles](Role), which are expected to
+/// be few.
+#[async_trait]
+pub trait ListAll {
+    /// List all the roles, ordered by their names.
+    async fn list_all(&mut self) -> Result<Vec<Role>>;
+}
+
+/// Implementors of this contract are able to insert new [Roles](Role) into the underlying
+/// persistent storage.
+#[async_trait]
+pub trait Insert {
+    /// Insert a new role, which fails if another one has the same name.
+    async fn insert(&mut self, entity: &Role) -> Result<()>;
+}
+
+/// Implementors of this contract are able to update existing [Roles](Role) in the underlying
+/// persistent storage.
+#[async_trait]
+pub trait Update {
+    /// Update a role, which fails if another one has the same name.
+    async fn update(&mut self, entity: &Role) -> Result<()>;
+}
+
+/// Implementors of this contract are able to delete existing [Roles](Role) together with their
+/// assignments.
+#[async_trait]
+pub trait Delete {
+    /// Delete a role by its UUID.
+    async fn delete(&mut self, id: Uuid) -> Result<()>;
+}
+
+/// Implementors of this contract are able to assign [Roles](Role) to users.
+#[async_trait]
+pub trait Assign {
+    /// Assign a role to a user, returning `false` if it was already assigned to them.
+    async fn assign(&mut self, assignment: &RoleAssignment) -> Result<bool>;
+}
+
+/// Implementors of this contract are able to take [Roles](Role) back from users.
+#[async_trait]
+pub trait Unassign {
+    /// Take a role back from a user, returning `false` if it wasn't assigned to them.
+    async fn unassign(&mut self, role_id: Uuid, user_id: Uuid) -> Result<bool>;
+}
+
+/// Implementors of this contract are able to list the users a [Role] is assigned to.
+#[async_trait]
+pub trait ListAssignments {
+    /// List the assignments of a role, the oldest first.
+    async fn list_assignments(
+        &mut self,
+        role_id: Uuid,
+    ) -> Result<Vec<RoleAssignment>>;
+}
+
+/// Implementors of this contract are able to list the [Roles](Role) of a user.
+#[async_trait]
+pub trait ListByUser {
+    /// List the roles assigned to a user, ordered by their names.
+    async fn list_by_user(&mut self, user_id: Uuid) -> Result<Vec<Role>>;
+}
+
+/// Implementors of this contract are able to find the users of a [RoleCohort] a [Role] still has
+/// to be assigned to.
+#[async_trait]
+pub trait ListUnassigned {
+    /// List at most `limit` users of the cohort who haven't been erased and don't have the role
+    /// yet, the oldest first.
+    async fn list_unassigned(
+        &mut self,
+        role_id: Uuid,
+        cohort: &RoleCohort,
+        limit: u32,
+    ) -> Result<Vec<User>>;
+}
+
+/// Implementors of this contract are able to move the assignments of [Roles](Role) from a user
+/// to another one, e.g. when merging duplicate users.
+#[async_trait]
+pub trait Reassign {
+    /// Move the roles of `from` to `to`, and delete the assignments of the roles `to` already
+    /// has. Returns how many assignments were moved.
+    async fn reassign(&mut self, from: Uuid, to: Uuid) -> Result<u64>;
+}
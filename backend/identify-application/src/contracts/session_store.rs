@@ -0,0 +1,62 @@
+use std::sync::Arc;
+
+use crate::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+/// A session of a user on a device, issued when they log in from it. Sessions are identified by
+/// the hashes of their tokens, see [TokenGenerator](crate::TokenGenerator).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Session {
+    pub user_id: Uuid,
+    /// ID of the device the user logged in from.
+    pub device_id: Uuid,
+    pub issued_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Implementors of this contract are able to keep [Sessions](Session) until they expire, e.g.
+/// in the memory of the process, or in a store shared by all the processes of a deployment.
+#[async_trait]
+pub trait SessionStore: Send + Sync {
+    /// Store the session under the hash of its token, until it expires.
+    async fn put(&self, token_hash: &str, session: &Session) -> Result<()>;
+
+    /// Get the session with the token hash, unless it expired or was revoked.
+    async fn get(&self, token_hash: &str) -> Result<Option<Session>>;
+
+    /// Revoke the sessions issued for the device, returning how many there were.
+    async fn revoke_device(&self, device_id: Uuid) -> Result<u64>;
+
+    /// Move the sessions of a user to another one, e.g. when they're merged, returning how many
+    /// there were.
+    async fn reassign(
+        &self,
+        from_user_id: Uuid,
+        to_user_id: Uuid,
+    ) -> Result<u64>;
+}
+
+#[async_trait]
+impl<S: SessionStore + ?Sized> SessionStore for Arc<S> {
+    async fn put(&self, token_hash: &str, session: &Session) -> Result<()> {
+        (**self).put(token_hash, session).await
+    }
+
+    async fn get(&self, token_hash: &str) -> Result<Option<Session>> {
+        (**self).get(token_hash).await
+    }
+
+    async fn revoke_device(&self, device_id: Uuid) -> Result<u64> {
+        (**self).revoke_device(device_id).await
+    }
+
+    async fn reassign(
+        &self,
+        from_user_id: Uuid,
+        to_user_id: Uuid,
+    ) -> Result<u64> {
+        (**self).reassign(from_user_id, to_user_id).await
+    }
+}
@@ -0,0 +1,47 @@
+use crate::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use identify_domain::SigningKey;
+
+/// Implementors of this contract are able to retrieve the [SigningKey](crate::SigningKey) new
+/// tokens are signed with.
+#[async_trait]
+pub trait GetActive {
+    /// Get the active key, if there's one.
+    async fn get_active(&mut self) -> Result<Option<SigningKey>>;
+}
+
+/// Implementors of this contract are able to list the [SigningKeys](crate::SigningKey) tokens
+/// may have been signed with.
+#[async_trait]
+pub trait ListPublished {
+    /// List the active key and the retired keys that haven't been deleted yet, the most recent
+    /// first.
+    async fn list_published(&mut self) -> Result<Vec<SigningKey>>;
+}
+
+/// Implementors of this contract are able to insert new [SigningKeys](crate::SigningKey) into
+/// the underlying persistent storage.
+#[async_trait]
+pub trait Insert {
+    /// Insert a new key. Fails if it's active while another key is.
+    async fn insert(&mut self, entity: &SigningKey) -> Result<()>;
+}
+
+/// Implementors of this contract are able to persist changes of existing
+/// [SigningKeys](crate::SigningKey).
+#[async_trait]
+pub trait Update {
+    /// Update the state of an existing key.
+    async fn update(&mut self, entity: &SigningKey) -> Result<()>;
+}
+
+/// Implementors of this contract are able to delete the [SigningKeys](crate::SigningKey) that
+/// were retired long enough ago.
+#[async_trait]
+pub trait PruneRetired {
+    /// Delete the keys retired before `before`. The active key is kept.
+    ///
+    /// Returns the number of deleted keys.
+    async fn prune_retired(&mut self, before: DateTime<Utc>) -> Result<u64>;
+}
@@ -0,0 +1,9 @@
+use crate::Result;
+use identify_domain::NewSigningKeyAttrs;
+
+/// Implementors of this contract are able to generate the key pairs of new
+/// [SigningKeys](identify_domain::SigningKey), e.g. Ed25519 ones.
+pub trait SigningKeyGenerator: Send + Sync {
+    /// Generate a fresh key pair along with the JWS algorithm it's used with.
+    fn generate(&self) -> Result<NewSigningKeyAttrs>;
+}
@@ -0,0 +1,27 @@
+use std::sync::Arc;
+
+use crate::Result;
+use async_trait::async_trait;
+
+/// A text message to a single phone number.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sms {
+    /// Phone number in the E.164 format, see [PhoneNumber](identify_domain::PhoneNumber).
+    pub to: String,
+    pub text: String,
+}
+
+/// Implementors of this contract are able to send [Sms] to users, e.g. through the API of an
+/// SMS gateway.
+#[async_trait]
+pub trait SmsSender: Send + Sync {
+    /// Send the message, returning once it has been accepted for delivery.
+    async fn send(&self, sms: &Sms) -> Result<()>;
+}
+
+#[async_trait]
+impl<S: SmsSender + ?Sized> SmsSender for Arc<S> {
+    async fn send(&self, sms: &Sms) -> Result<()> {
+        (**self).send(sms).await
+    }
+}
@@ -0,0 +1,14 @@
+/// Implementors of this contract are able to generate the secret tokens emailed to users, e.g.
+/// to accept an [Invitation](identify_domain::Invitation), and to hash them so that only the
+/// hashes are stored.
+pub trait TokenGenerator: Send + Sync {
+    /// Generate a fresh random token that is safe to put in a URL.
+    fn generate(&self) -> String;
+
+    /// Generate a fresh random numeric code that is short enough to be typed, e.g. from an SMS.
+    /// Codes can be guessed, so they must only be accepted a few times.
+    fn generate_code(&self) -> String;
+
+    /// Hash the token. The same token always has the same hash.
+    fn hash(&self, token: &str) -> String;
+}
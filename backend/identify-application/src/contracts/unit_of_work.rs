@@ -0,0 +1,226 @@
+use crate::{
+    Result, consent_contracts, device_contracts, email_alias_contracts,
+    email_change_contracts, email_verification_contracts, invitation_contracts,
+    membership_contracts, operation_contracts, organization_contracts,
+    organization_quota_contracts, phone_verification_contracts, role_contracts,
+    signing_key_contracts, user_attribute_contracts, user_contracts,
+    user_event_contracts, user_summary_contracts, webhook_delivery_contracts,
+    webhook_endpoint_contracts,
+};
+use async_trait::async_trait;
+
+/// Implementors of this contract group repositories that share a single transaction, so that
+/// changes made through all of them are either persisted or discarded together.
+///
+/// Repositories borrow the transaction exclusively, so they are handed out one at a time, e.g.
+/// `uow.users().get(id).await`, rather than all held at once.
+///
+/// Changes are persisted only by [commit](UnitOfWork::commit). Dropping the unit of work
+/// discards them.
+#[async_trait]
+pub trait UnitOfWork: Send {
+    type Users<'a>: user_contracts::Get
+        + user_contracts::GetByEmail
+        + user_contracts::GetMany
+        + user_contracts::FindBySpec
+        + user_contracts::Count
+        + user_contracts::Exists
+        + user_contracts::ExistsByEmails
+        + user_contracts::ExistsByUsername
+        + user_contracts::Insert
+        + user_contracts::InsertMany
+        + user_contracts::Update
+        + user_contracts::ChangeEmail
+        + user_contracts::SetPasswordHash
+        + user_contracts::Erase
+        + user_contracts::RecordMerge
+        + Send
+    where
+        Self: 'a;
+    type UserAttributes<'a>: user_attribute_contracts::ListByUser
+        + user_attribute_contracts::Upsert
+        + user_attribute_contracts::Delete
+        + user_attribute_contracts::DeleteAll
+        + user_attribute_contracts::Reassign
+        + Send
+    where
+        Self: 'a;
+    type Devices<'a>: device_contracts::Get
+        + device_contracts::GetByFingerprint
+        + device_contracts::ListByUser
+        + device_contracts::Insert
+        + device_contracts::Update
+        + device_contracts::DeleteAll
+        + device_contracts::Reassign
+        + Send
+    where
+        Self: 'a;
+    type Consents<'a>: consent_contracts::ListByUser
+        + consent_contracts::Insert
+        + consent_contracts::Reassign
+        + Send
+    where
+        Self: 'a;
+    type EmailVerifications<'a>: email_verification_contracts::Get
+        + email_verification_contracts::Insert
+        + email_verification_contracts::Update
+        + Send
+    where
+        Self: 'a;
+    type EmailAliases<'a>: email_alias_contracts::ListByUser
+        + email_alias_contracts::Insert
+        + email_alias_contracts::Delete
+        + email_alias_contracts::DeleteAll
+        + Send
+    where
+        Self: 'a;
+    type EmailChanges<'a>: email_change_contracts::Get
+        + email_change_contracts::Insert
+        + email_change_contracts::Update
+        + email_change_contracts::DeleteAll
+        + Send
+    where
+        Self: 'a;
+    type PhoneVerifications<'a>: phone_verification_contracts::Get
+        + phone_verification_contracts::Insert
+        + phone_verification_contracts::Update
+        + phone_verification_contracts::DeleteAll
+        + Send
+    where
+        Self: 'a;
+    type Organizations<'a>: organization_contracts::Get
+        + organization_contracts::GetByExternalId
+        + organization_contracts::GetBySlug
+        + organization_contracts::SlugOwner
+        + organization_contracts::Insert
+        + organization_contracts::Update
+        + Send
+    where
+        Self: 'a;
+    type OrganizationQuotas<'a>: organization_quota_contracts::Find
+        + organization_quota_contracts::Put
+        + organization_quota_contracts::Delete
+        + organization_quota_contracts::CountReached
+        + Send
+    where
+        Self: 'a;
+    type Invitations<'a>: invitation_contracts::Get
+        + invitation_contracts::Insert
+        + invitation_contracts::Update
+        + invitation_contracts::DeleteAll
+        + Send
+    where
+        Self: 'a;
+    type Memberships<'a>: membership_contracts::Get
+        + membership_contracts::CountByRole
+        + membership_contracts::CountNewAdmins
+        + membership_contracts::Insert
+        + membership_contracts::Update
+        + membership_contracts::Reassign
+        + Send
+    where
+        Self: 'a;
+    type Roles<'a>: role_contracts::Get
+        + role_contracts::GetByExternalId
+        + role_contracts::Insert
+        + role_contracts::Update
+        + role_contracts::Delete
+        + role_contracts::Assign
+        + role_contracts::Unassign
+        + role_contracts::ListUnassigned
+        + role_contracts::Reassign
+        + Send
+    where
+        Self: 'a;
+    type UserEvents<'a>: user_event_contracts::Emit
+        + user_event_contracts::Count
+        + user_event_contracts::ListUnpublished
+        + user_event_contracts::MarkPublished
+        + user_event_contracts::ListUnprojected
+        + user_event_contracts::MarkProjected
+        + user_event_contracts::PruneHandled
+        + Send
+    where
+        Self: 'a;
+    type UserSummaries<'a>: user_summary_contracts::Upsert
+        + user_summary_contracts::Remove
+        + Send
+    where
+        Self: 'a;
+    type Operations<'a>: operation_contracts::CountFailed + Send
+    where
+        Self: 'a;
+    type SigningKeys<'a>: signing_key_contracts::GetActive
+        + signing_key_contracts::Insert
+        + signing_key_contracts::Update
+        + signing_key_contracts::PruneRetired
+        + Send
+    where
+        Self: 'a;
+    type WebhookEndpoints<'a>: webhook_endpoint_contracts::Get
+        + webhook_endpoint_contracts::ListAll
+        + Send
+    where
+        Self: 'a;
+    type WebhookDeliveries<'a>: webhook_delivery_contracts::Get
+        + webhook_delivery_contracts::ListDue
+        + webhook_delivery_contracts::ListByEndpoint
+        + webhook_delivery_contracts::Insert
+        + webhook_delivery_contracts::Update
+        + webhook_delivery_contracts::PruneDelivered
+        + webhook_delivery_contracts::CountDead
+        + Send
+    where
+        Self: 'a;
+
+    fn users(&mut self) -> Self::Users<'_>;
+
+    fn user_attributes(&mut self) -> Self::UserAttributes<'_>;
+
+    fn devices(&mut self) -> Self::Devices<'_>;
+
+    fn consents(&mut self) -> Self::Consents<'_>;
+
+    fn email_verifications(&mut self) -> Self::EmailVerifications<'_>;
+
+    fn email_aliases(&mut self) -> Self::EmailAliases<'_>;
+
+    fn email_changes(&mut self) -> Self::EmailChanges<'_>;
+
+    fn phone_verifications(&mut self) -> Self::PhoneVerifications<'_>;
+
+    fn organizations(&mut self) -> Self::Organizations<'_>;
+
+    fn organization_quotas(&mut self) -> Self::OrganizationQuotas<'_>;
+
+    fn invitations(&mut self) -> Self::Invitations<'_>;
+
+    fn memberships(&mut self) -> Self::Memberships<'_>;
+
+    fn roles(&mut self) -> Self::Roles<'_>;
+
+    fn user_events(&mut self) -> Self::UserEvents<'_>;
+
+    fn user_summaries(&mut self) -> Self::UserSummaries<'_>;
+
+    fn operations(&mut self) -> Self::Operations<'_>;
+
+    fn signing_keys(&mut self) -> Self::SigningKeys<'_>;
+
+    fn webhook_endpoints(&mut self) -> Self::WebhookEndpoints<'_>;
+
+    fn webhook_deliveries(&mut self) -> Self::WebhookDeliveries<'_>;
+
+    /// Persist all the changes made within this unit of work.
+    async fn commit(self) -> Result<()>;
+}
+
+/// Implementors of this contract start new [UnitsOfWork](UnitOfWork), e.g. for every execution
+/// of a [UseCase](crate::UseCase) wrapped in [InTransaction](crate::InTransaction).
+#[async_trait]
+pub trait UnitOfWorkFactory: Send + Sync {
+    type UnitOfWork: UnitOfWork;
+
+    /// Start a new unit of work.
+    async fn begin(&self) -> Result<Self::UnitOfWork>;
+}
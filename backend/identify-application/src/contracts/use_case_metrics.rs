@@ -0,0 +1,16 @@
+use std::time::Duration;
+
+/// Implementors of this contract record how long [UseCases](crate::UseCase) take and how they
+/// end, e.g. as Prometheus metrics.
+pub trait UseCaseMetrics: Send + Sync {
+    /// Record an execution of the use case.
+    ///
+    /// The outcome is `ok`, or the [kind](crate::ApplicationError::kind) of the error the use
+    /// case failed with.
+    fn record(
+        &self,
+        use_case: &'static str,
+        outcome: &'static str,
+        elapsed: Duration,
+    );
+}
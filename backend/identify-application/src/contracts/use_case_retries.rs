@@ -0,0 +1,23 @@
+use async_trait::async_trait;
+
+use crate::ApplicationError;
+
+/// Implementors of this contract decide whether failed executions of
+/// [UseCases](crate::UseCase) are retried, e.g. because the database was locked by a concurrent
+/// writer, and how long to wait before they are.
+#[async_trait]
+pub trait UseCaseRetries: Send + Sync {
+    /// Record a new execution of the use case, before its first attempt.
+    fn record_execution(&self, use_case: &'static str);
+
+    /// Wait before the next attempt of the use case, whose (1-based) attempt failed with the
+    /// error.
+    ///
+    /// Returns `false` without waiting if the execution should fail with the error instead.
+    async fn wait_before_retry(
+        &self,
+        use_case: &'static str,
+        attempt: u32,
+        error: &ApplicationError,
+    ) -> bool;
+}
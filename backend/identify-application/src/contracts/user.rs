@@ -1,14 +1,230 @@
-use crate::Result;
+use crate::{ListQuery, PageRequest, Paginated, Result, Sort, Specification};
 use async_trait::async_trait;
-use identify_domain::User;
+use chrono::{DateTime, Utc};
+use futures_util::stream::BoxStream;
+use identify_domain::{User, Username};
 use uuid::Uuid;
 
+/// Fields [Users](crate::User) can be sorted by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UserSortField {
+    Email,
+    FirstName,
+    LastName,
+    Username,
+    CreatedAt,
+    UpdatedAt,
+}
+
+/// Criteria for filtering [Users](crate::User). A user matches the filter if they match all the
+/// set criteria.
+#[derive(Debug, Clone, Default)]
+pub struct UserFilter {
+    /// Exact email of the user.
+    pub email: Option<String>,
+    /// Domain of the email, e.g. `acme.test`, matched regardless of the case.
+    pub email_domain: Option<String>,
+    /// Only users created after this time match.
+    pub created_after: Option<DateTime<Utc>>,
+    /// Only users created before this time match.
+    pub created_before: Option<DateTime<Utc>>,
+    /// Only users who haven't logged in since this time match, including the ones who never
+    /// did, e.g. to report the inactive ones.
+    pub inactive_since: Option<DateTime<Utc>>,
+}
+
+pub type UserListQuery = ListQuery<UserFilter, UserSortField>;
+
+/// Statuses of [Users](crate::User) that [UserSpecs](UserSpec) match.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UserStatus {
+    /// Users who haven't been erased.
+    Active,
+    /// Users whose personal data was erased, see [User::erase].
+    Erased,
+}
+
+/// Criteria [UserSpecs](UserSpec) are composed of.
+#[derive(Debug, Clone, PartialEq)]
+pub enum UserCriterion {
+    /// Users identified by the email: the one who has it, the one who was created with it and
+    /// has changed it since, see [User::change_email], or the one it's an
+    /// [alias](identify_domain::EmailAlias) of.
+    ByEmail(String),
+    /// Users with the status.
+    ByStatus(UserStatus),
+    /// Users created at or after `from`, and before `to`. Missing bounds aren't checked.
+    CreatedBetween {
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    },
+    /// Members of the [Organization](identify_domain::Organization) with the ID.
+    InOrganization(Uuid),
+    /// Users who haven't logged in since the time, including the ones who never did, see
+    /// [User::last_login_at].
+    InactiveSince(DateTime<Utc>),
+}
+
+/// A predicate on [Users](crate::User), e.g.
+/// `UserSpec::by_status(UserStatus::Active).and(UserSpec::in_organization(id))`.
+pub type UserSpec = Specification<UserCriterion>;
+
+impl UserSpec {
+    pub fn by_email(email: impl Into<String>) -> Self {
+        UserCriterion::ByEmail(email.into()).into()
+    }
+
+    pub fn by_status(status: UserStatus) -> Self {
+        UserCriterion::ByStatus(status).into()
+    }
+
+    pub fn created_between(
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> Self {
+        UserCriterion::CreatedBetween { from, to }.into()
+    }
+
+    pub fn in_organization(organization_id: Uuid) -> Self {
+        UserCriterion::InOrganization(organization_id).into()
+    }
+
+    pub fn inactive_since(since: DateTime<Utc>) -> Self {
+        UserCriterion::InactiveSince(since).into()
+    }
+}
+
+pub type UserSpecQuery = ListQuery<UserSpec, UserSortField>;
+
 /// Implementors of this contract are able retrieve existing [Users](crate::User) from the underlying
 /// persistent storage.
 #[async_trait]
 pub trait Get {
     /// Get a user by their UUID.
-    async fn get(&self, id: Uuid) -> Result<User>;
+    async fn get(&mut self, id: Uuid) -> Result<User>;
+}
+
+/// Implementors of this contract are able to retrieve the versions of existing
+/// [Users](crate::User), e.g. to tell whether a copy of a user is still current.
+#[async_trait]
+pub trait GetVersion {
+    /// Get the version of a user by their UUID, without loading the user.
+    async fn get_version(&mut self, id: Uuid) -> Result<u32>;
+}
+
+/// Implementors of this contract are able to retrieve existing [Users](crate::User) by their
+/// usernames, the alternate identifiers people refer to users by.
+#[async_trait]
+pub trait GetByUsername {
+    /// Get a user by their username.
+    async fn get_by_username(&mut self, username: &Username) -> Result<User>;
+}
+
+/// Implementors of this contract are able to retrieve existing [Users](crate::User) by their
+/// emails.
+#[async_trait]
+pub trait GetByEmail {
+    /// Get the user with the email, the one who was created with it and has changed it since,
+    /// see [User::change_email], or the one it's an [alias](identify_domain::EmailAlias) of.
+    /// Erased users aren't found.
+    async fn get_by_email(&mut self, email: &str) -> Result<User>;
+}
+
+/// Implementors of this contract are able to retrieve many [Users](crate::User) at once, e.g. to
+/// resolve references to them without a query per user.
+#[async_trait]
+pub trait GetMany {
+    /// Get the users with the provided UUIDs. Users that don't exist are left out.
+    async fn get_many(&mut self, ids: &[Uuid]) -> Result<Vec<User>>;
+}
+
+/// Implementors of this contract are able to list [Users](crate::User) stored in the underlying
+/// persistent storage.
+#[async_trait]
+pub trait List {
+    /// List users matching the query.
+    async fn list(&mut self, query: &UserListQuery) -> Result<Paginated<User>>;
+}
+
+/// Implementors of this contract are able to find [Users](crate::User) by any [UserSpec], so
+/// that new combinations of criteria don't need new contracts.
+#[async_trait]
+pub trait FindBySpec {
+    /// Find users matching the specification of the query.
+    async fn find_by_spec(
+        &mut self,
+        query: &UserSpecQuery,
+    ) -> Result<Paginated<User>>;
+}
+
+/// Implementors of this contract are able to count the [Users](crate::User) matching a
+/// [UserSpec] without loading them, e.g. to enforce quotas.
+#[async_trait]
+pub trait Count {
+    /// Count the users matching the specification.
+    async fn count(&mut self, spec: &UserSpec) -> Result<u64>;
+}
+
+/// Implementors of this contract are able to check whether any [User](crate::User) matches a
+/// [UserSpec] without loading it, e.g. whether an email is taken.
+#[async_trait]
+pub trait Exists {
+    /// Check whether a user matching the specification exists.
+    async fn exists(&mut self, spec: &UserSpec) -> Result<bool>;
+}
+
+/// Implementors of this contract are able to check which of many emails are taken, each like
+/// [UserSpec::by_email] would, with a query per batch rather than per email, e.g. when
+/// importing users.
+#[async_trait]
+pub trait ExistsByEmails {
+    /// Check whether a user matching each of the emails exists, in the order of the emails.
+    async fn exists_by_emails(&mut self, emails: &[&str]) -> Result<Vec<bool>>;
+}
+
+/// Users read from the storage one by one, so that only a few of them are in memory at a time.
+pub type UserStream = BoxStream<'static, Result<User>>;
+
+/// Implementors of this contract are able to scan all the [Users](crate::User) matching a
+/// filter through a cursor, e.g. for exports and other operations on whole tables that would
+/// take too much memory if the users were loaded at once.
+pub trait Scan {
+    /// Streams the users matching the filter in the sort order. The stream ends after the
+    /// first error.
+    fn scan(
+        &mut self,
+        filter: &UserFilter,
+        sort: &[Sort<UserSortField>],
+    ) -> UserStream;
+}
+
+/// A full-text search for [Users](crate::User).
+#[derive(Debug, Clone)]
+pub struct UserSearchQuery {
+    /// Words that must all occur in the email or the names of the users, matched by their
+    /// prefixes regardless of the case and diacritics, e.g. `jo doe` matches `John Doe`.
+    pub text: String,
+    pub page: PageRequest,
+}
+
+/// Implementors of this contract are able to search [Users](crate::User) by their emails and
+/// names.
+#[async_trait]
+pub trait Search {
+    /// Search users matching the query, the most relevant first.
+    async fn search(
+        &mut self,
+        query: &UserSearchQuery,
+    ) -> Result<Paginated<User>>;
+}
+
+/// Implementors of this contract are able to check whether a username is taken by any of the
+/// [Users](crate::User).
+#[async_trait]
+pub trait ExistsByUsername {
+    /// Check whether a user with the username exists.
+    async fn exists_by_username(&mut self, username: &Username)
+    -> Result<bool>;
 }
 
 /// Implementors of this contract are able to insert new [Users](crate::User) into the underlying
@@ -16,5 +232,90 @@ pub trait Get {
 #[async_trait]
 pub trait Insert {
     /// Insert a new user.
-    async fn insert(&self, entity: &User) -> Result<()>;
+    async fn insert(&mut self, entity: &User) -> Result<()>;
+}
+
+/// Implementors of this contract are able to insert many new [Users](crate::User) at once, e.g.
+/// when importing them, without a statement per user.
+#[async_trait]
+pub trait InsertMany {
+    /// Insert the new users.
+    ///
+    /// If any of the users can't be inserted, some of the others may already be, so the
+    /// changes should be discarded.
+    async fn insert_many(&mut self, entities: &[User]) -> Result<()>;
+}
+
+/// Implementors of this contract are able to persist changes of existing [Users](crate::User).
+#[async_trait]
+pub trait Update {
+    /// Update the state of an existing user.
+    async fn update(&mut self, entity: &User) -> Result<()>;
+}
+
+/// Implementors of this contract are able to persist the new emails of existing
+/// [Users](crate::User), see [User::change_email].
+#[async_trait]
+pub trait ChangeEmail {
+    /// Persist the email of the user, along with their version.
+    async fn change_email(&mut self, entity: &User) -> Result<()>;
+}
+
+/// Implementors of this contract are able to store the password hashes of existing
+/// [Users](crate::User). Hashes are kept apart from the users, so they never leave the storage
+/// with them.
+#[async_trait]
+pub trait SetPasswordHash {
+    /// Replace the password hash of the user.
+    async fn set_password_hash(
+        &mut self,
+        id: Uuid,
+        password_hash: &str,
+    ) -> Result<()>;
+}
+
+/// Implementors of this contract are able to persist the erasure of [Users](crate::User), see
+/// [User::erase].
+#[async_trait]
+pub trait Erase {
+    /// Persist the anonymized user, delete their password hash and record who erased them.
+    async fn erase(&mut self, entity: &User, erased_by: &str) -> Result<()>;
+}
+
+/// Implementors of this contract are able to record that a duplicate [User](crate::User) was
+/// merged into another one.
+#[async_trait]
+pub trait RecordMerge {
+    /// Record that the duplicate was merged into the primary user, and who merged them. The
+    /// users merged into the duplicate earlier are recorded as merged into the primary user
+    /// instead, so that the trail leads to a user who's kept.
+    async fn record_merge(
+        &mut self,
+        duplicate_id: Uuid,
+        primary_id: Uuid,
+        merged_by: &str,
+        merged_at: DateTime<Utc>,
+    ) -> Result<()>;
+}
+
+/// Logins of a [User](crate::User) counted together, see [RecordLogins].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LoginTally {
+    pub user_id: Uuid,
+    /// How many times the user logged in.
+    pub count: u32,
+    /// When the user last logged in.
+    pub last_login_at: DateTime<Utc>,
+}
+
+/// Implementors of this contract are able to add logins to the statistics of
+/// [Users](crate::User), see [User::login_count].
+///
+/// Logins are added in bulk rather than one by one, so that they don't each write to the user,
+/// and don't change the versions of the users.
+#[async_trait]
+pub trait RecordLogins {
+    /// Add the logins to the statistics of their users. The tallies of users who don't exist
+    /// anymore are skipped.
+    async fn record_logins(&mut self, tallies: &[LoginTally]) -> Result<()>;
 }
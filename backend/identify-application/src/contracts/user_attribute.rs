@@ -0,0 +1,47 @@
+use crate::Result;
+use async_trait::async_trait;
+use identify_domain::UserAttribute;
+use uuid::Uuid;
+
+/// Implementors of this contract are able to list the [UserAttributes](crate::UserAttribute)
+/// of a user.
+#[async_trait]
+pub trait ListByUser {
+    /// List all the attributes of the user, ordered by their keys.
+    async fn list_by_user(
+        &mut self,
+        user_id: Uuid,
+    ) -> Result<Vec<UserAttribute>>;
+}
+
+/// Implementors of this contract are able to persist [UserAttributes](crate::UserAttribute),
+/// whether they are new or not.
+#[async_trait]
+pub trait Upsert {
+    /// Insert the attribute, or replace the value of the attribute of the user with its key.
+    async fn upsert(&mut self, entity: &UserAttribute) -> Result<()>;
+}
+
+/// Implementors of this contract are able to delete [UserAttributes](crate::UserAttribute).
+#[async_trait]
+pub trait Delete {
+    /// Delete the attribute of the user with the key, returning `false` if it doesn't exist.
+    async fn delete(&mut self, user_id: Uuid, key: &str) -> Result<bool>;
+}
+
+/// Implementors of this contract are able to delete all the
+/// [UserAttributes](crate::UserAttribute) of a user at once.
+#[async_trait]
+pub trait DeleteAll {
+    /// Delete all the attributes of the user, returning how many there were.
+    async fn delete_all(&mut self, user_id: Uuid) -> Result<u64>;
+}
+
+/// Implementors of this contract are able to move the [UserAttributes](crate::UserAttribute) of
+/// a user to another one, e.g. when merging duplicate users.
+#[async_trait]
+pub trait Reassign {
+    /// Move the attributes of `from` to `to`, and delete the ones whose keys `to` already has.
+    /// Returns how many attributes were moved.
+    async fn reassign(&mut self, from: Uuid, to: Uuid) -> Result<u64>;
+}
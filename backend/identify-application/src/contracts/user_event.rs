@@ -0,0 +1,110 @@
+use crate::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use identify_domain::{UserEvent, UserLifecycleTransition};
+
+/// Implementors of this contract are able to record [UserEvents](identify_domain::UserEvent) in
+/// the outbox they are later published from.
+#[async_trait]
+pub trait Emit {
+    /// Record an event unless an event with the same ID has already been recorded.
+    ///
+    /// Returns `false` if the event was a duplicate and nothing was recorded.
+    async fn emit(&mut self, event: &UserEvent) -> Result<bool>;
+}
+
+/// Implementors of this contract are able to count the recorded
+/// [UserEvents](identify_domain::UserEvent).
+#[async_trait]
+pub trait Count {
+    /// Count the events of every transition that occurred within `[since, until)`.
+    ///
+    /// Transitions without any events are left out.
+    async fn count_by_transition(
+        &mut self,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> Result<Vec<(UserLifecycleTransition, u64)>>;
+}
+
+/// A [UserEvent] together with its position among the recorded events.
+#[derive(Debug)]
+pub struct RecordedUserEvent {
+    /// Position of the event, greater than the positions of all the events recorded before
+    /// it. Positions are never reused, even once the events are pruned.
+    pub sequence: u64,
+    pub event: UserEvent,
+}
+
+/// Implementors of this contract are able to read the recorded
+/// [UserEvents](identify_domain::UserEvent) in the order they were recorded, e.g. to feed them
+/// to subscribers.
+#[async_trait]
+pub trait ListAfter {
+    /// List at most `limit` events recorded after the one at `sequence`, the oldest first.
+    async fn list_after(
+        &mut self,
+        sequence: u64,
+        limit: u32,
+    ) -> Result<Vec<RecordedUserEvent>>;
+}
+
+/// Implementors of this contract are able to tell the position of the newest recorded
+/// [UserEvent](identify_domain::UserEvent).
+#[async_trait]
+pub trait LastSequence {
+    /// Returns the position of the newest event, or `0` if no event has been recorded yet.
+    async fn last_sequence(&mut self) -> Result<u64>;
+}
+
+/// Implementors of this contract are able to take the recorded
+/// [UserEvents](identify_domain::UserEvent) that haven't been published yet.
+#[async_trait]
+pub trait ListUnpublished {
+    /// List at most `limit` unpublished events, the oldest first.
+    async fn list_unpublished(&mut self, limit: u32) -> Result<Vec<UserEvent>>;
+}
+
+/// Implementors of this contract are able to mark recorded
+/// [UserEvents](identify_domain::UserEvent) as published.
+#[async_trait]
+pub trait MarkPublished {
+    /// Mark the event as published, so that it's not listed as unpublished anymore.
+    async fn mark_published(
+        &mut self,
+        event: &UserEvent,
+        published_at: DateTime<Utc>,
+    ) -> Result<()>;
+}
+
+/// Implementors of this contract are able to take the recorded
+/// [UserEvents](identify_domain::UserEvent) that haven't been projected into the read models
+/// yet. Events are projected independently of their publishing.
+#[async_trait]
+pub trait ListUnprojected {
+    /// List at most `limit` unprojected events, the oldest first.
+    async fn list_unprojected(&mut self, limit: u32) -> Result<Vec<UserEvent>>;
+}
+
+/// Implementors of this contract are able to mark recorded
+/// [UserEvents](identify_domain::UserEvent) as projected into the read models.
+#[async_trait]
+pub trait MarkProjected {
+    /// Mark the event as projected, so that it's not listed as unprojected anymore.
+    async fn mark_projected(
+        &mut self,
+        event: &UserEvent,
+        projected_at: DateTime<Utc>,
+    ) -> Result<()>;
+}
+
+/// Implementors of this contract are able to delete the recorded
+/// [UserEvents](identify_domain::UserEvent) that have been both published and projected, and
+/// aren't needed anymore.
+#[async_trait]
+pub trait PruneHandled {
+    /// Delete the published and projected events that occurred before `before`.
+    ///
+    /// Returns the number of deleted events.
+    async fn prune_handled(&mut self, before: DateTime<Utc>) -> Result<u64>;
+}
@@ -0,0 +1,27 @@
+//! Contracts of the read model of [Users](identify_domain::User), which serves listings and
+//! searches apart from the tables the write path locks.
+//!
+//! Summaries are projected from the user events, so they may lag behind the users. They are
+//! read through the [List](crate::user_contracts::List) and
+//! [Search](crate::user_contracts::Search) contracts, like the users themselves.
+
+use crate::Result;
+use async_trait::async_trait;
+use identify_domain::User;
+use uuid::Uuid;
+
+/// Implementors of this contract are able to store the summaries of
+/// [Users](identify_domain::User).
+#[async_trait]
+pub trait Upsert {
+    /// Store the summary of the user, unless a summary of a newer version is already stored.
+    async fn upsert(&mut self, user: &User) -> Result<()>;
+}
+
+/// Implementors of this contract are able to remove the summaries of
+/// [Users](identify_domain::User) that don't exist anymore.
+#[async_trait]
+pub trait Remove {
+    /// Remove the summary of the user, if there is any.
+    async fn remove(&mut self, user_id: Uuid) -> Result<()>;
+}
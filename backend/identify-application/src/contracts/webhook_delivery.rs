@@ -0,0 +1,81 @@
+use crate::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use identify_domain::{WebhookDelivery, WebhookDeliveryStatus};
+use uuid::Uuid;
+
+/// Implementors of this contract are able retrieve existing
+/// [WebhookDeliveries](crate::WebhookDelivery) from the underlying persistent storage.
+#[async_trait]
+pub trait Get {
+    /// Get a delivery by its UUID.
+    async fn get(&mut self, id: Uuid) -> Result<WebhookDelivery>;
+}
+
+/// Implementors of this contract are able to find the
+/// [WebhookDeliveries](crate::WebhookDelivery) that should be attempted.
+#[async_trait]
+pub trait ListDue {
+    /// List at most `limit` pending deliveries whose next attempt is due at `now`, the most
+    /// overdue first.
+    async fn list_due(
+        &mut self,
+        now: DateTime<Utc>,
+        limit: u32,
+    ) -> Result<Vec<WebhookDelivery>>;
+}
+
+/// Implementors of this contract are able to list the
+/// [WebhookDeliveries](crate::WebhookDelivery) to a single endpoint.
+#[async_trait]
+pub trait ListByEndpoint {
+    /// List the latest `limit` deliveries to the endpoint in the given state, the most recent
+    /// first.
+    async fn list_by_endpoint(
+        &mut self,
+        endpoint_id: Uuid,
+        status: WebhookDeliveryStatus,
+        limit: u32,
+    ) -> Result<Vec<WebhookDelivery>>;
+}
+
+/// Implementors of this contract are able to count the
+/// [WebhookDeliveries](crate::WebhookDelivery) that gave up.
+#[async_trait]
+pub trait CountDead {
+    /// Count the deliveries that gave up within `[since, until)`.
+    async fn count_dead(
+        &mut self,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> Result<u64>;
+}
+
+/// Implementors of this contract are able to insert new
+/// [WebhookDeliveries](crate::WebhookDelivery) into the underlying persistent storage.
+#[async_trait]
+pub trait Insert {
+    /// Insert a new delivery unless the event is already being delivered to the endpoint.
+    ///
+    /// Returns `false` if the delivery was a duplicate and nothing was inserted.
+    async fn insert(&mut self, entity: &WebhookDelivery) -> Result<bool>;
+}
+
+/// Implementors of this contract are able to persist changes of existing
+/// [WebhookDeliveries](crate::WebhookDelivery).
+#[async_trait]
+pub trait Update {
+    /// Update the state of an existing delivery.
+    async fn update(&mut self, entity: &WebhookDelivery) -> Result<()>;
+}
+
+/// Implementors of this contract are able to delete the
+/// [WebhookDeliveries](crate::WebhookDelivery) that have been delivered.
+#[async_trait]
+pub trait PruneDelivered {
+    /// Delete the delivered deliveries that were last updated before `before`. Pending and dead
+    /// deliveries are kept.
+    ///
+    /// Returns the number of deleted deliveries.
+    async fn prune_delivered(&mut self, before: DateTime<Utc>) -> Result<u64>;
+}
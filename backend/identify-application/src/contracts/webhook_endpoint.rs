@@ -0,0 +1,55 @@
+use crate::Result;
+use async_trait::async_trait;
+use identify_domain::WebhookEndpoint;
+use uuid::Uuid;
+
+/// Implementors of this contract are able retrieve existing
+/// [WebhookEndpoints](crate::WebhookEndpoint) from the underlying persistent storage.
+#[async_trait]
+pub trait Get {
+    /// Get an endpoint by its UUID.
+    async fn get(&mut self, id: Uuid) -> Result<WebhookEndpoint>;
+}
+
+/// Implementors of this contract are able to look up [WebhookEndpoints](crate::WebhookEndpoint)
+/// by the IDs external tools have assigned to them.
+#[async_trait]
+pub trait GetByExternalId {
+    /// Get an endpoint by its external ID, if there is one.
+    async fn get_by_external_id(
+        &mut self,
+        external_id: &str,
+    ) -> Result<Option<WebhookEndpoint>>;
+}
+
+/// Implementors of this contract are able to list all the
+/// [WebhookEndpoints](crate::WebhookEndpoint), which are expected to be few.
+#[async_trait]
+pub trait ListAll {
+    /// List all the endpoints, ordered by when they were created.
+    async fn list_all(&mut self) -> Result<Vec<WebhookEndpoint>>;
+}
+
+/// Implementors of this contract are able to insert new
+/// [WebhookEndpoints](crate::WebhookEndpoint) into the underlying persistent storage.
+#[async_trait]
+pub trait Insert {
+    /// Insert a new endpoint.
+    async fn insert(&mut self, entity: &WebhookEndpoint) -> Result<()>;
+}
+
+/// Implementors of this contract are able to update existing
+/// [WebhookEndpoints](crate::WebhookEndpoint) in the underlying persistent storage.
+#[async_trait]
+pub trait Update {
+    /// Update an endpoint.
+    async fn update(&mut self, entity: &WebhookEndpoint) -> Result<()>;
+}
+
+/// Implementors of this contract are able to delete existing
+/// [WebhookEndpoints](crate::WebhookEndpoint) together with their deliveries.
+#[async_trait]
+pub trait Delete {
+    /// Delete an endpoint by its UUID.
+    async fn delete(&mut self, id: Uuid) -> Result<()>;
+}
@@ -0,0 +1,514 @@
+//! Templates of the emails sent to users.
+//!
+//! Every [kind of email](EmailKind) has a subject, a plain-text and an HTML template, in which
+//! `{{ name }}` is replaced with the value of the variable `name` of the [EmailMessage]. Values
+//! are HTML-escaped in the HTML part. The built-in templates can be replaced one by one with
+//! [EmailTemplates::set].
+//!
+//! Emails to users are localized with [EmailTemplates::render_for]: the templates translated to
+//! the [locale](Locale) of the user are preferred, and times are shown in their
+//! [time zone](TimeZone).
+
+use std::{borrow::Cow, fmt};
+
+use chrono::{DateTime, Utc};
+use identify_domain::{Locale, TimeZone, User};
+
+use crate::Email;
+
+/// Kinds of emails sent to users.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EmailKind {
+    /// Asks the user to verify their email address, e.g. again for a re-verification campaign.
+    Verification,
+    /// Links to the page where the user chooses a new password.
+    PasswordReset,
+    /// Signs the user in without a password.
+    MagicLink,
+    /// Tells the user their account was signed in to from a device it wasn't before.
+    NewDevice,
+    /// Invites someone without an account to join an organization.
+    Invitation,
+    /// Welcomes a user who registered themselves, and links to the page where they verify their
+    /// email address.
+    Welcome,
+    /// Links to the page where the user confirms the address they change theirs to. It's sent to
+    /// the new address.
+    EmailChange,
+}
+
+impl EmailKind {
+    pub const ALL: [EmailKind; 7] = [
+        EmailKind::Verification,
+        EmailKind::PasswordReset,
+        EmailKind::MagicLink,
+        EmailKind::NewDevice,
+        EmailKind::Invitation,
+        EmailKind::Welcome,
+        EmailKind::EmailChange,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EmailKind::Verification => "verification",
+            EmailKind::PasswordReset => "password_reset",
+            EmailKind::MagicLink => "magic_link",
+            EmailKind::NewDevice => "new_device",
+            EmailKind::Invitation => "invitation",
+            EmailKind::Welcome => "welcome",
+            EmailKind::EmailChange => "email_change",
+        }
+    }
+
+    /// Returns the names of the variables the templates of the kind may use.
+    pub fn variables(&self) -> &'static [&'static str] {
+        match self {
+            EmailKind::Verification => &["first_name", "email", "reason"],
+            EmailKind::PasswordReset
+            | EmailKind::MagicLink
+            | EmailKind::Welcome => &["first_name", "link"],
+            EmailKind::NewDevice => {
+                &["first_name", "user_agent", "ip", "location", "time"]
+            }
+            EmailKind::Invitation => {
+                &["inviter", "organization", "role", "link", "expires_at"]
+            }
+            EmailKind::EmailChange => &["first_name", "email", "link"],
+        }
+    }
+
+    fn builtin(&self, part: EmailPart) -> &'static str {
+        match (self, part) {
+            (EmailKind::Verification, EmailPart::Subject) => {
+                include_str!("verification.subject")
+            }
+            (EmailKind::Verification, EmailPart::Text) => {
+                include_str!("verification.txt")
+            }
+            (EmailKind::Verification, EmailPart::Html) => {
+                include_str!("verification.html")
+            }
+            (EmailKind::PasswordReset, EmailPart::Subject) => {
+                include_str!("password_reset.subject")
+            }
+            (EmailKind::PasswordReset, EmailPart::Text) => {
+                include_str!("password_reset.txt")
+            }
+            (EmailKind::PasswordReset, EmailPart::Html) => {
+                include_str!("password_reset.html")
+            }
+            (EmailKind::MagicLink, EmailPart::Subject) => {
+                include_str!("magic_link.subject")
+            }
+            (EmailKind::MagicLink, EmailPart::Text) => {
+                include_str!("magic_link.txt")
+            }
+            (EmailKind::MagicLink, EmailPart::Html) => {
+                include_str!("magic_link.html")
+            }
+            (EmailKind::NewDevice, EmailPart::Subject) => {
+                include_str!("new_device.subject")
+            }
+            (EmailKind::NewDevice, EmailPart::Text) => {
+                include_str!("new_device.txt")
+            }
+            (EmailKind::NewDevice, EmailPart::Html) => {
+                include_str!("new_device.html")
+            }
+            (EmailKind::Invitation, EmailPart::Subject) => {
+                include_str!("invitation.subject")
+            }
+            (EmailKind::Invitation, EmailPart::Text) => {
+                include_str!("invitation.txt")
+            }
+            (EmailKind::Invitation, EmailPart::Html) => {
+                include_str!("invitation.html")
+            }
+            (EmailKind::Welcome, EmailPart::Subject) => {
+                include_str!("welcome.subject")
+            }
+            (EmailKind::Welcome, EmailPart::Text) => {
+                include_str!("welcome.txt")
+            }
+            (EmailKind::Welcome, EmailPart::Html) => {
+                include_str!("welcome.html")
+            }
+            (EmailKind::EmailChange, EmailPart::Subject) => {
+                include_str!("email_change.subject")
+            }
+            (EmailKind::EmailChange, EmailPart::Text) => {
+                include_str!("email_change.txt")
+            }
+            (EmailKind::EmailChange, EmailPart::Html) => {
+                include_str!("email_change.html")
+            }
+        }
+    }
+}
+
+impl fmt::Display for EmailKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Parts of an email that are rendered from their own template.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EmailPart {
+    Subject,
+    Text,
+    Html,
+}
+
+impl EmailPart {
+    pub const ALL: [EmailPart; 3] =
+        [EmailPart::Subject, EmailPart::Text, EmailPart::Html];
+
+    /// Returns the extension of the files of the templates of the part, e.g. `txt` for
+    /// `verification.txt`.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            EmailPart::Subject => "subject",
+            EmailPart::Text => "txt",
+            EmailPart::Html => "html",
+        }
+    }
+}
+
+/// An email to render, with the values of the variables of its kind.
+#[derive(Debug, Clone, Copy)]
+pub enum EmailMessage<'a> {
+    Verification {
+        first_name: &'a str,
+        email: &'a str,
+        reason: &'a str,
+    },
+    PasswordReset {
+        first_name: &'a str,
+        link: &'a str,
+    },
+    MagicLink {
+        first_name: &'a str,
+        link: &'a str,
+    },
+    NewDevice {
+        first_name: &'a str,
+        user_agent: &'a str,
+        ip: &'a str,
+        /// Approximate location of the IP address, or a placeholder if it's unknown.
+        location: &'a str,
+        time: DateTime<Utc>,
+    },
+    Invitation {
+        /// Full name of the user who sent the invitation.
+        inviter: &'a str,
+        /// Name of the organization the invitee is invited to.
+        organization: &'a str,
+        role: &'a str,
+        link: &'a str,
+        expires_at: DateTime<Utc>,
+    },
+    Welcome {
+        first_name: &'a str,
+        link: &'a str,
+    },
+    EmailChange {
+        first_name: &'a str,
+        /// Address the user changes theirs to.
+        email: &'a str,
+        link: &'a str,
+    },
+}
+
+impl EmailMessage<'_> {
+    pub fn kind(&self) -> EmailKind {
+        match self {
+            EmailMessage::Verification { .. } => EmailKind::Verification,
+            EmailMessage::PasswordReset { .. } => EmailKind::PasswordReset,
+            EmailMessage::MagicLink { .. } => EmailKind::MagicLink,
+            EmailMessage::NewDevice { .. } => EmailKind::NewDevice,
+            EmailMessage::Invitation { .. } => EmailKind::Invitation,
+            EmailMessage::Welcome { .. } => EmailKind::Welcome,
+            EmailMessage::EmailChange { .. } => EmailKind::EmailChange,
+        }
+    }
+
+    /// Returns the value of the variable, with times shown in the time zone.
+    fn variable(&self, name: &str, timezone: TimeZone) -> Cow<'_, str> {
+        let value = match (self, name) {
+            (
+                EmailMessage::Verification { first_name, .. }
+                | EmailMessage::PasswordReset { first_name, .. }
+                | EmailMessage::MagicLink { first_name, .. }
+                | EmailMessage::NewDevice { first_name, .. }
+                | EmailMessage::Welcome { first_name, .. }
+                | EmailMessage::EmailChange { first_name, .. },
+                "first_name",
+            ) => first_name,
+            (
+                EmailMessage::Verification { email, .. }
+                | EmailMessage::EmailChange { email, .. },
+                "email",
+            ) => email,
+            (EmailMessage::Verification { reason, .. }, "reason") => reason,
+            (
+                EmailMessage::PasswordReset { link, .. }
+                | EmailMessage::MagicLink { link, .. }
+                | EmailMessage::Invitation { link, .. }
+                | EmailMessage::Welcome { link, .. }
+                | EmailMessage::EmailChange { link, .. },
+                "link",
+            ) => link,
+            (EmailMessage::NewDevice { user_agent, .. }, "user_agent") => {
+                user_agent
+            }
+            (EmailMessage::NewDevice { ip, .. }, "ip") => ip,
+            (EmailMessage::NewDevice { location, .. }, "location") => location,
+            (EmailMessage::NewDevice { time, .. }, "time") => {
+                return Cow::Owned(timezone.format(*time));
+            }
+            (EmailMessage::Invitation { inviter, .. }, "inviter") => inviter,
+            (EmailMessage::Invitation { organization, .. }, "organization") => {
+                organization
+            }
+            (EmailMessage::Invitation { role, .. }, "role") => role,
+            (EmailMessage::Invitation { expires_at, .. }, "expires_at") => {
+                return Cow::Owned(timezone.format(*expires_at));
+            }
+            _ => unreachable!("templates only use the variables of their kind"),
+        };
+
+        Cow::Borrowed(value)
+    }
+}
+
+/// A parsed template, whose variables are known to exist.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Template(Vec<Segment>);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Literal(String),
+    Variable(&'static str),
+}
+
+impl Template {
+    fn parse(kind: EmailKind, source: &str) -> Result<Self, String> {
+        let mut segments = Vec::new();
+        let mut rest = source;
+        while let Some(start) = rest.find("{{") {
+            let (literal, tail) = rest.split_at(start);
+            let Some(end) = tail.find("}}") else {
+                return Err(format!("unclosed '{{{{' in the {kind} template"));
+            };
+            let name = tail[2..end].trim();
+            let variable = kind
+                .variables()
+                .iter()
+                .find(|variable| **variable == name)
+                .ok_or_else(|| {
+                    format!(
+                        "the {kind} template uses the unknown variable '{name}', expected one of: {}",
+                        kind.variables().join(", ")
+                    )
+                })?;
+
+            segments.push(Segment::Literal(literal.to_owned()));
+            segments.push(Segment::Variable(variable));
+            rest = &tail[end + 2..];
+        }
+        segments.push(Segment::Literal(rest.to_owned()));
+
+        Ok(Template(segments))
+    }
+
+    fn render(
+        &self,
+        message: &EmailMessage<'_>,
+        timezone: TimeZone,
+        escape: fn(&str) -> String,
+    ) -> String {
+        self.0
+            .iter()
+            .map(|segment| match segment {
+                Segment::Literal(literal) => literal.clone(),
+                Segment::Variable(name) => {
+                    escape(&message.variable(name, timezone))
+                }
+            })
+            .collect()
+    }
+}
+
+/// Templates of all the kinds of emails, and their translations.
+#[derive(Debug, Clone)]
+pub struct EmailTemplates {
+    templates: Vec<(EmailKind, EmailPart, Template)>,
+    translations: Vec<(Locale, EmailKind, EmailPart, Template)>,
+}
+
+impl Default for EmailTemplates {
+    /// Returns the built-in templates.
+    fn default() -> Self {
+        let templates = EmailKind::ALL
+            .into_iter()
+            .flat_map(|kind| EmailPart::ALL.map(|part| (kind, part)))
+            .map(|(kind, part)| {
+                let template = Template::parse(kind, kind.builtin(part))
+                    .expect("the built-in templates are valid");
+
+                (kind, part, template)
+            })
+            .collect();
+
+        EmailTemplates {
+            templates,
+            translations: Vec::new(),
+        }
+    }
+}
+
+impl EmailTemplates {
+    /// Replaces the template of a part of a kind of emails. Fails if the template is malformed
+    /// or uses variables the kind doesn't have.
+    pub fn set(
+        &mut self,
+        kind: EmailKind,
+        part: EmailPart,
+        source: &str,
+    ) -> Result<(), String> {
+        let template = Template::parse(kind, source)?;
+        for (k, p, t) in &mut self.templates {
+            if (*k, *p) == (kind, part) {
+                *t = template;
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Adds or replaces the translation of the template of a part of a kind of emails. Fails
+    /// like [EmailTemplates::set].
+    pub fn translate(
+        &mut self,
+        locale: Locale,
+        kind: EmailKind,
+        part: EmailPart,
+        source: &str,
+    ) -> Result<(), String> {
+        let template = Template::parse(kind, source)?;
+        self.translations
+            .retain(|(l, k, p, _)| (l, *k, *p) != (&locale, kind, part));
+        self.translations.push((locale, kind, part, template));
+
+        Ok(())
+    }
+
+    /// Renders the email to the recipient, in English and with times in UTC.
+    pub fn render(&self, to: &str, message: &EmailMessage<'_>) -> Email {
+        self.render_localized(to, None, TimeZone::UTC, message)
+    }
+
+    /// Renders the email to the user, localized with their locale and time zone if they have
+    /// them.
+    ///
+    /// Every part is translated on its own, from the most specific translation of the locale,
+    /// e.g. `pt-BR`, to the least specific one, e.g. `pt`. Parts without any translation are
+    /// rendered from the default templates.
+    pub fn render_for(&self, user: &User, message: &EmailMessage<'_>) -> Email {
+        self.render_for_at(user, user.email(), message)
+    }
+
+    /// Renders the email localized for the user like [EmailTemplates::render_for], but to
+    /// another address of theirs, e.g. the one they change their email to.
+    pub fn render_for_at(
+        &self,
+        user: &User,
+        to: &str,
+        message: &EmailMessage<'_>,
+    ) -> Email {
+        self.render_localized(
+            to,
+            user.locale(),
+            user.timezone().unwrap_or(TimeZone::UTC),
+            message,
+        )
+    }
+
+    fn render_localized(
+        &self,
+        to: &str,
+        locale: Option<&Locale>,
+        timezone: TimeZone,
+        message: &EmailMessage<'_>,
+    ) -> Email {
+        let kind = message.kind();
+        // Headers can't span several lines.
+        let subject = self
+            .template(kind, EmailPart::Subject, locale)
+            .render(message, timezone, |value| value.to_owned())
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        Email {
+            to: to.to_owned(),
+            subject,
+            text: self.template(kind, EmailPart::Text, locale).render(
+                message,
+                timezone,
+                |value| value.to_owned(),
+            ),
+            html: Some(self.template(kind, EmailPart::Html, locale).render(
+                message,
+                timezone,
+                escape_html,
+            )),
+        }
+    }
+
+    fn template(
+        &self,
+        kind: EmailKind,
+        part: EmailPart,
+        locale: Option<&Locale>,
+    ) -> &Template {
+        let translation = locale
+            .into_iter()
+            .flat_map(Locale::fallbacks)
+            .find_map(|tag| {
+                self.translations.iter().find_map(|(l, k, p, template)| {
+                    (l.as_str() == tag && (*k, *p) == (kind, part))
+                        .then_some(template)
+                })
+            });
+        if let Some(template) = translation {
+            return template;
+        }
+
+        self.templates
+            .iter()
+            .find(|(k, p, _)| (*k, *p) == (kind, part))
+            .map(|(_, _, template)| template)
+            .expect("every part of every kind has a template")
+    }
+}
+
+fn escape_html(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
@@ -1,8 +1,132 @@
 mod contracts;
+mod email_templates;
+mod listing;
+mod pipeline;
+mod specification;
 mod use_cases;
 
+pub use contracts::authorization_policy as authorization_policy_contracts;
+pub use contracts::blob_storage::{Blob, BlobStorage};
+pub use contracts::consent as consent_contracts;
+pub use contracts::device as device_contracts;
+pub use contracts::email_alias as email_alias_contracts;
+pub use contracts::email_change as email_change_contracts;
+pub use contracts::email_sender::{Email, EmailSender};
+pub use contracts::email_verification as email_verification_contracts;
+pub use contracts::event_publisher::EventPublisher;
+pub use contracts::geo_resolver::GeoResolver;
+pub use contracts::invitation as invitation_contracts;
+pub use contracts::login_recorder::LoginRecorder;
+pub use contracts::membership as membership_contracts;
+pub use contracts::operation as operation_contracts;
+pub use contracts::organization as organization_contracts;
+pub use contracts::organization_quota as organization_quota_contracts;
+pub use contracts::password_hasher::PasswordHasher;
+pub use contracts::phone_verification as phone_verification_contracts;
+pub use contracts::reverification as reverification_contracts;
+pub use contracts::role as role_contracts;
+pub use contracts::session_store::{Session, SessionStore};
+pub use contracts::signing_key as signing_key_contracts;
+pub use contracts::signing_key_generator::SigningKeyGenerator;
+pub use contracts::sms_sender::{Sms, SmsSender};
+pub use contracts::token_generator::TokenGenerator;
+pub use contracts::unit_of_work::{UnitOfWork, UnitOfWorkFactory};
+pub use contracts::use_case_metrics::UseCaseMetrics;
+pub use contracts::use_case_retries::UseCaseRetries;
 pub use contracts::user as user_contracts;
-pub use use_cases::{CreateUserParams, UserUseCaseDeps, create_user};
+pub use contracts::user_attribute as user_attribute_contracts;
+pub use contracts::user_event as user_event_contracts;
+pub use contracts::user_summary as user_summary_contracts;
+pub use contracts::webhook_delivery as webhook_delivery_contracts;
+pub use contracts::webhook_endpoint as webhook_endpoint_contracts;
+pub use email_templates::{EmailKind, EmailMessage, EmailPart, EmailTemplates};
+pub use listing::{
+    DEFAULT_PAGE_SIZE, ListQuery, MAX_PAGE_SIZE, PageRequest, Paginated, Sort,
+    SortDirection,
+};
+pub use pipeline::{
+    AccessControlled, Actor, Authorize, Authorized, InTransaction, Logged,
+    Measured, Retried, System, TransactionalUseCase, TransactionalUseCaseExt,
+    UseCase, UseCaseExt,
+};
+pub use specification::Specification;
+pub use use_cases::{
+    AcceptInvitationParams, AcceptPolicyParams, AcceptedInvitation,
+    AcceptedPolicy, AddEmailAliasParams, AddOrganizationMemberParams,
+    AdminDigest, AdvanceOperationParams, AssignRole, AssignRoleParams,
+    AvatarUseCaseDeps, BuildAdminDigestParams, BulkAssignRoles,
+    BulkAssignRolesParams, BulkRoleAssignment, CancelOperationParams,
+    ChangeEmailParams, ChangeMemberRoleParams, ConsentUseCaseDeps,
+    CreateInvitationParams, CreateRole, CreateRoleParams, CreateUser,
+    CreateUserParams, CreateUsersParams, DeleteRole, DeleteRoleParams,
+    DeleteUserAttributeParams, DeleteWebhookEndpointParams, DeviceLogin,
+    DeviceLoginDeps, DeviceUseCaseDeps, DigestCategory, DigestUseCaseDeps,
+    DispatchUserEventsParams, DueWebhookDelivery, EmailAliasUseCaseDeps,
+    EmailChangeDeliveryDeps, EmailChangeDeps, EmailVerificationDeps,
+    EnsureSigningKeyParams, EraseUser, EraseUserParams, EventFeedUseCaseDeps,
+    ExportUsersParams, GetAvatarParams, GetInvitationParams,
+    GetOperationParams, GetOrganizationByExternalIdParams,
+    GetOrganizationBySlugParams, GetOrganizationQuotaParams,
+    GetReverificationProgressParams, GetRoleByExternalIdParams, GetRoleParams,
+    GetUserAttributesParams, GetUserByUsernameParams, GetUserParams,
+    GetUsersParams, GetWebhookEndpointByExternalIdParams,
+    GetWebhookEndpointParams, GrantRolePermission, GrantRolePermissionParams,
+    InvitationAcceptanceDeps, InvitationDeliveryDeps, InvitationUseCaseDeps,
+    IssueSessionParams, IssuedSession, ListConsentsParams, ListDevicesParams,
+    ListDueWebhookDeliveriesParams, ListEmailAliasesParams,
+    ListPublishedSigningKeysParams, ListRoleMembersParams, ListRolesParams,
+    ListUserRolesParams, ListUsersParams, ListWebhookDeliveriesParams,
+    ListWebhookEndpointsParams, MAX_BULK_ASSIGNMENTS, MaintenanceUseCaseDeps,
+    MergeUsers, MergeUsersParams, MergedUsers, NotifyReverificationBatchParams,
+    OperationTransition, OperationUseCaseDeps, OrganizationQuotaUsage,
+    OrganizationUseCaseDeps, PhoneVerificationDeliveryDeps,
+    PhoneVerificationDeps, ProjectUserSummariesParams, PruneSigningKeysParams,
+    PruneUserEventsParams, PruneWebhookDeliveriesParams, PutOrganizationParams,
+    PutOrganizationQuotaParams, PutOutcome, PutRole, PutRoleParams,
+    PutUserAttributesParams, PutWebhookEndpointParams, ReadModelProjectionDeps,
+    RecordDeviceLoginParams, RecordWebhookAttemptParams,
+    RedeliverWebhookDeliveryParams, RegisterUserParams,
+    RegisterUserWithOrganizationParams, RegisterWebhookEndpointParams,
+    RegistrationUseCaseDeps, RemoveEmailAliasParams, RequestEmailChangeParams,
+    ResetOrganizationQuotaParams, ReverificationNotificationDeps,
+    ReverificationUseCaseDeps, RevokeDeviceParams, RevokeDeviceSessionsParams,
+    RevokeRolePermission, RevokeRolePermissionParams, RoleUseCaseDeps,
+    RotateSigningKeyParams, SearchUsersParams, SelfRegistrationDeps,
+    SessionIssuanceDeps, SessionUseCaseDeps, SetUserPasswordParams,
+    SigningKeyRotationDeps, SigningKeyUseCaseDeps, StartOperationParams,
+    StartPhoneVerificationParams, StartReverificationCampaignParams,
+    TailUserEventsParams, UnassignRole, UnassignRoleParams, UpdateRole,
+    UpdateRoleParams, UpdateUserParams, UploadAvatarParams,
+    UserAttributeUseCaseDeps, UserConsents, UserEventTail,
+    UserLifecycleUseCaseDeps, UserPasswordUseCaseDeps, UserUseCaseDeps,
+    VerifyEmailParams, VerifyPhoneParams, VerifySessionParams,
+    WebhookDeliveryUseCaseDeps, WebhookDispatchUseCaseDeps,
+    WebhookEndpointUseCaseDeps, accept_invitation, accept_policy,
+    add_email_alias, add_organization_member, advance_operation, avatar_key,
+    build_admin_digest, cancel_operation, change_email, change_member_role,
+    create_invitation, create_user, create_users, delete_user_attribute,
+    delete_webhook_endpoint, dispatch_user_events, ensure_signing_key,
+    export_users, get_avatar, get_invitation, get_operation,
+    get_organization_by_external_id, get_organization_by_slug,
+    get_organization_quota, get_reverification_progress, get_role,
+    get_role_by_external_id, get_user, get_user_attributes,
+    get_user_by_username, get_users, get_webhook_endpoint,
+    get_webhook_endpoint_by_external_id, issue_session, list_consents,
+    list_devices, list_due_webhook_deliveries, list_email_aliases,
+    list_published_signing_keys, list_role_members, list_roles,
+    list_user_roles, list_users, list_webhook_deliveries,
+    list_webhook_endpoints, notify_reverification_batch,
+    project_user_summaries, prune_signing_keys, prune_user_events,
+    prune_webhook_deliveries, put_organization, put_organization_quota,
+    put_user_attributes, put_webhook_endpoint, record_device_login,
+    record_webhook_attempt, redeliver_webhook_delivery, register_user,
+    register_user_with_organization, register_webhook_endpoint,
+    remove_email_alias, request_email_change, reset_organization_quota,
+    revoke_device, revoke_device_sessions, rotate_signing_key, search_users,
+    set_user_password, start_operation, start_phone_verification,
+    start_reverification_campaign, tail_user_events, update_user,
+    upload_avatar, verify_email, verify_phone, verify_session,
+};
 
 use thiserror::Error;
 
@@ -18,6 +142,45 @@ pub enum ApplicationError {
 
     #[error("Failed to create an entity of type {entity}: {message}")]
     EntityAlreadyExists { entity: String, message: String },
+
+    #[error("{entity} with ID {id} was not found")]
+    EntityNotFound { entity: String, id: String },
+
+    #[error(
+        "The entity references one that doesn't exist, or is still referenced"
+    )]
+    ReferenceViolation,
+
+    #[error("The operation didn't finish before its deadline")]
+    DeadlineExceeded,
+
+    #[error("The storage is temporarily unavailable")]
+    Unavailable,
+
+    #[error("The permission {permission} is required")]
+    Forbidden { permission: String },
+
+    #[error(
+        "The user has to accept the current version of {}",
+        policies.join(", ")
+    )]
+    ConsentRequired { policies: Vec<String> },
+
+    #[error("Registration refused: {0}")]
+    RegistrationRefused(identify_domain::RegistrationRefusal),
+
+    #[error("Quota exceeded: {0}")]
+    QuotaExceeded(#[from] identify_domain::QuotaExceeded),
+
+    #[error(
+        "{entity} with ID {id} is at version {actual}, but version {expected} was expected"
+    )]
+    StaleVersion {
+        entity: String,
+        id: String,
+        expected: u32,
+        actual: u32,
+    },
 }
 
 impl ApplicationError {
@@ -41,4 +204,38 @@ impl ApplicationError {
             message: message.into(),
         }
     }
+
+    pub fn forbidden(permission: impl Into<String>) -> Self {
+        Self::Forbidden {
+            permission: permission.into(),
+        }
+    }
+
+    /// Short name of the error, e.g. used as a label of metrics.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Domain(_) => "domain",
+            Self::Internal(_) => "internal",
+            Self::EntityAlreadyExists { .. } => "already_exists",
+            Self::EntityNotFound { .. } => "not_found",
+            Self::ReferenceViolation => "reference_violation",
+            Self::DeadlineExceeded => "deadline_exceeded",
+            Self::Unavailable => "unavailable",
+            Self::Forbidden { .. } => "forbidden",
+            Self::ConsentRequired { .. } => "consent_required",
+            Self::RegistrationRefused(_) => "registration_refused",
+            Self::QuotaExceeded(_) => "quota_exceeded",
+            Self::StaleVersion { .. } => "stale_version",
+        }
+    }
+
+    pub fn entity_not_found<M: Into<String>>(
+        entity: M,
+        id: impl std::fmt::Display,
+    ) -> Self {
+        Self::EntityNotFound {
+            entity: entity.into(),
+            id: id.to_string(),
+        }
+    }
 }
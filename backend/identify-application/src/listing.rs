@@ -0,0 +1,68 @@
+//! Typed structures shared by all use cases that return lists of entities.
+
+/// Default number of items on a single page.
+pub const DEFAULT_PAGE_SIZE: u32 = 20;
+/// Maximum number of items that can be requested on a single page.
+pub const MAX_PAGE_SIZE: u32 = 100;
+
+/// A request for a single page of results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PageRequest {
+    /// 1-based number of the page.
+    pub number: u32,
+    /// Number of items on the page.
+    pub size: u32,
+}
+
+impl PageRequest {
+    /// Number of items to skip to get to this page.
+    pub fn offset(&self) -> u64 {
+        u64::from(self.number.saturating_sub(1)) * u64::from(self.size)
+    }
+}
+
+impl Default for PageRequest {
+    fn default() -> Self {
+        PageRequest {
+            number: 1,
+            size: DEFAULT_PAGE_SIZE,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortDirection {
+    Ascending,
+    Descending,
+}
+
+/// Sorting by a single field of type `F`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sort<F> {
+    pub field: F,
+    pub direction: SortDirection,
+}
+
+/// Everything needed to list entities: the filter of type `F`, the sort order by fields of type
+/// `S` (in order of priority) and the requested page.
+#[derive(Debug, Clone, Default)]
+pub struct ListQuery<F, S> {
+    pub filter: F,
+    pub sort: Vec<Sort<S>>,
+    pub page: PageRequest,
+}
+
+/// A single page of results.
+#[derive(Debug)]
+pub struct Paginated<T> {
+    pub items: Vec<T>,
+    pub page: PageRequest,
+    /// Total number of items matching the filter across all pages.
+    pub total_items: u64,
+}
+
+impl<T> Paginated<T> {
+    pub fn total_pages(&self) -> u64 {
+        self.total_items.div_ceil(u64::from(self.page.size.max(1)))
+    }
+}
@@ -0,0 +1,128 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use identify_domain::{
+    AccessDecision, AccessRequest, AuthorizationPolicies, Resource,
+};
+use tracing::debug;
+
+use crate::{
+    ApplicationError, Result,
+    pipeline::{Actor, UseCase},
+};
+
+/// Executes the use case only if the actor has the permission.
+pub struct Authorized<U> {
+    use_case: U,
+    permission: &'static str,
+}
+
+impl<U> Authorized<U> {
+    pub fn new(use_case: U, permission: &'static str) -> Self {
+        Authorized {
+            use_case,
+            permission,
+        }
+    }
+}
+
+#[async_trait]
+impl<U: UseCase> UseCase for Authorized<U> {
+    type Input = U::Input;
+    type Output = U::Output;
+
+    fn name(&self) -> &'static str {
+        self.use_case.name()
+    }
+
+    async fn execute(
+        &self,
+        actor: &dyn Actor,
+        input: Self::Input,
+    ) -> Result<Self::Output> {
+        if !actor.has_permission(self.permission) {
+            debug!(
+                use_case = self.name(),
+                actor = actor.subject(),
+                permission = self.permission,
+                "Actor is missing the permission of the use case"
+            );
+            return Err(ApplicationError::forbidden(self.permission));
+        }
+
+        self.use_case.execute(actor, input).await
+    }
+}
+
+/// Inputs of use cases that act on a resource, which [Authorize] describes to the policies.
+pub trait AccessControlled {
+    /// Returns the resource the use case acts on, e.g. the user it erases.
+    fn resource(&self) -> Resource;
+}
+
+/// Executes the use case only if the [AuthorizationPolicies] allow the actor to perform the
+/// action on the resource of the input.
+///
+/// Requests that no policy matches are decided by the permissions of the actor, so the action
+/// doubles as the permission the use case requires, like with [Authorized].
+pub struct Authorize<U> {
+    use_case: U,
+    policies: Arc<AuthorizationPolicies>,
+    action: &'static str,
+}
+
+impl<U> Authorize<U> {
+    pub fn new(
+        use_case: U,
+        policies: Arc<AuthorizationPolicies>,
+        action: &'static str,
+    ) -> Self {
+        Authorize {
+            use_case,
+            policies,
+            action,
+        }
+    }
+}
+
+#[async_trait]
+impl<U> UseCase for Authorize<U>
+where
+    U: UseCase,
+    U::Input: AccessControlled,
+{
+    type Input = U::Input;
+    type Output = U::Output;
+
+    fn name(&self) -> &'static str {
+        self.use_case.name()
+    }
+
+    async fn execute(
+        &self,
+        actor: &dyn Actor,
+        input: Self::Input,
+    ) -> Result<Self::Output> {
+        let request =
+            AccessRequest::new(actor.subject(), self.action, input.resource());
+        let allowed = match self.policies.evaluate(&request) {
+            AccessDecision::Allowed { .. } => true,
+            AccessDecision::Denied { policy } => {
+                debug!(
+                    use_case = self.name(),
+                    actor = actor.subject(),
+                    action = self.action,
+                    policy,
+                    "Policy denies the actor the action of the use case"
+                );
+                false
+            }
+            AccessDecision::NotApplicable => actor.has_permission(self.action),
+        };
+        if !allowed {
+            return Err(ApplicationError::forbidden(self.action));
+        }
+
+        self.use_case.execute(actor, input).await
+    }
+}
@@ -0,0 +1,62 @@
+use std::time::Instant;
+
+use async_trait::async_trait;
+use tracing::{Instrument, debug, info_span, trace, warn};
+
+use crate::{
+    ApplicationError, Result,
+    pipeline::{Actor, UseCase},
+};
+
+/// Executes the use case in its own span, and logs how it ended.
+///
+/// Internal errors are logged as warnings, while the errors caused by the input (e.g. failed
+/// validations) are only logged at the debug level, as they are reported back to the actor.
+pub struct Logged<U> {
+    use_case: U,
+}
+
+impl<U> Logged<U> {
+    pub fn new(use_case: U) -> Self {
+        Logged { use_case }
+    }
+}
+
+#[async_trait]
+impl<U: UseCase> UseCase for Logged<U> {
+    type Input = U::Input;
+    type Output = U::Output;
+
+    fn name(&self) -> &'static str {
+        self.use_case.name()
+    }
+
+    async fn execute(
+        &self,
+        actor: &dyn Actor,
+        input: Self::Input,
+    ) -> Result<Self::Output> {
+        let span =
+            info_span!("use_case", name = self.name(), actor = actor.subject());
+
+        async move {
+            trace!("Executing use case");
+            let started_at = Instant::now();
+
+            let result = self.use_case.execute(actor, input).await;
+
+            let elapsed_ms = started_at.elapsed().as_millis() as u64;
+            match &result {
+                Ok(_) => trace!(elapsed_ms, "Use case succeeded"),
+                Err(e @ ApplicationError::Internal(_)) => {
+                    warn!(elapsed_ms, error = %e, "Use case failed");
+                }
+                Err(e) => debug!(elapsed_ms, error = %e, "Use case failed"),
+            }
+
+            result
+        }
+        .instrument(span)
+        .await
+    }
+}
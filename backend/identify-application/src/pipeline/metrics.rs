@@ -0,0 +1,49 @@
+use std::time::Instant;
+
+use async_trait::async_trait;
+
+use crate::{
+    Result, UseCaseMetrics,
+    pipeline::{Actor, UseCase},
+};
+
+/// Records the duration and the outcome of every execution of the use case.
+pub struct Measured<U, M> {
+    use_case: U,
+    metrics: M,
+}
+
+impl<U, M> Measured<U, M> {
+    pub fn new(use_case: U, metrics: M) -> Self {
+        Measured { use_case, metrics }
+    }
+}
+
+#[async_trait]
+impl<U: UseCase, M: UseCaseMetrics> UseCase for Measured<U, M> {
+    type Input = U::Input;
+    type Output = U::Output;
+
+    fn name(&self) -> &'static str {
+        self.use_case.name()
+    }
+
+    async fn execute(
+        &self,
+        actor: &dyn Actor,
+        input: Self::Input,
+    ) -> Result<Self::Output> {
+        let started_at = Instant::now();
+
+        let result = self.use_case.execute(actor, input).await;
+
+        let outcome = match &result {
+            Ok(_) => "ok",
+            Err(e) => e.kind(),
+        };
+        self.metrics
+            .record(self.name(), outcome, started_at.elapsed());
+
+        result
+    }
+}
@@ -0,0 +1,120 @@
+//! Decorators that handle the concerns shared by all use cases, so that use cases only contain
+//! their own logic.
+//!
+//! A use case implements [UseCase], or [TransactionalUseCase] if it writes through a
+//! [UnitOfWork](crate::UnitOfWork), and gets wrapped in the decorators it needs. Decorators
+//! are applied from the inside out, so the last one runs first:
+//!
+//! ```ignore
+//! let create_user = CreateUser
+//!     .in_transaction(units_of_work)
+//!     .retried(retries)
+//!     .authorized("users:write")
+//!     .measured(metrics)
+//!     .logged();
+//!
+//! let user = create_user.execute(&principal, params).await?;
+//! ```
+
+mod authorization;
+mod logging;
+mod metrics;
+mod retry;
+mod transaction;
+
+pub use authorization::{AccessControlled, Authorize, Authorized};
+pub use logging::Logged;
+pub use metrics::Measured;
+pub use retry::Retried;
+pub use transaction::{
+    InTransaction, TransactionalUseCase, TransactionalUseCaseExt,
+};
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use identify_domain::AuthorizationPolicies;
+
+use crate::{Result, UseCaseMetrics, UseCaseRetries};
+
+/// Who executes a use case, e.g. a caller of the API.
+pub trait Actor: Send + Sync {
+    /// Identifies the actor in logs, e.g. the owner of an API key.
+    fn subject(&self) -> &str;
+
+    /// Whether the actor is allowed to do what the permission stands for, e.g. `users:write`.
+    fn has_permission(&self, permission: &str) -> bool;
+}
+
+/// The service itself, e.g. background jobs and administrative commands, which is allowed to
+/// do everything.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct System;
+
+impl Actor for System {
+    fn subject(&self) -> &str {
+        "system"
+    }
+
+    fn has_permission(&self, _permission: &str) -> bool {
+        true
+    }
+}
+
+/// A single action of the application, e.g. creating a user.
+#[async_trait]
+pub trait UseCase: Send + Sync {
+    type Input: Send;
+    type Output: Send;
+
+    /// Name of the use case in logs and metrics, e.g. `create_user`.
+    fn name(&self) -> &'static str;
+
+    /// Execute the use case on behalf of the actor.
+    async fn execute(
+        &self,
+        actor: &dyn Actor,
+        input: Self::Input,
+    ) -> Result<Self::Output>;
+}
+
+/// Wraps use cases in the decorators of the pipeline.
+pub trait UseCaseExt: UseCase + Sized {
+    /// Logs every execution together with its outcome.
+    fn logged(self) -> Logged<Self> {
+        Logged::new(self)
+    }
+
+    /// Records the duration and the outcome of every execution.
+    fn measured<M: UseCaseMetrics>(self, metrics: M) -> Measured<Self, M> {
+        Measured::new(self, metrics)
+    }
+
+    /// Executes the use case again when it fails with an error worth retrying.
+    fn retried<R: UseCaseRetries>(self, retries: R) -> Retried<Self, R>
+    where
+        Self::Input: Clone,
+    {
+        Retried::new(self, retries)
+    }
+
+    /// Rejects actors without the permission before the use case runs.
+    fn authorized(self, permission: &'static str) -> Authorized<Self> {
+        Authorized::new(self, permission)
+    }
+
+    /// Rejects actors the policies don't allow to perform the action on the resource of the
+    /// input, or who lack the action as a permission when no policy applies.
+    fn authorize(
+        self,
+        policies: Arc<AuthorizationPolicies>,
+        action: &'static str,
+    ) -> Authorize<Self>
+    where
+        Self::Input: AccessControlled,
+    {
+        Authorize::new(self, policies, action)
+    }
+}
+
+impl<U: UseCase> UseCaseExt for U {}
@@ -0,0 +1,60 @@
+use async_trait::async_trait;
+
+use crate::{
+    Result, UseCaseRetries,
+    pipeline::{Actor, UseCase},
+};
+
+/// Executes the use case again if it fails with an error worth retrying, e.g. because the
+/// database was busy.
+///
+/// Every attempt gets a copy of the input, and transactional use cases run in a new unit of
+/// work every time, so the decorator has to wrap [InTransaction](crate::InTransaction).
+pub struct Retried<U, R> {
+    use_case: U,
+    retries: R,
+}
+
+impl<U, R> Retried<U, R> {
+    pub fn new(use_case: U, retries: R) -> Self {
+        Retried { use_case, retries }
+    }
+}
+
+#[async_trait]
+impl<U, R> UseCase for Retried<U, R>
+where
+    U: UseCase,
+    U::Input: Clone + Sync,
+    R: UseCaseRetries,
+{
+    type Input = U::Input;
+    type Output = U::Output;
+
+    fn name(&self) -> &'static str {
+        self.use_case.name()
+    }
+
+    async fn execute(
+        &self,
+        actor: &dyn Actor,
+        input: Self::Input,
+    ) -> Result<Self::Output> {
+        self.retries.record_execution(self.name());
+
+        let mut attempt = 1;
+        loop {
+            match self.use_case.execute(actor, input.clone()).await {
+                Err(e)
+                    if self
+                        .retries
+                        .wait_before_retry(self.name(), attempt, &e)
+                        .await =>
+                {
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
@@ -0,0 +1,88 @@
+use async_trait::async_trait;
+
+use crate::{
+    Result, UnitOfWork, UnitOfWorkFactory,
+    pipeline::{Actor, UseCase},
+};
+
+/// A use case that writes through a [UnitOfWork], which is started and committed for it by
+/// [InTransaction].
+#[async_trait]
+pub trait TransactionalUseCase<W: UnitOfWork>: Send + Sync {
+    type Input: Send;
+    type Output: Send;
+
+    /// Name of the use case in logs and metrics, e.g. `create_user`.
+    fn name(&self) -> &'static str;
+
+    /// Execute the use case on behalf of the actor.
+    ///
+    /// The changes made through the unit of work are committed only if the use case succeeds.
+    async fn execute(
+        &self,
+        unit_of_work: &mut W,
+        actor: &dyn Actor,
+        input: Self::Input,
+    ) -> Result<Self::Output>;
+}
+
+/// Wraps transactional use cases, so that they can be decorated like any other [UseCase].
+pub trait TransactionalUseCaseExt<W: UnitOfWork>:
+    TransactionalUseCase<W> + Sized
+{
+    /// Executes the use case in a new unit of work started by the factory.
+    fn in_transaction<F>(self, factory: F) -> InTransaction<Self, F>
+    where
+        F: UnitOfWorkFactory<UnitOfWork = W>,
+    {
+        InTransaction::new(self, factory)
+    }
+}
+
+impl<W: UnitOfWork, T: TransactionalUseCase<W>> TransactionalUseCaseExt<W>
+    for T
+{
+}
+
+/// Executes the use case in its own unit of work, which is committed if the use case succeeds
+/// and discarded otherwise.
+pub struct InTransaction<T, F> {
+    use_case: T,
+    factory: F,
+}
+
+impl<T, F> InTransaction<T, F> {
+    pub fn new(use_case: T, factory: F) -> Self {
+        InTransaction { use_case, factory }
+    }
+}
+
+#[async_trait]
+impl<T, F> UseCase for InTransaction<T, F>
+where
+    F: UnitOfWorkFactory,
+    T: TransactionalUseCase<F::UnitOfWork>,
+{
+    type Input = T::Input;
+    type Output = T::Output;
+
+    fn name(&self) -> &'static str {
+        self.use_case.name()
+    }
+
+    async fn execute(
+        &self,
+        actor: &dyn Actor,
+        input: Self::Input,
+    ) -> Result<Self::Output> {
+        let mut unit_of_work = self.factory.begin().await?;
+
+        let output = self
+            .use_case
+            .execute(&mut unit_of_work, actor, input)
+            .await?;
+        unit_of_work.commit().await?;
+
+        Ok(output)
+    }
+}
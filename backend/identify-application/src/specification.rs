@@ -0,0 +1,76 @@
+//! Composable predicates on entities, which repositories compile to their own queries.
+//!
+//! A repository that finds entities by [Specifications](Specification) answers any combination
+//! of the criteria it knows, so that a new shape of query doesn't need a new contract.
+
+use std::ops::Not;
+
+/// A predicate on entities built from criteria of type `C`, e.g.
+/// [UserCriterion](crate::user_contracts::UserCriterion).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum Specification<C> {
+    /// Matches every entity.
+    #[default]
+    All,
+    /// Matches the entities that match the criterion.
+    Criterion(C),
+    /// Matches the entities that match all the specifications, or every entity if there are
+    /// none.
+    And(Vec<Specification<C>>),
+    /// Matches the entities that match any of the specifications, or no entity if there are
+    /// none.
+    Or(Vec<Specification<C>>),
+    /// Matches the entities that don't match the specification.
+    Not(Box<Specification<C>>),
+}
+
+impl<C> Specification<C> {
+    /// Matches the entities that match both this specification and the other one.
+    pub fn and(self, other: impl Into<Self>) -> Self {
+        match (self, other.into()) {
+            (Self::All, other) => other,
+            (this, Self::All) => this,
+            (Self::And(mut specs), Self::And(others)) => {
+                specs.extend(others);
+                Self::And(specs)
+            }
+            (Self::And(mut specs), other) => {
+                specs.push(other);
+                Self::And(specs)
+            }
+            (this, other) => Self::And(vec![this, other]),
+        }
+    }
+
+    /// Matches the entities that match either this specification or the other one.
+    pub fn or(self, other: impl Into<Self>) -> Self {
+        match (self, other.into()) {
+            (Self::Or(mut specs), Self::Or(others)) => {
+                specs.extend(others);
+                Self::Or(specs)
+            }
+            (Self::Or(mut specs), other) => {
+                specs.push(other);
+                Self::Or(specs)
+            }
+            (this, other) => Self::Or(vec![this, other]),
+        }
+    }
+}
+
+impl<C> From<C> for Specification<C> {
+    fn from(criterion: C) -> Self {
+        Self::Criterion(criterion)
+    }
+}
+
+impl<C> Not for Specification<C> {
+    type Output = Self;
+
+    fn not(self) -> Self {
+        match self {
+            Self::Not(spec) => *spec,
+            spec => Self::Not(Box::new(spec)),
+        }
+    }
+}
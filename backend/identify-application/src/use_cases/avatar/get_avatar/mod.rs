@@ -0,0 +1,35 @@
+use identify_domain::Avatar;
+use tracing::{instrument, trace};
+use uuid::Uuid;
+
+use crate::{
+    ApplicationError, BlobStorage, Result, UnitOfWork,
+    use_cases::avatar::{AvatarUseCaseDeps, avatar_key},
+    user_contracts::Get as _,
+};
+
+#[derive(Debug)]
+pub struct GetAvatarParams {
+    pub user_id: Uuid,
+}
+
+/// Returns the avatar of a user. Fails if the user doesn't exist or has no avatar.
+#[instrument(skip(deps))]
+pub async fn get_avatar<U: UnitOfWork, S: BlobStorage + ?Sized>(
+    deps: AvatarUseCaseDeps<'_, U, S>,
+    params: GetAvatarParams,
+) -> Result<Avatar> {
+    trace!("Executing use case");
+
+    deps.unit_of_work.users().get(params.user_id).await?;
+
+    let blob = deps
+        .storage
+        .get(&avatar_key(params.user_id))
+        .await?
+        .ok_or_else(|| {
+            ApplicationError::entity_not_found("Avatar", params.user_id)
+        })?;
+
+    Ok(Avatar::parse(&blob.content_type, blob.bytes)?)
+}
@@ -0,0 +1,27 @@
+use uuid::Uuid;
+
+pub mod get_avatar;
+pub mod upload_avatar;
+
+/// Dependencies of the use cases that manage the avatars of users.
+///
+/// Avatars are kept in the [BlobStorage](crate::BlobStorage) rather than in the database, so
+/// the [UnitOfWork](crate::UnitOfWork) is only used to look their users up.
+pub struct AvatarUseCaseDeps<'a, U, S: ?Sized> {
+    unit_of_work: &'a mut U,
+    storage: &'a S,
+}
+
+impl<'a, U, S: ?Sized> AvatarUseCaseDeps<'a, U, S> {
+    pub fn new(unit_of_work: &'a mut U, storage: &'a S) -> Self {
+        AvatarUseCaseDeps {
+            unit_of_work,
+            storage,
+        }
+    }
+}
+
+/// Returns the key the avatar of a user is stored under.
+pub fn avatar_key(user_id: Uuid) -> String {
+    format!("avatars/{user_id}")
+}
@@ -0,0 +1,38 @@
+use identify_domain::Avatar;
+use tracing::{instrument, trace};
+use uuid::Uuid;
+
+use crate::{
+    Blob, BlobStorage, Result, UnitOfWork,
+    use_cases::avatar::{AvatarUseCaseDeps, avatar_key},
+    user_contracts::Get as _,
+};
+
+#[derive(Debug)]
+pub struct UploadAvatarParams {
+    pub user_id: Uuid,
+    pub avatar: Avatar,
+}
+
+/// Stores the avatar of a user, replacing the previous one. Fails if the user doesn't exist.
+#[instrument(skip(deps))]
+pub async fn upload_avatar<U: UnitOfWork, S: BlobStorage + ?Sized>(
+    deps: AvatarUseCaseDeps<'_, U, S>,
+    params: UploadAvatarParams,
+) -> Result<Avatar> {
+    trace!("Executing use case");
+
+    deps.unit_of_work
+        .users()
+        .get(params.user_id)
+        .await?
+        .ensure_not_erased()?;
+
+    let blob = Blob {
+        content_type: params.avatar.format().content_type().to_owned(),
+        bytes: params.avatar.bytes().to_vec(),
+    };
+    deps.storage.put(&avatar_key(params.user_id), &blob).await?;
+
+    Ok(params.avatar)
+}
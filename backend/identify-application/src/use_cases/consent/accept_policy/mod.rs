@@ -0,0 +1,86 @@
+use identify_domain::{Consent, DomainError, NewConsentAttrs};
+use tracing::{instrument, trace};
+use uuid::Uuid;
+
+use crate::{
+    ApplicationError, Result, UnitOfWork,
+    consent_contracts::{Insert as _, ListByUser as _},
+    use_cases::consent::ConsentUseCaseDeps,
+    user_contracts::Get as _,
+};
+
+#[derive(Debug)]
+pub struct AcceptPolicyParams {
+    pub user_id: Uuid,
+    pub policy: String,
+    /// Version the user was shown, which has to be the current one.
+    pub version: String,
+}
+
+/// A consent recorded, or found, by [accept_policy].
+#[derive(Debug)]
+pub struct AcceptedPolicy {
+    pub consent: Consent,
+    /// Whether the user hadn't accepted the version before.
+    pub newly_accepted: bool,
+}
+
+/// Records that a user accepted the current version of a policy.
+///
+/// Accepting the same version again returns the original consent. Outdated versions can't be
+/// accepted, so that a user who was shown a policy right before it was replaced has to read the
+/// new one. Fails if the policy doesn't exist, or if the user doesn't exist or has been erased.
+#[instrument(skip(deps))]
+pub async fn accept_policy<U: UnitOfWork>(
+    deps: ConsentUseCaseDeps<'_, U>,
+    params: AcceptPolicyParams,
+) -> Result<AcceptedPolicy> {
+    trace!("Executing use case");
+
+    let ConsentUseCaseDeps {
+        unit_of_work: uow,
+        policies,
+    } = deps;
+    let user = uow.users().get(params.user_id).await?;
+    user.ensure_not_erased()?;
+
+    let current =
+        policies.current_version(&params.policy).ok_or_else(|| {
+            ApplicationError::entity_not_found("Policy", &params.policy)
+        })?;
+    if params.version != current {
+        return Err(DomainError::validation(
+            "Consent",
+            format!(
+                "'{}' is not the current version of the policy '{}', which is '{current}'",
+                params.version, params.policy
+            ),
+        )
+        .into());
+    }
+
+    let accepted = uow
+        .consents()
+        .list_by_user(params.user_id)
+        .await?
+        .into_iter()
+        .find(|consent| consent.is_for(&params.policy, &params.version));
+    if let Some(consent) = accepted {
+        return Ok(AcceptedPolicy {
+            consent,
+            newly_accepted: false,
+        });
+    }
+
+    let consent = Consent::new(NewConsentAttrs {
+        user_id: params.user_id,
+        policy: params.policy,
+        version: params.version,
+    })?;
+    uow.consents().insert(&consent).await?;
+
+    Ok(AcceptedPolicy {
+        consent,
+        newly_accepted: true,
+    })
+}
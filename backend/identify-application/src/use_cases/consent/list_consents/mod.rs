@@ -0,0 +1,43 @@
+use identify_domain::{Consent, PolicyVersion};
+use tracing::{instrument, trace};
+use uuid::Uuid;
+
+use crate::{
+    Result, UnitOfWork, consent_contracts::ListByUser as _,
+    use_cases::consent::ConsentUseCaseDeps, user_contracts::Get as _,
+};
+
+#[derive(Debug)]
+pub struct ListConsentsParams {
+    pub user_id: Uuid,
+}
+
+/// The consents of a user, returned by [list_consents].
+#[derive(Debug)]
+pub struct UserConsents {
+    /// All the consents of the user, the most recent first.
+    pub consents: Vec<Consent>,
+    /// Current versions of the policies the user has yet to accept.
+    pub pending: Vec<PolicyVersion>,
+}
+
+/// Returns the consents of a user, and the policies they have to accept before they can log
+/// in. Fails if the user doesn't exist.
+#[instrument(skip(deps))]
+pub async fn list_consents<U: UnitOfWork>(
+    deps: ConsentUseCaseDeps<'_, U>,
+    params: ListConsentsParams,
+) -> Result<UserConsents> {
+    trace!("Executing use case");
+
+    let ConsentUseCaseDeps {
+        unit_of_work: uow,
+        policies,
+    } = deps;
+    uow.users().get(params.user_id).await?;
+
+    let consents = uow.consents().list_by_user(params.user_id).await?;
+    let pending = policies.pending(&consents);
+
+    Ok(UserConsents { consents, pending })
+}
@@ -0,0 +1,50 @@
+use identify_domain::ConsentPolicies;
+use uuid::Uuid;
+
+use crate::{
+    ApplicationError, Result, UnitOfWork, consent_contracts::ListByUser as _,
+};
+
+pub mod accept_policy;
+pub mod list_consents;
+
+/// Dependencies of the use cases that record and query which policies users accepted.
+///
+/// Consents are read and written in a [UnitOfWork] together with the user they belong to,
+/// which is committed by the caller.
+pub struct ConsentUseCaseDeps<'a, U> {
+    unit_of_work: &'a mut U,
+    policies: &'a ConsentPolicies,
+}
+
+impl<'a, U> ConsentUseCaseDeps<'a, U> {
+    pub fn new(unit_of_work: &'a mut U, policies: &'a ConsentPolicies) -> Self {
+        ConsentUseCaseDeps {
+            unit_of_work,
+            policies,
+        }
+    }
+}
+
+/// Fails with [ApplicationError::ConsentRequired] if the user hasn't accepted the current
+/// version of every policy.
+pub(crate) async fn ensure_consented<U: UnitOfWork>(
+    uow: &mut U,
+    policies: &ConsentPolicies,
+    user_id: Uuid,
+) -> Result<()> {
+    let consents = uow.consents().list_by_user(user_id).await?;
+    let pending = policies.pending(&consents);
+    if !pending.is_empty() {
+        return Err(ApplicationError::ConsentRequired {
+            policies: pending
+                .into_iter()
+                .map(|pending| {
+                    format!("{}@{}", pending.policy, pending.version)
+                })
+                .collect(),
+        });
+    }
+
+    Ok(())
+}
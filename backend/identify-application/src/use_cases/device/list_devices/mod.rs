@@ -0,0 +1,29 @@
+use identify_domain::Device;
+use tracing::{instrument, trace};
+use uuid::Uuid;
+
+use crate::{
+    Result, UnitOfWork, device_contracts::ListByUser as _,
+    use_cases::device::DeviceUseCaseDeps, user_contracts::Get as _,
+};
+
+#[derive(Debug)]
+pub struct ListDevicesParams {
+    pub user_id: Uuid,
+}
+
+/// Returns all the devices a user logged in from, including the revoked ones, the most
+/// recently seen first. Fails if the user doesn't exist.
+#[instrument(skip(deps))]
+pub async fn list_devices<U: UnitOfWork>(
+    deps: DeviceUseCaseDeps<'_, U>,
+    params: ListDevicesParams,
+) -> Result<Vec<Device>> {
+    trace!("Executing use case");
+
+    let uow = deps.unit_of_work;
+
+    uow.users().get(params.user_id).await?;
+
+    uow.devices().list_by_user(params.user_id).await
+}
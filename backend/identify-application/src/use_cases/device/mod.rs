@@ -0,0 +1,53 @@
+use identify_domain::ConsentPolicies;
+
+use crate::EmailTemplates;
+
+pub mod list_devices;
+pub mod record_device_login;
+pub mod revoke_device;
+
+/// Dependencies of the use cases that manage the devices of users.
+///
+/// Devices are read and written in a [UnitOfWork](crate::UnitOfWork) together with the user
+/// they belong to, which is committed by the caller.
+pub struct DeviceUseCaseDeps<'a, U> {
+    unit_of_work: &'a mut U,
+}
+
+impl<'a, U> DeviceUseCaseDeps<'a, U> {
+    pub fn new(unit_of_work: &'a mut U) -> Self {
+        DeviceUseCaseDeps { unit_of_work }
+    }
+}
+
+/// Dependencies of the use cases that record the logins of users, and notify them of the ones
+/// from new devices.
+///
+/// The use cases begin and commit their own units of work, so that nothing slow, like
+/// resolving locations or sending emails, happens within a transaction.
+pub struct DeviceLoginDeps<'a, F, G: ?Sized, S: ?Sized> {
+    transactions: F,
+    geo_resolver: &'a G,
+    sender: &'a S,
+    templates: &'a EmailTemplates,
+    /// Policies users have to accept before they can log in.
+    policies: &'a ConsentPolicies,
+}
+
+impl<'a, F, G: ?Sized, S: ?Sized> DeviceLoginDeps<'a, F, G, S> {
+    pub fn new(
+        transactions: F,
+        geo_resolver: &'a G,
+        sender: &'a S,
+        templates: &'a EmailTemplates,
+        policies: &'a ConsentPolicies,
+    ) -> Self {
+        DeviceLoginDeps {
+            transactions,
+            geo_resolver,
+            sender,
+            templates,
+            policies,
+        }
+    }
+}
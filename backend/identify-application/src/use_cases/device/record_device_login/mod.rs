@@ -0,0 +1,144 @@
+use std::net::IpAddr;
+
+use identify_domain::{Device, NewDeviceAttrs, User};
+use tracing::{instrument, trace, warn};
+use uuid::Uuid;
+
+use crate::{
+    EmailMessage, EmailSender, EmailTemplates, GeoResolver, Result,
+    UnitOfWork as _, UnitOfWorkFactory,
+    device_contracts::{
+        GetByFingerprint as _, Insert as _, ListByUser as _, Update as _,
+    },
+    use_cases::{consent::ensure_consented, device::DeviceLoginDeps},
+    user_contracts::Get as _,
+};
+
+/// Shown in place of the location of the addresses the resolver doesn't know.
+const UNKNOWN_LOCATION: &str = "Unknown";
+
+#[derive(Debug)]
+pub struct RecordDeviceLoginParams {
+    pub user_id: Uuid,
+    /// Identifies the device across logins. Derived from the user agent if it's not set.
+    pub fingerprint: Option<String>,
+    pub user_agent: String,
+    pub ip: IpAddr,
+}
+
+/// A login recorded by [record_device_login].
+#[derive(Debug)]
+pub struct DeviceLogin {
+    pub device: Device,
+    /// Whether the device wasn't known, or was revoked, before the login.
+    pub new_device: bool,
+}
+
+/// Records a login of a user along with the metadata of their device, and emails them if the
+/// device is new. The login is counted once a session is issued for it, see
+/// [issue_session](crate::issue_session).
+///
+/// Fails with [ConsentRequired](crate::ApplicationError::ConsentRequired), without recording
+/// anything, if the user hasn't accepted the current version of every policy.
+///
+/// Users aren't notified of the login from their very first device, since there's no device
+/// they could have expected instead. The login is recorded even if the location can't be
+/// resolved or the email can't be sent, so that an outage of either doesn't lock users out.
+///
+/// The location is resolved before the transaction begins, and the user is emailed only once
+/// it's committed.
+#[instrument(skip(deps))]
+pub async fn record_device_login<F, G, S>(
+    deps: DeviceLoginDeps<'_, F, G, S>,
+    params: RecordDeviceLoginParams,
+) -> Result<DeviceLogin>
+where
+    F: UnitOfWorkFactory,
+    G: GeoResolver + ?Sized,
+    S: EmailSender + ?Sized,
+{
+    trace!("Executing use case");
+
+    let RecordDeviceLoginParams {
+        user_id,
+        fingerprint,
+        user_agent,
+        ip,
+    } = params;
+
+    let DeviceLoginDeps {
+        transactions,
+        geo_resolver,
+        sender,
+        templates,
+        policies,
+    } = deps;
+
+    let location = geo_resolver.locate(ip).await.unwrap_or_else(|e| {
+        warn!(error = %e, "Failed to locate the IP address of a login");
+        None
+    });
+    let fingerprint =
+        fingerprint.unwrap_or_else(|| Device::fingerprint_of(&user_agent));
+
+    let mut uow = transactions.begin().await?;
+    let user = uow.users().get(user_id).await?;
+    user.ensure_not_erased()?;
+    ensure_consented(&mut uow, policies, user_id).await?;
+
+    let known = uow
+        .devices()
+        .get_by_fingerprint(user_id, &fingerprint)
+        .await?;
+    let (device, new_device, notify_user) = match known {
+        Some(mut device) => {
+            device.record_login(user_agent, ip, location)?;
+            uow.devices().update(&device).await?;
+            (device, false, false)
+        }
+        None => {
+            // There's no device the user could have expected instead of their first one.
+            let first_device =
+                uow.devices().list_by_user(user_id).await?.is_empty();
+            let device = Device::new(NewDeviceAttrs {
+                user_id,
+                fingerprint,
+                user_agent,
+                ip,
+                location,
+            })?;
+            uow.devices().insert(&device).await?;
+            (device, true, !first_device)
+        }
+    };
+    uow.commit().await?;
+
+    if notify_user {
+        notify(sender, templates, &user, &device).await;
+    }
+
+    Ok(DeviceLogin { device, new_device })
+}
+
+async fn notify<S: EmailSender + ?Sized>(
+    sender: &S,
+    templates: &EmailTemplates,
+    user: &User,
+    device: &Device,
+) {
+    let ip = device.ip().to_string();
+    let email = templates.render_for(
+        user,
+        &EmailMessage::NewDevice {
+            first_name: user.first_name(),
+            user_agent: device.user_agent(),
+            ip: &ip,
+            location: device.location().as_deref().unwrap_or(UNKNOWN_LOCATION),
+            time: *device.last_seen_at(),
+        },
+    );
+
+    if let Err(e) = sender.send(&email).await {
+        warn!(error = %e, device_id = %device.id(), "Failed to notify a user of a new device");
+    }
+}
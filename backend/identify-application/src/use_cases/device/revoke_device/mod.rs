@@ -0,0 +1,34 @@
+use identify_domain::Device;
+use tracing::{instrument, trace};
+use uuid::Uuid;
+
+use crate::{
+    Result, UnitOfWork,
+    device_contracts::{Get as _, Update as _},
+    use_cases::device::DeviceUseCaseDeps,
+};
+
+#[derive(Debug)]
+pub struct RevokeDeviceParams {
+    pub user_id: Uuid,
+    pub device_id: Uuid,
+}
+
+/// Stops trusting a device of a user, so that the next login from it counts as one from a new
+/// device. Fails if the device is already revoked.
+#[instrument(skip(deps))]
+pub async fn revoke_device<U: UnitOfWork>(
+    deps: DeviceUseCaseDeps<'_, U>,
+    params: RevokeDeviceParams,
+) -> Result<Device> {
+    trace!("Executing use case");
+
+    let RevokeDeviceParams { user_id, device_id } = params;
+
+    let uow = deps.unit_of_work;
+    let mut device = uow.devices().get(user_id, device_id).await?;
+    device.revoke()?;
+    uow.devices().update(&device).await?;
+
+    Ok(device)
+}
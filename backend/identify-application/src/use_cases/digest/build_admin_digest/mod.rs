@@ -0,0 +1,112 @@
+use chrono::{DateTime, Utc};
+use identify_domain::UserLifecycleTransition;
+use tracing::{instrument, trace};
+
+use crate::{
+    Result, UnitOfWork,
+    membership_contracts::CountNewAdmins as _,
+    operation_contracts::CountFailed as _,
+    organization_quota_contracts::CountReached as _,
+    use_cases::digest::{DigestCategory, DigestUseCaseDeps},
+    user_event_contracts::Count as _,
+    webhook_delivery_contracts::CountDead as _,
+};
+
+#[derive(Debug)]
+pub struct BuildAdminDigestParams {
+    /// Start of the summarized period, inclusive.
+    pub since: DateTime<Utc>,
+    /// End of the summarized period, exclusive.
+    pub until: DateTime<Utc>,
+    /// Categories the digest includes, the rest is left out.
+    pub categories: Vec<DigestCategory>,
+}
+
+/// Summary of the notable events of a period.
+#[derive(Debug, Clone)]
+pub struct AdminDigest {
+    pub since: DateTime<Utc>,
+    pub until: DateTime<Utc>,
+    /// Number of events of every included category, in the order the categories were
+    /// requested.
+    pub entries: Vec<(DigestCategory, u64)>,
+}
+
+impl AdminDigest {
+    /// Whether nothing notable happened in the period.
+    pub fn is_empty(&self) -> bool {
+        self.entries.iter().all(|(_, count)| *count == 0)
+    }
+}
+
+/// Summarizes the notable events of a period for admins.
+#[instrument(skip(deps))]
+pub async fn build_admin_digest<U: UnitOfWork>(
+    deps: DigestUseCaseDeps<'_, U>,
+    params: BuildAdminDigestParams,
+) -> Result<AdminDigest> {
+    trace!("Executing use case");
+
+    let DigestUseCaseDeps {
+        unit_of_work: uow,
+        quotas,
+    } = deps;
+
+    let BuildAdminDigestParams {
+        since,
+        until,
+        categories,
+    } = params;
+
+    let transitions =
+        uow.user_events().count_by_transition(since, until).await?;
+    let count_transition = |expected| {
+        transitions
+            .iter()
+            .find(|(transition, _)| *transition == expected)
+            .map_or(0, |(_, count)| *count)
+    };
+
+    let mut entries = Vec::with_capacity(categories.len());
+    for category in categories {
+        let count = match category {
+            DigestCategory::NewUsers => {
+                count_transition(UserLifecycleTransition::Created)
+            }
+            DigestCategory::Verifications => {
+                count_transition(UserLifecycleTransition::Verified)
+            }
+            DigestCategory::Lockouts => {
+                count_transition(UserLifecycleTransition::Suspended)
+            }
+            DigestCategory::Deletions => {
+                count_transition(UserLifecycleTransition::Deleted)
+            }
+            DigestCategory::NewAdmins => {
+                uow.memberships().count_new_admins(since, until).await?
+            }
+            DigestCategory::FailedWebhookDeliveries => {
+                uow.webhook_deliveries().count_dead(since, until).await?
+            }
+            DigestCategory::QuotaCrossings => {
+                uow.organization_quotas()
+                    .count_reached(
+                        quotas.max_users_per_organization(),
+                        since,
+                        until,
+                    )
+                    .await?
+            }
+            DigestCategory::FailedOperations => {
+                uow.operations().count_failed(since, until).await?
+            }
+        };
+        entries.push((category, count));
+    }
+
+    Ok(AdminDigest {
+        since,
+        until,
+        entries,
+    })
+}
@@ -0,0 +1,89 @@
+pub mod build_admin_digest;
+
+use std::{fmt, str::FromStr};
+
+use identify_domain::QuotaPolicy;
+
+/// Dependencies of the use cases that summarize what happened in the system.
+///
+/// Events and operations are read through a single [UnitOfWork](crate::UnitOfWork).
+pub struct DigestUseCaseDeps<'a, U> {
+    unit_of_work: &'a mut U,
+    /// Limits organizations without a quota of their own, to tell when they reach it.
+    quotas: &'a QuotaPolicy,
+}
+
+impl<'a, U> DigestUseCaseDeps<'a, U> {
+    pub fn new(unit_of_work: &'a mut U, quotas: &'a QuotaPolicy) -> Self {
+        DigestUseCaseDeps {
+            unit_of_work,
+            quotas,
+        }
+    }
+}
+
+/// A kind of notable events summarized in admin digests.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DigestCategory {
+    /// Users that were created.
+    NewUsers,
+    /// Users that verified their email.
+    Verifications,
+    /// Users that were suspended, which locks them out.
+    Lockouts,
+    /// Users that were deleted.
+    Deletions,
+    /// Users that became admins or owners of organizations.
+    NewAdmins,
+    /// Webhook deliveries that gave up after every attempt failed.
+    FailedWebhookDeliveries,
+    /// Organizations that reached the maximum number of users of their quota.
+    QuotaCrossings,
+    /// Long-running operations that failed.
+    FailedOperations,
+}
+
+impl DigestCategory {
+    pub const ALL: [DigestCategory; 8] = [
+        DigestCategory::NewUsers,
+        DigestCategory::Verifications,
+        DigestCategory::Lockouts,
+        DigestCategory::Deletions,
+        DigestCategory::NewAdmins,
+        DigestCategory::FailedWebhookDeliveries,
+        DigestCategory::QuotaCrossings,
+        DigestCategory::FailedOperations,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DigestCategory::NewUsers => "new_users",
+            DigestCategory::Verifications => "verifications",
+            DigestCategory::Lockouts => "lockouts",
+            DigestCategory::Deletions => "deletions",
+            DigestCategory::NewAdmins => "new_admins",
+            DigestCategory::FailedWebhookDeliveries => {
+                "failed_webhook_deliveries"
+            }
+            DigestCategory::QuotaCrossings => "quota_crossings",
+            DigestCategory::FailedOperations => "failed_operations",
+        }
+    }
+}
+
+impl fmt::Display for DigestCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for DigestCategory {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        DigestCategory::ALL
+            .into_iter()
+            .find(|category| category.as_str() == s)
+            .ok_or_else(|| format!("unknown digest category '{s}'"))
+    }
+}
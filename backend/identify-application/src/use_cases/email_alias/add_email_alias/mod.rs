@@ -0,0 +1,59 @@
+use identify_domain::{EmailAlias, EmailAliasKind, NewEmailAliasAttrs};
+use tracing::{instrument, trace};
+use uuid::Uuid;
+
+use crate::{
+    ApplicationError, Result, UnitOfWork,
+    email_alias_contracts::Insert as _,
+    use_cases::email_alias::EmailAliasUseCaseDeps,
+    user_contracts::{Get as _, GetByEmail as _},
+};
+
+#[derive(Debug)]
+pub struct AddEmailAliasParams {
+    pub user_id: Uuid,
+    pub email: String,
+}
+
+/// Adds an email the user can also be found by, e.g. a tagged address.
+///
+/// Fails if the email already identifies anyone, the user included, or if the user has been
+/// erased.
+#[instrument(skip(deps))]
+pub async fn add_email_alias<U: UnitOfWork>(
+    deps: EmailAliasUseCaseDeps<'_, U>,
+    params: AddEmailAliasParams,
+) -> Result<EmailAlias> {
+    trace!("Executing use case");
+
+    let AddEmailAliasParams { user_id, email } = params;
+
+    let uow = deps.unit_of_work;
+    let user = uow.users().get(user_id).await?;
+    user.ensure_not_erased()?;
+
+    let alias = EmailAlias::new(NewEmailAliasAttrs {
+        user_id,
+        email,
+        kind: EmailAliasKind::Added,
+    })?;
+    match uow.users().get_by_email(alias.email()).await {
+        Ok(found) if found.id() == user_id => {
+            return Err(ApplicationError::entity_already_exists(
+                "EmailAlias",
+                "the email already identifies the user",
+            ));
+        }
+        Ok(_) => {
+            return Err(ApplicationError::entity_already_exists(
+                "User",
+                "Email is already taken",
+            ));
+        }
+        Err(ApplicationError::EntityNotFound { .. }) => {}
+        Err(e) => return Err(e),
+    }
+    uow.email_aliases().insert(&alias).await?;
+
+    Ok(alias)
+}
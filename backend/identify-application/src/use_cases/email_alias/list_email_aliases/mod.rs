@@ -0,0 +1,28 @@
+use identify_domain::EmailAlias;
+use tracing::{instrument, trace};
+use uuid::Uuid;
+
+use crate::{
+    Result, UnitOfWork, email_alias_contracts::ListByUser as _,
+    use_cases::email_alias::EmailAliasUseCaseDeps, user_contracts::Get as _,
+};
+
+#[derive(Debug)]
+pub struct ListEmailAliasesParams {
+    pub user_id: Uuid,
+}
+
+/// Returns all the aliases of a user, the oldest first. Fails if the user doesn't exist.
+#[instrument(skip(deps))]
+pub async fn list_email_aliases<U: UnitOfWork>(
+    deps: EmailAliasUseCaseDeps<'_, U>,
+    params: ListEmailAliasesParams,
+) -> Result<Vec<EmailAlias>> {
+    trace!("Executing use case");
+
+    let uow = deps.unit_of_work;
+
+    uow.users().get(params.user_id).await?;
+
+    uow.email_aliases().list_by_user(params.user_id).await
+}
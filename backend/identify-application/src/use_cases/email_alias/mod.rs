@@ -0,0 +1,17 @@
+pub mod add_email_alias;
+pub mod list_email_aliases;
+pub mod remove_email_alias;
+
+/// Dependencies of the use cases that manage the emails users can also be found by.
+///
+/// Aliases are read and written in a [UnitOfWork](crate::UnitOfWork) together with the user
+/// they belong to, which is committed by the caller.
+pub struct EmailAliasUseCaseDeps<'a, U> {
+    unit_of_work: &'a mut U,
+}
+
+impl<'a, U> EmailAliasUseCaseDeps<'a, U> {
+    pub fn new(unit_of_work: &'a mut U) -> Self {
+        EmailAliasUseCaseDeps { unit_of_work }
+    }
+}
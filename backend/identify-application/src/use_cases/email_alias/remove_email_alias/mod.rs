@@ -0,0 +1,30 @@
+use tracing::{instrument, trace};
+use uuid::Uuid;
+
+use crate::{
+    Result, UnitOfWork, email_alias_contracts::Delete as _,
+    use_cases::email_alias::EmailAliasUseCaseDeps,
+};
+
+#[derive(Debug)]
+pub struct RemoveEmailAliasParams {
+    pub user_id: Uuid,
+    pub alias_id: Uuid,
+}
+
+/// Removes an alias of a user, who can no longer be found by it, and anyone can take the email
+/// again. Fails if the user has no such alias.
+#[instrument(skip(deps))]
+pub async fn remove_email_alias<U: UnitOfWork>(
+    deps: EmailAliasUseCaseDeps<'_, U>,
+    params: RemoveEmailAliasParams,
+) -> Result<()> {
+    trace!("Executing use case");
+
+    let RemoveEmailAliasParams { user_id, alias_id } = params;
+
+    deps.unit_of_work
+        .email_aliases()
+        .delete(user_id, alias_id)
+        .await
+}
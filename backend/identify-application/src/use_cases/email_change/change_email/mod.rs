@@ -0,0 +1,90 @@
+use identify_domain::{
+    EmailAlias, EmailAliasKind, NewEmailAliasAttrs, User,
+    UserLifecycleTransition,
+};
+use tracing::{instrument, trace};
+use uuid::Uuid;
+
+use crate::{
+    ApplicationError, Result, TokenGenerator, UnitOfWork,
+    email_alias_contracts::{Delete as _, Insert as _, ListByUser as _},
+    email_change_contracts::{Get as _, Update as _},
+    use_cases::{
+        email_change::{EmailChangeDeps, ensure_email_available},
+        user::emit_user_event,
+    },
+    user_contracts::{ChangeEmail as _, Get as _},
+};
+
+#[derive(Debug)]
+pub struct ChangeEmailParams {
+    /// ID of the change the user requested.
+    pub id: Uuid,
+    /// Token the new address was emailed.
+    pub token: String,
+}
+
+/// Changes the email of a user to the address they requested, with the token that address was
+/// emailed, and emits the event announcing it.
+///
+/// The user keeps their ID, which still identifies them by the email they were created with,
+/// so nobody else can sign up with it. The email they had before becomes an
+/// [alias](EmailAlias) they can still be found by, unless it's that original email, and an
+/// alias of theirs they change to stops being one. A change whose token doesn't match is reported as not
+/// found, so that the IDs of changes can't be probed. Fails if the change is already confirmed
+/// or has expired, if the address has been taken in the meantime, or if the user has been
+/// erased.
+#[instrument(skip(deps))]
+pub async fn change_email<U, T>(
+    deps: EmailChangeDeps<'_, U, T>,
+    params: ChangeEmailParams,
+) -> Result<User>
+where
+    U: UnitOfWork,
+    T: TokenGenerator + ?Sized,
+{
+    trace!("Executing use case");
+
+    let ChangeEmailParams { id, token } = params;
+
+    let EmailChangeDeps {
+        unit_of_work: uow,
+        tokens,
+    } = deps;
+    let mut change = uow.email_changes().get(id).await?;
+    if tokens.hash(&token) != change.token_hash() {
+        return Err(ApplicationError::entity_not_found("EmailChange", id));
+    }
+    let mut user = uow.users().get(change.user_id()).await?;
+    user.ensure_not_erased()?;
+
+    change.confirm()?;
+    ensure_email_available(uow, &user, change.email()).await?;
+    let previous = user.email().to_owned();
+    user.change_email(change.email().to_owned())?;
+    uow.users().change_email(&user).await?;
+
+    let current_id = EmailAlias::id_of(user.email());
+    let aliases = uow.email_aliases().list_by_user(user.id()).await?;
+    if aliases.iter().any(|alias| alias.id() == current_id) {
+        uow.email_aliases().delete(user.id(), current_id).await?;
+    }
+    if EmailAlias::id_of(&previous) != user.id() {
+        let alias = EmailAlias::new(NewEmailAliasAttrs {
+            user_id: user.id(),
+            email: previous,
+            kind: EmailAliasKind::Previous,
+        })?;
+        uow.email_aliases().insert(&alias).await?;
+    }
+    uow.email_changes().update(&change).await?;
+    emit_user_event(
+        &mut uow.user_events(),
+        user.id(),
+        UserLifecycleTransition::Updated,
+        user.version(),
+    )
+    .await?;
+
+    Ok(user)
+}
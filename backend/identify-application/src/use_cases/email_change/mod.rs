@@ -0,0 +1,77 @@
+pub mod change_email;
+pub mod request_email_change;
+
+use identify_domain::{DomainError, User};
+
+use crate::{
+    ApplicationError, EmailTemplates, Result, UnitOfWork,
+    user_contracts::GetByEmail as _,
+};
+
+/// Dependencies of the use cases that email links to the addresses users change theirs to.
+pub struct EmailChangeDeliveryDeps<'a, U, T: ?Sized, S: ?Sized> {
+    unit_of_work: &'a mut U,
+    tokens: &'a T,
+    sender: &'a S,
+    templates: &'a EmailTemplates,
+}
+
+impl<'a, U, T: ?Sized, S: ?Sized> EmailChangeDeliveryDeps<'a, U, T, S> {
+    pub fn new(
+        unit_of_work: &'a mut U,
+        tokens: &'a T,
+        sender: &'a S,
+        templates: &'a EmailTemplates,
+    ) -> Self {
+        EmailChangeDeliveryDeps {
+            unit_of_work,
+            tokens,
+            sender,
+            templates,
+        }
+    }
+}
+
+/// Dependencies of the use cases that change emails with the tokens users were sent.
+pub struct EmailChangeDeps<'a, U, T: ?Sized> {
+    unit_of_work: &'a mut U,
+    tokens: &'a T,
+}
+
+impl<'a, U, T: ?Sized> EmailChangeDeps<'a, U, T> {
+    pub fn new(unit_of_work: &'a mut U, tokens: &'a T) -> Self {
+        EmailChangeDeps {
+            unit_of_work,
+            tokens,
+        }
+    }
+}
+
+/// Fails unless the user can change their email to the address, i.e. unless it identifies
+/// nobody but them and isn't their current email already.
+///
+/// Users are identified by the emails they were created with for good, so the original email
+/// of a user who changed it can be taken back by them only.
+async fn ensure_email_available<U: UnitOfWork>(
+    uow: &mut U,
+    user: &User,
+    email: &str,
+) -> Result<()> {
+    match uow.users().get_by_email(email).await {
+        Ok(owner) if owner.id() != user.id() => {
+            Err(ApplicationError::entity_already_exists(
+                "User",
+                "Email is already taken",
+            ))
+        }
+        Ok(owner) if owner.email().eq_ignore_ascii_case(email.trim()) => {
+            Err(DomainError::validation(
+                "EmailChange",
+                "the email is the current one of the user",
+            )
+            .into())
+        }
+        Ok(_) | Err(ApplicationError::EntityNotFound { .. }) => Ok(()),
+        Err(e) => Err(e),
+    }
+}
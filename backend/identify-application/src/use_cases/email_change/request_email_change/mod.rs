@@ -0,0 +1,89 @@
+use chrono::{TimeDelta, Utc};
+use identify_domain::{EmailChange, NewEmailChangeAttrs};
+use tracing::{instrument, trace};
+use uuid::Uuid;
+
+use crate::{
+    EmailMessage, EmailSender, Result, TokenGenerator, UnitOfWork,
+    email_change_contracts::Insert as _,
+    use_cases::email_change::{
+        EmailChangeDeliveryDeps, ensure_email_available,
+    },
+    user_contracts::Get as _,
+};
+
+#[derive(Debug)]
+pub struct RequestEmailChangeParams {
+    pub user_id: Uuid,
+    /// Email the user changes theirs to.
+    pub email: String,
+    /// URL of the page the user confirms the change on. The ID of the change and the token are
+    /// appended as the `change` and `token` query parameters.
+    pub confirm_url: String,
+    /// How long the user has to confirm the change.
+    pub valid_for: TimeDelta,
+}
+
+/// Starts changing the email of a user: emails a link to the new address, which the user
+/// confirms the change with, see [change_email](crate::change_email).
+///
+/// The email of the user stays the same until then. Fails if the address already identifies
+/// another user, if it's the current email of the user, or if the user has been erased. If the
+/// email can't be sent, the change isn't created either.
+#[instrument(skip(deps))]
+pub async fn request_email_change<U, T, S>(
+    deps: EmailChangeDeliveryDeps<'_, U, T, S>,
+    params: RequestEmailChangeParams,
+) -> Result<EmailChange>
+where
+    U: UnitOfWork,
+    T: TokenGenerator + ?Sized,
+    S: EmailSender + ?Sized,
+{
+    trace!("Executing use case");
+
+    let RequestEmailChangeParams {
+        user_id,
+        email,
+        confirm_url,
+        valid_for,
+    } = params;
+
+    let EmailChangeDeliveryDeps {
+        unit_of_work: uow,
+        tokens,
+        sender,
+        templates,
+    } = deps;
+    let user = uow.users().get(user_id).await?;
+    user.ensure_not_erased()?;
+
+    let token = tokens.generate();
+    let change = EmailChange::new(NewEmailChangeAttrs {
+        user_id,
+        email,
+        token_hash: tokens.hash(&token),
+        expires_at: Utc::now() + valid_for,
+    })?;
+    ensure_email_available(uow, &user, change.email()).await?;
+    uow.email_changes().insert(&change).await?;
+
+    let separator = if confirm_url.contains('?') { '&' } else { '?' };
+    let link = format!(
+        "{confirm_url}{separator}change={}&token={token}",
+        change.id()
+    );
+    // The link proves the user owns the new address, so it's sent there.
+    let email = templates.render_for_at(
+        &user,
+        change.email(),
+        &EmailMessage::EmailChange {
+            first_name: user.first_name(),
+            email: change.email(),
+            link: &link,
+        },
+    );
+    sender.send(&email).await?;
+
+    Ok(change)
+}
@@ -0,0 +1,12 @@
+pub mod tail_user_events;
+
+/// Dependencies of the use cases that feed the recorded user events to subscribers.
+pub struct EventFeedUseCaseDeps<'a, R> {
+    repository: &'a mut R,
+}
+
+impl<'a, R> EventFeedUseCaseDeps<'a, R> {
+    pub fn new(repository: &'a mut R) -> Self {
+        EventFeedUseCaseDeps { repository }
+    }
+}
@@ -0,0 +1,52 @@
+use tracing::{instrument, trace};
+
+use crate::{
+    Result,
+    use_cases::event_feed::EventFeedUseCaseDeps,
+    user_event_contracts::{self, RecordedUserEvent},
+};
+
+#[derive(Debug)]
+pub struct TailUserEventsParams {
+    /// Position of the last event the caller has seen, or `None` to start with the events
+    /// recorded from now on.
+    pub after: Option<u64>,
+    /// Most events returned at once.
+    pub limit: u32,
+}
+
+/// Events recorded after the position the caller has seen.
+#[derive(Debug)]
+pub struct UserEventTail {
+    /// The events, the oldest first.
+    pub events: Vec<RecordedUserEvent>,
+    /// Position to continue from, i.e. of the last returned event, or the position passed in if
+    /// there weren't any new events.
+    pub sequence: u64,
+}
+
+/// Returns the events recorded after the position the caller has seen.
+///
+/// Callers that haven't seen any events yet start at the newest one, so that they don't
+/// receive the whole history of the outbox.
+#[instrument(skip(deps))]
+pub async fn tail_user_events<
+    R: user_event_contracts::ListAfter + user_event_contracts::LastSequence,
+>(
+    deps: EventFeedUseCaseDeps<'_, R>,
+    params: TailUserEventsParams,
+) -> Result<UserEventTail> {
+    trace!("Executing use case");
+
+    let Some(after) = params.after else {
+        return Ok(UserEventTail {
+            events: Vec::new(),
+            sequence: deps.repository.last_sequence().await?,
+        });
+    };
+
+    let events = deps.repository.list_after(after, params.limit).await?;
+    let sequence = events.last().map_or(after, |event| event.sequence);
+
+    Ok(UserEventTail { events, sequence })
+}
@@ -0,0 +1,111 @@
+use std::sync::Arc;
+
+use identify_domain::{
+    IdentifierPolicy, Invitation, Membership, NewMembershipAttrs, NewUserAttrs,
+    QuotaPolicy, User, UsernameStrategy,
+};
+use tracing::{instrument, trace};
+use uuid::Uuid;
+
+use crate::{
+    ApplicationError, CreateUser, CreateUserParams, Result, System,
+    TokenGenerator, TransactionalUseCase, UnitOfWork,
+    invitation_contracts::{Get as _, Update as _},
+    membership_contracts::Insert as _,
+    use_cases::invitation::InvitationAcceptanceDeps,
+};
+
+#[derive(Debug)]
+pub struct AcceptInvitationParams {
+    pub id: Uuid,
+    /// Token the invitee was emailed.
+    pub token: String,
+    pub first_name: String,
+    pub last_name: Option<String>,
+    /// Username the invitee asked for. One is generated with the strategy if it's not set.
+    pub username: Option<String>,
+    pub username_strategy: UsernameStrategy,
+    /// Decides which usernames can be chosen.
+    pub identifier_policy: Arc<IdentifierPolicy>,
+    /// Limits how many users the organization can have.
+    pub quota_policy: QuotaPolicy,
+}
+
+/// An invitation accepted by [accept_invitation], along with the user it created.
+#[derive(Debug)]
+pub struct AcceptedInvitation {
+    pub invitation: Invitation,
+    pub user: User,
+}
+
+/// Accepts an invitation with the token the invitee was emailed, creating their user with the
+/// email the invitation was sent to and making them a member of the organization.
+///
+/// An invitation whose token doesn't match is reported as not found, so that the IDs of
+/// invitations can't be probed. Fails if the invitation is already accepted or has expired,
+/// and with [QuotaExceeded](ApplicationError::QuotaExceeded) if the organization can't have
+/// any more users.
+#[instrument(skip(deps))]
+pub async fn accept_invitation<U, T>(
+    deps: InvitationAcceptanceDeps<'_, U, T>,
+    params: AcceptInvitationParams,
+) -> Result<AcceptedInvitation>
+where
+    U: UnitOfWork,
+    T: TokenGenerator + ?Sized,
+{
+    trace!("Executing use case");
+
+    let AcceptInvitationParams {
+        id,
+        token,
+        first_name,
+        last_name,
+        username,
+        username_strategy,
+        identifier_policy,
+        quota_policy,
+    } = params;
+
+    let InvitationAcceptanceDeps {
+        unit_of_work: uow,
+        tokens,
+    } = deps;
+    let mut invitation = uow.invitations().get(id).await?;
+    if tokens.hash(&token) != invitation.token_hash() {
+        return Err(ApplicationError::entity_not_found("Invitation", id));
+    }
+    // Checked before the user is created, so that an invitation that can't be accepted
+    // doesn't fail with a confusing error about the user.
+    invitation.ensure_pending()?;
+
+    let user = CreateUser
+        .execute(
+            uow,
+            &System,
+            CreateUserParams {
+                user_attrs: NewUserAttrs {
+                    email: invitation.email().to_owned(),
+                    first_name,
+                    last_name,
+                },
+                username,
+                username_strategy,
+                identifier_policy,
+                organization_id: Some(invitation.organization_id()),
+                quota_policy,
+            },
+        )
+        .await?;
+    let membership = Membership::new(NewMembershipAttrs {
+        organization_id: invitation.organization_id(),
+        user_id: user.id(),
+        role: invitation.role(),
+    });
+    uow.memberships().insert(&membership).await?;
+
+    invitation.accept(user.id())?;
+    uow.invitations().update(&invitation).await?;
+
+    Ok(AcceptedInvitation { invitation, user })
+}
@@ -0,0 +1,131 @@
+use chrono::{TimeDelta, Utc};
+use identify_domain::{
+    DomainError, Invitation, MembershipRole, NewInvitationAttrs,
+};
+use tracing::{instrument, trace};
+use uuid::Uuid;
+
+use crate::{
+    ApplicationError, EmailMessage, EmailSender, Result, TokenGenerator,
+    UnitOfWork,
+    invitation_contracts::Insert as _,
+    membership_contracts::Get as _,
+    organization_contracts::Get as _,
+    use_cases::invitation::InvitationDeliveryDeps,
+    user_contracts::{Exists as _, Get as _, UserSpec},
+};
+
+#[derive(Debug)]
+pub struct CreateInvitationParams {
+    /// Email of the invitee.
+    pub email: String,
+    /// ID of the user who invites them, who must be an owner or an admin of the organization.
+    pub inviter_id: Uuid,
+    pub organization_id: Uuid,
+    /// Role the invitee gets once they accept, which can't be [MembershipRole::Owner].
+    pub role: MembershipRole,
+    /// URL of the page the invitee accepts the invitation on. The ID of the invitation and the
+    /// token are appended as the `invitation` and `token` query parameters.
+    pub accept_url: String,
+    /// How long the invitation can be accepted for.
+    pub valid_for: TimeDelta,
+}
+
+/// Invites someone to join an organization, and emails them the link to accept the invitation
+/// with.
+///
+/// Fails if the invitee already has an account, since they can be added to the organization
+/// directly. If the email can't be sent, the invitation isn't created either, so that the
+/// caller can simply try again.
+#[instrument(skip(deps))]
+pub async fn create_invitation<U, T, S>(
+    deps: InvitationDeliveryDeps<'_, U, T, S>,
+    params: CreateInvitationParams,
+) -> Result<Invitation>
+where
+    U: UnitOfWork,
+    T: TokenGenerator + ?Sized,
+    S: EmailSender + ?Sized,
+{
+    trace!("Executing use case");
+
+    let CreateInvitationParams {
+        email,
+        inviter_id,
+        organization_id,
+        role,
+        accept_url,
+        valid_for,
+    } = params;
+
+    let InvitationDeliveryDeps {
+        unit_of_work: uow,
+        tokens,
+        sender,
+        templates,
+    } = deps;
+    let organization = uow.organizations().get(organization_id).await?;
+    let inviter = uow.users().get(inviter_id).await?;
+    inviter.ensure_not_erased()?;
+    let inviter_role =
+        match uow.memberships().get(organization_id, inviter_id).await {
+            Ok(membership) => Some(membership.role()),
+            Err(ApplicationError::EntityNotFound { .. }) => None,
+            Err(e) => return Err(e),
+        };
+    if !matches!(
+        inviter_role,
+        Some(MembershipRole::Owner | MembershipRole::Admin)
+    ) {
+        return Err(DomainError::validation(
+            "Invitation",
+            "the inviter must be an owner or an admin of the organization",
+        )
+        .into());
+    }
+
+    if uow
+        .users()
+        .exists(&UserSpec::by_email(email.as_str()))
+        .await?
+    {
+        return Err(ApplicationError::entity_already_exists(
+            "Invitation",
+            "a user with the email already exists",
+        ));
+    }
+
+    let token = tokens.generate();
+    let invitation = Invitation::new(NewInvitationAttrs {
+        email,
+        inviter_id,
+        organization_id,
+        role,
+        token_hash: tokens.hash(&token),
+        expires_at: Utc::now() + valid_for,
+    })?;
+    uow.invitations().insert(&invitation).await?;
+
+    let separator = if accept_url.contains('?') { '&' } else { '?' };
+    let link = format!(
+        "{accept_url}{separator}invitation={}&token={token}",
+        invitation.id()
+    );
+    let inviter_name = match inviter.last_name() {
+        Some(last_name) => format!("{} {last_name}", inviter.first_name()),
+        None => inviter.first_name().clone(),
+    };
+    let email = templates.render(
+        invitation.email(),
+        &EmailMessage::Invitation {
+            inviter: &inviter_name,
+            organization: organization.name(),
+            role: invitation.role().as_str(),
+            link: &link,
+            expires_at: *invitation.expires_at(),
+        },
+    );
+    sender.send(&email).await?;
+
+    Ok(invitation)
+}
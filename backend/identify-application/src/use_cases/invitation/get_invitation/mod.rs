@@ -0,0 +1,27 @@
+use identify_domain::Invitation;
+use tracing::{instrument, trace};
+use uuid::Uuid;
+
+use crate::{
+    Result, UnitOfWork, invitation_contracts::Get as _,
+    use_cases::invitation::InvitationUseCaseDeps,
+};
+
+#[derive(Debug)]
+pub struct GetInvitationParams {
+    pub id: Uuid,
+}
+
+/// Returns an invitation, e.g. so that the page it links to can show who sent it before it's
+/// accepted.
+#[instrument(skip(deps))]
+pub async fn get_invitation<U: UnitOfWork>(
+    deps: InvitationUseCaseDeps<'_, U>,
+    params: GetInvitationParams,
+) -> Result<Invitation> {
+    trace!("Executing use case");
+
+    let InvitationUseCaseDeps { unit_of_work: uow } = deps;
+
+    uow.invitations().get(params.id).await
+}
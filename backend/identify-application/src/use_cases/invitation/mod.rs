@@ -0,0 +1,58 @@
+use crate::EmailTemplates;
+
+pub mod accept_invitation;
+pub mod create_invitation;
+pub mod get_invitation;
+
+/// Dependencies of the use cases that query invitations.
+///
+/// Invitations are read and written in a [UnitOfWork](crate::UnitOfWork), which is committed by
+/// the caller.
+pub struct InvitationUseCaseDeps<'a, U> {
+    unit_of_work: &'a mut U,
+}
+
+impl<'a, U> InvitationUseCaseDeps<'a, U> {
+    pub fn new(unit_of_work: &'a mut U) -> Self {
+        InvitationUseCaseDeps { unit_of_work }
+    }
+}
+
+/// Dependencies of the use cases that accept invitations with the tokens they were sent with.
+pub struct InvitationAcceptanceDeps<'a, U, T: ?Sized> {
+    unit_of_work: &'a mut U,
+    tokens: &'a T,
+}
+
+impl<'a, U, T: ?Sized> InvitationAcceptanceDeps<'a, U, T> {
+    pub fn new(unit_of_work: &'a mut U, tokens: &'a T) -> Self {
+        InvitationAcceptanceDeps {
+            unit_of_work,
+            tokens,
+        }
+    }
+}
+
+/// Dependencies of the use cases that create invitations and email them to the invitees.
+pub struct InvitationDeliveryDeps<'a, U, T: ?Sized, S: ?Sized> {
+    unit_of_work: &'a mut U,
+    tokens: &'a T,
+    sender: &'a S,
+    templates: &'a EmailTemplates,
+}
+
+impl<'a, U, T: ?Sized, S: ?Sized> InvitationDeliveryDeps<'a, U, T, S> {
+    pub fn new(
+        unit_of_work: &'a mut U,
+        tokens: &'a T,
+        sender: &'a S,
+        templates: &'a EmailTemplates,
+    ) -> Self {
+        InvitationDeliveryDeps {
+            unit_of_work,
+            tokens,
+            sender,
+            templates,
+        }
+    }
+}
@@ -0,0 +1,14 @@
+pub mod prune_signing_keys;
+pub mod prune_user_events;
+pub mod prune_webhook_deliveries;
+
+/// Dependencies of the use cases that clean up the data the system doesn't need anymore.
+pub struct MaintenanceUseCaseDeps<'a, U> {
+    unit_of_work: &'a mut U,
+}
+
+impl<'a, U> MaintenanceUseCaseDeps<'a, U> {
+    pub fn new(unit_of_work: &'a mut U) -> Self {
+        MaintenanceUseCaseDeps { unit_of_work }
+    }
+}
@@ -0,0 +1,30 @@
+use chrono::{DateTime, Utc};
+use tracing::{instrument, trace};
+
+use crate::{
+    Result, UnitOfWork, signing_key_contracts::PruneRetired as _,
+    use_cases::maintenance::MaintenanceUseCaseDeps,
+};
+
+#[derive(Debug)]
+pub struct PruneSigningKeysParams {
+    /// Keys that were retired before this are deleted.
+    pub before: DateTime<Utc>,
+}
+
+/// Deletes the signing keys that were retired long enough ago for the tokens they signed to
+/// have expired, so that they aren't published anymore.
+///
+/// Returns the number of deleted keys.
+#[instrument(skip(deps))]
+pub async fn prune_signing_keys<U: UnitOfWork>(
+    deps: MaintenanceUseCaseDeps<'_, U>,
+    params: PruneSigningKeysParams,
+) -> Result<u64> {
+    trace!("Executing use case");
+
+    deps.unit_of_work
+        .signing_keys()
+        .prune_retired(params.before)
+        .await
+}
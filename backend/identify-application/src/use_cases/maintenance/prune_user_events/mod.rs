@@ -0,0 +1,30 @@
+use chrono::{DateTime, Utc};
+use tracing::{instrument, trace};
+
+use crate::{
+    Result, UnitOfWork, use_cases::maintenance::MaintenanceUseCaseDeps,
+    user_event_contracts::PruneHandled as _,
+};
+
+#[derive(Debug)]
+pub struct PruneUserEventsParams {
+    /// Events that occurred before this are deleted once they are handled.
+    pub before: DateTime<Utc>,
+}
+
+/// Deletes the user events that have been both published and projected into the read models.
+///
+/// Admin digests count the events of their periods, so the events must be kept for longer than
+/// a period. Returns the number of deleted events.
+#[instrument(skip(deps))]
+pub async fn prune_user_events<U: UnitOfWork>(
+    deps: MaintenanceUseCaseDeps<'_, U>,
+    params: PruneUserEventsParams,
+) -> Result<u64> {
+    trace!("Executing use case");
+
+    deps.unit_of_work
+        .user_events()
+        .prune_handled(params.before)
+        .await
+}
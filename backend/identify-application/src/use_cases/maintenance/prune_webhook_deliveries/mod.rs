@@ -0,0 +1,30 @@
+use chrono::{DateTime, Utc};
+use tracing::{instrument, trace};
+
+use crate::{
+    Result, UnitOfWork, use_cases::maintenance::MaintenanceUseCaseDeps,
+    webhook_delivery_contracts::PruneDelivered as _,
+};
+
+#[derive(Debug)]
+pub struct PruneWebhookDeliveriesParams {
+    /// Deliveries that were delivered before this are deleted.
+    pub before: DateTime<Utc>,
+}
+
+/// Deletes the webhook deliveries that have been delivered. Pending deliveries and dead letters
+/// are kept, so that the latter can still be redelivered.
+///
+/// Returns the number of deleted deliveries.
+#[instrument(skip(deps))]
+pub async fn prune_webhook_deliveries<U: UnitOfWork>(
+    deps: MaintenanceUseCaseDeps<'_, U>,
+    params: PruneWebhookDeliveriesParams,
+) -> Result<u64> {
+    trace!("Executing use case");
+
+    deps.unit_of_work
+        .webhook_deliveries()
+        .prune_delivered(params.before)
+        .await
+}
@@ -1,5 +1,235 @@
+mod avatar;
+mod consent;
+mod device;
+mod digest;
+mod email_alias;
+mod email_change;
+mod event_feed;
+mod invitation;
+mod maintenance;
+mod operation;
+mod organization;
+mod phone_verification;
+mod read_model;
+mod registration;
+mod reverification;
+mod role;
+mod session;
+mod signing_key;
 mod user;
+mod user_attribute;
+mod webhook;
+pub use avatar::{
+    AvatarUseCaseDeps, avatar_key,
+    get_avatar::{GetAvatarParams, get_avatar},
+    upload_avatar::{UploadAvatarParams, upload_avatar},
+};
+pub use consent::{
+    ConsentUseCaseDeps,
+    accept_policy::{AcceptPolicyParams, AcceptedPolicy, accept_policy},
+    list_consents::{ListConsentsParams, UserConsents, list_consents},
+};
+pub use device::{
+    DeviceLoginDeps, DeviceUseCaseDeps,
+    list_devices::{ListDevicesParams, list_devices},
+    record_device_login::{
+        DeviceLogin, RecordDeviceLoginParams, record_device_login,
+    },
+    revoke_device::{RevokeDeviceParams, revoke_device},
+};
+pub use digest::{
+    DigestCategory, DigestUseCaseDeps,
+    build_admin_digest::{
+        AdminDigest, BuildAdminDigestParams, build_admin_digest,
+    },
+};
+pub use email_alias::{
+    EmailAliasUseCaseDeps,
+    add_email_alias::{AddEmailAliasParams, add_email_alias},
+    list_email_aliases::{ListEmailAliasesParams, list_email_aliases},
+    remove_email_alias::{RemoveEmailAliasParams, remove_email_alias},
+};
+pub use email_change::{
+    EmailChangeDeliveryDeps, EmailChangeDeps,
+    change_email::{ChangeEmailParams, change_email},
+    request_email_change::{RequestEmailChangeParams, request_email_change},
+};
+pub use event_feed::{
+    EventFeedUseCaseDeps,
+    tail_user_events::{TailUserEventsParams, UserEventTail, tail_user_events},
+};
+pub use invitation::{
+    InvitationAcceptanceDeps, InvitationDeliveryDeps, InvitationUseCaseDeps,
+    accept_invitation::{
+        AcceptInvitationParams, AcceptedInvitation, accept_invitation,
+    },
+    create_invitation::{CreateInvitationParams, create_invitation},
+    get_invitation::{GetInvitationParams, get_invitation},
+};
+pub use maintenance::{
+    MaintenanceUseCaseDeps,
+    prune_signing_keys::{PruneSigningKeysParams, prune_signing_keys},
+    prune_user_events::{PruneUserEventsParams, prune_user_events},
+    prune_webhook_deliveries::{
+        PruneWebhookDeliveriesParams, prune_webhook_deliveries,
+    },
+};
+pub use operation::{
+    OperationUseCaseDeps,
+    advance_operation::{
+        AdvanceOperationParams, OperationTransition, advance_operation,
+    },
+    cancel_operation::{CancelOperationParams, cancel_operation},
+    get_operation::{GetOperationParams, get_operation},
+    start_operation::{StartOperationParams, start_operation},
+};
+pub use organization::{
+    OrganizationQuotaUsage, OrganizationUseCaseDeps,
+    add_organization_member::{
+        AddOrganizationMemberParams, add_organization_member,
+    },
+    change_member_role::{ChangeMemberRoleParams, change_member_role},
+    get_organization_by_external_id::{
+        GetOrganizationByExternalIdParams, get_organization_by_external_id,
+    },
+    get_organization_by_slug::{
+        GetOrganizationBySlugParams, get_organization_by_slug,
+    },
+    get_organization_quota::{
+        GetOrganizationQuotaParams, get_organization_quota,
+    },
+    put_organization::{PutOrganizationParams, PutOutcome, put_organization},
+    put_organization_quota::{
+        PutOrganizationQuotaParams, put_organization_quota,
+    },
+    reset_organization_quota::{
+        ResetOrganizationQuotaParams, reset_organization_quota,
+    },
+};
+pub use phone_verification::{
+    PhoneVerificationDeliveryDeps, PhoneVerificationDeps,
+    start_phone_verification::{
+        StartPhoneVerificationParams, start_phone_verification,
+    },
+    verify_phone::{VerifyPhoneParams, verify_phone},
+};
+pub use read_model::{
+    ReadModelProjectionDeps,
+    project_user_summaries::{
+        ProjectUserSummariesParams, project_user_summaries,
+    },
+};
+pub use registration::{
+    EmailVerificationDeps, RegistrationUseCaseDeps, SelfRegistrationDeps,
+    register_user::{RegisterUserParams, register_user},
+    register_user_with_organization::{
+        RegisterUserWithOrganizationParams, register_user_with_organization,
+    },
+    verify_email::{VerifyEmailParams, verify_email},
+};
+pub use reverification::{
+    ReverificationNotificationDeps, ReverificationUseCaseDeps,
+    get_reverification_progress::{
+        GetReverificationProgressParams, get_reverification_progress,
+    },
+    notify_reverification_batch::{
+        NotifyReverificationBatchParams, notify_reverification_batch,
+    },
+    start_reverification_campaign::{
+        StartReverificationCampaignParams, start_reverification_campaign,
+    },
+};
+pub use role::{
+    RoleUseCaseDeps,
+    assign_role::{AssignRole, AssignRoleParams},
+    bulk_assign_roles::{
+        BulkAssignRoles, BulkAssignRolesParams, BulkRoleAssignment,
+        MAX_BULK_ASSIGNMENTS,
+    },
+    create_role::{CreateRole, CreateRoleParams},
+    delete_role::{DeleteRole, DeleteRoleParams},
+    get_role::{GetRoleParams, get_role},
+    get_role_by_external_id::{
+        GetRoleByExternalIdParams, get_role_by_external_id,
+    },
+    grant_role_permission::{GrantRolePermission, GrantRolePermissionParams},
+    list_role_members::{ListRoleMembersParams, list_role_members},
+    list_roles::{ListRolesParams, list_roles},
+    list_user_roles::{ListUserRolesParams, list_user_roles},
+    put_role::{PutRole, PutRoleParams},
+    revoke_role_permission::{
+        RevokeRolePermission, RevokeRolePermissionParams,
+    },
+    unassign_role::{UnassignRole, UnassignRoleParams},
+    update_role::{UpdateRole, UpdateRoleParams},
+};
+pub use session::{
+    SessionIssuanceDeps, SessionUseCaseDeps,
+    issue_session::{IssueSessionParams, IssuedSession, issue_session},
+    revoke_device_sessions::{
+        RevokeDeviceSessionsParams, revoke_device_sessions,
+    },
+    verify_session::{VerifySessionParams, verify_session},
+};
+pub use signing_key::{
+    SigningKeyRotationDeps, SigningKeyUseCaseDeps,
+    ensure_signing_key::{EnsureSigningKeyParams, ensure_signing_key},
+    list_published_signing_keys::{
+        ListPublishedSigningKeysParams, list_published_signing_keys,
+    },
+    rotate_signing_key::{RotateSigningKeyParams, rotate_signing_key},
+};
 pub use user::{
-    UserUseCaseDeps,
-    create_user::{CreateUserParams, create_user},
+    UserLifecycleUseCaseDeps, UserPasswordUseCaseDeps, UserUseCaseDeps,
+    create_user::{CreateUser, CreateUserParams, create_user},
+    create_users::{CreateUsersParams, create_users},
+    erase_user::{EraseUser, EraseUserParams},
+    export_users::{ExportUsersParams, export_users},
+    get_user::{GetUserParams, get_user},
+    get_user_by_username::{GetUserByUsernameParams, get_user_by_username},
+    get_users::{GetUsersParams, get_users},
+    list_users::{ListUsersParams, list_users},
+    merge_users::{MergeUsers, MergeUsersParams, MergedUsers},
+    search_users::{SearchUsersParams, search_users},
+    set_user_password::{SetUserPasswordParams, set_user_password},
+    update_user::{UpdateUserParams, update_user},
+};
+pub use user_attribute::{
+    UserAttributeUseCaseDeps,
+    delete_user_attribute::{DeleteUserAttributeParams, delete_user_attribute},
+    get_user_attributes::{GetUserAttributesParams, get_user_attributes},
+    put_user_attributes::{PutUserAttributesParams, put_user_attributes},
+};
+pub use webhook::{
+    WebhookDeliveryUseCaseDeps, WebhookDispatchUseCaseDeps,
+    WebhookEndpointUseCaseDeps,
+    delete_webhook_endpoint::{
+        DeleteWebhookEndpointParams, delete_webhook_endpoint,
+    },
+    dispatch_user_events::{DispatchUserEventsParams, dispatch_user_events},
+    get_webhook_endpoint::{GetWebhookEndpointParams, get_webhook_endpoint},
+    get_webhook_endpoint_by_external_id::{
+        GetWebhookEndpointByExternalIdParams,
+        get_webhook_endpoint_by_external_id,
+    },
+    list_due_webhook_deliveries::{
+        DueWebhookDelivery, ListDueWebhookDeliveriesParams,
+        list_due_webhook_deliveries,
+    },
+    list_webhook_deliveries::{
+        ListWebhookDeliveriesParams, list_webhook_deliveries,
+    },
+    list_webhook_endpoints::{
+        ListWebhookEndpointsParams, list_webhook_endpoints,
+    },
+    put_webhook_endpoint::{PutWebhookEndpointParams, put_webhook_endpoint},
+    record_webhook_attempt::{
+        RecordWebhookAttemptParams, record_webhook_attempt,
+    },
+    redeliver_webhook_delivery::{
+        RedeliverWebhookDeliveryParams, redeliver_webhook_delivery,
+    },
+    register_webhook_endpoint::{
+        RegisterWebhookEndpointParams, register_webhook_endpoint,
+    },
 };
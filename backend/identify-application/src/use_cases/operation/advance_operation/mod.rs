@@ -0,0 +1,51 @@
+use identify_domain::Operation;
+use tracing::{instrument, trace};
+use uuid::Uuid;
+
+use crate::{
+    Result, operation_contracts, use_cases::operation::OperationUseCaseDeps,
+};
+
+/// A state change reported by whoever executes an operation.
+#[derive(Debug)]
+pub enum OperationTransition {
+    Start,
+    Progress(u8),
+    Succeed,
+    Fail(String),
+    Cancel,
+}
+
+#[derive(Debug)]
+pub struct AdvanceOperationParams {
+    pub id: Uuid,
+    pub transition: OperationTransition,
+}
+
+/// Applies a state change to an operation and returns its up-to-date state, so executors can
+/// notice cancellation requests.
+#[instrument(skip(deps))]
+pub async fn advance_operation<
+    R: operation_contracts::Get + operation_contracts::Update,
+>(
+    deps: OperationUseCaseDeps<'_, R>,
+    params: AdvanceOperationParams,
+) -> Result<Operation> {
+    trace!("Executing use case");
+
+    let AdvanceOperationParams { id, transition } = params;
+
+    let mut operation = deps.repository.get(id).await?;
+    match transition {
+        OperationTransition::Start => operation.start()?,
+        OperationTransition::Progress(progress) => {
+            operation.report_progress(progress)?
+        }
+        OperationTransition::Succeed => operation.succeed()?,
+        OperationTransition::Fail(error) => operation.fail(error)?,
+        OperationTransition::Cancel => operation.cancel()?,
+    }
+    deps.repository.update(&operation).await?;
+
+    Ok(operation)
+}
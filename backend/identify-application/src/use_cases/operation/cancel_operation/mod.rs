@@ -0,0 +1,29 @@
+use identify_domain::Operation;
+use tracing::{instrument, trace};
+use uuid::Uuid;
+
+use crate::{
+    Result, operation_contracts, use_cases::operation::OperationUseCaseDeps,
+};
+
+#[derive(Debug)]
+pub struct CancelOperationParams {
+    pub id: Uuid,
+}
+
+/// Cancels a pending operation or asks the executor of a running one to stop.
+#[instrument(skip(deps))]
+pub async fn cancel_operation<
+    R: operation_contracts::Get + operation_contracts::Update,
+>(
+    deps: OperationUseCaseDeps<'_, R>,
+    params: CancelOperationParams,
+) -> Result<Operation> {
+    trace!("Executing use case");
+
+    let mut operation = deps.repository.get(params.id).await?;
+    operation.request_cancellation()?;
+    deps.repository.update(&operation).await?;
+
+    Ok(operation)
+}
@@ -0,0 +1,22 @@
+use identify_domain::Operation;
+use tracing::{instrument, trace};
+use uuid::Uuid;
+
+use crate::{
+    Result, operation_contracts, use_cases::operation::OperationUseCaseDeps,
+};
+
+#[derive(Debug)]
+pub struct GetOperationParams {
+    pub id: Uuid,
+}
+
+#[instrument(skip(deps))]
+pub async fn get_operation<R: operation_contracts::Get>(
+    deps: OperationUseCaseDeps<'_, R>,
+    params: GetOperationParams,
+) -> Result<Operation> {
+    trace!("Executing use case");
+
+    deps.repository.get(params.id).await
+}
@@ -0,0 +1,14 @@
+pub mod advance_operation;
+pub mod cancel_operation;
+pub mod get_operation;
+pub mod start_operation;
+
+pub struct OperationUseCaseDeps<'a, R> {
+    repository: &'a mut R,
+}
+
+impl<'a, R> OperationUseCaseDeps<'a, R> {
+    pub fn new(repository: &'a mut R) -> Self {
+        OperationUseCaseDeps { repository }
+    }
+}
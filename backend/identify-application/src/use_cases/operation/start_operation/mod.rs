@@ -0,0 +1,27 @@
+use identify_domain::{NewOperationAttrs, Operation};
+use tracing::{instrument, trace};
+
+use crate::{
+    Result, operation_contracts, use_cases::operation::OperationUseCaseDeps,
+};
+
+#[derive(Debug)]
+pub struct StartOperationParams {
+    pub operation_attrs: NewOperationAttrs,
+}
+
+/// Registers a new pending operation.
+#[instrument(skip(deps))]
+pub async fn start_operation<R: operation_contracts::Insert>(
+    deps: OperationUseCaseDeps<'_, R>,
+    params: StartOperationParams,
+) -> Result<Operation> {
+    trace!("Executing use case");
+
+    let StartOperationParams { operation_attrs } = params;
+
+    let operation = Operation::new(operation_attrs);
+    deps.repository.insert(&operation).await?;
+
+    Ok(operation)
+}
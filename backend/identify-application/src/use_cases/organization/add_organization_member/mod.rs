@@ -0,0 +1,58 @@
+use identify_domain::{
+    Membership, MembershipRole, NewMembershipAttrs, QuotaPolicy,
+};
+use tracing::{instrument, trace};
+use uuid::Uuid;
+
+use crate::{
+    Result, UnitOfWork,
+    membership_contracts::Insert as _,
+    organization_contracts::Get as _,
+    use_cases::organization::{OrganizationUseCaseDeps, ensure_room_for_user},
+    user_contracts::Get as _,
+};
+
+#[derive(Debug)]
+pub struct AddOrganizationMemberParams {
+    pub organization_id: Uuid,
+    pub user_id: Uuid,
+    pub role: MembershipRole,
+    /// Limits how many users the organization can have.
+    pub quota_policy: QuotaPolicy,
+}
+
+/// Adds an existing user to an existing organization with the provided role.
+///
+/// Fails with [QuotaExceeded](crate::ApplicationError::QuotaExceeded) if the organization
+/// can't have any more users.
+#[instrument(skip(deps))]
+pub async fn add_organization_member<U: UnitOfWork>(
+    deps: OrganizationUseCaseDeps<U>,
+    params: AddOrganizationMemberParams,
+) -> Result<Membership> {
+    trace!("Executing use case");
+
+    let AddOrganizationMemberParams {
+        organization_id,
+        user_id,
+        role,
+        quota_policy,
+    } = params;
+    let mut uow = deps.unit_of_work;
+
+    let organization = uow.organizations().get(organization_id).await?;
+    let user = uow.users().get(user_id).await?;
+    user.ensure_not_erased()?;
+    ensure_room_for_user(&mut uow, &quota_policy, organization.id()).await?;
+
+    let membership = Membership::new(NewMembershipAttrs {
+        organization_id: organization.id(),
+        user_id: user.id(),
+        role,
+    });
+    uow.memberships().insert(&membership).await?;
+
+    uow.commit().await?;
+
+    Ok(membership)
+}
@@ -0,0 +1,62 @@
+use identify_domain::{DomainError, Membership, MembershipRole};
+use tracing::{instrument, trace};
+use uuid::Uuid;
+
+use crate::{
+    Result, UnitOfWork,
+    membership_contracts::{CountByRole as _, Get as _, Update as _},
+    use_cases::organization::OrganizationUseCaseDeps,
+};
+
+#[derive(Debug)]
+pub struct ChangeMemberRoleParams {
+    pub organization_id: Uuid,
+    pub user_id: Uuid,
+    pub role: MembershipRole,
+}
+
+/// Changes the role of an existing member of an organization. Nothing is written if the member
+/// already has the role.
+///
+/// Fails if the member is the last owner of the organization and would stop being one, since
+/// nobody could manage the organization anymore.
+#[instrument(skip(deps))]
+pub async fn change_member_role<U: UnitOfWork>(
+    deps: OrganizationUseCaseDeps<U>,
+    params: ChangeMemberRoleParams,
+) -> Result<Membership> {
+    trace!("Executing use case");
+
+    let ChangeMemberRoleParams {
+        organization_id,
+        user_id,
+        role,
+    } = params;
+    let mut uow = deps.unit_of_work;
+
+    let mut membership =
+        uow.memberships().get(organization_id, user_id).await?;
+    if membership.role() == MembershipRole::Owner
+        && role != MembershipRole::Owner
+        && uow
+            .memberships()
+            .count_by_role(organization_id, MembershipRole::Owner)
+            .await?
+            <= 1
+    {
+        return Err(DomainError::invalid_state_transition(
+            "Membership",
+            "the organization must keep at least one owner",
+        )
+        .into());
+    }
+    if !membership.set_role(role) {
+        return Ok(membership);
+    }
+
+    uow.memberships().update(&membership).await?;
+
+    uow.commit().await?;
+
+    Ok(membership)
+}
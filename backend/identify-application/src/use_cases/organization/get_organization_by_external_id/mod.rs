@@ -0,0 +1,33 @@
+use identify_domain::Organization;
+use tracing::{instrument, trace};
+
+use crate::{
+    ApplicationError, Result, UnitOfWork,
+    organization_contracts::GetByExternalId as _,
+    use_cases::organization::OrganizationUseCaseDeps,
+};
+
+#[derive(Debug)]
+pub struct GetOrganizationByExternalIdParams {
+    pub external_id: String,
+}
+
+/// Returns the organization with the provided external ID.
+#[instrument(skip(deps))]
+pub async fn get_organization_by_external_id<U: UnitOfWork>(
+    mut deps: OrganizationUseCaseDeps<U>,
+    params: GetOrganizationByExternalIdParams,
+) -> Result<Organization> {
+    trace!("Executing use case");
+
+    deps.unit_of_work
+        .organizations()
+        .get_by_external_id(&params.external_id)
+        .await?
+        .ok_or_else(|| {
+            ApplicationError::entity_not_found(
+                "Organization",
+                params.external_id,
+            )
+        })
+}
@@ -0,0 +1,36 @@
+use identify_domain::{Organization, Slug};
+use tracing::{instrument, trace};
+
+use crate::{
+    ApplicationError, Result, UnitOfWork,
+    organization_contracts::GetBySlug as _,
+    use_cases::organization::OrganizationUseCaseDeps,
+};
+
+#[derive(Debug)]
+pub struct GetOrganizationBySlugParams {
+    pub slug: String,
+}
+
+/// Returns the organization that uses or used the provided slug.
+///
+/// The slug of the returned organization differs from the provided one if the organization has
+/// replaced it since, so that callers can redirect to the current one.
+#[instrument(skip(deps))]
+pub async fn get_organization_by_slug<U: UnitOfWork>(
+    mut deps: OrganizationUseCaseDeps<U>,
+    params: GetOrganizationBySlugParams,
+) -> Result<Organization> {
+    trace!("Executing use case");
+
+    // Anything that isn't a valid slug can't match any organization.
+    let not_found =
+        || ApplicationError::entity_not_found("Organization", &params.slug);
+    let slug = Slug::parse(&params.slug).map_err(|_| not_found())?;
+
+    deps.unit_of_work
+        .organizations()
+        .get_by_slug(&slug)
+        .await?
+        .ok_or_else(not_found)
+}
@@ -0,0 +1,31 @@
+use identify_domain::QuotaPolicy;
+use tracing::{instrument, trace};
+
+use crate::{
+    Result, UnitOfWork,
+    use_cases::organization::{
+        OrganizationQuotaUsage, OrganizationUseCaseDeps, get_by_external_id,
+        quota_usage,
+    },
+};
+
+#[derive(Debug)]
+pub struct GetOrganizationQuotaParams {
+    pub external_id: String,
+    /// Limits the organizations that don't have quotas of their own.
+    pub policy: QuotaPolicy,
+}
+
+/// Returns how many users the organization with the external ID can have, and how many it has.
+#[instrument(skip(deps))]
+pub async fn get_organization_quota<U: UnitOfWork>(
+    mut deps: OrganizationUseCaseDeps<U>,
+    params: GetOrganizationQuotaParams,
+) -> Result<OrganizationQuotaUsage> {
+    trace!("Executing use case");
+
+    let uow = &mut deps.unit_of_work;
+    let organization = get_by_external_id(uow, params.external_id).await?;
+
+    quota_usage(uow, &params.policy, organization).await
+}
@@ -0,0 +1,160 @@
+pub mod add_organization_member;
+pub mod change_member_role;
+pub mod get_organization_by_external_id;
+pub mod get_organization_by_slug;
+pub mod get_organization_quota;
+pub mod put_organization;
+pub mod put_organization_quota;
+pub mod reset_organization_quota;
+
+use identify_domain::{IdentifierPolicy, Organization, QuotaPolicy, Slug};
+use tracing::debug;
+use uuid::Uuid;
+
+use crate::{
+    ApplicationError, Result, UnitOfWork, organization_contracts,
+    organization_contracts::GetByExternalId as _,
+    organization_quota_contracts::Find as _,
+    user_contracts::{Count as _, UserSpec, UserStatus},
+};
+
+/// Most generated slugs tried before the organization is rejected.
+const MAX_SLUG_ATTEMPTS: u32 = 20;
+
+pub struct OrganizationUseCaseDeps<U> {
+    unit_of_work: U,
+}
+
+impl<U> OrganizationUseCaseDeps<U> {
+    pub fn new(unit_of_work: U) -> Self {
+        OrganizationUseCaseDeps { unit_of_work }
+    }
+}
+
+/// How many users an [Organization] can have, and how many it has.
+#[derive(Debug)]
+pub struct OrganizationQuotaUsage {
+    pub organization: Organization,
+    /// Most users the organization can have, or `None` if it can have any number of them.
+    pub max_users: Option<u32>,
+    /// Whether the organization has a quota of its own, rather than the default.
+    pub custom: bool,
+    /// Users of the organization who count towards its quota.
+    pub users: u64,
+}
+
+/// Returns the organization with the external ID, or fails if there's none.
+async fn get_by_external_id<U: UnitOfWork>(
+    uow: &mut U,
+    external_id: String,
+) -> Result<Organization> {
+    uow.organizations()
+        .get_by_external_id(&external_id)
+        .await?
+        .ok_or_else(|| {
+            ApplicationError::entity_not_found("Organization", external_id)
+        })
+}
+
+/// Counts the users of the organization who count towards its quota: its members who haven't
+/// been erased.
+async fn count_users<U: UnitOfWork>(
+    uow: &mut U,
+    organization_id: Uuid,
+) -> Result<u64> {
+    uow.users()
+        .count(
+            &UserSpec::in_organization(organization_id)
+                .and(UserSpec::by_status(UserStatus::Active)),
+        )
+        .await
+}
+
+async fn quota_usage<U: UnitOfWork>(
+    uow: &mut U,
+    policy: &QuotaPolicy,
+    organization: Organization,
+) -> Result<OrganizationQuotaUsage> {
+    let quota = uow.organization_quotas().find(organization.id()).await?;
+    let users = count_users(uow, organization.id()).await?;
+
+    Ok(OrganizationQuotaUsage {
+        max_users: policy.max_users(quota.as_ref()),
+        custom: quota.is_some(),
+        users,
+        organization,
+    })
+}
+
+/// Fails with [QuotaExceeded](ApplicationError::QuotaExceeded) unless the organization can have
+/// one more user, e.g. before someone joins it.
+pub(crate) async fn ensure_room_for_user<U: UnitOfWork>(
+    uow: &mut U,
+    policy: &QuotaPolicy,
+    organization_id: Uuid,
+) -> Result<()> {
+    let quota = uow.organization_quotas().find(organization_id).await?;
+    // Organizations without a limit don't need their users counted.
+    if policy.max_users(quota.as_ref()).is_none() {
+        return Ok(());
+    }
+
+    let users = count_users(uow, organization_id).await?;
+    policy.check(organization_id, quota.as_ref(), users)?;
+
+    Ok(())
+}
+
+/// Picks the slug of an organization, which is `None` for the ones that are being created.
+///
+/// The requested slug is used as is if no other organization has ever used it and the policy
+/// allows it. Otherwise, slugs are derived from the name until a free and allowed one is found,
+/// e.g. `acme`, `acme-2`, `acme-3` and so on.
+pub(crate) async fn assign_slug<R: organization_contracts::SlugOwner>(
+    organizations: &mut R,
+    organization_id: Option<Uuid>,
+    requested: Option<&str>,
+    name: &str,
+    policy: &IdentifierPolicy,
+) -> Result<Slug> {
+    let mut is_free = async |slug: &Slug| -> Result<bool> {
+        let owner = organizations.slug_owner(slug).await?;
+        Ok(owner.is_none() || owner == organization_id)
+    };
+
+    if let Some(requested) = requested {
+        let slug = Slug::parse(requested)?;
+        policy.check("Slug", slug.as_str())?;
+        if !is_free(&slug).await? {
+            return Err(ApplicationError::entity_already_exists(
+                "Organization".to_owned(),
+                format!("slug '{slug}' is already taken"),
+            ));
+        }
+
+        return Ok(slug);
+    }
+
+    let base = Slug::from_name(name);
+    for attempt in 1..=MAX_SLUG_ATTEMPTS {
+        let slug = match &base {
+            Some(base) if attempt == 1 => base.clone(),
+            Some(base) => base.with_suffix(attempt),
+            None => Slug::random(),
+        };
+
+        if policy.check("Slug", slug.as_str()).is_err() {
+            debug!(%slug, "Slug isn't allowed, trying the next one");
+            continue;
+        }
+        if is_free(&slug).await? {
+            return Ok(slug);
+        }
+        debug!(%slug, "Slug is taken, trying the next one");
+    }
+
+    Err(ApplicationError::entity_already_exists(
+        "Organization".to_owned(),
+        format!("no free slug found in {MAX_SLUG_ATTEMPTS} attempts"),
+    ))
+}
@@ -0,0 +1,111 @@
+use std::sync::Arc;
+
+use identify_domain::{IdentifierPolicy, NewOrganizationAttrs, Organization};
+use tracing::{instrument, trace};
+
+use crate::{
+    Result, UnitOfWork,
+    organization_contracts::{GetByExternalId as _, Insert as _, Update as _},
+    use_cases::organization::{OrganizationUseCaseDeps, assign_slug},
+};
+
+#[derive(Debug)]
+pub struct PutOrganizationParams {
+    pub external_id: String,
+    pub name: String,
+    /// Slug the organization should have. New organizations get one derived from the name if
+    /// it's not set, and existing ones keep theirs.
+    pub slug: Option<String>,
+    /// Decides which names and slugs can be chosen. Names and slugs that don't change aren't
+    /// checked again.
+    pub identifier_policy: Arc<IdentifierPolicy>,
+}
+
+/// What [put_organization], or another use case that puts an entity by its external ID, has
+/// done.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PutOutcome {
+    /// There was no entity with the external ID, so it was created.
+    Created,
+    /// The entity existed and its state was changed to the desired one.
+    Updated,
+    /// The entity already was in the desired state.
+    Unchanged,
+}
+
+/// Creates the organization with the provided external ID, or brings the existing one to the
+/// provided state.
+///
+/// Calling it repeatedly with the same parameters has the same effect as calling it once, which
+/// is what infrastructure as code tools expect.
+#[instrument(skip(deps))]
+pub async fn put_organization<U: UnitOfWork>(
+    deps: OrganizationUseCaseDeps<U>,
+    params: PutOrganizationParams,
+) -> Result<(Organization, PutOutcome)> {
+    trace!("Executing use case");
+
+    let PutOrganizationParams {
+        external_id,
+        name,
+        slug,
+        identifier_policy,
+    } = params;
+    let mut uow = deps.unit_of_work;
+
+    // The repository borrows the unit of work, so it must be gone before the arms use it.
+    let existing = uow.organizations().get_by_external_id(&external_id).await?;
+    let (organization, outcome) = match existing {
+        Some(mut organization) => {
+            let renamed = organization.rename(&name)?;
+            if renamed {
+                identifier_policy.check("Organization", organization.name())?;
+            }
+            let reslugged = match slug.as_deref() {
+                Some(slug) if slug != organization.slug() => {
+                    let slug = assign_slug(
+                        &mut uow.organizations(),
+                        Some(organization.id()),
+                        Some(slug),
+                        organization.name(),
+                        &identifier_policy,
+                    )
+                    .await?;
+                    organization.set_slug(slug)
+                }
+                _ => false,
+            };
+
+            if renamed || reslugged {
+                uow.organizations().update(&organization).await?;
+                (organization, PutOutcome::Updated)
+            } else {
+                (organization, PutOutcome::Unchanged)
+            }
+        }
+        None => {
+            let slug = assign_slug(
+                &mut uow.organizations(),
+                None,
+                slug.as_deref(),
+                &name,
+                &identifier_policy,
+            )
+            .await?;
+            let organization = Organization::new(
+                NewOrganizationAttrs {
+                    name,
+                    external_id: Some(external_id),
+                },
+                slug,
+            )?;
+            identifier_policy.check("Organization", organization.name())?;
+            uow.organizations().insert(&organization).await?;
+            (organization, PutOutcome::Created)
+        }
+    };
+
+    uow.commit().await?;
+
+    Ok((organization, outcome))
+}
@@ -0,0 +1,62 @@
+use identify_domain::{
+    NewOrganizationQuotaAttrs, OrganizationQuota, QuotaPolicy,
+};
+use tracing::{instrument, trace};
+
+use crate::{
+    Result, UnitOfWork,
+    organization_quota_contracts::{Find as _, Put as _},
+    use_cases::organization::{
+        OrganizationQuotaUsage, OrganizationUseCaseDeps, get_by_external_id,
+        quota_usage,
+    },
+};
+
+#[derive(Debug)]
+pub struct PutOrganizationQuotaParams {
+    pub external_id: String,
+    /// Most users the organization can have, or `None` if it can have any number of them.
+    pub max_users: Option<u32>,
+    /// Limits the organizations that don't have quotas of their own.
+    pub policy: QuotaPolicy,
+}
+
+/// Gives the organization with the external ID a quota of its own, which replaces the default.
+///
+/// Organizations that already have more users than their new quota keep them, but can't have
+/// any more.
+#[instrument(skip(deps))]
+pub async fn put_organization_quota<U: UnitOfWork>(
+    mut deps: OrganizationUseCaseDeps<U>,
+    params: PutOrganizationQuotaParams,
+) -> Result<OrganizationQuotaUsage> {
+    trace!("Executing use case");
+
+    let PutOrganizationQuotaParams {
+        external_id,
+        max_users,
+        policy,
+    } = params;
+    let uow = &mut deps.unit_of_work;
+    let organization = get_by_external_id(uow, external_id).await?;
+
+    let existing = uow.organization_quotas().find(organization.id()).await?;
+    let quota = match existing {
+        Some(mut quota) => {
+            if !quota.set_max_users(max_users) {
+                return quota_usage(uow, &policy, organization).await;
+            }
+            quota
+        }
+        None => OrganizationQuota::new(NewOrganizationQuotaAttrs {
+            organization_id: organization.id(),
+            max_users,
+        }),
+    };
+    uow.organization_quotas().put(&quota).await?;
+
+    let usage = quota_usage(uow, &policy, organization).await?;
+    deps.unit_of_work.commit().await?;
+
+    Ok(usage)
+}
@@ -0,0 +1,37 @@
+use identify_domain::QuotaPolicy;
+use tracing::{instrument, trace};
+
+use crate::{
+    Result, UnitOfWork,
+    organization_quota_contracts::Delete as _,
+    use_cases::organization::{
+        OrganizationQuotaUsage, OrganizationUseCaseDeps, get_by_external_id,
+        quota_usage,
+    },
+};
+
+#[derive(Debug)]
+pub struct ResetOrganizationQuotaParams {
+    pub external_id: String,
+    /// Limits the organizations that don't have quotas of their own.
+    pub policy: QuotaPolicy,
+}
+
+/// Removes the quota of the organization with the external ID, so that it's limited by the
+/// default again. Organizations without a quota of their own are left as they are.
+#[instrument(skip(deps))]
+pub async fn reset_organization_quota<U: UnitOfWork>(
+    mut deps: OrganizationUseCaseDeps<U>,
+    params: ResetOrganizationQuotaParams,
+) -> Result<OrganizationQuotaUsage> {
+    trace!("Executing use case");
+
+    let uow = &mut deps.unit_of_work;
+    let organization = get_by_external_id(uow, params.external_id).await?;
+    uow.organization_quotas().delete(organization.id()).await?;
+
+    let usage = quota_usage(uow, &params.policy, organization).await?;
+    deps.unit_of_work.commit().await?;
+
+    Ok(usage)
+}
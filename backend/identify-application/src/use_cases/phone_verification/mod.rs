@@ -0,0 +1,50 @@
+pub mod start_phone_verification;
+pub mod verify_phone;
+
+use uuid::Uuid;
+
+use crate::TokenGenerator;
+
+/// Dependencies of the use cases that text codes to users to verify their phone numbers with.
+pub struct PhoneVerificationDeliveryDeps<'a, U, T: ?Sized, S: ?Sized> {
+    unit_of_work: &'a mut U,
+    tokens: &'a T,
+    sender: &'a S,
+}
+
+impl<'a, U, T: ?Sized, S: ?Sized> PhoneVerificationDeliveryDeps<'a, U, T, S> {
+    pub fn new(unit_of_work: &'a mut U, tokens: &'a T, sender: &'a S) -> Self {
+        PhoneVerificationDeliveryDeps {
+            unit_of_work,
+            tokens,
+            sender,
+        }
+    }
+}
+
+/// Dependencies of the use cases that verify phone numbers with the codes users were texted.
+pub struct PhoneVerificationDeps<'a, U, T: ?Sized> {
+    unit_of_work: &'a mut U,
+    tokens: &'a T,
+}
+
+impl<'a, U, T: ?Sized> PhoneVerificationDeps<'a, U, T> {
+    pub fn new(unit_of_work: &'a mut U, tokens: &'a T) -> Self {
+        PhoneVerificationDeps {
+            unit_of_work,
+            tokens,
+        }
+    }
+}
+
+/// Hashes a code together with the ID of its verification.
+///
+/// Codes are short, so the same code is sent for many verifications, and a hash of the code
+/// alone could be reversed by hashing all the codes.
+fn code_hash<T: TokenGenerator + ?Sized>(
+    tokens: &T,
+    id: Uuid,
+    code: &str,
+) -> String {
+    tokens.hash(&format!("{id}:{code}"))
+}
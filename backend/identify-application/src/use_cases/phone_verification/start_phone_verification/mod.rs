@@ -0,0 +1,79 @@
+use chrono::{TimeDelta, Utc};
+use identify_domain::{
+    NewPhoneVerificationAttrs, PhoneNumber, PhoneVerification,
+};
+use tracing::{instrument, trace};
+use uuid::Uuid;
+
+use crate::{
+    Result, Sms, SmsSender, TokenGenerator, UnitOfWork,
+    phone_verification_contracts::Insert as _,
+    use_cases::phone_verification::{PhoneVerificationDeliveryDeps, code_hash},
+    user_contracts::Get as _,
+};
+
+#[derive(Debug)]
+pub struct StartPhoneVerificationParams {
+    pub user_id: Uuid,
+    /// Number to verify, normalized to the E.164 format.
+    pub phone_number: String,
+    /// How long the user has to enter the code.
+    pub valid_for: TimeDelta,
+}
+
+/// Texts a code to a phone number of a user, which they verify the number with by entering it,
+/// see [verify_phone](crate::verify_phone).
+///
+/// Fails if the number isn't valid, or if the user has been erased. If the SMS can't be sent,
+/// the verification isn't created either.
+#[instrument(skip(deps))]
+pub async fn start_phone_verification<U, T, S>(
+    deps: PhoneVerificationDeliveryDeps<'_, U, T, S>,
+    params: StartPhoneVerificationParams,
+) -> Result<PhoneVerification>
+where
+    U: UnitOfWork,
+    T: TokenGenerator + ?Sized,
+    S: SmsSender + ?Sized,
+{
+    trace!("Executing use case");
+
+    let StartPhoneVerificationParams {
+        user_id,
+        phone_number,
+        valid_for,
+    } = params;
+
+    let PhoneVerificationDeliveryDeps {
+        unit_of_work: uow,
+        tokens,
+        sender,
+    } = deps;
+    let phone_number = PhoneNumber::parse(&phone_number)?;
+    let user = uow.users().get(user_id).await?;
+    user.ensure_not_erased()?;
+
+    let id = Uuid::new_v4();
+    let code = tokens.generate_code();
+    let verification = PhoneVerification::new(
+        id,
+        NewPhoneVerificationAttrs {
+            user_id: user.id(),
+            phone_number,
+            code_hash: code_hash(tokens, id, &code),
+            expires_at: Utc::now() + valid_for,
+        },
+    )?;
+    uow.phone_verifications().insert(&verification).await?;
+
+    let sms = Sms {
+        to: verification.phone_number().to_owned(),
+        text: format!(
+            "Your verification code is {code}. It expires in {} minutes.",
+            valid_for.num_minutes()
+        ),
+    };
+    sender.send(&sms).await?;
+
+    Ok(verification)
+}
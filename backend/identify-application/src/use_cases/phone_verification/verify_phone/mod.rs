@@ -0,0 +1,52 @@
+use identify_domain::PhoneVerification;
+use tracing::{instrument, trace};
+use uuid::Uuid;
+
+use crate::{
+    Result, TokenGenerator, UnitOfWork,
+    phone_verification_contracts::{Get as _, Update as _},
+    use_cases::phone_verification::{PhoneVerificationDeps, code_hash},
+    user_contracts::Get as _,
+};
+
+#[derive(Debug)]
+pub struct VerifyPhoneParams {
+    pub id: Uuid,
+    /// Code the user was texted.
+    pub code: String,
+}
+
+/// Verifies a phone number of a user with the code they were texted.
+///
+/// Wrong codes don't fail, but are counted by the returned verification, which isn't
+/// [verified](PhoneVerification::is_verified) then. The changes must be committed either way,
+/// so that guessing codes runs out of attempts. Fails if the number is already verified, if
+/// the verification has expired or has run out of attempts, or if the user has been erased.
+#[instrument(skip(deps))]
+pub async fn verify_phone<U, T>(
+    deps: PhoneVerificationDeps<'_, U, T>,
+    params: VerifyPhoneParams,
+) -> Result<PhoneVerification>
+where
+    U: UnitOfWork,
+    T: TokenGenerator + ?Sized,
+{
+    trace!("Executing use case");
+
+    let VerifyPhoneParams { id, code } = params;
+
+    let PhoneVerificationDeps {
+        unit_of_work: uow,
+        tokens,
+    } = deps;
+    let mut verification = uow.phone_verifications().get(id).await?;
+    let user = uow.users().get(verification.user_id()).await?;
+    user.ensure_not_erased()?;
+
+    let matches =
+        code_hash(tokens, id, code.trim()) == verification.code_hash();
+    verification.attempt(matches)?;
+    uow.phone_verifications().update(&verification).await?;
+
+    Ok(verification)
+}
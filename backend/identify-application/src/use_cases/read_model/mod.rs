@@ -0,0 +1,16 @@
+pub mod project_user_summaries;
+
+/// Dependencies of the use cases that project events into the read models.
+///
+/// The events are marked as projected in the same transaction the read models are updated in,
+/// so the repositories come from a single [UnitOfWork](crate::UnitOfWork), which is committed
+/// by the caller.
+pub struct ReadModelProjectionDeps<'a, U> {
+    unit_of_work: &'a mut U,
+}
+
+impl<'a, U> ReadModelProjectionDeps<'a, U> {
+    pub fn new(unit_of_work: &'a mut U) -> Self {
+        ReadModelProjectionDeps { unit_of_work }
+    }
+}
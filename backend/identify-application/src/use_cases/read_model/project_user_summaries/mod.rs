@@ -0,0 +1,73 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::Utc;
+use identify_domain::UserEvent;
+use tracing::{instrument, trace};
+use uuid::Uuid;
+
+use crate::{
+    Result, UnitOfWork,
+    use_cases::read_model::ReadModelProjectionDeps,
+    user_contracts::GetMany as _,
+    user_event_contracts::{ListUnprojected as _, MarkProjected as _},
+    user_summary_contracts::{Remove as _, Upsert as _},
+};
+
+#[derive(Debug)]
+pub struct ProjectUserSummariesParams {
+    /// Most events projected at once.
+    pub limit: u32,
+}
+
+/// Updates the summaries of the users whose events haven't been projected yet, and marks the
+/// events as projected.
+///
+/// Returns the projected events, e.g. to invalidate caches of the users once the transaction is
+/// committed. Summaries are rebuilt from the current state of
+/// the users rather than from the events, so projecting an event again or out of order does no
+/// harm. Summaries of users that don't exist anymore are removed.
+#[instrument(skip(deps))]
+pub async fn project_user_summaries<U: UnitOfWork>(
+    deps: ReadModelProjectionDeps<'_, U>,
+    params: ProjectUserSummariesParams,
+) -> Result<Vec<UserEvent>> {
+    trace!("Executing use case");
+
+    let uow = deps.unit_of_work;
+
+    let events = uow.user_events().list_unprojected(params.limit).await?;
+    if events.is_empty() {
+        return Ok(events);
+    }
+
+    let user_ids = events
+        .iter()
+        .map(|event| event.user_id())
+        .collect::<HashSet<Uuid>>()
+        .into_iter()
+        .collect::<Vec<_>>();
+    let users = uow
+        .users()
+        .get_many(&user_ids)
+        .await?
+        .into_iter()
+        .map(|user| (user.id(), user))
+        .collect::<HashMap<_, _>>();
+
+    for user_id in &user_ids {
+        // Erased users are left out of listings, like the deleted ones.
+        match users.get(user_id) {
+            Some(user) if !user.is_erased() => {
+                uow.user_summaries().upsert(user).await?
+            }
+            _ => uow.user_summaries().remove(*user_id).await?,
+        }
+    }
+
+    let now = Utc::now();
+    for event in &events {
+        uow.user_events().mark_projected(event, now).await?;
+    }
+
+    Ok(events)
+}
@@ -0,0 +1,66 @@
+pub mod register_user;
+pub mod register_user_with_organization;
+pub mod verify_email;
+
+use identify_domain::RegistrationPolicy;
+
+use crate::EmailTemplates;
+
+pub struct RegistrationUseCaseDeps<U> {
+    unit_of_work: U,
+}
+
+impl<U> RegistrationUseCaseDeps<U> {
+    pub fn new(unit_of_work: U) -> Self {
+        RegistrationUseCaseDeps { unit_of_work }
+    }
+}
+
+/// Dependencies of the use cases with which people register themselves.
+///
+/// Users are registered in a [UnitOfWork](crate::UnitOfWork), which is committed by the caller.
+pub struct SelfRegistrationDeps<'a, U, H: ?Sized, T: ?Sized, S: ?Sized> {
+    unit_of_work: &'a mut U,
+    policy: &'a RegistrationPolicy,
+    hasher: &'a H,
+    tokens: &'a T,
+    sender: &'a S,
+    templates: &'a EmailTemplates,
+}
+
+impl<'a, U, H: ?Sized, T: ?Sized, S: ?Sized>
+    SelfRegistrationDeps<'a, U, H, T, S>
+{
+    pub fn new(
+        unit_of_work: &'a mut U,
+        policy: &'a RegistrationPolicy,
+        hasher: &'a H,
+        tokens: &'a T,
+        sender: &'a S,
+        templates: &'a EmailTemplates,
+    ) -> Self {
+        SelfRegistrationDeps {
+            unit_of_work,
+            policy,
+            hasher,
+            tokens,
+            sender,
+            templates,
+        }
+    }
+}
+
+/// Dependencies of the use cases that verify emails with the tokens users were sent.
+pub struct EmailVerificationDeps<'a, U, T: ?Sized> {
+    unit_of_work: &'a mut U,
+    tokens: &'a T,
+}
+
+impl<'a, U, T: ?Sized> EmailVerificationDeps<'a, U, T> {
+    pub fn new(unit_of_work: &'a mut U, tokens: &'a T) -> Self {
+        EmailVerificationDeps {
+            unit_of_work,
+            tokens,
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use std::sync::Arc;
+
+use chrono::{TimeDelta, Utc};
+use identify_domain::{
+    EmailVerification, IdentifierPolicy, NewEmailVerificationAttrs,
+    NewUserAttrs, Password, QuotaPolicy, User, UsernameStrategy,
+};
+use tracing::{instrument, trace};
+
+use crate::{
+    ApplicationError, CreateUser, CreateUserParams, EmailMessage, EmailSender,
+    PasswordHasher, Result, System, TokenGenerator, TransactionalUseCase,
+    UnitOfWork, email_verification_contracts::Insert as _,
+    use_cases::registration::SelfRegistrationDeps,
+    user_contracts::SetPasswordHash as _,
+};
+
+#[derive(Debug)]
+pub struct RegisterUserParams {
+    pub user_attrs: NewUserAttrs,
+    pub password: Password,
+    /// Username the user asked for. One is generated with the strategy if it's not set.
+    pub username: Option<String>,
+    pub username_strategy: UsernameStrategy,
+    /// Decides which usernames can be chosen.
+    pub identifier_policy: Arc<IdentifierPolicy>,
+    /// URL of the page the user verifies their email on. The ID of the verification and the
+    /// token are appended as the `verification` and `token` query parameters.
+    pub verify_url: String,
+    /// How long the user has to verify their email.
+    pub verification_valid_for: TimeDelta,
+}
+
+/// Registers a user who signed up by themselves: creates them with their password, and emails
+/// them a link to verify their email with.
+///
+/// Fails with [RegistrationRefused](ApplicationError::RegistrationRefused) if the
+/// [RegistrationPolicy](identify_domain::RegistrationPolicy) doesn't let them register. If the
+/// email can't be sent, the user isn't created either, so that they can simply try again.
+#[instrument(skip(deps))]
+pub async fn register_user<U, H, T, S>(
+    deps: SelfRegistrationDeps<'_, U, H, T, S>,
+    params: RegisterUserParams,
+) -> Result<User>
+where
+    U: UnitOfWork,
+    H: PasswordHasher + ?Sized,
+    T: TokenGenerator + ?Sized,
+    S: EmailSender + ?Sized,
+{
+    trace!("Executing use case");
+
+    let RegisterUserParams {
+        user_attrs,
+        password,
+        username,
+        username_strategy,
+        identifier_policy,
+        verify_url,
+        verification_valid_for,
+    } = params;
+
+    let SelfRegistrationDeps {
+        unit_of_work: uow,
+        policy,
+        hasher,
+        tokens,
+        sender,
+        templates,
+    } = deps;
+    policy
+        .check(&user_attrs.email)
+        .map_err(ApplicationError::RegistrationRefused)?;
+    let user = CreateUser
+        .execute(
+            uow,
+            &System,
+            CreateUserParams {
+                user_attrs,
+                username,
+                username_strategy,
+                identifier_policy,
+                organization_id: None,
+                quota_policy: QuotaPolicy::default(),
+            },
+        )
+        .await?;
+    let password_hash = hasher.hash(&password)?;
+    uow.users()
+        .set_password_hash(user.id(), &password_hash)
+        .await?;
+
+    let token = tokens.generate();
+    let verification = EmailVerification::new(NewEmailVerificationAttrs {
+        user_id: user.id(),
+        token_hash: tokens.hash(&token),
+        expires_at: Utc::now() + verification_valid_for,
+    })?;
+    uow.email_verifications().insert(&verification).await?;
+
+    let separator = if verify_url.contains('?') { '&' } else { '?' };
+    let link = format!(
+        "{verify_url}{separator}verification={}&token={token}",
+        verification.id()
+    );
+    let email = templates.render_for(
+        &user,
+        &EmailMessage::Welcome {
+            first_name: user.first_name(),
+            link: &link,
+        },
+    );
+    sender.send(&email).await?;
+
+    Ok(user)
+}
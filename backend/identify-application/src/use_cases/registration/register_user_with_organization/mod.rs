@@ -0,0 +1,96 @@
+use std::sync::Arc;
+
+use identify_domain::{
+    IdentifierPolicy, Membership, MembershipRole, NewMembershipAttrs,
+    NewOrganizationAttrs, NewUserAttrs, Organization, User,
+    UserLifecycleTransition, UsernameStrategy,
+};
+use tracing::{instrument, trace};
+
+use crate::{
+    Result, UnitOfWork,
+    membership_contracts::Insert as _,
+    organization_contracts::Insert as _,
+    use_cases::{
+        organization::assign_slug,
+        registration::RegistrationUseCaseDeps,
+        user::{assign_username, emit_user_event},
+    },
+    user_contracts::Insert as _,
+};
+
+#[derive(Debug)]
+pub struct RegisterUserWithOrganizationParams {
+    pub user_attrs: NewUserAttrs,
+    /// Username the user asked for. One is generated with the strategy if it's not set.
+    pub username: Option<String>,
+    pub username_strategy: UsernameStrategy,
+    /// Decides which usernames, and names and slugs of organizations can be chosen.
+    pub identifier_policy: Arc<IdentifierPolicy>,
+    pub organization_attrs: NewOrganizationAttrs,
+    /// Slug the organization asked for. One is derived from its name if it's not set.
+    pub organization_slug: Option<String>,
+}
+
+/// Creates a user together with a new organization they own.
+///
+/// All the entities are created within a single [UnitOfWork], so either all of them are
+/// persisted or none.
+#[instrument(skip(deps))]
+pub async fn register_user_with_organization<U: UnitOfWork>(
+    deps: RegistrationUseCaseDeps<U>,
+    params: RegisterUserWithOrganizationParams,
+) -> Result<(User, Organization)> {
+    trace!("Executing use case");
+
+    let RegisterUserWithOrganizationParams {
+        user_attrs,
+        username,
+        username_strategy,
+        identifier_policy,
+        organization_attrs,
+        organization_slug,
+    } = params;
+    let mut uow = deps.unit_of_work;
+
+    let username = assign_username(
+        &mut uow.users(),
+        username.as_deref(),
+        username_strategy,
+        &identifier_policy,
+        &user_attrs,
+    )
+    .await?;
+    let user = User::new(user_attrs, username);
+    let organization_slug = assign_slug(
+        &mut uow.organizations(),
+        None,
+        organization_slug.as_deref(),
+        &organization_attrs.name,
+        &identifier_policy,
+    )
+    .await?;
+    let organization =
+        Organization::new(organization_attrs, organization_slug)?;
+    identifier_policy.check("Organization", organization.name())?;
+    let membership = Membership::new(NewMembershipAttrs {
+        organization_id: organization.id(),
+        user_id: user.id(),
+        role: MembershipRole::Owner,
+    });
+
+    uow.users().insert(&user).await?;
+    uow.organizations().insert(&organization).await?;
+    uow.memberships().insert(&membership).await?;
+    emit_user_event(
+        &mut uow.user_events(),
+        user.id(),
+        UserLifecycleTransition::Created,
+        user.version(),
+    )
+    .await?;
+
+    uow.commit().await?;
+
+    Ok((user, organization))
+}
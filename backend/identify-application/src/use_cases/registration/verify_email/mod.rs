@@ -0,0 +1,63 @@
+use identify_domain::{EmailVerification, UserLifecycleTransition};
+use tracing::{instrument, trace};
+use uuid::Uuid;
+
+use crate::{
+    ApplicationError, Result, TokenGenerator, UnitOfWork,
+    email_verification_contracts::{Get as _, Update as _},
+    use_cases::{registration::EmailVerificationDeps, user::emit_user_event},
+    user_contracts::Get as _,
+};
+
+#[derive(Debug)]
+pub struct VerifyEmailParams {
+    pub id: Uuid,
+    /// Token the user was emailed.
+    pub token: String,
+}
+
+/// Verifies the email of a user with the token they were emailed, and emits the event
+/// announcing it.
+///
+/// A verification whose token doesn't match is reported as not found, so that the IDs of
+/// verifications can't be probed. Fails if the email is already verified, or if the
+/// verification has expired.
+#[instrument(skip(deps))]
+pub async fn verify_email<U, T>(
+    deps: EmailVerificationDeps<'_, U, T>,
+    params: VerifyEmailParams,
+) -> Result<EmailVerification>
+where
+    U: UnitOfWork,
+    T: TokenGenerator + ?Sized,
+{
+    trace!("Executing use case");
+
+    let VerifyEmailParams { id, token } = params;
+
+    let EmailVerificationDeps {
+        unit_of_work: uow,
+        tokens,
+    } = deps;
+    let mut verification = uow.email_verifications().get(id).await?;
+    if tokens.hash(&token) != verification.token_hash() {
+        return Err(ApplicationError::entity_not_found(
+            "EmailVerification",
+            id,
+        ));
+    }
+    let user = uow.users().get(verification.user_id()).await?;
+    user.ensure_not_erased()?;
+
+    verification.verify()?;
+    uow.email_verifications().update(&verification).await?;
+    emit_user_event(
+        &mut uow.user_events(),
+        user.id(),
+        UserLifecycleTransition::Verified,
+        user.version(),
+    )
+    .await?;
+
+    Ok(verification)
+}
@@ -0,0 +1,31 @@
+use identify_domain::ReverificationCampaign;
+use tracing::{instrument, trace};
+use uuid::Uuid;
+
+use crate::{
+    Result,
+    reverification_contracts::{self, ReverificationProgress},
+    use_cases::reverification::ReverificationUseCaseDeps,
+};
+
+#[derive(Debug)]
+pub struct GetReverificationProgressParams {
+    pub campaign_id: Uuid,
+}
+
+/// Returns the campaign together with how many of its users have been notified.
+#[instrument(skip(deps))]
+pub async fn get_reverification_progress<R>(
+    deps: ReverificationUseCaseDeps<'_, R>,
+    params: GetReverificationProgressParams,
+) -> Result<(ReverificationCampaign, ReverificationProgress)>
+where
+    R: reverification_contracts::Get + reverification_contracts::Progress,
+{
+    trace!("Executing use case");
+
+    let campaign = deps.repository.get(params.campaign_id).await?;
+    let progress = deps.repository.progress(campaign.id()).await?;
+
+    Ok((campaign, progress))
+}
@@ -0,0 +1,36 @@
+use crate::EmailTemplates;
+
+pub mod get_reverification_progress;
+pub mod notify_reverification_batch;
+pub mod start_reverification_campaign;
+
+pub struct ReverificationUseCaseDeps<'a, R> {
+    repository: &'a mut R,
+}
+
+impl<'a, R> ReverificationUseCaseDeps<'a, R> {
+    pub fn new(repository: &'a mut R) -> Self {
+        ReverificationUseCaseDeps { repository }
+    }
+}
+
+/// Dependencies of the use cases that notify the flagged users.
+pub struct ReverificationNotificationDeps<'a, R, S> {
+    repository: &'a mut R,
+    sender: &'a S,
+    templates: &'a EmailTemplates,
+}
+
+impl<'a, R, S> ReverificationNotificationDeps<'a, R, S> {
+    pub fn new(
+        repository: &'a mut R,
+        sender: &'a S,
+        templates: &'a EmailTemplates,
+    ) -> Self {
+        ReverificationNotificationDeps {
+            repository,
+            sender,
+            templates,
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use tracing::{instrument, trace};
+use uuid::Uuid;
+
+use crate::{
+    EmailMessage, EmailSender, Result,
+    reverification_contracts::{self, ReverificationProgress},
+    use_cases::reverification::ReverificationNotificationDeps,
+};
+
+#[derive(Debug)]
+pub struct NotifyReverificationBatchParams {
+    pub campaign_id: Uuid,
+    /// Most users notified at once.
+    pub batch_size: u32,
+}
+
+/// Emails the next batch of the flagged users that haven't been notified yet, and returns the
+/// progress of the campaign afterwards.
+///
+/// Each user is marked as notified right after their email is sent, so the repository is
+/// expected to commit every change on its own rather than within a transaction, which would be
+/// held open while emails are sent. If the sender fails, the users that have been emailed stay
+/// notified and the rest of the batch is sent later. Users can thus be notified more than once,
+/// if marking them fails, but never left out.
+#[instrument(skip(deps))]
+pub async fn notify_reverification_batch<R, S>(
+    deps: ReverificationNotificationDeps<'_, R, S>,
+    params: NotifyReverificationBatchParams,
+) -> Result<ReverificationProgress>
+where
+    R: reverification_contracts::Get
+        + reverification_contracts::ListPending
+        + reverification_contracts::MarkNotified
+        + reverification_contracts::Progress,
+    S: EmailSender,
+{
+    trace!("Executing use case");
+
+    let NotifyReverificationBatchParams {
+        campaign_id,
+        batch_size,
+    } = params;
+
+    let campaign = deps.repository.get(campaign_id).await?;
+    let users = deps
+        .repository
+        .list_pending(campaign.id(), batch_size)
+        .await?;
+
+    for user in &users {
+        let email = deps.templates.render_for(
+            user,
+            &EmailMessage::Verification {
+                first_name: user.first_name(),
+                email: user.email(),
+                reason: campaign.reason(),
+            },
+        );
+        deps.sender.send(&email).await?;
+        deps.repository
+            .mark_notified(campaign.id(), user.id())
+            .await?;
+    }
+
+    deps.repository.progress(campaign.id()).await
+}
@@ -0,0 +1,43 @@
+use identify_domain::{NewReverificationCampaignAttrs, ReverificationCampaign};
+use tracing::{info, instrument, trace};
+
+use crate::{
+    Result,
+    reverification_contracts::{self, ReverificationProgress},
+    use_cases::reverification::ReverificationUseCaseDeps,
+};
+
+#[derive(Debug)]
+pub struct StartReverificationCampaignParams {
+    pub campaign_attrs: NewReverificationCampaignAttrs,
+}
+
+/// Creates a campaign and flags every user of its cohort as requiring email re-verification.
+///
+/// Nobody is notified yet, see
+/// [notify_reverification_batch](crate::notify_reverification_batch).
+#[instrument(skip(deps))]
+pub async fn start_reverification_campaign<R>(
+    deps: ReverificationUseCaseDeps<'_, R>,
+    params: StartReverificationCampaignParams,
+) -> Result<(ReverificationCampaign, ReverificationProgress)>
+where
+    R: reverification_contracts::Insert + reverification_contracts::FlagCohort,
+{
+    trace!("Executing use case");
+
+    let StartReverificationCampaignParams { campaign_attrs } = params;
+
+    let campaign = ReverificationCampaign::new(campaign_attrs)?;
+    deps.repository.insert(&campaign).await?;
+    let flagged = deps.repository.flag_cohort(&campaign).await?;
+    info!(campaign_id = %campaign.id(), flagged, "Flagged users for re-verification");
+
+    Ok((
+        campaign,
+        ReverificationProgress {
+            flagged,
+            notified: 0,
+        },
+    ))
+}
@@ -0,0 +1,59 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use identify_domain::{Resource, RoleAssignment};
+use uuid::Uuid;
+
+use crate::{
+    AccessControlled, Actor, Result, TransactionalUseCase, UnitOfWork,
+    role_contracts::{Assign as _, Get as _},
+    user_contracts::Get as _,
+};
+
+#[derive(Debug, Clone)]
+pub struct AssignRoleParams {
+    pub role_id: Uuid,
+    pub user_id: Uuid,
+}
+
+impl AccessControlled for AssignRoleParams {
+    /// The role, whose new member policies can check as `resource.user_id`.
+    fn resource(&self) -> Resource {
+        Resource::new("role")
+            .with_id(self.role_id)
+            .with_attribute("user_id", self.user_id.to_string())
+    }
+}
+
+/// Assigns a role to a user who hasn't been erased. Returns whether the role is newly
+/// assigned to them.
+pub struct AssignRole;
+
+#[async_trait]
+impl<U: UnitOfWork> TransactionalUseCase<U> for AssignRole {
+    type Input = AssignRoleParams;
+    type Output = bool;
+
+    fn name(&self) -> &'static str {
+        "assign_role"
+    }
+
+    async fn execute(
+        &self,
+        uow: &mut U,
+        _actor: &dyn Actor,
+        params: AssignRoleParams,
+    ) -> Result<bool> {
+        let AssignRoleParams { role_id, user_id } = params;
+
+        uow.roles().get(role_id).await?;
+        uow.users().get(user_id).await?.ensure_not_erased()?;
+
+        uow.roles()
+            .assign(&RoleAssignment {
+                role_id,
+                user_id,
+                assigned_at: Utc::now(),
+            })
+            .await
+    }
+}
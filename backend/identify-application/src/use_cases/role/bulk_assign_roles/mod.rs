@@ -0,0 +1,117 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use identify_domain::{
+    DomainError, Resource, RoleAssignment, RoleCohort, User,
+};
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    AccessControlled, Actor, Result, TransactionalUseCase, UnitOfWork,
+    role_contracts::{Assign as _, Get as _, ListUnassigned as _},
+};
+
+/// Most users a role is assigned to at once.
+pub const MAX_BULK_ASSIGNMENTS: u32 = 1000;
+
+#[derive(Debug, Clone)]
+pub struct BulkAssignRolesParams {
+    pub role_id: Uuid,
+    pub cohort: RoleCohort,
+    /// Only find the users the role would be assigned to, without assigning it.
+    pub dry_run: bool,
+}
+
+impl AccessControlled for BulkAssignRolesParams {
+    /// The role, whose cohort policies can check as `resource.email_domain` and
+    /// `resource.organization_id`.
+    fn resource(&self) -> Resource {
+        let mut resource = Resource::new("role").with_id(self.role_id);
+        if let Some(domain) = self.cohort.email_domain() {
+            resource = resource.with_attribute("email_domain", domain);
+        }
+        if let Some(organization_id) = self.cohort.organization_id() {
+            resource = resource
+                .with_attribute("organization_id", organization_id.to_string());
+        }
+
+        resource
+    }
+}
+
+/// Users a role has been, or would be, assigned to by [BulkAssignRoles].
+#[derive(Debug, Clone)]
+pub struct BulkRoleAssignment {
+    /// Users of the cohort who didn't have the role yet, the oldest first.
+    pub users: Vec<User>,
+    /// Whether the role has been assigned to them, which it isn't in dry runs.
+    pub applied: bool,
+}
+
+/// Assigns a role to every user of a cohort who hasn't been erased and doesn't have it yet.
+///
+/// Cohorts of more than [MAX_BULK_ASSIGNMENTS] such users are refused as a whole, so they have to
+/// be narrowed down. A dry run returns the users without assigning the role, so that they can be
+/// reviewed first.
+pub struct BulkAssignRoles;
+
+#[async_trait]
+impl<U: UnitOfWork> TransactionalUseCase<U> for BulkAssignRoles {
+    type Input = BulkAssignRolesParams;
+    type Output = BulkRoleAssignment;
+
+    fn name(&self) -> &'static str {
+        "bulk_assign_roles"
+    }
+
+    async fn execute(
+        &self,
+        uow: &mut U,
+        _actor: &dyn Actor,
+        params: BulkAssignRolesParams,
+    ) -> Result<BulkRoleAssignment> {
+        let BulkAssignRolesParams {
+            role_id,
+            cohort,
+            dry_run,
+        } = params;
+
+        uow.roles().get(role_id).await?;
+        let users = uow
+            .roles()
+            .list_unassigned(role_id, &cohort, MAX_BULK_ASSIGNMENTS + 1)
+            .await?;
+        if users.len() > MAX_BULK_ASSIGNMENTS as usize {
+            return Err(DomainError::validation(
+                "RoleCohort",
+                format!(
+                    "the cohort has more than {MAX_BULK_ASSIGNMENTS} users without the role"
+                ),
+            )
+            .into());
+        }
+        if dry_run {
+            return Ok(BulkRoleAssignment {
+                users,
+                applied: false,
+            });
+        }
+
+        let assigned_at = Utc::now();
+        for user in &users {
+            uow.roles()
+                .assign(&RoleAssignment {
+                    role_id,
+                    user_id: user.id(),
+                    assigned_at,
+                })
+                .await?;
+        }
+        info!(%role_id, assigned = users.len(), "Assigned role in bulk");
+
+        Ok(BulkRoleAssignment {
+            users,
+            applied: true,
+        })
+    }
+}
@@ -0,0 +1,45 @@
+use async_trait::async_trait;
+use identify_domain::{NewRoleAttrs, Resource, Role};
+
+use crate::{
+    AccessControlled, Actor, Result, TransactionalUseCase, UnitOfWork,
+    role_contracts::Insert as _,
+};
+
+#[derive(Debug, Clone)]
+pub struct CreateRoleParams {
+    pub role_attrs: NewRoleAttrs,
+}
+
+impl AccessControlled for CreateRoleParams {
+    /// The role to be created, whose name policies can check as `resource.name`.
+    fn resource(&self) -> Resource {
+        Resource::new("role")
+            .with_attribute("name", self.role_attrs.name.trim())
+    }
+}
+
+/// Creates a role with a name no other role has.
+pub struct CreateRole;
+
+#[async_trait]
+impl<U: UnitOfWork> TransactionalUseCase<U> for CreateRole {
+    type Input = CreateRoleParams;
+    type Output = Role;
+
+    fn name(&self) -> &'static str {
+        "create_role"
+    }
+
+    async fn execute(
+        &self,
+        uow: &mut U,
+        _actor: &dyn Actor,
+        params: CreateRoleParams,
+    ) -> Result<Role> {
+        let role = Role::new(params.role_attrs)?;
+        uow.roles().insert(&role).await?;
+
+        Ok(role)
+    }
+}
@@ -0,0 +1,41 @@
+use async_trait::async_trait;
+use identify_domain::Resource;
+use uuid::Uuid;
+
+use crate::{
+    AccessControlled, Actor, Result, TransactionalUseCase, UnitOfWork,
+    role_contracts::Delete as _,
+};
+
+#[derive(Debug, Clone)]
+pub struct DeleteRoleParams {
+    pub id: Uuid,
+}
+
+impl AccessControlled for DeleteRoleParams {
+    fn resource(&self) -> Resource {
+        Resource::new("role").with_id(self.id)
+    }
+}
+
+/// Deletes a role, which is taken back from all the users it was assigned to.
+pub struct DeleteRole;
+
+#[async_trait]
+impl<U: UnitOfWork> TransactionalUseCase<U> for DeleteRole {
+    type Input = DeleteRoleParams;
+    type Output = ();
+
+    fn name(&self) -> &'static str {
+        "delete_role"
+    }
+
+    async fn execute(
+        &self,
+        uow: &mut U,
+        _actor: &dyn Actor,
+        params: DeleteRoleParams,
+    ) -> Result<()> {
+        uow.roles().delete(params.id).await
+    }
+}
@@ -0,0 +1,20 @@
+use identify_domain::Role;
+use tracing::{instrument, trace};
+use uuid::Uuid;
+
+use crate::{Result, role_contracts, use_cases::role::RoleUseCaseDeps};
+
+#[derive(Debug)]
+pub struct GetRoleParams {
+    pub id: Uuid,
+}
+
+#[instrument(skip(deps))]
+pub async fn get_role<R: role_contracts::Get>(
+    deps: RoleUseCaseDeps<'_, R>,
+    params: GetRoleParams,
+) -> Result<Role> {
+    trace!("Executing use case");
+
+    deps.repository.get(params.id).await
+}
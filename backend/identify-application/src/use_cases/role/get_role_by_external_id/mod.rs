@@ -0,0 +1,27 @@
+use identify_domain::Role;
+use tracing::{instrument, trace};
+
+use crate::{
+    ApplicationError, Result, role_contracts, use_cases::role::RoleUseCaseDeps,
+};
+
+#[derive(Debug)]
+pub struct GetRoleByExternalIdParams {
+    pub external_id: String,
+}
+
+/// Returns the role with the provided external ID.
+#[instrument(skip(deps))]
+pub async fn get_role_by_external_id<R: role_contracts::GetByExternalId>(
+    deps: RoleUseCaseDeps<'_, R>,
+    params: GetRoleByExternalIdParams,
+) -> Result<Role> {
+    trace!("Executing use case");
+
+    deps.repository
+        .get_by_external_id(&params.external_id)
+        .await?
+        .ok_or_else(|| {
+            ApplicationError::entity_not_found("Role", params.external_id)
+        })
+}
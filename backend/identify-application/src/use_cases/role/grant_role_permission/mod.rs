@@ -0,0 +1,51 @@
+use async_trait::async_trait;
+use identify_domain::{Resource, Role};
+use uuid::Uuid;
+
+use crate::{
+    AccessControlled, Actor, Result, TransactionalUseCase, UnitOfWork,
+    role_contracts::{Get as _, Update as _},
+};
+
+#[derive(Debug, Clone)]
+pub struct GrantRolePermissionParams {
+    pub id: Uuid,
+    /// Permission to grant, e.g. `users:read`.
+    pub permission: String,
+}
+
+impl AccessControlled for GrantRolePermissionParams {
+    /// The role, whose new permission policies can check as `resource.permission`.
+    fn resource(&self) -> Resource {
+        Resource::new("role")
+            .with_id(self.id)
+            .with_attribute("permission", self.permission.as_str())
+    }
+}
+
+/// Grants a permission to a role. Nothing is written if the role already has it.
+pub struct GrantRolePermission;
+
+#[async_trait]
+impl<U: UnitOfWork> TransactionalUseCase<U> for GrantRolePermission {
+    type Input = GrantRolePermissionParams;
+    type Output = Role;
+
+    fn name(&self) -> &'static str {
+        "grant_role_permission"
+    }
+
+    async fn execute(
+        &self,
+        uow: &mut U,
+        _actor: &dyn Actor,
+        params: GrantRolePermissionParams,
+    ) -> Result<Role> {
+        let mut role = uow.roles().get(params.id).await?;
+        if role.grant(&params.permission)? {
+            uow.roles().update(&role).await?;
+        }
+
+        Ok(role)
+    }
+}
@@ -0,0 +1,25 @@
+use identify_domain::RoleAssignment;
+use tracing::{instrument, trace};
+use uuid::Uuid;
+
+use crate::{Result, role_contracts, use_cases::role::RoleUseCaseDeps};
+
+#[derive(Debug)]
+pub struct ListRoleMembersParams {
+    pub role_id: Uuid,
+}
+
+/// Lists the users a role is assigned to, failing if the role doesn't exist.
+#[instrument(skip(deps))]
+pub async fn list_role_members<
+    R: role_contracts::Get + role_contracts::ListAssignments,
+>(
+    deps: RoleUseCaseDeps<'_, R>,
+    params: ListRoleMembersParams,
+) -> Result<Vec<RoleAssignment>> {
+    trace!("Executing use case");
+
+    deps.repository.get(params.role_id).await?;
+
+    deps.repository.list_assignments(params.role_id).await
+}
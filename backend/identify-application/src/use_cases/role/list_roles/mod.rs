@@ -0,0 +1,17 @@
+use identify_domain::Role;
+use tracing::{instrument, trace};
+
+use crate::{Result, role_contracts, use_cases::role::RoleUseCaseDeps};
+
+#[derive(Debug)]
+pub struct ListRolesParams;
+
+#[instrument(skip(deps))]
+pub async fn list_roles<R: role_contracts::ListAll>(
+    deps: RoleUseCaseDeps<'_, R>,
+    _params: ListRolesParams,
+) -> Result<Vec<Role>> {
+    trace!("Executing use case");
+
+    deps.repository.list_all().await
+}
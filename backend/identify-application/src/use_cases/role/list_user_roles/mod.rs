@@ -0,0 +1,21 @@
+use identify_domain::Role;
+use tracing::{instrument, trace};
+use uuid::Uuid;
+
+use crate::{Result, role_contracts, use_cases::role::RoleUseCaseDeps};
+
+#[derive(Debug)]
+pub struct ListUserRolesParams {
+    pub user_id: Uuid,
+}
+
+/// Lists the roles assigned to a user, which is empty for users that don't exist.
+#[instrument(skip(deps))]
+pub async fn list_user_roles<R: role_contracts::ListByUser>(
+    deps: RoleUseCaseDeps<'_, R>,
+    params: ListUserRolesParams,
+) -> Result<Vec<Role>> {
+    trace!("Executing use case");
+
+    deps.repository.list_by_user(params.user_id).await
+}
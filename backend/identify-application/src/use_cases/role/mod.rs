@@ -0,0 +1,25 @@
+pub mod assign_role;
+pub mod bulk_assign_roles;
+pub mod create_role;
+pub mod delete_role;
+pub mod get_role;
+pub mod get_role_by_external_id;
+pub mod grant_role_permission;
+pub mod list_role_members;
+pub mod list_roles;
+pub mod list_user_roles;
+pub mod put_role;
+pub mod revoke_role_permission;
+pub mod unassign_role;
+pub mod update_role;
+
+/// Dependencies of the use cases that read roles and their assignments.
+pub struct RoleUseCaseDeps<'a, R> {
+    repository: &'a mut R,
+}
+
+impl<'a, R> RoleUseCaseDeps<'a, R> {
+    pub fn new(repository: &'a mut R) -> Self {
+        RoleUseCaseDeps { repository }
+    }
+}
@@ -0,0 +1,80 @@
+use async_trait::async_trait;
+use identify_domain::{NewRoleAttrs, Resource, Role};
+
+use crate::{
+    AccessControlled, Actor, PutOutcome, Result, TransactionalUseCase,
+    UnitOfWork,
+    role_contracts::{GetByExternalId as _, Insert as _, Update as _},
+};
+
+#[derive(Debug, Clone)]
+pub struct PutRoleParams {
+    pub external_id: String,
+    pub name: String,
+    /// Removed if it's missing or blank.
+    pub description: Option<String>,
+    /// Replace the permissions of the role, in the order they're listed in.
+    pub permissions: Vec<String>,
+}
+
+impl AccessControlled for PutRoleParams {
+    /// The role to be created or updated, whose name policies can check as `resource.name`.
+    fn resource(&self) -> Resource {
+        Resource::new("role").with_attribute("name", self.name.trim())
+    }
+}
+
+/// Creates the role with the provided external ID, or brings the existing one to the provided
+/// state. Nothing is written if it's already in that state.
+///
+/// Calling it repeatedly with the same parameters has the same effect as calling it once, which
+/// is what infrastructure as code tools expect.
+pub struct PutRole;
+
+#[async_trait]
+impl<U: UnitOfWork> TransactionalUseCase<U> for PutRole {
+    type Input = PutRoleParams;
+    type Output = (Role, PutOutcome);
+
+    fn name(&self) -> &'static str {
+        "put_role"
+    }
+
+    async fn execute(
+        &self,
+        uow: &mut U,
+        _actor: &dyn Actor,
+        params: PutRoleParams,
+    ) -> Result<(Role, PutOutcome)> {
+        let PutRoleParams {
+            external_id,
+            name,
+            description,
+            permissions,
+        } = params;
+
+        let existing = uow.roles().get_by_external_id(&external_id).await?;
+        let Some(mut role) = existing else {
+            let role = Role::new(NewRoleAttrs {
+                name,
+                description,
+                permissions,
+                external_id: Some(external_id),
+            })?;
+            uow.roles().insert(&role).await?;
+
+            return Ok((role, PutOutcome::Created));
+        };
+
+        let renamed = role.rename(&name)?;
+        let described = role.describe(description)?;
+        let regranted = role.set_permissions(permissions)?;
+        if !(renamed || described || regranted) {
+            return Ok((role, PutOutcome::Unchanged));
+        }
+
+        uow.roles().update(&role).await?;
+
+        Ok((role, PutOutcome::Updated))
+    }
+}
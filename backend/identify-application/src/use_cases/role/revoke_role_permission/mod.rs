@@ -0,0 +1,51 @@
+use async_trait::async_trait;
+use identify_domain::{Resource, Role};
+use uuid::Uuid;
+
+use crate::{
+    AccessControlled, Actor, Result, TransactionalUseCase, UnitOfWork,
+    role_contracts::{Get as _, Update as _},
+};
+
+#[derive(Debug, Clone)]
+pub struct RevokeRolePermissionParams {
+    pub id: Uuid,
+    /// Permission to revoke, e.g. `users:read`.
+    pub permission: String,
+}
+
+impl AccessControlled for RevokeRolePermissionParams {
+    /// The role, whose revoked permission policies can check as `resource.permission`.
+    fn resource(&self) -> Resource {
+        Resource::new("role")
+            .with_id(self.id)
+            .with_attribute("permission", self.permission.as_str())
+    }
+}
+
+/// Revokes a permission from a role. Nothing is written if the role doesn't have it.
+pub struct RevokeRolePermission;
+
+#[async_trait]
+impl<U: UnitOfWork> TransactionalUseCase<U> for RevokeRolePermission {
+    type Input = RevokeRolePermissionParams;
+    type Output = Role;
+
+    fn name(&self) -> &'static str {
+        "revoke_role_permission"
+    }
+
+    async fn execute(
+        &self,
+        uow: &mut U,
+        _actor: &dyn Actor,
+        params: RevokeRolePermissionParams,
+    ) -> Result<Role> {
+        let mut role = uow.roles().get(params.id).await?;
+        if role.revoke(&params.permission) {
+            uow.roles().update(&role).await?;
+        }
+
+        Ok(role)
+    }
+}
@@ -0,0 +1,47 @@
+use async_trait::async_trait;
+use identify_domain::Resource;
+use uuid::Uuid;
+
+use crate::{
+    AccessControlled, Actor, Result, TransactionalUseCase, UnitOfWork,
+    role_contracts::{Get as _, Unassign as _},
+};
+
+#[derive(Debug, Clone)]
+pub struct UnassignRoleParams {
+    pub role_id: Uuid,
+    pub user_id: Uuid,
+}
+
+impl AccessControlled for UnassignRoleParams {
+    /// The role, whose former member policies can check as `resource.user_id`.
+    fn resource(&self) -> Resource {
+        Resource::new("role")
+            .with_id(self.role_id)
+            .with_attribute("user_id", self.user_id.to_string())
+    }
+}
+
+/// Takes a role back from a user. Returns whether the role was assigned to them.
+pub struct UnassignRole;
+
+#[async_trait]
+impl<U: UnitOfWork> TransactionalUseCase<U> for UnassignRole {
+    type Input = UnassignRoleParams;
+    type Output = bool;
+
+    fn name(&self) -> &'static str {
+        "unassign_role"
+    }
+
+    async fn execute(
+        &self,
+        uow: &mut U,
+        _actor: &dyn Actor,
+        params: UnassignRoleParams,
+    ) -> Result<bool> {
+        uow.roles().get(params.role_id).await?;
+
+        uow.roles().unassign(params.role_id, params.user_id).await
+    }
+}
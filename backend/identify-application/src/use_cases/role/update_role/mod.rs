@@ -0,0 +1,57 @@
+use async_trait::async_trait;
+use identify_domain::{Resource, Role};
+use uuid::Uuid;
+
+use crate::{
+    AccessControlled, Actor, Result, TransactionalUseCase, UnitOfWork,
+    role_contracts::{Get as _, Update as _},
+};
+
+#[derive(Debug, Clone)]
+pub struct UpdateRoleParams {
+    pub id: Uuid,
+    pub name: String,
+    /// Removed if it's missing or blank.
+    pub description: Option<String>,
+}
+
+impl AccessControlled for UpdateRoleParams {
+    fn resource(&self) -> Resource {
+        Resource::new("role").with_id(self.id)
+    }
+}
+
+/// Renames a role and changes its description. Nothing is written if they both stay the same.
+pub struct UpdateRole;
+
+#[async_trait]
+impl<U: UnitOfWork> TransactionalUseCase<U> for UpdateRole {
+    type Input = UpdateRoleParams;
+    type Output = Role;
+
+    fn name(&self) -> &'static str {
+        "update_role"
+    }
+
+    async fn execute(
+        &self,
+        uow: &mut U,
+        _actor: &dyn Actor,
+        params: UpdateRoleParams,
+    ) -> Result<Role> {
+        let UpdateRoleParams {
+            id,
+            name,
+            description,
+        } = params;
+
+        let mut role = uow.roles().get(id).await?;
+        let renamed = role.rename(&name)?;
+        let described = role.describe(description)?;
+        if renamed || described {
+            uow.roles().update(&role).await?;
+        }
+
+        Ok(role)
+    }
+}
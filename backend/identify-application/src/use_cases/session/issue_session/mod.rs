@@ -0,0 +1,60 @@
+use chrono::{TimeDelta, Utc};
+use tracing::{instrument, trace};
+use uuid::Uuid;
+
+use crate::{
+    LoginRecorder, Result, Session, SessionStore, TokenGenerator,
+    use_cases::session::SessionIssuanceDeps,
+};
+
+#[derive(Debug)]
+pub struct IssueSessionParams {
+    pub user_id: Uuid,
+    /// ID of the device the user logged in from.
+    pub device_id: Uuid,
+    /// How long the session lasts.
+    pub ttl: TimeDelta,
+}
+
+/// A session issued by [issue_session].
+#[derive(Debug)]
+pub struct IssuedSession {
+    /// Secret token of the session. Only its hash is stored, so it can't be returned again.
+    pub token: String,
+    pub session: Session,
+}
+
+/// Issues a session to a user who logged in from a device. The login is counted towards the
+/// statistics of the user once the session is stored, see
+/// [User::login_count](identify_domain::User::login_count).
+#[instrument(skip(deps))]
+pub async fn issue_session<S, T, L>(
+    deps: SessionIssuanceDeps<'_, S, T, L>,
+    params: IssueSessionParams,
+) -> Result<IssuedSession>
+where
+    S: SessionStore + ?Sized,
+    T: TokenGenerator + ?Sized,
+    L: LoginRecorder + ?Sized,
+{
+    trace!("Executing use case");
+
+    let SessionIssuanceDeps {
+        store,
+        tokens,
+        logins,
+    } = deps;
+
+    let token = tokens.generate();
+    let issued_at = Utc::now();
+    let session = Session {
+        user_id: params.user_id,
+        device_id: params.device_id,
+        issued_at,
+        expires_at: issued_at + params.ttl,
+    };
+    store.put(&tokens.hash(&token), &session).await?;
+    logins.record(session.user_id, issued_at);
+
+    Ok(IssuedSession { token, session })
+}
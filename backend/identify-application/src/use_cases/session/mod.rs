@@ -0,0 +1,39 @@
+pub mod issue_session;
+pub mod revoke_device_sessions;
+pub mod verify_session;
+
+/// Dependencies of the use cases that issue and check the sessions of users.
+///
+/// Sessions live in a [SessionStore](crate::SessionStore) rather than in a
+/// [UnitOfWork](crate::UnitOfWork), so that a deployment of several processes can share them
+/// without the database.
+pub struct SessionUseCaseDeps<'a, S: ?Sized, T: ?Sized> {
+    store: &'a S,
+    /// Generates the tokens of the sessions, and hashes them.
+    tokens: &'a T,
+}
+
+impl<'a, S: ?Sized, T: ?Sized> SessionUseCaseDeps<'a, S, T> {
+    pub fn new(store: &'a S, tokens: &'a T) -> Self {
+        SessionUseCaseDeps { store, tokens }
+    }
+}
+
+/// Dependencies of the use case that issues the sessions of users who log in, and counts their
+/// logins.
+pub struct SessionIssuanceDeps<'a, S: ?Sized, T: ?Sized, L: ?Sized> {
+    store: &'a S,
+    tokens: &'a T,
+    /// Counts the logins towards the statistics of users.
+    logins: &'a L,
+}
+
+impl<'a, S: ?Sized, T: ?Sized, L: ?Sized> SessionIssuanceDeps<'a, S, T, L> {
+    pub fn new(store: &'a S, tokens: &'a T, logins: &'a L) -> Self {
+        SessionIssuanceDeps {
+            store,
+            tokens,
+            logins,
+        }
+    }
+}
@@ -0,0 +1,25 @@
+use tracing::{instrument, trace};
+use uuid::Uuid;
+
+use crate::{Result, SessionStore, use_cases::session::SessionUseCaseDeps};
+
+#[derive(Debug)]
+pub struct RevokeDeviceSessionsParams {
+    pub device_id: Uuid,
+}
+
+/// Revokes the sessions issued for a device, e.g. once the device is revoked. Returns how many
+/// sessions were revoked.
+#[instrument(skip(deps))]
+pub async fn revoke_device_sessions<S, T>(
+    deps: SessionUseCaseDeps<'_, S, T>,
+    params: RevokeDeviceSessionsParams,
+) -> Result<u64>
+where
+    S: SessionStore + ?Sized,
+    T: ?Sized,
+{
+    trace!("Executing use case");
+
+    deps.store.revoke_device(params.device_id).await
+}
@@ -0,0 +1,34 @@
+use tracing::{instrument, trace};
+
+use crate::{
+    ApplicationError, Result, Session, SessionStore, TokenGenerator,
+    use_cases::session::SessionUseCaseDeps,
+};
+
+#[derive(Debug)]
+pub struct VerifySessionParams {
+    pub token: String,
+}
+
+/// Returns the session of a token, e.g. for a backend to tell who makes a request.
+///
+/// Fails with [EntityNotFound](ApplicationError::EntityNotFound) if the token is unknown, or
+/// its session expired or was revoked.
+#[instrument(skip(deps, params))]
+pub async fn verify_session<S, T>(
+    deps: SessionUseCaseDeps<'_, S, T>,
+    params: VerifySessionParams,
+) -> Result<Session>
+where
+    S: SessionStore + ?Sized,
+    T: TokenGenerator + ?Sized,
+{
+    trace!("Executing use case");
+
+    let SessionUseCaseDeps { store, tokens } = deps;
+
+    let token_hash = tokens.hash(&params.token);
+    store.get(&token_hash).await?.ok_or_else(|| {
+        ApplicationError::entity_not_found("Session", token_hash)
+    })
+}
@@ -0,0 +1,32 @@
+use identify_domain::SigningKey;
+use tracing::{instrument, trace};
+
+use crate::{
+    Result, SigningKeyGenerator, signing_key_contracts,
+    use_cases::signing_key::SigningKeyRotationDeps,
+};
+
+#[derive(Debug)]
+pub struct EnsureSigningKeyParams;
+
+/// Returns the active signing key, generating the first one if there's none yet.
+#[instrument(skip(deps))]
+pub async fn ensure_signing_key<R, G>(
+    deps: SigningKeyRotationDeps<'_, R, G>,
+    _params: EnsureSigningKeyParams,
+) -> Result<SigningKey>
+where
+    R: signing_key_contracts::GetActive + signing_key_contracts::Insert,
+    G: SigningKeyGenerator,
+{
+    trace!("Executing use case");
+
+    if let Some(key) = deps.repository.get_active().await? {
+        return Ok(key);
+    }
+
+    let key = SigningKey::new(deps.generator.generate()?)?;
+    deps.repository.insert(&key).await?;
+
+    Ok(key)
+}
@@ -0,0 +1,24 @@
+use identify_domain::SigningKey;
+use tracing::{instrument, trace};
+
+use crate::{
+    Result, signing_key_contracts,
+    use_cases::signing_key::SigningKeyUseCaseDeps,
+};
+
+#[derive(Debug)]
+pub struct ListPublishedSigningKeysParams;
+
+/// Lists the keys tokens may have been signed with, i.e. the active one and the retired ones
+/// that haven't been deleted yet.
+#[instrument(skip(deps))]
+pub async fn list_published_signing_keys<
+    R: signing_key_contracts::ListPublished,
+>(
+    deps: SigningKeyUseCaseDeps<'_, R>,
+    _params: ListPublishedSigningKeysParams,
+) -> Result<Vec<SigningKey>> {
+    trace!("Executing use case");
+
+    deps.repository.list_published().await
+}
@@ -0,0 +1,28 @@
+pub mod ensure_signing_key;
+pub mod list_published_signing_keys;
+pub mod rotate_signing_key;
+
+pub struct SigningKeyUseCaseDeps<'a, R> {
+    repository: &'a mut R,
+}
+
+impl<'a, R> SigningKeyUseCaseDeps<'a, R> {
+    pub fn new(repository: &'a mut R) -> Self {
+        SigningKeyUseCaseDeps { repository }
+    }
+}
+
+/// Dependencies of the use cases that create new signing keys.
+pub struct SigningKeyRotationDeps<'a, R, G> {
+    repository: &'a mut R,
+    generator: &'a G,
+}
+
+impl<'a, R, G> SigningKeyRotationDeps<'a, R, G> {
+    pub fn new(repository: &'a mut R, generator: &'a G) -> Self {
+        SigningKeyRotationDeps {
+            repository,
+            generator,
+        }
+    }
+}
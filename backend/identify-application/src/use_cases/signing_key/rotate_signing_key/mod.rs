@@ -0,0 +1,52 @@
+use chrono::{TimeDelta, Utc};
+use identify_domain::SigningKey;
+use tracing::{debug, instrument, trace};
+
+use crate::{
+    Result, SigningKeyGenerator, signing_key_contracts,
+    use_cases::signing_key::SigningKeyRotationDeps,
+};
+
+#[derive(Debug)]
+pub struct RotateSigningKeyParams {
+    /// The active key is kept if it's younger than this, e.g. so that replicas sharing a
+    /// database rotate it only once per schedule. It's always replaced if it's not set.
+    pub min_age: Option<TimeDelta>,
+}
+
+/// Replaces the active signing key with a freshly generated one.
+///
+/// The previous key is retired rather than deleted, so that the tokens it signed can still be
+/// verified. Returns the active key.
+#[instrument(skip(deps))]
+pub async fn rotate_signing_key<R, G>(
+    deps: SigningKeyRotationDeps<'_, R, G>,
+    params: RotateSigningKeyParams,
+) -> Result<SigningKey>
+where
+    R: signing_key_contracts::GetActive
+        + signing_key_contracts::Insert
+        + signing_key_contracts::Update,
+    G: SigningKeyGenerator,
+{
+    trace!("Executing use case");
+
+    let RotateSigningKeyParams { min_age } = params;
+
+    if let Some(mut previous) = deps.repository.get_active().await? {
+        if let Some(min_age) = min_age
+            && *previous.created_at() > Utc::now() - min_age
+        {
+            debug!(key_id = %previous.id(), "The active signing key is too young to be rotated");
+            return Ok(previous);
+        }
+
+        previous.retire()?;
+        deps.repository.update(&previous).await?;
+    }
+
+    let key = SigningKey::new(deps.generator.generate()?)?;
+    deps.repository.insert(&key).await?;
+
+    Ok(key)
+}
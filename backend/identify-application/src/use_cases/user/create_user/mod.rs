@@ -1,24 +1,128 @@
-use identify_domain::{NewUserAttrs, User};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use identify_domain::{
+    IdentifierPolicy, NewUserAttrs, QuotaPolicy, Resource, User,
+    UserLifecycleTransition, UsernameStrategy,
+};
 use tracing::{instrument, trace};
+use uuid::Uuid;
 
-use crate::{Result, use_cases::user::UserUseCaseDeps, user_contracts};
+use crate::{
+    AccessControlled, Actor, Result, System, TransactionalUseCase, UnitOfWork,
+    use_cases::{
+        organization::ensure_room_for_user,
+        user::{
+            UserLifecycleUseCaseDeps, assign_username, emit_user_event,
+            ensure_email_is_free,
+        },
+    },
+    user_contracts::Insert as _,
+};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct CreateUserParams {
     pub user_attrs: NewUserAttrs,
+    /// Username the user asked for. One is generated with the strategy if it's not set.
+    pub username: Option<String>,
+    pub username_strategy: UsernameStrategy,
+    /// Decides which usernames can be chosen.
+    pub identifier_policy: Arc<IdentifierPolicy>,
+    /// Organization the user is created to join, e.g. by accepting an invitation, whose quota
+    /// must have room for them. The caller makes them a member.
+    pub organization_id: Option<Uuid>,
+    /// Limits how many users the organization can have.
+    pub quota_policy: QuotaPolicy,
+}
+
+impl AccessControlled for CreateUserParams {
+    /// The user to be created, whose email domain policies can check as
+    /// `resource.email_domain`.
+    fn resource(&self) -> Resource {
+        let domain = self
+            .user_attrs
+            .email
+            .rsplit_once('@')
+            .map(|(_, domain)| domain.trim().to_lowercase())
+            .unwrap_or_default();
+
+        Resource::new("user").with_attribute("email_domain", domain)
+    }
+}
+
+/// Creates a user with a free username, and emits the event announcing them.
+///
+/// Fails if the email is taken, including by a user whose ID was generated from it by an older
+/// version of the recipe, see [UserId](identify_domain::UserId), and with
+/// [QuotaExceeded](crate::ApplicationError::QuotaExceeded) if the organization the user is
+/// created to join can't have any more users.
+pub struct CreateUser;
+
+#[async_trait]
+impl<U: UnitOfWork> TransactionalUseCase<U> for CreateUser {
+    type Input = CreateUserParams;
+    type Output = User;
+
+    fn name(&self) -> &'static str {
+        "create_user"
+    }
+
+    async fn execute(
+        &self,
+        uow: &mut U,
+        _actor: &dyn Actor,
+        params: CreateUserParams,
+    ) -> Result<User> {
+        let CreateUserParams {
+            user_attrs,
+            username,
+            username_strategy,
+            identifier_policy,
+            organization_id,
+            quota_policy,
+        } = params;
+
+        // Checked before anything is stored, so that no user is left without the organization
+        // they were created to join.
+        if let Some(organization_id) = organization_id {
+            ensure_room_for_user(uow, &quota_policy, organization_id).await?;
+        }
+        // Taken emails are refused up front, before a username is generated for them.
+        ensure_email_is_free(&mut uow.users(), &user_attrs.email).await?;
+        let username = assign_username(
+            &mut uow.users(),
+            username.as_deref(),
+            username_strategy,
+            &identifier_policy,
+            &user_attrs,
+        )
+        .await?;
+        let user = User::new(user_attrs, username);
+        uow.users().insert(&user).await?;
+        emit_user_event(
+            &mut uow.user_events(),
+            user.id(),
+            UserLifecycleTransition::Created,
+            user.version(),
+        )
+        .await?;
+
+        Ok(user)
+    }
 }
 
+/// Executes [CreateUser] on behalf of the [System] in the provided unit of work, and commits
+/// it.
 #[instrument(skip(deps))]
-pub async fn create_user<R: user_contracts::Insert>(
-    deps: UserUseCaseDeps<'_, R>,
+pub async fn create_user<U: UnitOfWork>(
+    deps: UserLifecycleUseCaseDeps<U>,
     params: CreateUserParams,
 ) -> Result<User> {
     trace!("Executing use case");
 
-    let CreateUserParams { user_attrs } = params;
-
-    let user = User::new(user_attrs);
-    deps.repository.insert(&user).await?;
+    let mut uow = deps.unit_of_work;
+    let user = CreateUser.execute(&mut uow, &System, params).await?;
+    uow.commit().await?;
 
     Ok(user)
 }
@@ -0,0 +1,134 @@
+use std::{collections::HashSet, sync::Arc};
+
+use async_trait::async_trait;
+use identify_domain::{
+    IdentifierPolicy, NewUserAttrs, User, UserLifecycleTransition, Username,
+    UsernameStrategy,
+};
+use tracing::{instrument, trace};
+
+use crate::{
+    ApplicationError, Result, UnitOfWork,
+    use_cases::user::{
+        UserLifecycleUseCaseDeps, assign_username, emit_user_event,
+    },
+    user_contracts::{self, ExistsByEmails, ExistsByUsername, InsertMany as _},
+};
+
+#[derive(Debug, Clone)]
+pub struct CreateUsersParams {
+    /// Attributes of the users, along with the usernames they asked for.
+    pub users: Vec<(NewUserAttrs, Option<String>)>,
+    pub username_strategy: UsernameStrategy,
+    /// Decides which usernames can be chosen.
+    pub identifier_policy: Arc<IdentifierPolicy>,
+}
+
+/// Creates many users at once, e.g. when importing them, and emits the events announcing them.
+///
+/// Every user gets a free username like with [create_user](crate::create_user), which no other
+/// user of the batch has taken either, and all of them are inserted together. Nothing is
+/// created if any of them can't be, e.g. if their emails are taken, in which case the error
+/// names every row whose email is.
+#[instrument(skip(deps, params), fields(users = params.users.len()))]
+pub async fn create_users<U: UnitOfWork>(
+    deps: UserLifecycleUseCaseDeps<U>,
+    params: CreateUsersParams,
+) -> Result<Vec<User>> {
+    trace!("Executing use case");
+
+    let CreateUsersParams {
+        users: requested,
+        username_strategy,
+        identifier_policy,
+    } = params;
+    let mut uow = deps.unit_of_work;
+
+    ensure_emails_are_free(&mut uow.users(), &requested).await?;
+
+    let mut taken = HashSet::new();
+    let mut users = Vec::with_capacity(requested.len());
+    for (user_attrs, username) in requested {
+        let username = assign_username(
+            &mut BatchUsernames {
+                users: &mut uow.users(),
+                taken: &taken,
+            },
+            username.as_deref(),
+            username_strategy,
+            &identifier_policy,
+            &user_attrs,
+        )
+        .await?;
+        taken.insert(username.to_string());
+        users.push(User::new(user_attrs, username));
+    }
+
+    uow.users().insert_many(&users).await?;
+    for user in &users {
+        emit_user_event(
+            &mut uow.user_events(),
+            user.id(),
+            UserLifecycleTransition::Created,
+            user.version(),
+        )
+        .await?;
+    }
+
+    uow.commit().await?;
+
+    Ok(users)
+}
+
+/// Fails, naming the rows of the batch (counted from 1) along with their emails, if any of the
+/// emails are taken. They're checked all at once, like
+/// [ensure_email_is_free](crate::use_cases::user::ensure_email_is_free) checks a single one.
+async fn ensure_emails_are_free<R: ExistsByEmails>(
+    users: &mut R,
+    requested: &[(NewUserAttrs, Option<String>)],
+) -> Result<()> {
+    let emails = requested
+        .iter()
+        .map(|(attrs, _)| attrs.email.as_str())
+        .collect::<Vec<_>>();
+    let conflicts = users
+        .exists_by_emails(&emails)
+        .await?
+        .into_iter()
+        .zip(&emails)
+        .enumerate()
+        .filter(|(_, (taken, _))| *taken)
+        .map(|(i, (_, email))| format!("{email} (row {})", i + 1))
+        .collect::<Vec<_>>();
+
+    if !conflicts.is_empty() {
+        return Err(ApplicationError::entity_already_exists(
+            "User".to_owned(),
+            format!("Emails are already taken: {}", conflicts.join(", ")),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Usernames of the stored users along with the ones assigned earlier in the same batch.
+struct BatchUsernames<'a, R> {
+    users: &'a mut R,
+    taken: &'a HashSet<String>,
+}
+
+#[async_trait]
+impl<R: ExistsByUsername + Send> user_contracts::ExistsByUsername
+    for BatchUsernames<'_, R>
+{
+    async fn exists_by_username(
+        &mut self,
+        username: &Username,
+    ) -> Result<bool> {
+        if self.taken.contains(username.as_str()) {
+            return Ok(true);
+        }
+
+        self.users.exists_by_username(username).await
+    }
+}
@@ -0,0 +1,121 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use identify_domain::{DomainError, Resource, User, UserLifecycleTransition};
+use tracing::debug;
+use uuid::Uuid;
+
+use crate::{
+    AccessControlled, Actor, ApplicationError, BlobStorage, Result,
+    TransactionalUseCase, UnitOfWork,
+    device_contracts::DeleteAll as _,
+    email_alias_contracts::DeleteAll as _,
+    email_change_contracts::DeleteAll as _,
+    invitation_contracts::DeleteAll as _,
+    phone_verification_contracts::DeleteAll as _,
+    use_cases::{avatar::avatar_key, user::emit_user_event},
+    user_attribute_contracts::DeleteAll as _,
+    user_contracts::{Erase as _, Get as _},
+};
+
+#[derive(Debug, Clone)]
+pub struct EraseUserParams {
+    pub id: Uuid,
+    /// Current email of the user, repeated by the caller to confirm that the erasure, which
+    /// can't be undone, is meant for them.
+    pub confirmation: String,
+    /// Version of the user the caller based the erasure on. The erasure fails with
+    /// [ApplicationError::StaleVersion] if the user has changed since.
+    pub expected_version: Option<u32>,
+}
+
+impl AccessControlled for EraseUserParams {
+    fn resource(&self) -> Resource {
+        Resource::new("user").with_id(self.id)
+    }
+}
+
+/// Erases a user on request, e.g. to honour the right to erasure.
+///
+/// The user is [anonymized](User::erase) rather than deleted, so that memberships and events
+/// keep referring to them. Their password hash, attributes, devices, accepted invitations,
+/// verifications of phone numbers and avatar are deleted, and the erasure is recorded along with the actor who requested it.
+pub struct EraseUser {
+    /// Storage of the avatars, which is deleted from once the erasure is persisted.
+    pub storage: Arc<dyn BlobStorage>,
+}
+
+#[async_trait]
+impl<U: UnitOfWork> TransactionalUseCase<U> for EraseUser {
+    type Input = EraseUserParams;
+    type Output = User;
+
+    fn name(&self) -> &'static str {
+        "erase_user"
+    }
+
+    async fn execute(
+        &self,
+        uow: &mut U,
+        actor: &dyn Actor,
+        params: EraseUserParams,
+    ) -> Result<User> {
+        let EraseUserParams {
+            id,
+            confirmation,
+            expected_version,
+        } = params;
+
+        let mut user = uow.users().get(id).await?;
+        user.ensure_not_erased()?;
+        if let Some(expected) = expected_version
+            && expected != user.version()
+        {
+            return Err(ApplicationError::StaleVersion {
+                entity: "User".to_owned(),
+                id: id.to_string(),
+                expected,
+                actual: user.version(),
+            });
+        }
+        if !confirmation.trim().eq_ignore_ascii_case(user.email()) {
+            return Err(DomainError::validation(
+                "User",
+                "the confirmation must be the email of the user",
+            )
+            .into());
+        }
+
+        user.erase()?;
+        uow.users().erase(&user, actor.subject()).await?;
+        let attributes = uow.user_attributes().delete_all(id).await?;
+        let devices = uow.devices().delete_all(id).await?;
+        let invitations = uow.invitations().delete_all(id).await?;
+        let email_changes = uow.email_changes().delete_all(id).await?;
+        let email_aliases = uow.email_aliases().delete_all(id).await?;
+        let phone_verifications =
+            uow.phone_verifications().delete_all(id).await?;
+        emit_user_event(
+            &mut uow.user_events(),
+            user.id(),
+            UserLifecycleTransition::Deleted,
+            user.version(),
+        )
+        .await?;
+
+        // Blobs aren't part of the transaction, so a failure here discards the erasure and it
+        // can simply be requested again.
+        self.storage.delete(&avatar_key(id)).await?;
+        debug!(
+            attributes,
+            devices,
+            invitations,
+            email_changes,
+            email_aliases,
+            phone_verifications,
+            "Erased user"
+        );
+
+        Ok(user)
+    }
+}
@@ -0,0 +1,25 @@
+use tracing::{instrument, trace};
+
+use crate::{
+    Sort,
+    use_cases::user::UserUseCaseDeps,
+    user_contracts::{self, UserFilter, UserSortField, UserStream},
+};
+
+#[derive(Debug)]
+pub struct ExportUsersParams {
+    pub filter: UserFilter,
+    pub sort: Vec<Sort<UserSortField>>,
+}
+
+/// Streams all the users matching the filter, unlike [list_users](super::list_users) which
+/// returns a single page of them.
+#[instrument(skip(deps))]
+pub fn export_users<R: user_contracts::Scan>(
+    deps: UserUseCaseDeps<'_, R>,
+    params: ExportUsersParams,
+) -> UserStream {
+    trace!("Executing use case");
+
+    deps.repository.scan(&params.filter, &params.sort)
+}
@@ -0,0 +1,20 @@
+use identify_domain::User;
+use tracing::{instrument, trace};
+use uuid::Uuid;
+
+use crate::{Result, use_cases::user::UserUseCaseDeps, user_contracts};
+
+#[derive(Debug)]
+pub struct GetUserParams {
+    pub id: Uuid,
+}
+
+#[instrument(skip(deps))]
+pub async fn get_user<R: user_contracts::Get>(
+    deps: UserUseCaseDeps<'_, R>,
+    params: GetUserParams,
+) -> Result<User> {
+    trace!("Executing use case");
+
+    deps.repository.get(params.id).await
+}
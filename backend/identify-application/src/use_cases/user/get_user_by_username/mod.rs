@@ -0,0 +1,30 @@
+use identify_domain::{User, Username};
+use tracing::{instrument, trace};
+
+use crate::{
+    ApplicationError, Result, use_cases::user::UserUseCaseDeps, user_contracts,
+};
+
+#[derive(Debug)]
+pub struct GetUserByUsernameParams {
+    /// Username of the user, matched regardless of the case.
+    pub username: String,
+}
+
+/// Returns the user with the username.
+///
+/// Usernames that aren't valid can't belong to anyone, so the user isn't found rather than the
+/// username being rejected.
+#[instrument(skip(deps))]
+pub async fn get_user_by_username<R: user_contracts::GetByUsername>(
+    deps: UserUseCaseDeps<'_, R>,
+    params: GetUserByUsernameParams,
+) -> Result<User> {
+    trace!("Executing use case");
+
+    let username = Username::parse(&params.username).map_err(|_| {
+        ApplicationError::entity_not_found("User", params.username.trim())
+    })?;
+
+    deps.repository.get_by_username(&username).await
+}
@@ -0,0 +1,26 @@
+use identify_domain::User;
+use tracing::{instrument, trace};
+use uuid::Uuid;
+
+use crate::{Result, use_cases::user::UserUseCaseDeps, user_contracts};
+
+#[derive(Debug)]
+pub struct GetUsersParams {
+    pub ids: Vec<Uuid>,
+}
+
+/// Returns the users with the provided IDs, in no particular order. Users that don't exist are
+/// left out.
+#[instrument(skip(deps))]
+pub async fn get_users<R: user_contracts::GetMany>(
+    deps: UserUseCaseDeps<'_, R>,
+    params: GetUsersParams,
+) -> Result<Vec<User>> {
+    trace!("Executing use case");
+
+    if params.ids.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    deps.repository.get_many(&params.ids).await
+}
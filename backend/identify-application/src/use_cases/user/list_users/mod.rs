@@ -0,0 +1,23 @@
+use identify_domain::User;
+use tracing::{instrument, trace};
+
+use crate::{
+    Paginated, Result,
+    use_cases::user::UserUseCaseDeps,
+    user_contracts::{self, UserListQuery},
+};
+
+#[derive(Debug)]
+pub struct ListUsersParams {
+    pub query: UserListQuery,
+}
+
+#[instrument(skip(deps))]
+pub async fn list_users<R: user_contracts::List>(
+    deps: UserUseCaseDeps<'_, R>,
+    params: ListUsersParams,
+) -> Result<Paginated<User>> {
+    trace!("Executing use case");
+
+    deps.repository.list(&params.query).await
+}
@@ -0,0 +1,162 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use identify_domain::{DomainError, Resource, User, UserLifecycleTransition};
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    AccessControlled, Actor, BlobStorage, Result, SessionStore,
+    TransactionalUseCase, UnitOfWork,
+    consent_contracts::Reassign as _,
+    device_contracts::Reassign as _,
+    email_alias_contracts::DeleteAll as _,
+    membership_contracts::Reassign as _,
+    phone_verification_contracts::DeleteAll as _,
+    role_contracts::Reassign as _,
+    use_cases::{avatar::avatar_key, user::emit_user_event},
+    user_attribute_contracts::Reassign as _,
+    user_contracts::{Erase as _, Get as _, RecordMerge as _},
+};
+
+#[derive(Debug, Clone)]
+pub struct MergeUsersParams {
+    /// ID of the user who is kept.
+    pub primary_id: Uuid,
+    /// ID of the user who duplicates the primary one and is erased.
+    pub duplicate_id: Uuid,
+}
+
+impl AccessControlled for MergeUsersParams {
+    /// The duplicate, whose primary user policies can check as `resource.primary_id`.
+    fn resource(&self) -> Resource {
+        Resource::new("user")
+            .with_id(self.duplicate_id)
+            .with_attribute("primary_id", self.primary_id.to_string())
+    }
+}
+
+/// Outcome of [MergeUsers].
+#[derive(Debug)]
+pub struct MergedUsers {
+    pub primary: User,
+    /// The duplicate, which has been erased.
+    pub duplicate: User,
+    /// How many memberships, devices, attributes, consents, roles and sessions were moved to
+    /// the primary user.
+    pub memberships: u64,
+    pub devices: u64,
+    pub attributes: u64,
+    pub consents: u64,
+    pub roles: u64,
+    pub sessions: u64,
+}
+
+/// Merges a duplicate user into the primary one, e.g. when the same person signed up twice
+/// through different providers.
+///
+/// The memberships, devices, attributes, consents, roles and sessions of the duplicate are
+/// moved to the primary user, who keeps their own where both have one, e.g. a membership in the
+/// same organization. The duplicate is then [erased](User::erase) and the merge is recorded
+/// along with the actor who requested it, so that the duplicate can still be traced to the
+/// primary user, as can the users merged into the duplicate before. Only the duplicate announces
+/// a [Merged](UserLifecycleTransition::Merged) transition.
+pub struct MergeUsers {
+    /// Storage of the avatars, which the avatar of the duplicate is deleted from.
+    pub storage: Arc<dyn BlobStorage>,
+    /// Store of the sessions, whose sessions of the duplicate are moved to the primary user.
+    pub sessions: Arc<dyn SessionStore>,
+}
+
+#[async_trait]
+impl<U: UnitOfWork> TransactionalUseCase<U> for MergeUsers {
+    type Input = MergeUsersParams;
+    type Output = MergedUsers;
+
+    fn name(&self) -> &'static str {
+        "merge_users"
+    }
+
+    async fn execute(
+        &self,
+        uow: &mut U,
+        actor: &dyn Actor,
+        params: MergeUsersParams,
+    ) -> Result<MergedUsers> {
+        let MergeUsersParams {
+            primary_id,
+            duplicate_id,
+        } = params;
+        if primary_id == duplicate_id {
+            return Err(DomainError::validation(
+                "User",
+                "a user can't be merged into themselves",
+            )
+            .into());
+        }
+
+        let primary = uow.users().get(primary_id).await?;
+        primary.ensure_not_erased()?;
+        let mut duplicate = uow.users().get(duplicate_id).await?;
+        duplicate.ensure_not_erased()?;
+
+        let memberships =
+            uow.memberships().reassign(duplicate_id, primary_id).await?;
+        let devices = uow.devices().reassign(duplicate_id, primary_id).await?;
+        let attributes = uow
+            .user_attributes()
+            .reassign(duplicate_id, primary_id)
+            .await?;
+        let consents =
+            uow.consents().reassign(duplicate_id, primary_id).await?;
+        let roles = uow.roles().reassign(duplicate_id, primary_id).await?;
+
+        duplicate.erase()?;
+        uow.users().erase(&duplicate, actor.subject()).await?;
+        // The aliases and the verifications hold emails and phone numbers of the duplicate,
+        // which are erased along with it.
+        uow.email_aliases().delete_all(duplicate_id).await?;
+        uow.phone_verifications().delete_all(duplicate_id).await?;
+        uow.users()
+            .record_merge(duplicate_id, primary_id, actor.subject(), Utc::now())
+            .await?;
+        emit_user_event(
+            &mut uow.user_events(),
+            duplicate_id,
+            UserLifecycleTransition::Merged,
+            duplicate.version(),
+        )
+        .await?;
+
+        // Blobs aren't part of the transaction, so a failure here discards the merge and it can
+        // simply be requested again.
+        self.storage.delete(&avatar_key(duplicate_id)).await?;
+        // Neither are sessions, which are moved last so that only the commit can fail once
+        // they're moved. The duplicate can't log in anymore, so nothing is issued to them past
+        // this point.
+        let sessions = self.sessions.reassign(duplicate_id, primary_id).await?;
+        info!(
+            %primary_id,
+            %duplicate_id,
+            memberships,
+            devices,
+            attributes,
+            consents,
+            roles,
+            sessions,
+            "Merged users"
+        );
+
+        Ok(MergedUsers {
+            primary,
+            duplicate,
+            memberships,
+            devices,
+            attributes,
+            consents,
+            roles,
+            sessions,
+        })
+    }
+}
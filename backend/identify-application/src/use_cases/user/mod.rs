@@ -1,5 +1,154 @@
 pub mod create_user;
+pub mod create_users;
+pub mod erase_user;
+pub mod export_users;
+pub mod get_user;
+pub mod get_user_by_username;
+pub mod get_users;
+pub mod list_users;
+pub mod merge_users;
+pub mod search_users;
+pub mod set_user_password;
+pub mod update_user;
+
+use identify_domain::{
+    IdentifierPolicy, NewUserAttrs, NewUserEventAttrs, UserEvent,
+    UserLifecycleTransition, Username, UsernameStrategy,
+};
+use tracing::debug;
+use uuid::Uuid;
+
+use crate::{
+    ApplicationError, Result, user_contracts, user_contracts::UserSpec,
+    user_event_contracts,
+};
+
+/// Most generated usernames tried before the provisioning gives up.
+const MAX_USERNAME_ATTEMPTS: usize = 20;
 
 pub struct UserUseCaseDeps<'a, R> {
-    repository: &'a R,
+    repository: &'a mut R,
+}
+
+impl<'a, R> UserUseCaseDeps<'a, R> {
+    pub fn new(repository: &'a mut R) -> Self {
+        UserUseCaseDeps { repository }
+    }
+}
+
+/// Dependencies of the use cases that move users through their lifecycle.
+///
+/// Every transition emits an event, so it's made within a [UnitOfWork](crate::UnitOfWork) that
+/// persists the change and the event together.
+pub struct UserLifecycleUseCaseDeps<U> {
+    unit_of_work: U,
+}
+
+impl<U> UserLifecycleUseCaseDeps<U> {
+    pub fn new(unit_of_work: U) -> Self {
+        UserLifecycleUseCaseDeps { unit_of_work }
+    }
+}
+
+/// Dependencies of the use cases that manage the credentials of users.
+pub struct UserPasswordUseCaseDeps<'a, U, H> {
+    unit_of_work: U,
+    hasher: &'a H,
+}
+
+impl<'a, U, H> UserPasswordUseCaseDeps<'a, U, H> {
+    pub fn new(unit_of_work: U, hasher: &'a H) -> Self {
+        UserPasswordUseCaseDeps {
+            unit_of_work,
+            hasher,
+        }
+    }
+}
+
+/// Emits an event announcing a lifecycle transition of a user.
+///
+/// Event IDs are derived from the transition, so retrying it doesn't emit a second event.
+pub(crate) async fn emit_user_event<E: user_event_contracts::Emit>(
+    events: &mut E,
+    user_id: Uuid,
+    transition: UserLifecycleTransition,
+    version: u32,
+) -> Result<()> {
+    let event = UserEvent::new(NewUserEventAttrs {
+        user_id,
+        transition,
+        version,
+    })?;
+
+    if !events.emit(&event).await? {
+        debug!(event_id = %event.id(), %transition, "Skipping duplicate user event");
+    }
+
+    Ok(())
+}
+
+/// Picks the username of a user that is being provisioned.
+///
+/// The requested username is used as is if it's free and the policy allows it. Otherwise,
+/// usernames are generated with the strategy until a free and allowed one is found.
+pub(crate) async fn assign_username<R: user_contracts::ExistsByUsername>(
+    users: &mut R,
+    requested: Option<&str>,
+    strategy: UsernameStrategy,
+    policy: &IdentifierPolicy,
+    user_attrs: &NewUserAttrs,
+) -> Result<Username> {
+    if let Some(requested) = requested {
+        let username = Username::parse(requested)?;
+        policy.check("Username", username.as_str())?;
+        if users.exists_by_username(&username).await? {
+            return Err(ApplicationError::entity_already_exists(
+                "User".to_owned(),
+                format!("username '{username}' is already taken"),
+            ));
+        }
+
+        return Ok(username);
+    }
+
+    let candidates = strategy
+        .candidates(
+            &user_attrs.email,
+            &user_attrs.first_name,
+            user_attrs.last_name.as_deref(),
+        )
+        .take(MAX_USERNAME_ATTEMPTS);
+    for username in candidates {
+        if policy.check("Username", username.as_str()).is_err() {
+            debug!(%username, "Username isn't allowed, trying the next one");
+            continue;
+        }
+        if !users.exists_by_username(&username).await? {
+            return Ok(username);
+        }
+        debug!(%username, "Username is taken, trying the next one");
+    }
+
+    Err(ApplicationError::entity_already_exists(
+        "User".to_owned(),
+        format!(
+            "no free username found in {MAX_USERNAME_ATTEMPTS} attempts with the {strategy} strategy"
+        ),
+    ))
+}
+
+/// Fails if any user, including an erased one or one whose ID was generated from the email by an
+/// older version of the recipe, has the email as their own or as an alias.
+pub(crate) async fn ensure_email_is_free<R: user_contracts::Exists>(
+    users: &mut R,
+    email: &str,
+) -> Result<()> {
+    if users.exists(&UserSpec::by_email(email)).await? {
+        return Err(ApplicationError::entity_already_exists(
+            "User",
+            "Email is already taken",
+        ));
+    }
+
+    Ok(())
 }
@@ -0,0 +1,23 @@
+use identify_domain::User;
+use tracing::{instrument, trace};
+
+use crate::{
+    Paginated, Result,
+    use_cases::user::UserUseCaseDeps,
+    user_contracts::{self, UserSearchQuery},
+};
+
+#[derive(Debug)]
+pub struct SearchUsersParams {
+    pub query: UserSearchQuery,
+}
+
+#[instrument(skip(deps))]
+pub async fn search_users<R: user_contracts::Search>(
+    deps: UserUseCaseDeps<'_, R>,
+    params: SearchUsersParams,
+) -> Result<Paginated<User>> {
+    trace!("Executing use case");
+
+    deps.repository.search(&params.query).await
+}
@@ -0,0 +1,40 @@
+use identify_domain::Password;
+use tracing::{instrument, trace};
+use uuid::Uuid;
+
+use crate::{
+    PasswordHasher, Result, UnitOfWork,
+    use_cases::user::UserPasswordUseCaseDeps,
+    user_contracts::{Get as _, SetPasswordHash as _},
+};
+
+#[derive(Debug)]
+pub struct SetUserPasswordParams {
+    pub id: Uuid,
+    pub password: String,
+}
+
+/// Replaces the password of a user with a new one.
+#[instrument(skip(deps))]
+pub async fn set_user_password<U: UnitOfWork, H: PasswordHasher>(
+    deps: UserPasswordUseCaseDeps<'_, U, H>,
+    params: SetUserPasswordParams,
+) -> Result<()> {
+    trace!("Executing use case");
+
+    let SetUserPasswordParams { id, password } = params;
+    let mut uow = deps.unit_of_work;
+
+    let password = Password::parse(&password)?;
+    let user = uow.users().get(id).await?;
+    user.ensure_not_erased()?;
+
+    let password_hash = deps.hasher.hash(&password)?;
+    uow.users()
+        .set_password_hash(user.id(), &password_hash)
+        .await?;
+
+    uow.commit().await?;
+
+    Ok(())
+}
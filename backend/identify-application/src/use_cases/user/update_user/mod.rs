@@ -0,0 +1,78 @@
+use identify_domain::{Locale, TimeZone, User, UserLifecycleTransition};
+use tracing::{instrument, trace};
+use uuid::Uuid;
+
+use crate::{
+    ApplicationError, Result, UnitOfWork,
+    use_cases::user::{UserLifecycleUseCaseDeps, emit_user_event},
+    user_contracts::{Get as _, Update as _},
+};
+
+#[derive(Debug)]
+pub struct UpdateUserParams {
+    pub id: Uuid,
+    pub first_name: String,
+    pub last_name: Option<String>,
+    /// BCP 47 language tag, see [Locale].
+    pub locale: Option<String>,
+    /// Identifier of an IANA time zone, see [TimeZone].
+    pub timezone: Option<String>,
+    /// Version of the user the caller based the change on. The update fails with
+    /// [ApplicationError::StaleVersion] if the user has changed since.
+    pub expected_version: Option<u32>,
+}
+
+/// Changes the name, the locale and the time zone of a user. Nothing is written and no event
+/// is emitted if they all stay the same.
+#[instrument(skip(deps))]
+pub async fn update_user<U: UnitOfWork>(
+    deps: UserLifecycleUseCaseDeps<U>,
+    params: UpdateUserParams,
+) -> Result<User> {
+    trace!("Executing use case");
+
+    let UpdateUserParams {
+        id,
+        first_name,
+        last_name,
+        locale,
+        timezone,
+        expected_version,
+    } = params;
+    let mut uow = deps.unit_of_work;
+
+    let mut user = uow.users().get(id).await?;
+    user.ensure_not_erased()?;
+    if let Some(expected) = expected_version
+        && expected != user.version()
+    {
+        return Err(ApplicationError::StaleVersion {
+            entity: "User".to_owned(),
+            id: id.to_string(),
+            expected,
+            actual: user.version(),
+        });
+    }
+    // Both are applied even if the name changes.
+    let renamed = user.rename(first_name, last_name);
+    let localized = user.localize(
+        locale.as_deref().map(Locale::parse).transpose()?,
+        timezone.as_deref().map(TimeZone::parse).transpose()?,
+    );
+    if !renamed && !localized {
+        return Ok(user);
+    }
+
+    uow.users().update(&user).await?;
+    emit_user_event(
+        &mut uow.user_events(),
+        user.id(),
+        UserLifecycleTransition::Updated,
+        user.version(),
+    )
+    .await?;
+
+    uow.commit().await?;
+
+    Ok(user)
+}
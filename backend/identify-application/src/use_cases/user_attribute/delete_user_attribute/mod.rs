@@ -0,0 +1,36 @@
+use tracing::{instrument, trace};
+use uuid::Uuid;
+
+use crate::{
+    ApplicationError, Result, UnitOfWork,
+    use_cases::user_attribute::UserAttributeUseCaseDeps,
+    user_attribute_contracts::Delete as _,
+};
+
+#[derive(Debug)]
+pub struct DeleteUserAttributeParams {
+    pub user_id: Uuid,
+    pub key: String,
+}
+
+/// Deletes an attribute of a user. Fails if the user has no attribute with the key.
+#[instrument(skip(deps))]
+pub async fn delete_user_attribute<U: UnitOfWork>(
+    deps: UserAttributeUseCaseDeps<'_, U>,
+    params: DeleteUserAttributeParams,
+) -> Result<()> {
+    trace!("Executing use case");
+
+    let DeleteUserAttributeParams { user_id, key } = params;
+
+    if !deps
+        .unit_of_work
+        .user_attributes()
+        .delete(user_id, &key)
+        .await?
+    {
+        return Err(ApplicationError::entity_not_found("UserAttribute", key));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,29 @@
+use identify_domain::UserAttribute;
+use tracing::{instrument, trace};
+use uuid::Uuid;
+
+use crate::{
+    Result, UnitOfWork, use_cases::user_attribute::UserAttributeUseCaseDeps,
+    user_attribute_contracts::ListByUser as _, user_contracts::Get as _,
+};
+
+#[derive(Debug)]
+pub struct GetUserAttributesParams {
+    pub user_id: Uuid,
+}
+
+/// Returns all the attributes of a user, ordered by their keys. Fails if the user doesn't
+/// exist.
+#[instrument(skip(deps))]
+pub async fn get_user_attributes<U: UnitOfWork>(
+    deps: UserAttributeUseCaseDeps<'_, U>,
+    params: GetUserAttributesParams,
+) -> Result<Vec<UserAttribute>> {
+    trace!("Executing use case");
+
+    let uow = deps.unit_of_work;
+
+    uow.users().get(params.user_id).await?;
+
+    uow.user_attributes().list_by_user(params.user_id).await
+}
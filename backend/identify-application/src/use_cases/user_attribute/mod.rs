@@ -0,0 +1,17 @@
+pub mod delete_user_attribute;
+pub mod get_user_attributes;
+pub mod put_user_attributes;
+
+/// Dependencies of the use cases that manage the custom attributes of users.
+///
+/// The attributes are read and written in a [UnitOfWork](crate::UnitOfWork) together with the
+/// user they belong to, which is committed by the caller.
+pub struct UserAttributeUseCaseDeps<'a, U> {
+    unit_of_work: &'a mut U,
+}
+
+impl<'a, U> UserAttributeUseCaseDeps<'a, U> {
+    pub fn new(unit_of_work: &'a mut U) -> Self {
+        UserAttributeUseCaseDeps { unit_of_work }
+    }
+}
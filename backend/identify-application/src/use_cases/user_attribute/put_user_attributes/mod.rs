@@ -0,0 +1,91 @@
+use std::collections::HashMap;
+
+use identify_domain::{
+    AttributeValue, DomainError, NewUserAttributeAttrs, UserAttribute,
+};
+use tracing::{instrument, trace};
+use uuid::Uuid;
+
+use crate::{
+    Result, UnitOfWork,
+    use_cases::user_attribute::UserAttributeUseCaseDeps,
+    user_attribute_contracts::{Delete as _, ListByUser as _, Upsert as _},
+    user_contracts::Get as _,
+};
+
+#[derive(Debug)]
+pub struct PutUserAttributesParams {
+    pub user_id: Uuid,
+    /// The new attributes of the user by their keys.
+    pub attributes: HashMap<String, AttributeValue>,
+}
+
+/// Replaces all the attributes of a user: attributes with new keys are added, the values of
+/// the existing ones are changed, and the ones that aren't given are deleted. Returns the new
+/// attributes, ordered by their keys.
+///
+/// Attributes whose values don't change keep their timestamps.
+#[instrument(skip(deps))]
+pub async fn put_user_attributes<U: UnitOfWork>(
+    deps: UserAttributeUseCaseDeps<'_, U>,
+    params: PutUserAttributesParams,
+) -> Result<Vec<UserAttribute>> {
+    trace!("Executing use case");
+
+    let uow = deps.unit_of_work;
+
+    let PutUserAttributesParams {
+        user_id,
+        attributes,
+    } = params;
+
+    if attributes.len() > UserAttribute::MAX_PER_USER {
+        return Err(DomainError::validation(
+            "UserAttribute",
+            format!(
+                "a user can have at most {} attributes",
+                UserAttribute::MAX_PER_USER
+            ),
+        )
+        .into());
+    }
+
+    uow.users().get(user_id).await?.ensure_not_erased()?;
+
+    let mut existing: HashMap<_, _> = uow
+        .user_attributes()
+        .list_by_user(user_id)
+        .await?
+        .into_iter()
+        .map(|attribute| (attribute.key().to_owned(), attribute))
+        .collect();
+
+    let mut updated = Vec::with_capacity(attributes.len());
+    for (key, value) in attributes {
+        let attribute = match existing.remove(&key) {
+            Some(mut attribute) => {
+                if attribute.change_value(value)? {
+                    uow.user_attributes().upsert(&attribute).await?;
+                }
+                attribute
+            }
+            None => {
+                let attribute = UserAttribute::new(NewUserAttributeAttrs {
+                    user_id,
+                    key,
+                    value,
+                })?;
+                uow.user_attributes().upsert(&attribute).await?;
+                attribute
+            }
+        };
+        updated.push(attribute);
+    }
+    for key in existing.keys() {
+        uow.user_attributes().delete(user_id, key).await?;
+    }
+
+    updated.sort_by(|a, b| a.key().cmp(b.key()));
+
+    Ok(updated)
+}
@@ -0,0 +1,23 @@
+use tracing::{instrument, trace};
+use uuid::Uuid;
+
+use crate::{
+    Result, use_cases::webhook::WebhookEndpointUseCaseDeps,
+    webhook_endpoint_contracts,
+};
+
+#[derive(Debug)]
+pub struct DeleteWebhookEndpointParams {
+    pub id: Uuid,
+}
+
+/// Deletes an endpoint. Its pending deliveries are dropped.
+#[instrument(skip(deps))]
+pub async fn delete_webhook_endpoint<R: webhook_endpoint_contracts::Delete>(
+    deps: WebhookEndpointUseCaseDeps<'_, R>,
+    params: DeleteWebhookEndpointParams,
+) -> Result<()> {
+    trace!("Executing use case");
+
+    deps.repository.delete(params.id).await
+}
@@ -0,0 +1,86 @@
+use chrono::Utc;
+use identify_domain::{NewWebhookDeliveryAttrs, WebhookDelivery};
+use tracing::{debug, instrument, trace};
+
+use crate::{
+    EventPublisher, Result, UnitOfWork as _, UnitOfWorkFactory,
+    use_cases::webhook::WebhookDispatchUseCaseDeps,
+    user_event_contracts::{ListUnpublished as _, MarkPublished as _},
+    webhook_delivery_contracts::Insert as _,
+    webhook_endpoint_contracts::ListAll as _,
+};
+
+#[derive(Debug)]
+pub struct DispatchUserEventsParams {
+    /// Most events dispatched at once.
+    pub limit: u32,
+}
+
+/// Streams every unpublished event through the publisher, then creates its delivery to every
+/// endpoint subscribed to it and marks it as published.
+///
+/// Returns the number of dispatched events. Events nobody is subscribed to are published
+/// without any deliveries. The events are published before any transaction begins, and marked
+/// as published afterwards. If the publisher fails, nothing is marked, so the batch is
+/// dispatched again later and its first events may be published twice.
+#[instrument(skip(deps))]
+pub async fn dispatch_user_events<F, P>(
+    deps: WebhookDispatchUseCaseDeps<'_, F, P>,
+    params: DispatchUserEventsParams,
+) -> Result<usize>
+where
+    F: UnitOfWorkFactory,
+    P: EventPublisher,
+{
+    trace!("Executing use case");
+
+    let WebhookDispatchUseCaseDeps {
+        transactions,
+        publisher,
+    } = deps;
+
+    let events = transactions
+        .begin()
+        .await?
+        .user_events()
+        .list_unpublished(params.limit)
+        .await?;
+    if events.is_empty() {
+        return Ok(0);
+    }
+
+    for event in &events {
+        publisher.publish(event).await?;
+    }
+
+    let mut uow = transactions.begin().await?;
+    let endpoints = uow.webhook_endpoints().list_all().await?;
+    let now = Utc::now();
+
+    for event in &events {
+        for endpoint in endpoints
+            .iter()
+            .filter(|endpoint| endpoint.accepts(event.transition()))
+        {
+            let delivery = WebhookDelivery::new(NewWebhookDeliveryAttrs {
+                endpoint_id: endpoint.id(),
+                event_id: event.id(),
+                user_id: event.user_id(),
+                transition: event.transition(),
+                occurred_at: *event.occurred_at(),
+            });
+            if !uow.webhook_deliveries().insert(&delivery).await? {
+                debug!(
+                    event_id = %event.id(),
+                    endpoint_id = %endpoint.id(),
+                    "Skipping duplicate webhook delivery"
+                );
+            }
+        }
+
+        uow.user_events().mark_published(event, now).await?;
+    }
+    uow.commit().await?;
+
+    Ok(events.len())
+}
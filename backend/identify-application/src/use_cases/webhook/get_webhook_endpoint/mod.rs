@@ -0,0 +1,23 @@
+use identify_domain::WebhookEndpoint;
+use tracing::{instrument, trace};
+use uuid::Uuid;
+
+use crate::{
+    Result, use_cases::webhook::WebhookEndpointUseCaseDeps,
+    webhook_endpoint_contracts,
+};
+
+#[derive(Debug)]
+pub struct GetWebhookEndpointParams {
+    pub id: Uuid,
+}
+
+#[instrument(skip(deps))]
+pub async fn get_webhook_endpoint<R: webhook_endpoint_contracts::Get>(
+    deps: WebhookEndpointUseCaseDeps<'_, R>,
+    params: GetWebhookEndpointParams,
+) -> Result<WebhookEndpoint> {
+    trace!("Executing use case");
+
+    deps.repository.get(params.id).await
+}
@@ -0,0 +1,33 @@
+use identify_domain::WebhookEndpoint;
+use tracing::{instrument, trace};
+
+use crate::{
+    ApplicationError, Result, use_cases::webhook::WebhookEndpointUseCaseDeps,
+    webhook_endpoint_contracts,
+};
+
+#[derive(Debug)]
+pub struct GetWebhookEndpointByExternalIdParams {
+    pub external_id: String,
+}
+
+/// Returns the endpoint with the provided external ID.
+#[instrument(skip(deps))]
+pub async fn get_webhook_endpoint_by_external_id<
+    R: webhook_endpoint_contracts::GetByExternalId,
+>(
+    deps: WebhookEndpointUseCaseDeps<'_, R>,
+    params: GetWebhookEndpointByExternalIdParams,
+) -> Result<WebhookEndpoint> {
+    trace!("Executing use case");
+
+    deps.repository
+        .get_by_external_id(&params.external_id)
+        .await?
+        .ok_or_else(|| {
+            ApplicationError::entity_not_found(
+                "WebhookEndpoint",
+                params.external_id,
+            )
+        })
+}
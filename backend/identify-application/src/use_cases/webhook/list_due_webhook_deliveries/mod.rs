@@ -0,0 +1,60 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use identify_domain::{WebhookDelivery, WebhookEndpoint};
+use tracing::{instrument, trace};
+
+use crate::{
+    Result, UnitOfWork, use_cases::webhook::WebhookDeliveryUseCaseDeps,
+    webhook_delivery_contracts::ListDue as _,
+    webhook_endpoint_contracts::ListAll as _,
+};
+
+#[derive(Debug)]
+pub struct ListDueWebhookDeliveriesParams {
+    pub now: DateTime<Utc>,
+    /// Most deliveries returned at once.
+    pub limit: u32,
+}
+
+/// A delivery that should be attempted, together with the endpoint it goes to.
+#[derive(Debug)]
+pub struct DueWebhookDelivery {
+    pub endpoint: WebhookEndpoint,
+    pub delivery: WebhookDelivery,
+}
+
+/// Lists the pending deliveries whose next attempt is due.
+#[instrument(skip(deps))]
+pub async fn list_due_webhook_deliveries<U: UnitOfWork>(
+    deps: WebhookDeliveryUseCaseDeps<'_, U>,
+    params: ListDueWebhookDeliveriesParams,
+) -> Result<Vec<DueWebhookDelivery>> {
+    trace!("Executing use case");
+
+    let uow = deps.unit_of_work;
+
+    let ListDueWebhookDeliveriesParams { now, limit } = params;
+
+    let deliveries = uow.webhook_deliveries().list_due(now, limit).await?;
+    if deliveries.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let endpoints: HashMap<_, _> = uow
+        .webhook_endpoints()
+        .list_all()
+        .await?
+        .into_iter()
+        .map(|endpoint| (endpoint.id(), endpoint))
+        .collect();
+
+    // Deliveries are deleted together with their endpoints, so every delivery has one.
+    Ok(deliveries
+        .into_iter()
+        .filter_map(|delivery| {
+            let endpoint = endpoints.get(&delivery.endpoint_id())?.clone();
+            Some(DueWebhookDelivery { endpoint, delivery })
+        })
+        .collect())
+}
@@ -0,0 +1,40 @@
+use identify_domain::{WebhookDelivery, WebhookDeliveryStatus};
+use tracing::{instrument, trace};
+use uuid::Uuid;
+
+use crate::{
+    Result, UnitOfWork, use_cases::webhook::WebhookDeliveryUseCaseDeps,
+    webhook_delivery_contracts::ListByEndpoint as _,
+    webhook_endpoint_contracts::Get as _,
+};
+
+#[derive(Debug)]
+pub struct ListWebhookDeliveriesParams {
+    pub endpoint_id: Uuid,
+    pub status: WebhookDeliveryStatus,
+    pub limit: u32,
+}
+
+/// Lists the latest deliveries to an endpoint in the given state, e.g. the dead letters.
+#[instrument(skip(deps))]
+pub async fn list_webhook_deliveries<U: UnitOfWork>(
+    deps: WebhookDeliveryUseCaseDeps<'_, U>,
+    params: ListWebhookDeliveriesParams,
+) -> Result<Vec<WebhookDelivery>> {
+    trace!("Executing use case");
+
+    let uow = deps.unit_of_work;
+
+    let ListWebhookDeliveriesParams {
+        endpoint_id,
+        status,
+        limit,
+    } = params;
+
+    // Reports missing endpoints instead of listing no deliveries.
+    uow.webhook_endpoints().get(endpoint_id).await?;
+
+    uow.webhook_deliveries()
+        .list_by_endpoint(endpoint_id, status, limit)
+        .await
+}
@@ -0,0 +1,20 @@
+use identify_domain::WebhookEndpoint;
+use tracing::{instrument, trace};
+
+use crate::{
+    Result, use_cases::webhook::WebhookEndpointUseCaseDeps,
+    webhook_endpoint_contracts,
+};
+
+#[derive(Debug)]
+pub struct ListWebhookEndpointsParams;
+
+#[instrument(skip(deps))]
+pub async fn list_webhook_endpoints<R: webhook_endpoint_contracts::ListAll>(
+    deps: WebhookEndpointUseCaseDeps<'_, R>,
+    _params: ListWebhookEndpointsParams,
+) -> Result<Vec<WebhookEndpoint>> {
+    trace!("Executing use case");
+
+    deps.repository.list_all().await
+}
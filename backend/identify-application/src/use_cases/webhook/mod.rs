@@ -0,0 +1,55 @@
+pub mod delete_webhook_endpoint;
+pub mod dispatch_user_events;
+pub mod get_webhook_endpoint;
+pub mod get_webhook_endpoint_by_external_id;
+pub mod list_due_webhook_deliveries;
+pub mod list_webhook_deliveries;
+pub mod list_webhook_endpoints;
+pub mod put_webhook_endpoint;
+pub mod record_webhook_attempt;
+pub mod redeliver_webhook_delivery;
+pub mod register_webhook_endpoint;
+
+pub struct WebhookEndpointUseCaseDeps<'a, R> {
+    repository: &'a mut R,
+}
+
+impl<'a, R> WebhookEndpointUseCaseDeps<'a, R> {
+    pub fn new(repository: &'a mut R) -> Self {
+        WebhookEndpointUseCaseDeps { repository }
+    }
+}
+
+/// Dependencies of the use cases that deliver events to the endpoints.
+/// Dependencies of the use cases that deliver events to the endpoints.
+///
+/// Endpoints and deliveries are read and written in a single [UnitOfWork](crate::UnitOfWork),
+/// which is committed by the caller.
+pub struct WebhookDeliveryUseCaseDeps<'a, U> {
+    unit_of_work: &'a mut U,
+}
+
+impl<'a, U> WebhookDeliveryUseCaseDeps<'a, U> {
+    pub fn new(unit_of_work: &'a mut U) -> Self {
+        WebhookDeliveryUseCaseDeps { unit_of_work }
+    }
+}
+
+/// Dependencies of the use cases that turn recorded events into deliveries and stream them
+/// through the publisher.
+///
+/// The use cases begin and commit their own units of work, so that no transaction is held open
+/// while events are published.
+pub struct WebhookDispatchUseCaseDeps<'a, F, P> {
+    transactions: F,
+    publisher: &'a P,
+}
+
+impl<'a, F, P> WebhookDispatchUseCaseDeps<'a, F, P> {
+    pub fn new(transactions: F, publisher: &'a P) -> Self {
+        WebhookDispatchUseCaseDeps {
+            transactions,
+            publisher,
+        }
+    }
+}
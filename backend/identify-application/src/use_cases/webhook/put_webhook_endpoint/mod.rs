@@ -0,0 +1,70 @@
+use identify_domain::{
+    NewWebhookEndpointAttrs, UserLifecycleTransition, WebhookEndpoint,
+    WebhookUrlPolicy,
+};
+use tracing::{instrument, trace};
+
+use crate::{
+    PutOutcome, Result, use_cases::webhook::WebhookEndpointUseCaseDeps,
+    webhook_endpoint_contracts,
+};
+
+#[derive(Debug)]
+pub struct PutWebhookEndpointParams {
+    pub external_id: String,
+    pub url: String,
+    pub secret: String,
+    /// Transitions the endpoint is subscribed to. Empty for all of them.
+    pub events: Vec<UserLifecycleTransition>,
+    pub url_policy: WebhookUrlPolicy,
+}
+
+/// Registers the endpoint with the provided external ID, or brings the existing one to the
+/// provided state. Nothing is written if it's already in that state.
+///
+/// Calling it repeatedly with the same parameters has the same effect as calling it once, which
+/// is what infrastructure as code tools expect. Fails if the URL of the endpoint isn't allowed
+/// by the policy, e.g. if it's a plain HTTP one.
+#[instrument(skip(deps))]
+pub async fn put_webhook_endpoint<R>(
+    deps: WebhookEndpointUseCaseDeps<'_, R>,
+    params: PutWebhookEndpointParams,
+) -> Result<(WebhookEndpoint, PutOutcome)>
+where
+    R: webhook_endpoint_contracts::GetByExternalId
+        + webhook_endpoint_contracts::Insert
+        + webhook_endpoint_contracts::Update,
+{
+    trace!("Executing use case");
+
+    let PutWebhookEndpointParams {
+        external_id,
+        url,
+        secret,
+        events,
+        url_policy,
+    } = params;
+
+    let existing = deps.repository.get_by_external_id(&external_id).await?;
+    let Some(mut endpoint) = existing else {
+        let endpoint = WebhookEndpoint::new(NewWebhookEndpointAttrs {
+            url,
+            secret,
+            events,
+            external_id: Some(external_id),
+        })?;
+        url_policy.check(&endpoint)?;
+        deps.repository.insert(&endpoint).await?;
+
+        return Ok((endpoint, PutOutcome::Created));
+    };
+
+    if !endpoint.reconfigure(&url, &secret, events)? {
+        return Ok((endpoint, PutOutcome::Unchanged));
+    }
+
+    url_policy.check(&endpoint)?;
+    deps.repository.update(&endpoint).await?;
+
+    Ok((endpoint, PutOutcome::Updated))
+}
@@ -0,0 +1,44 @@
+use identify_domain::{WebhookDelivery, WebhookRetryPolicy};
+use tracing::{instrument, trace};
+use uuid::Uuid;
+
+use crate::{
+    Result, UnitOfWork,
+    use_cases::webhook::WebhookDeliveryUseCaseDeps,
+    webhook_delivery_contracts::{Get as _, Update as _},
+};
+
+#[derive(Debug)]
+pub struct RecordWebhookAttemptParams {
+    pub delivery_id: Uuid,
+    /// Outcome of the attempt, with the reason it failed.
+    pub outcome: std::result::Result<(), String>,
+    pub retry_policy: WebhookRetryPolicy,
+}
+
+/// Records the outcome of an attempt to deliver an event, scheduling a retry or giving up if
+/// it has failed.
+#[instrument(skip(deps))]
+pub async fn record_webhook_attempt<U: UnitOfWork>(
+    deps: WebhookDeliveryUseCaseDeps<'_, U>,
+    params: RecordWebhookAttemptParams,
+) -> Result<WebhookDelivery> {
+    trace!("Executing use case");
+
+    let uow = deps.unit_of_work;
+
+    let RecordWebhookAttemptParams {
+        delivery_id,
+        outcome,
+        retry_policy,
+    } = params;
+
+    let mut delivery = uow.webhook_deliveries().get(delivery_id).await?;
+    match outcome {
+        Ok(()) => delivery.succeed()?,
+        Err(error) => delivery.fail(error, &retry_policy)?,
+    }
+    uow.webhook_deliveries().update(&delivery).await?;
+
+    Ok(delivery)
+}
@@ -0,0 +1,44 @@
+use identify_domain::WebhookDelivery;
+use tracing::{instrument, trace};
+use uuid::Uuid;
+
+use crate::{
+    ApplicationError, Result, UnitOfWork,
+    use_cases::webhook::WebhookDeliveryUseCaseDeps,
+    webhook_delivery_contracts::{Get as _, Update as _},
+};
+
+#[derive(Debug)]
+pub struct RedeliverWebhookDeliveryParams {
+    pub endpoint_id: Uuid,
+    pub delivery_id: Uuid,
+}
+
+/// Retries a dead delivery from scratch, e.g. after the endpoint has been fixed.
+#[instrument(skip(deps))]
+pub async fn redeliver_webhook_delivery<U: UnitOfWork>(
+    deps: WebhookDeliveryUseCaseDeps<'_, U>,
+    params: RedeliverWebhookDeliveryParams,
+) -> Result<WebhookDelivery> {
+    trace!("Executing use case");
+
+    let uow = deps.unit_of_work;
+
+    let RedeliverWebhookDeliveryParams {
+        endpoint_id,
+        delivery_id,
+    } = params;
+
+    let mut delivery = uow.webhook_deliveries().get(delivery_id).await?;
+    if delivery.endpoint_id() != endpoint_id {
+        return Err(ApplicationError::entity_not_found(
+            "WebhookDelivery",
+            delivery_id,
+        ));
+    }
+
+    delivery.redeliver()?;
+    uow.webhook_deliveries().update(&delivery).await?;
+
+    Ok(delivery)
+}
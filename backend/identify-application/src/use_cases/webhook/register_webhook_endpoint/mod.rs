@@ -0,0 +1,39 @@
+use identify_domain::{
+    NewWebhookEndpointAttrs, WebhookEndpoint, WebhookUrlPolicy,
+};
+use tracing::{instrument, trace};
+
+use crate::{
+    Result, use_cases::webhook::WebhookEndpointUseCaseDeps,
+    webhook_endpoint_contracts,
+};
+
+#[derive(Debug)]
+pub struct RegisterWebhookEndpointParams {
+    pub endpoint_attrs: NewWebhookEndpointAttrs,
+    pub url_policy: WebhookUrlPolicy,
+}
+
+/// Registers an endpoint that is delivered the events recorded from now on.
+///
+/// Fails if the URL of the endpoint isn't allowed by the policy, e.g. if it's a plain HTTP one.
+#[instrument(skip(deps))]
+pub async fn register_webhook_endpoint<
+    R: webhook_endpoint_contracts::Insert,
+>(
+    deps: WebhookEndpointUseCaseDeps<'_, R>,
+    params: RegisterWebhookEndpointParams,
+) -> Result<WebhookEndpoint> {
+    trace!("Executing use case");
+
+    let RegisterWebhookEndpointParams {
+        endpoint_attrs,
+        url_policy,
+    } = params;
+
+    let endpoint = WebhookEndpoint::new(endpoint_attrs)?;
+    url_policy.check(&endpoint)?;
+    deps.repository.insert(&endpoint).await?;
+
+    Ok(endpoint)
+}
@@ -0,0 +1,74 @@
+//! Compares copying the attributes out of users with moving them, as done when a page of users
+//! is mapped into responses.
+//!
+//! Run with `cargo bench -p identify-domain`.
+
+use std::hint::black_box;
+
+use criterion::{
+    BatchSize, BenchmarkId, Criterion, criterion_group, criterion_main,
+};
+use identify_domain::{NewUserAttrs, User, Username};
+
+fn users(count: usize) -> Vec<User> {
+    (0..count)
+        .map(|i| {
+            User::new(
+                NewUserAttrs {
+                    email: format!("user-{i}@example.test"),
+                    first_name: format!("First name {i}"),
+                    last_name: Some(format!("Last name {i}")),
+                },
+                Username::parse(&format!("user-{i}")).unwrap(),
+            )
+        })
+        .collect()
+}
+
+fn attributes(c: &mut Criterion) {
+    let mut group = c.benchmark_group("user_attributes");
+
+    for count in [100, 1_000] {
+        group.bench_with_input(
+            BenchmarkId::new("to_attributes", count),
+            &count,
+            |b, &count| {
+                b.iter_batched(
+                    || users(count),
+                    |users| {
+                        let attrs = users
+                            .iter()
+                            .map(User::to_attributes)
+                            .collect::<Vec<_>>();
+                        // Dropping the users is part of the copying path.
+                        drop(black_box(users));
+                        attrs
+                    },
+                    BatchSize::SmallInput,
+                )
+            },
+        );
+
+        group.bench_with_input(
+            BenchmarkId::new("into_attributes", count),
+            &count,
+            |b, &count| {
+                b.iter_batched(
+                    || users(count),
+                    |users| {
+                        users
+                            .into_iter()
+                            .map(User::into_attributes)
+                            .collect::<Vec<_>>()
+                    },
+                    BatchSize::SmallInput,
+                )
+            },
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, attributes);
+criterion_main!(benches);
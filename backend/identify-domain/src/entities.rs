@@ -1,5 +1,53 @@
 use uuid::Uuid;
 
+use crate::{DomainError, Result};
+
+pub mod invitation;
+pub mod membership;
+pub mod operation;
+pub mod organization;
+pub mod reverification;
+pub mod role;
+pub mod signing_key;
 pub mod user;
+pub mod webhook;
 
 pub const UUID_NAMESPACE: Uuid = Uuid::from_bytes(*b"identify-backend");
+
+/// Longest allowed ID assigned to an entity by an external tool.
+const MAX_EXTERNAL_ID_LENGTH: usize = 255;
+
+/// Checks an ID assigned to an entity of the model by an external tool, e.g. infrastructure as
+/// code.
+pub(crate) fn validate_external_id(
+    model: &'static str,
+    external_id: &str,
+) -> Result<()> {
+    if external_id.is_empty() {
+        return Err(DomainError::validation(
+            model,
+            "external ID must not be empty",
+        ));
+    }
+
+    if external_id.len() > MAX_EXTERNAL_ID_LENGTH {
+        return Err(DomainError::validation(
+            model,
+            format!(
+                "external ID must be at most {MAX_EXTERNAL_ID_LENGTH} bytes long"
+            ),
+        ));
+    }
+
+    if external_id
+        .chars()
+        .any(|c| c.is_whitespace() || c.is_control())
+    {
+        return Err(DomainError::validation(
+            model,
+            "external ID must not contain whitespace",
+        ));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,208 @@
+use chrono::{DateTime, Utc};
+use identify_macros::gen_model;
+use uuid::Uuid;
+
+use crate::{DomainError, MembershipRole, Result};
+
+gen_model! {
+    /// State of an [Invitation].
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[model(display, from_str(DomainError))]
+    pub enum InvitationStatus {
+        /// The invitation can still be accepted.
+        Pending,
+        /// The invitee accepted the invitation and became a member of the organization.
+        Accepted,
+        /// The invitation wasn't accepted in time.
+        Expired,
+    }
+}
+
+gen_model! {
+    /// An invitation for someone without an account to join an
+    /// [Organization](crate::Organization).
+    ///
+    /// The invitee is emailed a link with a random token, of which only the hash is kept. Accepting
+    /// the invitation creates their user and makes them a member of the organization with the
+    /// role they were invited with.
+    #[derive(Debug, Clone)]
+    pub struct Invitation {
+        #[get(into(Uuid))]
+        #[new(skip)]
+        id: Uuid,
+        /// Email the invitation was sent to, which the created user gets.
+        #[get(as_ref(&str))]
+        email: String,
+        /// ID of the user who sent the invitation.
+        #[get(into(Uuid))]
+        inviter_id: Uuid,
+        /// ID of the organization the invitee is invited to.
+        #[get(into(Uuid))]
+        organization_id: Uuid,
+        /// Role the invitee gets within the organization.
+        #[get(copy)]
+        role: MembershipRole,
+        /// Hash of the token the invitation is accepted with.
+        #[get(as_ref(&str))]
+        token_hash: String,
+        /// When the invitation can no longer be accepted.
+        expires_at: DateTime<Utc>,
+        #[new(skip)]
+        created_at: DateTime<Utc>,
+        /// When the invitation was accepted.
+        #[new(skip)]
+        accepted_at: Option<DateTime<Utc>>,
+        /// ID of the user created when the invitation was accepted.
+        #[new(skip)]
+        user_id: Option<Uuid>,
+    }
+
+    #[derive(Debug)]
+    pub struct NewInvitationAttrs;
+
+    #[derive(Debug)]
+    pub struct InvitationAttrs;
+}
+
+impl Invitation {
+    /// Creates a pending invitation. Nobody can be invited as an owner of an organization, and
+    /// the invitation must expire in the future.
+    pub fn new(attrs: NewInvitationAttrs) -> Result<Self> {
+        let email = attrs.email.trim().to_owned();
+        validate_email(&email)?;
+        if attrs.role == MembershipRole::Owner {
+            return Err(DomainError::validation(
+                "Invitation",
+                "nobody can be invited as an owner",
+            ));
+        }
+        validate_token_hash(&attrs.token_hash)?;
+
+        let now = Utc::now();
+        if attrs.expires_at <= now {
+            return Err(DomainError::validation(
+                "Invitation",
+                "expires_at must be in the future",
+            ));
+        }
+
+        Ok(Invitation {
+            id: Uuid::new_v4(),
+            email,
+            inviter_id: attrs.inviter_id,
+            organization_id: attrs.organization_id,
+            role: attrs.role,
+            token_hash: attrs.token_hash,
+            expires_at: attrs.expires_at,
+            created_at: now,
+            accepted_at: None,
+            user_id: None,
+        })
+    }
+
+    pub fn load(attrs: InvitationAttrs) -> Result<Self> {
+        validate_email(&attrs.email)?;
+        validate_token_hash(&attrs.token_hash)?;
+        if attrs.accepted_at.is_some() != attrs.user_id.is_some() {
+            return Err(DomainError::validation(
+                "Invitation",
+                "user_id must be set if and only if the invitation is accepted",
+            ));
+        }
+
+        Ok(Invitation {
+            id: attrs.id,
+            email: attrs.email,
+            inviter_id: attrs.inviter_id,
+            organization_id: attrs.organization_id,
+            role: attrs.role,
+            token_hash: attrs.token_hash,
+            expires_at: attrs.expires_at,
+            created_at: attrs.created_at,
+            accepted_at: attrs.accepted_at,
+            user_id: attrs.user_id,
+        })
+    }
+
+    pub fn to_attributes(&self) -> InvitationAttrs {
+        InvitationAttrs {
+            id: self.id,
+            email: self.email.clone(),
+            inviter_id: self.inviter_id,
+            organization_id: self.organization_id,
+            role: self.role,
+            token_hash: self.token_hash.clone(),
+            expires_at: self.expires_at,
+            created_at: self.created_at,
+            accepted_at: self.accepted_at,
+            user_id: self.user_id,
+        }
+    }
+
+    /// Returns the current state of the invitation. Accepted invitations stay accepted once they
+    /// expire.
+    pub fn status(&self) -> InvitationStatus {
+        if self.accepted_at.is_some() {
+            InvitationStatus::Accepted
+        } else if self.expires_at <= Utc::now() {
+            InvitationStatus::Expired
+        } else {
+            InvitationStatus::Pending
+        }
+    }
+
+    /// Fails unless the invitation can still be accepted.
+    pub fn ensure_pending(&self) -> Result<()> {
+        match self.status() {
+            InvitationStatus::Pending => Ok(()),
+            InvitationStatus::Accepted => {
+                Err(DomainError::invalid_state_transition(
+                    "Invitation",
+                    "the invitation is already accepted",
+                ))
+            }
+            InvitationStatus::Expired => {
+                Err(DomainError::invalid_state_transition(
+                    "Invitation",
+                    "the invitation has expired",
+                ))
+            }
+        }
+    }
+
+    /// Records that the invitee accepted the invitation and was created as the given user. Fails
+    /// unless the invitation is pending.
+    pub fn accept(&mut self, user_id: Uuid) -> Result<()> {
+        self.ensure_pending()?;
+
+        self.accepted_at = Some(Utc::now());
+        self.user_id = Some(user_id);
+
+        Ok(())
+    }
+}
+
+fn validate_email(email: &str) -> Result<()> {
+    let valid = email
+        .split_once('@')
+        .is_some_and(|(local, domain)| !local.is_empty() && !domain.is_empty());
+    if !valid {
+        return Err(DomainError::validation(
+            "Invitation",
+            format!("'{email}' is not a valid email"),
+        ));
+    }
+
+    Ok(())
+}
+
+fn validate_token_hash(token_hash: &str) -> Result<()> {
+    if token_hash.is_empty() {
+        return Err(DomainError::validation(
+            "Invitation",
+            "token_hash must not be empty",
+        ));
+    }
+
+    Ok(())
+}
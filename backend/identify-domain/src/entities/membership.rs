@@ -0,0 +1,81 @@
+use chrono::{DateTime, Utc};
+use identify_macros::gen_model;
+use uuid::Uuid;
+
+use crate::{DomainError, Result};
+
+gen_model! {
+    /// Role of a user within an [Organization](crate::Organization).
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[model(display, from_str(DomainError))]
+    pub enum MembershipRole {
+        /// Created the organization and can do everything in it, including deleting it.
+        Owner,
+        /// Manages members and settings of the organization.
+        Admin,
+        /// Regular member.
+        Member,
+    }
+}
+
+gen_model! {
+    /// Membership of a [User](crate::User) in an [Organization](crate::Organization).
+    ///
+    /// A user can be a member of an organization only once.
+    #[derive(Debug)]
+    pub struct Membership {
+        /// ID of the organization.
+        #[get(into(Uuid))]
+        organization_id: Uuid,
+        /// ID of the member.
+        #[get(into(Uuid))]
+        user_id: Uuid,
+        /// Role of the member within the organization.
+        #[get(copy)]
+        #[set(touch(updated_at))]
+        role: MembershipRole,
+        #[new(skip)]
+        created_at: DateTime<Utc>,
+        #[new(skip)]
+        updated_at: DateTime<Utc>,
+    }
+
+    #[derive(Debug)]
+    pub struct NewMembershipAttrs;
+
+    #[derive(Debug)]
+    pub struct MembershipAttrs;
+}
+
+impl Membership {
+    pub fn new(attrs: NewMembershipAttrs) -> Self {
+        let now = Utc::now();
+        Membership {
+            organization_id: attrs.organization_id,
+            user_id: attrs.user_id,
+            role: attrs.role,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    pub fn load(attrs: MembershipAttrs) -> Result<Self> {
+        Ok(Membership {
+            organization_id: attrs.organization_id,
+            user_id: attrs.user_id,
+            role: attrs.role,
+            created_at: attrs.created_at,
+            updated_at: attrs.updated_at,
+        })
+    }
+
+    pub fn to_attributes(&self) -> MembershipAttrs {
+        MembershipAttrs {
+            organization_id: self.organization_id,
+            user_id: self.user_id,
+            role: self.role,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+        }
+    }
+}
@@ -0,0 +1,214 @@
+use chrono::{DateTime, Utc};
+use identify_macros::gen_model;
+use uuid::Uuid;
+
+use crate::{DomainError, Result};
+
+gen_model! {
+    /// State of a long-running [Operation].
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[model(display, from_str(DomainError))]
+    pub enum OperationStatus {
+        /// The operation was accepted but hasn't been started yet.
+        Pending,
+        /// The operation is being executed.
+        Running,
+        /// The operation finished successfully.
+        Succeeded,
+        /// The operation finished with an error.
+        Failed,
+        /// The operation was cancelled before it could finish.
+        Cancelled,
+    }
+}
+
+impl OperationStatus {
+    /// Whether the operation can't change its state anymore.
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            OperationStatus::Succeeded
+                | OperationStatus::Failed
+                | OperationStatus::Cancelled
+        )
+    }
+}
+
+gen_model! {
+    /// A long-running operation (bulk import, offboarding, etc.) that is executed in the
+    /// background and can be polled by clients.
+    #[derive(Debug)]
+    pub struct Operation {
+        /// Unique ID of the operation.
+        #[get(into(Uuid))]
+        #[new(skip)]
+        id: Uuid,
+        /// Kind of the operation, e.g. `bulk_import`.
+        kind: String,
+        /// Current state of the operation.
+        #[get(copy)]
+        #[new(skip)]
+        status: OperationStatus,
+        /// Progress of the operation in percents.
+        #[get(into(u8))]
+        #[new(skip)]
+        progress: u8,
+        /// Error message if the operation has failed.
+        #[new(skip)]
+        error: Option<String>,
+        /// Whether a client has asked to cancel a running operation.
+        #[get(into(bool))]
+        #[new(skip)]
+        cancel_requested: bool,
+        #[new(skip)]
+        created_at: DateTime<Utc>,
+        #[new(skip)]
+        updated_at: DateTime<Utc>,
+    }
+
+    #[derive(Debug)]
+    pub struct NewOperationAttrs;
+
+    #[derive(Debug)]
+    pub struct OperationAttrs;
+}
+
+impl Operation {
+    pub fn new(attrs: NewOperationAttrs) -> Self {
+        let now = Utc::now();
+        Operation {
+            id: Uuid::new_v4(),
+            kind: attrs.kind,
+            status: OperationStatus::Pending,
+            progress: 0,
+            error: None,
+            cancel_requested: false,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    pub fn load(attrs: OperationAttrs) -> Result<Self> {
+        if attrs.progress > 100 {
+            return Err(DomainError::validation(
+                "Operation",
+                format!("progress must be at most 100, got {}", attrs.progress),
+            ));
+        }
+
+        Ok(Operation {
+            id: attrs.id,
+            kind: attrs.kind,
+            status: attrs.status,
+            progress: attrs.progress,
+            error: attrs.error,
+            cancel_requested: attrs.cancel_requested,
+            created_at: attrs.created_at,
+            updated_at: attrs.updated_at,
+        })
+    }
+
+    pub fn to_attributes(&self) -> OperationAttrs {
+        OperationAttrs {
+            id: self.id,
+            kind: self.kind.clone(),
+            status: self.status,
+            progress: self.progress,
+            error: self.error.clone(),
+            cancel_requested: self.cancel_requested,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+        }
+    }
+
+    /// Moves a pending operation into the running state.
+    pub fn start(&mut self) -> Result<()> {
+        self.ensure_status(&[OperationStatus::Pending], "start")?;
+        self.status = OperationStatus::Running;
+        self.touch();
+
+        Ok(())
+    }
+
+    /// Records the progress of a running operation, clamping it to 100%.
+    pub fn report_progress(&mut self, progress: u8) -> Result<()> {
+        self.ensure_status(&[OperationStatus::Running], "report progress")?;
+        self.progress = progress.min(100);
+        self.touch();
+
+        Ok(())
+    }
+
+    /// Marks a running operation as successfully finished.
+    pub fn succeed(&mut self) -> Result<()> {
+        self.ensure_status(&[OperationStatus::Running], "succeed")?;
+        self.status = OperationStatus::Succeeded;
+        self.progress = 100;
+        self.touch();
+
+        Ok(())
+    }
+
+    /// Marks an unfinished operation as failed.
+    pub fn fail(&mut self, error: impl Into<String>) -> Result<()> {
+        self.ensure_status(
+            &[OperationStatus::Pending, OperationStatus::Running],
+            "fail",
+        )?;
+        self.status = OperationStatus::Failed;
+        self.error = Some(error.into());
+        self.touch();
+
+        Ok(())
+    }
+
+    /// Requests cancellation of the operation.
+    ///
+    /// Pending operations are cancelled right away, while running operations are only
+    /// flagged and must be stopped by whoever executes them via [Operation::cancel].
+    pub fn request_cancellation(&mut self) -> Result<()> {
+        match self.status {
+            OperationStatus::Pending => {
+                self.status = OperationStatus::Cancelled
+            }
+            OperationStatus::Running => self.cancel_requested = true,
+            status => {
+                return Err(DomainError::invalid_state_transition(
+                    "Operation",
+                    format!("can't cancel an operation that is {status}"),
+                ));
+            }
+        }
+        self.touch();
+
+        Ok(())
+    }
+
+    /// Marks a running operation as cancelled.
+    pub fn cancel(&mut self) -> Result<()> {
+        self.ensure_status(&[OperationStatus::Running], "cancel")?;
+        self.status = OperationStatus::Cancelled;
+        self.touch();
+
+        Ok(())
+    }
+
+    fn ensure_status(
+        &self,
+        allowed: &[OperationStatus],
+        action: &str,
+    ) -> Result<()> {
+        if allowed.contains(&self.status) {
+            return Ok(());
+        }
+
+        Err(DomainError::invalid_state_transition(
+            "Operation",
+            format!("can't {action} an operation that is {}", self.status),
+        ))
+    }
+
+    fn touch(&mut self) {
+        self.updated_at = Utc::now();
+    }
+}
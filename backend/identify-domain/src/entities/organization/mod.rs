@@ -0,0 +1,127 @@
+pub mod quota;
+pub mod slug;
+
+use chrono::{DateTime, Utc};
+use identify_macros::gen_model;
+use slug::Slug;
+use uuid::Uuid;
+
+use crate::{DomainError, Result, entities::validate_external_id};
+
+/// Longest allowed name of an [Organization].
+const MAX_NAME_LENGTH: usize = 100;
+
+gen_model! {
+    /// A group of users (e.g. a company) that manages its members and their access together.
+    #[derive(Debug)]
+    pub struct Organization {
+        /// Unique ID of the organization.
+        #[get(into(Uuid))]
+        #[new(skip)]
+        id: Uuid,
+        /// Unique URL-safe name of the organization.
+        #[get(as_ref(&str))]
+        #[new(skip)]
+        #[hydrate(type(String))]
+        #[set(touch(updated_at))]
+        slug: Slug,
+        /// Display name of the organization.
+        name: String,
+        /// ID assigned to the organization by an external tool, e.g. infrastructure as code,
+        /// that is unique among all organizations.
+        external_id: Option<String>,
+        #[new(skip)]
+        created_at: DateTime<Utc>,
+        #[new(skip)]
+        updated_at: DateTime<Utc>,
+    }
+
+    #[derive(Debug)]
+    pub struct NewOrganizationAttrs;
+
+    #[derive(Debug)]
+    pub struct OrganizationAttrs;
+}
+
+impl Organization {
+    /// Creates an organization with the given slug, which must not be used by anyone else.
+    pub fn new(attrs: NewOrganizationAttrs, slug: Slug) -> Result<Self> {
+        let name = attrs.name.trim().to_owned();
+        validate_name(&name)?;
+        if let Some(external_id) = &attrs.external_id {
+            validate_external_id("Organization", external_id)?;
+        }
+
+        let now = Utc::now();
+        Ok(Organization {
+            id: Uuid::new_v4(),
+            slug,
+            name,
+            external_id: attrs.external_id,
+            created_at: now,
+            updated_at: now,
+        })
+    }
+
+    pub fn load(attrs: OrganizationAttrs) -> Result<Self> {
+        validate_name(&attrs.name)?;
+        if let Some(external_id) = &attrs.external_id {
+            validate_external_id("Organization", external_id)?;
+        }
+
+        Ok(Organization {
+            id: attrs.id,
+            slug: Slug::parse(&attrs.slug)?,
+            name: attrs.name,
+            external_id: attrs.external_id,
+            created_at: attrs.created_at,
+            updated_at: attrs.updated_at,
+        })
+    }
+
+    pub fn to_attributes(&self) -> OrganizationAttrs {
+        OrganizationAttrs {
+            id: self.id,
+            slug: self.slug.to_string(),
+            name: self.name.clone(),
+            external_id: self.external_id.clone(),
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+        }
+    }
+
+    /// Changes the name of the organization.
+    ///
+    /// Returns `false` if the name is the same as before and nothing has changed.
+    pub fn rename(&mut self, name: &str) -> Result<bool> {
+        let name = name.trim();
+        validate_name(name)?;
+
+        if self.name == name {
+            return Ok(false);
+        }
+
+        self.name = name.to_owned();
+        self.updated_at = Utc::now();
+
+        Ok(true)
+    }
+}
+
+fn validate_name(name: &str) -> Result<()> {
+    if name.is_empty() {
+        return Err(DomainError::validation(
+            "Organization",
+            "name must not be empty",
+        ));
+    }
+
+    if name.chars().count() > MAX_NAME_LENGTH {
+        return Err(DomainError::validation(
+            "Organization",
+            format!("name must be at most {MAX_NAME_LENGTH} characters long"),
+        ));
+    }
+
+    Ok(())
+}
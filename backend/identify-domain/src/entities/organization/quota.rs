@@ -0,0 +1,60 @@
+use chrono::{DateTime, Utc};
+use identify_macros::gen_model;
+use uuid::Uuid;
+
+use crate::Result;
+
+gen_model! {
+    /// The most users an [Organization](crate::Organization) can have, which replaces the
+    /// default of the [QuotaPolicy](crate::QuotaPolicy) for it.
+    #[derive(Debug, Clone)]
+    pub struct OrganizationQuota {
+        /// ID of the organization.
+        #[get(into(Uuid))]
+        organization_id: Uuid,
+        /// Most users the organization can have, or `None` if it can have any number of them.
+        #[get(copy)]
+        #[set(touch(updated_at))]
+        max_users: Option<u32>,
+        #[new(skip)]
+        created_at: DateTime<Utc>,
+        #[new(skip)]
+        updated_at: DateTime<Utc>,
+    }
+
+    #[derive(Debug)]
+    pub struct NewOrganizationQuotaAttrs;
+
+    #[derive(Debug)]
+    pub struct OrganizationQuotaAttrs;
+}
+
+impl OrganizationQuota {
+    pub fn new(attrs: NewOrganizationQuotaAttrs) -> Self {
+        let now = Utc::now();
+        OrganizationQuota {
+            organization_id: attrs.organization_id,
+            max_users: attrs.max_users,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    pub fn load(attrs: OrganizationQuotaAttrs) -> Result<Self> {
+        Ok(OrganizationQuota {
+            organization_id: attrs.organization_id,
+            max_users: attrs.max_users,
+            created_at: attrs.created_at,
+            updated_at: attrs.updated_at,
+        })
+    }
+
+    pub fn to_attributes(&self) -> OrganizationQuotaAttrs {
+        OrganizationQuotaAttrs {
+            organization_id: self.organization_id,
+            max_users: self.max_users,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use std::fmt;
+
+use uuid::Uuid;
+
+use crate::{DomainError, Result};
+
+/// Prefix of the random slugs.
+const RANDOM_PREFIX: &str = "org-";
+/// Number of random hex digits in a slug, enough to make collisions unlikely.
+const RANDOM_DIGITS: usize = 8;
+
+/// A URL-safe name that uniquely identifies an [Organization](super::Organization), e.g. in
+/// vanity URLs like `/orgs/acme-corp`.
+///
+/// Slugs consist of lowercase ASCII letters, digits and single hyphens between them. A slug
+/// stays reserved for its organization after it's replaced, so that old URLs keep working.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Slug(String);
+
+impl Slug {
+    /// Shortest allowed slug.
+    pub const MIN_LENGTH: usize = 3;
+    /// Longest allowed slug, which is the longest DNS label, so slugs can be used as subdomains.
+    pub const MAX_LENGTH: usize = 63;
+
+    /// Normalizes and validates a slug.
+    pub fn parse(value: &str) -> Result<Self> {
+        let value = value.trim().to_ascii_lowercase();
+
+        if !(Self::MIN_LENGTH..=Self::MAX_LENGTH).contains(&value.len()) {
+            return Err(DomainError::validation(
+                "Slug",
+                format!(
+                    "must be between {} and {} characters long",
+                    Self::MIN_LENGTH,
+                    Self::MAX_LENGTH
+                ),
+            ));
+        }
+        if !value
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'-')
+        {
+            return Err(DomainError::validation(
+                "Slug",
+                "may only contain letters, digits and '-'",
+            ));
+        }
+        if value.starts_with('-')
+            || value.ends_with('-')
+            || value.contains("--")
+        {
+            return Err(DomainError::validation(
+                "Slug",
+                "must start and end with a letter or a digit, and not contain '--'",
+            ));
+        }
+
+        Ok(Slug(value))
+    }
+
+    /// Derives a slug from a display name, e.g. `acme-corp` from `ACME Corp.`.
+    ///
+    /// Letters outside of ASCII are dropped, and any other characters become hyphens. Returns
+    /// `None` if too little of the name is left to make a slug.
+    pub fn from_name(name: &str) -> Option<Self> {
+        let mut slug = String::with_capacity(name.len());
+        for c in name.chars() {
+            if c.is_ascii_alphanumeric() {
+                slug.push(c.to_ascii_lowercase());
+            } else if !c.is_alphanumeric() && !slug.ends_with('-') {
+                slug.push('-');
+            }
+        }
+
+        Slug::parse(truncate(slug.trim_matches('-'), Self::MAX_LENGTH)).ok()
+    }
+
+    /// A random slug for the organizations whose names can't make one, e.g. `org-3f9a1c2e`.
+    pub fn random() -> Self {
+        let digits = Uuid::new_v4().simple().to_string();
+
+        Slug(format!("{RANDOM_PREFIX}{}", &digits[..RANDOM_DIGITS]))
+    }
+
+    /// Appends a numeric suffix to the slug, e.g. `acme-2`, shortening it if needed.
+    pub fn with_suffix(&self, suffix: u32) -> Self {
+        let suffix = format!("-{suffix}");
+        let base = truncate(&self.0, Self::MAX_LENGTH - suffix.len());
+
+        Slug(format!("{base}{suffix}"))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn into_string(self) -> String {
+        self.0
+    }
+}
+
+impl AsRef<str> for Slug {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for Slug {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Shortens an ASCII slug, so that it still ends with a letter or a digit.
+fn truncate(slug: &str, max_length: usize) -> &str {
+    slug[..slug.len().min(max_length)].trim_end_matches('-')
+}
@@ -0,0 +1,126 @@
+use chrono::{DateTime, Utc};
+use identify_macros::gen_model;
+use uuid::Uuid;
+
+use crate::{DomainError, Result};
+
+/// Longest allowed reason of a [ReverificationCampaign].
+const MAX_REASON_LENGTH: usize = 500;
+/// Longest allowed email domain of a [ReverificationCohort].
+const MAX_DOMAIN_LENGTH: usize = 253;
+
+/// Users targeted by a [ReverificationCampaign]. A user belongs to the cohort if they match all
+/// the set criteria, so an empty cohort contains everyone.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReverificationCohort {
+    /// Domain of the emails, e.g. `acme.test`, matched regardless of the case.
+    pub email_domain: Option<String>,
+    /// Only users created before this time belong to the cohort.
+    pub created_before: Option<DateTime<Utc>>,
+}
+
+gen_model! {
+    /// A campaign that asks a cohort of [Users](crate::User) to verify their emails again, e.g.
+    /// after their email domain has been migrated.
+    ///
+    /// The users are flagged when the campaign starts, so users created later aren't notified.
+    #[derive(Debug, Clone)]
+    pub struct ReverificationCampaign {
+        /// Unique ID of the campaign.
+        #[get(into(Uuid))]
+        #[new(skip)]
+        id: Uuid,
+        /// Why the users have to verify their emails again, included in the notifications.
+        #[get(as_ref(&str))]
+        reason: String,
+        cohort: ReverificationCohort,
+        #[new(skip)]
+        created_at: DateTime<Utc>,
+    }
+
+    #[derive(Debug)]
+    pub struct NewReverificationCampaignAttrs;
+
+    #[derive(Debug)]
+    pub struct ReverificationCampaignAttrs;
+}
+
+impl ReverificationCampaign {
+    pub fn new(attrs: NewReverificationCampaignAttrs) -> Result<Self> {
+        let reason = attrs.reason.trim().to_owned();
+        validate_reason(&reason)?;
+        let cohort = ReverificationCohort {
+            email_domain: attrs.cohort.email_domain.map(|domain| {
+                domain.trim().trim_start_matches('@').to_lowercase()
+            }),
+            created_before: attrs.cohort.created_before,
+        };
+        validate_cohort(&cohort)?;
+
+        Ok(ReverificationCampaign {
+            id: Uuid::new_v4(),
+            reason,
+            cohort,
+            created_at: Utc::now(),
+        })
+    }
+
+    pub fn load(attrs: ReverificationCampaignAttrs) -> Result<Self> {
+        validate_reason(&attrs.reason)?;
+        validate_cohort(&attrs.cohort)?;
+
+        Ok(ReverificationCampaign {
+            id: attrs.id,
+            reason: attrs.reason,
+            cohort: attrs.cohort,
+            created_at: attrs.created_at,
+        })
+    }
+
+    pub fn to_attributes(&self) -> ReverificationCampaignAttrs {
+        ReverificationCampaignAttrs {
+            id: self.id,
+            reason: self.reason.clone(),
+            cohort: self.cohort.clone(),
+            created_at: self.created_at,
+        }
+    }
+}
+
+fn validate_reason(reason: &str) -> Result<()> {
+    let length = reason.chars().count();
+    if !(1..=MAX_REASON_LENGTH).contains(&length) {
+        return Err(DomainError::validation(
+            "ReverificationCampaign",
+            format!(
+                "reason must be between 1 and {MAX_REASON_LENGTH} characters long"
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+fn validate_cohort(cohort: &ReverificationCohort) -> Result<()> {
+    let Some(domain) = &cohort.email_domain else {
+        return Ok(());
+    };
+
+    // Domains are matched as they are, so they are limited to the characters of domain names.
+    let valid = !domain.is_empty()
+        && domain.len() <= MAX_DOMAIN_LENGTH
+        && domain.bytes().all(|b| {
+            b.is_ascii_lowercase()
+                || b.is_ascii_digit()
+                || b == b'.'
+                || b == b'-'
+        });
+    if !valid {
+        return Err(DomainError::validation(
+            "ReverificationCampaign",
+            format!("'{domain}' isn't a valid email domain"),
+        ));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,328 @@
+use chrono::{DateTime, Utc};
+use identify_macros::gen_model;
+use uuid::Uuid;
+
+use crate::{DomainError, Result, entities::validate_external_id};
+
+/// Longest allowed name of a [Role].
+const MAX_NAME_LENGTH: usize = 64;
+/// Longest allowed description of a [Role].
+const MAX_DESCRIPTION_LENGTH: usize = 500;
+/// Longest allowed permission of a [Role].
+const MAX_PERMISSION_LENGTH: usize = 100;
+/// Longest allowed email domain of a [RoleCohort].
+const MAX_DOMAIN_LENGTH: usize = 253;
+
+gen_model! {
+    /// Named set of permissions granted to the [Users](crate::User) the role is assigned to,
+    /// e.g. `support` with `users:read` and `users:write`.
+    #[derive(Debug, Clone)]
+    pub struct Role {
+        /// Unique ID of the role.
+        #[get(into(Uuid))]
+        #[new(skip)]
+        id: Uuid,
+        /// Unique name of the role, made of lowercase letters, digits, `-` and `_`.
+        #[get(as_ref(&str))]
+        name: String,
+        /// What the role is for.
+        #[get(as_ref(Option<&String>))]
+        description: Option<String>,
+        /// Permissions of the role, e.g. `users:read`, in the order they were granted.
+        #[get(as_ref(&[String]))]
+        permissions: Vec<String>,
+        /// ID assigned to the role by an external tool, e.g. infrastructure as code, that is
+        /// unique among all roles.
+        #[get(as_ref(Option<&String>))]
+        external_id: Option<String>,
+        #[new(skip)]
+        created_at: DateTime<Utc>,
+        #[new(skip)]
+        updated_at: DateTime<Utc>,
+    }
+
+    #[derive(Debug, Clone)]
+    pub struct NewRoleAttrs;
+
+    #[derive(Debug)]
+    pub struct RoleAttrs;
+}
+
+impl Role {
+    pub fn new(attrs: NewRoleAttrs) -> Result<Self> {
+        let name = attrs.name.trim().to_owned();
+        validate_name(&name)?;
+        let description = normalize_description(attrs.description)?;
+        let mut permissions = Vec::with_capacity(attrs.permissions.len());
+        for permission in attrs.permissions {
+            validate_permission(&permission)?;
+            if !permissions.contains(&permission) {
+                permissions.push(permission);
+            }
+        }
+        if let Some(external_id) = &attrs.external_id {
+            validate_external_id("Role", external_id)?;
+        }
+
+        let now = Utc::now();
+        Ok(Role {
+            id: Uuid::new_v4(),
+            name,
+            description,
+            permissions,
+            external_id: attrs.external_id,
+            created_at: now,
+            updated_at: now,
+        })
+    }
+
+    pub fn load(attrs: RoleAttrs) -> Result<Self> {
+        validate_name(&attrs.name)?;
+        for permission in &attrs.permissions {
+            validate_permission(permission)?;
+        }
+        if let Some(external_id) = &attrs.external_id {
+            validate_external_id("Role", external_id)?;
+        }
+
+        Ok(Role {
+            id: attrs.id,
+            name: attrs.name,
+            description: attrs.description,
+            permissions: attrs.permissions,
+            external_id: attrs.external_id,
+            created_at: attrs.created_at,
+            updated_at: attrs.updated_at,
+        })
+    }
+
+    pub fn to_attributes(&self) -> RoleAttrs {
+        RoleAttrs {
+            id: self.id,
+            name: self.name.clone(),
+            description: self.description.clone(),
+            permissions: self.permissions.clone(),
+            external_id: self.external_id.clone(),
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+        }
+    }
+
+    /// Renames the role.
+    ///
+    /// Returns `false` if the name is the same as before and nothing has changed.
+    pub fn rename(&mut self, name: &str) -> Result<bool> {
+        let name = name.trim();
+        validate_name(name)?;
+        if self.name == name {
+            return Ok(false);
+        }
+
+        self.name = name.to_owned();
+        self.updated_at = Utc::now();
+
+        Ok(true)
+    }
+
+    /// Changes the description of the role, or removes it if it's blank.
+    ///
+    /// Returns `false` if the description is the same as before and nothing has changed.
+    pub fn describe(&mut self, description: Option<String>) -> Result<bool> {
+        let description = normalize_description(description)?;
+        if self.description == description {
+            return Ok(false);
+        }
+
+        self.description = description;
+        self.updated_at = Utc::now();
+
+        Ok(true)
+    }
+
+    /// Grants the permission to the role.
+    ///
+    /// Returns `false` if the role already had it and nothing has changed.
+    pub fn grant(&mut self, permission: &str) -> Result<bool> {
+        validate_permission(permission)?;
+        if self.has_permission(permission) {
+            return Ok(false);
+        }
+
+        self.permissions.push(permission.to_owned());
+        self.updated_at = Utc::now();
+
+        Ok(true)
+    }
+
+    /// Revokes the permission from the role.
+    ///
+    /// Returns `false` if the role didn't have it and nothing has changed.
+    pub fn revoke(&mut self, permission: &str) -> bool {
+        if !self.has_permission(permission) {
+            return false;
+        }
+
+        self.permissions.retain(|p| p != permission);
+        self.updated_at = Utc::now();
+
+        true
+    }
+
+    /// Replaces the permissions of the role, keeping the order they're listed in.
+    ///
+    /// Returns `false` if the role already had exactly these permissions and nothing has changed.
+    pub fn set_permissions(
+        &mut self,
+        permissions: Vec<String>,
+    ) -> Result<bool> {
+        let mut deduplicated = Vec::with_capacity(permissions.len());
+        for permission in permissions {
+            validate_permission(&permission)?;
+            if !deduplicated.contains(&permission) {
+                deduplicated.push(permission);
+            }
+        }
+        if self.permissions == deduplicated {
+            return Ok(false);
+        }
+
+        self.permissions = deduplicated;
+        self.updated_at = Utc::now();
+
+        Ok(true)
+    }
+
+    pub fn has_permission(&self, permission: &str) -> bool {
+        self.permissions.iter().any(|p| p == permission)
+    }
+}
+
+/// Assignment of a [Role] to a [User](crate::User), who is granted its permissions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoleAssignment {
+    pub role_id: Uuid,
+    pub user_id: Uuid,
+    pub assigned_at: DateTime<Utc>,
+}
+
+/// Users a [Role] is assigned to at once. A user belongs to the cohort if they match all the
+/// set criteria, at least one of which must be set so that a role is never assigned to everyone
+/// by mistake.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoleCohort {
+    email_domain: Option<String>,
+    organization_id: Option<Uuid>,
+}
+
+impl RoleCohort {
+    pub fn new(
+        email_domain: Option<String>,
+        organization_id: Option<Uuid>,
+    ) -> Result<Self> {
+        let email_domain = email_domain
+            .map(|domain| domain.trim().trim_start_matches('@').to_lowercase());
+        if email_domain.is_none() && organization_id.is_none() {
+            return Err(DomainError::validation(
+                "RoleCohort",
+                "at least one criterion must be set",
+            ));
+        }
+        if let Some(domain) = &email_domain {
+            validate_domain(domain)?;
+        }
+
+        Ok(RoleCohort {
+            email_domain,
+            organization_id,
+        })
+    }
+
+    /// Domain of the emails, e.g. `acme.test`, in lowercase.
+    pub fn email_domain(&self) -> Option<&str> {
+        self.email_domain.as_deref()
+    }
+
+    /// ID of an organization the users are members of.
+    pub fn organization_id(&self) -> Option<Uuid> {
+        self.organization_id
+    }
+}
+
+fn validate_name(name: &str) -> Result<()> {
+    let valid_chars = name.chars().all(|c| {
+        c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_'
+    });
+    if name.is_empty() || name.len() > MAX_NAME_LENGTH || !valid_chars {
+        return Err(DomainError::validation(
+            "Role",
+            format!(
+                "name must be 1 to {MAX_NAME_LENGTH} lowercase letters, digits, '-' or '_'"
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+fn normalize_description(
+    description: Option<String>,
+) -> Result<Option<String>> {
+    let description = description
+        .map(|description| description.trim().to_owned())
+        .filter(|description| !description.is_empty());
+    if description
+        .as_ref()
+        .is_some_and(|d| d.chars().count() > MAX_DESCRIPTION_LENGTH)
+    {
+        return Err(DomainError::validation(
+            "Role",
+            format!(
+                "description must be at most {MAX_DESCRIPTION_LENGTH} characters long"
+            ),
+        ));
+    }
+
+    Ok(description)
+}
+
+/// Permissions look like `<resource>:<action>`, e.g. `users:read`, and are stored in lists
+/// separated by commas, so they can't contain any.
+fn validate_permission(permission: &str) -> Result<()> {
+    let valid = permission.len() <= MAX_PERMISSION_LENGTH
+        && permission
+            .split_once(':')
+            .is_some_and(|(resource, action)| {
+                !resource.is_empty() && !action.is_empty()
+            })
+        && permission.chars().all(|c| c.is_ascii_graphic() && c != ',');
+    if !valid {
+        return Err(DomainError::validation(
+            "Role",
+            format!(
+                "'{permission}' is not a valid permission, e.g. 'users:read'"
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+fn validate_domain(domain: &str) -> Result<()> {
+    // Domains are matched as they are, so they are limited to the characters of domain names.
+    let valid = !domain.is_empty()
+        && domain.len() <= MAX_DOMAIN_LENGTH
+        && domain.bytes().all(|b| {
+            b.is_ascii_lowercase()
+                || b.is_ascii_digit()
+                || b == b'.'
+                || b == b'-'
+        });
+    if !valid {
+        return Err(DomainError::validation(
+            "RoleCohort",
+            format!("'{domain}' isn't a valid email domain"),
+        ));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,160 @@
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use identify_macros::gen_model;
+use uuid::Uuid;
+
+use crate::{DomainError, Result};
+
+gen_model! {
+    /// State of a [SigningKey].
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[model(display, from_str(DomainError))]
+    pub enum SigningKeyStatus {
+        /// New tokens are signed with the key. At most one key is active at a time.
+        Active,
+        /// The key was replaced by a newer one. Tokens it signed can still be verified until it's
+        /// deleted.
+        Retired,
+    }
+}
+
+gen_model! {
+    /// A key the tokens issued by the service are signed with.
+    ///
+    /// Public keys are published in the JWKS of the service, so that clients can verify the
+    /// tokens. Private keys never leave it.
+    pub struct SigningKey {
+        /// Unique ID of the key, which tokens name in their `kid` header.
+        #[get(into(Uuid))]
+        #[new(skip)]
+        id: Uuid,
+        /// JWS algorithm of the key, e.g. `EdDSA`.
+        #[get(as_ref(&str))]
+        algorithm: String,
+        /// Public key the tokens are verified with.
+        #[get(as_ref(&[u8]))]
+        public_key: Vec<u8>,
+        /// Private key the tokens are signed with.
+        #[get(as_ref(&[u8]))]
+        private_key: Vec<u8>,
+        /// Current state of the key.
+        #[get(copy)]
+        #[new(skip)]
+        status: SigningKeyStatus,
+        #[new(skip)]
+        created_at: DateTime<Utc>,
+        /// When the key was replaced by a newer one.
+        #[new(skip)]
+        retired_at: Option<DateTime<Utc>>,
+    }
+
+    pub struct NewSigningKeyAttrs;
+
+    pub struct SigningKeyAttrs;
+}
+
+impl SigningKey {
+    /// Creates a key that is active right away.
+    pub fn new(attrs: NewSigningKeyAttrs) -> Result<Self> {
+        validate(&attrs.algorithm, &attrs.public_key, &attrs.private_key)?;
+
+        Ok(SigningKey {
+            id: Uuid::new_v4(),
+            algorithm: attrs.algorithm,
+            public_key: attrs.public_key,
+            private_key: attrs.private_key,
+            status: SigningKeyStatus::Active,
+            created_at: Utc::now(),
+            retired_at: None,
+        })
+    }
+
+    pub fn load(attrs: SigningKeyAttrs) -> Result<Self> {
+        validate(&attrs.algorithm, &attrs.public_key, &attrs.private_key)?;
+        if (attrs.status == SigningKeyStatus::Retired)
+            != attrs.retired_at.is_some()
+        {
+            return Err(DomainError::validation(
+                "SigningKey",
+                "retired_at must be set if and only if the key is retired",
+            ));
+        }
+
+        Ok(SigningKey {
+            id: attrs.id,
+            algorithm: attrs.algorithm,
+            public_key: attrs.public_key,
+            private_key: attrs.private_key,
+            status: attrs.status,
+            created_at: attrs.created_at,
+            retired_at: attrs.retired_at,
+        })
+    }
+
+    pub fn to_attributes(&self) -> SigningKeyAttrs {
+        SigningKeyAttrs {
+            id: self.id,
+            algorithm: self.algorithm.clone(),
+            public_key: self.public_key.clone(),
+            private_key: self.private_key.clone(),
+            status: self.status,
+            created_at: self.created_at,
+            retired_at: self.retired_at,
+        }
+    }
+
+    /// Whether new tokens are signed with the key.
+    pub fn is_active(&self) -> bool {
+        self.status == SigningKeyStatus::Active
+    }
+
+    /// Stops signing new tokens with the key, once a newer one replaces it.
+    pub fn retire(&mut self) -> Result<()> {
+        if !self.is_active() {
+            return Err(DomainError::invalid_state_transition(
+                "SigningKey",
+                "the key is already retired",
+            ));
+        }
+
+        self.status = SigningKeyStatus::Retired;
+        self.retired_at = Some(Utc::now());
+
+        Ok(())
+    }
+}
+
+// The private key is left out, so that it doesn't end up in logs.
+impl fmt::Debug for SigningKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SigningKey")
+            .field("id", &self.id)
+            .field("algorithm", &self.algorithm)
+            .field("status", &self.status)
+            .field("created_at", &self.created_at)
+            .field("retired_at", &self.retired_at)
+            .finish_non_exhaustive()
+    }
+}
+
+fn validate(
+    algorithm: &str,
+    public_key: &[u8],
+    private_key: &[u8],
+) -> Result<()> {
+    if algorithm.is_empty() {
+        return Err(DomainError::validation(
+            "SigningKey",
+            "algorithm must not be empty",
+        ));
+    }
+    if public_key.is_empty() || private_key.is_empty() {
+        return Err(DomainError::validation(
+            "SigningKey",
+            "keys must not be empty",
+        ));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,213 @@
+use chrono::{DateTime, Utc};
+use identify_macros::gen_model;
+use uuid::Uuid;
+
+use crate::{DomainError, Result};
+
+/// Longest allowed key of a [UserAttribute].
+const MAX_KEY_LENGTH: usize = 64;
+/// Longest allowed value of a [UserAttribute] once encoded, in bytes.
+const MAX_VALUE_LENGTH: usize = 16 * 1024;
+
+gen_model! {
+    /// Type of the value of a [UserAttribute].
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[model(display, from_str(DomainError))]
+    pub enum AttributeType {
+        String,
+        Number,
+        Bool,
+        /// Any JSON document, e.g. an object or an array.
+        Json,
+    }
+}
+
+/// Value of a [UserAttribute].
+#[derive(Debug, Clone, PartialEq)]
+pub enum AttributeValue {
+    String(String),
+    /// A finite number.
+    Number(f64),
+    Bool(bool),
+    Json(serde_json::Value),
+}
+
+impl AttributeValue {
+    pub fn value_type(&self) -> AttributeType {
+        match self {
+            AttributeValue::String(_) => AttributeType::String,
+            AttributeValue::Number(_) => AttributeType::Number,
+            AttributeValue::Bool(_) => AttributeType::Bool,
+            AttributeValue::Json(_) => AttributeType::Json,
+        }
+    }
+
+    /// Encodes the value as text, e.g. to be stored next to its type.
+    pub fn encode(&self) -> String {
+        match self {
+            AttributeValue::String(value) => value.clone(),
+            AttributeValue::Number(value) => value.to_string(),
+            AttributeValue::Bool(value) => value.to_string(),
+            AttributeValue::Json(value) => value.to_string(),
+        }
+    }
+
+    /// Decodes a value of the type from its [encoding](AttributeValue::encode).
+    pub fn decode(value_type: AttributeType, encoded: &str) -> Result<Self> {
+        let invalid = || {
+            DomainError::validation(
+                "UserAttribute",
+                format!("'{encoded}' is not a valid {value_type} value"),
+            )
+        };
+
+        let value = match value_type {
+            AttributeType::String => AttributeValue::String(encoded.to_owned()),
+            AttributeType::Number => {
+                AttributeValue::Number(encoded.parse().map_err(|_| invalid())?)
+            }
+            AttributeType::Bool => {
+                AttributeValue::Bool(encoded.parse().map_err(|_| invalid())?)
+            }
+            AttributeType::Json => AttributeValue::Json(
+                serde_json::from_str(encoded).map_err(|_| invalid())?,
+            ),
+        };
+
+        Ok(value)
+    }
+}
+
+gen_model! {
+    /// Custom data an application attaches to the profile of a [User](crate::User), e.g. a
+    /// preference, stored under a key that is unique per user.
+    #[derive(Debug, Clone)]
+    pub struct UserAttribute {
+        /// ID of the user the attribute belongs to.
+        #[get(into(Uuid))]
+        user_id: Uuid,
+        /// Key of the attribute, made of ASCII letters, digits, `.`, `_` and `-`, and starting
+        /// with a letter.
+        #[get(as_ref(&str))]
+        key: String,
+        #[hydrate(skip)]
+        value: AttributeValue,
+        #[new(skip)]
+        created_at: DateTime<Utc>,
+        #[new(skip)]
+        updated_at: DateTime<Utc>,
+    }
+
+    #[derive(Debug)]
+    pub struct NewUserAttributeAttrs;
+
+    #[derive(Debug)]
+    pub struct UserAttributeAttrs {
+        /// Type of [UserAttribute::value].
+        value_type: AttributeType,
+        /// [UserAttribute::value], see [AttributeValue::encode].
+        encoded_value: String,
+    }
+}
+
+impl UserAttribute {
+    /// Most attributes a single user can have.
+    pub const MAX_PER_USER: usize = 100;
+
+    pub fn new(attrs: NewUserAttributeAttrs) -> Result<Self> {
+        validate_key(&attrs.key)?;
+        validate_value(&attrs.value)?;
+
+        let now = Utc::now();
+        Ok(UserAttribute {
+            user_id: attrs.user_id,
+            key: attrs.key,
+            value: attrs.value,
+            created_at: now,
+            updated_at: now,
+        })
+    }
+
+    pub fn load(attrs: UserAttributeAttrs) -> Result<Self> {
+        validate_key(&attrs.key)?;
+
+        Ok(UserAttribute {
+            user_id: attrs.user_id,
+            key: attrs.key,
+            value: AttributeValue::decode(
+                attrs.value_type,
+                &attrs.encoded_value,
+            )?,
+            created_at: attrs.created_at,
+            updated_at: attrs.updated_at,
+        })
+    }
+
+    pub fn to_attributes(&self) -> UserAttributeAttrs {
+        UserAttributeAttrs {
+            user_id: self.user_id,
+            key: self.key.clone(),
+            value_type: self.value.value_type(),
+            encoded_value: self.value.encode(),
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+        }
+    }
+
+    /// Changes the value, returning `false` if it's the same as before.
+    pub fn change_value(&mut self, value: AttributeValue) -> Result<bool> {
+        validate_value(&value)?;
+        if self.value == value {
+            return Ok(false);
+        }
+
+        self.value = value;
+        self.updated_at = Utc::now();
+
+        Ok(true)
+    }
+}
+
+fn validate_key(key: &str) -> Result<()> {
+    if key.is_empty() || key.len() > MAX_KEY_LENGTH {
+        return Err(DomainError::validation(
+            "UserAttribute",
+            format!(
+                "key must be between 1 and {MAX_KEY_LENGTH} characters long"
+            ),
+        ));
+    }
+    if !key.starts_with(|c: char| c.is_ascii_alphabetic())
+        || !key.bytes().all(|b| {
+            b.is_ascii_alphanumeric() || matches!(b, b'.' | b'_' | b'-')
+        })
+    {
+        return Err(DomainError::validation(
+            "UserAttribute",
+            format!(
+                "key '{key}' may only contain letters, digits, '.', '_' and '-', and must start with a letter"
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+fn validate_value(value: &AttributeValue) -> Result<()> {
+    if let AttributeValue::Number(number) = value
+        && !number.is_finite()
+    {
+        return Err(DomainError::validation(
+            "UserAttribute",
+            "numbers must be finite",
+        ));
+    }
+    if value.encode().len() > MAX_VALUE_LENGTH {
+        return Err(DomainError::validation(
+            "UserAttribute",
+            format!("values must be at most {MAX_VALUE_LENGTH} bytes long"),
+        ));
+    }
+
+    Ok(())
+}
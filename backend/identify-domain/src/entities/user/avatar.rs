@@ -0,0 +1,125 @@
+use std::fmt;
+
+use crate::{DomainError, Result};
+
+/// Image formats an [Avatar] can be uploaded in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AvatarFormat {
+    Png,
+    Jpeg,
+    Gif,
+    Webp,
+}
+
+impl AvatarFormat {
+    pub const ALL: [AvatarFormat; 4] = [
+        AvatarFormat::Png,
+        AvatarFormat::Jpeg,
+        AvatarFormat::Gif,
+        AvatarFormat::Webp,
+    ];
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            AvatarFormat::Png => "image/png",
+            AvatarFormat::Jpeg => "image/jpeg",
+            AvatarFormat::Gif => "image/gif",
+            AvatarFormat::Webp => "image/webp",
+        }
+    }
+
+    /// Returns the format of a content type, ignoring its parameters.
+    pub fn from_content_type(content_type: &str) -> Option<Self> {
+        let essence = content_type.split(';').next().unwrap_or_default().trim();
+
+        AvatarFormat::ALL
+            .into_iter()
+            .find(|format| format.content_type().eq_ignore_ascii_case(essence))
+    }
+
+    /// Returns whether the bytes start with the signature of the format.
+    fn matches(&self, bytes: &[u8]) -> bool {
+        match self {
+            AvatarFormat::Png => bytes.starts_with(b"\x89PNG\r\n\x1a\n"),
+            AvatarFormat::Jpeg => bytes.starts_with(b"\xff\xd8\xff"),
+            AvatarFormat::Gif => {
+                bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a")
+            }
+            AvatarFormat::Webp => {
+                bytes.len() >= 12
+                    && bytes.starts_with(b"RIFF")
+                    && &bytes[8..12] == b"WEBP"
+            }
+        }
+    }
+}
+
+impl fmt::Display for AvatarFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.content_type())
+    }
+}
+
+/// The picture of a [User](super::User).
+///
+/// The image itself isn't decoded, it's only checked to start with the signature of its
+/// declared format, so that a file can't be served with the content type of another one.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Avatar {
+    format: AvatarFormat,
+    bytes: Vec<u8>,
+}
+
+impl Avatar {
+    /// Largest allowed avatar, in bytes.
+    pub const MAX_SIZE: usize = 1024 * 1024;
+
+    /// Validates an avatar of the content type.
+    pub fn parse(content_type: &str, bytes: Vec<u8>) -> Result<Self> {
+        let format =
+            AvatarFormat::from_content_type(content_type).ok_or_else(|| {
+                DomainError::validation(
+                    "Avatar",
+                    format!(
+                        "the content type '{content_type}' isn't supported, expected one of: {}",
+                        AvatarFormat::ALL.map(|format| format.content_type()).join(", ")
+                    ),
+                )
+            })?;
+        if bytes.is_empty() || bytes.len() > Self::MAX_SIZE {
+            return Err(DomainError::validation(
+                "Avatar",
+                format!("must be between 1 and {} bytes long", Self::MAX_SIZE),
+            ));
+        }
+        if !format.matches(&bytes) {
+            return Err(DomainError::validation(
+                "Avatar",
+                format!("the content isn't a valid {format} image"),
+            ));
+        }
+
+        Ok(Avatar { format, bytes })
+    }
+
+    pub fn format(&self) -> AvatarFormat {
+        self.format
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+impl fmt::Debug for Avatar {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Avatar")
+            .field("format", &self.format)
+            .field("size", &self.bytes.len())
+            .finish()
+    }
+}
@@ -0,0 +1,120 @@
+use chrono::{DateTime, Utc};
+use identify_macros::gen_model;
+use uuid::Uuid;
+
+use crate::{DomainError, Result};
+
+/// Longest allowed name or version of a policy.
+const MAX_POLICY_LENGTH: usize = 64;
+
+gen_model! {
+    /// The acceptance of a version of a policy by a [User](crate::User), e.g. of the terms of
+    /// service.
+    ///
+    /// Consents are never changed. Accepting a new version of a policy records another consent,
+    /// so that the history of what the user agreed to is kept.
+    #[derive(Debug, Clone)]
+    pub struct Consent {
+        #[get(into(Uuid))]
+        #[new(skip)]
+        id: Uuid,
+        /// ID of the user who accepted the policy.
+        #[get(into(Uuid))]
+        user_id: Uuid,
+        /// Name of the policy, e.g. `terms`.
+        #[get(as_ref(&str))]
+        policy: String,
+        /// Version of the policy the user accepted, e.g. `2026-10`.
+        #[get(as_ref(&str))]
+        version: String,
+        #[new(skip)]
+        accepted_at: DateTime<Utc>,
+    }
+
+    #[derive(Debug)]
+    pub struct NewConsentAttrs;
+
+    #[derive(Debug)]
+    pub struct ConsentAttrs;
+}
+
+impl Consent {
+    pub fn new(attrs: NewConsentAttrs) -> Result<Self> {
+        validate_policy_name(&attrs.policy)?;
+        validate_policy_version(&attrs.version)?;
+
+        Ok(Consent {
+            id: Uuid::new_v4(),
+            user_id: attrs.user_id,
+            policy: attrs.policy,
+            version: attrs.version,
+            accepted_at: Utc::now(),
+        })
+    }
+
+    pub fn load(attrs: ConsentAttrs) -> Result<Self> {
+        validate_policy_name(&attrs.policy)?;
+        validate_policy_version(&attrs.version)?;
+
+        Ok(Consent {
+            id: attrs.id,
+            user_id: attrs.user_id,
+            policy: attrs.policy,
+            version: attrs.version,
+            accepted_at: attrs.accepted_at,
+        })
+    }
+
+    pub fn to_attributes(&self) -> ConsentAttrs {
+        ConsentAttrs {
+            id: self.id,
+            user_id: self.user_id,
+            policy: self.policy.clone(),
+            version: self.version.clone(),
+            accepted_at: self.accepted_at,
+        }
+    }
+
+    /// Whether the consent is for the provided version of the policy.
+    pub fn is_for(&self, policy: &str, version: &str) -> bool {
+        self.policy == policy && self.version == version
+    }
+}
+
+/// Names of policies are made of lowercase ASCII letters, digits, `-` and `_`, e.g.
+/// `terms` or `privacy-policy`.
+pub(crate) fn validate_policy_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name.len() <= MAX_POLICY_LENGTH
+        && name.bytes().all(|b| {
+            b.is_ascii_lowercase()
+                || b.is_ascii_digit()
+                || b == b'-'
+                || b == b'_'
+        });
+    if !valid {
+        return Err(DomainError::validation(
+            "Consent",
+            format!(
+                "'{name}' is not a valid policy name, which must be made of at most \
+                 {MAX_POLICY_LENGTH} lowercase letters, digits, '-' and '_'"
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+pub(crate) fn validate_policy_version(version: &str) -> Result<()> {
+    if version.trim().is_empty() || version.chars().count() > MAX_POLICY_LENGTH
+    {
+        return Err(DomainError::validation(
+            "Consent",
+            format!(
+                "version must be between 1 and {MAX_POLICY_LENGTH} characters long"
+            ),
+        ));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,182 @@
+use std::net::IpAddr;
+
+use chrono::{DateTime, Utc};
+use identify_macros::gen_model;
+use uuid::Uuid;
+
+use crate::{DomainError, Result};
+
+/// Longest allowed fingerprint of a [Device].
+const MAX_FINGERPRINT_LENGTH: usize = 256;
+/// Longest stored user agent of a [Device]. Longer ones are truncated.
+const MAX_USER_AGENT_LENGTH: usize = 512;
+
+gen_model! {
+    /// A device a [User](crate::User) logged in from, e.g. a browser on a laptop.
+    ///
+    /// Logins from the same device are recognized by its fingerprint, and the device keeps the
+    /// metadata of the latest one. Revoked devices are no longer trusted, so a later login with
+    /// the same fingerprint counts as a new device.
+    #[derive(Debug, Clone)]
+    pub struct Device {
+        #[get(into(Uuid))]
+        #[new(skip)]
+        id: Uuid,
+        /// ID of the user who logged in from the device.
+        #[get(into(Uuid))]
+        user_id: Uuid,
+        /// Opaque value that identifies the device across logins, e.g. a long-lived cookie set
+        /// by the login page.
+        #[get(as_ref(&str))]
+        fingerprint: String,
+        /// `User-Agent` of the latest login.
+        #[get(as_ref(&str))]
+        user_agent: String,
+        /// IP address of the latest login.
+        #[get(copy)]
+        #[hydrate(type(String))]
+        ip: IpAddr,
+        /// Approximate location of the IP address, e.g. `Paris, France`, if it's known.
+        location: Option<String>,
+        #[new(skip)]
+        first_seen_at: DateTime<Utc>,
+        #[new(skip)]
+        last_seen_at: DateTime<Utc>,
+        /// When the user stopped trusting the device.
+        #[new(skip)]
+        revoked_at: Option<DateTime<Utc>>,
+    }
+
+    #[derive(Debug)]
+    pub struct NewDeviceAttrs;
+
+    #[derive(Debug)]
+    pub struct DeviceAttrs;
+}
+
+impl Device {
+    pub fn new(attrs: NewDeviceAttrs) -> Result<Self> {
+        validate_fingerprint(&attrs.fingerprint)?;
+
+        let now = Utc::now();
+        Ok(Device {
+            id: Uuid::new_v4(),
+            user_id: attrs.user_id,
+            fingerprint: attrs.fingerprint,
+            user_agent: truncate_user_agent(attrs.user_agent),
+            ip: attrs.ip,
+            location: attrs.location,
+            first_seen_at: now,
+            last_seen_at: now,
+            revoked_at: None,
+        })
+    }
+
+    pub fn load(attrs: DeviceAttrs) -> Result<Self> {
+        validate_fingerprint(&attrs.fingerprint)?;
+        let ip = attrs.ip.parse().map_err(|_| {
+            DomainError::validation(
+                "Device",
+                format!("'{}' is not a valid IP address", attrs.ip),
+            )
+        })?;
+
+        Ok(Device {
+            id: attrs.id,
+            user_id: attrs.user_id,
+            fingerprint: attrs.fingerprint,
+            user_agent: attrs.user_agent,
+            ip,
+            location: attrs.location,
+            first_seen_at: attrs.first_seen_at,
+            last_seen_at: attrs.last_seen_at,
+            revoked_at: attrs.revoked_at,
+        })
+    }
+
+    pub fn to_attributes(&self) -> DeviceAttrs {
+        DeviceAttrs {
+            id: self.id,
+            user_id: self.user_id,
+            fingerprint: self.fingerprint.clone(),
+            user_agent: self.user_agent.clone(),
+            ip: self.ip.to_string(),
+            location: self.location.clone(),
+            first_seen_at: self.first_seen_at,
+            last_seen_at: self.last_seen_at,
+            revoked_at: self.revoked_at,
+        }
+    }
+
+    /// Returns the fingerprint of the devices that don't have one of their own, derived from
+    /// their user agent, so that at least different browsers are told apart.
+    pub fn fingerprint_of(user_agent: &str) -> String {
+        user_agent
+            .trim()
+            .chars()
+            .take(MAX_FINGERPRINT_LENGTH)
+            .collect()
+    }
+
+    pub fn is_revoked(&self) -> bool {
+        self.revoked_at.is_some()
+    }
+
+    /// Records another login from the device. Fails if the device is revoked.
+    pub fn record_login(
+        &mut self,
+        user_agent: String,
+        ip: IpAddr,
+        location: Option<String>,
+    ) -> Result<()> {
+        if self.is_revoked() {
+            return Err(DomainError::invalid_state_transition(
+                "Device",
+                "the device is revoked",
+            ));
+        }
+
+        self.user_agent = truncate_user_agent(user_agent);
+        self.ip = ip;
+        self.location = location;
+        self.last_seen_at = Utc::now();
+
+        Ok(())
+    }
+
+    /// Stops trusting the device, e.g. because it was lost or the login wasn't the user's.
+    pub fn revoke(&mut self) -> Result<()> {
+        if self.is_revoked() {
+            return Err(DomainError::invalid_state_transition(
+                "Device",
+                "the device is already revoked",
+            ));
+        }
+
+        self.revoked_at = Some(Utc::now());
+
+        Ok(())
+    }
+}
+
+fn validate_fingerprint(fingerprint: &str) -> Result<()> {
+    if fingerprint.trim().is_empty()
+        || fingerprint.chars().count() > MAX_FINGERPRINT_LENGTH
+    {
+        return Err(DomainError::validation(
+            "Device",
+            format!(
+                "fingerprint must be between 1 and {MAX_FINGERPRINT_LENGTH} characters long"
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+fn truncate_user_agent(user_agent: String) -> String {
+    match user_agent.char_indices().nth(MAX_USER_AGENT_LENGTH) {
+        Some((end, _)) => user_agent[..end].to_owned(),
+        None => user_agent,
+    }
+}
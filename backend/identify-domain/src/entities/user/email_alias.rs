@@ -0,0 +1,109 @@
+use chrono::{DateTime, Utc};
+use identify_macros::gen_model;
+use uuid::Uuid;
+
+use crate::{
+    DomainError, Result,
+    entities::user::id::{UserId, UserIdAttrs},
+};
+
+gen_model! {
+    /// How a [User](crate::User) got an [EmailAlias].
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[model(display, from_str(DomainError))]
+    pub enum EmailAliasKind {
+        /// Email the user had before they [changed it](crate::User::change_email).
+        Previous,
+        /// Added to the user, e.g. a tagged address like `jane+news@example.com`.
+        Added,
+    }
+}
+
+gen_model! {
+    /// Another email a [User](crate::User) can be found by, besides their current one and the
+    /// one they were created with.
+    ///
+    /// An alias has the ID a user created with its email would have, see [UserId], so an email
+    /// can be an alias of a single user only, and never of another user than the one it
+    /// identifies.
+    #[derive(Debug, Clone)]
+    pub struct EmailAlias {
+        #[get(into(Uuid))]
+        #[new(skip)]
+        id: Uuid,
+        /// ID of the user the email is an alias of.
+        #[get(into(Uuid))]
+        user_id: Uuid,
+        #[get(as_ref(&str))]
+        email: String,
+        #[get(copy)]
+        kind: EmailAliasKind,
+        #[new(skip)]
+        created_at: DateTime<Utc>,
+    }
+
+    #[derive(Debug)]
+    pub struct NewEmailAliasAttrs;
+
+    #[derive(Debug)]
+    pub struct EmailAliasAttrs;
+}
+
+impl EmailAlias {
+    pub fn new(attrs: NewEmailAliasAttrs) -> Result<Self> {
+        let email = attrs.email.trim().to_owned();
+        validate_email(&email)?;
+
+        Ok(EmailAlias {
+            id: EmailAlias::id_of(&email),
+            user_id: attrs.user_id,
+            email,
+            kind: attrs.kind,
+            created_at: Utc::now(),
+        })
+    }
+
+    pub fn load(attrs: EmailAliasAttrs) -> Result<Self> {
+        validate_email(&attrs.email)?;
+
+        Ok(EmailAlias {
+            id: attrs.id,
+            user_id: attrs.user_id,
+            email: attrs.email,
+            kind: attrs.kind,
+            created_at: attrs.created_at,
+        })
+    }
+
+    pub fn to_attributes(&self) -> EmailAliasAttrs {
+        EmailAliasAttrs {
+            id: self.id,
+            user_id: self.user_id,
+            email: self.email.clone(),
+            kind: self.kind,
+            created_at: self.created_at,
+        }
+    }
+
+    /// Returns the ID of the alias of the email, whichever user it belongs to.
+    pub fn id_of(email: &str) -> Uuid {
+        UserId::new(UserIdAttrs {
+            email: email.to_owned(),
+        })
+        .to_uuid()
+    }
+}
+
+fn validate_email(email: &str) -> Result<()> {
+    let valid = email
+        .split_once('@')
+        .is_some_and(|(local, domain)| !local.is_empty() && !domain.is_empty());
+    if !valid {
+        return Err(DomainError::validation(
+            "EmailAlias",
+            format!("'{email}' is not a valid email"),
+        ));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,146 @@
+use chrono::{DateTime, Utc};
+use identify_macros::gen_model;
+use uuid::Uuid;
+
+use crate::{DomainError, Result};
+
+gen_model! {
+    /// A request of a [User](crate::User) to change their email.
+    ///
+    /// The new address is emailed a link with a random token, of which only the hash is kept.
+    /// Following the link proves the user owns the new address, and only then is the email of
+    /// the user changed, see [User::change_email](crate::User::change_email).
+    #[derive(Debug, Clone)]
+    pub struct EmailChange {
+        #[get(into(Uuid))]
+        #[new(skip)]
+        id: Uuid,
+        /// ID of the user whose email is changed.
+        #[get(into(Uuid))]
+        user_id: Uuid,
+        /// Email the user changes theirs to.
+        #[get(as_ref(&str))]
+        email: String,
+        /// Hash of the token the change is confirmed with.
+        #[get(as_ref(&str))]
+        token_hash: String,
+        /// When the token can no longer be used.
+        expires_at: DateTime<Utc>,
+        #[new(skip)]
+        created_at: DateTime<Utc>,
+        /// When the user confirmed the change.
+        #[new(skip)]
+        confirmed_at: Option<DateTime<Utc>>,
+    }
+
+    #[derive(Debug)]
+    pub struct NewEmailChangeAttrs;
+
+    #[derive(Debug)]
+    pub struct EmailChangeAttrs;
+}
+
+impl EmailChange {
+    /// Creates a pending change, which must expire in the future.
+    pub fn new(attrs: NewEmailChangeAttrs) -> Result<Self> {
+        let email = attrs.email.trim().to_owned();
+        validate_email(&email)?;
+        validate_token_hash(&attrs.token_hash)?;
+
+        let now = Utc::now();
+        if attrs.expires_at <= now {
+            return Err(DomainError::validation(
+                "EmailChange",
+                "expires_at must be in the future",
+            ));
+        }
+
+        Ok(EmailChange {
+            id: Uuid::new_v4(),
+            user_id: attrs.user_id,
+            email,
+            token_hash: attrs.token_hash,
+            expires_at: attrs.expires_at,
+            created_at: now,
+            confirmed_at: None,
+        })
+    }
+
+    pub fn load(attrs: EmailChangeAttrs) -> Result<Self> {
+        validate_email(&attrs.email)?;
+        validate_token_hash(&attrs.token_hash)?;
+
+        Ok(EmailChange {
+            id: attrs.id,
+            user_id: attrs.user_id,
+            email: attrs.email,
+            token_hash: attrs.token_hash,
+            expires_at: attrs.expires_at,
+            created_at: attrs.created_at,
+            confirmed_at: attrs.confirmed_at,
+        })
+    }
+
+    pub fn to_attributes(&self) -> EmailChangeAttrs {
+        EmailChangeAttrs {
+            id: self.id,
+            user_id: self.user_id,
+            email: self.email.clone(),
+            token_hash: self.token_hash.clone(),
+            expires_at: self.expires_at,
+            created_at: self.created_at,
+            confirmed_at: self.confirmed_at,
+        }
+    }
+
+    pub fn is_confirmed(&self) -> bool {
+        self.confirmed_at.is_some()
+    }
+
+    /// Records that the user followed the link. Fails if they already did, or if the token has
+    /// expired.
+    pub fn confirm(&mut self) -> Result<()> {
+        if self.is_confirmed() {
+            return Err(DomainError::invalid_state_transition(
+                "EmailChange",
+                "the change is already confirmed",
+            ));
+        }
+        let now = Utc::now();
+        if self.expires_at <= now {
+            return Err(DomainError::invalid_state_transition(
+                "EmailChange",
+                "the change has expired",
+            ));
+        }
+
+        self.confirmed_at = Some(now);
+
+        Ok(())
+    }
+}
+
+fn validate_email(email: &str) -> Result<()> {
+    let valid = email
+        .split_once('@')
+        .is_some_and(|(local, domain)| !local.is_empty() && !domain.is_empty());
+    if !valid {
+        return Err(DomainError::validation(
+            "EmailChange",
+            format!("'{email}' is not a valid email"),
+        ));
+    }
+
+    Ok(())
+}
+
+fn validate_token_hash(token_hash: &str) -> Result<()> {
+    if token_hash.is_empty() {
+        return Err(DomainError::validation(
+            "EmailChange",
+            "token_hash must not be empty",
+        ));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,122 @@
+use chrono::{DateTime, Utc};
+use identify_macros::gen_model;
+use uuid::Uuid;
+
+use crate::{DomainError, Result};
+
+gen_model! {
+    /// A verification of the email of a [User](crate::User), e.g. right after they registered.
+    ///
+    /// The user is emailed a link with a random token, of which only the hash is kept. Following
+    /// the link proves they own the email.
+    #[derive(Debug, Clone)]
+    pub struct EmailVerification {
+        #[get(into(Uuid))]
+        #[new(skip)]
+        id: Uuid,
+        /// ID of the user whose email is verified.
+        #[get(into(Uuid))]
+        user_id: Uuid,
+        /// Hash of the token the email is verified with.
+        #[get(as_ref(&str))]
+        token_hash: String,
+        /// When the token can no longer be used.
+        expires_at: DateTime<Utc>,
+        #[new(skip)]
+        created_at: DateTime<Utc>,
+        /// When the user verified their email.
+        #[new(skip)]
+        verified_at: Option<DateTime<Utc>>,
+    }
+
+    #[derive(Debug)]
+    pub struct NewEmailVerificationAttrs;
+
+    #[derive(Debug)]
+    pub struct EmailVerificationAttrs;
+}
+
+impl EmailVerification {
+    /// Creates a pending verification, which must expire in the future.
+    pub fn new(attrs: NewEmailVerificationAttrs) -> Result<Self> {
+        validate_token_hash(&attrs.token_hash)?;
+
+        let now = Utc::now();
+        if attrs.expires_at <= now {
+            return Err(DomainError::validation(
+                "EmailVerification",
+                "expires_at must be in the future",
+            ));
+        }
+
+        Ok(EmailVerification {
+            id: Uuid::new_v4(),
+            user_id: attrs.user_id,
+            token_hash: attrs.token_hash,
+            expires_at: attrs.expires_at,
+            created_at: now,
+            verified_at: None,
+        })
+    }
+
+    pub fn load(attrs: EmailVerificationAttrs) -> Result<Self> {
+        validate_token_hash(&attrs.token_hash)?;
+
+        Ok(EmailVerification {
+            id: attrs.id,
+            user_id: attrs.user_id,
+            token_hash: attrs.token_hash,
+            expires_at: attrs.expires_at,
+            created_at: attrs.created_at,
+            verified_at: attrs.verified_at,
+        })
+    }
+
+    pub fn to_attributes(&self) -> EmailVerificationAttrs {
+        EmailVerificationAttrs {
+            id: self.id,
+            user_id: self.user_id,
+            token_hash: self.token_hash.clone(),
+            expires_at: self.expires_at,
+            created_at: self.created_at,
+            verified_at: self.verified_at,
+        }
+    }
+
+    pub fn is_verified(&self) -> bool {
+        self.verified_at.is_some()
+    }
+
+    /// Records that the user followed the link. Fails if they already did, or if the token has
+    /// expired.
+    pub fn verify(&mut self) -> Result<()> {
+        if self.is_verified() {
+            return Err(DomainError::invalid_state_transition(
+                "EmailVerification",
+                "the email is already verified",
+            ));
+        }
+        let now = Utc::now();
+        if self.expires_at <= now {
+            return Err(DomainError::invalid_state_transition(
+                "EmailVerification",
+                "the verification has expired",
+            ));
+        }
+
+        self.verified_at = Some(now);
+
+        Ok(())
+    }
+}
+
+fn validate_token_hash(token_hash: &str) -> Result<()> {
+    if token_hash.is_empty() {
+        return Err(DomainError::validation(
+            "EmailVerification",
+            "token_hash must not be empty",
+        ));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,174 @@
+use chrono::{DateTime, Utc};
+use identify_macros::{gen_id, gen_model};
+use uuid::Uuid;
+
+use crate::entities::UUID_NAMESPACE;
+use crate::{DomainError, Result};
+
+gen_model! {
+    /// A transition in the lifecycle of a [User](super::User) that is announced to other systems.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[model(display, from_str(DomainError))]
+    pub enum UserLifecycleTransition {
+        /// The user was created.
+        Created,
+        /// The profile of the user was changed.
+        Updated,
+        /// The user has verified their email.
+        Verified,
+        /// The user was suspended and can't sign in anymore.
+        Suspended,
+        /// The user was deleted.
+        Deleted,
+        /// The user was a duplicate of another one, which took over their memberships, devices,
+        /// attributes and consents, and was erased.
+        Merged,
+    }
+}
+
+fn transition_bytes(transition: &UserLifecycleTransition) -> &'static [u8] {
+    transition.as_str().as_bytes()
+}
+
+fn version_bytes(version: &u32) -> [u8; 4] {
+    version.to_be_bytes()
+}
+
+gen_id! {
+    UUID_NAMESPACE,
+    /// A stable and deterministic ID of a [UserEvent].
+    ///
+    /// Retrying a transition produces an event with the same ID, which allows the event to be
+    /// deduplicated instead of being emitted twice.
+    #[derive(Debug, Clone)]
+    pub struct UserEventId {
+        /// ID of the user the event is about.
+        #[get(into(Uuid))]
+        user_id: Uuid [Uuid::as_bytes],
+        /// Transition the event announces.
+        #[get(copy)]
+        transition: UserLifecycleTransition [transition_bytes],
+        /// Version of the user the transition resulted in.
+        #[get(into(u32))]
+        version: u32 [&version_bytes],
+    }
+
+    #[derive(Debug)]
+    pub struct UserEventIdAttrs;
+}
+
+impl UserEventId {
+    pub fn new(attrs: UserEventIdAttrs) -> Result<Self> {
+        if attrs.version == 0 {
+            return Err(DomainError::validation(
+                "UserEventId",
+                "versions start at 1",
+            ));
+        }
+
+        Ok(UserEventId {
+            user_id: attrs.user_id,
+            transition: attrs.transition,
+            version: attrs.version,
+        })
+    }
+
+    pub fn load(attrs: UserEventIdAttrs, expected: Uuid) -> Result<Self> {
+        let id = UserEventId::new(attrs)?;
+
+        let generated = id.to_uuid();
+
+        if generated != expected {
+            return Err(DomainError::id_mismatch(
+                "UserEventId",
+                format!("expected {}, got {}", expected, generated),
+            ));
+        }
+
+        Ok(id)
+    }
+}
+
+gen_model! {
+    /// An event announcing a [UserLifecycleTransition] of a [User](super::User).
+    #[derive(Debug)]
+    pub struct UserEvent {
+        /// A stable deterministic ID of this event.
+        #[get(ref_into(Uuid))]
+        #[new(skip)]
+        #[hydrate(type(Uuid))]
+        id: UserEventId,
+        /// When the transition happened.
+        #[new(skip)]
+        occurred_at: DateTime<Utc>,
+    }
+
+    #[derive(Debug)]
+    pub struct NewUserEventAttrs {
+        /// ID of the user the event is about.
+        user_id: Uuid,
+        /// Transition the event announces.
+        transition: UserLifecycleTransition,
+        /// Version of the user the transition resulted in.
+        version: u32,
+    }
+
+    #[derive(Debug)]
+    pub struct UserEventAttrs {
+        /// ID of the user the event is about.
+        user_id: Uuid,
+        /// Transition the event announces.
+        transition: UserLifecycleTransition,
+        /// Version of the user the transition resulted in.
+        version: u32,
+    }
+}
+
+impl UserEvent {
+    pub fn new(attrs: NewUserEventAttrs) -> Result<Self> {
+        Ok(UserEvent {
+            id: UserEventId::new(UserEventIdAttrs {
+                user_id: attrs.user_id,
+                transition: attrs.transition,
+                version: attrs.version,
+            })?,
+            occurred_at: Utc::now(),
+        })
+    }
+
+    pub fn load(attrs: UserEventAttrs) -> Result<Self> {
+        Ok(UserEvent {
+            id: UserEventId::load(
+                UserEventIdAttrs {
+                    user_id: attrs.user_id,
+                    transition: attrs.transition,
+                    version: attrs.version,
+                },
+                attrs.id,
+            )?,
+            occurred_at: attrs.occurred_at,
+        })
+    }
+
+    pub fn user_id(&self) -> Uuid {
+        self.id.user_id()
+    }
+
+    pub fn transition(&self) -> UserLifecycleTransition {
+        self.id.transition()
+    }
+
+    pub fn version(&self) -> u32 {
+        self.id.version()
+    }
+
+    pub fn to_attributes(&self) -> UserEventAttrs {
+        UserEventAttrs {
+            id: self.id(),
+            user_id: self.user_id(),
+            transition: self.transition(),
+            version: self.version(),
+            occurred_at: self.occurred_at,
+        }
+    }
+}
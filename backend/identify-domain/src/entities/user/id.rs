@@ -6,10 +6,16 @@ use crate::{DomainError, Result};
 
 gen_id! {
     UUID_NAMESPACE,
+    version = 2,
     /// A stable and deterministic ID that uniquely identifies a [User](super::User) within the system.
+    ///
+    /// Emails are case-insensitive in practice, so they're normalized before they're hashed:
+    /// `Jane@Example.com` and `jane@example.com` are the same user. Users created before that
+    /// have IDs of the first version, which are still accepted.
     #[derive(Debug, Clone)]
     pub struct UserId {
         /// Email of the user.
+        #[id(normalize(trim, lowercase, nfc), normalize_since = 2)]
         email: String,
     }
 
@@ -22,12 +28,14 @@ impl UserId {
         UserId { email: attrs.email }
     }
 
+    /// Loads the ID of a stored user, which must have been generated from the email by the
+    /// current or the first version of the recipe.
     pub fn load(attrs: UserIdAttrs, expected: Uuid) -> Result<Self> {
         let id = UserId { email: attrs.email };
 
         let generated = id.to_uuid();
 
-        if generated != expected {
+        if generated != expected && id.migrate_from_v1(expected).is_none() {
             return Err(DomainError::id_mismatch(
                 "UserId",
                 format!("expected {}, got {}", expected, generated),
@@ -36,4 +44,9 @@ impl UserId {
 
         Ok(id)
     }
+
+    /// Takes the email out of the ID without copying it.
+    pub fn into_email(self) -> String {
+        self.email
+    }
 }
@@ -0,0 +1,132 @@
+use std::fmt;
+
+use crate::{DomainError, Result};
+
+/// A BCP 47 language tag a user prefers, e.g. `en`, `pt-BR` or `zh-Hant-TW`.
+///
+/// Only the language, script, region and variant subtags are supported, which is all that's
+/// needed to choose translations. Tags are normalized to their conventional casing when they're
+/// parsed, and `_` is accepted in place of `-`, as in POSIX locales.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Locale(String);
+
+impl Locale {
+    /// Longest allowed tag.
+    pub const MAX_LENGTH: usize = 35;
+
+    /// Normalizes and validates a language tag.
+    pub fn parse(value: &str) -> Result<Self> {
+        let value = value.trim();
+        if value.is_empty() || value.len() > Self::MAX_LENGTH {
+            return Err(DomainError::validation(
+                "Locale",
+                format!(
+                    "must be between 1 and {} characters long",
+                    Self::MAX_LENGTH
+                ),
+            ));
+        }
+
+        let mut subtags = value.split(['-', '_']);
+        let language = subtags.next().unwrap_or_default();
+        if !(is_alpha(language, 2..=3) || is_alpha(language, 5..=8)) {
+            return Err(invalid(format!("'{language}' isn't a language")));
+        }
+
+        let mut tag = language.to_ascii_lowercase();
+        // Subtags must come in this order, so each one is only allowed after the previous ones.
+        let mut position = Position::Script;
+        for subtag in subtags {
+            if position <= Position::Script && is_alpha(subtag, 4..=4) {
+                tag.push('-');
+                tag.push_str(&subtag[..1].to_ascii_uppercase());
+                tag.push_str(&subtag[1..].to_ascii_lowercase());
+                position = Position::Region;
+            } else if position <= Position::Region
+                && (is_alpha(subtag, 2..=2) || is_digit(subtag, 3..=3))
+            {
+                tag.push('-');
+                tag.push_str(&subtag.to_ascii_uppercase());
+                position = Position::Variant;
+            } else if is_variant(subtag) {
+                tag.push('-');
+                tag.push_str(&subtag.to_ascii_lowercase());
+                position = Position::Variant;
+            } else {
+                return Err(invalid(format!(
+                    "'{subtag}' isn't a script, region or variant"
+                )));
+            }
+        }
+
+        Ok(Locale(tag))
+    }
+
+    /// Returns the language subtag, e.g. `pt` for `pt-BR`.
+    pub fn language(&self) -> &str {
+        self.0.split('-').next().unwrap_or(&self.0)
+    }
+
+    /// Returns the tag and all its prefixes, from the most to the least specific one, e.g.
+    /// `zh-Hant-TW`, `zh-Hant` and `zh`, in the order translations should be looked up.
+    pub fn fallbacks(&self) -> impl Iterator<Item = &str> {
+        let tag = self.0.as_str();
+        std::iter::once(tag).chain(
+            tag.char_indices()
+                .rev()
+                .filter(|(_, c)| *c == '-')
+                .map(move |(i, _)| &tag[..i]),
+        )
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn into_string(self) -> String {
+        self.0
+    }
+}
+
+impl AsRef<str> for Locale {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for Locale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Position {
+    Script,
+    Region,
+    Variant,
+}
+
+fn invalid(message: String) -> DomainError {
+    DomainError::validation("Locale", message)
+}
+
+fn is_alpha(subtag: &str, lengths: std::ops::RangeInclusive<usize>) -> bool {
+    lengths.contains(&subtag.len())
+        && subtag.bytes().all(|b| b.is_ascii_alphabetic())
+}
+
+fn is_digit(subtag: &str, lengths: std::ops::RangeInclusive<usize>) -> bool {
+    lengths.contains(&subtag.len())
+        && subtag.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// Variants are 5 to 8 letters or digits, or 4 of them starting with a digit, e.g. `1996`.
+fn is_variant(subtag: &str) -> bool {
+    let alphanumeric = subtag.bytes().all(|b| b.is_ascii_alphanumeric());
+    match subtag.len() {
+        4 => alphanumeric && subtag.as_bytes()[0].is_ascii_digit(),
+        5..=8 => alphanumeric,
+        _ => false,
+    }
+}
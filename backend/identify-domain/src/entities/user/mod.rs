@@ -1,72 +1,294 @@
+pub mod attribute;
+pub mod avatar;
+pub mod consent;
+pub mod device;
+pub mod email_alias;
+pub mod email_change;
+pub mod email_verification;
+pub mod event;
 pub mod id;
+pub mod locale;
+pub mod password;
+pub mod phone_number;
+pub mod phone_verification;
+pub mod time_zone;
+pub mod username;
 
-use crate::{Result, entities::user::id::UserIdAttrs};
+use crate::{DomainError, Result, entities::user::id::UserIdAttrs};
 use chrono::{DateTime, Utc};
 use id::UserId;
 use identify_macros::gen_model;
+use locale::Locale;
+use time_zone::TimeZone;
+use username::Username;
 use uuid::Uuid;
 
+/// Version every user starts with.
+const INITIAL_VERSION: u32 = 1;
+
 gen_model! {
-    #[derive(Debug)]
+    #[derive(Debug, Clone)]
+    #[model(builder, serde)]
     pub struct User {
-        /// A stable deterministic ID for this user.
-        #[get(ref_into(Uuid))]
+        /// A stable deterministic ID for this user, see [UserId]. It's generated from the email
+        /// the user was created with, and kept when they [change it](User::change_email).
+        #[get(into(Uuid))]
         #[new(skip)]
-        #[hydrate(type(Uuid))]
-        id: UserId,
+        id: Uuid,
+        /// Email of the user that uniquely identifies them within the system.
+        #[get(as_ref(&str))]
+        email: String,
+        /// Unique name the user can be referred to by.
+        #[get(as_ref(&str))]
+        #[new(skip)]
+        #[hydrate(type(String))]
+        username: Username,
         /// User's first name.
         first_name: String,
         /// User's last name.
         last_name: Option<String>,
+        /// Language the user prefers, which emails to them are translated to.
+        #[get(as_ref(Option<&Locale>))]
+        #[new(skip)]
+        #[hydrate(type(Option<String>))]
+        locale: Option<Locale>,
+        /// Time zone of the user, which times in emails to them are shown in.
+        #[get(copy)]
+        #[new(skip)]
+        #[hydrate(type(Option<String>))]
+        timezone: Option<TimeZone>,
+        /// Version of the user, incremented by every change.
+        #[get(into(u32))]
+        #[new(skip)]
+        version: u32,
         #[new(skip)]
         created_at: DateTime<Utc>,
         #[new(skip)]
         updated_at: DateTime<Utc>,
+        /// When the user was [erased](User::erase), if they were.
+        #[new(skip)]
+        erased_at: Option<DateTime<Utc>>,
+        /// When the user last [changed their email](User::change_email), if they ever did.
+        #[new(skip)]
+        email_changed_at: Option<DateTime<Utc>>,
+        /// When the user last logged in, if they ever did. Logins are counted in batches, so
+        /// the statistics of the user may lag behind their latest ones, and don't change their
+        /// version.
+        #[new(skip)]
+        last_login_at: Option<DateTime<Utc>>,
+        /// How many times the user has logged in.
+        #[get(copy)]
+        #[new(skip)]
+        login_count: u32,
     }
 
-    #[derive(Debug)]
-    pub struct NewUserAttrs {
-        /// Email of the user that uniquely identifies them within the system.
-        email: String,
-    }
+    #[derive(Debug, Clone)]
+    pub struct NewUserAttrs;
 
     #[derive(Debug)]
-    pub struct UserAttrs {
-        /// Email of the user that uniquely identifies them within the system.
-        email: String,
-    }
+    pub struct UserAttrs;
 }
 
 impl User {
-    pub fn new(attrs: NewUserAttrs) -> Self {
+    /// Creates a user with the given username, which must not be taken by anyone else.
+    pub fn new(attrs: NewUserAttrs, username: Username) -> Self {
         let now = Utc::now();
+        let id = UserId::new(UserIdAttrs { email: attrs.email });
         User {
-            id: UserId::new(UserIdAttrs { email: attrs.email }),
+            id: id.to_uuid(),
+            email: id.into_email(),
+            username,
             first_name: attrs.first_name,
             last_name: attrs.last_name,
+            locale: None,
+            timezone: None,
+            version: INITIAL_VERSION,
             created_at: now,
             updated_at: now,
+            erased_at: None,
+            email_changed_at: None,
+            last_login_at: None,
+            login_count: 0,
         }
     }
 
     pub fn load(attrs: UserAttrs) -> Result<Self> {
+        if attrs.version < INITIAL_VERSION {
+            return Err(DomainError::validation(
+                "User",
+                format!("versions start at {INITIAL_VERSION}"),
+            ));
+        }
+
+        // Erased users and the ones who changed their emails keep their IDs, but not the emails
+        // they were generated from.
+        let email =
+            if attrs.erased_at.is_some() || attrs.email_changed_at.is_some() {
+                attrs.email
+            } else {
+                UserId::load(UserIdAttrs { email: attrs.email }, attrs.id)?
+                    .into_email()
+            };
+
         Ok(User {
-            id: UserId::load(UserIdAttrs { email: attrs.email }, attrs.id)?,
+            id: attrs.id,
+            email,
+            username: Username::parse(&attrs.username)?,
             first_name: attrs.first_name,
             last_name: attrs.last_name,
+            locale: attrs.locale.as_deref().map(Locale::parse).transpose()?,
+            timezone: attrs
+                .timezone
+                .as_deref()
+                .map(TimeZone::parse)
+                .transpose()?,
+            version: attrs.version,
             created_at: attrs.created_at,
             updated_at: attrs.updated_at,
+            erased_at: attrs.erased_at,
+            email_changed_at: attrs.email_changed_at,
+            last_login_at: attrs.last_login_at,
+            login_count: attrs.login_count,
         })
     }
 
+    pub fn is_erased(&self) -> bool {
+        self.erased_at.is_some()
+    }
+
+    /// Fails if the user has been [erased](User::erase), since erased users can't change
+    /// anymore.
+    pub fn ensure_not_erased(&self) -> Result<()> {
+        if self.is_erased() {
+            return Err(DomainError::invalid_state_transition(
+                "User",
+                "the user has been erased",
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Changes the name of the user and increments their version.
+    ///
+    /// Returns `false` if the name is the same as before and nothing has changed.
+    pub fn rename(
+        &mut self,
+        first_name: String,
+        last_name: Option<String>,
+    ) -> bool {
+        if self.first_name == first_name && self.last_name == last_name {
+            return false;
+        }
+
+        self.first_name = first_name;
+        self.last_name = last_name;
+        self.version += 1;
+        self.updated_at = Utc::now();
+
+        true
+    }
+
+    /// Changes the language and the time zone the user prefers and increments their version.
+    ///
+    /// Returns `false` if both are the same as before and nothing has changed.
+    pub fn localize(
+        &mut self,
+        locale: Option<Locale>,
+        timezone: Option<TimeZone>,
+    ) -> bool {
+        if self.locale == locale && self.timezone == timezone {
+            return false;
+        }
+
+        self.locale = locale;
+        self.timezone = timezone;
+        self.version += 1;
+        self.updated_at = Utc::now();
+
+        true
+    }
+
+    /// Changes the email of the user and increments their version. Fails if the user has been
+    /// erased.
+    ///
+    /// The ID is kept, so that everything referring to the user stays consistent, and still
+    /// identifies the user by the email they were created with. Whether the new email belongs
+    /// to the user and isn't taken by anyone else has to be checked beforehand.
+    pub fn change_email(&mut self, email: String) -> Result<()> {
+        self.ensure_not_erased()?;
+
+        let now = Utc::now();
+        self.email = email;
+        self.version += 1;
+        self.updated_at = now;
+        self.email_changed_at = Some(now);
+
+        Ok(())
+    }
+
+    /// Irreversibly anonymizes the user, replacing their email and names with placeholders and
+    /// incrementing their version.
+    ///
+    /// The ID is kept, so that everything referring to the user stays consistent.
+    pub fn erase(&mut self) -> Result<()> {
+        self.ensure_not_erased()?;
+
+        let id = self.id.simple().to_string();
+        let now = Utc::now();
+        self.email = format!("erased-{id}@erased.invalid");
+        self.username = Username::parse(&format!("erased-{}", &id[..24]))?;
+        self.first_name = String::from("Erased");
+        self.last_name = None;
+        self.locale = None;
+        self.timezone = None;
+        self.version += 1;
+        self.updated_at = now;
+        self.erased_at = Some(now);
+
+        Ok(())
+    }
+
+    /// Copies all the attributes of the user.
+    ///
+    /// Prefer [User::into_attributes] when the user isn't needed anymore, e.g. when mapping
+    /// lists of users.
     pub fn to_attributes(&self) -> UserAttrs {
         UserAttrs {
-            id: self.id(),
-            email: self.id.email().to_owned(),
+            id: self.id,
+            email: self.email.clone(),
+            username: self.username.to_string(),
             first_name: self.first_name.clone(),
             last_name: self.last_name.clone(),
+            locale: self.locale.as_ref().map(Locale::to_string),
+            timezone: self.timezone.map(|timezone| timezone.to_string()),
+            version: self.version,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+            erased_at: self.erased_at,
+            email_changed_at: self.email_changed_at,
+            last_login_at: self.last_login_at,
+            login_count: self.login_count,
+        }
+    }
+
+    /// Moves all the attributes out of the user, without copying any strings.
+    pub fn into_attributes(self) -> UserAttrs {
+        UserAttrs {
+            id: self.id,
+            email: self.email,
+            username: self.username.into_string(),
+            first_name: self.first_name,
+            last_name: self.last_name,
+            locale: self.locale.map(Locale::into_string),
+            timezone: self.timezone.map(|timezone| timezone.to_string()),
+            version: self.version,
             created_at: self.created_at,
             updated_at: self.updated_at,
+            erased_at: self.erased_at,
+            email_changed_at: self.email_changed_at,
+            last_login_at: self.last_login_at,
+            login_count: self.login_count,
         }
     }
 }
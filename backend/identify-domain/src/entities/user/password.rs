@@ -0,0 +1,44 @@
+use std::fmt;
+
+use crate::{DomainError, Result};
+
+/// A plaintext password a [User](super::User) signs in with.
+///
+/// Passwords are never stored or logged, only their hashes are. Length is the only rule, as
+/// composition rules make passwords harder to remember rather than harder to guess.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Password(String);
+
+impl Password {
+    /// Shortest allowed password.
+    pub const MIN_LENGTH: usize = 12;
+    /// Longest allowed password, which bounds the cost of hashing it.
+    pub const MAX_LENGTH: usize = 128;
+
+    /// Validates a password. Unlike usernames, passwords are used exactly as they are.
+    pub fn parse(value: &str) -> Result<Self> {
+        let length = value.chars().count();
+        if !(Self::MIN_LENGTH..=Self::MAX_LENGTH).contains(&length) {
+            return Err(DomainError::validation(
+                "Password",
+                format!(
+                    "must be between {} and {} characters long",
+                    Self::MIN_LENGTH,
+                    Self::MAX_LENGTH
+                ),
+            ));
+        }
+
+        Ok(Password(value.to_owned()))
+    }
+
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for Password {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Password(***)")
+    }
+}
@@ -0,0 +1,79 @@
+use std::fmt;
+
+use crate::{DomainError, Result};
+
+/// A phone number in the E.164 format, e.g. `+14155550123`, that SMS can be sent to.
+///
+/// Numbers are normalized when they're parsed: spaces, dashes, dots and parentheses are
+/// dropped, and a leading international prefix `00` is replaced by `+`. Numbers without a
+/// country code are rejected, since there's no way to tell which country they belong to.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PhoneNumber(String);
+
+impl PhoneNumber {
+    /// Fewest digits of a number, including the country code.
+    pub const MIN_DIGITS: usize = 7;
+    /// Most digits of a number, including the country code, as allowed by E.164.
+    pub const MAX_DIGITS: usize = 15;
+
+    /// Normalizes and validates a phone number.
+    pub fn parse(value: &str) -> Result<Self> {
+        let value: String = value
+            .chars()
+            .filter(|c| !matches!(c, ' ' | '-' | '.' | '(' | ')'))
+            .collect();
+        let Some(digits) =
+            value.strip_prefix('+').or_else(|| value.strip_prefix("00"))
+        else {
+            return Err(DomainError::validation(
+                "PhoneNumber",
+                "must start with '+' and the country code",
+            ));
+        };
+
+        if !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(DomainError::validation(
+                "PhoneNumber",
+                "may only contain digits after the '+'",
+            ));
+        }
+        if !(Self::MIN_DIGITS..=Self::MAX_DIGITS).contains(&digits.len()) {
+            return Err(DomainError::validation(
+                "PhoneNumber",
+                format!(
+                    "must have between {} and {} digits",
+                    Self::MIN_DIGITS,
+                    Self::MAX_DIGITS
+                ),
+            ));
+        }
+        if digits.starts_with('0') {
+            return Err(DomainError::validation(
+                "PhoneNumber",
+                "country codes don't start with 0",
+            ));
+        }
+
+        Ok(PhoneNumber(format!("+{digits}")))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn into_string(self) -> String {
+        self.0
+    }
+}
+
+impl AsRef<str> for PhoneNumber {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for PhoneNumber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
@@ -0,0 +1,157 @@
+use chrono::{DateTime, Utc};
+use identify_macros::gen_model;
+use uuid::Uuid;
+
+use crate::{DomainError, PhoneNumber, Result};
+
+gen_model! {
+    /// A verification of a phone number of a [User](crate::User), e.g. before it's used as a
+    /// second factor.
+    ///
+    /// The user is sent a short code by SMS, of which only the hash is kept. Typing the code
+    /// back proves they own the number. Codes are short enough to be guessed, so only a few
+    /// wrong ones are allowed.
+    #[derive(Debug, Clone)]
+    pub struct PhoneVerification {
+        #[get(into(Uuid))]
+        #[new(skip)]
+        id: Uuid,
+        /// ID of the user whose phone number is verified.
+        #[get(into(Uuid))]
+        user_id: Uuid,
+        /// Number the code is sent to.
+        #[get(as_ref(&str))]
+        #[hydrate(type(String))]
+        phone_number: PhoneNumber,
+        /// Hash of the code the number is verified with.
+        #[get(as_ref(&str))]
+        code_hash: String,
+        /// Number of wrong codes entered so far.
+        #[get(into(u32))]
+        #[new(skip)]
+        attempts: u32,
+        /// When the code can no longer be used.
+        expires_at: DateTime<Utc>,
+        #[new(skip)]
+        created_at: DateTime<Utc>,
+        /// When the user verified their phone number.
+        #[new(skip)]
+        verified_at: Option<DateTime<Utc>>,
+    }
+
+    #[derive(Debug)]
+    pub struct NewPhoneVerificationAttrs;
+
+    #[derive(Debug)]
+    pub struct PhoneVerificationAttrs;
+}
+
+impl PhoneVerification {
+    /// Most wrong codes that can be entered before the verification can't be completed anymore.
+    pub const MAX_ATTEMPTS: u32 = 5;
+
+    /// Creates a pending verification, which must expire in the future.
+    ///
+    /// The ID is generated up front, so that the code can be hashed together with it.
+    pub fn new(id: Uuid, attrs: NewPhoneVerificationAttrs) -> Result<Self> {
+        validate_code_hash(&attrs.code_hash)?;
+
+        let now = Utc::now();
+        if attrs.expires_at <= now {
+            return Err(DomainError::validation(
+                "PhoneVerification",
+                "expires_at must be in the future",
+            ));
+        }
+
+        Ok(PhoneVerification {
+            id,
+            user_id: attrs.user_id,
+            phone_number: attrs.phone_number,
+            code_hash: attrs.code_hash,
+            attempts: 0,
+            expires_at: attrs.expires_at,
+            created_at: now,
+            verified_at: None,
+        })
+    }
+
+    pub fn load(attrs: PhoneVerificationAttrs) -> Result<Self> {
+        validate_code_hash(&attrs.code_hash)?;
+
+        Ok(PhoneVerification {
+            id: attrs.id,
+            user_id: attrs.user_id,
+            phone_number: PhoneNumber::parse(&attrs.phone_number)?,
+            code_hash: attrs.code_hash,
+            attempts: attrs.attempts,
+            expires_at: attrs.expires_at,
+            created_at: attrs.created_at,
+            verified_at: attrs.verified_at,
+        })
+    }
+
+    pub fn to_attributes(&self) -> PhoneVerificationAttrs {
+        PhoneVerificationAttrs {
+            id: self.id,
+            user_id: self.user_id,
+            phone_number: self.phone_number.to_string(),
+            code_hash: self.code_hash.clone(),
+            attempts: self.attempts,
+            expires_at: self.expires_at,
+            created_at: self.created_at,
+            verified_at: self.verified_at,
+        }
+    }
+
+    pub fn is_verified(&self) -> bool {
+        self.verified_at.is_some()
+    }
+
+    /// Records a code the user entered, whether it matches the one they were sent or not.
+    ///
+    /// Returns whether the number is verified. Wrong codes count towards the
+    /// [attempts](PhoneVerification::MAX_ATTEMPTS), so they have to be persisted too. Fails if
+    /// the number is already verified, if the verification has expired, or if too many wrong
+    /// codes were entered.
+    pub fn attempt(&mut self, code_matches: bool) -> Result<bool> {
+        if self.is_verified() {
+            return Err(DomainError::invalid_state_transition(
+                "PhoneVerification",
+                "the phone number is already verified",
+            ));
+        }
+        let now = Utc::now();
+        if self.expires_at <= now {
+            return Err(DomainError::invalid_state_transition(
+                "PhoneVerification",
+                "the verification has expired",
+            ));
+        }
+        if self.attempts >= Self::MAX_ATTEMPTS {
+            return Err(DomainError::invalid_state_transition(
+                "PhoneVerification",
+                "too many wrong codes were entered",
+            ));
+        }
+
+        if !code_matches {
+            self.attempts += 1;
+            return Ok(false);
+        }
+        self.verified_at = Some(now);
+
+        Ok(true)
+    }
+}
+
+fn validate_code_hash(code_hash: &str) -> Result<()> {
+    if code_hash.is_empty() {
+        return Err(DomainError::validation(
+            "PhoneVerification",
+            "code_hash must not be empty",
+        ));
+    }
+
+    Ok(())
+}
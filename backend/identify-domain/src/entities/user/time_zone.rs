@@ -0,0 +1,49 @@
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+
+use crate::{DomainError, Result};
+
+/// A time zone of the IANA database a user lives in, e.g. `Europe/Berlin`, which times shown
+/// to them are converted to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TimeZone(Tz);
+
+impl TimeZone {
+    /// Time zone of the users who haven't chosen one.
+    pub const UTC: TimeZone = TimeZone(Tz::UTC);
+
+    /// Validates the identifier of a time zone, which is case-sensitive.
+    pub fn parse(value: &str) -> Result<Self> {
+        value.trim().parse::<Tz>().map(TimeZone).map_err(|_| {
+            DomainError::validation(
+                "TimeZone",
+                format!("'{value}' isn't a time zone of the IANA database"),
+            )
+        })
+    }
+
+    /// Formats the time as it was in the time zone, e.g. `2026-10-16 14:30 CEST`.
+    pub fn format(&self, time: DateTime<Utc>) -> String {
+        time.with_timezone(&self.0)
+            .format("%Y-%m-%d %H:%M %Z")
+            .to_string()
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        self.0.name()
+    }
+}
+
+impl AsRef<str> for TimeZone {
+    fn as_ref(&self) -> &str {
+        self.0.name()
+    }
+}
+
+impl fmt::Display for TimeZone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.0.name())
+    }
+}
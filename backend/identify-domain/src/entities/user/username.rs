@@ -0,0 +1,76 @@
+use std::fmt;
+
+use crate::{DomainError, Result};
+
+/// A name that uniquely identifies a [User](super::User) within the system, e.g. in URLs or
+/// mentions.
+///
+/// Usernames are case-insensitive, so they are kept in lowercase. They consist of ASCII letters,
+/// digits, `.`, `_` and `-`, and start and end with a letter or a digit.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Username(String);
+
+impl Username {
+    /// Shortest allowed username.
+    pub const MIN_LENGTH: usize = 3;
+    /// Longest allowed username.
+    pub const MAX_LENGTH: usize = 32;
+
+    /// Normalizes and validates a username.
+    pub fn parse(value: &str) -> Result<Self> {
+        let value = value.trim().to_ascii_lowercase();
+
+        if !(Self::MIN_LENGTH..=Self::MAX_LENGTH).contains(&value.len()) {
+            return Err(DomainError::validation(
+                "Username",
+                format!(
+                    "must be between {} and {} characters long",
+                    Self::MIN_LENGTH,
+                    Self::MAX_LENGTH
+                ),
+            ));
+        }
+        if !value.bytes().all(is_username_byte) {
+            return Err(DomainError::validation(
+                "Username",
+                "may only contain letters, digits, '.', '_' and '-'",
+            ));
+        }
+        if !value.starts_with(|c: char| c.is_ascii_alphanumeric())
+            || !value.ends_with(|c: char| c.is_ascii_alphanumeric())
+        {
+            return Err(DomainError::validation(
+                "Username",
+                "must start and end with a letter or a digit",
+            ));
+        }
+
+        Ok(Username(value))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn into_string(self) -> String {
+        self.0
+    }
+}
+
+impl AsRef<str> for Username {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for Username {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+pub(crate) fn is_username_byte(byte: u8) -> bool {
+    byte.is_ascii_lowercase()
+        || byte.is_ascii_digit()
+        || matches!(byte, b'.' | b'_' | b'-')
+}
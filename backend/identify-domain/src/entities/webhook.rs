@@ -0,0 +1,400 @@
+use chrono::{DateTime, TimeDelta, Utc};
+use identify_macros::gen_model;
+use uuid::Uuid;
+
+use crate::{
+    DomainError, Result, UserLifecycleTransition,
+    entities::validate_external_id,
+};
+
+/// Longest allowed URL of a [WebhookEndpoint].
+const MAX_URL_LENGTH: usize = 2048;
+/// Shortest allowed secret of a [WebhookEndpoint], so that signatures can't be forged by
+/// guessing it.
+const MIN_SECRET_LENGTH: usize = 16;
+/// Longest allowed secret of a [WebhookEndpoint].
+const MAX_SECRET_LENGTH: usize = 256;
+
+gen_model! {
+    /// A URL that [UserEvents](crate::UserEvent) are delivered to.
+    #[derive(Debug, Clone)]
+    pub struct WebhookEndpoint {
+        /// Unique ID of the endpoint.
+        #[get(into(Uuid))]
+        #[new(skip)]
+        id: Uuid,
+        /// HTTP(S) URL the events are posted to.
+        url: String,
+        /// Secret the payloads are signed with, so that the receiver can verify them.
+        secret: String,
+        /// Transitions the endpoint is subscribed to. Empty for all of them.
+        events: Vec<UserLifecycleTransition>,
+        /// ID assigned to the endpoint by an external tool, e.g. infrastructure as code, that is
+        /// unique among all endpoints.
+        external_id: Option<String>,
+        #[new(skip)]
+        created_at: DateTime<Utc>,
+        #[new(skip)]
+        updated_at: DateTime<Utc>,
+    }
+
+    #[derive(Debug)]
+    pub struct NewWebhookEndpointAttrs;
+
+    #[derive(Debug)]
+    pub struct WebhookEndpointAttrs;
+}
+
+impl WebhookEndpoint {
+    pub fn new(attrs: NewWebhookEndpointAttrs) -> Result<Self> {
+        let url = attrs.url.trim().to_owned();
+        validate_url(&url)?;
+        validate_secret(&attrs.secret)?;
+        if let Some(external_id) = &attrs.external_id {
+            validate_external_id("WebhookEndpoint", external_id)?;
+        }
+
+        let now = Utc::now();
+        Ok(WebhookEndpoint {
+            id: Uuid::new_v4(),
+            url,
+            secret: attrs.secret,
+            events: deduplicate(attrs.events),
+            external_id: attrs.external_id,
+            created_at: now,
+            updated_at: now,
+        })
+    }
+
+    pub fn load(attrs: WebhookEndpointAttrs) -> Result<Self> {
+        validate_url(&attrs.url)?;
+        validate_secret(&attrs.secret)?;
+        if let Some(external_id) = &attrs.external_id {
+            validate_external_id("WebhookEndpoint", external_id)?;
+        }
+
+        Ok(WebhookEndpoint {
+            id: attrs.id,
+            url: attrs.url,
+            secret: attrs.secret,
+            events: attrs.events,
+            external_id: attrs.external_id,
+            created_at: attrs.created_at,
+            updated_at: attrs.updated_at,
+        })
+    }
+
+    pub fn to_attributes(&self) -> WebhookEndpointAttrs {
+        WebhookEndpointAttrs {
+            id: self.id,
+            url: self.url.clone(),
+            secret: self.secret.clone(),
+            events: self.events.clone(),
+            external_id: self.external_id.clone(),
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+        }
+    }
+
+    /// Changes where the events are posted, what they're signed with, and which ones are
+    /// delivered.
+    ///
+    /// Returns `false` if they're all the same as before and nothing has changed.
+    pub fn reconfigure(
+        &mut self,
+        url: &str,
+        secret: &str,
+        events: Vec<UserLifecycleTransition>,
+    ) -> Result<bool> {
+        let url = url.trim();
+        validate_url(url)?;
+        validate_secret(secret)?;
+        let events = deduplicate(events);
+        if self.url == url && self.secret == secret && self.events == events {
+            return Ok(false);
+        }
+
+        self.url = url.to_owned();
+        self.secret = secret.to_owned();
+        self.events = events;
+        self.updated_at = Utc::now();
+
+        Ok(true)
+    }
+
+    /// Whether the endpoint is subscribed to the transition.
+    pub fn accepts(&self, transition: UserLifecycleTransition) -> bool {
+        self.events.is_empty() || self.events.contains(&transition)
+    }
+}
+
+/// Which URLs [WebhookEndpoints](WebhookEndpoint) can be registered with.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WebhookUrlPolicy {
+    /// Whether plain HTTP URLs are allowed, e.g. of receivers in a trusted network. Only HTTPS
+    /// ones are by default, so that the payloads can't be read or altered on the way.
+    pub allow_http: bool,
+}
+
+impl WebhookUrlPolicy {
+    /// Fails if the URL of the endpoint isn't allowed.
+    pub fn check(&self, endpoint: &WebhookEndpoint) -> Result<()> {
+        if !self.allow_http && !endpoint.url.starts_with("https://") {
+            return Err(DomainError::validation(
+                "WebhookEndpoint",
+                "url must be an HTTPS URL",
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+fn validate_url(url: &str) -> Result<()> {
+    if !(url.starts_with("https://") || url.starts_with("http://")) {
+        return Err(DomainError::validation(
+            "WebhookEndpoint",
+            "url must be an HTTP(S) URL",
+        ));
+    }
+    if url.chars().count() > MAX_URL_LENGTH {
+        return Err(DomainError::validation(
+            "WebhookEndpoint",
+            format!("url must be at most {MAX_URL_LENGTH} characters long"),
+        ));
+    }
+
+    Ok(())
+}
+
+fn validate_secret(secret: &str) -> Result<()> {
+    let length = secret.chars().count();
+    if !(MIN_SECRET_LENGTH..=MAX_SECRET_LENGTH).contains(&length) {
+        return Err(DomainError::validation(
+            "WebhookEndpoint",
+            format!(
+                "secret must be between {MIN_SECRET_LENGTH} and {MAX_SECRET_LENGTH} characters long"
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+gen_model! {
+    /// State of a [WebhookDelivery].
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[model(display, from_str(DomainError))]
+    pub enum WebhookDeliveryStatus {
+        /// The event hasn't been delivered yet, but it will be (re)tried.
+        Pending,
+        /// The endpoint has accepted the event.
+        Delivered,
+        /// Every attempt has failed and the delivery gave up. It's only retried on request.
+        Dead,
+    }
+}
+
+/// How failed [WebhookDeliveries](WebhookDelivery) are retried.
+#[derive(Debug, Clone, Copy)]
+pub struct WebhookRetryPolicy {
+    /// Attempts after which the delivery gives up.
+    pub max_attempts: u32,
+    /// Delay after the first failed attempt. Every following delay is twice as long.
+    pub initial_backoff: TimeDelta,
+    /// Longest delay between two attempts.
+    pub max_backoff: TimeDelta,
+}
+
+impl WebhookRetryPolicy {
+    /// Returns the delay after the given number of failed attempts.
+    pub fn backoff(&self, attempts: u32) -> TimeDelta {
+        let exponent = attempts.saturating_sub(1).min(30);
+
+        self.initial_backoff
+            .checked_mul(1 << exponent)
+            .map_or(self.max_backoff, |backoff| backoff.min(self.max_backoff))
+    }
+}
+
+gen_model! {
+    /// Delivery of a single [UserEvent](crate::UserEvent) to a single [WebhookEndpoint].
+    #[derive(Debug)]
+    pub struct WebhookDelivery {
+        /// Unique ID of the delivery.
+        #[get(into(Uuid))]
+        #[new(skip)]
+        id: Uuid,
+        /// ID of the endpoint the event is delivered to.
+        #[get(into(Uuid))]
+        endpoint_id: Uuid,
+        /// ID of the delivered event.
+        #[get(into(Uuid))]
+        event_id: Uuid,
+        /// ID of the user the event is about.
+        #[get(into(Uuid))]
+        user_id: Uuid,
+        /// Transition the event announces.
+        #[get(into(UserLifecycleTransition))]
+        transition: UserLifecycleTransition,
+        /// When the transition happened.
+        occurred_at: DateTime<Utc>,
+        /// Current state of the delivery.
+        #[get(copy)]
+        #[new(skip)]
+        status: WebhookDeliveryStatus,
+        /// Number of attempts made so far.
+        #[get(into(u32))]
+        #[new(skip)]
+        attempts: u32,
+        /// When the delivery is attempted next, if it's pending.
+        #[new(skip)]
+        next_attempt_at: DateTime<Utc>,
+        /// Why the last attempt failed.
+        #[new(skip)]
+        last_error: Option<String>,
+        #[new(skip)]
+        created_at: DateTime<Utc>,
+        #[new(skip)]
+        updated_at: DateTime<Utc>,
+    }
+
+    #[derive(Debug)]
+    pub struct NewWebhookDeliveryAttrs;
+
+    #[derive(Debug)]
+    pub struct WebhookDeliveryAttrs;
+}
+
+impl WebhookDelivery {
+    /// Creates a delivery that is attempted right away.
+    pub fn new(attrs: NewWebhookDeliveryAttrs) -> Self {
+        let now = Utc::now();
+        WebhookDelivery {
+            id: Uuid::new_v4(),
+            endpoint_id: attrs.endpoint_id,
+            event_id: attrs.event_id,
+            user_id: attrs.user_id,
+            transition: attrs.transition,
+            occurred_at: attrs.occurred_at,
+            status: WebhookDeliveryStatus::Pending,
+            attempts: 0,
+            next_attempt_at: now,
+            last_error: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    pub fn load(attrs: WebhookDeliveryAttrs) -> Result<Self> {
+        Ok(WebhookDelivery {
+            id: attrs.id,
+            endpoint_id: attrs.endpoint_id,
+            event_id: attrs.event_id,
+            user_id: attrs.user_id,
+            transition: attrs.transition,
+            occurred_at: attrs.occurred_at,
+            status: attrs.status,
+            attempts: attrs.attempts,
+            next_attempt_at: attrs.next_attempt_at,
+            last_error: attrs.last_error,
+            created_at: attrs.created_at,
+            updated_at: attrs.updated_at,
+        })
+    }
+
+    pub fn to_attributes(&self) -> WebhookDeliveryAttrs {
+        WebhookDeliveryAttrs {
+            id: self.id,
+            endpoint_id: self.endpoint_id,
+            event_id: self.event_id,
+            user_id: self.user_id,
+            transition: self.transition,
+            occurred_at: self.occurred_at,
+            status: self.status,
+            attempts: self.attempts,
+            next_attempt_at: self.next_attempt_at,
+            last_error: self.last_error.clone(),
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+        }
+    }
+
+    /// Records an attempt the endpoint has accepted.
+    pub fn succeed(&mut self) -> Result<()> {
+        self.ensure_status(WebhookDeliveryStatus::Pending, "succeed")?;
+        self.status = WebhookDeliveryStatus::Delivered;
+        self.attempts += 1;
+        self.last_error = None;
+        self.touch();
+
+        Ok(())
+    }
+
+    /// Records a failed attempt and schedules the next one, or gives up if the policy doesn't
+    /// allow any more attempts.
+    pub fn fail(
+        &mut self,
+        error: impl Into<String>,
+        policy: &WebhookRetryPolicy,
+    ) -> Result<()> {
+        self.ensure_status(WebhookDeliveryStatus::Pending, "fail")?;
+        self.attempts += 1;
+        self.last_error = Some(error.into());
+        self.touch();
+
+        if self.attempts >= policy.max_attempts {
+            self.status = WebhookDeliveryStatus::Dead;
+        } else {
+            self.next_attempt_at =
+                self.updated_at + policy.backoff(self.attempts);
+        }
+
+        Ok(())
+    }
+
+    /// Moves a dead delivery back to pending, so that it's attempted again right away with a
+    /// fresh number of attempts.
+    pub fn redeliver(&mut self) -> Result<()> {
+        self.ensure_status(WebhookDeliveryStatus::Dead, "redeliver")?;
+        self.status = WebhookDeliveryStatus::Pending;
+        self.attempts = 0;
+        self.touch();
+        self.next_attempt_at = self.updated_at;
+
+        Ok(())
+    }
+
+    fn ensure_status(
+        &self,
+        expected: WebhookDeliveryStatus,
+        action: &str,
+    ) -> Result<()> {
+        if self.status == expected {
+            return Ok(());
+        }
+
+        Err(DomainError::invalid_state_transition(
+            "WebhookDelivery",
+            format!("can't {action} a delivery that is {}", self.status),
+        ))
+    }
+
+    fn touch(&mut self) {
+        self.updated_at = Utc::now();
+    }
+}
+
+/// Drops the transitions listed more than once, keeping the order they're listed in.
+fn deduplicate(
+    events: Vec<UserLifecycleTransition>,
+) -> Vec<UserLifecycleTransition> {
+    let mut deduplicated = Vec::with_capacity(events.len());
+    for transition in events {
+        if !deduplicated.contains(&transition) {
+            deduplicated.push(transition);
+        }
+    }
+
+    deduplicated
+}
@@ -1,9 +1,78 @@
 mod entities;
+mod services;
 
+pub use entities::invitation::{
+    Invitation, InvitationAttrs, InvitationStatus, NewInvitationAttrs,
+};
+pub use entities::membership::{
+    Membership, MembershipAttrs, MembershipRole, NewMembershipAttrs,
+};
+pub use entities::operation::{
+    NewOperationAttrs, Operation, OperationAttrs, OperationStatus,
+};
+pub use entities::organization::{
+    NewOrganizationAttrs, Organization, OrganizationAttrs,
+    quota::{
+        NewOrganizationQuotaAttrs, OrganizationQuota, OrganizationQuotaAttrs,
+    },
+    slug::Slug,
+};
+pub use entities::reverification::{
+    NewReverificationCampaignAttrs, ReverificationCampaign,
+    ReverificationCampaignAttrs, ReverificationCohort,
+};
+pub use entities::role::{
+    NewRoleAttrs, Role, RoleAssignment, RoleAttrs, RoleCohort,
+};
+pub use entities::signing_key::{
+    NewSigningKeyAttrs, SigningKey, SigningKeyAttrs, SigningKeyStatus,
+};
 pub use entities::user::{
     NewUserAttrs, User, UserAttrs,
+    attribute::{
+        AttributeType, AttributeValue, NewUserAttributeAttrs, UserAttribute,
+        UserAttributeAttrs,
+    },
+    avatar::{Avatar, AvatarFormat},
+    consent::{Consent, ConsentAttrs, NewConsentAttrs},
+    device::{Device, DeviceAttrs, NewDeviceAttrs},
+    email_alias::{
+        EmailAlias, EmailAliasAttrs, EmailAliasKind, NewEmailAliasAttrs,
+    },
+    email_change::{EmailChange, EmailChangeAttrs, NewEmailChangeAttrs},
+    email_verification::{
+        EmailVerification, EmailVerificationAttrs, NewEmailVerificationAttrs,
+    },
+    event::{
+        NewUserEventAttrs, UserEvent, UserEventAttrs, UserEventId,
+        UserEventIdAttrs, UserLifecycleTransition,
+    },
     id::{UserId, UserIdAttrs},
+    locale::Locale,
+    password::Password,
+    phone_number::PhoneNumber,
+    phone_verification::{
+        NewPhoneVerificationAttrs, PhoneVerification, PhoneVerificationAttrs,
+    },
+    time_zone::TimeZone,
+    username::Username,
+};
+pub use entities::webhook::{
+    NewWebhookDeliveryAttrs, NewWebhookEndpointAttrs, WebhookDelivery,
+    WebhookDeliveryAttrs, WebhookDeliveryStatus, WebhookEndpoint,
+    WebhookEndpointAttrs, WebhookRetryPolicy, WebhookUrlPolicy,
+};
+pub use services::authorization::{
+    AccessDecision, AccessRequest, AuthorizationPolicies, AuthorizationPolicy,
+    ConditionOperator, PolicyCondition, PolicyEffect, Resource,
 };
+pub use services::consents::{ConsentPolicies, PolicyVersion};
+pub use services::identifiers::{BUILTIN_RESERVED_WORDS, IdentifierPolicy};
+pub use services::quotas::{QuotaExceeded, QuotaPolicy};
+pub use services::registration::{
+    RegistrationMode, RegistrationPolicy, RegistrationRefusal,
+};
+pub use services::username::{UsernameCandidates, UsernameStrategy};
 
 use std::borrow::Cow;
 
@@ -18,6 +87,18 @@ pub enum DomainError {
         model: Cow<'static, str>,
         message: Cow<'static, str>,
     },
+
+    #[error("Invalid value for {model}: {message}")]
+    Validation {
+        model: Cow<'static, str>,
+        message: Cow<'static, str>,
+    },
+
+    #[error("Invalid state transition for {model}: {message}")]
+    InvalidStateTransition {
+        model: Cow<'static, str>,
+        message: Cow<'static, str>,
+    },
 }
 
 impl DomainError {
@@ -33,4 +114,35 @@ impl DomainError {
             message: message.into(),
         }
     }
+
+    pub fn validation<
+        MO: Into<Cow<'static, str>>,
+        ME: Into<Cow<'static, str>>,
+    >(
+        model: MO,
+        message: ME,
+    ) -> Self {
+        DomainError::Validation {
+            model: model.into(),
+            message: message.into(),
+        }
+    }
+
+    /// Error of a value that isn't the name of any variant of an enum.
+    pub fn unknown_variant(model: &'static str, value: &str) -> Self {
+        DomainError::validation(model, format!("unknown value '{value}'"))
+    }
+
+    pub fn invalid_state_transition<
+        MO: Into<Cow<'static, str>>,
+        ME: Into<Cow<'static, str>>,
+    >(
+        model: MO,
+        message: ME,
+    ) -> Self {
+        DomainError::InvalidStateTransition {
+            model: model.into(),
+            message: message.into(),
+        }
+    }
 }
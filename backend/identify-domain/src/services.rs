@@ -0,0 +1,6 @@
+pub mod authorization;
+pub mod consents;
+pub mod identifiers;
+pub mod quotas;
+pub mod registration;
+pub mod username;
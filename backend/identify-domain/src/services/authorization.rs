@@ -0,0 +1,367 @@
+//! Declarative authorization policies.
+//!
+//! A policy allows or denies subjects (e.g. the owners of API keys) to perform actions (e.g.
+//! `users:erase`) on resources (e.g. `user:<ID>`), provided that its conditions on the
+//! attributes of the request hold. Subjects, actions and resources are matched by patterns in
+//! which `*` stands for any run of characters, e.g. `users:*`.
+//!
+//! Denials win over allowances, so a single matching policy that denies a request refuses it.
+//! Requests that no policy matches are left to the permissions of the subject, see
+//! [AccessDecision::NotApplicable].
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use identify_macros::gen_model;
+
+use crate::{DomainError, Result};
+
+/// Longest allowed name of an [AuthorizationPolicy].
+const MAX_NAME_LENGTH: usize = 100;
+
+gen_model! {
+    /// What an [AuthorizationPolicy] does with the requests it matches.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[model(display, from_str(DomainError))]
+    pub enum PolicyEffect {
+        Allow,
+        Deny,
+    }
+}
+
+gen_model! {
+    /// How a [PolicyCondition] compares an attribute with its values.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[model(display, from_str(DomainError))]
+    pub enum ConditionOperator {
+        /// The attribute is one of the values.
+        In,
+        /// The attribute is none of the values, or the request doesn't have it.
+        NotIn,
+        /// The attribute equals one of the attributes the values name, e.g. `resource.id` is
+        /// the same as `subject`.
+        SameAs,
+    }
+}
+
+/// Condition on an attribute of an [AccessRequest], see [AccessRequest::attribute].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PolicyCondition {
+    pub attribute: String,
+    pub operator: ConditionOperator,
+    pub values: Vec<String>,
+}
+
+impl PolicyCondition {
+    fn holds(&self, request: &AccessRequest) -> bool {
+        let value = request.attribute(&self.attribute);
+        match self.operator {
+            ConditionOperator::In => value
+                .is_some_and(|value| self.values.iter().any(|v| v == value)),
+            ConditionOperator::NotIn => {
+                value.is_none_or(|value| self.values.iter().all(|v| v != value))
+            }
+            ConditionOperator::SameAs => value.is_some_and(|value| {
+                self.values
+                    .iter()
+                    .any(|other| request.attribute(other) == Some(value))
+            }),
+        }
+    }
+}
+
+/// Allows or denies the requests it matches.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthorizationPolicy {
+    name: String,
+    effect: PolicyEffect,
+    subjects: Vec<String>,
+    actions: Vec<String>,
+    resources: Vec<String>,
+    conditions: Vec<PolicyCondition>,
+}
+
+impl AuthorizationPolicy {
+    /// Creates a policy that matches the requests of any of the subjects to perform any of the
+    /// actions on any of the resources, for which all the conditions hold.
+    ///
+    /// Fails if the name is invalid, if any of the lists of patterns is empty or contains an
+    /// empty pattern, or if a condition has no values.
+    pub fn new(
+        name: String,
+        effect: PolicyEffect,
+        subjects: Vec<String>,
+        actions: Vec<String>,
+        resources: Vec<String>,
+        conditions: Vec<PolicyCondition>,
+    ) -> Result<Self> {
+        let name = name.trim().to_owned();
+        if name.is_empty() || name.len() > MAX_NAME_LENGTH {
+            return Err(invalid(format!(
+                "the name must be between 1 and {MAX_NAME_LENGTH} characters long"
+            )));
+        }
+        for (field, patterns) in [
+            ("subjects", &subjects),
+            ("actions", &actions),
+            ("resources", &resources),
+        ] {
+            if patterns.is_empty() {
+                return Err(invalid(format!(
+                    "{name}: {field} must not be empty"
+                )));
+            }
+            if let Some(pattern) = patterns.iter().find(|pattern| {
+                pattern.trim().is_empty() || pattern.contains(',')
+            }) {
+                return Err(invalid(format!(
+                    "{name}: '{pattern}' is not a valid pattern of {field}"
+                )));
+            }
+        }
+        if let Some(condition) = conditions.iter().find(|condition| {
+            condition.attribute.trim().is_empty() || condition.values.is_empty()
+        }) {
+            return Err(invalid(format!(
+                "{name}: the condition on '{}' needs an attribute and at least one value",
+                condition.attribute
+            )));
+        }
+
+        Ok(AuthorizationPolicy {
+            name,
+            effect,
+            subjects,
+            actions,
+            resources,
+            conditions,
+        })
+    }
+
+    /// Unique name of the policy, reported along with the decisions it makes.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn effect(&self) -> PolicyEffect {
+        self.effect
+    }
+
+    pub fn subjects(&self) -> &[String] {
+        &self.subjects
+    }
+
+    pub fn actions(&self) -> &[String] {
+        &self.actions
+    }
+
+    /// Patterns of the resources, which are named `<kind>:<ID>`, or just `<kind>` when they
+    /// have no ID yet. A pattern that ends with `:*` also matches the resources without an ID.
+    pub fn resources(&self) -> &[String] {
+        &self.resources
+    }
+
+    pub fn conditions(&self) -> &[PolicyCondition] {
+        &self.conditions
+    }
+
+    /// Checks whether the policy matches the request.
+    pub fn matches(&self, request: &AccessRequest) -> bool {
+        self.subjects
+            .iter()
+            .any(|pattern| glob_matches(pattern, &request.subject))
+            && self
+                .actions
+                .iter()
+                .any(|pattern| glob_matches(pattern, &request.action))
+            && self
+                .resources
+                .iter()
+                .any(|pattern| request.resource.matches(pattern))
+            && self
+                .conditions
+                .iter()
+                .all(|condition| condition.holds(request))
+    }
+}
+
+fn invalid(message: String) -> DomainError {
+    DomainError::validation("AuthorizationPolicy", message)
+}
+
+/// What an action is performed on, e.g. a user.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Resource {
+    kind: &'static str,
+    id: Option<String>,
+    attributes: BTreeMap<&'static str, String>,
+}
+
+impl Resource {
+    /// Creates a resource of the kind, e.g. `user`, that has no ID yet.
+    pub fn new(kind: &'static str) -> Self {
+        Resource {
+            kind,
+            id: None,
+            attributes: BTreeMap::new(),
+        }
+    }
+
+    pub fn with_id(mut self, id: impl ToString) -> Self {
+        self.id = Some(id.to_string());
+        self
+    }
+
+    /// Adds an attribute conditions can check as `resource.<name>`.
+    pub fn with_attribute(
+        mut self,
+        name: &'static str,
+        value: impl Into<String>,
+    ) -> Self {
+        self.attributes.insert(name, value.into());
+        self
+    }
+
+    fn matches(&self, pattern: &str) -> bool {
+        match &self.id {
+            Some(id) => glob_matches(pattern, &format!("{}:{id}", self.kind)),
+            None => pattern.strip_suffix(":*").map_or_else(
+                || glob_matches(pattern, self.kind),
+                |kind| glob_matches(kind, self.kind),
+            ),
+        }
+    }
+}
+
+/// Request of a subject to perform an action on a resource.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccessRequest {
+    subject: String,
+    action: String,
+    resource: Resource,
+}
+
+impl AccessRequest {
+    pub fn new(
+        subject: impl Into<String>,
+        action: impl Into<String>,
+        resource: Resource,
+    ) -> Self {
+        AccessRequest {
+            subject: subject.into(),
+            action: action.into(),
+            resource,
+        }
+    }
+
+    /// Returns the attribute with the name, which is `subject`, `action`, `resource.kind`,
+    /// `resource.id` or `resource.<name>` for the other attributes of the resource.
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        match name {
+            "subject" => Some(&self.subject),
+            "action" => Some(&self.action),
+            "resource.kind" => Some(self.resource.kind),
+            "resource.id" => self.resource.id.as_deref(),
+            _ => name
+                .strip_prefix("resource.")
+                .and_then(|name| self.resource.attributes.get(name))
+                .map(String::as_str),
+        }
+    }
+}
+
+/// Outcome of the evaluation of [AuthorizationPolicies].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AccessDecision {
+    /// A policy allows the request, and none denies it.
+    Allowed { policy: String },
+    /// A policy denies the request.
+    Denied { policy: String },
+    /// No policy matches the request, so the permissions of the subject decide.
+    NotApplicable,
+}
+
+/// The authorization policies of the service, see the [module](self) documentation.
+#[derive(Debug, Clone, Default)]
+pub struct AuthorizationPolicies {
+    policies: Vec<AuthorizationPolicy>,
+}
+
+impl AuthorizationPolicies {
+    /// Collects the policies, failing if several of them have the same name.
+    pub fn new<I>(policies: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = AuthorizationPolicy>,
+    {
+        let policies: Vec<_> = policies.into_iter().collect();
+        let mut names = BTreeSet::new();
+        if let Some(policy) =
+            policies.iter().find(|policy| !names.insert(policy.name()))
+        {
+            return Err(invalid(format!(
+                "several policies are named '{}'",
+                policy.name()
+            )));
+        }
+
+        Ok(AuthorizationPolicies { policies })
+    }
+
+    pub fn len(&self) -> usize {
+        self.policies.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.policies.is_empty()
+    }
+
+    /// Decides the request with the policies that match it.
+    pub fn evaluate(&self, request: &AccessRequest) -> AccessDecision {
+        let mut decision = AccessDecision::NotApplicable;
+        for policy in self.policies.iter().filter(|p| p.matches(request)) {
+            match policy.effect {
+                PolicyEffect::Deny => {
+                    return AccessDecision::Denied {
+                        policy: policy.name.clone(),
+                    };
+                }
+                PolicyEffect::Allow
+                    if decision == AccessDecision::NotApplicable =>
+                {
+                    decision = AccessDecision::Allowed {
+                        policy: policy.name.clone(),
+                    };
+                }
+                PolicyEffect::Allow => {}
+            }
+        }
+
+        decision
+    }
+}
+
+/// Checks whether the value matches the pattern, in which `*` matches any run of characters.
+fn glob_matches(pattern: &str, value: &str) -> bool {
+    let (pattern, value) = (pattern.as_bytes(), value.as_bytes());
+    let (mut p, mut v) = (0, 0);
+    // Position of the last `*` in the pattern, and of the value when it was reached.
+    let mut backtrack = None;
+
+    while v < value.len() {
+        if p < pattern.len() && pattern[p] == b'*' {
+            backtrack = Some((p, v));
+            p += 1;
+        } else if p < pattern.len() && pattern[p] == value[v] {
+            p += 1;
+            v += 1;
+        } else if let Some((star, matched)) = backtrack {
+            // Lets the last `*` match one more character.
+            p = star + 1;
+            v = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == b'*')
+}
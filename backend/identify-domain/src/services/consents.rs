@@ -0,0 +1,66 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    Consent, Result,
+    entities::user::consent::{validate_policy_name, validate_policy_version},
+};
+
+/// A version of a policy users have to accept.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PolicyVersion {
+    pub policy: String,
+    pub version: String,
+}
+
+/// The policies users have to accept before they can log in, by their current versions.
+///
+/// Only the current version of a policy counts: users who accepted an older one have to
+/// accept the policy again once it's replaced. Versions are opaque, so any version other than
+/// the current one is outdated, whatever it looks like.
+#[derive(Debug, Clone, Default)]
+pub struct ConsentPolicies {
+    current: BTreeMap<String, String>,
+}
+
+impl ConsentPolicies {
+    /// Creates the policies from their names and current versions, failing if any of them is
+    /// invalid.
+    pub fn new<I>(current: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = (String, String)>,
+    {
+        let current = current
+            .into_iter()
+            .map(|(policy, version)| {
+                validate_policy_name(&policy)?;
+                validate_policy_version(&version)?;
+
+                Ok((policy, version))
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(ConsentPolicies { current })
+    }
+
+    /// Returns the current version of a policy, or `None` if there's no such policy.
+    pub fn current_version(&self, policy: &str) -> Option<&str> {
+        self.current.get(policy).map(String::as_str)
+    }
+
+    /// Returns the current versions of the policies that none of the consents is for, by the
+    /// names of the policies.
+    pub fn pending(&self, consents: &[Consent]) -> Vec<PolicyVersion> {
+        self.current
+            .iter()
+            .filter(|(policy, version)| {
+                !consents
+                    .iter()
+                    .any(|consent| consent.is_for(policy, version))
+            })
+            .map(|(policy, version)| PolicyVersion {
+                policy: policy.clone(),
+                version: version.clone(),
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,135 @@
+use std::{collections::HashSet, fmt};
+
+use crate::{DomainError, Result};
+
+/// Words nobody can claim, as they could be mistaken for the service or its staff.
+pub const BUILTIN_RESERVED_WORDS: &[&str] = &[
+    "abuse",
+    "admin",
+    "administrator",
+    "api",
+    "billing",
+    "help",
+    "hostmaster",
+    "identify",
+    "info",
+    "mail",
+    "moderator",
+    "noreply",
+    "null",
+    "official",
+    "postmaster",
+    "root",
+    "security",
+    "staff",
+    "support",
+    "system",
+    "webmaster",
+    "www",
+];
+
+/// Decides which identifiers users can choose, e.g. usernames or names of organizations.
+///
+/// An identifier is reserved if it's one of the reserved words once the case and the separators
+/// (`.`, `_`, `-` and whitespace) are ignored, e.g. `Ad-Min`. It's profane if any of its words
+/// is on the profanity list, e.g. `jane.<word>`. Words are never matched within other words, so
+/// that innocent names that happen to contain them are accepted.
+///
+/// The policy applies only to identifiers that are being chosen. Existing ones stay valid when
+/// the lists change.
+#[derive(Clone)]
+pub struct IdentifierPolicy {
+    reserved: HashSet<String>,
+    profanity: HashSet<String>,
+}
+
+impl IdentifierPolicy {
+    /// Creates a policy with the built-in reserved words, the additional ones and the profanity
+    /// list, which may be empty to disable the filtering.
+    pub fn new<R, P>(reserved: R, profanity: P) -> Self
+    where
+        R: IntoIterator<Item = String>,
+        P: IntoIterator<Item = String>,
+    {
+        IdentifierPolicy {
+            reserved: BUILTIN_RESERVED_WORDS
+                .iter()
+                .map(|word| (*word).to_owned())
+                .chain(reserved)
+                .map(|word| compact(&word))
+                .filter(|word| !word.is_empty())
+                .collect(),
+            profanity: profanity
+                .into_iter()
+                .map(|word| word.trim().to_lowercase())
+                .filter(|word| !word.is_empty())
+                .collect(),
+        }
+    }
+
+    /// Parses a word list with a word per line. Blank lines and lines starting with `#` are
+    /// skipped.
+    pub fn parse_list(text: &str) -> Vec<String> {
+        text.lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(str::to_owned)
+            .collect()
+    }
+
+    pub fn is_reserved(&self, identifier: &str) -> bool {
+        self.reserved.contains(&compact(identifier))
+    }
+
+    pub fn is_profane(&self, identifier: &str) -> bool {
+        !self.profanity.is_empty()
+            && identifier
+                .to_lowercase()
+                .split(|c: char| !c.is_alphanumeric())
+                .any(|word| self.profanity.contains(word))
+    }
+
+    /// Checks that the identifier can be chosen, reporting violations as validation errors of
+    /// the model, e.g. `Username`.
+    pub fn check(&self, model: &'static str, identifier: &str) -> Result<()> {
+        if self.is_reserved(identifier) {
+            return Err(DomainError::validation(
+                model,
+                format!("'{}' is reserved", identifier.trim()),
+            ));
+        }
+        if self.is_profane(identifier) {
+            return Err(DomainError::validation(
+                model,
+                "contains a word that isn't allowed",
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for IdentifierPolicy {
+    fn default() -> Self {
+        IdentifierPolicy::new([], [])
+    }
+}
+
+// The lists can be long, and the policy ends up in the logs of the use cases.
+impl fmt::Debug for IdentifierPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IdentifierPolicy")
+            .field("reserved", &self.reserved.len())
+            .field("profanity", &self.profanity.len())
+            .finish()
+    }
+}
+
+/// Lowercases the identifier and drops the separators, e.g. `Ad-Min` becomes `admin`.
+fn compact(identifier: &str) -> String {
+    identifier
+        .chars()
+        .filter(|c| !matches!(c, '.' | '_' | '-') && !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
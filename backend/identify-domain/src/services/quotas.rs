@@ -0,0 +1,61 @@
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::OrganizationQuota;
+
+/// Why a [QuotaPolicy] doesn't let an organization have one more user.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("the organization has reached its quota of {max_users} users")]
+pub struct QuotaExceeded {
+    pub organization_id: Uuid,
+    pub max_users: u32,
+}
+
+/// Limits how many users organizations can have.
+///
+/// Organizations that have an [OrganizationQuota] of their own are limited by it instead of
+/// the default.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QuotaPolicy {
+    max_users_per_organization: Option<u32>,
+}
+
+impl QuotaPolicy {
+    /// Creates the policy, where `None` lets organizations have any number of users by default.
+    pub fn new(max_users_per_organization: Option<u32>) -> Self {
+        QuotaPolicy {
+            max_users_per_organization,
+        }
+    }
+
+    pub fn max_users_per_organization(&self) -> Option<u32> {
+        self.max_users_per_organization
+    }
+
+    /// Most users an organization with the quota can have, `None` if there's no limit.
+    pub fn max_users(&self, quota: Option<&OrganizationQuota>) -> Option<u32> {
+        match quota {
+            Some(quota) => quota.max_users(),
+            None => self.max_users_per_organization,
+        }
+    }
+
+    /// Checks whether an organization with the quota, which has `users` users, can have one
+    /// more.
+    pub fn check(
+        &self,
+        organization_id: Uuid,
+        quota: Option<&OrganizationQuota>,
+        users: u64,
+    ) -> std::result::Result<(), QuotaExceeded> {
+        match self.max_users(quota) {
+            Some(max_users) if users >= u64::from(max_users) => {
+                Err(QuotaExceeded {
+                    organization_id,
+                    max_users,
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+}
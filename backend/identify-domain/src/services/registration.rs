@@ -0,0 +1,107 @@
+use std::collections::BTreeSet;
+
+use identify_macros::gen_model;
+use thiserror::Error;
+
+use crate::{DomainError, Result};
+
+gen_model! {
+    /// Who can register themselves, see [RegistrationPolicy].
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+    #[model(display, from_str(DomainError))]
+    pub enum RegistrationMode {
+        /// Anyone can register.
+        Open,
+        /// Nobody can register. People join organizations by accepting invitations instead.
+        #[default]
+        InviteOnly,
+        /// Only people whose emails are in one of the allowed domains can register.
+        AllowedDomains,
+    }
+}
+
+/// Why a [RegistrationPolicy] doesn't let someone register.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum RegistrationRefusal {
+    #[error("registration is by invitation only")]
+    InviteOnly,
+
+    #[error("emails in the domain '{0}' can't be registered")]
+    DomainNotAllowed(String),
+}
+
+/// Decides who can register themselves, as opposed to being created by an admin or accepting
+/// an invitation.
+///
+/// Allowed domains are matched exactly and regardless of their case, so allowing `acme.test`
+/// doesn't allow `eu.acme.test`.
+#[derive(Debug, Clone, Default)]
+pub struct RegistrationPolicy {
+    mode: RegistrationMode,
+    allowed_domains: BTreeSet<String>,
+}
+
+impl RegistrationPolicy {
+    /// Creates the policy, failing if any of the domains is invalid, or if only the allowed
+    /// domains can register but there are none.
+    pub fn new<I>(mode: RegistrationMode, allowed_domains: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let allowed_domains = allowed_domains
+            .into_iter()
+            .map(|domain| {
+                let domain = domain.trim().to_lowercase();
+                if domain.is_empty() || domain.contains('@') {
+                    return Err(DomainError::validation(
+                        "RegistrationPolicy",
+                        format!("'{domain}' is not a valid email domain"),
+                    ));
+                }
+
+                Ok(domain)
+            })
+            .collect::<Result<BTreeSet<_>>>()?;
+        if mode == RegistrationMode::AllowedDomains
+            && allowed_domains.is_empty()
+        {
+            return Err(DomainError::validation(
+                "RegistrationPolicy",
+                "at least one domain must be allowed",
+            ));
+        }
+
+        Ok(RegistrationPolicy {
+            mode,
+            allowed_domains,
+        })
+    }
+
+    pub fn mode(&self) -> RegistrationMode {
+        self.mode
+    }
+
+    /// Checks whether someone with the email can register.
+    pub fn check(
+        &self,
+        email: &str,
+    ) -> std::result::Result<(), RegistrationRefusal> {
+        match self.mode {
+            RegistrationMode::Open => Ok(()),
+            RegistrationMode::InviteOnly => {
+                Err(RegistrationRefusal::InviteOnly)
+            }
+            RegistrationMode::AllowedDomains => {
+                let domain = email
+                    .rsplit_once('@')
+                    .map(|(_, domain)| domain.trim().to_lowercase())
+                    .unwrap_or_default();
+                if self.allowed_domains.contains(&domain) {
+                    Ok(())
+                } else {
+                    Err(RegistrationRefusal::DomainNotAllowed(domain))
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use identify_macros::gen_model;
+use uuid::Uuid;
+
+use crate::{DomainError, Username};
+
+/// Prefix of the random handles.
+const HANDLE_PREFIX: &str = "user-";
+/// Number of random hex digits in a handle, enough to make collisions unlikely.
+const HANDLE_DIGITS: usize = 8;
+
+gen_model! {
+    /// How usernames are generated for the users that are provisioned without one.
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+    #[model(display, from_str(DomainError))]
+    pub enum UsernameStrategy {
+        /// The local part of the email without any `+` tag, e.g. `jane.doe` for
+        /// `Jane.Doe+work@acme.test`.
+        #[default]
+        EmailLocalPart,
+        /// The first and the last name joined with a dot, e.g. `jane.doe`.
+        FirstDotLast,
+        /// A random handle, e.g. `user-3f9a1c2e`.
+        RandomHandle,
+    }
+}
+
+impl UsernameStrategy {
+    /// Returns the usernames to try for a user, the preferred one first.
+    ///
+    /// Collisions are resolved with a numeric suffix, e.g. `jane.doe2`, or with another random
+    /// handle. Names that can't make a valid username (e.g. ones without any ASCII letters)
+    /// fall back to random handles. The candidates never run out, so the callers must bound
+    /// the number of attempts.
+    pub fn candidates(
+        &self,
+        email: &str,
+        first_name: &str,
+        last_name: Option<&str>,
+    ) -> UsernameCandidates {
+        let base = match self {
+            UsernameStrategy::EmailLocalPart => {
+                let local_part =
+                    email.rsplit_once('@').map_or(email, |(local, _)| local);
+                let local_part = local_part
+                    .split_once('+')
+                    .map_or(local_part, |(local, _)| local);
+
+                slugify(local_part)
+            }
+            UsernameStrategy::FirstDotLast => {
+                let first = slugify(first_name);
+                let last = last_name.map(slugify).unwrap_or_default();
+
+                match (first.is_empty(), last.is_empty()) {
+                    (false, false) => format!("{first}.{last}"),
+                    (true, _) => last,
+                    (false, true) => first,
+                }
+            }
+            UsernameStrategy::RandomHandle => String::new(),
+        };
+
+        UsernameCandidates {
+            base: Some(base).filter(|base| !base.is_empty()),
+            attempt: 0,
+        }
+    }
+}
+
+/// Endless sequence of the usernames to try for a user, see [UsernameStrategy::candidates].
+#[derive(Debug)]
+pub struct UsernameCandidates {
+    /// Name the suffixes are appended to. Every candidate is a random handle without it.
+    base: Option<String>,
+    attempt: u32,
+}
+
+impl Iterator for UsernameCandidates {
+    type Item = Username;
+
+    fn next(&mut self) -> Option<Username> {
+        self.attempt += 1;
+
+        let candidate = self
+            .base
+            .as_deref()
+            .map(|base| {
+                // Bases too short to be a username on their own always get a suffix.
+                let suffix = if self.attempt == 1
+                    && base.len() >= Username::MIN_LENGTH
+                {
+                    String::new()
+                } else {
+                    self.attempt.to_string()
+                };
+                let base = truncate(base, Username::MAX_LENGTH - suffix.len());
+
+                format!("{base}{suffix}")
+            })
+            .and_then(|candidate| Username::parse(&candidate).ok());
+
+        candidate.or_else(|| Username::parse(&random_handle()).ok())
+    }
+}
+
+/// Turns a name into the base of a username, e.g. `Mary Ann` into `mary.ann`.
+///
+/// Letters outside of ASCII are dropped, and any other characters become separators.
+fn slugify(name: &str) -> String {
+    let mut slug = String::with_capacity(name.len());
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if matches!(c, '_' | '-') {
+            slug.push(c);
+        } else if !c.is_alphanumeric() && !slug.ends_with(['.', '_', '-']) {
+            slug.push('.');
+        }
+    }
+
+    slug.trim_matches(['.', '_', '-']).to_owned()
+}
+
+/// Shortens an ASCII base, so that it still ends with a letter or a digit.
+fn truncate(base: &str, max_length: usize) -> &str {
+    base[..base.len().min(max_length)].trim_end_matches(['.', '_', '-'])
+}
+
+fn random_handle() -> String {
+    let digits = Uuid::new_v4().simple().to_string();
+
+    format!("{HANDLE_PREFIX}{}", &digits[..HANDLE_DIGITS])
+}
@@ -0,0 +1,216 @@
+use identify_domain::{
+    AccessDecision, AccessRequest, AuthorizationPolicies, AuthorizationPolicy,
+    ConditionOperator, PolicyCondition, PolicyEffect, Resource,
+};
+
+fn strings(values: &[&str]) -> Vec<String> {
+    values.iter().map(|&value| value.to_owned()).collect()
+}
+
+fn policy(
+    name: &str,
+    effect: PolicyEffect,
+    subjects: &[&str],
+    actions: &[&str],
+    resources: &[&str],
+    conditions: Vec<PolicyCondition>,
+) -> AuthorizationPolicy {
+    AuthorizationPolicy::new(
+        name.to_owned(),
+        effect,
+        strings(subjects),
+        strings(actions),
+        strings(resources),
+        conditions,
+    )
+    .unwrap()
+}
+
+fn condition(
+    attribute: &str,
+    operator: ConditionOperator,
+    values: &[&str],
+) -> PolicyCondition {
+    PolicyCondition {
+        attribute: attribute.to_owned(),
+        operator,
+        values: strings(values),
+    }
+}
+
+fn erase(subject: &str, id: &str) -> AccessRequest {
+    AccessRequest::new(
+        subject,
+        "users:erase",
+        Resource::new("user").with_id(id),
+    )
+}
+
+#[test]
+fn denials_win_over_allowances() {
+    let policies = AuthorizationPolicies::new([
+        policy(
+            "support",
+            PolicyEffect::Allow,
+            &["support-*"],
+            &["users:*"],
+            &["user:*"],
+            vec![],
+        ),
+        policy(
+            "no-erasure-by-support",
+            PolicyEffect::Deny,
+            &["support-*"],
+            &["users:erase"],
+            &["*"],
+            vec![],
+        ),
+    ])
+    .unwrap();
+
+    let write =
+        AccessRequest::new("support-eu", "users:write", Resource::new("user"));
+
+    assert_eq!(
+        policies.evaluate(&write),
+        AccessDecision::Allowed {
+            policy: "support".to_owned()
+        }
+    );
+    assert_eq!(
+        policies.evaluate(&erase("support-eu", "42")),
+        AccessDecision::Denied {
+            policy: "no-erasure-by-support".to_owned()
+        }
+    );
+    assert_eq!(
+        policies.evaluate(&erase("billing", "42")),
+        AccessDecision::NotApplicable
+    );
+}
+
+#[test]
+fn resources_match_by_kind_and_id() {
+    let policies = AuthorizationPolicies::new([policy(
+        "one-user",
+        PolicyEffect::Allow,
+        &["*"],
+        &["*"],
+        &["user:42"],
+        vec![],
+    )])
+    .unwrap();
+
+    assert_ne!(
+        policies.evaluate(&erase("ops", "42")),
+        AccessDecision::NotApplicable
+    );
+    assert_eq!(
+        policies.evaluate(&erase("ops", "420")),
+        AccessDecision::NotApplicable
+    );
+    assert_eq!(
+        policies.evaluate(&AccessRequest::new(
+            "ops",
+            "users:write",
+            Resource::new("user")
+        )),
+        AccessDecision::NotApplicable
+    );
+}
+
+#[test]
+fn conditions_check_the_attributes_of_requests() {
+    let policies = AuthorizationPolicies::new([
+        policy(
+            "acme-only",
+            PolicyEffect::Deny,
+            &["acme-*"],
+            &["users:write"],
+            &["user:*"],
+            vec![condition(
+                "resource.email_domain",
+                ConditionOperator::NotIn,
+                &["acme.test"],
+            )],
+        ),
+        policy(
+            "self-erasure",
+            PolicyEffect::Allow,
+            &["*"],
+            &["users:erase"],
+            &["user:*"],
+            vec![condition(
+                "resource.id",
+                ConditionOperator::SameAs,
+                &["subject"],
+            )],
+        ),
+    ])
+    .unwrap();
+    let write = |domain: &str| {
+        AccessRequest::new(
+            "acme-sync",
+            "users:write",
+            Resource::new("user").with_attribute("email_domain", domain),
+        )
+    };
+
+    assert_eq!(
+        policies.evaluate(&write("acme.test")),
+        AccessDecision::NotApplicable
+    );
+    assert!(matches!(
+        policies.evaluate(&write("globex.test")),
+        AccessDecision::Denied { .. }
+    ));
+    assert!(matches!(
+        policies.evaluate(&erase("42", "42")),
+        AccessDecision::Allowed { .. }
+    ));
+    assert_eq!(
+        policies.evaluate(&erase("42", "43")),
+        AccessDecision::NotApplicable
+    );
+}
+
+#[test]
+fn invalid_policies_are_rejected() {
+    let new = |name: &str, subjects: &[&str], conditions| {
+        AuthorizationPolicy::new(
+            name.to_owned(),
+            PolicyEffect::Allow,
+            strings(subjects),
+            strings(&["*"]),
+            strings(&["*"]),
+            conditions,
+        )
+    };
+
+    assert!(new(" ", &["*"], vec![]).is_err());
+    assert!(new("empty", &[], vec![]).is_err());
+    assert!(new("blank", &[""], vec![]).is_err());
+    assert!(new("list", &["a,b"], vec![]).is_err());
+    assert!(
+        new(
+            "no-values",
+            &["*"],
+            vec![condition("subject", ConditionOperator::In, &[])]
+        )
+        .is_err()
+    );
+
+    let twice = new("twice", &["*"], vec![]).unwrap();
+    assert!(AuthorizationPolicies::new([twice.clone(), twice]).is_err());
+}
+
+#[test]
+fn effects_and_operators_are_parsed_in_snake_case() {
+    assert_eq!("deny".parse::<PolicyEffect>().unwrap(), PolicyEffect::Deny);
+    assert_eq!(
+        "same_as".parse::<ConditionOperator>().unwrap(),
+        ConditionOperator::SameAs
+    );
+    assert_eq!(ConditionOperator::NotIn.to_string(), "not_in");
+    assert!("maybe".parse::<PolicyEffect>().is_err());
+}
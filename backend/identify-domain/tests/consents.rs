@@ -0,0 +1,59 @@
+use identify_domain::{
+    Consent, ConsentPolicies, NewConsentAttrs, PolicyVersion,
+};
+use uuid::Uuid;
+
+fn policies() -> ConsentPolicies {
+    ConsentPolicies::new([
+        ("terms".to_owned(), "2026-10".to_owned()),
+        ("privacy".to_owned(), "3".to_owned()),
+    ])
+    .unwrap()
+}
+
+fn consent(policy: &str, version: &str) -> Consent {
+    Consent::new(NewConsentAttrs {
+        user_id: Uuid::nil(),
+        policy: policy.to_owned(),
+        version: version.to_owned(),
+    })
+    .unwrap()
+}
+
+#[test]
+fn only_current_versions_count() {
+    let policies = policies();
+
+    let pending = policies
+        .pending(&[consent("terms", "2026-09"), consent("privacy", "3")]);
+
+    assert_eq!(
+        pending,
+        [PolicyVersion {
+            policy: "terms".to_owned(),
+            version: "2026-10".to_owned(),
+        }]
+    );
+    assert!(
+        policies
+            .pending(&[consent("terms", "2026-10"), consent("privacy", "3")])
+            .is_empty()
+    );
+}
+
+#[test]
+fn nothing_is_pending_without_policies() {
+    assert!(ConsentPolicies::default().pending(&[]).is_empty());
+}
+
+#[test]
+fn policies_must_have_valid_names_and_versions() {
+    assert!(
+        ConsentPolicies::new([("Terms".to_owned(), "1".to_owned())]).is_err()
+    );
+    assert!(
+        ConsentPolicies::new([("terms".to_owned(), " ".to_owned())]).is_err()
+    );
+    assert_eq!(policies().current_version("terms"), Some("2026-10"));
+    assert_eq!(policies().current_version("cookies"), None);
+}
@@ -0,0 +1,45 @@
+use identify_domain::{
+    DomainError, EmailAlias, EmailAliasKind, NewEmailAliasAttrs, NewUserAttrs,
+    User, Username,
+};
+use uuid::Uuid;
+
+fn alias(email: &str) -> Result<EmailAlias, DomainError> {
+    EmailAlias::new(NewEmailAliasAttrs {
+        user_id: Uuid::new_v4(),
+        email: email.to_owned(),
+        kind: EmailAliasKind::Added,
+    })
+}
+
+#[test]
+fn aliases_have_the_ids_of_users_created_with_their_emails() {
+    let jane = User::new(
+        NewUserAttrs {
+            email: "jane@acme.test".to_owned(),
+            first_name: "Jane".to_owned(),
+            last_name: None,
+        },
+        Username::parse("jane").unwrap(),
+    );
+
+    let alias = alias(" Jane@Acme.test ").unwrap();
+
+    assert_eq!(alias.email(), "Jane@Acme.test");
+    assert_eq!(alias.id(), jane.id());
+    assert_eq!(EmailAlias::id_of("jane@acme.test"), jane.id());
+}
+
+#[test]
+fn aliases_must_be_emails() {
+    assert!(alias("jane").is_err());
+    assert!(alias("@acme.test").is_err());
+}
+
+#[test]
+fn kinds_round_trip_through_strings() {
+    for kind in [EmailAliasKind::Previous, EmailAliasKind::Added] {
+        assert_eq!(kind.to_string().parse::<EmailAliasKind>().unwrap(), kind);
+    }
+    assert!("tagged".parse::<EmailAliasKind>().is_err());
+}
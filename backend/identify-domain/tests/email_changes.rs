@@ -0,0 +1,101 @@
+use chrono::{TimeDelta, Utc};
+use identify_domain::{
+    EmailChange, NewEmailChangeAttrs, NewUserAttrs, User, UserId, UserIdAttrs,
+    Username,
+};
+use uuid::Uuid;
+
+fn user(email: &str) -> User {
+    User::new(
+        NewUserAttrs {
+            email: email.to_owned(),
+            first_name: "Jane".to_owned(),
+            last_name: None,
+        },
+        Username::parse("jane").unwrap(),
+    )
+}
+
+fn change(valid_for: TimeDelta) -> EmailChange {
+    EmailChange::new(NewEmailChangeAttrs {
+        user_id: Uuid::new_v4(),
+        email: " jane@example.test ".to_owned(),
+        token_hash: "hash".to_owned(),
+        expires_at: Utc::now() + valid_for,
+    })
+    .unwrap()
+}
+
+#[test]
+fn users_keep_their_ids_when_they_change_their_emails() {
+    let mut jane = user("jane@acme.test");
+    let id = jane.id();
+
+    jane.change_email("jane@example.test".to_owned()).unwrap();
+
+    assert_eq!(jane.id(), id);
+    assert_eq!(jane.email(), "jane@example.test");
+    assert_eq!(jane.version(), 2);
+    assert!(jane.email_changed_at().is_some());
+    // The ID still identifies the user by the email they were created with.
+    let original = UserId::new(UserIdAttrs {
+        email: "jane@acme.test".to_owned(),
+    });
+    assert_eq!(original.to_uuid(), id);
+
+    let loaded = User::load(jane.to_attributes()).unwrap();
+    assert_eq!(loaded.id(), id);
+    assert_eq!(loaded.email(), "jane@example.test");
+}
+
+#[test]
+fn users_who_never_changed_their_emails_are_still_checked() {
+    let mut attrs = user("jane@acme.test").to_attributes();
+    attrs.email = "jane@example.test".to_owned();
+
+    assert!(User::load(attrs).is_err());
+}
+
+#[test]
+fn erased_users_cannot_change_their_emails() {
+    let mut jane = user("jane@acme.test");
+    jane.erase().unwrap();
+
+    assert!(jane.change_email("jane@example.test".to_owned()).is_err());
+}
+
+#[test]
+fn changes_are_confirmed_once() {
+    let mut change = change(TimeDelta::hours(24));
+    assert_eq!(change.email(), "jane@example.test");
+
+    change.confirm().unwrap();
+
+    assert!(change.is_confirmed());
+    assert!(change.confirm().is_err());
+}
+
+#[test]
+fn expired_changes_cannot_be_confirmed() {
+    let mut attrs = change(TimeDelta::hours(24)).to_attributes();
+    attrs.expires_at = Utc::now() - TimeDelta::minutes(1);
+    let mut expired = EmailChange::load(attrs).unwrap();
+
+    assert!(expired.confirm().is_err());
+}
+
+#[test]
+fn changes_must_be_valid() {
+    let attrs = |email: &str, valid_for| NewEmailChangeAttrs {
+        user_id: Uuid::new_v4(),
+        email: email.to_owned(),
+        token_hash: "hash".to_owned(),
+        expires_at: Utc::now() + valid_for,
+    };
+
+    assert!(EmailChange::new(attrs("jane", TimeDelta::hours(24))).is_err());
+    assert!(
+        EmailChange::new(attrs("jane@example.test", TimeDelta::zero()))
+            .is_err()
+    );
+}
@@ -0,0 +1,48 @@
+use identify_domain::IdentifierPolicy;
+
+fn policy() -> IdentifierPolicy {
+    IdentifierPolicy::new(["acme".to_owned()], ["darn".to_owned()])
+}
+
+#[test]
+fn reserved_words_ignore_case_and_separators() {
+    let policy = policy();
+
+    assert!(policy.is_reserved("admin"));
+    assert!(policy.is_reserved("Ad-Min"));
+    assert!(policy.is_reserved("a.c.m.e"));
+    assert!(!policy.is_reserved("admin2"));
+    assert!(!policy.is_reserved("jane"));
+}
+
+#[test]
+fn profanity_is_matched_by_whole_words() {
+    let policy = policy();
+
+    assert!(policy.is_profane("darn"));
+    assert!(policy.is_profane("jane.DARN"));
+    assert!(policy.is_profane("Darn Corp"));
+    assert!(!policy.is_profane("darnell"));
+}
+
+#[test]
+fn profanity_is_not_filtered_without_a_list() {
+    assert!(!IdentifierPolicy::default().is_profane("darn"));
+}
+
+#[test]
+fn lists_skip_comments_and_blank_lines() {
+    let words = IdentifierPolicy::parse_list("# brands\nacme\n\n  globex  \n");
+
+    assert_eq!(words, ["acme", "globex"]);
+}
+
+#[test]
+fn violations_are_validation_errors() {
+    let error = policy().check("Username", "root").unwrap_err();
+
+    assert_eq!(
+        error.to_string(),
+        "Invalid value for Username: 'root' is reserved"
+    );
+}
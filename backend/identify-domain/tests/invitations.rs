@@ -0,0 +1,65 @@
+use chrono::{TimeDelta, Utc};
+use identify_domain::{
+    Invitation, InvitationStatus, MembershipRole, NewInvitationAttrs,
+};
+use uuid::Uuid;
+
+fn attrs(role: MembershipRole, valid_for: TimeDelta) -> NewInvitationAttrs {
+    NewInvitationAttrs {
+        email: " jane@acme.test ".to_owned(),
+        inviter_id: Uuid::new_v4(),
+        organization_id: Uuid::new_v4(),
+        role,
+        token_hash: "hash".to_owned(),
+        expires_at: Utc::now() + valid_for,
+    }
+}
+
+#[test]
+fn invitations_are_accepted_once() {
+    let mut invitation =
+        Invitation::new(attrs(MembershipRole::Admin, TimeDelta::days(7)))
+            .unwrap();
+    assert_eq!(invitation.email(), "jane@acme.test");
+    assert_eq!(invitation.status(), InvitationStatus::Pending);
+
+    let user_id = Uuid::new_v4();
+    invitation.accept(user_id).unwrap();
+
+    assert_eq!(invitation.status(), InvitationStatus::Accepted);
+    assert_eq!(*invitation.user_id(), Some(user_id));
+    assert!(invitation.accepted_at().is_some());
+    assert!(invitation.accept(Uuid::new_v4()).is_err());
+}
+
+#[test]
+fn expired_invitations_cannot_be_accepted() {
+    let invitation =
+        Invitation::new(attrs(MembershipRole::Member, TimeDelta::days(7)))
+            .unwrap();
+    let mut attrs = invitation.to_attributes();
+    attrs.expires_at = Utc::now() - TimeDelta::minutes(1);
+    let mut expired = Invitation::load(attrs).unwrap();
+
+    assert_eq!(expired.status(), InvitationStatus::Expired);
+    assert!(expired.accept(Uuid::new_v4()).is_err());
+}
+
+#[test]
+fn invitations_must_be_valid() {
+    assert!(
+        Invitation::new(attrs(MembershipRole::Owner, TimeDelta::days(7)))
+            .is_err()
+    );
+    assert!(
+        Invitation::new(attrs(MembershipRole::Member, TimeDelta::zero()))
+            .is_err()
+    );
+    assert!(
+        Invitation::new(NewInvitationAttrs {
+            email: "jane".to_owned(),
+            ..attrs(MembershipRole::Member, TimeDelta::days(7))
+        })
+        .is_err()
+    );
+}
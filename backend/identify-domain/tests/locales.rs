@@ -0,0 +1,66 @@
+use chrono::{TimeZone as _, Utc};
+use identify_domain::{Locale, NewUserAttrs, TimeZone, User, Username};
+
+#[test]
+fn locales_are_normalized() {
+    for (value, expected) in [
+        ("en", "en"),
+        ("PT_br", "pt-BR"),
+        ("zh-hant-tw", "zh-Hant-TW"),
+        ("es-419", "es-419"),
+        ("de-CH-1996", "de-CH-1996"),
+    ] {
+        assert_eq!(Locale::parse(value).unwrap().as_str(), expected);
+    }
+}
+
+#[test]
+fn malformed_locales_are_rejected() {
+    for value in ["", "e", "english1", "en-US-Hant", "en--US", "en-x-private"] {
+        assert!(Locale::parse(value).is_err(), "{value}");
+    }
+}
+
+#[test]
+fn locales_fall_back_to_shorter_tags() {
+    let locale = Locale::parse("zh-Hant-TW").unwrap();
+
+    assert_eq!(locale.language(), "zh");
+    assert_eq!(
+        locale.fallbacks().collect::<Vec<_>>(),
+        ["zh-Hant-TW", "zh-Hant", "zh"]
+    );
+}
+
+#[test]
+fn time_zones_are_iana_identifiers() {
+    let timezone = TimeZone::parse("Europe/Berlin").unwrap();
+    let time = Utc.with_ymd_and_hms(2026, 7, 1, 12, 0, 0).unwrap();
+
+    assert_eq!(timezone.as_str(), "Europe/Berlin");
+    assert_eq!(timezone.format(time), "2026-07-01 14:00 CEST");
+    assert!(TimeZone::parse("Mars/Olympus_Mons").is_err());
+    assert!(TimeZone::parse("CEST").is_err());
+}
+
+#[test]
+fn localizing_users_increments_their_versions() {
+    let mut user = User::new(
+        NewUserAttrs {
+            email: "jane@acme.test".to_owned(),
+            first_name: "Jane".to_owned(),
+            last_name: None,
+        },
+        Username::parse("jane").unwrap(),
+    );
+    let locale = Some(Locale::parse("de").unwrap());
+    let timezone = Some(TimeZone::parse("Europe/Berlin").unwrap());
+
+    assert!(user.localize(locale.clone(), timezone));
+    assert!(!user.localize(locale, timezone));
+    assert_eq!(user.version(), 2);
+
+    let loaded = User::load(user.to_attributes()).unwrap();
+    assert_eq!(loaded.locale().map(Locale::as_str), Some("de"));
+    assert_eq!(loaded.timezone(), timezone);
+}
@@ -0,0 +1,69 @@
+use chrono::{TimeDelta, Utc};
+use identify_domain::{
+    NewPhoneVerificationAttrs, PhoneNumber, PhoneVerification,
+};
+use uuid::Uuid;
+
+#[test]
+fn phone_numbers_are_normalized_to_e164() {
+    for (value, expected) in [
+        ("+1 (415) 555-0123", "+14155550123"),
+        ("0044 20 7946 0958", "+442079460958"),
+        ("+49.30.901820", "+4930901820"),
+    ] {
+        assert_eq!(PhoneNumber::parse(value).unwrap().as_str(), expected);
+    }
+
+    for value in [
+        "415 555 0123",
+        "+1 415 555 012a",
+        "+0 415 555 0123",
+        "+12345",
+        "+1234567890123456",
+        "",
+    ] {
+        assert!(PhoneNumber::parse(value).is_err(), "{value}");
+    }
+}
+
+fn verification() -> PhoneVerification {
+    PhoneVerification::new(
+        Uuid::new_v4(),
+        NewPhoneVerificationAttrs {
+            user_id: Uuid::new_v4(),
+            phone_number: PhoneNumber::parse("+14155550123").unwrap(),
+            code_hash: "hash".to_owned(),
+            expires_at: Utc::now() + TimeDelta::minutes(10),
+        },
+    )
+    .unwrap()
+}
+
+#[test]
+fn phone_numbers_are_verified_once_with_the_right_code() {
+    let mut verification = verification();
+
+    assert!(!verification.attempt(false).unwrap());
+    assert!(verification.attempt(true).unwrap());
+
+    assert!(verification.is_verified());
+    assert_eq!(verification.attempts(), 1);
+    assert!(verification.attempt(true).is_err());
+}
+
+#[test]
+fn verifications_fail_after_too_many_wrong_codes_or_once_expired() {
+    let mut verification = verification();
+    for _ in 0..PhoneVerification::MAX_ATTEMPTS {
+        assert!(!verification.attempt(false).unwrap());
+    }
+
+    assert!(verification.attempt(true).is_err());
+    assert!(!verification.is_verified());
+
+    let mut attrs = verification.to_attributes();
+    attrs.attempts = 0;
+    attrs.expires_at = Utc::now() - TimeDelta::minutes(1);
+    let mut expired = PhoneVerification::load(attrs).unwrap();
+    assert!(expired.attempt(true).is_err());
+}
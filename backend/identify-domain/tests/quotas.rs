@@ -0,0 +1,55 @@
+use identify_domain::{
+    NewOrganizationQuotaAttrs, OrganizationQuota, QuotaExceeded, QuotaPolicy,
+};
+use uuid::Uuid;
+
+fn quota(max_users: Option<u32>) -> OrganizationQuota {
+    OrganizationQuota::new(NewOrganizationQuotaAttrs {
+        organization_id: Uuid::new_v4(),
+        max_users,
+    })
+}
+
+#[test]
+fn organizations_are_limited_by_the_default() {
+    let id = Uuid::new_v4();
+    let policy = QuotaPolicy::new(Some(2));
+
+    assert_eq!(policy.check(id, None, 1), Ok(()));
+    assert_eq!(
+        policy.check(id, None, 2),
+        Err(QuotaExceeded {
+            organization_id: id,
+            max_users: 2,
+        })
+    );
+    assert_eq!(QuotaPolicy::default().check(id, None, 1000), Ok(()));
+}
+
+#[test]
+fn quotas_of_organizations_replace_the_default() {
+    let id = Uuid::new_v4();
+    let policy = QuotaPolicy::new(Some(2));
+
+    let larger = quota(Some(5));
+    assert_eq!(policy.max_users(Some(&larger)), Some(5));
+    assert_eq!(policy.check(id, Some(&larger), 4), Ok(()));
+
+    let unlimited = quota(None);
+    assert_eq!(policy.max_users(Some(&unlimited)), None);
+    assert_eq!(policy.check(id, Some(&unlimited), 1000), Ok(()));
+
+    let none = quota(Some(0));
+    assert!(policy.check(id, Some(&none), 0).is_err());
+}
+
+#[test]
+fn changed_quotas_are_touched() {
+    let mut quota = quota(Some(5));
+    let updated_at = *quota.updated_at();
+
+    assert!(!quota.set_max_users(Some(5)));
+    assert!(quota.set_max_users(Some(10)));
+    assert_eq!(quota.max_users(), Some(10));
+    assert!(*quota.updated_at() >= updated_at);
+}
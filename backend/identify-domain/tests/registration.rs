@@ -0,0 +1,72 @@
+use chrono::{TimeDelta, Utc};
+use identify_domain::{
+    EmailVerification, NewEmailVerificationAttrs, RegistrationMode,
+    RegistrationPolicy, RegistrationRefusal,
+};
+use uuid::Uuid;
+
+#[test]
+fn policies_decide_who_can_register() {
+    let open = RegistrationPolicy::new(RegistrationMode::Open, []).unwrap();
+    assert_eq!(open.check("jane@acme.test"), Ok(()));
+
+    let invite_only = RegistrationPolicy::default();
+    assert_eq!(invite_only.mode(), RegistrationMode::InviteOnly);
+    assert_eq!(
+        invite_only.check("jane@acme.test"),
+        Err(RegistrationRefusal::InviteOnly)
+    );
+
+    let domains = RegistrationPolicy::new(
+        RegistrationMode::AllowedDomains,
+        [" Acme.Test ".to_owned()],
+    )
+    .unwrap();
+    assert_eq!(domains.check("jane@ACME.test"), Ok(()));
+    assert_eq!(
+        domains.check("jane@eu.acme.test"),
+        Err(RegistrationRefusal::DomainNotAllowed(
+            "eu.acme.test".to_owned()
+        ))
+    );
+}
+
+#[test]
+fn policies_must_be_valid() {
+    assert!(
+        RegistrationPolicy::new(RegistrationMode::AllowedDomains, []).is_err()
+    );
+    assert!(
+        RegistrationPolicy::new(
+            RegistrationMode::Open,
+            ["jane@acme.test".to_owned()]
+        )
+        .is_err()
+    );
+    assert_eq!(
+        "allowed_domains".parse::<RegistrationMode>().unwrap(),
+        RegistrationMode::AllowedDomains
+    );
+}
+
+#[test]
+fn emails_are_verified_once_before_they_expire() {
+    let mut verification = EmailVerification::new(NewEmailVerificationAttrs {
+        user_id: Uuid::new_v4(),
+        token_hash: "hash".to_owned(),
+        expires_at: Utc::now() + TimeDelta::days(2),
+    })
+    .unwrap();
+
+    verification.verify().unwrap();
+
+    assert!(verification.is_verified());
+    assert!(verification.verify().is_err());
+
+    let mut attrs = verification.to_attributes();
+    attrs.verified_at = None;
+    attrs.expires_at = Utc::now() - TimeDelta::minutes(1);
+    let mut expired = EmailVerification::load(attrs).unwrap();
+    assert!(expired.verify().is_err());
+    assert!(!expired.is_verified());
+}
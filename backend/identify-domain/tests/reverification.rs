@@ -0,0 +1,40 @@
+use identify_domain::{
+    NewReverificationCampaignAttrs, ReverificationCampaign,
+    ReverificationCohort,
+};
+
+fn campaign(
+    email_domain: &str,
+) -> identify_domain::Result<ReverificationCampaign> {
+    ReverificationCampaign::new(NewReverificationCampaignAttrs {
+        reason: "Our email domain has moved.".to_owned(),
+        cohort: ReverificationCohort {
+            email_domain: Some(email_domain.to_owned()),
+            created_before: None,
+        },
+    })
+}
+
+#[test]
+fn email_domains_are_normalized() {
+    let campaign = campaign(" @ACME.test ").unwrap();
+
+    assert_eq!(campaign.cohort().email_domain.as_deref(), Some("acme.test"));
+}
+
+#[test]
+fn email_domains_with_wildcards_are_rejected() {
+    for domain in ["", "acme%", "ac_me.test", "acme test"] {
+        assert!(campaign(domain).is_err(), "{domain}");
+    }
+}
+
+#[test]
+fn reasons_are_required() {
+    let result = ReverificationCampaign::new(NewReverificationCampaignAttrs {
+        reason: "  ".to_owned(),
+        cohort: ReverificationCohort::default(),
+    });
+
+    assert!(result.is_err());
+}
@@ -0,0 +1,53 @@
+use identify_domain::{NewRoleAttrs, Role, RoleCohort};
+use uuid::Uuid;
+
+fn role(name: &str, permissions: &[&str]) -> identify_domain::Result<Role> {
+    Role::new(NewRoleAttrs {
+        name: name.to_owned(),
+        description: Some("  ".to_owned()),
+        permissions: permissions.iter().map(|&p| p.to_owned()).collect(),
+        external_id: None,
+    })
+}
+
+#[test]
+fn roles_keep_their_permissions_once() {
+    let mut role = role(" support ", &["users:read", "users:read"]).unwrap();
+
+    assert_eq!(role.name(), "support");
+    assert_eq!(role.description(), None);
+    assert_eq!(role.permissions(), ["users:read"]);
+
+    assert!(role.grant("users:write").unwrap());
+    assert!(!role.grant("users:write").unwrap());
+    assert!(role.revoke("users:read"));
+    assert!(!role.revoke("users:read"));
+    assert_eq!(role.permissions(), ["users:write"]);
+}
+
+#[test]
+fn invalid_names_and_permissions_are_rejected() {
+    assert!(role("", &[]).is_err());
+    assert!(role("Support", &[]).is_err());
+    assert!(role(&"a".repeat(65), &[]).is_err());
+    assert!(role("support", &["users"]).is_err());
+    assert!(role("support", &["users:"]).is_err());
+    assert!(role("support", &["users:read,write"]).is_err());
+    assert!(role("support", &["users: read"]).is_err());
+
+    let mut role = role("support", &[]).unwrap();
+    assert!(role.rename("help desk").is_err());
+    assert!(role.grant(":read").is_err());
+    assert!(!role.rename("support").unwrap());
+}
+
+#[test]
+fn cohorts_need_a_valid_criterion() {
+    let cohort =
+        RoleCohort::new(Some(" @Acme.test ".to_owned()), None).unwrap();
+
+    assert_eq!(cohort.email_domain(), Some("acme.test"));
+    assert!(RoleCohort::new(None, Some(Uuid::new_v4())).is_ok());
+    assert!(RoleCohort::new(None, None).is_err());
+    assert!(RoleCohort::new(Some("%.test".to_owned()), None).is_err());
+}
@@ -0,0 +1,41 @@
+use identify_domain::Slug;
+
+#[test]
+fn slugs_are_normalized() {
+    assert_eq!(Slug::parse(" Acme-Corp ").unwrap().as_str(), "acme-corp");
+}
+
+#[test]
+fn invalid_slugs_are_rejected() {
+    for slug in ["ac", "acme corp", "-acme", "acme-", "acme--corp", "acmé"] {
+        assert!(Slug::parse(slug).is_err(), "{slug}");
+    }
+    assert!(Slug::parse(&"a".repeat(Slug::MAX_LENGTH + 1)).is_err());
+}
+
+#[test]
+fn slugs_are_derived_from_names() {
+    let slug = |name| Slug::from_name(name).map(Slug::into_string);
+
+    assert_eq!(slug("ACME Corp."), Some("acme-corp".to_owned()));
+    assert_eq!(slug("Café & Co"), Some("caf-co".to_owned()));
+    assert_eq!(slug("東京"), None);
+}
+
+#[test]
+fn suffixes_keep_slugs_short() {
+    let long = Slug::parse(&"a".repeat(Slug::MAX_LENGTH)).unwrap();
+    let suffixed = long.with_suffix(12);
+
+    assert_eq!(suffixed.as_str().len(), Slug::MAX_LENGTH);
+    assert!(suffixed.as_str().ends_with("a-12"));
+    assert!(Slug::parse(suffixed.as_str()).is_ok());
+}
+
+#[test]
+fn random_slugs_are_valid() {
+    let slug = Slug::random();
+
+    assert!(slug.as_str().starts_with("org-"));
+    assert!(Slug::parse(slug.as_str()).is_ok());
+}
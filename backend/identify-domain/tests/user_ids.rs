@@ -0,0 +1,53 @@
+use identify_domain::{DomainError, UserId, UserIdAttrs};
+
+fn id(email: &str) -> UserId {
+    UserId::new(UserIdAttrs {
+        email: email.to_owned(),
+    })
+}
+
+#[test]
+fn emails_are_normalized_before_hashing() {
+    let expected = id("jane@example.test").to_uuid();
+
+    assert_eq!(id("Jane@Example.TEST").to_uuid(), expected);
+    assert_eq!(id(" jane@example.test\n").to_uuid(), expected);
+    assert_ne!(id("joan@example.test").to_uuid(), expected);
+}
+
+#[test]
+fn emails_are_composed_before_hashing() {
+    assert_eq!(
+        id("zo\u{e9}@example.test").to_uuid(),
+        id("zoe\u{301}@example.test").to_uuid()
+    );
+}
+
+#[test]
+fn ids_of_the_first_version_are_still_loaded() {
+    let legacy = id("Jane@Example.test");
+    let stored = legacy.to_uuid_v1();
+
+    assert_ne!(stored, legacy.to_uuid());
+    assert!(
+        UserId::load(
+            UserIdAttrs {
+                email: "Jane@Example.test".to_owned(),
+            },
+            stored,
+        )
+        .is_ok()
+    );
+}
+
+#[test]
+fn ids_of_other_emails_are_rejected() {
+    let result = UserId::load(
+        UserIdAttrs {
+            email: "jane@example.test".to_owned(),
+        },
+        id("joan@example.test").to_uuid(),
+    );
+
+    assert!(matches!(result, Err(DomainError::IdMismatch { .. })));
+}
@@ -0,0 +1,86 @@
+use identify_domain::{Username, UsernameStrategy};
+
+fn candidates(
+    strategy: UsernameStrategy,
+    email: &str,
+    first_name: &str,
+    last_name: Option<&str>,
+) -> Vec<String> {
+    strategy
+        .candidates(email, first_name, last_name)
+        .take(3)
+        .map(Username::into_string)
+        .collect()
+}
+
+#[test]
+fn email_local_part_drops_tags() {
+    assert_eq!(
+        candidates(
+            UsernameStrategy::EmailLocalPart,
+            "Jane.Doe+work@acme.test",
+            "Jane",
+            None
+        ),
+        ["jane.doe", "jane.doe2", "jane.doe3"]
+    );
+}
+
+#[test]
+fn first_dot_last_joins_the_names() {
+    assert_eq!(
+        candidates(
+            UsernameStrategy::FirstDotLast,
+            "jd@acme.test",
+            "Mary Ann",
+            Some("O'Neil")
+        ),
+        ["mary.ann.o.neil", "mary.ann.o.neil2", "mary.ann.o.neil3"]
+    );
+}
+
+#[test]
+fn short_names_always_get_a_suffix() {
+    assert_eq!(
+        candidates(
+            UsernameStrategy::EmailLocalPart,
+            "jo@acme.test",
+            "Jo",
+            None
+        ),
+        ["jo1", "jo2", "jo3"]
+    );
+}
+
+#[test]
+fn suffixes_fit_into_the_maximum_length() {
+    let email = format!("{}@acme.test", "a".repeat(40));
+
+    for username in
+        candidates(UsernameStrategy::EmailLocalPart, &email, "A", None)
+    {
+        assert!(username.len() <= Username::MAX_LENGTH, "{username}");
+    }
+}
+
+#[test]
+fn names_without_ascii_fall_back_to_random_handles() {
+    let handles = candidates(
+        UsernameStrategy::FirstDotLast,
+        "x@acme.test",
+        "Йосип",
+        None,
+    );
+
+    assert!(handles.iter().all(|handle| handle.starts_with("user-")));
+    assert_ne!(handles[0], handles[1]);
+}
+
+#[test]
+fn usernames_are_normalized_and_validated() {
+    assert_eq!(Username::parse(" Jane_Doe ").unwrap().as_str(), "jane_doe");
+
+    for invalid in ["jd", "jane doe", "-jane", "jane.", "jané"] {
+        assert!(Username::parse(invalid).is_err(), "{invalid}");
+    }
+}
@@ -0,0 +1,10 @@
+create table operations (
+  id                text primary key not null,
+  kind              text not null,
+  status            text not null,
+  progress          integer not null,
+  error             text null,
+  cancel_requested  boolean not null,
+  created_at datetime not null,
+  updated_at datetime not null
+);
@@ -0,0 +1,17 @@
+create table organizations (
+  id          text primary key not null,
+  name        text not null,
+  created_at datetime not null,
+  updated_at datetime not null
+);
+
+create table memberships (
+  organization_id text not null references organizations (id) on delete cascade,
+  user_id         text not null references users (id) on delete cascade,
+  role            text not null,
+  created_at datetime not null,
+  updated_at datetime not null,
+  primary key (organization_id, user_id)
+);
+
+create index memberships_user_id_idx on memberships (user_id);
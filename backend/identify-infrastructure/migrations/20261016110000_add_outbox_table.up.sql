@@ -0,0 +1,15 @@
+-- Events waiting to be published to other systems.
+--
+-- Event IDs are derived from the event contents, so the primary key deduplicates events
+-- emitted more than once.
+create table outbox (
+  id             text primary key not null,
+  aggregate_type text not null,
+  aggregate_id   text not null,
+  event_type     text not null,
+  version        integer not null,
+  occurred_at  datetime not null,
+  published_at datetime
+);
+
+create index outbox_unpublished_idx on outbox (occurred_at) where published_at is null;
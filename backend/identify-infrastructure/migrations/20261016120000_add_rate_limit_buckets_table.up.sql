@@ -0,0 +1,11 @@
+-- Token buckets of the rate limiter, shared by all the processes that use the database.
+--
+-- Times are Unix timestamps in (fractional) seconds, so that refills can be computed in SQL.
+create table rate_limit_buckets (
+  key        text primary key not null,
+  tokens     real not null,
+  allowed    boolean not null,
+  updated_at real not null
+);
+
+create index rate_limit_buckets_updated_at_idx on rate_limit_buckets (updated_at);
@@ -0,0 +1,3 @@
+drop index organizations_external_id_idx;
+
+alter table organizations drop column external_id;
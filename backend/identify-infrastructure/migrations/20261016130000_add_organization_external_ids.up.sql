@@ -0,0 +1,5 @@
+-- IDs assigned to organizations by external tools, e.g. infrastructure as code, so that they
+-- can manage the organizations idempotently.
+alter table organizations add column external_id text null;
+
+create unique index organizations_external_id_idx on organizations (external_id);
@@ -0,0 +1,6 @@
+-- Incident banner shown on the status page. There is at most a single incident at a time.
+create table status_incident (
+  id         integer primary key not null check (id = 1),
+  message    text not null,
+  updated_at datetime not null
+);
@@ -0,0 +1,3 @@
+-- Every change of a user increments their version, so that the events announcing the changes
+-- get distinct IDs.
+alter table users add column version integer not null default 1;
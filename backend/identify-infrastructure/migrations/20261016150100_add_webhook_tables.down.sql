@@ -0,0 +1,5 @@
+drop index webhook_deliveries_due_idx;
+
+drop table webhook_deliveries;
+
+drop table webhook_endpoints;
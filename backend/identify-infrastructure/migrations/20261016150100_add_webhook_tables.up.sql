@@ -0,0 +1,31 @@
+-- Endpoints user events are delivered to.
+create table webhook_endpoints (
+  id          text primary key not null,
+  url         text not null,
+  secret      text not null,
+  -- Comma-separated event types the endpoint is subscribed to, or empty for all of them.
+  event_types text not null,
+  created_at datetime not null,
+  updated_at datetime not null
+);
+
+-- Deliveries of events to the endpoints, including the ones that are still being retried and
+-- the ones that gave up (dead letters).
+create table webhook_deliveries (
+  id              text primary key not null,
+  endpoint_id     text not null references webhook_endpoints (id) on delete cascade,
+  event_id        text not null,
+  user_id         text not null,
+  event_type      text not null,
+  occurred_at     datetime not null,
+  status          text not null,
+  attempts        integer not null,
+  next_attempt_at datetime not null,
+  last_error      text null,
+  created_at datetime not null,
+  updated_at datetime not null,
+  unique (endpoint_id, event_id)
+);
+
+create index webhook_deliveries_due_idx on webhook_deliveries (next_attempt_at)
+  where status = 'pending';
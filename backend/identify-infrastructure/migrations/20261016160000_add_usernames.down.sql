@@ -0,0 +1,2 @@
+drop index users_username_idx;
+alter table users drop column username;
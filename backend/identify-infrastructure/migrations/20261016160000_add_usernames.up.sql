@@ -0,0 +1,5 @@
+-- Users created before usernames existed get a handle derived from their ID, since names
+-- derived from their emails could collide.
+alter table users add column username text not null default '';
+update users set username = 'user-' || substr(lower(hex(id)), 1, 16);
+create unique index users_username_idx on users (username);
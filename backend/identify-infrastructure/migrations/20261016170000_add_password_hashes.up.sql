@@ -0,0 +1,3 @@
+-- Hashes in the PHC string format, e.g. `$argon2id$v=19$m=19456,t=2,p=1$...`. Users without a
+-- password can't sign in with one.
+alter table users add column password_hash text;
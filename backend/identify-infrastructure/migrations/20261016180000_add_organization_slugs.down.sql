@@ -0,0 +1,3 @@
+drop table organization_slugs;
+drop index organizations_slug_idx;
+alter table organizations drop column slug;
@@ -0,0 +1,18 @@
+-- Organizations created before slugs existed get one derived from their ID, since slugs
+-- derived from their names could collide.
+alter table organizations add column slug text not null default '';
+update organizations set slug = 'org-' || substr(lower(hex(id)), 1, 12);
+create unique index organizations_slug_idx on organizations (slug);
+
+-- Every slug an organization has ever used, so that old slugs keep pointing to it and can't be
+-- taken by another organization.
+create table organization_slugs (
+  slug            text primary key not null,
+  organization_id text not null references organizations (id) on delete cascade,
+  created_at datetime not null
+);
+
+create index organization_slugs_organization_id_idx on organization_slugs (organization_id);
+
+insert into organization_slugs (slug, organization_id, created_at)
+select slug, id, created_at from organizations;
@@ -0,0 +1,2 @@
+drop table reverification_requests;
+drop table reverification_campaigns;
@@ -0,0 +1,21 @@
+create table reverification_campaigns (
+  id              text primary key not null,
+  reason          text not null,
+  email_domain    text null,
+  created_before  datetime null,
+  created_at datetime not null
+);
+
+-- Users flagged as requiring email re-verification by a campaign. The notified ones have the
+-- time they were notified at.
+create table reverification_requests (
+  campaign_id text not null references reverification_campaigns (id) on delete cascade,
+  user_id     text not null references users (id) on delete cascade,
+  notified_at datetime null,
+  created_at datetime not null,
+  primary key (campaign_id, user_id)
+);
+
+create index reverification_requests_pending_idx
+  on reverification_requests (campaign_id, created_at)
+  where notified_at is null;
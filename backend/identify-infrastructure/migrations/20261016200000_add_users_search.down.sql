@@ -0,0 +1,5 @@
+drop trigger users_search_delete;
+drop trigger users_search_update;
+drop trigger users_search_insert;
+drop table users_search;
+drop table users_search_keys;
@@ -0,0 +1,39 @@
+-- Full-text index over the emails and names of users. The index can only be keyed by integers,
+-- and the rowids of users can change when the database is vacuumed, so every user gets a
+-- stable key of their own. The index is kept in sync by triggers.
+create table users_search_keys (
+  key     integer primary key,
+  user_id blob not null unique references users (id) on delete cascade
+);
+
+create virtual table users_search using fts5(
+  email,
+  first_name,
+  last_name,
+  tokenize = 'unicode61 remove_diacritics 2',
+  prefix = '2 3'
+);
+
+insert into users_search_keys (user_id) select id from users;
+insert into users_search (rowid, email, first_name, last_name)
+select k.key, u.email, u.first_name, coalesce(u.last_name, '')
+from users u join users_search_keys k on k.user_id = u.id;
+
+create trigger users_search_insert after insert on users begin
+  insert into users_search_keys (user_id) values (new.id);
+  insert into users_search (rowid, email, first_name, last_name)
+  values (last_insert_rowid(), new.email, new.first_name, coalesce(new.last_name, ''));
+end;
+
+create trigger users_search_update after update of email, first_name, last_name on users begin
+  delete from users_search
+  where rowid = (select key from users_search_keys where user_id = old.id);
+  insert into users_search (rowid, email, first_name, last_name)
+  select key, new.email, new.first_name, coalesce(new.last_name, '')
+  from users_search_keys where user_id = new.id;
+end;
+
+create trigger users_search_delete before delete on users begin
+  delete from users_search
+  where rowid = (select key from users_search_keys where user_id = old.id);
+end;
@@ -0,0 +1,3 @@
+drop index outbox_unprojected_idx;
+alter table outbox drop column projected_at;
+drop table user_summaries;
@@ -0,0 +1,33 @@
+-- Read model of users, projected from the outbox, so that listings don't read from the tables
+-- the write path locks. Summaries may lag behind the users until their events are projected.
+create table user_summaries (
+  id           blob primary key not null,
+  email        text not null,
+  email_domain text not null,
+  username     text not null,
+  first_name   text not null,
+  last_name    text null,
+  version      integer not null,
+  created_at   datetime not null,
+  updated_at   datetime not null
+);
+
+create index user_summaries_email_idx on user_summaries (email);
+create index user_summaries_email_domain_idx on user_summaries (email_domain);
+create index user_summaries_created_at_idx on user_summaries (created_at);
+
+insert into user_summaries (
+  id, email, email_domain, username, first_name, last_name, version, created_at,
+  updated_at
+)
+select
+  id, email, lower(substr(email, instr(email, '@') + 1)), username, first_name, last_name,
+  version, created_at, updated_at
+from users;
+
+-- Events are projected independently of their publishing. The existing events are already
+-- reflected by the summaries created above.
+alter table outbox add column projected_at datetime;
+update outbox set projected_at = occurred_at;
+
+create index outbox_unprojected_idx on outbox (occurred_at) where projected_at is null;
@@ -0,0 +1,11 @@
+-- Custom attributes applications attach to users, with their values encoded as text next to
+-- their types (`string`, `number`, `bool` or `json`).
+create table user_attributes (
+  user_id    text not null references users (id) on delete cascade,
+  key        text not null,
+  value_type text not null,
+  value      text not null,
+  created_at datetime not null,
+  updated_at datetime not null,
+  primary key (user_id, key)
+);
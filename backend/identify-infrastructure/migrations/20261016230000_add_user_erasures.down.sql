@@ -0,0 +1,2 @@
+drop table user_erasures;
+alter table users drop column erased_at;
@@ -0,0 +1,10 @@
+-- Erased users are kept with their personal data replaced by placeholders, so that everything
+-- referring to them stays consistent.
+alter table users add column erased_at datetime;
+
+-- Audit trail of erasures. `erased_by` is the subject of the actor who requested the erasure.
+create table user_erasures (
+  user_id   text primary key not null references users (id),
+  erased_by text not null,
+  erased_at datetime not null
+);
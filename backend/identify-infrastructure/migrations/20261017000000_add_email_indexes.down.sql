@@ -0,0 +1,5 @@
+drop index user_summaries_email_index_idx;
+drop index users_email_index_idx;
+
+alter table user_summaries drop column email_index;
+alter table users drop column email_index;
@@ -0,0 +1,7 @@
+-- Blind indexes of the emails, i.e. their keyed hashes, so that encrypted emails can still be
+-- looked up. They're only set while the encryption of personal data is enabled.
+alter table users add column email_index text;
+alter table user_summaries add column email_index text;
+
+create index users_email_index_idx on users (email_index);
+create index user_summaries_email_index_idx on user_summaries (email_index);
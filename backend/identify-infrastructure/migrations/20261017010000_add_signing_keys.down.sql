@@ -0,0 +1,3 @@
+drop index signing_keys_active_idx;
+
+drop table signing_keys;
@@ -0,0 +1,15 @@
+-- Keys the tokens issued by the service are signed with. Keys are base64-encoded, and the
+-- private ones are encrypted like the personal data of users if the encryption is enabled.
+create table signing_keys (
+  id          text primary key not null,
+  algorithm   text not null,
+  public_key  text not null,
+  private_key text not null,
+  status      text not null,
+  created_at  datetime not null,
+  retired_at  datetime null
+);
+
+-- At most one key is active at a time.
+create unique index signing_keys_active_idx on signing_keys (status)
+  where status = 'active';
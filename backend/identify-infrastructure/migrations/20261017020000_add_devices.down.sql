@@ -0,0 +1,5 @@
+drop index devices_user_id_idx;
+
+drop index devices_fingerprint_idx;
+
+drop table devices;
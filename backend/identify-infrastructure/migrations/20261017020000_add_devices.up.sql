@@ -0,0 +1,19 @@
+-- Devices users logged in from, recognized by their fingerprints. Revoked devices are kept, so
+-- that users can still see them, but the next login with their fingerprint adds a new device.
+create table devices (
+  id            text primary key not null,
+  user_id       text not null references users (id) on delete cascade,
+  fingerprint   text not null,
+  user_agent    text not null,
+  ip            text not null,
+  location      text null,
+  first_seen_at datetime not null,
+  last_seen_at  datetime not null,
+  revoked_at    datetime null
+);
+
+-- A user trusts at most one device with the same fingerprint.
+create unique index devices_fingerprint_idx on devices (user_id, fingerprint)
+  where revoked_at is null;
+
+create index devices_user_id_idx on devices (user_id, last_seen_at);
@@ -0,0 +1,3 @@
+drop index consents_version_idx;
+
+drop table consents;
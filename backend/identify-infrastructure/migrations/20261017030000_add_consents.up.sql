@@ -0,0 +1,11 @@
+-- Versions of policies users accepted, e.g. of the terms of service. Consents are never changed
+-- or deleted while the user exists, so that the history of what they agreed to is kept.
+create table consents (
+  id          text primary key not null,
+  user_id     text not null references users (id) on delete cascade,
+  policy      text not null,
+  version     text not null,
+  accepted_at datetime not null
+);
+
+create unique index consents_version_idx on consents (user_id, policy, version);
@@ -0,0 +1,5 @@
+drop index invitations_user_id_idx;
+
+drop index invitations_token_hash_idx;
+
+drop table invitations;
@@ -0,0 +1,19 @@
+-- Invitations for people without an account to join organizations. Only the hashes of the
+-- tokens are stored. The emails are encrypted like the ones of users, but invitations are
+-- short-lived, so they're left out of re-encryptions.
+create table invitations (
+  id              text primary key not null,
+  email           text not null,
+  inviter_id      text not null references users (id),
+  organization_id text not null references organizations (id) on delete cascade,
+  role            text not null,
+  token_hash      text not null,
+  expires_at      datetime not null,
+  created_at      datetime not null,
+  accepted_at     datetime null,
+  user_id         text null references users (id) on delete cascade
+);
+
+create unique index invitations_token_hash_idx on invitations (token_hash);
+
+create index invitations_user_id_idx on invitations (user_id) where user_id is not null;
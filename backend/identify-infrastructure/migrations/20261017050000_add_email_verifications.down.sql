@@ -0,0 +1,5 @@
+drop index email_verifications_user_id_idx;
+
+drop index email_verifications_token_hash_idx;
+
+drop table email_verifications;
@@ -0,0 +1,14 @@
+-- Verifications of the emails of users, e.g. of the ones who registered themselves. Only the
+-- hashes of the tokens are stored.
+create table email_verifications (
+  id          text primary key not null,
+  user_id     text not null references users (id) on delete cascade,
+  token_hash  text not null,
+  expires_at  datetime not null,
+  created_at  datetime not null,
+  verified_at datetime null
+);
+
+create unique index email_verifications_token_hash_idx on email_verifications (token_hash);
+
+create index email_verifications_user_id_idx on email_verifications (user_id);
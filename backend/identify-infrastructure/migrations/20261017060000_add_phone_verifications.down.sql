@@ -0,0 +1,3 @@
+drop index phone_verifications_user_id_idx;
+
+drop table phone_verifications;
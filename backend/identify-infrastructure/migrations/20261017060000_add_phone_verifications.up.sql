@@ -0,0 +1,14 @@
+-- Verifications of the phone numbers of users. The numbers are encrypted like the emails of
+-- users, and only the hashes of the codes are stored.
+create table phone_verifications (
+  id           text primary key not null,
+  user_id      text not null references users (id) on delete cascade,
+  phone_number text not null,
+  code_hash    text not null,
+  attempts     integer not null default 0,
+  expires_at   datetime not null,
+  created_at   datetime not null,
+  verified_at  datetime null
+);
+
+create index phone_verifications_user_id_idx on phone_verifications (user_id);
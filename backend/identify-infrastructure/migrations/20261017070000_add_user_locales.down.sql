@@ -0,0 +1,5 @@
+alter table user_summaries drop column timezone;
+alter table user_summaries drop column locale;
+
+alter table users drop column timezone;
+alter table users drop column locale;
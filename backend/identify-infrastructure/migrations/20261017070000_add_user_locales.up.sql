@@ -0,0 +1,7 @@
+-- BCP 47 language tags, e.g. `pt-BR`, and IANA time zones, e.g. `Europe/Berlin`, emails to
+-- users are localized with. Users without them get emails in English with times in UTC.
+alter table users add column locale text;
+alter table users add column timezone text;
+
+alter table user_summaries add column locale text;
+alter table user_summaries add column timezone text;
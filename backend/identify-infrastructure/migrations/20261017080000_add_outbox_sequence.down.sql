@@ -0,0 +1,22 @@
+create table outbox_unsequenced (
+  id             text primary key not null,
+  aggregate_type text not null,
+  aggregate_id   text not null,
+  event_type     text not null,
+  version        integer not null,
+  occurred_at    datetime not null,
+  published_at   datetime,
+  projected_at   datetime
+);
+
+insert into outbox_unsequenced
+select
+  id, aggregate_type, aggregate_id, event_type, version, occurred_at, published_at,
+  projected_at
+from outbox;
+
+drop table outbox;
+alter table outbox_unsequenced rename to outbox;
+
+create index outbox_unpublished_idx on outbox (occurred_at) where published_at is null;
+create index outbox_unprojected_idx on outbox (occurred_at) where projected_at is null;
@@ -0,0 +1,32 @@
+-- Positions of the events in the order they were recorded, which feeds of events resume from.
+--
+-- `autoincrement` keeps positions from being reused once the newest events are pruned, so the
+-- table is rebuilt with the sequence as its rowid. Existing events are numbered in the order
+-- they occurred.
+create table outbox_sequenced (
+  sequence       integer primary key autoincrement,
+  id             text not null unique,
+  aggregate_type text not null,
+  aggregate_id   text not null,
+  event_type     text not null,
+  version        integer not null,
+  occurred_at    datetime not null,
+  published_at   datetime,
+  projected_at   datetime
+);
+
+insert into outbox_sequenced (
+  id, aggregate_type, aggregate_id, event_type, version, occurred_at, published_at,
+  projected_at
+)
+select
+  id, aggregate_type, aggregate_id, event_type, version, occurred_at, published_at,
+  projected_at
+from outbox
+order by occurred_at, rowid;
+
+drop table outbox;
+alter table outbox_sequenced rename to outbox;
+
+create index outbox_unpublished_idx on outbox (occurred_at) where published_at is null;
+create index outbox_unprojected_idx on outbox (occurred_at) where projected_at is null;
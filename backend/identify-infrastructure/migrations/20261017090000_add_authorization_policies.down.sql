@@ -0,0 +1,2 @@
+drop table authorization_policy_conditions;
+drop table authorization_policies;
@@ -0,0 +1,20 @@
+-- Authorization policies stored in the database, which are loaded when the server starts and
+-- evaluated along with the configured ones, so changes take effect on the next restart.
+-- Patterns and condition values are separated by commas, which they can't contain.
+create table authorization_policies (
+  name        text primary key not null,
+  effect      text not null,
+  subjects    text not null,
+  actions     text not null,
+  resources   text not null,
+  created_at datetime not null
+);
+
+create table authorization_policy_conditions (
+  policy_name text not null references authorization_policies (name) on delete cascade,
+  position    integer not null,
+  attribute   text not null,
+  operator    text not null,
+  "values"    text not null,
+  primary key (policy_name, position)
+);
@@ -0,0 +1,19 @@
+-- Roles group permissions, e.g. `users:read`, which are separated by commas, under a name that
+-- can be assigned to users.
+create table roles (
+  id          text primary key not null,
+  name        text not null unique,
+  description text null,
+  permissions text not null,
+  created_at datetime not null,
+  updated_at datetime not null
+);
+
+create table role_assignments (
+  role_id     text not null references roles (id) on delete cascade,
+  user_id     text not null references users (id) on delete cascade,
+  assigned_at datetime not null,
+  primary key (role_id, user_id)
+);
+
+create index role_assignments_user_id_idx on role_assignments (user_id);
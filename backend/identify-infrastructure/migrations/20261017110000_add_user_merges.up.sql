@@ -0,0 +1,11 @@
+-- Audit trail of merges of duplicate users into the ones they duplicated. The duplicates are
+-- erased, so this is what still tells who they were merged into. `merged_by` is the subject of
+-- the actor who requested the merge.
+create table user_merges (
+  user_id     text primary key not null references users (id),
+  merged_into text not null references users (id),
+  merged_by   text not null,
+  merged_at   datetime not null
+);
+
+create index user_merges_merged_into_idx on user_merges (merged_into);
@@ -0,0 +1,4 @@
+drop table email_changes;
+
+alter table user_summaries drop column email_changed_at;
+alter table users drop column email_changed_at;
@@ -0,0 +1,21 @@
+-- Users keep their IDs when they change their emails, so the IDs of the ones who did are no
+-- longer generated from their emails.
+alter table users add column email_changed_at datetime null;
+alter table user_summaries add column email_changed_at datetime null;
+
+-- Changes of emails the users requested, confirmed from the new addresses. Only the hashes of
+-- the tokens are stored. The emails are encrypted like the ones of users, but changes are
+-- short-lived, so they're left out of re-encryptions.
+create table email_changes (
+  id           text primary key not null,
+  user_id      text not null references users (id) on delete cascade,
+  email        text not null,
+  token_hash   text not null,
+  expires_at   datetime not null,
+  created_at   datetime not null,
+  confirmed_at datetime null
+);
+
+create unique index email_changes_token_hash_idx on email_changes (token_hash);
+
+create index email_changes_user_id_idx on email_changes (user_id);
@@ -0,0 +1,4 @@
+drop trigger users_email_taken;
+drop trigger email_aliases_taken;
+
+drop table email_aliases;
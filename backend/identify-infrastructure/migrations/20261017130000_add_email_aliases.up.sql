@@ -0,0 +1,34 @@
+-- Other emails users can be found by, e.g. the ones they had before changing them. An alias
+-- has the ID a user created with its email would have, so an email is an alias of a single
+-- user at most. The emails are encrypted like the ones of users when the aliases are added, and
+-- they're looked up by their IDs rather than blind indexes.
+create table email_aliases (
+  id         text primary key not null,
+  user_id    text not null references users (id) on delete cascade,
+  email      text not null,
+  kind       text not null,
+  created_at datetime not null
+);
+
+create index email_aliases_user_id_idx on email_aliases (user_id);
+
+-- Emails identify a single user, whether they were created with them, changed to them or have
+-- them as aliases. The triggers raise the errors of unique violations, so that the emails are
+-- reported as taken.
+create trigger email_aliases_taken before insert on email_aliases
+when exists (select 1 from users where id = new.id and id != new.user_id)
+begin
+  select raise(abort, 'UNIQUE constraint failed: users.email');
+end;
+
+create trigger users_email_taken before insert on users
+when exists (select 1 from email_aliases where id = new.id)
+  or exists (
+    select 1 from users
+    where
+      email_changed_at is not null
+      and (email_index = new.email_index or lower(email) = lower(new.email))
+  )
+begin
+  select raise(abort, 'UNIQUE constraint failed: users.email');
+end;
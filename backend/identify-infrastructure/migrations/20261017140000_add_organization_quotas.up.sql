@@ -0,0 +1,8 @@
+-- Quotas organizations have of their own, which replace the default maximum number of users
+-- from the configuration. A null maximum lets the organization have any number of users.
+create table organization_quotas (
+  organization_id text primary key not null references organizations (id) on delete cascade,
+  max_users       integer null,
+  created_at      datetime not null,
+  updated_at      datetime not null
+);
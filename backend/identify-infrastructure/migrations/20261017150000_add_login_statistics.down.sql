@@ -0,0 +1,7 @@
+drop index user_summaries_last_login_at_idx;
+drop index users_last_login_at_idx;
+
+alter table user_summaries drop column login_count;
+alter table user_summaries drop column last_login_at;
+alter table users drop column login_count;
+alter table users drop column last_login_at;
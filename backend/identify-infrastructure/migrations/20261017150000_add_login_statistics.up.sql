@@ -0,0 +1,11 @@
+-- Logins of users are counted in batches rather than one by one, see `RecordLogins`, and don't
+-- change the versions of the users. They don't emit events either, so the summaries are
+-- updated along with the users.
+alter table users add column last_login_at datetime null;
+alter table users add column login_count integer not null default 0;
+alter table user_summaries add column last_login_at datetime null;
+alter table user_summaries add column login_count integer not null default 0;
+
+-- Inactive users are listed by the time of their last logins.
+create index users_last_login_at_idx on users (last_login_at);
+create index user_summaries_last_login_at_idx on user_summaries (last_login_at);
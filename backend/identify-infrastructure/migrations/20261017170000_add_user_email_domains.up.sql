@@ -0,0 +1,73 @@
+-- Domains of the emails of users, kept in the clear even while the emails are encrypted, so
+-- that users can still be filtered by them, like their summaries are. The domains of the
+-- emails encrypted before are set when the users are re-encrypted.
+alter table users add column email_domain text;
+update users
+set email_domain = lower(substr(email, instr(email, '@') + 1))
+where email not like 'enc:v1:%';
+
+create index users_email_domain_idx on users (email_domain);
+
+-- Encrypted emails and names are kept out of the full-text index, where they would only match
+-- pieces of their ciphertexts. The domains of the emails are indexed along with them, so that
+-- users with encrypted emails can still be found by their domains.
+drop trigger users_search_delete;
+drop trigger users_search_update;
+drop trigger users_search_insert;
+drop table users_search;
+drop table users_search_keys;
+
+create table users_search_keys (
+  key     integer primary key,
+  user_id blob not null unique references user_summaries (id) on delete cascade
+);
+
+create virtual table users_search using fts5(
+  email,
+  first_name,
+  last_name,
+  email_domain,
+  tokenize = 'unicode61 remove_diacritics 2',
+  prefix = '2 3'
+);
+
+insert into users_search_keys (user_id) select id from user_summaries;
+insert into users_search (rowid, email, first_name, last_name, email_domain)
+select
+  k.key,
+  iif(u.email like 'enc:v1:%', '', u.email),
+  iif(u.first_name like 'enc:v1:%', '', u.first_name),
+  iif(coalesce(u.last_name, '') like 'enc:v1:%', '', coalesce(u.last_name, '')),
+  u.email_domain
+from user_summaries u join users_search_keys k on k.user_id = u.id;
+
+create trigger users_search_insert after insert on user_summaries begin
+  insert into users_search_keys (user_id) values (new.id);
+  insert into users_search (rowid, email, first_name, last_name, email_domain)
+  values (
+    last_insert_rowid(),
+    iif(new.email like 'enc:v1:%', '', new.email),
+    iif(new.first_name like 'enc:v1:%', '', new.first_name),
+    iif(coalesce(new.last_name, '') like 'enc:v1:%', '', coalesce(new.last_name, '')),
+    new.email_domain
+  );
+end;
+
+create trigger users_search_update
+after update of email, first_name, last_name, email_domain on user_summaries begin
+  delete from users_search
+  where rowid = (select key from users_search_keys where user_id = old.id);
+  insert into users_search (rowid, email, first_name, last_name, email_domain)
+  select
+    key,
+    iif(new.email like 'enc:v1:%', '', new.email),
+    iif(new.first_name like 'enc:v1:%', '', new.first_name),
+    iif(coalesce(new.last_name, '') like 'enc:v1:%', '', coalesce(new.last_name, '')),
+    new.email_domain
+  from users_search_keys where user_id = new.id;
+end;
+
+create trigger users_search_delete before delete on user_summaries begin
+  delete from users_search
+  where rowid = (select key from users_search_keys where user_id = old.id);
+end;
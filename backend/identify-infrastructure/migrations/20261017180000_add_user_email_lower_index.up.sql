@@ -0,0 +1,3 @@
+-- Users are looked up by their emails regardless of case, see `UserCriterion::ByEmail`, which
+-- would otherwise scan the whole table.
+create index users_email_lower_idx on users (lower(email));
@@ -0,0 +1,7 @@
+drop index webhook_endpoints_external_id_idx;
+
+alter table webhook_endpoints drop column external_id;
+
+drop index roles_external_id_idx;
+
+alter table roles drop column external_id;
@@ -0,0 +1,9 @@
+-- IDs assigned to roles and webhook endpoints by external tools, e.g. infrastructure as code,
+-- so that they can manage them idempotently like organizations.
+alter table roles add column external_id text null;
+
+create unique index roles_external_id_idx on roles (external_id);
+
+alter table webhook_endpoints add column external_id text null;
+
+create unique index webhook_endpoints_external_id_idx on webhook_endpoints (external_id);
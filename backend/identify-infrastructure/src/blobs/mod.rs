@@ -0,0 +1,148 @@
+//! Storage of [Blobs](Blob) outside of the database, e.g. the avatars of users.
+
+#[cfg(feature = "s3")]
+pub mod s3;
+
+use std::{
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use async_trait::async_trait;
+use eyre::{WrapErr, eyre};
+use identify_application::{ApplicationError, Blob, BlobStorage};
+use uuid::Uuid;
+
+/// Stores every blob in its own file under a root directory, at the path of its key.
+///
+/// Files start with the content type of their blob on a line of its own, followed by its bytes.
+/// Blobs are written to a temporary file that is then renamed, so that a blob being replaced is
+/// never read half-written.
+#[derive(Debug, Clone)]
+pub struct FilesystemBlobStorage {
+    root: PathBuf,
+}
+
+impl FilesystemBlobStorage {
+    /// Stores the blobs under the directory, which is created with the first blob.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        FilesystemBlobStorage { root: root.into() }
+    }
+
+    fn path(&self, key: &str) -> Result<PathBuf, ApplicationError> {
+        validate_key(key)?;
+
+        Ok(key
+            .split('/')
+            .fold(self.root.clone(), |path, segment| path.join(segment)))
+    }
+}
+
+#[async_trait]
+impl BlobStorage for FilesystemBlobStorage {
+    async fn put(
+        &self,
+        key: &str,
+        blob: &Blob,
+    ) -> Result<(), ApplicationError> {
+        let path = self.path(key)?;
+        if blob.content_type.contains(['\r', '\n']) {
+            return Err(ApplicationError::internal(eyre!(
+                "the content type of the blob {key} spans several lines"
+            )));
+        }
+
+        let mut contents =
+            Vec::with_capacity(blob.content_type.len() + 1 + blob.bytes.len());
+        contents.extend_from_slice(blob.content_type.as_bytes());
+        contents.push(b'\n');
+        contents.extend_from_slice(&blob.bytes);
+
+        write_atomically(&path, &contents)
+            .await
+            .wrap_err_with(|| format!("failed to write the blob {key}"))
+            .map_err(ApplicationError::internal)
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Blob>, ApplicationError> {
+        let path = self.path(key)?;
+        let mut contents = match tokio::fs::read(&path).await {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => {
+                return Err(ApplicationError::internal_with_message(
+                    e,
+                    format!("failed to read the blob {key}"),
+                ));
+            }
+        };
+
+        let Some(end) = contents.iter().position(|b| *b == b'\n') else {
+            return Err(ApplicationError::internal(eyre!(
+                "the blob {key} has no content type"
+            )));
+        };
+        let bytes = contents.split_off(end + 1);
+        contents.truncate(end);
+        let content_type = String::from_utf8(contents)
+            .wrap_err_with(|| {
+                format!("the blob {key} has an invalid content type")
+            })
+            .map_err(ApplicationError::internal)?;
+
+        Ok(Some(Blob {
+            content_type,
+            bytes,
+        }))
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), ApplicationError> {
+        let path = self.path(key)?;
+        match tokio::fs::remove_file(&path).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(ApplicationError::internal_with_message(
+                e,
+                format!("failed to delete the blob {key}"),
+            )),
+        }
+    }
+}
+
+async fn write_atomically(path: &Path, contents: &[u8]) -> eyre::Result<()> {
+    let parent = path
+        .parent()
+        .ok_or_else(|| eyre!("the path has no parent"))?;
+    tokio::fs::create_dir_all(parent).await?;
+
+    let temporary = parent.join(format!(".{}.tmp", Uuid::new_v4()));
+    if let Err(e) = tokio::fs::write(&temporary, contents).await {
+        let _ = tokio::fs::remove_file(&temporary).await;
+        return Err(e.into());
+    }
+    if let Err(e) = tokio::fs::rename(&temporary, path).await {
+        let _ = tokio::fs::remove_file(&temporary).await;
+        return Err(e.into());
+    }
+
+    Ok(())
+}
+
+/// Checks that the key follows the rules of [BlobStorage], so that it can't escape the root
+/// of the storage.
+fn validate_key(key: &str) -> Result<(), ApplicationError> {
+    let valid = key.split('/').all(|segment| {
+        !segment.is_empty()
+            && !segment.starts_with('.')
+            && segment.bytes().all(|b| {
+                b.is_ascii_alphanumeric() || matches!(b, b'.' | b'_' | b'-')
+            })
+    });
+    if !valid {
+        return Err(ApplicationError::internal(eyre!(
+            "'{key}' isn't a valid blob key"
+        )));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,251 @@
+//! Storage of blobs in a bucket of an S3-compatible service, e.g. AWS S3, MinIO or Cloudflare
+//! R2.
+//!
+//! Requests are signed by [aws_sigv4] with AWS Signature Version 4, with the hash of their
+//! payload in the `x-amz-content-sha256` header.
+
+use std::time::{Duration, SystemTime};
+
+use async_trait::async_trait;
+use aws_credential_types::Credentials;
+use aws_sigv4::{
+    http_request::{
+        PayloadChecksumKind, PercentEncodingMode, SignableBody,
+        SignableRequest, SigningSettings, UriPathNormalizationMode, sign,
+    },
+    sign::v4,
+};
+use eyre::{Context, eyre};
+use identify_application::{ApplicationError, Blob, BlobStorage};
+use reqwest::{
+    Client, Method, RequestBuilder, StatusCode, Url, header::CONTENT_TYPE,
+};
+
+use crate::blobs::validate_key;
+
+/// Connection options of the bucket.
+#[derive(Debug, Clone)]
+pub struct S3Settings {
+    /// URL of the service, e.g. `https://s3.eu-west-1.amazonaws.com`.
+    pub endpoint: String,
+    pub bucket: String,
+    /// Region of the bucket, e.g. `eu-west-1`. Services without regions usually accept
+    /// `us-east-1` or `auto`.
+    pub region: String,
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    /// Whether the bucket is addressed in the path of the URLs (`<endpoint>/<bucket>/<key>`)
+    /// rather than in their host (`<bucket>.<endpoint host>/<key>`), as most self-hosted
+    /// services require.
+    pub path_style: bool,
+    /// How long to wait for the service to answer.
+    pub timeout: Duration,
+}
+
+/// Stores every blob as an object of the bucket, whose key is the key of the blob.
+#[derive(Debug, Clone)]
+pub struct S3BlobStorage {
+    client: Client,
+    settings: S3Settings,
+    /// URL of the bucket, ending with `/`.
+    bucket_url: Url,
+}
+
+impl S3BlobStorage {
+    pub fn new(settings: S3Settings) -> eyre::Result<Self> {
+        let mut bucket_url = Url::parse(&settings.endpoint)
+            .wrap_err("the endpoint is not a valid URL")?;
+        if !matches!(bucket_url.scheme(), "http" | "https") {
+            return Err(eyre!("the endpoint must be an HTTP(S) URL"));
+        }
+        if settings.path_style {
+            bucket_url
+                .path_segments_mut()
+                .map_err(|()| eyre!("the endpoint can't have a path"))?
+                .pop_if_empty()
+                .push(&settings.bucket)
+                .push("");
+        } else {
+            let host = bucket_url
+                .host_str()
+                .ok_or_else(|| eyre!("the endpoint has no host"))?;
+            let host = format!("{}.{host}", settings.bucket);
+            bucket_url
+                .set_host(Some(&host))
+                .wrap_err("the bucket isn't a valid host name")?;
+            bucket_url
+                .path_segments_mut()
+                .map_err(|()| eyre!("the endpoint can't have a path"))?
+                .pop_if_empty()
+                .push("");
+        }
+
+        let client = Client::builder()
+            .timeout(settings.timeout)
+            .build()
+            .wrap_err("failed to create the HTTP client")?;
+
+        Ok(S3BlobStorage {
+            client,
+            settings,
+            bucket_url,
+        })
+    }
+
+    /// Builds a request to the object of the key, signed at the current time.
+    fn request(
+        &self,
+        method: Method,
+        key: &str,
+        payload: &[u8],
+    ) -> Result<RequestBuilder, ApplicationError> {
+        validate_key(key)?;
+        let url = self
+            .bucket_url
+            .join(key)
+            .wrap_err_with(|| format!("'{key}' isn't a valid object key"))
+            .map_err(ApplicationError::internal)?;
+
+        let headers = self
+            .sign(&method, &url, payload, SystemTime::now())
+            .map_err(ApplicationError::internal)?;
+        let request = headers.into_iter().fold(
+            self.client.request(method, url),
+            |request, (name, value)| request.header(name, value),
+        );
+
+        Ok(request)
+    }
+
+    /// Returns the headers that authenticate the request.
+    fn sign(
+        &self,
+        method: &Method,
+        url: &Url,
+        payload: &[u8],
+        now: SystemTime,
+    ) -> eyre::Result<Vec<(String, String)>> {
+        let identity = Credentials::new(
+            &self.settings.access_key_id,
+            &self.settings.secret_access_key,
+            None,
+            None,
+            "identify",
+        )
+        .into();
+        // S3 signs the paths of the keys as they are, rather than normalized and encoded again
+        // like other services do.
+        let mut settings = SigningSettings::default();
+        settings.payload_checksum_kind = PayloadChecksumKind::XAmzSha256;
+        settings.percent_encoding_mode = PercentEncodingMode::Single;
+        settings.uri_path_normalization_mode =
+            UriPathNormalizationMode::Disabled;
+        let params = v4::SigningParams::builder()
+            .identity(&identity)
+            .region(&self.settings.region)
+            .name("s3")
+            .time(now)
+            .settings(settings)
+            .build()
+            .wrap_err("invalid signing parameters")?
+            .into();
+
+        let request = SignableRequest::new(
+            method.as_str(),
+            url.as_str(),
+            std::iter::empty(),
+            SignableBody::Bytes(payload),
+        )
+        .wrap_err("the request can't be signed")?;
+        let (instructions, _) = sign(request, &params)
+            .wrap_err("failed to sign the request")?
+            .into_parts();
+
+        Ok(instructions
+            .headers()
+            .map(|(name, value)| (name.to_owned(), value.to_owned()))
+            .collect())
+    }
+}
+
+fn request_error(key: &str, e: reqwest::Error) -> ApplicationError {
+    if e.is_timeout() || e.is_connect() {
+        return ApplicationError::Unavailable;
+    }
+
+    ApplicationError::internal_with_message(
+        e,
+        format!("the request for the object {key} failed"),
+    )
+}
+
+fn status_error(key: &str, status: StatusCode) -> ApplicationError {
+    ApplicationError::internal(eyre!(
+        "the service answered the request for the object {key} with {status}"
+    ))
+}
+
+#[async_trait]
+impl BlobStorage for S3BlobStorage {
+    async fn put(
+        &self,
+        key: &str,
+        blob: &Blob,
+    ) -> Result<(), ApplicationError> {
+        let response = self
+            .request(Method::PUT, key, &blob.bytes)?
+            .header(CONTENT_TYPE, &blob.content_type)
+            .body(blob.bytes.clone())
+            .send()
+            .await
+            .map_err(|e| request_error(key, e))?;
+        if !response.status().is_success() {
+            return Err(status_error(key, response.status()));
+        }
+
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Blob>, ApplicationError> {
+        let response = self
+            .request(Method::GET, key, &[])?
+            .send()
+            .await
+            .map_err(|e| request_error(key, e))?;
+        match response.status() {
+            StatusCode::NOT_FOUND => return Ok(None),
+            status if !status.is_success() => {
+                return Err(status_error(key, status));
+            }
+            _ => {}
+        }
+
+        let content_type = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or("application/octet-stream")
+            .to_owned();
+        let bytes =
+            response.bytes().await.map_err(|e| request_error(key, e))?;
+
+        Ok(Some(Blob {
+            content_type,
+            bytes: bytes.to_vec(),
+        }))
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), ApplicationError> {
+        let response = self
+            .request(Method::DELETE, key, &[])?
+            .send()
+            .await
+            .map_err(|e| request_error(key, e))?;
+        // Deleting a missing object succeeds, but some services answer with 404.
+        match response.status() {
+            StatusCode::NOT_FOUND => Ok(()),
+            status if status.is_success() => Ok(()),
+            status => Err(status_error(key, status)),
+        }
+    }
+}
@@ -0,0 +1,151 @@
+//! Delivery of [Emails](identify_application::Email) to users.
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use eyre::Context;
+use identify_application::{ApplicationError, Email, EmailSender};
+use lettre::{
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+    message::{Mailbox, MultiPart, SinglePart},
+    transport::smtp::authentication::Credentials,
+};
+use tracing::info;
+
+/// Writes the emails to the log instead of sending them, e.g. in development environments that
+/// have no mail server.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct LogEmailSender;
+
+#[async_trait]
+impl EmailSender for LogEmailSender {
+    async fn send(&self, email: &Email) -> Result<(), ApplicationError> {
+        info!(
+            to = %email.to,
+            subject = %email.subject,
+            text = %email.text,
+            "Email not sent, as no sender is configured"
+        );
+
+        Ok(())
+    }
+}
+
+/// How the connection to the SMTP server is secured.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmtpSecurity {
+    /// TLS from the start of the connection, usually on port 465.
+    Tls,
+    /// Plain connection upgraded with `STARTTLS`, usually on port 587. Fails if the server
+    /// doesn't support it.
+    StartTls,
+    /// No encryption at all, e.g. for a relay on the same host.
+    None,
+}
+
+/// Connection options of the SMTP server.
+#[derive(Debug, Clone)]
+pub struct SmtpSettings {
+    pub host: String,
+    pub port: u16,
+    pub security: SmtpSecurity,
+    /// Username and password, if the server requires authentication.
+    pub credentials: Option<(String, String)>,
+    /// How long to wait for the server to answer.
+    pub timeout: Duration,
+    /// Sender of the emails, e.g. `Identify <no-reply@example.com>`.
+    pub from: String,
+}
+
+impl SmtpSettings {
+    /// Checks the settings that can be checked without connecting to the server.
+    pub fn validate(&self) -> eyre::Result<()> {
+        self.from
+            .parse::<Mailbox>()
+            .map(drop)
+            .wrap_err_with(|| format!("invalid sender '{}'", self.from))
+    }
+}
+
+/// Sends the emails through an SMTP server, reusing its connections.
+#[derive(Clone)]
+pub struct SmtpEmailSender {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl SmtpEmailSender {
+    /// Creates the sender. The server isn't connected to until the first email is sent.
+    pub fn new(settings: &SmtpSettings) -> eyre::Result<Self> {
+        let from = settings
+            .from
+            .parse()
+            .wrap_err_with(|| format!("invalid sender '{}'", settings.from))?;
+
+        let builder = match settings.security {
+            SmtpSecurity::Tls => {
+                AsyncSmtpTransport::<Tokio1Executor>::relay(&settings.host)
+            }
+            SmtpSecurity::StartTls => {
+                AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(
+                    &settings.host,
+                )
+            }
+            SmtpSecurity::None => {
+                Ok(AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(
+                    &settings.host,
+                ))
+            }
+        }
+        .wrap_err("failed to configure the SMTP transport")?;
+        let builder =
+            builder.port(settings.port).timeout(Some(settings.timeout));
+        let builder = match &settings.credentials {
+            Some((username, password)) => builder.credentials(
+                Credentials::new(username.clone(), password.clone()),
+            ),
+            None => builder,
+        };
+
+        Ok(SmtpEmailSender {
+            transport: builder.build(),
+            from,
+        })
+    }
+
+    fn message(&self, email: &Email) -> eyre::Result<Message> {
+        let to: Mailbox = email
+            .to
+            .parse()
+            .wrap_err_with(|| format!("invalid recipient '{}'", email.to))?;
+        let builder = Message::builder()
+            .from(self.from.clone())
+            .to(to)
+            .subject(&email.subject);
+
+        let message = match &email.html {
+            Some(html) => builder.multipart(MultiPart::alternative_plain_html(
+                email.text.clone(),
+                html.clone(),
+            )),
+            None => builder.singlepart(SinglePart::plain(email.text.clone())),
+        };
+
+        message.wrap_err("failed to build the email")
+    }
+}
+
+#[async_trait]
+impl EmailSender for SmtpEmailSender {
+    async fn send(&self, email: &Email) -> Result<(), ApplicationError> {
+        let message =
+            self.message(email).map_err(ApplicationError::internal)?;
+
+        self.transport
+            .send(message)
+            .await
+            .map(drop)
+            .wrap_err("failed to send the email")
+            .map_err(ApplicationError::internal)
+    }
+}
@@ -0,0 +1,291 @@
+//! Encryption of the personal data of users at rest, i.e. their emails and names, and of the
+//! secrets of webhook endpoints.
+//!
+//! Fields are encrypted with AES-256-GCM by the [FieldCipher] of the [FieldEncryption] handed to
+//! the repositories that store them, and stored as
+//! `enc:v1:<key ID>:<nonce and ciphertext in base64>`, so that every value names the key it was
+//! encrypted with. Keys are rotated by adding a new one to the [KeyRing], making it the current
+//! one, and re-encrypting the stored values with it, see
+//! [reencrypt_users](crate::storage::users::reencrypt::reencrypt_users). Values that aren't
+//! encrypted, e.g. the ones stored before the encryption was enabled, are read as they are.
+//! Values stored in the clear that would read as encrypted or escaped ones are escaped with
+//! `plain:`, so that no value a user submits can be mistaken for a ciphertext.
+//!
+//! Encrypted emails can't be compared in queries, so a blind index is stored next to them: an
+//! HMAC of the normalized email with a key of its own, which is the same for the same email.
+//! Blind indexes that are stale, e.g. built before emails were normalized like they are now,
+//! are rebuilt along with the re-encryption.
+//! Encrypted fields can't be sorted by meaningfully, nor searched: they're kept out of the
+//! full-text search of users. The domains of emails are stored in the clear instead, in both the
+//! users and their read model, so that users can still be filtered and searched by them, e.g.
+//! for listings or re-verification cohorts.
+
+use std::{borrow::Cow, collections::HashMap, fmt, sync::Arc};
+
+use aes_gcm::{
+    Aes256Gcm, KeyInit, Nonce,
+    aead::{Aead, AeadCore, OsRng},
+};
+use async_trait::async_trait;
+use base64::{Engine, engine::general_purpose::STANDARD};
+use eyre::{bail, eyre};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use unicode_normalization::UnicodeNormalization;
+
+/// Prefix of the encrypted values, followed by the ID of their key.
+const PREFIX: &str = "enc:v1:";
+/// Prefix of the values stored in the clear that would otherwise read as encrypted ones.
+const ESCAPE: &str = "plain:";
+/// Length of the nonces of AES-GCM, which precede the ciphertexts.
+const NONCE_LENGTH: usize = 12;
+
+/// A 256-bit key.
+#[derive(Clone)]
+pub struct EncryptionKey([u8; 32]);
+
+impl EncryptionKey {
+    /// Decodes a key from 32 bytes in base64, e.g. generated with `openssl rand -base64 32`.
+    pub fn from_base64(encoded: &str) -> eyre::Result<Self> {
+        let bytes = STANDARD
+            .decode(encoded.trim())
+            .map_err(|e| eyre!("the key isn't valid base64: {e}"))?;
+        let key = bytes.try_into().map_err(|bytes: Vec<u8>| {
+            eyre!("the key must be 32 bytes long, not {}", bytes.len())
+        })?;
+
+        Ok(EncryptionKey(key))
+    }
+}
+
+impl fmt::Debug for EncryptionKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("EncryptionKey(***)")
+    }
+}
+
+/// Keys fields are encrypted and indexed with.
+#[derive(Debug, Clone)]
+pub struct KeyRing {
+    /// ID of the key new values are encrypted with.
+    current: String,
+    /// All the keys values may have been encrypted with, by their IDs.
+    keys: HashMap<String, EncryptionKey>,
+    /// Key of the blind indexes, which can't be rotated without rebuilding them.
+    index_key: EncryptionKey,
+}
+
+impl KeyRing {
+    /// Fails if the current key is missing or an ID can't be stored along with the values.
+    pub fn new(
+        current: impl Into<String>,
+        keys: HashMap<String, EncryptionKey>,
+        index_key: EncryptionKey,
+    ) -> eyre::Result<Self> {
+        let current = current.into();
+        for id in keys.keys() {
+            if id.is_empty() || id.contains(':') {
+                bail!(
+                    "key ID '{id}' must be non-empty and must not contain ':'"
+                );
+            }
+        }
+        if !keys.contains_key(&current) {
+            bail!("the current key '{current}' isn't one of the keys");
+        }
+
+        Ok(KeyRing {
+            current,
+            keys,
+            index_key,
+        })
+    }
+}
+
+/// Source of the [KeyRing], e.g. the configuration, or a key management service that unwraps
+/// the keys when the process starts.
+#[async_trait]
+pub trait KeyProvider: Send + Sync {
+    async fn key_ring(&self) -> eyre::Result<KeyRing>;
+}
+
+/// Provides a [KeyRing] known upfront, e.g. read from the configuration.
+#[derive(Debug, Clone)]
+pub struct StaticKeyProvider(KeyRing);
+
+impl StaticKeyProvider {
+    pub fn new(ring: KeyRing) -> Self {
+        StaticKeyProvider(ring)
+    }
+}
+
+#[async_trait]
+impl KeyProvider for StaticKeyProvider {
+    async fn key_ring(&self) -> eyre::Result<KeyRing> {
+        Ok(self.0.clone())
+    }
+}
+
+/// Encrypts and decrypts fields with the keys of a [KeyRing].
+pub struct FieldCipher {
+    current: String,
+    ciphers: HashMap<String, Aes256Gcm>,
+    index_key: EncryptionKey,
+}
+
+impl FieldCipher {
+    pub fn new(ring: KeyRing) -> Self {
+        let ciphers = ring
+            .keys
+            .iter()
+            .map(|(id, key)| (id.clone(), Aes256Gcm::new(&key.0.into())))
+            .collect();
+
+        FieldCipher {
+            current: ring.current,
+            ciphers,
+            index_key: ring.index_key,
+        }
+    }
+
+    /// Encrypts the value with the current key.
+    pub fn encrypt(&self, plaintext: &str) -> String {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = self.ciphers[&self.current]
+            .encrypt(&nonce, plaintext.as_bytes())
+            .expect("values are far shorter than what AES-GCM can encrypt");
+
+        let mut payload = nonce.to_vec();
+        payload.extend_from_slice(&ciphertext);
+
+        format!("{PREFIX}{}:{}", self.current, STANDARD.encode(payload))
+    }
+
+    /// Decrypts a value with the key it was encrypted with. Values that aren't encrypted are
+    /// returned as they are, without their escape if they have one.
+    pub fn decrypt(&self, value: &str) -> eyre::Result<String> {
+        if let Some(value) = value.strip_prefix(ESCAPE) {
+            return Ok(value.to_owned());
+        }
+        let Some((key_id, payload)) = split(value) else {
+            return Ok(value.to_owned());
+        };
+        let cipher = self.ciphers.get(key_id).ok_or_else(|| {
+            eyre!("the value is encrypted with the unknown key '{key_id}'")
+        })?;
+        let payload = STANDARD.decode(payload).map_err(|e| {
+            eyre!("the encrypted value isn't valid base64: {e}")
+        })?;
+        if payload.len() < NONCE_LENGTH {
+            bail!("the encrypted value is too short");
+        }
+
+        let (nonce, ciphertext) = payload.split_at(NONCE_LENGTH);
+        let plaintext = cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| {
+                eyre!("the value can't be decrypted with the key '{key_id}'")
+            })?;
+
+        String::from_utf8(plaintext)
+            .map_err(|_| eyre!("the decrypted value isn't valid UTF-8"))
+    }
+
+    /// Whether the value is encrypted with the current key, so it doesn't need to be
+    /// re-encrypted.
+    pub fn is_current(&self, value: &str) -> bool {
+        split(value).is_some_and(|(key_id, _)| key_id == self.current)
+    }
+
+    /// Returns the blind index of an email, which is the same for the emails that differ only
+    /// by the case, surrounding whitespace or Unicode composition, like their
+    /// [UserIds](identify_domain::UserId).
+    pub fn blind_index(&self, email: &str) -> String {
+        let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(&self.index_key.0)
+            .expect("HMAC accepts keys of any length");
+        let email: String = email.trim().to_lowercase().nfc().collect();
+        mac.update(email.as_bytes());
+
+        STANDARD.encode(mac.finalize().into_bytes())
+    }
+}
+
+/// Splits an encrypted value into the ID of its key and its payload.
+fn split(value: &str) -> Option<(&str, &str)> {
+    value.strip_prefix(PREFIX)?.split_once(':')
+}
+
+/// Encryption of the fields of the stored entities, handed to the repositories that store them,
+/// e.g. through the [SqliteUnitOfWork](crate::storage::unit_of_work::SqliteUnitOfWork).
+///
+/// Fields are stored in the clear while it's [disabled](FieldEncryption::disabled), i.e. when no
+/// keys are configured. Clones share the same cipher.
+#[derive(Clone, Default)]
+pub struct FieldEncryption(Option<Arc<FieldCipher>>);
+
+impl FieldEncryption {
+    /// Encrypts the fields with the cipher.
+    pub fn new(cipher: FieldCipher) -> Self {
+        FieldEncryption(Some(Arc::new(cipher)))
+    }
+
+    /// Stores the fields in the clear.
+    pub const fn disabled() -> Self {
+        FieldEncryption(None)
+    }
+
+    /// Encrypts the fields with a cipher of the keys of the provider.
+    pub async fn from_provider(
+        provider: &dyn KeyProvider,
+    ) -> eyre::Result<Self> {
+        let ring = provider.key_ring().await?;
+
+        Ok(FieldEncryption::new(FieldCipher::new(ring)))
+    }
+
+    /// Returns the cipher, unless the encryption is disabled.
+    pub fn cipher(&self) -> Option<&FieldCipher> {
+        self.0.as_deref()
+    }
+
+    /// Encrypts the value, or leaves it as it is if the encryption is disabled. Values left in
+    /// the clear are escaped if they'd read as encrypted or escaped ones.
+    pub(crate) fn seal<'v>(&self, value: &'v str) -> Cow<'v, str> {
+        match self.cipher() {
+            Some(cipher) => Cow::Owned(cipher.encrypt(value)),
+            None if value.starts_with(PREFIX) || value.starts_with(ESCAPE) => {
+                Cow::Owned(format!("{ESCAPE}{value}"))
+            }
+            None => Cow::Borrowed(value),
+        }
+    }
+
+    /// Decrypts a stored value. Fails if the value is encrypted, but the encryption is
+    /// disabled.
+    pub(crate) fn open(&self, value: String) -> eyre::Result<String> {
+        match self.cipher() {
+            Some(cipher) => cipher.decrypt(&value),
+            None => match value.strip_prefix(ESCAPE) {
+                Some(value) => Ok(value.to_owned()),
+                None if split(&value).is_some() => Err(eyre!(
+                    "the value is encrypted, but no encryption keys are configured"
+                )),
+                None => Ok(value),
+            },
+        }
+    }
+
+    /// Returns the blind index of the email, unless the encryption is disabled.
+    pub(crate) fn email_index(&self, email: &str) -> Option<String> {
+        self.cipher().map(|cipher| cipher.blind_index(email))
+    }
+}
+
+impl fmt::Debug for FieldEncryption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(_) => f.write_str("FieldEncryption(enabled)"),
+            None => f.write_str("FieldEncryption(disabled)"),
+        }
+    }
+}
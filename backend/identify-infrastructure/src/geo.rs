@@ -0,0 +1,84 @@
+//! Resolution of the approximate locations of IP addresses, e.g. of the devices users log in
+//! from.
+
+use std::{fs, net::IpAddr, path::Path};
+
+use async_trait::async_trait;
+use eyre::{Context, eyre};
+use identify_application::{ApplicationError, GeoResolver};
+
+use crate::network::Network;
+
+/// Knows no locations, e.g. when no GeoIP data is configured.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NoGeoResolver;
+
+#[async_trait]
+impl GeoResolver for NoGeoResolver {
+    async fn locate(
+        &self,
+        _ip: IpAddr,
+    ) -> Result<Option<String>, ApplicationError> {
+        Ok(None)
+    }
+}
+
+/// Locates addresses with a table of networks, e.g. exported from a GeoIP database.
+///
+/// Every line of the table is a network in CIDR notation and its location, separated by a
+/// comma, e.g. `203.0.113.0/24,Paris, France`. Blank lines and lines starting with `#` are
+/// skipped. Addresses in several networks get the location of the most specific one.
+#[derive(Debug, Clone)]
+pub struct NetworkGeoResolver {
+    /// Networks as IPv6 ranges, IPv4 ones being mapped, the most specific first.
+    networks: Vec<(Network, String)>,
+}
+
+impl NetworkGeoResolver {
+    /// Parses a table, failing on the first malformed line.
+    pub fn parse(table: &str) -> eyre::Result<Self> {
+        let mut networks = table
+            .lines()
+            .enumerate()
+            .map(|(n, line)| (n + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+            .map(|(n, line)| {
+                let (cidr, location) =
+                    line.split_once(',').ok_or_else(|| {
+                        eyre!("line {n}: expected '<network>,<location>'")
+                    })?;
+                let network = Network::parse(cidr)
+                    .wrap_err_with(|| format!("line {n}"))?;
+
+                Ok((network, location.trim().to_owned()))
+            })
+            .collect::<eyre::Result<Vec<_>>>()?;
+        networks
+            .sort_by_key(|(network, _)| std::cmp::Reverse(network.prefix()));
+
+        Ok(NetworkGeoResolver { networks })
+    }
+
+    /// Reads the table from a file.
+    pub fn from_file(path: &Path) -> eyre::Result<Self> {
+        let table = fs::read_to_string(path)
+            .wrap_err_with(|| format!("failed to read {}", path.display()))?;
+
+        NetworkGeoResolver::parse(&table)
+            .wrap_err_with(|| format!("invalid table {}", path.display()))
+    }
+}
+
+#[async_trait]
+impl GeoResolver for NetworkGeoResolver {
+    async fn locate(
+        &self,
+        ip: IpAddr,
+    ) -> Result<Option<String>, ApplicationError> {
+        Ok(self
+            .networks
+            .iter()
+            .find(|(network, _)| network.contains(ip))
+            .map(|(_, location)| location.clone()))
+    }
+}
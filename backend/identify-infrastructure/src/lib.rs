@@ -1,8 +1,59 @@
+use eyre::eyre;
+use identify_application::ApplicationError;
 use thiserror::Error;
 
+pub mod blobs;
+pub mod email;
+pub mod encryption;
+pub mod geo;
+#[cfg(feature = "nats")]
+pub mod nats;
+pub mod network;
+pub mod passwords;
+#[cfg(feature = "redis")]
+pub mod redis;
+pub mod sessions;
+pub mod signing;
+pub mod sms;
 pub mod storage;
+pub mod tokens;
 
 pub type Result<T> = std::result::Result<T, InfrastructureError>;
 
 #[derive(Debug, Error)]
-pub enum InfrastructureError {}
+pub enum InfrastructureError {
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+
+    #[error("Migration error: {0}")]
+    Migration(#[from] sqlx::migrate::MigrateError),
+
+    #[error("Deadline exceeded")]
+    DeadlineExceeded,
+
+    #[error("Database is temporarily unavailable: {0}")]
+    Unavailable(sqlx::Error),
+
+    #[error("Backup error: {0}")]
+    Backup(String),
+
+    #[error("Message broker error: {0}")]
+    Broker(String),
+
+    #[error("Redis error: {0}")]
+    Redis(String),
+}
+
+impl From<InfrastructureError> for ApplicationError {
+    fn from(value: InfrastructureError) -> Self {
+        match value {
+            InfrastructureError::DeadlineExceeded => {
+                ApplicationError::DeadlineExceeded
+            }
+            InfrastructureError::Unavailable(_) => {
+                ApplicationError::Unavailable
+            }
+            e => ApplicationError::internal(eyre!(e)),
+        }
+    }
+}
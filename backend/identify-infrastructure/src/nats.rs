@@ -0,0 +1,135 @@
+//! Streaming of [UserEvents](identify_domain::UserEvent) to NATS JetStream.
+//!
+//! Events are published by the [async_nats] client, which waits for the JetStream
+//! acknowledgement of every event and reconnects on its own. Servers whose URL starts with
+//! `tls://` are connected to over TLS. The stream capturing the subjects has to be provisioned
+//! separately, e.g. with the `nats` CLI.
+//!
+//! Every event is published to `{subject_prefix}.user.{transition}` with its ID in the
+//! `Nats-Msg-Id` header, so JetStream drops the events published again within the duplicate
+//! window of the stream.
+
+use std::time::Duration;
+
+use async_nats::{
+    ConnectOptions, ServerAddr,
+    jetstream::{self, context::Publish},
+};
+use async_trait::async_trait;
+use identify_application::{ApplicationError, EventPublisher};
+use identify_domain::UserEvent;
+use serde_json::json;
+use tokio::sync::OnceCell;
+use tracing::debug;
+
+use crate::{InfrastructureError, Result};
+
+#[derive(Debug, Clone)]
+pub struct NatsConfig {
+    /// URL of the server, e.g. `nats://127.0.0.1:4222`, or `tls://...` for TLS.
+    pub url: String,
+    /// Token the connection is authenticated with, if the server requires one.
+    pub token: Option<String>,
+    /// Prefix of the subjects the events are published to, e.g. `identify.events`.
+    pub subject_prefix: String,
+    /// How long to wait for the connection to open, or for JetStream to acknowledge an event.
+    pub timeout: Duration,
+}
+
+/// Publishes events to the JetStream stream that captures their subjects.
+pub struct NatsPublisher {
+    config: NatsConfig,
+    server: ServerAddr,
+    /// Opened by the first event rather than at startup, so that the server may start after the
+    /// process. A failed attempt is retried by the next event.
+    context: OnceCell<jetstream::Context>,
+}
+
+impl NatsPublisher {
+    /// Creates the publisher, which connects when it publishes the first event.
+    pub fn new(config: NatsConfig) -> Result<Self> {
+        let invalid = |e: &dyn std::fmt::Display| {
+            broker_error(format!("invalid URL '{}': {e}", config.url))
+        };
+        let server: ServerAddr =
+            config.url.trim().parse().map_err(|e| invalid(&e))?;
+        if server.host().is_empty() {
+            return Err(invalid(&"the host is missing"));
+        }
+
+        Ok(NatsPublisher {
+            config,
+            server,
+            context: OnceCell::new(),
+        })
+    }
+
+    async fn context(&self) -> Result<&jetstream::Context> {
+        self.context
+            .get_or_try_init(|| async {
+                let options = match &self.config.token {
+                    Some(token) => ConnectOptions::with_token(token.clone()),
+                    None => ConnectOptions::new(),
+                };
+                let client = options
+                    .name("identify")
+                    .connection_timeout(self.config.timeout)
+                    .request_timeout(Some(self.config.timeout))
+                    .connect(self.server.clone())
+                    .await
+                    .map_err(broker_error)?;
+
+                let mut context = jetstream::new(client);
+                context.set_timeout(self.config.timeout);
+
+                Ok(context)
+            })
+            .await
+    }
+}
+
+#[async_trait]
+impl EventPublisher for NatsPublisher {
+    async fn publish(
+        &self,
+        event: &UserEvent,
+    ) -> std::result::Result<(), ApplicationError> {
+        let subject = format!(
+            "{}.user.{}",
+            self.config.subject_prefix,
+            event.transition()
+        );
+        let payload = json!({
+            "id": event.id().to_string(),
+            "type": format!("user.{}", event.transition()),
+            "occurred_at": event.occurred_at().to_rfc3339(),
+            "data": {
+                "user_id": event.user_id().to_string(),
+                "version": event.version(),
+            },
+        })
+        .to_string();
+
+        let acknowledgement = self
+            .context()
+            .await?
+            .send_publish(
+                subject.clone(),
+                Publish::build()
+                    .payload(payload.into())
+                    .message_id(event.id().to_string()),
+            )
+            .await
+            .map_err(broker_error)?;
+        // Fails if no stream captures the subject, or if JetStream rejects the event.
+        acknowledgement.await.map_err(broker_error)?;
+
+        debug!(event_id = %event.id(), subject, "Published user event");
+
+        Ok(())
+    }
+}
+
+fn broker_error(message: impl ToString) -> InfrastructureError {
+    InfrastructureError::Broker(message.to_string())
+}
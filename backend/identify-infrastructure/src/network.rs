@@ -0,0 +1,107 @@
+//! Networks of IP addresses, e.g. of the table of locations or of trusted proxies.
+
+use std::{
+    net::{IpAddr, Ipv6Addr},
+    sync::LazyLock,
+};
+
+use eyre::{bail, eyre};
+
+/// Networks that aren't reachable from the internet, or not meant to be: loopback, private,
+/// link-local, shared, documentation, multicast and reserved ones, of both families.
+const NON_PUBLIC_NETWORKS: [&str; 20] = [
+    "0.0.0.0/8",
+    "10.0.0.0/8",
+    "100.64.0.0/10",
+    "127.0.0.0/8",
+    "169.254.0.0/16",
+    "172.16.0.0/12",
+    "192.0.0.0/24",
+    "192.0.2.0/24",
+    "192.168.0.0/16",
+    "198.18.0.0/15",
+    "198.51.100.0/24",
+    "203.0.113.0/24",
+    "224.0.0.0/3",
+    "::/127",
+    "64:ff9b::/96",
+    "100::/64",
+    "2001:db8::/32",
+    "fc00::/7",
+    "fe80::/10",
+    "ff00::/8",
+];
+
+static NON_PUBLIC: LazyLock<Vec<Network>> = LazyLock::new(|| {
+    NON_PUBLIC_NETWORKS
+        .iter()
+        .map(|cidr| Network::parse(cidr).unwrap_or_else(|_| unreachable!()))
+        .collect()
+});
+
+/// A network in CIDR notation, e.g. `203.0.113.0/24`.
+///
+/// Networks are kept as IPv6 ranges, IPv4 ones being mapped, so that addresses of both families
+/// are compared alike.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Network {
+    address: u128,
+    prefix: u32,
+}
+
+impl Network {
+    pub fn parse(cidr: &str) -> eyre::Result<Self> {
+        let (address, prefix) = cidr
+            .split_once('/')
+            .ok_or_else(|| eyre!("'{cidr}' isn't in CIDR notation"))?;
+        let address: IpAddr = address
+            .trim()
+            .parse()
+            .map_err(|_| eyre!("'{address}' isn't an IP address"))?;
+        let prefix: u32 = prefix
+            .trim()
+            .parse()
+            .map_err(|_| eyre!("'{prefix}' isn't a prefix length"))?;
+
+        let (address, prefix) = match address {
+            IpAddr::V4(address) if prefix <= 32 => {
+                (address.to_ipv6_mapped(), prefix + 96)
+            }
+            IpAddr::V6(address) if prefix <= 128 => (address, prefix),
+            _ => bail!("the prefix of '{cidr}' is too long"),
+        };
+
+        Ok(Network {
+            address: u128::from(address) & mask(prefix),
+            prefix,
+        })
+    }
+
+    /// Length of the prefix of the IPv6 range, the longest being the most specific.
+    pub fn prefix(&self) -> u32 {
+        self.prefix
+    }
+
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        to_u128(ip) & mask(self.prefix) == self.address
+    }
+}
+
+fn mask(prefix: u32) -> u128 {
+    u128::MAX.checked_shl(128 - prefix).unwrap_or(0)
+}
+
+fn to_u128(ip: IpAddr) -> u128 {
+    let ip: Ipv6Addr = match ip {
+        IpAddr::V4(ip) => ip.to_ipv6_mapped(),
+        IpAddr::V6(ip) => ip,
+    };
+
+    u128::from(ip)
+}
+
+/// Whether the address is reachable from the internet, i.e. it isn't in any of the
+/// [NON_PUBLIC_NETWORKS].
+pub fn is_public(ip: IpAddr) -> bool {
+    !NON_PUBLIC.iter().any(|network| network.contains(ip))
+}
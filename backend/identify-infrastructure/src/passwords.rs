@@ -0,0 +1,54 @@
+//! Hashing of [Passwords](identify_domain::Password) with Argon2id.
+
+use argon2::{
+    Argon2, PasswordHash, PasswordVerifier,
+    password_hash::{self, PasswordHasher as _, SaltString, rand_core::OsRng},
+};
+use identify_application::{ApplicationError, PasswordHasher};
+use identify_domain::Password;
+
+/// Hashes passwords with Argon2id and the parameters recommended by OWASP (19 MiB of memory,
+/// 2 iterations and 1 degree of parallelism).
+///
+/// The parameters are stored in the hashes, so hashes made with other parameters can still be
+/// verified.
+#[derive(Default)]
+pub struct Argon2Hasher {
+    argon2: Argon2<'static>,
+}
+
+impl Argon2Hasher {
+    pub fn new() -> Self {
+        Argon2Hasher::default()
+    }
+}
+
+impl PasswordHasher for Argon2Hasher {
+    fn hash(&self, password: &Password) -> Result<String, ApplicationError> {
+        let salt = SaltString::generate(&mut OsRng);
+
+        self.argon2
+            .hash_password(password.expose().as_bytes(), &salt)
+            .map(|hash| hash.to_string())
+            .map_err(|e| hashing_error(e, "Failed to hash a password"))
+    }
+
+    fn verify(
+        &self,
+        password: &str,
+        hash: &str,
+    ) -> Result<bool, ApplicationError> {
+        let hash = PasswordHash::new(hash)
+            .map_err(|e| hashing_error(e, "Invalid password hash"))?;
+
+        match self.argon2.verify_password(password.as_bytes(), &hash) {
+            Ok(()) => Ok(true),
+            Err(password_hash::Error::Password) => Ok(false),
+            Err(e) => Err(hashing_error(e, "Failed to verify a password")),
+        }
+    }
+}
+
+fn hashing_error(e: password_hash::Error, message: &str) -> ApplicationError {
+    ApplicationError::internal(eyre::eyre!("{message}: {e}"))
+}
@@ -0,0 +1,187 @@
+//! Shared state in Redis, for deployments of several processes that can't keep it in memory.
+//!
+//! Commands are sent by the [redis] crate over a single multiplexed connection, which is opened
+//! by the first command and reopened whenever it fails. Servers whose URL starts with
+//! `rediss://` are connected to over TLS. Clusters aren't supported.
+
+pub mod rate_limits;
+pub mod sessions;
+
+use std::{sync::Arc, time::Duration};
+
+use redis::{
+    AsyncCommands, FromRedisValue, IntoConnectionInfo,
+    aio::{ConnectionManager, ConnectionManagerConfig},
+};
+use tokio::sync::OnceCell;
+
+use crate::{InfrastructureError, Result};
+
+/// How many times a connection that failed is reopened before the command fails.
+const RECONNECT_ATTEMPTS: usize = 2;
+
+#[derive(Debug, Clone)]
+pub struct RedisConfig {
+    /// URL of the server, e.g. `redis://127.0.0.1:6379/0`, or `rediss://...` for TLS.
+    pub url: String,
+    /// Password the connection is authenticated with, if the server requires one. Takes
+    /// precedence over the one of the URL.
+    pub password: Option<String>,
+    /// Prefix of all the keys, e.g. `identify`, so that several deployments can share a server.
+    pub key_prefix: String,
+    /// How long to wait for a connection to open, or for the reply to a command.
+    pub timeout: Duration,
+}
+
+/// Client of a Redis server, shared by all the tasks of the process.
+///
+/// Commands of concurrent tasks are pipelined over the same connection, so there's no pool to
+/// size. A command whose reply doesn't arrive within [RedisConfig::timeout] fails, and the
+/// connection is replaced rather than reused with a reply still in flight.
+#[derive(Clone)]
+pub struct RedisClient {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    client: redis::Client,
+    key_prefix: String,
+    timeout: Duration,
+    /// Opened by the first command rather than at startup, so that the server may start after
+    /// the process.
+    connection: OnceCell<ConnectionManager>,
+}
+
+impl RedisClient {
+    pub fn new(config: RedisConfig) -> Result<Self> {
+        let mut info =
+            config.url.trim().into_connection_info().map_err(|e| {
+                redis_error(format!("invalid URL '{}': {e}", config.url))
+            })?;
+        if config.password.is_some() {
+            info.redis.password = config.password;
+        }
+
+        Ok(RedisClient {
+            inner: Arc::new(Inner {
+                client: redis::Client::open(info).map_err(redis_error)?,
+                key_prefix: config.key_prefix,
+                timeout: config.timeout,
+                connection: OnceCell::new(),
+            }),
+        })
+    }
+
+    /// Returns the key prefixed by the [RedisConfig::key_prefix], e.g. `identify:users:<id>`.
+    pub fn key(&self, key: &str) -> String {
+        format!("{}:{key}", self.inner.key_prefix)
+    }
+
+    /// Returns the connection, opening it if no command did yet. An attempt that fails is
+    /// retried by the next command.
+    async fn connection(&self) -> Result<ConnectionManager> {
+        self.inner
+            .connection
+            .get_or_try_init(|| async {
+                // Attempts are spaced by at most the timeout, rather than the minutes the
+                // defaults of the crate grow to.
+                let config = ConnectionManagerConfig::new()
+                    .set_connection_timeout(self.inner.timeout)
+                    .set_response_timeout(self.inner.timeout)
+                    .set_number_of_retries(RECONNECT_ATTEMPTS)
+                    .set_factor(2)
+                    .set_max_delay(
+                        u64::try_from(self.inner.timeout.as_millis())
+                            .unwrap_or(u64::MAX),
+                    );
+
+                ConnectionManager::new_with_config(
+                    self.inner.client.clone(),
+                    config,
+                )
+                .await
+                .map_err(redis_error)
+            })
+            .await
+            .cloned()
+    }
+
+    pub async fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        let mut connection = self.connection().await?;
+        connection.get(key).await.map_err(redis_error)
+    }
+
+    /// Sets the value of the key, which expires after the TTL.
+    pub async fn set_ex(
+        &self,
+        key: &str,
+        value: &[u8],
+        ttl: Duration,
+    ) -> Result<()> {
+        let ttl = u64::try_from(ttl.as_millis().max(1)).unwrap_or(u64::MAX);
+        let mut connection = self.connection().await?;
+        connection
+            .pset_ex(key, value, ttl)
+            .await
+            .map_err(redis_error)
+    }
+
+    /// Deletes the keys, returning how many of them existed.
+    pub async fn del(&self, keys: &[String]) -> Result<i64> {
+        if keys.is_empty() {
+            return Ok(0);
+        }
+
+        let mut connection = self.connection().await?;
+        connection.del(keys).await.map_err(redis_error)
+    }
+
+    /// Adds the member to the set, returning whether it wasn't there yet.
+    pub async fn sadd(&self, key: &str, member: &[u8]) -> Result<bool> {
+        let mut connection = self.connection().await?;
+        connection.sadd(key, member).await.map_err(redis_error)
+    }
+
+    /// Returns the members of the set, which is empty if the key doesn't exist.
+    pub async fn smembers(&self, key: &str) -> Result<Vec<Vec<u8>>> {
+        let mut connection = self.connection().await?;
+        connection.smembers(key).await.map_err(redis_error)
+    }
+
+    /// Sets the time to live of the key, returning whether it exists.
+    pub async fn pexpire(&self, key: &str, ttl: Duration) -> Result<bool> {
+        let ttl = i64::try_from(ttl.as_millis().max(1)).unwrap_or(i64::MAX);
+        let mut connection = self.connection().await?;
+        connection.pexpire(key, ttl).await.map_err(redis_error)
+    }
+
+    /// Returns the time to live of the key, or `None` if it doesn't exist or never expires.
+    pub async fn pttl(&self, key: &str) -> Result<Option<Duration>> {
+        let mut connection = self.connection().await?;
+        let ttl: i64 = connection.pttl(key).await.map_err(redis_error)?;
+
+        Ok(u64::try_from(ttl).ok().map(Duration::from_millis))
+    }
+
+    /// Runs a Lua script atomically, converting its reply to `T`.
+    pub async fn eval<T: FromRedisValue>(
+        &self,
+        script: &str,
+        keys: &[&str],
+        arguments: &[&str],
+    ) -> Result<T> {
+        let mut connection = self.connection().await?;
+        redis::cmd("EVAL")
+            .arg(script)
+            .arg(keys.len())
+            .arg(keys)
+            .arg(arguments)
+            .query_async(&mut connection)
+            .await
+            .map_err(redis_error)
+    }
+}
+
+fn redis_error(message: impl ToString) -> InfrastructureError {
+    InfrastructureError::Redis(message.to_string())
+}
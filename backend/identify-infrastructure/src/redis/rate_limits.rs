@@ -0,0 +1,75 @@
+//! Token buckets of the rate limiter, shared by all the processes that use the Redis server.
+
+use crate::{
+    InfrastructureError, Result,
+    redis::RedisClient,
+    storage::rate_limits::{BucketParams, TakenToken},
+};
+
+/// Refills the bucket in `KEYS[1]` and takes a token from it, see [take_token].
+///
+/// Arguments are the capacity, the refill rate per second, the current Unix timestamp in
+/// seconds and the time to live of the bucket in milliseconds. Returns whether the token was
+/// taken, and the tokens left as a string, since Lua numbers are truncated to integers in
+/// replies.
+const TAKE_TOKEN_SCRIPT: &str = r#"
+local capacity = tonumber(ARGV[1])
+local refill_per_second = tonumber(ARGV[2])
+local now = tonumber(ARGV[3])
+
+local bucket = redis.call('HMGET', KEYS[1], 'tokens', 'updated_at')
+local tokens = tonumber(bucket[1]) or capacity
+local updated_at = tonumber(bucket[2]) or now
+
+tokens = math.min(capacity, tokens + math.max(now - updated_at, 0) * refill_per_second)
+local allowed = 0
+if tokens >= 1 then
+    tokens = tokens - 1
+    allowed = 1
+end
+
+redis.call('HSET', KEYS[1], 'tokens', tostring(tokens), 'updated_at', tostring(math.max(now, updated_at)))
+redis.call('PEXPIRE', KEYS[1], ARGV[4])
+
+return {allowed, tostring(tokens)}
+"#;
+
+/// Takes a token from the bucket, refilling it for the time since it was last updated first.
+///
+/// `now` is a Unix timestamp in seconds. A bucket that doesn't exist yet starts full. The
+/// update runs as a script, so concurrent callers can't take the same token. Buckets expire
+/// once they would be full again, which is no different from missing ones, so they don't have
+/// to be deleted.
+pub async fn take_token(
+    client: &RedisClient,
+    key: &str,
+    params: BucketParams,
+    now: f64,
+) -> Result<TakenToken> {
+    let key = client.key(&format!("rate_limits:{key}"));
+    let ttl_ms =
+        (params.capacity / params.refill_per_second * 1000.0).ceil() as u64 + 1;
+
+    let (allowed, tokens): (i64, String) = client
+        .eval(
+            TAKE_TOKEN_SCRIPT,
+            &[&key],
+            &[
+                &params.capacity.to_string(),
+                &params.refill_per_second.to_string(),
+                &now.to_string(),
+                &ttl_ms.to_string(),
+            ],
+        )
+        .await?;
+    let tokens = tokens.parse().map_err(|_| {
+        InfrastructureError::Redis(format!(
+            "invalid count of tokens '{tokens}'"
+        ))
+    })?;
+
+    Ok(TakenToken {
+        allowed: allowed == 1,
+        tokens,
+    })
+}
@@ -0,0 +1,166 @@
+//! Sessions shared by all the processes that use the Redis server.
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use identify_application::{ApplicationError, Session, SessionStore};
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+use crate::redis::RedisClient;
+
+/// Keeps the sessions in Redis, where they expire on their own.
+///
+/// Every session is stored under the hash of its token, and the hashes of the sessions of a
+/// device, and of a user, are kept in sets, so that they can be revoked or reassigned together.
+/// The sets live as long as the latest of their sessions.
+#[derive(Clone)]
+pub struct RedisSessionStore {
+    client: RedisClient,
+}
+
+impl RedisSessionStore {
+    pub fn new(client: RedisClient) -> Self {
+        RedisSessionStore { client }
+    }
+
+    fn session_key(&self, token_hash: &str) -> String {
+        self.client.key(&format!("sessions:{token_hash}"))
+    }
+
+    fn device_key(&self, device_id: Uuid) -> String {
+        self.client.key(&format!("sessions:devices:{device_id}"))
+    }
+
+    fn user_key(&self, user_id: Uuid) -> String {
+        self.client.key(&format!("sessions:users:{user_id}"))
+    }
+
+    /// Adds the token hash to the set, which lives at least as long as the session.
+    async fn index(
+        &self,
+        key: &str,
+        token_hash: &str,
+        ttl: Duration,
+    ) -> Result<(), ApplicationError> {
+        self.client.sadd(key, token_hash.as_bytes()).await?;
+        // Sessions moved from another user may expire before the ones of the set.
+        if self
+            .client
+            .pttl(key)
+            .await?
+            .is_none_or(|current| current < ttl)
+        {
+            self.client.pexpire(key, ttl).await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl SessionStore for RedisSessionStore {
+    async fn put(
+        &self,
+        token_hash: &str,
+        session: &Session,
+    ) -> Result<(), ApplicationError> {
+        let ttl = (session.expires_at - Utc::now())
+            .to_std()
+            .unwrap_or(Duration::ZERO);
+
+        self.client
+            .set_ex(&self.session_key(token_hash), &encode(session), ttl)
+            .await?;
+        self.index(&self.device_key(session.device_id), token_hash, ttl)
+            .await?;
+        self.index(&self.user_key(session.user_id), token_hash, ttl)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn get(
+        &self,
+        token_hash: &str,
+    ) -> Result<Option<Session>, ApplicationError> {
+        let value = self.client.get(&self.session_key(token_hash)).await?;
+
+        Ok(value
+            .and_then(|value| decode(&value))
+            .filter(|session| session.expires_at > Utc::now()))
+    }
+
+    async fn revoke_device(
+        &self,
+        device_id: Uuid,
+    ) -> Result<u64, ApplicationError> {
+        let device_key = self.device_key(device_id);
+        let keys = self
+            .client
+            .smembers(&device_key)
+            .await?
+            .into_iter()
+            .map(|token_hash| {
+                self.session_key(&String::from_utf8_lossy(&token_hash))
+            })
+            .collect::<Vec<_>>();
+
+        // The set may list sessions that already expired, which aren't counted.
+        let revoked = self.client.del(&keys).await?;
+        self.client.del(&[device_key]).await?;
+
+        Ok(revoked as u64)
+    }
+
+    async fn reassign(
+        &self,
+        from_user_id: Uuid,
+        to_user_id: Uuid,
+    ) -> Result<u64, ApplicationError> {
+        let user_key = self.user_key(from_user_id);
+        let mut reassigned = 0;
+        for token_hash in self.client.smembers(&user_key).await? {
+            let token_hash = String::from_utf8_lossy(&token_hash);
+            // The set may list sessions that already expired or were revoked, which are left
+            // out.
+            let Some(mut session) = self.get(&token_hash).await? else {
+                continue;
+            };
+
+            session.user_id = to_user_id;
+            self.put(&token_hash, &session).await?;
+            reassigned += 1;
+        }
+        self.client.del(&[user_key]).await?;
+
+        Ok(reassigned)
+    }
+}
+
+/// Sessions are stored as JSON objects.
+fn encode(session: &Session) -> Vec<u8> {
+    json!({
+        "user_id": session.user_id,
+        "device_id": session.device_id,
+        "issued_at": session.issued_at,
+        "expires_at": session.expires_at,
+    })
+    .to_string()
+    .into_bytes()
+}
+
+/// Decodes a stored session, or returns `None` if it's invalid, e.g. because it was stored by
+/// another version of the server.
+fn decode(value: &[u8]) -> Option<Session> {
+    let value: Value = serde_json::from_slice(value).ok()?;
+    let field = |name: &str| value.get(name)?.as_str();
+
+    Some(Session {
+        user_id: field("user_id")?.parse().ok()?,
+        device_id: field("device_id")?.parse().ok()?,
+        issued_at: field("issued_at")?.parse::<DateTime<Utc>>().ok()?,
+        expires_at: field("expires_at")?.parse::<DateTime<Utc>>().ok()?,
+    })
+}
@@ -0,0 +1,86 @@
+//! Sessions kept in the memory of the process, see [MemorySessionStore]. With the `redis`
+//! feature, they can be kept in Redis instead, see
+//! [RedisSessionStore](crate::redis::sessions::RedisSessionStore).
+
+use std::{
+    collections::HashMap,
+    sync::{Mutex, MutexGuard, PoisonError},
+};
+
+use async_trait::async_trait;
+use chrono::Utc;
+use identify_application::{ApplicationError, Session, SessionStore};
+use uuid::Uuid;
+
+/// Keeps the sessions in the memory of the process, so they're lost when it exits and aren't
+/// shared with the other processes of a deployment.
+///
+/// Expired sessions are dropped whenever a session is stored.
+#[derive(Debug, Default)]
+pub struct MemorySessionStore {
+    sessions: Mutex<HashMap<String, Session>>,
+}
+
+impl MemorySessionStore {
+    pub fn new() -> Self {
+        MemorySessionStore::default()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, Session>> {
+        self.sessions.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[async_trait]
+impl SessionStore for MemorySessionStore {
+    async fn put(
+        &self,
+        token_hash: &str,
+        session: &Session,
+    ) -> Result<(), ApplicationError> {
+        let now = Utc::now();
+        let mut sessions = self.lock();
+        sessions.retain(|_, session| session.expires_at > now);
+        sessions.insert(token_hash.to_owned(), session.clone());
+
+        Ok(())
+    }
+
+    async fn get(
+        &self,
+        token_hash: &str,
+    ) -> Result<Option<Session>, ApplicationError> {
+        Ok(self
+            .lock()
+            .get(token_hash)
+            .filter(|session| session.expires_at > Utc::now())
+            .cloned())
+    }
+
+    async fn revoke_device(
+        &self,
+        device_id: Uuid,
+    ) -> Result<u64, ApplicationError> {
+        let mut sessions = self.lock();
+        let before = sessions.len();
+        sessions.retain(|_, session| session.device_id != device_id);
+
+        Ok((before - sessions.len()) as u64)
+    }
+
+    async fn reassign(
+        &self,
+        from_user_id: Uuid,
+        to_user_id: Uuid,
+    ) -> Result<u64, ApplicationError> {
+        let mut reassigned = 0;
+        for session in self.lock().values_mut() {
+            if session.user_id == from_user_id {
+                session.user_id = to_user_id;
+                reassigned += 1;
+            }
+        }
+
+        Ok(reassigned)
+    }
+}
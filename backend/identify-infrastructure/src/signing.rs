@@ -0,0 +1,32 @@
+//! Generation of the [SigningKeys](identify_domain::SigningKey) tokens are signed with.
+
+use ed25519_dalek::SigningKey;
+use identify_application::{ApplicationError, SigningKeyGenerator};
+use identify_domain::NewSigningKeyAttrs;
+use rand_core::OsRng;
+
+/// JWS algorithm of the Ed25519 keys.
+pub const ED25519_ALGORITHM: &str = "EdDSA";
+
+/// Generates Ed25519 key pairs, whose public keys are 32 bytes long and whose private keys are
+/// the 32-byte seeds they're derived from.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Ed25519KeyGenerator;
+
+impl Ed25519KeyGenerator {
+    pub fn new() -> Self {
+        Ed25519KeyGenerator
+    }
+}
+
+impl SigningKeyGenerator for Ed25519KeyGenerator {
+    fn generate(&self) -> Result<NewSigningKeyAttrs, ApplicationError> {
+        let key = SigningKey::generate(&mut OsRng);
+
+        Ok(NewSigningKeyAttrs {
+            algorithm: ED25519_ALGORITHM.to_owned(),
+            public_key: key.verifying_key().to_bytes().to_vec(),
+            private_key: key.to_bytes().to_vec(),
+        })
+    }
+}
@@ -0,0 +1,23 @@
+//! Delivery of [Sms](identify_application::Sms) to users.
+
+use async_trait::async_trait;
+use identify_application::{ApplicationError, Sms, SmsSender};
+use tracing::info;
+
+/// Writes the messages to the log instead of sending them, e.g. in development environments or
+/// in deployments that don't text their users yet.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct LogSmsSender;
+
+#[async_trait]
+impl SmsSender for LogSmsSender {
+    async fn send(&self, sms: &Sms) -> Result<(), ApplicationError> {
+        info!(
+            to = %sms.to,
+            text = %sms.text,
+            "SMS not sent, as no sender is configured"
+        );
+
+        Ok(())
+    }
+}
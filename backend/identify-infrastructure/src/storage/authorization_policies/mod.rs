@@ -0,0 +1,164 @@
+mod row;
+
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use identify_application::{ApplicationError, authorization_policy_contracts};
+use identify_domain::AuthorizationPolicy;
+use sqlx::SqliteConnection;
+
+use crate::storage::{
+    authorization_policies::row::{AuthorizationPolicyRow, PolicyConditionRow},
+    query_error,
+    timing::TimedExt,
+};
+
+/// Stores the authorization policies that are loaded along with the configured ones when the
+/// server starts.
+pub struct AuthorizationPoliciesRepository<'a> {
+    conn: &'a mut SqliteConnection,
+}
+
+impl AuthorizationPoliciesRepository<'_> {
+    pub fn new<'a>(
+        conn: &'a mut SqliteConnection,
+    ) -> AuthorizationPoliciesRepository<'a> {
+        AuthorizationPoliciesRepository { conn }
+    }
+}
+
+#[async_trait]
+impl<'a> authorization_policy_contracts::ListAll
+    for AuthorizationPoliciesRepository<'a>
+{
+    async fn list_all(
+        &mut self,
+    ) -> Result<Vec<AuthorizationPolicy>, ApplicationError> {
+        let rows = sqlx::query_as::<_, AuthorizationPolicyRow>(
+            r#"
+                select
+                    name,
+                    effect,
+                    subjects,
+                    actions,
+                    resources,
+                    created_at
+                from
+                    authorization_policies
+                order by
+                    name
+            "#,
+        )
+        .fetch_all(&mut *self.conn)
+        .timed("authorization_policies.list_all")
+        .await
+        .map_err(query_error)?;
+        let condition_rows = sqlx::query_as::<_, PolicyConditionRow>(
+            r#"
+                select
+                    policy_name,
+                    position,
+                    attribute,
+                    operator,
+                    "values"
+                from
+                    authorization_policy_conditions
+                order by
+                    policy_name,
+                    position
+            "#,
+        )
+        .fetch_all(&mut *self.conn)
+        .timed("authorization_policies.list_all_conditions")
+        .await
+        .map_err(query_error)?;
+
+        let mut conditions: HashMap<_, Vec<_>> = HashMap::new();
+        for row in condition_rows {
+            conditions
+                .entry(row.policy_name.clone())
+                .or_default()
+                .push(row);
+        }
+        rows.into_iter()
+            .map(|row| {
+                let conditions =
+                    conditions.remove(&row.name).unwrap_or_default();
+                Ok(row.try_into_policy(conditions)?)
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl<'a> authorization_policy_contracts::Insert
+    for AuthorizationPoliciesRepository<'a>
+{
+    async fn insert(
+        &mut self,
+        entity: &AuthorizationPolicy,
+    ) -> Result<(), ApplicationError> {
+        let row: AuthorizationPolicyRow = entity.into();
+
+        sqlx::query(
+            r#"
+                insert into authorization_policies (
+                    name,
+                    effect,
+                    subjects,
+                    actions,
+                    resources,
+                    created_at
+                ) values (
+                    (?),
+                    (?),
+                    (?),
+                    (?),
+                    (?),
+                    (?)
+                )
+            "#,
+        )
+        .bind(row.name)
+        .bind(row.effect)
+        .bind(row.subjects)
+        .bind(row.actions)
+        .bind(row.resources)
+        .bind(row.created_at)
+        .execute(&mut *self.conn)
+        .timed("authorization_policies.insert")
+        .await
+        .map_err(query_error)?;
+
+        for condition in PolicyConditionRow::all_of(entity) {
+            sqlx::query(
+                r#"
+                    insert into authorization_policy_conditions (
+                        policy_name,
+                        position,
+                        attribute,
+                        operator,
+                        "values"
+                    ) values (
+                        (?),
+                        (?),
+                        (?),
+                        (?),
+                        (?)
+                    )
+                "#,
+            )
+            .bind(condition.policy_name)
+            .bind(condition.position)
+            .bind(condition.attribute)
+            .bind(condition.operator)
+            .bind(condition.values)
+            .execute(&mut *self.conn)
+            .timed("authorization_policies.insert_condition")
+            .await
+            .map_err(query_error)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,96 @@
+use chrono::{DateTime, Utc};
+use identify_domain::{AuthorizationPolicy, DomainError, PolicyCondition};
+use sqlx::FromRow;
+
+/// Separates the patterns of a policy, and the values of its conditions.
+const LIST_SEPARATOR: char = ',';
+
+#[derive(FromRow)]
+pub struct AuthorizationPolicyRow {
+    pub name: String,
+    pub effect: String,
+    pub subjects: String,
+    pub actions: String,
+    pub resources: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(FromRow)]
+pub struct PolicyConditionRow {
+    pub policy_name: String,
+    pub position: u32,
+    pub attribute: String,
+    pub operator: String,
+    pub values: String,
+}
+
+impl From<&AuthorizationPolicy> for AuthorizationPolicyRow {
+    fn from(value: &AuthorizationPolicy) -> Self {
+        AuthorizationPolicyRow {
+            name: value.name().to_owned(),
+            effect: value.effect().to_string(),
+            subjects: join(value.subjects()),
+            actions: join(value.actions()),
+            resources: join(value.resources()),
+            created_at: Utc::now(),
+        }
+    }
+}
+
+impl PolicyConditionRow {
+    /// Returns the rows of the conditions of the policy, numbered in their order.
+    pub fn all_of(policy: &AuthorizationPolicy) -> Vec<Self> {
+        policy
+            .conditions()
+            .iter()
+            .zip(0..)
+            .map(|(condition, position)| PolicyConditionRow {
+                policy_name: policy.name().to_owned(),
+                position,
+                attribute: condition.attribute.clone(),
+                operator: condition.operator.to_string(),
+                values: join(&condition.values),
+            })
+            .collect()
+    }
+}
+
+impl AuthorizationPolicyRow {
+    /// Loads the policy with its conditions, whose rows must be in their order.
+    pub fn try_into_policy(
+        self,
+        conditions: Vec<PolicyConditionRow>,
+    ) -> Result<AuthorizationPolicy, DomainError> {
+        let conditions = conditions
+            .into_iter()
+            .map(|row| {
+                Ok(PolicyCondition {
+                    attribute: row.attribute,
+                    operator: row.operator.parse()?,
+                    values: split(&row.values),
+                })
+            })
+            .collect::<Result<_, DomainError>>()?;
+
+        AuthorizationPolicy::new(
+            self.name,
+            self.effect.parse()?,
+            split(&self.subjects),
+            split(&self.actions),
+            split(&self.resources),
+            conditions,
+        )
+    }
+}
+
+fn join(values: &[String]) -> String {
+    values.join(&LIST_SEPARATOR.to_string())
+}
+
+fn split(values: &str) -> Vec<String> {
+    values
+        .split(LIST_SEPARATOR)
+        .filter(|value| !value.is_empty())
+        .map(str::to_owned)
+        .collect()
+}
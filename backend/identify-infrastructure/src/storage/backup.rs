@@ -0,0 +1,207 @@
+//! Backups of the database, and restores from them.
+//!
+//! Backups are taken with `VACUUM INTO`, which copies a consistent snapshot of the database
+//! into a new file while the service keeps reading and writing it. The copy is compacted and
+//! doesn't need the write-ahead log, so it's a single self-contained file.
+//!
+//! Restores replace the database file, so they must only run while the service is stopped.
+
+use std::{
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use sqlx::{
+    ConnectOptions as _, SqliteConnection, SqlitePool,
+    sqlite::SqliteConnectOptions,
+};
+use tracing::info;
+
+use crate::{
+    InfrastructureError, Result,
+    storage::{connection::MIGRATOR, database_error, timing::TimedExt},
+};
+
+/// Copies the database behind the pool into a new file at `path`. Returns the size of the
+/// backup in bytes.
+///
+/// Fails if the file already exists, so that a backup never overwrites another one.
+pub async fn backup(pool: &SqlitePool, path: &Path) -> Result<u64> {
+    let target = path.to_str().ok_or_else(|| {
+        InfrastructureError::Backup(format!(
+            "{} isn't a valid UTF-8 path",
+            path.display()
+        ))
+    })?;
+    if tokio::fs::try_exists(path).await.map_err(sqlx::Error::Io)? {
+        return Err(InfrastructureError::Backup(format!(
+            "{} already exists",
+            path.display()
+        )));
+    }
+
+    sqlx::query("vacuum into ?")
+        .bind(file_uri(target))
+        .execute(pool)
+        .timed("backup.vacuum_into")
+        .await
+        .map_err(database_error)?;
+
+    let size = tokio::fs::metadata(path)
+        .await
+        .map_err(sqlx::Error::Io)?
+        .len();
+    info!(path = %path.display(), size, "Backed up the database");
+
+    Ok(size)
+}
+
+/// Checks that the file is a sound backup of the database: SQLite finds no corruption and no
+/// broken foreign keys in it, and it wasn't migrated further than this binary knows.
+///
+/// Returns the problems that were found, which are empty if the backup can be restored.
+pub async fn check(path: &Path) -> Result<Vec<String>> {
+    let mut conn = SqliteConnectOptions::new()
+        .filename(path)
+        .read_only(true)
+        .connect()
+        .await?;
+
+    let mut problems: Vec<String> =
+        sqlx::query_scalar("pragma integrity_check")
+            .fetch_all(&mut conn)
+            .timed("backup.integrity_check")
+            .await
+            .map_err(database_error)?;
+    problems.retain(|problem| problem != "ok");
+
+    let broken_references: Vec<String> = sqlx::query_scalar(
+        r#"select distinct "table" from pragma_foreign_key_check"#,
+    )
+    .fetch_all(&mut conn)
+    .timed("backup.foreign_key_check")
+    .await
+    .map_err(database_error)?;
+    problems.extend(
+        broken_references
+            .into_iter()
+            .map(|table| format!("{table} has broken foreign keys")),
+    );
+
+    problems.extend(check_migrations(&mut conn).await?);
+
+    Ok(problems)
+}
+
+async fn check_migrations(
+    conn: &mut SqliteConnection,
+) -> Result<Option<String>> {
+    let migrated: bool = sqlx::query_scalar(
+        "select exists (select 1 from sqlite_schema where type = 'table' and name = '_sqlx_migrations')",
+    )
+    .fetch_one(&mut *conn)
+    .timed("backup.migrated")
+    .await
+    .map_err(database_error)?;
+    if !migrated {
+        return Ok(Some("it was never migrated".to_owned()));
+    }
+
+    let version: Option<i64> = sqlx::query_scalar(
+        "select max(version) from _sqlx_migrations where success",
+    )
+    .fetch_one(&mut *conn)
+    .timed("backup.last_migration")
+    .await
+    .map_err(database_error)?;
+    let known = MIGRATOR.iter().map(|migration| migration.version).max();
+
+    Ok(match (version, known) {
+        (Some(version), Some(known)) if version > known => Some(format!(
+            "it was migrated to version {version}, which is newer than this binary"
+        )),
+        _ => None,
+    })
+}
+
+/// Replaces the database behind the pool with the backup at `backup`, once it passed the
+/// [check]. The pool is closed, since its connections would keep using the replaced file.
+///
+/// The backup is copied next to the database before it replaces it, so the database is left
+/// as it was if the copy fails. Migrations older than this binary are applied when the service
+/// starts again.
+pub async fn restore(pool: &SqlitePool, backup: &Path) -> Result<()> {
+    let problems = check(backup).await?;
+    if !problems.is_empty() {
+        return Err(InfrastructureError::Backup(format!(
+            "{} can't be restored: {}",
+            backup.display(),
+            problems.join("; ")
+        )));
+    }
+
+    let file: String = sqlx::query_scalar(
+        "select file from pragma_database_list where name = 'main'",
+    )
+    .fetch_one(pool)
+    .timed("backup.database_file")
+    .await
+    .map_err(database_error)?;
+    if file.is_empty() {
+        return Err(InfrastructureError::Backup(
+            "in-memory databases can't be restored".to_owned(),
+        ));
+    }
+    let target = PathBuf::from(file);
+
+    // Checkpoints the write-ahead log once the last connection is closed.
+    pool.close().await;
+
+    let copy = sibling(&target, ".restoring");
+    tokio::fs::copy(backup, &copy)
+        .await
+        .map_err(sqlx::Error::Io)?;
+    // A log left behind would be applied to the restored database.
+    for suffix in ["-wal", "-shm"] {
+        match tokio::fs::remove_file(sibling(&target, suffix)).await {
+            Err(e) if e.kind() != ErrorKind::NotFound => {
+                let _ = tokio::fs::remove_file(&copy).await;
+                return Err(sqlx::Error::Io(e).into());
+            }
+            _ => {}
+        }
+    }
+    if let Err(e) = tokio::fs::rename(&copy, &target).await {
+        let _ = tokio::fs::remove_file(&copy).await;
+        return Err(sqlx::Error::Io(e).into());
+    }
+
+    info!(
+        backup = %backup.display(),
+        database = %target.display(),
+        "Restored the database"
+    );
+
+    Ok(())
+}
+
+/// Returns the URI of the file at the path.
+///
+/// The target of `VACUUM INTO` is opened like the database, so a plain path would be opened in
+/// memory when the database is, and the copy would be lost. The URI sets the mode explicitly.
+fn file_uri(path: &str) -> String {
+    let path = path
+        .replace('%', "%25")
+        .replace('?', "%3f")
+        .replace('#', "%23");
+
+    format!("file:{path}?mode=rwc")
+}
+
+/// Returns the path of the file named after the database file, e.g. its write-ahead log.
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(suffix);
+
+    PathBuf::from(path)
+}
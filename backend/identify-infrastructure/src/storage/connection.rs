@@ -0,0 +1,293 @@
+use std::{str::FromStr, time::Duration};
+
+use log::LevelFilter;
+use sqlx::{
+    ConnectOptions as _, SqlitePool,
+    migrate::Migrator,
+    sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions},
+};
+
+use tracing::warn;
+
+use crate::{
+    Result,
+    storage::{deadline, statement_cache::StatementCacheTracker},
+};
+
+/// SQLite result codes of errors that usually go away on their own.
+const SQLITE_BUSY: i32 = 5;
+const SQLITE_LOCKED: i32 = 6;
+const SQLITE_CANTOPEN: i32 = 14;
+
+/// Tuning options of the connection pool.
+#[derive(Debug, Clone)]
+pub struct PoolConfig {
+    /// Minimum number of connections kept open.
+    pub min_connections: u32,
+    /// Maximum number of connections the pool can open.
+    pub max_connections: u32,
+    /// How long to wait for a free connection before giving up.
+    pub acquire_timeout: Duration,
+    /// Maximum lifetime of a connection before it gets closed and reopened.
+    pub max_lifetime: Option<Duration>,
+    /// How long a connection can stay idle before it gets closed.
+    pub idle_timeout: Option<Duration>,
+    /// Check that connections are alive before handing them out, so that broken connections
+    /// are replaced instead of failing requests.
+    pub test_before_acquire: bool,
+    /// How to retry opening the pool if the database is unavailable.
+    pub reconnect: ReconnectPolicy,
+    /// Pragmas applied to every new connection.
+    pub pragmas: SqlitePragmas,
+    /// Maximum number of prepared statements cached by every connection.
+    pub statement_cache_capacity: usize,
+    /// Prepare the statements of the hottest queries as soon as a connection is opened.
+    pub warm_up_statements: bool,
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        PoolConfig {
+            min_connections: 1,
+            max_connections: 10,
+            acquire_timeout: Duration::from_secs(30),
+            max_lifetime: Some(Duration::from_secs(30 * 60)),
+            idle_timeout: Some(Duration::from_secs(10 * 60)),
+            test_before_acquire: true,
+            reconnect: ReconnectPolicy::default(),
+            pragmas: SqlitePragmas::default(),
+            statement_cache_capacity: 100,
+            warm_up_statements: true,
+        }
+    }
+}
+
+impl PoolConfig {
+    /// Returns the defaults that suit the database at the provided URL.
+    ///
+    /// In-memory databases only live as long as their connection, so they get a single
+    /// connection that is never closed and don't use WAL, which they don't support.
+    pub fn for_url(url: &str) -> Self {
+        if !is_in_memory(url) {
+            return PoolConfig::default();
+        }
+
+        PoolConfig {
+            min_connections: 1,
+            max_connections: 1,
+            max_lifetime: None,
+            idle_timeout: None,
+            pragmas: SqlitePragmas {
+                wal: false,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+}
+
+/// Checks whether the URL points to an in-memory SQLite database.
+pub fn is_in_memory(url: &str) -> bool {
+    url.contains(":memory:") || url.contains("mode=memory")
+}
+
+/// Exponential backoff used while the database is unavailable.
+#[derive(Debug, Clone)]
+pub struct ReconnectPolicy {
+    /// How many times to try to connect before giving up.
+    pub max_attempts: u32,
+    /// Delay after the first failed attempt, doubled after every next one.
+    pub initial_backoff: Duration,
+    /// Upper bound of the delay.
+    pub max_backoff: Duration,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        ReconnectPolicy {
+            max_attempts: 5,
+            initial_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(5),
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    /// Returns the delay after the provided (1-based) failed attempt.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+}
+
+/// Checks whether the error is likely to go away on its own, e.g. because the database is
+/// locked by a backup or all connections are busy.
+pub fn is_transient(e: &sqlx::Error) -> bool {
+    match e {
+        sqlx::Error::PoolTimedOut => true,
+        sqlx::Error::Io(_) => !deadline::is_exceeded_error(e),
+        sqlx::Error::Database(db_error) => db_error
+            .code()
+            .and_then(|code| code.parse::<i32>().ok())
+            // Extended result codes keep the primary code in the lowest byte.
+            .is_some_and(|code| {
+                matches!(
+                    code & 0xff,
+                    SQLITE_BUSY | SQLITE_LOCKED | SQLITE_CANTOPEN
+                )
+            }),
+        _ => false,
+    }
+}
+
+/// SQLite pragmas applied to every new connection.
+#[derive(Debug, Clone)]
+pub struct SqlitePragmas {
+    /// Use the write-ahead log instead of the rollback journal.
+    pub wal: bool,
+    /// How long to wait for a locked database before failing with `SQLITE_BUSY`.
+    pub busy_timeout: Duration,
+    /// Enforce foreign key constraints.
+    pub foreign_keys: bool,
+}
+
+impl Default for SqlitePragmas {
+    fn default() -> Self {
+        SqlitePragmas {
+            wal: true,
+            busy_timeout: Duration::from_secs(5),
+            foreign_keys: true,
+        }
+    }
+}
+
+/// Pool of read-only connections that serve the queries made outside of transactions, e.g.
+/// the lookups and listings of the API.
+///
+/// It's a separate type, so that writes can't be routed to it by mistake.
+#[derive(Debug, Clone)]
+pub struct ReadPool(SqlitePool);
+
+impl ReadPool {
+    pub fn pool(&self) -> &SqlitePool {
+        &self.0
+    }
+}
+
+/// Shares the connections of a write pool for reads, e.g. for in-memory databases.
+impl From<SqlitePool> for ReadPool {
+    fn from(pool: SqlitePool) -> Self {
+        ReadPool(pool)
+    }
+}
+
+/// Creates a new connection pool for the provided database URL.
+///
+/// If the database is unavailable, connecting is retried according to the
+/// [ReconnectPolicy]. Once the pool is open, it reopens broken connections on its own.
+pub async fn get_pool(url: &str, config: &PoolConfig) -> Result<SqlitePool> {
+    let options = SqliteConnectOptions::from_str(url)?.create_if_missing(true);
+
+    open(options, config).await
+}
+
+/// Creates the pool that serves reads from the database at the provided URL, which is either
+/// the primary database or a replica of it.
+///
+/// Its connections set the `query_only` pragma, so that a write sent to the read pool fails
+/// instead of going to a replica. In-memory databases are private to their connection, so they
+/// are read through the connections of the write pool instead.
+pub async fn get_read_pool(
+    url: &str,
+    config: &PoolConfig,
+    write_pool: &SqlitePool,
+) -> Result<ReadPool> {
+    if is_in_memory(url) {
+        return Ok(ReadPool::from(write_pool.clone()));
+    }
+
+    let options =
+        SqliteConnectOptions::from_str(url)?.pragma("query_only", "ON");
+
+    open(options, config).await.map(ReadPool)
+}
+
+async fn open(
+    options: SqliteConnectOptions,
+    config: &PoolConfig,
+) -> Result<SqlitePool> {
+    let journal_mode = if config.pragmas.wal {
+        SqliteJournalMode::Wal
+    } else {
+        SqliteJournalMode::Delete
+    };
+
+    let options = options
+        .journal_mode(journal_mode)
+        .busy_timeout(config.pragmas.busy_timeout)
+        .foreign_keys(config.pragmas.foreign_keys)
+        .statement_cache_capacity(config.statement_cache_capacity)
+        // Queries are logged by their names, and slow ones are reported against the threshold
+        // of the process, see [timed](crate::storage::timing::TimedExt::timed). Their SQL is
+        // only logged for debugging.
+        .log_statements(LevelFilter::Trace)
+        .log_slow_statements(LevelFilter::Off, Duration::ZERO);
+
+    let statement_cache = StatementCacheTracker::default();
+    let warm_up = config.warm_up_statements;
+
+    let pool_options = SqlitePoolOptions::new()
+        .min_connections(config.min_connections)
+        .max_connections(config.max_connections)
+        .acquire_timeout(config.acquire_timeout)
+        .max_lifetime(config.max_lifetime)
+        .idle_timeout(config.idle_timeout)
+        .test_before_acquire(config.test_before_acquire)
+        .after_connect({
+            let statement_cache = statement_cache.clone();
+            move |conn, _| {
+                let statement_cache = statement_cache.clone();
+                Box::pin(async move {
+                    statement_cache.on_connect(conn, warm_up).await
+                })
+            }
+        })
+        .after_release(move |conn, _| {
+            let statement_cache = statement_cache.clone();
+            Box::pin(async move {
+                statement_cache.on_release(conn).await?;
+                Ok(true)
+            })
+        });
+
+    let mut attempt = 1;
+    loop {
+        match pool_options.clone().connect_with(options.clone()).await {
+            Ok(pool) => return Ok(pool),
+            Err(e)
+                if attempt < config.reconnect.max_attempts
+                    && is_transient(&e) =>
+            {
+                let backoff = config.reconnect.backoff(attempt);
+                warn!(attempt, ?backoff, error = %e, "Database is unavailable, retrying");
+
+                tokio::time::sleep(backoff).await;
+                attempt += 1;
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+/// Migrations embedded in the binary.
+pub static MIGRATOR: Migrator = sqlx::migrate!();
+
+/// Applies all pending migrations.
+pub async fn migrate(pool: &SqlitePool) -> Result<()> {
+    MIGRATOR.run(pool).await?;
+
+    Ok(())
+}
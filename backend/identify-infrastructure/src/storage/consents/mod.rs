@@ -0,0 +1,128 @@
+mod row;
+
+use async_trait::async_trait;
+use identify_application::{ApplicationError, consent_contracts};
+use identify_domain::Consent;
+use sqlx::SqliteConnection;
+use uuid::Uuid;
+
+use crate::storage::{
+    consents::row::ConsentRow, query_error, timing::TimedExt,
+};
+
+/// Stores the versions of policies users accepted.
+pub struct ConsentsRepository<'a> {
+    conn: &'a mut SqliteConnection,
+}
+
+impl ConsentsRepository<'_> {
+    pub fn new<'a>(conn: &'a mut SqliteConnection) -> ConsentsRepository<'a> {
+        ConsentsRepository { conn }
+    }
+}
+
+#[async_trait]
+impl<'a> consent_contracts::ListByUser for ConsentsRepository<'a> {
+    async fn list_by_user(
+        &mut self,
+        user_id: Uuid,
+    ) -> Result<Vec<Consent>, ApplicationError> {
+        let rows = sqlx::query_as::<_, ConsentRow>(
+            r#"
+                select
+                    id,
+                    user_id,
+                    policy,
+                    version,
+                    accepted_at
+                from
+                    consents
+                where
+                    user_id = (?)
+                order by
+                    accepted_at desc,
+                    id
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&mut *self.conn)
+        .timed("consents.list_by_user")
+        .await
+        .map_err(query_error)?;
+
+        rows.into_iter().map(|row| Ok(row.try_into()?)).collect()
+    }
+}
+
+#[async_trait]
+impl<'a> consent_contracts::Insert for ConsentsRepository<'a> {
+    async fn insert(
+        &mut self,
+        entity: &Consent,
+    ) -> Result<(), ApplicationError> {
+        let row: ConsentRow = entity.into();
+
+        sqlx::query(
+            r#"
+                insert into consents (
+                    id,
+                    user_id,
+                    policy,
+                    version,
+                    accepted_at
+                ) values (
+                    (?),
+                    (?),
+                    (?),
+                    (?),
+                    (?)
+                )
+            "#,
+        )
+        .bind(row.id)
+        .bind(row.user_id)
+        .bind(row.policy)
+        .bind(row.version)
+        .bind(row.accepted_at)
+        .execute(&mut *self.conn)
+        .timed("consents.insert")
+        .await
+        .map(|_| ())
+        .map_err(query_error)
+    }
+}
+
+#[async_trait]
+impl<'a> consent_contracts::Reassign for ConsentsRepository<'a> {
+    async fn reassign(
+        &mut self,
+        from: Uuid,
+        to: Uuid,
+    ) -> Result<u64, ApplicationError> {
+        sqlx::query(
+            r#"
+                update consents
+                set
+                    user_id = (?)
+                where
+                    user_id = (?)
+                    and not exists (
+                        select 1
+                        from consents accepted
+                        where
+                            accepted.user_id = (?)
+                            and accepted.policy = consents.policy
+                            and accepted.version = consents.version
+                    )
+            "#,
+        )
+        .bind(to)
+        .bind(from)
+        .bind(to)
+        .execute(&mut *self.conn)
+        .timed("consents.reassign")
+        .await
+        .map(|result| result.rows_affected())
+        .map_err(query_error)
+    }
+}
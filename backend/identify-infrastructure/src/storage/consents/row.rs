@@ -0,0 +1,15 @@
+use chrono::{DateTime, Utc};
+use identify_domain::{Consent, ConsentAttrs, DomainError};
+use identify_macros::ModelRow;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+#[derive(FromRow, ModelRow)]
+#[row(model = Consent, error = DomainError)]
+pub struct ConsentRow {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub policy: String,
+    pub version: String,
+    pub accepted_at: DateTime<Utc>,
+}
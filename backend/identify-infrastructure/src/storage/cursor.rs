@@ -0,0 +1,54 @@
+//! Streams of rows read through cursors, for scans of whole tables.
+//!
+//! sqlx reads the rows of a `fetch` one by one as they're polled, but its stream borrows the
+//! query and the connection. Cursors therefore run on a task of their own, which sends the rows
+//! to a `'static` stream through a bounded channel.
+
+use futures_util::{StreamExt as _, stream, stream::BoxStream};
+use identify_application::ApplicationError;
+use sqlx::{FromRow, QueryBuilder, Sqlite, SqlitePool, sqlite::SqliteRow};
+use tokio::sync::mpsc;
+use tracing::{Instrument as _, info_span};
+
+use crate::storage::query_error;
+
+/// Rows a cursor reads ahead of its consumer.
+const BUFFER: usize = 64;
+
+/// Streams the rows the query selects, reading them on a connection of the pool named after
+/// the query, e.g. `users.scan`.
+///
+/// The connection is held until the stream ends or is dropped. The cursor reads ahead only as
+/// far as the buffer allows, so slow consumers slow the query down instead of filling the
+/// memory. The stream ends after the first error.
+pub(crate) fn fetch<R>(
+    pool: &SqlitePool,
+    mut query: QueryBuilder<'static, Sqlite>,
+    name: &'static str,
+) -> BoxStream<'static, Result<R, ApplicationError>>
+where
+    R: for<'r> FromRow<'r, SqliteRow> + Send + Unpin + 'static,
+{
+    let pool = pool.clone();
+    let (sender, receiver) = mpsc::channel(BUFFER);
+
+    tokio::spawn(
+        async move {
+            let mut rows = query.build_query_as::<R>().fetch(&pool);
+            while let Some(row) = rows.next().await {
+                let row = row.map_err(query_error);
+                let failed = row.is_err();
+                // The consumer is gone if the stream has been dropped.
+                if sender.send(row).await.is_err() || failed {
+                    break;
+                }
+            }
+        }
+        .instrument(info_span!("db.query", query = name)),
+    );
+
+    stream::unfold(receiver, |mut receiver| async move {
+        receiver.recv().await.map(|row| (row, receiver))
+    })
+    .boxed()
+}
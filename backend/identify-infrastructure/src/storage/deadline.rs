@@ -0,0 +1,51 @@
+//! Deadlines of database queries.
+//!
+//! A deadline is set for a whole task (e.g. the handling of a request) with [scope], and all
+//! queries executed within it are aborted once the deadline passes. Queries that would start
+//! after the deadline are not sent to the database at all, so a timed out request can't
+//! modify any data.
+
+use std::{fmt, io};
+
+use tokio::time::Instant;
+
+tokio::task_local! {
+    static DEADLINE: Instant;
+}
+
+/// Runs the future with the provided deadline applied to all its queries.
+pub async fn scope<F: Future>(deadline: Instant, future: F) -> F::Output {
+    DEADLINE.scope(deadline, future).await
+}
+
+/// Returns the deadline of the current task, if any.
+///
+/// Tasks spawned while handling a request don't inherit its deadline, so it has to be applied
+/// to them explicitly with [scope].
+pub fn current() -> Option<Instant> {
+    DEADLINE.try_with(|deadline| *deadline).ok()
+}
+
+/// Error returned by queries that didn't finish before the deadline.
+#[derive(Debug)]
+struct DeadlineExceeded;
+
+impl fmt::Display for DeadlineExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("query deadline exceeded")
+    }
+}
+
+impl std::error::Error for DeadlineExceeded {}
+
+pub(crate) fn exceeded_error() -> sqlx::Error {
+    sqlx::Error::Io(io::Error::new(io::ErrorKind::TimedOut, DeadlineExceeded))
+}
+
+pub(crate) fn is_exceeded_error(e: &sqlx::Error) -> bool {
+    matches!(
+        e,
+        sqlx::Error::Io(e)
+            if e.get_ref().is_some_and(|inner| inner.is::<DeadlineExceeded>())
+    )
+}
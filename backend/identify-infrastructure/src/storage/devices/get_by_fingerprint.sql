@@ -0,0 +1,16 @@
+select
+    id,
+    user_id,
+    fingerprint,
+    user_agent,
+    ip,
+    location,
+    first_seen_at,
+    last_seen_at,
+    revoked_at
+from
+    devices
+where
+    user_id = (?)
+    and fingerprint = (?)
+    and revoked_at is null
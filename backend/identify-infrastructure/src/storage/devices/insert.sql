@@ -0,0 +1,21 @@
+insert into devices (
+    id,
+    user_id,
+    fingerprint,
+    user_agent,
+    ip,
+    location,
+    first_seen_at,
+    last_seen_at,
+    revoked_at
+) values (
+    (?),
+    (?),
+    (?),
+    (?),
+    (?),
+    (?),
+    (?),
+    (?),
+    (?)
+)
@@ -0,0 +1,235 @@
+mod row;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use identify_application::{ApplicationError, device_contracts};
+use identify_domain::Device;
+use sqlx::SqliteConnection;
+use uuid::Uuid;
+
+use crate::storage::{devices::row::DeviceRow, query_error, timing::TimedExt};
+
+/// Stores the devices users logged in from.
+pub struct DevicesRepository<'a> {
+    conn: &'a mut SqliteConnection,
+}
+
+impl DevicesRepository<'_> {
+    pub fn new<'a>(conn: &'a mut SqliteConnection) -> DevicesRepository<'a> {
+        DevicesRepository { conn }
+    }
+}
+
+#[async_trait]
+impl<'a> device_contracts::Get for DevicesRepository<'a> {
+    async fn get(
+        &mut self,
+        user_id: Uuid,
+        id: Uuid,
+    ) -> Result<Device, ApplicationError> {
+        let row = sqlx::query_as::<_, DeviceRow>(
+            r#"
+                select
+                    id,
+                    user_id,
+                    fingerprint,
+                    user_agent,
+                    ip,
+                    location,
+                    first_seen_at,
+                    last_seen_at,
+                    revoked_at
+                from
+                    devices
+                where
+                    user_id = (?)
+                    and id = (?)
+            "#,
+        )
+        .bind(user_id)
+        .bind(id)
+        .fetch_optional(&mut *self.conn)
+        .timed("devices.get")
+        .await
+        .map_err(query_error)?
+        .ok_or_else(|| ApplicationError::entity_not_found("Device", id))?;
+
+        Ok(row.try_into()?)
+    }
+}
+
+#[async_trait]
+impl<'a> device_contracts::GetByFingerprint for DevicesRepository<'a> {
+    async fn get_by_fingerprint(
+        &mut self,
+        user_id: Uuid,
+        fingerprint: &str,
+    ) -> Result<Option<Device>, ApplicationError> {
+        let row = sqlx::query_as::<_, DeviceRow>(include_str!(
+            "get_by_fingerprint.sql"
+        ))
+        .bind(user_id)
+        .bind(fingerprint)
+        .fetch_optional(&mut *self.conn)
+        .timed("devices.get_by_fingerprint")
+        .await
+        .map_err(query_error)?;
+
+        Ok(row.map(TryInto::try_into).transpose()?)
+    }
+}
+
+#[async_trait]
+impl<'a> device_contracts::ListByUser for DevicesRepository<'a> {
+    async fn list_by_user(
+        &mut self,
+        user_id: Uuid,
+    ) -> Result<Vec<Device>, ApplicationError> {
+        let rows = sqlx::query_as::<_, DeviceRow>(
+            r#"
+                select
+                    id,
+                    user_id,
+                    fingerprint,
+                    user_agent,
+                    ip,
+                    location,
+                    first_seen_at,
+                    last_seen_at,
+                    revoked_at
+                from
+                    devices
+                where
+                    user_id = (?)
+                order by
+                    last_seen_at desc,
+                    id
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&mut *self.conn)
+        .timed("devices.list_by_user")
+        .await
+        .map_err(query_error)?;
+
+        rows.into_iter().map(|row| Ok(row.try_into()?)).collect()
+    }
+}
+
+#[async_trait]
+impl<'a> device_contracts::Insert for DevicesRepository<'a> {
+    async fn insert(
+        &mut self,
+        entity: &Device,
+    ) -> Result<(), ApplicationError> {
+        let row: DeviceRow = entity.into();
+
+        sqlx::query(include_str!("insert.sql"))
+            .bind(row.id)
+            .bind(row.user_id)
+            .bind(row.fingerprint)
+            .bind(row.user_agent)
+            .bind(row.ip)
+            .bind(row.location)
+            .bind(row.first_seen_at)
+            .bind(row.last_seen_at)
+            .bind(row.revoked_at)
+            .execute(&mut *self.conn)
+            .timed("devices.insert")
+            .await
+            .map(|_| ())
+            .map_err(query_error)
+    }
+}
+
+#[async_trait]
+impl<'a> device_contracts::Update for DevicesRepository<'a> {
+    async fn update(
+        &mut self,
+        entity: &Device,
+    ) -> Result<(), ApplicationError> {
+        let row: DeviceRow = entity.into();
+
+        sqlx::query(
+            r#"
+                update devices
+                set
+                    user_agent = (?),
+                    ip = (?),
+                    location = (?),
+                    last_seen_at = (?),
+                    revoked_at = (?)
+                where
+                    id = (?)
+            "#,
+        )
+        .bind(row.user_agent)
+        .bind(row.ip)
+        .bind(row.location)
+        .bind(row.last_seen_at)
+        .bind(row.revoked_at)
+        .bind(row.id)
+        .execute(&mut *self.conn)
+        .timed("devices.update")
+        .await
+        .map(|_| ())
+        .map_err(query_error)
+    }
+}
+
+#[async_trait]
+impl<'a> device_contracts::DeleteAll for DevicesRepository<'a> {
+    async fn delete_all(
+        &mut self,
+        user_id: Uuid,
+    ) -> Result<u64, ApplicationError> {
+        sqlx::query("delete from devices where user_id = (?)")
+            .bind(user_id)
+            .execute(&mut *self.conn)
+            .timed("devices.delete_all")
+            .await
+            .map(|result| result.rows_affected())
+            .map_err(query_error)
+    }
+}
+
+#[async_trait]
+impl<'a> device_contracts::Reassign for DevicesRepository<'a> {
+    async fn reassign(
+        &mut self,
+        from: Uuid,
+        to: Uuid,
+    ) -> Result<u64, ApplicationError> {
+        sqlx::query(
+            r#"
+                update devices
+                set
+                    revoked_at = (?)
+                where
+                    user_id = (?)
+                    and revoked_at is null
+                    and fingerprint in (
+                        select fingerprint
+                        from devices
+                        where user_id = (?) and revoked_at is null
+                    )
+            "#,
+        )
+        .bind(Utc::now())
+        .bind(from)
+        .bind(to)
+        .execute(&mut *self.conn)
+        .timed("devices.revoke_reassigned")
+        .await
+        .map_err(query_error)?;
+
+        sqlx::query("update devices set user_id = (?) where user_id = (?)")
+            .bind(to)
+            .bind(from)
+            .execute(&mut *self.conn)
+            .timed("devices.reassign")
+            .await
+            .map(|result| result.rows_affected())
+            .map_err(query_error)
+    }
+}
@@ -0,0 +1,19 @@
+use chrono::{DateTime, Utc};
+use identify_domain::{Device, DeviceAttrs, DomainError};
+use identify_macros::ModelRow;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+#[derive(FromRow, ModelRow)]
+#[row(model = Device, error = DomainError)]
+pub struct DeviceRow {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub fingerprint: String,
+    pub user_agent: String,
+    pub ip: String,
+    pub location: Option<String>,
+    pub first_seen_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
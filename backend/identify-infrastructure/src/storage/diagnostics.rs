@@ -0,0 +1,221 @@
+//! Statistics of the database for the operators of the service.
+//!
+//! The statistics are gathered with queries built at runtime, since they read the schema and
+//! the tables of the migrator, which the queries checked at compile time don't know about.
+
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use sqlx::{FromRow, SqliteConnection, SqlitePool};
+
+use crate::{
+    Result,
+    storage::{database_error, timing::TimedExt},
+};
+
+/// Table the migrator records the applied migrations in.
+const MIGRATIONS_TABLE: &str = "_sqlx_migrations";
+
+/// Statistics of the whole database.
+#[derive(Debug, Clone)]
+pub struct DatabaseStats {
+    pub pool: PoolStats,
+    pub storage: StorageStats,
+    /// Tables of the schema, by name.
+    pub tables: Vec<TableStats>,
+    /// The most recent migration, unless the database has never been migrated.
+    pub last_migration: Option<MigrationStats>,
+}
+
+/// Connections of a pool.
+#[derive(Debug, Clone)]
+pub struct PoolStats {
+    /// Open connections, including the idle ones.
+    pub size: u32,
+    pub idle: usize,
+    pub max_connections: u32,
+    /// How long it took to acquire the connection the statistics were gathered on.
+    pub acquire_wait: Duration,
+}
+
+/// Pages and journal of the database file.
+#[derive(Debug, Clone)]
+pub struct StorageStats {
+    pub page_size: i64,
+    pub page_count: i64,
+    /// Pages that are unused, e.g. after rows were deleted, until the database is vacuumed.
+    pub freelist_count: i64,
+    /// E.g. `wal` or `delete`.
+    pub journal_mode: String,
+    /// Size of the write-ahead log, if the database uses one and it's a file.
+    pub wal_size: Option<u64>,
+}
+
+impl StorageStats {
+    /// Size of the database file, without the write-ahead log.
+    pub fn size(&self) -> i64 {
+        self.page_size * self.page_count
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TableStats {
+    pub name: String,
+    pub rows: i64,
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct MigrationStats {
+    pub version: i64,
+    pub description: String,
+    pub installed_on: DateTime<Utc>,
+}
+
+/// Gathers the statistics of the database behind the pool.
+///
+/// Rows are counted table by table, so this reads the whole database and is only meant for
+/// occasional requests of operators.
+pub async fn stats(pool: &SqlitePool) -> Result<DatabaseStats> {
+    let started_at = Instant::now();
+    let mut conn = pool
+        .acquire()
+        .timed("diagnostics.acquire")
+        .await
+        .map_err(database_error)?;
+    let acquire_wait = started_at.elapsed();
+
+    // Taken after the connection was acquired, so it's counted as in use.
+    let pool_stats = PoolStats {
+        size: pool.size(),
+        idle: pool.num_idle(),
+        max_connections: pool.options().get_max_connections(),
+        acquire_wait,
+    };
+
+    Ok(DatabaseStats {
+        pool: pool_stats,
+        storage: storage_stats(&mut conn, pool).await?,
+        tables: table_stats(&mut conn).await?,
+        last_migration: last_migration(&mut conn).await?,
+    })
+}
+
+async fn storage_stats(
+    conn: &mut SqliteConnection,
+    pool: &SqlitePool,
+) -> Result<StorageStats> {
+    let page_size = pragma(conn, "page_size").await?;
+    let page_count = pragma(conn, "page_count").await?;
+    let freelist_count = pragma(conn, "freelist_count").await?;
+    let journal_mode: String = sqlx::query_scalar("pragma journal_mode")
+        .fetch_one(&mut *conn)
+        .timed("diagnostics.journal_mode")
+        .await
+        .map_err(database_error)?;
+
+    let filename = pool.connect_options().get_filename().to_owned();
+    let wal_size = if journal_mode.eq_ignore_ascii_case("wal") {
+        let mut wal = filename.into_os_string();
+        wal.push("-wal");
+        // The log doesn't exist until something is written to the database.
+        Some(tokio::fs::metadata(wal).await.map_or(0, |meta| meta.len()))
+    } else {
+        None
+    };
+
+    Ok(StorageStats {
+        page_size,
+        page_count,
+        freelist_count,
+        journal_mode,
+        wal_size,
+    })
+}
+
+async fn pragma(
+    conn: &mut SqliteConnection,
+    name: &'static str,
+) -> Result<i64> {
+    let value = sqlx::query_scalar(&format!("pragma {name}"))
+        .fetch_one(&mut *conn)
+        .timed("diagnostics.pragma")
+        .await
+        .map_err(database_error)?;
+
+    Ok(value)
+}
+
+async fn table_stats(conn: &mut SqliteConnection) -> Result<Vec<TableStats>> {
+    let names: Vec<String> = sqlx::query_scalar(
+        r#"
+            select
+                name
+            from
+                sqlite_schema
+            where
+                type = 'table'
+                and name not like 'sqlite_%'
+            order by
+                name
+        "#,
+    )
+    .fetch_all(&mut *conn)
+    .timed("diagnostics.tables")
+    .await
+    .map_err(database_error)?;
+
+    let mut tables = Vec::with_capacity(names.len());
+    for name in names {
+        // Names come from the schema, but are quoted in case they aren't plain identifiers.
+        let rows = sqlx::query_scalar(&format!(
+            r#"select count(*) from "{}""#,
+            name.replace('"', r#""""#)
+        ))
+        .fetch_one(&mut *conn)
+        .timed("diagnostics.count_rows")
+        .await
+        .map_err(database_error)?;
+
+        tables.push(TableStats { name, rows });
+    }
+
+    Ok(tables)
+}
+
+async fn last_migration(
+    conn: &mut SqliteConnection,
+) -> Result<Option<MigrationStats>> {
+    let migrated: bool = sqlx::query_scalar(
+        "select exists (select 1 from sqlite_schema where type = 'table' and name = (?))",
+    )
+    .bind(MIGRATIONS_TABLE)
+    .fetch_one(&mut *conn)
+    .timed("diagnostics.migrated")
+    .await
+    .map_err(database_error)?;
+    if !migrated {
+        return Ok(None);
+    }
+
+    let migration = sqlx::query_as::<_, MigrationStats>(&format!(
+        r#"
+            select
+                version,
+                description,
+                installed_on
+            from
+                {MIGRATIONS_TABLE}
+            where
+                success
+            order by
+                version desc
+            limit 1
+        "#
+    ))
+    .fetch_optional(&mut *conn)
+    .timed("diagnostics.last_migration")
+    .await
+    .map_err(database_error)?;
+
+    Ok(migration)
+}
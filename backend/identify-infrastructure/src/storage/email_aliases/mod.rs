@@ -0,0 +1,159 @@
+mod row;
+
+use async_trait::async_trait;
+use identify_application::{ApplicationError, email_alias_contracts};
+use identify_domain::EmailAlias;
+use sqlx::SqliteConnection;
+use uuid::Uuid;
+
+use crate::{
+    encryption::FieldEncryption,
+    storage::{
+        RepositoryError,
+        email_aliases::row::{EmailAliasRow, EmailAliasRowRef},
+        query_error,
+        timing::TimedExt,
+    },
+};
+
+/// Stores the other emails users can be found by.
+pub struct EmailAliasesRepository<'a> {
+    conn: &'a mut SqliteConnection,
+    encryption: &'a FieldEncryption,
+}
+
+impl EmailAliasesRepository<'_> {
+    pub fn new<'a>(
+        conn: &'a mut SqliteConnection,
+        encryption: &'a FieldEncryption,
+    ) -> EmailAliasesRepository<'a> {
+        EmailAliasesRepository { conn, encryption }
+    }
+}
+
+#[async_trait]
+impl<'a> email_alias_contracts::ListByUser for EmailAliasesRepository<'a> {
+    async fn list_by_user(
+        &mut self,
+        user_id: Uuid,
+    ) -> Result<Vec<EmailAlias>, ApplicationError> {
+        sqlx::query_as::<_, EmailAliasRow>(
+            r#"
+                select
+                    id,
+                    user_id,
+                    email,
+                    kind,
+                    created_at
+                from
+                    email_aliases
+                where
+                    user_id = (?)
+                order by
+                    created_at asc,
+                    id asc
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&mut *self.conn)
+        .timed("email_aliases.list_by_user")
+        .await
+        .map_err(query_error)?
+        .into_iter()
+        .map(|row| row.open(self.encryption))
+        .collect()
+    }
+}
+
+#[async_trait]
+impl<'a> email_alias_contracts::Insert for EmailAliasesRepository<'a> {
+    async fn insert(
+        &mut self,
+        entity: &EmailAlias,
+    ) -> Result<(), ApplicationError> {
+        let row = EmailAliasRowRef::seal(entity, self.encryption);
+
+        sqlx::query(
+            r#"
+                insert into email_aliases (
+                    id,
+                    user_id,
+                    email,
+                    kind,
+                    created_at
+                ) values (
+                    (?),
+                    (?),
+                    (?),
+                    (?),
+                    (?)
+                )
+            "#,
+        )
+        .bind(row.id)
+        .bind(row.user_id)
+        .bind(row.email)
+        .bind(row.kind)
+        .bind(row.created_at)
+        .execute(&mut *self.conn)
+        .timed("email_aliases.insert")
+        .await
+        .map(|_| ())
+        .map_err(insert_error)
+    }
+}
+
+#[async_trait]
+impl<'a> email_alias_contracts::Delete for EmailAliasesRepository<'a> {
+    async fn delete(
+        &mut self,
+        user_id: Uuid,
+        id: Uuid,
+    ) -> Result<(), ApplicationError> {
+        let deleted = sqlx::query(
+            "delete from email_aliases where user_id = (?) and id = (?)",
+        )
+        .bind(user_id)
+        .bind(id)
+        .execute(&mut *self.conn)
+        .timed("email_aliases.delete")
+        .await
+        .map_err(query_error)?
+        .rows_affected();
+        if deleted == 0 {
+            return Err(ApplicationError::entity_not_found("EmailAlias", id));
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<'a> email_alias_contracts::DeleteAll for EmailAliasesRepository<'a> {
+    async fn delete_all(
+        &mut self,
+        user_id: Uuid,
+    ) -> Result<u64, ApplicationError> {
+        sqlx::query("delete from email_aliases where user_id = (?)")
+            .bind(user_id)
+            .execute(&mut *self.conn)
+            .timed("email_aliases.delete_all")
+            .await
+            .map(|result| result.rows_affected())
+            .map_err(query_error)
+    }
+}
+
+/// Maps an error of an insert, so that emails that are already aliases, or that identify
+/// another user, are reported as taken.
+fn insert_error(e: sqlx::Error) -> ApplicationError {
+    match RepositoryError::from(e) {
+        RepositoryError::UniqueViolation { .. } => {
+            ApplicationError::entity_already_exists(
+                "User",
+                "Email is already taken",
+            )
+        }
+        e => e.into(),
+    }
+}
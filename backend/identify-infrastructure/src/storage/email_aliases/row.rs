@@ -0,0 +1,67 @@
+use std::borrow::Cow;
+
+use chrono::{DateTime, Utc};
+use identify_application::ApplicationError;
+use identify_domain::{EmailAlias, EmailAliasAttrs};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use crate::encryption::FieldEncryption;
+
+/// A stored alias, whose email may be encrypted, see [encryption](crate::encryption).
+#[derive(FromRow)]
+pub struct EmailAliasRow {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub email: String,
+    pub kind: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl EmailAliasRow {
+    /// Loads the alias of the row, decrypting its fields.
+    pub fn open(
+        self,
+        encryption: &FieldEncryption,
+    ) -> Result<EmailAlias, ApplicationError> {
+        let email = encryption.open(self.email).map_err(|e| {
+            ApplicationError::internal_with_message(
+                e,
+                format!("error while decrypting email alias {}", self.id),
+            )
+        })?;
+
+        let alias = EmailAlias::load(EmailAliasAttrs {
+            id: self.id,
+            user_id: self.user_id,
+            email,
+            kind: self.kind.parse()?,
+            created_at: self.created_at,
+        })?;
+
+        Ok(alias)
+    }
+}
+
+/// A row borrowed from an alias, so that writing it doesn't copy the email unless it's
+/// encrypted.
+pub struct EmailAliasRowRef<'a> {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub email: Cow<'a, str>,
+    pub kind: &'static str,
+    pub created_at: DateTime<Utc>,
+}
+
+impl<'a> EmailAliasRowRef<'a> {
+    /// Borrows the row of the alias, encrypting its fields.
+    pub fn seal(value: &'a EmailAlias, encryption: &FieldEncryption) -> Self {
+        EmailAliasRowRef {
+            id: value.id(),
+            user_id: value.user_id(),
+            email: encryption.seal(value.email()),
+            kind: value.kind().as_str(),
+            created_at: *value.created_at(),
+        }
+    }
+}
@@ -0,0 +1,140 @@
+mod row;
+
+use async_trait::async_trait;
+use identify_application::{ApplicationError, email_change_contracts};
+use identify_domain::EmailChange;
+use sqlx::SqliteConnection;
+use uuid::Uuid;
+
+use crate::{
+    encryption::FieldEncryption,
+    storage::{
+        email_changes::row::{EmailChangeRow, EmailChangeRowRef},
+        query_error,
+        timing::TimedExt,
+    },
+};
+
+/// Stores the changes of emails the users requested.
+pub struct EmailChangesRepository<'a> {
+    conn: &'a mut SqliteConnection,
+    encryption: &'a FieldEncryption,
+}
+
+impl EmailChangesRepository<'_> {
+    pub fn new<'a>(
+        conn: &'a mut SqliteConnection,
+        encryption: &'a FieldEncryption,
+    ) -> EmailChangesRepository<'a> {
+        EmailChangesRepository { conn, encryption }
+    }
+}
+
+#[async_trait]
+impl<'a> email_change_contracts::Get for EmailChangesRepository<'a> {
+    async fn get(&mut self, id: Uuid) -> Result<EmailChange, ApplicationError> {
+        let row = sqlx::query_as::<_, EmailChangeRow>(
+            r#"
+                select
+                    id,
+                    user_id,
+                    email,
+                    token_hash,
+                    expires_at,
+                    created_at,
+                    confirmed_at
+                from
+                    email_changes
+                where
+                    id = (?)
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&mut *self.conn)
+        .timed("email_changes.get")
+        .await
+        .map_err(query_error)?
+        .ok_or_else(|| ApplicationError::entity_not_found("EmailChange", id))?;
+
+        row.open(self.encryption)
+    }
+}
+
+#[async_trait]
+impl<'a> email_change_contracts::Insert for EmailChangesRepository<'a> {
+    async fn insert(
+        &mut self,
+        entity: &EmailChange,
+    ) -> Result<(), ApplicationError> {
+        let row = EmailChangeRowRef::seal(entity, self.encryption);
+
+        sqlx::query(
+            r#"
+                insert into email_changes (
+                    id,
+                    user_id,
+                    email,
+                    token_hash,
+                    expires_at,
+                    created_at,
+                    confirmed_at
+                ) values (
+                    (?),
+                    (?),
+                    (?),
+                    (?),
+                    (?),
+                    (?),
+                    (?)
+                )
+            "#,
+        )
+        .bind(row.id)
+        .bind(row.user_id)
+        .bind(row.email)
+        .bind(row.token_hash)
+        .bind(row.expires_at)
+        .bind(row.created_at)
+        .bind(row.confirmed_at)
+        .execute(&mut *self.conn)
+        .timed("email_changes.insert")
+        .await
+        .map(|_| ())
+        .map_err(query_error)
+    }
+}
+
+#[async_trait]
+impl<'a> email_change_contracts::Update for EmailChangesRepository<'a> {
+    async fn update(
+        &mut self,
+        entity: &EmailChange,
+    ) -> Result<(), ApplicationError> {
+        sqlx::query(
+            "update email_changes set confirmed_at = (?) where id = (?)",
+        )
+        .bind(entity.confirmed_at())
+        .bind(entity.id())
+        .execute(&mut *self.conn)
+        .timed("email_changes.update")
+        .await
+        .map(|_| ())
+        .map_err(query_error)
+    }
+}
+
+#[async_trait]
+impl<'a> email_change_contracts::DeleteAll for EmailChangesRepository<'a> {
+    async fn delete_all(
+        &mut self,
+        user_id: Uuid,
+    ) -> Result<u64, ApplicationError> {
+        sqlx::query("delete from email_changes where user_id = (?)")
+            .bind(user_id)
+            .execute(&mut *self.conn)
+            .timed("email_changes.delete_all")
+            .await
+            .map(|result| result.rows_affected())
+            .map_err(query_error)
+    }
+}
@@ -0,0 +1,75 @@
+use std::borrow::Cow;
+
+use chrono::{DateTime, Utc};
+use identify_application::ApplicationError;
+use identify_domain::{EmailChange, EmailChangeAttrs};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use crate::encryption::FieldEncryption;
+
+/// A stored change of email, whose new email may be encrypted, see [encryption](crate::encryption).
+#[derive(FromRow)]
+pub struct EmailChangeRow {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub email: String,
+    pub token_hash: String,
+    pub expires_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub confirmed_at: Option<DateTime<Utc>>,
+}
+
+impl EmailChangeRow {
+    /// Loads the change of email of the row, decrypting its fields.
+    pub fn open(
+        self,
+        encryption: &FieldEncryption,
+    ) -> Result<EmailChange, ApplicationError> {
+        let email = encryption.open(self.email).map_err(|e| {
+            ApplicationError::internal_with_message(
+                e,
+                format!("error while decrypting email change {}", self.id),
+            )
+        })?;
+
+        let change = EmailChange::load(EmailChangeAttrs {
+            id: self.id,
+            user_id: self.user_id,
+            email,
+            token_hash: self.token_hash,
+            expires_at: self.expires_at,
+            created_at: self.created_at,
+            confirmed_at: self.confirmed_at,
+        })?;
+
+        Ok(change)
+    }
+}
+
+/// A row borrowed from a change of email, so that writing it doesn't copy the email unless it's
+/// encrypted.
+pub struct EmailChangeRowRef<'a> {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub email: Cow<'a, str>,
+    pub token_hash: &'a str,
+    pub expires_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub confirmed_at: Option<DateTime<Utc>>,
+}
+
+impl<'a> EmailChangeRowRef<'a> {
+    /// Borrows the row of the change of email, encrypting its fields.
+    pub fn seal(value: &'a EmailChange, encryption: &FieldEncryption) -> Self {
+        EmailChangeRowRef {
+            id: value.id(),
+            user_id: value.user_id(),
+            email: encryption.seal(value.email()),
+            token_hash: value.token_hash(),
+            expires_at: *value.expires_at(),
+            created_at: *value.created_at(),
+            confirmed_at: *value.confirmed_at(),
+        }
+    }
+}
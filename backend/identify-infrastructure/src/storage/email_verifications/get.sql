@@ -0,0 +1,11 @@
+select
+    id,
+    user_id,
+    token_hash,
+    expires_at,
+    created_at,
+    verified_at
+from
+    email_verifications
+where
+    id = (?)
@@ -0,0 +1,118 @@
+mod row;
+
+use async_trait::async_trait;
+use identify_application::{ApplicationError, email_verification_contracts};
+use identify_domain::EmailVerification;
+use sqlx::SqliteConnection;
+use uuid::Uuid;
+
+use crate::storage::{
+    email_verifications::row::EmailVerificationRow, query_error,
+    timing::TimedExt,
+};
+
+/// Stores the verifications of the emails of users.
+pub struct EmailVerificationsRepository<'a> {
+    conn: &'a mut SqliteConnection,
+}
+
+impl EmailVerificationsRepository<'_> {
+    pub fn new<'a>(
+        conn: &'a mut SqliteConnection,
+    ) -> EmailVerificationsRepository<'a> {
+        EmailVerificationsRepository { conn }
+    }
+}
+
+#[async_trait]
+impl<'a> email_verification_contracts::Get
+    for EmailVerificationsRepository<'a>
+{
+    async fn get(
+        &mut self,
+        id: Uuid,
+    ) -> Result<EmailVerification, ApplicationError> {
+        let row =
+            sqlx::query_as::<_, EmailVerificationRow>(include_str!("get.sql"))
+                .bind(id)
+                .fetch_optional(&mut *self.conn)
+                .timed("email_verifications.get")
+                .await
+                .map_err(query_error)?
+                .ok_or_else(|| {
+                    ApplicationError::entity_not_found("EmailVerification", id)
+                })?;
+
+        Ok(row.try_into()?)
+    }
+}
+
+#[async_trait]
+impl<'a> email_verification_contracts::Insert
+    for EmailVerificationsRepository<'a>
+{
+    async fn insert(
+        &mut self,
+        entity: &EmailVerification,
+    ) -> Result<(), ApplicationError> {
+        let row: EmailVerificationRow = entity.into();
+
+        sqlx::query(
+            r#"
+                insert into email_verifications (
+                    id,
+                    user_id,
+                    token_hash,
+                    expires_at,
+                    created_at,
+                    verified_at
+                ) values (
+                    (?),
+                    (?),
+                    (?),
+                    (?),
+                    (?),
+                    (?)
+                )
+            "#,
+        )
+        .bind(row.id)
+        .bind(row.user_id)
+        .bind(row.token_hash)
+        .bind(row.expires_at)
+        .bind(row.created_at)
+        .bind(row.verified_at)
+        .execute(&mut *self.conn)
+        .timed("email_verifications.insert")
+        .await
+        .map(|_| ())
+        .map_err(query_error)
+    }
+}
+
+#[async_trait]
+impl<'a> email_verification_contracts::Update
+    for EmailVerificationsRepository<'a>
+{
+    async fn update(
+        &mut self,
+        entity: &EmailVerification,
+    ) -> Result<(), ApplicationError> {
+        sqlx::query(
+            r#"
+                update email_verifications
+                set
+                    verified_at = (?)
+                where
+                    id = (?)
+            "#,
+        )
+        .bind(entity.verified_at())
+        .bind(entity.id())
+        .execute(&mut *self.conn)
+        .timed("email_verifications.update")
+        .await
+        .map(|_| ())
+        .map_err(query_error)
+    }
+}
@@ -0,0 +1,16 @@
+use chrono::{DateTime, Utc};
+use identify_domain::{DomainError, EmailVerification, EmailVerificationAttrs};
+use identify_macros::ModelRow;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+#[derive(FromRow, ModelRow)]
+#[row(model = EmailVerification, error = DomainError)]
+pub struct EmailVerificationRow {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub token_hash: String,
+    pub expires_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub verified_at: Option<DateTime<Utc>>,
+}
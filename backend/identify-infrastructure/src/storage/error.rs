@@ -0,0 +1,131 @@
+//! Failures of repository queries, classified so that callers can react to them.
+
+use eyre::eyre;
+use identify_application::ApplicationError;
+use sqlx::error::ErrorKind;
+use thiserror::Error;
+use tracing::warn;
+
+use crate::storage::{connection, deadline};
+
+/// Extended result code of a transaction whose snapshot is older than a concurrent write, in
+/// which case it can only be retried from the start.
+const SQLITE_BUSY_SNAPSHOT: &str = "517";
+/// Extended result code of a constraint raised by a trigger. The triggers that keep values
+/// unique across tables raise it with the message of a unique violation.
+const SQLITE_CONSTRAINT_TRIGGER: &str = "1811";
+
+/// A failed repository query.
+#[derive(Debug, Error)]
+pub enum RepositoryError {
+    /// The query was expected to return a row, but it returned none.
+    #[error("The row was not found")]
+    NotFound,
+
+    /// A row with the same value for the constraint already exists, e.g. `users.email`.
+    #[error("Unique constraint {constraint} was violated")]
+    UniqueViolation { constraint: String },
+
+    /// A row references one that doesn't exist, or a row that is still referenced was
+    /// deleted.
+    #[error("Foreign key constraint was violated")]
+    ForeignKeyViolation,
+
+    /// The transaction conflicted with a concurrent one, and has to be retried from the start.
+    #[error("The transaction conflicted with a concurrent one: {0}")]
+    Serialization(sqlx::Error),
+
+    /// The query didn't finish before the deadline of the task.
+    #[error("The query didn't finish before its deadline")]
+    Timeout,
+
+    /// The database couldn't be reached, e.g. because it's locked, all connections are busy or
+    /// the pool is closing.
+    #[error("Database is temporarily unavailable: {0}")]
+    Connection(sqlx::Error),
+
+    #[error("Database error: {0}")]
+    Other(sqlx::Error),
+}
+
+impl From<sqlx::Error> for RepositoryError {
+    fn from(e: sqlx::Error) -> Self {
+        if deadline::is_exceeded_error(&e) {
+            return RepositoryError::Timeout;
+        }
+
+        match &e {
+            sqlx::Error::RowNotFound => RepositoryError::NotFound,
+            sqlx::Error::PoolClosed => RepositoryError::Connection(e),
+            sqlx::Error::Database(db_error) => match db_error.kind() {
+                ErrorKind::UniqueViolation => {
+                    RepositoryError::UniqueViolation {
+                        constraint: constraint(db_error.message()),
+                    }
+                }
+                ErrorKind::ForeignKeyViolation => {
+                    RepositoryError::ForeignKeyViolation
+                }
+                _ if db_error.code().as_deref()
+                    == Some(SQLITE_CONSTRAINT_TRIGGER)
+                    && db_error.message().starts_with("UNIQUE") =>
+                {
+                    RepositoryError::UniqueViolation {
+                        constraint: constraint(db_error.message()),
+                    }
+                }
+                _ if db_error.code().as_deref()
+                    == Some(SQLITE_BUSY_SNAPSHOT) =>
+                {
+                    RepositoryError::Serialization(e)
+                }
+                _ if connection::is_transient(&e) => {
+                    RepositoryError::Connection(e)
+                }
+                _ => RepositoryError::Other(e),
+            },
+            _ if connection::is_transient(&e) => RepositoryError::Connection(e),
+            _ => RepositoryError::Other(e),
+        }
+    }
+}
+
+impl From<RepositoryError> for ApplicationError {
+    fn from(value: RepositoryError) -> Self {
+        match value {
+            RepositoryError::UniqueViolation { constraint } => {
+                let entity = constraint
+                    .split_once('.')
+                    .map_or(constraint.as_str(), |(table, _)| table)
+                    .to_owned();
+                ApplicationError::EntityAlreadyExists {
+                    message: format!("{constraint} is already taken"),
+                    entity,
+                }
+            }
+            RepositoryError::ForeignKeyViolation => {
+                ApplicationError::ReferenceViolation
+            }
+            RepositoryError::Timeout => ApplicationError::DeadlineExceeded,
+            RepositoryError::Serialization(e)
+            | RepositoryError::Connection(e) => {
+                warn!(error = %e, "Database is temporarily unavailable");
+                ApplicationError::Unavailable
+            }
+            // Lookups report missing rows with the entity they were looking for, so any other
+            // missing row is unexpected.
+            e @ (RepositoryError::NotFound | RepositoryError::Other(_)) => {
+                ApplicationError::internal(eyre!(e))
+            }
+        }
+    }
+}
+
+/// The columns of a violated constraint, e.g. `users.email` in the message
+/// `UNIQUE constraint failed: users.email`.
+fn constraint(message: &str) -> String {
+    message
+        .split_once(": ")
+        .map_or(message, |(_, constraint)| constraint)
+        .to_owned()
+}
@@ -0,0 +1,162 @@
+mod row;
+
+use async_trait::async_trait;
+use identify_application::{ApplicationError, invitation_contracts};
+use identify_domain::Invitation;
+use sqlx::SqliteConnection;
+use uuid::Uuid;
+
+use crate::{
+    encryption::FieldEncryption,
+    storage::{
+        invitations::row::{InvitationRow, InvitationRowRef},
+        query_error,
+        timing::TimedExt,
+    },
+};
+
+/// Stores the invitations to join organizations.
+pub struct InvitationsRepository<'a> {
+    conn: &'a mut SqliteConnection,
+    encryption: &'a FieldEncryption,
+}
+
+impl InvitationsRepository<'_> {
+    pub fn new<'a>(
+        conn: &'a mut SqliteConnection,
+        encryption: &'a FieldEncryption,
+    ) -> InvitationsRepository<'a> {
+        InvitationsRepository { conn, encryption }
+    }
+}
+
+#[async_trait]
+impl<'a> invitation_contracts::Get for InvitationsRepository<'a> {
+    async fn get(&mut self, id: Uuid) -> Result<Invitation, ApplicationError> {
+        let row = sqlx::query_as::<_, InvitationRow>(
+            r#"
+                select
+                    id,
+                    email,
+                    inviter_id,
+                    organization_id,
+                    role,
+                    token_hash,
+                    expires_at,
+                    created_at,
+                    accepted_at,
+                    user_id
+                from
+                    invitations
+                where
+                    id = (?)
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&mut *self.conn)
+        .timed("invitations.get")
+        .await
+        .map_err(query_error)?
+        .ok_or_else(|| ApplicationError::entity_not_found("Invitation", id))?;
+
+        row.open(self.encryption)
+    }
+}
+
+#[async_trait]
+impl<'a> invitation_contracts::Insert for InvitationsRepository<'a> {
+    async fn insert(
+        &mut self,
+        entity: &Invitation,
+    ) -> Result<(), ApplicationError> {
+        let row = InvitationRowRef::seal(entity, self.encryption);
+
+        sqlx::query(
+            r#"
+                insert into invitations (
+                    id,
+                    email,
+                    inviter_id,
+                    organization_id,
+                    role,
+                    token_hash,
+                    expires_at,
+                    created_at,
+                    accepted_at,
+                    user_id
+                ) values (
+                    (?),
+                    (?),
+                    (?),
+                    (?),
+                    (?),
+                    (?),
+                    (?),
+                    (?),
+                    (?),
+                    (?)
+                )
+            "#,
+        )
+        .bind(row.id)
+        .bind(row.email)
+        .bind(row.inviter_id)
+        .bind(row.organization_id)
+        .bind(row.role)
+        .bind(row.token_hash)
+        .bind(row.expires_at)
+        .bind(row.created_at)
+        .bind(row.accepted_at)
+        .bind(row.user_id)
+        .execute(&mut *self.conn)
+        .timed("invitations.insert")
+        .await
+        .map(|_| ())
+        .map_err(query_error)
+    }
+}
+
+#[async_trait]
+impl<'a> invitation_contracts::Update for InvitationsRepository<'a> {
+    async fn update(
+        &mut self,
+        entity: &Invitation,
+    ) -> Result<(), ApplicationError> {
+        let row = InvitationRowRef::seal(entity, self.encryption);
+
+        sqlx::query(
+            r#"
+                update invitations
+                set
+                    accepted_at = (?),
+                    user_id = (?)
+                where
+                    id = (?)
+            "#,
+        )
+        .bind(row.accepted_at)
+        .bind(row.user_id)
+        .bind(row.id)
+        .execute(&mut *self.conn)
+        .timed("invitations.update")
+        .await
+        .map(|_| ())
+        .map_err(query_error)
+    }
+}
+
+#[async_trait]
+impl<'a> invitation_contracts::DeleteAll for InvitationsRepository<'a> {
+    async fn delete_all(
+        &mut self,
+        user_id: Uuid,
+    ) -> Result<u64, ApplicationError> {
+        sqlx::query("delete from invitations where user_id = (?)")
+            .bind(user_id)
+            .execute(&mut *self.conn)
+            .timed("invitations.delete_all")
+            .await
+            .map(|result| result.rows_affected())
+            .map_err(query_error)
+    }
+}
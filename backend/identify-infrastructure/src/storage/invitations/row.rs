@@ -0,0 +1,87 @@
+use std::borrow::Cow;
+
+use chrono::{DateTime, Utc};
+use identify_application::ApplicationError;
+use identify_domain::{Invitation, InvitationAttrs};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use crate::encryption::FieldEncryption;
+
+/// A stored invitation, whose email may be encrypted, see [encryption](crate::encryption).
+#[derive(FromRow)]
+pub struct InvitationRow {
+    pub id: Uuid,
+    pub email: String,
+    pub inviter_id: Uuid,
+    pub organization_id: Uuid,
+    pub role: String,
+    pub token_hash: String,
+    pub expires_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub accepted_at: Option<DateTime<Utc>>,
+    pub user_id: Option<Uuid>,
+}
+
+impl InvitationRow {
+    /// Loads the invitation of the row, decrypting its fields.
+    pub fn open(
+        self,
+        encryption: &FieldEncryption,
+    ) -> Result<Invitation, ApplicationError> {
+        let email = encryption.open(self.email).map_err(|e| {
+            ApplicationError::internal_with_message(
+                e,
+                format!("error while decrypting invitation {}", self.id),
+            )
+        })?;
+
+        let invitation = Invitation::load(InvitationAttrs {
+            id: self.id,
+            email,
+            inviter_id: self.inviter_id,
+            organization_id: self.organization_id,
+            role: self.role.parse()?,
+            token_hash: self.token_hash,
+            expires_at: self.expires_at,
+            created_at: self.created_at,
+            accepted_at: self.accepted_at,
+            user_id: self.user_id,
+        })?;
+
+        Ok(invitation)
+    }
+}
+
+/// A row borrowed from an invitation, so that writing it doesn't copy the email unless it's
+/// encrypted.
+pub struct InvitationRowRef<'a> {
+    pub id: Uuid,
+    pub email: Cow<'a, str>,
+    pub inviter_id: Uuid,
+    pub organization_id: Uuid,
+    pub role: &'static str,
+    pub token_hash: &'a str,
+    pub expires_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub accepted_at: Option<DateTime<Utc>>,
+    pub user_id: Option<Uuid>,
+}
+
+impl<'a> InvitationRowRef<'a> {
+    /// Borrows the row of the invitation, encrypting its fields.
+    pub fn seal(value: &'a Invitation, encryption: &FieldEncryption) -> Self {
+        InvitationRowRef {
+            id: value.id(),
+            email: encryption.seal(value.email()),
+            inviter_id: value.inviter_id(),
+            organization_id: value.organization_id(),
+            role: value.role().as_str(),
+            token_hash: value.token_hash(),
+            expires_at: *value.expires_at(),
+            created_at: *value.created_at(),
+            accepted_at: *value.accepted_at(),
+            user_id: *value.user_id(),
+        }
+    }
+}
@@ -0,0 +1,238 @@
+mod row;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use identify_application::{ApplicationError, membership_contracts};
+use identify_domain::{Membership, MembershipRole};
+use sqlx::SqliteConnection;
+use uuid::Uuid;
+
+use crate::storage::{
+    RepositoryError, lookup_error,
+    memberships::row::MembershipRow,
+    queries::{query, query_as},
+    query_error,
+    timing::TimedExt,
+};
+
+pub struct MembershipsRepository<'a> {
+    conn: &'a mut SqliteConnection,
+}
+
+impl MembershipsRepository<'_> {
+    pub fn new<'a>(
+        conn: &'a mut SqliteConnection,
+    ) -> MembershipsRepository<'a> {
+        MembershipsRepository { conn }
+    }
+}
+
+#[async_trait]
+impl<'a> membership_contracts::Get for MembershipsRepository<'a> {
+    async fn get(
+        &mut self,
+        organization_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Membership, ApplicationError> {
+        let membership = query_as!(
+            MembershipRow,
+            r#"
+                select
+                    organization_id as "organization_id: Uuid",
+                    user_id as "user_id: Uuid",
+                    role,
+                    created_at as "created_at: _",
+                    updated_at as "updated_at: _"
+                from
+                    memberships
+                where
+                    organization_id = (?) and user_id = (?)
+            "#,
+            organization_id,
+            user_id
+        )
+        .fetch_one(&mut *self.conn)
+        .timed("memberships.get")
+        .await
+        .map_err(lookup_error("Membership", user_id))
+        .map(TryInto::try_into)??;
+
+        Ok(membership)
+    }
+}
+
+#[async_trait]
+impl<'a> membership_contracts::CountByRole for MembershipsRepository<'a> {
+    async fn count_by_role(
+        &mut self,
+        organization_id: Uuid,
+        role: MembershipRole,
+    ) -> Result<u64, ApplicationError> {
+        let count: i64 = sqlx::query_scalar(
+            r#"
+                select
+                    count(*)
+                from
+                    memberships
+                where
+                    organization_id = (?) and role = (?)
+            "#,
+        )
+        .bind(organization_id)
+        .bind(role.to_string())
+        .fetch_one(&mut *self.conn)
+        .timed("memberships.count_by_role")
+        .await
+        .map_err(query_error)?;
+
+        Ok(count as u64)
+    }
+}
+
+#[async_trait]
+impl<'a> membership_contracts::CountNewAdmins for MembershipsRepository<'a> {
+    async fn count_new_admins(
+        &mut self,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> Result<u64, ApplicationError> {
+        // Only the role of a membership can change, so it was last updated when it got it.
+        let count: i64 = sqlx::query_scalar(
+            r#"
+                select
+                    count(*)
+                from
+                    memberships
+                where
+                    role in ((?), (?))
+                    and updated_at >= (?)
+                    and updated_at < (?)
+            "#,
+        )
+        .bind(MembershipRole::Owner.to_string())
+        .bind(MembershipRole::Admin.to_string())
+        .bind(since)
+        .bind(until)
+        .fetch_one(&mut *self.conn)
+        .timed("memberships.count_new_admins")
+        .await
+        .map_err(query_error)?;
+
+        Ok(count as u64)
+    }
+}
+
+#[async_trait]
+impl<'a> membership_contracts::Insert for MembershipsRepository<'a> {
+    async fn insert(
+        &mut self,
+        entity: &Membership,
+    ) -> Result<(), ApplicationError> {
+        let row: MembershipRow = entity.into();
+
+        query!(
+            r#"
+                insert into memberships (
+                    organization_id,
+                    user_id,
+                    role,
+                    created_at,
+                    updated_at
+                ) values (
+                    (?),
+                    (?),
+                    (?),
+                    (?),
+                    (?)
+                )
+            "#,
+            row.organization_id,
+            row.user_id,
+            row.role,
+            row.created_at,
+            row.updated_at
+        )
+        .execute(&mut *self.conn)
+        .timed("memberships.insert")
+        .await
+        .map(|_| ())
+        .map_err(|e| match RepositoryError::from(e) {
+            RepositoryError::UniqueViolation { .. } => {
+                ApplicationError::entity_already_exists(
+                    "Membership",
+                    "User is already a member of the organization",
+                )
+            }
+            e => e.into(),
+        })
+    }
+}
+
+#[async_trait]
+impl<'a> membership_contracts::Update for MembershipsRepository<'a> {
+    async fn update(
+        &mut self,
+        entity: &Membership,
+    ) -> Result<(), ApplicationError> {
+        let row: MembershipRow = entity.into();
+
+        query!(
+            r#"
+                update memberships
+                set
+                    role = (?),
+                    updated_at = (?)
+                where
+                    organization_id = (?) and user_id = (?)
+            "#,
+            row.role,
+            row.updated_at,
+            row.organization_id,
+            row.user_id
+        )
+        .execute(&mut *self.conn)
+        .timed("memberships.update")
+        .await
+        .map(|_| ())
+        .map_err(query_error)
+    }
+}
+
+#[async_trait]
+impl<'a> membership_contracts::Reassign for MembershipsRepository<'a> {
+    async fn reassign(
+        &mut self,
+        from: Uuid,
+        to: Uuid,
+    ) -> Result<u64, ApplicationError> {
+        let moved = sqlx::query(
+            r#"
+                update memberships
+                set
+                    user_id = (?)
+                where
+                    user_id = (?)
+                    and organization_id not in (
+                        select organization_id from memberships where user_id = (?)
+                    )
+            "#,
+        )
+        .bind(to)
+        .bind(from)
+        .bind(to)
+        .execute(&mut *self.conn)
+        .timed("memberships.reassign")
+        .await
+        .map_err(query_error)?
+        .rows_affected();
+
+        sqlx::query("delete from memberships where user_id = (?)")
+            .bind(from)
+            .execute(&mut *self.conn)
+            .timed("memberships.delete_reassigned")
+            .await
+            .map_err(query_error)?;
+
+        Ok(moved)
+    }
+}
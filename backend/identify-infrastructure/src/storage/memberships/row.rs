@@ -0,0 +1,16 @@
+use chrono::{DateTime, Utc};
+use identify_domain::{DomainError, Membership, MembershipAttrs};
+use identify_macros::ModelRow;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+#[derive(FromRow, ModelRow)]
+#[row(model = Membership, error = DomainError)]
+pub struct MembershipRow {
+    pub organization_id: Uuid,
+    pub user_id: Uuid,
+    #[row(text)]
+    pub role: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
@@ -1,8 +1,127 @@
-use std::sync::Arc;
+use identify_application::ApplicationError;
+use sqlx::{Sqlite, SqlitePool, SqliteTransaction, pool::PoolConnection};
+use uuid::Uuid;
 
-use sqlx::SqliteTransaction;
-use tokio::sync::Mutex;
+use crate::{
+    InfrastructureError, Result,
+    storage::{connection::ReadPool, timing::TimedExt},
+};
 
+pub mod authorization_policies;
+pub mod backup;
+pub mod connection;
+pub mod consents;
+pub mod cursor;
+pub mod deadline;
+pub mod devices;
+pub mod diagnostics;
+pub mod email_aliases;
+pub mod email_changes;
+pub mod email_verifications;
+pub mod error;
+pub mod invitations;
+pub mod memberships;
+pub mod operations;
+pub mod organization_quotas;
+pub mod organizations;
+pub mod outbox;
+pub mod phone_verifications;
+pub mod queries;
+pub mod rate_limits;
+pub mod retry;
+pub mod reverifications;
+pub mod roles;
+pub mod signing_keys;
+pub(crate) mod specification;
+pub mod statement_cache;
+pub mod status;
+pub mod tenants;
+pub mod timing;
+pub mod unit_of_work;
+pub mod user_attributes;
+pub mod user_summaries;
 pub mod users;
+pub mod webhooks;
 
-pub type SharedTransaction<'a> = Arc<Mutex<SqliteTransaction<'a>>>;
+pub use error::RepositoryError;
+
+/// Starts a new transaction.
+///
+/// Repositories borrow the connection of the transaction mutably, so only one of them can use it
+/// at a time, without any locking. A transaction that is dropped without being committed (e.g.
+/// because the request failed or panicked) is rolled back.
+pub async fn begin(pool: &SqlitePool) -> Result<SqliteTransaction<'static>> {
+    let tx = pool
+        .begin()
+        .timed("transaction.begin")
+        .await
+        .map_err(database_error)?;
+
+    Ok(tx)
+}
+
+/// Takes a connection of the read pool for queries that don't need a transaction.
+///
+/// Repositories created on it can only read, see [ReadPool].
+pub async fn acquire(pool: &ReadPool) -> Result<PoolConnection<Sqlite>> {
+    let conn = pool
+        .pool()
+        .acquire()
+        .timed("connection.acquire")
+        .await
+        .map_err(database_error)?;
+
+    Ok(conn)
+}
+
+/// Takes a connection of the write pool for changes that are committed one statement at a time,
+/// e.g. to record each side effect as soon as it happens instead of holding a transaction open
+/// while it's done.
+pub async fn connect(pool: &SqlitePool) -> Result<PoolConnection<Sqlite>> {
+    let conn = pool
+        .acquire()
+        .timed("connection.acquire")
+        .await
+        .map_err(database_error)?;
+
+    Ok(conn)
+}
+
+/// Commits a transaction.
+pub async fn commit(tx: SqliteTransaction<'_>) -> Result<()> {
+    tx.commit()
+        .timed("transaction.commit")
+        .await
+        .map_err(database_error)?;
+
+    Ok(())
+}
+
+fn database_error(e: sqlx::Error) -> InfrastructureError {
+    if deadline::is_exceeded_error(&e) {
+        InfrastructureError::DeadlineExceeded
+    } else if connection::is_transient(&e) {
+        InfrastructureError::Unavailable(e)
+    } else {
+        InfrastructureError::Database(e)
+    }
+}
+
+/// Maps an error of a repository query to the application error, see [RepositoryError].
+pub(crate) fn query_error(e: sqlx::Error) -> ApplicationError {
+    RepositoryError::from(e).into()
+}
+
+/// Maps an error of a query that looks up a single entity by its ID, so that a missing row is
+/// reported as [ApplicationError::EntityNotFound].
+pub(crate) fn lookup_error(
+    entity: &'static str,
+    id: Uuid,
+) -> impl FnOnce(sqlx::Error) -> ApplicationError {
+    move |e| match RepositoryError::from(e) {
+        RepositoryError::NotFound => {
+            ApplicationError::entity_not_found(entity, id)
+        }
+        e => e.into(),
+    }
+}
@@ -0,0 +1,13 @@
+select
+    id as "id: Uuid",
+    kind,
+    status,
+    progress as "progress: u8",
+    error,
+    cancel_requested as "cancel_requested: bool",
+    created_at as "created_at: _",
+    updated_at as "updated_at: _"
+from
+    operations
+where
+    id = (?)
@@ -0,0 +1,155 @@
+mod row;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use identify_application::{ApplicationError, operation_contracts};
+use identify_domain::{Operation, OperationStatus};
+use sqlx::SqliteConnection;
+use uuid::Uuid;
+
+use crate::storage::{
+    lookup_error,
+    operations::row::OperationRow,
+    queries::{query, query_file_as, query_scalar},
+    query_error,
+    timing::TimedExt,
+};
+
+pub struct OperationsRepository<'a> {
+    conn: &'a mut SqliteConnection,
+}
+
+impl OperationsRepository<'_> {
+    pub fn new<'a>(conn: &'a mut SqliteConnection) -> OperationsRepository<'a> {
+        OperationsRepository { conn }
+    }
+}
+
+#[async_trait]
+impl<'a> operation_contracts::Get for OperationsRepository<'a> {
+    async fn get(&mut self, id: Uuid) -> Result<Operation, ApplicationError> {
+        let operation =
+            query_file_as!(OperationRow, "src/storage/operations/get.sql", id)
+                .fetch_one(&mut *self.conn)
+                .timed("operations.get")
+                .await
+                .map_err(lookup_error("Operation", id))
+                .map(TryInto::try_into)??;
+
+        Ok(operation)
+    }
+}
+
+#[async_trait]
+impl<'a> operation_contracts::Insert for OperationsRepository<'a> {
+    async fn insert(
+        &mut self,
+        entity: &Operation,
+    ) -> Result<(), ApplicationError> {
+        let row: OperationRow = entity.into();
+
+        query!(
+            r#"
+                insert into operations (
+                    id,
+                    kind,
+                    status,
+                    progress,
+                    error,
+                    cancel_requested,
+                    created_at,
+                    updated_at
+                ) values (
+                    (?),
+                    (?),
+                    (?),
+                    (?),
+                    (?),
+                    (?),
+                    (?),
+                    (?)
+                )
+            "#,
+            row.id,
+            row.kind,
+            row.status,
+            row.progress,
+            row.error,
+            row.cancel_requested,
+            row.created_at,
+            row.updated_at
+        )
+        .execute(&mut *self.conn)
+        .timed("operations.insert")
+        .await
+        .map(|_| ())
+        .map_err(query_error)
+    }
+}
+
+#[async_trait]
+impl<'a> operation_contracts::Update for OperationsRepository<'a> {
+    async fn update(
+        &mut self,
+        entity: &Operation,
+    ) -> Result<(), ApplicationError> {
+        let row: OperationRow = entity.into();
+
+        query!(
+            r#"
+                update operations
+                set
+                    status = (?),
+                    progress = (?),
+                    error = (?),
+                    cancel_requested = (?),
+                    updated_at = (?)
+                where
+                    id = (?)
+            "#,
+            row.status,
+            row.progress,
+            row.error,
+            row.cancel_requested,
+            row.updated_at,
+            row.id
+        )
+        .execute(&mut *self.conn)
+        .timed("operations.update")
+        .await
+        .map(|_| ())
+        .map_err(query_error)
+    }
+}
+
+#[async_trait]
+impl<'a> operation_contracts::CountFailed for OperationsRepository<'a> {
+    async fn count_failed(
+        &mut self,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> Result<u64, ApplicationError> {
+        let status = OperationStatus::Failed.as_str();
+        let count: i64 = query_scalar!(
+            r#"
+                select
+                    count(*) as "count!: i64"
+                from
+                    operations
+                where
+                    status = (?)
+                    and updated_at >= (?)
+                    and updated_at < (?)
+            "#,
+            status,
+            since,
+            until
+        )
+        .fetch_one(&mut *self.conn)
+        .timed("operations.count_failed")
+        .await
+        .map_err(query_error)?;
+
+        Ok(count as u64)
+    }
+}
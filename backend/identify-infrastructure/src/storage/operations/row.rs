@@ -0,0 +1,19 @@
+use chrono::{DateTime, Utc};
+use identify_domain::{DomainError, Operation, OperationAttrs};
+use identify_macros::ModelRow;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+#[derive(FromRow, ModelRow)]
+#[row(model = Operation, error = DomainError)]
+pub struct OperationRow {
+    pub id: Uuid,
+    pub kind: String,
+    #[row(text)]
+    pub status: String,
+    pub progress: u8,
+    pub error: Option<String>,
+    pub cancel_requested: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
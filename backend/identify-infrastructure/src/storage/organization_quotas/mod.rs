@@ -0,0 +1,163 @@
+mod row;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use identify_application::{ApplicationError, organization_quota_contracts};
+use identify_domain::OrganizationQuota;
+use sqlx::SqliteConnection;
+use uuid::Uuid;
+
+use crate::storage::{
+    organization_quotas::row::OrganizationQuotaRow, query_error,
+    timing::TimedExt,
+};
+
+/// Stores the quotas organizations have of their own.
+pub struct OrganizationQuotasRepository<'a> {
+    conn: &'a mut SqliteConnection,
+}
+
+impl OrganizationQuotasRepository<'_> {
+    pub fn new<'a>(
+        conn: &'a mut SqliteConnection,
+    ) -> OrganizationQuotasRepository<'a> {
+        OrganizationQuotasRepository { conn }
+    }
+}
+
+#[async_trait]
+impl<'a> organization_quota_contracts::Find
+    for OrganizationQuotasRepository<'a>
+{
+    async fn find(
+        &mut self,
+        organization_id: Uuid,
+    ) -> Result<Option<OrganizationQuota>, ApplicationError> {
+        let row = sqlx::query_as::<_, OrganizationQuotaRow>(
+            r#"
+                select
+                    organization_id,
+                    max_users,
+                    created_at,
+                    updated_at
+                from
+                    organization_quotas
+                where
+                    organization_id = (?)
+            "#,
+        )
+        .bind(organization_id)
+        .fetch_optional(&mut *self.conn)
+        .timed("organization_quotas.find")
+        .await
+        .map_err(query_error)?;
+
+        Ok(row.map(TryInto::try_into).transpose()?)
+    }
+}
+
+#[async_trait]
+impl<'a> organization_quota_contracts::Put
+    for OrganizationQuotasRepository<'a>
+{
+    async fn put(
+        &mut self,
+        entity: &OrganizationQuota,
+    ) -> Result<(), ApplicationError> {
+        let row: OrganizationQuotaRow = entity.into();
+
+        sqlx::query(
+            r#"
+                insert into organization_quotas (
+                    organization_id,
+                    max_users,
+                    created_at,
+                    updated_at
+                ) values (
+                    (?),
+                    (?),
+                    (?),
+                    (?)
+                )
+                on conflict (organization_id) do update set
+                    max_users = excluded.max_users,
+                    updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(row.organization_id)
+        .bind(row.max_users)
+        .bind(row.created_at)
+        .bind(row.updated_at)
+        .execute(&mut *self.conn)
+        .timed("organization_quotas.put")
+        .await
+        .map(|_| ())
+        .map_err(query_error)
+    }
+}
+
+#[async_trait]
+impl<'a> organization_quota_contracts::Delete
+    for OrganizationQuotasRepository<'a>
+{
+    async fn delete(
+        &mut self,
+        organization_id: Uuid,
+    ) -> Result<bool, ApplicationError> {
+        sqlx::query(
+            "delete from organization_quotas where organization_id = (?)",
+        )
+        .bind(organization_id)
+        .execute(&mut *self.conn)
+        .timed("organization_quotas.delete")
+        .await
+        .map(|result| result.rows_affected() > 0)
+        .map_err(query_error)
+    }
+}
+
+#[async_trait]
+impl<'a> organization_quota_contracts::CountReached
+    for OrganizationQuotasRepository<'a>
+{
+    async fn count_reached(
+        &mut self,
+        default_max_users: Option<u32>,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> Result<u64, ApplicationError> {
+        // An organization reached its maximum when the member that filled its last place joined.
+        let count: i64 = sqlx::query_scalar(
+            r#"
+                select
+                    count(*)
+                from (
+                    select
+                        organization_id,
+                        created_at,
+                        row_number() over (
+                            partition by organization_id
+                            order by created_at, user_id
+                        ) as position
+                    from
+                        memberships
+                ) m
+                left join organization_quotas q
+                    on q.organization_id = m.organization_id
+                where
+                    m.position = iif(q.organization_id is null, (?), q.max_users)
+                    and m.created_at >= (?)
+                    and m.created_at < (?)
+            "#,
+        )
+        .bind(default_max_users)
+        .bind(since)
+        .bind(until)
+        .fetch_one(&mut *self.conn)
+        .timed("organization_quotas.count_reached")
+        .await
+        .map_err(query_error)?;
+
+        Ok(count as u64)
+    }
+}
@@ -0,0 +1,14 @@
+use chrono::{DateTime, Utc};
+use identify_domain::{DomainError, OrganizationQuota, OrganizationQuotaAttrs};
+use identify_macros::ModelRow;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+#[derive(FromRow, ModelRow)]
+#[row(model = OrganizationQuota, error = DomainError)]
+pub struct OrganizationQuotaRow {
+    pub organization_id: Uuid,
+    pub max_users: Option<u32>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
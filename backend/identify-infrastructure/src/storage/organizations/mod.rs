@@ -0,0 +1,259 @@
+mod row;
+
+use async_trait::async_trait;
+use identify_application::{ApplicationError, organization_contracts};
+use identify_domain::{Organization, Slug};
+use sqlx::SqliteConnection;
+use uuid::Uuid;
+
+use crate::storage::{
+    lookup_error,
+    organizations::row::OrganizationRow,
+    queries::{query, query_as, query_scalar},
+    query_error,
+    timing::TimedExt,
+};
+
+pub struct OrganizationsRepository<'a> {
+    conn: &'a mut SqliteConnection,
+}
+
+impl OrganizationsRepository<'_> {
+    pub fn new<'a>(
+        conn: &'a mut SqliteConnection,
+    ) -> OrganizationsRepository<'a> {
+        OrganizationsRepository { conn }
+    }
+}
+
+#[async_trait]
+impl<'a> organization_contracts::Get for OrganizationsRepository<'a> {
+    async fn get(
+        &mut self,
+        id: Uuid,
+    ) -> Result<Organization, ApplicationError> {
+        let organization = query_as!(
+            OrganizationRow,
+            r#"
+                select
+                    id as "id: Uuid",
+                    name,
+                    slug,
+                    external_id,
+                    created_at as "created_at: _",
+                    updated_at as "updated_at: _"
+                from
+                    organizations
+                where
+                    id = (?)
+            "#,
+            id
+        )
+        .fetch_one(&mut *self.conn)
+        .timed("organizations.get")
+        .await
+        .map_err(lookup_error("Organization", id))
+        .map(TryInto::try_into)??;
+
+        Ok(organization)
+    }
+}
+
+#[async_trait]
+impl<'a> organization_contracts::GetByExternalId
+    for OrganizationsRepository<'a>
+{
+    async fn get_by_external_id(
+        &mut self,
+        external_id: &str,
+    ) -> Result<Option<Organization>, ApplicationError> {
+        let row = query_as!(
+            OrganizationRow,
+            r#"
+                select
+                    id as "id: Uuid",
+                    name,
+                    slug,
+                    external_id,
+                    created_at as "created_at: _",
+                    updated_at as "updated_at: _"
+                from
+                    organizations
+                where
+                    external_id = (?)
+            "#,
+            external_id
+        )
+        .fetch_optional(&mut *self.conn)
+        .timed("organizations.get_by_external_id")
+        .await
+        .map_err(query_error)?;
+
+        Ok(row.map(TryInto::try_into).transpose()?)
+    }
+}
+
+#[async_trait]
+impl<'a> organization_contracts::GetBySlug for OrganizationsRepository<'a> {
+    async fn get_by_slug(
+        &mut self,
+        slug: &Slug,
+    ) -> Result<Option<Organization>, ApplicationError> {
+        let slug = slug.as_str();
+        let row = query_as!(
+            OrganizationRow,
+            r#"
+                select
+                    o.id as "id: Uuid",
+                    o.name,
+                    o.slug,
+                    o.external_id,
+                    o.created_at as "created_at: _",
+                    o.updated_at as "updated_at: _"
+                from
+                    organization_slugs s
+                    join organizations o on o.id = s.organization_id
+                where
+                    s.slug = (?)
+            "#,
+            slug
+        )
+        .fetch_optional(&mut *self.conn)
+        .timed("organizations.get_by_slug")
+        .await
+        .map_err(query_error)?;
+
+        Ok(row.map(TryInto::try_into).transpose()?)
+    }
+}
+
+#[async_trait]
+impl<'a> organization_contracts::SlugOwner for OrganizationsRepository<'a> {
+    async fn slug_owner(
+        &mut self,
+        slug: &Slug,
+    ) -> Result<Option<Uuid>, ApplicationError> {
+        let slug = slug.as_str();
+        query_scalar!(
+            r#"
+                select
+                    organization_id as "organization_id: Uuid"
+                from
+                    organization_slugs
+                where
+                    slug = (?)
+            "#,
+            slug
+        )
+        .fetch_optional(&mut *self.conn)
+        .timed("organizations.slug_owner")
+        .await
+        .map_err(query_error)
+    }
+}
+
+#[async_trait]
+impl<'a> organization_contracts::Insert for OrganizationsRepository<'a> {
+    async fn insert(
+        &mut self,
+        entity: &Organization,
+    ) -> Result<(), ApplicationError> {
+        let row: OrganizationRow = entity.into();
+
+        query!(
+            r#"
+                insert into organizations (
+                    id,
+                    name,
+                    slug,
+                    external_id,
+                    created_at,
+                    updated_at
+                ) values (
+                    (?),
+                    (?),
+                    (?),
+                    (?),
+                    (?),
+                    (?)
+                )
+            "#,
+            row.id,
+            row.name,
+            row.slug,
+            row.external_id,
+            row.created_at,
+            row.updated_at
+        )
+        .execute(&mut *self.conn)
+        .timed("organizations.insert")
+        .await
+        .map_err(query_error)?;
+
+        record_slug(&mut *self.conn, &row).await
+    }
+}
+
+#[async_trait]
+impl<'a> organization_contracts::Update for OrganizationsRepository<'a> {
+    async fn update(
+        &mut self,
+        entity: &Organization,
+    ) -> Result<(), ApplicationError> {
+        let row: OrganizationRow = entity.into();
+
+        query!(
+            r#"
+                update organizations
+                set
+                    name = (?),
+                    slug = (?),
+                    external_id = (?),
+                    updated_at = (?)
+                where
+                    id = (?)
+            "#,
+            row.name,
+            row.slug,
+            row.external_id,
+            row.updated_at,
+            row.id
+        )
+        .execute(&mut *self.conn)
+        .timed("organizations.update")
+        .await
+        .map_err(query_error)?;
+
+        record_slug(&mut *self.conn, &row).await
+    }
+}
+
+/// Adds the current slug of the organization to its history, unless it's already there, e.g.
+/// when the organization takes back one of its previous slugs.
+async fn record_slug(
+    conn: &mut SqliteConnection,
+    row: &OrganizationRow,
+) -> Result<(), ApplicationError> {
+    query!(
+        r#"
+            insert into organization_slugs (
+                slug,
+                organization_id,
+                created_at
+            ) values (
+                (?),
+                (?),
+                (?)
+            )
+            on conflict (slug) do nothing
+        "#,
+        row.slug,
+        row.id,
+        row.updated_at
+    )
+    .execute(conn)
+    .timed("organizations.record_slug")
+    .await
+    .map(|_| ())
+    .map_err(query_error)
+}
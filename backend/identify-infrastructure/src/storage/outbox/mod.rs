@@ -0,0 +1,307 @@
+mod row;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use identify_application::{
+    ApplicationError,
+    user_event_contracts::{self, RecordedUserEvent},
+};
+use identify_domain::{UserEvent, UserLifecycleTransition};
+use sqlx::SqliteConnection;
+// Only named by the type overrides of the queries checked at compile time.
+#[cfg_attr(feature = "runtime-queries", allow(unused_imports))]
+use uuid::Uuid;
+
+use crate::storage::{
+    outbox::row::{
+        EventRow, OutboxRow, RecordedEventRow, TransitionCountRow,
+        USER_AGGREGATE,
+    },
+    queries::{query, query_as},
+    query_error,
+    timing::TimedExt,
+};
+
+/// Records events in the transactional outbox, so that they are persisted together with the
+/// changes they announce.
+pub struct OutboxRepository<'a> {
+    conn: &'a mut SqliteConnection,
+}
+
+impl OutboxRepository<'_> {
+    pub fn new<'a>(conn: &'a mut SqliteConnection) -> OutboxRepository<'a> {
+        OutboxRepository { conn }
+    }
+}
+
+#[async_trait]
+impl<'a> user_event_contracts::Emit for OutboxRepository<'a> {
+    async fn emit(
+        &mut self,
+        event: &UserEvent,
+    ) -> Result<bool, ApplicationError> {
+        let row: OutboxRow = event.into();
+
+        query!(
+            r#"
+                insert into outbox (
+                    id,
+                    aggregate_type,
+                    aggregate_id,
+                    event_type,
+                    version,
+                    occurred_at
+                ) values (
+                    (?),
+                    (?),
+                    (?),
+                    (?),
+                    (?),
+                    (?)
+                )
+                on conflict (id) do nothing
+            "#,
+            row.id,
+            row.aggregate_type,
+            row.aggregate_id,
+            row.event_type,
+            row.version,
+            row.occurred_at
+        )
+        .execute(&mut *self.conn)
+        .timed("outbox.insert")
+        .await
+        .map(|result| result.rows_affected() > 0)
+        .map_err(query_error)
+    }
+}
+
+#[async_trait]
+impl<'a> user_event_contracts::Count for OutboxRepository<'a> {
+    async fn count_by_transition(
+        &mut self,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> Result<Vec<(UserLifecycleTransition, u64)>, ApplicationError> {
+        let rows = query_as!(
+            TransitionCountRow,
+            r#"
+                select
+                    event_type,
+                    count(*) as "count!: i64"
+                from
+                    outbox
+                where
+                    aggregate_type = (?)
+                    and occurred_at >= (?)
+                    and occurred_at < (?)
+                group by
+                    event_type
+            "#,
+            USER_AGGREGATE,
+            since,
+            until
+        )
+        .fetch_all(&mut *self.conn)
+        .timed("outbox.count_by_transition")
+        .await
+        .map_err(query_error)?;
+
+        rows.into_iter()
+            .map(|row| Ok((row.event_type.parse()?, row.count as u64)))
+            .collect()
+    }
+}
+
+#[async_trait]
+impl<'a> user_event_contracts::ListUnpublished for OutboxRepository<'a> {
+    async fn list_unpublished(
+        &mut self,
+        limit: u32,
+    ) -> Result<Vec<UserEvent>, ApplicationError> {
+        let rows = query_as!(
+            EventRow,
+            r#"
+                select
+                    id as "id: Uuid",
+                    aggregate_id as "aggregate_id: Uuid",
+                    event_type,
+                    version as "version: u32",
+                    occurred_at as "occurred_at: DateTime<Utc>"
+                from
+                    outbox
+                where
+                    aggregate_type = (?)
+                    and published_at is null
+                order by
+                    occurred_at
+                limit (?)
+            "#,
+            USER_AGGREGATE,
+            limit
+        )
+        .fetch_all(&mut *self.conn)
+        .timed("outbox.list_unpublished")
+        .await
+        .map_err(query_error)?;
+
+        rows.into_iter().map(|row| Ok(row.try_into()?)).collect()
+    }
+}
+
+#[async_trait]
+impl<'a> user_event_contracts::ListAfter for OutboxRepository<'a> {
+    async fn list_after(
+        &mut self,
+        sequence: u64,
+        limit: u32,
+    ) -> Result<Vec<RecordedUserEvent>, ApplicationError> {
+        let rows = sqlx::query_as::<_, RecordedEventRow>(
+            r#"
+                select
+                    sequence,
+                    id,
+                    aggregate_id,
+                    event_type,
+                    version,
+                    occurred_at
+                from
+                    outbox
+                where
+                    aggregate_type = (?)
+                    and sequence > (?)
+                order by
+                    sequence
+                limit (?)
+            "#,
+        )
+        .bind(USER_AGGREGATE)
+        .bind(sequence as i64)
+        .bind(limit)
+        .fetch_all(&mut *self.conn)
+        .timed("outbox.list_after")
+        .await
+        .map_err(query_error)?;
+
+        rows.into_iter().map(|row| Ok(row.try_into()?)).collect()
+    }
+}
+
+#[async_trait]
+impl<'a> user_event_contracts::LastSequence for OutboxRepository<'a> {
+    async fn last_sequence(&mut self) -> Result<u64, ApplicationError> {
+        let sequence: i64 =
+            sqlx::query_scalar("select coalesce(max(sequence), 0) from outbox")
+                .fetch_one(&mut *self.conn)
+                .timed("outbox.last_sequence")
+                .await
+                .map_err(query_error)?;
+
+        Ok(sequence as u64)
+    }
+}
+
+#[async_trait]
+impl<'a> user_event_contracts::MarkPublished for OutboxRepository<'a> {
+    async fn mark_published(
+        &mut self,
+        event: &UserEvent,
+        published_at: DateTime<Utc>,
+    ) -> Result<(), ApplicationError> {
+        let id = event.id();
+
+        query!(
+            r#"
+                update outbox
+                set
+                    published_at = (?)
+                where
+                    id = (?)
+            "#,
+            published_at,
+            id
+        )
+        .execute(&mut *self.conn)
+        .timed("outbox.mark_published")
+        .await
+        .map(|_| ())
+        .map_err(query_error)
+    }
+}
+
+#[async_trait]
+impl<'a> user_event_contracts::ListUnprojected for OutboxRepository<'a> {
+    async fn list_unprojected(
+        &mut self,
+        limit: u32,
+    ) -> Result<Vec<UserEvent>, ApplicationError> {
+        let rows = sqlx::query_as::<_, EventRow>(
+            r#"
+                select
+                    id,
+                    aggregate_id,
+                    event_type,
+                    version,
+                    occurred_at
+                from
+                    outbox
+                where
+                    aggregate_type = (?)
+                    and projected_at is null
+                order by
+                    occurred_at
+                limit (?)
+            "#,
+        )
+        .bind(USER_AGGREGATE)
+        .bind(limit)
+        .fetch_all(&mut *self.conn)
+        .timed("outbox.list_unprojected")
+        .await
+        .map_err(query_error)?;
+
+        rows.into_iter().map(|row| Ok(row.try_into()?)).collect()
+    }
+}
+
+#[async_trait]
+impl<'a> user_event_contracts::MarkProjected for OutboxRepository<'a> {
+    async fn mark_projected(
+        &mut self,
+        event: &UserEvent,
+        projected_at: DateTime<Utc>,
+    ) -> Result<(), ApplicationError> {
+        sqlx::query("update outbox set projected_at = (?) where id = (?)")
+            .bind(projected_at)
+            .bind(event.id())
+            .execute(&mut *self.conn)
+            .timed("outbox.mark_projected")
+            .await
+            .map(|_| ())
+            .map_err(query_error)
+    }
+}
+
+#[async_trait]
+impl<'a> user_event_contracts::PruneHandled for OutboxRepository<'a> {
+    async fn prune_handled(
+        &mut self,
+        before: DateTime<Utc>,
+    ) -> Result<u64, ApplicationError> {
+        sqlx::query(
+            r#"
+                delete from outbox
+                where
+                    published_at is not null
+                    and projected_at is not null
+                    and occurred_at < (?)
+            "#,
+        )
+        .bind(before)
+        .execute(&mut *self.conn)
+        .timed("outbox.prune_handled")
+        .await
+        .map(|result| result.rows_affected())
+        .map_err(query_error)
+    }
+}
@@ -0,0 +1,72 @@
+use chrono::{DateTime, Utc};
+use identify_application::user_event_contracts::RecordedUserEvent;
+use identify_domain::{DomainError, UserEvent, UserEventAttrs};
+use identify_macros::ModelRow;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Type of the aggregate [UserEvents](UserEvent) are about.
+pub const USER_AGGREGATE: &str = "user";
+
+pub struct OutboxRow {
+    pub id: Uuid,
+    pub aggregate_type: String,
+    pub aggregate_id: Uuid,
+    pub event_type: String,
+    pub version: i64,
+    pub occurred_at: DateTime<Utc>,
+}
+
+impl From<&UserEvent> for OutboxRow {
+    fn from(value: &UserEvent) -> Self {
+        let attrs = value.to_attributes();
+
+        OutboxRow {
+            id: attrs.id,
+            aggregate_type: USER_AGGREGATE.to_owned(),
+            aggregate_id: attrs.user_id,
+            event_type: attrs.transition.to_string(),
+            version: attrs.version.into(),
+            occurred_at: attrs.occurred_at,
+        }
+    }
+}
+
+/// An event as it's read back from the outbox.
+#[derive(FromRow, ModelRow)]
+#[row(model = UserEvent, error = DomainError)]
+pub struct EventRow {
+    pub id: Uuid,
+    #[row(field = user_id)]
+    pub aggregate_id: Uuid,
+    #[row(field = transition, text)]
+    pub event_type: String,
+    pub version: u32,
+    pub occurred_at: DateTime<Utc>,
+}
+
+/// Number of events of a transition.
+#[derive(FromRow)]
+pub struct TransitionCountRow {
+    pub event_type: String,
+    pub count: i64,
+}
+
+/// An event read back from the outbox together with its position.
+#[derive(FromRow)]
+pub struct RecordedEventRow {
+    pub sequence: i64,
+    #[sqlx(flatten)]
+    pub event: EventRow,
+}
+
+impl TryFrom<RecordedEventRow> for RecordedUserEvent {
+    type Error = DomainError;
+
+    fn try_from(value: RecordedEventRow) -> Result<Self, Self::Error> {
+        Ok(RecordedUserEvent {
+            sequence: value.sequence as u64,
+            event: value.event.try_into()?,
+        })
+    }
+}
@@ -0,0 +1,162 @@
+mod row;
+
+use async_trait::async_trait;
+use identify_application::{ApplicationError, phone_verification_contracts};
+use identify_domain::PhoneVerification;
+use sqlx::SqliteConnection;
+use uuid::Uuid;
+
+use crate::{
+    encryption::FieldEncryption,
+    storage::{
+        phone_verifications::row::PhoneVerificationRow, query_error,
+        timing::TimedExt,
+    },
+};
+
+/// Stores the verifications of the phone numbers of users.
+pub struct PhoneVerificationsRepository<'a> {
+    conn: &'a mut SqliteConnection,
+    encryption: &'a FieldEncryption,
+}
+
+impl PhoneVerificationsRepository<'_> {
+    pub fn new<'a>(
+        conn: &'a mut SqliteConnection,
+        encryption: &'a FieldEncryption,
+    ) -> PhoneVerificationsRepository<'a> {
+        PhoneVerificationsRepository { conn, encryption }
+    }
+}
+
+#[async_trait]
+impl<'a> phone_verification_contracts::Get
+    for PhoneVerificationsRepository<'a>
+{
+    async fn get(
+        &mut self,
+        id: Uuid,
+    ) -> Result<PhoneVerification, ApplicationError> {
+        let row = sqlx::query_as::<_, PhoneVerificationRow>(
+            r#"
+                select
+                    id,
+                    user_id,
+                    phone_number,
+                    code_hash,
+                    attempts,
+                    expires_at,
+                    created_at,
+                    verified_at
+                from
+                    phone_verifications
+                where
+                    id = (?)
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&mut *self.conn)
+        .timed("phone_verifications.get")
+        .await
+        .map_err(query_error)?
+        .ok_or_else(|| {
+            ApplicationError::entity_not_found("PhoneVerification", id)
+        })?;
+
+        row.open(self.encryption)
+    }
+}
+
+#[async_trait]
+impl<'a> phone_verification_contracts::Insert
+    for PhoneVerificationsRepository<'a>
+{
+    async fn insert(
+        &mut self,
+        entity: &PhoneVerification,
+    ) -> Result<(), ApplicationError> {
+        sqlx::query(
+            r#"
+                insert into phone_verifications (
+                    id,
+                    user_id,
+                    phone_number,
+                    code_hash,
+                    attempts,
+                    expires_at,
+                    created_at,
+                    verified_at
+                ) values (
+                    (?),
+                    (?),
+                    (?),
+                    (?),
+                    (?),
+                    (?),
+                    (?),
+                    (?)
+                )
+            "#,
+        )
+        .bind(entity.id())
+        .bind(entity.user_id())
+        .bind(self.encryption.seal(entity.phone_number()))
+        .bind(entity.code_hash())
+        .bind(entity.attempts())
+        .bind(entity.expires_at())
+        .bind(entity.created_at())
+        .bind(entity.verified_at())
+        .execute(&mut *self.conn)
+        .timed("phone_verifications.insert")
+        .await
+        .map(|_| ())
+        .map_err(query_error)
+    }
+}
+
+#[async_trait]
+impl<'a> phone_verification_contracts::Update
+    for PhoneVerificationsRepository<'a>
+{
+    async fn update(
+        &mut self,
+        entity: &PhoneVerification,
+    ) -> Result<(), ApplicationError> {
+        sqlx::query(
+            r#"
+                update phone_verifications
+                set
+                    attempts = (?),
+                    verified_at = (?)
+                where
+                    id = (?)
+            "#,
+        )
+        .bind(entity.attempts())
+        .bind(entity.verified_at())
+        .bind(entity.id())
+        .execute(&mut *self.conn)
+        .timed("phone_verifications.update")
+        .await
+        .map(|_| ())
+        .map_err(query_error)
+    }
+}
+
+#[async_trait]
+impl<'a> phone_verification_contracts::DeleteAll
+    for PhoneVerificationsRepository<'a>
+{
+    async fn delete_all(
+        &mut self,
+        user_id: Uuid,
+    ) -> Result<u64, ApplicationError> {
+        sqlx::query("delete from phone_verifications where user_id = (?)")
+            .bind(user_id)
+            .execute(&mut *self.conn)
+            .timed("phone_verifications.delete_all")
+            .await
+            .map(|result| result.rows_affected())
+            .map_err(query_error)
+    }
+}
@@ -0,0 +1,53 @@
+use chrono::{DateTime, Utc};
+use identify_application::ApplicationError;
+use identify_domain::{PhoneVerification, PhoneVerificationAttrs};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use crate::encryption::FieldEncryption;
+
+/// A stored phone verification, whose phone number may be encrypted, see [encryption](crate::encryption).
+///
+/// Verifications expire within minutes, so they aren't re-encrypted when the key is rotated.
+#[derive(FromRow)]
+pub struct PhoneVerificationRow {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub phone_number: String,
+    pub code_hash: String,
+    pub attempts: u32,
+    pub expires_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub verified_at: Option<DateTime<Utc>>,
+}
+
+impl PhoneVerificationRow {
+    /// Loads the phone verification of the row, decrypting its fields.
+    pub fn open(
+        self,
+        encryption: &FieldEncryption,
+    ) -> Result<PhoneVerification, ApplicationError> {
+        let phone_number = encryption.open(self.phone_number).map_err(|e| {
+            ApplicationError::internal_with_message(
+                e,
+                format!(
+                    "error while decrypting phone verification {}",
+                    self.id
+                ),
+            )
+        })?;
+
+        let verification = PhoneVerification::load(PhoneVerificationAttrs {
+            id: self.id,
+            user_id: self.user_id,
+            phone_number,
+            code_hash: self.code_hash,
+            attempts: self.attempts,
+            expires_at: self.expires_at,
+            created_at: self.created_at,
+            verified_at: self.verified_at,
+        })?;
+
+        Ok(verification)
+    }
+}
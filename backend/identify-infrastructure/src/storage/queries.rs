@@ -0,0 +1,157 @@
+//! Queries checked against the schema at compile time, or at runtime.
+//!
+//! Repositories write their static queries with the macros of this module, which are
+//! `sqlx::query!` and friends by default. Those check the queries against the metadata in the
+//! `.sqlx` directory of the crate, even if `DATABASE_URL` is set (see `.cargo/config.toml`), so
+//! builds never need a database. After changing a query or a migration, the metadata is
+//! updated against a migrated database with:
+//!
+//! ```sh
+//! cd identify-infrastructure
+//! export DATABASE_URL=sqlite:///tmp/identify.db
+//! sqlx database setup
+//! cargo sqlx prepare
+//! ```
+//!
+//! With the `runtime-queries` feature, the macros build the same queries with `sqlx::query_as`
+//! and friends instead, which the database checks when they're prepared. Builds then need
+//! neither a database nor the metadata, e.g. when the crate is vendored, but a query that
+//! doesn't match the schema only fails once it runs.
+//!
+//! Runtime-checked queries can't infer the types of the columns, so the rows they're read into
+//! must implement [FromRow](sqlx::FromRow), and the overrides in the names of the columns (e.g.
+//! `id as "id: Uuid"`) are stripped from the SQL by [runtime_sql].
+
+#[cfg(feature = "runtime-queries")]
+use std::{
+    collections::HashMap,
+    sync::{LazyLock, Mutex},
+};
+
+/// Same as `sqlx::query!`. The results can only be executed or ignored, since runtime-checked
+/// queries have no records to read the columns from.
+#[cfg(not(feature = "runtime-queries"))]
+macro_rules! query {
+    ($sql:literal $(, $arg:expr)* $(,)?) => {
+        sqlx::query!($sql $(, $arg)*)
+    };
+}
+
+#[cfg(feature = "runtime-queries")]
+macro_rules! query {
+    ($sql:literal $(, $arg:expr)* $(,)?) => {
+        sqlx::query($crate::storage::queries::runtime_sql($sql))
+            $(.bind(&$arg))*
+    };
+}
+
+/// Same as `sqlx::query_as!`.
+#[cfg(not(feature = "runtime-queries"))]
+macro_rules! query_as {
+    ($out:path, $sql:literal $(, $arg:expr)* $(,)?) => {
+        sqlx::query_as!($out, $sql $(, $arg)*)
+    };
+}
+
+#[cfg(feature = "runtime-queries")]
+macro_rules! query_as {
+    ($out:path, $sql:literal $(, $arg:expr)* $(,)?) => {
+        sqlx::query_as::<_, $out>($crate::storage::queries::runtime_sql($sql))
+            $(.bind(&$arg))*
+    };
+}
+
+/// Same as `sqlx::query_file_as!`, with the path relative to the root of the crate.
+#[cfg(not(feature = "runtime-queries"))]
+macro_rules! query_file_as {
+    ($out:path, $path:literal $(, $arg:expr)* $(,)?) => {
+        sqlx::query_file_as!($out, $path $(, $arg)*)
+    };
+}
+
+#[cfg(feature = "runtime-queries")]
+macro_rules! query_file_as {
+    ($out:path, $path:literal $(, $arg:expr)* $(,)?) => {
+        sqlx::query_as::<_, $out>($crate::storage::queries::runtime_sql(
+            include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/", $path)),
+        ))
+        $(.bind(&$arg))*
+    };
+}
+
+/// Same as `sqlx::query_scalar!`. Runtime-checked queries infer the type of the scalar from
+/// how it's used.
+#[cfg(not(feature = "runtime-queries"))]
+macro_rules! query_scalar {
+    ($sql:literal $(, $arg:expr)* $(,)?) => {
+        sqlx::query_scalar!($sql $(, $arg)*)
+    };
+}
+
+#[cfg(feature = "runtime-queries")]
+macro_rules! query_scalar {
+    ($sql:literal $(, $arg:expr)* $(,)?) => {
+        sqlx::query_scalar($crate::storage::queries::runtime_sql($sql))
+            $(.bind(&$arg))*
+    };
+}
+
+pub(crate) use {query, query_as, query_file_as, query_scalar};
+
+/// The SQL of a static query as it's sent to the database.
+///
+/// With the `runtime-queries` feature, the overrides of the names of its columns are stripped,
+/// e.g. `id as "id: Uuid"` becomes `id as "id"`. Queries are only rewritten the first time
+/// they run, and the SQL is cached for the lifetime of the process after that.
+pub(crate) fn runtime_sql(sql: &'static str) -> &'static str {
+    #[cfg(feature = "runtime-queries")]
+    {
+        static REWRITTEN: LazyLock<Mutex<HashMap<&str, &str>>> =
+            LazyLock::new(Mutex::default);
+
+        let mut rewritten = REWRITTEN.lock().unwrap_or_else(|e| e.into_inner());
+        // Static queries are few, so they're leaked rather than reference-counted.
+        rewritten
+            .entry(sql)
+            .or_insert_with(|| Box::leak(strip_overrides(sql).into_boxed_str()))
+    }
+
+    #[cfg(not(feature = "runtime-queries"))]
+    sql
+}
+
+/// Strips everything after the first `:`, `!` or `?` in quoted identifiers. String literals
+/// are kept as they are.
+#[cfg(feature = "runtime-queries")]
+fn strip_overrides(sql: &str) -> String {
+    let mut stripped = String::with_capacity(sql.len());
+    let mut chars = sql.chars();
+    while let Some(c) = chars.next() {
+        stripped.push(c);
+        match c {
+            '\'' => {
+                for c in chars.by_ref() {
+                    stripped.push(c);
+                    if c == '\'' {
+                        break;
+                    }
+                }
+            }
+            '"' => {
+                let identifier = chars
+                    .by_ref()
+                    .take_while(|c| *c != '"')
+                    .collect::<String>();
+                let name = identifier
+                    .split([':', '!', '?'])
+                    .next()
+                    .unwrap_or_default();
+                stripped.push_str(name.trim_end());
+                stripped.push('"');
+            }
+            _ => {}
+        }
+    }
+
+    stripped
+}
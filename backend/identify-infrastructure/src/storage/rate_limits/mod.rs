@@ -0,0 +1,100 @@
+//! Token buckets of the rate limiter, shared by all the processes that use the database.
+
+use sqlx::{FromRow, SqlitePool};
+
+use crate::{
+    Result,
+    storage::{
+        database_error,
+        queries::{query, query_as},
+        timing::TimedExt,
+    },
+};
+
+/// Parameters of a token bucket.
+#[derive(Debug, Clone, Copy)]
+pub struct BucketParams {
+    /// Maximum number of tokens, i.e. the size of a burst. Must be at least 1.
+    pub capacity: f64,
+    /// Number of tokens added every second.
+    pub refill_per_second: f64,
+}
+
+/// State of a bucket after trying to take a token from it.
+#[derive(Debug, Clone, Copy, FromRow)]
+pub struct TakenToken {
+    /// Whether the bucket had a token to take.
+    pub allowed: bool,
+    /// Tokens left in the bucket.
+    pub tokens: f64,
+}
+
+/// Takes a token from the bucket, refilling it for the time since it was last updated first.
+///
+/// `now` is a Unix timestamp in seconds. A bucket that doesn't exist yet starts full. The whole
+/// update is a single statement, so concurrent callers can't take the same token.
+pub async fn take_token(
+    pool: &SqlitePool,
+    key: &str,
+    params: BucketParams,
+    now: f64,
+) -> Result<TakenToken> {
+    let token = query_as!(
+        TakenToken,
+        r#"
+            insert into rate_limit_buckets (
+                key,
+                tokens,
+                allowed,
+                updated_at
+            ) values (
+                ?1,
+                ?2 - 1,
+                true,
+                ?4
+            )
+            on conflict (key) do update set
+                allowed = min(?2, tokens + max(?4 - updated_at, 0) * ?3) >= 1,
+                tokens = min(?2, tokens + max(?4 - updated_at, 0) * ?3)
+                    - (min(?2, tokens + max(?4 - updated_at, 0) * ?3) >= 1),
+                updated_at = max(updated_at, ?4)
+            returning
+                tokens,
+                allowed as "allowed: bool"
+        "#,
+        key,
+        params.capacity,
+        params.refill_per_second,
+        now
+    )
+    .fetch_one(pool)
+    .timed("rate_limits.take")
+    .await
+    .map_err(database_error)?;
+
+    Ok(token)
+}
+
+/// Deletes the buckets that haven't been updated since the provided Unix timestamp.
+///
+/// Buckets that have been idle long enough to be full again are no different from missing
+/// ones, so this keeps the table from growing with every new caller.
+pub async fn delete_idle(
+    pool: &SqlitePool,
+    updated_before: f64,
+) -> Result<u64> {
+    let result = query!(
+        r#"
+            delete from rate_limit_buckets
+            where
+                updated_at < (?)
+        "#,
+        updated_before
+    )
+    .execute(pool)
+    .timed("rate_limits.delete_idle")
+    .await
+    .map_err(database_error)?;
+
+    Ok(result.rows_affected())
+}
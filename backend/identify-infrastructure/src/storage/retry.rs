@@ -0,0 +1,239 @@
+//! Retries of database operations that failed because of a concurrent writer or an
+//! unavailable connection.
+//!
+//! SQLite waits for a locked database for up to the busy timeout, but a transaction that has
+//! to be upgraded to a write while another connection writes fails right away, and so do
+//! transactions whose snapshot is older than a concurrent commit. Such transactions succeed if
+//! they are started over, so they're retried as a whole (see [Retrier::run] and
+//! [Retried](identify_application::Retried)) after a jittered exponential backoff.
+//!
+//! Retries are limited by a budget shared by all operations, which grows with every new
+//! execution, so that a database that stays locked doesn't get flooded with retries.
+
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use identify_application::{ApplicationError, UseCaseRetries};
+use metrics::counter;
+use tokio::time::Instant;
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+use crate::{
+    InfrastructureError,
+    storage::{connection, deadline},
+};
+
+/// Counter of retried database operations, labeled by the operation.
+pub const DB_RETRIES_METRIC: &str = "db_retries_total";
+/// Counter of database operations that failed with an error worth retrying, but weren't
+/// retried anymore, labeled by the operation and the reason (`attempts`, `budget` or
+/// `deadline`).
+pub const DB_RETRIES_EXHAUSTED_METRIC: &str = "db_retries_exhausted_total";
+
+/// How operations that failed with a transient error are retried.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// How many times an operation is attempted at most, including the first attempt. `1`
+    /// disables retries.
+    pub max_attempts: u32,
+    /// Delay after the first failed attempt, doubled after every next one. The actual delay
+    /// is a random duration between half of it and all of it.
+    pub initial_backoff: Duration,
+    /// Upper bound of the delay.
+    pub max_backoff: Duration,
+    /// Retries earned by every execution of an operation, e.g. `0.1` allows one retry for
+    /// every ten executions.
+    pub budget_ratio: f64,
+    /// Retries that can be made before any were earned, and the most that can be saved up.
+    pub budget_reserve: u32,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(20),
+            max_backoff: Duration::from_millis(500),
+            budget_ratio: 0.1,
+            budget_reserve: 10,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Returns the delay after the provided (1-based) failed attempt, without the jitter.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+}
+
+/// Errors of database operations that may succeed when the operation is started over.
+pub trait Retryable {
+    fn is_retryable(&self) -> bool;
+}
+
+/// The database is busy or locked, the transaction conflicted with a concurrent one (see
+/// [RepositoryError](crate::storage::RepositoryError)), or no connection was available in time.
+/// Closed pools don't open again, so they're not worth retrying.
+impl Retryable for sqlx::Error {
+    fn is_retryable(&self) -> bool {
+        !matches!(self, sqlx::Error::PoolClosed)
+            && !deadline::is_exceeded_error(self)
+            && connection::is_transient(self)
+    }
+}
+
+impl Retryable for InfrastructureError {
+    fn is_retryable(&self) -> bool {
+        matches!(self, InfrastructureError::Unavailable(e) if e.is_retryable())
+    }
+}
+
+impl Retryable for ApplicationError {
+    fn is_retryable(&self) -> bool {
+        matches!(self, ApplicationError::Unavailable)
+    }
+}
+
+/// Retries operations according to a [RetryPolicy].
+///
+/// Clones share the retry budget, so a single retrier should be created for the whole process.
+#[derive(Debug, Clone)]
+pub struct Retrier {
+    policy: RetryPolicy,
+    /// Retries left in the budget. Fractions are kept, so that executions earn retries even if
+    /// the ratio is below one.
+    budget: Arc<Mutex<f64>>,
+}
+
+impl Retrier {
+    pub fn new(policy: RetryPolicy) -> Self {
+        Retrier {
+            budget: Arc::new(Mutex::new(f64::from(policy.budget_reserve))),
+            policy,
+        }
+    }
+
+    /// Runs the operation, and runs it again as long as it fails with an error worth retrying.
+    ///
+    /// The operation is named in the metrics and logs, e.g. `transaction.begin`. It has to be
+    /// safe to start over, e.g. a whole transaction or a single statement outside of one.
+    pub async fn run<T, E, F, Fut>(
+        &self,
+        operation: &'static str,
+        mut f: F,
+    ) -> Result<T, E>
+    where
+        E: Retryable + std::fmt::Display,
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        self.record();
+
+        let mut attempt = 1;
+        loop {
+            match f().await {
+                Err(e) if self.wait(operation, attempt, &e).await => {
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Earns the budget for another execution.
+    fn record(&self) {
+        let mut budget = self.budget.lock().unwrap_or_else(|e| e.into_inner());
+        *budget = (*budget + self.policy.budget_ratio)
+            .min(f64::from(self.policy.budget_reserve));
+    }
+
+    /// Waits before the next attempt if the error is worth retrying and the policy, the budget
+    /// and the deadline of the task allow it.
+    async fn wait<E: Retryable + std::fmt::Display>(
+        &self,
+        operation: &'static str,
+        attempt: u32,
+        error: &E,
+    ) -> bool {
+        if !error.is_retryable() {
+            return false;
+        }
+
+        let backoff = jitter(self.policy.backoff(attempt));
+        let exhausted = if attempt >= self.policy.max_attempts {
+            Some("attempts")
+        } else if deadline::current()
+            .is_some_and(|deadline| Instant::now() + backoff >= deadline)
+        {
+            Some("deadline")
+        } else if !self.withdraw() {
+            Some("budget")
+        } else {
+            None
+        };
+
+        if let Some(reason) = exhausted {
+            counter!(DB_RETRIES_EXHAUSTED_METRIC, "operation" => operation, "reason" => reason)
+                .increment(1);
+            warn!(operation, attempt, reason, %error, "Giving up retrying a database operation");
+
+            return false;
+        }
+
+        counter!(DB_RETRIES_METRIC, "operation" => operation).increment(1);
+        debug!(operation, attempt, ?backoff, %error, "Retrying a database operation");
+        tokio::time::sleep(backoff).await;
+
+        true
+    }
+
+    /// Takes a retry out of the budget, if there's one left.
+    fn withdraw(&self) -> bool {
+        let mut budget = self.budget.lock().unwrap_or_else(|e| e.into_inner());
+        if *budget < 1.0 {
+            return false;
+        }
+        *budget -= 1.0;
+
+        true
+    }
+}
+
+#[async_trait]
+impl UseCaseRetries for Retrier {
+    fn record_execution(&self, _use_case: &'static str) {
+        self.record();
+    }
+
+    async fn wait_before_retry(
+        &self,
+        use_case: &'static str,
+        attempt: u32,
+        error: &ApplicationError,
+    ) -> bool {
+        self.wait(use_case, attempt, error).await
+    }
+}
+
+/// Returns a random duration between half of the backoff and all of it, so that operations
+/// that failed together don't retry together.
+fn jitter(backoff: Duration) -> Duration {
+    let half = backoff / 2;
+    match half.as_micros() {
+        0 => backoff,
+        range => {
+            half + Duration::from_micros(
+                (Uuid::new_v4().as_u128() % (range + 1)) as u64,
+            )
+        }
+    }
+}
@@ -0,0 +1,267 @@
+mod row;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use identify_application::{
+    ApplicationError,
+    reverification_contracts::{self, ReverificationProgress},
+};
+use identify_domain::{ReverificationCampaign, User};
+use sqlx::SqliteConnection;
+use uuid::Uuid;
+
+use crate::{
+    encryption::FieldEncryption,
+    storage::{
+        lookup_error,
+        queries::{query, query_as},
+        query_error,
+        reverifications::row::{ProgressRow, ReverificationCampaignRow},
+        timing::TimedExt,
+        users::row::UserRow,
+    },
+};
+
+pub struct ReverificationsRepository<'a> {
+    conn: &'a mut SqliteConnection,
+    encryption: &'a FieldEncryption,
+}
+
+impl ReverificationsRepository<'_> {
+    pub fn new<'a>(
+        conn: &'a mut SqliteConnection,
+        encryption: &'a FieldEncryption,
+    ) -> ReverificationsRepository<'a> {
+        ReverificationsRepository { conn, encryption }
+    }
+}
+
+#[async_trait]
+impl<'a> reverification_contracts::Get for ReverificationsRepository<'a> {
+    async fn get(
+        &mut self,
+        id: Uuid,
+    ) -> Result<ReverificationCampaign, ApplicationError> {
+        let campaign = query_as!(
+            ReverificationCampaignRow,
+            r#"
+                select
+                    id as "id: Uuid",
+                    reason,
+                    email_domain,
+                    created_before as "created_before: _",
+                    created_at as "created_at: _"
+                from
+                    reverification_campaigns
+                where
+                    id = (?)
+            "#,
+            id
+        )
+        .fetch_one(&mut *self.conn)
+        .timed("reverifications.get")
+        .await
+        .map_err(lookup_error("ReverificationCampaign", id))
+        .map(TryInto::try_into)??;
+
+        Ok(campaign)
+    }
+}
+
+#[async_trait]
+impl<'a> reverification_contracts::Insert for ReverificationsRepository<'a> {
+    async fn insert(
+        &mut self,
+        entity: &ReverificationCampaign,
+    ) -> Result<(), ApplicationError> {
+        let row: ReverificationCampaignRow = entity.into();
+
+        query!(
+            r#"
+                insert into reverification_campaigns (
+                    id,
+                    reason,
+                    email_domain,
+                    created_before,
+                    created_at
+                ) values (
+                    (?),
+                    (?),
+                    (?),
+                    (?),
+                    (?)
+                )
+            "#,
+            row.id,
+            row.reason,
+            row.email_domain,
+            row.created_before,
+            row.created_at
+        )
+        .execute(&mut *self.conn)
+        .timed("reverifications.insert")
+        .await
+        .map(|_| ())
+        .map_err(query_error)
+    }
+}
+
+#[async_trait]
+impl<'a> reverification_contracts::FlagCohort
+    for ReverificationsRepository<'a>
+{
+    async fn flag_cohort(
+        &mut self,
+        campaign: &ReverificationCampaign,
+    ) -> Result<u64, ApplicationError> {
+        let row: ReverificationCampaignRow = campaign.into();
+
+        // Domains are stored in the clear, even while the emails are encrypted.
+        let result = query!(
+            r#"
+                insert into reverification_requests (
+                    campaign_id,
+                    user_id,
+                    created_at
+                )
+                select
+                    (?),
+                    id,
+                    (?)
+                from
+                    users
+                where
+                    erased_at is null
+                    and ((?) is null or email_domain = lower((?)))
+                    and ((?) is null or created_at < (?))
+            "#,
+            row.id,
+            row.created_at,
+            row.email_domain,
+            row.email_domain,
+            row.created_before,
+            row.created_before
+        )
+        .execute(&mut *self.conn)
+        .timed("reverifications.flag_cohort")
+        .await
+        .map_err(query_error)?;
+
+        Ok(result.rows_affected())
+    }
+}
+
+#[async_trait]
+impl<'a> reverification_contracts::ListPending
+    for ReverificationsRepository<'a>
+{
+    async fn list_pending(
+        &mut self,
+        campaign_id: Uuid,
+        limit: u32,
+    ) -> Result<Vec<User>, ApplicationError> {
+        let rows = query_as!(
+            UserRow,
+            r#"
+                select
+                    u.id as "id: Uuid",
+                    u.email,
+                    u.username,
+                    u.first_name,
+                    u.last_name,
+                    u.locale,
+                    u.timezone,
+                    u.version as "version: u32",
+                    u.created_at as "created_at: _",
+                    u.updated_at as "updated_at: _",
+                    u.erased_at as "erased_at: _",
+                    u.email_changed_at as "email_changed_at: _",
+                    u.last_login_at as "last_login_at: _",
+                    u.login_count as "login_count: u32"
+                from
+                    reverification_requests r
+                    join users u on u.id = r.user_id
+                where
+                    r.campaign_id = (?)
+                    and r.notified_at is null
+                    and u.erased_at is null
+                order by
+                    u.created_at,
+                    u.id
+                limit (?)
+            "#,
+            campaign_id,
+            limit
+        )
+        .fetch_all(&mut *self.conn)
+        .timed("reverifications.list_pending")
+        .await
+        .map_err(query_error)?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| row.open(self.encryption))
+            .collect::<Result<_, _>>()?)
+    }
+}
+
+#[async_trait]
+impl<'a> reverification_contracts::MarkNotified
+    for ReverificationsRepository<'a>
+{
+    async fn mark_notified(
+        &mut self,
+        campaign_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<(), ApplicationError> {
+        let now = Utc::now();
+        query!(
+            r#"
+                update reverification_requests
+                set
+                    notified_at = (?)
+                where
+                    campaign_id = (?) and user_id = (?)
+            "#,
+            now,
+            campaign_id,
+            user_id
+        )
+        .execute(&mut *self.conn)
+        .timed("reverifications.mark_notified")
+        .await
+        .map(|_| ())
+        .map_err(query_error)
+    }
+}
+
+#[async_trait]
+impl<'a> reverification_contracts::Progress for ReverificationsRepository<'a> {
+    async fn progress(
+        &mut self,
+        campaign_id: Uuid,
+    ) -> Result<ReverificationProgress, ApplicationError> {
+        let counts = query_as!(
+            ProgressRow,
+            r#"
+                select
+                    count(*) as "flagged: i64",
+                    count(notified_at) as "notified: i64"
+                from
+                    reverification_requests
+                where
+                    campaign_id = (?)
+            "#,
+            campaign_id
+        )
+        .fetch_one(&mut *self.conn)
+        .timed("reverifications.progress")
+        .await
+        .map_err(query_error)?;
+
+        Ok(ReverificationProgress {
+            flagged: counts.flagged.try_into().unwrap_or_default(),
+            notified: counts.notified.try_into().unwrap_or_default(),
+        })
+    }
+}
@@ -0,0 +1,53 @@
+use chrono::{DateTime, Utc};
+use identify_domain::{
+    DomainError, ReverificationCampaign, ReverificationCampaignAttrs,
+    ReverificationCohort,
+};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+#[derive(FromRow)]
+pub struct ReverificationCampaignRow {
+    pub id: Uuid,
+    pub reason: String,
+    pub email_domain: Option<String>,
+    pub created_before: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<&ReverificationCampaign> for ReverificationCampaignRow {
+    fn from(value: &ReverificationCampaign) -> Self {
+        let attrs = value.to_attributes();
+
+        ReverificationCampaignRow {
+            id: attrs.id,
+            reason: attrs.reason,
+            email_domain: attrs.cohort.email_domain,
+            created_before: attrs.cohort.created_before,
+            created_at: attrs.created_at,
+        }
+    }
+}
+
+impl TryFrom<ReverificationCampaignRow> for ReverificationCampaign {
+    type Error = DomainError;
+
+    fn try_from(value: ReverificationCampaignRow) -> Result<Self, Self::Error> {
+        ReverificationCampaign::load(ReverificationCampaignAttrs {
+            id: value.id,
+            reason: value.reason,
+            cohort: ReverificationCohort {
+                email_domain: value.email_domain,
+                created_before: value.created_before,
+            },
+            created_at: value.created_at,
+        })
+    }
+}
+
+/// Number of users flagged by a campaign, and of the ones notified so far.
+#[derive(FromRow)]
+pub struct ProgressRow {
+    pub flagged: i64,
+    pub notified: i64,
+}
@@ -0,0 +1,453 @@
+mod row;
+
+use async_trait::async_trait;
+use identify_application::{ApplicationError, role_contracts};
+use identify_domain::{Role, RoleAssignment, RoleCohort, User};
+use sqlx::SqliteConnection;
+use uuid::Uuid;
+
+use crate::{
+    encryption::FieldEncryption,
+    storage::{
+        lookup_error, query_error,
+        roles::row::{RoleAssignmentRow, RoleRow},
+        timing::TimedExt,
+        users::row::UserRow,
+    },
+};
+
+/// Stores the roles and the users they're assigned to.
+pub struct RolesRepository<'a> {
+    conn: &'a mut SqliteConnection,
+    encryption: &'a FieldEncryption,
+}
+
+impl RolesRepository<'_> {
+    pub fn new<'a>(
+        conn: &'a mut SqliteConnection,
+        encryption: &'a FieldEncryption,
+    ) -> RolesRepository<'a> {
+        RolesRepository { conn, encryption }
+    }
+}
+
+#[async_trait]
+impl<'a> role_contracts::Get for RolesRepository<'a> {
+    async fn get(&mut self, id: Uuid) -> Result<Role, ApplicationError> {
+        let role = sqlx::query_as::<_, RoleRow>(
+            r#"
+                select
+                    id,
+                    name,
+                    description,
+                    permissions,
+                    external_id,
+                    created_at,
+                    updated_at
+                from
+                    roles
+                where
+                    id = (?)
+            "#,
+        )
+        .bind(id)
+        .fetch_one(&mut *self.conn)
+        .timed("roles.get")
+        .await
+        .map_err(lookup_error("Role", id))?
+        .try_into()?;
+
+        Ok(role)
+    }
+}
+
+#[async_trait]
+impl<'a> role_contracts::GetByExternalId for RolesRepository<'a> {
+    async fn get_by_external_id(
+        &mut self,
+        external_id: &str,
+    ) -> Result<Option<Role>, ApplicationError> {
+        let row = sqlx::query_as::<_, RoleRow>(
+            r#"
+                select
+                    id,
+                    name,
+                    description,
+                    permissions,
+                    external_id,
+                    created_at,
+                    updated_at
+                from
+                    roles
+                where
+                    external_id = (?)
+            "#,
+        )
+        .bind(external_id)
+        .fetch_optional(&mut *self.conn)
+        .timed("roles.get_by_external_id")
+        .await
+        .map_err(query_error)?;
+
+        Ok(row.map(TryInto::try_into).transpose()?)
+    }
+}
+
+#[async_trait]
+impl<'a> role_contracts::ListAll for RolesRepository<'a> {
+    async fn list_all(&mut self) -> Result<Vec<Role>, ApplicationError> {
+        let rows = sqlx::query_as::<_, RoleRow>(
+            r#"
+                select
+                    id,
+                    name,
+                    description,
+                    permissions,
+                    external_id,
+                    created_at,
+                    updated_at
+                from
+                    roles
+                order by
+                    name
+            "#,
+        )
+        .fetch_all(&mut *self.conn)
+        .timed("roles.list_all")
+        .await
+        .map_err(query_error)?;
+
+        rows.into_iter().map(|row| Ok(row.try_into()?)).collect()
+    }
+}
+
+#[async_trait]
+impl<'a> role_contracts::Insert for RolesRepository<'a> {
+    async fn insert(&mut self, entity: &Role) -> Result<(), ApplicationError> {
+        let row: RoleRow = entity.into();
+
+        sqlx::query(
+            r#"
+                insert into roles (
+                    id,
+                    name,
+                    description,
+                    permissions,
+                    external_id,
+                    created_at,
+                    updated_at
+                ) values (
+                    (?),
+                    (?),
+                    (?),
+                    (?),
+                    (?),
+                    (?),
+                    (?)
+                )
+            "#,
+        )
+        .bind(row.id)
+        .bind(row.name)
+        .bind(row.description)
+        .bind(row.permissions)
+        .bind(row.external_id)
+        .bind(row.created_at)
+        .bind(row.updated_at)
+        .execute(&mut *self.conn)
+        .timed("roles.insert")
+        .await
+        .map(|_| ())
+        .map_err(query_error)
+    }
+}
+
+#[async_trait]
+impl<'a> role_contracts::Update for RolesRepository<'a> {
+    async fn update(&mut self, entity: &Role) -> Result<(), ApplicationError> {
+        let row: RoleRow = entity.into();
+
+        let result = sqlx::query(
+            r#"
+                update roles
+                set
+                    name = (?),
+                    description = (?),
+                    permissions = (?),
+                    updated_at = (?)
+                where
+                    id = (?)
+            "#,
+        )
+        .bind(row.name)
+        .bind(row.description)
+        .bind(row.permissions)
+        .bind(row.updated_at)
+        .bind(row.id)
+        .execute(&mut *self.conn)
+        .timed("roles.update")
+        .await
+        .map_err(query_error)?;
+
+        if result.rows_affected() == 0 {
+            return Err(ApplicationError::entity_not_found("Role", row.id));
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<'a> role_contracts::Delete for RolesRepository<'a> {
+    async fn delete(&mut self, id: Uuid) -> Result<(), ApplicationError> {
+        let result = sqlx::query(
+            r#"
+                delete from roles
+                where
+                    id = (?)
+            "#,
+        )
+        .bind(id)
+        .execute(&mut *self.conn)
+        .timed("roles.delete")
+        .await
+        .map_err(query_error)?;
+
+        if result.rows_affected() == 0 {
+            return Err(ApplicationError::entity_not_found("Role", id));
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<'a> role_contracts::Assign for RolesRepository<'a> {
+    async fn assign(
+        &mut self,
+        assignment: &RoleAssignment,
+    ) -> Result<bool, ApplicationError> {
+        let result = sqlx::query(
+            r#"
+                insert into role_assignments (
+                    role_id,
+                    user_id,
+                    assigned_at
+                ) values (
+                    (?),
+                    (?),
+                    (?)
+                )
+                on conflict (role_id, user_id) do nothing
+            "#,
+        )
+        .bind(assignment.role_id)
+        .bind(assignment.user_id)
+        .bind(assignment.assigned_at)
+        .execute(&mut *self.conn)
+        .timed("roles.assign")
+        .await
+        .map_err(query_error)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+#[async_trait]
+impl<'a> role_contracts::Unassign for RolesRepository<'a> {
+    async fn unassign(
+        &mut self,
+        role_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<bool, ApplicationError> {
+        let result = sqlx::query(
+            r#"
+                delete from role_assignments
+                where
+                    role_id = (?)
+                    and user_id = (?)
+            "#,
+        )
+        .bind(role_id)
+        .bind(user_id)
+        .execute(&mut *self.conn)
+        .timed("roles.unassign")
+        .await
+        .map_err(query_error)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+#[async_trait]
+impl<'a> role_contracts::ListAssignments for RolesRepository<'a> {
+    async fn list_assignments(
+        &mut self,
+        role_id: Uuid,
+    ) -> Result<Vec<RoleAssignment>, ApplicationError> {
+        let rows = sqlx::query_as::<_, RoleAssignmentRow>(
+            r#"
+                select
+                    role_id,
+                    user_id,
+                    assigned_at
+                from
+                    role_assignments
+                where
+                    role_id = (?)
+                order by
+                    assigned_at,
+                    user_id
+            "#,
+        )
+        .bind(role_id)
+        .fetch_all(&mut *self.conn)
+        .timed("roles.list_assignments")
+        .await
+        .map_err(query_error)?;
+
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+}
+
+#[async_trait]
+impl<'a> role_contracts::ListByUser for RolesRepository<'a> {
+    async fn list_by_user(
+        &mut self,
+        user_id: Uuid,
+    ) -> Result<Vec<Role>, ApplicationError> {
+        let rows = sqlx::query_as::<_, RoleRow>(
+            r#"
+                select
+                    roles.id,
+                    roles.name,
+                    roles.description,
+                    roles.permissions,
+                    roles.external_id,
+                    roles.created_at,
+                    roles.updated_at
+                from
+                    roles
+                    join role_assignments on role_assignments.role_id = roles.id
+                where
+                    role_assignments.user_id = (?)
+                order by
+                    roles.name
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&mut *self.conn)
+        .timed("roles.list_by_user")
+        .await
+        .map_err(query_error)?;
+
+        rows.into_iter().map(|row| Ok(row.try_into()?)).collect()
+    }
+}
+
+#[async_trait]
+impl<'a> role_contracts::ListUnassigned for RolesRepository<'a> {
+    async fn list_unassigned(
+        &mut self,
+        role_id: Uuid,
+        cohort: &RoleCohort,
+        limit: u32,
+    ) -> Result<Vec<User>, ApplicationError> {
+        // Domains are stored in the clear, even while the emails are encrypted.
+        let rows = sqlx::query_as::<_, UserRow>(
+            r#"
+                select
+                    u.id,
+                    u.email,
+                    u.username,
+                    u.first_name,
+                    u.last_name,
+                    u.locale,
+                    u.timezone,
+                    u.version,
+                    u.created_at,
+                    u.updated_at,
+                    u.erased_at,
+                    u.email_changed_at,
+                    u.last_login_at,
+                    u.login_count
+                from
+                    users u
+                where
+                    u.erased_at is null
+                    and ((?) is null or u.email_domain = lower((?)))
+                    and (
+                        (?) is null
+                        or exists (
+                            select 1
+                            from memberships m
+                            where m.organization_id = (?) and m.user_id = u.id
+                        )
+                    )
+                    and not exists (
+                        select 1
+                        from role_assignments a
+                        where a.role_id = (?) and a.user_id = u.id
+                    )
+                order by
+                    u.created_at,
+                    u.id
+                limit (?)
+            "#,
+        )
+        .bind(cohort.email_domain())
+        .bind(cohort.email_domain())
+        .bind(cohort.organization_id())
+        .bind(cohort.organization_id())
+        .bind(role_id)
+        .bind(limit)
+        .fetch_all(&mut *self.conn)
+        .timed("roles.list_unassigned")
+        .await
+        .map_err(query_error)?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| row.open(self.encryption))
+            .collect::<Result<_, _>>()?)
+    }
+}
+
+#[async_trait]
+impl<'a> role_contracts::Reassign for RolesRepository<'a> {
+    async fn reassign(
+        &mut self,
+        from: Uuid,
+        to: Uuid,
+    ) -> Result<u64, ApplicationError> {
+        let moved = sqlx::query(
+            r#"
+                update role_assignments
+                set
+                    user_id = (?)
+                where
+                    user_id = (?)
+                    and role_id not in (
+                        select role_id from role_assignments where user_id = (?)
+                    )
+            "#,
+        )
+        .bind(to)
+        .bind(from)
+        .bind(to)
+        .execute(&mut *self.conn)
+        .timed("roles.reassign")
+        .await
+        .map_err(query_error)?
+        .rows_affected();
+
+        sqlx::query("delete from role_assignments where user_id = (?)")
+            .bind(from)
+            .execute(&mut *self.conn)
+            .timed("roles.delete_reassigned")
+            .await
+            .map_err(query_error)?;
+
+        Ok(moved)
+    }
+}
@@ -0,0 +1,74 @@
+use chrono::{DateTime, Utc};
+use identify_domain::{DomainError, Role, RoleAssignment, RoleAttrs};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Separates the permissions of a role.
+const PERMISSION_SEPARATOR: char = ',';
+
+#[derive(FromRow)]
+pub struct RoleRow {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub permissions: String,
+    pub external_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<&Role> for RoleRow {
+    fn from(value: &Role) -> Self {
+        let attrs = value.to_attributes();
+
+        RoleRow {
+            id: attrs.id,
+            name: attrs.name,
+            description: attrs.description,
+            permissions: attrs
+                .permissions
+                .join(&PERMISSION_SEPARATOR.to_string()),
+            external_id: attrs.external_id,
+            created_at: attrs.created_at,
+            updated_at: attrs.updated_at,
+        }
+    }
+}
+
+impl TryFrom<RoleRow> for Role {
+    type Error = DomainError;
+
+    fn try_from(value: RoleRow) -> Result<Self, Self::Error> {
+        Role::load(RoleAttrs {
+            id: value.id,
+            name: value.name,
+            description: value.description,
+            permissions: value
+                .permissions
+                .split(PERMISSION_SEPARATOR)
+                .filter(|permission| !permission.is_empty())
+                .map(str::to_owned)
+                .collect(),
+            external_id: value.external_id,
+            created_at: value.created_at,
+            updated_at: value.updated_at,
+        })
+    }
+}
+
+#[derive(FromRow)]
+pub struct RoleAssignmentRow {
+    pub role_id: Uuid,
+    pub user_id: Uuid,
+    pub assigned_at: DateTime<Utc>,
+}
+
+impl From<RoleAssignmentRow> for RoleAssignment {
+    fn from(value: RoleAssignmentRow) -> Self {
+        RoleAssignment {
+            role_id: value.role_id,
+            user_id: value.user_id,
+            assigned_at: value.assigned_at,
+        }
+    }
+}
@@ -0,0 +1,197 @@
+mod row;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use identify_application::{ApplicationError, signing_key_contracts};
+use identify_domain::{SigningKey, SigningKeyStatus};
+use sqlx::SqliteConnection;
+// Only named by the type overrides of the queries checked at compile time.
+#[cfg_attr(feature = "runtime-queries", allow(unused_imports))]
+use uuid::Uuid;
+
+use crate::{
+    encryption::FieldEncryption,
+    storage::{
+        queries::{query, query_as},
+        query_error,
+        signing_keys::row::SigningKeyRow,
+        timing::TimedExt,
+    },
+};
+
+pub struct SigningKeysRepository<'a> {
+    conn: &'a mut SqliteConnection,
+    encryption: &'a FieldEncryption,
+}
+
+impl SigningKeysRepository<'_> {
+    pub fn new<'a>(
+        conn: &'a mut SqliteConnection,
+        encryption: &'a FieldEncryption,
+    ) -> SigningKeysRepository<'a> {
+        SigningKeysRepository { conn, encryption }
+    }
+}
+
+#[async_trait]
+impl<'a> signing_key_contracts::GetActive for SigningKeysRepository<'a> {
+    async fn get_active(
+        &mut self,
+    ) -> Result<Option<SigningKey>, ApplicationError> {
+        let row = query_as!(
+            SigningKeyRow,
+            r#"
+                select
+                    id as "id: Uuid",
+                    algorithm,
+                    public_key,
+                    private_key,
+                    status,
+                    created_at as "created_at: _",
+                    retired_at as "retired_at: _"
+                from
+                    signing_keys
+                where
+                    status = 'active'
+            "#
+        )
+        .fetch_optional(&mut *self.conn)
+        .timed("signing_keys.get_active")
+        .await
+        .map_err(query_error)?;
+
+        row.map(|row| row.open(self.encryption)).transpose()
+    }
+}
+
+#[async_trait]
+impl<'a> signing_key_contracts::ListPublished for SigningKeysRepository<'a> {
+    async fn list_published(
+        &mut self,
+    ) -> Result<Vec<SigningKey>, ApplicationError> {
+        query_as!(
+            SigningKeyRow,
+            r#"
+                select
+                    id as "id: Uuid",
+                    algorithm,
+                    public_key,
+                    private_key,
+                    status,
+                    created_at as "created_at: _",
+                    retired_at as "retired_at: _"
+                from
+                    signing_keys
+                order by
+                    created_at desc,
+                    id
+            "#
+        )
+        .fetch_all(&mut *self.conn)
+        .timed("signing_keys.list_published")
+        .await
+        .map_err(query_error)?
+        .into_iter()
+        .map(|row| row.open(self.encryption))
+        .collect()
+    }
+}
+
+#[async_trait]
+impl<'a> signing_key_contracts::Insert for SigningKeysRepository<'a> {
+    async fn insert(
+        &mut self,
+        entity: &SigningKey,
+    ) -> Result<(), ApplicationError> {
+        let row = SigningKeyRow::seal(entity, self.encryption);
+
+        query!(
+            r#"
+                insert into signing_keys (
+                    id,
+                    algorithm,
+                    public_key,
+                    private_key,
+                    status,
+                    created_at,
+                    retired_at
+                ) values (
+                    (?),
+                    (?),
+                    (?),
+                    (?),
+                    (?),
+                    (?),
+                    (?)
+                )
+            "#,
+            row.id,
+            row.algorithm,
+            row.public_key,
+            row.private_key,
+            row.status,
+            row.created_at,
+            row.retired_at
+        )
+        .execute(&mut *self.conn)
+        .timed("signing_keys.insert")
+        .await
+        .map(|_| ())
+        .map_err(query_error)
+    }
+}
+
+#[async_trait]
+impl<'a> signing_key_contracts::Update for SigningKeysRepository<'a> {
+    async fn update(
+        &mut self,
+        entity: &SigningKey,
+    ) -> Result<(), ApplicationError> {
+        let id = entity.id();
+        let status = entity.status().as_str();
+        let retired_at = *entity.retired_at();
+
+        query!(
+            r#"
+                update signing_keys
+                set
+                    status = (?),
+                    retired_at = (?)
+                where
+                    id = (?)
+            "#,
+            status,
+            retired_at,
+            id
+        )
+        .execute(&mut *self.conn)
+        .timed("signing_keys.update")
+        .await
+        .map(|_| ())
+        .map_err(query_error)
+    }
+}
+
+#[async_trait]
+impl<'a> signing_key_contracts::PruneRetired for SigningKeysRepository<'a> {
+    async fn prune_retired(
+        &mut self,
+        before: DateTime<Utc>,
+    ) -> Result<u64, ApplicationError> {
+        sqlx::query(
+            r#"
+                delete from signing_keys
+                where
+                    status = (?)
+                    and retired_at < (?)
+            "#,
+        )
+        .bind(SigningKeyStatus::Retired.as_str())
+        .bind(before)
+        .execute(&mut *self.conn)
+        .timed("signing_keys.prune_retired")
+        .await
+        .map(|result| result.rows_affected())
+        .map_err(query_error)
+    }
+}
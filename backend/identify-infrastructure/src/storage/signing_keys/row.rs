@@ -0,0 +1,73 @@
+use base64::{Engine, engine::general_purpose::STANDARD};
+use chrono::{DateTime, Utc};
+use identify_application::ApplicationError;
+use identify_domain::{SigningKey, SigningKeyAttrs};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use crate::encryption::FieldEncryption;
+
+/// A stored signing key, whose private key may be encrypted, see [encryption](crate::encryption).
+#[derive(FromRow)]
+pub struct SigningKeyRow {
+    pub id: Uuid,
+    pub algorithm: String,
+    pub public_key: String,
+    pub private_key: String,
+    pub status: String,
+    pub created_at: DateTime<Utc>,
+    pub retired_at: Option<DateTime<Utc>>,
+}
+
+impl SigningKeyRow {
+    /// Returns the row of the signing key, encrypting its fields.
+    pub fn seal(value: &SigningKey, encryption: &FieldEncryption) -> Self {
+        let attrs = value.to_attributes();
+
+        SigningKeyRow {
+            id: attrs.id,
+            algorithm: attrs.algorithm,
+            public_key: STANDARD.encode(attrs.public_key),
+            private_key: encryption
+                .seal(&STANDARD.encode(attrs.private_key))
+                .into_owned(),
+            status: attrs.status.to_string(),
+            created_at: attrs.created_at,
+            retired_at: attrs.retired_at,
+        }
+    }
+
+    /// Loads the signing key of the row, decrypting its fields.
+    pub fn open(
+        self,
+        encryption: &FieldEncryption,
+    ) -> Result<SigningKey, ApplicationError> {
+        let id = self.id;
+        let decode = |encoded: &str| {
+            STANDARD.decode(encoded).map_err(|e| {
+                ApplicationError::internal_with_message(
+                    e,
+                    format!("error while decoding signing key {id}"),
+                )
+            })
+        };
+        let private_key = encryption.open(self.private_key).map_err(|e| {
+            ApplicationError::internal_with_message(
+                e,
+                format!("error while decrypting signing key {id}"),
+            )
+        })?;
+
+        let key = SigningKey::load(SigningKeyAttrs {
+            id,
+            algorithm: self.algorithm,
+            public_key: decode(&self.public_key)?,
+            private_key: decode(&private_key)?,
+            status: self.status.parse()?,
+            created_at: self.created_at,
+            retired_at: self.retired_at,
+        })?;
+
+        Ok(key)
+    }
+}
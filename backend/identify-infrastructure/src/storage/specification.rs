@@ -0,0 +1,58 @@
+//! Compilation of [Specifications](Specification) to SQL conditions.
+
+use identify_application::Specification;
+use sqlx::{QueryBuilder, Sqlite};
+
+/// Appends the `where` clause matching the specification, compiling its criteria with
+/// `push_criterion`, which must push a single condition.
+pub(crate) fn push_where<'args, C>(
+    builder: &mut QueryBuilder<'args, Sqlite>,
+    spec: &Specification<C>,
+    push_criterion: &dyn Fn(&mut QueryBuilder<'args, Sqlite>, &C),
+) {
+    if !matches!(spec, Specification::All) {
+        builder.push(" where ");
+        push_condition(builder, spec, push_criterion);
+    }
+}
+
+/// Appends the condition of the specification, parenthesized so that it can be combined with
+/// others.
+fn push_condition<'args, C>(
+    builder: &mut QueryBuilder<'args, Sqlite>,
+    spec: &Specification<C>,
+    push_criterion: &dyn Fn(&mut QueryBuilder<'args, Sqlite>, &C),
+) {
+    let mut push_all = |specs: &[Specification<C>], separator, empty| {
+        if specs.is_empty() {
+            builder.push(empty);
+            return;
+        }
+
+        builder.push("(");
+        for (i, spec) in specs.iter().enumerate() {
+            if i > 0 {
+                builder.push(separator);
+            }
+            push_condition(builder, spec, push_criterion);
+        }
+        builder.push(")");
+    };
+
+    match spec {
+        Specification::All => {
+            builder.push("1");
+        }
+        Specification::Criterion(criterion) => {
+            builder.push("(");
+            push_criterion(builder, criterion);
+            builder.push(")");
+        }
+        Specification::And(specs) => push_all(specs, " and ", "1"),
+        Specification::Or(specs) => push_all(specs, " or ", "0"),
+        Specification::Not(spec) => {
+            builder.push("not ");
+            push_condition(builder, spec, push_criterion);
+        }
+    }
+}
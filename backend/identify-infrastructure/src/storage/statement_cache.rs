@@ -0,0 +1,108 @@
+//! Reuse of prepared statements.
+//!
+//! Every connection caches the statements it prepares (up to
+//! [PoolConfig::statement_cache_capacity](crate::storage::connection::PoolConfig)), so a query
+//! is only prepared the first time it runs on the connection. On top of that, the statements of
+//! the hottest queries are prepared as soon as the connection is opened, so requests never pay
+//! for preparing them.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use metrics::counter;
+use sqlx::{Connection, Executor, SqliteConnection};
+use tracing::debug;
+
+use crate::storage::queries;
+
+/// Counter of statements that connections prepared and added to their caches after they were
+/// opened, i.e. the misses of the caches.
+///
+/// Statements prepared by full caches, which evict another one, don't grow them and aren't
+/// counted. The number of executed queries (the count of
+/// [QUERY_DURATION_METRIC](crate::storage::timing::QUERY_DURATION_METRIC)) less this count is
+/// thus an upper bound of the hits.
+pub const STATEMENTS_PREPARED_METRIC: &str = "db_statements_prepared_total";
+/// Counter of statements prepared when connections are opened.
+pub const STATEMENTS_WARMED_UP_METRIC: &str = "db_statements_warmed_up_total";
+
+/// Statements of the hottest queries: getting users and operations, recording the device a
+/// user logs in from before their session is issued, and looking verification tokens up.
+///
+/// Sessions themselves are kept by the [SessionStore](identify_application::SessionStore),
+/// e.g. in Redis, so the statements of the login are the ones of its device.
+///
+/// The cache is keyed by the SQL, so the repositories read it from the same files, e.g. with
+/// `query_file_as!` or `include_str!`, and it's rewritten the same way they do.
+const HOT_STATEMENTS: &[&str] = &[
+    include_str!("users/get.sql"),
+    include_str!("operations/get.sql"),
+    include_str!("devices/get_by_fingerprint.sql"),
+    include_str!("devices/insert.sql"),
+    include_str!("email_verifications/get.sql"),
+];
+
+/// Tracks the statement caches of all the connections of a pool.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct StatementCacheTracker {
+    /// Number of statements cached by every connection, keyed by the address of its SQLite
+    /// handle.
+    ///
+    /// Entries of closed connections are replaced once the address is reused by a new one.
+    sizes: Arc<Mutex<HashMap<usize, usize>>>,
+}
+
+impl StatementCacheTracker {
+    /// Prepares the hot statements on a new connection if `warm_up` is set, and starts
+    /// tracking its cache.
+    ///
+    /// Statements that can't be prepared are skipped, e.g. connections opened before the
+    /// migrations are applied don't have the tables yet. They're prepared on the first use
+    /// instead.
+    pub async fn on_connect(
+        &self,
+        conn: &mut SqliteConnection,
+        warm_up: bool,
+    ) -> Result<(), sqlx::Error> {
+        if warm_up {
+            for statement in HOT_STATEMENTS {
+                match conn.prepare(queries::runtime_sql(statement)).await {
+                    Ok(_) => counter!(STATEMENTS_WARMED_UP_METRIC).increment(1),
+                    Err(e) => {
+                        debug!(error = %e, "Failed to warm up a statement")
+                    }
+                }
+            }
+        }
+
+        let key = handle_key(conn).await?;
+        let size = conn.cached_statements_size();
+        let mut sizes = self.sizes.lock().unwrap_or_else(|e| e.into_inner());
+        sizes.insert(key, size);
+
+        Ok(())
+    }
+
+    /// Counts the statements the connection prepared since it was acquired.
+    pub async fn on_release(
+        &self,
+        conn: &mut SqliteConnection,
+    ) -> Result<(), sqlx::Error> {
+        let key = handle_key(conn).await?;
+        let size = conn.cached_statements_size();
+
+        let mut sizes = self.sizes.lock().unwrap_or_else(|e| e.into_inner());
+        let previous = sizes.insert(key, size).unwrap_or_default();
+        counter!(STATEMENTS_PREPARED_METRIC)
+            .increment(size.saturating_sub(previous) as u64);
+
+        Ok(())
+    }
+}
+
+/// Returns a key that identifies the connection as long as it's open.
+async fn handle_key(conn: &mut SqliteConnection) -> Result<usize, sqlx::Error> {
+    Ok(conn.lock_handle().await?.as_raw_handle().as_ptr().addr())
+}
@@ -0,0 +1,93 @@
+//! State of the service shown on its status page.
+
+use chrono::{DateTime, Utc};
+use sqlx::{FromRow, SqlitePool};
+
+use crate::{
+    Result,
+    storage::{
+        database_error,
+        queries::{query, query_as},
+        timing::TimedExt,
+    },
+};
+
+/// Banner announcing an ongoing incident.
+#[derive(Debug, Clone, PartialEq, Eq, FromRow)]
+pub struct Incident {
+    pub message: String,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Checks that the database answers queries.
+pub async fn ping(pool: &SqlitePool) -> Result<()> {
+    query!("select 1 as one")
+        .fetch_one(pool)
+        .timed("status.ping")
+        .await
+        .map_err(database_error)?;
+
+    Ok(())
+}
+
+/// Returns the current incident, if there is one.
+pub async fn get_incident(pool: &SqlitePool) -> Result<Option<Incident>> {
+    let incident = query_as!(
+        Incident,
+        r#"
+            select
+                message,
+                updated_at as "updated_at: _"
+            from
+                status_incident
+        "#
+    )
+    .fetch_optional(pool)
+    .timed("status.get_incident")
+    .await
+    .map_err(database_error)?;
+
+    Ok(incident)
+}
+
+/// Replaces the current incident, if any, with the provided one.
+pub async fn set_incident(
+    pool: &SqlitePool,
+    incident: &Incident,
+) -> Result<()> {
+    query!(
+        r#"
+            insert into status_incident (
+                id,
+                message,
+                updated_at
+            ) values (
+                1,
+                (?),
+                (?)
+            )
+            on conflict (id) do update set
+                message = excluded.message,
+                updated_at = excluded.updated_at
+        "#,
+        incident.message,
+        incident.updated_at
+    )
+    .execute(pool)
+    .timed("status.set_incident")
+    .await
+    .map_err(database_error)?;
+
+    Ok(())
+}
+
+/// Removes the current incident. Returns `false` if there was none.
+pub async fn clear_incident(pool: &SqlitePool) -> Result<bool> {
+    let result = query!("delete from status_incident")
+        .execute(pool)
+        .timed("status.clear_incident")
+        .await
+        .map_err(database_error)?;
+
+    Ok(result.rows_affected() > 0)
+}
@@ -0,0 +1,117 @@
+//! Databases of the tenants, when every organization gets a database of its own.
+//!
+//! The database of the service keeps the organizations, which are the tenants, and every
+//! organization keeps its users, memberships and the rest of its data in a SQLite file named
+//! after its ID. Tenant databases are opened the first time they're needed and stay open.
+
+use std::{collections::HashMap, path::PathBuf, sync::Arc};
+
+use identify_application::{
+    ApplicationError,
+    organization_contracts::{Get as _, Insert as _, Update as _},
+};
+use identify_domain::Organization;
+use sqlx::SqlitePool;
+use tokio::sync::Mutex;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::storage::{
+    self,
+    connection::{self, PoolConfig},
+    organizations::OrganizationsRepository,
+};
+
+/// Opens the databases of the tenants and keeps their pools.
+///
+/// Clones share the pools, so a single registry should be created for the whole process.
+#[derive(Clone)]
+pub struct TenantRegistry {
+    directory: PathBuf,
+    config: PoolConfig,
+    pools: Arc<Mutex<HashMap<Uuid, SqlitePool>>>,
+}
+
+impl TenantRegistry {
+    /// Creates a registry of the tenant databases in the directory, which are opened with the
+    /// provided pool configuration.
+    pub fn new(directory: impl Into<PathBuf>, config: PoolConfig) -> Self {
+        TenantRegistry {
+            directory: directory.into(),
+            config,
+            pools: Arc::default(),
+        }
+    }
+
+    /// Returns the path of the database of the organization.
+    pub fn path(&self, organization_id: Uuid) -> PathBuf {
+        self.directory.join(format!("{organization_id}.db"))
+    }
+
+    /// Returns the pool of the database of the organization.
+    ///
+    /// The first time, the database is created if it doesn't exist yet and migrated, and the
+    /// organization is copied into it, so that its data can reference it. Later changes of the
+    /// organization reach its database the next time the process opens it.
+    pub async fn pool(
+        &self,
+        organization: &Organization,
+    ) -> Result<SqlitePool, ApplicationError> {
+        // Held while the database is opened, so that concurrent requests open it only once.
+        let mut pools = self.pools.lock().await;
+        if let Some(pool) = pools.get(&organization.id()) {
+            return Ok(pool.clone());
+        }
+
+        tokio::fs::create_dir_all(&self.directory)
+            .await
+            .map_err(ApplicationError::internal)?;
+        let path = self.path(organization.id());
+        let url = format!("sqlite://{}", path.display());
+        let pool = connection::get_pool(&url, &self.config).await?;
+        connection::migrate(&pool).await?;
+        copy_organization(&pool, organization).await?;
+
+        info!(
+            organization_id = %organization.id(),
+            path = %path.display(),
+            "Opened the database of a tenant"
+        );
+        pools.insert(organization.id(), pool.clone());
+
+        Ok(pool)
+    }
+
+    /// Returns the IDs of the organizations whose databases are open.
+    pub async fn opened(&self) -> Vec<Uuid> {
+        self.pools.lock().await.keys().copied().collect()
+    }
+
+    /// Closes the pools of all the tenant databases.
+    pub async fn close(&self) {
+        let pools = std::mem::take(&mut *self.pools.lock().await);
+        for pool in pools.into_values() {
+            pool.close().await;
+        }
+    }
+}
+
+/// Inserts the organization into the tenant database, or updates the copy that is already
+/// there.
+async fn copy_organization(
+    pool: &SqlitePool,
+    organization: &Organization,
+) -> Result<(), ApplicationError> {
+    let mut tx = storage::begin(pool).await?;
+    let mut organizations = OrganizationsRepository::new(&mut tx);
+    match organizations.get(organization.id()).await {
+        Ok(_) => organizations.update(organization).await?,
+        Err(ApplicationError::EntityNotFound { .. }) => {
+            organizations.insert(organization).await?
+        }
+        Err(e) => return Err(e),
+    }
+    storage::commit(tx).await?;
+
+    Ok(())
+}
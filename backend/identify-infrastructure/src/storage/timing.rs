@@ -0,0 +1,119 @@
+use std::{
+    pin::Pin,
+    sync::atomic::{AtomicU64, Ordering},
+    task::{Context, Poll, ready},
+    time::{Duration, Instant},
+};
+
+use metrics::histogram;
+use tokio::time::{Sleep, sleep_until};
+use tracing::{Span, debug, info_span, warn};
+
+use crate::storage::deadline;
+
+/// Histogram of database query latencies, labeled by the query name.
+pub const QUERY_DURATION_METRIC: &str = "db_query_duration_seconds";
+
+/// Queries that take longer than this are logged as warnings, unless set otherwise with
+/// [set_slow_query_threshold].
+pub const DEFAULT_SLOW_QUERY_THRESHOLD: Duration = Duration::from_millis(500);
+
+/// Threshold of slow queries in microseconds, or `0` if they're not logged.
+static SLOW_QUERY_THRESHOLD_MICROS: AtomicU64 =
+    AtomicU64::new(DEFAULT_SLOW_QUERY_THRESHOLD.as_micros() as u64);
+
+/// Sets how long queries of this process can take before they're logged as warnings. `None`
+/// disables the warnings.
+pub fn set_slow_query_threshold(threshold: Option<Duration>) {
+    let micros =
+        threshold.map_or(0, |threshold| (threshold.as_micros() as u64).max(1));
+    SLOW_QUERY_THRESHOLD_MICROS.store(micros, Ordering::Relaxed);
+}
+
+/// Returns how long queries can take before they're logged as warnings, if they are.
+pub fn slow_query_threshold() -> Option<Duration> {
+    match SLOW_QUERY_THRESHOLD_MICROS.load(Ordering::Relaxed) {
+        0 => None,
+        micros => Some(Duration::from_micros(micros)),
+    }
+}
+
+/// Records the latency of database queries and enforces the deadline of the current task.
+pub(crate) trait TimedExt<T>:
+    Future<Output = Result<T, sqlx::Error>> + Sized
+{
+    /// Records how long the query takes under the provided name, e.g. `users.get`.
+    ///
+    /// The query runs within a `db.query` span, so it's accounted for in the latency
+    /// breakdown of the request, and its outcome is logged within the span of the request at
+    /// the debug level, or as a warning if it's slower than the [slow_query_threshold]. Only
+    /// the name of the query is logged, never its parameters. The SQL of its statements is
+    /// logged by sqlx at the trace level, with placeholders in place of the parameters.
+    ///
+    /// The query fails without being sent to the database if the deadline has already
+    /// passed, and it's aborted if the deadline passes while it's running.
+    fn timed(self, query: &'static str) -> Timed<Self> {
+        Timed {
+            future: Box::pin(self),
+            query,
+            span: info_span!("db.query", query),
+            started_at: Instant::now(),
+            deadline: deadline::current()
+                .map(|deadline| Box::pin(sleep_until(deadline))),
+        }
+    }
+}
+
+impl<T, F: Future<Output = Result<T, sqlx::Error>>> TimedExt<T> for F {}
+
+pub(crate) struct Timed<F> {
+    future: Pin<Box<F>>,
+    query: &'static str,
+    span: Span,
+    started_at: Instant,
+    deadline: Option<Pin<Box<Sleep>>>,
+}
+
+impl<T, F: Future<Output = Result<T, sqlx::Error>>> Future for Timed<F> {
+    type Output = F::Output;
+
+    fn poll(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Self::Output> {
+        let span = self.span.clone();
+        let _entered = span.enter();
+
+        if let Some(deadline) = &mut self.deadline
+            && deadline.as_mut().poll(cx).is_ready()
+        {
+            return Poll::Ready(Err(deadline::exceeded_error()));
+        }
+
+        let output = ready!(self.future.as_mut().poll(cx));
+
+        let elapsed = self.started_at.elapsed();
+        histogram!(QUERY_DURATION_METRIC, "query" => self.query)
+            .record(elapsed);
+        log(self.query, elapsed, &output);
+
+        Poll::Ready(output)
+    }
+}
+
+fn log<T>(
+    query: &'static str,
+    elapsed: Duration,
+    output: &Result<T, sqlx::Error>,
+) {
+    if let Some(threshold) = slow_query_threshold()
+        && elapsed >= threshold
+    {
+        warn!(query, ?elapsed, ?threshold, "Slow query");
+    }
+
+    match output {
+        Ok(_) => debug!(query, ?elapsed, "Query executed"),
+        Err(e) => debug!(query, ?elapsed, error = %e, "Query failed"),
+    }
+}
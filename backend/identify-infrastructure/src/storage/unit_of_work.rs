@@ -0,0 +1,179 @@
+use async_trait::async_trait;
+use identify_application::{UnitOfWork, UnitOfWorkFactory};
+use sqlx::{SqlitePool, SqliteTransaction};
+
+use crate::{
+    Result,
+    encryption::FieldEncryption,
+    storage::{
+        self,
+        consents::ConsentsRepository,
+        devices::DevicesRepository,
+        email_aliases::EmailAliasesRepository,
+        email_changes::EmailChangesRepository,
+        email_verifications::EmailVerificationsRepository,
+        invitations::InvitationsRepository,
+        memberships::MembershipsRepository,
+        operations::OperationsRepository,
+        organization_quotas::OrganizationQuotasRepository,
+        organizations::OrganizationsRepository,
+        outbox::OutboxRepository,
+        phone_verifications::PhoneVerificationsRepository,
+        roles::RolesRepository,
+        signing_keys::SigningKeysRepository,
+        user_attributes::UserAttributesRepository,
+        user_summaries::UserSummariesRepository,
+        users::UsersRepository,
+        webhooks::{WebhookDeliveriesRepository, WebhookEndpointsRepository},
+    },
+};
+
+/// [UnitOfWork] whose repositories share a single SQLite transaction.
+///
+/// Every repository borrows the transaction for as long as it's used, so no locking is needed,
+/// and the encryption of the fields it stores.
+pub struct SqliteUnitOfWork {
+    tx: SqliteTransaction<'static>,
+    encryption: FieldEncryption,
+}
+
+impl SqliteUnitOfWork {
+    /// Starts a new transaction.
+    pub async fn begin(
+        pool: &SqlitePool,
+        encryption: &FieldEncryption,
+    ) -> Result<Self> {
+        Ok(SqliteUnitOfWork {
+            tx: storage::begin(pool).await?,
+            encryption: encryption.clone(),
+        })
+    }
+}
+
+#[async_trait]
+impl UnitOfWork for SqliteUnitOfWork {
+    type Users<'a> = UsersRepository<'a>;
+    type UserAttributes<'a> = UserAttributesRepository<'a>;
+    type Devices<'a> = DevicesRepository<'a>;
+    type Consents<'a> = ConsentsRepository<'a>;
+    type EmailVerifications<'a> = EmailVerificationsRepository<'a>;
+    type EmailAliases<'a> = EmailAliasesRepository<'a>;
+    type EmailChanges<'a> = EmailChangesRepository<'a>;
+    type PhoneVerifications<'a> = PhoneVerificationsRepository<'a>;
+    type Organizations<'a> = OrganizationsRepository<'a>;
+    type OrganizationQuotas<'a> = OrganizationQuotasRepository<'a>;
+    type Invitations<'a> = InvitationsRepository<'a>;
+    type Memberships<'a> = MembershipsRepository<'a>;
+    type Roles<'a> = RolesRepository<'a>;
+    type UserEvents<'a> = OutboxRepository<'a>;
+    type UserSummaries<'a> = UserSummariesRepository<'a>;
+    type Operations<'a> = OperationsRepository<'a>;
+    type SigningKeys<'a> = SigningKeysRepository<'a>;
+    type WebhookEndpoints<'a> = WebhookEndpointsRepository<'a>;
+    type WebhookDeliveries<'a> = WebhookDeliveriesRepository<'a>;
+
+    fn users(&mut self) -> UsersRepository<'_> {
+        UsersRepository::new(&mut self.tx, &self.encryption)
+    }
+
+    fn user_attributes(&mut self) -> UserAttributesRepository<'_> {
+        UserAttributesRepository::new(&mut self.tx)
+    }
+
+    fn devices(&mut self) -> DevicesRepository<'_> {
+        DevicesRepository::new(&mut self.tx)
+    }
+
+    fn consents(&mut self) -> ConsentsRepository<'_> {
+        ConsentsRepository::new(&mut self.tx)
+    }
+
+    fn email_verifications(&mut self) -> EmailVerificationsRepository<'_> {
+        EmailVerificationsRepository::new(&mut self.tx)
+    }
+
+    fn email_aliases(&mut self) -> EmailAliasesRepository<'_> {
+        EmailAliasesRepository::new(&mut self.tx, &self.encryption)
+    }
+
+    fn email_changes(&mut self) -> EmailChangesRepository<'_> {
+        EmailChangesRepository::new(&mut self.tx, &self.encryption)
+    }
+
+    fn phone_verifications(&mut self) -> PhoneVerificationsRepository<'_> {
+        PhoneVerificationsRepository::new(&mut self.tx, &self.encryption)
+    }
+
+    fn organizations(&mut self) -> OrganizationsRepository<'_> {
+        OrganizationsRepository::new(&mut self.tx)
+    }
+
+    fn organization_quotas(&mut self) -> OrganizationQuotasRepository<'_> {
+        OrganizationQuotasRepository::new(&mut self.tx)
+    }
+
+    fn invitations(&mut self) -> InvitationsRepository<'_> {
+        InvitationsRepository::new(&mut self.tx, &self.encryption)
+    }
+
+    fn memberships(&mut self) -> MembershipsRepository<'_> {
+        MembershipsRepository::new(&mut self.tx)
+    }
+
+    fn roles(&mut self) -> RolesRepository<'_> {
+        RolesRepository::new(&mut self.tx, &self.encryption)
+    }
+
+    fn user_events(&mut self) -> OutboxRepository<'_> {
+        OutboxRepository::new(&mut self.tx)
+    }
+
+    fn user_summaries(&mut self) -> UserSummariesRepository<'_> {
+        UserSummariesRepository::new(&mut self.tx, &self.encryption)
+    }
+
+    fn operations(&mut self) -> OperationsRepository<'_> {
+        OperationsRepository::new(&mut self.tx)
+    }
+
+    fn signing_keys(&mut self) -> SigningKeysRepository<'_> {
+        SigningKeysRepository::new(&mut self.tx, &self.encryption)
+    }
+
+    fn webhook_endpoints(&mut self) -> WebhookEndpointsRepository<'_> {
+        WebhookEndpointsRepository::new(&mut self.tx, &self.encryption)
+    }
+
+    fn webhook_deliveries(&mut self) -> WebhookDeliveriesRepository<'_> {
+        WebhookDeliveriesRepository::new(&mut self.tx)
+    }
+
+    async fn commit(self) -> identify_application::Result<()> {
+        storage::commit(self.tx).await.map_err(Into::into)
+    }
+}
+
+/// Starts a [SqliteUnitOfWork] on the pool whenever asked to, e.g. for every execution of a
+/// use case wrapped in [InTransaction](identify_application::InTransaction).
+#[derive(Debug, Clone)]
+pub struct SqliteUnitOfWorkFactory {
+    pool: SqlitePool,
+    encryption: FieldEncryption,
+}
+
+impl SqliteUnitOfWorkFactory {
+    pub fn new(pool: SqlitePool, encryption: FieldEncryption) -> Self {
+        SqliteUnitOfWorkFactory { pool, encryption }
+    }
+}
+
+#[async_trait]
+impl UnitOfWorkFactory for SqliteUnitOfWorkFactory {
+    type UnitOfWork = SqliteUnitOfWork;
+
+    async fn begin(&self) -> identify_application::Result<SqliteUnitOfWork> {
+        SqliteUnitOfWork::begin(&self.pool, &self.encryption)
+            .await
+            .map_err(Into::into)
+    }
+}
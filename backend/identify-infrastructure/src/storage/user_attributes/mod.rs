@@ -0,0 +1,177 @@
+mod row;
+
+use async_trait::async_trait;
+use identify_application::{ApplicationError, user_attribute_contracts};
+use identify_domain::UserAttribute;
+use sqlx::SqliteConnection;
+use uuid::Uuid;
+
+use crate::storage::{
+    query_error, timing::TimedExt, user_attributes::row::UserAttributeRow,
+};
+
+/// Stores the custom attributes of users.
+pub struct UserAttributesRepository<'a> {
+    conn: &'a mut SqliteConnection,
+}
+
+impl UserAttributesRepository<'_> {
+    pub fn new<'a>(
+        conn: &'a mut SqliteConnection,
+    ) -> UserAttributesRepository<'a> {
+        UserAttributesRepository { conn }
+    }
+}
+
+#[async_trait]
+impl<'a> user_attribute_contracts::ListByUser for UserAttributesRepository<'a> {
+    async fn list_by_user(
+        &mut self,
+        user_id: Uuid,
+    ) -> Result<Vec<UserAttribute>, ApplicationError> {
+        let rows = sqlx::query_as::<_, UserAttributeRow>(
+            r#"
+                select
+                    user_id,
+                    key,
+                    value_type,
+                    value,
+                    created_at,
+                    updated_at
+                from
+                    user_attributes
+                where
+                    user_id = (?)
+                order by
+                    key
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&mut *self.conn)
+        .timed("user_attributes.list_by_user")
+        .await
+        .map_err(query_error)?;
+
+        rows.into_iter().map(|row| Ok(row.try_into()?)).collect()
+    }
+}
+
+#[async_trait]
+impl<'a> user_attribute_contracts::Upsert for UserAttributesRepository<'a> {
+    async fn upsert(
+        &mut self,
+        entity: &UserAttribute,
+    ) -> Result<(), ApplicationError> {
+        let row: UserAttributeRow = entity.into();
+
+        sqlx::query(
+            r#"
+                insert into user_attributes (
+                    user_id,
+                    key,
+                    value_type,
+                    value,
+                    created_at,
+                    updated_at
+                ) values (
+                    (?),
+                    (?),
+                    (?),
+                    (?),
+                    (?),
+                    (?)
+                )
+                on conflict (user_id, key) do update set
+                    value_type = excluded.value_type,
+                    value = excluded.value,
+                    updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(row.user_id)
+        .bind(row.key)
+        .bind(row.value_type)
+        .bind(row.value)
+        .bind(row.created_at)
+        .bind(row.updated_at)
+        .execute(&mut *self.conn)
+        .timed("user_attributes.upsert")
+        .await
+        .map(|_| ())
+        .map_err(query_error)
+    }
+}
+
+#[async_trait]
+impl<'a> user_attribute_contracts::Delete for UserAttributesRepository<'a> {
+    async fn delete(
+        &mut self,
+        user_id: Uuid,
+        key: &str,
+    ) -> Result<bool, ApplicationError> {
+        sqlx::query(
+            "delete from user_attributes where user_id = (?) and key = (?)",
+        )
+        .bind(user_id)
+        .bind(key)
+        .execute(&mut *self.conn)
+        .timed("user_attributes.delete")
+        .await
+        .map(|result| result.rows_affected() > 0)
+        .map_err(query_error)
+    }
+}
+
+#[async_trait]
+impl<'a> user_attribute_contracts::DeleteAll for UserAttributesRepository<'a> {
+    async fn delete_all(
+        &mut self,
+        user_id: Uuid,
+    ) -> Result<u64, ApplicationError> {
+        sqlx::query("delete from user_attributes where user_id = (?)")
+            .bind(user_id)
+            .execute(&mut *self.conn)
+            .timed("user_attributes.delete_all")
+            .await
+            .map(|result| result.rows_affected())
+            .map_err(query_error)
+    }
+}
+
+#[async_trait]
+impl<'a> user_attribute_contracts::Reassign for UserAttributesRepository<'a> {
+    async fn reassign(
+        &mut self,
+        from: Uuid,
+        to: Uuid,
+    ) -> Result<u64, ApplicationError> {
+        let moved = sqlx::query(
+            r#"
+                update user_attributes
+                set
+                    user_id = (?)
+                where
+                    user_id = (?)
+                    and key not in (
+                        select key from user_attributes where user_id = (?)
+                    )
+            "#,
+        )
+        .bind(to)
+        .bind(from)
+        .bind(to)
+        .execute(&mut *self.conn)
+        .timed("user_attributes.reassign")
+        .await
+        .map_err(query_error)?
+        .rows_affected();
+
+        sqlx::query("delete from user_attributes where user_id = (?)")
+            .bind(from)
+            .execute(&mut *self.conn)
+            .timed("user_attributes.delete_reassigned")
+            .await
+            .map_err(query_error)?;
+
+        Ok(moved)
+    }
+}
@@ -0,0 +1,18 @@
+use chrono::{DateTime, Utc};
+use identify_domain::{DomainError, UserAttribute, UserAttributeAttrs};
+use identify_macros::ModelRow;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+#[derive(FromRow, ModelRow)]
+#[row(model = UserAttribute, error = DomainError)]
+pub struct UserAttributeRow {
+    pub user_id: Uuid,
+    pub key: String,
+    #[row(text)]
+    pub value_type: String,
+    #[row(field = encoded_value)]
+    pub value: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
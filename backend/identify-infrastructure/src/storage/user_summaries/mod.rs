@@ -0,0 +1,324 @@
+//! Read model of users, projected from the user events in the outbox.
+//!
+//! Listings and searches read the summaries straight from the read pool, outside of the
+//! transactions of the write path.
+
+use async_trait::async_trait;
+use futures_util::StreamExt as _;
+use identify_application::{
+    ApplicationError, Paginated, Sort,
+    user_contracts::{
+        self, UserFilter, UserListQuery, UserSearchQuery, UserSortField,
+        UserStream,
+    },
+    user_summary_contracts,
+};
+use identify_domain::User;
+use sqlx::{QueryBuilder, SqliteConnection};
+use uuid::Uuid;
+
+use crate::{
+    encryption::FieldEncryption,
+    storage::{
+        connection::ReadPool,
+        cursor, query_error,
+        timing::TimedExt,
+        users::{
+            query,
+            row::{UserRow, UserRowRef},
+        },
+    },
+};
+
+/// Selects the summaries as [UserRows](UserRow), to be followed by a filter and a sort order.
+const SELECT_SUMMARIES: &str = r#"
+    select
+        id,
+        email,
+        username,
+        first_name,
+        last_name,
+        locale,
+        timezone,
+        version,
+        created_at,
+        updated_at,
+        null as erased_at,
+        email_changed_at,
+        last_login_at,
+        login_count
+    from
+        user_summaries
+"#;
+
+/// Writes the summaries as part of the transaction that projects the events.
+pub struct UserSummariesRepository<'a> {
+    conn: &'a mut SqliteConnection,
+    encryption: &'a FieldEncryption,
+}
+
+impl UserSummariesRepository<'_> {
+    pub fn new<'a>(
+        conn: &'a mut SqliteConnection,
+        encryption: &'a FieldEncryption,
+    ) -> UserSummariesRepository<'a> {
+        UserSummariesRepository { conn, encryption }
+    }
+}
+
+#[async_trait]
+impl<'a> user_summary_contracts::Upsert for UserSummariesRepository<'a> {
+    async fn upsert(&mut self, user: &User) -> Result<(), ApplicationError> {
+        let row = UserRowRef::seal(user, self.encryption);
+
+        // Logins don't emit events, and are recorded in the summaries along with the users, so
+        // the statistics are only copied from the users when their summaries are created.
+        sqlx::query(
+            r#"
+                insert into user_summaries (
+                    id,
+                    email,
+                    email_index,
+                    email_domain,
+                    username,
+                    first_name,
+                    last_name,
+                    locale,
+                    timezone,
+                    version,
+                    created_at,
+                    updated_at,
+                    email_changed_at,
+                    last_login_at,
+                    login_count
+                ) values (
+                    (?),
+                    (?),
+                    (?),
+                    (?),
+                    (?),
+                    (?),
+                    (?),
+                    (?),
+                    (?),
+                    (?),
+                    (?),
+                    (?),
+                    (?),
+                    (select last_login_at from users where id = (?)),
+                    coalesce((select login_count from users where id = (?)), 0)
+                )
+                on conflict (id) do update
+                set
+                    email = excluded.email,
+                    email_index = excluded.email_index,
+                    email_domain = excluded.email_domain,
+                    username = excluded.username,
+                    first_name = excluded.first_name,
+                    last_name = excluded.last_name,
+                    locale = excluded.locale,
+                    timezone = excluded.timezone,
+                    version = excluded.version,
+                    updated_at = excluded.updated_at,
+                    email_changed_at = excluded.email_changed_at
+                where
+                    excluded.version >= user_summaries.version
+            "#,
+        )
+        .bind(row.id)
+        .bind(row.email)
+        .bind(row.email_index)
+        .bind(row.email_domain)
+        .bind(row.username)
+        .bind(row.first_name)
+        .bind(row.last_name)
+        .bind(row.locale)
+        .bind(row.timezone)
+        .bind(row.version)
+        .bind(row.created_at)
+        .bind(row.updated_at)
+        .bind(row.email_changed_at)
+        .bind(row.id)
+        .bind(row.id)
+        .execute(&mut *self.conn)
+        .timed("user_summaries.upsert")
+        .await
+        .map(|_| ())
+        .map_err(query_error)
+    }
+}
+
+#[async_trait]
+impl<'a> user_summary_contracts::Remove for UserSummariesRepository<'a> {
+    async fn remove(&mut self, user_id: Uuid) -> Result<(), ApplicationError> {
+        sqlx::query("delete from user_summaries where id = (?)")
+            .bind(user_id)
+            .execute(&mut *self.conn)
+            .timed("user_summaries.remove")
+            .await
+            .map(|_| ())
+            .map_err(query_error)
+    }
+}
+
+/// Lists and searches users by their summaries.
+///
+/// Every query runs on its own connection of the read pool, outside of any transaction.
+#[derive(Clone)]
+pub struct UserSummariesReader {
+    pool: ReadPool,
+    encryption: FieldEncryption,
+}
+
+impl UserSummariesReader {
+    pub fn new(pool: ReadPool, encryption: FieldEncryption) -> Self {
+        UserSummariesReader { pool, encryption }
+    }
+}
+
+#[async_trait]
+impl user_contracts::List for UserSummariesReader {
+    async fn list(
+        &mut self,
+        query: &UserListQuery,
+    ) -> Result<Paginated<User>, ApplicationError> {
+        let mut count =
+            QueryBuilder::new("select count(*) from user_summaries");
+        query::push_filter(&mut count, &query.filter, &self.encryption);
+
+        let total_items: i64 = count
+            .build_query_scalar()
+            .fetch_one(self.pool.pool())
+            .timed("user_summaries.count")
+            .await
+            .map_err(query_error)?;
+
+        let mut select = QueryBuilder::new(SELECT_SUMMARIES);
+        query::push_filter(&mut select, &query.filter, &self.encryption);
+        query::push_sort(&mut select, &query.sort);
+        select
+            .push(" limit ")
+            .push_bind(i64::from(query.page.size))
+            .push(" offset ")
+            .push_bind(query.page.offset() as i64);
+
+        let items = select
+            .build_query_as::<UserRow>()
+            .fetch_all(self.pool.pool())
+            .timed("user_summaries.list")
+            .await
+            .map_err(query_error)?
+            .into_iter()
+            .map(|row| row.open(&self.encryption))
+            .collect::<Result<Vec<User>, _>>()?;
+
+        Ok(Paginated {
+            items,
+            page: query.page,
+            total_items: total_items as u64,
+        })
+    }
+}
+
+impl user_contracts::Scan for UserSummariesReader {
+    fn scan(
+        &mut self,
+        filter: &UserFilter,
+        sort: &[Sort<UserSortField>],
+    ) -> UserStream {
+        let mut select = QueryBuilder::new(SELECT_SUMMARIES);
+        query::push_filter(&mut select, filter, &self.encryption);
+        query::push_sort(&mut select, sort);
+
+        let encryption = self.encryption.clone();
+        cursor::fetch::<UserRow>(
+            self.pool.pool(),
+            select,
+            "user_summaries.scan",
+        )
+        .map(move |row| row.and_then(|row| row.open(&encryption)))
+        .boxed()
+    }
+}
+
+#[async_trait]
+impl user_contracts::Search for UserSummariesReader {
+    async fn search(
+        &mut self,
+        query: &UserSearchQuery,
+    ) -> Result<Paginated<User>, ApplicationError> {
+        let Some(expression) = query::match_expression(&query.text) else {
+            return Ok(Paginated {
+                items: Vec::new(),
+                page: query.page,
+                total_items: 0,
+            });
+        };
+
+        // The index follows the summaries, so that users are matched by the same emails and
+        // names they're rendered with.
+        let total_items: i64 = sqlx::query_scalar(
+            r#"
+                select
+                    count(*)
+                from
+                    users_search s
+                    join users_search_keys k on k.key = s.rowid
+                    join user_summaries u on u.id = k.user_id
+                where
+                    users_search match (?)
+            "#,
+        )
+        .bind(&expression)
+        .fetch_one(self.pool.pool())
+        .timed("user_summaries.search_count")
+        .await
+        .map_err(query_error)?;
+
+        let items = sqlx::query_as::<_, UserRow>(
+            r#"
+                select
+                    u.id,
+                    u.email,
+                    u.username,
+                    u.first_name,
+                    u.last_name,
+                    u.locale,
+                    u.timezone,
+                    u.version,
+                    u.created_at,
+                    u.updated_at,
+                    null as erased_at,
+                    u.email_changed_at,
+                    u.last_login_at,
+                    u.login_count
+                from
+                    users_search s
+                    join users_search_keys k on k.key = s.rowid
+                    join user_summaries u on u.id = k.user_id
+                where
+                    users_search match (?)
+                order by
+                    bm25(users_search) asc,
+                    u.id asc
+                limit (?) offset (?)
+            "#,
+        )
+        .bind(&expression)
+        .bind(i64::from(query.page.size))
+        .bind(query.page.offset() as i64)
+        .fetch_all(self.pool.pool())
+        .timed("user_summaries.search")
+        .await
+        .map_err(query_error)?
+        .into_iter()
+        .map(|row| row.open(&self.encryption))
+        .collect::<Result<Vec<User>, _>>()?;
+
+        Ok(Paginated {
+            items,
+            page: query.page,
+            total_items: total_items as u64,
+        })
+    }
+}
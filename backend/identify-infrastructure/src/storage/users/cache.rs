@@ -0,0 +1,292 @@
+//! Cache of the users looked up by their IDs.
+//!
+//! Users are cached in the memory of the process by default, where entries are evicted when the
+//! cache is full, starting with the least recently used ones. With the `redis` feature, they can
+//! be cached in Redis instead, so that all the processes of a deployment share them.
+//!
+//! Entries expire after a fixed time to live. The users changed by the events projected in this
+//! process are forgotten right away, see [UserCache::invalidate_events]. Since events are
+//! projected after the changes are committed, and other processes may not share the cache,
+//! cached users are only returned once their version has been checked against the database, see
+//! [CachedUsersRepository].
+
+use std::{
+    num::NonZeroUsize,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use identify_application::{ApplicationError, user_contracts};
+use identify_domain::{User, UserEvent, UserLifecycleTransition};
+use lru::LruCache;
+use metrics::counter;
+use uuid::Uuid;
+
+#[cfg(feature = "redis")]
+use crate::redis::RedisClient;
+
+/// Counter of the lookups of users through the cache, labeled by their result.
+pub const USER_CACHE_LOOKUPS_METRIC: &str = "user_cache_lookups_total";
+
+struct CachedUser {
+    user: User,
+    expires_at: Instant,
+}
+
+/// Users recently looked up by their IDs, shared by all the requests of the process.
+#[derive(Clone)]
+pub struct UserCache {
+    backend: Backend,
+    ttl: Duration,
+}
+
+#[derive(Clone)]
+enum Backend {
+    Disabled,
+    Memory(Arc<Mutex<LruCache<Uuid, CachedUser>>>),
+    #[cfg(feature = "redis")]
+    Redis {
+        client: RedisClient,
+        /// Organization whose tenant database the users are cached for, if not the primary one.
+        tenant: Option<Uuid>,
+    },
+}
+
+impl UserCache {
+    /// Creates a cache of at most `capacity` users in the memory of the process, which are kept
+    /// for `ttl`.
+    ///
+    /// A cache without capacity caches nothing.
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        let backend = match NonZeroUsize::new(capacity) {
+            Some(capacity) => {
+                Backend::Memory(Arc::new(Mutex::new(LruCache::new(capacity))))
+            }
+            None => Backend::Disabled,
+        };
+
+        UserCache { backend, ttl }
+    }
+
+    /// Creates a cache in Redis, whose users are kept for `ttl`. Redis evicts them on its own
+    /// when it runs out of memory, according to its eviction policy.
+    #[cfg(feature = "redis")]
+    pub fn redis(client: RedisClient, ttl: Duration) -> Self {
+        UserCache {
+            backend: Backend::Redis {
+                client,
+                tenant: None,
+            },
+            ttl,
+        }
+    }
+
+    /// Creates a cache that caches nothing.
+    pub fn disabled() -> Self {
+        UserCache::new(0, Duration::ZERO)
+    }
+
+    /// Creates a cache like this one for the users of the tenant database of an organization,
+    /// which are kept apart from those of the primary database and of the other tenants.
+    ///
+    /// A cache in memory starts empty with the same capacity, and a cache in Redis shares the
+    /// server but not the keys.
+    #[cfg_attr(not(feature = "redis"), allow(unused_variables))]
+    pub fn for_tenant(&self, organization_id: Uuid) -> Self {
+        let backend = match &self.backend {
+            Backend::Disabled => Backend::Disabled,
+            Backend::Memory(entries) => Backend::Memory(Arc::new(Mutex::new(
+                LruCache::new(lock(entries).cap()),
+            ))),
+            #[cfg(feature = "redis")]
+            Backend::Redis { client, .. } => Backend::Redis {
+                client: client.clone(),
+                tenant: Some(organization_id),
+            },
+        };
+
+        UserCache {
+            backend,
+            ttl: self.ttl,
+        }
+    }
+
+    /// Forgets the users changed by the events.
+    ///
+    /// Users can't be looked up before they are created, so the events of new users are
+    /// skipped.
+    pub async fn invalidate_events(&self, events: &[UserEvent]) {
+        let user_ids = events
+            .iter()
+            .filter(|event| {
+                event.transition() != UserLifecycleTransition::Created
+            })
+            .map(|event| event.user_id());
+
+        match &self.backend {
+            Backend::Disabled => {}
+            Backend::Memory(entries) => {
+                let mut entries = lock(entries);
+                for user_id in user_ids {
+                    entries.pop(&user_id);
+                }
+            }
+            #[cfg(feature = "redis")]
+            Backend::Redis { client, tenant } => {
+                let keys = user_ids
+                    .map(|user_id| redis_key(client, *tenant, user_id))
+                    .collect::<Vec<_>>();
+                if let Err(e) = client.del(&keys).await {
+                    tracing::warn!(error = %e, "Failed to forget cached users");
+                }
+            }
+        }
+    }
+
+    async fn get(&self, id: Uuid) -> Option<User> {
+        match &self.backend {
+            Backend::Disabled => None,
+            Backend::Memory(entries) => {
+                let mut entries = lock(entries);
+                let cached = entries.get(&id)?;
+                if cached.expires_at <= Instant::now() {
+                    entries.pop(&id);
+                    return None;
+                }
+
+                Some(cached.user.clone())
+            }
+            // A failing cache doesn't fail the lookup, the user is read from the database
+            // instead.
+            #[cfg(feature = "redis")]
+            Backend::Redis { client, tenant } => {
+                match client.get(&redis_key(client, *tenant, id)).await {
+                    Ok(value) => value.and_then(|value| json::decode(&value)),
+                    Err(e) => {
+                        tracing::warn!(error = %e, "Failed to get a cached user");
+                        None
+                    }
+                }
+            }
+        }
+    }
+
+    async fn insert(&self, user: &User) {
+        match &self.backend {
+            Backend::Disabled => {}
+            Backend::Memory(entries) => {
+                lock(entries).put(
+                    user.id(),
+                    CachedUser {
+                        user: user.clone(),
+                        expires_at: Instant::now() + self.ttl,
+                    },
+                );
+            }
+            #[cfg(feature = "redis")]
+            Backend::Redis { client, tenant } => {
+                let key = redis_key(client, *tenant, user.id());
+                if let Err(e) =
+                    client.set_ex(&key, &json::encode(user), self.ttl).await
+                {
+                    tracing::warn!(error = %e, "Failed to cache a user");
+                }
+            }
+        }
+    }
+
+    fn is_disabled(&self) -> bool {
+        matches!(self.backend, Backend::Disabled)
+    }
+}
+
+fn lock(
+    entries: &Mutex<LruCache<Uuid, CachedUser>>,
+) -> MutexGuard<'_, LruCache<Uuid, CachedUser>> {
+    entries.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Returns the key of a user, e.g. `identify:users:<id>`, or
+/// `identify:tenants:<organization id>:users:<id>` for a tenant.
+#[cfg(feature = "redis")]
+fn redis_key(
+    client: &RedisClient,
+    tenant: Option<Uuid>,
+    user_id: Uuid,
+) -> String {
+    match tenant {
+        Some(organization_id) => {
+            client.key(&format!("tenants:{organization_id}:users:{user_id}"))
+        }
+        None => client.key(&format!("users:{user_id}")),
+    }
+}
+
+/// Users are cached in Redis as JSON objects of their attributes.
+#[cfg(feature = "redis")]
+mod json {
+    use identify_domain::User;
+
+    pub(super) fn encode(user: &User) -> Vec<u8> {
+        serde_json::to_vec(&user.to_attributes())
+            .expect("attributes of users are serializable")
+    }
+
+    /// Decodes a cached user, or returns `None` if it's invalid, e.g. because it was cached by
+    /// another version of the server.
+    pub(super) fn decode(value: &[u8]) -> Option<User> {
+        User::load(serde_json::from_slice(value).ok()?).ok()
+    }
+}
+
+/// Looks users up in the [UserCache] before asking the decorated repository.
+///
+/// A cached user is returned only if the decorated repository still has the same version of
+/// them, which saves loading the whole user but not a lookup. The decorated repository should
+/// thus read from the primary database, so that the users it returns are current.
+pub struct CachedUsersRepository<R> {
+    inner: R,
+    cache: UserCache,
+}
+
+impl<R> CachedUsersRepository<R> {
+    pub fn new(inner: R, cache: UserCache) -> Self {
+        CachedUsersRepository { inner, cache }
+    }
+
+    /// Returns the decorated repository.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+#[async_trait]
+impl<R> user_contracts::Get for CachedUsersRepository<R>
+where
+    R: user_contracts::Get + user_contracts::GetVersion + Send,
+{
+    async fn get(&mut self, id: Uuid) -> Result<User, ApplicationError> {
+        if self.cache.is_disabled() {
+            return self.inner.get(id).await;
+        }
+
+        if let Some(user) = self.cache.get(id).await {
+            if self.inner.get_version(id).await? == user.version() {
+                counter!(USER_CACHE_LOOKUPS_METRIC, "result" => "hit")
+                    .increment(1);
+                return Ok(user);
+            }
+            counter!(USER_CACHE_LOOKUPS_METRIC, "result" => "stale")
+                .increment(1);
+        } else {
+            counter!(USER_CACHE_LOOKUPS_METRIC, "result" => "miss")
+                .increment(1);
+        }
+
+        let user = self.inner.get(id).await?;
+        self.cache.insert(&user).await;
+
+        Ok(user)
+    }
+}
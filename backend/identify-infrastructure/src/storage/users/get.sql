@@ -0,0 +1,19 @@
+select
+    id as "id: Uuid",
+    email,
+    username,
+    first_name,
+    last_name,
+    locale,
+    timezone,
+    version as "version: u32",
+    created_at as "created_at: _",
+    updated_at as "updated_at: _",
+    erased_at as "erased_at: _",
+    email_changed_at as "email_changed_at: _",
+    last_login_at as "last_login_at: _",
+    login_count as "login_count: u32"
+from
+    users
+where
+    id = (?)
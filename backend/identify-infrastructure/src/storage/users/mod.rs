@@ -1,71 +1,451 @@
-mod row;
+pub mod cache;
+pub(crate) mod query;
+pub mod reencrypt;
+pub(crate) mod row;
 
 use async_trait::async_trait;
-use eyre::eyre;
-use identify_application::{ApplicationError, user_contracts};
-use identify_domain::User;
+use chrono::{DateTime, Utc};
+use futures_util::StreamExt as _;
+use identify_application::{
+    ApplicationError, Paginated, Sort,
+    user_contracts::{
+        self, LoginTally, UserFilter, UserListQuery, UserSortField, UserSpec,
+        UserSpecQuery, UserStatus, UserStream,
+    },
+};
+use identify_domain::{User, UserId, UserIdAttrs, Username};
+use sqlx::{QueryBuilder, SqliteConnection, SqlitePool};
 use uuid::Uuid;
 
-use crate::storage::{SharedTransaction, users::row::UserRow};
+use crate::{
+    encryption::FieldEncryption,
+    storage::{
+        RepositoryError, cursor, lookup_error,
+        queries::{query, query_file_as, query_scalar},
+        query_error, specification,
+        timing::TimedExt,
+        users::row::{UserRow, UserRowRef},
+    },
+};
+
+/// Most parameters SQLite allows to be bound to a single statement.
+const MAX_BINDS: usize = 32766;
+/// Number of columns written by every inserted user.
+const USER_COLUMNS: usize = 12;
+/// Number of columns bound by every email checked by [ExistsByEmails](user_contracts::ExistsByEmails).
+const EMAIL_COLUMNS: usize = 5;
+/// Number of columns bound by every recorded [LoginTally].
+const TALLY_COLUMNS: usize = 3;
+
+/// Selects the users as [UserRows](UserRow), to be followed by a filter and a sort order.
+const SELECT_USERS: &str = r#"
+    select
+        id,
+        email,
+        username,
+        first_name,
+        last_name,
+        locale,
+        timezone,
+        version,
+        created_at,
+        updated_at,
+        erased_at,
+        email_changed_at,
+        last_login_at,
+        login_count
+    from
+        users
+"#;
 
 pub struct UsersRepository<'a> {
-    tx: SharedTransaction<'a>,
+    conn: &'a mut SqliteConnection,
+    encryption: &'a FieldEncryption,
 }
 
 impl UsersRepository<'_> {
-    pub fn new<'a>(tx: SharedTransaction<'a>) -> UsersRepository<'a> {
-        UsersRepository { tx }
+    pub fn new<'a>(
+        conn: &'a mut SqliteConnection,
+        encryption: &'a FieldEncryption,
+    ) -> UsersRepository<'a> {
+        UsersRepository { conn, encryption }
     }
 }
 
 #[async_trait]
 impl<'a> user_contracts::Get for UsersRepository<'a> {
-    async fn get(&self, id: Uuid) -> Result<User, ApplicationError> {
-        let mut tx = self.tx.lock().await;
+    async fn get(&mut self, id: Uuid) -> Result<User, ApplicationError> {
+        let user = query_file_as!(UserRow, "src/storage/users/get.sql", id)
+            .fetch_one(&mut *self.conn)
+            .timed("users.get")
+            .await
+            .map_err(lookup_error("User", id))
+            .map(|row| row.open(self.encryption))??;
+
+        Ok(user)
+    }
+}
 
-        let user = sqlx::query_as!(
+#[async_trait]
+impl<'a> user_contracts::GetVersion for UsersRepository<'a> {
+    async fn get_version(&mut self, id: Uuid) -> Result<u32, ApplicationError> {
+        let version = query_scalar!(
+            r#"select version as "version: u32" from users where id = (?)"#,
+            id
+        )
+        .fetch_one(&mut *self.conn)
+        .timed("users.get_version")
+        .await
+        .map_err(lookup_error("User", id))?;
+
+        Ok(version)
+    }
+}
+
+#[async_trait]
+impl<'a> user_contracts::GetByUsername for UsersRepository<'a> {
+    async fn get_by_username(
+        &mut self,
+        username: &Username,
+    ) -> Result<User, ApplicationError> {
+        let username = username.as_str();
+        let user = query_file_as!(
             UserRow,
+            "src/storage/users/get_by_username.sql",
+            username
+        )
+        .fetch_optional(&mut *self.conn)
+        .timed("users.get_by_username")
+        .await
+        .map_err(query_error)?
+        .ok_or_else(|| ApplicationError::entity_not_found("User", username))?
+        .open(self.encryption)?;
+
+        Ok(user)
+    }
+}
+
+#[async_trait]
+impl<'a> user_contracts::GetByEmail for UsersRepository<'a> {
+    async fn get_by_email(
+        &mut self,
+        email: &str,
+    ) -> Result<User, ApplicationError> {
+        let mut select = QueryBuilder::new(SELECT_USERS);
+        specification::push_where(
+            &mut select,
+            &UserSpec::by_email(email)
+                .and(UserSpec::by_status(UserStatus::Active)),
+            &|builder, criterion| {
+                query::push_criterion(builder, criterion, self.encryption)
+            },
+        );
+
+        let user = select
+            .build_query_as::<UserRow>()
+            .fetch_optional(&mut *self.conn)
+            .timed("users.get_by_email")
+            .await
+            .map_err(query_error)?
+            // The email is personal data, which is kept out of the responses and the logs.
+            .ok_or_else(|| {
+                ApplicationError::entity_not_found("User", "[redacted]")
+            })?
+            .open(self.encryption)?;
+
+        Ok(user)
+    }
+}
+
+#[async_trait]
+impl<'a> user_contracts::GetMany for UsersRepository<'a> {
+    async fn get_many(
+        &mut self,
+        ids: &[Uuid],
+    ) -> Result<Vec<User>, ApplicationError> {
+        let mut select = QueryBuilder::new(
             r#"
                 select
-                    id as "id: Uuid",
+                    id,
                     email,
+                    username,
                     first_name,
                     last_name,
-                    created_at as "created_at: _",
-                    updated_at as "updated_at: _"
+                    locale,
+                    timezone,
+                    version,
+                    created_at,
+                    updated_at,
+                    erased_at,
+                    email_changed_at,
+                    last_login_at,
+                    login_count
                 from
                     users
                 where
-                    id = (?)
+                    id in (
             "#,
-            id
+        );
+        let mut separated = select.separated(", ");
+        for id in ids {
+            separated.push_bind(*id);
+        }
+        select.push(")");
+
+        let users = select
+            .build_query_as::<UserRow>()
+            .fetch_all(&mut *self.conn)
+            .timed("users.get_many")
+            .await
+            .map_err(query_error)?
+            .into_iter()
+            .map(|row| row.open(self.encryption))
+            .collect::<Result<Vec<User>, _>>()?;
+
+        Ok(users)
+    }
+}
+
+#[async_trait]
+impl<'a> user_contracts::List for UsersRepository<'a> {
+    async fn list(
+        &mut self,
+        query: &UserListQuery,
+    ) -> Result<Paginated<User>, ApplicationError> {
+        let mut count = QueryBuilder::new("select count(*) from users");
+        query::push_filter(&mut count, &query.filter, self.encryption);
+
+        let total_items: i64 = count
+            .build_query_scalar()
+            .fetch_one(&mut *self.conn)
+            .timed("users.count")
+            .await
+            .map_err(query_error)?;
+
+        let mut select = QueryBuilder::new(SELECT_USERS);
+        query::push_filter(&mut select, &query.filter, self.encryption);
+        query::push_sort(&mut select, &query.sort);
+        select
+            .push(" limit ")
+            .push_bind(i64::from(query.page.size))
+            .push(" offset ")
+            .push_bind(query.page.offset() as i64);
+
+        let items = select
+            .build_query_as::<UserRow>()
+            .fetch_all(&mut *self.conn)
+            .timed("users.list")
+            .await
+            .map_err(query_error)?
+            .into_iter()
+            .map(|row| row.open(self.encryption))
+            .collect::<Result<Vec<User>, _>>()?;
+
+        Ok(Paginated {
+            items,
+            page: query.page,
+            total_items: total_items as u64,
+        })
+    }
+}
+
+#[async_trait]
+impl<'a> user_contracts::FindBySpec for UsersRepository<'a> {
+    async fn find_by_spec(
+        &mut self,
+        query: &UserSpecQuery,
+    ) -> Result<Paginated<User>, ApplicationError> {
+        let total_items =
+            user_contracts::Count::count(self, &query.filter).await?;
+
+        let mut select = QueryBuilder::new(SELECT_USERS);
+        specification::push_where(
+            &mut select,
+            &query.filter,
+            &|builder, criterion| {
+                query::push_criterion(builder, criterion, self.encryption)
+            },
+        );
+        query::push_sort(&mut select, &query.sort);
+        select
+            .push(" limit ")
+            .push_bind(i64::from(query.page.size))
+            .push(" offset ")
+            .push_bind(query.page.offset() as i64);
+
+        let items = select
+            .build_query_as::<UserRow>()
+            .fetch_all(&mut *self.conn)
+            .timed("users.find_by_spec")
+            .await
+            .map_err(query_error)?
+            .into_iter()
+            .map(|row| row.open(self.encryption))
+            .collect::<Result<Vec<User>, _>>()?;
+
+        Ok(Paginated {
+            items,
+            page: query.page,
+            total_items,
+        })
+    }
+}
+
+#[async_trait]
+impl<'a> user_contracts::Count for UsersRepository<'a> {
+    async fn count(
+        &mut self,
+        spec: &UserSpec,
+    ) -> Result<u64, ApplicationError> {
+        let mut count = QueryBuilder::new("select count(*) from users");
+        specification::push_where(&mut count, spec, &|builder, criterion| {
+            query::push_criterion(builder, criterion, self.encryption)
+        });
+
+        let count: i64 = count
+            .build_query_scalar()
+            .fetch_one(&mut *self.conn)
+            .timed("users.count_by_spec")
+            .await
+            .map_err(query_error)?;
+
+        Ok(count as u64)
+    }
+}
+
+#[async_trait]
+impl<'a> user_contracts::Exists for UsersRepository<'a> {
+    async fn exists(
+        &mut self,
+        spec: &UserSpec,
+    ) -> Result<bool, ApplicationError> {
+        let mut exists =
+            QueryBuilder::new("select exists (select 1 from users");
+        specification::push_where(&mut exists, spec, &|builder, criterion| {
+            query::push_criterion(builder, criterion, self.encryption)
+        });
+        exists.push(")");
+
+        let exists: bool = exists
+            .build_query_scalar()
+            .fetch_one(&mut *self.conn)
+            .timed("users.exists")
+            .await
+            .map_err(query_error)?;
+
+        Ok(exists)
+    }
+}
+
+#[async_trait]
+impl<'a> user_contracts::ExistsByEmails for UsersRepository<'a> {
+    async fn exists_by_emails(
+        &mut self,
+        emails: &[&str],
+    ) -> Result<Vec<bool>, ApplicationError> {
+        let chunk_size = MAX_BINDS / EMAIL_COLUMNS;
+        let mut exists = vec![false; emails.len()];
+
+        for (n, chunk) in emails.chunks(chunk_size).enumerate() {
+            let start = n * chunk_size;
+            // Every email is matched like by `UserCriterion::ByEmail`, through the indexes of
+            // the users and aliases.
+            let mut select = QueryBuilder::new(
+                "with requested (position, id, id_v1, email, email_index) as (",
+            );
+            select.push_values(
+                chunk.iter().enumerate(),
+                |mut values, (i, email)| {
+                    let id = UserId::new(UserIdAttrs {
+                        email: (*email).to_owned(),
+                    });
+                    values
+                        .push_bind((start + i) as i64)
+                        .push_bind(id.to_uuid())
+                        .push_bind(id.to_uuid_v1())
+                        .push_bind(*email)
+                        .push_bind(self.encryption.email_index(email));
+                },
+            );
+            select.push(
+                r#"
+                    )
+                    select r.position
+                    from requested r
+                    where
+                        exists (
+                            select 1 from users u
+                            where
+                                u.id in (r.id, r.id_v1)
+                                or lower(u.email) = lower(trim(r.email))
+                                or u.email_index = r.email_index
+                        )
+                        or exists (select 1 from email_aliases a where a.id = r.id)
+                "#,
+            );
+
+            let taken: Vec<i64> = select
+                .build_query_scalar()
+                .fetch_all(&mut *self.conn)
+                .timed("users.exists_by_emails")
+                .await
+                .map_err(query_error)?;
+            for position in taken {
+                exists[position as usize] = true;
+            }
+        }
+
+        Ok(exists)
+    }
+}
+
+#[async_trait]
+impl<'a> user_contracts::ExistsByUsername for UsersRepository<'a> {
+    async fn exists_by_username(
+        &mut self,
+        username: &Username,
+    ) -> Result<bool, ApplicationError> {
+        let username = username.as_str();
+        let exists = query_scalar!(
+            r#"select exists(select 1 from users where username = (?)) as "exists: bool""#,
+            username
         )
-        .fetch_one(tx.as_mut())
+        .fetch_one(&mut *self.conn)
+        .timed("users.exists_by_username")
         .await
-        .map_err(|e| ApplicationError::internal(eyre!(e)))
-        .map(TryInto::try_into)??;
+        .map_err(query_error)?;
 
-        Ok(user)
+        Ok(exists)
     }
 }
 
 #[async_trait]
 impl<'a> user_contracts::Insert for UsersRepository<'a> {
-    async fn insert(&self, entity: &User) -> Result<(), ApplicationError> {
-        let mut tx = self.tx.lock().await;
-
-        let row: UserRow = entity.into();
+    async fn insert(&mut self, entity: &User) -> Result<(), ApplicationError> {
+        let row = UserRowRef::seal(entity, self.encryption);
 
-        sqlx::query!(
+        query!(
             r#"
                 insert into users (
                     id,
                     email,
+                    email_index,
+                    email_domain,
+                    username,
                     first_name,
                     last_name,
+                    locale,
+                    timezone,
+                    version,
                     created_at,
                     updated_at
                 ) values (
+                    (?),
+                    (?),
+                    (?),
+                    (?),
+                    (?),
+                    (?),
                     (?),
                     (?),
                     (?),
@@ -76,22 +456,387 @@ impl<'a> user_contracts::Insert for UsersRepository<'a> {
             "#,
             row.id,
             row.email,
+            row.email_index,
+            row.email_domain,
+            row.username,
             row.first_name,
             row.last_name,
+            row.locale,
+            row.timezone,
+            row.version,
             row.created_at,
             row.updated_at
         )
-        .execute(tx.as_mut())
+        .execute(&mut *self.conn)
+        .timed("users.insert")
         .await
         .map(|_| ())
-        .map_err(|e| match e.as_database_error() {
-            Some(db_error) if db_error.is_unique_violation() => {
-                ApplicationError::entity_already_exists(
-                    "User",
-                    "Email is already taken",
+        .map_err(insert_error)
+    }
+}
+
+#[async_trait]
+impl<'a> user_contracts::InsertMany for UsersRepository<'a> {
+    async fn insert_many(
+        &mut self,
+        entities: &[User],
+    ) -> Result<(), ApplicationError> {
+        for chunk in entities.chunks(MAX_BINDS / USER_COLUMNS) {
+            let mut insert = QueryBuilder::new(
+                r#"
+                    insert into users (
+                        id,
+                        email,
+                        email_index,
+                        email_domain,
+                        username,
+                        first_name,
+                        last_name,
+                        locale,
+                        timezone,
+                        version,
+                        created_at,
+                        updated_at
+                    )
+                "#,
+            );
+            insert.push_values(chunk, |mut values, entity| {
+                let row = UserRowRef::seal(entity, self.encryption);
+                values
+                    .push_bind(row.id)
+                    .push_bind(row.email)
+                    .push_bind(row.email_index)
+                    .push_bind(row.email_domain)
+                    .push_bind(row.username)
+                    .push_bind(row.first_name)
+                    .push_bind(row.last_name)
+                    .push_bind(row.locale)
+                    .push_bind(row.timezone)
+                    .push_bind(row.version)
+                    .push_bind(row.created_at)
+                    .push_bind(row.updated_at);
+            });
+
+            insert
+                .build()
+                .execute(&mut *self.conn)
+                .timed("users.insert_many")
+                .await
+                .map_err(insert_error)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<'a> user_contracts::Update for UsersRepository<'a> {
+    async fn update(&mut self, entity: &User) -> Result<(), ApplicationError> {
+        let row = UserRowRef::seal(entity, self.encryption);
+
+        query!(
+            r#"
+                update users
+                set
+                    first_name = (?),
+                    last_name = (?),
+                    locale = (?),
+                    timezone = (?),
+                    version = (?),
+                    updated_at = (?)
+                where
+                    id = (?)
+            "#,
+            row.first_name,
+            row.last_name,
+            row.locale,
+            row.timezone,
+            row.version,
+            row.updated_at,
+            row.id
+        )
+        .execute(&mut *self.conn)
+        .timed("users.update")
+        .await
+        .map(|_| ())
+        .map_err(query_error)
+    }
+}
+
+#[async_trait]
+impl<'a> user_contracts::ChangeEmail for UsersRepository<'a> {
+    async fn change_email(
+        &mut self,
+        entity: &User,
+    ) -> Result<(), ApplicationError> {
+        let row = UserRowRef::seal(entity, self.encryption);
+
+        sqlx::query(
+            r#"
+                update users
+                set
+                    email = (?),
+                    email_index = (?),
+                    email_domain = (?),
+                    version = (?),
+                    updated_at = (?),
+                    email_changed_at = (?)
+                where
+                    id = (?)
+            "#,
+        )
+        .bind(row.email)
+        .bind(row.email_index)
+        .bind(row.email_domain)
+        .bind(row.version)
+        .bind(row.updated_at)
+        .bind(row.email_changed_at)
+        .bind(row.id)
+        .execute(&mut *self.conn)
+        .timed("users.change_email")
+        .await
+        .map(|_| ())
+        .map_err(query_error)
+    }
+}
+
+#[async_trait]
+impl<'a> user_contracts::SetPasswordHash for UsersRepository<'a> {
+    async fn set_password_hash(
+        &mut self,
+        id: Uuid,
+        password_hash: &str,
+    ) -> Result<(), ApplicationError> {
+        let result = query!(
+            "update users set password_hash = (?) where id = (?)",
+            password_hash,
+            id
+        )
+        .execute(&mut *self.conn)
+        .timed("users.set_password_hash")
+        .await
+        .map_err(query_error)?;
+
+        if result.rows_affected() == 0 {
+            return Err(ApplicationError::entity_not_found("User", id));
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<'a> user_contracts::Erase for UsersRepository<'a> {
+    async fn erase(
+        &mut self,
+        entity: &User,
+        erased_by: &str,
+    ) -> Result<(), ApplicationError> {
+        let row = UserRowRef::seal(entity, self.encryption);
+        let Some(erased_at) = entity.erased_at() else {
+            return Err(ApplicationError::internal(eyre::eyre!(
+                "user {} hasn't been erased",
+                row.id
+            )));
+        };
+
+        sqlx::query(
+            r#"
+                update users
+                set
+                    email = (?),
+                    email_index = (?),
+                    email_domain = (?),
+                    username = (?),
+                    first_name = (?),
+                    last_name = (?),
+                    locale = null,
+                    timezone = null,
+                    password_hash = null,
+                    version = (?),
+                    updated_at = (?),
+                    erased_at = (?)
+                where
+                    id = (?)
+            "#,
+        )
+        .bind(row.email)
+        .bind(row.email_index)
+        .bind(row.email_domain)
+        .bind(row.username)
+        .bind(row.first_name)
+        .bind(row.last_name)
+        .bind(row.version)
+        .bind(row.updated_at)
+        .bind(erased_at)
+        .bind(row.id)
+        .execute(&mut *self.conn)
+        .timed("users.erase")
+        .await
+        .map_err(query_error)?;
+
+        sqlx::query(
+            "insert into user_erasures (user_id, erased_by, erased_at) values ((?), (?), (?))",
+        )
+        .bind(row.id)
+        .bind(erased_by)
+        .bind(erased_at)
+        .execute(&mut *self.conn)
+        .timed("users.record_erasure")
+        .await
+        .map(|_| ())
+        .map_err(query_error)
+    }
+}
+
+#[async_trait]
+impl<'a> user_contracts::RecordMerge for UsersRepository<'a> {
+    async fn record_merge(
+        &mut self,
+        duplicate_id: Uuid,
+        primary_id: Uuid,
+        merged_by: &str,
+        merged_at: DateTime<Utc>,
+    ) -> Result<(), ApplicationError> {
+        sqlx::query(
+            r#"
+                update user_merges
+                set merged_into = (?)
+                where merged_into = (?)
+            "#,
+        )
+        .bind(primary_id)
+        .bind(duplicate_id)
+        .execute(&mut *self.conn)
+        .timed("users.reassign_merges")
+        .await
+        .map_err(query_error)?;
+
+        sqlx::query(
+            r#"
+                insert into user_merges (
+                    user_id,
+                    merged_into,
+                    merged_by,
+                    merged_at
+                ) values (
+                    (?),
+                    (?),
+                    (?),
+                    (?)
                 )
+            "#,
+        )
+        .bind(duplicate_id)
+        .bind(primary_id)
+        .bind(merged_by)
+        .bind(merged_at)
+        .execute(&mut *self.conn)
+        .timed("users.record_merge")
+        .await
+        .map(|_| ())
+        .map_err(query_error)
+    }
+}
+
+#[async_trait]
+impl<'a> user_contracts::RecordLogins for UsersRepository<'a> {
+    async fn record_logins(
+        &mut self,
+        tallies: &[LoginTally],
+    ) -> Result<(), ApplicationError> {
+        // Logins don't emit events, so the summaries are updated along with the users rather
+        // than by the projection of the read model.
+        for table in ["users", "user_summaries"] {
+            for chunk in tallies.chunks(MAX_BINDS / TALLY_COLUMNS) {
+                let mut update = QueryBuilder::new(
+                    "with tallies (user_id, logins, logged_in_at) as (",
+                );
+                update.push_values(chunk, |mut values, tally| {
+                    values
+                        .push_bind(tally.user_id)
+                        .push_bind(tally.count)
+                        .push_bind(tally.last_login_at);
+                });
+                update.push(") update ").push(table).push(
+                    r#"
+                        set
+                            login_count = login_count + logins,
+                            last_login_at = max(
+                                coalesce(last_login_at, logged_in_at),
+                                logged_in_at
+                            )
+                        from
+                            tallies
+                        where
+                            id = user_id
+                        "#,
+                );
+
+                update
+                    .build()
+                    .execute(&mut *self.conn)
+                    .timed("users.record_logins")
+                    .await
+                    .map_err(query_error)?;
             }
-            _ => ApplicationError::internal(eyre!(e)),
-        })
+        }
+
+        Ok(())
+    }
+}
+
+/// Scans the users on connections of their own, outside of any transaction, e.g. for the
+/// exports of the admin CLI, which have to include the users the read model hasn't caught up
+/// with yet.
+#[derive(Clone)]
+pub struct UsersReader {
+    pool: SqlitePool,
+    encryption: FieldEncryption,
+}
+
+impl UsersReader {
+    pub fn new(pool: SqlitePool, encryption: FieldEncryption) -> Self {
+        UsersReader { pool, encryption }
+    }
+}
+
+impl user_contracts::Scan for UsersReader {
+    fn scan(
+        &mut self,
+        filter: &UserFilter,
+        sort: &[Sort<UserSortField>],
+    ) -> UserStream {
+        let mut select = QueryBuilder::new(SELECT_USERS);
+        query::push_filter(&mut select, filter, &self.encryption);
+        query::push_sort(&mut select, sort);
+
+        let encryption = self.encryption.clone();
+        cursor::fetch::<UserRow>(&self.pool, select, "users.scan")
+            .map(move |row| row.and_then(|row| row.open(&encryption)))
+            .boxed()
+    }
+}
+
+/// Maps an error of an insert, so that taken emails and usernames are reported as
+/// [ApplicationError::EntityAlreadyExists].
+fn insert_error(e: sqlx::Error) -> ApplicationError {
+    match RepositoryError::from(e) {
+        // A username can be taken by a concurrent insert after it was checked.
+        RepositoryError::UniqueViolation { constraint }
+            if constraint == "users.username" =>
+        {
+            ApplicationError::entity_already_exists(
+                "User",
+                "Username is already taken",
+            )
+        }
+        RepositoryError::UniqueViolation { .. } => {
+            ApplicationError::entity_already_exists(
+                "User",
+                "Email is already taken",
+            )
+        }
+        e => e.into(),
     }
 }
@@ -0,0 +1,159 @@
+use identify_application::{
+    Sort, SortDirection,
+    user_contracts::{UserCriterion, UserFilter, UserSortField, UserStatus},
+};
+use identify_domain::{UserId, UserIdAttrs};
+use sqlx::{QueryBuilder, Sqlite};
+
+use crate::encryption::FieldEncryption;
+
+/// Appends the `where` clause matching the filter.
+pub fn push_filter(
+    builder: &mut QueryBuilder<'_, Sqlite>,
+    filter: &UserFilter,
+    encryption: &FieldEncryption,
+) {
+    let mut conditions = 0;
+    let mut condition = |builder: &mut QueryBuilder<'_, Sqlite>, sql| {
+        builder.push(if conditions == 0 { " where " } else { " and " });
+        builder.push(sql);
+        conditions += 1;
+    };
+
+    if let Some(email) = &filter.email {
+        // Emails are matched like the ones of [UserCriterion::ByEmail], regardless of their
+        // case and surrounding whitespace. Encrypted emails are matched by their blind indexes,
+        // and the ones stored before the encryption was enabled by their values.
+        condition(builder, "(lower(email) = lower(trim(");
+        builder.push_bind(email.clone()).push("))");
+        if let Some(index) = encryption.email_index(email) {
+            builder.push(" or email_index = ").push_bind(index);
+        }
+        builder.push(")");
+    }
+    if let Some(domain) = &filter.email_domain {
+        // Domains are stored in the clear, even while the emails are encrypted.
+        condition(builder, "email_domain = lower(");
+        builder.push_bind(domain.clone()).push(")");
+    }
+    if let Some(created_after) = filter.created_after {
+        condition(builder, "created_at > ");
+        builder.push_bind(created_after);
+    }
+    if let Some(created_before) = filter.created_before {
+        condition(builder, "created_at < ");
+        builder.push_bind(created_before);
+    }
+    if let Some(inactive_since) = filter.inactive_since {
+        condition(builder, "(last_login_at is null or last_login_at < ");
+        builder.push_bind(inactive_since).push(")");
+    }
+}
+
+/// Appends the condition of a single criterion of a [UserSpec](identify_application::user_contracts::UserSpec),
+/// see [push_where](crate::storage::specification::push_where).
+pub fn push_criterion(
+    builder: &mut QueryBuilder<'_, Sqlite>,
+    criterion: &UserCriterion,
+    encryption: &FieldEncryption,
+) {
+    match criterion {
+        UserCriterion::ByEmail(email) => {
+            // Users keep the IDs generated from the emails they were created with, by the
+            // current or the first version of the recipe, and aliases have the IDs of their
+            // emails too. Current emails are matched like the ones of filters.
+            let id = UserId::new(UserIdAttrs {
+                email: email.clone(),
+            });
+            builder
+                .push("id in (")
+                .push_bind(id.to_uuid())
+                .push(", ")
+                .push_bind(id.to_uuid_v1())
+                .push(
+                    ") or id in (select user_id from email_aliases where id = ",
+                )
+                .push_bind(id.to_uuid())
+                .push(") or lower(email) = lower(trim(")
+                .push_bind(email.clone())
+                .push(")) or email_index = ")
+                .push_bind(encryption.email_index(email));
+        }
+        UserCriterion::ByStatus(UserStatus::Active) => {
+            builder.push("erased_at is null");
+        }
+        UserCriterion::ByStatus(UserStatus::Erased) => {
+            builder.push("erased_at is not null");
+        }
+        UserCriterion::CreatedBetween { from, to } => {
+            builder.push("1");
+            if let Some(from) = from {
+                builder.push(" and created_at >= ").push_bind(*from);
+            }
+            if let Some(to) = to {
+                builder.push(" and created_at < ").push_bind(*to);
+            }
+        }
+        UserCriterion::InOrganization(organization_id) => {
+            builder
+                .push(
+                    "id in (select user_id from memberships where organization_id = ",
+                )
+                .push_bind(*organization_id)
+                .push(")");
+        }
+        UserCriterion::InactiveSince(since) => {
+            builder
+                .push("last_login_at is null or last_login_at < ")
+                .push_bind(*since);
+        }
+    }
+}
+
+/// Appends the `order by` clause.
+///
+/// The ID is always used as the last sort key, so that pagination is stable.
+pub fn push_sort(
+    builder: &mut QueryBuilder<'_, Sqlite>,
+    sort: &[Sort<UserSortField>],
+) {
+    builder.push(" order by ");
+
+    for Sort { field, direction } in sort {
+        let column = match field {
+            UserSortField::Email => "email",
+            UserSortField::FirstName => "first_name",
+            UserSortField::LastName => "last_name",
+            UserSortField::Username => "username",
+            UserSortField::CreatedAt => "created_at",
+            UserSortField::UpdatedAt => "updated_at",
+        };
+        let direction = match direction {
+            SortDirection::Ascending => "asc",
+            SortDirection::Descending => "desc",
+        };
+
+        builder.push(column).push(" ").push(direction).push(", ");
+    }
+
+    builder.push("id asc");
+}
+
+/// Most words of a search that are matched, so that long texts don't make expensive queries.
+const MAX_SEARCH_TERMS: usize = 8;
+
+/// Builds the FTS5 expression that matches all words of the text by their prefixes, e.g.
+/// `"jo"* "doe"*` from `Jo Doe!`.
+///
+/// Anything but letters and digits separates words, like it does in the index, so the text
+/// can't inject any FTS5 syntax. Returns `None` if the text contains no words.
+pub fn match_expression(text: &str) -> Option<String> {
+    let terms = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|term| !term.is_empty())
+        .take(MAX_SEARCH_TERMS)
+        .map(|term| format!("\"{term}\"*"))
+        .collect::<Vec<_>>();
+
+    (!terms.is_empty()).then(|| terms.join(" "))
+}
@@ -0,0 +1,163 @@
+//! Re-encryption of the personal data of users, e.g. once a new key has become the current one
+//! or the encryption has been enabled, see [encryption](crate::encryption).
+
+use futures_util::TryStreamExt as _;
+use identify_application::ApplicationError;
+use sqlx::{QueryBuilder, SqlitePool};
+use uuid::Uuid;
+
+use crate::{
+    encryption::{FieldCipher, FieldEncryption},
+    storage::{
+        self, cursor, query_error,
+        timing::TimedExt,
+        users::{MAX_BINDS, row::email_domain},
+    },
+};
+
+/// Tables that store the emails and names of users.
+const TABLES: [&str; 2] = ["users", "user_summaries"];
+
+/// Numbers of rows a [reencrypt_users] pass has rewritten.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ReencryptionReport {
+    pub users: u64,
+    pub summaries: u64,
+}
+
+type EncryptedRow = (
+    Uuid,
+    String,
+    String,
+    Option<String>,
+    Option<String>,
+    Option<String>,
+);
+
+/// Encrypts the emails and names of all the users and their summaries with the current key of
+/// the cipher, and sets the domains of their emails and the blind indexes that are missing or
+/// stale, e.g. built before emails were normalized like they are now.
+///
+/// Each table is first scanned through a cursor for the rows that aren't encrypted with the
+/// current key, of which only the IDs are kept. Those rows are then rewritten in batches, each
+/// in a transaction of its own, once the scan has released its connection. Rows that are
+/// already encrypted with the current key are left as they are, so an interrupted pass can
+/// simply be run again.
+pub async fn reencrypt_users(
+    pool: &SqlitePool,
+    encryption: &FieldEncryption,
+    batch_size: u32,
+) -> Result<ReencryptionReport, ApplicationError> {
+    let Some(cipher) = encryption.cipher() else {
+        return Err(ApplicationError::internal(eyre::eyre!(
+            "no encryption keys are configured"
+        )));
+    };
+
+    let mut counts = [0; TABLES.len()];
+    for (table, count) in TABLES.into_iter().zip(&mut counts) {
+        *count = reencrypt_table(pool, cipher, table, batch_size).await?;
+    }
+
+    Ok(ReencryptionReport {
+        users: counts[0],
+        summaries: counts[1],
+    })
+}
+
+async fn reencrypt_table(
+    pool: &SqlitePool,
+    cipher: &FieldCipher,
+    table: &str,
+    batch_size: u32,
+) -> Result<u64, ApplicationError> {
+    let columns = format!(
+        "select id, email, first_name, last_name, email_index, email_domain \
+         from {table}"
+    );
+    let update = format!(
+        "update {table} set email = (?), email_index = (?), email_domain = (?), first_name = (?), \
+         last_name = (?) where id = (?)"
+    );
+    let decrypt = |id: Uuid, value: &str| {
+        cipher.decrypt(value).map_err(|e| {
+            ApplicationError::internal_with_message(
+                e,
+                format!("error while decrypting user {id}"),
+            )
+        })
+    };
+
+    let stale = cursor::fetch::<EncryptedRow>(
+        pool,
+        QueryBuilder::new(format!("{columns} order by id")),
+        "users.reencrypt_scan",
+    )
+    .try_filter_map(|row| async move {
+        Ok((!is_current(cipher, &row)).then_some(row.0))
+    })
+    .try_collect::<Vec<_>>()
+    .await?;
+
+    let mut rewritten = 0;
+    // Each batch binds the IDs of its rows.
+    for ids in stale.chunks((batch_size as usize).min(MAX_BINDS)) {
+        let mut tx = storage::begin(pool).await?;
+        let mut select = QueryBuilder::new(format!("{columns} where id in ("));
+        let mut separated = select.separated(", ");
+        for id in ids {
+            separated.push_bind(*id);
+        }
+        select.push(")");
+        let rows: Vec<EncryptedRow> = select
+            .build_query_as()
+            .fetch_all(&mut *tx)
+            .timed("users.reencrypt_batch")
+            .await
+            .map_err(query_error)?;
+
+        // Rows may have been rewritten since the scan, e.g. by a concurrent pass.
+        for row in rows.iter().filter(|row| !is_current(cipher, row)) {
+            let (id, email, first_name, last_name, ..) = row;
+            let email = decrypt(*id, email)?;
+            let first_name = decrypt(*id, first_name)?;
+            let last_name = last_name
+                .as_deref()
+                .map(|last_name| decrypt(*id, last_name))
+                .transpose()?;
+            sqlx::query(&update)
+                .bind(cipher.encrypt(&email))
+                .bind(cipher.blind_index(&email))
+                .bind(email_domain(&email))
+                .bind(cipher.encrypt(&first_name))
+                .bind(last_name.map(|last_name| cipher.encrypt(&last_name)))
+                .bind(id)
+                .execute(&mut *tx)
+                .timed("users.reencrypt")
+                .await
+                .map_err(query_error)?;
+            rewritten += 1;
+        }
+
+        storage::commit(tx).await?;
+    }
+
+    Ok(rewritten)
+}
+
+/// Checks whether the row is encrypted with the current key and has a domain and the blind
+/// index of its email.
+fn is_current(
+    cipher: &FieldCipher,
+    (_, email, first_name, last_name, email_index, email_domain): &EncryptedRow,
+) -> bool {
+    cipher.is_current(email)
+        && cipher.is_current(first_name)
+        && last_name.as_deref().is_none_or(|v| cipher.is_current(v))
+        && email_index.as_deref().is_some_and(|index| {
+            cipher
+                .decrypt(email)
+                .is_ok_and(|email| cipher.blind_index(&email) == index)
+        })
+        && email_domain.is_some()
+}
@@ -1,42 +1,116 @@
+use std::borrow::Cow;
+
 use chrono::{DateTime, Utc};
-use identify_domain::{DomainError, User, UserAttrs};
+use identify_application::ApplicationError;
+use identify_domain::{User, UserAttrs};
+use sqlx::FromRow;
 use uuid::Uuid;
 
+use crate::encryption::FieldEncryption;
+
+/// A stored user, whose email and names may be encrypted, see [encryption](crate::encryption).
+#[derive(FromRow)]
 pub struct UserRow {
     pub id: Uuid,
     pub email: String,
+    pub username: String,
     pub first_name: String,
     pub last_name: Option<String>,
+    pub locale: Option<String>,
+    pub timezone: Option<String>,
+    pub version: u32,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub erased_at: Option<DateTime<Utc>>,
+    pub email_changed_at: Option<DateTime<Utc>>,
+    pub last_login_at: Option<DateTime<Utc>>,
+    pub login_count: u32,
 }
 
-impl From<&User> for UserRow {
-    fn from(value: &User) -> Self {
-        let attrs = value.to_attributes();
-
-        UserRow {
-            id: attrs.id,
-            email: attrs.email,
-            first_name: attrs.first_name,
-            last_name: attrs.last_name,
-            created_at: attrs.created_at,
-            updated_at: attrs.updated_at,
-        }
+impl UserRow {
+    /// Loads the user of the row, decrypting its fields.
+    pub fn open(
+        self,
+        encryption: &FieldEncryption,
+    ) -> Result<User, ApplicationError> {
+        let open = |field| {
+            encryption.open(field).map_err(|e| {
+                ApplicationError::internal_with_message(
+                    e,
+                    format!("error while decrypting user {}", self.id),
+                )
+            })
+        };
+
+        let user = User::load(UserAttrs {
+            id: self.id,
+            email: open(self.email)?,
+            username: self.username,
+            first_name: open(self.first_name)?,
+            last_name: self.last_name.map(open).transpose()?,
+            locale: self.locale,
+            timezone: self.timezone,
+            version: self.version,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+            erased_at: self.erased_at,
+            email_changed_at: self.email_changed_at,
+            last_login_at: self.last_login_at,
+            login_count: self.login_count,
+        })?;
+
+        Ok(user)
     }
 }
 
-impl TryFrom<UserRow> for User {
-    type Error = DomainError;
-
-    fn try_from(value: UserRow) -> Result<Self, Self::Error> {
-        User::load(UserAttrs {
-            id: value.id,
-            email: value.email,
-            first_name: value.first_name,
-            last_name: value.last_name,
-            created_at: value.created_at,
-            updated_at: value.updated_at,
-        })
+/// A row borrowed from a user, so that writing it doesn't copy any strings unless they're
+/// encrypted.
+pub struct UserRowRef<'a> {
+    pub id: Uuid,
+    pub email: Cow<'a, str>,
+    /// Blind index of the email, set only while the encryption is enabled.
+    pub email_index: Option<String>,
+    /// Lowercase domain of the email, which is never encrypted.
+    pub email_domain: String,
+    pub username: &'a str,
+    pub first_name: Cow<'a, str>,
+    pub last_name: Option<Cow<'a, str>>,
+    pub locale: Option<&'a str>,
+    pub timezone: Option<&'static str>,
+    pub version: u32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub email_changed_at: Option<DateTime<Utc>>,
+}
+
+impl<'a> UserRowRef<'a> {
+    /// Borrows the row of the user, encrypting its fields.
+    pub fn seal(value: &'a User, encryption: &FieldEncryption) -> Self {
+        UserRowRef {
+            id: value.id(),
+            email: encryption.seal(value.email()),
+            email_index: encryption.email_index(value.email()),
+            email_domain: email_domain(value.email()),
+            username: value.username(),
+            first_name: encryption.seal(value.first_name()),
+            last_name: value
+                .last_name()
+                .as_deref()
+                .map(|name| encryption.seal(name)),
+            locale: value.locale().map(|locale| locale.as_str()),
+            timezone: value.timezone().map(|timezone| timezone.as_str()),
+            version: value.version(),
+            created_at: *value.created_at(),
+            updated_at: *value.updated_at(),
+            email_changed_at: *value.email_changed_at(),
+        }
     }
 }
+
+/// Returns the lowercase domain of the email, or an empty string if it has none.
+pub fn email_domain(email: &str) -> String {
+    email
+        .rsplit_once('@')
+        .map_or("", |(_, domain)| domain)
+        .to_lowercase()
+}
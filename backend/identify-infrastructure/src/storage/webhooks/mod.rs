@@ -0,0 +1,618 @@
+mod row;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use identify_application::{
+    ApplicationError, webhook_delivery_contracts, webhook_endpoint_contracts,
+};
+use identify_domain::{
+    WebhookDelivery, WebhookDeliveryStatus, WebhookEndpoint,
+};
+use sqlx::{SqliteConnection, SqlitePool};
+use uuid::Uuid;
+
+use crate::{
+    encryption::FieldEncryption,
+    storage::{
+        self, lookup_error,
+        queries::{query, query_as},
+        query_error,
+        timing::TimedExt,
+        webhooks::row::{WebhookDeliveryRow, WebhookEndpointRow},
+    },
+};
+
+pub struct WebhookEndpointsRepository<'a> {
+    conn: &'a mut SqliteConnection,
+    encryption: &'a FieldEncryption,
+}
+
+impl WebhookEndpointsRepository<'_> {
+    pub fn new<'a>(
+        conn: &'a mut SqliteConnection,
+        encryption: &'a FieldEncryption,
+    ) -> WebhookEndpointsRepository<'a> {
+        WebhookEndpointsRepository { conn, encryption }
+    }
+}
+
+#[async_trait]
+impl<'a> webhook_endpoint_contracts::Get for WebhookEndpointsRepository<'a> {
+    async fn get(
+        &mut self,
+        id: Uuid,
+    ) -> Result<WebhookEndpoint, ApplicationError> {
+        let endpoint = query_as!(
+            WebhookEndpointRow,
+            r#"
+                select
+                    id as "id: Uuid",
+                    url,
+                    secret,
+                    event_types,
+                    external_id,
+                    created_at as "created_at: _",
+                    updated_at as "updated_at: _"
+                from
+                    webhook_endpoints
+                where
+                    id = (?)
+            "#,
+            id
+        )
+        .fetch_one(&mut *self.conn)
+        .timed("webhook_endpoints.get")
+        .await
+        .map_err(lookup_error("WebhookEndpoint", id))
+        .map(|row| row.open(self.encryption))??;
+
+        Ok(endpoint)
+    }
+}
+
+#[async_trait]
+impl<'a> webhook_endpoint_contracts::GetByExternalId
+    for WebhookEndpointsRepository<'a>
+{
+    async fn get_by_external_id(
+        &mut self,
+        external_id: &str,
+    ) -> Result<Option<WebhookEndpoint>, ApplicationError> {
+        let row = query_as!(
+            WebhookEndpointRow,
+            r#"
+                select
+                    id as "id: Uuid",
+                    url,
+                    secret,
+                    event_types,
+                    external_id,
+                    created_at as "created_at: _",
+                    updated_at as "updated_at: _"
+                from
+                    webhook_endpoints
+                where
+                    external_id = (?)
+            "#,
+            external_id
+        )
+        .fetch_optional(&mut *self.conn)
+        .timed("webhook_endpoints.get_by_external_id")
+        .await
+        .map_err(query_error)?;
+
+        Ok(row.map(|row| row.open(self.encryption)).transpose()?)
+    }
+}
+
+#[async_trait]
+impl<'a> webhook_endpoint_contracts::ListAll
+    for WebhookEndpointsRepository<'a>
+{
+    async fn list_all(
+        &mut self,
+    ) -> Result<Vec<WebhookEndpoint>, ApplicationError> {
+        let endpoints = query_as!(
+            WebhookEndpointRow,
+            r#"
+                select
+                    id as "id: Uuid",
+                    url,
+                    secret,
+                    event_types,
+                    external_id,
+                    created_at as "created_at: _",
+                    updated_at as "updated_at: _"
+                from
+                    webhook_endpoints
+                order by
+                    created_at,
+                    id
+            "#
+        )
+        .fetch_all(&mut *self.conn)
+        .timed("webhook_endpoints.list_all")
+        .await
+        .map_err(query_error)?
+        .into_iter()
+        .map(|row| row.open(self.encryption))
+        .collect::<Result<Vec<WebhookEndpoint>, _>>()?;
+
+        Ok(endpoints)
+    }
+}
+
+#[async_trait]
+impl<'a> webhook_endpoint_contracts::Insert for WebhookEndpointsRepository<'a> {
+    async fn insert(
+        &mut self,
+        entity: &WebhookEndpoint,
+    ) -> Result<(), ApplicationError> {
+        let row = WebhookEndpointRow::seal(entity, self.encryption);
+
+        query!(
+            r#"
+                insert into webhook_endpoints (
+                    id,
+                    url,
+                    secret,
+                    event_types,
+                    external_id,
+                    created_at,
+                    updated_at
+                ) values (
+                    (?),
+                    (?),
+                    (?),
+                    (?),
+                    (?),
+                    (?),
+                    (?)
+                )
+            "#,
+            row.id,
+            row.url,
+            row.secret,
+            row.event_types,
+            row.external_id,
+            row.created_at,
+            row.updated_at
+        )
+        .execute(&mut *self.conn)
+        .timed("webhook_endpoints.insert")
+        .await
+        .map(|_| ())
+        .map_err(query_error)
+    }
+}
+
+#[async_trait]
+impl<'a> webhook_endpoint_contracts::Update for WebhookEndpointsRepository<'a> {
+    async fn update(
+        &mut self,
+        entity: &WebhookEndpoint,
+    ) -> Result<(), ApplicationError> {
+        let row = WebhookEndpointRow::seal(entity, self.encryption);
+
+        let result = query!(
+            r#"
+                update webhook_endpoints
+                set
+                    url = (?),
+                    secret = (?),
+                    event_types = (?),
+                    updated_at = (?)
+                where
+                    id = (?)
+            "#,
+            row.url,
+            row.secret,
+            row.event_types,
+            row.updated_at,
+            row.id
+        )
+        .execute(&mut *self.conn)
+        .timed("webhook_endpoints.update")
+        .await
+        .map_err(query_error)?;
+
+        if result.rows_affected() == 0 {
+            return Err(ApplicationError::entity_not_found(
+                "WebhookEndpoint",
+                row.id,
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<'a> webhook_endpoint_contracts::Delete for WebhookEndpointsRepository<'a> {
+    async fn delete(&mut self, id: Uuid) -> Result<(), ApplicationError> {
+        let result = query!(
+            r#"
+                delete from webhook_endpoints
+                where
+                    id = (?)
+            "#,
+            id
+        )
+        .execute(&mut *self.conn)
+        .timed("webhook_endpoints.delete")
+        .await
+        .map_err(query_error)?;
+
+        if result.rows_affected() == 0 {
+            return Err(ApplicationError::entity_not_found(
+                "WebhookEndpoint",
+                id,
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+pub struct WebhookDeliveriesRepository<'a> {
+    conn: &'a mut SqliteConnection,
+}
+
+impl WebhookDeliveriesRepository<'_> {
+    pub fn new<'a>(
+        conn: &'a mut SqliteConnection,
+    ) -> WebhookDeliveriesRepository<'a> {
+        WebhookDeliveriesRepository { conn }
+    }
+}
+
+#[async_trait]
+impl<'a> webhook_delivery_contracts::Get for WebhookDeliveriesRepository<'a> {
+    async fn get(
+        &mut self,
+        id: Uuid,
+    ) -> Result<WebhookDelivery, ApplicationError> {
+        let delivery = query_as!(
+            WebhookDeliveryRow,
+            r#"
+                select
+                    id as "id: Uuid",
+                    endpoint_id as "endpoint_id: Uuid",
+                    event_id as "event_id: Uuid",
+                    user_id as "user_id: Uuid",
+                    event_type,
+                    occurred_at as "occurred_at: _",
+                    status,
+                    attempts as "attempts: u32",
+                    next_attempt_at as "next_attempt_at: _",
+                    last_error,
+                    created_at as "created_at: _",
+                    updated_at as "updated_at: _"
+                from
+                    webhook_deliveries
+                where
+                    id = (?)
+            "#,
+            id
+        )
+        .fetch_one(&mut *self.conn)
+        .timed("webhook_deliveries.get")
+        .await
+        .map_err(lookup_error("WebhookDelivery", id))
+        .map(TryInto::try_into)??;
+
+        Ok(delivery)
+    }
+}
+
+#[async_trait]
+impl<'a> webhook_delivery_contracts::ListDue
+    for WebhookDeliveriesRepository<'a>
+{
+    async fn list_due(
+        &mut self,
+        now: DateTime<Utc>,
+        limit: u32,
+    ) -> Result<Vec<WebhookDelivery>, ApplicationError> {
+        let status = WebhookDeliveryStatus::Pending.as_str();
+        let deliveries = query_as!(
+            WebhookDeliveryRow,
+            r#"
+                select
+                    id as "id: Uuid",
+                    endpoint_id as "endpoint_id: Uuid",
+                    event_id as "event_id: Uuid",
+                    user_id as "user_id: Uuid",
+                    event_type,
+                    occurred_at as "occurred_at: _",
+                    status,
+                    attempts as "attempts: u32",
+                    next_attempt_at as "next_attempt_at: _",
+                    last_error,
+                    created_at as "created_at: _",
+                    updated_at as "updated_at: _"
+                from
+                    webhook_deliveries
+                where
+                    status = (?)
+                    and next_attempt_at <= (?)
+                order by
+                    next_attempt_at
+                limit (?)
+            "#,
+            status,
+            now,
+            limit
+        )
+        .fetch_all(&mut *self.conn)
+        .timed("webhook_deliveries.list_due")
+        .await
+        .map_err(query_error)?
+        .into_iter()
+        .map(TryInto::try_into)
+        .collect::<Result<Vec<WebhookDelivery>, _>>()?;
+
+        Ok(deliveries)
+    }
+}
+
+#[async_trait]
+impl<'a> webhook_delivery_contracts::ListByEndpoint
+    for WebhookDeliveriesRepository<'a>
+{
+    async fn list_by_endpoint(
+        &mut self,
+        endpoint_id: Uuid,
+        status: WebhookDeliveryStatus,
+        limit: u32,
+    ) -> Result<Vec<WebhookDelivery>, ApplicationError> {
+        let status = status.as_str();
+        let deliveries = query_as!(
+            WebhookDeliveryRow,
+            r#"
+                select
+                    id as "id: Uuid",
+                    endpoint_id as "endpoint_id: Uuid",
+                    event_id as "event_id: Uuid",
+                    user_id as "user_id: Uuid",
+                    event_type,
+                    occurred_at as "occurred_at: _",
+                    status,
+                    attempts as "attempts: u32",
+                    next_attempt_at as "next_attempt_at: _",
+                    last_error,
+                    created_at as "created_at: _",
+                    updated_at as "updated_at: _"
+                from
+                    webhook_deliveries
+                where
+                    endpoint_id = (?)
+                    and status = (?)
+                order by
+                    updated_at desc,
+                    id
+                limit (?)
+            "#,
+            endpoint_id,
+            status,
+            limit
+        )
+        .fetch_all(&mut *self.conn)
+        .timed("webhook_deliveries.list_by_endpoint")
+        .await
+        .map_err(query_error)?
+        .into_iter()
+        .map(TryInto::try_into)
+        .collect::<Result<Vec<WebhookDelivery>, _>>()?;
+
+        Ok(deliveries)
+    }
+}
+
+#[async_trait]
+impl<'a> webhook_delivery_contracts::Insert
+    for WebhookDeliveriesRepository<'a>
+{
+    async fn insert(
+        &mut self,
+        entity: &WebhookDelivery,
+    ) -> Result<bool, ApplicationError> {
+        let row: WebhookDeliveryRow = entity.into();
+
+        query!(
+            r#"
+                insert into webhook_deliveries (
+                    id,
+                    endpoint_id,
+                    event_id,
+                    user_id,
+                    event_type,
+                    occurred_at,
+                    status,
+                    attempts,
+                    next_attempt_at,
+                    last_error,
+                    created_at,
+                    updated_at
+                ) values (
+                    (?),
+                    (?),
+                    (?),
+                    (?),
+                    (?),
+                    (?),
+                    (?),
+                    (?),
+                    (?),
+                    (?),
+                    (?),
+                    (?)
+                )
+                on conflict (endpoint_id, event_id) do nothing
+            "#,
+            row.id,
+            row.endpoint_id,
+            row.event_id,
+            row.user_id,
+            row.event_type,
+            row.occurred_at,
+            row.status,
+            row.attempts,
+            row.next_attempt_at,
+            row.last_error,
+            row.created_at,
+            row.updated_at
+        )
+        .execute(&mut *self.conn)
+        .timed("webhook_deliveries.insert")
+        .await
+        .map(|result| result.rows_affected() > 0)
+        .map_err(query_error)
+    }
+}
+
+#[async_trait]
+impl<'a> webhook_delivery_contracts::Update
+    for WebhookDeliveriesRepository<'a>
+{
+    async fn update(
+        &mut self,
+        entity: &WebhookDelivery,
+    ) -> Result<(), ApplicationError> {
+        let row: WebhookDeliveryRow = entity.into();
+
+        query!(
+            r#"
+                update webhook_deliveries
+                set
+                    status = (?),
+                    attempts = (?),
+                    next_attempt_at = (?),
+                    last_error = (?),
+                    updated_at = (?)
+                where
+                    id = (?)
+            "#,
+            row.status,
+            row.attempts,
+            row.next_attempt_at,
+            row.last_error,
+            row.updated_at,
+            row.id
+        )
+        .execute(&mut *self.conn)
+        .timed("webhook_deliveries.update")
+        .await
+        .map(|_| ())
+        .map_err(query_error)
+    }
+}
+
+#[async_trait]
+impl<'a> webhook_delivery_contracts::PruneDelivered
+    for WebhookDeliveriesRepository<'a>
+{
+    async fn prune_delivered(
+        &mut self,
+        before: DateTime<Utc>,
+    ) -> Result<u64, ApplicationError> {
+        sqlx::query(
+            r#"
+                delete from webhook_deliveries
+                where
+                    status = (?)
+                    and updated_at < (?)
+            "#,
+        )
+        .bind(WebhookDeliveryStatus::Delivered.as_str())
+        .bind(before)
+        .execute(&mut *self.conn)
+        .timed("webhook_deliveries.prune_delivered")
+        .await
+        .map(|result| result.rows_affected())
+        .map_err(query_error)
+    }
+}
+
+#[async_trait]
+impl<'a> webhook_delivery_contracts::CountDead
+    for WebhookDeliveriesRepository<'a>
+{
+    async fn count_dead(
+        &mut self,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> Result<u64, ApplicationError> {
+        let count: i64 = sqlx::query_scalar(
+            r#"
+                select
+                    count(*)
+                from
+                    webhook_deliveries
+                where
+                    status = (?)
+                    and updated_at >= (?)
+                    and updated_at < (?)
+            "#,
+        )
+        .bind(WebhookDeliveryStatus::Dead.as_str())
+        .bind(since)
+        .bind(until)
+        .fetch_one(&mut *self.conn)
+        .timed("webhook_deliveries.count_dead")
+        .await
+        .map_err(query_error)?;
+
+        Ok(count as u64)
+    }
+}
+
+/// Encrypts the secrets of all the webhook endpoints with the current key of the cipher, like [reencrypt_users](crate::storage::users::reencrypt::reencrypt_users) does with
+/// the personal data of users. Returns the number of rewritten secrets.
+///
+/// Endpoints are few, so they are all rewritten in a single transaction.
+pub async fn reencrypt_secrets(
+    pool: &SqlitePool,
+    encryption: &FieldEncryption,
+) -> Result<u64, ApplicationError> {
+    let Some(cipher) = encryption.cipher() else {
+        return Err(ApplicationError::internal(eyre::eyre!(
+            "no encryption keys are configured"
+        )));
+    };
+
+    let mut tx = storage::begin(pool).await?;
+    let secrets: Vec<(Uuid, String)> =
+        sqlx::query_as("select id, secret from webhook_endpoints")
+            .fetch_all(&mut *tx)
+            .timed("webhook_endpoints.reencrypt_scan")
+            .await
+            .map_err(query_error)?;
+
+    let mut rewritten = 0;
+    for (id, secret) in secrets {
+        if cipher.is_current(&secret) {
+            continue;
+        }
+        let secret = cipher.decrypt(&secret).map_err(|e| {
+            ApplicationError::internal_with_message(
+                e,
+                format!(
+                    "error while decrypting the secret of webhook endpoint {id}"
+                ),
+            )
+        })?;
+        sqlx::query("update webhook_endpoints set secret = (?) where id = (?)")
+            .bind(cipher.encrypt(&secret))
+            .bind(id)
+            .execute(&mut *tx)
+            .timed("webhook_endpoints.reencrypt")
+            .await
+            .map_err(query_error)?;
+        rewritten += 1;
+    }
+
+    storage::commit(tx).await?;
+
+    Ok(rewritten)
+}
@@ -0,0 +1,100 @@
+use chrono::{DateTime, Utc};
+use identify_application::ApplicationError;
+use identify_domain::{
+    DomainError, WebhookDelivery, WebhookDeliveryAttrs, WebhookEndpoint,
+    WebhookEndpointAttrs,
+};
+use identify_macros::ModelRow;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use crate::encryption::FieldEncryption;
+
+/// Separates the event types an endpoint is subscribed to.
+const EVENT_TYPE_SEPARATOR: char = ',';
+
+#[derive(FromRow)]
+pub struct WebhookEndpointRow {
+    pub id: Uuid,
+    pub url: String,
+    /// Encrypted like the personal data of users, if the encryption is enabled.
+    pub secret: String,
+    pub event_types: String,
+    pub external_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl WebhookEndpointRow {
+    /// Returns the row of the webhook endpoint, encrypting its fields.
+    pub fn seal(value: &WebhookEndpoint, encryption: &FieldEncryption) -> Self {
+        let attrs = value.to_attributes();
+
+        WebhookEndpointRow {
+            id: attrs.id,
+            url: attrs.url,
+            secret: encryption.seal(&attrs.secret).into_owned(),
+            event_types: attrs
+                .events
+                .iter()
+                .map(|transition| transition.as_str())
+                .collect::<Vec<_>>()
+                .join(&EVENT_TYPE_SEPARATOR.to_string()),
+            external_id: attrs.external_id,
+            created_at: attrs.created_at,
+            updated_at: attrs.updated_at,
+        }
+    }
+
+    /// Loads the webhook endpoint of the row, decrypting its fields.
+    pub fn open(
+        self,
+        encryption: &FieldEncryption,
+    ) -> Result<WebhookEndpoint, ApplicationError> {
+        let secret = encryption.open(self.secret).map_err(|e| {
+            ApplicationError::internal_with_message(
+                e,
+                format!(
+                    "error while decrypting the secret of webhook endpoint {}",
+                    self.id
+                ),
+            )
+        })?;
+
+        let endpoint = WebhookEndpoint::load(WebhookEndpointAttrs {
+            id: self.id,
+            url: self.url,
+            secret,
+            events: self
+                .event_types
+                .split(EVENT_TYPE_SEPARATOR)
+                .filter(|event_type| !event_type.is_empty())
+                .map(str::parse)
+                .collect::<Result<_, DomainError>>()?,
+            external_id: self.external_id,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+        })?;
+
+        Ok(endpoint)
+    }
+}
+
+#[derive(FromRow, ModelRow)]
+#[row(model = WebhookDelivery, error = DomainError)]
+pub struct WebhookDeliveryRow {
+    pub id: Uuid,
+    pub endpoint_id: Uuid,
+    pub event_id: Uuid,
+    pub user_id: Uuid,
+    #[row(field = transition, text)]
+    pub event_type: String,
+    pub occurred_at: DateTime<Utc>,
+    #[row(text)]
+    pub status: String,
+    pub attempts: u32,
+    pub next_attempt_at: DateTime<Utc>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
@@ -0,0 +1,54 @@
+//! Generation of the secret tokens emailed to users, e.g. to accept an
+//! [Invitation](identify_domain::Invitation), and of the codes texted to them.
+
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use identify_application::TokenGenerator;
+use rand_core::{OsRng, RngCore};
+use sha2::{Digest, Sha256};
+
+/// Number of random bytes of a token, which are enough to make guessing it hopeless.
+const TOKEN_LENGTH: usize = 32;
+/// Number of digits of a code.
+const CODE_DIGITS: u32 = 6;
+
+/// Generates tokens from the random number generator of the OS, encoded as URL-safe base64.
+/// Their hashes are their SHA-256 digests, encoded the same way.
+///
+/// Tokens carry enough entropy that a fast unsalted hash is as good as a slow one, and it lets
+/// them be looked up by their hashes.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RandomTokenGenerator;
+
+impl RandomTokenGenerator {
+    pub fn new() -> Self {
+        RandomTokenGenerator
+    }
+}
+
+impl TokenGenerator for RandomTokenGenerator {
+    fn generate(&self) -> String {
+        let mut bytes = [0; TOKEN_LENGTH];
+        OsRng.fill_bytes(&mut bytes);
+
+        URL_SAFE_NO_PAD.encode(bytes)
+    }
+
+    fn generate_code(&self) -> String {
+        let modulus = 10u32.pow(CODE_DIGITS);
+        // Values past the last whole multiple of the modulus are redrawn, so that every code
+        // is equally likely.
+        let limit = u32::MAX - u32::MAX % modulus;
+        let value = loop {
+            let value = OsRng.next_u32();
+            if value < limit {
+                break value % modulus;
+            }
+        };
+
+        format!("{value:0width$}", width = CODE_DIGITS as usize)
+    }
+
+    fn hash(&self, token: &str) -> String {
+        URL_SAFE_NO_PAD.encode(Sha256::digest(token.as_bytes()))
+    }
+}
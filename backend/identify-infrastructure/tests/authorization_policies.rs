@@ -0,0 +1,63 @@
+use identify_application::authorization_policy_contracts::{Insert, ListAll};
+use identify_domain::{
+    AuthorizationPolicy, ConditionOperator, PolicyCondition, PolicyEffect,
+};
+use identify_infrastructure::storage::authorization_policies::AuthorizationPoliciesRepository;
+
+mod common;
+
+use common::pool;
+
+fn policy(name: &str, conditions: Vec<PolicyCondition>) -> AuthorizationPolicy {
+    AuthorizationPolicy::new(
+        name.to_owned(),
+        PolicyEffect::Deny,
+        vec!["support-*".to_owned(), "billing".to_owned()],
+        vec!["users:erase".to_owned()],
+        vec!["user:*".to_owned()],
+        conditions,
+    )
+    .unwrap()
+}
+
+#[tokio::test]
+async fn policies_are_listed_with_their_conditions_in_order() {
+    let pool = pool().await;
+    let conditioned = policy(
+        "b-conditioned",
+        vec![
+            PolicyCondition {
+                attribute: "resource.email_domain".to_owned(),
+                operator: ConditionOperator::NotIn,
+                values: vec!["acme.test".to_owned(), "globex.test".to_owned()],
+            },
+            PolicyCondition {
+                attribute: "resource.id".to_owned(),
+                operator: ConditionOperator::SameAs,
+                values: vec!["subject".to_owned()],
+            },
+        ],
+    );
+    let unconditioned = policy("a-unconditioned", vec![]);
+    let mut conn = pool.acquire().await.unwrap();
+    let mut repository = AuthorizationPoliciesRepository::new(&mut conn);
+
+    repository.insert(&conditioned).await.unwrap();
+    repository.insert(&unconditioned).await.unwrap();
+
+    assert_eq!(
+        repository.list_all().await.unwrap(),
+        [unconditioned, conditioned]
+    );
+}
+
+#[tokio::test]
+async fn policy_names_are_unique() {
+    let pool = pool().await;
+    let mut conn = pool.acquire().await.unwrap();
+    let mut repository = AuthorizationPoliciesRepository::new(&mut conn);
+
+    repository.insert(&policy("twice", vec![])).await.unwrap();
+
+    assert!(repository.insert(&policy("twice", vec![])).await.is_err());
+}
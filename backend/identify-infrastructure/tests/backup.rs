@@ -0,0 +1,94 @@
+use identify_infrastructure::{
+    InfrastructureError,
+    storage::{
+        backup,
+        connection::{self, PoolConfig},
+    },
+};
+use sqlx::SqlitePool;
+use tempfile::TempDir;
+
+async fn pool(dir: &TempDir) -> SqlitePool {
+    let url = format!("sqlite://{}", dir.path().join("identify.db").display());
+    let pool = connection::get_pool(&url, &PoolConfig::default())
+        .await
+        .unwrap();
+    connection::migrate(&pool).await.unwrap();
+
+    pool
+}
+
+async fn buckets(pool: &SqlitePool) -> Vec<String> {
+    sqlx::query_scalar("select key from rate_limit_buckets order by key")
+        .fetch_all(pool)
+        .await
+        .unwrap()
+}
+
+async fn insert_bucket(pool: &SqlitePool, key: &str) {
+    sqlx::query(
+        "insert into rate_limit_buckets (key, tokens, allowed, updated_at) values (?, 1, true, 0)",
+    )
+    .bind(key)
+    .execute(pool)
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn restored_databases_are_back_to_the_backup() {
+    let dir = TempDir::new().unwrap();
+    let backup_path = dir.path().join("backup.db");
+    let pool = pool(&dir).await;
+    insert_bucket(&pool, "a").await;
+
+    let size = backup::backup(&pool, &backup_path).await.unwrap();
+    insert_bucket(&pool, "b").await;
+    backup::restore(&pool, &backup_path).await.unwrap();
+
+    assert!(size > 0);
+    assert!(pool.is_closed());
+    let pool = self::pool(&dir).await;
+    assert_eq!(buckets(&pool).await, ["a"]);
+}
+
+#[tokio::test]
+async fn backups_never_overwrite_files() {
+    let dir = TempDir::new().unwrap();
+    let backup_path = dir.path().join("backup.db");
+    let pool = pool(&dir).await;
+    std::fs::write(&backup_path, b"precious").unwrap();
+
+    let result = backup::backup(&pool, &backup_path).await;
+
+    assert!(matches!(result, Err(InfrastructureError::Backup(_))));
+    assert_eq!(std::fs::read(&backup_path).unwrap(), b"precious");
+}
+
+#[tokio::test]
+async fn unsound_backups_are_not_restored() {
+    let dir = TempDir::new().unwrap();
+    let backup_path = dir.path().join("backup.db");
+    let pool = pool(&dir).await;
+    insert_bucket(&pool, "a").await;
+    // A database that was never migrated isn't a backup of this one.
+    let other = connection::get_pool(
+        &format!("sqlite://{}", backup_path.display()),
+        &PoolConfig::default(),
+    )
+    .await
+    .unwrap();
+    sqlx::query("create table t (id integer)")
+        .execute(&other)
+        .await
+        .unwrap();
+    other.close().await;
+
+    let problems = backup::check(&backup_path).await.unwrap();
+    let result = backup::restore(&pool, &backup_path).await;
+
+    assert_eq!(problems, ["it was never migrated"]);
+    assert!(matches!(result, Err(InfrastructureError::Backup(_))));
+    assert!(!pool.is_closed());
+    assert_eq!(buckets(&pool).await, ["a"]);
+}
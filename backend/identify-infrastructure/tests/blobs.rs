@@ -0,0 +1,195 @@
+use identify_application::{Blob, BlobStorage};
+use identify_infrastructure::blobs::FilesystemBlobStorage;
+
+fn blob(content_type: &str, bytes: &[u8]) -> Blob {
+    Blob {
+        content_type: content_type.to_owned(),
+        bytes: bytes.to_vec(),
+    }
+}
+
+/// Checks the behavior every storage must have.
+async fn round_trip(storage: &dyn BlobStorage) {
+    assert_eq!(storage.get("avatars/jane").await.unwrap(), None);
+
+    let first = blob("image/png", b"\x89PNG\r\n\x1a\n\nfirst");
+    storage.put("avatars/jane", &first).await.unwrap();
+    assert_eq!(storage.get("avatars/jane").await.unwrap(), Some(first));
+
+    let second = blob("image/gif", b"GIF89a\nsecond");
+    storage.put("avatars/jane", &second).await.unwrap();
+    assert_eq!(storage.get("avatars/jane").await.unwrap(), Some(second));
+
+    storage.delete("avatars/jane").await.unwrap();
+    assert_eq!(storage.get("avatars/jane").await.unwrap(), None);
+    // Deleting a missing blob succeeds.
+    storage.delete("avatars/jane").await.unwrap();
+}
+
+#[tokio::test]
+async fn filesystem_round_trip() {
+    let dir = tempfile::tempdir().unwrap();
+    let storage = FilesystemBlobStorage::new(dir.path().join("blobs"));
+
+    round_trip(&storage).await;
+}
+
+#[tokio::test]
+async fn filesystem_rejects_keys_outside_of_the_root() {
+    let dir = tempfile::tempdir().unwrap();
+    let storage = FilesystemBlobStorage::new(dir.path().join("blobs"));
+
+    for key in [
+        "",
+        "/avatars/jane",
+        "avatars//jane",
+        "../jane",
+        "avatars/.jane",
+    ] {
+        assert!(
+            storage.put(key, &blob("image/png", b"")).await.is_err(),
+            "'{key}' should be rejected"
+        );
+    }
+    assert!(!dir.path().join("jane").exists());
+}
+
+#[cfg(feature = "s3")]
+mod s3 {
+    use std::{
+        collections::HashMap,
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+
+    use identify_infrastructure::blobs::s3::{S3BlobStorage, S3Settings};
+    use tokio::{
+        io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+        net::{TcpListener, TcpStream},
+    };
+
+    use super::round_trip;
+
+    /// Objects of the fake service by their paths, with their content types.
+    type Objects = Arc<Mutex<HashMap<String, (String, Vec<u8>)>>>;
+
+    /// Serves a minimal S3-compatible service, which only accepts requests signed by the
+    /// `test` access key.
+    async fn serve() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let objects = Objects::default();
+
+        tokio::spawn(async move {
+            loop {
+                let (stream, _) = listener.accept().await.unwrap();
+                tokio::spawn(handle(stream, objects.clone()));
+            }
+        });
+
+        format!("http://{address}")
+    }
+
+    async fn handle(stream: TcpStream, objects: Objects) {
+        let mut stream = BufReader::new(stream);
+        loop {
+            let mut request_line = String::new();
+            if stream.read_line(&mut request_line).await.unwrap_or(0) == 0 {
+                return;
+            }
+            let mut parts = request_line.split_whitespace();
+            let method = parts.next().unwrap().to_owned();
+            let path = parts.next().unwrap().to_owned();
+
+            let mut headers = HashMap::new();
+            loop {
+                let mut line = String::new();
+                stream.read_line(&mut line).await.unwrap();
+                let Some((name, value)) = line.trim_end().split_once(':')
+                else {
+                    break;
+                };
+                headers.insert(name.to_lowercase(), value.trim().to_owned());
+            }
+            let length = headers
+                .get("content-length")
+                .map_or(0, |length| length.parse().unwrap());
+            let mut body = vec![0; length];
+            stream.read_exact(&mut body).await.unwrap();
+
+            let signed = headers.get("authorization").is_some_and(|value| {
+                value.starts_with("AWS4-HMAC-SHA256 Credential=test/")
+                    && value.contains("/us-east-1/s3/aws4_request")
+            }) && headers.contains_key("x-amz-date");
+
+            let (status, content_type, body) = if !signed {
+                ("403 Forbidden", String::new(), Vec::new())
+            } else {
+                let mut objects = objects.lock().unwrap();
+                match method.as_str() {
+                    "PUT" => {
+                        let content_type = headers
+                            .get("content-type")
+                            .cloned()
+                            .unwrap_or_default();
+                        objects.insert(path, (content_type, body));
+                        ("200 OK", String::new(), Vec::new())
+                    }
+                    "GET" => match objects.get(&path) {
+                        Some((content_type, body)) => {
+                            ("200 OK", content_type.clone(), body.clone())
+                        }
+                        None => ("404 Not Found", String::new(), Vec::new()),
+                    },
+                    "DELETE" => {
+                        objects.remove(&path);
+                        ("204 No Content", String::new(), Vec::new())
+                    }
+                    _ => ("405 Method Not Allowed", String::new(), Vec::new()),
+                }
+            };
+
+            let mut response = format!("HTTP/1.1 {status}\r\n");
+            if !content_type.is_empty() {
+                response.push_str(&format!("content-type: {content_type}\r\n"));
+            }
+            if !status.starts_with("204") {
+                response
+                    .push_str(&format!("content-length: {}\r\n", body.len()));
+            }
+            response.push_str("\r\n");
+            let stream = stream.get_mut();
+            stream.write_all(response.as_bytes()).await.unwrap();
+            stream.write_all(&body).await.unwrap();
+        }
+    }
+
+    fn settings(endpoint: String) -> S3Settings {
+        S3Settings {
+            endpoint,
+            bucket: "identify".to_owned(),
+            region: "us-east-1".to_owned(),
+            access_key_id: "test".to_owned(),
+            secret_access_key: "secret".to_owned(),
+            path_style: true,
+            timeout: Duration::from_secs(5),
+        }
+    }
+
+    #[tokio::test]
+    async fn s3_round_trip() {
+        let storage = S3BlobStorage::new(settings(serve().await)).unwrap();
+
+        round_trip(&storage).await;
+    }
+
+    #[tokio::test]
+    async fn s3_rejects_invalid_endpoints() {
+        for endpoint in ["", "not a url", "ftp://example.com"] {
+            assert!(
+                S3BlobStorage::new(settings(endpoint.to_owned())).is_err(),
+                "'{endpoint}' should be rejected"
+            );
+        }
+    }
+}
@@ -0,0 +1,13 @@
+use identify_infrastructure::storage::connection::{self, PoolConfig};
+use sqlx::SqlitePool;
+
+/// Returns a pool of a migrated in-memory database.
+pub async fn pool() -> SqlitePool {
+    let url = "sqlite::memory:";
+    let pool = connection::get_pool(url, &PoolConfig::for_url(url))
+        .await
+        .unwrap();
+    connection::migrate(&pool).await.unwrap();
+
+    pool
+}
@@ -0,0 +1,119 @@
+use chrono::{TimeDelta, Utc};
+use identify_application::{
+    BuildAdminDigestParams, DigestCategory, DigestUseCaseDeps,
+    build_admin_digest,
+    operation_contracts::{Insert, Update},
+    user_event_contracts::Emit,
+};
+use identify_domain::{
+    NewOperationAttrs, NewUserEventAttrs, Operation, QuotaPolicy, UserEvent,
+    UserLifecycleTransition,
+};
+use identify_infrastructure::{
+    encryption::FieldEncryption,
+    storage::{
+        self, operations::OperationsRepository, outbox::OutboxRepository,
+        unit_of_work::SqliteUnitOfWork,
+    },
+};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+mod common;
+
+use common::pool;
+
+/// Stores the fields in the clear, as the tests of this file don't check the encryption.
+static NO_ENCRYPTION: FieldEncryption = FieldEncryption::disabled();
+
+async fn record_events(pool: &SqlitePool) {
+    let mut tx = storage::begin(pool).await.unwrap();
+    let mut outbox = OutboxRepository::new(&mut tx);
+
+    for (transition, version) in [
+        (UserLifecycleTransition::Created, 1),
+        (UserLifecycleTransition::Suspended, 2),
+    ] {
+        for _ in 0..2 {
+            let event = UserEvent::new(NewUserEventAttrs {
+                user_id: Uuid::new_v4(),
+                transition,
+                version,
+            })
+            .unwrap();
+            outbox.emit(&event).await.unwrap();
+        }
+    }
+
+    let mut operations = OperationsRepository::new(&mut tx);
+    let mut operation = Operation::new(NewOperationAttrs {
+        kind: "test".to_owned(),
+    });
+    operations.insert(&operation).await.unwrap();
+    operation.start().unwrap();
+    operation.fail("boom").unwrap();
+    operations.update(&operation).await.unwrap();
+
+    storage::commit(tx).await.unwrap();
+}
+
+#[tokio::test]
+async fn digest_counts_the_events_of_the_period() {
+    let pool = pool().await;
+    record_events(&pool).await;
+
+    let mut uow = SqliteUnitOfWork::begin(&pool, &NO_ENCRYPTION)
+        .await
+        .unwrap();
+    let now = Utc::now();
+
+    let digest = build_admin_digest(
+        DigestUseCaseDeps::new(&mut uow, &QuotaPolicy::default()),
+        BuildAdminDigestParams {
+            since: now - TimeDelta::hours(1),
+            until: now + TimeDelta::hours(1),
+            categories: vec![
+                DigestCategory::NewUsers,
+                DigestCategory::Lockouts,
+                DigestCategory::Deletions,
+                DigestCategory::FailedOperations,
+            ],
+        },
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        digest.entries,
+        [
+            (DigestCategory::NewUsers, 2),
+            (DigestCategory::Lockouts, 2),
+            (DigestCategory::Deletions, 0),
+            (DigestCategory::FailedOperations, 1),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn digest_leaves_out_events_of_other_periods() {
+    let pool = pool().await;
+    record_events(&pool).await;
+
+    let mut uow = SqliteUnitOfWork::begin(&pool, &NO_ENCRYPTION)
+        .await
+        .unwrap();
+    let now = Utc::now();
+
+    let digest = build_admin_digest(
+        DigestUseCaseDeps::new(&mut uow, &QuotaPolicy::default()),
+        BuildAdminDigestParams {
+            since: now - TimeDelta::days(2),
+            until: now - TimeDelta::days(1),
+            categories: DigestCategory::ALL.into(),
+        },
+    )
+    .await
+    .unwrap();
+
+    assert!(digest.is_empty());
+}
@@ -0,0 +1,143 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use identify_application::{
+    EmailKind, EmailMessage, EmailPart, EmailSender, EmailTemplates,
+};
+use identify_infrastructure::email::{
+    SmtpEmailSender, SmtpSecurity, SmtpSettings,
+};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::TcpListener,
+};
+
+/// Contents of the messages the server received.
+type Received = Arc<Mutex<Vec<String>>>;
+
+/// Starts a server that accepts every message, or rejects them with `550` if `reject` is set.
+async fn server(reject: bool) -> (u16, Received) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let received = Received::default();
+
+    let messages = received.clone();
+    tokio::spawn(async move {
+        loop {
+            let (stream, _) = listener.accept().await.unwrap();
+            let messages = messages.clone();
+            tokio::spawn(async move {
+                let (reader, mut writer) = stream.into_split();
+                let mut reader = BufReader::new(reader);
+                writer.write_all(b"220 localhost ESMTP\r\n").await.unwrap();
+
+                let mut line = String::new();
+                while reader.read_line(&mut line).await.unwrap() > 0 {
+                    let command = line.trim_end().to_ascii_uppercase();
+                    line.clear();
+
+                    let reply: &[u8] = if command == "DATA" {
+                        writer.write_all(b"354 Go ahead\r\n").await.unwrap();
+                        let mut message = String::new();
+                        while reader.read_line(&mut line).await.unwrap() > 0
+                            && line != ".\r\n"
+                        {
+                            message.push_str(&line);
+                            line.clear();
+                        }
+                        line.clear();
+                        messages.lock().unwrap().push(message);
+
+                        b"250 Queued\r\n"
+                    } else if command == "QUIT" {
+                        writer.write_all(b"221 Bye\r\n").await.unwrap();
+                        return;
+                    } else if reject && command.starts_with("RCPT") {
+                        b"550 No such user\r\n"
+                    } else {
+                        b"250 OK\r\n"
+                    };
+                    writer.write_all(reply).await.unwrap();
+                }
+            });
+        }
+    });
+
+    (port, received)
+}
+
+fn sender(port: u16) -> SmtpEmailSender {
+    SmtpEmailSender::new(&SmtpSettings {
+        host: "127.0.0.1".to_owned(),
+        port,
+        security: SmtpSecurity::None,
+        credentials: None,
+        timeout: Duration::from_secs(5),
+        from: "Identify <no-reply@identify.test>".to_owned(),
+    })
+    .unwrap()
+}
+
+#[tokio::test]
+async fn sends_rendered_templates_with_both_parts() {
+    let (port, received) = server(false).await;
+    let mut templates = EmailTemplates::default();
+    templates
+        .set(
+            EmailKind::MagicLink,
+            EmailPart::Html,
+            "<p>Hi {{ first_name }}, <a href=\"{{link}}\">sign in</a></p>",
+        )
+        .unwrap();
+
+    let email = templates.render(
+        "jane@identify.test",
+        &EmailMessage::MagicLink {
+            first_name: "<Jane>",
+            link: "https://identify.test/sign-in?token=abc",
+        },
+    );
+    sender(port).send(&email).await.unwrap();
+
+    let received = received.lock().unwrap().clone();
+    assert_eq!(received.len(), 1);
+    let message = &received[0];
+    assert!(message.contains("To: jane@identify.test"));
+    assert!(message.contains("Subject: Your sign-in link"));
+    assert!(message.contains("multipart/alternative"));
+    assert!(message.contains("Hi <Jane>,"));
+    assert!(message.contains("Hi &lt;Jane&gt;,"));
+}
+
+#[tokio::test]
+async fn rejected_emails_fail() {
+    let (port, received) = server(true).await;
+    let email = EmailTemplates::default().render(
+        "jim@identify.test",
+        &EmailMessage::PasswordReset {
+            first_name: "Jim",
+            link: "https://identify.test/reset",
+        },
+    );
+
+    assert!(sender(port).send(&email).await.is_err());
+    assert!(received.lock().unwrap().is_empty());
+}
+
+#[test]
+fn templates_with_unknown_variables_are_rejected() {
+    let mut templates = EmailTemplates::default();
+
+    assert!(
+        templates
+            .set(EmailKind::Verification, EmailPart::Text, "{{ link }}")
+            .is_err()
+    );
+    assert!(
+        templates
+            .set(EmailKind::Verification, EmailPart::Subject, "{{ email")
+            .is_err()
+    );
+}
@@ -0,0 +1,491 @@
+use std::collections::HashMap;
+
+use identify_application::{
+    ListQuery, PageRequest,
+    reverification_contracts::{FlagCohort as _, Insert as _},
+    role_contracts::{Insert as _, ListUnassigned as _},
+    user_contracts::{
+        Get as _, InsertMany as _, List as _, Search as _, UserFilter,
+        UserSearchQuery,
+    },
+    user_summary_contracts::Upsert as _,
+    webhook_endpoint_contracts::{Get as _, Insert as _},
+};
+use identify_domain::{
+    NewReverificationCampaignAttrs, NewRoleAttrs, NewUserAttrs,
+    NewWebhookEndpointAttrs, ReverificationCampaign, ReverificationCohort,
+    Role, RoleCohort, User, Username, WebhookEndpoint,
+};
+use identify_infrastructure::{
+    encryption::{EncryptionKey, FieldCipher, FieldEncryption, KeyRing},
+    storage::{
+        self,
+        connection::ReadPool,
+        reverifications::ReverificationsRepository,
+        roles::RolesRepository,
+        user_summaries::{UserSummariesReader, UserSummariesRepository},
+        users::{UsersRepository, reencrypt::reencrypt_users},
+        webhooks::{self, WebhookEndpointsRepository},
+    },
+};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+mod common;
+
+fn key(byte: u8) -> EncryptionKey {
+    EncryptionKey::from_base64(&base64_of([byte; 32])).unwrap()
+}
+
+fn base64_of(bytes: [u8; 32]) -> String {
+    use base64::{Engine, engine::general_purpose::STANDARD};
+
+    STANDARD.encode(bytes)
+}
+
+fn ring(current: &str, ids: &[&str]) -> KeyRing {
+    let keys = ids
+        .iter()
+        .enumerate()
+        .map(|(n, id)| (id.to_string(), key(n as u8 + 1)))
+        .collect::<HashMap<_, _>>();
+
+    KeyRing::new(current, keys, key(0)).unwrap()
+}
+
+fn encryption() -> FieldEncryption {
+    FieldEncryption::new(FieldCipher::new(ring("k1", &["k1"])))
+}
+
+async fn pool() -> (SqlitePool, FieldEncryption) {
+    (common::pool().await, encryption())
+}
+
+fn user(email: &str) -> User {
+    User::new(
+        NewUserAttrs {
+            email: email.to_owned(),
+            first_name: "Jane".to_owned(),
+            last_name: Some("Doe".to_owned()),
+        },
+        Username::parse(email.split('@').next().unwrap()).unwrap(),
+    )
+}
+
+async fn insert(
+    pool: &SqlitePool,
+    encryption: &FieldEncryption,
+    users: &[User],
+) {
+    let mut tx = storage::begin(pool).await.unwrap();
+    UsersRepository::new(&mut tx, encryption)
+        .insert_many(users)
+        .await
+        .unwrap();
+    storage::commit(tx).await.unwrap();
+}
+
+async fn stored(
+    pool: &SqlitePool,
+    id: Uuid,
+) -> (String, String, Option<String>) {
+    sqlx::query_as(
+        "select email, first_name, email_index from users where id = ?",
+    )
+    .bind(id)
+    .fetch_one(pool)
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn users_are_stored_encrypted() {
+    let (pool, encryption) = pool().await;
+    let jane = user("jane@acme.test");
+    insert(&pool, &encryption, std::slice::from_ref(&jane)).await;
+
+    let (email, first_name, email_index) = stored(&pool, jane.id()).await;
+    assert!(email.starts_with("enc:v1:k1:"));
+    assert!(first_name.starts_with("enc:v1:k1:"));
+    assert!(email_index.is_some());
+
+    let mut tx = storage::begin(&pool).await.unwrap();
+    let loaded = UsersRepository::new(&mut tx, &encryption)
+        .get(jane.id())
+        .await
+        .unwrap();
+    assert_eq!(loaded.email(), "jane@acme.test");
+    assert_eq!(loaded.first_name(), "Jane");
+    assert_eq!(loaded.last_name().as_deref(), Some("Doe"));
+}
+
+#[tokio::test]
+async fn names_that_look_encrypted_are_encrypted_as_they_are() {
+    let (pool, encryption) = pool().await;
+    let jane = User::new(
+        NewUserAttrs {
+            email: "jane-enc@acme.test".to_owned(),
+            first_name: "enc:v1:k:abc".to_owned(),
+            last_name: Some("plain:doe".to_owned()),
+        },
+        Username::parse("jane-enc").unwrap(),
+    );
+    insert(&pool, &encryption, std::slice::from_ref(&jane)).await;
+
+    let mut tx = storage::begin(&pool).await.unwrap();
+    let mut repository = UsersRepository::new(&mut tx, &encryption);
+    let loaded = repository.get(jane.id()).await.unwrap();
+    let listed = repository
+        .list(&ListQuery {
+            filter: UserFilter {
+                email: Some("jane-enc@acme.test".to_owned()),
+                ..UserFilter::default()
+            },
+            sort: vec![],
+            page: Default::default(),
+        })
+        .await
+        .unwrap();
+
+    assert_eq!(loaded.first_name(), "enc:v1:k:abc");
+    assert_eq!(loaded.last_name().as_deref(), Some("plain:doe"));
+    assert_eq!(listed.items[0].first_name(), "enc:v1:k:abc");
+}
+
+#[tokio::test]
+async fn users_are_filtered_by_the_blind_indexes_of_their_emails() {
+    let (pool, encryption) = pool().await;
+    insert(
+        &pool,
+        &encryption,
+        &[user("jane@acme.test"), user("john@acme.test")],
+    )
+    .await;
+
+    let mut tx = storage::begin(&pool).await.unwrap();
+    let users = UsersRepository::new(&mut tx, &encryption)
+        .list(&ListQuery {
+            filter: UserFilter {
+                email: Some("Jane@Acme.test".to_owned()),
+                ..UserFilter::default()
+            },
+            sort: vec![],
+            page: Default::default(),
+        })
+        .await
+        .unwrap();
+
+    assert_eq!(users.items.len(), 1);
+    assert_eq!(users.items[0].email(), "jane@acme.test");
+}
+
+#[tokio::test]
+async fn reencryption_encrypts_users_stored_in_the_clear() {
+    let (pool, encryption) = pool().await;
+    let users = (0..5)
+        .map(|n| user(&format!("user-{n}@acme.test")))
+        .collect::<Vec<_>>();
+    insert(&pool, &encryption, &users).await;
+    // As if they were stored before the encryption was enabled.
+    sqlx::query(
+        "update users set email = 'legacy-' || username || '@acme.test', \
+         first_name = 'Legacy', email_index = null, email_domain = null \
+         where username in ('user-1', 'user-3')",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let report = reencrypt_users(&pool, &encryption, 2).await.unwrap();
+
+    assert_eq!(report.users, 2);
+    for user in &users {
+        let (email, first_name, email_index) = stored(&pool, user.id()).await;
+        assert!(email.starts_with("enc:v1:k1:"));
+        assert!(first_name.starts_with("enc:v1:k1:"));
+        assert!(email_index.is_some());
+    }
+    let domains: Vec<String> =
+        sqlx::query_scalar("select distinct email_domain from users")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+    assert_eq!(domains, ["acme.test"]);
+    assert_eq!(
+        reencrypt_users(&pool, &encryption, 2).await.unwrap().users,
+        0
+    );
+}
+
+#[tokio::test]
+async fn users_are_filtered_by_the_composed_forms_of_their_emails() {
+    let (pool, encryption) = pool().await;
+    let jose = User::new(
+        NewUserAttrs {
+            // "é" as a single code point.
+            email: "jos\u{e9}@acme.test".to_owned(),
+            first_name: "José".to_owned(),
+            last_name: None,
+        },
+        Username::parse("jose").unwrap(),
+    );
+    insert(&pool, &encryption, &[jose]).await;
+
+    let mut tx = storage::begin(&pool).await.unwrap();
+    let users = UsersRepository::new(&mut tx, &encryption)
+        .list(&ListQuery {
+            filter: UserFilter {
+                // "e" followed by a combining acute accent.
+                email: Some("Jose\u{301}@acme.test".to_owned()),
+                ..UserFilter::default()
+            },
+            sort: vec![],
+            page: Default::default(),
+        })
+        .await
+        .unwrap();
+
+    assert_eq!(users.items.len(), 1);
+}
+
+#[tokio::test]
+async fn values_encrypted_with_other_keys_are_rejected() {
+    let (pool, encryption) = pool().await;
+    let jane = user("jane@acme.test");
+    insert(&pool, &encryption, std::slice::from_ref(&jane)).await;
+
+    let mut tx = storage::begin(&pool).await.unwrap();
+    let other = FieldEncryption::new(FieldCipher::new(ring("k2", &["k2"])));
+    let with_other_keys =
+        UsersRepository::new(&mut tx, &other).get(jane.id()).await;
+    let without_keys =
+        UsersRepository::new(&mut tx, &FieldEncryption::disabled())
+            .get(jane.id())
+            .await;
+
+    assert!(with_other_keys.is_err());
+    assert!(without_keys.is_err());
+}
+
+#[tokio::test]
+async fn users_are_filtered_by_the_domains_of_their_emails() {
+    let (pool, encryption) = pool().await;
+    insert(
+        &pool,
+        &encryption,
+        &[user("jane@acme.test"), user("john@globex.test")],
+    )
+    .await;
+
+    let mut tx = storage::begin(&pool).await.unwrap();
+    let users = UsersRepository::new(&mut tx, &encryption)
+        .list(&ListQuery {
+            filter: UserFilter {
+                email_domain: Some("ACME.test".to_owned()),
+                ..UserFilter::default()
+            },
+            sort: vec![],
+            page: Default::default(),
+        })
+        .await
+        .unwrap();
+
+    assert_eq!(users.items.len(), 1);
+    assert_eq!(users.items[0].email(), "jane@acme.test");
+}
+
+#[tokio::test]
+async fn reverification_cohorts_match_the_domains_of_encrypted_emails() {
+    let (pool, encryption) = pool().await;
+    insert(
+        &pool,
+        &encryption,
+        &[user("jane@acme.test"), user("john@globex.test")],
+    )
+    .await;
+    let campaign =
+        ReverificationCampaign::new(NewReverificationCampaignAttrs {
+            reason: "The domain has moved".to_owned(),
+            cohort: ReverificationCohort {
+                email_domain: Some("acme.test".to_owned()),
+                created_before: None,
+            },
+        })
+        .unwrap();
+
+    let mut tx = storage::begin(&pool).await.unwrap();
+    let mut repository = ReverificationsRepository::new(&mut tx, &encryption);
+    repository.insert(&campaign).await.unwrap();
+
+    assert_eq!(repository.flag_cohort(&campaign).await.unwrap(), 1);
+}
+
+#[tokio::test]
+async fn role_cohorts_match_the_domains_of_encrypted_emails() {
+    let (pool, encryption) = pool().await;
+    insert(
+        &pool,
+        &encryption,
+        &[user("jane@acme.test"), user("john@globex.test")],
+    )
+    .await;
+    let role = Role::new(NewRoleAttrs {
+        name: "support".to_owned(),
+        description: None,
+        permissions: vec!["users:read".to_owned()],
+        external_id: None,
+    })
+    .unwrap();
+    let cohort = RoleCohort::new(Some("acme.test".to_owned()), None).unwrap();
+
+    let mut tx = storage::begin(&pool).await.unwrap();
+    let mut repository = RolesRepository::new(&mut tx, &encryption);
+    repository.insert(&role).await.unwrap();
+    let users = repository
+        .list_unassigned(role.id(), &cohort, 10)
+        .await
+        .unwrap();
+
+    assert_eq!(users.len(), 1);
+    assert_eq!(users[0].email(), "jane@acme.test");
+}
+
+#[tokio::test]
+async fn encrypted_users_are_searched_by_domain_but_not_by_ciphertext() {
+    let (pool, encryption) = pool().await;
+    let jane = user("jane@acme.test");
+    let mut tx = storage::begin(&pool).await.unwrap();
+    UserSummariesRepository::new(&mut tx, &encryption)
+        .upsert(&jane)
+        .await
+        .unwrap();
+    storage::commit(tx).await.unwrap();
+
+    let search = async |text: &str| {
+        UserSummariesReader::new(
+            ReadPool::from(pool.clone()),
+            encryption.clone(),
+        )
+        .search(&UserSearchQuery {
+            text: text.to_owned(),
+            page: PageRequest::default(),
+        })
+        .await
+        .unwrap()
+        .total_items
+    };
+    assert_eq!(search("acme").await, 1);
+    assert_eq!(search("enc v1 k1").await, 0);
+    assert_eq!(search("jane").await, 0);
+}
+
+#[tokio::test]
+async fn webhook_secrets_are_stored_encrypted() {
+    let (pool, encryption) = pool().await;
+    let endpoint = WebhookEndpoint::new(NewWebhookEndpointAttrs {
+        url: "https://hooks.acme.test/identify".to_owned(),
+        secret: "0123456789abcdef".to_owned(),
+        events: vec![],
+        external_id: None,
+    })
+    .unwrap();
+    let mut tx = storage::begin(&pool).await.unwrap();
+    WebhookEndpointsRepository::new(&mut tx, &encryption)
+        .insert(&endpoint)
+        .await
+        .unwrap();
+    storage::commit(tx).await.unwrap();
+    // As if it was registered before the encryption was enabled.
+    let legacy = WebhookEndpoint::new(NewWebhookEndpointAttrs {
+        url: "https://hooks.globex.test/identify".to_owned(),
+        secret: "fedcba9876543210".to_owned(),
+        events: vec![],
+        external_id: None,
+    })
+    .unwrap();
+    sqlx::query(
+        "insert into webhook_endpoints \
+         (id, url, secret, event_types, created_at, updated_at) \
+         values (?, ?, ?, '', ?, ?)",
+    )
+    .bind(legacy.id())
+    .bind(legacy.url())
+    .bind(legacy.secret())
+    .bind(legacy.created_at())
+    .bind(legacy.updated_at())
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let secret = async |id: Uuid| -> String {
+        sqlx::query_scalar("select secret from webhook_endpoints where id = ?")
+            .bind(id)
+            .fetch_one(&pool)
+            .await
+            .unwrap()
+    };
+    assert!(secret(endpoint.id()).await.starts_with("enc:v1:k1:"));
+    assert_eq!(
+        webhooks::reencrypt_secrets(&pool, &encryption)
+            .await
+            .unwrap(),
+        1
+    );
+    assert!(secret(legacy.id()).await.starts_with("enc:v1:k1:"));
+
+    let mut tx = storage::begin(&pool).await.unwrap();
+    let mut repository = WebhookEndpointsRepository::new(&mut tx, &encryption);
+    for expected in [&endpoint, &legacy] {
+        let loaded = repository.get(expected.id()).await.unwrap();
+        assert_eq!(loaded.secret(), expected.secret());
+    }
+}
+
+#[test]
+fn values_encrypted_with_previous_keys_can_be_decrypted() {
+    let old = FieldCipher::new(ring("k1", &["k1"]));
+    let rotated = FieldCipher::new(ring("k2", &["k1", "k2"]));
+    let value = old.encrypt("jane@acme.test");
+
+    assert!(!rotated.is_current(&value));
+    assert_eq!(rotated.decrypt(&value).unwrap(), "jane@acme.test");
+    assert!(rotated.is_current(&rotated.encrypt("jane@acme.test")));
+    assert_eq!(rotated.decrypt("plain").unwrap(), "plain");
+    assert_eq!(
+        old.blind_index(" Jane@Acme.test"),
+        rotated.blind_index("jane@acme.test")
+    );
+}
+
+#[test]
+fn blind_indexes_are_the_same_for_composed_and_decomposed_emails() {
+    let cipher = FieldCipher::new(ring("k1", &["k1"]));
+
+    assert_eq!(
+        cipher.blind_index("jos\u{e9}@acme.test"),
+        cipher.blind_index("JOSE\u{301}@acme.test")
+    );
+}
+
+#[test]
+fn escaped_values_are_read_without_their_escape() {
+    let cipher = FieldCipher::new(ring("k1", &["k1"]));
+
+    assert_eq!(
+        cipher.decrypt("plain:enc:v1:k:abc").unwrap(),
+        "enc:v1:k:abc"
+    );
+    assert!(!cipher.is_current("plain:enc:v1:k1:abc"));
+}
+
+#[test]
+fn values_encrypted_with_unknown_keys_are_rejected() {
+    let value = FieldCipher::new(ring("k2", &["k1", "k2"])).encrypt("jane");
+
+    assert!(
+        FieldCipher::new(ring("k1", &["k1"]))
+            .decrypt(&value)
+            .is_err()
+    );
+    assert!(KeyRing::new("k3", HashMap::new(), key(0)).is_err());
+}
@@ -0,0 +1,49 @@
+use identify_application::GeoResolver;
+use identify_infrastructure::geo::NetworkGeoResolver;
+
+const TABLE: &str = "
+# Networks of the examples
+203.0.113.0/24,Paris, France
+203.0.113.128/25, Lyon, France
+2001:db8::/32,Berlin, Germany
+";
+
+async fn locate(resolver: &NetworkGeoResolver, ip: &str) -> Option<String> {
+    resolver.locate(ip.parse().unwrap()).await.unwrap()
+}
+
+#[tokio::test]
+async fn the_most_specific_network_wins() {
+    let resolver = NetworkGeoResolver::parse(TABLE).unwrap();
+
+    assert_eq!(
+        locate(&resolver, "203.0.113.7").await.as_deref(),
+        Some("Paris, France")
+    );
+    assert_eq!(
+        locate(&resolver, "203.0.113.200").await.as_deref(),
+        Some("Lyon, France")
+    );
+    assert_eq!(
+        locate(&resolver, "2001:db8::1").await.as_deref(),
+        Some("Berlin, Germany")
+    );
+    assert_eq!(
+        locate(&resolver, "::ffff:203.0.113.7").await.as_deref(),
+        Some("Paris, France")
+    );
+    assert_eq!(locate(&resolver, "198.51.100.1").await, None);
+}
+
+#[test]
+fn malformed_lines_are_reported() {
+    for table in [
+        "203.0.113.0/24",
+        "203.0.113.0,Paris",
+        "203.0.113.0/33,Paris",
+        "example.test/24,Paris",
+    ] {
+        let error = NetworkGeoResolver::parse(table).unwrap_err();
+        assert!(format!("{error:#}").contains("line 1"), "{error:#}");
+    }
+}
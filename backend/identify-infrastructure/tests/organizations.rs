@@ -0,0 +1,103 @@
+use std::sync::Arc;
+
+use identify_application::{
+    ApplicationError, OrganizationUseCaseDeps, PutOrganizationParams,
+    PutOutcome, put_organization,
+};
+use identify_domain::Organization;
+use identify_infrastructure::{
+    encryption::FieldEncryption, storage::unit_of_work::SqliteUnitOfWork,
+};
+use sqlx::SqlitePool;
+
+mod common;
+
+use common::pool;
+
+/// Stores the fields in the clear, as the tests of this file don't check the encryption.
+static NO_ENCRYPTION: FieldEncryption = FieldEncryption::disabled();
+
+async fn put(pool: &SqlitePool, name: &str) -> (Organization, PutOutcome) {
+    put_with_slug(pool, "acme", name, None).await.unwrap()
+}
+
+async fn put_with_slug(
+    pool: &SqlitePool,
+    external_id: &str,
+    name: &str,
+    slug: Option<&str>,
+) -> identify_application::Result<(Organization, PutOutcome)> {
+    let unit_of_work =
+        SqliteUnitOfWork::begin(pool, &NO_ENCRYPTION).await.unwrap();
+
+    put_organization(
+        OrganizationUseCaseDeps::new(unit_of_work),
+        PutOrganizationParams {
+            external_id: external_id.to_owned(),
+            name: name.to_owned(),
+            slug: slug.map(str::to_owned),
+            identifier_policy: Arc::default(),
+        },
+    )
+    .await
+}
+
+#[tokio::test]
+async fn put_organization_keeps_the_id() {
+    let pool = pool().await;
+
+    let (created, outcome) = put(&pool, "Acme").await;
+    assert_eq!(outcome, PutOutcome::Created);
+
+    let (unchanged, outcome) = put(&pool, "Acme").await;
+    assert_eq!(outcome, PutOutcome::Unchanged);
+    assert_eq!(unchanged.id(), created.id());
+    assert_eq!(unchanged.updated_at(), created.updated_at());
+
+    let (updated, outcome) = put(&pool, "Acme Inc.").await;
+    assert_eq!(outcome, PutOutcome::Updated);
+    assert_eq!(updated.id(), created.id());
+    assert_eq!(updated.name(), "Acme Inc.");
+}
+
+#[tokio::test]
+async fn slugs_are_derived_from_names() {
+    let pool = pool().await;
+
+    let (first, _) = put_with_slug(&pool, "first", "ACME Corp.", None)
+        .await
+        .unwrap();
+    let (second, _) = put_with_slug(&pool, "second", "Acme corp", None)
+        .await
+        .unwrap();
+
+    assert_eq!(first.slug(), "acme-corp");
+    assert_eq!(second.slug(), "acme-corp-2");
+}
+
+#[tokio::test]
+async fn replaced_slugs_stay_reserved() {
+    let pool = pool().await;
+
+    let (created, _) =
+        put_with_slug(&pool, "acme", "Acme", None).await.unwrap();
+    let (updated, outcome) =
+        put_with_slug(&pool, "acme", "Acme", Some("acme-inc"))
+            .await
+            .unwrap();
+    assert_eq!(outcome, PutOutcome::Updated);
+    assert_eq!(updated.slug(), "acme-inc");
+
+    let result =
+        put_with_slug(&pool, "other", "Other", Some(created.slug())).await;
+    assert!(matches!(
+        result,
+        Err(ApplicationError::EntityAlreadyExists { .. })
+    ));
+
+    // The organization can take its previous slug back.
+    let (reverted, _) = put_with_slug(&pool, "acme", "Acme", Some("acme"))
+        .await
+        .unwrap();
+    assert_eq!(reverted.slug(), "acme");
+}
@@ -0,0 +1,94 @@
+use std::sync::Arc;
+
+use identify_application::{
+    CreateUser, CreateUserParams, EventFeedUseCaseDeps, System,
+    TailUserEventsParams, TransactionalUseCaseExt as _, UseCase as _,
+    UserEventTail, tail_user_events,
+};
+use identify_domain::{NewUserAttrs, QuotaPolicy, User, UsernameStrategy};
+use identify_infrastructure::{
+    encryption::FieldEncryption,
+    storage::{
+        self, outbox::OutboxRepository, unit_of_work::SqliteUnitOfWorkFactory,
+    },
+};
+use sqlx::SqlitePool;
+
+mod common;
+
+use common::pool;
+
+async fn create_user(pool: &SqlitePool, email: &str) -> User {
+    CreateUser
+        .in_transaction(SqliteUnitOfWorkFactory::new(
+            pool.clone(),
+            FieldEncryption::disabled(),
+        ))
+        .execute(
+            &System,
+            CreateUserParams {
+                user_attrs: NewUserAttrs {
+                    email: email.to_owned(),
+                    first_name: "John".to_owned(),
+                    last_name: None,
+                },
+                username: None,
+                username_strategy: UsernameStrategy::default(),
+                identifier_policy: Arc::default(),
+                organization_id: None,
+                quota_policy: QuotaPolicy::default(),
+            },
+        )
+        .await
+        .unwrap()
+}
+
+async fn tail(pool: &SqlitePool, after: Option<u64>) -> UserEventTail {
+    let mut conn = pool.acquire().await.unwrap();
+
+    tail_user_events(
+        EventFeedUseCaseDeps::new(&mut OutboxRepository::new(&mut conn)),
+        TailUserEventsParams { after, limit: 10 },
+    )
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn events_are_tailed_in_the_order_they_were_recorded() {
+    let pool = pool().await;
+    let start = tail(&pool, None).await;
+    let first = create_user(&pool, "first@acme.test").await;
+    let second = create_user(&pool, "second@acme.test").await;
+
+    let tail = tail(&pool, Some(start.sequence)).await;
+
+    let users = tail
+        .events
+        .iter()
+        .map(|recorded| recorded.event.user_id())
+        .collect::<Vec<_>>();
+    assert!(start.events.is_empty());
+    assert_eq!(users, [first.id(), second.id()]);
+    assert_eq!(tail.sequence, tail.events[1].sequence);
+}
+
+#[tokio::test]
+async fn positions_of_pruned_events_are_not_reused() {
+    let pool = pool().await;
+    create_user(&pool, "first@acme.test").await;
+    let seen = tail(&pool, None).await.sequence;
+    let mut tx = storage::begin(&pool).await.unwrap();
+    sqlx::query("delete from outbox")
+        .execute(&mut *tx)
+        .await
+        .unwrap();
+    storage::commit(tx).await.unwrap();
+
+    let user = create_user(&pool, "second@acme.test").await;
+
+    let tail = tail(&pool, Some(seen)).await;
+    assert_eq!(tail.events.len(), 1);
+    assert_eq!(tail.events[0].event.user_id(), user.id());
+    assert!(tail.sequence > seen);
+}
@@ -0,0 +1,242 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use chrono::Utc;
+use identify_application::{
+    Actor, ApplicationError, CreateUser, CreateUserParams, CreateUsersParams,
+    System, TransactionalUseCaseExt as _, UseCase, UseCaseExt as _,
+    UseCaseMetrics, UserLifecycleUseCaseDeps, create_users,
+    user_contracts::Insert as _,
+};
+use identify_domain::{
+    NewUserAttrs, QuotaPolicy, User, UserAttrs, UserId, UserIdAttrs,
+    UsernameStrategy,
+};
+use identify_infrastructure::{
+    encryption::FieldEncryption,
+    storage::{
+        self,
+        unit_of_work::{SqliteUnitOfWork, SqliteUnitOfWorkFactory},
+        users::UsersRepository,
+    },
+};
+use sqlx::SqlitePool;
+
+mod common;
+
+use common::pool;
+
+/// Stores the fields in the clear, as the tests of this file don't check the encryption.
+static NO_ENCRYPTION: FieldEncryption = FieldEncryption::disabled();
+
+/// An actor with a single permission.
+struct Reader;
+
+impl Actor for Reader {
+    fn subject(&self) -> &str {
+        "reader"
+    }
+
+    fn has_permission(&self, permission: &str) -> bool {
+        permission == "users:read"
+    }
+}
+
+#[derive(Clone, Default)]
+struct RecordedMetrics(Arc<Mutex<Vec<(&'static str, &'static str)>>>);
+
+impl UseCaseMetrics for RecordedMetrics {
+    fn record(
+        &self,
+        use_case: &'static str,
+        outcome: &'static str,
+        _elapsed: Duration,
+    ) {
+        self.0.lock().unwrap().push((use_case, outcome));
+    }
+}
+
+fn params(email: &str, username: Option<&str>) -> CreateUserParams {
+    CreateUserParams {
+        user_attrs: NewUserAttrs {
+            email: email.to_owned(),
+            first_name: "John".to_owned(),
+            last_name: None,
+        },
+        username: username.map(str::to_owned),
+        username_strategy: UsernameStrategy::default(),
+        identifier_policy: Arc::default(),
+        organization_id: None,
+        quota_policy: QuotaPolicy::default(),
+    }
+}
+
+async fn count_users(pool: &SqlitePool) -> i64 {
+    sqlx::query_scalar("select count(*) from users")
+        .fetch_one(pool)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn successful_use_cases_are_committed() {
+    let pool = pool().await;
+    let metrics = RecordedMetrics::default();
+    let create_user = CreateUser
+        .in_transaction(SqliteUnitOfWorkFactory::new(
+            pool.clone(),
+            NO_ENCRYPTION.clone(),
+        ))
+        .authorized("users:write")
+        .measured(metrics.clone())
+        .logged();
+
+    let user = create_user
+        .execute(&System, params("john@acme.test", None))
+        .await
+        .unwrap();
+
+    assert_eq!(create_user.name(), "create_user");
+    assert_eq!(user.to_attributes().email, "john@acme.test");
+    assert_eq!(count_users(&pool).await, 1);
+    assert_eq!(*metrics.0.lock().unwrap(), [("create_user", "ok")]);
+}
+
+#[tokio::test]
+async fn failed_use_cases_are_rolled_back() {
+    let pool = pool().await;
+    let metrics = RecordedMetrics::default();
+    let create_user = CreateUser
+        .in_transaction(SqliteUnitOfWorkFactory::new(
+            pool.clone(),
+            NO_ENCRYPTION.clone(),
+        ))
+        .measured(metrics.clone());
+
+    create_user
+        .execute(&System, params("john@acme.test", Some("john")))
+        .await
+        .unwrap();
+    let result = create_user
+        .execute(&System, params("jane@acme.test", Some("john")))
+        .await;
+
+    assert!(matches!(
+        result,
+        Err(ApplicationError::EntityAlreadyExists { .. })
+    ));
+    assert_eq!(count_users(&pool).await, 1);
+    assert_eq!(
+        *metrics.0.lock().unwrap(),
+        [("create_user", "ok"), ("create_user", "already_exists")]
+    );
+}
+
+#[tokio::test]
+async fn actors_without_the_permission_are_rejected() {
+    let pool = pool().await;
+    let create_user = CreateUser
+        .in_transaction(SqliteUnitOfWorkFactory::new(
+            pool.clone(),
+            NO_ENCRYPTION.clone(),
+        ))
+        .authorized("users:write");
+
+    let result = create_user
+        .execute(&Reader, params("john@acme.test", None))
+        .await;
+
+    assert!(matches!(
+        result,
+        Err(ApplicationError::Forbidden { permission }) if permission == "users:write"
+    ));
+    assert_eq!(count_users(&pool).await, 0);
+}
+
+#[tokio::test]
+async fn users_stored_with_ids_of_the_first_version_are_not_created_again() {
+    let pool = pool().await;
+    let now = Utc::now();
+    let id = UserId::new(UserIdAttrs {
+        email: "John@Acme.test".to_owned(),
+    });
+    let legacy = User::load(UserAttrs {
+        id: id.to_uuid_v1(),
+        email: "John@Acme.test".to_owned(),
+        username: "john".to_owned(),
+        first_name: "John".to_owned(),
+        last_name: None,
+        locale: None,
+        timezone: None,
+        version: 1,
+        created_at: now,
+        updated_at: now,
+        erased_at: None,
+        email_changed_at: None,
+        last_login_at: None,
+        login_count: 0,
+    })
+    .unwrap();
+    let mut tx = storage::begin(&pool).await.unwrap();
+    UsersRepository::new(&mut tx, &NO_ENCRYPTION)
+        .insert(&legacy)
+        .await
+        .unwrap();
+    storage::commit(tx).await.unwrap();
+
+    let result = CreateUser
+        .in_transaction(SqliteUnitOfWorkFactory::new(
+            pool.clone(),
+            NO_ENCRYPTION.clone(),
+        ))
+        .execute(&System, params("john@acme.test", None))
+        .await;
+
+    assert!(matches!(
+        result,
+        Err(ApplicationError::EntityAlreadyExists { .. })
+    ));
+    assert_eq!(count_users(&pool).await, 1);
+}
+
+#[tokio::test]
+async fn batches_name_every_row_whose_email_is_taken() {
+    let pool = pool().await;
+    CreateUser
+        .in_transaction(SqliteUnitOfWorkFactory::new(
+            pool.clone(),
+            NO_ENCRYPTION.clone(),
+        ))
+        .execute(&System, params("john@acme.test", None))
+        .await
+        .unwrap();
+
+    let uow = SqliteUnitOfWork::begin(&pool, &NO_ENCRYPTION)
+        .await
+        .unwrap();
+    let result = create_users(
+        UserLifecycleUseCaseDeps::new(uow),
+        CreateUsersParams {
+            users: [
+                "jane@acme.test",
+                "John@Acme.test",
+                "jim@acme.test",
+                "john@acme.test",
+            ]
+            .map(|email| (params(email, None).user_attrs, None))
+            .to_vec(),
+            username_strategy: UsernameStrategy::default(),
+            identifier_policy: Arc::default(),
+        },
+    )
+    .await;
+
+    assert!(matches!(
+        result,
+        Err(ApplicationError::EntityAlreadyExists { message, .. })
+            if message == "Emails are already taken: John@Acme.test (row 2), john@acme.test (row 4)"
+    ));
+    assert_eq!(count_users(&pool).await, 1);
+}
@@ -0,0 +1,86 @@
+use std::{
+    io,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use identify_infrastructure::storage::{
+    connection::{self, PoolConfig},
+    rate_limits::{self, BucketParams},
+    timing,
+};
+use tracing_subscriber::fmt::MakeWriter;
+
+/// Collects the logs written by a subscriber.
+#[derive(Clone, Default)]
+struct Logs(Arc<Mutex<Vec<u8>>>);
+
+impl Logs {
+    fn take(&self) -> String {
+        String::from_utf8(std::mem::take(&mut *self.0.lock().unwrap())).unwrap()
+    }
+}
+
+impl io::Write for Logs {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> MakeWriter<'a> for Logs {
+    type Writer = Logs;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        self.clone()
+    }
+}
+
+#[tokio::test]
+async fn queries_are_logged_by_name_without_their_parameters() {
+    let logs = Logs::default();
+    let _subscriber = tracing::subscriber::set_default(
+        tracing_subscriber::fmt()
+            .with_max_level(tracing::Level::DEBUG)
+            .with_ansi(false)
+            .with_writer(logs.clone())
+            .finish(),
+    );
+    let url = "sqlite::memory:";
+    let pool = connection::get_pool(url, &PoolConfig::for_url(url))
+        .await
+        .unwrap();
+    connection::migrate(&pool).await.unwrap();
+    let params = BucketParams {
+        capacity: 1.0,
+        refill_per_second: 1.0,
+    };
+    logs.take();
+
+    // Every query is slower than the shortest threshold.
+    timing::set_slow_query_threshold(Some(Duration::ZERO));
+    rate_limits::take_token(&pool, "secret-key", params, 0.0)
+        .await
+        .unwrap();
+    let slow = logs.take();
+
+    timing::set_slow_query_threshold(None);
+    rate_limits::take_token(&pool, "secret-key", params, 0.0)
+        .await
+        .unwrap();
+    let fast = logs.take();
+    timing::set_slow_query_threshold(Some(
+        timing::DEFAULT_SLOW_QUERY_THRESHOLD,
+    ));
+
+    assert!(slow.contains("WARN"), "{slow}");
+    assert!(slow.contains("Slow query"), "{slow}");
+    assert!(slow.contains("rate_limits.take"), "{slow}");
+    assert!(!slow.contains("secret-key"), "{slow}");
+    assert!(fast.contains("Query executed"), "{fast}");
+    assert!(!fast.contains("Slow query"), "{fast}");
+}
@@ -0,0 +1,69 @@
+use identify_infrastructure::storage::rate_limits::{self, BucketParams};
+
+mod common;
+
+use common::pool;
+
+const BUCKET: BucketParams = BucketParams {
+    capacity: 2.0,
+    refill_per_second: 0.5,
+};
+
+#[tokio::test]
+async fn buckets_start_full_and_run_out() {
+    let pool = pool().await;
+
+    let allowed = [
+        rate_limits::take_token(&pool, "caller", BUCKET, 0.0)
+            .await
+            .unwrap(),
+        rate_limits::take_token(&pool, "caller", BUCKET, 0.0)
+            .await
+            .unwrap(),
+        rate_limits::take_token(&pool, "caller", BUCKET, 0.0)
+            .await
+            .unwrap(),
+    ]
+    .map(|taken| taken.allowed);
+    assert_eq!(allowed, [true, true, false]);
+
+    let other = rate_limits::take_token(&pool, "other", BUCKET, 0.0)
+        .await
+        .unwrap();
+    assert!(other.allowed);
+}
+
+#[tokio::test]
+async fn buckets_are_refilled_up_to_the_capacity() {
+    let pool = pool().await;
+    for _ in 0..3 {
+        rate_limits::take_token(&pool, "caller", BUCKET, 0.0)
+            .await
+            .unwrap();
+    }
+
+    let taken = rate_limits::take_token(&pool, "caller", BUCKET, 2.0)
+        .await
+        .unwrap();
+    assert!(taken.allowed);
+    assert_eq!(taken.tokens, 0.0);
+
+    let taken = rate_limits::take_token(&pool, "caller", BUCKET, 100.0)
+        .await
+        .unwrap();
+    assert!(taken.allowed);
+    assert_eq!(taken.tokens, 1.0);
+}
+
+#[tokio::test]
+async fn idle_buckets_are_deleted() {
+    let pool = pool().await;
+    rate_limits::take_token(&pool, "idle", BUCKET, 0.0)
+        .await
+        .unwrap();
+    rate_limits::take_token(&pool, "active", BUCKET, 10.0)
+        .await
+        .unwrap();
+
+    assert_eq!(rate_limits::delete_idle(&pool, 5.0).await.unwrap(), 1);
+}
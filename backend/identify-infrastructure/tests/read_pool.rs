@@ -0,0 +1,90 @@
+use identify_application::user_contracts::{Get as _, InsertMany as _};
+use identify_domain::{NewUserAttrs, User, Username};
+use identify_infrastructure::{
+    encryption::FieldEncryption,
+    storage::{
+        self,
+        connection::{self, PoolConfig, ReadPool},
+        users::UsersRepository,
+    },
+};
+use sqlx::SqlitePool;
+use tempfile::TempDir;
+
+/// Stores the fields in the clear, as the tests of this file don't check the encryption.
+static NO_ENCRYPTION: FieldEncryption = FieldEncryption::disabled();
+
+fn url(dir: &TempDir) -> String {
+    format!("sqlite://{}", dir.path().join("identify.db").display())
+}
+
+async fn pools(url: &str) -> (SqlitePool, ReadPool) {
+    let config = PoolConfig::for_url(url);
+    let pool = connection::get_pool(url, &config).await.unwrap();
+    connection::migrate(&pool).await.unwrap();
+    let read_pool = connection::get_read_pool(url, &config, &pool)
+        .await
+        .unwrap();
+
+    (pool, read_pool)
+}
+
+async fn insert_user(pool: &SqlitePool) -> User {
+    let user = User::new(
+        NewUserAttrs {
+            email: "john@acme.test".to_owned(),
+            first_name: "John".to_owned(),
+            last_name: None,
+        },
+        Username::parse("john").unwrap(),
+    );
+
+    let mut tx = storage::begin(pool).await.unwrap();
+    UsersRepository::new(&mut tx, &NO_ENCRYPTION)
+        .insert_many(std::slice::from_ref(&user))
+        .await
+        .unwrap();
+    storage::commit(tx).await.unwrap();
+
+    user
+}
+
+#[tokio::test]
+async fn read_pool_sees_committed_writes() {
+    let dir = TempDir::new().unwrap();
+    let (pool, read_pool) = pools(&url(&dir)).await;
+    let user = insert_user(&pool).await;
+
+    let mut conn = storage::acquire(&read_pool).await.unwrap();
+    let found = UsersRepository::new(&mut conn, &NO_ENCRYPTION)
+        .get(user.id())
+        .await
+        .unwrap();
+
+    assert_eq!(found.email(), user.email());
+}
+
+#[tokio::test]
+async fn read_pool_rejects_writes() {
+    let dir = TempDir::new().unwrap();
+    let (_, read_pool) = pools(&url(&dir)).await;
+
+    let mut conn = storage::acquire(&read_pool).await.unwrap();
+    let result = sqlx::query("delete from users").execute(&mut *conn).await;
+
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn in_memory_databases_are_read_through_the_write_pool() {
+    let (pool, read_pool) = pools("sqlite::memory:").await;
+    let user = insert_user(&pool).await;
+
+    let mut conn = storage::acquire(&read_pool).await.unwrap();
+    let found = UsersRepository::new(&mut conn, &NO_ENCRYPTION)
+        .get(user.id())
+        .await
+        .unwrap();
+
+    assert_eq!(found.email(), user.email());
+}
@@ -0,0 +1,63 @@
+use std::time::{Duration, Instant};
+
+use identify_infrastructure::storage::connection::{
+    self, PoolConfig, ReconnectPolicy,
+};
+
+fn policy() -> ReconnectPolicy {
+    ReconnectPolicy {
+        max_attempts: 4,
+        initial_backoff: Duration::from_millis(20),
+        max_backoff: Duration::from_millis(50),
+    }
+}
+
+#[test]
+fn backoff_doubles_after_every_failed_attempt() {
+    let policy = policy();
+
+    let schedule: Vec<_> =
+        (1..=5).map(|attempt| policy.backoff(attempt)).collect();
+
+    assert_eq!(schedule, [20, 40, 50, 50, 50].map(Duration::from_millis),);
+}
+
+#[test]
+fn backoff_does_not_overflow() {
+    let policy = ReconnectPolicy {
+        max_attempts: u32::MAX,
+        ..ReconnectPolicy::default()
+    };
+
+    assert_eq!(policy.backoff(0), policy.initial_backoff);
+    assert_eq!(policy.backoff(u32::MAX), policy.max_backoff);
+}
+
+#[tokio::test]
+async fn connecting_stops_after_the_last_attempt() {
+    let directory = tempfile::tempdir().unwrap();
+    // The database can't be created in a directory that doesn't exist.
+    let url = format!(
+        "sqlite://{}",
+        directory
+            .path()
+            .join("missing")
+            .join("identify.db")
+            .display()
+    );
+    let config = PoolConfig {
+        reconnect: policy(),
+        ..PoolConfig::default()
+    };
+    let started = Instant::now();
+
+    let result = connection::get_pool(&url, &config).await;
+
+    assert!(result.is_err());
+    // Waited after each of the first three attempts.
+    let elapsed = started.elapsed();
+    assert!(
+        elapsed >= Duration::from_millis(20 + 40 + 50),
+        "{elapsed:?}"
+    );
+}
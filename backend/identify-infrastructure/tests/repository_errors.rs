@@ -0,0 +1,111 @@
+use identify_application::{
+    ApplicationError,
+    membership_contracts::Insert as _,
+    user_contracts::{Get as _, Insert as _},
+};
+use identify_domain::{
+    Membership, MembershipRole, NewMembershipAttrs, NewUserAttrs, User,
+    Username,
+};
+use identify_infrastructure::{
+    encryption::FieldEncryption,
+    storage::{
+        self, RepositoryError, memberships::MembershipsRepository,
+        users::UsersRepository,
+    },
+};
+use uuid::Uuid;
+
+mod common;
+
+use common::pool;
+
+/// Stores the fields in the clear, as the tests of this file don't check the encryption.
+static NO_ENCRYPTION: FieldEncryption = FieldEncryption::disabled();
+
+fn user(username: &str) -> User {
+    User::new(
+        NewUserAttrs {
+            email: format!("{username}@acme.test"),
+            first_name: "Jane".to_owned(),
+            last_name: None,
+        },
+        Username::parse(username).unwrap(),
+    )
+}
+
+#[tokio::test]
+async fn unique_violations_name_the_constraint() {
+    let pool = pool().await;
+    let mut tx = storage::begin(&pool).await.unwrap();
+    let mut repository = UsersRepository::new(&mut tx, &NO_ENCRYPTION);
+    repository.insert(&user("jane")).await.unwrap();
+    repository.insert(&user("john")).await.unwrap();
+    storage::commit(tx).await.unwrap();
+
+    let e = sqlx::query(
+        "update users set username = 'jane' where username = 'john'",
+    )
+    .execute(&pool)
+    .await
+    .unwrap_err();
+
+    let e = RepositoryError::from(e);
+    assert!(matches!(
+        &e,
+        RepositoryError::UniqueViolation { constraint } if constraint == "users.username"
+    ));
+    assert!(matches!(
+        ApplicationError::from(e),
+        ApplicationError::EntityAlreadyExists { entity, .. } if entity == "users"
+    ));
+}
+
+#[tokio::test]
+async fn foreign_key_violations_are_reference_violations() {
+    let pool = pool().await;
+    let membership = Membership::new(NewMembershipAttrs {
+        organization_id: Uuid::new_v4(),
+        user_id: Uuid::new_v4(),
+        role: MembershipRole::Member,
+    });
+
+    let mut tx = storage::begin(&pool).await.unwrap();
+    let result = MembershipsRepository::new(&mut tx)
+        .insert(&membership)
+        .await;
+
+    assert!(matches!(result, Err(ApplicationError::ReferenceViolation)));
+}
+
+#[tokio::test]
+async fn missing_rows_are_reported_by_lookups() {
+    let pool = pool().await;
+    let id = Uuid::new_v4();
+
+    let mut tx = storage::begin(&pool).await.unwrap();
+    let result = UsersRepository::new(&mut tx, &NO_ENCRYPTION).get(id).await;
+
+    assert!(matches!(
+        result,
+        Err(ApplicationError::EntityNotFound { id: missing, .. })
+            if missing == id.to_string()
+    ));
+    assert!(matches!(
+        RepositoryError::from(sqlx::Error::RowNotFound),
+        RepositoryError::NotFound
+    ));
+}
+
+#[tokio::test]
+async fn closed_pools_are_connection_errors() {
+    let pool = pool().await;
+    pool.close().await;
+
+    let e = sqlx::query("select 1").execute(&pool).await.unwrap_err();
+
+    assert!(matches!(
+        RepositoryError::from(e),
+        RepositoryError::Connection(_)
+    ));
+}
@@ -0,0 +1,142 @@
+use std::{
+    sync::atomic::{AtomicU32, Ordering},
+    time::Duration,
+};
+
+use identify_infrastructure::storage::{
+    self,
+    connection::{self, PoolConfig, SqlitePragmas},
+    retry::{Retrier, RetryPolicy},
+};
+use tempfile::TempDir;
+
+fn policy() -> RetryPolicy {
+    RetryPolicy {
+        max_attempts: 3,
+        initial_backoff: Duration::from_millis(1),
+        max_backoff: Duration::from_millis(5),
+        ..RetryPolicy::default()
+    }
+}
+
+/// Fails with a transient error until the provided attempt.
+async fn fail_until(
+    attempts: &AtomicU32,
+    succeeding_attempt: u32,
+) -> Result<u32, sqlx::Error> {
+    let attempt = attempts.fetch_add(1, Ordering::SeqCst) + 1;
+    if attempt < succeeding_attempt {
+        return Err(sqlx::Error::PoolTimedOut);
+    }
+
+    Ok(attempt)
+}
+
+#[tokio::test]
+async fn transient_errors_are_retried() {
+    let retrier = Retrier::new(policy());
+    let attempts = AtomicU32::new(0);
+
+    let result = retrier.run("test", || fail_until(&attempts, 3)).await;
+
+    assert_eq!(result.unwrap(), 3);
+}
+
+#[tokio::test]
+async fn retries_stop_after_the_last_attempt() {
+    let retrier = Retrier::new(policy());
+    let attempts = AtomicU32::new(0);
+
+    let result = retrier.run("test", || fail_until(&attempts, 4)).await;
+
+    assert!(matches!(result, Err(sqlx::Error::PoolTimedOut)));
+    assert_eq!(attempts.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn other_errors_are_not_retried() {
+    let retrier = Retrier::new(policy());
+    let attempts = AtomicU32::new(0);
+
+    let result: Result<(), _> = retrier
+        .run("test", || async {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            Err(sqlx::Error::RowNotFound)
+        })
+        .await;
+
+    assert!(matches!(result, Err(sqlx::Error::RowNotFound)));
+    assert_eq!(attempts.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn retries_are_limited_by_the_budget() {
+    let retrier = Retrier::new(RetryPolicy {
+        budget_ratio: 0.0,
+        budget_reserve: 1,
+        ..policy()
+    });
+    let attempts = AtomicU32::new(0);
+
+    // The only retry of the budget is spent on the first execution.
+    retrier
+        .run("test", || fail_until(&attempts, 2))
+        .await
+        .unwrap();
+    attempts.store(0, Ordering::SeqCst);
+    let result = retrier.run("test", || fail_until(&attempts, 2)).await;
+
+    assert!(matches!(result, Err(sqlx::Error::PoolTimedOut)));
+    assert_eq!(attempts.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn transactions_locked_out_by_a_concurrent_writer_are_retried() {
+    let dir = TempDir::new().unwrap();
+    let url = format!("sqlite://{}", dir.path().join("identify.db").display());
+    let pool = connection::get_pool(
+        &url,
+        &PoolConfig {
+            // Fail right away instead of waiting for the lock.
+            pragmas: SqlitePragmas {
+                busy_timeout: Duration::ZERO,
+                ..SqlitePragmas::default()
+            },
+            ..PoolConfig::default()
+        },
+    )
+    .await
+    .unwrap();
+    connection::migrate(&pool).await.unwrap();
+    let retrier = Retrier::new(RetryPolicy {
+        max_attempts: 10,
+        initial_backoff: Duration::from_millis(20),
+        max_backoff: Duration::from_millis(100),
+        ..policy()
+    });
+
+    let mut writer = storage::begin(&pool).await.unwrap();
+    sqlx::query("delete from rate_limit_buckets")
+        .execute(&mut *writer)
+        .await
+        .unwrap();
+    let released = tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        storage::commit(writer).await.unwrap();
+    });
+
+    let inserted = retrier
+        .run("test", || async {
+            let mut tx = pool.begin().await?;
+            sqlx::query(
+                "insert into rate_limit_buckets (key, tokens, allowed, updated_at) values ('a', 1, true, 0)",
+            )
+            .execute(&mut *tx)
+            .await?;
+            tx.commit().await
+        })
+        .await;
+    released.await.unwrap();
+
+    inserted.unwrap();
+}
@@ -0,0 +1,78 @@
+use chrono::{TimeDelta, Utc};
+use identify_application::{Session, SessionStore};
+use identify_infrastructure::sessions::MemorySessionStore;
+use uuid::Uuid;
+
+fn session(device_id: Uuid, valid_for: TimeDelta) -> Session {
+    let now = Utc::now();
+    Session {
+        user_id: Uuid::new_v4(),
+        device_id,
+        issued_at: now,
+        expires_at: now + valid_for,
+    }
+}
+
+#[tokio::test]
+async fn sessions_are_revoked_with_their_devices() {
+    let store = MemorySessionStore::new();
+    let (laptop, phone) = (Uuid::new_v4(), Uuid::new_v4());
+
+    let first = session(laptop, TimeDelta::hours(1));
+    store.put("first", &first).await.unwrap();
+    store
+        .put("second", &session(laptop, TimeDelta::hours(1)))
+        .await
+        .unwrap();
+    store
+        .put("third", &session(phone, TimeDelta::hours(1)))
+        .await
+        .unwrap();
+    assert_eq!(store.get("first").await.unwrap(), Some(first));
+
+    assert_eq!(store.revoke_device(laptop).await.unwrap(), 2);
+
+    assert_eq!(store.get("first").await.unwrap(), None);
+    assert_eq!(store.get("second").await.unwrap(), None);
+    assert!(store.get("third").await.unwrap().is_some());
+}
+
+#[tokio::test]
+async fn expired_sessions_are_not_returned() {
+    let store = MemorySessionStore::new();
+    let device_id = Uuid::new_v4();
+
+    store
+        .put("expired", &session(device_id, TimeDelta::seconds(-1)))
+        .await
+        .unwrap();
+
+    assert_eq!(store.get("expired").await.unwrap(), None);
+    // Dropped when the next session is stored.
+    store
+        .put("current", &session(device_id, TimeDelta::hours(1)))
+        .await
+        .unwrap();
+    assert_eq!(store.revoke_device(device_id).await.unwrap(), 1);
+}
+
+#[tokio::test]
+async fn sessions_are_reassigned_to_other_users() {
+    let store = MemorySessionStore::new();
+    let (jane, bob) = (Uuid::new_v4(), Uuid::new_v4());
+    let mut laptop = session(Uuid::new_v4(), TimeDelta::hours(1));
+    laptop.user_id = jane;
+    store.put("laptop", &laptop).await.unwrap();
+    store
+        .put("phone", &session(Uuid::new_v4(), TimeDelta::hours(1)))
+        .await
+        .unwrap();
+
+    assert_eq!(store.reassign(jane, bob).await.unwrap(), 1);
+
+    let reassigned = store.get("laptop").await.unwrap().unwrap();
+    assert_eq!(reassigned.user_id, bob);
+    assert_eq!(reassigned.device_id, laptop.device_id);
+    assert_ne!(store.get("phone").await.unwrap().unwrap().user_id, bob);
+    assert_eq!(store.reassign(jane, bob).await.unwrap(), 0);
+}
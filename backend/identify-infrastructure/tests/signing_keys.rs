@@ -0,0 +1,107 @@
+use chrono::{TimeDelta, Utc};
+use ed25519_dalek::{Signer, Verifier, VerifyingKey};
+use identify_application::{
+    SigningKeyGenerator,
+    signing_key_contracts::{
+        GetActive as _, Insert as _, ListPublished as _, PruneRetired as _,
+        Update as _,
+    },
+};
+use identify_domain::{SigningKey, SigningKeyStatus};
+use identify_infrastructure::{
+    encryption::FieldEncryption,
+    signing::{ED25519_ALGORITHM, Ed25519KeyGenerator},
+    storage::{self, signing_keys::SigningKeysRepository},
+};
+
+mod common;
+
+use common::pool;
+
+/// Stores the fields in the clear, as the tests of this file don't check the encryption.
+static NO_ENCRYPTION: FieldEncryption = FieldEncryption::disabled();
+
+fn key() -> SigningKey {
+    SigningKey::new(Ed25519KeyGenerator::new().generate().unwrap()).unwrap()
+}
+
+#[tokio::test]
+async fn keys_are_stored_and_loaded() {
+    let pool = pool().await;
+    let mut tx = storage::begin(&pool).await.unwrap();
+    let mut repository = SigningKeysRepository::new(&mut tx, &NO_ENCRYPTION);
+    let key = key();
+
+    assert!(repository.get_active().await.unwrap().is_none());
+    repository.insert(&key).await.unwrap();
+
+    let loaded = repository.get_active().await.unwrap().unwrap();
+    assert_eq!(loaded.id(), key.id());
+    assert_eq!(loaded.algorithm(), ED25519_ALGORITHM);
+    assert_eq!(loaded.public_key(), key.public_key());
+    assert_eq!(loaded.private_key(), key.private_key());
+    assert_eq!(loaded.status(), SigningKeyStatus::Active);
+}
+
+#[tokio::test]
+async fn retired_keys_stay_published_until_pruned() {
+    let pool = pool().await;
+    let mut tx = storage::begin(&pool).await.unwrap();
+    let mut repository = SigningKeysRepository::new(&mut tx, &NO_ENCRYPTION);
+    let mut previous = key();
+    repository.insert(&previous).await.unwrap();
+    previous.retire().unwrap();
+    repository.update(&previous).await.unwrap();
+    let current = key();
+    repository.insert(&current).await.unwrap();
+
+    let published = repository.list_published().await.unwrap();
+    assert_eq!(
+        published.iter().map(SigningKey::id).collect::<Vec<_>>(),
+        [current.id(), previous.id()]
+    );
+    assert_eq!(published[1].status(), SigningKeyStatus::Retired);
+    assert!(published[1].retired_at().is_some());
+
+    let pruned = repository
+        .prune_retired(Utc::now() - TimeDelta::days(1))
+        .await
+        .unwrap();
+    assert_eq!(pruned, 0);
+    let pruned = repository
+        .prune_retired(Utc::now() + TimeDelta::days(1))
+        .await
+        .unwrap();
+    assert_eq!(pruned, 1);
+
+    let published = repository.list_published().await.unwrap();
+    assert_eq!(
+        published.iter().map(SigningKey::id).collect::<Vec<_>>(),
+        [current.id()]
+    );
+}
+
+#[tokio::test]
+async fn only_one_key_can_be_active() {
+    let pool = pool().await;
+    let mut tx = storage::begin(&pool).await.unwrap();
+    let mut repository = SigningKeysRepository::new(&mut tx, &NO_ENCRYPTION);
+    repository.insert(&key()).await.unwrap();
+
+    assert!(repository.insert(&key()).await.is_err());
+}
+
+#[test]
+fn generated_keys_sign_what_their_public_keys_verify() {
+    let attrs = Ed25519KeyGenerator::new().generate().unwrap();
+    let private_key: [u8; 32] = attrs.private_key.try_into().unwrap();
+    let public_key: [u8; 32] = attrs.public_key.try_into().unwrap();
+
+    let signature = ed25519_dalek::SigningKey::from_bytes(&private_key)
+        .sign(b"header.payload");
+
+    VerifyingKey::from_bytes(&public_key)
+        .unwrap()
+        .verify(b"header.payload", &signature)
+        .unwrap();
+}
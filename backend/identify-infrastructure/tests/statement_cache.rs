@@ -0,0 +1,98 @@
+use identify_application::{
+    ApplicationError, device_contracts::GetByFingerprint as _,
+    email_verification_contracts::Get as _, user_contracts::Get,
+};
+use identify_infrastructure::{
+    encryption::FieldEncryption,
+    storage::{
+        self,
+        connection::{self, PoolConfig},
+        devices::DevicesRepository,
+        email_verifications::EmailVerificationsRepository,
+        users::UsersRepository,
+    },
+};
+use sqlx::{Connection, SqliteConnection, SqlitePool};
+use uuid::Uuid;
+
+/// Stores the fields in the clear, as the tests of this file don't check the encryption.
+static NO_ENCRYPTION: FieldEncryption = FieldEncryption::disabled();
+
+/// Opens a pool of a new in-memory database with a single connection.
+///
+/// The connection opened along with the pool predates the migrations, so it's detached and
+/// replaced by a new one that sees the tables. It's returned to keep the shared database
+/// alive.
+async fn pool(warm_up_statements: bool) -> (SqlitePool, SqliteConnection) {
+    let url =
+        format!("sqlite:file:{}?mode=memory&cache=shared", Uuid::new_v4());
+    let config = PoolConfig {
+        warm_up_statements,
+        ..PoolConfig::for_url(&url)
+    };
+
+    let pool = connection::get_pool(&url, &config).await.unwrap();
+    connection::migrate(&pool).await.unwrap();
+    let first_connection = pool.acquire().await.unwrap().detach();
+
+    (pool, first_connection)
+}
+
+/// Returns how many statements the connection had to prepare to get a user.
+async fn statements_prepared_by_get_user(pool: &SqlitePool) -> usize {
+    let mut tx = storage::begin(pool).await.unwrap();
+    let before = tx.cached_statements_size();
+
+    let mut repository = UsersRepository::new(&mut tx, &NO_ENCRYPTION);
+    let result = repository.get(Uuid::nil()).await;
+    assert!(matches!(
+        result,
+        Err(ApplicationError::EntityNotFound { .. })
+    ));
+
+    let after = tx.cached_statements_size();
+
+    after - before
+}
+
+/// Returns how many statements the connection had to prepare to look a device and a
+/// verification token up.
+async fn statements_prepared_by_lookups(pool: &SqlitePool) -> usize {
+    let mut tx = storage::begin(pool).await.unwrap();
+    let before = tx.cached_statements_size();
+
+    let device = DevicesRepository::new(&mut tx)
+        .get_by_fingerprint(Uuid::nil(), "fingerprint")
+        .await
+        .unwrap();
+    assert!(device.is_none());
+    let verification = EmailVerificationsRepository::new(&mut tx)
+        .get(Uuid::nil())
+        .await;
+    assert!(matches!(
+        verification,
+        Err(ApplicationError::EntityNotFound { .. })
+    ));
+
+    let after = tx.cached_statements_size();
+
+    after - before
+}
+
+#[tokio::test]
+async fn hot_statements_are_prepared_on_connect() {
+    let (pool, _first_connection) = pool(true).await;
+
+    assert_eq!(statements_prepared_by_get_user(&pool).await, 0);
+    assert_eq!(statements_prepared_by_lookups(&pool).await, 0);
+}
+
+#[tokio::test]
+async fn statements_are_reused_by_connections() {
+    let (pool, _first_connection) = pool(false).await;
+
+    assert_eq!(statements_prepared_by_get_user(&pool).await, 1);
+    assert_eq!(statements_prepared_by_get_user(&pool).await, 0);
+    assert_eq!(statements_prepared_by_lookups(&pool).await, 2);
+    assert_eq!(statements_prepared_by_lookups(&pool).await, 0);
+}
@@ -0,0 +1,180 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use identify_application::{
+    ApplicationError,
+    user_contracts::{Get, GetVersion},
+};
+use identify_domain::{
+    NewUserAttrs, NewUserEventAttrs, User, UserEvent, UserLifecycleTransition,
+    Username,
+};
+use identify_infrastructure::storage::users::cache::{
+    CachedUsersRepository, UserCache,
+};
+use uuid::Uuid;
+
+/// Repository of a fixed set of users that counts how many times it's asked for them.
+struct CountingRepository {
+    users: Vec<User>,
+    lookups: usize,
+}
+
+#[async_trait]
+impl Get for CountingRepository {
+    async fn get(&mut self, id: Uuid) -> Result<User, ApplicationError> {
+        self.lookups += 1;
+        self.users
+            .iter()
+            .find(|user| user.id() == id)
+            .cloned()
+            .ok_or_else(|| ApplicationError::entity_not_found("User", id))
+    }
+}
+
+#[async_trait]
+impl GetVersion for CountingRepository {
+    async fn get_version(&mut self, id: Uuid) -> Result<u32, ApplicationError> {
+        self.users
+            .iter()
+            .find(|user| user.id() == id)
+            .map(User::version)
+            .ok_or_else(|| ApplicationError::entity_not_found("User", id))
+    }
+}
+
+fn user(name: &str) -> User {
+    User::new(
+        NewUserAttrs {
+            email: format!("{name}@acme.test"),
+            first_name: name.to_owned(),
+            last_name: None,
+        },
+        Username::parse(name).unwrap(),
+    )
+}
+
+fn repository(
+    users: &[&User],
+    cache: &UserCache,
+) -> CachedUsersRepository<CountingRepository> {
+    CachedUsersRepository::new(
+        CountingRepository {
+            users: users.iter().map(|user| (*user).clone()).collect(),
+            lookups: 0,
+        },
+        cache.clone(),
+    )
+}
+
+fn event(user: &User, transition: UserLifecycleTransition) -> UserEvent {
+    UserEvent::new(NewUserEventAttrs {
+        user_id: user.id(),
+        transition,
+        version: 2,
+    })
+    .unwrap()
+}
+
+#[tokio::test]
+async fn users_are_looked_up_once_until_they_change() {
+    let john = user("john");
+    let jane = user("jane");
+    let cache = UserCache::new(10, Duration::from_secs(60));
+
+    let mut first = repository(&[&john, &jane], &cache);
+    first.get(john.id()).await.unwrap();
+    first.get(jane.id()).await.unwrap();
+
+    let mut second = repository(&[&john, &jane], &cache);
+    second.get(john.id()).await.unwrap();
+    second.get(jane.id()).await.unwrap();
+    assert!(
+        second.get(Uuid::new_v4()).await.is_err(),
+        "missing users aren't cached"
+    );
+
+    cache
+        .invalidate_events(&[
+            event(&john, UserLifecycleTransition::Updated),
+            event(&jane, UserLifecycleTransition::Created),
+        ])
+        .await;
+
+    let mut third = repository(&[&john, &jane], &cache);
+    third.get(john.id()).await.unwrap();
+    third.get(jane.id()).await.unwrap();
+
+    assert_eq!(
+        [first.into_inner(), second.into_inner(), third.into_inner()]
+            .map(|repository| repository.lookups),
+        [2, 1, 1]
+    );
+}
+
+#[tokio::test]
+async fn users_changed_since_they_were_cached_are_looked_up_again() {
+    let john = user("john");
+    let cache = UserCache::new(10, Duration::from_secs(60));
+    repository(&[&john], &cache).get(john.id()).await.unwrap();
+
+    let mut renamed = john.clone();
+    renamed.rename(String::from("Johnny"), None);
+    let mut repository = repository(&[&renamed], &cache);
+    let first = repository.get(john.id()).await.unwrap();
+    let second = repository.get(john.id()).await.unwrap();
+
+    assert_eq!(first.first_name(), "Johnny");
+    assert_eq!(second.version(), renamed.version());
+    assert_eq!(repository.into_inner().lookups, 1);
+}
+
+#[tokio::test]
+async fn least_recently_used_and_expired_users_are_evicted() {
+    let john = user("john");
+    let jane = user("jane");
+    let jim = user("jim");
+
+    let cache = UserCache::new(2, Duration::from_secs(60));
+    let mut repository = repository(&[&john, &jane, &jim], &cache);
+    for user in [&john, &jane, &john, &jim, &john, &jane] {
+        repository.get(user.id()).await.unwrap();
+    }
+    // Jane is evicted by Jim, and Jim by Jane.
+    assert_eq!(repository.into_inner().lookups, 4);
+
+    let cache = UserCache::new(2, Duration::ZERO);
+    let mut repository = self::repository(&[&john], &cache);
+    repository.get(john.id()).await.unwrap();
+    repository.get(john.id()).await.unwrap();
+    assert_eq!(repository.into_inner().lookups, 2);
+}
+
+#[tokio::test]
+async fn disabled_cache_looks_up_every_user() {
+    let john = user("john");
+
+    let mut repository = repository(&[&john], &UserCache::disabled());
+    repository.get(john.id()).await.unwrap();
+    repository.get(john.id()).await.unwrap();
+
+    assert_eq!(repository.into_inner().lookups, 2);
+}
+
+#[tokio::test]
+async fn tenants_have_caches_of_their_own() {
+    let john = user("john");
+    let cache = UserCache::new(10, Duration::from_secs(60));
+    let acme = cache.for_tenant(Uuid::new_v4());
+    let globex = cache.for_tenant(Uuid::new_v4());
+
+    repository(&[&john], &acme).get(john.id()).await.unwrap();
+    let mut lookups = Vec::new();
+    for cache in [&acme, &globex, &cache] {
+        let mut repository = repository(&[&john], cache);
+        repository.get(john.id()).await.unwrap();
+        lookups.push(repository.into_inner().lookups);
+    }
+
+    assert_eq!(lookups, [0, 1, 1]);
+}
@@ -0,0 +1,260 @@
+use std::sync::Arc;
+
+use identify_application::{
+    CreateUser, CreateUserParams, ListQuery, PageRequest,
+    ProjectUserSummariesParams, ReadModelProjectionDeps, System,
+    TransactionalUseCaseExt as _, UnitOfWork as _, UseCase as _,
+    project_user_summaries,
+    user_contracts::{List as _, Search as _, UserFilter, UserSearchQuery},
+    user_summary_contracts::Upsert as _,
+};
+use identify_domain::{
+    NewUserAttrs, QuotaPolicy, User, Username, UsernameStrategy,
+};
+use identify_infrastructure::{
+    encryption::FieldEncryption,
+    storage::{
+        self,
+        connection::ReadPool,
+        unit_of_work::{SqliteUnitOfWork, SqliteUnitOfWorkFactory},
+        user_summaries::{UserSummariesReader, UserSummariesRepository},
+    },
+};
+use sqlx::SqlitePool;
+
+mod common;
+
+use common::pool;
+
+/// Stores the fields in the clear, as the tests of this file don't check the encryption.
+static NO_ENCRYPTION: FieldEncryption = FieldEncryption::disabled();
+
+async fn create_user(pool: &SqlitePool, email: &str, last_name: &str) -> User {
+    CreateUser
+        .in_transaction(SqliteUnitOfWorkFactory::new(
+            pool.clone(),
+            NO_ENCRYPTION.clone(),
+        ))
+        .execute(
+            &System,
+            CreateUserParams {
+                user_attrs: NewUserAttrs {
+                    email: email.to_owned(),
+                    first_name: "John".to_owned(),
+                    last_name: Some(last_name.to_owned()),
+                },
+                username: None,
+                username_strategy: UsernameStrategy::default(),
+                identifier_policy: Arc::default(),
+                organization_id: None,
+                quota_policy: QuotaPolicy::default(),
+            },
+        )
+        .await
+        .unwrap()
+}
+
+async fn project(pool: &SqlitePool, limit: u32) -> usize {
+    let mut uow = SqliteUnitOfWork::begin(pool, &NO_ENCRYPTION).await.unwrap();
+
+    let projected = project_user_summaries(
+        ReadModelProjectionDeps::new(&mut uow),
+        ProjectUserSummariesParams { limit },
+    )
+    .await
+    .unwrap();
+
+    uow.commit().await.unwrap();
+
+    projected.len()
+}
+
+/// Writes the summaries of the users as if their events had been projected.
+async fn summarize(pool: &SqlitePool, users: &[User]) {
+    let mut tx = storage::begin(pool).await.unwrap();
+    let mut repository = UserSummariesRepository::new(&mut tx, &NO_ENCRYPTION);
+    for user in users {
+        repository.upsert(user).await.unwrap();
+    }
+    storage::commit(tx).await.unwrap();
+}
+
+fn named(email: &str, first_name: &str, last_name: &str) -> User {
+    User::new(
+        NewUserAttrs {
+            email: email.to_owned(),
+            first_name: first_name.to_owned(),
+            last_name: Some(last_name.to_owned()),
+        },
+        Username::parse(email.split('@').next().unwrap()).unwrap(),
+    )
+}
+
+async fn search(pool: &SqlitePool, text: &str) -> Vec<String> {
+    UserSummariesReader::new(
+        ReadPool::from(pool.clone()),
+        NO_ENCRYPTION.clone(),
+    )
+    .search(&UserSearchQuery {
+        text: text.to_owned(),
+        page: PageRequest::default(),
+    })
+    .await
+    .unwrap()
+    .items
+    .iter()
+    .map(|user| user.email().to_owned())
+    .collect()
+}
+
+async fn list(pool: &SqlitePool, filter: UserFilter) -> Vec<String> {
+    UserSummariesReader::new(
+        ReadPool::from(pool.clone()),
+        NO_ENCRYPTION.clone(),
+    )
+    .list(&ListQuery {
+        filter,
+        sort: Vec::new(),
+        page: PageRequest::default(),
+    })
+    .await
+    .unwrap()
+    .items
+    .iter()
+    .map(|user| user.email().to_owned())
+    .collect()
+}
+
+#[tokio::test]
+async fn users_are_listed_once_their_events_are_projected() {
+    let pool = pool().await;
+    create_user(&pool, "john@acme.test", "Doe").await;
+    create_user(&pool, "jane@globex.test", "Doe").await;
+
+    assert!(list(&pool, UserFilter::default()).await.is_empty());
+
+    assert_eq!(project(&pool, 1).await, 1);
+    assert_eq!(project(&pool, 100).await, 1);
+    assert_eq!(project(&pool, 100).await, 0);
+
+    let mut emails = list(&pool, UserFilter::default()).await;
+    emails.sort();
+    assert_eq!(emails, ["jane@globex.test", "john@acme.test"]);
+    assert_eq!(
+        list(
+            &pool,
+            UserFilter {
+                email_domain: Some("ACME.test".to_owned()),
+                ..Default::default()
+            }
+        )
+        .await,
+        ["john@acme.test"]
+    );
+}
+
+#[tokio::test]
+async fn summaries_follow_changes_of_users() {
+    let pool = pool().await;
+    let user = create_user(&pool, "john@acme.test", "Doe").await;
+    project(&pool, 100).await;
+
+    sqlx::query("update users set last_name = 'Smith', version = version + 1 where id = (?)")
+        .bind(user.id())
+        .execute(&pool)
+        .await
+        .unwrap();
+    sqlx::query("update outbox set projected_at = null")
+        .execute(&pool)
+        .await
+        .unwrap();
+    project(&pool, 100).await;
+
+    let mut reader = UserSummariesReader::new(
+        ReadPool::from(pool.clone()),
+        NO_ENCRYPTION.clone(),
+    );
+    let search = |text: &str| UserSearchQuery {
+        text: text.to_owned(),
+        page: PageRequest::default(),
+    };
+    assert_eq!(reader.search(&search("doe")).await.unwrap().total_items, 0);
+    let found = reader.search(&search("smith")).await.unwrap();
+    assert_eq!(found.total_items, 1);
+    assert_eq!(found.items[0].last_name().as_deref(), Some("Smith"));
+
+    sqlx::query("delete from users where id = (?)")
+        .bind(user.id())
+        .execute(&pool)
+        .await
+        .unwrap();
+    sqlx::query("update outbox set projected_at = null")
+        .execute(&pool)
+        .await
+        .unwrap();
+    project(&pool, 100).await;
+
+    assert!(list(&pool, UserFilter::default()).await.is_empty());
+    assert_eq!(
+        reader.search(&search("smith")).await.unwrap().total_items,
+        0
+    );
+}
+
+#[tokio::test]
+async fn search_matches_prefixes_of_all_words() {
+    let pool = pool().await;
+    summarize(
+        &pool,
+        &[
+            named("jdoe@acme.test", "John", "Doe"),
+            named("jane@acme.test", "Jane", "Doe"),
+            named("jose@globex.test", "José", "Smith"),
+        ],
+    )
+    .await;
+
+    assert_eq!(search(&pool, "jo doe").await, ["jdoe@acme.test"]);
+    assert_eq!(
+        search(&pool, "jose").await,
+        ["jose@globex.test"],
+        "diacritics are ignored"
+    );
+    assert_eq!(search(&pool, "GLOBEX").await, ["jose@globex.test"]);
+    assert_eq!(search(&pool, "\"doe\"* -").await.len(), 2);
+    assert!(search(&pool, "!?").await.is_empty());
+}
+
+#[tokio::test]
+async fn search_orders_users_by_relevance() {
+    let pool = pool().await;
+    summarize(
+        &pool,
+        &[
+            named("jane@acme.test", "Jane", "Doe"),
+            named("doe@doe.test", "Doe", "Doe"),
+        ],
+    )
+    .await;
+
+    assert_eq!(
+        search(&pool, "doe").await,
+        ["doe@doe.test", "jane@acme.test"]
+    );
+}
+
+#[tokio::test]
+async fn search_matches_summaries_rather_than_users() {
+    let pool = pool().await;
+    let user = create_user(&pool, "john@acme.test", "Doe").await;
+    project(&pool, 100).await;
+
+    sqlx::query("update users set last_name = 'Smith' where id = (?)")
+        .bind(user.id())
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    assert!(search(&pool, "smith").await.is_empty());
+    assert_eq!(search(&pool, "doe").await, ["john@acme.test"]);
+}
@@ -0,0 +1,652 @@
+use chrono::{TimeZone as _, Utc};
+use futures_util::{StreamExt as _, TryStreamExt as _};
+use identify_application::{
+    ApplicationError, ListQuery, PageRequest, Sort, SortDirection,
+    Specification,
+    email_alias_contracts::Insert as _,
+    user_contracts::{
+        ChangeEmail as _, Count as _, Erase as _, Exists as _,
+        ExistsByEmails as _, FindBySpec as _, Get as _, GetByEmail as _,
+        GetByUsername as _, InsertMany as _, List as _, LoginTally,
+        RecordLogins as _, RecordMerge as _, Scan as _, SetPasswordHash as _,
+        UserFilter, UserSortField, UserSpec, UserSpecQuery, UserStatus,
+    },
+};
+use identify_domain::{
+    EmailAlias, EmailAliasKind, NewEmailAliasAttrs, NewUserAttrs, User,
+    Username,
+};
+use identify_infrastructure::{
+    encryption::FieldEncryption,
+    storage::{
+        self,
+        email_aliases::EmailAliasesRepository,
+        users::{UsersReader, UsersRepository},
+    },
+};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+mod common;
+
+use common::pool;
+
+/// Stores the fields in the clear, as the tests of this file don't check the encryption.
+static NO_ENCRYPTION: FieldEncryption = FieldEncryption::disabled();
+
+fn user(n: usize) -> User {
+    User::new(
+        NewUserAttrs {
+            email: format!("user-{n}@acme.test"),
+            first_name: format!("User {n}"),
+            last_name: n.is_multiple_of(2).then(|| "Doe".to_owned()),
+        },
+        Username::parse(&format!("user-{n}")).unwrap(),
+    )
+}
+
+async fn count_users(pool: &SqlitePool) -> i64 {
+    sqlx::query_scalar("select count(*) from users")
+        .fetch_one(pool)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn insert_many_inserts_users_in_chunks() {
+    let pool = pool().await;
+    // More users than fit into a single statement.
+    let users = (0..5000).map(user).collect::<Vec<_>>();
+
+    let mut tx = storage::begin(&pool).await.unwrap();
+    let mut repository = UsersRepository::new(&mut tx, &NO_ENCRYPTION);
+    repository.insert_many(&users).await.unwrap();
+    let stored = repository.get(users[4999].id()).await.unwrap();
+    storage::commit(tx).await.unwrap();
+
+    assert_eq!(count_users(&pool).await, 5000);
+    assert_eq!(stored.email(), users[4999].email());
+    assert_eq!(stored.last_name(), users[4999].last_name());
+}
+
+#[tokio::test]
+async fn insert_many_rejects_taken_emails() {
+    let pool = pool().await;
+    let mut users = vec![user(1), user(2)];
+    users.push(User::new(
+        NewUserAttrs {
+            email: "user-1@acme.test".to_owned(),
+            first_name: "Jane".to_owned(),
+            last_name: None,
+        },
+        Username::parse("jane").unwrap(),
+    ));
+
+    let mut tx = storage::begin(&pool).await.unwrap();
+    let result = UsersRepository::new(&mut tx, &NO_ENCRYPTION)
+        .insert_many(&users)
+        .await;
+    drop(tx);
+
+    assert!(matches!(
+        result,
+        Err(ApplicationError::EntityAlreadyExists { message, .. })
+            if message == "Email is already taken"
+    ));
+    assert_eq!(count_users(&pool).await, 0);
+}
+
+#[tokio::test]
+async fn insert_many_accepts_no_users() {
+    let pool = pool().await;
+
+    let mut tx = storage::begin(&pool).await.unwrap();
+    let mut repository = UsersRepository::new(&mut tx, &NO_ENCRYPTION);
+    repository.insert_many(&[]).await.unwrap();
+    storage::commit(tx).await.unwrap();
+
+    assert_eq!(count_users(&pool).await, 0);
+}
+
+#[tokio::test]
+async fn scan_streams_all_matching_users_in_order() {
+    let pool = pool().await;
+    // More users than the cursor reads ahead.
+    let mut users = (0..200).map(user).collect::<Vec<_>>();
+    users.push(User::new(
+        NewUserAttrs {
+            email: "jane@other.test".to_owned(),
+            first_name: "Jane".to_owned(),
+            last_name: None,
+        },
+        Username::parse("jane").unwrap(),
+    ));
+    insert(&pool, &users).await;
+
+    let scanned = UsersReader::new(pool.clone(), NO_ENCRYPTION.clone())
+        .scan(
+            &UserFilter {
+                email_domain: Some("acme.test".to_owned()),
+                ..Default::default()
+            },
+            &[Sort {
+                field: UserSortField::Username,
+                direction: SortDirection::Descending,
+            }],
+        )
+        .map_ok(|user| user.username().to_owned())
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap();
+
+    let mut expected =
+        (0..200).map(|n| format!("user-{n}")).collect::<Vec<_>>();
+    expected.sort_by(|a, b| b.cmp(a));
+    assert_eq!(scanned, expected);
+}
+
+#[tokio::test]
+async fn email_filters_match_emails_like_lookups_by_email() {
+    let pool = pool().await;
+    insert(&pool, &(0..3).map(user).collect::<Vec<_>>()).await;
+
+    let mut tx = storage::begin(&pool).await.unwrap();
+    let mut repository = UsersRepository::new(&mut tx, &NO_ENCRYPTION);
+    for email in ["user-1@acme.test", " USER-1@Acme.test "] {
+        let users = repository
+            .list(&ListQuery {
+                filter: UserFilter {
+                    email: Some(email.to_owned()),
+                    ..UserFilter::default()
+                },
+                sort: vec![],
+                page: Default::default(),
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(users.items.len(), 1, "{email}");
+        assert_eq!(users.items[0].email(), "user-1@acme.test");
+    }
+}
+
+#[tokio::test]
+async fn dropped_scans_release_their_connection() {
+    let pool = pool().await;
+    insert(&pool, &(0..200).map(user).collect::<Vec<_>>()).await;
+
+    let mut users = UsersReader::new(pool.clone(), NO_ENCRYPTION.clone())
+        .scan(&UserFilter::default(), &[]);
+    users.next().await.unwrap().unwrap();
+    drop(users);
+
+    // The pool of in-memory databases has a single connection.
+    let tx = storage::begin(&pool).await.unwrap();
+    storage::commit(tx).await.unwrap();
+}
+
+async fn insert(pool: &SqlitePool, users: &[User]) {
+    let mut tx = storage::begin(pool).await.unwrap();
+    let mut repository = UsersRepository::new(&mut tx, &NO_ENCRYPTION);
+    repository.insert_many(users).await.unwrap();
+    storage::commit(tx).await.unwrap();
+}
+
+fn named(email: &str, first_name: &str, last_name: &str) -> User {
+    User::new(
+        NewUserAttrs {
+            email: email.to_owned(),
+            first_name: first_name.to_owned(),
+            last_name: Some(last_name.to_owned()),
+        },
+        Username::parse(email.split('@').next().unwrap()).unwrap(),
+    )
+}
+
+#[tokio::test]
+async fn get_by_username_finds_users_by_their_usernames() {
+    let pool = pool().await;
+    let users = [user(1), user(2)];
+    insert(&pool, &users).await;
+
+    let mut tx = storage::begin(&pool).await.unwrap();
+    let mut repository = UsersRepository::new(&mut tx, &NO_ENCRYPTION);
+    let found = repository
+        .get_by_username(&Username::parse("USER-2").unwrap())
+        .await
+        .unwrap();
+    let missing = repository
+        .get_by_username(&Username::parse("user-3").unwrap())
+        .await;
+
+    assert_eq!(found.id(), users[1].id());
+    assert!(matches!(
+        missing,
+        Err(ApplicationError::EntityNotFound { .. })
+    ));
+}
+
+#[tokio::test]
+async fn names_that_look_encrypted_are_stored_in_the_clear() {
+    let pool = pool().await;
+    let jane = named("jane@acme.test", "enc:v1:k:abc", "plain:doe");
+    insert(&pool, std::slice::from_ref(&jane)).await;
+
+    let mut tx = storage::begin(&pool).await.unwrap();
+    let mut repository = UsersRepository::new(&mut tx, &NO_ENCRYPTION);
+    let loaded = repository.get(jane.id()).await.unwrap();
+    let listed = repository
+        .list(&ListQuery {
+            filter: UserFilter::default(),
+            sort: vec![],
+            page: Default::default(),
+        })
+        .await
+        .unwrap();
+
+    assert_eq!(loaded.first_name(), "enc:v1:k:abc");
+    assert_eq!(loaded.last_name().as_deref(), Some("plain:doe"));
+    assert_eq!(listed.items[0].first_name(), "enc:v1:k:abc");
+}
+
+#[tokio::test]
+async fn get_by_email_finds_users_by_their_current_and_original_emails() {
+    let pool = pool().await;
+    let users = [user(1), user(2)];
+    insert(&pool, &users).await;
+    let mut changed = users[1].clone();
+    changed.change_email("jane@doe.test".to_owned()).unwrap();
+
+    let mut tx = storage::begin(&pool).await.unwrap();
+    let mut repository = UsersRepository::new(&mut tx, &NO_ENCRYPTION);
+    repository.change_email(&changed).await.unwrap();
+    let current = repository.get_by_email("Jane@Doe.test").await.unwrap();
+    let original = repository.get_by_email("user-2@acme.test").await.unwrap();
+    let other = repository.get_by_email("user-1@acme.test").await.unwrap();
+    let missing = repository.get_by_email("user-3@acme.test").await;
+
+    assert_eq!(current.id(), users[1].id());
+    assert_eq!(current.email(), "jane@doe.test");
+    assert_eq!(original.id(), users[1].id());
+    assert_eq!(other.id(), users[0].id());
+    assert!(matches!(
+        missing,
+        Err(ApplicationError::EntityNotFound { id, .. }) if !id.contains('@')
+    ));
+}
+
+#[tokio::test]
+async fn emails_identify_a_single_user_across_aliases() {
+    let pool = pool().await;
+    let users = [user(1), user(2)];
+    insert(&pool, &users).await;
+    let alias = |user: &User, email: &str| {
+        EmailAlias::new(NewEmailAliasAttrs {
+            user_id: user.id(),
+            email: email.to_owned(),
+            kind: EmailAliasKind::Added,
+        })
+        .unwrap()
+    };
+
+    let mut tx = storage::begin(&pool).await.unwrap();
+    EmailAliasesRepository::new(&mut tx, &NO_ENCRYPTION)
+        .insert(&alias(&users[0], "news@acme.test"))
+        .await
+        .unwrap();
+    let found = UsersRepository::new(&mut tx, &NO_ENCRYPTION)
+        .get_by_email("News@Acme.test")
+        .await
+        .unwrap();
+    let same_alias = EmailAliasesRepository::new(&mut tx, &NO_ENCRYPTION)
+        .insert(&alias(&users[1], "news@acme.test"))
+        .await;
+    let other_user = EmailAliasesRepository::new(&mut tx, &NO_ENCRYPTION)
+        .insert(&alias(&users[1], "user-1@acme.test"))
+        .await;
+    let new_user = UsersRepository::new(&mut tx, &NO_ENCRYPTION)
+        .insert_many(&[named("news@acme.test", "Jane", "Doe")])
+        .await;
+
+    assert_eq!(found.id(), users[0].id());
+    for result in [same_alias, other_user, new_user] {
+        assert!(matches!(
+            result,
+            Err(ApplicationError::EntityAlreadyExists { .. })
+        ));
+    }
+}
+
+#[tokio::test]
+async fn erase_anonymizes_users_and_records_the_erasure() {
+    let pool = pool().await;
+    let mut user = user(1);
+    insert(&pool, std::slice::from_ref(&user)).await;
+
+    let mut tx = storage::begin(&pool).await.unwrap();
+    let mut repository = UsersRepository::new(&mut tx, &NO_ENCRYPTION);
+    repository
+        .set_password_hash(user.id(), "$argon2id$hash")
+        .await
+        .unwrap();
+    user.erase().unwrap();
+    repository.erase(&user, "admin").await.unwrap();
+    let stored = repository.get(user.id()).await.unwrap();
+    storage::commit(tx).await.unwrap();
+
+    assert!(stored.is_erased());
+    assert_eq!(stored.email(), user.email());
+    assert_eq!(stored.version(), 2);
+
+    let (password_hash, erased_by): (Option<String>, String) = sqlx::query_as(
+        r#"
+            select u.password_hash, e.erased_by
+            from users u join user_erasures e on e.user_id = u.id
+        "#,
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(password_hash, None);
+    assert_eq!(erased_by, "admin");
+}
+
+async fn find(
+    pool: &SqlitePool,
+    spec: UserSpec,
+    page: PageRequest,
+) -> (Vec<String>, u64) {
+    let mut tx = storage::begin(pool).await.unwrap();
+
+    let found = UsersRepository::new(&mut tx, &NO_ENCRYPTION)
+        .find_by_spec(&UserSpecQuery {
+            filter: spec,
+            sort: vec![Sort {
+                field: UserSortField::Username,
+                direction: SortDirection::Ascending,
+            }],
+            page,
+        })
+        .await
+        .unwrap();
+
+    let usernames = found
+        .items
+        .iter()
+        .map(|user| user.username().to_owned())
+        .collect();
+    (usernames, found.total_items)
+}
+
+#[tokio::test]
+async fn merges_lead_to_the_users_who_are_kept() {
+    let pool = pool().await;
+    let users = (0..3).map(user).collect::<Vec<_>>();
+    insert(&pool, &users).await;
+    let [first, second, third] = [0, 1, 2].map(|n| users[n].id());
+
+    let mut tx = storage::begin(&pool).await.unwrap();
+    let mut repository = UsersRepository::new(&mut tx, &NO_ENCRYPTION);
+    repository
+        .record_merge(first, second, "admin", Utc::now())
+        .await
+        .unwrap();
+    repository
+        .record_merge(second, third, "admin", Utc::now())
+        .await
+        .unwrap();
+    storage::commit(tx).await.unwrap();
+
+    let merges: Vec<(Uuid, Uuid)> = sqlx::query_as(
+        "select user_id, merged_into from user_merges order by merged_at",
+    )
+    .fetch_all(&pool)
+    .await
+    .unwrap();
+    assert_eq!(merges, [(first, third), (second, third)]);
+}
+
+#[tokio::test]
+async fn find_by_spec_matches_composed_criteria() {
+    let pool = pool().await;
+    let mut users = (1..=4).map(user).collect::<Vec<_>>();
+    insert(&pool, &users).await;
+
+    let mut tx = storage::begin(&pool).await.unwrap();
+    users[1].erase().unwrap();
+    UsersRepository::new(&mut tx, &NO_ENCRYPTION)
+        .erase(&users[1], "admin")
+        .await
+        .unwrap();
+    storage::commit(tx).await.unwrap();
+
+    let long_ago = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
+    sqlx::query("update users set created_at = ? where id in (?, ?)")
+        .bind(long_ago)
+        .bind(users[0].id())
+        .bind(users[1].id())
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let organization_id = Uuid::new_v4();
+    sqlx::query(
+        r#"
+            insert into organizations (id, name, slug, created_at, updated_at)
+            values (?, 'Acme', 'acme', current_timestamp, current_timestamp)
+        "#,
+    )
+    .bind(organization_id)
+    .execute(&pool)
+    .await
+    .unwrap();
+    for user in &users[1..3] {
+        sqlx::query(
+            r#"
+                insert into memberships
+                values (?, ?, 'member', current_timestamp, current_timestamp)
+            "#,
+        )
+        .bind(organization_id)
+        .bind(user.id())
+        .execute(&pool)
+        .await
+        .unwrap();
+    }
+
+    let page = PageRequest::default();
+    let in_organization = UserSpec::in_organization(organization_id);
+    let created_long_ago = UserSpec::created_between(
+        None,
+        Some(Utc.with_ymd_and_hms(2021, 1, 1, 0, 0, 0).unwrap()),
+    );
+
+    assert_eq!(find(&pool, Specification::All, page).await.1, 4);
+    assert_eq!(
+        find(&pool, Specification::Or(vec![]), page).await,
+        (vec![], 0)
+    );
+    assert_eq!(
+        find(
+            &pool,
+            in_organization
+                .clone()
+                .and(UserSpec::by_status(UserStatus::Active)),
+            page
+        )
+        .await
+        .0,
+        ["user-3"]
+    );
+    assert_eq!(
+        find(
+            &pool,
+            (!UserSpec::by_status(UserStatus::Erased))
+                .and(created_long_ago.clone()),
+            page
+        )
+        .await
+        .0,
+        ["user-1"]
+    );
+    assert_eq!(
+        find(
+            &pool,
+            !(in_organization.or(created_long_ago)),
+            PageRequest { number: 1, size: 1 }
+        )
+        .await,
+        (vec!["user-4".to_owned()], 1)
+    );
+    assert_eq!(
+        find(
+            &pool,
+            UserSpec::by_status(UserStatus::Active),
+            PageRequest { number: 2, size: 2 }
+        )
+        .await,
+        (vec!["user-4".to_owned()], 3)
+    );
+}
+
+#[tokio::test]
+async fn count_and_exists_check_users_without_loading_them() {
+    let pool = pool().await;
+    let mut users = (1..=3).map(user).collect::<Vec<_>>();
+    insert(&pool, &users).await;
+
+    let mut tx = storage::begin(&pool).await.unwrap();
+    let mut repository = UsersRepository::new(&mut tx, &NO_ENCRYPTION);
+    users[0].change_email("first@acme.test".to_owned()).unwrap();
+    repository.change_email(&users[0]).await.unwrap();
+    users[2].erase().unwrap();
+    repository.erase(&users[2], "admin").await.unwrap();
+
+    let active = UserSpec::by_status(UserStatus::Active);
+    assert_eq!(repository.count(&Specification::All).await.unwrap(), 3);
+    assert_eq!(repository.count(&active).await.unwrap(), 2);
+    assert_eq!(repository.count(&!active).await.unwrap(), 1);
+    for email in ["first@acme.test", " USER-1@acme.test", "user-3@acme.test"] {
+        assert!(
+            repository.exists(&UserSpec::by_email(email)).await.unwrap(),
+            "{email}"
+        );
+    }
+    assert!(
+        !repository
+            .exists(&UserSpec::by_email("user-4@acme.test"))
+            .await
+            .unwrap()
+    );
+    storage::commit(tx).await.unwrap();
+}
+
+#[tokio::test]
+async fn exists_by_emails_checks_every_email_of_a_batch() {
+    let pool = pool().await;
+    let mut users = (1..=2).map(user).collect::<Vec<_>>();
+    insert(&pool, &users).await;
+
+    let mut tx = storage::begin(&pool).await.unwrap();
+    users[0].change_email("first@acme.test".to_owned()).unwrap();
+    UsersRepository::new(&mut tx, &NO_ENCRYPTION)
+        .change_email(&users[0])
+        .await
+        .unwrap();
+    EmailAliasesRepository::new(&mut tx, &NO_ENCRYPTION)
+        .insert(
+            &EmailAlias::new(NewEmailAliasAttrs {
+                user_id: users[1].id(),
+                email: "news@acme.test".to_owned(),
+                kind: EmailAliasKind::Added,
+            })
+            .unwrap(),
+        )
+        .await
+        .unwrap();
+    // More emails than fit into a single statement, the taken ones at the end.
+    let mut emails = (3..7000)
+        .map(|n| format!("user-{n}@acme.test"))
+        .collect::<Vec<_>>();
+    emails.extend(
+        ["first@acme.test", " USER-1@acme.test", "News@acme.test"]
+            .map(str::to_owned),
+    );
+    let emails = emails.iter().map(String::as_str).collect::<Vec<_>>();
+    let exists = UsersRepository::new(&mut tx, &NO_ENCRYPTION)
+        .exists_by_emails(&emails)
+        .await
+        .unwrap();
+    storage::commit(tx).await.unwrap();
+
+    assert_eq!(exists.len(), emails.len());
+    assert!(exists[..6997].iter().all(|exists| !exists));
+    assert!(exists[6997..].iter().all(|exists| *exists));
+}
+
+#[tokio::test]
+async fn logins_are_added_to_the_statistics_of_users() {
+    let pool = pool().await;
+    let users = (1..=3).map(user).collect::<Vec<_>>();
+    insert(&pool, &users).await;
+    let earlier = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
+    let later = Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap();
+    let tally = |user: &User, count, last_login_at| LoginTally {
+        user_id: user.id(),
+        count,
+        last_login_at,
+    };
+
+    let mut tx = storage::begin(&pool).await.unwrap();
+    let mut repository = UsersRepository::new(&mut tx, &NO_ENCRYPTION);
+    repository
+        .record_logins(&[
+            tally(&users[0], 2, later),
+            tally(&users[1], 1, earlier),
+            // Users who don't exist are skipped.
+            tally(&user(4), 1, later),
+        ])
+        .await
+        .unwrap();
+    // Tallies recorded late don't move the last logins back.
+    repository
+        .record_logins(&[tally(&users[0], 1, earlier)])
+        .await
+        .unwrap();
+
+    let first = repository.get(users[0].id()).await.unwrap();
+    assert_eq!(first.login_count(), 3);
+    assert_eq!(*first.last_login_at(), Some(later));
+    assert_eq!(first.version(), 1);
+    let third = repository.get(users[2].id()).await.unwrap();
+    assert_eq!(third.login_count(), 0);
+    assert_eq!(*third.last_login_at(), None);
+
+    storage::commit(tx).await.unwrap();
+
+    let since = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+    let (inactive, _) = find(
+        &pool,
+        UserSpec::inactive_since(since)
+            .and(UserSpec::by_status(UserStatus::Active)),
+        PageRequest::default(),
+    )
+    .await;
+    assert_eq!(inactive, ["user-2", "user-3"]);
+    let scanned = UsersReader::new(pool.clone(), NO_ENCRYPTION.clone())
+        .scan(
+            &UserFilter {
+                inactive_since: Some(since),
+                ..Default::default()
+            },
+            &[Sort {
+                field: UserSortField::Username,
+                direction: SortDirection::Ascending,
+            }],
+        )
+        .map_ok(|user| user.username().to_owned())
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap();
+    assert_eq!(scanned, ["user-2", "user-3"]);
+}
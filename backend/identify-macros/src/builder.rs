@@ -0,0 +1,169 @@
+//! Typed builders of new entity helpers, see `#[model(builder)]`.
+//!
+//! Every required field of the helper is tracked by a type parameter of the builder, which is
+//! `()` until the field is set and `(T,)` after. Setters of required fields are only available
+//! while the field isn't set, and `build` only once all of them are, so a missing or repeated
+//! field fails to compile. Optional fields, i.e. the ones of type `Option<T>`, start as `None`
+//! and can be set at any time.
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{Ident, PathArguments, Type};
+
+use crate::model::{Helper, HelperStructField};
+
+/// Generates the builder of the helper with the given fields.
+pub(crate) fn expand(
+    helper: &Helper,
+    fields: &[HelperStructField],
+) -> TokenStream {
+    let Helper {
+        vis, ident: helper, ..
+    } = helper;
+    let builder = format_ident!("{helper}Builder");
+
+    let (required, optional): (Vec<_>, Vec<_>) =
+        fields.iter().partition(|field| !is_option(field.ty));
+    let states: Vec<Ident> = required
+        .iter()
+        .map(|field| format_ident!("__{}", upper_camel_case(field.ident)))
+        .collect();
+
+    let required_idents: Vec<_> =
+        required.iter().map(|field| field.ident).collect();
+    let optional_idents: Vec<_> =
+        optional.iter().map(|field| field.ident).collect();
+
+    let required_setters = required.iter().enumerate().map(|(index, field)| {
+        let HelperStructField {
+            attrs, ident, ty, ..
+        } = field;
+        let others = states
+            .iter()
+            .enumerate()
+            .filter(|(other, _)| *other != index)
+            .map(|(_, state)| state);
+        let state = |set: TokenStream| {
+            states.iter().enumerate().map(move |(other, state)| {
+                if other == index {
+                    set.clone()
+                } else {
+                    quote!(#state)
+                }
+            })
+        };
+        let unset = state(quote!(()));
+        let set = state(quote!((#ty,)));
+        let kept = required_idents
+            .iter()
+            .chain(&optional_idents)
+            .filter(|other| *other != ident)
+            .collect::<Vec<_>>();
+
+        quote! {
+            impl<#(#others),*> #builder<#(#unset),*> {
+                #(#attrs)*
+                #vis fn #ident(self, #ident: #ty) -> #builder<#(#set),*> {
+                    #builder {
+                        #ident: (#ident,),
+                        #(#kept: self.#kept,)*
+                    }
+                }
+            }
+        }
+    });
+
+    let optional_setters = optional.iter().map(|field| {
+        let HelperStructField {
+            attrs, ident, ty, ..
+        } = field;
+
+        quote! {
+            #(#attrs)*
+            #vis fn #ident(mut self, #ident: impl Into<#ty>) -> Self {
+                self.#ident = #ident.into();
+                self
+            }
+        }
+    });
+    let optional_setters = (!optional.is_empty()).then(|| {
+        quote! {
+            impl<#(#states),*> #builder<#(#states),*> {
+                #(#optional_setters)*
+            }
+        }
+    });
+
+    let required_types = required.iter().map(|field| field.ty);
+    let optional_types = optional.iter().map(|field| field.ty);
+    let builder_doc = format!(
+        "Builder of [{helper}], which can only be built once all the required fields are set."
+    );
+
+    quote! {
+        #[doc = #builder_doc]
+        #[must_use]
+        #vis struct #builder<#(#states = ()),*> {
+            #(#required_idents: #states,)*
+            #(#optional_idents: #optional_types,)*
+        }
+
+        impl #helper {
+            /// Returns a builder, whose optional fields are not set.
+            #vis fn builder() -> #builder {
+                #builder {
+                    #(#required_idents: (),)*
+                    #(#optional_idents: None,)*
+                }
+            }
+        }
+
+        #(#required_setters)*
+
+        #optional_setters
+
+        impl #builder<#((#required_types,)),*> {
+            #vis fn build(self) -> #helper {
+                #helper {
+                    #(#required_idents: self.#required_idents.0,)*
+                    #(#optional_idents: self.#optional_idents,)*
+                }
+            }
+        }
+    }
+}
+
+/// Returns whether the type is `Option<T>`, which makes the field optional.
+fn is_option(ty: &Type) -> bool {
+    let Type::Path(path) = ty else {
+        return false;
+    };
+
+    let Some(segment) = path.path.segments.last() else {
+        return false;
+    };
+    let PathArguments::AngleBracketed(arguments) = &segment.arguments else {
+        return false;
+    };
+
+    path.qself.is_none()
+        && segment.ident == "Option"
+        && arguments.args.len() == 1
+}
+
+fn upper_camel_case(ident: &Ident) -> String {
+    ident
+        .to_string()
+        .split('_')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            chars
+                .next()
+                .map(|first| {
+                    first.to_uppercase().chain(chars).collect::<String>()
+                })
+                .unwrap_or_default()
+        })
+        .collect()
+}
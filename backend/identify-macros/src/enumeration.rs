@@ -0,0 +1,267 @@
+//! Parsing and expansion of enums given to [gen_model!](crate::gen_model).
+//!
+//! Enums are stored and exchanged as strings, so every variant has a name, the variant in
+//! snake case unless it's renamed with `#[model(rename = "...")]`.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{
+    Attribute, Error, Fields, Ident, LitStr, Path, Result, Token, Variant,
+    Visibility, braced,
+    parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+};
+
+use crate::model::Errors;
+
+/// An enum with variants without any fields.
+pub(crate) struct EnumModel {
+    attrs: Vec<Attribute>,
+    options: EnumOptions,
+    vis: Visibility,
+    ident: Ident,
+    variants: Vec<EnumVariant>,
+}
+
+impl Parse for EnumModel {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let (options, attrs) =
+            EnumOptions::extract(input.call(Attribute::parse_outer)?)?;
+        let vis = input.parse()?;
+        input.parse::<Token![enum]>()?;
+        let ident: Ident = input.parse()?;
+
+        let content;
+        braced!(content in input);
+        let mut errors = Errors::default();
+        let mut variants = Vec::new();
+        for variant in
+            Punctuated::<Variant, Token![,]>::parse_terminated(&content)?
+        {
+            match EnumVariant::new(variant) {
+                Ok(variant) => variants.push(variant),
+                Err(e) => errors.push(e),
+            }
+        }
+        for (index, variant) in variants.iter().enumerate() {
+            let name = variant.name.value();
+            if variants[..index]
+                .iter()
+                .any(|other| other.name.value() == name)
+            {
+                errors.push(Error::new(
+                    variant.name.span(),
+                    format!("`{name}` is the name of another variant"),
+                ));
+            }
+        }
+        errors.finish()?;
+
+        if variants.is_empty() {
+            return Err(Error::new(
+                ident.span(),
+                format!("`{ident}` must have at least one variant"),
+            ));
+        }
+        if !input.is_empty() {
+            return Err(input.error("enums can't have helpers"));
+        }
+
+        Ok(EnumModel {
+            attrs,
+            options,
+            vis,
+            ident,
+            variants,
+        })
+    }
+}
+
+/// Options of the code generated from the enum, see `#[model(...)]`.
+#[derive(Default)]
+struct EnumOptions {
+    /// Implements `Display` with the names of the variants.
+    display: bool,
+    /// Implements `FromStr` failing with this error type.
+    from_str: Option<Path>,
+}
+
+impl EnumOptions {
+    /// Takes the options out of the attributes of the enum, returning the other attributes.
+    fn extract(attrs: Vec<Attribute>) -> Result<(Self, Vec<Attribute>)> {
+        let mut options = EnumOptions::default();
+        let mut others = Vec::new();
+        let mut errors = Errors::default();
+
+        for attr in attrs {
+            if !attr.path().is_ident("model") {
+                others.push(attr);
+                continue;
+            }
+
+            let result = attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("display") {
+                    options.display = true;
+                    return Ok(());
+                }
+                if meta.path.is_ident("from_str") {
+                    let content;
+                    syn::parenthesized!(content in meta.input);
+                    options.from_str = Some(content.parse()?);
+                    return Ok(());
+                }
+
+                Err(meta.error(
+                    "unknown `model` option of an enum, expected `display` or \
+                     `from_str(<error type>)`",
+                ))
+            });
+            if let Err(e) = result {
+                errors.push(e);
+            }
+        }
+        errors.finish()?;
+
+        Ok((options, others))
+    }
+}
+
+struct EnumVariant {
+    attrs: Vec<Attribute>,
+    ident: Ident,
+    /// Name of the variant in strings.
+    name: LitStr,
+}
+
+impl EnumVariant {
+    fn new(variant: Variant) -> Result<Self> {
+        if !matches!(variant.fields, Fields::Unit) {
+            return Err(Error::new_spanned(
+                &variant.fields,
+                "variants of enum models can't have fields",
+            ));
+        }
+        if let Some((_, discriminant)) = &variant.discriminant {
+            return Err(Error::new_spanned(
+                discriminant,
+                "variants of enum models can't have discriminants, they're stored by name",
+            ));
+        }
+
+        let mut name = None;
+        let mut attrs = Vec::new();
+        for attr in variant.attrs {
+            if !attr.path().is_ident("model") {
+                attrs.push(attr);
+                continue;
+            }
+
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    name = Some(meta.value()?.parse()?);
+                    return Ok(());
+                }
+
+                Err(meta.error(
+                    "unknown `model` option of a variant, expected `rename = \"<name>\"`",
+                ))
+            })?;
+        }
+
+        let ident = variant.ident;
+        let name = name.unwrap_or_else(|| {
+            LitStr::new(&snake_case(&ident.to_string()), ident.span())
+        });
+
+        Ok(EnumVariant { attrs, ident, name })
+    }
+}
+
+/// Generates the enum and the conversions between its variants and their names.
+pub(crate) fn expand(input: &EnumModel) -> TokenStream {
+    let EnumModel {
+        attrs,
+        options,
+        vis,
+        ident,
+        variants,
+    } = input;
+
+    let definitions = variants.iter().map(|variant| {
+        let EnumVariant { attrs, ident, .. } = variant;
+
+        quote! {
+            #(#attrs)*
+            #ident
+        }
+    });
+    let idents: Vec<_> =
+        variants.iter().map(|variant| &variant.ident).collect();
+    let names: Vec<_> = variants.iter().map(|variant| &variant.name).collect();
+    let count = variants.len();
+
+    let display = options.display.then(|| {
+        quote! {
+            impl ::core::fmt::Display for #ident {
+                fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                    f.write_str(self.as_str())
+                }
+            }
+        }
+    });
+    let from_str = options.from_str.as_ref().map(|error| {
+        let type_name = ident.to_string();
+
+        quote! {
+            impl ::core::str::FromStr for #ident {
+                type Err = #error;
+
+                fn from_str(s: &str) -> ::core::result::Result<Self, Self::Err> {
+                    Self::parse(s).ok_or_else(|| #error::unknown_variant(#type_name, s))
+                }
+            }
+        }
+    });
+
+    quote! {
+        #(#attrs)*
+        #vis enum #ident {
+            #(#definitions,)*
+        }
+
+        impl #ident {
+            /// All the variants, in the order they're declared in.
+            #vis const ALL: [Self; #count] = [#(Self::#idents),*];
+
+            /// Returns the name the variant is stored and exchanged as.
+            #vis fn as_str(&self) -> &'static str {
+                match self {
+                    #(Self::#idents => #names,)*
+                }
+            }
+
+            /// Returns the variant with the given name, if any.
+            #vis fn parse(name: &str) -> Option<Self> {
+                match name {
+                    #(#names => Some(Self::#idents),)*
+                    _ => None,
+                }
+            }
+        }
+
+        #display
+        #from_str
+    }
+}
+
+fn snake_case(ident: &str) -> String {
+    let mut name = String::with_capacity(ident.len() + 4);
+    for (index, c) in ident.char_indices() {
+        if c.is_uppercase() && index > 0 {
+            name.push('_');
+        }
+        name.extend(c.to_lowercase());
+    }
+
+    name
+}
@@ -1,210 +1,321 @@
-/// Macro for generating stable UUID identifiers from a set of inputs.
-///
-/// This macro generates an ID model that is used to generate a UUID V5 from the contained fields.
-///
-/// It uses the [model generation macros](crate::gen_model) internally for generating the ID model and the
-/// new entity helper, so possible field attributes and configurations are the same as for that macro.
-///
-/// # Examples
-///
-/// ```
-/// # use uuid::Uuid;
-/// # use identify_macros::gen_id;
-/// const UUID_NAMESPACE: Uuid = Uuid::from_bytes(*b"doc-example-uuid");
-///
-/// gen_id! {
-///     UUID_NAMESPACE,
-///     /// A stable and deterministic ID that uniquely identifies an entity within the system.
-///    #[derive(Debug, Clone)]
-///     pub struct ModelId {
-///         /// Email.
-///         email: String,
-///         /// Username.
-///         username: String,
-///     }
-///
-///     #[derive(Debug, Clone)]
-///     pub struct ModelIdAttrs;
-/// }
-/// ```
-///
-/// The above expands to the following code:
-///
-/// ```
-/// # const UUID_NAMESPACE: uuid::Uuid = uuid::Uuid::from_bytes(*b"doc-example-uuid");
-/// /// A stable and deterministic ID that uniquely identifies an entity within the system.
-/// #[derive(Debug, Clone)]
-/// pub struct ModelId {
-///     /// Email.
-///     email: String,
-///     /// Username.
-///     username: String,
-/// }
-///
-/// impl ModelId {
-///     /// Email.
-///     pub fn email(&self) -> &String {
-///         &self.email
-///     }
-///
-///     /// Username.
-///     pub fn username(&self) -> &String {
-///         &self.username
-///     }
-/// }
-///
-/// #[derive(Debug, Clone)]
-/// pub struct ModelIdAttrs {
-///     /// Email.
-///     pub email: String,
-///     /// Username.
-///     pub username: String,
-/// }
-///
-/// impl ModelId {
-///     /// Generates a UUID V5 from the fields this ID model has.
-///     pub fn to_uuid(&self) -> ::uuid::Uuid {
-///         let mut name = Vec::new();
-///
-///         name.extend_from_slice("ModelId".as_bytes());
-///         name.extend_from_slice(b" ID");
-///         name.extend_from_slice(self.email.as_bytes());
-///         name.extend_from_slice(self.username.as_bytes());
-///
-///         ::uuid::Uuid::new_v5(&UUID_NAMESPACE, &name)
-///     }
-/// }
-///
-/// impl From<&ModelId> for ::uuid::Uuid {
-///     fn from(value: &ModelId) -> Self {
-///         value.to_uuid()
-///     }
-/// }
-/// ```
-///
-/// # Usage
-///
-/// ## Using custom byte slice conversion function
-///
-/// By default, the macro tries to call the `as_bytes` method on every field to gets their byte slice representation.
-///
-/// You can override this if needed by using the following syntax:
-///
-/// ```
-/// # use identify_macros::gen_id;
-/// # const UUID_NAMESPACE: uuid::Uuid = uuid::Uuid::from_bytes(*b"doc-example-uuid");
-/// // An explicit version of what is done by default.
-/// fn custom_to_bytes(value: &str) -> &[u8] {
-///     value.as_ref()
-/// }
-///
-/// gen_id! {
-///     UUID_NAMESPACE,
-///     pub struct ModelId {
-///         value: String [custom_to_bytes],
-///     }
-/// }
-/// ```
-///
-/// The UUID generation function will then look like this:
-///
-/// ```
-/// # const UUID_NAMESPACE: uuid::Uuid = uuid::Uuid::from_bytes(*b"doc-example-uuid");
-/// # fn custom_to_bytes(value: &str) -> &[u8] {
-/// #    value.as_ref()
-/// # }
-/// # pub struct ModelId {
-/// #     value: String,
-/// # }
-/// # impl ModelId {
-///  pub fn to_uuid(&self) -> ::uuid::Uuid {
-///     let mut name = Vec::new();
-///
-///     name.extend_from_slice("ModelId".as_bytes());
-///     name.extend_from_slice(b" ID");
-///     name.extend_from_slice(custom_to_bytes(&self.value));
-///
-///     ::uuid::Uuid::new_v5(&UUID_NAMESPACE, &name)
-///  }
-/// #    }
-/// ```
-///
-/// # Notes
-///
-/// The generated UUIDs **depend on the order of fields in the ID model**. Rearranging the fields will
-/// result in different UUIDs being generated.
-#[macro_export]
-macro_rules! gen_id {
-    ($($input:tt)*) => {
-        $crate::gen_id_helper!($($input)*);
-    };
+//! Parsing and expansion of [gen_id!](crate::gen_id).
+
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::{
+    Attribute, Error, Ident, LitInt, Path, Result, Token,
+    parse::{Parse, ParseStream},
+};
+
+use crate::model::{self, Helper, Model, ModelField, ModelInput};
+
+/// Input of [gen_id!](crate::gen_id): the UUID namespace and the version of the recipe, followed
+/// by the input of [gen_model!](crate::gen_model).
+pub(crate) struct IdInput {
+    namespace: Path,
+    version: u32,
+    model: ModelInput,
 }
 
-#[doc(hidden)]
-#[macro_export(local_inner_macros)]
-macro_rules! gen_id_helper {
-    (
-        // UUID namespace used for the UUID V5 generation.
-        $uuid_namespace:ident,
-
-        // ID model.
-        $(#[$id_attrs:meta])*
-        $id_vis:vis struct $name:ident {
-            $(
-                $(#[$($f_attrs:tt)*])*
-                $f_vis:vis $f_name:ident: $f_type:ty $([$($conv_fn:tt)*])?,
-            )+
+impl Parse for IdInput {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let namespace = input.parse().map_err(|e| {
+            Error::new(
+                e.span(),
+                "expected the UUID namespace of the IDs before the model, e.g. \
+                 `gen_id! { NAMESPACE, pub struct ModelId { ... } }`",
+            )
+        })?;
+        input.parse::<Token![,]>()?;
+
+        let mut version = 1;
+        if input.peek(Ident) && input.peek2(Token![=]) {
+            let key: Ident = input.parse()?;
+            if key != "version" {
+                return Err(Error::new(
+                    key.span(),
+                    format!(
+                        "unknown `{key}` argument, expected `version = <version>`"
+                    ),
+                ));
+            }
+            input.parse::<Token![=]>()?;
+            version = Version::parse(&input.parse()?)?.version;
+            input.parse::<Token![,]>()?;
         }
 
-        // Optional helper definitions as expected by the `gen_model` macro.
-        $($helpers:tt)*
-    ) => {
-        // Generate the ID model using the model generation macro.
-        $crate::gen_model! {
-            $(#[$id_attrs])*
-            $id_vis struct $name {
-               $(
-                    $(#[$($f_attrs)*])*
-                    $f_vis $f_name: $f_type,
-                )+
+        let model = Model::parse(input, true)?;
+        for field in &model.fields {
+            let IdOption {
+                since,
+                normalize,
+                normalize_since,
+                ..
+            } = &field.id;
+            for added in [since, normalize_since].into_iter().flatten() {
+                if added.version > version {
+                    return Err(Error::new(
+                        added.span,
+                        format!(
+                            "`{}` can't change after the current version {version} of the ID",
+                            field.ident
+                        ),
+                    ));
+                }
+            }
+            if let (Some(bytes), false) = (&field.bytes, normalize.is_empty()) {
+                return Err(Error::new_spanned(
+                    bytes,
+                    "fields with a byte conversion can't be normalized",
+                ));
+            }
+        }
+        let helpers = Helper::parse_all(input, &model)?;
+
+        Ok(IdInput {
+            namespace,
+            version,
+            model: ModelInput { model, helpers },
+        })
+    }
+}
+
+/// Generates the ID model and the UUID generation from its fields.
+pub(crate) fn expand(input: &IdInput) -> TokenStream {
+    let IdInput {
+        namespace,
+        version,
+        model,
+    } = input;
+    let ident = &model.model.ident;
+
+    let model_tokens = model::expand(model);
+    let name = recipe(ident, &model.model.fields, *version);
+
+    // Previous recipes are kept so that stored IDs can be re-keyed to the current one.
+    let migrations = (1..*version).map(|previous| {
+        let name = recipe(ident, &model.model.fields, previous);
+        let to_uuid = format_ident!("to_uuid_v{previous}");
+        let migrate = format_ident!("migrate_from_v{previous}");
+        let to_uuid_doc =
+            format!("Generates the UUID this ID had in version {previous} of its recipe.");
+        let migrate_doc = format!(
+            "Re-keys a UUID generated by version {previous} of the recipe of this ID.\n\n\
+             Returns the current UUID of the ID, or `None` if `uuid` wasn't generated from this \
+             ID by that version."
+        );
+
+        quote! {
+            #[doc = #to_uuid_doc]
+            pub fn #to_uuid(&self) -> ::uuid::Uuid {
+                #name
+                ::uuid::Uuid::new_v5(&#namespace, &name)
             }
 
-            // Optional helpers.
-            $($helpers)*
+            #[doc = #migrate_doc]
+            pub fn #migrate(&self, uuid: ::uuid::Uuid) -> Option<::uuid::Uuid> {
+                (self.#to_uuid() == uuid).then(|| self.to_uuid())
+            }
         }
+    });
+
+    quote! {
+        #model_tokens
+
+        impl #ident {
+            /// Version of the recipe the UUIDs of this ID are generated with.
+            pub const ID_VERSION: u32 = #version;
 
-        // Implement the UUID generation method.
-        impl $name {
             /// Generates a UUID V5 from the fields this ID model has.
             pub fn to_uuid(&self) -> ::uuid::Uuid {
-                let mut name = Vec::new();
+                #name
+                ::uuid::Uuid::new_v5(&#namespace, &name)
+            }
 
-                name.extend_from_slice(::core::stringify!($name).as_bytes());
-                name.extend_from_slice(b" ID");
-                // Use all fields this ID model has.
-                $(
-                    // Account for fields that use custom functions to get bytes representation.
-                    name.extend_from_slice(gen_id_helper!(@bytes self, $f_name $(, $($conv_fn)*)?));
-                )+
+            #(#migrations)*
+        }
 
-                ::uuid::Uuid::new_v5(&$uuid_namespace, &name)
+        impl From<&#ident> for ::uuid::Uuid {
+            fn from(value: &#ident) -> Self {
+                value.to_uuid()
             }
         }
+    }
+}
 
-        impl From<&$name> for ::uuid::Uuid {
-            fn from(value: &$name) -> Self {
-                value.to_uuid()
+/// Builds the `name` the UUID is generated from with the given version of the recipe.
+///
+/// The first version has no marker, so that IDs generated before versions were introduced
+/// don't change. Fields are only included by the versions they were added in or after.
+fn recipe(ident: &Ident, fields: &[ModelField], version: u32) -> TokenStream {
+    let type_name = ident.to_string();
+    let marker = (version > 1).then(|| {
+        let marker = format!(" v{version}");
+        quote!(name.extend_from_slice(#marker.as_bytes());)
+    });
+    let fields = fields
+        .iter()
+        .filter(|field| {
+            let since = field.id.since.as_ref();
+            since.is_none_or(|since| since.version <= version)
+        })
+        .map(|field| {
+            let ModelField { ident, bytes, .. } = field;
+            let normalized = field
+                .id
+                .normalize_since
+                .as_ref()
+                .is_none_or(|since| since.version <= version);
+
+            match bytes {
+                Some(bytes) => {
+                    quote!(name.extend_from_slice(#bytes(&self.#ident));)
+                }
+                _ if normalized && !field.id.normalize.is_empty() => {
+                    let steps = Normalization::ORDER
+                        .iter()
+                        .filter(|step| field.id.normalize.contains(step))
+                        .map(Normalization::expand);
+
+                    quote! {{
+                        let value = ::core::convert::AsRef::<str>::as_ref(&self.#ident);
+                        #(#steps)*
+                        name.extend_from_slice(value.as_bytes());
+                    }}
+                }
+                // Fields without a custom conversion are expected to have `as_bytes`.
+                _ => quote!(name.extend_from_slice(self.#ident.as_bytes());),
+            }
+        });
+
+    quote! {
+        let mut name = Vec::new();
+
+        name.extend_from_slice(#type_name.as_bytes());
+        name.extend_from_slice(b" ID");
+        #marker
+        #(#fields)*
+    }
+}
+
+/// Options of a field of an ID model, see `#[id(...)]`.
+#[derive(Default)]
+pub(crate) struct IdOption {
+    /// Version of the recipe the field was added in.
+    since: Option<Version>,
+    /// Normalizations of the value before it's hashed.
+    normalize: Vec<Normalization>,
+    /// Version of the recipe the value started to be normalized in.
+    normalize_since: Option<Version>,
+}
+
+impl IdOption {
+    pub fn parse(attr: &Attribute) -> Result<Self> {
+        let mut option = IdOption::default();
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("since") {
+                option.since = Some(Version::parse(&meta.value()?.parse()?)?);
+                return Ok(());
+            }
+            if meta.path.is_ident("normalize_since") {
+                option.normalize_since =
+                    Some(Version::parse(&meta.value()?.parse()?)?);
+                return Ok(());
+            }
+            if meta.path.is_ident("normalize") {
+                return meta.parse_nested_meta(|step| {
+                    let Some(normalization) = Normalization::ORDER
+                        .into_iter()
+                        .find(|normalization| step.path.is_ident(normalization.name()))
+                    else {
+                        return Err(step.error(
+                            "unknown normalization, expected `trim`, `lowercase` or `nfc`",
+                        ));
+                    };
+                    if option.normalize.contains(&normalization) {
+                        return Err(step.error("duplicate normalization"));
+                    }
+                    option.normalize.push(normalization);
+
+                    Ok(())
+                });
             }
+
+            Err(meta.error(
+                "unknown `id` option, expected `since = <version>`, `normalize(...)` or \
+                 `normalize_since = <version>`",
+            ))
+        })?;
+
+        if let (Some(since), true) =
+            (&option.normalize_since, option.normalize.is_empty())
+        {
+            return Err(Error::new(
+                since.span,
+                "`normalize_since` requires `normalize(...)`",
+            ));
         }
-    };
 
-    // A standard field.
-    (@bytes $self:ident, $field:ident) => {
-        $self.$field.as_bytes()
-    };
+        Ok(option)
+    }
+}
+
+/// A normalization of the value of a field before it's hashed.
+#[derive(Clone, Copy, PartialEq)]
+enum Normalization {
+    Trim,
+    Lowercase,
+    Nfc,
+}
 
-    // Field with a custom byte slice conversion function.
-    (@bytes $self:ident, $field:ident, $($conv_fn:tt)*) => {
-        $($conv_fn)*(&$self.$field)
-    };
+impl Normalization {
+    /// Order the normalizations are applied in, whatever order they're listed in. NFC comes
+    /// last, since lowercasing may produce sequences that aren't composed.
+    const ORDER: [Normalization; 3] = [
+        Normalization::Trim,
+        Normalization::Lowercase,
+        Normalization::Nfc,
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Normalization::Trim => "trim",
+            Normalization::Lowercase => "lowercase",
+            Normalization::Nfc => "nfc",
+        }
+    }
+
+    /// Rebinds `value` to its normalized form.
+    fn expand(&self) -> TokenStream {
+        match self {
+            Normalization::Trim => quote!(let value = value.trim();),
+            Normalization::Lowercase => quote! {
+                let value = value.to_lowercase();
+                let value = value.as_str();
+            },
+            Normalization::Nfc => quote! {
+                let value = ::unicode_normalization::UnicodeNormalization::nfc(value)
+                    .collect::<String>();
+                let value = value.as_str();
+            },
+        }
+    }
+}
+
+/// A version of the recipe of an ID.
+struct Version {
+    version: u32,
+    span: Span,
+}
+
+impl Version {
+    fn parse(version: &LitInt) -> Result<Self> {
+        match version.base10_parse()? {
+            0 => Err(Error::new(version.span(), "versions start at 1")),
+            parsed => Ok(Version {
+                version: parsed,
+                span: version.span(),
+            }),
+        }
+    }
 }
@@ -1,4 +1,576 @@
 //! This crate contains macros used in Identify to reduce code duplication and simplify frequent operations.
 
+use proc_macro::TokenStream;
+use syn::{DeriveInput, parse_macro_input};
+
+mod builder;
+mod enumeration;
 mod id;
 mod model;
+mod row;
+
+/// Macro for generating domain models.
+///
+/// This macro generates a domain model and two optional helpers:
+///
+/// - One for creating a new instance of the entity (the "new entity" helper).
+/// - One for hydrating an existing entity from a set of attributes (the "hydration" helper).
+///
+/// # Examples
+///
+/// ```
+/// # use identify_macros::gen_model;
+/// gen_model!  {
+///     // This is the model itself.
+///     pub struct Model {
+///         /// A numeric ID with a custom type for new models.
+///         #[new(type(u32))]
+///         id: u64,
+///         /// A string field with a custom getter.
+///         #[get(as_ref(&str))]
+///         #[new(skip)]
+///         first_name: String,
+///         #[new(skip)]
+///         #[hydrate(skip)]
+///         last_name: String,
+///     }
+///
+///     // This is the new entity helper with additional field.
+///     pub struct NewModelAttrs {
+///         /// We want to parse first name and last name from a concatenated string.
+///         full_name: String,
+///     }
+///
+///     // This is the hydration helper with additional field.
+///     pub struct ModelAttrs {
+///         /// Concatenated [Model::first_name] and [Model::last_name].
+///         full_name: String,
+///     }
+/// }
+/// ```
+///
+/// The above expands to the following code:
+///
+/// ```
+/// pub struct Model {
+///     /// A numeric ID with a custom type for new models.
+///     id: u64,
+///     first_name: String,
+///     last_name: String,
+/// }
+///
+/// impl Model {
+///     /// A numeric ID with a custom type for new models.
+///     pub fn id(&self) -> &u64 {
+///         &self.id
+///     }
+///
+///     /// A string field with a custom getter.
+///     pub fn first_name(&self) -> &str {
+///         self.first_name.as_ref()
+///     }
+///
+///     pub fn last_name(&self) -> &String {
+///         &self.last_name
+///     }
+/// }
+///
+/// pub struct NewModelAttrs {
+///     /// We want to parse first name and last name from a concatenated string.
+///     pub full_name: String,
+///     /// A numeric ID with a custom type for new models.
+///     pub id: u32,
+/// }
+///
+/// pub struct ModelAttrs {
+///     /// Concatenated [Model::first_name] and [Model::last_name].
+///     pub full_name: String,
+///     /// A numeric ID with a custom type for new models.
+///     pub id: u64,
+///     /// A string field with a custom getter.
+///     pub first_name: String,
+/// }
+/// ```
+///
+/// # Usage
+///
+/// ## Getter options
+///
+/// You can annotate fields on your model with `#[get(...)]` to change the generated getters.
+///
+/// Supported options:
+///
+/// - `#[get(skip)]` - skips the field.
+/// - `#[get(copy)]` - returns a copy of the field, e.g. of a [generated enum](#enums).
+/// - `#[get(into(<type>))]` - calls `Into::into()` on the field to cast it to the specified type.
+/// - `#[get(ref_into(<type>))]` - calls `Into::into()` on a reference to the field to cast it to the specified type.
+/// - `#[get(as_ref(<type>))]` - calls `AsRef::as_ref()` on the field to borrow the specified type from it.
+///
+///  ⚠️ All provided options are **mutually-exclusive**.
+///
+/// ## Setter options
+///
+/// You can annotate fields on your model with `#[set(...)]` to generate a `set_<field>` mutator,
+/// which returns `false` without changing anything if the value is the same as before, so that
+/// callers know whether the model has to be saved.
+///
+/// Supported options:
+///
+/// - `#[set]` - only changes the field.
+/// - `#[set(touch(<field>))]` - also sets another field of the model to `chrono::Utc::now()`
+///   when the value changes, e.g. `updated_at`.
+///
+/// ```
+/// # use identify_macros::gen_model;
+/// # use chrono::{DateTime, Utc};
+/// gen_model! {
+///     pub struct Model {
+///         #[set(touch(updated_at))]
+///         name: String,
+///         updated_at: DateTime<Utc>,
+///     }
+/// }
+/// ```
+///
+/// ## Generating helpers
+///
+/// You can have at most two additional helper structs for every model you generate:
+///
+/// - One for creating a new instance of the entity.
+/// - And the second one for hydrating an existing entity from a set of attributes.
+///
+/// ## New entity helper options
+///
+/// You can annotate fields on your model with `#[new(...)]` to change the generated helper.
+///
+/// Supported options:
+///
+/// - `#[new(skip)]` - skips the field.
+/// - `#[new(type(<type>))]` - uses a different type for this field in the helper struct.
+///
+/// ⚠️ All provided options are **mutually-exclusive**.
+///
+/// ## Hydration helper options
+///
+/// You can annotate fields on your model with `#[hydrate(...)]` to change the generated helper.
+///
+/// Supported options:
+///
+/// - `#[hydrate(skip)]` - skips the field.
+/// - `#[hydrate(type(<type>))]` - uses a different type for this field in the helper struct.
+///
+/// ⚠️ All provided options are **mutually-exclusive**.
+///
+/// ## Model options
+///
+/// You can annotate the model with `#[model(...)]` to generate additional code.
+///
+/// Supported options:
+///
+/// - `#[model(builder)]` - generates a typed builder for the new entity helper, returned by its
+///   `builder()` function. Fields of type `Option<T>` are optional and start as `None`, while
+///   all the other fields are required: `build()` compiles only once all of them are set, and
+///   each of them can be set only once.
+/// - `#[model(serde)]` - derives `serde::Serialize` and `serde::Deserialize` on the helpers.
+///   Fields can be annotated with `#[api(...)]` to change how they're serialized, which stands
+///   for `#[serde(...)]` on the fields of the helpers: `#[api(rename = "<name>")]`,
+///   `#[api(skip)]` and `#[api(default)]` are supported.
+///
+/// ```
+/// # use identify_macros::gen_model;
+/// gen_model! {
+///     #[model(builder)]
+///     pub struct Model {
+///         first_name: String,
+///         last_name: Option<String>,
+///     }
+///
+///     pub struct NewModelAttrs {
+///         email: String,
+///     }
+/// }
+///
+/// let attrs = NewModelAttrs::builder()
+///     .first_name("Jane".to_owned())
+///     .email("jane@example.com".to_owned())
+///     .build();
+///
+/// assert_eq!(attrs.last_name, None);
+/// ```
+///
+/// ```
+/// # use identify_macros::gen_model;
+/// gen_model! {
+///     #[model(serde)]
+///     pub struct Model {
+///         #[api(rename = "firstName")]
+///         first_name: String,
+///     }
+///
+///     pub struct NewModelAttrs;
+/// }
+///
+/// let attrs: NewModelAttrs = serde_json::from_str(r#"{"firstName":"Jane"}"#).unwrap();
+///
+/// assert_eq!(attrs.first_name, "Jane");
+/// ```
+///
+/// ## Enums
+///
+/// Enums with unit variants can be generated too, so that enum fields of models are stored and
+/// exchanged by name. Every variant gets the snake case of its name, unless it's annotated with
+/// `#[model(rename = "<name>")]`, and the enum gets:
+///
+/// - `as_str()` and `parse(<name>)`, converting between the variants and their names.
+/// - `ALL`, the array of all the variants.
+///
+/// Supported options of the enum:
+///
+/// - `#[model(display)]` - implements `Display` with the names of the variants.
+/// - `#[model(from_str(<error type>))]` - implements `FromStr`, failing with
+///   `<error type>::unknown_variant(<enum name>, <value>)`.
+///
+/// Other attributes of the enum and its variants are kept as they are.
+///
+/// ```
+/// # use identify_macros::gen_model;
+/// # #[derive(Debug)]
+/// # pub struct Error;
+/// # impl Error {
+/// #     fn unknown_variant(_: &'static str, _: &str) -> Self { Error }
+/// # }
+/// gen_model! {
+///     #[derive(Debug, Clone, Copy, PartialEq)]
+///     #[model(display, from_str(Error))]
+///     pub enum Status {
+///         Active,
+///         #[model(rename = "on_hold")]
+///         Suspended,
+///     }
+/// }
+///
+/// assert_eq!(Status::Suspended.to_string(), "on_hold");
+/// assert_eq!("active".parse::<Status>().unwrap(), Status::Active);
+/// assert!("suspended".parse::<Status>().is_err());
+/// ```
+///
+/// ## Diagnostics
+///
+/// Misused options are reported on the attributes that carry them, e.g. unknown or duplicate
+/// options, mutually-exclusive options used together, or attributes that aren't wrapped in
+/// `#[fw(...)]`.
+///
+/// ## Using custom attributes
+///
+/// This macro supports forwarding any custom attributes using a special attribute `#[fw(...)]`.
+///
+/// The wrapping is needed to simplify parsing of attributes provided by this macro.
+///
+/// Example:
+///
+/// ```
+/// # use identify_macros::gen_model;
+/// gen_model! {
+///     pub struct Model {
+///         #[fw(allow(dead_code))]
+///         field: String,
+///     }
+/// }
+/// ```
+#[proc_macro]
+pub fn gen_model(input: TokenStream) -> TokenStream {
+    match parse_macro_input!(input as model::GenModelInput) {
+        model::GenModelInput::Struct(input) => model::expand(&input).into(),
+        model::GenModelInput::Enum(input) => enumeration::expand(&input).into(),
+    }
+}
+
+/// Macro for generating stable UUID identifiers from a set of inputs.
+///
+/// This macro generates an ID model that is used to generate a UUID V5 from the contained fields.
+///
+/// It uses the [model generation macros](crate::gen_model) internally for generating the ID model and the
+/// new entity helper, so possible field attributes and configurations are the same as for that macro.
+///
+/// # Examples
+///
+/// ```
+/// # use uuid::Uuid;
+/// # use identify_macros::gen_id;
+/// const UUID_NAMESPACE: Uuid = Uuid::from_bytes(*b"doc-example-uuid");
+///
+/// gen_id! {
+///     UUID_NAMESPACE,
+///     /// A stable and deterministic ID that uniquely identifies an entity within the system.
+///    #[derive(Debug, Clone)]
+///     pub struct ModelId {
+///         /// Email.
+///         email: String,
+///         /// Username.
+///         username: String,
+///     }
+///
+///     #[derive(Debug, Clone)]
+///     pub struct ModelIdAttrs;
+/// }
+/// ```
+///
+/// The above expands to the following code:
+///
+/// ```
+/// # const UUID_NAMESPACE: uuid::Uuid = uuid::Uuid::from_bytes(*b"doc-example-uuid");
+/// /// A stable and deterministic ID that uniquely identifies an entity within the system.
+/// #[derive(Debug, Clone)]
+/// pub struct ModelId {
+///     /// Email.
+///     email: String,
+///     /// Username.
+///     username: String,
+/// }
+///
+/// impl ModelId {
+///     /// Email.
+///     pub fn email(&self) -> &String {
+///         &self.email
+///     }
+///
+///     /// Username.
+///     pub fn username(&self) -> &String {
+///         &self.username
+///     }
+/// }
+///
+/// #[derive(Debug, Clone)]
+/// pub struct ModelIdAttrs {
+///     /// Email.
+///     pub email: String,
+///     /// Username.
+///     pub username: String,
+/// }
+///
+/// impl ModelId {
+///     /// Generates a UUID V5 from the fields this ID model has.
+///     pub fn to_uuid(&self) -> ::uuid::Uuid {
+///         let mut name = Vec::new();
+///
+///         name.extend_from_slice("ModelId".as_bytes());
+///         name.extend_from_slice(b" ID");
+///         name.extend_from_slice(self.email.as_bytes());
+///         name.extend_from_slice(self.username.as_bytes());
+///
+///         ::uuid::Uuid::new_v5(&UUID_NAMESPACE, &name)
+///     }
+/// }
+///
+/// impl From<&ModelId> for ::uuid::Uuid {
+///     fn from(value: &ModelId) -> Self {
+///         value.to_uuid()
+///     }
+/// }
+/// ```
+///
+/// # Usage
+///
+/// ## Using custom byte slice conversion function
+///
+/// By default, the macro tries to call the `as_bytes` method on every field to gets their byte slice representation.
+///
+/// You can override this if needed by using the following syntax:
+///
+/// ```
+/// # use identify_macros::gen_id;
+/// # const UUID_NAMESPACE: uuid::Uuid = uuid::Uuid::from_bytes(*b"doc-example-uuid");
+/// // An explicit version of what is done by default.
+/// fn custom_to_bytes(value: &str) -> &[u8] {
+///     value.as_ref()
+/// }
+///
+/// gen_id! {
+///     UUID_NAMESPACE,
+///     pub struct ModelId {
+///         value: String [custom_to_bytes],
+///     }
+/// }
+/// ```
+///
+/// The UUID generation function will then look like this:
+///
+/// ```
+/// # const UUID_NAMESPACE: uuid::Uuid = uuid::Uuid::from_bytes(*b"doc-example-uuid");
+/// # fn custom_to_bytes(value: &str) -> &[u8] {
+/// #    value.as_ref()
+/// # }
+/// # pub struct ModelId {
+/// #     value: String,
+/// # }
+/// # impl ModelId {
+///  pub fn to_uuid(&self) -> ::uuid::Uuid {
+///     let mut name = Vec::new();
+///
+///     name.extend_from_slice("ModelId".as_bytes());
+///     name.extend_from_slice(b" ID");
+///     name.extend_from_slice(custom_to_bytes(&self.value));
+///
+///     ::uuid::Uuid::new_v5(&UUID_NAMESPACE, &name)
+///  }
+/// #    }
+/// ```
+///
+/// ## Normalizing fields
+///
+/// String fields can be normalized before they're hashed with `#[id(normalize(...))]`, so that
+/// values that only differ in their form generate the same UUID. The field itself is kept as is.
+///
+/// Supported normalizations, which are applied in this order whatever order they're listed in:
+///
+/// - `trim` - removes leading and trailing whitespace.
+/// - `lowercase` - converts the value to lowercase.
+/// - `nfc` - composes the value to Unicode Normalization Form C, which requires the
+///   `unicode-normalization` crate.
+///
+/// Normalizing a field changes the UUIDs of values that weren't normalized, so adding a
+/// normalization to a stored ID needs a new version of the recipe, and
+/// `#[id(normalize_since = <version>)]` to keep hashing raw values in the previous ones.
+///
+/// ```
+/// # use identify_macros::gen_id;
+/// # const UUID_NAMESPACE: uuid::Uuid = uuid::Uuid::from_bytes(*b"doc-example-uuid");
+/// gen_id! {
+///     UUID_NAMESPACE,
+///     pub struct ModelId {
+///         #[id(normalize(trim, lowercase))]
+///         email: String,
+///     }
+/// }
+///
+/// let id = |email: &str| ModelId { email: email.to_owned() }.to_uuid();
+///
+/// assert_eq!(id(" Jane@Example.com"), id("jane@example.com"));
+/// ```
+///
+/// ## Versioning the recipe
+///
+/// The recipe the UUIDs are generated with can be versioned by passing `version = <version>`
+/// after the namespace. Versions after the first are mixed into the UUIDs, and fields added by a
+/// later version are annotated with `#[id(since = <version>)]`.
+///
+/// For every previous version `n`, the macro generates `to_uuid_vn()`, which generates the UUID
+/// the ID had in that version, and `migrate_from_vn(uuid)`, which re-keys a stored UUID to the
+/// current recipe if it was generated from the ID by that version:
+///
+/// ```
+/// # use identify_macros::gen_id;
+/// # const UUID_NAMESPACE: uuid::Uuid = uuid::Uuid::from_bytes(*b"doc-example-uuid");
+/// gen_id! {
+///     UUID_NAMESPACE,
+///     version = 2,
+///     pub struct ModelId {
+///         email: String,
+///         #[id(since = 2)]
+///         tenant: String,
+///     }
+/// }
+///
+/// let id = ModelId {
+///     email: "jane@example.com".to_owned(),
+///     tenant: "acme".to_owned(),
+/// };
+/// let stored = id.to_uuid_v1();
+///
+/// assert_eq!(id.migrate_from_v1(stored), Some(id.to_uuid()));
+/// assert_eq!(id.migrate_from_v1(id.to_uuid()), None);
+/// ```
+///
+/// # Notes
+///
+/// The generated UUIDs **depend on the order of fields in the ID model**. Rearranging the fields will
+/// result in different UUIDs being generated, so only bump the version and add fields with
+/// `#[id(since = <version>)]` to change the recipe of IDs that are already stored.
+#[proc_macro]
+pub fn gen_id(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as id::IdInput);
+
+    id::expand(&input).into()
+}
+
+/// Derive macro for mapping database rows to the models of [gen_model!](crate::gen_model).
+///
+/// It implements `From<&Model> for Row`, which goes through `Model::to_attributes()`, and
+/// `TryFrom<Row> for Model`, which goes through `Model::load(attrs)`, as is the convention for
+/// the models with a hydration helper. Every field of the row is mapped to the field of the
+/// helper with the same name, so a field added to the model without a column fails to compile.
+///
+/// # Usage
+///
+/// The row must be annotated with `#[row(...)]`:
+///
+/// - `model = <type>` - the model the row maps to.
+/// - `error = <type>` - the error `Model::load` returns.
+/// - `attrs = <type>` - the hydration helper of the model, `<model>Attrs` by default.
+///
+/// Fields of the row can be annotated with `#[row(...)]` too:
+///
+/// - `#[row(field = <field>)]` - maps the column to a field of the helper with another name.
+/// - `#[row(text)]` - stores the field as text, with `Display` and `FromStr`, e.g. for
+///   [enums](crate::gen_model#enums).
+///
+/// ```
+/// # use identify_macros::{ModelRow, gen_model};
+/// # #[derive(Debug)]
+/// # pub struct Error;
+/// # impl Error {
+/// #     fn unknown_variant(_: &'static str, _: &str) -> Self { Error }
+/// # }
+/// gen_model! {
+///     #[derive(Debug, Clone, Copy, PartialEq)]
+///     #[model(display, from_str(Error))]
+///     pub enum Role {
+///         Owner,
+///         Member,
+///     }
+/// }
+///
+/// gen_model! {
+///     pub struct Member {
+///         name: String,
+///         #[get(copy)]
+///         role: Role,
+///     }
+///
+///     pub struct MemberAttrs;
+/// }
+///
+/// impl Member {
+///     pub fn load(attrs: MemberAttrs) -> Result<Self, Error> {
+///         Ok(Member { name: attrs.name, role: attrs.role })
+///     }
+///
+///     pub fn to_attributes(&self) -> MemberAttrs {
+///         MemberAttrs { name: self.name.clone(), role: self.role }
+///     }
+/// }
+///
+/// #[derive(ModelRow)]
+/// #[row(model = Member, error = Error)]
+/// pub struct MemberRow {
+///     #[row(field = name)]
+///     display_name: String,
+///     #[row(text)]
+///     role: String,
+/// }
+///
+/// let row = MemberRow { display_name: "Jane".to_owned(), role: "owner".to_owned() };
+/// let member = Member::try_from(row).unwrap();
+///
+/// assert_eq!(member.role(), Role::Owner);
+/// assert_eq!(MemberRow::from(&member).role, "owner");
+/// ```
+#[proc_macro_derive(ModelRow, attributes(row))]
+pub fn model_row(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    match row::Row::parse(input) {
+        Ok(row) => row::expand(&row).into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
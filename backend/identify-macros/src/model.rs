@@ -1,679 +1,774 @@
-/// Macro for generating domain models.
-///
-/// This macro generates a domain model and two optional helpers:
-///
-/// - One for creating a new instance of the entity (the "new entity" helper).
-/// - One for hydrating an existing entity from a set of attributes (the "hydration" helper).
-///
-/// # Examples
-///
-/// ```
-/// # use identify_macros::gen_model;
-/// gen_model!  {
-///     // This is the model itself.
-///     pub struct Model {
-///         /// A numeric ID with a custom type for new models.
-///         #[new(type(u32))]
-///         id: u64,
-///         /// A string field with a custom getter.
-///         #[get(as_ref(&str))]
-///         #[new(skip)]
-///         first_name: String,
-///         #[new(skip)]
-///         #[hydrate(skip)]
-///         last_name: String,
-///     }
-///
-///     // This is the new entity helper with additional field.
-///     pub struct NewModelAttrs {
-///         /// We want to parse first name and last name from a concatenated string.
-///         full_name: String,
-///     }
-///
-///     // This is the hydration helper with additional field.
-///     pub struct ModelAttrs {
-///         /// Concatenated [Model::first_name] and [Model::last_name].
-///         full_name: String,
-///     }
-/// }
-/// ```
-///
-/// The above expands to the following code:
-///
-/// ```
-/// pub struct Model {
-///     /// A numeric ID with a custom type for new models.
-///     id: u64,
-///     first_name: String,
-///     last_name: String,
-/// }
-///
-/// impl Model {
-///     /// A numeric ID with a custom type for new models.
-///     pub fn id(&self) -> &u64 {
-///         &self.id
-///     }
-///
-///     /// A string field with a custom getter.
-///     pub fn first_name(&self) -> &str {
-///         self.first_name.as_ref()
-///     }
-///
-///     pub fn last_name(&self) -> &String {
-///         &self.last_name
-///     }
-/// }
-///
-/// pub struct NewModelAttrs {
-///     /// We want to parse first name and last name from a concatenated string.
-///     pub full_name: String,
-///     /// A numeric ID with a custom type for new models.
-///     pub id: u32,
-/// }
-///
-/// pub struct ModelAttrs {
-///     /// Concatenated [Model::first_name] and [Model::last_name].
-///     pub full_name: String,
-///     /// A numeric ID with a custom type for new models.
-///     pub id: u64,
-///     /// A string field with a custom getter.
-///     pub first_name: String,
-/// }
-/// ```
-///
-/// # Usage
-///
-/// ## Getter options
-///
-/// You can annotate fields on your model with `#[get(...)]` to change the generated getters.
-///
-/// Supported options:
-///
-/// - `#[get(skip)]` - skips the field.
-/// - `#[get(into(<type>))]` - calls `Into::into()` on the field to cast it to the specified type.
-/// - `#[get(ref_into(<type>))]` - calls `Into::into()` on a reference to the field to cast it to the specified type.
-/// - `#[get(as_ref(<type>))]` - calls `AsRef::as_ref()` on the field to borrow the specified type from it.
-///
-///  ⚠️ All provided options are **mutually-exclusive**.
-///
-/// ## Generating helpers
-///
-/// You can have at most two additional helper structs for every model you generate:
-///
-/// - One for creating a new instance of the entity.
-/// - And the second one for hydrating an existing entity from a set of attributes.
-///
-/// ## New entity helper options
-///
-/// You can annotate fields on your model with `#[new(...)]` to change the generated helper.
-///
-/// Supported options:
-///
-/// - `#[new(skip)]` - skips the field.
-/// - `#[new(type(<type>))]` - uses a different type for this field in the helper struct.
-///
-/// ⚠️ All provided options are **mutually-exclusive**.
-///
-/// ## Hydration helper options
-///
-/// You can annotate fields on your model with `#[new(...)]` to change the generated helper.
-///
-/// Supported options:
-///
-/// - `#[hydrate(skip)]` - skips the field.
-/// - `#[hydrate(type(<type>))]` - uses a different type for this field in the helper struct.
-///
-/// ⚠️ All provided options are **mutually-exclusive**.
-///
-/// ## Using custom attributes
-///
-/// This macro supports forwarding any custom attributes using a special attribute `#[fw(...)]`.
-///
-/// The wrapping is needed to simplify parsing of attributes provided by this macro.
-///
-/// Example:
-///
-/// ```
-/// # use identify_macros::gen_model;
-/// gen_model! {
-///     pub struct Model {
-///         #[fw(allow(dead_code))]
-///         field: String,
-///     }
-/// }
-/// ```
-#[macro_export]
-macro_rules! gen_model {
-    ($($input:tt)*) => {
-        $crate::gen_model_helper!($($input)*);
+//! Parsing and expansion of [gen_model!](crate::gen_model).
+
+use proc_macro2::{Span, TokenStream};
+use quote::{ToTokens, format_ident, quote};
+use syn::{
+    Attribute, Error, Ident, LitStr, Meta, Result, Token, Type, Visibility,
+    braced, bracketed,
+    ext::IdentExt,
+    parse::{Parse, ParseStream},
+    spanned::Spanned,
+    token,
+};
+
+use crate::{builder, enumeration::EnumModel, id::IdOption};
+
+/// Input of [gen_model!](crate::gen_model): a model followed by at most two helper structs.
+pub(crate) struct ModelInput {
+    pub model: Model,
+    pub helpers: Vec<Helper>,
+}
+
+/// Input of [gen_model!](crate::gen_model), either a model struct with its helpers or an enum.
+pub(crate) enum GenModelInput {
+    Struct(ModelInput),
+    Enum(EnumModel),
+}
+
+impl Parse for GenModelInput {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let fork = input.fork();
+        fork.call(Attribute::parse_outer)?;
+        fork.parse::<Visibility>()?;
+
+        if fork.peek(Token![enum]) {
+            input.parse().map(GenModelInput::Enum)
+        } else {
+            input.parse().map(GenModelInput::Struct)
+        }
+    }
+}
+
+impl Parse for ModelInput {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let model = Model::parse(input, false)?;
+        let helpers = Helper::parse_all(input, &model)?;
+
+        Ok(ModelInput { model, helpers })
     }
 }
 
-#[doc(hidden)]
-#[macro_export(local_inner_macros)]
-macro_rules! gen_model_helper {
-    // Main entrypoint.
-    (
-        $(#[$model_attrs:meta])*
-        $model_vis:vis struct $model_name:ident {
-            $(
-                // Doc comments.
-                $(#[doc = $($f_doc:tt)*])*
-                $(#[doc($($f_doc2:tt)*)])*
-
-                // Additional options for the getter that will be generated for this field.
-                $(#[get(
-                    $(skip$(($get_skip_marker:tt))?)?
-                    $(into($into_type:ty))?
-                    $(ref_into($ref_into_type:ty))?
-                    $(as_ref($as_ref_type:ty))?
-                )])?
-
-                // Additional options for the new entity creation helper struct field generated from this field.
-                $(#[new(
-                    $(skip$(($new_skip_marker:tt))?)?
-                    $(type($new_type:ty))?
-                )])?
-
-                // Additional options for the hydration helper struct field generated from this field.
-                $(#[hydrate(
-                    $(skip$(($hydrate_skip_marker:tt))?)?
-                    $(type($hydrate_type:ty))?
-                )])?
-
-                // A forwarding wrapper for any additional attributes the field needs.
-                $(#[fw($($f_forwarded_attr:tt)*)])*
-                $f_vis:vis $f_name:ident: $f_type:ty,
-            )+
+/// The model struct.
+pub(crate) struct Model {
+    pub attrs: Vec<Attribute>,
+    pub options: ModelOptions,
+    pub vis: Visibility,
+    pub ident: Ident,
+    pub fields: Vec<ModelField>,
+}
+
+impl Model {
+    /// Parses the model. Fields may be followed by a byte conversion in brackets, and annotated
+    /// with `#[id(...)]`, only if `id` is set, which is how [gen_id!](crate::gen_id) models are
+    /// written.
+    pub fn parse(input: ParseStream, id: bool) -> Result<Self> {
+        let (options, attrs) =
+            ModelOptions::extract(input.call(Attribute::parse_outer)?)?;
+        let vis = input.parse()?;
+        input.parse::<Token![struct]>()?;
+        let ident: Ident = input.parse()?;
+
+        let content;
+        braced!(content in input);
+        let mut fields = Vec::new();
+        // Misused options are collected, so that all of them are reported at once, but a field
+        // that can't be parsed stops the parsing.
+        let mut errors = Errors::default();
+        while !content.is_empty() {
+            fields.push(ModelField::parse(&content, id, &mut errors)?);
+            if content.is_empty() {
+                break;
+            }
+            content.parse::<Token![,]>()?;
         }
+        for field in &fields {
+            if let Some(api) = &field.api
+                && !options.serde
+            {
+                errors.push(Error::new_spanned(
+                    &api.attr,
+                    "`#[api(...)]` requires `#[model(serde)]` on the model",
+                ));
+            }
 
-        // You can have at most two additional helper structs for every model you generate:
-        //  - One for creating a new instance of the entity.
-        //  - And the second one for hydrating an existing entity from a set of attributes.
-        $(
-            $(#[$helper_attrs:meta])*
-            $helper_vis:vis struct $helper_name:ident$(;)?
-            $(
-                {
-                    $(
-                        $(#[$helper_f_attrs:meta])*
-                        $helper_f_name:ident: $helper_f_type:ty,
-                    )*
-                }
-            )?
-        )*
-    ) => {
-        // Create the model itself.
-        $(#[$model_attrs])*
-        $model_vis struct $model_name {
-            // Use all attrs except the ones meant for this macro.
-            $(
-                $(#[doc = $($f_doc)*])*
-                $(#[doc($($f_doc2)*)])*
-                $(#[$($f_forwarded_attr)*])*
-                $f_vis $f_name: $f_type,
-            )+
+            let touched = field.set.as_ref().and_then(|set| set.touch.as_ref());
+            if let Some(touched) = touched
+                && !fields.iter().any(|field| field.ident == *touched)
+            {
+                errors.push(Error::new(
+                    touched.span(),
+                    format!("`{touched}` is not a field of `{ident}`"),
+                ));
+            }
         }
+        errors.finish()?;
 
-        // Generate getters.
-        gen_model_helper!(
-            @gen-getters
-            $model_vis,
-            $model_name,
-            $(
-                $(#[doc = $($f_doc)*])*
-                $(#[doc($($f_doc2)*)])*
-                $(#[get(
-                    $(skip$(($get_skip_marker))?)?
-                    $(into($into_type))?
-                    $(ref_into($ref_into_type))?
-                    $(as_ref($as_ref_type))?
-                )])?
-                $f_name: $f_type,
-            )*
-        );
-
-        // Generate helpers (if any).
-        gen_model_helper!(
-            @gen-helpers
-            $(
-                $(#[$helper_attrs])*
-                $helper_vis struct $helper_name
-                $(
-                    // Additional fields required by the corresponding helper struct
-                    {
-                        $(
-                            $(#[$helper_f_attrs])*
-                            $helper_f_name: $helper_f_type,
-                        )*
+        if fields.is_empty() {
+            return Err(Error::new(
+                ident.span(),
+                format!("`{ident}` must have at least one field"),
+            ));
+        }
+
+        Ok(Model {
+            attrs,
+            options,
+            vis,
+            ident,
+            fields,
+        })
+    }
+}
+
+/// Options of the code generated from the model, see `#[model(...)]`.
+#[derive(Default)]
+pub(crate) struct ModelOptions {
+    /// Generates a builder of the new entity helper, set to where it's requested.
+    pub builder: Option<Span>,
+    /// Derives `Serialize` and `Deserialize` on the helpers.
+    pub serde: bool,
+}
+
+impl ModelOptions {
+    /// Takes the options out of the attributes of the model, returning the other attributes.
+    fn extract(attrs: Vec<Attribute>) -> Result<(Self, Vec<Attribute>)> {
+        let mut options = ModelOptions::default();
+        let mut others = Vec::new();
+        let mut errors = Errors::default();
+
+        for attr in attrs {
+            if !attr.path().is_ident("model") {
+                others.push(attr);
+                continue;
+            }
+
+            let result = attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("builder") {
+                    if options.builder.replace(meta.path.span()).is_some() {
+                        return Err(meta.error("duplicate `builder` option"));
                     }
-                )?
-            )*
-            $(
-                $(#[doc = $($f_doc)*])*
-                $(#[doc($($f_doc2)*)])*
-                $(#[new($(skip$(($new_skip_marker))?)? $(type($new_type))?)])?
-                $(#[hydrate($(skip$(($hydrate_skip_marker))?)? $(type($hydrate_type))?)])?
-                $f_name: $f_type,
-            )*
-        );
-    };
+                    return Ok(());
+                }
+                if meta.path.is_ident("serde") {
+                    if std::mem::replace(&mut options.serde, true) {
+                        return Err(meta.error("duplicate `serde` option"));
+                    }
+                    return Ok(());
+                }
 
-    // Generate both helper structs.
-    (
-        @gen-helpers
+                Err(meta.error(
+                    "unknown `model` option, expected `builder` or `serde`",
+                ))
+            });
+            if let Err(e) = result {
+                errors.push(e);
+            }
+        }
+        errors.finish()?;
 
-        // New entity helper.
-        $(#[$new_h_attrs:meta])*
-        $new_h_vis:vis struct $new_h_name:ident
-        $(
-            {
-                $(
-                    $(#[$new_h_f_attrs:meta])*
-                    $new_h_f_name:ident: $new_h_f_type:ty,
-                )*
+        Ok((options, others))
+    }
+}
+
+/// A field of the model with the options of the code generated from it.
+pub(crate) struct ModelField {
+    /// Doc comments, which are also put on the getter and the fields of the helpers.
+    pub docs: Vec<Attribute>,
+    /// Attributes wrapped in `#[fw(...)]`, which are put on the field of the model only.
+    pub forwarded: Vec<TokenStream>,
+    pub get: GetOption,
+    pub new: HelperOption,
+    pub hydrate: HelperOption,
+    pub set: Option<SetOption>,
+    pub api: Option<ApiOption>,
+    pub vis: Visibility,
+    pub ident: Ident,
+    pub ty: Type,
+    /// Function turning the field into bytes, used by [gen_id!](crate::gen_id).
+    pub bytes: Option<TokenStream>,
+    /// How the field is hashed by [gen_id!](crate::gen_id), see `#[id(...)]`.
+    pub id: IdOption,
+}
+
+impl ModelField {
+    /// Parses a field, pushing the errors of its options to `errors`.
+    fn parse(
+        input: ParseStream,
+        id: bool,
+        errors: &mut Errors,
+    ) -> Result<Self> {
+        let attrs = input.call(Attribute::parse_outer)?;
+        let vis = input.parse()?;
+        let ident = input.parse()?;
+        input.parse::<Token![:]>()?;
+        let ty = input.parse()?;
+
+        let bytes = if input.peek(token::Bracket) {
+            let content;
+            let brackets = bracketed!(content in input);
+            if !id {
+                errors.push(Error::new(
+                    brackets.span.join(),
+                    "byte conversions are only supported by `gen_id!`",
+                ));
+            }
+            Some(content.parse()?)
+        } else {
+            None
+        };
+
+        let mut field = ModelField {
+            docs: Vec::new(),
+            forwarded: Vec::new(),
+            get: GetOption::Ref,
+            new: HelperOption::Keep,
+            hydrate: HelperOption::Keep,
+            set: None,
+            api: None,
+            vis,
+            ident,
+            ty,
+            bytes,
+            id: IdOption::default(),
+        };
+
+        let (mut get, mut new, mut hydrate, mut set, mut api, mut id_option) =
+            (false, false, false, false, false, false);
+        for attr in attrs {
+            let Some(name) = attr.path().get_ident().map(Ident::to_string)
+            else {
+                errors.push(unsupported_attribute(&attr));
+                continue;
+            };
+
+            let result = match name.as_str() {
+                "doc" => {
+                    field.docs.push(attr);
+                    Ok(())
+                }
+                "fw" => attr
+                    .parse_args::<TokenStream>()
+                    .map(|forwarded| field.forwarded.push(forwarded)),
+                "get" => once(&mut get, &attr).and_then(|()| {
+                    field.get = attr.parse_args()?;
+                    Ok(())
+                }),
+                "new" => once(&mut new, &attr).and_then(|()| {
+                    field.new = attr.parse_args()?;
+                    Ok(())
+                }),
+                "hydrate" => once(&mut hydrate, &attr).and_then(|()| {
+                    field.hydrate = attr.parse_args()?;
+                    Ok(())
+                }),
+                "api" => once(&mut api, &attr).and_then(|()| {
+                    field.api = Some(ApiOption::parse(&attr)?);
+                    Ok(())
+                }),
+                "id" if id => once(&mut id_option, &attr).and_then(|()| {
+                    field.id = IdOption::parse(&attr)?;
+                    Ok(())
+                }),
+                "set" => once(&mut set, &attr).and_then(|()| {
+                    field.set = Some(SetOption::parse(&attr)?);
+                    Ok(())
+                }),
+                _ => Err(unsupported_attribute(&attr)),
+            };
+            if let Err(e) = result {
+                errors.push(e);
             }
-        )?
+        }
 
-        // Hydration helper.
-        $(#[$hydrate_h_attrs:meta])*
-        $hydrate_h_vis:vis struct $hydrate_h_name:ident
-        $(
-            {
-                $(
-                    $(#[$hydrate_h_f_attrs:meta])*
-                    $hydrate_h_f_name:ident: $hydrate_h_f_type:ty,
-                )*
+        Ok(field)
+    }
+}
+
+/// How the getter of a field is generated, see `#[get(...)]`.
+pub(crate) enum GetOption {
+    /// Returns a reference to the field.
+    Ref,
+    Skip,
+    /// Returns a copy of the field, for `Copy` types such as enums.
+    Copy,
+    Into(Type),
+    RefInto(Type),
+    AsRef(Type),
+}
+
+impl Parse for GetOption {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let name = Ident::parse_any(input)?;
+        let option = match name.to_string().as_str() {
+            "skip" => GetOption::Skip,
+            "copy" => GetOption::Copy,
+            "into" => GetOption::Into(parenthesized_type(input)?),
+            "ref_into" => GetOption::RefInto(parenthesized_type(input)?),
+            "as_ref" => GetOption::AsRef(parenthesized_type(input)?),
+            _ => {
+                return Err(Error::new(
+                    name.span(),
+                    format!(
+                        "unknown `get` option `{name}`, expected one of `skip`, `copy`, `into(<type>)`, \
+                         `ref_into(<type>)` or `as_ref(<type>)`"
+                    ),
+                ));
             }
-        )?
-
-        // Model fields.
-        $(
-            $(#[doc = $($f_doc:tt)*])*
-            $(#[doc($($f_doc2:tt)*)])*
-            $(#[new($(skip$(($new_skip_marker:ident))?)? $(type($new_type:ty))?)])?
-            $(#[hydrate($(skip$(($hydrate_skip_marker:ident))?)? $(type($hydrate_type:ty))?)])?
-            $f_vis:vis $f_name:ident: $f_type:ty,
-        )*
-    ) => {
-        // Generate new entity helper.
-        gen_model_helper!(
-            @gen-new-helper
-            $(#[$new_h_attrs])*
-            $new_h_vis struct $new_h_name
-            [
-                $(
-                    $(
-                        $(#[$new_h_f_attrs])*
-                        pub $new_h_f_name: $new_h_f_type,
-                    )*
-                )?
-            ]
-            $(
-                $(#[doc = $($f_doc)*])*
-                $(#[doc($($f_doc2)*)])*
-                $(#[new($(skip$(($new_skip_marker))?)? $(type($new_type))?)])?
-                $f_name: $f_type,
-            )*
-        );
-
-        // Generate hydration helper.
-        gen_model_helper!(
-            @gen-hydrate-helper
-            $(#[$hydrate_h_attrs])*
-            $hydrate_h_vis struct $hydrate_h_name
-            [
-                $(
-                    $(
-                        $(#[$hydrate_h_f_attrs])*
-                        pub $hydrate_h_f_name: $hydrate_h_f_type,
-                    )*
-                )?
-            ]
-            $(
-                $(#[doc = $($f_doc)*])*
-                $(#[doc($($f_doc2)*)])*
-                $(#[hydrate($(skip$(($hydrate_skip_marker))?)? $(type($hydrate_type))?)])?
-                $f_name: $f_type,
-            )*
-        );
-    };
+        };
+        only_option(input, "get")?;
 
-    // Generate only the new entity helper struct.
-    (
-        @gen-helpers
+        Ok(option)
+    }
+}
 
-        // New entity helper.
-        $(#[$new_h_attrs:meta])*
-        $new_h_vis:vis struct $new_h_name:ident
-        $(
-            {
-                $(
-                    $(#[$new_h_f_attrs:meta])*
-                    $new_h_f_name:ident: $new_h_f_type:ty,
-                )*
+/// How a field of the model shows up in a helper struct, see `#[new(...)]` and
+/// `#[hydrate(...)]`.
+pub(crate) enum HelperOption {
+    /// The helper has the field with the type of the model.
+    Keep,
+    Skip,
+    Type(Box<Type>),
+}
+
+impl Parse for HelperOption {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let name = Ident::parse_any(input)?;
+        let option = match name.to_string().as_str() {
+            "skip" => HelperOption::Skip,
+            "type" => HelperOption::Type(Box::new(parenthesized_type(input)?)),
+            _ => {
+                return Err(Error::new(
+                    name.span(),
+                    format!(
+                        "unknown helper option `{name}`, expected `skip` or `type(<type>)`"
+                    ),
+                ));
             }
-        )?
-
-        // Model fields.
-        $(
-            $(#[doc = $($f_doc:tt)*])*
-            $(#[doc($($f_doc2:tt)*)])*
-            $(#[new($(skip$(($new_skip_marker:ident))?)? $(type($new_type:ty))?)])?
-            $(#[hydrate$($_:tt)*])?
-            $f_vis:vis $f_name:ident: $f_type:ty,
-        )*
-    ) => {
-        gen_model_helper!(
-            @gen-new-helper
-            $(#[$new_h_attrs])*
-            $new_h_vis struct $new_h_name
-            [
-                $(
-                    $(
-                        $(#[$new_h_f_attrs])*
-                        pub $new_h_f_name: $new_h_f_type,
-                    )*
-                )?
-            ]
-            $(
-                $(#[doc = $($f_doc)*])*
-                $(#[doc($($f_doc2)*)])*
-                $(#[new($(skip$(($new_skip_marker))?)? $(type($new_type))?)])?
-                $f_name: $f_type,
-            )*
-        );
-    };
+        };
+        only_option(input, "helper")?;
 
-    // Fallback case when no helpers are required.
-    (@gen-helpers $($_:tt)*) => {};
-
-    // Generate an ordinary field for the new entity helper struct.
-    (
-        @gen-new-helper
-        $(#[$attr:meta])*
-        $vis:vis struct $name:ident
-        [$($processed:tt)*]
-
-        $(#[doc = $($f_doc:tt)*])*
-        $(#[doc($($f_doc2:tt)*)])*
-        $f_name:ident: $f_type:ty,
-
-        $($rest:tt)*
-    ) => {
-        gen_model_helper!(
-            @gen-new-helper
-            $(#[$attr])*
-            $vis struct $name
-            [
-                $($processed)*
-
-                $(#[doc = $($f_doc)*])*
-                $(#[doc($($f_doc2)*)])*
-                pub $f_name: $f_type,
-            ]
-            $($rest)*
-        );
-    };
+        Ok(option)
+    }
+}
 
-    // Skip a field for the new entity helper struct.
-    (
-        @gen-new-helper
-        $(#[$attr:meta])*
-        $vis:vis struct $name:ident
-        [$($processed:tt)*]
-
-        $(#[doc = $($f_doc:tt)*])*
-        $(#[doc($($f_doc2:tt)*)])*
-        #[new(skip$([$_:tt])?)]
-        $f_name:ident: $f_type:ty,
-
-        $($rest:tt)*
-    ) => {
-        gen_model_helper!(
-            @gen-new-helper
-            $(#[$attr])*
-            $vis struct $name
-            [$($processed)*]
-            $($rest)*
-        );
-    };
+/// How the setter of a field is generated, see `#[set(...)]`.
+#[derive(Default)]
+pub(crate) struct SetOption {
+    /// Field set to the current time whenever the value changes.
+    pub touch: Option<Ident>,
+}
 
-    // Use a custom type for a field in the new entity helper struct.
-    (
-        @gen-new-helper
-        $(#[$attr:meta])*
-        $vis:vis struct $name:ident
-        [$($processed:tt)*]
-
-        $(#[doc = $($f_doc:tt)*])*
-        $(#[doc($($f_doc2:tt)*)])*
-        #[new(type($type:ty))]
-        $f_name:ident: $f_type:ty,
-
-        $($rest:tt)*
-    ) => {
-        gen_model_helper!(
-            @gen-new-helper
-            $(#[$attr])*
-            $vis struct $name
-            [
-                $($processed)*
-
-                $(#[doc = $($f_doc)*])*
-                $(#[doc($($f_doc2)*)])*
-                pub $f_name: $type,
-            ]
-            $($rest)*
-        );
-    };
+impl SetOption {
+    fn parse(attr: &Attribute) -> Result<Self> {
+        let mut option = SetOption::default();
+        if let Meta::Path(_) = attr.meta {
+            return Ok(option);
+        }
+
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("touch") {
+                let content;
+                syn::parenthesized!(content in meta.input);
+                if option.touch.replace(content.parse()?).is_some() {
+                    return Err(meta.error("duplicate `touch` option"));
+                }
+                return Ok(());
+            }
+
+            Err(meta.error("unknown `set` option, expected `touch(<field>)`"))
+        })?;
+
+        Ok(option)
+    }
+}
+
+/// Serialization of a field in the helpers, see `#[api(...)]`.
+pub(crate) struct ApiOption {
+    attr: Attribute,
+    /// Arguments of the `#[serde(...)]` attribute the option stands for.
+    pub serde: Vec<TokenStream>,
+}
 
-    // Generate the new entity helper struct from all the processed fields.
-    (
-        @gen-new-helper
-        $(#[$attr:meta])*
-        $vis:vis struct $name:ident
-        [$($processed:tt)*]
-    ) => {
-        $(#[$attr])*
-        $vis struct $name {
-            $($processed)*
+impl ApiOption {
+    fn parse(attr: &Attribute) -> Result<Self> {
+        let mut serde = Vec::new();
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") {
+                let name: LitStr = meta.value()?.parse()?;
+                serde.push(quote!(rename = #name));
+                return Ok(());
+            }
+            if meta.path.is_ident("skip") {
+                serde.push(quote!(skip));
+                return Ok(());
+            }
+            if meta.path.is_ident("default") {
+                serde.push(quote!(default));
+                return Ok(());
+            }
+
+            Err(meta.error(
+                "unknown `api` option, expected `rename = \"<name>\"`, `skip` or `default`",
+            ))
+        })?;
+
+        Ok(ApiOption {
+            attr: attr.clone(),
+            serde,
+        })
+    }
+}
+
+/// A helper struct generated from the model, with fields of its own.
+pub(crate) struct Helper {
+    pub attrs: Vec<Attribute>,
+    pub vis: Visibility,
+    pub ident: Ident,
+    pub fields: Vec<HelperField>,
+}
+
+impl Helper {
+    /// Parses the helpers that follow the model: the new entity helper, and the hydration
+    /// helper.
+    pub fn parse_all(input: ParseStream, model: &Model) -> Result<Vec<Self>> {
+        let mut helpers: Vec<Helper> = Vec::new();
+        while !input.is_empty() {
+            let helper: Helper = input.parse()?;
+            if helpers.len() == 2 {
+                return Err(Error::new(
+                    helper.ident.span(),
+                    "at most two helpers can be generated: the new entity helper and the \
+                     hydration helper",
+                ));
+            }
+            helpers.push(helper);
         }
-    };
 
-    // Generate an ordinary field for the hydration helper struct.
-    (
-        @gen-hydrate-helper
-        $(#[$attr:meta])*
-        $vis:vis struct $name:ident
-        [$($processed:tt)*]
-
-        $(#[doc = $($f_doc:tt)*])*
-        $(#[doc($($f_doc2:tt)*)])*
-        $f_name:ident: $f_type:ty,
-
-        $($rest:tt)*
-    ) => {
-        gen_model_helper!(
-            @gen-hydrate-helper
-            $(#[$attr])*
-            $vis struct $name
-            [
-                $($processed)*
-
-                $(#[doc = $($f_doc)*])*
-                $(#[doc($($f_doc2)*)])*
-                pub $f_name: $f_type,
-            ]
-            $($rest)*
-        );
-    };
+        if let Some(span) = model.options.builder
+            && helpers.is_empty()
+        {
+            return Err(Error::new(
+                span,
+                "`builder` requires the new entity helper to build",
+            ));
+        }
 
-    // Skip a field for the hydration helper struct.
-    (
-        @gen-hydrate-helper
-        $(#[$attr:meta])*
-        $vis:vis struct $name:ident
-        [$($processed:tt)*]
-
-        $(#[doc = $($f_doc:tt)*])*
-        $(#[doc($($f_doc2:tt)*)])*
-        #[hydrate(skip$([$_:tt])?)]
-        $f_name:ident: $f_type:ty,
-
-        $($rest:tt)*
-    ) => {
-        gen_model_helper!(
-            @gen-hydrate-helper
-            $(#[$attr])*
-            $vis struct $name
-            [$($processed)*]
-            $($rest)*
-        );
-    };
+        Ok(helpers)
+    }
+}
 
-    // Use a custom type for a field in the hydration helper struct.
-    (
-        @gen-hydrate-helper
-        $(#[$attr:meta])*
-        $vis:vis struct $name:ident
-        [$($processed:tt)*]
-
-        $(#[doc = $($f_doc:tt)*])*
-        $(#[doc($($f_doc2:tt)*)])*
-        #[hydrate(type($type:ty))]
-        $f_name:ident: $f_type:ty,
-
-        $($rest:tt)*
-    ) => {
-        gen_model_helper!(
-            @gen-hydrate-helper
-            $(#[$attr])*
-            $vis struct $name
-            [
-                $($processed)*
-
-                $(#[doc = $($f_doc)*])*
-                $(#[doc($($f_doc2)*)])*
-                pub $f_name: $type,
-            ]
-            $($rest)*
-        );
-    };
+impl Parse for Helper {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let attrs = input.call(Attribute::parse_outer)?;
+        let vis = input.parse()?;
+        input.parse::<Token![struct]>()?;
+        let ident = input.parse()?;
+
+        let mut fields = Vec::new();
+        if input.peek(token::Brace) {
+            let content;
+            braced!(content in input);
+            while !content.is_empty() {
+                fields.push(content.parse()?);
+                if content.is_empty() {
+                    break;
+                }
+                content.parse::<Token![,]>()?;
+            }
+        } else if input.peek(Token![;]) {
+            input.parse::<Token![;]>()?;
+        }
 
-    // Generate the hydration helper struct from all the processed fields.
-    (
-        @gen-hydrate-helper
-        $(#[$attr:meta])*
-        $vis:vis struct $name:ident
-        [$($processed:tt)*]
-    ) => {
-        $(#[$attr])*
-        $vis struct $name {
-            $($processed)*
+        Ok(Helper {
+            attrs,
+            vis,
+            ident,
+            fields,
+        })
+    }
+}
+
+/// A field of a helper struct that the model doesn't have.
+pub(crate) struct HelperField {
+    pub attrs: Vec<Attribute>,
+    pub ident: Ident,
+    pub ty: Type,
+}
+
+impl Parse for HelperField {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let attrs = input.call(Attribute::parse_outer)?;
+        let vis: Visibility = input.parse()?;
+        if !matches!(vis, Visibility::Inherited) {
+            return Err(Error::new_spanned(
+                vis,
+                "fields of helpers are always public, remove the visibility",
+            ));
         }
-    };
+        let ident = input.parse()?;
+        input.parse::<Token![:]>()?;
+        let ty = input.parse()?;
 
-    // Entrypoint for generating the getters.
-    (
-        @gen-getters
-        $vis:vis,
-        $name:ident,
-        $($fields:tt)*
-    ) => {
-        impl $name {
-            gen_model_helper!(@gen-getter $vis, $($fields)*);
+        Ok(HelperField { attrs, ident, ty })
+    }
+}
+
+/// Generates the model, its getters and its helpers.
+pub(crate) fn expand(input: &ModelInput) -> TokenStream {
+    let ModelInput { model, helpers } = input;
+    let Model {
+        attrs,
+        options,
+        vis,
+        ident,
+        fields,
+    } = model;
+
+    let model_fields = fields.iter().map(|field| {
+        let ModelField {
+            docs,
+            forwarded,
+            vis,
+            ident,
+            ty,
+            ..
+        } = field;
+
+        quote! {
+            #(#docs)*
+            #(#[#forwarded])*
+            #vis #ident: #ty
         }
-    };
+    });
+    let getters = fields.iter().filter_map(|field| getter(vis, field));
+    let setters = fields.iter().filter_map(|field| setter(vis, field));
+
+    let new_helper = helpers.first().map(|helper| {
+        let fields = helper_fields(helper, fields, |field| &field.new);
+        let builder = options.builder.map(|_| builder::expand(helper, &fields));
+        let helper = expand_helper(helper, &fields, options.serde);
+
+        quote!(#helper #builder)
+    });
+    let hydrate_helper = helpers.get(1).map(|helper| {
+        let fields = helper_fields(helper, fields, |field| &field.hydrate);
+
+        expand_helper(helper, &fields, options.serde)
+    });
+
+    quote! {
+        #(#attrs)*
+        #vis struct #ident {
+            #(#model_fields,)*
+        }
+
+        impl #ident {
+            #(#getters)*
 
-    // Generate an ordinary getter.
-    (
-        @gen-getter
-        $vis:vis,
-        $(#[doc = $($f_doc:tt)*])*
-        $(#[doc($($f_doc2:tt)*)])*
-        $f_name:ident: $f_type:ty,
-        $($rest:tt)*
-    ) => {
-        $(#[doc = $($f_doc)*])*
-        $(#[doc($($f_doc2)*)])*
-        $vis fn $f_name(&self) -> &$f_type {
-            &self.$f_name
+            #(#setters)*
         }
 
-        gen_model_helper!(@gen-getter $vis, $($rest)*);
-    };
+        #new_helper
+        #hydrate_helper
+    }
+}
 
-    // Skip a field when generating getters.
-    (
-        @gen-getter
-        $vis:vis,
-        $(#[doc = $($f_doc:tt)*])*
-        $(#[doc($($f_doc2:tt)*)])*
-        #[get(skip)]
-        $f_name:ident: $f_type:ty,
-        $($rest:tt)*
-    ) => {
-        gen_model_helper!(@gen-getter $vis, $($rest)*);
+fn getter(vis: &Visibility, field: &ModelField) -> Option<TokenStream> {
+    let ModelField {
+        docs, ident, ty, ..
+    } = field;
+
+    let (ty, body) = match &field.get {
+        GetOption::Ref => (quote!(&#ty), quote!(&self.#ident)),
+        GetOption::Skip => return None,
+        GetOption::Copy => (ty.to_token_stream(), quote!(self.#ident)),
+        GetOption::Into(into) => {
+            (into.to_token_stream(), quote!(self.#ident.into()))
+        }
+        GetOption::RefInto(into) => {
+            (into.to_token_stream(), quote!((&self.#ident).into()))
+        }
+        GetOption::AsRef(as_ref) => {
+            (as_ref.to_token_stream(), quote!(self.#ident.as_ref()))
+        }
     };
 
-    // Call `Into::into()` on the field in the getter.
-    (
-        @gen-getter
-        $vis:vis,
-        $(#[doc = $($f_doc:tt)*])*
-        $(#[doc($($f_doc2:tt)*)])*
-        #[get(into($into_ty:ty))]
-        $f_name:ident: $f_type:ty,
-        $($rest:tt)*
-    ) => {
-        $(#[doc = $($f_doc)*])*
-        $(#[doc($($f_doc2)*)])*
-        $vis fn $f_name(&self) -> $into_ty {
-            self.$f_name.into()
+    Some(quote! {
+        #(#docs)*
+        #vis fn #ident(&self) -> #ty {
+            #body
         }
+    })
+}
 
-        gen_model_helper!(@gen-getter $vis, $($rest)*);
+fn setter(vis: &Visibility, field: &ModelField) -> Option<TokenStream> {
+    let ModelField { ident, ty, .. } = field;
+    let SetOption { touch } = field.set.as_ref()?;
+
+    let name = format_ident!("set_{}", ident.unraw());
+    let (summary, touch) = match touch {
+        Some(touched) => (
+            format!("Changes `{ident}` and touches `{touched}`."),
+            Some(quote!(self.#touched = ::chrono::Utc::now();)),
+        ),
+        None => (format!("Changes `{ident}`."), None),
     };
+    let doc = format!(
+        "{summary}\n\nReturns `false` if the value is the same as before and nothing has changed."
+    );
+
+    Some(quote! {
+        #[doc = #doc]
+        #vis fn #name(&mut self, #ident: #ty) -> bool {
+            if self.#ident == #ident {
+                return false;
+            }
+
+            self.#ident = #ident;
+            #touch
 
-    // Call `Into::into()` on a reference to a field in the getter.
-    (
-        @gen-getter
-        $vis:vis,
-        $(#[doc = $($f_doc:tt)*])*
-        $(#[doc($($f_doc2:tt)*)])*
-        #[get(ref_into($into_ty:ty))]
-        $f_name:ident: $f_type:ty,
-        $($rest:tt)*
-    ) => {
-        $(#[doc = $($f_doc)*])*
-        $(#[doc($($f_doc2)*)])*
-        $vis fn $f_name(&self) -> $into_ty {
-            (&self.$f_name).into()
+            true
         }
+    })
+}
 
-        gen_model_helper!(@gen-getter $vis, $($rest)*);
-    };
+/// A field of a generated helper struct.
+pub(crate) struct HelperStructField<'a> {
+    pub attrs: &'a [Attribute],
+    pub ident: &'a Ident,
+    pub ty: &'a Type,
+    /// Arguments of the `#[serde(...)]` attribute of the field, if the helper derives serde.
+    pub serde: &'a [TokenStream],
+}
+
+/// Returns the fields of a helper: its own fields followed by the fields of the model it keeps.
+fn helper_fields<'a>(
+    helper: &'a Helper,
+    fields: &'a [ModelField],
+    option: impl Fn(&ModelField) -> &HelperOption,
+) -> Vec<HelperStructField<'a>> {
+    let own_fields = helper.fields.iter().map(|field| HelperStructField {
+        attrs: &field.attrs,
+        ident: &field.ident,
+        ty: &field.ty,
+        serde: &[],
+    });
+    let model_fields = fields.iter().filter_map(|field| {
+        let ty = match option(field) {
+            HelperOption::Keep => &field.ty,
+            HelperOption::Skip => return None,
+            HelperOption::Type(ty) => &**ty,
+        };
+
+        Some(HelperStructField {
+            attrs: &field.docs,
+            ident: &field.ident,
+            ty,
+            serde: field.api.as_ref().map_or(&[], |api| &api.serde),
+        })
+    });
+
+    own_fields.chain(model_fields).collect()
+}
 
-    // Call `AsRef::as_ref()` on a field in the getter.
-    (
-        @gen-getter
-        $vis:vis,
-        $(#[doc = $($f_doc:tt)*])*
-        $(#[doc($($f_doc2:tt)*)])*
-        #[get(as_ref($as_ref_ty:ty))]
-        $f_name:ident: $f_type:ty,
-        $($rest:tt)*
-    ) => {
-        $(#[doc = $($f_doc)*])*
-        $(#[doc($($f_doc2)*)])*
-        $vis fn $f_name(&self) -> $as_ref_ty {
-            self.$f_name.as_ref()
+/// Generates a helper struct with the given fields, deriving serde if `serde` is set.
+fn expand_helper(
+    helper: &Helper,
+    fields: &[HelperStructField],
+    serde: bool,
+) -> TokenStream {
+    let Helper {
+        attrs, vis, ident, ..
+    } = helper;
+    let derive_serde = serde
+        .then(|| quote!(#[derive(::serde::Serialize, ::serde::Deserialize)]));
+    let fields = fields.iter().map(|field| {
+        let HelperStructField {
+            attrs,
+            ident,
+            ty,
+            serde,
+        } = field;
+        let serde = (!serde.is_empty()).then(|| quote!(#[serde(#(#serde),*)]));
+
+        quote! {
+            #(#attrs)*
+            #serde
+            pub #ident: #ty
         }
+    });
 
-        gen_model_helper!(@gen-getter $vis, $($rest)*);
+    quote! {
+        #(#attrs)*
+        #derive_serde
+        #vis struct #ident {
+            #(#fields,)*
+        }
+    }
+}
+
+fn parenthesized_type(input: ParseStream) -> Result<Type> {
+    let content;
+    syn::parenthesized!(content in input);
+
+    content.parse()
+}
+
+/// Fails if more options follow the first one.
+fn only_option(input: ParseStream, attribute: &str) -> Result<()> {
+    if input.is_empty() {
+        return Ok(());
+    }
+
+    Err(input.error(format!(
+        "options of `{attribute}` are mutually exclusive, use only one of them"
+    )))
+}
+
+/// Fails if the attribute was already used on the field.
+fn once(seen: &mut bool, attr: &Attribute) -> Result<()> {
+    if std::mem::replace(seen, true) {
+        let name = attr.path().to_token_stream();
+
+        return Err(Error::new_spanned(
+            attr,
+            format!("duplicate `#[{name}(...)]` attribute"),
+        ));
+    }
+
+    Ok(())
+}
+
+fn unsupported_attribute(attr: &Attribute) -> Error {
+    let name = match &attr.meta {
+        Meta::Path(path) => path.to_token_stream(),
+        Meta::List(list) => list.path.to_token_stream(),
+        Meta::NameValue(name_value) => name_value.path.to_token_stream(),
     };
 
-    // Fallback case when all getters have been generated already.
-    (@gen-getter $vis:vis$(,)?) => {};
+    Error::new_spanned(
+        attr,
+        format!(
+            "unsupported attribute `{name}` on a model field, only `doc`, `get`, `new`, \
+             `hydrate`, `set`, `api` and `fw` are allowed, and `id` in `gen_id!`; wrap other \
+             attributes in `#[fw(...)]` to put them on the field of the model"
+        ),
+    )
+}
+
+/// Errors collected before failing, so that all of them are reported at once.
+#[derive(Default)]
+pub(crate) struct Errors(Option<Error>);
+
+impl Errors {
+    pub fn push(&mut self, e: Error) {
+        match &mut self.0 {
+            Some(errors) => errors.combine(e),
+            None => self.0 = Some(e),
+        }
+    }
+
+    pub fn finish(self) -> Result<()> {
+        self.0.map_or(Ok(()), Err)
+    }
 }
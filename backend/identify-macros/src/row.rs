@@ -0,0 +1,194 @@
+//! Parsing and expansion of [ModelRow](crate::ModelRow).
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{
+    Data, DeriveInput, Error, Fields, Ident, Path, Result, spanned::Spanned,
+};
+
+use crate::model::Errors;
+
+/// A row struct mapped to a model, see `#[row(...)]`.
+pub(crate) struct Row {
+    ident: Ident,
+    model: Path,
+    attrs: Path,
+    error: Path,
+    fields: Vec<RowField>,
+}
+
+impl Row {
+    pub fn parse(input: DeriveInput) -> Result<Self> {
+        let mut model: Option<Path> = None;
+        let mut attrs = None;
+        let mut error = None;
+        let mut errors = Errors::default();
+
+        for attr in input
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("row"))
+        {
+            let result = attr.parse_nested_meta(|meta| {
+                let target = if meta.path.is_ident("model") {
+                    &mut model
+                } else if meta.path.is_ident("attrs") {
+                    &mut attrs
+                } else if meta.path.is_ident("error") {
+                    &mut error
+                } else {
+                    return Err(meta.error(
+                        "unknown `row` option, expected `model = <type>`, `attrs = <type>` or \
+                         `error = <type>`",
+                    ));
+                };
+                if target.is_some() {
+                    return Err(meta.error("duplicate `row` option"));
+                }
+                *target = Some(meta.value()?.parse()?);
+
+                Ok(())
+            });
+            if let Err(e) = result {
+                errors.push(e);
+            }
+        }
+
+        let Data::Struct(data) = input.data else {
+            return Err(Error::new(
+                input.ident.span(),
+                "rows can only be structs",
+            ));
+        };
+        let Fields::Named(named) = data.fields else {
+            return Err(Error::new(
+                data.fields.span(),
+                "rows must have named fields",
+            ));
+        };
+        let mut fields = Vec::new();
+        for field in named.named {
+            match RowField::parse(field) {
+                Ok(field) => fields.push(field),
+                Err(e) => errors.push(e),
+            }
+        }
+        errors.finish()?;
+
+        let (Some(model), Some(error)) = (model, error) else {
+            return Err(Error::new(
+                input.ident.span(),
+                "rows must be annotated with `#[row(model = <type>, error = <type>)]`",
+            ));
+        };
+        // Hydration helpers are named after their models by convention.
+        let attrs = attrs.unwrap_or_else(|| {
+            let mut attrs = model.clone();
+            if let Some(last) = attrs.segments.last_mut() {
+                last.ident = format_ident!("{}Attrs", last.ident);
+            }
+            attrs
+        });
+
+        Ok(Row {
+            ident: input.ident,
+            model,
+            attrs,
+            error,
+            fields,
+        })
+    }
+}
+
+struct RowField {
+    ident: Ident,
+    /// Field of the hydration helper the column maps to.
+    field: Ident,
+    /// Whether the column stores the field as text, with `Display` and `FromStr`.
+    text: bool,
+}
+
+impl RowField {
+    fn parse(field: syn::Field) -> Result<Self> {
+        let ident = field.ident.expect("fields are named");
+
+        let mut mapped = None;
+        let mut text = false;
+        for attr in field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("row"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("field") {
+                    mapped = Some(meta.value()?.parse()?);
+                    return Ok(());
+                }
+                if meta.path.is_ident("text") {
+                    text = true;
+                    return Ok(());
+                }
+
+                Err(meta.error(
+                    "unknown `row` option of a field, expected `field = <field>` or `text`",
+                ))
+            })?;
+        }
+
+        Ok(RowField {
+            field: mapped.unwrap_or_else(|| ident.clone()),
+            ident,
+            text,
+        })
+    }
+}
+
+/// Generates the conversions between the row and its model.
+pub(crate) fn expand(row: &Row) -> TokenStream {
+    let Row {
+        ident,
+        model,
+        attrs,
+        error,
+        fields,
+    } = row;
+
+    let to_row = fields.iter().map(|field| {
+        let RowField { ident, field, text } = field;
+        if *text {
+            quote!(#ident: ::std::string::ToString::to_string(&attrs.#field))
+        } else {
+            quote!(#ident: attrs.#field)
+        }
+    });
+    let from_row = fields.iter().map(|field| {
+        let RowField { ident, field, text } = field;
+        if *text {
+            quote!(#field: value.#ident.parse()?)
+        } else {
+            quote!(#field: value.#ident)
+        }
+    });
+
+    quote! {
+        impl From<&#model> for #ident {
+            fn from(value: &#model) -> Self {
+                let attrs = value.to_attributes();
+
+                #ident {
+                    #(#to_row,)*
+                }
+            }
+        }
+
+        impl TryFrom<#ident> for #model {
+            type Error = #error;
+
+            fn try_from(value: #ident) -> ::core::result::Result<Self, Self::Error> {
+                #model::load(#attrs {
+                    #(#from_row,)*
+                })
+            }
+        }
+    }
+}
@@ -0,0 +1,5 @@
+#[test]
+fn misused_macros_fail_with_precise_errors() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/ui/*.rs");
+}
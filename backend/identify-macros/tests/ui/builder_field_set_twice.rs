@@ -0,0 +1,17 @@
+use identify_macros::gen_model;
+
+gen_model! {
+    #[model(builder)]
+    pub struct Model {
+        first_name: String,
+    }
+
+    pub struct NewModelAttrs;
+}
+
+fn main() {
+    NewModelAttrs::builder()
+        .first_name("Jane".to_owned())
+        .first_name("Joan".to_owned())
+        .build();
+}
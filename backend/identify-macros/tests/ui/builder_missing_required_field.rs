@@ -0,0 +1,20 @@
+use identify_macros::gen_model;
+
+gen_model! {
+    #[model(builder)]
+    pub struct Model {
+        first_name: String,
+        last_name: Option<String>,
+    }
+
+    pub struct NewModelAttrs {
+        email: String,
+    }
+}
+
+fn main() {
+    NewModelAttrs::builder()
+        .first_name("Jane".to_owned())
+        .last_name("Doe".to_owned())
+        .build();
+}
@@ -0,0 +1,10 @@
+use identify_macros::gen_model;
+
+gen_model! {
+    #[model(builder)]
+    pub struct Model {
+        first_name: String,
+    }
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use identify_macros::gen_model;
+
+gen_model! {
+    pub struct Model {
+        name: String [str::as_bytes],
+    }
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+use identify_macros::gen_model;
+
+gen_model! {
+    pub struct Model {
+        #[new(skip)]
+        #[new(type(u32))]
+        id: u64,
+        #[hydrate(rename(u32))]
+        name: String,
+    }
+}
+
+fn main() {}
@@ -0,0 +1,7 @@
+use identify_macros::gen_model;
+
+gen_model! {
+    pub struct Model {}
+}
+
+fn main() {}
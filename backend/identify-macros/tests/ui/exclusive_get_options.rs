@@ -0,0 +1,10 @@
+use identify_macros::gen_model;
+
+gen_model! {
+    pub struct Model {
+        #[get(skip, into(u64))]
+        id: u32,
+    }
+}
+
+fn main() {}
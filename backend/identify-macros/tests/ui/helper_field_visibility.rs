@@ -0,0 +1,13 @@
+use identify_macros::gen_model;
+
+gen_model! {
+    pub struct Model {
+        id: u64,
+    }
+
+    pub struct NewModelAttrs {
+        pub name: String,
+    }
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+use identify_macros::gen_id;
+
+const NAMESPACE: uuid::Uuid = uuid::Uuid::nil();
+
+gen_id! {
+    NAMESPACE,
+    version = 2,
+    pub struct ModelId {
+        email: String,
+        #[id(since = 3)]
+        tenant: String,
+    }
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use identify_macros::gen_id;
+
+gen_id! {
+    pub struct ModelId {
+        name: String,
+    }
+}
+
+fn main() {}
@@ -0,0 +1,14 @@
+use identify_macros::gen_model;
+
+gen_model! {
+    pub struct Model {
+        #[api(rename = "firstName")]
+        first_name: String,
+        #[api(flatten)]
+        last_name: String,
+    }
+
+    pub struct NewModelAttrs;
+}
+
+fn main() {}
@@ -0,0 +1,19 @@
+use identify_macros::gen_model;
+
+gen_model! {
+    #[model(builder)]
+    pub enum Status {
+        Active,
+    }
+}
+
+gen_model! {
+    pub enum Role {
+        Owner,
+        #[model(rename = "owner")]
+        Admin,
+        Member(String),
+    }
+}
+
+fn main() {}
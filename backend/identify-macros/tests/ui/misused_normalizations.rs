@@ -0,0 +1,25 @@
+use identify_macros::gen_id;
+
+const NAMESPACE: uuid::Uuid = uuid::Uuid::nil();
+
+fn to_bytes(value: &str) -> &[u8] {
+    value.as_bytes()
+}
+
+gen_id! {
+    NAMESPACE,
+    pub struct ModelId {
+        #[id(normalize(trim, casefold))]
+        email: String,
+    }
+}
+
+gen_id! {
+    NAMESPACE,
+    pub struct OtherId {
+        #[id(normalize(lowercase))]
+        email: String [to_bytes],
+    }
+}
+
+fn main() {}
@@ -0,0 +1,19 @@
+use identify_macros::ModelRow;
+
+#[derive(ModelRow)]
+#[row(model = String, table = "users")]
+pub struct UserRow {
+    #[row(column = "id")]
+    id: u32,
+}
+
+#[derive(ModelRow)]
+pub struct EventRow {
+    id: u32,
+}
+
+#[derive(ModelRow)]
+#[row(model = String, error = String)]
+pub struct TupleRow(u32);
+
+fn main() {}
@@ -0,0 +1,12 @@
+use identify_macros::gen_model;
+
+gen_model! {
+    pub struct Model {
+        #[set(touch(modified_at))]
+        name: String,
+        #[set(bump)]
+        title: String,
+    }
+}
+
+fn main() {}
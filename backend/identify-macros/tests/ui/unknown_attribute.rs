@@ -0,0 +1,10 @@
+use identify_macros::gen_model;
+
+gen_model! {
+    pub struct Model {
+        #[serde(rename = "id")]
+        id: u64,
+    }
+}
+
+fn main() {}
@@ -0,0 +1,449 @@
+//! Black-box tests of the HTTP API, enabled by the `api` feature.
+//!
+//! A [TestApp] serves the full router on a random local port, backed by a migrated database in
+//! a temporary file, so tests talk to it over HTTP like any other client would.
+
+use std::{net::SocketAddr, sync::Arc};
+
+use eyre::{Context, Result, eyre};
+use identify::{
+    api::{
+        self, ApiState,
+        auth::{Authenticator, Principal},
+        middleware::{
+            latency::ServerTiming,
+            sdk::{SdkPolicy, SdkTracker},
+        },
+        rate_limit::RateLimiter,
+        tenancy::{self, TenantResolver, Tenants},
+    },
+    config::{
+        EmailChangesConfig, EventStreamConfig, InvitationsConfig, LoginsConfig,
+        ReadModelConfig, RegistrationConfig, SessionsConfig, SmsConfig,
+    },
+    event_feed::UserEventFeed,
+    identifiers::IdentifierLists,
+    logins::LoginTracker,
+    metrics::Metrics,
+    operations::OperationRunner,
+    session_monitor::SessionMonitor,
+    shutdown::{self, ShutdownTrigger},
+    status::StatusBoard,
+};
+use identify_domain::{
+    AuthorizationPolicies, QuotaPolicy, UsernameStrategy, WebhookUrlPolicy,
+};
+use identify_infrastructure::{
+    blobs::FilesystemBlobStorage,
+    email::LogEmailSender,
+    geo::NoGeoResolver,
+    passwords::Argon2Hasher,
+    sms::LogSmsSender,
+    storage::{
+        connection::PoolConfig,
+        retry::{Retrier, RetryPolicy},
+        tenants::TenantRegistry,
+        users::cache::UserCache,
+    },
+    tokens::RandomTokenGenerator,
+};
+use reqwest::{Client, RequestBuilder, Response};
+use serde::Deserialize;
+use serde_json::json;
+use tempfile::TempDir;
+use tokio::{net::TcpListener, task::JoinHandle};
+use uuid::Uuid;
+
+use crate::{Fixtures, Testkit, database_url};
+
+/// Key of the admin every [TestApp] knows, which is allowed to call every endpoint.
+pub const ADMIN_KEY: &str = "test-admin-key";
+
+/// Builder of a [TestApp].
+pub struct TestAppBuilder {
+    keys: Vec<(String, Principal)>,
+    /// Keys limited to the tenants of organizations, by the slugs of the organizations.
+    tenant_keys: Vec<(String, String)>,
+    rate_limiter: RateLimiter,
+    username_strategy: UsernameStrategy,
+    identifiers: IdentifierLists,
+    authorization_policies: AuthorizationPolicies,
+    tenancy: Option<TenantResolver>,
+}
+
+impl TestAppBuilder {
+    /// Adds an API key of a caller with the permissions, e.g. `users:read`.
+    pub fn with_api_key(
+        mut self,
+        key: impl Into<String>,
+        permissions: &[&str],
+    ) -> Self {
+        let key = key.into();
+        self.keys.push((
+            key.clone(),
+            Principal {
+                subject: key,
+                admin: false,
+                permissions: permissions
+                    .iter()
+                    .map(|p| (*p).to_owned())
+                    .collect(),
+                organization_id: None,
+                user_id: None,
+            },
+        ));
+        self
+    }
+
+    /// Adds an API key of a caller that acts on behalf of the user, so that it only has the
+    /// permissions of the roles assigned to them.
+    pub fn with_user_api_key(
+        mut self,
+        key: impl Into<String>,
+        user_id: Uuid,
+    ) -> Self {
+        let key = key.into();
+        self.keys.push((
+            key.clone(),
+            Principal {
+                subject: key,
+                admin: false,
+                permissions: Vec::new(),
+                organization_id: None,
+                user_id: Some(user_id),
+            },
+        ));
+        self
+    }
+
+    /// Adds an API key of a caller with the permissions, limited to the tenant of the
+    /// organization with the slug. The organization is registered when the app starts, with the
+    /// slug as its name and its external ID, so that the key can be limited to its ID.
+    pub fn with_tenant_api_key(
+        mut self,
+        key: impl Into<String>,
+        organization: &str,
+        permissions: &[&str],
+    ) -> Self {
+        let key = key.into();
+        self.tenant_keys
+            .push((key.clone(), organization.to_owned()));
+        self.with_api_key(key, permissions)
+    }
+
+    /// Limits the requests, which aren't limited by default.
+    pub fn with_rate_limiter(mut self, rate_limiter: RateLimiter) -> Self {
+        self.rate_limiter = rate_limiter;
+        self
+    }
+
+    pub fn with_username_strategy(
+        mut self,
+        strategy: UsernameStrategy,
+    ) -> Self {
+        self.username_strategy = strategy;
+        self
+    }
+
+    pub fn with_identifiers(mut self, identifiers: IdentifierLists) -> Self {
+        self.identifiers = identifiers;
+        self
+    }
+
+    pub fn with_authorization_policies(
+        mut self,
+        policies: AuthorizationPolicies,
+    ) -> Self {
+        self.authorization_policies = policies;
+        self
+    }
+
+    /// Gives every organization a database of its own, in the temporary directory of the app.
+    pub fn with_tenancy(mut self, resolver: TenantResolver) -> Self {
+        self.tenancy = Some(resolver);
+        self
+    }
+
+    /// Migrates a new database and starts serving the API.
+    pub async fn start(mut self) -> Result<TestApp> {
+        let dir = tempfile::tempdir()
+            .wrap_err("failed to create a temporary directory")?;
+        let kit = Testkit::with_database_url(&database_url(
+            &dir.path().join("identify.db"),
+        )?)
+        .await?;
+        for (key, slug) in &self.tenant_keys {
+            let (_, organization) = kit
+                .fixtures()
+                .organization()
+                .with_name(slug.as_str())
+                .with_slug(slug.as_str())
+                .with_external_id(slug.as_str())
+                .owned_by(format!("owner@{slug}.test"))
+                .create()
+                .await?;
+            for (_, principal) in self
+                .keys
+                .iter_mut()
+                .filter(|(candidate, _)| candidate == key)
+            {
+                principal.organization_id = Some(organization.id());
+            }
+        }
+        let pool = kit.pool().clone();
+
+        // Events are tailed often, so that tests don't wait long for them.
+        let user_events = UserEventFeed::from_config(
+            kit.read_pool().clone(),
+            &EventStreamConfig {
+                poll_interval_ms: 20,
+                ..EventStreamConfig::default()
+            },
+        );
+        user_events.spawn();
+        let sessions =
+            SessionMonitor::from_config(&EventStreamConfig::default());
+        sessions.spawn(user_events.clone());
+        let logins = LoginTracker::from_config(
+            pool.clone(),
+            kit.encryption().clone(),
+            &LoginsConfig::default(),
+        );
+        // The background jobs run until the app is dropped.
+        let (shutdown_trigger, shutdown) = shutdown::channel();
+        logins.spawn(shutdown.clone());
+
+        let status = StatusBoard::new();
+        let state = ApiState {
+            operation_runner: OperationRunner::new(
+                pool.clone(),
+                status.clone(),
+            ),
+            pool,
+            read_pool: kit.read_pool().clone(),
+            encryption: kit.encryption().clone(),
+            user_cache: UserCache::disabled(),
+            sdk_tracker: SdkTracker::new(SdkPolicy::default()),
+            authenticator: Authenticator::new(self.keys),
+            metrics: Metrics::detached(),
+            server_timing: ServerTiming(false),
+            rate_limiter: self.rate_limiter,
+            status,
+            username_strategy: self.username_strategy,
+            identifiers: self.identifiers,
+            blob_storage: Arc::new(FilesystemBlobStorage::new(
+                dir.path().join("blobs"),
+            )),
+            email_sender: Arc::new(LogEmailSender),
+            email_templates: Arc::default(),
+            geo_resolver: Arc::new(NoGeoResolver),
+            consent_policies: Arc::default(),
+            authorization_policies: Arc::new(self.authorization_policies),
+            token_generator: Arc::new(RandomTokenGenerator::new()),
+            invitations: InvitationsConfig::default().settings(),
+            email_changes: EmailChangesConfig::default().settings(),
+            password_hasher: Arc::new(Argon2Hasher::new()),
+            registration: RegistrationConfig::default()
+                .settings()
+                .map_err(|e| eyre!(e))?,
+            quotas: QuotaPolicy::default(),
+            webhook_urls: WebhookUrlPolicy::default(),
+            sms_sender: Arc::new(LogSmsSender),
+            phone_verifications: SmsConfig::default()
+                .phone_verification_settings(),
+            user_events,
+            sessions,
+            session_tokens: SessionsConfig::default()
+                .settings(None)
+                .map_err(|e| eyre!(e))?,
+            logins,
+            funnel: LoginsConfig::default().funnel(),
+            retrier: Retrier::new(RetryPolicy::default()),
+        };
+        let app = match self.tenancy {
+            Some(resolver) => tenancy::router(Tenants::new(
+                resolver,
+                TenantRegistry::new(
+                    dir.path().join("tenants"),
+                    PoolConfig::default(),
+                ),
+                // Tenant databases are projected often, so that tests don't wait long for
+                // their listings.
+                ReadModelConfig {
+                    poll_interval_ms: 20,
+                },
+                // And their events are tailed just as often as those of the primary one.
+                EventStreamConfig {
+                    poll_interval_ms: 20,
+                    ..EventStreamConfig::default()
+                },
+                state,
+                shutdown,
+            )),
+            None => api::router(state),
+        };
+
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let address = listener.local_addr()?;
+        let server = tokio::spawn(async move {
+            axum::serve(
+                listener,
+                app.into_make_service_with_connect_info::<SocketAddr>(),
+            )
+            .await
+            .expect("the test server failed");
+        });
+
+        Ok(TestApp {
+            kit,
+            base_url: format!("http://{address}"),
+            client: Client::new(),
+            server,
+            _shutdown: shutdown_trigger,
+            _dir: dir,
+        })
+    }
+}
+
+/// The API served over HTTP for a single test. The server stops and the database is deleted
+/// when the app is dropped.
+///
+/// # Examples
+///
+/// ```
+/// # use identify_testkit::api::TestApp;
+/// # #[tokio::main]
+/// # async fn main() -> eyre::Result<()> {
+/// let app = TestApp::builder().start().await?;
+/// let user = app.create_test_user().await?;
+///
+/// let response = app.get(&format!("/api/v1/users/{}", user.id)).send().await?;
+/// assert_eq!(response.status(), 200);
+/// # Ok(())
+/// # }
+/// ```
+pub struct TestApp {
+    kit: Testkit,
+    base_url: String,
+    client: Client,
+    server: JoinHandle<()>,
+    _shutdown: ShutdownTrigger,
+    _dir: TempDir,
+}
+
+/// A user created through the API.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TestUser {
+    pub id: Uuid,
+    pub email: String,
+    pub username: String,
+}
+
+impl TestApp {
+    /// Starts building an app that knows only the [ADMIN_KEY].
+    pub fn builder() -> TestAppBuilder {
+        TestAppBuilder {
+            keys: vec![(
+                ADMIN_KEY.to_owned(),
+                Principal {
+                    subject: "admin".to_owned(),
+                    admin: true,
+                    permissions: Vec::new(),
+                    organization_id: None,
+                    user_id: None,
+                },
+            )],
+            tenant_keys: Vec::new(),
+            rate_limiter: RateLimiter::disabled(),
+            username_strategy: UsernameStrategy::default(),
+            identifiers: IdentifierLists::builtin(),
+            authorization_policies: AuthorizationPolicies::default(),
+            tenancy: None,
+        }
+    }
+
+    /// URL of the server, e.g. `http://127.0.0.1:49152`.
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// A client without any credentials, for requests the helpers don't cover.
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
+
+    /// Builds a request of the admin to the path, e.g. `/api/v1/users`.
+    pub fn get(&self, path: &str) -> RequestBuilder {
+        self.request(reqwest::Method::GET, path)
+    }
+
+    pub fn post(&self, path: &str) -> RequestBuilder {
+        self.request(reqwest::Method::POST, path)
+    }
+
+    pub fn put(&self, path: &str) -> RequestBuilder {
+        self.request(reqwest::Method::PUT, path)
+    }
+
+    pub fn request(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+    ) -> RequestBuilder {
+        self.client
+            .request(method, format!("{}{path}", self.base_url))
+            .bearer_auth(ADMIN_KEY)
+    }
+
+    /// Creates a user with a unique email through the API, and waits until it can be listed.
+    pub async fn create_test_user(&self) -> Result<TestUser> {
+        let n = self.kit.fixtures().next();
+        let response = self
+            .post("/api/v1/users")
+            .json(&json!({
+                "email": format!("user-{n}@example.test"),
+                "first_name": format!("User {n}"),
+            }))
+            .send()
+            .await?;
+        let user = json_of(response).await?;
+        self.catch_up().await?;
+
+        Ok(user)
+    }
+
+    /// Projects the pending user events into the read model, which the served API doesn't do
+    /// on its own, so that listings reflect all the changes made so far.
+    pub async fn catch_up(&self) -> Result<()> {
+        self.kit.project_read_model().await
+    }
+
+    /// Builders of entities persisted directly in the database, for state the API can't create.
+    pub fn fixtures(&self) -> Fixtures<'_> {
+        self.kit.fixtures()
+    }
+
+    /// The testkit of the database behind the API.
+    pub fn kit(&self) -> &Testkit {
+        &self.kit
+    }
+}
+
+impl Drop for TestApp {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
+
+/// Deserializes the body of a successful response.
+pub async fn json_of<T: for<'de> Deserialize<'de>>(
+    response: Response,
+) -> Result<T> {
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(eyre!("request failed with {status}: {body}"));
+    }
+
+    Ok(response.json().await?)
+}
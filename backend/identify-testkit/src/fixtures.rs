@@ -0,0 +1,248 @@
+//! Builders that persist entities through the use cases, so that all related entities (e.g.
+//! memberships and lifecycle events) are created along the way.
+//!
+//! Every field has a default, so tests only set what they assert on.
+
+use std::sync::{
+    Arc,
+    atomic::{AtomicU32, Ordering},
+};
+
+use eyre::Result;
+use identify_application::{
+    AddOrganizationMemberParams, CreateUserParams, OrganizationUseCaseDeps,
+    RegisterUserWithOrganizationParams, RegistrationUseCaseDeps,
+    UserLifecycleUseCaseDeps, add_organization_member, create_user,
+    register_user_with_organization,
+};
+use identify_domain::{
+    MembershipRole, NewOrganizationAttrs, NewUserAttrs, Organization,
+    QuotaPolicy, User, UsernameStrategy,
+};
+use identify_infrastructure::{
+    encryption::FieldEncryption, storage::unit_of_work::SqliteUnitOfWork,
+};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+/// Entry point of all fixture builders.
+#[derive(Clone, Copy)]
+pub struct Fixtures<'a> {
+    pool: &'a SqlitePool,
+    encryption: &'a FieldEncryption,
+    sequence: &'a AtomicU32,
+}
+
+impl<'a> Fixtures<'a> {
+    pub(crate) fn new(
+        pool: &'a SqlitePool,
+        encryption: &'a FieldEncryption,
+        sequence: &'a AtomicU32,
+    ) -> Self {
+        Fixtures {
+            pool,
+            encryption,
+            sequence,
+        }
+    }
+
+    /// Starts building a user.
+    pub fn user(&self) -> UserFixture<'a> {
+        UserFixture {
+            fixtures: *self,
+            email: None,
+            username: None,
+            first_name: None,
+            last_name: None,
+            organization_id: None,
+            role: MembershipRole::Member,
+        }
+    }
+
+    /// Starts building an organization together with its owner.
+    pub fn organization(&self) -> OrganizationFixture<'a> {
+        OrganizationFixture {
+            fixtures: *self,
+            name: None,
+            slug: None,
+            external_id: None,
+            owner: self.user(),
+        }
+    }
+
+    /// Returns a number that is unique within the test, used to build unique defaults.
+    pub(crate) fn next(&self) -> u32 {
+        self.sequence.fetch_add(1, Ordering::Relaxed) + 1
+    }
+}
+
+/// Builder of a [User], optionally added to an organization.
+pub struct UserFixture<'a> {
+    fixtures: Fixtures<'a>,
+    email: Option<String>,
+    username: Option<String>,
+    first_name: Option<String>,
+    last_name: Option<String>,
+    organization_id: Option<Uuid>,
+    role: MembershipRole,
+}
+
+impl UserFixture<'_> {
+    pub fn with_email(mut self, email: impl Into<String>) -> Self {
+        self.email = Some(email.into());
+        self
+    }
+
+    /// Username of the user. It's generated from the email if it's not set.
+    pub fn with_username(mut self, username: impl Into<String>) -> Self {
+        self.username = Some(username.into());
+        self
+    }
+
+    pub fn with_name(
+        mut self,
+        first_name: impl Into<String>,
+        last_name: Option<&str>,
+    ) -> Self {
+        self.first_name = Some(first_name.into());
+        self.last_name = last_name.map(str::to_owned);
+        self
+    }
+
+    /// Adds the user to the organization, as a [member](MembershipRole::Member) unless
+    /// [with_role](UserFixture::with_role) says otherwise.
+    pub fn in_org(mut self, organization: &Organization) -> Self {
+        self.organization_id = Some(organization.id());
+        self
+    }
+
+    /// Role of the user within the organization set by [in_org](UserFixture::in_org).
+    pub fn with_role(mut self, role: MembershipRole) -> Self {
+        self.role = role;
+        self
+    }
+
+    fn into_attrs(self) -> NewUserAttrs {
+        let n = self.fixtures.next();
+
+        NewUserAttrs::builder()
+            .email(
+                self.email
+                    .unwrap_or_else(|| format!("user-{n}@example.test")),
+            )
+            .first_name(self.first_name.unwrap_or_else(|| format!("User {n}")))
+            .last_name(self.last_name)
+            .build()
+    }
+
+    /// Persists the user and their membership.
+    pub async fn create(self) -> Result<User> {
+        let fixtures = self.fixtures;
+        let membership = self.organization_id.map(|id| (id, self.role));
+        let username = self.username.clone();
+
+        let user = create_user(
+            UserLifecycleUseCaseDeps::new(
+                SqliteUnitOfWork::begin(fixtures.pool, fixtures.encryption)
+                    .await?,
+            ),
+            CreateUserParams {
+                user_attrs: self.into_attrs(),
+                username,
+                username_strategy: UsernameStrategy::default(),
+                identifier_policy: Arc::default(),
+                organization_id: None,
+                quota_policy: QuotaPolicy::default(),
+            },
+        )
+        .await?;
+
+        if let Some((organization_id, role)) = membership {
+            add_organization_member(
+                OrganizationUseCaseDeps::new(
+                    SqliteUnitOfWork::begin(fixtures.pool, fixtures.encryption)
+                        .await?,
+                ),
+                AddOrganizationMemberParams {
+                    organization_id,
+                    user_id: user.id(),
+                    role,
+                    quota_policy: QuotaPolicy::default(),
+                },
+            )
+            .await?;
+        }
+        crate::project_read_model(fixtures.pool, fixtures.encryption).await?;
+
+        Ok(user)
+    }
+}
+
+/// Builder of an [Organization] registered by its owner.
+pub struct OrganizationFixture<'a> {
+    fixtures: Fixtures<'a>,
+    name: Option<String>,
+    slug: Option<String>,
+    external_id: Option<String>,
+    owner: UserFixture<'a>,
+}
+
+impl OrganizationFixture<'_> {
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Slug of the organization, derived from the name by default.
+    pub fn with_slug(mut self, slug: impl Into<String>) -> Self {
+        self.slug = Some(slug.into());
+        self
+    }
+
+    /// ID assigned to the organization by the caller, e.g. to manage it through the API.
+    pub fn with_external_id(mut self, external_id: impl Into<String>) -> Self {
+        self.external_id = Some(external_id.into());
+        self
+    }
+
+    /// Email of the owner who registers the organization.
+    pub fn owned_by(mut self, email: impl Into<String>) -> Self {
+        self.owner = self.owner.with_email(email);
+        self
+    }
+
+    /// Registers the owner together with the organization.
+    pub async fn create(self) -> Result<(User, Organization)> {
+        let name = self.name.unwrap_or_else(|| {
+            format!("Organization {}", self.fixtures.next())
+        });
+
+        let username = self.owner.username.clone();
+
+        let registered = register_user_with_organization(
+            RegistrationUseCaseDeps::new(
+                SqliteUnitOfWork::begin(
+                    self.fixtures.pool,
+                    self.fixtures.encryption,
+                )
+                .await?,
+            ),
+            RegisterUserWithOrganizationParams {
+                user_attrs: self.owner.into_attrs(),
+                username,
+                username_strategy: UsernameStrategy::default(),
+                identifier_policy: Arc::default(),
+                organization_attrs: NewOrganizationAttrs {
+                    name,
+                    external_id: self.external_id,
+                },
+                organization_slug: self.slug,
+            },
+        )
+        .await?;
+        crate::project_read_model(self.fixtures.pool, self.fixtures.encryption)
+            .await?;
+
+        Ok(registered)
+    }
+}
@@ -0,0 +1,226 @@
+//! This crate contains helpers for writing integration tests of Identify.
+//!
+//! Every [Testkit] owns a fresh migrated database, and its [fixtures](Testkit::fixtures) persist
+//! entities through the same use cases the API uses, so tests always start from a consistent
+//! state.
+//!
+//! The database is in memory by default. Setting `IDENTIFY_TEST_DATABASE=file` runs the same
+//! tests against files instead, whose readers and writers use separate connections like in
+//! production, so that the tests also check what concurrent transactions see. See
+//! [TestDatabase].
+//!
+//! # Examples
+//!
+//! ```
+//! # use identify_domain::MembershipRole;
+//! # use identify_testkit::Testkit;
+//! # #[tokio::main]
+//! # async fn main() -> eyre::Result<()> {
+//! let kit = Testkit::new().await?;
+//! let fixtures = kit.fixtures();
+//!
+//! let (_owner, acme) = fixtures.organization().with_name("Acme").create().await?;
+//! let admin = fixtures
+//!     .user()
+//!     .with_email("admin@acme.test")
+//!     .in_org(&acme)
+//!     .with_role(MembershipRole::Admin)
+//!     .create()
+//!     .await?;
+//!
+//! assert_eq!(admin.to_attributes().email, "admin@acme.test");
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! With the `api` feature, [TestApp](api::TestApp) serves the whole API over HTTP on top of a
+//! testkit, for black-box tests of the endpoints.
+
+#[cfg(feature = "api")]
+pub mod api;
+mod fixtures;
+
+pub use fixtures::{Fixtures, OrganizationFixture, UserFixture};
+
+use std::{
+    env::{self, VarError},
+    path::Path,
+    str::FromStr,
+    sync::atomic::AtomicU32,
+};
+
+use eyre::{Result, WrapErr as _, bail, eyre};
+use identify_application::{
+    ProjectUserSummariesParams, ReadModelProjectionDeps, UnitOfWork as _,
+    project_user_summaries,
+};
+use identify_infrastructure::{
+    encryption::FieldEncryption,
+    storage::{
+        connection::{self, PoolConfig, ReadPool},
+        unit_of_work::SqliteUnitOfWork,
+    },
+};
+use sqlx::SqlitePool;
+use tempfile::TempDir;
+
+/// URL of the in-memory database a [Testkit] gets.
+const DATABASE_URL: &str = "sqlite::memory:";
+/// Environment variable that selects the [TestDatabase] of [Testkit::new].
+pub const TEST_DATABASE_VAR: &str = "IDENTIFY_TEST_DATABASE";
+
+/// Kind of database a [Testkit] opens.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TestDatabase {
+    /// A single connection to an in-memory database, which is the fastest.
+    #[default]
+    Memory,
+    /// A file in a temporary directory, written and read through separate pools of
+    /// connections, so that uncommitted transactions aren't visible to the readers.
+    File,
+}
+
+impl TestDatabase {
+    /// Returns the kind set by [TEST_DATABASE_VAR], or the default one if it isn't set.
+    pub fn from_env() -> Result<Self> {
+        match env::var(TEST_DATABASE_VAR) {
+            Ok(value) => value.parse(),
+            Err(VarError::NotPresent) => Ok(TestDatabase::default()),
+            Err(e) => Err(e).wrap_err_with(|| {
+                format!("failed to read {TEST_DATABASE_VAR}")
+            }),
+        }
+    }
+}
+
+impl FromStr for TestDatabase {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "memory" => Ok(TestDatabase::Memory),
+            "file" => Ok(TestDatabase::File),
+            _ => bail!(
+                "unknown test database '{s}', expected 'memory' or 'file'"
+            ),
+        }
+    }
+}
+
+/// An isolated environment for a single test.
+pub struct Testkit {
+    pool: SqlitePool,
+    read_pool: ReadPool,
+    encryption: FieldEncryption,
+    sequence: AtomicU32,
+    /// Directory of the database file, which is removed when the testkit is dropped.
+    _dir: Option<TempDir>,
+}
+
+impl Testkit {
+    /// Opens a new database of the kind set by [TEST_DATABASE_VAR] and applies all migrations
+    /// to it.
+    pub async fn new() -> Result<Self> {
+        Testkit::with_database(TestDatabase::from_env()?).await
+    }
+
+    /// Opens a new database of the kind and applies all migrations to it.
+    pub async fn with_database(database: TestDatabase) -> Result<Self> {
+        match database {
+            TestDatabase::Memory => {
+                Testkit::with_database_url(DATABASE_URL).await
+            }
+            TestDatabase::File => {
+                let dir = tempfile::tempdir()
+                    .wrap_err("failed to create a temporary directory")?;
+                let url = database_url(&dir.path().join("identify.db"))?;
+                let mut kit = Testkit::with_database_url(&url).await?;
+                kit._dir = Some(dir);
+
+                Ok(kit)
+            }
+        }
+    }
+
+    /// Opens the database at the URL, e.g. a temporary file shared by several connections, and
+    /// applies all migrations to it.
+    pub async fn with_database_url(url: &str) -> Result<Self> {
+        let config = PoolConfig::for_url(url);
+        let pool = connection::get_pool(url, &config).await?;
+        connection::migrate(&pool).await?;
+        let read_pool = connection::get_read_pool(url, &config, &pool).await?;
+
+        Ok(Testkit {
+            pool,
+            read_pool,
+            encryption: FieldEncryption::disabled(),
+            sequence: AtomicU32::new(0),
+            _dir: None,
+        })
+    }
+
+    /// The pool of the test database.
+    pub fn pool(&self) -> &SqlitePool {
+        &self.pool
+    }
+
+    /// The pool of read-only connections to the test database, which shares the connection of
+    /// [pool](Self::pool) if the database is in memory.
+    pub fn read_pool(&self) -> &ReadPool {
+        &self.read_pool
+    }
+
+    /// Encrypts the fields stored by the fixtures and the API with the encryption, which is
+    /// disabled by default.
+    pub fn with_encryption(mut self, encryption: FieldEncryption) -> Self {
+        self.encryption = encryption;
+        self
+    }
+
+    /// The encryption of the fields stored in the test database.
+    pub fn encryption(&self) -> &FieldEncryption {
+        &self.encryption
+    }
+
+    /// Returns the builders of entities persisted in the test database.
+    pub fn fixtures(&self) -> Fixtures<'_> {
+        Fixtures::new(&self.pool, &self.encryption, &self.sequence)
+    }
+
+    /// Projects the pending user events into the read model that users are listed from.
+    ///
+    /// Fixtures do this on their own, so it's only needed after changing users otherwise, e.g.
+    /// through the API or the repositories.
+    pub async fn project_read_model(&self) -> Result<()> {
+        project_read_model(&self.pool, &self.encryption).await
+    }
+}
+
+pub(crate) async fn project_read_model(
+    pool: &SqlitePool,
+    encryption: &FieldEncryption,
+) -> Result<()> {
+    loop {
+        let mut uow = SqliteUnitOfWork::begin(pool, encryption).await?;
+
+        let projected = project_user_summaries(
+            ReadModelProjectionDeps::new(&mut uow),
+            ProjectUserSummariesParams { limit: 100 },
+        )
+        .await?;
+
+        uow.commit().await?;
+
+        if projected.is_empty() {
+            return Ok(());
+        }
+    }
+}
+
+pub(crate) fn database_url(path: &Path) -> Result<String> {
+    let path = path
+        .to_str()
+        .ok_or_else(|| eyre!("the temporary directory isn't valid UTF-8"))?;
+
+    Ok(format!("sqlite://{path}"))
+}
@@ -0,0 +1,555 @@
+//! Administration of the service from the command line, see the `identify-admin` binary.
+//!
+//! Commands run the use cases directly against the configured database, so operators can
+//! manage the service without going through the API, e.g. to create the first users before any
+//! API keys are handed out.
+
+use std::{
+    io::{BufRead, Write},
+    path::PathBuf,
+    sync::Arc,
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use clap::{Args, Parser, Subcommand};
+use eyre::{Context, Result, bail, eyre};
+use futures_util::TryStreamExt as _;
+use identify_application::{
+    ChangeMemberRoleParams, CreateUserParams, EmailSender, EmailTemplates,
+    ExportUsersParams, GetOperationParams, GetReverificationProgressParams,
+    OperationUseCaseDeps, OrganizationUseCaseDeps, ReverificationUseCaseDeps,
+    RotateSigningKeyParams, SetUserPasswordParams, SigningKeyRotationDeps,
+    Sort, SortDirection, StartReverificationCampaignParams,
+    UserLifecycleUseCaseDeps, UserPasswordUseCaseDeps, UserUseCaseDeps,
+    change_member_role, create_user, export_users, get_operation,
+    get_reverification_progress,
+    reverification_contracts::ReverificationProgress,
+    rotate_signing_key, set_user_password, start_reverification_campaign,
+    user_contracts::{UserFilter, UserSortField},
+};
+use identify_domain::{
+    IdentifierPolicy, MembershipRole, NewReverificationCampaignAttrs,
+    NewUserAttrs, OperationStatus, QuotaPolicy, ReverificationCohort,
+    UsernameStrategy,
+};
+use identify_infrastructure::{
+    encryption::FieldEncryption,
+    passwords::Argon2Hasher,
+    signing::Ed25519KeyGenerator,
+    storage::{
+        self, backup, connection,
+        operations::OperationsRepository,
+        reverifications::ReverificationsRepository,
+        signing_keys::SigningKeysRepository,
+        unit_of_work::SqliteUnitOfWork,
+        users::{UsersReader, reencrypt::reencrypt_users},
+        webhooks,
+    },
+};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::{
+    api::{
+        services::users::{CreateUserRequest, UserResponse},
+        validation::{Validate, Validator},
+    },
+    operations::OperationRunner,
+    reverification::{self, CampaignSchedule},
+    seed::{self, SeedFile},
+    status::StatusBoard,
+};
+
+/// How often the progress of long-running commands is checked.
+const PROGRESS_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Manages Identify directly through its database.
+#[derive(Debug, Parser)]
+#[command(name = "identify-admin", version)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Applies the pending database migrations.
+    Migrate,
+    /// Creates a user and prints it as JSON.
+    CreateUser {
+        #[arg(long)]
+        email: String,
+        #[arg(long)]
+        first_name: String,
+        #[arg(long)]
+        last_name: Option<String>,
+        /// Generated with the configured strategy if it's not set.
+        #[arg(long)]
+        username: Option<String>,
+    },
+    /// Sets the password of a user.
+    ///
+    /// The password is read from the first line of the standard input, so that it doesn't end
+    /// up in the shell history.
+    SetPassword {
+        /// ID of the user.
+        user_id: Uuid,
+    },
+    /// Changes the role of a member of an organization.
+    Promote {
+        /// ID of the organization.
+        #[arg(long)]
+        organization: Uuid,
+        /// New role of the member: `owner`, `admin` or `member`.
+        #[arg(long, default_value = "admin")]
+        role: MembershipRole,
+        /// ID of the member.
+        user_id: Uuid,
+    },
+    /// Prints all users as JSON, one per line, in the order they were created.
+    ExportUsers,
+    /// Creates the organizations, users and memberships of a seed file that don't exist yet.
+    ///
+    /// Meant for development and demo environments. Loading the same file again changes
+    /// nothing.
+    Seed {
+        /// YAML file describing the entities, see the `seed` module.
+        #[arg(long)]
+        file: PathBuf,
+    },
+    /// Flags a cohort of users as requiring email re-verification and emails them.
+    ///
+    /// The users are notified in batches until all of them are, which may take a while. A
+    /// campaign that gets interrupted can be resumed with `resume-reverification`.
+    StartReverification {
+        /// Why the users have to verify their emails again, included in the emails.
+        #[arg(long)]
+        reason: String,
+        /// Only users with emails in the domain, e.g. `acme.test`.
+        #[arg(long)]
+        email_domain: Option<String>,
+        /// Only users created before the time, e.g. `2026-01-01T00:00:00Z`.
+        #[arg(long)]
+        created_before: Option<DateTime<Utc>>,
+        #[command(flatten)]
+        schedule: ScheduleArgs,
+    },
+    /// Emails the users of a campaign that haven't been notified yet.
+    ResumeReverification {
+        /// ID of the campaign.
+        campaign_id: Uuid,
+        #[command(flatten)]
+        schedule: ScheduleArgs,
+    },
+    /// Prints how many users of a campaign have been notified.
+    ReverificationProgress {
+        /// ID of the campaign.
+        campaign_id: Uuid,
+    },
+    /// Encrypts the emails and names of all users, and the secrets of the webhook endpoints, with
+    /// the current encryption key, e.g. after the keys were rotated or the encryption was
+    /// enabled.
+    ///
+    /// Values already encrypted with the current key are left as they are, so the command can
+    /// be run again if it gets interrupted. Older keys can be removed from the configuration
+    /// once it has finished.
+    ReencryptUsers {
+        /// Most users rewritten in a single transaction.
+        #[arg(long, default_value_t = 500)]
+        batch_size: u32,
+    },
+    /// Replaces the active signing key with a new one right away, e.g. when it may have leaked.
+    ///
+    /// The previous key stays published until the scheduled rotation deletes it, so that the
+    /// tokens it signed can still be verified.
+    RotateSigningKey,
+    /// Copies the database into a new file while the service keeps running.
+    Backup {
+        /// File the backup is written to. It must not exist yet.
+        #[arg(long)]
+        out: PathBuf,
+    },
+    /// Replaces the database with a backup, once it passed an integrity check.
+    ///
+    /// The service must be stopped first, since its connections would keep using the replaced
+    /// database. Migrations the backup lacks are applied when it starts again.
+    Restore {
+        /// Backup taken with `backup`.
+        #[arg(long)]
+        from: PathBuf,
+    },
+}
+
+/// How fast the users of a re-verification campaign are emailed.
+#[derive(Debug, Args)]
+pub struct ScheduleArgs {
+    /// Most users emailed at once.
+    #[arg(long, default_value_t = 100)]
+    batch_size: u32,
+    /// Seconds to wait between two batches.
+    #[arg(long, default_value_t = 60)]
+    pause_secs: u64,
+}
+
+impl From<ScheduleArgs> for CampaignSchedule {
+    fn from(value: ScheduleArgs) -> Self {
+        CampaignSchedule {
+            batch_size: value.batch_size,
+            pause: Duration::from_secs(value.pause_secs),
+        }
+    }
+}
+
+/// What the commands need besides their arguments.
+pub struct AdminContext<'a> {
+    pub pool: &'a SqlitePool,
+    /// Encrypts the personal data and secrets stored in the database.
+    pub encryption: FieldEncryption,
+    pub username_strategy: UsernameStrategy,
+    pub identifier_policy: Arc<IdentifierPolicy>,
+    /// Limits how many users organizations can have.
+    pub quota_policy: QuotaPolicy,
+    pub email_sender: Arc<dyn EmailSender>,
+    pub email_templates: Arc<EmailTemplates>,
+}
+
+/// Runs the command, reading the input it needs from `input` and writing its results to
+/// `output`.
+pub async fn run(
+    command: Command,
+    context: &AdminContext<'_>,
+    input: &mut dyn BufRead,
+    output: &mut dyn Write,
+) -> Result<()> {
+    match command {
+        Command::Migrate => {
+            connection::migrate(context.pool)
+                .await
+                .wrap_err("error while applying migrations")?;
+            writeln!(output, "Migrations applied")?;
+        }
+        Command::CreateUser {
+            email,
+            first_name,
+            last_name,
+            username,
+        } => {
+            let request = CreateUserRequest {
+                email,
+                username,
+                first_name,
+                last_name,
+            };
+            validate(&request)?;
+
+            let unit_of_work =
+                SqliteUnitOfWork::begin(context.pool, &context.encryption)
+                    .await?;
+            let user = create_user(
+                UserLifecycleUseCaseDeps::new(unit_of_work),
+                CreateUserParams {
+                    user_attrs: NewUserAttrs {
+                        email: request.email,
+                        first_name: request.first_name,
+                        last_name: request.last_name,
+                    },
+                    username: request.username,
+                    username_strategy: context.username_strategy,
+                    identifier_policy: context.identifier_policy.clone(),
+                    organization_id: None,
+                    quota_policy: QuotaPolicy::default(),
+                },
+            )
+            .await?;
+
+            print_json(output, &UserResponse::from(user))?;
+        }
+        Command::SetPassword { user_id } => {
+            let mut password = String::new();
+            input
+                .read_line(&mut password)
+                .wrap_err("error while reading the password")?;
+            let password = password.trim_end_matches(['\r', '\n']).to_owned();
+
+            let unit_of_work =
+                SqliteUnitOfWork::begin(context.pool, &context.encryption)
+                    .await?;
+            set_user_password(
+                UserPasswordUseCaseDeps::new(
+                    unit_of_work,
+                    &Argon2Hasher::new(),
+                ),
+                SetUserPasswordParams {
+                    id: user_id,
+                    password,
+                },
+            )
+            .await?;
+
+            writeln!(output, "Password of user {user_id} set")?;
+        }
+        Command::Promote {
+            organization,
+            role,
+            user_id,
+        } => {
+            let unit_of_work =
+                SqliteUnitOfWork::begin(context.pool, &context.encryption)
+                    .await?;
+            let membership = change_member_role(
+                OrganizationUseCaseDeps::new(unit_of_work),
+                ChangeMemberRoleParams {
+                    organization_id: organization,
+                    user_id,
+                    role,
+                },
+            )
+            .await?;
+
+            writeln!(
+                output,
+                "User {user_id} is now {} of organization {organization}",
+                membership.role()
+            )?;
+        }
+        Command::ExportUsers => print_users(context, output).await?,
+        Command::Seed { file } => {
+            let seed = SeedFile::read(&file)?;
+            let report = seed::load(&seed, context).await?;
+            writeln!(output, "{report}")?;
+        }
+        Command::StartReverification {
+            reason,
+            email_domain,
+            created_before,
+            schedule,
+        } => {
+            let mut tx = storage::begin(context.pool).await?;
+            let mut repository =
+                ReverificationsRepository::new(&mut tx, &context.encryption);
+            let (campaign, progress) = start_reverification_campaign(
+                ReverificationUseCaseDeps::new(&mut repository),
+                StartReverificationCampaignParams {
+                    campaign_attrs: NewReverificationCampaignAttrs {
+                        reason,
+                        cohort: ReverificationCohort {
+                            email_domain,
+                            created_before,
+                        },
+                    },
+                },
+            )
+            .await?;
+            storage::commit(tx).await?;
+
+            writeln!(
+                output,
+                "Campaign {} flagged {} users",
+                campaign.id(),
+                progress.flagged
+            )?;
+            run_campaign(campaign.id(), schedule.into(), context, output)
+                .await?;
+        }
+        Command::ResumeReverification {
+            campaign_id,
+            schedule,
+        } => {
+            run_campaign(campaign_id, schedule.into(), context, output).await?;
+        }
+        Command::ReverificationProgress { campaign_id } => {
+            let progress =
+                reverification_progress(context, campaign_id).await?;
+            writeln!(
+                output,
+                "Notified {} of {} users",
+                progress.notified, progress.flagged
+            )?;
+        }
+        Command::ReencryptUsers { batch_size } => {
+            if batch_size == 0 {
+                bail!("--batch-size must be positive");
+            }
+
+            let report =
+                reencrypt_users(context.pool, &context.encryption, batch_size)
+                    .await?;
+            let secrets =
+                webhooks::reencrypt_secrets(context.pool, &context.encryption)
+                    .await?;
+            writeln!(
+                output,
+                "Re-encrypted {} users, {} summaries and {secrets} webhook secrets",
+                report.users, report.summaries
+            )?;
+        }
+        Command::RotateSigningKey => {
+            let mut tx = storage::begin(context.pool).await?;
+            let key = rotate_signing_key(
+                SigningKeyRotationDeps::new(
+                    &mut SigningKeysRepository::new(
+                        &mut tx,
+                        &context.encryption,
+                    ),
+                    &Ed25519KeyGenerator::new(),
+                ),
+                RotateSigningKeyParams { min_age: None },
+            )
+            .await?;
+            storage::commit(tx).await?;
+
+            writeln!(output, "Signing key {} is now active", key.id())?;
+        }
+        Command::Backup { out } => {
+            let size = backup::backup(context.pool, &out)
+                .await
+                .wrap_err("error while backing up the database")?;
+            writeln!(
+                output,
+                "Backed up the database to {} ({size} bytes)",
+                out.display()
+            )?;
+        }
+        Command::Restore { from } => {
+            backup::restore(context.pool, &from)
+                .await
+                .wrap_err("error while restoring the database")?;
+            writeln!(output, "Restored the database from {}", from.display())?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Notifies the users of the campaign with the job runner, reporting the progress until it
+/// finishes.
+async fn run_campaign(
+    campaign_id: Uuid,
+    schedule: CampaignSchedule,
+    context: &AdminContext<'_>,
+    output: &mut dyn Write,
+) -> Result<()> {
+    // Fails early if the campaign doesn't exist.
+    let mut reported = reverification_progress(context, campaign_id).await?;
+
+    let runner = OperationRunner::new(context.pool.clone(), StatusBoard::new());
+    let operation = reverification::notify_campaign(
+        &runner,
+        context.pool.clone(),
+        context.encryption.clone(),
+        context.email_sender.clone(),
+        context.email_templates.clone(),
+        campaign_id,
+        schedule,
+    )
+    .await?;
+
+    loop {
+        tokio::time::sleep(PROGRESS_POLL_INTERVAL).await;
+
+        // The transaction is released before the progress is read, so that the poll takes
+        // a single connection at a time.
+        let operation = {
+            let mut tx = storage::begin(context.pool).await?;
+            get_operation(
+                OperationUseCaseDeps::new(&mut OperationsRepository::new(
+                    &mut tx,
+                )),
+                GetOperationParams { id: operation.id() },
+            )
+            .await?
+        };
+
+        let progress = reverification_progress(context, campaign_id).await?;
+        if progress != reported {
+            writeln!(
+                output,
+                "Notified {} of {} users",
+                progress.notified, progress.flagged
+            )?;
+            reported = progress;
+        }
+
+        match operation.status() {
+            OperationStatus::Succeeded => return Ok(()),
+            OperationStatus::Cancelled => {
+                bail!("campaign {campaign_id} was cancelled")
+            }
+            OperationStatus::Failed => bail!(
+                "campaign {campaign_id} failed: {}",
+                operation.error().as_deref().unwrap_or("unknown error")
+            ),
+            OperationStatus::Pending | OperationStatus::Running => {}
+        }
+    }
+}
+
+async fn reverification_progress(
+    context: &AdminContext<'_>,
+    campaign_id: Uuid,
+) -> Result<ReverificationProgress> {
+    let mut tx = storage::begin(context.pool).await?;
+    let mut repository =
+        ReverificationsRepository::new(&mut tx, &context.encryption);
+    let (_, progress) = get_reverification_progress(
+        ReverificationUseCaseDeps::new(&mut repository),
+        GetReverificationProgressParams { campaign_id },
+    )
+    .await?;
+
+    Ok(progress)
+}
+
+/// Writes the users as they're read through a cursor, so that the export doesn't hold all of
+/// them in memory.
+async fn print_users(
+    context: &AdminContext<'_>,
+    output: &mut dyn Write,
+) -> Result<()> {
+    let mut reader =
+        UsersReader::new(context.pool.clone(), context.encryption.clone());
+    let mut users = export_users(
+        UserUseCaseDeps::new(&mut reader),
+        ExportUsersParams {
+            filter: UserFilter::default(),
+            sort: vec![Sort {
+                field: UserSortField::CreatedAt,
+                direction: SortDirection::Ascending,
+            }],
+        },
+    );
+
+    while let Some(user) = users.try_next().await? {
+        print_json(output, &UserResponse::from(user))?;
+    }
+
+    Ok(())
+}
+
+/// Applies the rules of the API, so that the commands accept the same entities.
+pub(crate) fn validate(request: &impl Validate) -> Result<()> {
+    let mut validator = Validator::default();
+    request.validate(&mut validator);
+
+    let errors = validator.into_errors();
+    if errors.is_empty() {
+        return Ok(());
+    }
+
+    let errors: Vec<String> = errors
+        .iter()
+        .map(|error| format!("{}: {}", error.field, error.message))
+        .collect();
+    bail!("invalid arguments: {}", errors.join(", "))
+}
+
+fn print_json(
+    output: &mut dyn Write,
+    value: &impl serde::Serialize,
+) -> Result<()> {
+    let line = serde_json::to_string(value).map_err(|e| eyre!(e))?;
+    writeln!(output, "{line}")?;
+
+    Ok(())
+}
@@ -0,0 +1,198 @@
+use std::{collections::HashMap, sync::Arc};
+
+use axum::{
+    extract::{Request, State},
+    http::{HeaderMap, header::AUTHORIZATION},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use identify_application::{
+    Actor, ListUserRolesParams, RoleUseCaseDeps, UnitOfWork as _,
+    list_user_roles,
+};
+use identify_domain::Role;
+use identify_infrastructure::storage::unit_of_work::SqliteUnitOfWork;
+use tracing::{Span, debug};
+use uuid::Uuid;
+
+use crate::api::{ApiState, error::ApiError, tenancy::Tenant};
+
+/// An authenticated caller of the API.
+#[derive(Debug, Clone)]
+pub struct Principal {
+    /// Who the caller is, e.g. the name of the API key owner.
+    pub subject: String,
+    /// Admins are allowed to do everything.
+    pub admin: bool,
+    /// Permissions granted to the caller, e.g. `users:read`.
+    pub permissions: Vec<String>,
+    /// ID of the organization whose tenant the caller is limited to, see [Principal::serves].
+    /// Its slug may change, while its ID never does.
+    pub organization_id: Option<Uuid>,
+    /// ID of the user the caller acts on behalf of, who grants it the permissions of their
+    /// roles, see [grant_roles].
+    pub user_id: Option<Uuid>,
+}
+
+impl Principal {
+    /// Caller of a public route that carries no credentials, e.g. someone registering
+    /// themselves. It has no permissions.
+    pub fn anonymous() -> Self {
+        Principal {
+            subject: "anonymous".to_owned(),
+            admin: false,
+            permissions: Vec::new(),
+            organization_id: None,
+            user_id: None,
+        }
+    }
+
+    pub fn has_permission(&self, permission: &str) -> bool {
+        self.admin || self.permissions.iter().any(|p| p == permission)
+    }
+
+    /// Whether the caller may make requests for the tenant, or for the primary database if
+    /// there's none.
+    ///
+    /// Callers limited to an organization are only served for its tenant. The others are only
+    /// served for the primary database, except for admins who are served for every tenant.
+    pub fn serves(&self, tenant: Option<&Tenant>) -> bool {
+        match (self.organization_id, tenant) {
+            (Some(organization_id), Some(tenant)) => {
+                organization_id == tenant.organization_id
+            }
+            (Some(_), None) => false,
+            (None, Some(_)) => self.admin,
+            (None, None) => true,
+        }
+    }
+}
+
+impl Actor for Principal {
+    fn subject(&self) -> &str {
+        &self.subject
+    }
+
+    fn has_permission(&self, permission: &str) -> bool {
+        Principal::has_permission(self, permission)
+    }
+}
+
+/// Authenticates callers by the API keys passed as bearer tokens.
+#[derive(Debug, Clone, Default)]
+pub struct Authenticator {
+    keys: Arc<HashMap<String, Principal>>,
+}
+
+impl Authenticator {
+    pub fn new(keys: impl IntoIterator<Item = (String, Principal)>) -> Self {
+        Authenticator {
+            keys: Arc::new(keys.into_iter().collect()),
+        }
+    }
+
+    fn authenticate(&self, key: &str) -> Option<Principal> {
+        self.keys.get(key).cloned()
+    }
+
+    /// Returns the caller of the request, or `None` if it carries no credentials. Fails with
+    /// `401 Unauthorized` if its credentials are invalid.
+    pub fn principal(
+        &self,
+        headers: &HeaderMap,
+    ) -> Result<Option<Principal>, ApiError> {
+        let Some(header) = headers.get(AUTHORIZATION) else {
+            return Ok(None);
+        };
+
+        header
+            .to_str()
+            .ok()
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .and_then(|key| self.authenticate(key.trim()))
+            .map(Some)
+            .ok_or_else(|| {
+                debug!("Rejecting request with invalid credentials");
+                ApiError::unauthorized()
+            })
+    }
+}
+
+/// Resolves the [Principal] of the request if it carries credentials.
+///
+/// Requests without credentials are passed through, and it's up to the route policy to decide
+/// whether they are allowed. Requests with invalid credentials are rejected right away, and so
+/// are the requests for tenants the caller isn't served for.
+pub async fn authenticate(
+    State(authenticator): State<Authenticator>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let principal = match authenticator.principal(request.headers()) {
+        Ok(Some(principal)) => principal,
+        Ok(None) => return next.run(request).await,
+        Err(e) => return e.into_response(),
+    };
+    if !principal.serves(request.extensions().get::<Tenant>()) {
+        debug!("Rejecting request for a tenant the caller isn't served for");
+        return ApiError::forbidden().into_response();
+    }
+
+    Span::current().record("actor", principal.subject.as_str());
+    request.extensions_mut().insert(principal);
+
+    next.run(request).await
+}
+
+/// Grants the caller the permissions of the roles assigned to the user it acts on behalf of.
+///
+/// Roles are stored in the database of the request, so they're looked up for every request,
+/// and changes to them apply right away. The route policies check the permissions afterwards,
+/// see [authorize](crate::api::policy::authorize).
+pub async fn grant_roles(
+    State(state): State<ApiState>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let Some(principal) = request.extensions_mut().get_mut::<Principal>()
+    else {
+        return next.run(request).await;
+    };
+    let Some(user_id) = principal.user_id else {
+        return next.run(request).await;
+    };
+
+    let roles = match roles_of(&state, user_id).await {
+        Ok(roles) => roles,
+        Err(e) => return e.into_response(),
+    };
+    for permission in roles.iter().flat_map(Role::permissions) {
+        if !principal.permissions.contains(permission) {
+            principal.permissions.push(permission.clone());
+        }
+    }
+
+    next.run(request).await
+}
+
+/// Lists the roles assigned to the user, on the [ReadPool](identify_infrastructure::storage::connection::ReadPool).
+async fn roles_of(
+    state: &ApiState,
+    user_id: Uuid,
+) -> Result<Vec<Role>, ApiError> {
+    let mut unit_of_work = state
+        .retrier
+        .run("transaction.begin", || {
+            SqliteUnitOfWork::begin(state.read_pool.pool(), &state.encryption)
+        })
+        .await?;
+    let mut repository = unit_of_work.roles();
+
+    let roles = list_user_roles(
+        RoleUseCaseDeps::new(&mut repository),
+        ListUserRolesParams { user_id },
+    )
+    .await?;
+
+    Ok(roles)
+}
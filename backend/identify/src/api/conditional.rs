@@ -0,0 +1,226 @@
+//! Conditional requests (RFC 9110, section 13) on versioned resources.
+//!
+//! Resources are tagged with strong entity tags made of their version and the time of their last
+//! change, e.g. `"3-1767225600000000"`, see [EntityTag]. Clients revalidate the copies they have
+//! with `If-None-Match`, and changes require `If-Match` with the tag of the copy they are based
+//! on, so that a client can't overwrite changes it hasn't seen. Resources without versions are
+//! tagged with a hash of their content instead, see [ContentTag].
+
+use std::fmt;
+
+use axum::{
+    extract::FromRequestParts,
+    http::{
+        HeaderMap, HeaderName, HeaderValue, StatusCode,
+        header::{ETAG, IF_MATCH, IF_NONE_MATCH},
+        request::Parts,
+    },
+};
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+
+use crate::api::error::ApiError;
+
+/// Strong entity tag of a version of a resource.
+///
+/// The version alone identifies the state of a resource, since it changes with every change.
+/// The time of the last change keeps the tags of resources that are recreated with the same ID
+/// apart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EntityTag {
+    version: u32,
+    updated_at: i64,
+}
+
+impl EntityTag {
+    pub fn new(version: u32, updated_at: DateTime<Utc>) -> Self {
+        EntityTag {
+            version,
+            updated_at: updated_at.timestamp_micros(),
+        }
+    }
+
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Returns the `ETag` header of the tag.
+    pub fn header(&self) -> [(HeaderName, HeaderValue); 1] {
+        let value = HeaderValue::from_str(&self.to_string())
+            .expect("entity tags are valid header values");
+
+        [(ETAG, value)]
+    }
+
+    /// Parses a strong tag, or returns `None` if it's weak or wasn't issued by this API.
+    fn parse(tag: &str) -> Option<Self> {
+        let (version, updated_at) =
+            tag.strip_prefix('"')?.strip_suffix('"')?.split_once('-')?;
+
+        Some(EntityTag {
+            version: version.parse().ok()?,
+            updated_at: updated_at.parse().ok()?,
+        })
+    }
+}
+
+impl fmt::Display for EntityTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\"{}-{}\"", self.version, self.updated_at)
+    }
+}
+
+/// Strong entity tag of a resource that has no version, e.g. an avatar, made of a hash of its
+/// content.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContentTag(String);
+
+impl ContentTag {
+    pub fn new(content: &[u8]) -> Self {
+        let hash = Sha256::digest(content);
+
+        ContentTag(hex::encode(&hash[..16]))
+    }
+
+    /// Returns the `ETag` header of the tag.
+    pub fn header(&self) -> [(HeaderName, HeaderValue); 1] {
+        let value = HeaderValue::from_str(&self.to_string())
+            .expect("entity tags are valid header values");
+
+        [(ETAG, value)]
+    }
+}
+
+impl fmt::Display for ContentTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\"{}\"", self.0)
+    }
+}
+
+/// Entity tags of the `If-None-Match` header, which is matched if the client already has the
+/// current version of the resource.
+#[derive(Debug, Default)]
+pub struct IfNoneMatch(Option<Vec<String>>);
+
+impl IfNoneMatch {
+    /// Returns whether the header matches the tag, comparing weak tags like strong ones.
+    ///
+    /// A missing header never matches, while `*` matches any tag.
+    pub fn matches(&self, tag: &impl fmt::Display) -> bool {
+        let tag = tag.to_string();
+
+        self.0.as_ref().is_some_and(|tags| {
+            tags.iter().any(|candidate| {
+                candidate == "*"
+                    || candidate.strip_prefix("W/").unwrap_or(candidate) == tag
+            })
+        })
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for IfNoneMatch {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        _state: &S,
+    ) -> Result<Self, Self::Rejection> {
+        let tags = header_tags(&parts.headers, &IF_NONE_MATCH)?;
+
+        Ok(IfNoneMatch(tags))
+    }
+}
+
+/// The `If-Match` header, which changes of resources require.
+///
+/// The request fails with `428 Precondition Required` if the header is missing, and with
+/// `400 Bad Request` if it isn't `*` or a single tag. Tags that weren't issued by this API can
+/// never match, so they fail the request with `412 Precondition Failed` right away.
+#[derive(Debug)]
+pub struct IfMatch(Option<EntityTag>);
+
+impl IfMatch {
+    /// Whether the header is `*`, which any version matches.
+    pub fn is_any(&self) -> bool {
+        self.0.is_none()
+    }
+
+    /// Returns the version the change is based on, or `None` for `*`, given the tag of the
+    /// current version of the resource.
+    ///
+    /// Fails with `412 Precondition Failed` if the tag has the current version, but not the
+    /// time of its last change, since it then wasn't issued for that version. Tags of other
+    /// versions are left to the change, which fails if the resource isn't at that version
+    /// anymore when it's made.
+    pub fn expected_version(
+        &self,
+        current: &EntityTag,
+    ) -> Result<Option<u32>, ApiError> {
+        match self.0 {
+            Some(tag) if tag.version == current.version && tag != *current => {
+                Err(ApiError::new(
+                    StatusCode::PRECONDITION_FAILED,
+                    "The If-Match header doesn't match the ETag of the resource",
+                ))
+            }
+            tag => Ok(tag.map(|tag| tag.version)),
+        }
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for IfMatch {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        _state: &S,
+    ) -> Result<Self, Self::Rejection> {
+        let Some(tags) = header_tags(&parts.headers, &IF_MATCH)? else {
+            return Err(ApiError::new(
+                StatusCode::PRECONDITION_REQUIRED,
+                "The request must have an If-Match header with the ETag of the resource",
+            ));
+        };
+
+        match tags.as_slice() {
+            [any] if any == "*" => Ok(IfMatch(None)),
+            [tag] => match EntityTag::parse(tag) {
+                Some(tag) => Ok(IfMatch(Some(tag))),
+                None => Err(ApiError::new(
+                    StatusCode::PRECONDITION_FAILED,
+                    "The If-Match header doesn't match the ETag of the resource",
+                )),
+            },
+            _ => Err(ApiError::bad_request(
+                "The If-Match header must be '*' or a single ETag",
+            )),
+        }
+    }
+}
+
+/// Splits the comma-separated tags of a header, or returns `None` if it's missing.
+fn header_tags(
+    headers: &HeaderMap,
+    name: &HeaderName,
+) -> Result<Option<Vec<String>>, ApiError> {
+    let mut values = headers.get_all(name).iter().peekable();
+    if values.peek().is_none() {
+        return Ok(None);
+    }
+
+    let mut tags = Vec::new();
+    for value in values {
+        let value = value.to_str().map_err(|_| {
+            ApiError::bad_request(format!("The {name} header isn't valid"))
+        })?;
+        tags.extend(
+            value
+                .split(',')
+                .map(str::trim)
+                .filter(|tag| !tag.is_empty())
+                .map(str::to_owned),
+        );
+    }
+
+    Ok(Some(tags))
+}
@@ -0,0 +1,200 @@
+//! Errors returned by the API as RFC 9457 (formerly RFC 7807) problem documents.
+
+use std::borrow::Cow;
+
+use axum::{
+    Json,
+    http::{HeaderValue, StatusCode, header::CONTENT_TYPE},
+    response::{IntoResponse, Response},
+};
+use identify_application::ApplicationError;
+use identify_domain::DomainError;
+use identify_infrastructure::InfrastructureError;
+use serde::Serialize;
+use tracing::{error, warn};
+use utoipa::ToSchema;
+
+use crate::api::validation::FieldError;
+
+/// Content type of problem documents.
+pub const PROBLEM_CONTENT_TYPE: &str = "application/problem+json";
+
+/// An error response of the API.
+///
+/// Rendered as a problem document with the canonical reason of the status as its title.
+/// Internal details (e.g. database errors) are logged, but never included in the response.
+#[derive(Debug)]
+pub struct ApiError {
+    status: StatusCode,
+    detail: Cow<'static, str>,
+    errors: Vec<String>,
+    field_errors: Vec<FieldError>,
+}
+
+impl ApiError {
+    pub fn new(
+        status: StatusCode,
+        detail: impl Into<Cow<'static, str>>,
+    ) -> Self {
+        ApiError {
+            status,
+            detail: detail.into(),
+            errors: Vec::new(),
+            field_errors: Vec::new(),
+        }
+    }
+
+    pub fn bad_request(detail: impl Into<Cow<'static, str>>) -> Self {
+        ApiError::new(StatusCode::BAD_REQUEST, detail)
+    }
+
+    pub fn unauthorized() -> Self {
+        ApiError::new(
+            StatusCode::UNAUTHORIZED,
+            "The request lacks valid credentials",
+        )
+    }
+
+    pub fn forbidden() -> Self {
+        ApiError::new(
+            StatusCode::FORBIDDEN,
+            "The caller is not allowed to perform this request",
+        )
+    }
+
+    pub fn not_found(detail: impl Into<Cow<'static, str>>) -> Self {
+        ApiError::new(StatusCode::NOT_FOUND, detail)
+    }
+
+    /// Maps an unexpected error to `500 Internal Server Error`.
+    pub fn internal(e: impl std::fmt::Debug) -> Self {
+        error!(error = ?e, "Request failed");
+
+        ApiError::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "The server failed to process the request",
+        )
+    }
+
+    /// Attaches a list of individual problems, e.g. invalid query parameters.
+    pub fn with_errors(mut self, errors: Vec<String>) -> Self {
+        self.errors = errors;
+        self
+    }
+
+    /// Attaches the problems with individual fields of the request body.
+    pub fn with_field_errors(mut self, field_errors: Vec<FieldError>) -> Self {
+        self.field_errors = field_errors;
+        self
+    }
+
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    pub fn detail(&self) -> &str {
+        &self.detail
+    }
+
+    pub fn field_errors(&self) -> &[FieldError] {
+        &self.field_errors
+    }
+}
+
+/// Body of an [ApiError].
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(
+    description = "Problem document (RFC 9457) describing why the request failed."
+)]
+pub struct Problem<'a> {
+    r#type: &'static str,
+    title: &'static str,
+    status: u16,
+    detail: &'a str,
+    /// Individual problems, e.g. invalid query parameters.
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    errors: &'a [String],
+    /// Problems with individual fields of the request body.
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    field_errors: &'a [FieldError],
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let problem = Problem {
+            r#type: "about:blank",
+            title: self.status.canonical_reason().unwrap_or("Unknown Error"),
+            status: self.status.as_u16(),
+            detail: &self.detail,
+            errors: &self.errors,
+            field_errors: &self.field_errors,
+        };
+
+        let mut response = (self.status, Json(problem)).into_response();
+        response.headers_mut().insert(
+            CONTENT_TYPE,
+            HeaderValue::from_static(PROBLEM_CONTENT_TYPE),
+        );
+
+        response
+    }
+}
+
+impl From<ApplicationError> for ApiError {
+    fn from(e: ApplicationError) -> Self {
+        match e {
+            ApplicationError::Domain(
+                e @ DomainError::InvalidStateTransition { .. },
+            ) => ApiError::new(StatusCode::CONFLICT, e.to_string()),
+            e @ ApplicationError::EntityAlreadyExists { .. } => {
+                ApiError::new(StatusCode::CONFLICT, e.to_string())
+            }
+            e @ ApplicationError::EntityNotFound { .. } => {
+                ApiError::not_found(e.to_string())
+            }
+            e @ ApplicationError::ReferenceViolation => {
+                ApiError::new(StatusCode::CONFLICT, e.to_string())
+            }
+            ApplicationError::Domain(e @ DomainError::Validation { .. }) => {
+                ApiError::new(StatusCode::UNPROCESSABLE_ENTITY, e.to_string())
+            }
+            e @ ApplicationError::DeadlineExceeded => {
+                ApiError::new(StatusCode::REQUEST_TIMEOUT, e.to_string())
+            }
+            e @ ApplicationError::Unavailable => {
+                ApiError::new(StatusCode::SERVICE_UNAVAILABLE, e.to_string())
+            }
+            ApplicationError::Forbidden { .. } => ApiError::forbidden(),
+            ApplicationError::ConsentRequired { policies } => ApiError::new(
+                StatusCode::UNAUTHORIZED,
+                "Consent required: the user has to accept the current version of every policy",
+            )
+            .with_errors(policies),
+            e @ ApplicationError::RegistrationRefused(_) => {
+                ApiError::new(StatusCode::FORBIDDEN, e.to_string())
+            }
+            e @ ApplicationError::QuotaExceeded(_) => {
+                ApiError::new(StatusCode::FORBIDDEN, e.to_string())
+            }
+            e @ ApplicationError::StaleVersion { .. } => {
+                ApiError::new(StatusCode::PRECONDITION_FAILED, e.to_string())
+            }
+            e => ApiError::internal(e),
+        }
+    }
+}
+
+impl From<InfrastructureError> for ApiError {
+    fn from(e: InfrastructureError) -> Self {
+        match e {
+            InfrastructureError::DeadlineExceeded => {
+                ApiError::from(ApplicationError::DeadlineExceeded)
+            }
+            InfrastructureError::Unavailable(e) => {
+                warn!(error = %e, "Database is temporarily unavailable");
+                ApiError::from(ApplicationError::Unavailable)
+            }
+            e => ApiError::internal(e),
+        }
+    }
+}
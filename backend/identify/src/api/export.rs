@@ -0,0 +1,195 @@
+//! Exports of whole lists in the formats negotiated with the `Accept` header.
+//!
+//! List endpoints respond with a page of JSON by default. Clients that accept `text/csv` or
+//! `application/x-ndjson` get every item matching the filter instead, streamed as it's read
+//! from the storage, so that exports of any size take little memory.
+
+use std::{convert::Infallible, io};
+
+use axum::{
+    body::{Body, Bytes},
+    extract::FromRequestParts,
+    http::{
+        HeaderValue,
+        header::{ACCEPT, CONTENT_DISPOSITION, CONTENT_TYPE},
+        request::Parts,
+    },
+    response::{IntoResponse, Response},
+};
+use futures_util::{Stream, StreamExt as _, stream};
+use identify_application::ApplicationError;
+use serde::Serialize;
+use serde_json::Value;
+use tracing::error;
+
+pub const CSV_CONTENT_TYPE: &str = "text/csv";
+pub const NDJSON_CONTENT_TYPE: &str = "application/x-ndjson";
+
+/// Formats lists can be exported in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// Comma-separated values (RFC 4180) with a header row.
+    Csv,
+    /// A JSON document per line.
+    Ndjson,
+}
+
+impl ExportFormat {
+    fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+            ExportFormat::Ndjson => NDJSON_CONTENT_TYPE,
+        }
+    }
+
+    fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Ndjson => "ndjson",
+        }
+    }
+}
+
+/// The export format the client prefers, or `None` if it prefers JSON or doesn't say.
+///
+/// Media ranges are tried by their quality, and in the order they're listed among the ones of
+/// the same quality. Ranges the API doesn't produce are skipped, and so are the ones with a
+/// quality of `0`.
+#[derive(Debug)]
+pub struct Accept(pub Option<ExportFormat>);
+
+impl<S: Send + Sync> FromRequestParts<S> for Accept {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        _state: &S,
+    ) -> Result<Self, Self::Rejection> {
+        let mut ranges = parts
+            .headers
+            .get_all(ACCEPT)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .filter_map(media_range)
+            .collect::<Vec<_>>();
+        // The sort is stable, so ranges of the same quality keep their order.
+        ranges.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+
+        let format = ranges
+            .into_iter()
+            .filter(|(_, quality)| *quality > 0.0)
+            .find_map(|(range, _)| match range.as_str() {
+                CSV_CONTENT_TYPE => Some(Some(ExportFormat::Csv)),
+                NDJSON_CONTENT_TYPE => Some(Some(ExportFormat::Ndjson)),
+                "application/json" | "application/*" | "*/*" => Some(None),
+                _ => None,
+            })
+            .flatten();
+
+        Ok(Accept(format))
+    }
+}
+
+/// Parses a media range, e.g. `text/csv;q=0.5`, into its lowercase type and its quality.
+fn media_range(value: &str) -> Option<(String, f32)> {
+    let mut parts = value.split(';');
+    let range = parts.next()?.trim().to_ascii_lowercase();
+    if range.is_empty() {
+        return None;
+    }
+
+    let quality = parts
+        .filter_map(|parameter| parameter.split_once('='))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("q"))
+        .map_or(Some(1.0), |(_, quality)| quality.trim().parse().ok())?;
+
+    Some((range, quality))
+}
+
+/// Streams the items as an attachment named after the file, e.g. `users.csv`.
+///
+/// CSV has a column for each of the fields of the items, in the given order. Failures can't
+/// change the status anymore once the export has started, so they're logged and abort the
+/// response, which clients see as a truncated body.
+pub fn response<T, S>(
+    format: ExportFormat,
+    file: &str,
+    columns: &'static [&'static str],
+    items: S,
+) -> Response
+where
+    T: Serialize,
+    S: Stream<Item = Result<T, ApplicationError>> + Send + 'static,
+{
+    let header = match format {
+        ExportFormat::Csv => Some(Ok(Bytes::from(csv_record(
+            columns.iter().map(|column| (*column).to_owned()),
+        )))),
+        ExportFormat::Ndjson => None,
+    };
+    let lines = items.map(move |item| {
+        let item = item.map_err(|e| {
+            error!(error = %e, "Failed to export an item");
+            io::Error::other("the export failed")
+        })?;
+        let value = serde_json::to_value(item).map_err(io::Error::other)?;
+
+        Ok::<_, io::Error>(Bytes::from(match format {
+            ExportFormat::Csv => csv_record(
+                columns.iter().map(|column| csv_field(&value[*column])),
+            ),
+            ExportFormat::Ndjson => format!("{value}\n"),
+        }))
+    });
+
+    let disposition =
+        format!("attachment; filename=\"{file}.{}\"", format.extension());
+    (
+        [
+            (
+                CONTENT_TYPE,
+                HeaderValue::from_static(format.content_type()),
+            ),
+            (
+                CONTENT_DISPOSITION,
+                HeaderValue::try_from(disposition)
+                    .unwrap_or(HeaderValue::from_static("attachment")),
+            ),
+        ],
+        Body::from_stream(stream::iter(header).chain(lines)),
+    )
+        .into_response()
+}
+
+/// Joins the fields into a line, quoting the ones that contain separators or quotes.
+fn csv_record(fields: impl Iterator<Item = String>) -> String {
+    let mut record = fields
+        .map(|field| {
+            if field.contains([',', '"', '\r', '\n']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+    record.push_str("\r\n");
+
+    record
+}
+
+/// Formats a field of a CSV record. Strings that spreadsheets would evaluate as formulas, e.g.
+/// `=HYPERLINK(...)` as the name of a user, are prefixed with `'` so that they stay text.
+fn csv_field(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(value)
+            if value.starts_with(['=', '+', '-', '@', '\t', '\r']) =>
+        {
+            format!("'{value}")
+        }
+        Value::String(value) => value.clone(),
+        value => value.to_string(),
+    }
+}
@@ -0,0 +1,69 @@
+use std::collections::HashMap;
+
+use async_graphql::dataloader::{DataLoader, Loader};
+use identify_application::{GetUsersParams, UserUseCaseDeps, get_users};
+use identify_infrastructure::{
+    encryption::FieldEncryption,
+    storage::{self, connection::ReadPool, deadline, users::UsersRepository},
+};
+use tracing::Instrument;
+use uuid::Uuid;
+
+use crate::api::{error::ApiError, graphql::users::UserObject};
+
+/// Loads the users referenced by a GraphQL request in batches, so that resolving many of them
+/// takes a single query instead of one per user.
+pub struct UserLoader {
+    pool: ReadPool,
+    encryption: FieldEncryption,
+}
+
+impl UserLoader {
+    /// Creates a loader for a single request.
+    ///
+    /// Batches are loaded in separate tasks, which get the query deadline and the span of the
+    /// request.
+    pub fn for_request(
+        pool: ReadPool,
+        encryption: FieldEncryption,
+    ) -> DataLoader<Self> {
+        let deadline = deadline::current();
+
+        DataLoader::new(UserLoader { pool, encryption }, move |task| {
+            let task = task.in_current_span();
+            match deadline {
+                Some(deadline) => tokio::spawn(deadline::scope(deadline, task)),
+                None => tokio::spawn(task),
+            }
+        })
+    }
+}
+
+impl Loader<Uuid> for UserLoader {
+    type Value = UserObject;
+    type Error = async_graphql::Error;
+
+    async fn load(
+        &self,
+        keys: &[Uuid],
+    ) -> Result<HashMap<Uuid, Self::Value>, Self::Error> {
+        let mut conn =
+            storage::acquire(&self.pool).await.map_err(ApiError::from)?;
+        let mut repository = UsersRepository::new(&mut conn, &self.encryption);
+
+        let users = get_users(
+            UserUseCaseDeps::new(&mut repository),
+            GetUsersParams { ids: keys.to_vec() },
+        )
+        .await
+        .map_err(ApiError::from)?;
+
+        Ok(users
+            .into_iter()
+            .map(|user| {
+                let user = UserObject::from(user);
+                (user.id, user)
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,187 @@
+//! GraphQL API over users, served at `/graphql` next to the REST API.
+//!
+//! The route itself only requires authentication. Permissions are checked by the fields, so
+//! that a single request can't read or change more than its caller is allowed to.
+
+mod loader;
+mod users;
+
+use std::sync::LazyLock;
+
+use async_graphql::{
+    EmptySubscription, ErrorExtensions, Guard, MergedObject, Schema,
+};
+use axum::{
+    Extension, Json,
+    extract::{FromRef, State},
+    routing::post,
+};
+use identify_domain::UsernameStrategy;
+use identify_infrastructure::{
+    encryption::FieldEncryption,
+    storage::{connection::ReadPool, retry::Retrier},
+};
+use sqlx::SqlitePool;
+use utoipa::OpenApi;
+
+use crate::{
+    api::{
+        ApiState, Route, Service,
+        auth::Principal,
+        error::ApiError,
+        graphql::{
+            loader::UserLoader,
+            users::{UserMutation, UserQuery},
+        },
+        policy::RoutePolicy,
+    },
+    identifiers::IdentifierLists,
+};
+
+#[derive(MergedObject, Default)]
+pub struct Query(UserQuery);
+
+#[derive(MergedObject, Default)]
+pub struct Mutation(UserMutation);
+
+pub type ApiSchema = Schema<Query, Mutation, EmptySubscription>;
+
+/// Deepest selections a query can nest, which leaves room for the introspection queries of
+/// GraphQL clients.
+pub const MAX_DEPTH: usize = 16;
+
+/// Most expensive query that is executed. Every field costs 1, and lists of users cost as much
+/// as their fields times the size of their pages, so a full page of users with all their
+/// fields fits, but not many of them aliased in the same query.
+pub const MAX_COMPLEXITY: usize = 2_000;
+
+/// The schema doesn't depend on the state, everything a request needs is passed as its data.
+static SCHEMA: LazyLock<ApiSchema> = LazyLock::new(|| {
+    Schema::build(Query::default(), Mutation::default(), EmptySubscription)
+        .limit_depth(MAX_DEPTH)
+        .limit_complexity(MAX_COMPLEXITY)
+        .finish()
+});
+
+/// Returns the schema, e.g. to print it in the SDL.
+pub fn schema() -> &'static ApiSchema {
+    &SCHEMA
+}
+
+pub struct GraphQlService;
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(graphql_handler),
+    tags((name = "graphql", description = "GraphQL API"))
+)]
+struct GraphQlApi;
+
+impl Service for GraphQlService {
+    fn routes() -> Vec<Route> {
+        vec![Route::new(
+            "/graphql",
+            post(graphql_handler),
+            RoutePolicy::authenticated(),
+        )]
+    }
+
+    fn openapi() -> utoipa::openapi::OpenApi {
+        GraphQlApi::openapi()
+    }
+}
+
+/// What requests pass to the resolvers from the state of the API, as their data.
+#[derive(Clone)]
+struct GraphQlState {
+    pool: SqlitePool,
+    read_pool: ReadPool,
+    encryption: FieldEncryption,
+    retrier: Retrier,
+    username_strategy: UsernameStrategy,
+    identifiers: IdentifierLists,
+}
+
+impl FromRef<ApiState> for GraphQlState {
+    fn from_ref(state: &ApiState) -> Self {
+        GraphQlState {
+            pool: state.pool.clone(),
+            read_pool: state.read_pool.clone(),
+            encryption: state.encryption.clone(),
+            retrier: state.retrier.clone(),
+            username_strategy: state.username_strategy,
+            identifiers: state.identifiers.clone(),
+        }
+    }
+}
+
+/// Requires the caller to have the permission to resolve a field.
+struct Permission(&'static str);
+
+impl Guard for Permission {
+    async fn check(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+    ) -> async_graphql::Result<()> {
+        let principal = ctx.data::<Principal>()?;
+        if principal.has_permission(self.0) {
+            Ok(())
+        } else {
+            Err(ApiError::forbidden().into())
+        }
+    }
+}
+
+/// Errors are reported with the same status and details as in the REST API, as extensions of
+/// the GraphQL error.
+impl From<ApiError> for async_graphql::Error {
+    fn from(e: ApiError) -> Self {
+        let status = e.status();
+        let field_errors = async_graphql::to_value(e.field_errors());
+
+        async_graphql::Error::new(e.detail()).extend_with(|_, extensions| {
+            extensions.set("status", status.as_u16());
+            if !e.field_errors().is_empty()
+                && let Ok(field_errors) = field_errors
+            {
+                extensions.set("fieldErrors", field_errors);
+            }
+        })
+    }
+}
+
+/// Executes a GraphQL request.
+///
+/// Errors of the individual fields are reported in the response body, which always has the
+/// `200 OK` status.
+#[utoipa::path(
+    post,
+    path = "/graphql",
+    operation_id = "graphql",
+    tag = "graphql",
+    request_body(content = Object, description = "GraphQL request with the query, its variables and the operation name"),
+    responses(
+        (status = OK, description = "GraphQL response with the data and the errors", body = Object),
+    ),
+    security(("api_key" = [])),
+)]
+async fn graphql_handler(
+    State(state): State<GraphQlState>,
+    Extension(principal): Extension<Principal>,
+    Json(request): Json<async_graphql::Request>,
+) -> Json<async_graphql::Response> {
+    let request = request
+        .data(UserLoader::for_request(
+            state.read_pool.clone(),
+            state.encryption.clone(),
+        ))
+        .data(state.pool)
+        .data(state.encryption)
+        .data(state.read_pool)
+        .data(state.retrier)
+        .data(state.username_strategy)
+        .data(state.identifiers.policy())
+        .data(principal);
+
+    Json(SCHEMA.execute(request).await)
+}
@@ -0,0 +1,291 @@
+use std::sync::Arc;
+
+use async_graphql::{
+    Context, InputObject, Object, Result, SimpleObject, dataloader::DataLoader,
+};
+use chrono::{DateTime, Utc};
+use identify_application::{
+    CreateUserParams, ListQuery, ListUsersParams, MAX_PAGE_SIZE, PageRequest,
+    UpdateUserParams, UserLifecycleUseCaseDeps, UserUseCaseDeps, create_user,
+    list_users, update_user, user_contracts::UserFilter,
+};
+use identify_domain::{
+    IdentifierPolicy, Locale, NewUserAttrs, QuotaPolicy, User, Username,
+    UsernameStrategy,
+};
+use identify_infrastructure::{
+    encryption::FieldEncryption,
+    storage::{
+        connection::ReadPool, retry::Retrier, unit_of_work::SqliteUnitOfWork,
+        user_summaries::UserSummariesReader,
+    },
+};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::api::{
+    error::ApiError,
+    graphql::{Permission, loader::UserLoader},
+    services::users::{MAX_EMAIL_LENGTH, MAX_NAME_LENGTH},
+    validation::{self, Validate, Validator},
+};
+
+#[derive(Debug, Clone, SimpleObject)]
+#[graphql(name = "User")]
+pub struct UserObject {
+    pub id: Uuid,
+    pub email: String,
+    pub username: String,
+    pub first_name: String,
+    pub last_name: Option<String>,
+    /// BCP 47 language tag emails to the user are translated to, e.g. `pt-BR`.
+    pub locale: Option<String>,
+    /// IANA time zone times in emails to the user are shown in, e.g. `Europe/Berlin`.
+    pub timezone: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<User> for UserObject {
+    fn from(value: User) -> Self {
+        let attrs = value.into_attributes();
+
+        UserObject {
+            id: attrs.id,
+            email: attrs.email,
+            username: attrs.username,
+            first_name: attrs.first_name,
+            last_name: attrs.last_name,
+            locale: attrs.locale,
+            timezone: attrs.timezone,
+            created_at: attrs.created_at,
+            updated_at: attrs.updated_at,
+        }
+    }
+}
+
+/// A single page of users.
+#[derive(Debug, SimpleObject)]
+pub struct UserPage {
+    pub items: Vec<UserObject>,
+    /// 1-based number of the page.
+    pub page: u32,
+    pub page_size: u32,
+    /// Number of users matching the filter across all pages.
+    pub total_items: u64,
+    pub total_pages: u64,
+}
+
+#[derive(Debug, InputObject)]
+pub struct CreateUserInput {
+    pub email: String,
+    /// Generated if it's not set.
+    pub username: Option<String>,
+    pub first_name: String,
+    pub last_name: Option<String>,
+}
+
+impl Validate for CreateUserInput {
+    fn validate(&self, validator: &mut Validator) {
+        validator
+            .field("email", Some(&self.email))
+            .required()
+            .max_length(MAX_EMAIL_LENGTH)
+            .email();
+        validator
+            .field("username", self.username.as_deref())
+            .min_length(Username::MIN_LENGTH)
+            .max_length(Username::MAX_LENGTH)
+            .username();
+        validator
+            .field("firstName", Some(&self.first_name))
+            .required()
+            .max_length(MAX_NAME_LENGTH);
+        validator
+            .field("lastName", self.last_name.as_deref())
+            .max_length(MAX_NAME_LENGTH);
+    }
+}
+
+#[derive(Debug, InputObject)]
+pub struct UpdateUserInput {
+    pub first_name: String,
+    pub last_name: Option<String>,
+    pub locale: Option<String>,
+    pub timezone: Option<String>,
+}
+
+impl Validate for UpdateUserInput {
+    fn validate(&self, validator: &mut Validator) {
+        validator
+            .field("firstName", Some(&self.first_name))
+            .required()
+            .max_length(MAX_NAME_LENGTH);
+        validator
+            .field("lastName", self.last_name.as_deref())
+            .max_length(MAX_NAME_LENGTH);
+        validator
+            .field("locale", self.locale.as_deref())
+            .max_length(Locale::MAX_LENGTH)
+            .locale();
+        validator
+            .field("timezone", self.timezone.as_deref())
+            .timezone();
+    }
+}
+
+#[derive(Default)]
+pub struct UserQuery;
+
+#[Object]
+impl UserQuery {
+    /// Returns a user, or `null` if they don't exist.
+    #[graphql(guard = "Permission(\"users:read\")")]
+    async fn user(
+        &self,
+        ctx: &Context<'_>,
+        id: Uuid,
+    ) -> Result<Option<UserObject>> {
+        ctx.data::<DataLoader<UserLoader>>()?.load_one(id).await
+    }
+
+    /// Lists users, ordered by their IDs.
+    #[graphql(
+        guard = "Permission(\"users:read\")",
+        complexity = "page_size as usize * child_complexity"
+    )]
+    async fn users(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default = 1)] page: u32,
+        #[graphql(default_with = "PageRequest::default().size")] page_size: u32,
+        email: Option<String>,
+    ) -> Result<UserPage> {
+        if page == 0 {
+            return Err(ApiError::bad_request("page must be at least 1").into());
+        }
+        if page_size == 0 || page_size > MAX_PAGE_SIZE {
+            return Err(ApiError::bad_request(format!(
+                "pageSize must be between 1 and {MAX_PAGE_SIZE}"
+            ))
+            .into());
+        }
+
+        let mut reader = UserSummariesReader::new(
+            ctx.data::<ReadPool>()?.clone(),
+            ctx.data::<FieldEncryption>()?.clone(),
+        );
+
+        let users = list_users(
+            UserUseCaseDeps::new(&mut reader),
+            ListUsersParams {
+                query: ListQuery {
+                    filter: UserFilter {
+                        email,
+                        ..Default::default()
+                    },
+                    sort: Vec::new(),
+                    page: PageRequest {
+                        number: page,
+                        size: page_size,
+                    },
+                },
+            },
+        )
+        .await
+        .map_err(ApiError::from)?;
+
+        Ok(UserPage {
+            total_pages: users.total_pages(),
+            page: users.page.number,
+            page_size: users.page.size,
+            total_items: users.total_items,
+            items: users.items.into_iter().map(UserObject::from).collect(),
+        })
+    }
+}
+
+#[derive(Default)]
+pub struct UserMutation;
+
+#[Object]
+impl UserMutation {
+    /// Creates a user.
+    #[graphql(guard = "Permission(\"users:write\")")]
+    async fn create_user(
+        &self,
+        ctx: &Context<'_>,
+        input: CreateUserInput,
+    ) -> Result<UserObject> {
+        validation::check(&input)?;
+        let pool = ctx.data::<SqlitePool>()?;
+        let encryption = ctx.data::<FieldEncryption>()?;
+        let username_strategy = *ctx.data::<UsernameStrategy>()?;
+        let identifier_policy = ctx.data::<Arc<IdentifierPolicy>>()?;
+
+        let user = ctx
+            .data::<Retrier>()?
+            .run("create_user", || async {
+                let unit_of_work =
+                    SqliteUnitOfWork::begin(pool, encryption).await?;
+
+                create_user(
+                    UserLifecycleUseCaseDeps::new(unit_of_work),
+                    CreateUserParams {
+                        user_attrs: NewUserAttrs {
+                            email: input.email.clone(),
+                            first_name: input.first_name.clone(),
+                            last_name: input.last_name.clone(),
+                        },
+                        username: input.username.clone(),
+                        username_strategy,
+                        identifier_policy: identifier_policy.clone(),
+                        organization_id: None,
+                        quota_policy: QuotaPolicy::default(),
+                    },
+                )
+                .await
+            })
+            .await
+            .map_err(ApiError::from)?;
+
+        Ok(UserObject::from(user))
+    }
+
+    /// Changes the name of a user.
+    #[graphql(guard = "Permission(\"users:write\")")]
+    async fn update_user(
+        &self,
+        ctx: &Context<'_>,
+        id: Uuid,
+        input: UpdateUserInput,
+    ) -> Result<UserObject> {
+        validation::check(&input)?;
+        let pool = ctx.data::<SqlitePool>()?;
+        let encryption = ctx.data::<FieldEncryption>()?;
+
+        let user = ctx
+            .data::<Retrier>()?
+            .run("update_user", || async {
+                let unit_of_work =
+                    SqliteUnitOfWork::begin(pool, encryption).await?;
+
+                update_user(
+                    UserLifecycleUseCaseDeps::new(unit_of_work),
+                    UpdateUserParams {
+                        id,
+                        first_name: input.first_name.clone(),
+                        last_name: input.last_name.clone(),
+                        locale: input.locale.clone(),
+                        timezone: input.timezone.clone(),
+                        expected_version: None,
+                    },
+                )
+                .await
+            })
+            .await
+            .map_err(ApiError::from)?;
+
+        Ok(UserObject::from(user))
+    }
+}
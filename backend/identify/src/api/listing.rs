@@ -0,0 +1,368 @@
+//! Query parameters and responses shared by all list endpoints.
+//!
+//! Supported query parameters:
+//!
+//! - `page[number]` - 1-based number of the page.
+//! - `page[size]` - number of items on the page (up to [MAX_PAGE_SIZE]).
+//! - `sort` - comma-separated list of fields, prefixed with `-` for descending order,
+//!   e.g. `sort=-created_at,email`.
+//! - `filter[<field>]` - endpoint-specific filters.
+
+use std::collections::HashSet;
+
+use axum::{
+    extract::{FromRequestParts, Query},
+    http::request::Parts,
+};
+use identify_application::{
+    ListQuery, MAX_PAGE_SIZE, PageRequest, Paginated, Sort, SortDirection,
+};
+use serde::Serialize;
+use utoipa::{
+    IntoParams, PartialSchema, ToSchema,
+    openapi::{
+        RefOr, Required, Schema,
+        path::{Parameter, ParameterBuilder, ParameterIn},
+    },
+};
+
+use crate::api::error::ApiError;
+
+/// A field that list results can be sorted by.
+pub trait SortField: Sized {
+    /// Names of all the fields as they appear in the `sort` parameter.
+    const NAMES: &'static [&'static str];
+
+    fn from_name(name: &str) -> Option<Self>;
+}
+
+/// A filter that can be built from `filter[<field>]` parameters.
+pub trait Filter: Default {
+    /// Names of all the supported filter fields.
+    const FIELDS: &'static [&'static str];
+
+    /// Applies the value of a single filter field.
+    ///
+    /// Returns an error message if the value is invalid. Unknown fields are rejected before
+    /// this method is called.
+    fn set(&mut self, field: &str, value: String) -> Result<(), String>;
+
+    /// Checks the whole filter after all fields have been set.
+    fn validate(&self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// Extractor that parses pagination, sorting and filtering query parameters.
+#[derive(Debug)]
+pub struct ListParams<F, S>(pub ListQuery<F, S>);
+
+impl<F, S, St> FromRequestParts<St> for ListParams<F, S>
+where
+    F: Filter + Send,
+    S: SortField + Send,
+    St: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        _state: &St,
+    ) -> Result<Self, Self::Rejection> {
+        let Query(params) =
+            Query::<Vec<(String, String)>>::try_from_uri(&parts.uri)
+                .map_err(|e| invalid_list_params(vec![e.body_text()]))?;
+
+        let mut errors = Vec::new();
+        let mut query = ListQuery::<F, S> {
+            filter: F::default(),
+            sort: Vec::new(),
+            page: PageRequest::default(),
+        };
+
+        for (key, value) in params {
+            if let Err(e) = apply(&mut query, &key, value) {
+                errors.push(e);
+            }
+        }
+
+        if let Err(e) = query.filter.validate() {
+            errors.push(e);
+        }
+
+        if errors.is_empty() {
+            Ok(ListParams(query))
+        } else {
+            Err(invalid_list_params(errors))
+        }
+    }
+}
+
+impl<F: Filter, S: SortField> IntoParams for ListParams<F, S> {
+    fn into_params(
+        _parameter_in_provider: impl Fn() -> Option<ParameterIn>,
+    ) -> Vec<Parameter> {
+        let parameter =
+            |name: String, description: String, schema: RefOr<Schema>| {
+                ParameterBuilder::new()
+                    .name(name)
+                    .parameter_in(ParameterIn::Query)
+                    .required(Required::False)
+                    .description(Some(description))
+                    .schema(Some(schema))
+                    .build()
+            };
+
+        let mut params = vec![
+            parameter(
+                "page[number]".to_owned(),
+                "1-based number of the page".to_owned(),
+                u32::schema(),
+            ),
+            parameter(
+                "page[size]".to_owned(),
+                format!("Number of items on the page, up to {MAX_PAGE_SIZE}"),
+                u32::schema(),
+            ),
+            parameter(
+                "sort".to_owned(),
+                format!(
+                    "Comma-separated list of fields prefixed with `-` for descending order, \
+                     any of: {}",
+                    S::NAMES.join(", ")
+                ),
+                String::schema(),
+            ),
+        ];
+        params.extend(F::FIELDS.iter().map(|field| {
+            parameter(
+                format!("filter[{field}]"),
+                format!("Filter by {field}"),
+                String::schema(),
+            )
+        }));
+
+        params
+    }
+}
+
+/// Extractor of the parameters of search endpoints: the searched text `q` and the same
+/// pagination parameters as list endpoints have. Search results are always ordered by their
+/// relevance, so they can't be sorted.
+#[derive(Debug)]
+pub struct SearchParams {
+    pub text: String,
+    pub page: PageRequest,
+}
+
+impl SearchParams {
+    /// Longest searched text.
+    pub const MAX_TEXT_LENGTH: usize = 100;
+}
+
+impl<St: Send + Sync> FromRequestParts<St> for SearchParams {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        _state: &St,
+    ) -> Result<Self, Self::Rejection> {
+        let Query(params) =
+            Query::<Vec<(String, String)>>::try_from_uri(&parts.uri)
+                .map_err(|e| invalid_list_params(vec![e.body_text()]))?;
+
+        let mut errors = Vec::new();
+        let mut text = None;
+        let mut page = PageRequest::default();
+
+        for (key, value) in params {
+            let applied = match key.as_str() {
+                "q" => {
+                    text = Some(value);
+                    Ok(())
+                }
+                "page[number]" => {
+                    parse_page_number(&value).map(|number| page.number = number)
+                }
+                "page[size]" => {
+                    parse_page_size(&value).map(|size| page.size = size)
+                }
+                _ => Err(format!("unknown query parameter '{key}'")),
+            };
+            if let Err(e) = applied {
+                errors.push(e);
+            }
+        }
+
+        let text = text.map(|text| text.trim().to_owned()).unwrap_or_default();
+        if text.is_empty() {
+            errors.push("q is required".to_owned());
+        } else if text.chars().count() > Self::MAX_TEXT_LENGTH {
+            errors.push(format!(
+                "q must be at most {} characters long",
+                Self::MAX_TEXT_LENGTH
+            ));
+        }
+
+        if errors.is_empty() {
+            Ok(SearchParams { text, page })
+        } else {
+            Err(invalid_list_params(errors))
+        }
+    }
+}
+
+impl IntoParams for SearchParams {
+    fn into_params(
+        _parameter_in_provider: impl Fn() -> Option<ParameterIn>,
+    ) -> Vec<Parameter> {
+        vec![
+            ParameterBuilder::new()
+                .name("q")
+                .parameter_in(ParameterIn::Query)
+                .required(Required::True)
+                .description(Some(
+                    "Words to search for, matched by their prefixes",
+                ))
+                .schema(Some(String::schema()))
+                .build(),
+            ParameterBuilder::new()
+                .name("page[number]")
+                .parameter_in(ParameterIn::Query)
+                .required(Required::False)
+                .description(Some("1-based number of the page"))
+                .schema(Some(u32::schema()))
+                .build(),
+            ParameterBuilder::new()
+                .name("page[size]")
+                .parameter_in(ParameterIn::Query)
+                .required(Required::False)
+                .description(Some(format!(
+                    "Number of items on the page, up to {MAX_PAGE_SIZE}"
+                )))
+                .schema(Some(u32::schema()))
+                .build(),
+        ]
+    }
+}
+
+/// Rejection returned when the list query parameters are invalid.
+fn invalid_list_params(errors: Vec<String>) -> ApiError {
+    ApiError::bad_request("Invalid query parameters").with_errors(errors)
+}
+
+fn apply<F: Filter, S: SortField>(
+    query: &mut ListQuery<F, S>,
+    key: &str,
+    value: String,
+) -> Result<(), String> {
+    match key {
+        "page[number]" => query.page.number = parse_page_number(&value)?,
+        "page[size]" => query.page.size = parse_page_size(&value)?,
+        "sort" => query.sort = parse_sort(&value)?,
+        _ => {
+            let field = key
+                .strip_prefix("filter[")
+                .and_then(|rest| rest.strip_suffix(']'))
+                .ok_or_else(|| format!("unknown query parameter '{key}'"))?;
+
+            if !F::FIELDS.contains(&field) {
+                return Err(format!(
+                    "unknown filter '{field}', expected one of: {}",
+                    F::FIELDS.join(", ")
+                ));
+            }
+
+            query
+                .filter
+                .set(field, value)
+                .map_err(|e| format!("filter[{field}]: {e}"))?;
+        }
+    }
+
+    Ok(())
+}
+
+fn parse_page_number(value: &str) -> Result<u32, String> {
+    value
+        .parse()
+        .ok()
+        .filter(|number| *number > 0)
+        .ok_or_else(|| {
+            format!("page[number] must be a positive integer, got '{value}'")
+        })
+}
+
+fn parse_page_size(value: &str) -> Result<u32, String> {
+    value
+        .parse()
+        .ok()
+        .filter(|size| (1..=MAX_PAGE_SIZE).contains(size))
+        .ok_or_else(|| {
+            format!(
+                "page[size] must be an integer between 1 and {MAX_PAGE_SIZE}, got '{value}'"
+            )
+        })
+}
+
+fn parse_sort<S: SortField>(value: &str) -> Result<Vec<Sort<S>>, String> {
+    let mut seen = HashSet::new();
+
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let (name, direction) = match part.strip_prefix('-') {
+                Some(name) => (name, SortDirection::Descending),
+                None => (part, SortDirection::Ascending),
+            };
+
+            let field = S::from_name(name).ok_or_else(|| {
+                format!(
+                    "can't sort by '{name}', expected one of: {}",
+                    S::NAMES.join(", ")
+                )
+            })?;
+
+            if !seen.insert(name) {
+                return Err(format!("'{name}' is used in sort more than once"));
+            }
+
+            Ok(Sort { field, direction })
+        })
+        .collect()
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PageResponse {
+    pub number: u32,
+    pub size: u32,
+    pub total_items: u64,
+    pub total_pages: u64,
+}
+
+/// Response of all list endpoints.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ListResponse<T> {
+    pub items: Vec<T>,
+    pub page: PageResponse,
+}
+
+/// Items are moved into their DTOs, so that mapping large pages doesn't copy them.
+impl<E, T> From<Paginated<E>> for ListResponse<T>
+where
+    T: From<E>,
+{
+    fn from(value: Paginated<E>) -> Self {
+        ListResponse {
+            page: PageResponse {
+                number: value.page.number,
+                size: value.page.size,
+                total_items: value.total_items,
+                total_pages: value.total_pages(),
+            },
+            items: value.items.into_iter().map(T::from).collect(),
+        }
+    }
+}
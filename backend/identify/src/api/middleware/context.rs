@@ -0,0 +1,100 @@
+//! Request context that is attached to everything logged while handling a request.
+
+use axum::{
+    extract::{MatchedPath, Request},
+    http::{HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use tracing::{Instrument, field, info_span};
+use uuid::Uuid;
+
+use crate::latency::REQUEST_SPAN;
+
+/// Header that carries the ID of the request.
+///
+/// IDs passed by callers are reused, so that requests can be correlated across services.
+pub const REQUEST_ID_HEADER: HeaderName =
+    HeaderName::from_static("x-request-id");
+
+/// W3C Trace Context header that carries the ID of the distributed trace.
+pub const TRACEPARENT_HEADER: HeaderName =
+    HeaderName::from_static("traceparent");
+
+/// Longest request ID accepted from callers.
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+/// ID of the request that is being handled.
+#[derive(Debug, Clone)]
+pub struct RequestId(pub String);
+
+/// Wraps the request into a span with its ID, trace ID, route and the actor that performs it.
+///
+/// The trace ID is taken from the [TRACEPARENT_HEADER], so that logs of the request can be
+/// matched with the other services that took part in the trace. Requests that don't carry it
+/// start a new trace.
+///
+/// All logs and errors produced while handling the request are reported within this span,
+/// so they can be traced back to the request without correlating separate log lines. The
+/// actor is recorded later by the authentication middleware.
+pub async fn propagate_context(mut request: Request, next: Next) -> Response {
+    let request_id = request
+        .headers()
+        .get(&REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|id| is_valid_request_id(id))
+        .map(str::to_owned)
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+
+    let trace_id = request
+        .headers()
+        .get(&TRACEPARENT_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(parse_trace_id)
+        .unwrap_or_else(|| Uuid::new_v4().simple().to_string());
+
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_owned());
+
+    let span = info_span!(
+        REQUEST_SPAN,
+        request_id,
+        trace_id,
+        method = %request.method(),
+        route,
+        actor = field::Empty,
+    );
+
+    request
+        .extensions_mut()
+        .insert(RequestId(request_id.clone()));
+
+    let mut response = next.run(request).instrument(span).await;
+
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+
+    response
+}
+
+fn is_valid_request_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= MAX_REQUEST_ID_LEN
+        && id.bytes().all(|b| b.is_ascii_graphic())
+}
+
+/// Extracts the trace ID from a `traceparent` header, e.g.
+/// `00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01`.
+fn parse_trace_id(traceparent: &str) -> Option<String> {
+    let mut parts = traceparent.trim().split('-');
+    let (_version, trace_id) = (parts.next()?, parts.next()?);
+
+    let is_valid = trace_id.len() == 32
+        && trace_id.bytes().all(|b| b.is_ascii_hexdigit())
+        && trace_id.bytes().any(|b| b != b'0');
+
+    is_valid.then(|| trace_id.to_ascii_lowercase())
+}
@@ -0,0 +1,154 @@
+//! Latency budget of requests broken down by the stage of the request handling.
+//!
+//! Every route has a budget, see [RoutePolicy::latency_budget]. Requests that exceed it are
+//! still answered, but logged with their breakdown and counted by
+//! [HTTP_LATENCY_BUDGET_EXCEEDED_METRIC], so that the stage that got slower can be found.
+
+use std::time::{Duration, Instant};
+
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::HeaderValue,
+    middleware::Next,
+    response::Response,
+};
+use metrics::{counter, histogram};
+use tracing::{Instrument, info_span, warn};
+
+use crate::{
+    api::policy::RoutePolicy,
+    latency::{Breakdown, HANDLER_SPAN, Stage},
+    metrics::{
+        HTTP_LATENCY_BUDGET_EXCEEDED_METRIC, HTTP_REQUEST_STAGE_DURATION_METRIC,
+    },
+};
+
+/// Header with the breakdown of the request latency, understood by browser dev tools.
+pub const SERVER_TIMING_HEADER: &str = "server-timing";
+
+/// Whether to expose the latency breakdown of every request in the [SERVER_TIMING_HEADER].
+///
+/// The breakdown tells a lot about the internals, so it's meant for development only.
+#[derive(Debug, Clone, Copy)]
+pub struct ServerTiming(pub bool);
+
+/// Latency budget of the route that handled a request, attached to its response by
+/// [attach_budget].
+#[derive(Debug, Clone, Copy)]
+pub struct LatencyBudget(pub Duration);
+
+/// Attaches the latency budget of the route policy to the response, so that [track_latency]
+/// can check it once the whole request has been handled.
+pub async fn attach_budget(
+    State(policy): State<RoutePolicy>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let mut response = next.run(request).await;
+    response
+        .extensions_mut()
+        .insert(LatencyBudget(policy.latency_budget));
+
+    response
+}
+
+/// Records how much time the request spent in every [Stage], and reports requests that exceed
+/// the [LatencyBudget] of their route.
+///
+/// Must run within the request span, so that the [LatencyLayer](crate::latency::LatencyLayer)
+/// has somewhere to collect the breakdown.
+pub async fn track_latency(
+    State(ServerTiming(expose)): State<ServerTiming>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let started_at = Instant::now();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_owned());
+
+    let mut response = next.run(request).await;
+
+    let total = started_at.elapsed();
+    let budget = response
+        .extensions()
+        .get::<LatencyBudget>()
+        .map(|LatencyBudget(budget)| *budget);
+    let breakdown = Breakdown::current();
+    let exceeded = budget.is_some_and(|budget| total > budget);
+    if let (Some(budget), true) = (budget, exceeded) {
+        let route = route.clone().unwrap_or_default();
+        let stage =
+            |stage| breakdown.map(|b| b.get(stage, total).as_secs_f64());
+        warn!(
+            route,
+            total_s = total.as_secs_f64(),
+            budget_s = budget.as_secs_f64(),
+            middleware_s = stage(Stage::Middleware),
+            handler_s = stage(Stage::Handler),
+            use_case_s = stage(Stage::UseCase),
+            repository_s = stage(Stage::Repository),
+            "Request exceeded the latency budget of its route"
+        );
+        counter!(HTTP_LATENCY_BUDGET_EXCEEDED_METRIC, "route" => route)
+            .increment(1);
+    }
+    let Some(breakdown) = breakdown else {
+        return response;
+    };
+
+    if let Some(route) = route {
+        for stage in Stage::ALL {
+            histogram!(
+                HTTP_REQUEST_STAGE_DURATION_METRIC,
+                "route" => route.clone(),
+                "stage" => stage.as_str(),
+            )
+            .record(breakdown.get(stage, total));
+        }
+    }
+
+    if expose
+        && let Ok(value) =
+            HeaderValue::from_str(&server_timing(&breakdown, total, budget))
+    {
+        response.headers_mut().insert(SERVER_TIMING_HEADER, value);
+    }
+
+    response
+}
+
+/// Runs the handler within the span its stage is measured by.
+pub async fn handler_span(request: Request, next: Next) -> Response {
+    next.run(request).instrument(info_span!(HANDLER_SPAN)).await
+}
+
+/// Formats the breakdown as `Server-Timing` metrics, followed by the budget, which is described
+/// as `exceeded` if the request took longer.
+fn server_timing(
+    breakdown: &Breakdown,
+    total: Duration,
+    budget: Option<Duration>,
+) -> String {
+    let metric = |name: &str, duration: Duration| {
+        format!("{name};dur={:.3}", duration.as_secs_f64() * 1000.0)
+    };
+
+    let mut metrics = Stage::ALL
+        .iter()
+        .map(|stage| {
+            metric(stage.server_timing_name(), breakdown.get(*stage, total))
+        })
+        .chain([metric("total", total)])
+        .collect::<Vec<_>>();
+    if let Some(budget) = budget {
+        let mut entry = metric("budget", budget);
+        if total > budget {
+            entry.push_str(";desc=\"exceeded\"");
+        }
+        metrics.push(entry);
+    }
+
+    metrics.join(", ")
+}
@@ -0,0 +1,39 @@
+use std::time::Instant;
+
+use axum::{
+    extract::{MatchedPath, Request},
+    middleware::Next,
+    response::Response,
+};
+use metrics::{counter, histogram};
+
+use crate::metrics::{HTTP_REQUEST_DURATION_METRIC, HTTP_REQUESTS_METRIC};
+
+/// Label used for requests that didn't match any route, so that random paths don't blow up
+/// the number of series.
+const UNMATCHED_ROUTE: &str = "unmatched";
+
+/// Records the number and latency of requests per route.
+pub async fn track_requests(request: Request, next: Next) -> Response {
+    let started_at = Instant::now();
+
+    let method = request.method().to_string();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map_or(UNMATCHED_ROUTE, MatchedPath::as_str)
+        .to_owned();
+
+    let response = next.run(request).await;
+
+    let labels = [
+        ("method", method),
+        ("route", route),
+        ("status", response.status().as_u16().to_string()),
+    ];
+    counter!(HTTP_REQUESTS_METRIC, &labels).increment(1);
+    histogram!(HTTP_REQUEST_DURATION_METRIC, &labels)
+        .record(started_at.elapsed());
+
+    response
+}
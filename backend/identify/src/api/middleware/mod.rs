@@ -0,0 +1,6 @@
+pub mod context;
+pub mod latency;
+pub mod metrics;
+pub mod panic;
+pub mod sdk;
+pub mod security;
@@ -0,0 +1,35 @@
+//! Boundary that keeps handler panics from tearing down the connection.
+
+use std::any::Any;
+
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use metrics::counter;
+use tracing::error;
+
+use crate::{api::error::ApiError, metrics::HTTP_PANICS_METRIC};
+
+/// Converts a handler panic into `500 Internal Server Error`.
+///
+/// The panic message is logged (and reported, if error reporting is enabled) but never
+/// returned to the caller. Everything owned by the handler, including its transaction, is
+/// dropped while unwinding, so the transaction is rolled back and its connection goes back to
+/// the pool.
+pub fn handle_panic(panic: Box<dyn Any + Send + 'static>) -> Response {
+    let message = panic
+        .downcast_ref::<String>()
+        .map(String::as_str)
+        .or_else(|| panic.downcast_ref::<&str>().copied())
+        .unwrap_or("unknown panic");
+
+    error!(panic = message, "Request handler panicked");
+    counter!(HTTP_PANICS_METRIC).increment(1);
+
+    ApiError::new(
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "The server failed to process the request",
+    )
+    .into_response()
+}
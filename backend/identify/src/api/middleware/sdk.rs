@@ -0,0 +1,258 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
+
+use axum::{
+    Json,
+    extract::{Request, State},
+    http::{HeaderMap, HeaderName, StatusCode, header::USER_AGENT},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use eyre::{Result, WrapErr, eyre};
+use semver::Version;
+use serde::Serialize;
+use tracing::{debug, warn};
+use utoipa::ToSchema;
+
+/// Header used by the official client SDKs to identify themselves.
+///
+/// The expected format is `<sdk name>/<semver version>`, e.g. `identify-js/1.4.2`.
+pub const SDK_HEADER: HeaderName = HeaderName::from_static("x-identify-sdk");
+
+/// Label used in metrics for requests that don't carry the SDK header.
+const UNKNOWN_LABEL: &str = "unknown";
+/// Label used in metrics for requests with a malformed SDK header.
+const INVALID_LABEL: &str = "invalid";
+/// Label used in metrics for SDKs that the policy doesn't know, whatever their version.
+const OTHER_LABEL: &str = "other";
+
+/// Client SDK information parsed from the [SDK_HEADER].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SdkVersion {
+    pub name: String,
+    pub version: Version,
+}
+
+impl FromStr for SdkVersion {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, version) = s
+            .trim()
+            .split_once('/')
+            .ok_or_else(|| eyre!("expected '<name>/<version>', got '{s}'"))?;
+
+        if name.is_empty() {
+            return Err(eyre!("SDK name is empty"));
+        }
+
+        let version = Version::parse(version)
+            .wrap_err_with(|| format!("invalid version of SDK '{name}'"))?;
+
+        Ok(SdkVersion {
+            name: name.to_owned(),
+            version,
+        })
+    }
+}
+
+impl fmt::Display for SdkVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.name, self.version)
+    }
+}
+
+/// Minimum SDK versions that are still allowed to call the API, and the SDKs whose adoption is
+/// tracked by name.
+///
+/// SDKs that are not mentioned in the policy are always allowed. Their adoption is tracked
+/// under a single `other` label, so that made up names can't add labels to the metrics.
+#[derive(Debug, Clone, Default)]
+pub struct SdkPolicy {
+    minimum_versions: HashMap<String, Version>,
+    known_names: HashSet<String>,
+}
+
+impl SdkPolicy {
+    pub fn new(minimum_versions: HashMap<String, Version>) -> Self {
+        SdkPolicy {
+            minimum_versions,
+            known_names: HashSet::new(),
+        }
+    }
+
+    /// Adds SDKs whose adoption is tracked by name, besides the ones with minimum versions.
+    pub fn with_known_names(
+        mut self,
+        names: impl IntoIterator<Item = String>,
+    ) -> Self {
+        self.known_names.extend(names);
+        self
+    }
+
+    /// Whether the adoption of the SDK is tracked by its name.
+    pub fn is_known(&self, name: &str) -> bool {
+        self.known_names.contains(name)
+            || self.minimum_versions.contains_key(name)
+    }
+
+    /// Returns the minimum supported version if the provided SDK version is deprecated.
+    pub fn required_upgrade(&self, sdk: &SdkVersion) -> Option<&Version> {
+        self.minimum_versions
+            .get(&sdk.name)
+            .filter(|minimum| sdk.version < **minimum)
+    }
+}
+
+/// A single row of the SDK adoption report.
+#[derive(Debug, Serialize, ToSchema, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SdkAdoption {
+    pub sdk: String,
+    pub version: String,
+    pub requests: u64,
+}
+
+/// Collects per-SDK request counters and enforces the [SdkPolicy].
+///
+/// The counters are kept by the names of the known SDKs and the major and minor versions,
+/// e.g. `identify-js` and `1.4` for `identify-js/1.4.2`, so that their number stays bounded.
+#[derive(Debug, Clone, Default)]
+pub struct SdkTracker {
+    policy: Arc<SdkPolicy>,
+    counters: Arc<Mutex<HashMap<(String, String), u64>>>,
+}
+
+impl SdkTracker {
+    pub fn new(policy: SdkPolicy) -> Self {
+        SdkTracker {
+            policy: Arc::new(policy),
+            counters: Default::default(),
+        }
+    }
+
+    /// Counts a request made by the SDK.
+    pub fn record(&self, sdk: &SdkVersion) {
+        if self.policy.is_known(&sdk.name) {
+            let version =
+                format!("{}.{}", sdk.version.major, sdk.version.minor);
+            self.count(&sdk.name, &version);
+        } else {
+            self.count(OTHER_LABEL, OTHER_LABEL);
+        }
+    }
+
+    fn count(&self, sdk: &str, version: &str) {
+        let mut counters =
+            self.counters.lock().unwrap_or_else(|e| e.into_inner());
+
+        *counters
+            .entry((sdk.to_owned(), version.to_owned()))
+            .or_default() += 1;
+    }
+
+    /// Returns a snapshot of the adoption counters ordered by SDK name and version.
+    pub fn adoption(&self) -> Vec<SdkAdoption> {
+        let counters = self.counters.lock().unwrap_or_else(|e| e.into_inner());
+
+        let mut report: Vec<_> = counters
+            .iter()
+            .map(|((sdk, version), requests)| SdkAdoption {
+                sdk: sdk.clone(),
+                version: version.clone(),
+                requests: *requests,
+            })
+            .collect();
+        report.sort();
+
+        report
+    }
+}
+
+/// Error body returned to clients that use a deprecated SDK version.
+#[derive(Debug, Serialize)]
+struct UpgradeRequired<'a> {
+    error: &'static str,
+    message: String,
+    sdk: &'a str,
+    current_version: String,
+    minimum_version: String,
+}
+
+fn user_agent(headers: &HeaderMap) -> &str {
+    headers
+        .get(USER_AGENT)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+}
+
+/// Records the client SDK of every request and rejects deprecated SDK versions with
+/// `426 Upgrade Required`.
+pub async fn track_sdk(
+    State(tracker): State<SdkTracker>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let headers = request.headers();
+    let user_agent = user_agent(headers);
+
+    let Some(header) = headers.get(SDK_HEADER) else {
+        debug!(user_agent, "Request without an SDK header");
+        tracker.count(UNKNOWN_LABEL, UNKNOWN_LABEL);
+        return next.run(request).await;
+    };
+
+    let sdk = match header
+        .to_str()
+        .map_err(|e| eyre!(e))
+        .and_then(SdkVersion::from_str)
+    {
+        Ok(sdk) => sdk,
+        Err(e) => {
+            debug!(user_agent, error = %e, "Malformed SDK header");
+            tracker.count(INVALID_LABEL, INVALID_LABEL);
+            return next.run(request).await;
+        }
+    };
+
+    tracker.record(&sdk);
+
+    if let Some(minimum) = tracker.policy.required_upgrade(&sdk) {
+        warn!(%sdk, %minimum, user_agent, "Rejecting deprecated SDK version");
+
+        let body = UpgradeRequired {
+            error: "sdk_upgrade_required",
+            message: format!(
+                "{} {} is no longer supported, please upgrade to {} or newer",
+                sdk.name, sdk.version, minimum
+            ),
+            sdk: &sdk.name,
+            current_version: sdk.version.to_string(),
+            minimum_version: minimum.to_string(),
+        };
+
+        return (StatusCode::UPGRADE_REQUIRED, Json(body)).into_response();
+    }
+
+    next.run(request).await
+}
+
+/// Returns the SDK adoption report.
+#[utoipa::path(
+    get,
+    path = "/sdk/adoption",
+    operation_id = "get_sdk_adoption",
+    tag = "sdk",
+    responses(
+        (status = OK, description = "Number of requests made by every SDK version", body = Vec<SdkAdoption>),
+    ),
+    security(("api_key" = [])),
+)]
+pub async fn adoption(
+    State(tracker): State<SdkTracker>,
+) -> Json<Vec<SdkAdoption>> {
+    Json(tracker.adoption())
+}
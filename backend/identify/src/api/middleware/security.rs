@@ -0,0 +1,126 @@
+//! Protections of browser-based clients: CORS and the security headers of responses.
+
+use std::{sync::Arc, time::Duration};
+
+use axum::{
+    Router,
+    extract::{Request, State},
+    http::{
+        HeaderName, HeaderValue,
+        header::{
+            CONTENT_SECURITY_POLICY, ETAG, LINK, LOCATION, RETRY_AFTER,
+            STRICT_TRANSPORT_SECURITY, X_CONTENT_TYPE_OPTIONS, X_FRAME_OPTIONS,
+        },
+    },
+    middleware::{Next, from_fn_with_state},
+    response::Response,
+};
+use eyre::{Result, eyre};
+use tower_http::cors::{AllowOrigin, CorsLayer};
+
+use crate::{
+    api::version::{DEPRECATION_HEADER, SUNSET_HEADER},
+    config::{CorsConfig, SecurityHeadersConfig},
+};
+
+/// Response headers that browsers let cross-origin callers read, in addition to the safelisted
+/// ones.
+const EXPOSED_HEADERS: [HeaderName; 6] = [
+    LOCATION,
+    ETAG,
+    RETRY_AFTER,
+    LINK,
+    DEPRECATION_HEADER,
+    SUNSET_HEADER,
+];
+
+/// Wraps the router with the CORS and security headers layers.
+///
+/// Security headers are added outside of CORS, so that the responses to preflight requests
+/// get them as well.
+pub fn protect(
+    router: Router,
+    cors: &CorsConfig,
+    security_headers: &SecurityHeadersConfig,
+) -> Result<Router> {
+    let router = match cors_layer(cors)? {
+        Some(cors) => router.layer(cors),
+        None => router,
+    };
+
+    Ok(router.layer(from_fn_with_state(
+        Arc::new(SecurityHeaders::from_config(security_headers)),
+        add_security_headers,
+    )))
+}
+
+/// Builds the CORS layer, unless no origins are allowed.
+fn cors_layer(config: &CorsConfig) -> Result<Option<CorsLayer>> {
+    if config.allowed_origins.is_empty() {
+        return Ok(None);
+    }
+
+    let origins = if config.allows_any_origin() {
+        AllowOrigin::any()
+    } else {
+        AllowOrigin::list(config.origins().map_err(|e| eyre!(e))?)
+    };
+
+    Ok(Some(
+        CorsLayer::new()
+            .allow_origin(origins)
+            .allow_methods(config.methods().map_err(|e| eyre!(e))?)
+            .allow_headers(config.headers().map_err(|e| eyre!(e))?)
+            .expose_headers(EXPOSED_HEADERS)
+            .max_age(Duration::from_secs(config.max_age_secs)),
+    ))
+}
+
+/// Headers added to every response.
+#[derive(Debug)]
+struct SecurityHeaders {
+    headers: Vec<(HeaderName, HeaderValue)>,
+}
+
+impl SecurityHeaders {
+    fn from_config(config: &SecurityHeadersConfig) -> Self {
+        let mut headers = vec![
+            (X_CONTENT_TYPE_OPTIONS, HeaderValue::from_static("nosniff")),
+            (X_FRAME_OPTIONS, HeaderValue::from_static("DENY")),
+            // Supersedes `X-Frame-Options` in the browsers that support it.
+            (
+                CONTENT_SECURITY_POLICY,
+                HeaderValue::from_static("frame-ancestors 'none'"),
+            ),
+        ];
+
+        if config.hsts_max_age_secs > 0 {
+            let mut hsts = format!("max-age={}", config.hsts_max_age_secs);
+            if config.hsts_include_subdomains {
+                hsts.push_str("; includeSubDomains");
+            }
+            if let Ok(hsts) = HeaderValue::from_str(&hsts) {
+                headers.push((STRICT_TRANSPORT_SECURITY, hsts));
+            }
+        }
+
+        SecurityHeaders { headers }
+    }
+}
+
+/// Adds the security headers to the response, unless the handler has set them already.
+async fn add_security_headers(
+    State(security_headers): State<Arc<SecurityHeaders>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let mut response = next.run(request).await;
+
+    for (name, value) in &security_headers.headers {
+        if !response.headers().contains_key(name) {
+            response.headers_mut().insert(name.clone(), value.clone());
+        }
+    }
+
+    response
+}
@@ -1 +1,500 @@
+use std::sync::Arc;
 
+use axum::{
+    Extension, Router,
+    extract::FromRef,
+    middleware::{from_fn, from_fn_with_state},
+    routing::{MethodRouter, get},
+};
+use identify_application::{
+    BlobStorage, EmailSender, EmailTemplates, GeoResolver, PasswordHasher,
+    SmsSender, TokenGenerator,
+};
+use identify_domain::{
+    AuthorizationPolicies, ConsentPolicies, QuotaPolicy, UsernameStrategy,
+    WebhookUrlPolicy,
+};
+use identify_infrastructure::{
+    encryption::FieldEncryption,
+    storage::{connection::ReadPool, retry::Retrier, users::cache::UserCache},
+};
+use sqlx::SqlitePool;
+use tower_http::catch_panic::CatchPanicLayer;
+
+pub mod auth;
+pub mod conditional;
+pub mod error;
+pub mod export;
+pub mod graphql;
+pub mod listing;
+pub mod middleware;
+pub mod openapi;
+pub mod patch;
+pub mod policy;
+pub mod rate_limit;
+pub mod services;
+pub mod tenancy;
+pub mod use_case;
+pub mod validation;
+pub mod version;
+
+use auth::Authenticator;
+use graphql::GraphQlService;
+use middleware::{
+    context,
+    latency::{self, ServerTiming},
+    metrics, panic,
+    sdk::{self, SdkTracker},
+};
+use policy::RoutePolicy;
+use rate_limit::RateLimiter;
+use services::{
+    avatars::AvatarService,
+    consents::ConsentService,
+    database::DatabaseService,
+    devices::DeviceService,
+    email_aliases::EmailAliasService,
+    email_changes::{EmailChangeService, EmailChangeSettings},
+    events::EventService,
+    invitations::{InvitationService, InvitationSettings},
+    metrics::MetricsService,
+    operations::OperationService,
+    organizations::OrganizationService,
+    phone_verifications::{
+        PhoneVerificationService, PhoneVerificationSettings,
+    },
+    registration::{RegistrationService, RegistrationSettings},
+    roles::RoleService,
+    sdk::SdkService,
+    sessions::{SessionService, SessionSettings},
+    signing_keys::{JwksService, SigningKeyService},
+    status::StatusService,
+    user_attributes::UserAttributeService,
+    users::UserService,
+    webhooks::WebhookService,
+};
+
+use version::ApiVersion;
+
+use crate::{
+    event_feed::UserEventFeed, funnel::LoginFunnel,
+    identifiers::IdentifierLists, logins::LoginTracker, metrics::Metrics,
+    operations::OperationRunner, session_monitor::SessionMonitor,
+    status::StatusBoard,
+};
+
+/// State shared by all API handlers.
+#[derive(Clone)]
+pub struct ApiState {
+    pub pool: SqlitePool,
+    /// Pool of read-only connections, for the handlers that don't write.
+    pub read_pool: ReadPool,
+    /// Encrypts the personal data and secrets stored in the database.
+    pub encryption: FieldEncryption,
+    /// Users recently looked up by their IDs.
+    pub user_cache: UserCache,
+    pub sdk_tracker: SdkTracker,
+    pub operation_runner: OperationRunner,
+    pub authenticator: Authenticator,
+    pub metrics: Metrics,
+    pub server_timing: ServerTiming,
+    pub rate_limiter: RateLimiter,
+    pub status: StatusBoard,
+    /// How usernames are generated for the users created without one.
+    pub username_strategy: UsernameStrategy,
+    /// Identifiers users can't choose.
+    pub identifiers: IdentifierLists,
+    /// Storage of the avatars of users.
+    pub blob_storage: Arc<dyn BlobStorage>,
+    /// Sender of the emails to users, e.g. about logins from new devices.
+    pub email_sender: Arc<dyn EmailSender>,
+    pub email_templates: Arc<EmailTemplates>,
+    /// Locates the devices users log in from.
+    pub geo_resolver: Arc<dyn GeoResolver>,
+    /// Policies users have to accept before they can log in.
+    pub consent_policies: Arc<ConsentPolicies>,
+    /// Decide which callers may perform the actions of the use cases that are authorized by
+    /// policies.
+    pub authorization_policies: Arc<AuthorizationPolicies>,
+    /// Generates the tokens invitations are accepted with.
+    pub token_generator: Arc<dyn TokenGenerator>,
+    pub invitations: InvitationSettings,
+    pub email_changes: EmailChangeSettings,
+    /// Hashes the passwords users register with.
+    pub password_hasher: Arc<dyn PasswordHasher>,
+    pub registration: RegistrationSettings,
+    /// Limits how many users organizations can have.
+    pub quotas: QuotaPolicy,
+    /// Which URLs webhook endpoints can be registered with.
+    pub webhook_urls: WebhookUrlPolicy,
+    /// Sender of the text messages to users, e.g. the codes phone numbers are verified with.
+    pub sms_sender: Arc<dyn SmsSender>,
+    pub phone_verifications: PhoneVerificationSettings,
+    /// Broadcasts the user events to the subscribers of the event stream.
+    pub user_events: UserEventFeed,
+    /// Broadcasts the logins, logouts and lockouts of users to the session monitors.
+    pub sessions: SessionMonitor,
+    /// Keeps the sessions issued to users when they log in.
+    pub session_tokens: SessionSettings,
+    /// Counts the logins of users towards their statistics.
+    pub logins: LoginTracker,
+    /// Tracks the steps of login attempts for product analytics.
+    pub funnel: LoginFunnel,
+    /// Starts transactions over when the database was busy.
+    pub retrier: Retrier,
+}
+
+impl FromRef<ApiState> for QuotaPolicy {
+    fn from_ref(state: &ApiState) -> Self {
+        state.quotas
+    }
+}
+
+impl FromRef<ApiState> for WebhookUrlPolicy {
+    fn from_ref(state: &ApiState) -> Self {
+        state.webhook_urls
+    }
+}
+
+impl FromRef<ApiState> for SqlitePool {
+    fn from_ref(state: &ApiState) -> Self {
+        state.pool.clone()
+    }
+}
+
+impl FromRef<ApiState> for ReadPool {
+    fn from_ref(state: &ApiState) -> Self {
+        state.read_pool.clone()
+    }
+}
+
+impl FromRef<ApiState> for FieldEncryption {
+    fn from_ref(state: &ApiState) -> Self {
+        state.encryption.clone()
+    }
+}
+
+impl FromRef<ApiState> for UserCache {
+    fn from_ref(state: &ApiState) -> Self {
+        state.user_cache.clone()
+    }
+}
+
+impl FromRef<ApiState> for SdkTracker {
+    fn from_ref(state: &ApiState) -> Self {
+        state.sdk_tracker.clone()
+    }
+}
+
+impl FromRef<ApiState> for OperationRunner {
+    fn from_ref(state: &ApiState) -> Self {
+        state.operation_runner.clone()
+    }
+}
+
+impl FromRef<ApiState> for StatusBoard {
+    fn from_ref(state: &ApiState) -> Self {
+        state.status.clone()
+    }
+}
+
+impl FromRef<ApiState> for UsernameStrategy {
+    fn from_ref(state: &ApiState) -> Self {
+        state.username_strategy
+    }
+}
+
+impl FromRef<ApiState> for IdentifierLists {
+    fn from_ref(state: &ApiState) -> Self {
+        state.identifiers.clone()
+    }
+}
+
+impl FromRef<ApiState> for Arc<dyn BlobStorage> {
+    fn from_ref(state: &ApiState) -> Self {
+        state.blob_storage.clone()
+    }
+}
+
+impl FromRef<ApiState> for Arc<dyn EmailSender> {
+    fn from_ref(state: &ApiState) -> Self {
+        state.email_sender.clone()
+    }
+}
+
+impl FromRef<ApiState> for Arc<EmailTemplates> {
+    fn from_ref(state: &ApiState) -> Self {
+        state.email_templates.clone()
+    }
+}
+
+impl FromRef<ApiState> for Arc<dyn GeoResolver> {
+    fn from_ref(state: &ApiState) -> Self {
+        state.geo_resolver.clone()
+    }
+}
+
+impl FromRef<ApiState> for Arc<ConsentPolicies> {
+    fn from_ref(state: &ApiState) -> Self {
+        state.consent_policies.clone()
+    }
+}
+
+impl FromRef<ApiState> for Arc<AuthorizationPolicies> {
+    fn from_ref(state: &ApiState) -> Self {
+        state.authorization_policies.clone()
+    }
+}
+
+impl FromRef<ApiState> for Arc<dyn TokenGenerator> {
+    fn from_ref(state: &ApiState) -> Self {
+        state.token_generator.clone()
+    }
+}
+
+impl FromRef<ApiState> for InvitationSettings {
+    fn from_ref(state: &ApiState) -> Self {
+        state.invitations.clone()
+    }
+}
+
+impl FromRef<ApiState> for EmailChangeSettings {
+    fn from_ref(state: &ApiState) -> Self {
+        state.email_changes.clone()
+    }
+}
+
+impl FromRef<ApiState> for Arc<dyn SmsSender> {
+    fn from_ref(state: &ApiState) -> Self {
+        state.sms_sender.clone()
+    }
+}
+
+impl FromRef<ApiState> for PhoneVerificationSettings {
+    fn from_ref(state: &ApiState) -> Self {
+        state.phone_verifications.clone()
+    }
+}
+
+impl FromRef<ApiState> for UserEventFeed {
+    fn from_ref(state: &ApiState) -> Self {
+        state.user_events.clone()
+    }
+}
+
+impl FromRef<ApiState> for SessionMonitor {
+    fn from_ref(state: &ApiState) -> Self {
+        state.sessions.clone()
+    }
+}
+
+impl FromRef<ApiState> for SessionSettings {
+    fn from_ref(state: &ApiState) -> Self {
+        state.session_tokens.clone()
+    }
+}
+
+impl FromRef<ApiState> for LoginFunnel {
+    fn from_ref(state: &ApiState) -> Self {
+        state.funnel.clone()
+    }
+}
+
+impl FromRef<ApiState> for Retrier {
+    fn from_ref(state: &ApiState) -> Self {
+        state.retrier.clone()
+    }
+}
+
+impl FromRef<ApiState> for Metrics {
+    fn from_ref(state: &ApiState) -> Self {
+        state.metrics.clone()
+    }
+}
+
+/// A single route together with the policy that protects it.
+pub struct Route {
+    pub path: &'static str,
+    pub handler: MethodRouter<ApiState>,
+    pub policy: RoutePolicy,
+}
+
+impl Route {
+    pub fn new(
+        path: &'static str,
+        handler: MethodRouter<ApiState>,
+        policy: RoutePolicy,
+    ) -> Self {
+        Route {
+            path,
+            handler,
+            policy,
+        }
+    }
+}
+
+/// A group of related routes exposed by the API.
+pub trait Service {
+    /// Returns the table of all the routes of this service.
+    ///
+    /// Every route must declare its policy, so that no route is left unprotected by accident.
+    /// Several routes can share a path as long as they handle different methods.
+    fn routes() -> Vec<Route>;
+
+    /// Returns the OpenAPI description of all the routes of this service.
+    ///
+    /// It's usually derived with [utoipa::OpenApi] from the `#[utoipa::path]` annotations of
+    /// the handlers.
+    fn openapi() -> utoipa::openapi::OpenApi;
+}
+
+/// Collects routes of all the services and wraps each of them into the middleware stack
+/// required by its policy.
+///
+/// The OpenAPI descriptions of the services are collected as well, and served along with the
+/// routes.
+#[derive(Default)]
+pub struct ServiceRegistry {
+    version: Option<ApiVersion>,
+    router: Router<ApiState>,
+    openapi: utoipa::openapi::OpenApi,
+}
+
+impl ServiceRegistry {
+    /// Creates a registry of unversioned services, e.g. the operational ones, that are mounted
+    /// at the root.
+    pub fn new() -> Self {
+        ServiceRegistry::default()
+    }
+
+    /// Creates a registry of the services of an API version, that are mounted under its
+    /// prefix.
+    pub fn versioned(version: ApiVersion) -> Self {
+        ServiceRegistry {
+            version: Some(version),
+            ..ServiceRegistry::default()
+        }
+    }
+
+    pub fn register<S: Service>(mut self) -> Self {
+        for route in S::routes() {
+            self.add(route);
+        }
+        self.openapi.merge(S::openapi());
+
+        self
+    }
+
+    fn add(
+        &mut self,
+        Route {
+            path,
+            handler,
+            policy,
+        }: Route,
+    ) {
+        self.router = std::mem::take(&mut self.router)
+            .route(path, policy::protect(handler, policy));
+    }
+
+    pub fn into_router(mut self) -> Router<ApiState> {
+        let document =
+            openapi::document(std::mem::take(&mut self.openapi), self.version);
+        for route in openapi::routes(document) {
+            self.add(route);
+        }
+
+        let Some(version) = self.version else {
+            return self.router;
+        };
+
+        let mut router = self.router;
+        if let Some(deprecation) = version.deprecation() {
+            router = router.layer(from_fn_with_state(
+                deprecation,
+                version::announce_deprecation,
+            ));
+        }
+
+        Router::new().nest(version.prefix(), router)
+    }
+}
+
+/// Builds the routers of all the services of an API version.
+///
+/// A new version starts as a copy of the previous one, with the services that break
+/// compatibility replaced.
+fn versioned_services(version: ApiVersion) -> Router<ApiState> {
+    match version {
+        ApiVersion::V1 => ServiceRegistry::versioned(version)
+            .register::<SdkService>()
+            .register::<OperationService>()
+            .register::<OrganizationService>()
+            .register::<UserService>()
+            .register::<UserAttributeService>()
+            .register::<AvatarService>()
+            .register::<DeviceService>()
+            .register::<ConsentService>()
+            .register::<InvitationService>()
+            .register::<RegistrationService>()
+            .register::<EmailChangeService>()
+            .register::<EmailAliasService>()
+            .register::<PhoneVerificationService>()
+            .register::<WebhookService>()
+            .register::<EventService>()
+            .register::<SessionService>()
+            .register::<SigningKeyService>()
+            .register::<RoleService>()
+            .into_router(),
+    }
+}
+
+/// Builds the API router with all the routes and middleware.
+pub fn router(state: ApiState) -> Router {
+    let mut router = Router::new()
+        .route("/", get(|| async { "Hello, World!" }))
+        .merge(
+            ServiceRegistry::new()
+                .register::<MetricsService>()
+                .register::<GraphQlService>()
+                .register::<StatusService>()
+                .register::<DatabaseService>()
+                .register::<JwksService>()
+                .into_router(),
+        );
+
+    for version in ApiVersion::ALL {
+        router = router.merge(versioned_services(version));
+    }
+
+    // Swagger UI only serves static assets and loads the documents from their public routes.
+    #[cfg(feature = "swagger-ui")]
+    let router = router.merge(
+        utoipa_swagger_ui::SwaggerUi::new(openapi::SWAGGER_UI_PATH)
+            .config(utoipa_swagger_ui::Config::new(openapi::document_paths())),
+    );
+
+    router
+        .layer(Extension(state.rate_limiter.clone()))
+        .layer(from_fn_with_state(state.clone(), auth::grant_roles))
+        .layer(from_fn_with_state(
+            state.authenticator.clone(),
+            auth::authenticate,
+        ))
+        .layer(from_fn_with_state(
+            state.rate_limiter.clone(),
+            rate_limit::limit_clients,
+        ))
+        .layer(from_fn_with_state(
+            state.sdk_tracker.clone(),
+            sdk::track_sdk,
+        ))
+        .layer(CatchPanicLayer::custom(panic::handle_panic))
+        .layer(from_fn(metrics::track_requests))
+        .layer(from_fn_with_state(
+            state.server_timing,
+            latency::track_latency,
+        ))
+        .layer(from_fn(context::propagate_context))
+        .with_state(state)
+}
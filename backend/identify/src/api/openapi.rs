@@ -0,0 +1,178 @@
+//! OpenAPI description of the API.
+//!
+//! Every [Service](crate::api::Service) describes its own routes, and the
+//! [ServiceRegistry](crate::api::ServiceRegistry) merges them into a single document that is
+//! served at [OPENAPI_PATH] under the prefix of the registry, e.g. `/api/v1/openapi.json` for
+//! the first version of the API and `/openapi.json` for the unversioned services. Schemas are
+//! derived from the same DTOs the handlers use, so the document can't drift from the actual
+//! requests and responses.
+
+use axum::{Json, routing::get};
+use utoipa::{
+    Modify, OpenApi,
+    openapi::{
+        self, ComponentsBuilder, Content, Ref, RefOr, Response,
+        ResponseBuilder, Server,
+        security::{HttpAuthScheme, HttpBuilder, SecurityScheme},
+    },
+};
+
+use crate::api::{
+    Route,
+    error::{PROBLEM_CONTENT_TYPE, Problem},
+    policy::RoutePolicy,
+    version::ApiVersion,
+};
+
+/// Path the OpenAPI document is served at.
+pub const OPENAPI_PATH: &str = "/openapi.json";
+
+/// Path Swagger UI is served at when the `swagger-ui` feature is enabled.
+#[cfg(feature = "swagger-ui")]
+pub const SWAGGER_UI_PATH: &str = "/swagger-ui";
+
+/// Name of the security scheme of API keys passed as bearer tokens.
+///
+/// Operations list the permissions they require as the scopes of this scheme. `#[utoipa::path]`
+/// only accepts literals, so handlers have to spell the name out.
+pub const API_KEY_SCHEME: &str = "api_key";
+
+/// Base of the document that the routes of all services are merged into.
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "Identify", description = "Identity management service."),
+    components(schemas(Problem)),
+    modifiers(&Security, &ProblemResponses)
+)]
+pub struct ApiDoc;
+
+/// Declares the [API_KEY_SCHEME].
+struct Security;
+
+impl Modify for Security {
+    fn modify(&self, openapi: &mut openapi::OpenApi) {
+        let components = openapi
+            .components
+            .get_or_insert_with(|| ComponentsBuilder::new().build());
+
+        components.add_security_scheme(
+            API_KEY_SCHEME,
+            SecurityScheme::Http(
+                HttpBuilder::new().scheme(HttpAuthScheme::Bearer).build(),
+            ),
+        );
+    }
+}
+
+/// Declares the error responses shared by all the protected operations, so that routes don't
+/// have to repeat them.
+struct ProblemResponses;
+
+impl ProblemResponses {
+    const UNAUTHORIZED: &str = "Unauthorized";
+    const FORBIDDEN: &str = "Forbidden";
+
+    fn problem(description: &str) -> Response {
+        ResponseBuilder::new()
+            .description(description)
+            .content(
+                PROBLEM_CONTENT_TYPE,
+                Content::new(Some(Ref::from_schema_name("Problem"))),
+            )
+            .build()
+    }
+}
+
+impl Modify for ProblemResponses {
+    fn modify(&self, openapi: &mut openapi::OpenApi) {
+        let components = openapi
+            .components
+            .get_or_insert_with(|| ComponentsBuilder::new().build());
+
+        components.responses.insert(
+            ProblemResponses::UNAUTHORIZED.to_owned(),
+            RefOr::T(ProblemResponses::problem(
+                "The request lacks valid credentials",
+            )),
+        );
+        components.responses.insert(
+            ProblemResponses::FORBIDDEN.to_owned(),
+            RefOr::T(ProblemResponses::problem(
+                "The caller is not allowed to perform this request",
+            )),
+        );
+    }
+}
+
+/// Builds the whole document from the parts contributed by the services of a version, or the
+/// unversioned services.
+///
+/// The shared error responses are attached after merging, so that they apply to the operations
+/// of all the services.
+pub fn document(
+    services: openapi::OpenApi,
+    version: Option<ApiVersion>,
+) -> openapi::OpenApi {
+    let mut document = ApiDoc::openapi().merge_from(services);
+    // The package has no license, so the one taken from the manifest is empty.
+    document.info.license = None;
+
+    // Paths are relative to the prefix the routes are mounted under.
+    if let Some(version) = version {
+        document.servers = Some(vec![Server::new(version.prefix())]);
+    }
+
+    for item in document.paths.paths.values_mut() {
+        for operation in [
+            &mut item.get,
+            &mut item.put,
+            &mut item.post,
+            &mut item.delete,
+            &mut item.patch,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            if operation.security.as_ref().is_none_or(Vec::is_empty) {
+                continue;
+            }
+
+            for (status, response) in [
+                ("401", ProblemResponses::UNAUTHORIZED),
+                ("403", ProblemResponses::FORBIDDEN),
+            ] {
+                operation
+                    .responses
+                    .responses
+                    .entry(status.to_owned())
+                    .or_insert_with(|| {
+                        RefOr::Ref(Ref::from_response_name(response))
+                    });
+            }
+        }
+    }
+
+    document
+}
+
+/// Returns the paths all the documents are served at.
+#[cfg(feature = "swagger-ui")]
+pub fn document_paths() -> Vec<String> {
+    std::iter::once(OPENAPI_PATH.to_owned())
+        .chain(
+            ApiVersion::ALL
+                .map(|version| format!("{}{OPENAPI_PATH}", version.prefix())),
+        )
+        .collect()
+}
+
+/// Returns the routes that serve the document.
+pub fn routes(document: openapi::OpenApi) -> Vec<Route> {
+    let document = Json(document);
+
+    vec![Route::new(
+        OPENAPI_PATH,
+        get(move || async move { document }),
+        RoutePolicy::public(),
+    )]
+}
@@ -0,0 +1,179 @@
+//! Partial updates of resources with JSON Merge Patch (RFC 7396) and JSON Patch (RFC 6902).
+//!
+//! `PATCH` handlers extract a [PatchRequest]. Plain `application/json` bodies hold the new values
+//! of the fields as before, while patches are applied to the JSON document of the current state
+//! of the resource with [Patch::apply_to], which returns the new values of its mutable fields for
+//! the handler to pass on to the mutators of the entity. Both kinds of patches are applied by
+//! the [json_patch] crate.
+
+use axum::{
+    Json,
+    extract::{FromRequest, Request},
+    http::{StatusCode, header::CONTENT_TYPE},
+};
+use json_patch::PatchErrorKind;
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::Value;
+
+use crate::api::{
+    error::ApiError,
+    validation::{self, FieldError, ValidJson, Validate},
+};
+
+/// Content type of JSON Merge Patch documents.
+pub const MERGE_PATCH_CONTENT_TYPE: &str = "application/merge-patch+json";
+/// Content type of JSON Patch documents.
+pub const JSON_PATCH_CONTENT_TYPE: &str = "application/json-patch+json";
+
+/// A patch of the JSON document of a resource.
+#[derive(Debug, Clone)]
+pub enum Patch {
+    /// A JSON Merge Patch, where `null` removes a field.
+    Merge(Value),
+    /// A JSON Patch, whose operations are applied in order and all or none at all.
+    Operations(json_patch::Patch),
+}
+
+impl Patch {
+    /// Applies the patch to the document of a resource and reads the new values of its fields.
+    ///
+    /// Fails with `422 Unprocessable Entity` if the patch changes any of the `immutable` fields,
+    /// adds fields the resource doesn't have, or the new values break the rules of [Validate],
+    /// and with `409 Conflict` if a `test` operation fails.
+    pub fn apply_to<T>(
+        &self,
+        resource: &impl Serialize,
+        immutable: &[&'static str],
+    ) -> Result<T, ApiError>
+    where
+        T: DeserializeOwned + Validate,
+    {
+        let original =
+            serde_json::to_value(resource).map_err(ApiError::internal)?;
+        let mut document = original.clone();
+        self.apply(&mut document)?;
+
+        let Value::Object(mut fields) = document else {
+            return Err(unprocessable(
+                "The patched resource must be an object",
+            ));
+        };
+        let mut immutable_errors = Vec::new();
+        for field in immutable {
+            if fields.remove(*field).as_ref() != original.get(field) {
+                immutable_errors.push(FieldError {
+                    field,
+                    code: "immutable",
+                    message: "can't be changed".into(),
+                });
+            }
+        }
+        if !immutable_errors.is_empty() {
+            return Err(unprocessable("The patch changes immutable fields")
+                .with_field_errors(immutable_errors));
+        }
+        let unknown: Vec<_> = fields
+            .keys()
+            .filter(|field| original.get(field).is_none())
+            .map(|field| format!("the resource has no field '{field}'"))
+            .collect();
+        if !unknown.is_empty() {
+            return Err(unprocessable("The patch adds unknown fields")
+                .with_errors(unknown));
+        }
+
+        let value =
+            serde_json::from_value(Value::Object(fields)).map_err(|e| {
+                unprocessable(format!("The patched resource is invalid: {e}"))
+            })?;
+        validation::check(&value)?;
+
+        Ok(value)
+    }
+
+    /// Applies the patch to a JSON document, which is left untouched if any of the operations
+    /// of a JSON Patch fails.
+    pub fn apply(&self, document: &mut Value) -> Result<(), ApiError> {
+        match self {
+            Patch::Merge(patch) => {
+                json_patch::merge(document, patch);
+                Ok(())
+            }
+            Patch::Operations(operations) => {
+                json_patch::patch(document, operations).map_err(|e| {
+                    match e.kind {
+                        PatchErrorKind::TestFailed => ApiError::new(
+                            StatusCode::CONFLICT,
+                            format!(
+                                "The value at '{}' doesn't match the test",
+                                e.path
+                            ),
+                        ),
+                        _ => unprocessable(format!(
+                            "Operation {} of the patch failed: {e}",
+                            e.operation
+                        )),
+                    }
+                })
+            }
+        }
+    }
+}
+
+fn unprocessable(
+    detail: impl Into<std::borrow::Cow<'static, str>>,
+) -> ApiError {
+    ApiError::new(StatusCode::UNPROCESSABLE_ENTITY, detail)
+}
+
+/// Extractor of the body of a `PATCH` request, depending on its content type.
+#[derive(Debug, Clone)]
+pub enum PatchRequest<T> {
+    /// An `application/json` body with the new values of the fields, see [ValidJson].
+    Fields(T),
+    /// An `application/merge-patch+json` or `application/json-patch+json` body.
+    Patch(Patch),
+}
+
+impl<T, S> FromRequest<S> for PatchRequest<T>
+where
+    T: DeserializeOwned + Validate,
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request(
+        request: Request,
+        state: &S,
+    ) -> Result<Self, Self::Rejection> {
+        let content_type = request
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.split(';').next())
+            .map(|value| value.trim().to_ascii_lowercase());
+
+        match content_type.as_deref() {
+            Some(MERGE_PATCH_CONTENT_TYPE) => {
+                let Json(patch) = Json::from_request(request, state)
+                    .await
+                    .map_err(|e| ApiError::new(e.status(), e.body_text()))?;
+
+                Ok(PatchRequest::Patch(Patch::Merge(patch)))
+            }
+            Some(JSON_PATCH_CONTENT_TYPE) => {
+                let Json(operations) = Json::from_request(request, state)
+                    .await
+                    .map_err(|e| ApiError::new(e.status(), e.body_text()))?;
+
+                Ok(PatchRequest::Patch(Patch::Operations(operations)))
+            }
+            _ => {
+                let ValidJson(fields) =
+                    ValidJson::from_request(request, state).await?;
+
+                Ok(PatchRequest::Fields(fields))
+            }
+        }
+    }
+}
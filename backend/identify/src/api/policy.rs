@@ -0,0 +1,202 @@
+//! Declarative policies that describe how every route is protected.
+
+use std::time::Duration;
+
+use std::convert::Infallible;
+
+use axum::{
+    Extension,
+    extract::{Request, State},
+    http::StatusCode,
+    middleware::{Next, from_fn, from_fn_with_state},
+    response::{IntoResponse, Response},
+    routing::MethodRouter,
+};
+use identify_infrastructure::storage::deadline;
+use tokio::time::Instant;
+use tower_http::timeout::TimeoutLayer;
+use tracing::debug;
+
+use crate::api::{
+    auth::Principal, error::ApiError, middleware::latency, rate_limit,
+};
+
+/// Timeout applied to routes that don't override it.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+/// Latency budget of routes that don't override it.
+pub const DEFAULT_LATENCY_BUDGET: Duration = Duration::from_millis(500);
+
+/// Who is allowed to call a route.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthLevel {
+    /// Anyone, including anonymous callers.
+    Public,
+    /// Any authenticated caller that has the required permissions.
+    Authenticated,
+    /// Only admins.
+    Admin,
+}
+
+/// How strictly requests to a route are rate-limited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RateLimitClass {
+    /// Regular API routes.
+    Default,
+    /// Sensitive routes, e.g. the ones that deal with credentials.
+    Strict,
+    /// Routes that are never rate-limited, e.g. health checks.
+    Unlimited,
+}
+
+impl RateLimitClass {
+    /// Name used in rate limit keys and metric labels.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RateLimitClass::Default => "default",
+            RateLimitClass::Strict => "strict",
+            RateLimitClass::Unlimited => "unlimited",
+        }
+    }
+}
+
+/// Protections applied to a single route.
+///
+/// The policy is also attached to the request extensions, so that other middleware can
+/// inspect it.
+#[derive(Debug, Clone, Copy)]
+pub struct RoutePolicy {
+    pub auth: AuthLevel,
+    /// Permissions the caller must have (all of them).
+    pub permissions: &'static [&'static str],
+    pub rate_limit: RateLimitClass,
+    pub timeout: Duration,
+    /// How long requests are expected to take at most. Slower requests are still answered,
+    /// but reported, see [track_latency](crate::api::middleware::latency::track_latency).
+    pub latency_budget: Duration,
+}
+
+impl RoutePolicy {
+    const fn with_auth(auth: AuthLevel) -> Self {
+        RoutePolicy {
+            auth,
+            permissions: &[],
+            rate_limit: RateLimitClass::Default,
+            timeout: DEFAULT_TIMEOUT,
+            latency_budget: DEFAULT_LATENCY_BUDGET,
+        }
+    }
+
+    pub const fn public() -> Self {
+        RoutePolicy::with_auth(AuthLevel::Public)
+    }
+
+    pub const fn authenticated() -> Self {
+        RoutePolicy::with_auth(AuthLevel::Authenticated)
+    }
+
+    pub const fn admin() -> Self {
+        RoutePolicy::with_auth(AuthLevel::Admin)
+    }
+
+    pub const fn permissions(
+        mut self,
+        permissions: &'static [&'static str],
+    ) -> Self {
+        self.permissions = permissions;
+        self
+    }
+
+    pub const fn rate_limit(mut self, rate_limit: RateLimitClass) -> Self {
+        self.rate_limit = rate_limit;
+        self
+    }
+
+    pub const fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub const fn latency_budget(mut self, latency_budget: Duration) -> Self {
+        self.latency_budget = latency_budget;
+        self
+    }
+
+    /// Checks whether the caller is allowed to access the route.
+    fn check(&self, principal: Option<&Principal>) -> Result<(), ApiError> {
+        if self.auth == AuthLevel::Public {
+            return Ok(());
+        }
+
+        let principal = principal.ok_or_else(ApiError::unauthorized)?;
+
+        if self.auth == AuthLevel::Admin && !principal.admin {
+            return Err(ApiError::forbidden());
+        }
+
+        if let Some(missing) = self
+            .permissions
+            .iter()
+            .find(|permission| !principal.has_permission(permission))
+        {
+            debug!(subject = principal.subject, missing, "Missing permission");
+            return Err(ApiError::forbidden());
+        }
+
+        Ok(())
+    }
+}
+
+/// Wraps the handler of a route in the middleware that enforces its policy.
+///
+/// The rate limiter is taken from the extensions of the request, where the API router puts it.
+pub fn protect<S>(
+    handler: MethodRouter<S>,
+    policy: RoutePolicy,
+) -> MethodRouter<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    // Layers are listed from the innermost to the outermost one.
+    handler
+        .layer::<_, Infallible>(from_fn(latency::handler_span))
+        .layer::<_, Infallible>(from_fn_with_state(policy, apply_deadline))
+        .layer(TimeoutLayer::with_status_code(
+            StatusCode::REQUEST_TIMEOUT,
+            policy.timeout,
+        ))
+        .layer::<_, Infallible>(from_fn_with_state(policy, authorize))
+        .layer::<_, Infallible>(from_fn(rate_limit::limit))
+        .layer::<_, Infallible>(from_fn_with_state(
+            policy,
+            latency::attach_budget,
+        ))
+        .layer(Extension(policy))
+}
+
+/// Enforces the authentication and permission requirements of the route policy.
+pub async fn authorize(
+    State(policy): State<RoutePolicy>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if let Err(e) = policy.check(request.extensions().get::<Principal>()) {
+        return e.into_response();
+    }
+
+    next.run(request).await
+}
+
+/// Applies the timeout of the route policy as the deadline of all database queries made while
+/// handling the request.
+///
+/// Without it, queries of a timed out request could keep running (and writing) after the
+/// response has already been sent.
+pub async fn apply_deadline(
+    State(policy): State<RoutePolicy>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let deadline = Instant::now() + policy.timeout;
+
+    deadline::scope(deadline, next.run(request)).await
+}
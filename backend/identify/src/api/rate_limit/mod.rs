@@ -0,0 +1,507 @@
+//! Rate limiting of callers with token buckets.
+//!
+//! Every caller gets a bucket per [RateLimitClass] of routes, so that exhausting the limit of
+//! regular routes doesn't lock the caller out of the sensitive ones, and the other way around.
+//! Callers are identified by their API key, or by their IP address if they don't have one.
+//! Every client IP address also gets a bucket of its own, which is taken from before the request
+//! is authenticated, so that requests with invalid API keys are limited too, see
+//! [limit_clients].
+//!
+//! Buckets are kept in a [RateLimitStore]: in memory by default, in sharded memory when the
+//! server handles many requests per second, or in the database or Redis when the limits must be
+//! shared by multiple processes.
+
+#[cfg(feature = "redis")]
+mod redis;
+mod sharded;
+
+use std::{
+    collections::HashMap,
+    net::{IpAddr, SocketAddr},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use axum::{
+    Extension,
+    extract::{ConnectInfo, Request, State},
+    http::{HeaderMap, HeaderValue, StatusCode, header::RETRY_AFTER},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use chrono::Utc;
+use eyre::WrapErr as _;
+use identify_infrastructure::{
+    network::Network,
+    storage::rate_limits::{self, BucketParams},
+};
+use metrics::counter;
+use sqlx::SqlitePool;
+use tracing::{debug, warn};
+
+use crate::{
+    api::{
+        auth::Principal,
+        error::ApiError,
+        policy::{RateLimitClass, RoutePolicy},
+    },
+    config::{RateLimitBucketConfig, RateLimitConfig, RateLimitStoreKind},
+    metrics::HTTP_RATE_LIMITED_METRIC,
+    redis::Redis,
+};
+
+/// How often buckets that are full again are forgotten.
+const CLEANUP_INTERVAL: Duration = Duration::from_secs(60);
+/// Header proxies report the addresses of the clients in.
+const X_FORWARDED_FOR: &str = "x-forwarded-for";
+/// Label of the metric of the requests rejected by [limit_clients].
+const CLIENTS_CLASS: &str = "clients";
+
+/// Parameters of a token bucket.
+#[derive(Debug, Clone, Copy)]
+pub struct Bucket {
+    /// Maximum number of requests in a burst.
+    pub capacity: f64,
+    /// Number of requests allowed every second on average.
+    pub refill_per_second: f64,
+}
+
+impl Bucket {
+    /// How long it takes for an empty bucket to become full.
+    fn refill_time(&self) -> f64 {
+        self.capacity / self.refill_per_second
+    }
+}
+
+impl From<RateLimitBucketConfig> for Bucket {
+    fn from(value: RateLimitBucketConfig) -> Self {
+        Bucket {
+            capacity: f64::from(value.capacity),
+            refill_per_second: value.refill_per_second,
+        }
+    }
+}
+
+/// Result of taking a token from a bucket.
+#[derive(Debug, Clone, Copy)]
+pub struct Take {
+    /// Whether the bucket had a token to take.
+    pub allowed: bool,
+    /// Tokens left in the bucket.
+    pub tokens: f64,
+}
+
+/// Storage of the token buckets.
+///
+/// Times are Unix timestamps in seconds.
+#[async_trait]
+pub trait RateLimitStore: Send + Sync {
+    /// Takes a token from the bucket of the key, refilling it first. Buckets that don't exist
+    /// yet start full.
+    async fn take(
+        &self,
+        key: &str,
+        bucket: Bucket,
+        now: f64,
+    ) -> eyre::Result<Take>;
+
+    /// Forgets the buckets that haven't been used since the provided time.
+    async fn forget_idle(&self, idle_since: f64) -> eyre::Result<()>;
+}
+
+#[cfg(feature = "redis")]
+pub use redis::RedisStore;
+pub use sharded::ShardedStore;
+
+/// Keeps the buckets in the memory of the process.
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    /// Tokens in every bucket and when they were counted.
+    buckets: Mutex<HashMap<String, (f64, f64)>>,
+}
+
+#[async_trait]
+impl RateLimitStore for MemoryStore {
+    async fn take(
+        &self,
+        key: &str,
+        bucket: Bucket,
+        now: f64,
+    ) -> eyre::Result<Take> {
+        let mut buckets =
+            self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        let (tokens, updated_at) = buckets
+            .entry(key.to_owned())
+            .or_insert((bucket.capacity, now));
+
+        let elapsed = (now - *updated_at).max(0.0);
+        let refilled =
+            (*tokens + elapsed * bucket.refill_per_second).min(bucket.capacity);
+        let allowed = refilled >= 1.0;
+
+        *tokens = if allowed { refilled - 1.0 } else { refilled };
+        *updated_at = updated_at.max(now);
+
+        Ok(Take {
+            allowed,
+            tokens: *tokens,
+        })
+    }
+
+    async fn forget_idle(&self, idle_since: f64) -> eyre::Result<()> {
+        self.buckets
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|_, (_, updated_at)| *updated_at >= idle_since);
+
+        Ok(())
+    }
+}
+
+/// Keeps the buckets in the database, so that they are shared by all the processes that use
+/// it.
+#[derive(Debug)]
+pub struct SqliteStore {
+    pool: SqlitePool,
+}
+
+impl SqliteStore {
+    pub fn new(pool: SqlitePool) -> Self {
+        SqliteStore { pool }
+    }
+}
+
+#[async_trait]
+impl RateLimitStore for SqliteStore {
+    async fn take(
+        &self,
+        key: &str,
+        bucket: Bucket,
+        now: f64,
+    ) -> eyre::Result<Take> {
+        let params = BucketParams {
+            capacity: bucket.capacity,
+            refill_per_second: bucket.refill_per_second,
+        };
+        let taken =
+            rate_limits::take_token(&self.pool, key, params, now).await?;
+
+        Ok(Take {
+            allowed: taken.allowed,
+            tokens: taken.tokens,
+        })
+    }
+
+    async fn forget_idle(&self, idle_since: f64) -> eyre::Result<()> {
+        rate_limits::delete_idle(&self.pool, idle_since).await?;
+
+        Ok(())
+    }
+}
+
+/// Enforces the rate limits of the routes.
+///
+/// It's attached to the request extensions, so that the middleware of every route can reach
+/// it.
+#[derive(Clone)]
+pub struct RateLimiter {
+    inner: Option<Arc<Inner>>,
+}
+
+struct Inner {
+    store: Arc<dyn RateLimitStore>,
+    default: Bucket,
+    strict: Bucket,
+    /// Bucket of every client IP address, if they are limited before authentication.
+    clients: Option<Bucket>,
+    /// Proxies trusted to report the addresses of the clients.
+    trusted_proxies: Vec<Network>,
+    /// Unix timestamp of the last cleanup, in seconds.
+    last_cleanup: AtomicU64,
+}
+
+/// Address of the client of a request, see [client_ip].
+#[derive(Debug, Clone, Copy)]
+pub struct ClientIp(pub IpAddr);
+
+impl RateLimiter {
+    pub fn new(
+        store: impl RateLimitStore + 'static,
+        default: Bucket,
+        strict: Bucket,
+    ) -> Self {
+        RateLimiter {
+            inner: Some(Arc::new(Inner {
+                store: Arc::new(store),
+                default,
+                strict,
+                clients: None,
+                trusted_proxies: Vec::new(),
+                last_cleanup: AtomicU64::new(0),
+            })),
+        }
+    }
+
+    /// Also limits every client IP address before its requests are authenticated, see
+    /// [limit_clients]. The addresses reported by the trusted proxies are believed.
+    pub fn with_clients(
+        self,
+        bucket: Bucket,
+        trusted_proxies: Vec<Network>,
+    ) -> Self {
+        RateLimiter {
+            inner: self.inner.map(|inner| {
+                Arc::new(Inner {
+                    store: Arc::clone(&inner.store),
+                    default: inner.default,
+                    strict: inner.strict,
+                    clients: Some(bucket),
+                    trusted_proxies,
+                    last_cleanup: AtomicU64::new(0),
+                })
+            }),
+        }
+    }
+
+    /// Creates a rate limiter that lets all requests through.
+    pub fn disabled() -> Self {
+        RateLimiter { inner: None }
+    }
+
+    pub fn from_config(
+        config: &RateLimitConfig,
+        pool: &SqlitePool,
+        redis: Option<&Redis>,
+    ) -> eyre::Result<Self> {
+        if !config.enabled {
+            return Ok(RateLimiter::disabled());
+        }
+
+        let (default, strict) = (config.default.into(), config.strict.into());
+        let rate_limiter = match config.store {
+            RateLimitStoreKind::Memory => {
+                RateLimiter::new(MemoryStore::default(), default, strict)
+            }
+            RateLimitStoreKind::Sharded => RateLimiter::new(
+                ShardedStore::new(config.shards),
+                default,
+                strict,
+            ),
+            RateLimitStoreKind::Sqlite => RateLimiter::new(
+                SqliteStore::new(pool.clone()),
+                default,
+                strict,
+            ),
+            RateLimitStoreKind::Redis => redis
+                .ok_or_else(|| {
+                    eyre::eyre!(
+                        "rate_limit.store is 'redis', but redis.url isn't set"
+                    )
+                })?
+                .rate_limiter(default, strict),
+        };
+        let trusted_proxies = config
+            .trusted_proxies
+            .iter()
+            .map(|network| Network::parse(network))
+            .collect::<eyre::Result<Vec<_>>>()
+            .wrap_err("rate_limit.trusted_proxies is invalid")?;
+
+        Ok(rate_limiter.with_clients(config.clients.into(), trusted_proxies))
+    }
+}
+
+impl Inner {
+    fn bucket(&self, class: RateLimitClass) -> Option<Bucket> {
+        match class {
+            RateLimitClass::Default => Some(self.default),
+            RateLimitClass::Strict => Some(self.strict),
+            RateLimitClass::Unlimited => None,
+        }
+    }
+
+    /// Forgets idle buckets once in a [CLEANUP_INTERVAL], in the background.
+    fn schedule_cleanup(self: &Arc<Self>, now: f64) {
+        let last_cleanup = self.last_cleanup.load(Ordering::Relaxed);
+        if (now as u64).saturating_sub(last_cleanup)
+            < CLEANUP_INTERVAL.as_secs()
+            || self
+                .last_cleanup
+                .compare_exchange(
+                    last_cleanup,
+                    now as u64,
+                    Ordering::Relaxed,
+                    Ordering::Relaxed,
+                )
+                .is_err()
+        {
+            return;
+        }
+
+        // Buckets that have been idle for this long are full again, which is no different from
+        // not having them at all.
+        let idle_since = now
+            - [Some(self.default), Some(self.strict), self.clients]
+                .into_iter()
+                .flatten()
+                .map(|bucket| bucket.refill_time())
+                .fold(0.0, f64::max);
+        let inner = Arc::clone(self);
+        tokio::spawn(async move {
+            if let Err(e) = inner.store.forget_idle(idle_since).await {
+                warn!(error = %e, "Failed to forget idle rate limit buckets");
+            }
+        });
+    }
+}
+
+/// Rejects requests of clients that exceeded the limit of their IP address, before they are
+/// authenticated, and attaches the [ClientIp] to the others.
+///
+/// Requests are let through if the store fails, so that its outage doesn't take the whole API
+/// down.
+pub async fn limit_clients(
+    State(limiter): State<RateLimiter>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let Some(inner) = &limiter.inner else {
+        return next.run(request).await;
+    };
+
+    let ip = request.extensions().get::<ConnectInfo<SocketAddr>>().map(
+        |ConnectInfo(peer)| {
+            client_ip(peer.ip(), request.headers(), &inner.trusted_proxies)
+        },
+    );
+    if let Some(ip) = ip {
+        request.extensions_mut().insert(ClientIp(ip));
+    }
+    let Some(bucket) = inner.clients else {
+        return next.run(request).await;
+    };
+
+    let key = format!("{CLIENTS_CLASS}:{}", caller_ip(&request));
+    take(inner, &key, bucket, CLIENTS_CLASS, request, next).await
+}
+
+/// Rejects requests of callers that exceeded the rate limit of the route.
+///
+/// Requests are let through if the store fails, so that its outage doesn't take the whole API
+/// down.
+pub async fn limit(
+    Extension(limiter): Extension<RateLimiter>,
+    Extension(policy): Extension<RoutePolicy>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(inner) = &limiter.inner else {
+        return next.run(request).await;
+    };
+    let Some(bucket) = inner.bucket(policy.rate_limit) else {
+        return next.run(request).await;
+    };
+
+    let class = policy.rate_limit.as_str();
+    let key = format!("{class}:{}", caller(&request));
+    take(inner, &key, bucket, class, request, next).await
+}
+
+/// Takes a token from the bucket of the key, and lets the request through if there was one.
+async fn take(
+    inner: &Arc<Inner>,
+    key: &str,
+    bucket: Bucket,
+    class: &'static str,
+    request: Request,
+    next: Next,
+) -> Response {
+    let now = Utc::now().timestamp_micros() as f64 / 1_000_000.0;
+
+    let take = inner.store.take(key, bucket, now).await;
+    inner.schedule_cleanup(now);
+
+    match take {
+        Ok(take) if !take.allowed => {
+            debug!(key, "Rate limit exceeded");
+            counter!(HTTP_RATE_LIMITED_METRIC, "class" => class).increment(1);
+
+            let retry_after = ((1.0 - take.tokens) / bucket.refill_per_second)
+                .ceil()
+                .max(1.0);
+            let mut response = ApiError::new(
+                StatusCode::TOO_MANY_REQUESTS,
+                "The rate limit has been exceeded, retry later",
+            )
+            .into_response();
+            response
+                .headers_mut()
+                .insert(RETRY_AFTER, HeaderValue::from(retry_after as u64));
+
+            response
+        }
+        Ok(_) => next.run(request).await,
+        Err(e) => {
+            warn!(error = %e, "Failed to check the rate limit");
+            next.run(request).await
+        }
+    }
+}
+
+/// Resolves the address of the client of a request made by the peer.
+///
+/// Proxies append the address of their own peer to `X-Forwarded-For`, so the client is the
+/// last address that isn't a trusted proxy, going from the peer back through the header. The
+/// addresses before it may have been forged by the client. The header of untrusted peers is
+/// ignored altogether.
+pub fn client_ip(
+    peer: IpAddr,
+    headers: &HeaderMap,
+    trusted_proxies: &[Network],
+) -> IpAddr {
+    let trusted =
+        |ip: IpAddr| trusted_proxies.iter().any(|network| network.contains(ip));
+
+    let mut client = peer;
+    let forwarded = headers
+        .get_all(X_FORWARDED_FOR)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .collect::<Vec<_>>();
+    for address in forwarded.into_iter().rev() {
+        if !trusted(client) {
+            break;
+        }
+        match address.trim().parse() {
+            Ok(ip) => client = ip,
+            Err(_) => break,
+        }
+    }
+
+    client
+}
+
+/// Identifies the caller of the request.
+fn caller(request: &Request) -> String {
+    if let Some(principal) = request.extensions().get::<Principal>() {
+        return format!("key:{}", principal.subject);
+    }
+
+    caller_ip(request)
+}
+
+fn caller_ip(request: &Request) -> String {
+    if let Some(ClientIp(ip)) = request.extensions().get::<ClientIp>() {
+        return format!("ip:{ip}");
+    }
+
+    match request.extensions().get::<ConnectInfo<SocketAddr>>() {
+        Some(ConnectInfo(address)) => format!("ip:{}", address.ip()),
+        None => "ip:unknown".to_owned(),
+    }
+}
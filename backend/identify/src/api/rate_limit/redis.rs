@@ -0,0 +1,49 @@
+//! Store shared by all the processes that use the same Redis server.
+
+use async_trait::async_trait;
+use identify_infrastructure::{
+    redis::{RedisClient, rate_limits},
+    storage::rate_limits::BucketParams,
+};
+
+use crate::api::rate_limit::{Bucket, RateLimitStore, Take};
+
+/// Keeps the buckets in Redis.
+///
+/// Buckets expire on their own once they would be full again, so there's nothing to forget.
+#[derive(Clone)]
+pub struct RedisStore {
+    client: RedisClient,
+}
+
+impl RedisStore {
+    pub fn new(client: RedisClient) -> Self {
+        RedisStore { client }
+    }
+}
+
+#[async_trait]
+impl RateLimitStore for RedisStore {
+    async fn take(
+        &self,
+        key: &str,
+        bucket: Bucket,
+        now: f64,
+    ) -> eyre::Result<Take> {
+        let params = BucketParams {
+            capacity: bucket.capacity,
+            refill_per_second: bucket.refill_per_second,
+        };
+        let taken =
+            rate_limits::take_token(&self.client, key, params, now).await?;
+
+        Ok(Take {
+            allowed: taken.allowed,
+            tokens: taken.tokens,
+        })
+    }
+
+    async fn forget_idle(&self, _idle_since: f64) -> eyre::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,134 @@
+//! In-memory store for servers that handle many requests per second.
+//!
+//! Buckets are kept in a [DashMap], whose shards have their own locks, and the locks are only
+//! taken for writing when buckets are added or forgotten. The state of a bucket is a single
+//! atomic, the theoretical arrival time of the generic cell rate algorithm (GCRA), so taking
+//! tokens from a bucket never blocks the other callers of its shard.
+
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    thread,
+};
+
+use async_trait::async_trait;
+use dashmap::{DashMap, try_result::TryResult};
+use metrics::{counter, gauge};
+
+use crate::{
+    api::rate_limit::{Bucket, RateLimitStore, Take},
+    metrics::{RATE_LIMIT_BUCKETS_METRIC, RATE_LIMIT_SHARD_CONTENTION_METRIC},
+};
+
+/// Shards per CPU used when the number of shards isn't configured.
+const SHARDS_PER_CPU: usize = 4;
+
+/// Keeps the buckets in a sharded map in the memory of the process.
+///
+/// A bucket is the time (in microseconds since the Unix epoch) at which it's going to be full
+/// again if nobody takes any more tokens from it. Buckets that don't exist yet, or whose time
+/// has already passed, are full.
+#[derive(Debug)]
+pub struct ShardedStore {
+    buckets: DashMap<String, AtomicU64>,
+}
+
+impl ShardedStore {
+    /// Creates a store with the provided number of shards, or a few shards per CPU if it's not
+    /// set. The number is rounded up to a power of two, and to at least two shards.
+    pub fn new(shards: Option<usize>) -> Self {
+        let shards = shards.unwrap_or_else(|| {
+            thread::available_parallelism().map_or(1, usize::from)
+                * SHARDS_PER_CPU
+        });
+
+        ShardedStore {
+            buckets: DashMap::with_shard_amount(
+                shards.max(2).next_power_of_two(),
+            ),
+        }
+    }
+}
+
+#[async_trait]
+impl RateLimitStore for ShardedStore {
+    async fn take(
+        &self,
+        key: &str,
+        bucket: Bucket,
+        now: f64,
+    ) -> eyre::Result<Take> {
+        match self.buckets.try_get(key) {
+            TryResult::Present(state) => {
+                return Ok(take_token(&state, bucket, now));
+            }
+            TryResult::Absent => {}
+            TryResult::Locked => {
+                counter!(RATE_LIMIT_SHARD_CONTENTION_METRIC).increment(1);
+                if let Some(state) = self.buckets.get(key) {
+                    return Ok(take_token(&state, bucket, now));
+                }
+            }
+        }
+
+        let state = match self.buckets.try_entry(key.to_owned()) {
+            Some(entry) => entry,
+            None => {
+                counter!(RATE_LIMIT_SHARD_CONTENTION_METRIC).increment(1);
+                self.buckets.entry(key.to_owned())
+            }
+        }
+        .or_insert_with(|| AtomicU64::new(0));
+
+        Ok(take_token(&state, bucket, now))
+    }
+
+    async fn forget_idle(&self, idle_since: f64) -> eyre::Result<()> {
+        let idle_since = micros(idle_since);
+
+        self.buckets
+            .retain(|_, state| state.load(Ordering::Relaxed) >= idle_since);
+        gauge!(RATE_LIMIT_BUCKETS_METRIC).set(self.buckets.len() as f64);
+
+        Ok(())
+    }
+}
+
+/// Takes a token from the bucket, retrying if other callers took tokens in the meantime.
+fn take_token(state: &AtomicU64, bucket: Bucket, now: f64) -> Take {
+    let now = micros(now) as f64;
+    // Time it takes to refill a single token, and to refill the whole bucket.
+    let interval = 1_000_000.0 / bucket.refill_per_second;
+    let tolerance = bucket.capacity * interval;
+
+    let mut current = state.load(Ordering::Acquire);
+    loop {
+        let full_at = (current as f64).max(now) + interval;
+        let tokens = (tolerance - (full_at - now)) / interval;
+        if tokens < 0.0 {
+            return Take {
+                allowed: false,
+                tokens: tokens + 1.0,
+            };
+        }
+
+        match state.compare_exchange_weak(
+            current,
+            full_at as u64,
+            Ordering::AcqRel,
+            Ordering::Acquire,
+        ) {
+            Ok(_) => {
+                return Take {
+                    allowed: true,
+                    tokens,
+                };
+            }
+            Err(actual) => current = actual,
+        }
+    }
+}
+
+/// Converts a Unix timestamp in seconds to microseconds.
+fn micros(timestamp: f64) -> u64 {
+    (timestamp * 1_000_000.0) as u64
+}
@@ -0,0 +1,196 @@
+use std::sync::Arc;
+
+use axum::{
+    body::Bytes,
+    extract::{DefaultBodyLimit, Path, State, rejection::BytesRejection},
+    http::{
+        HeaderMap, HeaderName, HeaderValue, StatusCode,
+        header::{CACHE_CONTROL, CONTENT_TYPE},
+    },
+    response::{IntoResponse, Response},
+    routing::{get, put},
+};
+use identify_application::{
+    ApplicationError, BlobStorage, GetAvatarParams, UploadAvatarParams,
+    get_avatar, upload_avatar,
+};
+use identify_domain::{Avatar, AvatarFormat};
+use utoipa::OpenApi;
+use uuid::Uuid;
+
+use crate::api::{
+    Route, Service,
+    conditional::{ContentTag, IfNoneMatch},
+    error::{ApiError, PROBLEM_CONTENT_TYPE, Problem},
+    policy::RoutePolicy,
+    use_case::{ReadContext, UseCaseContext},
+};
+
+/// How long clients may use an avatar before revalidating it, in seconds.
+const MAX_AGE_SECS: u32 = 60;
+
+pub struct AvatarService;
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(get_handler, put_handler),
+    tags((name = "users", description = "Users of the service"))
+)]
+struct AvatarApi;
+
+impl Service for AvatarService {
+    fn routes() -> Vec<Route> {
+        vec![
+            Route::new(
+                "/users/{id}/avatar",
+                get(get_handler),
+                RoutePolicy::authenticated().permissions(&["users:read"]),
+            ),
+            Route::new(
+                "/users/{id}/avatar",
+                put(put_handler).layer(DefaultBodyLimit::max(Avatar::MAX_SIZE)),
+                RoutePolicy::authenticated().permissions(&["users:write"]),
+            ),
+        ]
+    }
+
+    fn openapi() -> utoipa::openapi::OpenApi {
+        AvatarApi::openapi()
+    }
+}
+
+/// Returns the headers of an avatar, which only the caller may cache.
+fn cache_headers(tag: &ContentTag) -> [(HeaderName, HeaderValue); 2] {
+    let [etag] = tag.header();
+    let cache_control =
+        HeaderValue::from_str(&format!("private, max-age={MAX_AGE_SECS}"))
+            .expect("the cache control is a valid header value");
+
+    [etag, (CACHE_CONTROL, cache_control)]
+}
+
+/// Returns the avatar of a user.
+///
+/// Avatars may be cached by the caller for a minute, and revalidated with `If-None-Match`
+/// afterwards.
+#[utoipa::path(
+    get,
+    path = "/users/{id}/avatar",
+    operation_id = "get_user_avatar",
+    tag = "users",
+    params(("id" = Uuid, Path, description = "ID of the user")),
+    responses(
+        (status = OK, description = "The avatar", content(
+            (Vec<u8> = "image/png"),
+            (Vec<u8> = "image/jpeg"),
+            (Vec<u8> = "image/gif"),
+            (Vec<u8> = "image/webp"),
+        ), headers(
+            ("ETag" = String, description = "ETag of the avatar"),
+            ("Cache-Control" = String, description = "How long the avatar may be cached"),
+        )),
+        (status = NOT_MODIFIED, description = "The avatar matches one of the ETags of `If-None-Match`",
+            headers(("ETag" = String, description = "ETag of the avatar"))),
+        (status = NOT_FOUND, description = "The user doesn't exist or has no avatar", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+    ),
+    security(("api_key" = ["users:read"])),
+)]
+async fn get_handler(
+    ReadContext(mut context): ReadContext,
+    State(storage): State<Arc<dyn BlobStorage>>,
+    Path(id): Path<Uuid>,
+    if_none_match: IfNoneMatch,
+) -> Result<Response, ApiError> {
+    let avatar = get_avatar(
+        context.avatars(storage.as_ref()).await?,
+        GetAvatarParams { user_id: id },
+    )
+    .await?;
+
+    let tag = ContentTag::new(avatar.bytes());
+    if if_none_match.matches(&tag) {
+        return Ok(
+            (StatusCode::NOT_MODIFIED, cache_headers(&tag)).into_response()
+        );
+    }
+
+    Ok((
+        cache_headers(&tag),
+        [(CONTENT_TYPE, avatar.format().content_type())],
+        avatar.into_bytes(),
+    )
+        .into_response())
+}
+
+/// Uploads the avatar of a user, replacing the previous one.
+///
+/// The body is the image itself, in PNG, JPEG, GIF or WebP, with the matching `Content-Type`.
+/// Avatars are at most 1 MiB large.
+#[utoipa::path(
+    put,
+    path = "/users/{id}/avatar",
+    operation_id = "put_user_avatar",
+    tag = "users",
+    params(("id" = Uuid, Path, description = "ID of the user")),
+    request_body(content(
+        (Vec<u8> = "image/png"),
+        (Vec<u8> = "image/jpeg"),
+        (Vec<u8> = "image/gif"),
+        (Vec<u8> = "image/webp"),
+    )),
+    responses(
+        (status = NO_CONTENT, description = "The avatar was stored",
+            headers(("ETag" = String, description = "ETag of the avatar"))),
+        (status = NOT_FOUND, description = "The user doesn't exist", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+        (status = PAYLOAD_TOO_LARGE, description = "The avatar is larger than 1 MiB", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+        (status = UNSUPPORTED_MEDIA_TYPE, description = "The content type isn't a supported image format", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+        (status = UNPROCESSABLE_ENTITY, description = "The avatar is empty or doesn't match its content type", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+    ),
+    security(("api_key" = ["users:write"])),
+)]
+async fn put_handler(
+    context: UseCaseContext,
+    State(storage): State<Arc<dyn BlobStorage>>,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+    body: Result<Bytes, BytesRejection>,
+) -> Result<Response, ApiError> {
+    let content_type = headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    if AvatarFormat::from_content_type(content_type).is_none() {
+        return Err(ApiError::new(
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            format!(
+                "Avatars must be one of: {}",
+                AvatarFormat::ALL
+                    .map(|format| format.content_type())
+                    .join(", ")
+            ),
+        ));
+    }
+    let body = body.map_err(|e| ApiError::new(e.status(), e.body_text()))?;
+    let avatar = Avatar::parse(content_type, body.to_vec())
+        .map_err(ApplicationError::from)?;
+
+    let avatar = context
+        .retried("upload_avatar", |mut context| async {
+            let avatar = upload_avatar(
+                context.avatars(storage.as_ref()).await?,
+                UploadAvatarParams {
+                    user_id: id,
+                    avatar: avatar.clone(),
+                },
+            )
+            .await?;
+            context.commit().await?;
+
+            Ok(avatar)
+        })
+        .await?;
+
+    let tag = ContentTag::new(avatar.bytes());
+
+    Ok((StatusCode::NO_CONTENT, tag.header()).into_response())
+}
@@ -0,0 +1,215 @@
+use std::sync::Arc;
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    routing::{get, post},
+};
+use chrono::{DateTime, Utc};
+use identify_application::{
+    AcceptPolicyParams, ListConsentsParams, accept_policy, list_consents,
+};
+use identify_domain::{Consent, ConsentPolicies, PolicyVersion};
+use serde::{Deserialize, Serialize};
+use utoipa::{OpenApi, ToSchema};
+use uuid::Uuid;
+
+use crate::api::{
+    Route, Service,
+    error::{ApiError, PROBLEM_CONTENT_TYPE, Problem},
+    policy::RoutePolicy,
+    use_case::{ReadContext, UseCaseContext},
+    validation::{ValidJson, Validate, Validator},
+};
+
+const MAX_POLICY_LENGTH: usize = 64;
+
+pub struct ConsentService;
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(list_handler, accept_handler),
+    tags((name = "users", description = "Users of the service"))
+)]
+struct ConsentApi;
+
+impl Service for ConsentService {
+    fn routes() -> Vec<Route> {
+        vec![
+            Route::new(
+                "/users/{id}/consents",
+                get(list_handler),
+                RoutePolicy::authenticated().permissions(&["users:read"]),
+            ),
+            Route::new(
+                "/users/{id}/consents",
+                post(accept_handler),
+                RoutePolicy::authenticated().permissions(&["users:write"]),
+            ),
+        ]
+    }
+
+    fn openapi() -> utoipa::openapi::OpenApi {
+        ConsentApi::openapi()
+    }
+}
+
+/// The acceptance of a version of a policy by a user.
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(as = Consent)]
+pub struct ConsentResponse {
+    pub id: Uuid,
+    #[schema(example = "terms")]
+    pub policy: String,
+    #[schema(example = "2026-10")]
+    pub version: String,
+    pub accepted_at: DateTime<Utc>,
+}
+
+impl From<Consent> for ConsentResponse {
+    fn from(value: Consent) -> Self {
+        ConsentResponse {
+            id: value.id(),
+            policy: value.policy().to_owned(),
+            version: value.version().to_owned(),
+            accepted_at: *value.accepted_at(),
+        }
+    }
+}
+
+/// A version of a policy the user has yet to accept.
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(as = PendingPolicy)]
+pub struct PendingPolicyResponse {
+    #[schema(example = "terms")]
+    pub policy: String,
+    #[schema(example = "2026-10")]
+    pub version: String,
+}
+
+impl From<PolicyVersion> for PendingPolicyResponse {
+    fn from(value: PolicyVersion) -> Self {
+        PendingPolicyResponse {
+            policy: value.policy,
+            version: value.version,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(as = ConsentList)]
+pub struct ConsentListResponse {
+    /// All the consents of the user, including the ones to outdated versions, the most recent
+    /// first.
+    pub items: Vec<ConsentResponse>,
+    /// Current versions of the policies the user has to accept before they can log in.
+    pub pending: Vec<PendingPolicyResponse>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[schema(as = AcceptPolicy)]
+pub struct AcceptPolicyRequest {
+    // Missing required fields are reported by the validation.
+    #[serde(default)]
+    #[schema(required = true, max_length = 64, example = "terms")]
+    pub policy: String,
+    /// Version the user was shown, which has to be the current one.
+    #[serde(default)]
+    #[schema(required = true, max_length = 64, example = "2026-10")]
+    pub version: String,
+}
+
+impl Validate for AcceptPolicyRequest {
+    fn validate(&self, validator: &mut Validator) {
+        validator
+            .field("policy", Some(&self.policy))
+            .required()
+            .max_length(MAX_POLICY_LENGTH);
+        validator
+            .field("version", Some(&self.version))
+            .required()
+            .max_length(MAX_POLICY_LENGTH);
+    }
+}
+
+/// Lists the versions of policies a user accepted, and the ones they have yet to accept.
+#[utoipa::path(
+    get,
+    path = "/users/{id}/consents",
+    operation_id = "list_user_consents",
+    tag = "users",
+    params(("id" = Uuid, Path, description = "ID of the user")),
+    responses(
+        (status = OK, description = "The consents of the user", body = ConsentListResponse),
+        (status = NOT_FOUND, description = "The user doesn't exist", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+    ),
+    security(("api_key" = ["users:read"])),
+)]
+async fn list_handler(
+    ReadContext(mut context): ReadContext,
+    State(policies): State<Arc<ConsentPolicies>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ConsentListResponse>, ApiError> {
+    let consents = list_consents(
+        context.consents(&policies).await?,
+        ListConsentsParams { user_id: id },
+    )
+    .await?;
+
+    Ok(Json(ConsentListResponse {
+        items: consents.consents.into_iter().map(Into::into).collect(),
+        pending: consents.pending.into_iter().map(Into::into).collect(),
+    }))
+}
+
+/// Records that a user accepted the current version of a policy, e.g. the terms of service.
+///
+/// Accepting a version the user already accepted returns the original consent.
+#[utoipa::path(
+    post,
+    path = "/users/{id}/consents",
+    operation_id = "accept_user_policy",
+    tag = "users",
+    params(("id" = Uuid, Path, description = "ID of the user")),
+    request_body = AcceptPolicyRequest,
+    responses(
+        (status = CREATED, description = "The new consent", body = ConsentResponse),
+        (status = OK, description = "The user had already accepted the version", body = ConsentResponse),
+        (status = NOT_FOUND, description = "The user or the policy doesn't exist", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+        (status = CONFLICT, description = "The user has been erased", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+        (status = UNPROCESSABLE_ENTITY, description = "The body is invalid, or the version isn't the current one", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+    ),
+    security(("api_key" = ["users:write"])),
+)]
+async fn accept_handler(
+    context: UseCaseContext,
+    State(policies): State<Arc<ConsentPolicies>>,
+    Path(id): Path<Uuid>,
+    ValidJson(request): ValidJson<AcceptPolicyRequest>,
+) -> Result<(StatusCode, Json<ConsentResponse>), ApiError> {
+    let accepted = context
+        .retried("accept_policy", |mut context| async {
+            let accepted = accept_policy(
+                context.consents(&policies).await?,
+                AcceptPolicyParams {
+                    user_id: id,
+                    policy: request.policy.clone(),
+                    version: request.version.clone(),
+                },
+            )
+            .await?;
+            context.commit().await?;
+
+            Ok(accepted)
+        })
+        .await?;
+
+    let status = if accepted.newly_accepted {
+        StatusCode::CREATED
+    } else {
+        StatusCode::OK
+    };
+
+    Ok((status, Json(accepted.consent.into())))
+}
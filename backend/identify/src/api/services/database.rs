@@ -0,0 +1,176 @@
+use axum::{Json, extract::State, routing::get};
+use chrono::{DateTime, Utc};
+use identify_infrastructure::storage::diagnostics::{
+    self, DatabaseStats, MigrationStats, PoolStats, StorageStats, TableStats,
+};
+use serde::Serialize;
+use sqlx::SqlitePool;
+use utoipa::{OpenApi, ToSchema};
+
+use crate::api::{Route, Service, error::ApiError, policy::RoutePolicy};
+
+pub struct DatabaseService;
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(stats_handler),
+    tags((name = "database", description = "Health and statistics of the database"))
+)]
+struct DatabaseApi;
+
+impl Service for DatabaseService {
+    fn routes() -> Vec<Route> {
+        vec![Route::new(
+            "/admin/db/stats",
+            get(stats_handler),
+            RoutePolicy::admin(),
+        )]
+    }
+
+    fn openapi() -> utoipa::openapi::OpenApi {
+        DatabaseApi::openapi()
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(as = DatabaseStats)]
+pub struct DatabaseStatsResponse {
+    pub pool: PoolStatsResponse,
+    pub storage: StorageStatsResponse,
+    /// Tables of the schema, by name.
+    pub tables: Vec<TableStatsResponse>,
+    /// The most recent migration, unless the database has never been migrated.
+    pub last_migration: Option<MigrationResponse>,
+}
+
+impl From<DatabaseStats> for DatabaseStatsResponse {
+    fn from(value: DatabaseStats) -> Self {
+        DatabaseStatsResponse {
+            pool: value.pool.into(),
+            storage: value.storage.into(),
+            tables: value.tables.into_iter().map(Into::into).collect(),
+            last_migration: value.last_migration.map(Into::into),
+        }
+    }
+}
+
+/// Connections of the pool the writes go through.
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(as = DatabasePoolStats)]
+pub struct PoolStatsResponse {
+    /// Open connections, including the idle ones.
+    #[schema(example = 4)]
+    pub size: u32,
+    #[schema(example = 3)]
+    pub idle: usize,
+    #[schema(example = 10)]
+    pub max_connections: u32,
+    /// How long it took to acquire a connection for these statistics, in milliseconds.
+    #[schema(example = 0.2)]
+    pub acquire_wait_ms: f64,
+}
+
+impl From<PoolStats> for PoolStatsResponse {
+    fn from(value: PoolStats) -> Self {
+        PoolStatsResponse {
+            size: value.size,
+            idle: value.idle,
+            max_connections: value.max_connections,
+            acquire_wait_ms: value.acquire_wait.as_secs_f64() * 1000.0,
+        }
+    }
+}
+
+/// Pages and journal of the database file.
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(as = DatabaseStorageStats)]
+pub struct StorageStatsResponse {
+    #[schema(example = 4096)]
+    pub page_size: i64,
+    #[schema(example = 1024)]
+    pub page_count: i64,
+    /// Unused pages, which are reclaimed when the database is vacuumed.
+    #[schema(example = 12)]
+    pub freelist_count: i64,
+    /// Size of the database file, without the write-ahead log.
+    #[schema(example = 4194304)]
+    pub size_bytes: i64,
+    #[schema(example = "wal")]
+    pub journal_mode: String,
+    /// Size of the write-ahead log, if the database uses one.
+    #[schema(example = 32768)]
+    pub wal_size_bytes: Option<u64>,
+}
+
+impl From<StorageStats> for StorageStatsResponse {
+    fn from(value: StorageStats) -> Self {
+        StorageStatsResponse {
+            size_bytes: value.size(),
+            page_size: value.page_size,
+            page_count: value.page_count,
+            freelist_count: value.freelist_count,
+            journal_mode: value.journal_mode,
+            wal_size_bytes: value.wal_size,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(as = DatabaseTableStats)]
+pub struct TableStatsResponse {
+    #[schema(example = "users")]
+    pub name: String,
+    #[schema(example = 1250)]
+    pub rows: i64,
+}
+
+impl From<TableStats> for TableStatsResponse {
+    fn from(value: TableStats) -> Self {
+        TableStatsResponse {
+            name: value.name,
+            rows: value.rows,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(as = DatabaseMigration)]
+pub struct MigrationResponse {
+    #[schema(example = 20261016200000_i64)]
+    pub version: i64,
+    #[schema(example = "add users search")]
+    pub description: String,
+    pub installed_at: DateTime<Utc>,
+}
+
+impl From<MigrationStats> for MigrationResponse {
+    fn from(value: MigrationStats) -> Self {
+        MigrationResponse {
+            version: value.version,
+            description: value.description,
+            installed_at: value.installed_on,
+        }
+    }
+}
+
+/// Returns the connections of the pool, the size of the database, the number of rows of every
+/// table and the last applied migration.
+///
+/// Rows are counted table by table, so the request reads the whole database.
+#[utoipa::path(
+    get,
+    path = "/admin/db/stats",
+    operation_id = "get_database_stats",
+    tag = "database",
+    responses(
+        (status = OK, description = "Statistics of the database", body = DatabaseStatsResponse),
+    ),
+    security(("api_key" = [])),
+)]
+async fn stats_handler(
+    State(pool): State<SqlitePool>,
+) -> Result<Json<DatabaseStatsResponse>, ApiError> {
+    let stats = diagnostics::stats(&pool).await?;
+
+    Ok(Json(stats.into()))
+}
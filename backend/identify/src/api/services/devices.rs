@@ -0,0 +1,486 @@
+use std::sync::Arc;
+
+use axum::{
+    Json,
+    extract::{FromRef, Path, State},
+    http::StatusCode,
+    routing::{get, post},
+};
+use chrono::{DateTime, Utc};
+use identify_application::{
+    EmailSender, EmailTemplates, GeoResolver, IssueSessionParams,
+    ListDevicesParams, RecordDeviceLoginParams, RevokeDeviceParams,
+    RevokeDeviceSessionsParams, SessionIssuanceDeps, SessionUseCaseDeps,
+    TokenGenerator, issue_session, list_devices, record_device_login,
+    revoke_device, revoke_device_sessions,
+};
+use identify_domain::{ConsentPolicies, Device};
+use serde::{Deserialize, Serialize};
+use utoipa::{OpenApi, ToSchema};
+use uuid::Uuid;
+
+use crate::{
+    api::{
+        ApiState, Route, Service,
+        error::{ApiError, PROBLEM_CONTENT_TYPE, Problem},
+        policy::RoutePolicy,
+        services::sessions::SessionSettings,
+        use_case::{ReadContext, UseCaseContext},
+        validation::{ValidJson, Validate, Validator},
+    },
+    funnel::{LoginFunnel, LoginStep},
+    logins::LoginTracker,
+    session_monitor::{SessionActivity, SessionMonitor},
+};
+
+const MAX_USER_AGENT_LENGTH: usize = 512;
+const MAX_FINGERPRINT_LENGTH: usize = 256;
+
+pub struct DeviceService;
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        record_login_handler,
+        list_handler,
+        revoke_handler,
+        start_attempt_handler,
+        record_step_handler
+    ),
+    tags((name = "users", description = "Users of the service"))
+)]
+struct DeviceApi;
+
+impl Service for DeviceService {
+    fn routes() -> Vec<Route> {
+        vec![
+            Route::new(
+                "/users/{id}/logins",
+                post(record_login_handler),
+                RoutePolicy::authenticated().permissions(&["users:write"]),
+            ),
+            Route::new(
+                "/users/{id}/devices",
+                get(list_handler),
+                RoutePolicy::authenticated().permissions(&["users:read"]),
+            ),
+            Route::new(
+                "/users/{id}/devices/{device_id}/revoke",
+                post(revoke_handler),
+                RoutePolicy::authenticated().permissions(&["users:write"]),
+            ),
+            Route::new(
+                "/login-attempts",
+                post(start_attempt_handler),
+                RoutePolicy::authenticated().permissions(&["users:write"]),
+            ),
+            Route::new(
+                "/login-attempts/{id}/steps",
+                post(record_step_handler),
+                RoutePolicy::authenticated().permissions(&["users:write"]),
+            ),
+        ]
+    }
+
+    fn openapi() -> utoipa::openapi::OpenApi {
+        DeviceApi::openapi()
+    }
+}
+
+/// A device a user logged in from. The fingerprint of the device is never returned.
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(as = Device)]
+pub struct DeviceResponse {
+    pub id: Uuid,
+    /// `User-Agent` of the latest login.
+    #[schema(
+        example = "Mozilla/5.0 (X11; Linux x86_64; rv:131.0) Gecko/20100101 Firefox/131.0"
+    )]
+    pub user_agent: String,
+    /// IP address of the latest login.
+    #[schema(example = "203.0.113.7")]
+    pub ip: String,
+    /// Approximate location of the IP address, if it's known.
+    #[schema(example = "Paris, France")]
+    pub location: Option<String>,
+    pub first_seen_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+    /// When the device was revoked.
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+impl From<Device> for DeviceResponse {
+    fn from(value: Device) -> Self {
+        DeviceResponse {
+            id: value.id(),
+            user_agent: value.user_agent().to_owned(),
+            ip: value.ip().to_string(),
+            location: value.location().clone(),
+            first_seen_at: *value.first_seen_at(),
+            last_seen_at: *value.last_seen_at(),
+            revoked_at: *value.revoked_at(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(as = DeviceList)]
+pub struct DeviceListResponse {
+    pub items: Vec<DeviceResponse>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(as = DeviceLogin)]
+pub struct DeviceLoginResponse {
+    pub device: DeviceResponse,
+    /// Whether the user hadn't logged in from the device before, or had revoked it.
+    pub new_device: bool,
+    /// Session issued by the login.
+    pub session: IssuedSessionResponse,
+}
+
+/// A session issued by a login, see `/sessions/verify`.
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(as = IssuedSession)]
+pub struct IssuedSessionResponse {
+    /// Secret token of the session. It's only returned once.
+    pub token: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[schema(as = RecordLogin)]
+pub struct RecordLoginRequest {
+    // Missing required fields are reported by the validation.
+    #[serde(default)]
+    #[schema(
+        required = true,
+        max_length = 512,
+        example = "Mozilla/5.0 (X11; Linux x86_64; rv:131.0) Gecko/20100101 Firefox/131.0"
+    )]
+    pub user_agent: String,
+    #[serde(default)]
+    #[schema(required = true, example = "203.0.113.7")]
+    pub ip: String,
+    /// Opaque value that identifies the device across logins, e.g. a long-lived cookie set by
+    /// the login page. Devices are told apart by their user agents if it's missing.
+    #[schema(max_length = 256)]
+    pub fingerprint: Option<String>,
+    /// ID of the [login attempt](LoginAttemptResponse) the login ends, if it was tracked.
+    pub attempt_id: Option<Uuid>,
+}
+
+/// A login attempt tracked by the login funnel, see [funnel](crate::funnel).
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(as = LoginAttempt)]
+pub struct LoginAttemptResponse {
+    /// Correlation ID of the attempt, which its steps and the login it ends with are recorded
+    /// with.
+    pub id: Uuid,
+}
+
+/// A step of a login attempt between its start and the login it ends with.
+#[derive(Debug, Clone, Copy, Deserialize, ToSchema)]
+pub enum LoginAttemptStep {
+    #[serde(rename = "password_ok")]
+    PasswordOk,
+    #[serde(rename = "2fa_prompted")]
+    TwoFactorPrompted,
+    #[serde(rename = "2fa_ok")]
+    TwoFactorOk,
+}
+
+impl From<LoginAttemptStep> for LoginStep {
+    fn from(value: LoginAttemptStep) -> Self {
+        match value {
+            LoginAttemptStep::PasswordOk => LoginStep::PasswordOk,
+            LoginAttemptStep::TwoFactorPrompted => LoginStep::TwoFactorPrompted,
+            LoginAttemptStep::TwoFactorOk => LoginStep::TwoFactorOk,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[schema(as = RecordLoginStep)]
+pub struct RecordLoginStepRequest {
+    pub step: LoginAttemptStep,
+}
+
+impl Validate for RecordLoginStepRequest {
+    /// Unknown steps are rejected when the body is deserialized.
+    fn validate(&self, _validator: &mut Validator) {}
+}
+
+impl Validate for RecordLoginRequest {
+    fn validate(&self, validator: &mut Validator) {
+        validator
+            .field("user_agent", Some(&self.user_agent))
+            .required()
+            .max_length(MAX_USER_AGENT_LENGTH);
+        validator
+            .field("ip", Some(&self.ip))
+            .required()
+            .ip_address();
+        validator
+            .field("fingerprint", self.fingerprint.as_deref())
+            .min_length(1)
+            .max_length(MAX_FINGERPRINT_LENGTH);
+    }
+}
+
+/// What recording a login takes besides the unit of work.
+#[derive(Clone)]
+struct LoginServices {
+    geo_resolver: Arc<dyn GeoResolver>,
+    sender: Arc<dyn EmailSender>,
+    templates: Arc<EmailTemplates>,
+    policies: Arc<ConsentPolicies>,
+    sessions: SessionMonitor,
+    session_tokens: SessionSettings,
+    tokens: Arc<dyn TokenGenerator>,
+    logins: LoginTracker,
+    funnel: LoginFunnel,
+}
+
+impl FromRef<ApiState> for LoginServices {
+    fn from_ref(state: &ApiState) -> Self {
+        LoginServices {
+            geo_resolver: state.geo_resolver.clone(),
+            sender: state.email_sender.clone(),
+            templates: state.email_templates.clone(),
+            policies: state.consent_policies.clone(),
+            sessions: state.sessions.clone(),
+            session_tokens: state.session_tokens.clone(),
+            tokens: state.token_generator.clone(),
+            logins: state.logins.clone(),
+            funnel: state.funnel.clone(),
+        }
+    }
+}
+
+/// Records a login of a user, e.g. by the login page once the user is authenticated.
+///
+/// The user is emailed about logins from devices they haven't logged in from before, except
+/// for their very first one. The login is refused if the user hasn't accepted the current
+/// version of every policy, which the caller should have them do before trying again. A
+/// successful login issues the session of the login attempt it ends, if any.
+#[utoipa::path(
+    post,
+    path = "/users/{id}/logins",
+    operation_id = "record_user_login",
+    tag = "users",
+    params(("id" = Uuid, Path, description = "ID of the user")),
+    request_body = RecordLoginRequest,
+    responses(
+        (status = CREATED, description = "The login was from a new device", body = DeviceLoginResponse),
+        (status = OK, description = "The login was from a known device", body = DeviceLoginResponse),
+        (status = UNAUTHORIZED, description = "Consent required: the user has yet to accept the policies listed in `errors`, e.g. `terms@2026-10`", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+        (status = NOT_FOUND, description = "The user doesn't exist", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+        (status = UNPROCESSABLE_ENTITY, description = "The body is invalid", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+    ),
+    security(("api_key" = ["users:write"])),
+)]
+async fn record_login_handler(
+    context: UseCaseContext,
+    State(services): State<LoginServices>,
+    Path(id): Path<Uuid>,
+    ValidJson(request): ValidJson<RecordLoginRequest>,
+) -> Result<(StatusCode, Json<DeviceLoginResponse>), ApiError> {
+    let ip = request
+        .ip
+        .parse()
+        .expect("the IP address is checked by the validation");
+
+    let login = context
+        .retried("record_device_login", |context| async {
+            let login = record_device_login(
+                context.device_logins(
+                    services.geo_resolver.as_ref(),
+                    services.sender.as_ref(),
+                    &services.templates,
+                    &services.policies,
+                ),
+                RecordDeviceLoginParams {
+                    user_id: id,
+                    fingerprint: request.fingerprint.clone(),
+                    user_agent: request.user_agent.clone(),
+                    ip,
+                },
+            )
+            .await?;
+
+            Ok(login)
+        })
+        .await?;
+
+    let issued = issue_session(
+        SessionIssuanceDeps::new(
+            services.session_tokens.store.as_ref(),
+            services.tokens.as_ref(),
+            &services.logins,
+        ),
+        IssueSessionParams {
+            user_id: id,
+            device_id: login.device.id(),
+            ttl: services.session_tokens.valid_for,
+        },
+    )
+    .await?;
+
+    services
+        .sessions
+        .publish(SessionActivity::login(&login.device, login.new_device));
+    if let Some(attempt_id) = request.attempt_id {
+        services.funnel.record(attempt_id, LoginStep::SessionIssued);
+    }
+
+    let status = if login.new_device {
+        StatusCode::CREATED
+    } else {
+        StatusCode::OK
+    };
+
+    Ok((
+        status,
+        Json(DeviceLoginResponse {
+            device: login.device.into(),
+            new_device: login.new_device,
+            session: IssuedSessionResponse {
+                token: issued.token,
+                expires_at: issued.session.expires_at,
+            },
+        }),
+    ))
+}
+
+/// Lists the devices a user logged in from, the most recently seen first.
+#[utoipa::path(
+    get,
+    path = "/users/{id}/devices",
+    operation_id = "list_user_devices",
+    tag = "users",
+    params(("id" = Uuid, Path, description = "ID of the user")),
+    responses(
+        (status = OK, description = "The devices of the user", body = DeviceListResponse),
+        (status = NOT_FOUND, description = "The user doesn't exist", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+    ),
+    security(("api_key" = ["users:read"])),
+)]
+async fn list_handler(
+    ReadContext(mut context): ReadContext,
+    Path(id): Path<Uuid>,
+) -> Result<Json<DeviceListResponse>, ApiError> {
+    let devices = list_devices(
+        context.devices().await?,
+        ListDevicesParams { user_id: id },
+    )
+    .await?;
+
+    Ok(Json(DeviceListResponse {
+        items: devices.into_iter().map(Into::into).collect(),
+    }))
+}
+
+/// Revokes a device of a user, e.g. because it was lost, together with the sessions issued for
+/// it. The next login from the device counts as a login from a new one.
+#[utoipa::path(
+    post,
+    path = "/users/{id}/devices/{device_id}/revoke",
+    operation_id = "revoke_user_device",
+    tag = "users",
+    params(
+        ("id" = Uuid, Path, description = "ID of the user"),
+        ("device_id" = Uuid, Path, description = "ID of the device"),
+    ),
+    responses(
+        (status = OK, description = "The revoked device", body = DeviceResponse),
+        (status = NOT_FOUND, description = "The user or the device doesn't exist", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+        (status = CONFLICT, description = "The device is already revoked", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+    ),
+    security(("api_key" = ["users:write"])),
+)]
+async fn revoke_handler(
+    context: UseCaseContext,
+    State(sessions): State<SessionMonitor>,
+    State(session_tokens): State<SessionSettings>,
+    State(tokens): State<Arc<dyn TokenGenerator>>,
+    Path((id, device_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<DeviceResponse>, ApiError> {
+    let device = context
+        .retried("revoke_device", |mut context| async {
+            let device = revoke_device(
+                context.devices().await?,
+                RevokeDeviceParams {
+                    user_id: id,
+                    device_id,
+                },
+            )
+            .await?;
+
+            // The sessions are revoked first, so that a device can't be left revoked with
+            // sessions that still work.
+            revoke_device_sessions(
+                SessionUseCaseDeps::new(
+                    session_tokens.store.as_ref(),
+                    tokens.as_ref(),
+                ),
+                RevokeDeviceSessionsParams { device_id },
+            )
+            .await?;
+            context.commit().await?;
+
+            Ok(device)
+        })
+        .await?;
+    sessions.publish(SessionActivity::logout(&device));
+
+    Ok(Json(device.into()))
+}
+
+/// Starts tracking a login attempt, e.g. when the login page is shown, and returns its ID.
+///
+/// The attempt is reported as abandoned unless a login is recorded with its ID in time.
+/// Attempts never refer to users, so that drop-off is measured without tracking anyone.
+#[utoipa::path(
+    post,
+    path = "/login-attempts",
+    operation_id = "start_login_attempt",
+    tag = "users",
+    responses(
+        (status = CREATED, description = "The attempt was started", body = LoginAttemptResponse),
+    ),
+    security(("api_key" = ["users:write"])),
+)]
+async fn start_attempt_handler(
+    State(funnel): State<LoginFunnel>,
+) -> (StatusCode, Json<LoginAttemptResponse>) {
+    (
+        StatusCode::CREATED,
+        Json(LoginAttemptResponse { id: funnel.start() }),
+    )
+}
+
+/// Records a step of a login attempt, e.g. once the password of the user was checked. Steps
+/// of unknown attempts are counted, but don't belong to any attempt.
+#[utoipa::path(
+    post,
+    path = "/login-attempts/{id}/steps",
+    operation_id = "record_login_attempt_step",
+    tag = "users",
+    params(("id" = Uuid, Path, description = "ID of the login attempt")),
+    request_body = RecordLoginStepRequest,
+    responses(
+        (status = NO_CONTENT, description = "The step was recorded"),
+        (status = UNPROCESSABLE_ENTITY, description = "The body is invalid", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+    ),
+    security(("api_key" = ["users:write"])),
+)]
+async fn record_step_handler(
+    State(funnel): State<LoginFunnel>,
+    Path(id): Path<Uuid>,
+    ValidJson(request): ValidJson<RecordLoginStepRequest>,
+) -> StatusCode {
+    funnel.record(id, request.step.into());
+
+    StatusCode::NO_CONTENT
+}
@@ -0,0 +1,219 @@
+use axum::{
+    Json,
+    extract::Path,
+    http::StatusCode,
+    routing::{delete, get, post},
+};
+use chrono::{DateTime, Utc};
+use identify_application::{
+    AddEmailAliasParams, ListEmailAliasesParams, RemoveEmailAliasParams,
+    add_email_alias, list_email_aliases, remove_email_alias,
+};
+use identify_domain::EmailAlias;
+use serde::{Deserialize, Serialize};
+use utoipa::{OpenApi, ToSchema};
+use uuid::Uuid;
+
+use crate::api::{
+    Route, Service,
+    error::{ApiError, PROBLEM_CONTENT_TYPE, Problem},
+    policy::RoutePolicy,
+    services::users::MAX_EMAIL_LENGTH,
+    use_case::{ReadContext, UseCaseContext},
+    validation::{ValidJson, Validate, Validator},
+};
+
+pub struct EmailAliasService;
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(list_handler, add_handler, remove_handler),
+    tags((name = "users", description = "Users of the service"))
+)]
+struct EmailAliasApi;
+
+impl Service for EmailAliasService {
+    fn routes() -> Vec<Route> {
+        vec![
+            Route::new(
+                "/users/{id}/email-aliases",
+                get(list_handler),
+                RoutePolicy::authenticated().permissions(&["users:read"]),
+            ),
+            Route::new(
+                "/users/{id}/email-aliases",
+                post(add_handler),
+                RoutePolicy::authenticated().permissions(&["users:write"]),
+            ),
+            Route::new(
+                "/users/{id}/email-aliases/{alias_id}",
+                delete(remove_handler),
+                RoutePolicy::authenticated().permissions(&["users:write"]),
+            ),
+        ]
+    }
+
+    fn openapi() -> utoipa::openapi::OpenApi {
+        EmailAliasApi::openapi()
+    }
+}
+
+/// Another email a user can be found by.
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(as = EmailAlias)]
+pub struct EmailAliasResponse {
+    /// The ID a user created with the email would have, which is the same whoever the alias
+    /// belongs to.
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub email: String,
+    /// `previous` for the emails the user had before changing them, `added` for the other ones.
+    #[schema(example = "added")]
+    pub kind: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<EmailAlias> for EmailAliasResponse {
+    fn from(value: EmailAlias) -> Self {
+        let attrs = value.to_attributes();
+
+        EmailAliasResponse {
+            id: attrs.id,
+            user_id: attrs.user_id,
+            email: attrs.email,
+            kind: attrs.kind.to_string(),
+            created_at: attrs.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(as = EmailAliasList)]
+pub struct EmailAliasListResponse {
+    pub items: Vec<EmailAliasResponse>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[schema(as = AddEmailAlias)]
+pub struct AddEmailAliasRequest {
+    // Missing required fields are reported by the validation.
+    #[serde(default)]
+    #[schema(required = true, format = Email, max_length = 254)]
+    pub email: String,
+}
+
+impl Validate for AddEmailAliasRequest {
+    fn validate(&self, validator: &mut Validator) {
+        validator
+            .field("email", Some(&self.email))
+            .required()
+            .max_length(MAX_EMAIL_LENGTH)
+            .email();
+    }
+}
+
+/// Lists the aliases of a user, the oldest first.
+#[utoipa::path(
+    get,
+    path = "/users/{id}/email-aliases",
+    operation_id = "list_email_aliases",
+    tag = "users",
+    params(("id" = Uuid, Path, description = "ID of the user")),
+    responses(
+        (status = OK, description = "The aliases of the user", body = EmailAliasListResponse),
+        (status = NOT_FOUND, description = "The user doesn't exist", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+    ),
+    security(("api_key" = ["users:read"])),
+)]
+async fn list_handler(
+    ReadContext(mut context): ReadContext,
+    Path(id): Path<Uuid>,
+) -> Result<Json<EmailAliasListResponse>, ApiError> {
+    let aliases = list_email_aliases(
+        context.email_aliases().await?,
+        ListEmailAliasesParams { user_id: id },
+    )
+    .await?;
+
+    Ok(Json(EmailAliasListResponse {
+        items: aliases.into_iter().map(Into::into).collect(),
+    }))
+}
+
+/// Adds an email a user can also be found by, e.g. a tagged address. The email isn't verified.
+#[utoipa::path(
+    post,
+    path = "/users/{id}/email-aliases",
+    operation_id = "add_email_alias",
+    tag = "users",
+    params(("id" = Uuid, Path, description = "ID of the user")),
+    request_body = AddEmailAliasRequest,
+    responses(
+        (status = CREATED, description = "The alias", body = EmailAliasResponse),
+        (status = NOT_FOUND, description = "The user doesn't exist", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+        (status = CONFLICT, description = "The email already identifies a user, or the user has been erased", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+        (status = UNPROCESSABLE_ENTITY, description = "The body is invalid", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+    ),
+    security(("api_key" = ["users:write"])),
+)]
+async fn add_handler(
+    context: UseCaseContext,
+    Path(id): Path<Uuid>,
+    ValidJson(request): ValidJson<AddEmailAliasRequest>,
+) -> Result<(StatusCode, Json<EmailAliasResponse>), ApiError> {
+    let alias = context
+        .retried("add_email_alias", |mut context| async {
+            let alias = add_email_alias(
+                context.email_aliases().await?,
+                AddEmailAliasParams {
+                    user_id: id,
+                    email: request.email.clone(),
+                },
+            )
+            .await?;
+            context.commit().await?;
+
+            Ok(alias)
+        })
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(alias.into())))
+}
+
+/// Removes an alias of a user, whose email anyone can take again.
+#[utoipa::path(
+    delete,
+    path = "/users/{id}/email-aliases/{alias_id}",
+    operation_id = "remove_email_alias",
+    tag = "users",
+    params(
+        ("id" = Uuid, Path, description = "ID of the user"),
+        ("alias_id" = Uuid, Path, description = "ID of the alias"),
+    ),
+    responses(
+        (status = NO_CONTENT, description = "The alias was removed"),
+        (status = NOT_FOUND, description = "The user has no such alias", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+    ),
+    security(("api_key" = ["users:write"])),
+)]
+async fn remove_handler(
+    context: UseCaseContext,
+    Path((id, alias_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, ApiError> {
+    context
+        .retried("remove_email_alias", |mut context| async move {
+            remove_email_alias(
+                context.email_aliases().await?,
+                RemoveEmailAliasParams {
+                    user_id: id,
+                    alias_id,
+                },
+            )
+            .await?;
+
+            context.commit().await
+        })
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
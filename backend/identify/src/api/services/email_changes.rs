@@ -0,0 +1,235 @@
+use std::sync::Arc;
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    routing::post,
+};
+use chrono::{DateTime, TimeDelta, Utc};
+use identify_application::{
+    ChangeEmailParams, EmailSender, EmailTemplates, RequestEmailChangeParams,
+    TokenGenerator, change_email, request_email_change,
+};
+use identify_domain::EmailChange;
+use serde::{Deserialize, Serialize};
+use utoipa::{OpenApi, ToSchema};
+use uuid::Uuid;
+
+use crate::api::{
+    Route, Service,
+    error::{ApiError, PROBLEM_CONTENT_TYPE, Problem},
+    policy::{RateLimitClass, RoutePolicy},
+    services::users::{MAX_EMAIL_LENGTH, UserResponse},
+    use_case::UseCaseContext,
+    validation::{ValidJson, Validate, Validator},
+};
+
+/// Longest token accepted, well above the length of the generated ones.
+const MAX_TOKEN_LENGTH: usize = 256;
+
+/// Where the emailed links point to, and how long changes of emails last.
+#[derive(Debug, Clone)]
+pub struct EmailChangeSettings {
+    /// URL of the page users confirm the changes of their emails on.
+    pub confirm_url: String,
+    pub valid_for: TimeDelta,
+}
+
+pub struct EmailChangeService;
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(request_handler, confirm_handler),
+    tags((name = "users", description = "Users of the service"))
+)]
+struct EmailChangeApi;
+
+impl Service for EmailChangeService {
+    fn routes() -> Vec<Route> {
+        vec![
+            Route::new(
+                "/users/{id}/email-changes",
+                post(request_handler),
+                RoutePolicy::authenticated()
+                    .permissions(&["users:write"])
+                    .rate_limit(RateLimitClass::Strict),
+            ),
+            Route::new(
+                "/email-changes/{id}/confirm",
+                post(confirm_handler),
+                RoutePolicy::authenticated()
+                    .permissions(&["users:write"])
+                    .rate_limit(RateLimitClass::Strict),
+            ),
+        ]
+    }
+
+    fn openapi() -> utoipa::openapi::OpenApi {
+        EmailChangeApi::openapi()
+    }
+}
+
+/// A change of the email of a user. The token it's confirmed with is never returned.
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(as = EmailChange)]
+pub struct EmailChangeResponse {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    /// Email the user changes theirs to, which the link was sent to.
+    pub email: String,
+    pub expires_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub confirmed_at: Option<DateTime<Utc>>,
+}
+
+impl From<EmailChange> for EmailChangeResponse {
+    fn from(value: EmailChange) -> Self {
+        let attrs = value.to_attributes();
+
+        EmailChangeResponse {
+            id: attrs.id,
+            user_id: attrs.user_id,
+            email: attrs.email,
+            expires_at: attrs.expires_at,
+            created_at: attrs.created_at,
+            confirmed_at: attrs.confirmed_at,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[schema(as = RequestEmailChange)]
+pub struct RequestEmailChangeRequest {
+    // Missing required fields are reported by the validation.
+    #[serde(default)]
+    #[schema(required = true, format = Email, max_length = 254)]
+    pub email: String,
+}
+
+impl Validate for RequestEmailChangeRequest {
+    fn validate(&self, validator: &mut Validator) {
+        validator
+            .field("email", Some(&self.email))
+            .required()
+            .max_length(MAX_EMAIL_LENGTH)
+            .email();
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[schema(as = ConfirmEmailChange)]
+pub struct ConfirmEmailChangeRequest {
+    /// Token from the link the new address was emailed.
+    #[serde(default)]
+    #[schema(required = true, max_length = 256)]
+    pub token: String,
+}
+
+impl Validate for ConfirmEmailChangeRequest {
+    fn validate(&self, validator: &mut Validator) {
+        validator
+            .field("token", Some(&self.token))
+            .required()
+            .max_length(MAX_TOKEN_LENGTH);
+    }
+}
+
+/// Starts changing the email of a user, and emails a link to the new address, which the user
+/// confirms the change with.
+///
+/// The email of the user stays the same until then, and their ID never changes.
+#[utoipa::path(
+    post,
+    path = "/users/{id}/email-changes",
+    operation_id = "request_email_change",
+    tag = "users",
+    params(("id" = Uuid, Path, description = "ID of the user")),
+    request_body = RequestEmailChangeRequest,
+    responses(
+        (status = CREATED, description = "The change, whose link was emailed to the new address", body = EmailChangeResponse),
+        (status = NOT_FOUND, description = "The user doesn't exist", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+        (status = CONFLICT, description = "The email identifies another user, or the user has been erased", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+        (status = UNPROCESSABLE_ENTITY, description = "The body is invalid, or the email is the current one of the user", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+    ),
+    security(("api_key" = ["users:write"])),
+)]
+async fn request_handler(
+    context: UseCaseContext,
+    State(tokens): State<Arc<dyn TokenGenerator>>,
+    State(sender): State<Arc<dyn EmailSender>>,
+    State(templates): State<Arc<EmailTemplates>>,
+    State(settings): State<EmailChangeSettings>,
+    Path(id): Path<Uuid>,
+    ValidJson(request): ValidJson<RequestEmailChangeRequest>,
+) -> Result<(StatusCode, Json<EmailChangeResponse>), ApiError> {
+    let change = context
+        .retried("request_email_change", |mut context| async {
+            let change = request_email_change(
+                context
+                    .email_change_deliveries(
+                        tokens.as_ref(),
+                        sender.as_ref(),
+                        &templates,
+                    )
+                    .await?,
+                RequestEmailChangeParams {
+                    user_id: id,
+                    email: request.email.clone(),
+                    confirm_url: settings.confirm_url.clone(),
+                    valid_for: settings.valid_for,
+                },
+            )
+            .await?;
+            context.commit().await?;
+
+            Ok(change)
+        })
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(change.into())))
+}
+
+/// Changes the email of a user with the token the new address was emailed.
+///
+/// The user keeps their ID, and can still be found by the email they were created with. Changes
+/// whose token doesn't match are reported as not found.
+#[utoipa::path(
+    post,
+    path = "/email-changes/{id}/confirm",
+    operation_id = "confirm_email_change",
+    tag = "users",
+    params(("id" = Uuid, Path, description = "ID of the change")),
+    request_body = ConfirmEmailChangeRequest,
+    responses(
+        (status = OK, description = "The user with their new email", body = UserResponse),
+        (status = NOT_FOUND, description = "The change doesn't exist, or the token doesn't match", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+        (status = CONFLICT, description = "The change is already confirmed or has expired, the email has been taken in the meantime, or the user has been erased", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+        (status = UNPROCESSABLE_ENTITY, description = "The body is invalid", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+    ),
+    security(("api_key" = ["users:write"])),
+)]
+async fn confirm_handler(
+    context: UseCaseContext,
+    State(tokens): State<Arc<dyn TokenGenerator>>,
+    Path(id): Path<Uuid>,
+    ValidJson(request): ValidJson<ConfirmEmailChangeRequest>,
+) -> Result<Json<UserResponse>, ApiError> {
+    let user = context
+        .retried("change_email", |mut context| async {
+            let user = change_email(
+                context.email_changes(tokens.as_ref()).await?,
+                ChangeEmailParams {
+                    id,
+                    token: request.token.clone(),
+                },
+            )
+            .await?;
+            context.commit().await?;
+
+            Ok(user)
+        })
+        .await?;
+
+    Ok(Json(user.into()))
+}
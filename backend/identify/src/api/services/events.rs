@@ -0,0 +1,194 @@
+use std::convert::Infallible;
+
+use axum::{
+    extract::{FromRequestParts, Query, State},
+    http::{HeaderName, request::Parts},
+    response::sse::{Event, KeepAlive, Sse},
+    routing::get,
+};
+use chrono::{DateTime, Utc};
+use futures_util::{Stream, StreamExt as _, future};
+use identify_domain::UserLifecycleTransition;
+use serde::{
+    Deserialize, Serialize,
+    de::{IntoDeserializer as _, value},
+};
+use tracing::error;
+use utoipa::{IntoParams, OpenApi, ToSchema};
+use uuid::Uuid;
+
+use crate::{
+    api::{
+        Route, Service,
+        error::{ApiError, PROBLEM_CONTENT_TYPE, Problem},
+        policy::RoutePolicy,
+        services::webhooks::WebhookEventType,
+    },
+    event_feed::UserEventFeed,
+    webhooks,
+};
+
+/// Header browsers send the ID of the last received event in when they reconnect.
+const LAST_EVENT_ID: HeaderName = HeaderName::from_static("last-event-id");
+
+pub struct EventService;
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(stream_handler),
+    tags((name = "events", description = "Live feed of user events"))
+)]
+struct EventApi;
+
+impl Service for EventService {
+    fn routes() -> Vec<Route> {
+        vec![Route::new(
+            "/events/stream",
+            get(stream_handler),
+            RoutePolicy::admin(),
+        )]
+    }
+
+    fn openapi() -> utoipa::openapi::OpenApi {
+        EventApi::openapi()
+    }
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct StreamParams {
+    /// Comma-separated types of the streamed events, e.g. `user.created,user.deleted`. All of
+    /// them are streamed if it's missing.
+    #[param(example = "user.created,user.deleted")]
+    types: Option<String>,
+}
+
+/// The `Last-Event-ID` header of reconnecting subscribers.
+struct LastEventId(Option<u64>);
+
+impl<S: Send + Sync> FromRequestParts<S> for LastEventId {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        _state: &S,
+    ) -> Result<Self, Self::Rejection> {
+        let Some(value) = parts.headers.get(LAST_EVENT_ID) else {
+            return Ok(LastEventId(None));
+        };
+
+        value
+            .to_str()
+            .ok()
+            .and_then(|value| value.trim().parse().ok())
+            .map(|sequence| LastEventId(Some(sequence)))
+            .ok_or_else(|| {
+                ApiError::bad_request(
+                    "Last-Event-ID must be the ID of an event",
+                )
+            })
+    }
+}
+
+/// A user event as it's streamed, in the same form it's posted to webhooks.
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(as = UserEventMessage)]
+struct UserEventMessage {
+    /// ID of the event, the same as in webhook payloads.
+    id: Uuid,
+    #[serde(rename = "type")]
+    #[schema(example = "user.created")]
+    event_type: String,
+    occurred_at: DateTime<Utc>,
+    data: UserEventData,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+struct UserEventData {
+    user_id: Uuid,
+}
+
+/// Streams user events as Server-Sent Events as they're recorded.
+///
+/// Every event has the type of the user event as its name, e.g. `user.created`, and its
+/// position as its ID. Subscribers that reconnect with the `Last-Event-ID` header first receive
+/// the events they've missed, as long as they haven't been pruned yet. Subscribers that fall
+/// too far behind are disconnected, and should reconnect to catch up.
+#[utoipa::path(
+    get,
+    path = "/events/stream",
+    operation_id = "stream_events",
+    tag = "events",
+    params(
+        StreamParams,
+        ("Last-Event-ID" = Option<u64>, Header, description = "ID of the last received event"),
+    ),
+    responses(
+        (status = OK, description = "The stream of events", body = UserEventMessage, content_type = "text/event-stream"),
+        (status = BAD_REQUEST, description = "The types or the ID of the last event are invalid", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+    ),
+    security(("api_key" = [])),
+)]
+async fn stream_handler(
+    State(feed): State<UserEventFeed>,
+    Query(params): Query<StreamParams>,
+    LastEventId(after): LastEventId,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, ApiError> {
+    let types = params.types.as_deref().map(parse_types).transpose()?;
+
+    let events = feed
+        .subscribe(after)
+        // The feed ends after an error, so it ends the response as well.
+        .filter_map(|event| {
+            future::ready(
+                event
+                    .inspect_err(
+                        |e| error!(error = %e, "Failed to stream user events"),
+                    )
+                    .ok(),
+            )
+        })
+        .filter(move |recorded| {
+            let transition = recorded.event.transition();
+            future::ready(
+                types
+                    .as_ref()
+                    .is_none_or(|types| types.contains(&transition)),
+            )
+        })
+        .map(|recorded| {
+            let event = &recorded.event;
+            let message = UserEventMessage {
+                id: event.id(),
+                event_type: webhooks::event_type(event.transition()),
+                occurred_at: *event.occurred_at(),
+                data: UserEventData {
+                    user_id: event.user_id(),
+                },
+            };
+
+            Ok(Event::default()
+                .id(recorded.sequence.to_string())
+                .event(&message.event_type)
+                .data(serde_json::to_string(&message).unwrap_or_default()))
+        });
+
+    Ok(Sse::new(events)
+        .keep_alive(KeepAlive::new().interval(feed.keep_alive())))
+}
+
+fn parse_types(types: &str) -> Result<Vec<UserLifecycleTransition>, ApiError> {
+    types
+        .split(',')
+        .map(|name| {
+            WebhookEventType::deserialize(name.trim().into_deserializer())
+                .map(UserLifecycleTransition::from)
+                .map_err(|_: value::Error| {
+                    ApiError::bad_request(format!(
+                        "'{}' isn't a type of user events",
+                        name.trim()
+                    ))
+                })
+        })
+        .collect()
+}
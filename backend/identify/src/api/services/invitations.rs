@@ -0,0 +1,353 @@
+use std::sync::Arc;
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    routing::{get, post},
+};
+use chrono::{DateTime, TimeDelta, Utc};
+use identify_application::{
+    AcceptInvitationParams, CreateInvitationParams, EmailSender,
+    EmailTemplates, GetInvitationParams, TokenGenerator, accept_invitation,
+    create_invitation, get_invitation,
+};
+use identify_domain::{
+    Invitation, MembershipRole, QuotaPolicy, Username, UsernameStrategy,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::{OpenApi, ToSchema};
+use uuid::Uuid;
+
+use crate::{
+    api::{
+        Route, Service,
+        error::{ApiError, PROBLEM_CONTENT_TYPE, Problem},
+        policy::RoutePolicy,
+        services::users::{MAX_EMAIL_LENGTH, MAX_NAME_LENGTH, UserResponse},
+        use_case::{ReadContext, UseCaseContext},
+        validation::{ValidJson, Validate, Validator},
+    },
+    identifiers::IdentifierLists,
+};
+
+/// Longest token accepted, well above the length of the generated ones.
+const MAX_TOKEN_LENGTH: usize = 256;
+
+/// Where the emailed links point to, and how long invitations last.
+#[derive(Debug, Clone)]
+pub struct InvitationSettings {
+    /// URL of the page invitees accept invitations on.
+    pub accept_url: String,
+    pub valid_for: TimeDelta,
+}
+
+pub struct InvitationService;
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(create_handler, get_handler, accept_handler),
+    tags((name = "organizations", description = "Organizations managed by admins"))
+)]
+struct InvitationApi;
+
+impl Service for InvitationService {
+    fn routes() -> Vec<Route> {
+        vec![
+            Route::new(
+                "/invitations",
+                post(create_handler),
+                RoutePolicy::authenticated().permissions(&["users:write"]),
+            ),
+            Route::new(
+                "/invitations/{id}",
+                get(get_handler),
+                RoutePolicy::authenticated().permissions(&["users:read"]),
+            ),
+            Route::new(
+                "/invitations/{id}/accept",
+                post(accept_handler),
+                RoutePolicy::authenticated().permissions(&["users:write"]),
+            ),
+        ]
+    }
+
+    fn openapi() -> utoipa::openapi::OpenApi {
+        InvitationApi::openapi()
+    }
+}
+
+/// Role an invitee gets within the organization. Nobody can be invited as an owner.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+#[schema(as = InvitationRole)]
+pub enum InvitationRoleSchema {
+    Admin,
+    #[default]
+    Member,
+}
+
+impl From<InvitationRoleSchema> for MembershipRole {
+    fn from(value: InvitationRoleSchema) -> Self {
+        match value {
+            InvitationRoleSchema::Admin => MembershipRole::Admin,
+            InvitationRoleSchema::Member => MembershipRole::Member,
+        }
+    }
+}
+
+/// An invitation to join an organization. The token it's accepted with is never returned.
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(as = Invitation)]
+pub struct InvitationResponse {
+    pub id: Uuid,
+    /// Email the invitation was sent to.
+    pub email: String,
+    /// ID of the user who sent the invitation.
+    pub inviter_id: Uuid,
+    pub organization_id: Uuid,
+    #[schema(example = "member")]
+    pub role: String,
+    /// `pending`, `accepted` or `expired`.
+    #[schema(example = "pending")]
+    pub status: String,
+    pub expires_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub accepted_at: Option<DateTime<Utc>>,
+    /// ID of the user created when the invitation was accepted.
+    pub user_id: Option<Uuid>,
+}
+
+impl From<Invitation> for InvitationResponse {
+    fn from(value: Invitation) -> Self {
+        let status = value.status().to_string();
+        let attrs = value.to_attributes();
+
+        InvitationResponse {
+            id: attrs.id,
+            email: attrs.email,
+            inviter_id: attrs.inviter_id,
+            organization_id: attrs.organization_id,
+            role: attrs.role.to_string(),
+            status,
+            expires_at: attrs.expires_at,
+            created_at: attrs.created_at,
+            accepted_at: attrs.accepted_at,
+            user_id: attrs.user_id,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(as = AcceptedInvitation)]
+pub struct AcceptedInvitationResponse {
+    pub invitation: InvitationResponse,
+    /// The user created for the invitee.
+    pub user: UserResponse,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[schema(as = CreateInvitation)]
+pub struct CreateInvitationRequest {
+    // Missing required fields are reported by the validation.
+    #[serde(default)]
+    #[schema(required = true, format = Email, max_length = 254)]
+    pub email: String,
+    /// ID of the user who invites them, who must be an owner or an admin of the organization.
+    #[serde(default)]
+    #[schema(required = true)]
+    pub inviter_id: Uuid,
+    #[serde(default)]
+    #[schema(required = true)]
+    pub organization_id: Uuid,
+    /// `member` if it's not set.
+    #[serde(default)]
+    pub role: InvitationRoleSchema,
+}
+
+impl Validate for CreateInvitationRequest {
+    fn validate(&self, validator: &mut Validator) {
+        validator
+            .field("email", Some(&self.email))
+            .required()
+            .max_length(MAX_EMAIL_LENGTH)
+            .email();
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[schema(as = AcceptInvitation)]
+pub struct AcceptInvitationRequest {
+    /// Token from the link the invitee was emailed.
+    #[serde(default)]
+    #[schema(required = true, max_length = 256)]
+    pub token: String,
+    /// Generated from the email or the name if it's not set, depending on the configuration.
+    #[schema(min_length = 3, max_length = 32)]
+    pub username: Option<String>,
+    #[serde(default)]
+    #[schema(required = true, min_length = 1, max_length = 100)]
+    pub first_name: String,
+    #[schema(max_length = 100)]
+    pub last_name: Option<String>,
+}
+
+impl Validate for AcceptInvitationRequest {
+    fn validate(&self, validator: &mut Validator) {
+        validator
+            .field("token", Some(&self.token))
+            .required()
+            .max_length(MAX_TOKEN_LENGTH);
+        validator
+            .field("username", self.username.as_deref())
+            .min_length(Username::MIN_LENGTH)
+            .max_length(Username::MAX_LENGTH)
+            .username();
+        validator
+            .field("first_name", Some(&self.first_name))
+            .required()
+            .max_length(MAX_NAME_LENGTH);
+        validator
+            .field("last_name", self.last_name.as_deref())
+            .max_length(MAX_NAME_LENGTH);
+    }
+}
+
+/// Invites someone without an account to join an organization, and emails them a link to the
+/// page they accept the invitation on.
+#[utoipa::path(
+    post,
+    path = "/invitations",
+    operation_id = "create_invitation",
+    tag = "organizations",
+    request_body = CreateInvitationRequest,
+    responses(
+        (status = CREATED, description = "The invitation, which was emailed to the invitee", body = InvitationResponse),
+        (status = NOT_FOUND, description = "The organization or the inviter doesn't exist", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+        (status = CONFLICT, description = "A user with the email already exists, or the inviter has been erased", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+        (status = UNPROCESSABLE_ENTITY, description = "The body is invalid, or the inviter isn't an owner or an admin of the organization", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+    ),
+    security(("api_key" = ["users:write"])),
+)]
+async fn create_handler(
+    context: UseCaseContext,
+    State(tokens): State<Arc<dyn TokenGenerator>>,
+    State(sender): State<Arc<dyn EmailSender>>,
+    State(templates): State<Arc<EmailTemplates>>,
+    State(settings): State<InvitationSettings>,
+    ValidJson(request): ValidJson<CreateInvitationRequest>,
+) -> Result<(StatusCode, Json<InvitationResponse>), ApiError> {
+    let invitation = context
+        .retried("create_invitation", |mut context| async {
+            let invitation = create_invitation(
+                context
+                    .invitation_deliveries(
+                        tokens.as_ref(),
+                        sender.as_ref(),
+                        &templates,
+                    )
+                    .await?,
+                CreateInvitationParams {
+                    email: request.email.clone(),
+                    inviter_id: request.inviter_id,
+                    organization_id: request.organization_id,
+                    role: request.role.into(),
+                    accept_url: settings.accept_url.clone(),
+                    valid_for: settings.valid_for,
+                },
+            )
+            .await?;
+            context.commit().await?;
+
+            Ok(invitation)
+        })
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(invitation.into())))
+}
+
+/// Returns an invitation, e.g. so that the page it links to can show who sent it.
+#[utoipa::path(
+    get,
+    path = "/invitations/{id}",
+    operation_id = "get_invitation",
+    tag = "organizations",
+    params(("id" = Uuid, Path, description = "ID of the invitation")),
+    responses(
+        (status = OK, description = "The invitation", body = InvitationResponse),
+        (status = NOT_FOUND, description = "The invitation doesn't exist", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+    ),
+    security(("api_key" = ["users:read"])),
+)]
+async fn get_handler(
+    ReadContext(mut context): ReadContext,
+    Path(id): Path<Uuid>,
+) -> Result<Json<InvitationResponse>, ApiError> {
+    let invitation = get_invitation(
+        context.invitations().await?,
+        GetInvitationParams { id },
+    )
+    .await?;
+
+    Ok(Json(invitation.into()))
+}
+
+/// Accepts an invitation with the token the invitee was emailed, creating their user with the
+/// email the invitation was sent to and adding them to the organization.
+///
+/// Invitations whose token doesn't match are reported as not found.
+#[utoipa::path(
+    post,
+    path = "/invitations/{id}/accept",
+    operation_id = "accept_invitation",
+    tag = "organizations",
+    params(("id" = Uuid, Path, description = "ID of the invitation")),
+    request_body = AcceptInvitationRequest,
+    responses(
+        (status = CREATED, description = "The accepted invitation and the created user", body = AcceptedInvitationResponse),
+        (status = NOT_FOUND, description = "The invitation doesn't exist, or the token doesn't match", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+        (status = FORBIDDEN, description = "The API key lacks a permission, or the organization can't have any more users", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+        (status = CONFLICT, description = "The invitation is already accepted or has expired, or the username is taken", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+        (status = UNPROCESSABLE_ENTITY, description = "The body is invalid", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+    ),
+    security(("api_key" = ["users:write"])),
+)]
+async fn accept_handler(
+    context: UseCaseContext,
+    State(tokens): State<Arc<dyn TokenGenerator>>,
+    State(username_strategy): State<UsernameStrategy>,
+    State(identifiers): State<IdentifierLists>,
+    State(quota_policy): State<QuotaPolicy>,
+    Path(id): Path<Uuid>,
+    ValidJson(request): ValidJson<AcceptInvitationRequest>,
+) -> Result<(StatusCode, Json<AcceptedInvitationResponse>), ApiError> {
+    let accepted = context
+        .retried("accept_invitation", |mut context| async {
+            let accepted = accept_invitation(
+                context.invitation_acceptances(tokens.as_ref()).await?,
+                AcceptInvitationParams {
+                    id,
+                    token: request.token.clone(),
+                    first_name: request.first_name.clone(),
+                    last_name: request.last_name.clone(),
+                    username: request.username.clone(),
+                    username_strategy,
+                    identifier_policy: identifiers.policy(),
+                    quota_policy,
+                },
+            )
+            .await?;
+            context.commit().await?;
+
+            Ok(accepted)
+        })
+        .await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(AcceptedInvitationResponse {
+            invitation: accepted.invitation.into(),
+            user: accepted.user.into(),
+        }),
+    ))
+}
@@ -0,0 +1,63 @@
+use axum::{
+    extract::State, http::header::CONTENT_TYPE, response::IntoResponse,
+    routing::get,
+};
+use sqlx::SqlitePool;
+use utoipa::OpenApi;
+
+use crate::{
+    api::{
+        Route, Service,
+        policy::{RateLimitClass, RoutePolicy},
+    },
+    metrics::Metrics,
+};
+
+/// Content type of the Prometheus text format.
+const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+pub struct MetricsService;
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(metrics_handler),
+    tags((name = "metrics", description = "Metrics of the service"))
+)]
+struct MetricsApi;
+
+impl Service for MetricsService {
+    fn routes() -> Vec<Route> {
+        vec![Route::new(
+            "/metrics",
+            get(metrics_handler),
+            RoutePolicy::authenticated()
+                .permissions(&["metrics:read"])
+                .rate_limit(RateLimitClass::Unlimited),
+        )]
+    }
+
+    fn openapi() -> utoipa::openapi::OpenApi {
+        MetricsApi::openapi()
+    }
+}
+
+/// Returns the metrics in the Prometheus text format.
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    operation_id = "get_metrics",
+    tag = "metrics",
+    responses(
+        (status = OK, description = "The metrics", body = String, content_type = PROMETHEUS_CONTENT_TYPE),
+    ),
+    security(("api_key" = ["metrics:read"])),
+)]
+async fn metrics_handler(
+    State(metrics): State<Metrics>,
+    State(pool): State<SqlitePool>,
+) -> impl IntoResponse {
+    (
+        [(CONTENT_TYPE, PROMETHEUS_CONTENT_TYPE)],
+        metrics.render(&pool),
+    )
+}
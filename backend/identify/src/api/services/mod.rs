@@ -0,0 +1,21 @@
+pub mod avatars;
+pub mod consents;
+pub mod database;
+pub mod devices;
+pub mod email_aliases;
+pub mod email_changes;
+pub mod events;
+pub mod invitations;
+pub mod metrics;
+pub mod operations;
+pub mod organizations;
+pub mod phone_verifications;
+pub mod registration;
+pub mod roles;
+pub mod sdk;
+pub mod sessions;
+pub mod signing_keys;
+pub mod status;
+pub mod user_attributes;
+pub mod users;
+pub mod webhooks;
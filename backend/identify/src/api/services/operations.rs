@@ -0,0 +1,179 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::{StatusCode, header::LOCATION},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+};
+use chrono::{DateTime, Utc};
+use identify_application::{
+    ApplicationError, CancelOperationParams, GetOperationParams,
+    OperationUseCaseDeps, cancel_operation, get_operation,
+};
+use identify_domain::Operation;
+use identify_infrastructure::storage::{
+    self, connection::ReadPool, operations::OperationsRepository,
+    retry::Retrier,
+};
+use serde::Serialize;
+use sqlx::SqlitePool;
+use utoipa::{OpenApi, ToSchema};
+use uuid::Uuid;
+
+use crate::api::{
+    Route, Service,
+    error::{ApiError, PROBLEM_CONTENT_TYPE, Problem},
+    policy::RoutePolicy,
+    version::ApiVersion,
+};
+
+pub struct OperationService;
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(get_handler, cancel_handler),
+    tags((name = "operations", description = "Long-running operations"))
+)]
+struct OperationApi;
+
+impl Service for OperationService {
+    fn routes() -> Vec<Route> {
+        vec![
+            Route::new(
+                "/operations/{id}",
+                get(get_handler),
+                RoutePolicy::authenticated().permissions(&["operations:read"]),
+            ),
+            Route::new(
+                "/operations/{id}/cancel",
+                post(cancel_handler),
+                RoutePolicy::authenticated()
+                    .permissions(&["operations:cancel"]),
+            ),
+        ]
+    }
+
+    fn openapi() -> utoipa::openapi::OpenApi {
+        OperationApi::openapi()
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(as = Operation)]
+pub struct OperationResponse {
+    pub id: Uuid,
+    pub kind: String,
+    #[schema(example = "running")]
+    pub status: &'static str,
+    /// Progress in percent.
+    #[schema(maximum = 100)]
+    pub progress: u8,
+    pub error: Option<String>,
+    pub cancel_requested: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<&Operation> for OperationResponse {
+    fn from(value: &Operation) -> Self {
+        OperationResponse {
+            id: value.id(),
+            kind: value.kind().clone(),
+            status: value.status().as_str(),
+            progress: value.progress(),
+            error: value.error().clone(),
+            cancel_requested: value.cancel_requested(),
+            created_at: *value.created_at(),
+            updated_at: *value.updated_at(),
+        }
+    }
+}
+
+/// Returns the canonical URL of an operation.
+///
+/// Operations don't depend on the API version, so the URL always points to the first one.
+pub fn operation_location(id: Uuid) -> String {
+    format!("{}/operations/{id}", ApiVersion::V1.prefix())
+}
+
+/// Builds a `202 Accepted` response pointing to the operation status endpoint.
+///
+/// Every endpoint that starts a long-running operation must respond with this.
+pub fn accepted(operation: &Operation) -> Response {
+    (
+        StatusCode::ACCEPTED,
+        [(LOCATION, operation_location(operation.id()))],
+        Json(OperationResponse::from(operation)),
+    )
+        .into_response()
+}
+
+/// Returns the status of an operation.
+#[utoipa::path(
+    get,
+    path = "/operations/{id}",
+    operation_id = "get_operation",
+    tag = "operations",
+    params(("id" = Uuid, Path, description = "ID of the operation")),
+    responses(
+        (status = OK, description = "The operation", body = OperationResponse),
+        (status = NOT_FOUND, description = "The operation doesn't exist", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+    ),
+    security(("api_key" = ["operations:read"])),
+)]
+async fn get_handler(
+    State(pool): State<ReadPool>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<OperationResponse>, ApiError> {
+    let mut conn = storage::acquire(&pool).await?;
+    let mut repository = OperationsRepository::new(&mut conn);
+
+    let operation = get_operation(
+        OperationUseCaseDeps::new(&mut repository),
+        GetOperationParams { id },
+    )
+    .await?;
+
+    Ok(Json(OperationResponse::from(&operation)))
+}
+
+/// Requests the cancellation of an operation.
+///
+/// The operation is cancelled asynchronously, poll its status to find out when it stops.
+#[utoipa::path(
+    post,
+    path = "/operations/{id}/cancel",
+    operation_id = "cancel_operation",
+    tag = "operations",
+    params(("id" = Uuid, Path, description = "ID of the operation")),
+    responses(
+        (status = ACCEPTED, description = "The cancellation was requested", body = OperationResponse,
+            headers(("Location" = String, description = "URL of the operation status"))),
+        (status = NOT_FOUND, description = "The operation doesn't exist", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+        (status = CONFLICT, description = "The operation has already finished", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+    ),
+    security(("api_key" = ["operations:cancel"])),
+)]
+async fn cancel_handler(
+    State(pool): State<SqlitePool>,
+    State(retrier): State<Retrier>,
+    Path(id): Path<Uuid>,
+) -> Result<Response, ApiError> {
+    let operation = retrier
+        .run("cancel_operation", || async {
+            let mut tx = storage::begin(&pool).await?;
+            let mut repository = OperationsRepository::new(&mut tx);
+
+            let operation = cancel_operation(
+                OperationUseCaseDeps::new(&mut repository),
+                CancelOperationParams { id },
+            )
+            .await?;
+            storage::commit(tx).await?;
+
+            Ok::<_, ApplicationError>(operation)
+        })
+        .await?;
+
+    Ok(accepted(&operation))
+}
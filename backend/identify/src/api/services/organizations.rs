@@ -0,0 +1,402 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::{StatusCode, header::LOCATION},
+    response::{IntoResponse, Response},
+    routing::{delete, get, put},
+};
+use chrono::{DateTime, Utc};
+use identify_application::{
+    GetOrganizationByExternalIdParams, GetOrganizationBySlugParams,
+    GetOrganizationQuotaParams, OrganizationQuotaUsage, PutOrganizationParams,
+    PutOrganizationQuotaParams, PutOutcome, ResetOrganizationQuotaParams,
+    get_organization_by_external_id, get_organization_by_slug,
+    get_organization_quota, put_organization, put_organization_quota,
+    reset_organization_quota,
+};
+use identify_domain::{Organization, QuotaPolicy};
+use serde::{Deserialize, Serialize};
+use utoipa::{OpenApi, ToSchema};
+use uuid::Uuid;
+
+use crate::{
+    api::{
+        Route, Service,
+        error::{ApiError, PROBLEM_CONTENT_TYPE, Problem},
+        policy::RoutePolicy,
+        use_case::{ReadContext, UseCaseContext},
+        validation::{ValidJson, Validate, Validator},
+        version::ApiVersion,
+    },
+    identifiers::IdentifierLists,
+};
+
+pub struct OrganizationService;
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        get_handler,
+        put_handler,
+        get_by_slug_handler,
+        get_quota_handler,
+        put_quota_handler,
+        reset_quota_handler
+    ),
+    tags((name = "organizations", description = "Organizations managed by admins"))
+)]
+struct OrganizationApi;
+
+impl Service for OrganizationService {
+    fn routes() -> Vec<Route> {
+        vec![
+            Route::new(
+                "/organizations/{external_id}",
+                get(get_handler),
+                RoutePolicy::admin(),
+            ),
+            Route::new(
+                "/organizations/{external_id}",
+                put(put_handler),
+                RoutePolicy::admin(),
+            ),
+            Route::new(
+                "/organizations/by-slug/{slug}",
+                get(get_by_slug_handler),
+                RoutePolicy::admin(),
+            ),
+            Route::new(
+                "/organizations/{external_id}/quota",
+                get(get_quota_handler),
+                RoutePolicy::admin(),
+            ),
+            Route::new(
+                "/organizations/{external_id}/quota",
+                put(put_quota_handler),
+                RoutePolicy::admin(),
+            ),
+            Route::new(
+                "/organizations/{external_id}/quota",
+                delete(reset_quota_handler),
+                RoutePolicy::admin(),
+            ),
+        ]
+    }
+
+    fn openapi() -> utoipa::openapi::OpenApi {
+        OrganizationApi::openapi()
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(as = Organization)]
+pub struct OrganizationResponse {
+    pub id: Uuid,
+    pub external_id: Option<String>,
+    pub name: String,
+    pub slug: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<Organization> for OrganizationResponse {
+    fn from(value: Organization) -> Self {
+        let attrs = value.to_attributes();
+
+        OrganizationResponse {
+            id: attrs.id,
+            external_id: attrs.external_id,
+            name: attrs.name,
+            slug: attrs.slug,
+            created_at: attrs.created_at,
+            updated_at: attrs.updated_at,
+        }
+    }
+}
+
+/// How many users an organization can have, and how many it has.
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(as = OrganizationQuota)]
+pub struct OrganizationQuotaResponse {
+    pub organization_id: Uuid,
+    /// Most users the organization can have, or `null` if it can have any number of them.
+    pub max_users: Option<u32>,
+    /// Whether the organization has a quota of its own, rather than the default.
+    pub custom: bool,
+    /// Members of the organization who haven't been erased.
+    pub users: u64,
+}
+
+impl From<OrganizationQuotaUsage> for OrganizationQuotaResponse {
+    fn from(value: OrganizationQuotaUsage) -> Self {
+        OrganizationQuotaResponse {
+            organization_id: value.organization.id(),
+            max_users: value.max_users,
+            custom: value.custom,
+            users: value.users,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct PutOrganizationQuotaRequest {
+    /// Most users the organization can have, or `null` if it can have any number of them.
+    pub max_users: Option<u32>,
+}
+
+const MAX_NAME_LENGTH: usize = 100;
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct PutOrganizationRequest {
+    // Missing required fields are reported by the validation.
+    #[serde(default)]
+    #[schema(required = true, min_length = 1, max_length = 100)]
+    pub name: String,
+    /// Slug used in vanity URLs. New organizations get one derived from the name if it's not
+    /// set, and existing ones keep theirs. Replaced slugs keep pointing to the organization.
+    #[schema(
+        min_length = 3,
+        max_length = 63,
+        pattern = "^[a-z0-9]+(-[a-z0-9]+)*$"
+    )]
+    pub slug: Option<String>,
+}
+
+impl Validate for PutOrganizationRequest {
+    fn validate(&self, validator: &mut Validator) {
+        validator
+            .field("name", Some(&self.name))
+            .required()
+            .max_length(MAX_NAME_LENGTH);
+        validator.field("slug", self.slug.as_deref()).slug();
+    }
+}
+
+/// Returns the canonical URL of the organization with the slug.
+fn slug_location(slug: &str) -> String {
+    format!("{}/organizations/by-slug/{slug}", ApiVersion::V1.prefix())
+}
+
+/// Returns the full state of an organization, so that IaC tools can detect drift.
+#[utoipa::path(
+    get,
+    path = "/organizations/{external_id}",
+    operation_id = "get_organization",
+    tag = "organizations",
+    params(("external_id" = String, Path, description = "ID of the organization assigned by the caller")),
+    responses(
+        (status = OK, description = "The organization", body = OrganizationResponse),
+        (status = NOT_FOUND, description = "The organization doesn't exist", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+    ),
+    security(("api_key" = [])),
+)]
+async fn get_handler(
+    ReadContext(context): ReadContext,
+    Path(external_id): Path<String>,
+) -> Result<Json<OrganizationResponse>, ApiError> {
+    let organization = get_organization_by_external_id(
+        context.organizations().await?,
+        GetOrganizationByExternalIdParams { external_id },
+    )
+    .await?;
+
+    Ok(Json(OrganizationResponse::from(organization)))
+}
+
+/// Creates or updates an organization.
+///
+/// The request is idempotent: repeating it leaves the organization as it is, and its ID stays
+/// the same.
+#[utoipa::path(
+    put,
+    path = "/organizations/{external_id}",
+    operation_id = "put_organization",
+    tag = "organizations",
+    params(("external_id" = String, Path, description = "ID of the organization assigned by the caller")),
+    request_body = PutOrganizationRequest,
+    responses(
+        (status = CREATED, description = "The created organization", body = OrganizationResponse),
+        (status = OK, description = "The organization, which was updated if needed", body = OrganizationResponse),
+        (status = CONFLICT, description = "The slug is used by another organization", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+        (status = UNPROCESSABLE_ENTITY, description = "The request is invalid", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+    ),
+    security(("api_key" = [])),
+)]
+async fn put_handler(
+    context: UseCaseContext,
+    State(identifiers): State<IdentifierLists>,
+    Path(external_id): Path<String>,
+    ValidJson(request): ValidJson<PutOrganizationRequest>,
+) -> Result<(StatusCode, Json<OrganizationResponse>), ApiError> {
+    let (organization, outcome) = context
+        .retried("put_organization", |context| async {
+            let put = put_organization(
+                context.organizations().await?,
+                PutOrganizationParams {
+                    external_id: external_id.clone(),
+                    name: request.name.clone(),
+                    slug: request.slug.clone(),
+                    identifier_policy: identifiers.policy(),
+                },
+            )
+            .await?;
+
+            Ok(put)
+        })
+        .await?;
+
+    let status = match outcome {
+        PutOutcome::Created => StatusCode::CREATED,
+        PutOutcome::Updated | PutOutcome::Unchanged => StatusCode::OK,
+    };
+
+    Ok((status, Json(OrganizationResponse::from(organization))))
+}
+
+/// Returns the organization with the slug used in its vanity URLs.
+///
+/// Slugs the organization has replaced redirect to its current one, so that old links keep
+/// working.
+#[utoipa::path(
+    get,
+    path = "/organizations/by-slug/{slug}",
+    operation_id = "get_organization_by_slug",
+    tag = "organizations",
+    params(("slug" = String, Path, description = "Current or previous slug of the organization")),
+    responses(
+        (status = OK, description = "The organization", body = OrganizationResponse),
+        (status = PERMANENT_REDIRECT, description = "The slug was replaced, the current one is in the `Location` header",
+            headers(("Location" = String, description = "URL of the organization with its current slug"))),
+        (status = NOT_FOUND, description = "No organization has ever used the slug", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+    ),
+    security(("api_key" = [])),
+)]
+async fn get_by_slug_handler(
+    ReadContext(context): ReadContext,
+    Path(slug): Path<String>,
+) -> Result<Response, ApiError> {
+    let organization = get_organization_by_slug(
+        context.organizations().await?,
+        GetOrganizationBySlugParams { slug: slug.clone() },
+    )
+    .await?;
+
+    if organization.slug() != slug {
+        return Ok((
+            StatusCode::PERMANENT_REDIRECT,
+            [(LOCATION, slug_location(organization.slug()))],
+        )
+            .into_response());
+    }
+
+    Ok(Json(OrganizationResponse::from(organization)).into_response())
+}
+
+/// Returns how many users the organization can have, and how many it has.
+#[utoipa::path(
+    get,
+    path = "/organizations/{external_id}/quota",
+    operation_id = "get_organization_quota",
+    tag = "organizations",
+    params(("external_id" = String, Path, description = "ID of the organization assigned by the caller")),
+    responses(
+        (status = OK, description = "The quota of the organization", body = OrganizationQuotaResponse),
+        (status = NOT_FOUND, description = "The organization doesn't exist", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+    ),
+    security(("api_key" = [])),
+)]
+async fn get_quota_handler(
+    ReadContext(context): ReadContext,
+    State(policy): State<QuotaPolicy>,
+    Path(external_id): Path<String>,
+) -> Result<Json<OrganizationQuotaResponse>, ApiError> {
+    let usage = get_organization_quota(
+        context.organizations().await?,
+        GetOrganizationQuotaParams {
+            external_id,
+            policy,
+        },
+    )
+    .await?;
+
+    Ok(Json(usage.into()))
+}
+
+/// Gives the organization a quota of its own, which replaces the default of the configuration.
+///
+/// Organizations that already have more users keep them, but no one else can join them.
+#[utoipa::path(
+    put,
+    path = "/organizations/{external_id}/quota",
+    operation_id = "put_organization_quota",
+    tag = "organizations",
+    params(("external_id" = String, Path, description = "ID of the organization assigned by the caller")),
+    request_body = PutOrganizationQuotaRequest,
+    responses(
+        (status = OK, description = "The quota of the organization", body = OrganizationQuotaResponse),
+        (status = NOT_FOUND, description = "The organization doesn't exist", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+        (status = UNPROCESSABLE_ENTITY, description = "The request is invalid", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+    ),
+    security(("api_key" = [])),
+)]
+async fn put_quota_handler(
+    context: UseCaseContext,
+    State(policy): State<QuotaPolicy>,
+    Path(external_id): Path<String>,
+    Json(request): Json<PutOrganizationQuotaRequest>,
+) -> Result<Json<OrganizationQuotaResponse>, ApiError> {
+    let usage = context
+        .retried("put_organization_quota", |context| async {
+            let usage = put_organization_quota(
+                context.organizations().await?,
+                PutOrganizationQuotaParams {
+                    external_id: external_id.clone(),
+                    max_users: request.max_users,
+                    policy,
+                },
+            )
+            .await?;
+
+            Ok(usage)
+        })
+        .await?;
+
+    Ok(Json(usage.into()))
+}
+
+/// Removes the quota of the organization, so that it's limited by the default of the
+/// configuration again.
+#[utoipa::path(
+    delete,
+    path = "/organizations/{external_id}/quota",
+    operation_id = "reset_organization_quota",
+    tag = "organizations",
+    params(("external_id" = String, Path, description = "ID of the organization assigned by the caller")),
+    responses(
+        (status = OK, description = "The default quota the organization has now", body = OrganizationQuotaResponse),
+        (status = NOT_FOUND, description = "The organization doesn't exist", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+    ),
+    security(("api_key" = [])),
+)]
+async fn reset_quota_handler(
+    context: UseCaseContext,
+    State(policy): State<QuotaPolicy>,
+    Path(external_id): Path<String>,
+) -> Result<Json<OrganizationQuotaResponse>, ApiError> {
+    let usage = context
+        .retried("reset_organization_quota", |context| async {
+            let usage = reset_organization_quota(
+                context.organizations().await?,
+                ResetOrganizationQuotaParams {
+                    external_id: external_id.clone(),
+                    policy,
+                },
+            )
+            .await?;
+
+            Ok(usage)
+        })
+        .await?;
+
+    Ok(Json(usage.into()))
+}
@@ -0,0 +1,240 @@
+use std::sync::Arc;
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    routing::post,
+};
+use chrono::{DateTime, TimeDelta, Utc};
+use identify_application::{
+    SmsSender, StartPhoneVerificationParams, TokenGenerator, VerifyPhoneParams,
+    start_phone_verification, verify_phone,
+};
+use identify_domain::PhoneVerification;
+use serde::{Deserialize, Serialize};
+use utoipa::{OpenApi, ToSchema};
+use uuid::Uuid;
+
+use crate::api::{
+    Route, Service,
+    error::{ApiError, PROBLEM_CONTENT_TYPE, Problem},
+    policy::{RateLimitClass, RoutePolicy},
+    use_case::UseCaseContext,
+    validation::{ValidJson, Validate, Validator},
+};
+
+/// Longest phone number accepted before it's normalized, e.g. with spaces and parentheses.
+const MAX_PHONE_NUMBER_LENGTH: usize = 32;
+/// Longest code accepted, well above the length of the generated ones.
+const MAX_CODE_LENGTH: usize = 16;
+
+/// How long the texted codes last.
+#[derive(Debug, Clone)]
+pub struct PhoneVerificationSettings {
+    pub valid_for: TimeDelta,
+}
+
+pub struct PhoneVerificationService;
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(start_handler, verify_handler),
+    tags((name = "users", description = "Users of the service"))
+)]
+struct PhoneVerificationApi;
+
+impl Service for PhoneVerificationService {
+    fn routes() -> Vec<Route> {
+        vec![
+            Route::new(
+                "/users/{id}/phone-verifications",
+                post(start_handler),
+                RoutePolicy::authenticated()
+                    .permissions(&["users:write"])
+                    .rate_limit(RateLimitClass::Strict),
+            ),
+            Route::new(
+                "/phone-verifications/{id}/verify",
+                post(verify_handler),
+                RoutePolicy::authenticated()
+                    .permissions(&["users:write"])
+                    .rate_limit(RateLimitClass::Strict),
+            ),
+        ]
+    }
+
+    fn openapi() -> utoipa::openapi::OpenApi {
+        PhoneVerificationApi::openapi()
+    }
+}
+
+/// A verification of a phone number of a user. The code it's made with is never returned.
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(as = PhoneVerification)]
+pub struct PhoneVerificationResponse {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    /// Number the code was texted to, in the E.164 format.
+    #[schema(example = "+14155550123")]
+    pub phone_number: String,
+    /// Number of wrong codes entered so far.
+    pub attempts: u32,
+    pub expires_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub verified_at: Option<DateTime<Utc>>,
+}
+
+impl From<PhoneVerification> for PhoneVerificationResponse {
+    fn from(value: PhoneVerification) -> Self {
+        PhoneVerificationResponse {
+            id: value.id(),
+            user_id: value.user_id(),
+            phone_number: value.phone_number().to_owned(),
+            attempts: value.attempts(),
+            expires_at: *value.expires_at(),
+            created_at: *value.created_at(),
+            verified_at: *value.verified_at(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[schema(as = StartPhoneVerification)]
+pub struct StartPhoneVerificationRequest {
+    /// Number with its country code, e.g. `+1 415 555 0123`. It's normalized to the E.164
+    /// format.
+    #[serde(default)]
+    #[schema(required = true, max_length = 32, example = "+1 415 555 0123")]
+    pub phone_number: String,
+}
+
+impl Validate for StartPhoneVerificationRequest {
+    fn validate(&self, validator: &mut Validator) {
+        validator
+            .field("phone_number", Some(&self.phone_number))
+            .required()
+            .max_length(MAX_PHONE_NUMBER_LENGTH)
+            .phone_number();
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[schema(as = VerifyPhone)]
+pub struct VerifyPhoneRequest {
+    /// Code the user was texted.
+    #[serde(default)]
+    #[schema(required = true, max_length = 16)]
+    pub code: String,
+}
+
+impl Validate for VerifyPhoneRequest {
+    fn validate(&self, validator: &mut Validator) {
+        validator
+            .field("code", Some(&self.code))
+            .required()
+            .max_length(MAX_CODE_LENGTH);
+    }
+}
+
+/// Texts a code to a phone number of a user, which they verify the number with.
+#[utoipa::path(
+    post,
+    path = "/users/{id}/phone-verifications",
+    operation_id = "start_phone_verification",
+    tag = "users",
+    params(("id" = Uuid, Path, description = "ID of the user")),
+    request_body = StartPhoneVerificationRequest,
+    responses(
+        (status = CREATED, description = "The verification, whose code was texted to the number", body = PhoneVerificationResponse),
+        (status = NOT_FOUND, description = "The user doesn't exist", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+        (status = CONFLICT, description = "The user has been erased", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+        (status = UNPROCESSABLE_ENTITY, description = "The body is invalid", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+    ),
+    security(("api_key" = ["users:write"])),
+)]
+async fn start_handler(
+    context: UseCaseContext,
+    State(tokens): State<Arc<dyn TokenGenerator>>,
+    State(sender): State<Arc<dyn SmsSender>>,
+    State(settings): State<PhoneVerificationSettings>,
+    Path(id): Path<Uuid>,
+    ValidJson(request): ValidJson<StartPhoneVerificationRequest>,
+) -> Result<(StatusCode, Json<PhoneVerificationResponse>), ApiError> {
+    let verification = context
+        .retried("start_phone_verification", |mut context| async {
+            let verification = start_phone_verification(
+                context
+                    .phone_verification_deliveries(
+                        tokens.as_ref(),
+                        sender.as_ref(),
+                    )
+                    .await?,
+                StartPhoneVerificationParams {
+                    user_id: id,
+                    phone_number: request.phone_number.clone(),
+                    valid_for: settings.valid_for,
+                },
+            )
+            .await?;
+            context.commit().await?;
+
+            Ok(verification)
+        })
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(verification.into())))
+}
+
+/// Verifies a phone number with the code the user was texted.
+///
+/// Wrong codes are counted, and the verification can't be completed anymore after a few of
+/// them.
+#[utoipa::path(
+    post,
+    path = "/phone-verifications/{id}/verify",
+    operation_id = "verify_phone",
+    tag = "users",
+    params(("id" = Uuid, Path, description = "ID of the verification")),
+    request_body = VerifyPhoneRequest,
+    responses(
+        (status = OK, description = "The completed verification", body = PhoneVerificationResponse),
+        (status = NOT_FOUND, description = "The verification doesn't exist", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+        (status = CONFLICT, description = "The number is already verified, the verification has expired or too many wrong codes were entered, or the user has been erased", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+        (status = UNPROCESSABLE_ENTITY, description = "The body is invalid, or the code is wrong", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+    ),
+    security(("api_key" = ["users:write"])),
+)]
+async fn verify_handler(
+    context: UseCaseContext,
+    State(tokens): State<Arc<dyn TokenGenerator>>,
+    Path(id): Path<Uuid>,
+    ValidJson(request): ValidJson<VerifyPhoneRequest>,
+) -> Result<Json<PhoneVerificationResponse>, ApiError> {
+    let verification = context
+        .retried("verify_phone", |mut context| async {
+            let verification = verify_phone(
+                context.phone_verifications(tokens.as_ref()).await?,
+                VerifyPhoneParams {
+                    id,
+                    code: request.code.clone(),
+                },
+            )
+            .await?;
+            // Wrong codes are committed too, so that they count towards the attempts.
+            context.commit().await?;
+
+            Ok(verification)
+        })
+        .await?;
+
+    if !verification.is_verified() {
+        let left = PhoneVerification::MAX_ATTEMPTS - verification.attempts();
+        return Err(ApiError::new(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            format!("The code is wrong, {left} attempts are left"),
+        ));
+    }
+
+    Ok(Json(verification.into()))
+}
@@ -0,0 +1,297 @@
+use std::sync::Arc;
+
+use axum::{
+    Json,
+    extract::{FromRef, State},
+    http::StatusCode,
+    routing::post,
+};
+use chrono::{DateTime, TimeDelta, Utc};
+use identify_application::{
+    ApplicationError, EmailSender, EmailTemplates, PasswordHasher,
+    RegisterUserParams, TokenGenerator, VerifyEmailParams, register_user,
+    verify_email,
+};
+use identify_domain::{
+    EmailVerification, NewUserAttrs, Password, RegistrationPolicy, Username,
+    UsernameStrategy,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::{OpenApi, ToSchema};
+use uuid::Uuid;
+
+use crate::{
+    api::{
+        ApiState, Route, Service,
+        error::{ApiError, PROBLEM_CONTENT_TYPE, Problem},
+        policy::{RateLimitClass, RoutePolicy},
+        services::users::{MAX_EMAIL_LENGTH, MAX_NAME_LENGTH, UserResponse},
+        use_case::PublicContext,
+        validation::{ValidJson, Validate, Validator},
+    },
+    identifiers::IdentifierLists,
+};
+
+/// Longest token accepted, well above the length of the generated ones.
+const MAX_TOKEN_LENGTH: usize = 256;
+
+/// Who can register themselves, where the emailed links point to, and how long they last.
+#[derive(Debug, Clone)]
+pub struct RegistrationSettings {
+    pub policy: Arc<RegistrationPolicy>,
+    /// URL of the page users verify their emails on.
+    pub verify_url: String,
+    pub verification_valid_for: TimeDelta,
+}
+
+/// Everything registering a user takes from the state, besides how their username is chosen.
+#[derive(Clone)]
+struct Registrar {
+    settings: RegistrationSettings,
+    hasher: Arc<dyn PasswordHasher>,
+    tokens: Arc<dyn TokenGenerator>,
+    sender: Arc<dyn EmailSender>,
+    templates: Arc<EmailTemplates>,
+}
+
+impl FromRef<ApiState> for Registrar {
+    fn from_ref(state: &ApiState) -> Self {
+        Registrar {
+            settings: state.registration.clone(),
+            hasher: state.password_hasher.clone(),
+            tokens: state.token_generator.clone(),
+            sender: state.email_sender.clone(),
+            templates: state.email_templates.clone(),
+        }
+    }
+}
+
+pub struct RegistrationService;
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(register_handler, verify_email_handler),
+    tags((name = "auth", description = "Self-service registration of users"))
+)]
+struct RegistrationApi;
+
+impl Service for RegistrationService {
+    fn routes() -> Vec<Route> {
+        vec![
+            Route::new(
+                "/auth/register",
+                post(register_handler),
+                RoutePolicy::public().rate_limit(RateLimitClass::Strict),
+            ),
+            Route::new(
+                "/auth/verify-email",
+                post(verify_email_handler),
+                RoutePolicy::public().rate_limit(RateLimitClass::Strict),
+            ),
+        ]
+    }
+
+    fn openapi() -> utoipa::openapi::OpenApi {
+        RegistrationApi::openapi()
+    }
+}
+
+/// A verification of the email of a user. The token it's made with is never returned.
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(as = EmailVerification)]
+pub struct EmailVerificationResponse {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub expires_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub verified_at: Option<DateTime<Utc>>,
+}
+
+impl From<EmailVerification> for EmailVerificationResponse {
+    fn from(value: EmailVerification) -> Self {
+        EmailVerificationResponse {
+            id: value.id(),
+            user_id: value.user_id(),
+            expires_at: *value.expires_at(),
+            created_at: *value.created_at(),
+            verified_at: *value.verified_at(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[schema(as = RegisterUser)]
+pub struct RegisterUserRequest {
+    // Missing required fields are reported by the validation.
+    #[serde(default)]
+    #[schema(required = true, format = Email, max_length = 254)]
+    pub email: String,
+    #[serde(default)]
+    #[schema(required = true, format = Password, min_length = 12, max_length = 128)]
+    pub password: String,
+    /// Generated from the email or the name if it's not set, depending on the configuration.
+    #[schema(min_length = 3, max_length = 32)]
+    pub username: Option<String>,
+    #[serde(default)]
+    #[schema(required = true, min_length = 1, max_length = 100)]
+    pub first_name: String,
+    #[schema(max_length = 100)]
+    pub last_name: Option<String>,
+}
+
+impl Validate for RegisterUserRequest {
+    fn validate(&self, validator: &mut Validator) {
+        validator
+            .field("email", Some(&self.email))
+            .required()
+            .max_length(MAX_EMAIL_LENGTH)
+            .email();
+        validator
+            .field("password", Some(&self.password))
+            .required()
+            .min_length(Password::MIN_LENGTH)
+            .max_length(Password::MAX_LENGTH);
+        validator
+            .field("username", self.username.as_deref())
+            .min_length(Username::MIN_LENGTH)
+            .max_length(Username::MAX_LENGTH)
+            .username();
+        validator
+            .field("first_name", Some(&self.first_name))
+            .required()
+            .max_length(MAX_NAME_LENGTH);
+        validator
+            .field("last_name", self.last_name.as_deref())
+            .max_length(MAX_NAME_LENGTH);
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[schema(as = VerifyEmail)]
+pub struct VerifyEmailRequest {
+    /// ID of the verification, from the `verification` query parameter of the emailed link.
+    #[serde(default)]
+    #[schema(required = true)]
+    pub verification: Uuid,
+    /// Token from the `token` query parameter of the emailed link.
+    #[serde(default)]
+    #[schema(required = true, max_length = 256)]
+    pub token: String,
+}
+
+impl Validate for VerifyEmailRequest {
+    fn validate(&self, validator: &mut Validator) {
+        validator
+            .field("token", Some(&self.token))
+            .required()
+            .max_length(MAX_TOKEN_LENGTH);
+    }
+}
+
+/// Registers a user who signs up by themselves, e.g. through a sign-up page, with their
+/// password, and emails them a link to the page where they verify their email.
+///
+/// Whether anyone can register, only people with emails in some domains, or nobody (since
+/// people are invited instead) depends on the configuration. No credentials are needed, so
+/// callers are rate-limited strictly by their IP addresses.
+#[utoipa::path(
+    post,
+    path = "/auth/register",
+    operation_id = "register_user",
+    tag = "auth",
+    request_body = RegisterUserRequest,
+    responses(
+        (status = CREATED, description = "The registered user, who was emailed a verification link", body = UserResponse),
+        (status = FORBIDDEN, description = "Registration is by invitation only, or the domain of the email isn't allowed", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+        (status = CONFLICT, description = "A user with the email or the username already exists", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+        (status = UNPROCESSABLE_ENTITY, description = "The body is invalid", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+    ),
+)]
+async fn register_handler(
+    PublicContext(context): PublicContext,
+    State(registrar): State<Registrar>,
+    State(username_strategy): State<UsernameStrategy>,
+    State(identifiers): State<IdentifierLists>,
+    ValidJson(request): ValidJson<RegisterUserRequest>,
+) -> Result<(StatusCode, Json<UserResponse>), ApiError> {
+    let password =
+        Password::parse(&request.password).map_err(ApplicationError::from)?;
+
+    let user = context
+        .retried("register_user", |mut context| async {
+            let user = register_user(
+                context
+                    .self_registrations(
+                        &registrar.settings.policy,
+                        registrar.hasher.as_ref(),
+                        registrar.tokens.as_ref(),
+                        registrar.sender.as_ref(),
+                        &registrar.templates,
+                    )
+                    .await?,
+                RegisterUserParams {
+                    user_attrs: NewUserAttrs {
+                        email: request.email.clone(),
+                        first_name: request.first_name.clone(),
+                        last_name: request.last_name.clone(),
+                    },
+                    password: password.clone(),
+                    username: request.username.clone(),
+                    username_strategy,
+                    identifier_policy: identifiers.policy(),
+                    verify_url: registrar.settings.verify_url.clone(),
+                    verification_valid_for: registrar
+                        .settings
+                        .verification_valid_for,
+                },
+            )
+            .await?;
+            context.commit().await?;
+
+            Ok(user)
+        })
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(user.into())))
+}
+
+/// Verifies the email of a user with the token from the link they were emailed.
+///
+/// Verifications whose token doesn't match are reported as not found. No credentials are
+/// needed, since the token proves that the caller received the email.
+#[utoipa::path(
+    post,
+    path = "/auth/verify-email",
+    operation_id = "verify_email",
+    tag = "auth",
+    request_body = VerifyEmailRequest,
+    responses(
+        (status = OK, description = "The completed verification", body = EmailVerificationResponse),
+        (status = NOT_FOUND, description = "The verification doesn't exist, or the token doesn't match", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+        (status = CONFLICT, description = "The email is already verified, the verification has expired, or the user has been erased", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+        (status = UNPROCESSABLE_ENTITY, description = "The body is invalid", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+    ),
+)]
+async fn verify_email_handler(
+    PublicContext(context): PublicContext,
+    State(tokens): State<Arc<dyn TokenGenerator>>,
+    ValidJson(request): ValidJson<VerifyEmailRequest>,
+) -> Result<Json<EmailVerificationResponse>, ApiError> {
+    let verification = context
+        .retried("verify_email", |mut context| async {
+            let verification = verify_email(
+                context.email_verifications(tokens.as_ref()).await?,
+                VerifyEmailParams {
+                    id: request.verification,
+                    token: request.token.clone(),
+                },
+            )
+            .await?;
+            context.commit().await?;
+
+            Ok(verification)
+        })
+        .await?;
+
+    Ok(Json(verification.into()))
+}
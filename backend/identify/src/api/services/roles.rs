@@ -0,0 +1,810 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    routing::{delete, get, post, put},
+};
+use chrono::{DateTime, Utc};
+use identify_application::{
+    ApplicationError, AssignRole, AssignRoleParams, BulkAssignRoles,
+    BulkAssignRolesParams, CreateRole, CreateRoleParams, DeleteRole,
+    DeleteRoleParams, GetRoleByExternalIdParams, GetRoleParams,
+    GrantRolePermission, GrantRolePermissionParams, ListRoleMembersParams,
+    ListRolesParams, ListUserRolesParams, PutOutcome, PutRole, PutRoleParams,
+    RevokeRolePermission, RevokeRolePermissionParams, RoleUseCaseDeps,
+    TransactionalUseCaseExt as _, UnassignRole, UnassignRoleParams, UpdateRole,
+    UpdateRoleParams, UseCase as _, UseCaseExt as _, get_role,
+    get_role_by_external_id, list_role_members, list_roles, list_user_roles,
+};
+use identify_domain::{NewRoleAttrs, Role, RoleAssignment, RoleCohort};
+use serde::{Deserialize, Serialize};
+use utoipa::{OpenApi, ToSchema};
+use uuid::Uuid;
+
+use crate::api::{
+    Route, Service,
+    error::{ApiError, PROBLEM_CONTENT_TYPE, Problem},
+    policy::RoutePolicy,
+    services::users::UserResponse,
+    use_case::{PolicyPipeline, ReadContext, UseCaseContext},
+    validation::{ValidJson, Validate, Validator},
+};
+
+const MAX_NAME_LENGTH: usize = 64;
+const MAX_DESCRIPTION_LENGTH: usize = 500;
+const MAX_DOMAIN_LENGTH: usize = 253;
+
+pub struct RoleService;
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        list_handler,
+        create_handler,
+        get_handler,
+        get_by_external_id_handler,
+        put_by_external_id_handler,
+        update_handler,
+        delete_handler,
+        grant_permission_handler,
+        revoke_permission_handler,
+        list_members_handler,
+        assign_handler,
+        bulk_assign_handler,
+        unassign_handler,
+        list_user_roles_handler,
+    ),
+    tags((name = "roles", description = "Named sets of permissions assigned to users"))
+)]
+struct RoleApi;
+
+impl Service for RoleService {
+    fn routes() -> Vec<Route> {
+        let read = || RoutePolicy::authenticated().permissions(&["roles:read"]);
+        // The changes are authorized by the policies of their use cases, see `create_handler`.
+        let write = RoutePolicy::authenticated;
+
+        vec![
+            Route::new("/admin/roles", get(list_handler), read()),
+            Route::new("/admin/roles", post(create_handler), write()),
+            Route::new("/admin/roles/{id}", get(get_handler), read()),
+            Route::new(
+                "/admin/roles/by-external-id/{external_id}",
+                get(get_by_external_id_handler),
+                read(),
+            ),
+            Route::new(
+                "/admin/roles/by-external-id/{external_id}",
+                put(put_by_external_id_handler),
+                write(),
+            ),
+            Route::new("/admin/roles/{id}", put(update_handler), write()),
+            Route::new("/admin/roles/{id}", delete(delete_handler), write()),
+            Route::new(
+                "/admin/roles/{id}/permissions/{permission}",
+                put(grant_permission_handler),
+                write(),
+            ),
+            Route::new(
+                "/admin/roles/{id}/permissions/{permission}",
+                delete(revoke_permission_handler),
+                write(),
+            ),
+            Route::new(
+                "/admin/roles/{id}/members",
+                get(list_members_handler),
+                read(),
+            ),
+            Route::new(
+                "/admin/roles/{id}/members/{user_id}",
+                put(assign_handler),
+                write(),
+            ),
+            Route::new(
+                "/admin/roles/{id}/members/{user_id}",
+                delete(unassign_handler),
+                write(),
+            ),
+            Route::new(
+                "/admin/roles/{id}/assign",
+                post(bulk_assign_handler),
+                write(),
+            ),
+            Route::new(
+                "/admin/users/{id}/roles",
+                get(list_user_roles_handler),
+                read(),
+            ),
+        ]
+    }
+
+    fn openapi() -> utoipa::openapi::OpenApi {
+        RoleApi::openapi()
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(as = Role)]
+pub struct RoleResponse {
+    pub id: Uuid,
+    #[schema(example = "support")]
+    pub name: String,
+    pub description: Option<String>,
+    /// Permissions granted to the members of the role, in the order they were granted.
+    #[schema(example = json!(["users:read", "users:write"]))]
+    pub permissions: Vec<String>,
+    /// ID of the role assigned by the tool that manages it, if any.
+    pub external_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<Role> for RoleResponse {
+    fn from(value: Role) -> Self {
+        let attrs = value.to_attributes();
+
+        RoleResponse {
+            id: attrs.id,
+            name: attrs.name,
+            description: attrs.description,
+            permissions: attrs.permissions,
+            external_id: attrs.external_id,
+            created_at: attrs.created_at,
+            updated_at: attrs.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(as = RoleList)]
+pub struct RoleListResponse {
+    pub items: Vec<RoleResponse>,
+}
+
+impl From<Vec<Role>> for RoleListResponse {
+    fn from(value: Vec<Role>) -> Self {
+        RoleListResponse {
+            items: value.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(as = RoleMember)]
+pub struct RoleMemberResponse {
+    pub user_id: Uuid,
+    pub assigned_at: DateTime<Utc>,
+}
+
+impl From<RoleAssignment> for RoleMemberResponse {
+    fn from(value: RoleAssignment) -> Self {
+        RoleMemberResponse {
+            user_id: value.user_id,
+            assigned_at: value.assigned_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(as = RoleMemberList)]
+pub struct RoleMemberListResponse {
+    pub items: Vec<RoleMemberResponse>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateRoleRequest {
+    // Missing required fields are reported by the validation.
+    #[serde(default)]
+    #[schema(required = true, max_length = 64, example = "support")]
+    pub name: String,
+    #[schema(max_length = 500)]
+    pub description: Option<String>,
+    /// Permissions granted to the members of the role, e.g. `users:read`.
+    #[serde(default)]
+    pub permissions: Vec<String>,
+}
+
+impl Validate for CreateRoleRequest {
+    fn validate(&self, validator: &mut Validator) {
+        validator
+            .field("name", Some(&self.name))
+            .required()
+            .max_length(MAX_NAME_LENGTH);
+        validator
+            .field("description", self.description.as_deref())
+            .max_length(MAX_DESCRIPTION_LENGTH);
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateRoleRequest {
+    // Missing required fields are reported by the validation.
+    #[serde(default)]
+    #[schema(required = true, max_length = 64, example = "support")]
+    pub name: String,
+    /// Removed if it's missing or blank.
+    #[schema(max_length = 500)]
+    pub description: Option<String>,
+}
+
+impl Validate for UpdateRoleRequest {
+    fn validate(&self, validator: &mut Validator) {
+        validator
+            .field("name", Some(&self.name))
+            .required()
+            .max_length(MAX_NAME_LENGTH);
+        validator
+            .field("description", self.description.as_deref())
+            .max_length(MAX_DESCRIPTION_LENGTH);
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct PutRoleRequest {
+    // Missing required fields are reported by the validation.
+    #[serde(default)]
+    #[schema(required = true, max_length = 64, example = "support")]
+    pub name: String,
+    /// Removed if it's missing or blank.
+    #[schema(max_length = 500)]
+    pub description: Option<String>,
+    /// All the permissions granted to the members of the role, e.g. `users:read`. The ones
+    /// that aren't listed are revoked.
+    #[serde(default)]
+    pub permissions: Vec<String>,
+}
+
+impl Validate for PutRoleRequest {
+    fn validate(&self, validator: &mut Validator) {
+        validator
+            .field("name", Some(&self.name))
+            .required()
+            .max_length(MAX_NAME_LENGTH);
+        validator
+            .field("description", self.description.as_deref())
+            .max_length(MAX_DESCRIPTION_LENGTH);
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct BulkAssignRoleRequest {
+    /// Assign the role to the users with emails of this domain.
+    #[schema(max_length = 253, example = "acme.test")]
+    pub email_domain: Option<String>,
+    /// Assign the role to the members of this organization.
+    pub organization_id: Option<Uuid>,
+    /// Only return the users the role would be assigned to.
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+impl Validate for BulkAssignRoleRequest {
+    fn validate(&self, validator: &mut Validator) {
+        validator
+            .field("email_domain", self.email_domain.as_deref())
+            .max_length(MAX_DOMAIN_LENGTH);
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(as = BulkRoleAssignment)]
+pub struct BulkRoleAssignmentResponse {
+    /// Whether the role has been assigned, which it isn't in dry runs.
+    pub applied: bool,
+    /// Users the role has been, or would be, assigned to, the oldest first.
+    pub users: Vec<UserResponse>,
+}
+
+/// Lists all the roles by name.
+#[utoipa::path(
+    get,
+    path = "/admin/roles",
+    operation_id = "list_roles",
+    tag = "roles",
+    responses(
+        (status = OK, description = "All the roles", body = RoleListResponse),
+    ),
+    security(("api_key" = ["roles:read"])),
+)]
+async fn list_handler(
+    ReadContext(mut context): ReadContext,
+) -> Result<Json<RoleListResponse>, ApiError> {
+    let mut repository = context.roles().await?;
+
+    let roles =
+        list_roles(RoleUseCaseDeps::new(&mut repository), ListRolesParams)
+            .await?;
+
+    Ok(Json(RoleListResponse::from(roles)))
+}
+
+/// Creates a role.
+///
+/// The request is authorized by the authorization policies, which can check the name of the
+/// role as `resource.name`, and falls back to the `roles:write` permission when none of them
+/// applies.
+#[utoipa::path(
+    post,
+    path = "/admin/roles",
+    operation_id = "create_role",
+    tag = "roles",
+    request_body = CreateRoleRequest,
+    responses(
+        (status = CREATED, description = "The created role", body = RoleResponse),
+        (status = CONFLICT, description = "Another role has the name", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+        (status = UNPROCESSABLE_ENTITY, description = "The request body is invalid", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+    ),
+    security(("api_key" = ["roles:write"])),
+)]
+async fn create_handler(
+    context: UseCaseContext,
+    State(pipeline): State<PolicyPipeline>,
+    ValidJson(request): ValidJson<CreateRoleRequest>,
+) -> Result<(StatusCode, Json<RoleResponse>), ApiError> {
+    let create_role = CreateRole
+        .in_transaction(context.transactions())
+        .retried(pipeline.retrier)
+        .authorize(pipeline.policies, "roles:write")
+        .measured(pipeline.metrics)
+        .logged();
+
+    let role = create_role
+        .execute(
+            context.principal(),
+            CreateRoleParams {
+                role_attrs: NewRoleAttrs {
+                    name: request.name,
+                    description: request.description,
+                    permissions: request.permissions,
+                    external_id: None,
+                },
+            },
+        )
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(RoleResponse::from(role))))
+}
+
+/// Returns a role.
+#[utoipa::path(
+    get,
+    path = "/admin/roles/{id}",
+    operation_id = "get_role",
+    tag = "roles",
+    params(("id" = Uuid, Path, description = "ID of the role")),
+    responses(
+        (status = OK, description = "The role", body = RoleResponse),
+        (status = NOT_FOUND, description = "The role doesn't exist", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+    ),
+    security(("api_key" = ["roles:read"])),
+)]
+async fn get_handler(
+    ReadContext(mut context): ReadContext,
+    Path(id): Path<Uuid>,
+) -> Result<Json<RoleResponse>, ApiError> {
+    let mut repository = context.roles().await?;
+
+    let role =
+        get_role(RoleUseCaseDeps::new(&mut repository), GetRoleParams { id })
+            .await?;
+
+    Ok(Json(RoleResponse::from(role)))
+}
+
+/// Returns the full state of a role by the ID assigned to it by the tool that manages it, so
+/// that IaC tools can detect drift.
+#[utoipa::path(
+    get,
+    path = "/admin/roles/by-external-id/{external_id}",
+    operation_id = "get_role_by_external_id",
+    tag = "roles",
+    params(("external_id" = String, Path, description = "ID of the role assigned by the caller")),
+    responses(
+        (status = OK, description = "The role", body = RoleResponse),
+        (status = NOT_FOUND, description = "The role doesn't exist", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+    ),
+    security(("api_key" = ["roles:read"])),
+)]
+async fn get_by_external_id_handler(
+    ReadContext(mut context): ReadContext,
+    Path(external_id): Path<String>,
+) -> Result<Json<RoleResponse>, ApiError> {
+    let mut repository = context.roles().await?;
+
+    let role = get_role_by_external_id(
+        RoleUseCaseDeps::new(&mut repository),
+        GetRoleByExternalIdParams { external_id },
+    )
+    .await?;
+
+    Ok(Json(RoleResponse::from(role)))
+}
+
+/// Creates or updates a role by the ID assigned to it by the tool that manages it.
+///
+/// The request is idempotent: repeating it leaves the role as it is, and its ID stays the same.
+/// It's authorized by the authorization policies, which can check the name of the role as
+/// `resource.name`, and falls back to the `roles:write` permission when none of them applies.
+#[utoipa::path(
+    put,
+    path = "/admin/roles/by-external-id/{external_id}",
+    operation_id = "put_role",
+    tag = "roles",
+    params(("external_id" = String, Path, description = "ID of the role assigned by the caller")),
+    request_body = PutRoleRequest,
+    responses(
+        (status = CREATED, description = "The created role", body = RoleResponse),
+        (status = OK, description = "The role, which was updated if needed", body = RoleResponse),
+        (status = CONFLICT, description = "Another role has the name", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+        (status = UNPROCESSABLE_ENTITY, description = "The request is invalid", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+    ),
+    security(("api_key" = ["roles:write"])),
+)]
+async fn put_by_external_id_handler(
+    context: UseCaseContext,
+    State(pipeline): State<PolicyPipeline>,
+    Path(external_id): Path<String>,
+    ValidJson(request): ValidJson<PutRoleRequest>,
+) -> Result<(StatusCode, Json<RoleResponse>), ApiError> {
+    let put_role = PutRole
+        .in_transaction(context.transactions())
+        .retried(pipeline.retrier)
+        .authorize(pipeline.policies, "roles:write")
+        .measured(pipeline.metrics)
+        .logged();
+
+    let (role, outcome) = put_role
+        .execute(
+            context.principal(),
+            PutRoleParams {
+                external_id,
+                name: request.name,
+                description: request.description,
+                permissions: request.permissions,
+            },
+        )
+        .await?;
+
+    let status = match outcome {
+        PutOutcome::Created => StatusCode::CREATED,
+        PutOutcome::Updated | PutOutcome::Unchanged => StatusCode::OK,
+    };
+
+    Ok((status, Json(RoleResponse::from(role))))
+}
+
+/// Renames a role and replaces its description.
+#[utoipa::path(
+    put,
+    path = "/admin/roles/{id}",
+    operation_id = "update_role",
+    tag = "roles",
+    params(("id" = Uuid, Path, description = "ID of the role")),
+    request_body = UpdateRoleRequest,
+    responses(
+        (status = OK, description = "The updated role", body = RoleResponse),
+        (status = NOT_FOUND, description = "The role doesn't exist", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+        (status = CONFLICT, description = "Another role has the name", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+        (status = UNPROCESSABLE_ENTITY, description = "The request body is invalid", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+    ),
+    security(("api_key" = ["roles:write"])),
+)]
+async fn update_handler(
+    context: UseCaseContext,
+    State(pipeline): State<PolicyPipeline>,
+    Path(id): Path<Uuid>,
+    ValidJson(request): ValidJson<UpdateRoleRequest>,
+) -> Result<Json<RoleResponse>, ApiError> {
+    let update_role = UpdateRole
+        .in_transaction(context.transactions())
+        .retried(pipeline.retrier)
+        .authorize(pipeline.policies, "roles:write")
+        .measured(pipeline.metrics)
+        .logged();
+
+    let role = update_role
+        .execute(
+            context.principal(),
+            UpdateRoleParams {
+                id,
+                name: request.name,
+                description: request.description,
+            },
+        )
+        .await?;
+
+    Ok(Json(RoleResponse::from(role)))
+}
+
+/// Deletes a role, which is taken back from all its members.
+#[utoipa::path(
+    delete,
+    path = "/admin/roles/{id}",
+    operation_id = "delete_role",
+    tag = "roles",
+    params(("id" = Uuid, Path, description = "ID of the role")),
+    responses(
+        (status = NO_CONTENT, description = "The role was deleted"),
+        (status = NOT_FOUND, description = "The role doesn't exist", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+    ),
+    security(("api_key" = ["roles:write"])),
+)]
+async fn delete_handler(
+    context: UseCaseContext,
+    State(pipeline): State<PolicyPipeline>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    let delete_role = DeleteRole
+        .in_transaction(context.transactions())
+        .retried(pipeline.retrier)
+        .authorize(pipeline.policies, "roles:write")
+        .measured(pipeline.metrics)
+        .logged();
+
+    delete_role
+        .execute(context.principal(), DeleteRoleParams { id })
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Grants a permission to a role, e.g. `users:read`. Granting it again changes nothing.
+#[utoipa::path(
+    put,
+    path = "/admin/roles/{id}/permissions/{permission}",
+    operation_id = "grant_role_permission",
+    tag = "roles",
+    params(
+        ("id" = Uuid, Path, description = "ID of the role"),
+        ("permission" = String, Path, description = "The permission, e.g. `users:read`"),
+    ),
+    responses(
+        (status = OK, description = "The role", body = RoleResponse),
+        (status = NOT_FOUND, description = "The role doesn't exist", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+        (status = UNPROCESSABLE_ENTITY, description = "The permission is invalid", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+    ),
+    security(("api_key" = ["roles:write"])),
+)]
+async fn grant_permission_handler(
+    context: UseCaseContext,
+    State(pipeline): State<PolicyPipeline>,
+    Path((id, permission)): Path<(Uuid, String)>,
+) -> Result<Json<RoleResponse>, ApiError> {
+    let grant_permission = GrantRolePermission
+        .in_transaction(context.transactions())
+        .retried(pipeline.retrier)
+        .authorize(pipeline.policies, "roles:write")
+        .measured(pipeline.metrics)
+        .logged();
+
+    let role = grant_permission
+        .execute(
+            context.principal(),
+            GrantRolePermissionParams { id, permission },
+        )
+        .await?;
+
+    Ok(Json(RoleResponse::from(role)))
+}
+
+/// Revokes a permission from a role. Revoking one the role doesn't have changes nothing.
+#[utoipa::path(
+    delete,
+    path = "/admin/roles/{id}/permissions/{permission}",
+    operation_id = "revoke_role_permission",
+    tag = "roles",
+    params(
+        ("id" = Uuid, Path, description = "ID of the role"),
+        ("permission" = String, Path, description = "The permission, e.g. `users:read`"),
+    ),
+    responses(
+        (status = OK, description = "The role", body = RoleResponse),
+        (status = NOT_FOUND, description = "The role doesn't exist", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+    ),
+    security(("api_key" = ["roles:write"])),
+)]
+async fn revoke_permission_handler(
+    context: UseCaseContext,
+    State(pipeline): State<PolicyPipeline>,
+    Path((id, permission)): Path<(Uuid, String)>,
+) -> Result<Json<RoleResponse>, ApiError> {
+    let revoke_permission = RevokeRolePermission
+        .in_transaction(context.transactions())
+        .retried(pipeline.retrier)
+        .authorize(pipeline.policies, "roles:write")
+        .measured(pipeline.metrics)
+        .logged();
+
+    let role = revoke_permission
+        .execute(
+            context.principal(),
+            RevokeRolePermissionParams { id, permission },
+        )
+        .await?;
+
+    Ok(Json(RoleResponse::from(role)))
+}
+
+/// Lists the users a role is assigned to, the first assigned first.
+#[utoipa::path(
+    get,
+    path = "/admin/roles/{id}/members",
+    operation_id = "list_role_members",
+    tag = "roles",
+    params(("id" = Uuid, Path, description = "ID of the role")),
+    responses(
+        (status = OK, description = "The members of the role", body = RoleMemberListResponse),
+        (status = NOT_FOUND, description = "The role doesn't exist", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+    ),
+    security(("api_key" = ["roles:read"])),
+)]
+async fn list_members_handler(
+    ReadContext(mut context): ReadContext,
+    Path(id): Path<Uuid>,
+) -> Result<Json<RoleMemberListResponse>, ApiError> {
+    let mut repository = context.roles().await?;
+
+    let assignments = list_role_members(
+        RoleUseCaseDeps::new(&mut repository),
+        ListRoleMembersParams { role_id: id },
+    )
+    .await?;
+
+    Ok(Json(RoleMemberListResponse {
+        items: assignments.into_iter().map(Into::into).collect(),
+    }))
+}
+
+/// Assigns a role to a user. Assigning it again changes nothing.
+#[utoipa::path(
+    put,
+    path = "/admin/roles/{id}/members/{user_id}",
+    operation_id = "assign_role",
+    tag = "roles",
+    params(
+        ("id" = Uuid, Path, description = "ID of the role"),
+        ("user_id" = Uuid, Path, description = "ID of the user"),
+    ),
+    responses(
+        (status = NO_CONTENT, description = "The role is assigned to the user"),
+        (status = NOT_FOUND, description = "The role or the user doesn't exist", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+        (status = CONFLICT, description = "The user has been erased", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+    ),
+    security(("api_key" = ["roles:write"])),
+)]
+async fn assign_handler(
+    context: UseCaseContext,
+    State(pipeline): State<PolicyPipeline>,
+    Path((role_id, user_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, ApiError> {
+    let assign_role = AssignRole
+        .in_transaction(context.transactions())
+        .retried(pipeline.retrier)
+        .authorize(pipeline.policies, "roles:write")
+        .measured(pipeline.metrics)
+        .logged();
+
+    assign_role
+        .execute(context.principal(), AssignRoleParams { role_id, user_id })
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Takes a role back from a user.
+#[utoipa::path(
+    delete,
+    path = "/admin/roles/{id}/members/{user_id}",
+    operation_id = "unassign_role",
+    tag = "roles",
+    params(
+        ("id" = Uuid, Path, description = "ID of the role"),
+        ("user_id" = Uuid, Path, description = "ID of the user"),
+    ),
+    responses(
+        (status = NO_CONTENT, description = "The role was taken back from the user"),
+        (status = NOT_FOUND, description = "The role doesn't exist or isn't assigned to the user", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+    ),
+    security(("api_key" = ["roles:write"])),
+)]
+async fn unassign_handler(
+    context: UseCaseContext,
+    State(pipeline): State<PolicyPipeline>,
+    Path((role_id, user_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, ApiError> {
+    let unassign_role = UnassignRole
+        .in_transaction(context.transactions())
+        .retried(pipeline.retrier)
+        .authorize(pipeline.policies, "roles:write")
+        .measured(pipeline.metrics)
+        .logged();
+
+    let unassigned = unassign_role
+        .execute(context.principal(), UnassignRoleParams { role_id, user_id })
+        .await?;
+    if !unassigned {
+        return Err(ApiError::not_found(format!(
+            "Role {role_id} isn't assigned to user {user_id}"
+        )));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Lists the roles assigned to a user by name.
+#[utoipa::path(
+    get,
+    path = "/admin/users/{id}/roles",
+    operation_id = "list_user_roles",
+    tag = "roles",
+    params(("id" = Uuid, Path, description = "ID of the user")),
+    responses(
+        (status = OK, description = "The roles of the user, empty if the user doesn't exist", body = RoleListResponse),
+    ),
+    security(("api_key" = ["roles:read"])),
+)]
+async fn list_user_roles_handler(
+    ReadContext(mut context): ReadContext,
+    Path(id): Path<Uuid>,
+) -> Result<Json<RoleListResponse>, ApiError> {
+    let mut repository = context.roles().await?;
+
+    let roles = list_user_roles(
+        RoleUseCaseDeps::new(&mut repository),
+        ListUserRolesParams { user_id: id },
+    )
+    .await?;
+
+    Ok(Json(RoleListResponse::from(roles)))
+}
+
+/// Assigns a role to every user who matches all the criteria of the request and doesn't have it
+/// yet, e.g. to the members of an organization with emails of its domain.
+///
+/// At least one criterion is required, and at most 1000 users are assigned the role at once. A
+/// dry run returns the users without assigning the role, so that they can be reviewed first.
+#[utoipa::path(
+    post,
+    path = "/admin/roles/{id}/assign",
+    operation_id = "bulk_assign_role",
+    tag = "roles",
+    params(("id" = Uuid, Path, description = "ID of the role")),
+    request_body = BulkAssignRoleRequest,
+    responses(
+        (status = OK, description = "The users the role has been, or would be, assigned to", body = BulkRoleAssignmentResponse),
+        (status = NOT_FOUND, description = "The role doesn't exist", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+        (status = UNPROCESSABLE_ENTITY, description = "The request body is invalid, no criterion is set, or too many users match", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+    ),
+    security(("api_key" = ["roles:write"])),
+)]
+async fn bulk_assign_handler(
+    context: UseCaseContext,
+    State(pipeline): State<PolicyPipeline>,
+    Path(id): Path<Uuid>,
+    ValidJson(request): ValidJson<BulkAssignRoleRequest>,
+) -> Result<Json<BulkRoleAssignmentResponse>, ApiError> {
+    let cohort = RoleCohort::new(request.email_domain, request.organization_id)
+        .map_err(ApplicationError::from)?;
+    let bulk_assign_roles = BulkAssignRoles
+        .in_transaction(context.transactions())
+        .retried(pipeline.retrier)
+        .authorize(pipeline.policies, "roles:write")
+        .measured(pipeline.metrics)
+        .logged();
+
+    let assignment = bulk_assign_roles
+        .execute(
+            context.principal(),
+            BulkAssignRolesParams {
+                role_id: id,
+                cohort,
+                dry_run: request.dry_run,
+            },
+        )
+        .await?;
+
+    Ok(Json(BulkRoleAssignmentResponse {
+        applied: assignment.applied,
+        users: assignment.users.into_iter().map(Into::into).collect(),
+    }))
+}
@@ -0,0 +1,31 @@
+use axum::routing::get;
+use utoipa::OpenApi;
+
+use crate::api::{
+    Route, Service,
+    middleware::sdk::{self, adoption},
+    policy::RoutePolicy,
+};
+
+pub struct SdkService;
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(sdk::adoption),
+    tags((name = "sdk", description = "SDKs used by the callers"))
+)]
+struct SdkApi;
+
+impl Service for SdkService {
+    fn routes() -> Vec<Route> {
+        vec![Route::new(
+            "/sdk/adoption",
+            get(adoption),
+            RoutePolicy::admin(),
+        )]
+    }
+
+    fn openapi() -> utoipa::openapi::OpenApi {
+        SdkApi::openapi()
+    }
+}
@@ -0,0 +1,282 @@
+use std::{sync::Arc, time::Duration};
+
+use axum::{
+    Json,
+    extract::{
+        State,
+        ws::{CloseFrame, Message, WebSocket, WebSocketUpgrade, close_code},
+    },
+    response::Response,
+    routing::{get, post},
+};
+use chrono::{DateTime, TimeDelta, Utc};
+use identify_application::{
+    Session, SessionStore, SessionUseCaseDeps, TokenGenerator,
+    VerifySessionParams, verify_session,
+};
+use serde::{Deserialize, Serialize};
+use tokio::{
+    sync::broadcast::{self, error::RecvError},
+    time::{self, MissedTickBehavior},
+};
+use tracing::{debug, warn};
+use utoipa::{OpenApi, ToSchema};
+use uuid::Uuid;
+
+use crate::{
+    api::{
+        Route, Service,
+        error::{ApiError, PROBLEM_CONTENT_TYPE, Problem},
+        policy::RoutePolicy,
+        validation::{ValidJson, Validate, Validator},
+    },
+    session_monitor::{SessionActivity, SessionMonitor},
+};
+
+/// Where the sessions issued to users are kept, and how long they last.
+#[derive(Clone)]
+pub struct SessionSettings {
+    pub store: Arc<dyn SessionStore>,
+    pub valid_for: TimeDelta,
+}
+
+pub struct SessionService;
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(live_handler, verify_handler),
+    tags((name = "sessions", description = "Sessions of users and their live activity"))
+)]
+struct SessionApi;
+
+impl Service for SessionService {
+    fn routes() -> Vec<Route> {
+        vec![
+            Route::new(
+                "/sessions/live",
+                get(live_handler),
+                RoutePolicy::admin(),
+            ),
+            Route::new(
+                "/sessions/verify",
+                post(verify_handler),
+                RoutePolicy::authenticated().permissions(&["users:read"]),
+            ),
+        ]
+    }
+
+    fn openapi() -> utoipa::openapi::OpenApi {
+        SessionApi::openapi()
+    }
+}
+
+/// Something that happened to the session of a user, as it's sent to subscribers.
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[schema(as = SessionActivity)]
+enum SessionActivityMessage {
+    /// The user logged in from a device.
+    Login {
+        user_id: Uuid,
+        device_id: Uuid,
+        /// Whether the user had never logged in from the device before.
+        new_device: bool,
+        occurred_at: DateTime<Utc>,
+    },
+    /// A device of the user was revoked, which signs it out.
+    Logout {
+        user_id: Uuid,
+        device_id: Uuid,
+        occurred_at: DateTime<Utc>,
+    },
+    /// The user was suspended and can't log in anymore.
+    Lockout {
+        user_id: Uuid,
+        occurred_at: DateTime<Utc>,
+    },
+}
+
+impl From<&SessionActivity> for SessionActivityMessage {
+    fn from(value: &SessionActivity) -> Self {
+        match *value {
+            SessionActivity::Login {
+                user_id,
+                device_id,
+                new_device,
+                occurred_at,
+            } => SessionActivityMessage::Login {
+                user_id,
+                device_id,
+                new_device,
+                occurred_at,
+            },
+            SessionActivity::Logout {
+                user_id,
+                device_id,
+                occurred_at,
+            } => SessionActivityMessage::Logout {
+                user_id,
+                device_id,
+                occurred_at,
+            },
+            SessionActivity::Lockout {
+                user_id,
+                occurred_at,
+            } => SessionActivityMessage::Lockout {
+                user_id,
+                occurred_at,
+            },
+        }
+    }
+}
+
+/// A session issued to a user when they logged in from a device.
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(as = Session)]
+pub struct SessionResponse {
+    pub user_id: Uuid,
+    /// ID of the device the user logged in from.
+    pub device_id: Uuid,
+    pub issued_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl From<Session> for SessionResponse {
+    fn from(value: Session) -> Self {
+        SessionResponse {
+            user_id: value.user_id,
+            device_id: value.device_id,
+            issued_at: value.issued_at,
+            expires_at: value.expires_at,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[schema(as = VerifySession)]
+pub struct VerifySessionRequest {
+    // Missing required fields are reported by the validation.
+    #[serde(default)]
+    #[schema(required = true)]
+    pub token: String,
+}
+
+impl Validate for VerifySessionRequest {
+    fn validate(&self, validator: &mut Validator) {
+        validator.field("token", Some(&self.token)).required();
+    }
+}
+
+/// Returns the session of a token issued by a login, e.g. for a backend to tell who makes a
+/// request.
+///
+/// Sessions expire after a while, and are revoked together with the devices they were issued
+/// for.
+#[utoipa::path(
+    post,
+    path = "/sessions/verify",
+    operation_id = "verify_session",
+    tag = "sessions",
+    request_body = VerifySessionRequest,
+    responses(
+        (status = OK, description = "The session of the token", body = SessionResponse),
+        (status = NOT_FOUND, description = "The token is unknown, or its session expired or was revoked", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+        (status = UNPROCESSABLE_ENTITY, description = "The body is invalid", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+    ),
+    security(("api_key" = ["users:read"])),
+)]
+async fn verify_handler(
+    State(settings): State<SessionSettings>,
+    State(tokens): State<Arc<dyn TokenGenerator>>,
+    ValidJson(request): ValidJson<VerifySessionRequest>,
+) -> Result<Json<SessionResponse>, ApiError> {
+    let session = verify_session(
+        SessionUseCaseDeps::new(settings.store.as_ref(), tokens.as_ref()),
+        VerifySessionParams {
+            token: request.token,
+        },
+    )
+    .await?;
+
+    Ok(Json(session.into()))
+}
+
+/// Sends the logins, logouts and lockouts of users over a WebSocket as they happen.
+///
+/// Every activity is sent as a JSON text message, and nothing is expected from the client.
+/// Only the logins and logouts handled by the server the client is connected to are sent,
+/// while lockouts are sent by all the servers. Clients that fall too far behind are
+/// disconnected with the `1013` (try again later) close code, and should reconnect.
+#[utoipa::path(
+    get,
+    path = "/sessions/live",
+    operation_id = "monitor_sessions",
+    tag = "sessions",
+    responses(
+        (status = SWITCHING_PROTOCOLS, description = "The connection was upgraded to a WebSocket of session activity", body = SessionActivityMessage),
+    ),
+    security(("api_key" = [])),
+)]
+async fn live_handler(
+    State(monitor): State<SessionMonitor>,
+    upgrade: WebSocketUpgrade,
+) -> Response {
+    // Subscribing before the upgrade, the client receives whatever happens once it's connected.
+    let activity = monitor.subscribe();
+    let keep_alive = monitor.keep_alive();
+    upgrade.on_upgrade(move |socket| forward(activity, keep_alive, socket))
+}
+
+/// Sends the activity to the client until either of them closes the connection.
+async fn forward(
+    mut activity: broadcast::Receiver<Arc<SessionActivity>>,
+    keep_alive: Duration,
+    mut socket: WebSocket,
+) {
+    let mut keep_alive = time::interval(keep_alive);
+    keep_alive.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    // The first tick is immediate.
+    keep_alive.tick().await;
+
+    let close = loop {
+        let message = tokio::select! {
+            received = activity.recv() => match received {
+                Ok(activity) => {
+                    let message = SessionActivityMessage::from(activity.as_ref());
+                    Message::text(serde_json::to_string(&message).unwrap_or_default())
+                }
+                Err(RecvError::Lagged(missed)) => {
+                    warn!(missed, "Disconnecting a session monitor that fell behind");
+                    break Some(CloseFrame {
+                        code: close_code::AGAIN,
+                        reason: "fell behind".into(),
+                    });
+                }
+                Err(RecvError::Closed) => {
+                    break Some(CloseFrame {
+                        code: close_code::AWAY,
+                        reason: "shutting down".into(),
+                    });
+                }
+            },
+            _ = keep_alive.tick() => Message::Ping(Default::default()),
+            received = socket.recv() => match received {
+                Some(Ok(Message::Close(_))) | None => break None,
+                Some(Ok(_)) => continue,
+                Some(Err(e)) => {
+                    debug!(error = %e, "Session monitor disconnected");
+                    break None;
+                }
+            },
+        };
+
+        if let Err(e) = socket.send(message).await {
+            debug!(error = %e, "Session monitor disconnected");
+            return;
+        }
+    };
+
+    if let Some(frame) = close {
+        let _ = socket.send(Message::Close(Some(frame))).await;
+    }
+}
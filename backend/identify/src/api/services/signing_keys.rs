@@ -0,0 +1,229 @@
+use axum::{
+    Json,
+    extract::State,
+    http::{HeaderName, HeaderValue, StatusCode, header::CACHE_CONTROL},
+    routing::{get, post},
+};
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use chrono::{DateTime, Utc};
+use identify_application::{
+    ApplicationError, ListPublishedSigningKeysParams, RotateSigningKeyParams,
+    SigningKeyRotationDeps, SigningKeyUseCaseDeps, list_published_signing_keys,
+    rotate_signing_key,
+};
+use identify_domain::SigningKey;
+use identify_infrastructure::{
+    encryption::FieldEncryption,
+    signing::{ED25519_ALGORITHM, Ed25519KeyGenerator},
+    storage::{
+        self, connection::ReadPool, retry::Retrier,
+        signing_keys::SigningKeysRepository,
+    },
+};
+use serde::Serialize;
+use sqlx::SqlitePool;
+use utoipa::{OpenApi, ToSchema};
+use uuid::Uuid;
+
+use crate::api::{Route, Service, error::ApiError, policy::RoutePolicy};
+
+/// How long clients may cache the JWKS. Clients are expected to fetch it again when a token names
+/// a key they don't know, e.g. right after a rotation.
+const JWKS_MAX_AGE_SECS: u32 = 5 * 60;
+
+/// Publishes the keys tokens are verified with, at the root of the service.
+pub struct JwksService;
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(jwks_handler),
+    tags((name = "keys", description = "Keys the tokens issued by the service are signed with"))
+)]
+struct JwksApi;
+
+impl Service for JwksService {
+    fn routes() -> Vec<Route> {
+        vec![Route::new(
+            "/.well-known/jwks.json",
+            get(jwks_handler),
+            RoutePolicy::public(),
+        )]
+    }
+
+    fn openapi() -> utoipa::openapi::OpenApi {
+        JwksApi::openapi()
+    }
+}
+
+pub struct SigningKeyService;
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(rotate_handler),
+    tags((name = "keys", description = "Keys the tokens issued by the service are signed with"))
+)]
+struct SigningKeyApi;
+
+impl Service for SigningKeyService {
+    fn routes() -> Vec<Route> {
+        vec![Route::new(
+            "/signing-keys/rotate",
+            post(rotate_handler),
+            RoutePolicy::admin(),
+        )]
+    }
+
+    fn openapi() -> utoipa::openapi::OpenApi {
+        SigningKeyApi::openapi()
+    }
+}
+
+/// A public key in the JSON Web Key format (RFC 7517).
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(as = Jwk)]
+pub struct JwkResponse {
+    /// Key type, `OKP` for Ed25519 keys.
+    #[schema(example = "OKP")]
+    pub kty: &'static str,
+    /// Curve of the key.
+    #[schema(example = "Ed25519")]
+    pub crv: &'static str,
+    /// Public key in base64url.
+    pub x: String,
+    /// ID of the key, which tokens name in their `kid` header.
+    pub kid: Uuid,
+    #[schema(example = "EdDSA")]
+    pub alg: String,
+    #[serde(rename = "use")]
+    #[schema(example = "sig")]
+    pub use_: &'static str,
+}
+
+impl JwkResponse {
+    /// Returns the JWK of the key, unless its algorithm can't be described by one.
+    fn from_key(key: &SigningKey) -> Option<Self> {
+        if key.algorithm() != ED25519_ALGORITHM {
+            return None;
+        }
+
+        Some(JwkResponse {
+            kty: "OKP",
+            crv: "Ed25519",
+            x: URL_SAFE_NO_PAD.encode(key.public_key()),
+            kid: key.id(),
+            alg: key.algorithm().to_owned(),
+            use_: "sig",
+        })
+    }
+}
+
+/// A JSON Web Key Set (RFC 7517).
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(as = Jwks)]
+pub struct JwksResponse {
+    pub keys: Vec<JwkResponse>,
+}
+
+/// The private key is never returned.
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(as = SigningKey)]
+pub struct SigningKeyResponse {
+    pub id: Uuid,
+    #[schema(example = "EdDSA")]
+    pub algorithm: String,
+    #[schema(example = "active")]
+    pub status: &'static str,
+    pub created_at: DateTime<Utc>,
+    pub retired_at: Option<DateTime<Utc>>,
+}
+
+impl From<SigningKey> for SigningKeyResponse {
+    fn from(value: SigningKey) -> Self {
+        SigningKeyResponse {
+            id: value.id(),
+            algorithm: value.algorithm().to_owned(),
+            status: value.status().as_str(),
+            created_at: *value.created_at(),
+            retired_at: *value.retired_at(),
+        }
+    }
+}
+
+/// Returns the public keys tokens are verified with: the active one, and the retired ones
+/// tokens may still have been signed with.
+#[utoipa::path(
+    get,
+    path = "/.well-known/jwks.json",
+    operation_id = "get_jwks",
+    tag = "keys",
+    responses(
+        (status = OK, description = "The published keys, the most recent first", body = JwksResponse,
+            headers(("Cache-Control" = String, description = "How long the keys may be cached"))),
+    ),
+)]
+async fn jwks_handler(
+    State(pool): State<ReadPool>,
+    State(encryption): State<FieldEncryption>,
+) -> Result<([(HeaderName, HeaderValue); 1], Json<JwksResponse>), ApiError> {
+    let mut conn = storage::acquire(&pool).await?;
+    let mut repository = SigningKeysRepository::new(&mut conn, &encryption);
+
+    let keys = list_published_signing_keys(
+        SigningKeyUseCaseDeps::new(&mut repository),
+        ListPublishedSigningKeysParams,
+    )
+    .await?;
+
+    let cache_control =
+        HeaderValue::from_str(&format!("public, max-age={JWKS_MAX_AGE_SECS}"))
+            .expect("the cache control is a valid header value");
+
+    Ok((
+        [(CACHE_CONTROL, cache_control)],
+        Json(JwksResponse {
+            keys: keys.iter().filter_map(JwkResponse::from_key).collect(),
+        }),
+    ))
+}
+
+/// Replaces the active signing key with a new one right away, e.g. when it may have leaked.
+///
+/// The previous key is retired, but stays published until the scheduled rotation deletes it,
+/// so that the tokens it signed can still be verified.
+#[utoipa::path(
+    post,
+    path = "/signing-keys/rotate",
+    operation_id = "rotate_signing_key",
+    tag = "keys",
+    responses(
+        (status = CREATED, description = "The new active key", body = SigningKeyResponse),
+    ),
+    security(("api_key" = [])),
+)]
+async fn rotate_handler(
+    State(pool): State<SqlitePool>,
+    State(encryption): State<FieldEncryption>,
+    State(retrier): State<Retrier>,
+) -> Result<(StatusCode, Json<SigningKeyResponse>), ApiError> {
+    let key = retrier
+        .run("rotate_signing_key", || async {
+            let mut tx = storage::begin(&pool).await?;
+            let mut repository =
+                SigningKeysRepository::new(&mut tx, &encryption);
+
+            let key = rotate_signing_key(
+                SigningKeyRotationDeps::new(
+                    &mut repository,
+                    &Ed25519KeyGenerator::new(),
+                ),
+                RotateSigningKeyParams { min_age: None },
+            )
+            .await?;
+            storage::commit(tx).await?;
+
+            Ok::<_, ApplicationError>(key)
+        })
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(SigningKeyResponse::from(key))))
+}
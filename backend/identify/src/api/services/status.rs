@@ -0,0 +1,210 @@
+use std::time::Duration;
+
+use axum::{
+    Json,
+    extract::State,
+    http::StatusCode,
+    routing::{delete, get, put},
+};
+use chrono::{DateTime, Utc};
+use identify_infrastructure::storage::status::{self, Incident};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use tracing::warn;
+use utoipa::{OpenApi, ToSchema};
+
+use crate::{
+    api::{
+        Route, Service,
+        error::{ApiError, PROBLEM_CONTENT_TYPE, Problem},
+        policy::RoutePolicy,
+        validation::{ValidJson, Validate, Validator},
+    },
+    status::{Component, Health, StatusBoard},
+};
+
+/// The database is reported as failing if it doesn't answer within this time.
+const STATUS_TIMEOUT: Duration = Duration::from_secs(5);
+const MAX_MESSAGE_LENGTH: usize = 500;
+
+pub struct StatusService;
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(status_handler, put_incident_handler, delete_incident_handler),
+    tags((name = "status", description = "Status of the service for status pages"))
+)]
+struct StatusApi;
+
+impl Service for StatusService {
+    fn routes() -> Vec<Route> {
+        vec![
+            Route::new(
+                "/status",
+                get(status_handler),
+                RoutePolicy::public().timeout(STATUS_TIMEOUT),
+            ),
+            Route::new(
+                "/status/incident",
+                put(put_incident_handler),
+                RoutePolicy::admin(),
+            ),
+            Route::new(
+                "/status/incident",
+                delete(delete_incident_handler),
+                RoutePolicy::admin(),
+            ),
+        ]
+    }
+
+    fn openapi() -> utoipa::openapi::OpenApi {
+        StatusApi::openapi()
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(as = Status)]
+pub struct StatusResponse {
+    /// `degraded` if any component is failing, `operational` otherwise.
+    #[schema(example = "operational")]
+    pub status: &'static str,
+    pub incident: Option<IncidentResponse>,
+    pub components: Vec<ComponentResponse>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(as = Component)]
+pub struct ComponentResponse {
+    #[schema(example = "database")]
+    pub name: &'static str,
+    #[schema(example = "operational")]
+    pub status: &'static str,
+    /// When the component last did its work successfully since the start of the service.
+    pub last_success_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(as = Incident)]
+pub struct IncidentResponse {
+    pub message: String,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<Incident> for IncidentResponse {
+    fn from(value: Incident) -> Self {
+        IncidentResponse {
+            message: value.message,
+            updated_at: value.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct PutIncidentRequest {
+    // Missing required fields are reported by the validation.
+    #[serde(default)]
+    #[schema(required = true, min_length = 1, max_length = 500)]
+    pub message: String,
+}
+
+impl Validate for PutIncidentRequest {
+    fn validate(&self, validator: &mut Validator) {
+        validator
+            .field("message", Some(&self.message))
+            .required()
+            .max_length(MAX_MESSAGE_LENGTH);
+    }
+}
+
+/// Returns the health of the components of the service and the current incident, if any.
+///
+/// The database is checked on every request, the other components report the outcomes of
+/// their work as they go.
+#[utoipa::path(
+    get,
+    path = "/status",
+    operation_id = "get_status",
+    tag = "status",
+    responses(
+        (status = OK, description = "Status of the service", body = StatusResponse),
+    ),
+)]
+async fn status_handler(
+    State(pool): State<SqlitePool>,
+    State(board): State<StatusBoard>,
+) -> Json<StatusResponse> {
+    let ping = status::ping(&pool).await;
+    if let Err(e) = board.record(Component::Database, ping) {
+        warn!(error = %e, "Database is unavailable");
+    }
+
+    // The incident is stored in the database, so it can't be shown while the database is down.
+    let incident = status::get_incident(&pool).await.ok().flatten();
+
+    let components: Vec<_> = Component::ALL
+        .into_iter()
+        .map(|component| {
+            let status = board.get(component);
+            ComponentResponse {
+                name: component.as_str(),
+                status: status.health().as_str(),
+                last_success_at: status.last_success,
+            }
+        })
+        .collect();
+
+    let failing = components
+        .iter()
+        .any(|component| component.status == Health::Failing.as_str());
+
+    Json(StatusResponse {
+        status: if failing { "degraded" } else { "operational" },
+        incident: incident.map(IncidentResponse::from),
+        components,
+    })
+}
+
+/// Sets the incident banner shown on the status page, replacing the current one.
+#[utoipa::path(
+    put,
+    path = "/status/incident",
+    operation_id = "put_status_incident",
+    tag = "status",
+    request_body = PutIncidentRequest,
+    responses(
+        (status = OK, description = "The incident", body = IncidentResponse),
+        (status = UNPROCESSABLE_ENTITY, description = "The request body is invalid", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+    ),
+    security(("api_key" = [])),
+)]
+async fn put_incident_handler(
+    State(pool): State<SqlitePool>,
+    ValidJson(request): ValidJson<PutIncidentRequest>,
+) -> Result<Json<IncidentResponse>, ApiError> {
+    let incident = Incident {
+        message: request.message.trim().to_owned(),
+        updated_at: Utc::now(),
+    };
+    status::set_incident(&pool, &incident).await?;
+
+    Ok(Json(IncidentResponse::from(incident)))
+}
+
+/// Removes the incident banner from the status page.
+#[utoipa::path(
+    delete,
+    path = "/status/incident",
+    operation_id = "delete_status_incident",
+    tag = "status",
+    responses(
+        (status = NO_CONTENT, description = "There is no incident anymore"),
+    ),
+    security(("api_key" = [])),
+)]
+async fn delete_incident_handler(
+    State(pool): State<SqlitePool>,
+) -> Result<StatusCode, ApiError> {
+    status::clear_incident(&pool).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,238 @@
+use std::collections::{BTreeMap, HashMap};
+
+use axum::{
+    Json,
+    extract::Path,
+    http::StatusCode,
+    routing::{delete, get, put},
+};
+use identify_application::{
+    DeleteUserAttributeParams, GetUserAttributesParams,
+    PutUserAttributesParams, delete_user_attribute, get_user_attributes,
+    put_user_attributes,
+};
+use identify_domain::{AttributeValue, UserAttribute};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::{OpenApi, ToSchema};
+use uuid::Uuid;
+
+use crate::api::{
+    Route, Service,
+    error::{ApiError, PROBLEM_CONTENT_TYPE, Problem},
+    policy::RoutePolicy,
+    use_case::{ReadContext, UseCaseContext},
+};
+
+pub struct UserAttributeService;
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(get_handler, put_handler, delete_handler),
+    tags((name = "users", description = "Users of the service"))
+)]
+struct UserAttributeApi;
+
+impl Service for UserAttributeService {
+    fn routes() -> Vec<Route> {
+        vec![
+            Route::new(
+                "/users/{id}/attributes",
+                get(get_handler),
+                RoutePolicy::authenticated().permissions(&["users:read"]),
+            ),
+            Route::new(
+                "/users/{id}/attributes",
+                put(put_handler),
+                RoutePolicy::authenticated().permissions(&["users:write"]),
+            ),
+            Route::new(
+                "/users/{id}/attributes/{key}",
+                delete(delete_handler),
+                RoutePolicy::authenticated().permissions(&["users:write"]),
+            ),
+        ]
+    }
+
+    fn openapi() -> utoipa::openapi::OpenApi {
+        UserAttributeApi::openapi()
+    }
+}
+
+/// Custom attributes of a user by their keys. Values are strings, numbers, booleans, or any
+/// other JSON document such as objects and arrays.
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(
+    as = UserAttributes,
+    example = json!({"locale": "en-GB", "newsletter": true, "seats": 5})
+)]
+pub struct UserAttributesResponse(BTreeMap<String, Value>);
+
+impl From<Vec<UserAttribute>> for UserAttributesResponse {
+    fn from(value: Vec<UserAttribute>) -> Self {
+        UserAttributesResponse(
+            value
+                .into_iter()
+                .map(|attribute| {
+                    let value = match attribute.value() {
+                        AttributeValue::String(value) => {
+                            Value::from(value.as_str())
+                        }
+                        AttributeValue::Number(value) => Value::from(*value),
+                        AttributeValue::Bool(value) => Value::from(*value),
+                        AttributeValue::Json(value) => value.clone(),
+                    };
+
+                    (attribute.key().to_owned(), value)
+                })
+                .collect(),
+        )
+    }
+}
+
+/// All the attributes of the user, which replace the current ones. Attributes missing from the
+/// body are deleted.
+#[derive(Debug, Deserialize, ToSchema)]
+#[schema(
+    as = PutUserAttributes,
+    example = json!({"locale": "en-GB", "newsletter": true, "seats": 5})
+)]
+pub struct PutUserAttributesRequest(HashMap<String, Value>);
+
+impl PutUserAttributesRequest {
+    /// Infers the types of the values from their JSON types. `null` has no type, attributes are
+    /// deleted by leaving them out instead.
+    fn into_attributes(
+        self,
+    ) -> Result<HashMap<String, AttributeValue>, ApiError> {
+        self.0
+            .into_iter()
+            .map(|(key, value)| {
+                let value = match value {
+                    Value::Null => {
+                        return Err(ApiError::new(
+                            StatusCode::UNPROCESSABLE_ENTITY,
+                            format!(
+                                "The attribute '{key}' is null, leave it out to delete it"
+                            ),
+                        ));
+                    }
+                    Value::String(value) => AttributeValue::String(value),
+                    Value::Number(value) => AttributeValue::Number(
+                        value.as_f64().unwrap_or(f64::NAN),
+                    ),
+                    Value::Bool(value) => AttributeValue::Bool(value),
+                    value @ (Value::Array(_) | Value::Object(_)) => {
+                        AttributeValue::Json(value)
+                    }
+                };
+
+                Ok((key, value))
+            })
+            .collect()
+    }
+}
+
+/// Returns the custom attributes of a user.
+#[utoipa::path(
+    get,
+    path = "/users/{id}/attributes",
+    operation_id = "get_user_attributes",
+    tag = "users",
+    params(("id" = Uuid, Path, description = "ID of the user")),
+    responses(
+        (status = OK, description = "The attributes of the user", body = UserAttributesResponse),
+        (status = NOT_FOUND, description = "The user doesn't exist", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+    ),
+    security(("api_key" = ["users:read"])),
+)]
+async fn get_handler(
+    ReadContext(mut context): ReadContext,
+    Path(id): Path<Uuid>,
+) -> Result<Json<UserAttributesResponse>, ApiError> {
+    let attributes = get_user_attributes(
+        context.user_attributes().await?,
+        GetUserAttributesParams { user_id: id },
+    )
+    .await?;
+
+    Ok(Json(attributes.into()))
+}
+
+/// Replaces the custom attributes of a user.
+#[utoipa::path(
+    put,
+    path = "/users/{id}/attributes",
+    operation_id = "put_user_attributes",
+    tag = "users",
+    params(("id" = Uuid, Path, description = "ID of the user")),
+    request_body = PutUserAttributesRequest,
+    responses(
+        (status = OK, description = "The new attributes of the user", body = UserAttributesResponse),
+        (status = NOT_FOUND, description = "The user doesn't exist", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+        (status = UNPROCESSABLE_ENTITY, description = "A key or a value is invalid, or there are too many attributes", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+    ),
+    security(("api_key" = ["users:write"])),
+)]
+async fn put_handler(
+    context: UseCaseContext,
+    Path(id): Path<Uuid>,
+    Json(request): Json<PutUserAttributesRequest>,
+) -> Result<Json<UserAttributesResponse>, ApiError> {
+    let attributes = request.into_attributes()?;
+    let attributes = context
+        .retried("put_user_attributes", |mut context| async {
+            let attributes = put_user_attributes(
+                context.user_attributes().await?,
+                PutUserAttributesParams {
+                    user_id: id,
+                    attributes: attributes.clone(),
+                },
+            )
+            .await?;
+            context.commit().await?;
+
+            Ok(attributes)
+        })
+        .await?;
+
+    Ok(Json(attributes.into()))
+}
+
+/// Deletes a custom attribute of a user.
+#[utoipa::path(
+    delete,
+    path = "/users/{id}/attributes/{key}",
+    operation_id = "delete_user_attribute",
+    tag = "users",
+    params(
+        ("id" = Uuid, Path, description = "ID of the user"),
+        ("key" = String, Path, description = "Key of the attribute"),
+    ),
+    responses(
+        (status = NO_CONTENT, description = "The attribute was deleted"),
+        (status = NOT_FOUND, description = "The user or the attribute doesn't exist", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+    ),
+    security(("api_key" = ["users:write"])),
+)]
+async fn delete_handler(
+    context: UseCaseContext,
+    Path((id, key)): Path<(Uuid, String)>,
+) -> Result<StatusCode, ApiError> {
+    context
+        .retried("delete_user_attribute", |mut context| async {
+            delete_user_attribute(
+                context.user_attributes().await?,
+                DeleteUserAttributeParams {
+                    user_id: id,
+                    key: key.clone(),
+                },
+            )
+            .await?;
+
+            context.commit().await
+        })
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,844 @@
+use std::sync::Arc;
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, patch, post},
+};
+use chrono::{DateTime, Utc};
+use futures_util::StreamExt as _;
+use identify_application::{
+    BlobStorage, CreateUser, CreateUserParams, EraseUser, EraseUserParams,
+    ExportUsersParams, GetUserByUsernameParams, GetUserParams, ListUsersParams,
+    MergeUsers, MergeUsersParams, SearchUsersParams,
+    TransactionalUseCaseExt as _, UnitOfWork as _, UnitOfWorkFactory as _,
+    UpdateUserParams, UseCase as _, UseCaseExt as _, UserUseCaseDeps,
+    export_users, get_user, get_user_by_username, list_users, search_users,
+    update_user,
+    user_contracts::{UserFilter, UserSearchQuery, UserSortField},
+};
+use identify_domain::{
+    Locale, NewUserAttrs, QuotaPolicy, User, Username, UsernameStrategy,
+};
+use identify_infrastructure::storage::users::cache::{
+    CachedUsersRepository, UserCache,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::{OpenApi, ToSchema};
+use uuid::Uuid;
+
+use crate::{
+    api::{
+        Route, Service,
+        conditional::{EntityTag, IfMatch, IfNoneMatch},
+        error::{ApiError, PROBLEM_CONTENT_TYPE, Problem},
+        export::{self, Accept},
+        listing::{Filter, ListParams, ListResponse, SearchParams, SortField},
+        patch::PatchRequest,
+        policy::RoutePolicy,
+        services::sessions::SessionSettings,
+        use_case::{PolicyPipeline, ReadContext, UseCaseContext},
+        validation::{ValidJson, Validate, Validator},
+    },
+    identifiers::IdentifierLists,
+};
+
+pub struct UserService;
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        list_handler,
+        search_handler,
+        create_handler,
+        get_handler,
+        get_by_username_handler,
+        update_handler,
+        erase_handler,
+        merge_handler
+    ),
+    tags((name = "users", description = "Users of the service"))
+)]
+struct UserApi;
+
+impl Service for UserService {
+    fn routes() -> Vec<Route> {
+        vec![
+            Route::new(
+                "/users",
+                get(list_handler),
+                RoutePolicy::authenticated().permissions(&["users:read"]),
+            ),
+            // Authorized by the policies of the use case, see `create_handler`.
+            Route::new(
+                "/users",
+                post(create_handler),
+                RoutePolicy::authenticated(),
+            ),
+            Route::new(
+                "/users/search",
+                get(search_handler),
+                RoutePolicy::authenticated().permissions(&["users:read"]),
+            ),
+            Route::new(
+                "/users/by-username/{username}",
+                get(get_by_username_handler),
+                RoutePolicy::authenticated().permissions(&["users:read"]),
+            ),
+            Route::new(
+                "/users/{id}",
+                get(get_handler),
+                RoutePolicy::authenticated().permissions(&["users:read"]),
+            ),
+            Route::new(
+                "/users/{id}",
+                patch(update_handler),
+                RoutePolicy::authenticated().permissions(&["users:write"]),
+            ),
+            // Authorized by the policies of the use case, see `erase_handler`.
+            Route::new(
+                "/users/{id}/erasure",
+                post(erase_handler),
+                RoutePolicy::authenticated(),
+            ),
+            // Authorized by the policies of the use case, see `merge_handler`.
+            Route::new(
+                "/admin/users/{id}/merge",
+                post(merge_handler),
+                RoutePolicy::authenticated(),
+            ),
+        ]
+    }
+
+    fn openapi() -> utoipa::openapi::OpenApi {
+        UserApi::openapi()
+    }
+}
+
+impl SortField for UserSortField {
+    const NAMES: &'static [&'static str] = &[
+        "email",
+        "first_name",
+        "last_name",
+        "username",
+        "created_at",
+        "updated_at",
+    ];
+
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "email" => Some(UserSortField::Email),
+            "first_name" => Some(UserSortField::FirstName),
+            "last_name" => Some(UserSortField::LastName),
+            "username" => Some(UserSortField::Username),
+            "created_at" => Some(UserSortField::CreatedAt),
+            "updated_at" => Some(UserSortField::UpdatedAt),
+            _ => None,
+        }
+    }
+}
+
+impl Filter for UserFilter {
+    const FIELDS: &'static [&'static str] = &[
+        "email",
+        "email_domain",
+        "created_after",
+        "created_before",
+        "inactive_since",
+    ];
+
+    fn set(&mut self, field: &str, value: String) -> Result<(), String> {
+        match field {
+            "email" => self.email = Some(value),
+            "email_domain" => {
+                self.email_domain = Some(parse_email_domain(&value)?)
+            }
+            "created_after" => {
+                self.created_after = Some(parse_timestamp(&value)?)
+            }
+            "created_before" => {
+                self.created_before = Some(parse_timestamp(&value)?)
+            }
+            "inactive_since" => {
+                self.inactive_since = Some(parse_timestamp(&value)?)
+            }
+            _ => return Err(format!("unsupported filter '{field}'")),
+        }
+
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if let (Some(after), Some(before)) =
+            (self.created_after, self.created_before)
+            && after >= before
+        {
+            return Err(
+                "filter[created_after] must be earlier than filter[created_before]"
+                    .to_owned(),
+            );
+        }
+        if let (Some(email), Some(domain)) = (&self.email, &self.email_domain)
+            && !email.to_lowercase().ends_with(&format!("@{domain}"))
+        {
+            return Err("filter[email] doesn't belong to filter[email_domain]"
+                .to_owned());
+        }
+
+        Ok(())
+    }
+}
+
+/// Normalizes an email domain, which may start with `@`, e.g. `@ACME.test` to `acme.test`.
+///
+/// Only letters, digits, dots and hyphens are allowed, so the domain can't contain wildcards.
+fn parse_email_domain(value: &str) -> Result<String, String> {
+    let domain = value.trim().trim_start_matches('@').to_lowercase();
+    let valid = !domain.is_empty()
+        && domain.bytes().all(|b| {
+            b.is_ascii_lowercase()
+                || b.is_ascii_digit()
+                || b == b'.'
+                || b == b'-'
+        });
+
+    if valid {
+        Ok(domain)
+    } else {
+        Err(format!("'{value}' isn't a valid email domain"))
+    }
+}
+
+fn parse_timestamp(value: &str) -> Result<DateTime<Utc>, String> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|timestamp| timestamp.to_utc())
+        .map_err(|_| {
+            format!(
+                "'{value}' isn't an RFC 3339 timestamp, e.g. 2026-01-31T12:00:00Z"
+            )
+        })
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(as = User)]
+pub struct UserResponse {
+    pub id: Uuid,
+    pub email: String,
+    pub username: String,
+    pub first_name: String,
+    pub last_name: Option<String>,
+    /// BCP 47 language tag emails to the user are translated to, e.g. `pt-BR`.
+    pub locale: Option<String>,
+    /// IANA time zone times in emails to the user are shown in, e.g. `Europe/Berlin`.
+    pub timezone: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    /// When the user last logged in, if they ever did. Logins are counted in batches, so this
+    /// may lag behind the latest ones by a few seconds.
+    pub last_login_at: Option<DateTime<Utc>>,
+    /// How many times the user has logged in.
+    pub login_count: u32,
+}
+
+/// Columns of the CSV exports of users.
+const USER_COLUMNS: &[&str] = &[
+    "id",
+    "email",
+    "username",
+    "first_name",
+    "last_name",
+    "locale",
+    "timezone",
+    "created_at",
+    "updated_at",
+    "last_login_at",
+    "login_count",
+];
+
+/// Returns the entity tag of the current version of the user.
+fn user_tag(user: &User) -> EntityTag {
+    EntityTag::new(user.version(), *user.updated_at())
+}
+
+impl From<User> for UserResponse {
+    fn from(value: User) -> Self {
+        let attrs = value.into_attributes();
+
+        UserResponse {
+            id: attrs.id,
+            email: attrs.email,
+            username: attrs.username,
+            first_name: attrs.first_name,
+            last_name: attrs.last_name,
+            locale: attrs.locale,
+            timezone: attrs.timezone,
+            created_at: attrs.created_at,
+            updated_at: attrs.updated_at,
+            last_login_at: attrs.last_login_at,
+            login_count: attrs.login_count,
+        }
+    }
+}
+
+/// Longest email allowed by RFC 5321.
+pub(crate) const MAX_EMAIL_LENGTH: usize = 254;
+pub(crate) const MAX_NAME_LENGTH: usize = 100;
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateUserRequest {
+    // Missing required fields are reported by the validation.
+    #[serde(default)]
+    #[schema(required = true, format = Email, max_length = 254)]
+    pub email: String,
+    /// Generated from the email or the name if it's not set, depending on the configuration.
+    #[schema(min_length = 3, max_length = 32)]
+    pub username: Option<String>,
+    #[serde(default)]
+    #[schema(required = true, min_length = 1, max_length = 100)]
+    pub first_name: String,
+    #[schema(max_length = 100)]
+    pub last_name: Option<String>,
+}
+
+impl Validate for CreateUserRequest {
+    fn validate(&self, validator: &mut Validator) {
+        validator
+            .field("email", Some(&self.email))
+            .required()
+            .max_length(MAX_EMAIL_LENGTH)
+            .email();
+        validator
+            .field("username", self.username.as_deref())
+            .min_length(Username::MIN_LENGTH)
+            .max_length(Username::MAX_LENGTH)
+            .username();
+        validator
+            .field("first_name", Some(&self.first_name))
+            .required()
+            .max_length(MAX_NAME_LENGTH);
+        validator
+            .field("last_name", self.last_name.as_deref())
+            .max_length(MAX_NAME_LENGTH);
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct UpdateUserRequest {
+    #[serde(default)]
+    #[schema(required = true, min_length = 1, max_length = 100)]
+    pub first_name: String,
+    #[schema(max_length = 100)]
+    pub last_name: Option<String>,
+    /// BCP 47 language tag emails to the user are translated to, e.g. `pt-BR`.
+    #[schema(max_length = 35)]
+    pub locale: Option<String>,
+    /// IANA time zone times in emails to the user are shown in, e.g. `Europe/Berlin`.
+    pub timezone: Option<String>,
+}
+
+impl Validate for UpdateUserRequest {
+    fn validate(&self, validator: &mut Validator) {
+        validator
+            .field("first_name", Some(&self.first_name))
+            .required()
+            .max_length(MAX_NAME_LENGTH);
+        validator
+            .field("last_name", self.last_name.as_deref())
+            .max_length(MAX_NAME_LENGTH);
+        validator
+            .field("locale", self.locale.as_deref())
+            .max_length(Locale::MAX_LENGTH)
+            .locale();
+        validator
+            .field("timezone", self.timezone.as_deref())
+            .timezone();
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct EraseUserRequest {
+    /// Current email of the user, to confirm the erasure.
+    #[serde(default)]
+    #[schema(required = true, max_length = 254)]
+    pub confirmation: String,
+}
+
+impl Validate for EraseUserRequest {
+    fn validate(&self, validator: &mut Validator) {
+        validator
+            .field("confirmation", Some(&self.confirmation))
+            .required()
+            .max_length(MAX_EMAIL_LENGTH);
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct MergeUsersRequest {
+    /// ID of the user who duplicates the one in the path, and is merged into them.
+    pub duplicate_id: Uuid,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(as = MergedUsers)]
+pub struct MergedUsersResponse {
+    /// The user who was kept.
+    pub primary: UserResponse,
+    /// The duplicate, which was erased.
+    pub duplicate: UserResponse,
+    /// How many memberships were moved to the primary user.
+    pub memberships: u64,
+    /// How many devices were moved to the primary user.
+    pub devices: u64,
+    /// How many attributes were moved to the primary user.
+    pub attributes: u64,
+    /// How many consents were moved to the primary user.
+    pub consents: u64,
+    /// How many roles were moved to the primary user.
+    pub roles: u64,
+    /// How many sessions were moved to the primary user.
+    pub sessions: u64,
+}
+
+/// Lists users.
+///
+/// Users are listed from a read model, so changes of users may take a moment to show up.
+///
+/// Clients that accept `text/csv` or `application/x-ndjson` get all the users matching the
+/// filter in the sort order instead of a page of them, streamed as they're read.
+#[utoipa::path(
+    get,
+    path = "/users",
+    operation_id = "list_users",
+    tag = "users",
+    params(ListParams<UserFilter, UserSortField>),
+    responses(
+        (status = OK, description = "A page of users, or all of them as an export", content(
+            (ListResponse<UserResponse> = "application/json"),
+            (String = "text/csv"),
+            (UserResponse = "application/x-ndjson"),
+        )),
+        (status = BAD_REQUEST, description = "Invalid query parameters", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+    ),
+    security(("api_key" = ["users:read"])),
+)]
+async fn list_handler(
+    context: ReadContext,
+    Accept(format): Accept,
+    ListParams(query): ListParams<UserFilter, UserSortField>,
+) -> Result<Response, ApiError> {
+    let mut reader = context.user_summaries();
+
+    if let Some(format) = format {
+        let users = export_users(
+            UserUseCaseDeps::new(&mut reader),
+            ExportUsersParams {
+                filter: query.filter,
+                sort: query.sort,
+            },
+        );
+
+        return Ok(export::response(
+            format,
+            "users",
+            USER_COLUMNS,
+            users.map(|user| user.map(UserResponse::from)),
+        ));
+    }
+
+    let users = list_users(
+        UserUseCaseDeps::new(&mut reader),
+        ListUsersParams { query },
+    )
+    .await?;
+
+    Ok(Json(ListResponse::<UserResponse>::from(users)).into_response())
+}
+
+/// Searches users by their emails and names.
+///
+/// All words of the query must occur in the email or the names of a user, matched by their
+/// prefixes regardless of the case and diacritics. The most relevant users come first. Like
+/// listings, searches may not reflect the latest changes of users yet.
+#[utoipa::path(
+    get,
+    path = "/users/search",
+    operation_id = "search_users",
+    tag = "users",
+    params(SearchParams),
+    responses(
+        (status = OK, description = "A page of matching users", body = ListResponse<UserResponse>),
+        (status = BAD_REQUEST, description = "Invalid query parameters", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+    ),
+    security(("api_key" = ["users:read"])),
+)]
+async fn search_handler(
+    context: ReadContext,
+    SearchParams { text, page }: SearchParams,
+) -> Result<Json<ListResponse<UserResponse>>, ApiError> {
+    let mut reader = context.user_summaries();
+
+    let users = search_users(
+        UserUseCaseDeps::new(&mut reader),
+        SearchUsersParams {
+            query: UserSearchQuery { text, page },
+        },
+    )
+    .await?;
+
+    Ok(Json(users.into()))
+}
+
+/// Returns a user.
+///
+/// The response has the ETag of the user, so that clients can revalidate their copy with
+/// `If-None-Match`. Unlike listings, the user is read from the primary database, so that the
+/// ETag can be used right away to update them.
+#[utoipa::path(
+    get,
+    path = "/users/{id}",
+    operation_id = "get_user",
+    tag = "users",
+    params(
+        ("id" = Uuid, Path, description = "ID of the user"),
+        ("If-None-Match" = Option<String>, Header, description = "ETags of the copies of the user the client has"),
+    ),
+    responses(
+        (status = OK, description = "The user", body = UserResponse,
+            headers(("ETag" = String, description = "ETag of the user"))),
+        (status = NOT_MODIFIED, description = "The user matches one of the ETags of `If-None-Match`",
+            headers(("ETag" = String, description = "ETag of the user"))),
+        (status = NOT_FOUND, description = "The user doesn't exist", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+    ),
+    security(("api_key" = ["users:read"])),
+)]
+async fn get_handler(
+    mut context: UseCaseContext,
+    State(cache): State<UserCache>,
+    Path(id): Path<Uuid>,
+    if_none_match: IfNoneMatch,
+) -> Result<Response, ApiError> {
+    let mut repository =
+        CachedUsersRepository::new(context.users().await?, cache);
+
+    let user =
+        get_user(UserUseCaseDeps::new(&mut repository), GetUserParams { id })
+            .await?;
+
+    let tag = user_tag(&user);
+    if if_none_match.matches(&tag) {
+        return Ok((StatusCode::NOT_MODIFIED, tag.header()).into_response());
+    }
+
+    Ok((tag.header(), Json(UserResponse::from(user))).into_response())
+}
+
+/// Returns the user with a username, matched regardless of the case.
+///
+/// The response has the ETag of the user, like the one of `GET /users/{id}`.
+#[utoipa::path(
+    get,
+    path = "/users/by-username/{username}",
+    operation_id = "get_user_by_username",
+    tag = "users",
+    params(("username" = String, Path, description = "Username of the user")),
+    responses(
+        (status = OK, description = "The user", body = UserResponse,
+            headers(("ETag" = String, description = "ETag of the user"))),
+        (status = NOT_FOUND, description = "No user has the username", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+    ),
+    security(("api_key" = ["users:read"])),
+)]
+async fn get_by_username_handler(
+    ReadContext(mut context): ReadContext,
+    Path(username): Path<String>,
+) -> Result<Response, ApiError> {
+    let user = get_user_by_username(
+        UserUseCaseDeps::new(&mut context.users().await?),
+        GetUserByUsernameParams { username },
+    )
+    .await?;
+
+    Ok((user_tag(&user).header(), Json(UserResponse::from(user)))
+        .into_response())
+}
+
+/// Fields of users that can't be changed by patches.
+const IMMUTABLE_USER_FIELDS: &[&str] =
+    &["id", "email", "username", "created_at", "updated_at"];
+
+/// Changes the name of a user.
+///
+/// The body is either the new name as `application/json`, a JSON Merge Patch
+/// (`application/merge-patch+json`), or a JSON Patch (`application/json-patch+json`) of the
+/// user. Patches may only change the name, since the other fields of users are immutable.
+///
+/// The request must have an `If-Match` header with the ETag of the user the change is based
+/// on, and fails if the user has changed since.
+#[utoipa::path(
+    patch,
+    path = "/users/{id}",
+    operation_id = "update_user",
+    tag = "users",
+    params(
+        ("id" = Uuid, Path, description = "ID of the user"),
+        ("If-Match" = String, Header, description = "ETag of the user the change is based on, or `*` for any version"),
+    ),
+    request_body(content(
+        (UpdateUserRequest = "application/json"),
+        (UpdateUserRequest = "application/merge-patch+json"),
+        (json_patch::Patch = "application/json-patch+json"),
+    )),
+    responses(
+        (status = OK, description = "The updated user", body = UserResponse,
+            headers(("ETag" = String, description = "ETag of the updated user"))),
+        (status = NOT_FOUND, description = "The user doesn't exist", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+        (status = CONFLICT, description = "A `test` operation of the JSON Patch failed", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+        (status = PRECONDITION_FAILED, description = "The user has changed since the version of `If-Match`", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+        (status = UNSUPPORTED_MEDIA_TYPE, description = "The request body isn't JSON or a patch", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+        (status = UNPROCESSABLE_ENTITY, description = "The request body is invalid, or the patch changes immutable fields", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+        (status = PRECONDITION_REQUIRED, description = "The request has no `If-Match` header", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+    ),
+    security(("api_key" = ["users:write"])),
+)]
+async fn update_handler(
+    context: UseCaseContext,
+    Path(id): Path<Uuid>,
+    if_match: IfMatch,
+    request: PatchRequest<UpdateUserRequest>,
+) -> Result<Response, ApiError> {
+    let user = context
+        .retried("update_user", |mut context| async {
+            let (request, expected_version) = match request.clone() {
+                PatchRequest::Fields(request) if if_match.is_any() => {
+                    (request, None)
+                }
+                request => {
+                    let user = get_user(
+                        UserUseCaseDeps::new(&mut context.users().await?),
+                        GetUserParams { id },
+                    )
+                    .await?;
+                    let expected_version =
+                        if_match.expected_version(&user_tag(&user))?;
+
+                    match request {
+                        PatchRequest::Fields(request) => {
+                            (request, expected_version)
+                        }
+                        PatchRequest::Patch(patch) => {
+                            // The patch is applied to this version of the user, so the change
+                            // must be based on it even if any version matches `If-Match`.
+                            let expected_version =
+                                expected_version.unwrap_or(user.version());
+                            let request = patch.apply_to(
+                                &UserResponse::from(user),
+                                IMMUTABLE_USER_FIELDS,
+                            )?;
+
+                            (request, Some(expected_version))
+                        }
+                    }
+                }
+            };
+
+            let user = update_user(
+                context.user_lifecycle().await?,
+                UpdateUserParams {
+                    id,
+                    first_name: request.first_name,
+                    last_name: request.last_name,
+                    locale: request.locale,
+                    timezone: request.timezone,
+                    expected_version,
+                },
+            )
+            .await?;
+
+            Ok(user)
+        })
+        .await?;
+
+    Ok((user_tag(&user).header(), Json(UserResponse::from(user)))
+        .into_response())
+}
+
+/// Creates a user.
+#[utoipa::path(
+    post,
+    path = "/users",
+    operation_id = "create_user",
+    tag = "users",
+    request_body = CreateUserRequest,
+    responses(
+        (status = CREATED, description = "The created user", body = UserResponse),
+        (status = CONFLICT, description = "A user with the email or the username already exists", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+        (status = UNPROCESSABLE_ENTITY, description = "The request body is invalid", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+    ),
+    security(("api_key" = ["users:write"])),
+)]
+async fn create_handler(
+    context: UseCaseContext,
+    State(pipeline): State<PolicyPipeline>,
+    State(username_strategy): State<UsernameStrategy>,
+    State(identifiers): State<IdentifierLists>,
+    ValidJson(request): ValidJson<CreateUserRequest>,
+) -> Result<(StatusCode, Json<UserResponse>), ApiError> {
+    let create_user = CreateUser
+        .in_transaction(context.transactions())
+        .retried(pipeline.retrier)
+        .authorize(pipeline.policies, "users:write")
+        .measured(pipeline.metrics)
+        .logged();
+
+    let user = create_user
+        .execute(
+            context.principal(),
+            CreateUserParams {
+                user_attrs: NewUserAttrs {
+                    email: request.email,
+                    first_name: request.first_name,
+                    last_name: request.last_name,
+                },
+                username: request.username,
+                username_strategy,
+                identifier_policy: identifiers.policy(),
+                organization_id: None,
+                quota_policy: QuotaPolicy::default(),
+            },
+        )
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(UserResponse::from(user))))
+}
+
+/// Erases a user, e.g. to honour their right to erasure.
+///
+/// The email and the names of the user are replaced with placeholders, and their password,
+/// attributes and avatar are deleted. The user keeps their ID, so memberships and events still
+/// refer to them. Erasures can't be undone, so the request must repeat the current email of the
+/// user as a confirmation.
+///
+/// The request must have an `If-Match` header with the ETag of the user the erasure is based
+/// on, and fails if the user has changed since.
+#[utoipa::path(
+    post,
+    path = "/users/{id}/erasure",
+    operation_id = "erase_user",
+    tag = "users",
+    params(
+        ("id" = Uuid, Path, description = "ID of the user"),
+        ("If-Match" = String, Header, description = "ETag of the user the erasure is based on, or `*` for any version"),
+    ),
+    request_body = EraseUserRequest,
+    responses(
+        (status = OK, description = "The erased user", body = UserResponse),
+        (status = NOT_FOUND, description = "The user doesn't exist", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+        (status = CONFLICT, description = "The user has already been erased", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+        (status = PRECONDITION_FAILED, description = "The user has changed since the version of `If-Match`", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+        (status = UNPROCESSABLE_ENTITY, description = "The request body is invalid, or the confirmation isn't the email of the user", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+        (status = PRECONDITION_REQUIRED, description = "The request has no `If-Match` header", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+    ),
+    security(("api_key" = ["users:erase"])),
+)]
+async fn erase_handler(
+    context: UseCaseContext,
+    State(pipeline): State<PolicyPipeline>,
+    State(storage): State<Arc<dyn BlobStorage>>,
+    Path(id): Path<Uuid>,
+    if_match: IfMatch,
+    ValidJson(request): ValidJson<EraseUserRequest>,
+) -> Result<Json<UserResponse>, ApiError> {
+    let transactions = context.transactions();
+    let expected_version = if if_match.is_any() {
+        None
+    } else {
+        let user = get_user(
+            UserUseCaseDeps::new(&mut transactions.begin().await?.users()),
+            GetUserParams { id },
+        )
+        .await?;
+        if_match.expected_version(&user_tag(&user))?
+    };
+    let erase_user = EraseUser { storage }
+        .in_transaction(transactions)
+        .retried(pipeline.retrier)
+        .authorize(pipeline.policies, "users:erase")
+        .measured(pipeline.metrics)
+        .logged();
+
+    let user = erase_user
+        .execute(
+            context.principal(),
+            EraseUserParams {
+                id,
+                confirmation: request.confirmation,
+                expected_version,
+            },
+        )
+        .await?;
+
+    Ok(Json(UserResponse::from(user)))
+}
+
+/// Merges a duplicate into a user, e.g. when the same person signed up twice through different
+/// providers.
+///
+/// The memberships, devices, attributes, consents and roles of the duplicate are moved to the
+/// user, who keeps their own where both have one. The duplicate is then erased, like by
+/// `POST /users/{id}/erasure`, and announces a `user.merged` event. Merges can't be undone.
+#[utoipa::path(
+    post,
+    path = "/admin/users/{id}/merge",
+    operation_id = "merge_users",
+    tag = "users",
+    params(("id" = Uuid, Path, description = "ID of the user who is kept")),
+    request_body = MergeUsersRequest,
+    responses(
+        (status = OK, description = "The merged users", body = MergedUsersResponse),
+        (status = NOT_FOUND, description = "One of the users doesn't exist", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+        (status = CONFLICT, description = "One of the users has been erased", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+        (status = UNPROCESSABLE_ENTITY, description = "The request body is invalid, or the users are the same", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+    ),
+    security(("api_key" = ["users:merge"])),
+)]
+async fn merge_handler(
+    context: UseCaseContext,
+    State(pipeline): State<PolicyPipeline>,
+    State(storage): State<Arc<dyn BlobStorage>>,
+    State(sessions): State<SessionSettings>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<MergeUsersRequest>,
+) -> Result<Json<MergedUsersResponse>, ApiError> {
+    let merge_users = MergeUsers {
+        storage,
+        sessions: sessions.store,
+    }
+    .in_transaction(context.transactions())
+    .retried(pipeline.retrier)
+    .authorize(pipeline.policies, "users:merge")
+    .measured(pipeline.metrics)
+    .logged();
+
+    let merged = merge_users
+        .execute(
+            context.principal(),
+            MergeUsersParams {
+                primary_id: id,
+                duplicate_id: request.duplicate_id,
+            },
+        )
+        .await?;
+
+    Ok(Json(MergedUsersResponse {
+        primary: UserResponse::from(merged.primary),
+        duplicate: UserResponse::from(merged.duplicate),
+        memberships: merged.memberships,
+        devices: merged.devices,
+        attributes: merged.attributes,
+        consents: merged.consents,
+        roles: merged.roles,
+        sessions: merged.sessions,
+    }))
+}
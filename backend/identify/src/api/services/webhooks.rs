@@ -0,0 +1,593 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    routing::{delete, get, post, put},
+};
+use chrono::{DateTime, Utc};
+use identify_application::{
+    ApplicationError, DeleteWebhookEndpointParams,
+    GetWebhookEndpointByExternalIdParams, GetWebhookEndpointParams,
+    ListWebhookDeliveriesParams, ListWebhookEndpointsParams, PutOutcome,
+    PutWebhookEndpointParams, RedeliverWebhookDeliveryParams,
+    RegisterWebhookEndpointParams, WebhookEndpointUseCaseDeps,
+    delete_webhook_endpoint, get_webhook_endpoint,
+    get_webhook_endpoint_by_external_id, list_webhook_deliveries,
+    list_webhook_endpoints, put_webhook_endpoint, redeliver_webhook_delivery,
+    register_webhook_endpoint,
+};
+use identify_domain::{
+    NewWebhookEndpointAttrs, UserLifecycleTransition, WebhookDelivery,
+    WebhookDeliveryStatus, WebhookEndpoint, WebhookUrlPolicy,
+};
+use identify_infrastructure::{
+    encryption::FieldEncryption,
+    storage::{
+        self, connection::ReadPool, retry::Retrier,
+        webhooks::WebhookEndpointsRepository,
+    },
+};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use utoipa::{OpenApi, ToSchema};
+use uuid::Uuid;
+
+use crate::api::{
+    Route, Service,
+    error::{ApiError, PROBLEM_CONTENT_TYPE, Problem},
+    policy::RoutePolicy,
+    use_case::{ReadContext, UseCaseContext},
+    validation::{ValidJson, Validate, Validator},
+};
+
+const MAX_URL_LENGTH: usize = 2048;
+const MIN_SECRET_LENGTH: usize = 16;
+const MAX_SECRET_LENGTH: usize = 256;
+/// Most dead letters listed at once.
+const MAX_DEAD_LETTERS: u32 = 100;
+
+pub struct WebhookService;
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        list_handler,
+        get_handler,
+        create_handler,
+        get_by_external_id_handler,
+        put_by_external_id_handler,
+        delete_handler,
+        list_dead_letters_handler,
+        redeliver_handler,
+    ),
+    tags((name = "webhooks", description = "Endpoints user events are delivered to"))
+)]
+struct WebhookApi;
+
+impl Service for WebhookService {
+    fn routes() -> Vec<Route> {
+        vec![
+            Route::new("/webhooks", get(list_handler), RoutePolicy::admin()),
+            Route::new("/webhooks", post(create_handler), RoutePolicy::admin()),
+            Route::new(
+                "/webhooks/{id}",
+                get(get_handler),
+                RoutePolicy::admin(),
+            ),
+            Route::new(
+                "/webhooks/{id}",
+                delete(delete_handler),
+                RoutePolicy::admin(),
+            ),
+            Route::new(
+                "/webhooks/by-external-id/{external_id}",
+                get(get_by_external_id_handler),
+                RoutePolicy::admin(),
+            ),
+            Route::new(
+                "/webhooks/by-external-id/{external_id}",
+                put(put_by_external_id_handler),
+                RoutePolicy::admin(),
+            ),
+            Route::new(
+                "/webhooks/{id}/dead-letters",
+                get(list_dead_letters_handler),
+                RoutePolicy::admin(),
+            ),
+            Route::new(
+                "/webhooks/{id}/dead-letters/{delivery_id}/redeliver",
+                post(redeliver_handler),
+                RoutePolicy::admin(),
+            ),
+        ]
+    }
+
+    fn openapi() -> utoipa::openapi::OpenApi {
+        WebhookApi::openapi()
+    }
+}
+
+/// Type of the events delivered to webhooks.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema,
+)]
+pub enum WebhookEventType {
+    #[serde(rename = "user.created")]
+    UserCreated,
+    #[serde(rename = "user.updated")]
+    UserUpdated,
+    #[serde(rename = "user.verified")]
+    UserVerified,
+    #[serde(rename = "user.suspended")]
+    UserSuspended,
+    #[serde(rename = "user.deleted")]
+    UserDeleted,
+    #[serde(rename = "user.merged")]
+    UserMerged,
+}
+
+impl From<WebhookEventType> for UserLifecycleTransition {
+    fn from(value: WebhookEventType) -> Self {
+        match value {
+            WebhookEventType::UserCreated => UserLifecycleTransition::Created,
+            WebhookEventType::UserUpdated => UserLifecycleTransition::Updated,
+            WebhookEventType::UserVerified => UserLifecycleTransition::Verified,
+            WebhookEventType::UserSuspended => {
+                UserLifecycleTransition::Suspended
+            }
+            WebhookEventType::UserDeleted => UserLifecycleTransition::Deleted,
+            WebhookEventType::UserMerged => UserLifecycleTransition::Merged,
+        }
+    }
+}
+
+impl From<UserLifecycleTransition> for WebhookEventType {
+    fn from(value: UserLifecycleTransition) -> Self {
+        match value {
+            UserLifecycleTransition::Created => WebhookEventType::UserCreated,
+            UserLifecycleTransition::Updated => WebhookEventType::UserUpdated,
+            UserLifecycleTransition::Verified => WebhookEventType::UserVerified,
+            UserLifecycleTransition::Suspended => {
+                WebhookEventType::UserSuspended
+            }
+            UserLifecycleTransition::Deleted => WebhookEventType::UserDeleted,
+            UserLifecycleTransition::Merged => WebhookEventType::UserMerged,
+        }
+    }
+}
+
+/// The secret is never returned, callers have to keep the one they registered the endpoint
+/// with.
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(as = WebhookEndpoint)]
+pub struct WebhookEndpointResponse {
+    pub id: Uuid,
+    pub url: String,
+    /// Types of the delivered events. Empty if all of them are delivered.
+    pub events: Vec<WebhookEventType>,
+    /// ID of the endpoint assigned by the tool that manages it, if any.
+    pub external_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<WebhookEndpoint> for WebhookEndpointResponse {
+    fn from(value: WebhookEndpoint) -> Self {
+        let attrs = value.to_attributes();
+
+        WebhookEndpointResponse {
+            id: attrs.id,
+            url: attrs.url,
+            events: attrs.events.into_iter().map(Into::into).collect(),
+            external_id: attrs.external_id,
+            created_at: attrs.created_at,
+            updated_at: attrs.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(as = WebhookEndpointList)]
+pub struct WebhookEndpointListResponse {
+    pub items: Vec<WebhookEndpointResponse>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(as = WebhookDelivery)]
+pub struct WebhookDeliveryResponse {
+    pub id: Uuid,
+    pub endpoint_id: Uuid,
+    /// ID of the delivered event.
+    pub event_id: Uuid,
+    pub event_type: WebhookEventType,
+    pub user_id: Uuid,
+    pub occurred_at: DateTime<Utc>,
+    #[schema(example = "dead")]
+    pub status: &'static str,
+    pub attempts: u32,
+    /// Why the last attempt failed.
+    pub last_error: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<WebhookDelivery> for WebhookDeliveryResponse {
+    fn from(value: WebhookDelivery) -> Self {
+        let attrs = value.to_attributes();
+
+        WebhookDeliveryResponse {
+            id: attrs.id,
+            endpoint_id: attrs.endpoint_id,
+            event_id: attrs.event_id,
+            event_type: attrs.transition.into(),
+            user_id: attrs.user_id,
+            occurred_at: attrs.occurred_at,
+            status: attrs.status.as_str(),
+            attempts: attrs.attempts,
+            last_error: attrs.last_error,
+            updated_at: attrs.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(as = WebhookDeliveryList)]
+pub struct WebhookDeliveryListResponse {
+    pub items: Vec<WebhookDeliveryResponse>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateWebhookEndpointRequest {
+    // Missing required fields are reported by the validation.
+    #[serde(default)]
+    #[schema(
+        required = true,
+        max_length = 2048,
+        example = "https://hooks.example.com/identify"
+    )]
+    pub url: String,
+    /// Secret the payloads are signed with.
+    #[serde(default)]
+    #[schema(required = true, min_length = 16, max_length = 256)]
+    pub secret: String,
+    /// Types of the delivered events. All of them are delivered if it's empty or missing.
+    #[serde(default)]
+    pub events: Vec<WebhookEventType>,
+}
+
+impl Validate for CreateWebhookEndpointRequest {
+    fn validate(&self, validator: &mut Validator) {
+        validator
+            .field("url", Some(&self.url))
+            .required()
+            .max_length(MAX_URL_LENGTH)
+            .http_url();
+        validator
+            .field("secret", Some(&self.secret))
+            .required()
+            .min_length(MIN_SECRET_LENGTH)
+            .max_length(MAX_SECRET_LENGTH);
+    }
+}
+
+/// Lists all the webhook endpoints.
+#[utoipa::path(
+    get,
+    path = "/webhooks",
+    operation_id = "list_webhook_endpoints",
+    tag = "webhooks",
+    responses(
+        (status = OK, description = "All the endpoints", body = WebhookEndpointListResponse),
+    ),
+    security(("api_key" = [])),
+)]
+async fn list_handler(
+    State(pool): State<ReadPool>,
+    State(encryption): State<FieldEncryption>,
+) -> Result<Json<WebhookEndpointListResponse>, ApiError> {
+    let mut conn = storage::acquire(&pool).await?;
+    let mut repository =
+        WebhookEndpointsRepository::new(&mut conn, &encryption);
+
+    let endpoints = list_webhook_endpoints(
+        WebhookEndpointUseCaseDeps::new(&mut repository),
+        ListWebhookEndpointsParams,
+    )
+    .await?;
+
+    Ok(Json(WebhookEndpointListResponse {
+        items: endpoints.into_iter().map(Into::into).collect(),
+    }))
+}
+
+/// Returns a webhook endpoint.
+#[utoipa::path(
+    get,
+    path = "/webhooks/{id}",
+    operation_id = "get_webhook_endpoint",
+    tag = "webhooks",
+    params(("id" = Uuid, Path, description = "ID of the endpoint")),
+    responses(
+        (status = OK, description = "The endpoint", body = WebhookEndpointResponse),
+        (status = NOT_FOUND, description = "The endpoint doesn't exist", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+    ),
+    security(("api_key" = [])),
+)]
+async fn get_handler(
+    State(pool): State<ReadPool>,
+    State(encryption): State<FieldEncryption>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<WebhookEndpointResponse>, ApiError> {
+    let mut conn = storage::acquire(&pool).await?;
+    let mut repository =
+        WebhookEndpointsRepository::new(&mut conn, &encryption);
+
+    let endpoint = get_webhook_endpoint(
+        WebhookEndpointUseCaseDeps::new(&mut repository),
+        GetWebhookEndpointParams { id },
+    )
+    .await?;
+
+    Ok(Json(WebhookEndpointResponse::from(endpoint)))
+}
+
+/// Registers a webhook endpoint. Events recorded from now on are delivered to it.
+#[utoipa::path(
+    post,
+    path = "/webhooks",
+    operation_id = "create_webhook_endpoint",
+    tag = "webhooks",
+    request_body = CreateWebhookEndpointRequest,
+    responses(
+        (status = CREATED, description = "The registered endpoint", body = WebhookEndpointResponse),
+        (status = UNPROCESSABLE_ENTITY, description = "The request body is invalid", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+    ),
+    security(("api_key" = [])),
+)]
+async fn create_handler(
+    State(pool): State<SqlitePool>,
+    State(encryption): State<FieldEncryption>,
+    State(retrier): State<Retrier>,
+    State(url_policy): State<WebhookUrlPolicy>,
+    ValidJson(request): ValidJson<CreateWebhookEndpointRequest>,
+) -> Result<(StatusCode, Json<WebhookEndpointResponse>), ApiError> {
+    let endpoint = retrier
+        .run("register_webhook_endpoint", || async {
+            let mut tx = storage::begin(&pool).await?;
+            let mut repository =
+                WebhookEndpointsRepository::new(&mut tx, &encryption);
+
+            let endpoint = register_webhook_endpoint(
+                WebhookEndpointUseCaseDeps::new(&mut repository),
+                RegisterWebhookEndpointParams {
+                    endpoint_attrs: NewWebhookEndpointAttrs {
+                        url: request.url.clone(),
+                        secret: request.secret.clone(),
+                        events: request
+                            .events
+                            .iter()
+                            .copied()
+                            .map(Into::into)
+                            .collect(),
+                        external_id: None,
+                    },
+                    url_policy,
+                },
+            )
+            .await?;
+            storage::commit(tx).await?;
+
+            Ok::<_, ApplicationError>(endpoint)
+        })
+        .await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(WebhookEndpointResponse::from(endpoint)),
+    ))
+}
+
+/// Returns the full state of a webhook endpoint by the ID assigned to it by the tool that
+/// manages it, so that IaC tools can detect drift.
+#[utoipa::path(
+    get,
+    path = "/webhooks/by-external-id/{external_id}",
+    operation_id = "get_webhook_endpoint_by_external_id",
+    tag = "webhooks",
+    params(("external_id" = String, Path, description = "ID of the endpoint assigned by the caller")),
+    responses(
+        (status = OK, description = "The endpoint", body = WebhookEndpointResponse),
+        (status = NOT_FOUND, description = "The endpoint doesn't exist", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+    ),
+    security(("api_key" = [])),
+)]
+async fn get_by_external_id_handler(
+    State(pool): State<ReadPool>,
+    State(encryption): State<FieldEncryption>,
+    Path(external_id): Path<String>,
+) -> Result<Json<WebhookEndpointResponse>, ApiError> {
+    let mut conn = storage::acquire(&pool).await?;
+    let mut repository =
+        WebhookEndpointsRepository::new(&mut conn, &encryption);
+
+    let endpoint = get_webhook_endpoint_by_external_id(
+        WebhookEndpointUseCaseDeps::new(&mut repository),
+        GetWebhookEndpointByExternalIdParams { external_id },
+    )
+    .await?;
+
+    Ok(Json(WebhookEndpointResponse::from(endpoint)))
+}
+
+/// Registers or reconfigures a webhook endpoint by the ID assigned to it by the tool that
+/// manages it.
+///
+/// The request is idempotent: repeating it leaves the endpoint as it is, and its ID stays the
+/// same.
+#[utoipa::path(
+    put,
+    path = "/webhooks/by-external-id/{external_id}",
+    operation_id = "put_webhook_endpoint",
+    tag = "webhooks",
+    params(("external_id" = String, Path, description = "ID of the endpoint assigned by the caller")),
+    request_body = CreateWebhookEndpointRequest,
+    responses(
+        (status = CREATED, description = "The registered endpoint", body = WebhookEndpointResponse),
+        (status = OK, description = "The endpoint, which was reconfigured if needed", body = WebhookEndpointResponse),
+        (status = UNPROCESSABLE_ENTITY, description = "The request is invalid", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+    ),
+    security(("api_key" = [])),
+)]
+async fn put_by_external_id_handler(
+    State(pool): State<SqlitePool>,
+    State(encryption): State<FieldEncryption>,
+    State(retrier): State<Retrier>,
+    State(url_policy): State<WebhookUrlPolicy>,
+    Path(external_id): Path<String>,
+    ValidJson(request): ValidJson<CreateWebhookEndpointRequest>,
+) -> Result<(StatusCode, Json<WebhookEndpointResponse>), ApiError> {
+    let (endpoint, outcome) = retrier
+        .run("put_webhook_endpoint", || async {
+            let mut tx = storage::begin(&pool).await?;
+            let mut repository =
+                WebhookEndpointsRepository::new(&mut tx, &encryption);
+
+            let put = put_webhook_endpoint(
+                WebhookEndpointUseCaseDeps::new(&mut repository),
+                PutWebhookEndpointParams {
+                    external_id: external_id.clone(),
+                    url: request.url.clone(),
+                    secret: request.secret.clone(),
+                    events: request
+                        .events
+                        .iter()
+                        .copied()
+                        .map(Into::into)
+                        .collect(),
+                    url_policy,
+                },
+            )
+            .await?;
+            storage::commit(tx).await?;
+
+            Ok::<_, ApplicationError>(put)
+        })
+        .await?;
+
+    let status = match outcome {
+        PutOutcome::Created => StatusCode::CREATED,
+        PutOutcome::Updated | PutOutcome::Unchanged => StatusCode::OK,
+    };
+
+    Ok((status, Json(WebhookEndpointResponse::from(endpoint))))
+}
+
+/// Deletes a webhook endpoint. Events that haven't been delivered to it yet are dropped.
+#[utoipa::path(
+    delete,
+    path = "/webhooks/{id}",
+    operation_id = "delete_webhook_endpoint",
+    tag = "webhooks",
+    params(("id" = Uuid, Path, description = "ID of the endpoint")),
+    responses(
+        (status = NO_CONTENT, description = "The endpoint was deleted"),
+        (status = NOT_FOUND, description = "The endpoint doesn't exist", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+    ),
+    security(("api_key" = [])),
+)]
+async fn delete_handler(
+    State(pool): State<SqlitePool>,
+    State(encryption): State<FieldEncryption>,
+    State(retrier): State<Retrier>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    retrier
+        .run("delete_webhook_endpoint", || async {
+            let mut tx = storage::begin(&pool).await?;
+            let mut repository =
+                WebhookEndpointsRepository::new(&mut tx, &encryption);
+
+            delete_webhook_endpoint(
+                WebhookEndpointUseCaseDeps::new(&mut repository),
+                DeleteWebhookEndpointParams { id },
+            )
+            .await?;
+            storage::commit(tx).await?;
+
+            Ok::<_, ApplicationError>(())
+        })
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Lists the latest deliveries to an endpoint that gave up after all their attempts failed.
+#[utoipa::path(
+    get,
+    path = "/webhooks/{id}/dead-letters",
+    operation_id = "list_webhook_dead_letters",
+    tag = "webhooks",
+    params(("id" = Uuid, Path, description = "ID of the endpoint")),
+    responses(
+        (status = OK, description = "The latest dead letters, the most recent first", body = WebhookDeliveryListResponse),
+        (status = NOT_FOUND, description = "The endpoint doesn't exist", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+    ),
+    security(("api_key" = [])),
+)]
+async fn list_dead_letters_handler(
+    ReadContext(mut context): ReadContext,
+    Path(id): Path<Uuid>,
+) -> Result<Json<WebhookDeliveryListResponse>, ApiError> {
+    let deliveries = list_webhook_deliveries(
+        context.webhook_deliveries().await?,
+        ListWebhookDeliveriesParams {
+            endpoint_id: id,
+            status: WebhookDeliveryStatus::Dead,
+            limit: MAX_DEAD_LETTERS,
+        },
+    )
+    .await?;
+
+    Ok(Json(WebhookDeliveryListResponse {
+        items: deliveries.into_iter().map(Into::into).collect(),
+    }))
+}
+
+/// Retries a dead letter from scratch, e.g. after the endpoint has been fixed.
+#[utoipa::path(
+    post,
+    path = "/webhooks/{id}/dead-letters/{delivery_id}/redeliver",
+    operation_id = "redeliver_webhook_dead_letter",
+    tag = "webhooks",
+    params(
+        ("id" = Uuid, Path, description = "ID of the endpoint"),
+        ("delivery_id" = Uuid, Path, description = "ID of the dead delivery"),
+    ),
+    responses(
+        (status = OK, description = "The delivery, which is pending again", body = WebhookDeliveryResponse),
+        (status = NOT_FOUND, description = "The delivery doesn't exist", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+        (status = CONFLICT, description = "The delivery isn't dead", body = Problem, content_type = PROBLEM_CONTENT_TYPE),
+    ),
+    security(("api_key" = [])),
+)]
+async fn redeliver_handler(
+    context: UseCaseContext,
+    Path((id, delivery_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<WebhookDeliveryResponse>, ApiError> {
+    let delivery = context
+        .retried("redeliver_webhook_delivery", |mut context| async move {
+            let delivery = redeliver_webhook_delivery(
+                context.webhook_deliveries().await?,
+                RedeliverWebhookDeliveryParams {
+                    endpoint_id: id,
+                    delivery_id,
+                },
+            )
+            .await?;
+            context.commit().await?;
+
+            Ok(delivery)
+        })
+        .await?;
+
+    Ok(Json(WebhookDeliveryResponse::from(delivery)))
+}
@@ -0,0 +1,312 @@
+//! Isolation of the data of every organization in a database of its own.
+//!
+//! Requests name the organization they're made for, the tenant, in a header or in the
+//! subdomain they're sent to (see [TenantResolver]). They're served by a router of their own for
+//! every tenant, whose state has the pools of the tenant database instead of the primary one,
+//! so handlers don't have to know about tenancy at all. Requests that name no tenant are served
+//! from the primary database, which keeps the organizations and serves the operational routes.
+//!
+//! Requests for a tenant are only served for the API keys limited to its organization, and for
+//! admin keys, see [Principal::serves]. They're authenticated before the tenant is looked up,
+//! and the callers that aren't served for it are refused the same way whether it exists or not,
+//! so that nobody can tell which organizations there are or have their databases opened.
+//!
+//! The projection of the read model, the recording of logins, the relay of user events to
+//! webhooks and streaming, and the maintenance run for every tenant database once it's opened,
+//! as they do for the primary one. Tenants have a user cache, a feed of user events and a
+//! monitor of sessions of their own, so that subscribers only see the events and the activity
+//! of their tenant. Backups and the admin digest only cover the primary database.
+
+use std::{
+    collections::HashMap,
+    convert::Infallible,
+    sync::{Arc, Mutex},
+};
+
+use axum::{
+    Router,
+    extract::{Request, State},
+    http::{HeaderMap, HeaderName, header::HOST},
+    response::{IntoResponse, Response},
+};
+use identify_application::organization_contracts::GetBySlug as _;
+use identify_domain::{Organization, Slug};
+use identify_infrastructure::storage::{
+    self, connection::ReadPool, organizations::OrganizationsRepository,
+    tenants::TenantRegistry,
+};
+use sqlx::SqlitePool;
+use tower::ServiceExt as _;
+use uuid::Uuid;
+
+use crate::{
+    api::{self, ApiState, auth::Principal, error::ApiError},
+    config::{EventStreamConfig, ReadModelConfig},
+    event_feed::UserEventFeed,
+    maintenance::MaintenanceScheduler,
+    operations::OperationRunner,
+    read_model::ReadModelProjector,
+    session_monitor::SessionMonitor,
+    shutdown::Shutdown,
+    webhooks::WebhookWorker,
+};
+
+/// Where requests name their tenant, by the slug of the organization.
+#[derive(Debug, Clone)]
+pub enum TenantResolver {
+    /// In a header, e.g. `X-Identify-Tenant: acme`.
+    Header(HeaderName),
+    /// In the subdomain of the base domain the request is sent to, e.g. `acme.identify.test`
+    /// for the base domain `identify.test`.
+    Subdomain(String),
+}
+
+impl TenantResolver {
+    /// Returns the slug of the tenant named by the request, if any.
+    pub fn resolve(&self, headers: &HeaderMap) -> Option<String> {
+        let slug = match self {
+            TenantResolver::Header(name) => headers
+                .get(name)?
+                .to_str()
+                .ok()?
+                .trim()
+                .to_ascii_lowercase(),
+            TenantResolver::Subdomain(base_domain) => {
+                let host = headers.get(HOST)?.to_str().ok()?;
+                let host = host
+                    .rsplit_once(':')
+                    .map_or(host, |(host, _)| host)
+                    .to_ascii_lowercase();
+                let subdomain = host
+                    .strip_suffix(base_domain.as_str())?
+                    .strip_suffix('.')?;
+                if subdomain.contains('.') {
+                    return None;
+                }
+
+                subdomain.to_owned()
+            }
+        };
+
+        (!slug.is_empty()).then_some(slug)
+    }
+}
+
+/// The tenant a request is made for, added to the extensions of the request before it's routed.
+#[derive(Debug, Clone)]
+pub struct Tenant {
+    pub organization_id: Uuid,
+    /// Current slug of the organization, which may not be the one named by the request.
+    pub slug: String,
+}
+
+impl From<&Organization> for Tenant {
+    fn from(organization: &Organization) -> Self {
+        Tenant {
+            organization_id: organization.id(),
+            slug: organization.slug().to_owned(),
+        }
+    }
+}
+
+/// Routers of the primary database and of the tenants.
+#[derive(Clone)]
+pub struct Tenants {
+    resolver: TenantResolver,
+    registry: TenantRegistry,
+    read_model: Arc<ReadModelConfig>,
+    event_stream: Arc<EventStreamConfig>,
+    /// Relays the events of the primary database, and of every tenant with a pool of its own.
+    webhooks: Option<Arc<WebhookWorker>>,
+    /// Maintains the primary database, and every tenant with a pool of its own.
+    maintenance: Option<Arc<MaintenanceScheduler>>,
+    /// Stops the background jobs of the tenants.
+    shutdown: Shutdown,
+    state: ApiState,
+    primary: Router,
+    /// Routers of the tenants whose databases are open, by organization ID.
+    routers: Arc<Mutex<HashMap<Uuid, Router>>>,
+}
+
+impl Tenants {
+    pub fn new(
+        resolver: TenantResolver,
+        registry: TenantRegistry,
+        read_model: ReadModelConfig,
+        event_stream: EventStreamConfig,
+        state: ApiState,
+        shutdown: Shutdown,
+    ) -> Self {
+        Tenants {
+            resolver,
+            registry,
+            read_model: Arc::new(read_model),
+            event_stream: Arc::new(event_stream),
+            webhooks: None,
+            maintenance: None,
+            shutdown,
+            primary: api::router(state.clone()),
+            state,
+            routers: Arc::default(),
+        }
+    }
+
+    /// Relays the user events of every tenant the way the worker relays those of the primary
+    /// database.
+    pub fn with_webhooks(mut self, worker: WebhookWorker) -> Self {
+        self.webhooks = Some(Arc::new(worker));
+        self
+    }
+
+    /// Maintains every tenant database on the schedules of the primary one, until the shutdown
+    /// starts.
+    pub fn with_maintenance(mut self, scheduler: MaintenanceScheduler) -> Self {
+        self.maintenance = Some(Arc::new(scheduler));
+        self
+    }
+
+    /// Returns the tenant with the slug and its router, opening its database the first time.
+    ///
+    /// Fails if the caller isn't served for the tenant, with `404 Not Found` for admins, who
+    /// are served for every tenant that exists, and with `403 Forbidden` for the others,
+    /// whether the tenant exists or not.
+    async fn router(
+        &self,
+        slug: &str,
+        principal: &Principal,
+    ) -> Result<(Tenant, Router), ApiError> {
+        let refused = || {
+            if principal.admin {
+                ApiError::not_found(format!("Tenant '{slug}' doesn't exist"))
+            } else {
+                ApiError::forbidden()
+            }
+        };
+        if !principal.admin && principal.organization_id.is_none() {
+            return Err(refused());
+        }
+        let slug = Slug::parse(slug).map_err(|_| refused())?;
+
+        let mut conn = storage::acquire(&self.state.read_pool).await?;
+        let organization = OrganizationsRepository::new(&mut conn)
+            .get_by_slug(&slug)
+            .await?
+            .ok_or_else(refused)?;
+        let tenant = Tenant::from(&organization);
+        if !principal.serves(Some(&tenant)) {
+            return Err(refused());
+        }
+        if let Some(router) = self.cached(organization.id()) {
+            return Ok((tenant, router));
+        }
+
+        let pool = self.registry.pool(&organization).await?;
+        let mut routers =
+            self.routers.lock().unwrap_or_else(|e| e.into_inner());
+        // Another request may have built it while the database was opened.
+        let router = routers
+            .entry(organization.id())
+            .or_insert_with(|| self.open(organization.id(), pool));
+
+        Ok((tenant, router.clone()))
+    }
+
+    /// Starts the background jobs of a tenant database and builds its router.
+    fn open(&self, organization_id: Uuid, pool: SqlitePool) -> Router {
+        let state = tenant_state(
+            &self.state,
+            organization_id,
+            pool.clone(),
+            &self.event_stream,
+        );
+
+        ReadModelProjector::from_config(
+            pool.clone(),
+            state.encryption.clone(),
+            &self.read_model,
+            state.user_cache.clone(),
+        )
+        .spawn();
+        state.logins.spawn(self.shutdown.clone());
+        state.user_events.spawn();
+        state.sessions.spawn(state.user_events.clone());
+        if let Some(webhooks) = &self.webhooks {
+            webhooks.with_pool(pool.clone()).spawn();
+        }
+        if let Some(maintenance) = &self.maintenance {
+            maintenance.with_pool(pool).spawn(self.shutdown.clone());
+        }
+
+        api::router(state)
+    }
+
+    fn cached(&self, organization_id: Uuid) -> Option<Router> {
+        self.routers
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&organization_id)
+            .cloned()
+    }
+}
+
+/// Returns the state of the API for the requests of a tenant, which read and write its
+/// database, and cache its users and stream its events apart from those of the other tenants.
+fn tenant_state(
+    state: &ApiState,
+    organization_id: Uuid,
+    pool: SqlitePool,
+    event_stream: &EventStreamConfig,
+) -> ApiState {
+    ApiState {
+        user_cache: state.user_cache.for_tenant(organization_id),
+        operation_runner: OperationRunner::new(
+            pool.clone(),
+            state.status.clone(),
+        ),
+        read_pool: ReadPool::from(pool.clone()),
+        logins: state.logins.with_pool(pool.clone()),
+        user_events: UserEventFeed::from_config(
+            ReadPool::from(pool.clone()),
+            event_stream,
+        ),
+        sessions: SessionMonitor::from_config(event_stream),
+        pool,
+        ..state.clone()
+    }
+}
+
+/// Builds the API router that serves every request from the database of its tenant.
+pub fn router(tenants: Tenants) -> Router {
+    Router::new().fallback(dispatch).with_state(tenants)
+}
+
+async fn dispatch(
+    State(tenants): State<Tenants>,
+    mut request: Request,
+) -> Response {
+    let router = match tenants.resolver.resolve(request.headers()) {
+        None => tenants.primary.clone(),
+        Some(slug) => {
+            // Tenants are only looked up for the callers that may be served for them.
+            let principal = match tenants
+                .state
+                .authenticator
+                .principal(request.headers())
+            {
+                Ok(Some(principal)) => principal,
+                Ok(None) => return ApiError::unauthorized().into_response(),
+                Err(e) => return e.into_response(),
+            };
+            match tenants.router(&slug, &principal).await {
+                Ok((tenant, router)) => {
+                    request.extensions_mut().insert(tenant);
+                    router
+                }
+                Err(e) => return e.into_response(),
+            }
+        }
+    };
+
+    let response: Result<Response, Infallible> = router.oneshot(request).await;
+    response.into_response()
+}
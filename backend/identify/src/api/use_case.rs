@@ -0,0 +1,538 @@
+//! Per-request context of use cases.
+//!
+//! Handlers that change state extract a [UseCaseContext], which has the principal of the caller
+//! attached, and take the dependencies of their use cases from it instead of assembling them
+//! from the pool by hand. Use cases run through pipelines that begin their own transactions take
+//! them from the context too, see [UseCaseContext::transactions]. Handlers that only read
+//! extract a [ReadContext] instead, whose transaction runs on the [ReadPool]. Handlers of public
+//! routes extract a [PublicContext], whose caller may be anonymous.
+
+use std::{fmt, sync::Arc};
+
+use axum::{
+    extract::{FromRef, FromRequestParts},
+    http::{StatusCode, request::Parts},
+};
+use identify_application::{
+    AvatarUseCaseDeps, BlobStorage, ConsentUseCaseDeps, DeviceLoginDeps,
+    DeviceUseCaseDeps, EmailAliasUseCaseDeps, EmailChangeDeliveryDeps,
+    EmailChangeDeps, EmailSender, EmailTemplates, EmailVerificationDeps,
+    GeoResolver, InvitationAcceptanceDeps, InvitationDeliveryDeps,
+    InvitationUseCaseDeps, OrganizationUseCaseDeps, PasswordHasher,
+    PhoneVerificationDeliveryDeps, PhoneVerificationDeps, SelfRegistrationDeps,
+    SmsSender, TokenGenerator, UnitOfWork as _, UserAttributeUseCaseDeps,
+    UserLifecycleUseCaseDeps, WebhookDeliveryUseCaseDeps,
+};
+use identify_domain::{
+    AuthorizationPolicies, ConsentPolicies, RegistrationPolicy,
+};
+use identify_infrastructure::{
+    encryption::FieldEncryption,
+    storage::{
+        connection::ReadPool,
+        retry::{Retrier, Retryable},
+        roles::RolesRepository,
+        unit_of_work::{SqliteUnitOfWork, SqliteUnitOfWorkFactory},
+        user_summaries::UserSummariesReader,
+        users::UsersRepository,
+    },
+};
+use sqlx::SqlitePool;
+
+use crate::{
+    api::{ApiState, auth::Principal, error::ApiError},
+    metrics::Metrics,
+};
+
+/// Dependencies of the use cases of a request, which all share its transaction.
+///
+/// The transaction begins when the first dependencies are taken, so that no connection is held
+/// while the request body is read and checked. It's rolled back if the context is dropped
+/// without being committed. Use cases that take the unit of work over (e.g.
+/// [UserLifecycleUseCaseDeps]) commit it themselves.
+///
+/// Extracting the context fails with `401 Unauthorized` on routes that don't require
+/// authentication if the caller has no credentials.
+pub struct UseCaseContext {
+    principal: Principal,
+    pool: SqlitePool,
+    encryption: FieldEncryption,
+    retrier: Retrier,
+    unit_of_work: Option<SqliteUnitOfWork>,
+}
+
+impl UseCaseContext {
+    /// Returns the transaction of the request, beginning it if it hasn't been yet.
+    async fn unit_of_work(
+        &mut self,
+    ) -> Result<&mut SqliteUnitOfWork, ApiError> {
+        if self.unit_of_work.is_none() {
+            self.unit_of_work = Some(self.begin().await?);
+        }
+
+        Ok(self
+            .unit_of_work
+            .as_mut()
+            .expect("the transaction has just begun"))
+    }
+
+    async fn into_unit_of_work(self) -> Result<SqliteUnitOfWork, ApiError> {
+        match self.unit_of_work {
+            Some(unit_of_work) => Ok(unit_of_work),
+            None => self.begin().await,
+        }
+    }
+
+    async fn begin(&self) -> Result<SqliteUnitOfWork, ApiError> {
+        // Nothing has been done in the transaction yet, so it can be started over.
+        let unit_of_work = self
+            .retrier
+            .run("transaction.begin", || {
+                SqliteUnitOfWork::begin(&self.pool, &self.encryption)
+            })
+            .await?;
+
+        Ok(unit_of_work)
+    }
+
+    /// Makes the changes of the request, and makes them again in a new transaction as long as
+    /// they fail because the database was busy, like [Retrier::run]. The use case names the
+    /// changes in the metrics and the logs of the retries, e.g. `update_user`.
+    ///
+    /// Every attempt gets a context of its own, which the changes commit like a handler would.
+    /// They're started over from scratch, so effects outside of the database, e.g. emails,
+    /// should only be made once they're committed.
+    pub async fn retried<T, F, Fut>(
+        self,
+        use_case: &'static str,
+        mut changes: F,
+    ) -> Result<T, ApiError>
+    where
+        F: FnMut(UseCaseContext) -> Fut,
+        Fut: Future<Output = Result<T, ApiError>>,
+    {
+        self.retrier
+            .run(use_case, || {
+                let attempt = changes(self.attempt());
+                async { attempt.await.map_err(FailedAttempt) }
+            })
+            .await
+            .map_err(|FailedAttempt(e)| e)
+    }
+
+    /// Returns a context for an attempt of the changes of the request, whose transaction hasn't
+    /// begun yet.
+    fn attempt(&self) -> UseCaseContext {
+        UseCaseContext {
+            principal: self.principal.clone(),
+            pool: self.pool.clone(),
+            encryption: self.encryption.clone(),
+            retrier: self.retrier.clone(),
+            unit_of_work: None,
+        }
+    }
+
+    /// Returns the authenticated caller of the request.
+    pub fn principal(&self) -> &Principal {
+        &self.principal
+    }
+
+    /// Returns the repository of users within the transaction, e.g. to look a user up before
+    /// changing them.
+    pub async fn users(&mut self) -> Result<UsersRepository<'_>, ApiError> {
+        Ok(self.unit_of_work().await?.users())
+    }
+
+    /// Returns the repository of roles within the transaction.
+    pub async fn roles(&mut self) -> Result<RolesRepository<'_>, ApiError> {
+        Ok(self.unit_of_work().await?.roles())
+    }
+
+    /// Returns the factory of the transactions of the use cases that begin and commit their
+    /// own, e.g. those run through a pipeline with
+    /// [in_transaction](identify_application::TransactionalUseCaseExt::in_transaction).
+    ///
+    /// They don't share the transaction of the context, so the context shouldn't have begun one.
+    pub fn transactions(&self) -> SqliteUnitOfWorkFactory {
+        SqliteUnitOfWorkFactory::new(self.pool.clone(), self.encryption.clone())
+    }
+
+    pub async fn user_lifecycle(
+        self,
+    ) -> Result<UserLifecycleUseCaseDeps<SqliteUnitOfWork>, ApiError> {
+        Ok(UserLifecycleUseCaseDeps::new(
+            self.into_unit_of_work().await?,
+        ))
+    }
+
+    pub async fn user_attributes(
+        &mut self,
+    ) -> Result<UserAttributeUseCaseDeps<'_, SqliteUnitOfWork>, ApiError> {
+        Ok(UserAttributeUseCaseDeps::new(self.unit_of_work().await?))
+    }
+
+    pub async fn avatars<'a>(
+        &'a mut self,
+        storage: &'a dyn BlobStorage,
+    ) -> Result<
+        AvatarUseCaseDeps<'a, SqliteUnitOfWork, dyn BlobStorage + 'a>,
+        ApiError,
+    > {
+        Ok(AvatarUseCaseDeps::new(self.unit_of_work().await?, storage))
+    }
+
+    pub async fn devices(
+        &mut self,
+    ) -> Result<DeviceUseCaseDeps<'_, SqliteUnitOfWork>, ApiError> {
+        Ok(DeviceUseCaseDeps::new(self.unit_of_work().await?))
+    }
+
+    /// Takes the dependencies of the use cases that begin and commit their own transactions
+    /// on the pool of the request.
+    pub fn device_logins<'a>(
+        self,
+        geo_resolver: &'a dyn GeoResolver,
+        sender: &'a dyn EmailSender,
+        templates: &'a EmailTemplates,
+        policies: &'a ConsentPolicies,
+    ) -> DeviceLoginDeps<
+        'a,
+        SqliteUnitOfWorkFactory,
+        dyn GeoResolver + 'a,
+        dyn EmailSender + 'a,
+    > {
+        DeviceLoginDeps::new(
+            SqliteUnitOfWorkFactory::new(self.pool, self.encryption),
+            geo_resolver,
+            sender,
+            templates,
+            policies,
+        )
+    }
+
+    pub async fn consents<'a>(
+        &'a mut self,
+        policies: &'a ConsentPolicies,
+    ) -> Result<ConsentUseCaseDeps<'a, SqliteUnitOfWork>, ApiError> {
+        Ok(ConsentUseCaseDeps::new(
+            self.unit_of_work().await?,
+            policies,
+        ))
+    }
+
+    pub async fn invitations(
+        &mut self,
+    ) -> Result<InvitationUseCaseDeps<'_, SqliteUnitOfWork>, ApiError> {
+        Ok(InvitationUseCaseDeps::new(self.unit_of_work().await?))
+    }
+
+    pub async fn invitation_acceptances<'a>(
+        &'a mut self,
+        tokens: &'a dyn TokenGenerator,
+    ) -> Result<
+        InvitationAcceptanceDeps<'a, SqliteUnitOfWork, dyn TokenGenerator + 'a>,
+        ApiError,
+    > {
+        Ok(InvitationAcceptanceDeps::new(
+            self.unit_of_work().await?,
+            tokens,
+        ))
+    }
+
+    pub async fn invitation_deliveries<'a>(
+        &'a mut self,
+        tokens: &'a dyn TokenGenerator,
+        sender: &'a dyn EmailSender,
+        templates: &'a EmailTemplates,
+    ) -> Result<
+        InvitationDeliveryDeps<
+            'a,
+            SqliteUnitOfWork,
+            dyn TokenGenerator + 'a,
+            dyn EmailSender + 'a,
+        >,
+        ApiError,
+    > {
+        Ok(InvitationDeliveryDeps::new(
+            self.unit_of_work().await?,
+            tokens,
+            sender,
+            templates,
+        ))
+    }
+
+    pub async fn self_registrations<'a>(
+        &'a mut self,
+        policy: &'a RegistrationPolicy,
+        hasher: &'a dyn PasswordHasher,
+        tokens: &'a dyn TokenGenerator,
+        sender: &'a dyn EmailSender,
+        templates: &'a EmailTemplates,
+    ) -> Result<
+        SelfRegistrationDeps<
+            'a,
+            SqliteUnitOfWork,
+            dyn PasswordHasher + 'a,
+            dyn TokenGenerator + 'a,
+            dyn EmailSender + 'a,
+        >,
+        ApiError,
+    > {
+        Ok(SelfRegistrationDeps::new(
+            self.unit_of_work().await?,
+            policy,
+            hasher,
+            tokens,
+            sender,
+            templates,
+        ))
+    }
+
+    pub async fn email_verifications<'a>(
+        &'a mut self,
+        tokens: &'a dyn TokenGenerator,
+    ) -> Result<
+        EmailVerificationDeps<'a, SqliteUnitOfWork, dyn TokenGenerator + 'a>,
+        ApiError,
+    > {
+        Ok(EmailVerificationDeps::new(
+            self.unit_of_work().await?,
+            tokens,
+        ))
+    }
+
+    pub async fn email_aliases(
+        &mut self,
+    ) -> Result<EmailAliasUseCaseDeps<'_, SqliteUnitOfWork>, ApiError> {
+        Ok(EmailAliasUseCaseDeps::new(self.unit_of_work().await?))
+    }
+
+    pub async fn email_change_deliveries<'a>(
+        &'a mut self,
+        tokens: &'a dyn TokenGenerator,
+        sender: &'a dyn EmailSender,
+        templates: &'a EmailTemplates,
+    ) -> Result<
+        EmailChangeDeliveryDeps<
+            'a,
+            SqliteUnitOfWork,
+            dyn TokenGenerator + 'a,
+            dyn EmailSender + 'a,
+        >,
+        ApiError,
+    > {
+        Ok(EmailChangeDeliveryDeps::new(
+            self.unit_of_work().await?,
+            tokens,
+            sender,
+            templates,
+        ))
+    }
+
+    pub async fn email_changes<'a>(
+        &'a mut self,
+        tokens: &'a dyn TokenGenerator,
+    ) -> Result<
+        EmailChangeDeps<'a, SqliteUnitOfWork, dyn TokenGenerator + 'a>,
+        ApiError,
+    > {
+        Ok(EmailChangeDeps::new(self.unit_of_work().await?, tokens))
+    }
+
+    pub async fn phone_verification_deliveries<'a>(
+        &'a mut self,
+        tokens: &'a dyn TokenGenerator,
+        sender: &'a dyn SmsSender,
+    ) -> Result<
+        PhoneVerificationDeliveryDeps<
+            'a,
+            SqliteUnitOfWork,
+            dyn TokenGenerator + 'a,
+            dyn SmsSender + 'a,
+        >,
+        ApiError,
+    > {
+        Ok(PhoneVerificationDeliveryDeps::new(
+            self.unit_of_work().await?,
+            tokens,
+            sender,
+        ))
+    }
+
+    pub async fn phone_verifications<'a>(
+        &'a mut self,
+        tokens: &'a dyn TokenGenerator,
+    ) -> Result<
+        PhoneVerificationDeps<'a, SqliteUnitOfWork, dyn TokenGenerator + 'a>,
+        ApiError,
+    > {
+        Ok(PhoneVerificationDeps::new(
+            self.unit_of_work().await?,
+            tokens,
+        ))
+    }
+
+    pub async fn organizations(
+        self,
+    ) -> Result<OrganizationUseCaseDeps<SqliteUnitOfWork>, ApiError> {
+        Ok(OrganizationUseCaseDeps::new(
+            self.into_unit_of_work().await?,
+        ))
+    }
+
+    pub async fn webhook_deliveries(
+        &mut self,
+    ) -> Result<WebhookDeliveryUseCaseDeps<'_, SqliteUnitOfWork>, ApiError>
+    {
+        Ok(WebhookDeliveryUseCaseDeps::new(self.unit_of_work().await?))
+    }
+
+    /// Persists the changes made through the borrowed dependencies, if any were taken.
+    pub async fn commit(self) -> Result<(), ApiError> {
+        if let Some(unit_of_work) = self.unit_of_work {
+            unit_of_work.commit().await?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<S> FromRequestParts<S> for UseCaseContext
+where
+    SqlitePool: FromRef<S>,
+    FieldEncryption: FromRef<S>,
+    Retrier: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &S,
+    ) -> Result<Self, Self::Rejection> {
+        let principal = principal(parts)?;
+
+        Ok(UseCaseContext {
+            principal,
+            pool: SqlitePool::from_ref(state),
+            encryption: FieldEncryption::from_ref(state),
+            retrier: Retrier::from_ref(state),
+            unit_of_work: None,
+        })
+    }
+}
+
+/// [UseCaseContext] of the handlers that only read, whose transaction runs on the [ReadPool].
+///
+/// Writes made through it fail, since the connections of the read pool are read-only.
+pub struct ReadContext(pub UseCaseContext);
+
+impl ReadContext {
+    /// Returns the reader of the summaries of users, whose queries run on connections of their
+    /// own rather than in the transaction of the context.
+    pub fn user_summaries(&self) -> UserSummariesReader {
+        UserSummariesReader::new(
+            ReadPool::from(self.0.pool.clone()),
+            self.0.encryption.clone(),
+        )
+    }
+}
+
+impl<S> FromRequestParts<S> for ReadContext
+where
+    ReadPool: FromRef<S>,
+    FieldEncryption: FromRef<S>,
+    Retrier: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &S,
+    ) -> Result<Self, Self::Rejection> {
+        let principal = principal(parts)?;
+
+        Ok(ReadContext(UseCaseContext {
+            principal,
+            pool: ReadPool::from_ref(state).pool().clone(),
+            encryption: FieldEncryption::from_ref(state),
+            retrier: Retrier::from_ref(state),
+            unit_of_work: None,
+        }))
+    }
+}
+
+/// [UseCaseContext] of the handlers of public routes, whose caller is
+/// [anonymous](Principal::anonymous) if the request carries no credentials.
+pub struct PublicContext(pub UseCaseContext);
+
+impl<S> FromRequestParts<S> for PublicContext
+where
+    SqlitePool: FromRef<S>,
+    FieldEncryption: FromRef<S>,
+    Retrier: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &S,
+    ) -> Result<Self, Self::Rejection> {
+        let principal =
+            principal(parts).unwrap_or_else(|_| Principal::anonymous());
+
+        Ok(PublicContext(UseCaseContext {
+            principal,
+            pool: SqlitePool::from_ref(state),
+            encryption: FieldEncryption::from_ref(state),
+            retrier: Retrier::from_ref(state),
+            unit_of_work: None,
+        }))
+    }
+}
+
+/// Error of an attempt of the changes of a request, see [UseCaseContext::retried].
+struct FailedAttempt(ApiError);
+
+/// Requests fail with `503 Service Unavailable` if the database is busy or a connection isn't
+/// available in time, and may succeed when they're made again.
+impl Retryable for FailedAttempt {
+    fn is_retryable(&self) -> bool {
+        self.0.status() == StatusCode::SERVICE_UNAVAILABLE
+    }
+}
+
+impl fmt::Display for FailedAttempt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.0.detail())
+    }
+}
+
+fn principal(parts: &Parts) -> Result<Principal, ApiError> {
+    parts
+        .extensions
+        .get::<Principal>()
+        .cloned()
+        .ok_or_else(ApiError::unauthorized)
+}
+
+/// What the pipelines of the use cases authorized by policies take besides the unit of work,
+/// see [Authorize](identify_application::Authorize).
+#[derive(Clone)]
+pub struct PolicyPipeline {
+    pub metrics: Metrics,
+    pub retrier: Retrier,
+    pub policies: Arc<AuthorizationPolicies>,
+}
+
+impl FromRef<ApiState> for PolicyPipeline {
+    fn from_ref(state: &ApiState) -> Self {
+        PolicyPipeline {
+            metrics: state.metrics.clone(),
+            retrier: state.retrier.clone(),
+            policies: state.authorization_policies.clone(),
+        }
+    }
+}
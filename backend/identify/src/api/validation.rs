@@ -0,0 +1,285 @@
+//! Validation of request bodies.
+//!
+//! Request DTOs declare the rules of their fields by implementing [Validate], and handlers
+//! extract them with [ValidJson]. All fields are checked before responding, so callers get
+//! every problem with the body at once instead of fixing them one by one.
+
+use std::{borrow::Cow, net::IpAddr};
+
+use axum::{
+    Json,
+    extract::{FromRequest, Request},
+    http::StatusCode,
+};
+use identify_domain::{Locale, PhoneNumber, Slug, TimeZone, Username};
+use serde::{Serialize, de::DeserializeOwned};
+use utoipa::ToSchema;
+
+use crate::api::error::ApiError;
+
+/// A problem with a single field of the request body.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct FieldError {
+    /// Name of the field as it appears in the body.
+    pub field: &'static str,
+    /// Machine-readable name of the violated rule, e.g. `required`.
+    pub code: &'static str,
+    pub message: Cow<'static, str>,
+}
+
+/// A request DTO whose fields are checked against declarative rules.
+pub trait Validate {
+    /// Declares the rules of all fields, e.g.
+    /// `validator.field("email", Some(&self.email)).required().email();`.
+    fn validate(&self, validator: &mut Validator);
+}
+
+/// Collects the [FieldErrors](FieldError) of a request body.
+#[derive(Debug, Default)]
+pub struct Validator {
+    errors: Vec<FieldError>,
+}
+
+impl Validator {
+    /// Starts checking a field. Missing optional fields are passed as `None`.
+    pub fn field<'v>(
+        &'v mut self,
+        field: &'static str,
+        value: Option<&'v str>,
+    ) -> FieldRules<'v> {
+        FieldRules {
+            validator: self,
+            field,
+            value,
+            failed: false,
+        }
+    }
+
+    pub fn into_errors(self) -> Vec<FieldError> {
+        self.errors
+    }
+}
+
+/// Rules applied to a single field.
+///
+/// Only the first violated rule of a field is reported. Rules other than
+/// [required](FieldRules::required) are skipped for missing values.
+pub struct FieldRules<'v> {
+    validator: &'v mut Validator,
+    field: &'static str,
+    value: Option<&'v str>,
+    failed: bool,
+}
+
+impl FieldRules<'_> {
+    /// The value must be present and not blank.
+    pub fn required(mut self) -> Self {
+        if self.value.is_none_or(|value| value.trim().is_empty()) {
+            self.fail("required", "must not be empty".into());
+        }
+
+        self
+    }
+
+    /// The value must have at least the provided number of characters.
+    pub fn min_length(self, min: usize) -> Self {
+        self.check(
+            |value| value.chars().count() >= min,
+            "length",
+            || format!("must be at least {min} characters long").into(),
+        )
+    }
+
+    /// The value must have at most the provided number of characters.
+    pub fn max_length(self, max: usize) -> Self {
+        self.check(
+            |value| value.chars().count() <= max,
+            "length",
+            || format!("must be at most {max} characters long").into(),
+        )
+    }
+
+    /// The value must look like an email address.
+    pub fn email(self) -> Self {
+        self.check(is_email, "format", || {
+            "must be a valid email address".into()
+        })
+    }
+
+    /// The value must be an absolute HTTP(S) URL.
+    pub fn http_url(self) -> Self {
+        self.check(is_http_url, "format", || {
+            "must be a valid HTTP(S) URL".into()
+        })
+    }
+
+    /// The value must be an IPv4 or IPv6 address.
+    pub fn ip_address(self) -> Self {
+        self.check(
+            |value| value.parse::<IpAddr>().is_ok(),
+            "format",
+            || "must be a valid IP address".into(),
+        )
+    }
+
+    /// The value must be a valid username, see [Username].
+    pub fn username(self) -> Self {
+        self.check(
+            |value| Username::parse(value).is_ok(),
+            "format",
+            || {
+                "must only contain letters, digits, '.', '_' and '-', and start and end with a letter or a digit".into()
+            },
+        )
+    }
+
+    /// The value must be a phone number with its country code, see [PhoneNumber].
+    pub fn phone_number(self) -> Self {
+        self.check(
+            |value| PhoneNumber::parse(value).is_ok(),
+            "format",
+            || {
+                format!(
+                    "must be a phone number starting with '+' and the country code, with {} to {} digits",
+                    PhoneNumber::MIN_DIGITS,
+                    PhoneNumber::MAX_DIGITS
+                )
+                .into()
+            },
+        )
+    }
+
+    /// The value must be a BCP 47 language tag, see [Locale].
+    pub fn locale(self) -> Self {
+        self.check(
+            |value| Locale::parse(value).is_ok(),
+            "format",
+            || "must be a BCP 47 language tag, e.g. 'en' or 'pt-BR'".into(),
+        )
+    }
+
+    /// The value must be the identifier of a time zone, see [TimeZone].
+    pub fn timezone(self) -> Self {
+        self.check(
+            |value| TimeZone::parse(value).is_ok(),
+            "format",
+            || {
+                "must be a time zone of the IANA database, e.g. 'Europe/Berlin'"
+                    .into()
+            },
+        )
+    }
+
+    /// The value must be a valid slug, see [Slug].
+    pub fn slug(self) -> Self {
+        self.check(
+            |value| Slug::parse(value).is_ok(),
+            "format",
+            || {
+                format!(
+                    "must be {} to {} letters, digits and '-', and start and end with a letter or a digit",
+                    Slug::MIN_LENGTH,
+                    Slug::MAX_LENGTH
+                )
+                .into()
+            },
+        )
+    }
+
+    fn check(
+        mut self,
+        rule: impl FnOnce(&str) -> bool,
+        code: &'static str,
+        message: impl FnOnce() -> Cow<'static, str>,
+    ) -> Self {
+        if let Some(value) = self.value
+            && !rule(value)
+        {
+            self.fail(code, message());
+        }
+
+        self
+    }
+
+    fn fail(&mut self, code: &'static str, message: Cow<'static, str>) {
+        if self.failed {
+            return;
+        }
+
+        self.failed = true;
+        self.validator.errors.push(FieldError {
+            field: self.field,
+            code,
+            message,
+        });
+    }
+}
+
+/// A deliberately loose check, the address is only proven to work by verifying it.
+fn is_email(value: &str) -> bool {
+    let Some((local, domain)) = value.rsplit_once('@') else {
+        return false;
+    };
+
+    !local.is_empty()
+        && domain.contains('.')
+        && !domain.starts_with('.')
+        && !domain.ends_with('.')
+        && !value.chars().any(char::is_whitespace)
+}
+
+fn is_http_url(value: &str) -> bool {
+    reqwest::Url::parse(value).is_ok_and(|url| {
+        matches!(url.scheme(), "http" | "https") && url.has_host()
+    })
+}
+
+/// Extractor that deserializes a JSON body and validates it.
+///
+/// Malformed bodies are rejected with the status of the underlying [Json] rejection, and
+/// bodies that break the rules of [Validate] with `422 Unprocessable Entity` that lists all
+/// the [FieldErrors](FieldError).
+#[derive(Debug)]
+pub struct ValidJson<T>(pub T);
+
+impl<T, S> FromRequest<S> for ValidJson<T>
+where
+    T: DeserializeOwned + Validate,
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request(
+        request: Request,
+        state: &S,
+    ) -> Result<Self, Self::Rejection> {
+        let Json(value) = Json::<T>::from_request(request, state)
+            .await
+            .map_err(|e| ApiError::new(e.status(), e.body_text()))?;
+
+        check(&value)?;
+
+        Ok(ValidJson(value))
+    }
+}
+
+/// Checks the value against its rules, and reports all the violated ones with
+/// `422 Unprocessable Entity`.
+///
+/// [ValidJson] does this for JSON bodies, other inputs (e.g. GraphQL arguments) are checked
+/// explicitly.
+pub fn check(value: &impl Validate) -> Result<(), ApiError> {
+    let mut validator = Validator::default();
+    value.validate(&mut validator);
+
+    let errors = validator.into_errors();
+    if !errors.is_empty() {
+        return Err(ApiError::new(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "The request body is invalid",
+        )
+        .with_field_errors(errors));
+    }
+
+    Ok(())
+}
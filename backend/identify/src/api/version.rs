@@ -0,0 +1,89 @@
+//! Versions of the API.
+//!
+//! Every version is mounted under its own prefix, e.g. `/api/v1`, with its own set of services,
+//! so that breaking changes to DTOs can be shipped as a new version while the old one keeps
+//! working. Deprecated versions keep being served, but their responses announce the
+//! deprecation with the `Deprecation` (RFC 9745) and `Sunset` (RFC 8594) headers.
+
+use axum::{
+    extract::{Request, State},
+    http::{HeaderName, HeaderValue, header::LINK},
+    middleware::Next,
+    response::Response,
+};
+use chrono::{DateTime, Utc};
+
+pub const DEPRECATION_HEADER: HeaderName =
+    HeaderName::from_static("deprecation");
+pub const SUNSET_HEADER: HeaderName = HeaderName::from_static("sunset");
+
+/// A version of the API.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ApiVersion {
+    V1,
+}
+
+impl ApiVersion {
+    /// All the versions that are served, from the oldest to the newest one.
+    pub const ALL: [ApiVersion; 1] = [ApiVersion::V1];
+
+    /// Path all the routes of the version are mounted under.
+    pub const fn prefix(self) -> &'static str {
+        match self {
+            ApiVersion::V1 => "/api/v1",
+        }
+    }
+
+    /// Returns the deprecation of the version, if it's deprecated.
+    pub const fn deprecation(self) -> Option<Deprecation> {
+        match self {
+            ApiVersion::V1 => None,
+        }
+    }
+}
+
+/// When a version was deprecated and what replaces it.
+#[derive(Debug, Clone, Copy)]
+pub struct Deprecation {
+    /// When the version was deprecated.
+    pub since: DateTime<Utc>,
+    /// When the version is going to be removed.
+    pub sunset: Option<DateTime<Utc>>,
+    /// Version callers should migrate to.
+    pub successor: ApiVersion,
+}
+
+/// Announces the deprecation of the version in the response headers.
+pub async fn announce_deprecation(
+    State(deprecation): State<Deprecation>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let mut response = next.run(request).await;
+
+    let mut announcements = vec![(
+        DEPRECATION_HEADER,
+        format!("@{}", deprecation.since.timestamp()),
+    )];
+    if let Some(sunset) = deprecation.sunset {
+        announcements.push((
+            SUNSET_HEADER,
+            sunset.format("%a, %d %b %Y %H:%M:%S GMT").to_string(),
+        ));
+    }
+    announcements.push((
+        LINK,
+        format!(
+            "<{}>; rel=\"successor-version\"",
+            deprecation.successor.prefix()
+        ),
+    ));
+
+    for (name, value) in announcements {
+        if let Ok(value) = HeaderValue::from_str(&value) {
+            response.headers_mut().append(name, value);
+        }
+    }
+
+    response
+}
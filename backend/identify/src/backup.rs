@@ -0,0 +1,143 @@
+//! Scheduled backups of the database to the blob storage.
+//!
+//! Backups are taken on a cron schedule (see [Schedule]), delayed by a random jitter like the
+//! [maintenance](crate::maintenance) tasks. They're stored under a key made of the time they
+//! were scheduled at, so replicas sharing a database replace each other's backup instead of
+//! storing one each. Old backups are never deleted, which is left to the storage, e.g. to the
+//! lifecycle rules of a bucket.
+
+use std::{sync::Arc, time::Duration};
+
+use chrono::{DateTime, Utc};
+use eyre::{Context, Result, eyre};
+use identify_application::{Blob, BlobStorage};
+use identify_infrastructure::storage::backup;
+use sqlx::SqlitePool;
+use tokio::task::JoinHandle;
+use tracing::{Instrument, debug, error, info, info_span};
+use uuid::Uuid;
+
+use crate::{config::BackupConfig, cron::Schedule, shutdown::Shutdown};
+
+/// Content type of the stored backups, which are SQLite database files.
+pub const BACKUP_CONTENT_TYPE: &str = "application/vnd.sqlite3";
+
+/// Uploads backups of the database to the blob storage on a schedule.
+pub struct BackupScheduler {
+    pool: SqlitePool,
+    storage: Arc<dyn BlobStorage>,
+    schedule: Schedule,
+    jitter: Duration,
+    key_prefix: String,
+}
+
+impl BackupScheduler {
+    /// Creates the scheduler, unless backups are disabled.
+    pub fn from_config(
+        pool: SqlitePool,
+        storage: Arc<dyn BlobStorage>,
+        config: &BackupConfig,
+    ) -> Result<Option<Self>> {
+        if !config.enabled {
+            return Ok(None);
+        }
+
+        let schedule = config
+            .schedule()
+            .map_err(|e| eyre!("backup.schedule: {e}"))?;
+
+        Ok(Some(BackupScheduler {
+            pool,
+            storage,
+            schedule,
+            jitter: config.jitter(),
+            key_prefix: config.key_prefix.trim_matches('/').to_owned(),
+        }))
+    }
+
+    /// Takes the backups in the background until the shutdown starts. A backup in progress is
+    /// finished first.
+    pub fn spawn(self, mut shutdown: Shutdown) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            while let Some(scheduled_at) = self.schedule.next_after(Utc::now())
+            {
+                let wait =
+                    (scheduled_at - Utc::now()).to_std().unwrap_or_default()
+                        + jitter(self.jitter);
+                tokio::select! {
+                    () = tokio::time::sleep(wait) => {}
+                    () = shutdown.wait() => return,
+                }
+
+                let span = info_span!("backup", %scheduled_at);
+                if let Err(e) = self.run(scheduled_at).instrument(span).await {
+                    error!(error = ?e, "Failed to back up the database");
+                }
+            }
+            debug!("The schedule never fires again, stopping the backups");
+        })
+    }
+
+    /// Backs up the database and uploads the backup under the key of `scheduled_at`. Returns
+    /// the key.
+    ///
+    /// The backup is written to a temporary file first, and uploaded as a whole.
+    pub async fn run(&self, scheduled_at: DateTime<Utc>) -> Result<String> {
+        let path = std::env::temp_dir()
+            .join(format!("identify-backup-{}.db", Uuid::new_v4()));
+        let bytes = async {
+            backup::backup(&self.pool, &path).await?;
+            tokio::fs::read(&path)
+                .await
+                .wrap_err("error while reading the backup")
+        }
+        .await;
+        let _ = tokio::fs::remove_file(&path).await;
+        let bytes = bytes?;
+
+        let key = key(&self.key_prefix, scheduled_at);
+        let size = bytes.len();
+        self.storage
+            .put(
+                &key,
+                &Blob {
+                    content_type: BACKUP_CONTENT_TYPE.to_owned(),
+                    bytes,
+                },
+            )
+            .await
+            .wrap_err("error while uploading the backup")?;
+
+        info!(key, size, "Uploaded a backup of the database");
+
+        Ok(key)
+    }
+}
+
+/// Returns the blob key of the backup scheduled at the time, e.g.
+/// `backups/identify-20261016T020000Z.db`.
+pub fn key(prefix: &str, scheduled_at: DateTime<Utc>) -> String {
+    format!(
+        "{prefix}/identify-{}.db",
+        scheduled_at.format("%Y%m%dT%H%M%SZ")
+    )
+}
+
+/// Checks that the prefix makes valid keys for the [BlobStorage].
+pub fn is_valid_key_prefix(prefix: &str) -> bool {
+    prefix.trim_matches('/').split('/').all(|segment| {
+        !segment.is_empty()
+            && !segment.starts_with('.')
+            && segment.bytes().all(|b| {
+                b.is_ascii_alphanumeric() || matches!(b, b'.' | b'_' | b'-')
+            })
+    })
+}
+
+/// Returns a random delay up to `max`.
+fn jitter(max: Duration) -> Duration {
+    match max.as_millis() {
+        0 => Duration::ZERO,
+        max => Duration::from_millis((Uuid::new_v4().as_u128() % max) as u64),
+    }
+}
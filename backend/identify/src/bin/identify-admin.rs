@@ -0,0 +1,61 @@
+use std::{io, sync::Arc};
+
+use clap::Parser;
+use eyre::{Context, Result, eyre};
+use identify::{
+    admin::{self, AdminContext, Cli},
+    config::Config,
+    identifiers::IdentifierLists,
+};
+use identify_infrastructure::{
+    encryption::FieldEncryption,
+    storage::{connection, timing},
+};
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let config = Config::load()?;
+
+    let encryption =
+        match config.encryption.key_provider().map_err(|e| eyre!(e))? {
+            Some(keys) => FieldEncryption::from_provider(&keys)
+                .await
+                .wrap_err("error while loading the encryption keys")?,
+            None => FieldEncryption::disabled(),
+        };
+
+    timing::set_slow_query_threshold(config.database.slow_query_threshold());
+    let pool_config = config.database.pool_config();
+    let pool = connection::get_pool(&config.database.url, &pool_config)
+        .await
+        .wrap_err("error while connecting to the database")?;
+
+    let context = AdminContext {
+        pool: &pool,
+        encryption,
+        username_strategy: config
+            .users
+            .username_strategy()
+            .map_err(|e| eyre!(e))?,
+        identifier_policy: IdentifierLists::from_config(&config.identifiers)
+            .wrap_err("error while loading the identifier lists")?
+            .policy(),
+        quota_policy: config.quotas.policy(),
+        email_sender: config.email.sender().map_err(|e| eyre!(e))?,
+        email_templates: Arc::new(
+            config.email.templates().map_err(|e| eyre!(e))?,
+        ),
+    };
+    admin::run(
+        cli.command,
+        &context,
+        &mut io::stdin().lock(),
+        &mut io::stdout().lock(),
+    )
+    .await?;
+
+    pool.close().await;
+
+    Ok(())
+}
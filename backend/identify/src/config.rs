@@ -0,0 +1,2509 @@
+//! Layered configuration of the Identify server.
+//!
+//! The configuration is loaded from the following sources (later sources override earlier ones):
+//!
+//! 1. Built-in defaults.
+//! 2. An optional TOML file at the path from [CONFIG_FILE_ENV] (`identify.toml` by default).
+//! 3. `IDENTIFY_*` env variables, where nested keys are separated by `__`, e.g.
+//!    `IDENTIFY_SERVER__BIND_ADDRESS=127.0.0.1:8080`.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+    time::Duration,
+};
+
+use axum::http::{HeaderName, HeaderValue, Method};
+use chrono::{TimeDelta, Utc};
+use config::{Environment, File, FileFormat};
+use identify_application::{
+    BlobStorage, DigestCategory, EmailKind, EmailPart, EmailSender,
+    EmailTemplates, GeoResolver, SessionStore, SmsSender,
+};
+use identify_domain::{
+    AuthorizationPolicies, AuthorizationPolicy, ConsentPolicies, DomainError,
+    Locale, PolicyCondition, QuotaPolicy, RegistrationPolicy, UsernameStrategy,
+    WebhookRetryPolicy, WebhookUrlPolicy,
+};
+use identify_infrastructure::{
+    blobs::FilesystemBlobStorage,
+    email::{LogEmailSender, SmtpEmailSender, SmtpSecurity, SmtpSettings},
+    encryption::{EncryptionKey, KeyRing, StaticKeyProvider},
+    geo::{NetworkGeoResolver, NoGeoResolver},
+    sessions::MemorySessionStore,
+    sms::LogSmsSender,
+    storage::{
+        connection::{self, PoolConfig, ReconnectPolicy, SqlitePragmas},
+        retry::RetryPolicy,
+        tenants::TenantRegistry,
+        timing::DEFAULT_SLOW_QUERY_THRESHOLD,
+        users::cache::UserCache,
+    },
+};
+use semver::Version;
+use serde::Deserialize;
+use thiserror::Error;
+use tracing_subscriber::EnvFilter;
+use uuid::Uuid;
+
+use crate::{
+    api::services::{
+        email_changes::EmailChangeSettings, invitations::InvitationSettings,
+        phone_verifications::PhoneVerificationSettings,
+        registration::RegistrationSettings, sessions::SessionSettings,
+    },
+    api::{
+        auth::{Authenticator, Principal},
+        middleware::sdk::SdkPolicy,
+        tenancy::TenantResolver,
+    },
+    backup,
+    cron::Schedule,
+    funnel::LoginFunnel,
+    maintenance::{MIN_SIGNING_KEY_AGE, MaintenanceTask},
+    redis::Redis,
+};
+
+pub const CONFIG_FILE_ENV: &str = "IDENTIFY_CONFIG";
+pub const DEFAULT_CONFIG_FILE: &str = "identify.toml";
+
+const ENV_PREFIX: &str = "IDENTIFY";
+const ENV_SEPARATOR: &str = "__";
+
+/// Minimum length of the token signing secret.
+const MIN_SECRET_LENGTH: usize = 32;
+
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("Failed to load the configuration: {0}")]
+    Load(#[from] config::ConfigError),
+
+    #[error("Invalid configuration:\n  - {}", .0.join("\n  - "))]
+    Invalid(Vec<String>),
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub server: ServerConfig,
+    pub cors: CorsConfig,
+    pub security_headers: SecurityHeadersConfig,
+    pub database: DatabaseConfig,
+    pub tenancy: TenancyConfig,
+    pub auth: AuthConfig,
+    pub authorization: AuthorizationConfig,
+    pub rate_limit: RateLimitConfig,
+    pub sdk: SdkConfig,
+    pub users: UsersConfig,
+    pub identifiers: IdentifiersConfig,
+    pub devices: DevicesConfig,
+    pub consents: ConsentsConfig,
+    pub invitations: InvitationsConfig,
+    pub email_changes: EmailChangesConfig,
+    pub registration: RegistrationConfig,
+    pub quotas: QuotasConfig,
+    pub email: EmailConfig,
+    pub sms: SmsConfig,
+    pub blob_storage: BlobStorageConfig,
+    pub encryption: EncryptionConfig,
+    pub digest: DigestConfig,
+    pub webhooks: WebhooksConfig,
+    pub read_model: ReadModelConfig,
+    pub logins: LoginsConfig,
+    pub event_stream: EventStreamConfig,
+    pub maintenance: MaintenanceConfig,
+    pub backup: BackupConfig,
+    pub user_cache: UserCacheConfig,
+    pub sessions: SessionsConfig,
+    pub redis: RedisConfig,
+    pub streaming: StreamingConfig,
+    pub logging: LoggingConfig,
+    pub error_reporting: ErrorReportingConfig,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct ServerConfig {
+    /// Address the HTTP server listens on.
+    pub bind_address: SocketAddr,
+    /// Whether to expose the latency breakdown of requests in the `Server-Timing` header.
+    ///
+    /// Enabled in debug builds by default.
+    pub server_timing: bool,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        ServerConfig {
+            bind_address: SocketAddr::from(([0, 0, 0, 0], 3000)),
+            server_timing: cfg!(debug_assertions),
+        }
+    }
+}
+
+/// Cross-origin requests of browser-based clients.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct CorsConfig {
+    /// Origins allowed to call the API, e.g. `https://app.example.com`, or `*` for any origin.
+    ///
+    /// Cross-origin requests are not allowed at all if it's empty.
+    pub allowed_origins: Vec<String>,
+    /// Methods allowed in cross-origin requests.
+    pub allowed_methods: Vec<String>,
+    /// Request headers allowed in cross-origin requests.
+    pub allowed_headers: Vec<String>,
+    /// How long browsers can cache the results of preflight requests, in seconds.
+    pub max_age_secs: u64,
+}
+
+impl Default for CorsConfig {
+    fn default() -> Self {
+        CorsConfig {
+            allowed_origins: Vec::new(),
+            allowed_methods: ["GET", "POST", "PUT", "PATCH", "DELETE"]
+                .map(String::from)
+                .into(),
+            allowed_headers: [
+                "authorization",
+                "content-type",
+                "if-match",
+                "if-none-match",
+                "x-identify-sdk",
+            ]
+            .map(String::from)
+            .into(),
+            max_age_secs: 600,
+        }
+    }
+}
+
+impl CorsConfig {
+    /// Whether any origin is allowed.
+    pub fn allows_any_origin(&self) -> bool {
+        self.allowed_origins.iter().any(|origin| origin == "*")
+    }
+
+    /// Parses the allowed origins.
+    pub fn origins(&self) -> Result<Vec<HeaderValue>, String> {
+        self.allowed_origins
+            .iter()
+            .map(|origin| {
+                HeaderValue::from_str(origin.trim_end_matches('/'))
+                    .map_err(|_| invalid_cors_entry("allowed_origins", origin))
+            })
+            .collect()
+    }
+
+    /// Parses the allowed methods.
+    pub fn methods(&self) -> Result<Vec<Method>, String> {
+        self.allowed_methods
+            .iter()
+            .map(|method| {
+                Method::from_bytes(method.to_ascii_uppercase().as_bytes())
+                    .map_err(|_| invalid_cors_entry("allowed_methods", method))
+            })
+            .collect()
+    }
+
+    /// Parses the allowed request headers.
+    pub fn headers(&self) -> Result<Vec<HeaderName>, String> {
+        self.allowed_headers
+            .iter()
+            .map(|header| {
+                HeaderName::from_bytes(header.as_bytes())
+                    .map_err(|_| invalid_cors_entry("allowed_headers", header))
+            })
+            .collect()
+    }
+}
+
+fn invalid_cors_entry(field: &str, value: &str) -> String {
+    format!("cors.{field} contains an invalid value '{value}'")
+}
+
+/// Headers that harden the responses against common browser attacks.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct SecurityHeadersConfig {
+    /// How long browsers should only connect over HTTPS (`Strict-Transport-Security`), in
+    /// seconds. `0` disables the header, e.g. for local development over plain HTTP.
+    pub hsts_max_age_secs: u64,
+    /// Whether HTTPS is enforced for the subdomains as well.
+    pub hsts_include_subdomains: bool,
+}
+
+impl Default for SecurityHeadersConfig {
+    fn default() -> Self {
+        SecurityHeadersConfig {
+            hsts_max_age_secs: 31_536_000,
+            hsts_include_subdomains: true,
+        }
+    }
+}
+
+/// Database connection settings.
+///
+/// Pool options that are not set fall back to the defaults of the database backend, see
+/// [PoolConfig::for_url].
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct DatabaseConfig {
+    /// Database connection URL.
+    pub url: String,
+    /// URL of a read replica of the database that serves lookups and listings. Defaults to
+    /// [url](Self::url), which is then read through a separate pool of read-only connections.
+    pub read_url: Option<String>,
+    /// Minimum number of connections kept in the pool.
+    pub min_connections: Option<u32>,
+    /// Maximum number of connections the pool can open.
+    pub max_connections: Option<u32>,
+    /// How long to wait for a free connection, in seconds.
+    pub acquire_timeout_secs: Option<u64>,
+    /// Maximum lifetime of a connection, in seconds. `0` disables the limit.
+    pub max_lifetime_secs: Option<u64>,
+    /// How long a connection can stay idle, in seconds. `0` disables the limit.
+    pub idle_timeout_secs: Option<u64>,
+    /// Whether to check that connections are alive before using them.
+    pub test_before_acquire: Option<bool>,
+    /// How many times to try to connect on startup while the database is unavailable.
+    pub connect_attempts: Option<u32>,
+    /// Delay after the first failed connection attempt, in milliseconds.
+    pub connect_backoff_ms: Option<u64>,
+    /// Upper bound of the delay between connection attempts, in milliseconds.
+    pub connect_max_backoff_ms: Option<u64>,
+    /// Whether to use the SQLite write-ahead log.
+    pub wal: Option<bool>,
+    /// How long to wait for a locked database, in milliseconds.
+    pub busy_timeout_ms: Option<u64>,
+    /// Whether to enforce foreign key constraints.
+    pub foreign_keys: Option<bool>,
+    /// Maximum number of prepared statements cached by every connection.
+    pub statement_cache_capacity: Option<usize>,
+    /// Whether to prepare the statements of the hottest queries when connections are opened.
+    pub warm_up_statements: Option<bool>,
+    /// How many times a transaction that failed because the database was busy is attempted at
+    /// most. `1` disables retries.
+    pub retry_attempts: Option<u32>,
+    /// Delay before the first retry, in milliseconds.
+    pub retry_backoff_ms: Option<u64>,
+    /// Upper bound of the delay between retries, in milliseconds.
+    pub retry_max_backoff_ms: Option<u64>,
+    /// Retries earned by every transaction, e.g. `0.1` allows one retry for every ten.
+    pub retry_budget_ratio: Option<f64>,
+    /// Retries that can be made before any were earned.
+    pub retry_budget_reserve: Option<u32>,
+    /// How long a query can take before it's logged as a warning, in milliseconds. `0`
+    /// disables the warnings.
+    pub slow_query_ms: Option<u64>,
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        DatabaseConfig {
+            url: "sqlite://data.db".into(),
+            read_url: None,
+            min_connections: None,
+            max_connections: None,
+            acquire_timeout_secs: None,
+            max_lifetime_secs: None,
+            idle_timeout_secs: None,
+            test_before_acquire: None,
+            connect_attempts: None,
+            connect_backoff_ms: None,
+            connect_max_backoff_ms: None,
+            wal: None,
+            busy_timeout_ms: None,
+            foreign_keys: None,
+            statement_cache_capacity: None,
+            warm_up_statements: None,
+            retry_attempts: None,
+            retry_backoff_ms: None,
+            retry_max_backoff_ms: None,
+            retry_budget_ratio: None,
+            retry_budget_reserve: None,
+            slow_query_ms: None,
+        }
+    }
+}
+
+impl DatabaseConfig {
+    /// Returns the URL of the database the reads are routed to.
+    pub fn read_url(&self) -> &str {
+        self.read_url.as_deref().unwrap_or(&self.url)
+    }
+
+    /// Builds the pool configuration from the backend defaults and the configured overrides.
+    pub fn pool_config(&self) -> PoolConfig {
+        let defaults = PoolConfig::for_url(&self.url);
+        let optional_secs =
+            |secs| (secs > 0).then(|| Duration::from_secs(secs));
+
+        PoolConfig {
+            min_connections: self
+                .min_connections
+                .unwrap_or(defaults.min_connections),
+            max_connections: self
+                .max_connections
+                .unwrap_or(defaults.max_connections),
+            acquire_timeout: self
+                .acquire_timeout_secs
+                .map_or(defaults.acquire_timeout, Duration::from_secs),
+            max_lifetime: self
+                .max_lifetime_secs
+                .map_or(defaults.max_lifetime, optional_secs),
+            idle_timeout: self
+                .idle_timeout_secs
+                .map_or(defaults.idle_timeout, optional_secs),
+            test_before_acquire: self
+                .test_before_acquire
+                .unwrap_or(defaults.test_before_acquire),
+            reconnect: ReconnectPolicy {
+                max_attempts: self
+                    .connect_attempts
+                    .unwrap_or(defaults.reconnect.max_attempts),
+                initial_backoff: self.connect_backoff_ms.map_or(
+                    defaults.reconnect.initial_backoff,
+                    Duration::from_millis,
+                ),
+                max_backoff: self.connect_max_backoff_ms.map_or(
+                    defaults.reconnect.max_backoff,
+                    Duration::from_millis,
+                ),
+            },
+            pragmas: SqlitePragmas {
+                wal: self.wal.unwrap_or(defaults.pragmas.wal),
+                busy_timeout: self.busy_timeout_ms.map_or(
+                    defaults.pragmas.busy_timeout,
+                    Duration::from_millis,
+                ),
+                foreign_keys: self
+                    .foreign_keys
+                    .unwrap_or(defaults.pragmas.foreign_keys),
+            },
+            statement_cache_capacity: self
+                .statement_cache_capacity
+                .unwrap_or(defaults.statement_cache_capacity),
+            warm_up_statements: self
+                .warm_up_statements
+                .unwrap_or(defaults.warm_up_statements),
+        }
+    }
+
+    /// Returns how long a query can take before it's logged as a warning, if it is.
+    pub fn slow_query_threshold(&self) -> Option<Duration> {
+        self.slow_query_ms
+            .map_or(Some(DEFAULT_SLOW_QUERY_THRESHOLD), |ms| {
+                (ms > 0).then(|| Duration::from_millis(ms))
+            })
+    }
+
+    /// Builds the policy of retrying transactions from the defaults and the configured
+    /// overrides.
+    pub fn retry_policy(&self) -> RetryPolicy {
+        let defaults = RetryPolicy::default();
+
+        RetryPolicy {
+            max_attempts: self.retry_attempts.unwrap_or(defaults.max_attempts),
+            initial_backoff: self
+                .retry_backoff_ms
+                .map_or(defaults.initial_backoff, Duration::from_millis),
+            max_backoff: self
+                .retry_max_backoff_ms
+                .map_or(defaults.max_backoff, Duration::from_millis),
+            budget_ratio: self
+                .retry_budget_ratio
+                .unwrap_or(defaults.budget_ratio),
+            budget_reserve: self
+                .retry_budget_reserve
+                .unwrap_or(defaults.budget_reserve),
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct AuthConfig {
+    /// Static API keys that grant access to the API.
+    pub api_keys: Vec<ApiKeyConfig>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ApiKeyConfig {
+    /// The key itself that is passed by clients as a bearer token.
+    pub key: Secret,
+    /// Name of the key owner.
+    pub subject: String,
+    /// Whether the key grants unrestricted access.
+    #[serde(default)]
+    pub admin: bool,
+    /// Permissions granted by the key, e.g. `users:read`.
+    #[serde(default)]
+    pub permissions: Vec<String>,
+    /// ID of the organization whose tenant the key is limited to, if organizations have
+    /// databases of their own. Keys name organizations by their IDs, which stay the same when
+    /// they're renamed.
+    #[serde(default)]
+    pub organization_id: Option<Uuid>,
+    /// ID of the user the key acts on behalf of. The key is granted the permissions of the
+    /// roles assigned to the user too.
+    #[serde(default)]
+    pub user_id: Option<Uuid>,
+}
+
+impl AuthConfig {
+    pub fn authenticator(&self) -> Result<Authenticator, String> {
+        let keys = self
+            .api_keys
+            .iter()
+            .map(|api_key| {
+                let principal = Principal {
+                    subject: api_key.subject.clone(),
+                    admin: api_key.admin,
+                    permissions: api_key.permissions.clone(),
+                    organization_id: api_key.organization_id,
+                    user_id: api_key.user_id,
+                };
+
+                (api_key.key.expose().to_owned(), principal)
+            })
+            .collect::<Vec<_>>();
+
+        Ok(Authenticator::new(keys))
+    }
+}
+
+/// Declarative policies that allow or deny callers actions on resources, on top of the
+/// permissions of their API keys.
+///
+/// The policies stored in the database are loaded along with these on startup.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct AuthorizationConfig {
+    pub policies: Vec<AuthorizationPolicyConfig>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AuthorizationPolicyConfig {
+    /// Unique name of the policy, logged with the requests it denies.
+    pub name: String,
+    /// `allow` or `deny`.
+    pub effect: String,
+    /// Patterns of the subjects of the API keys, in which `*` matches anything.
+    pub subjects: Vec<String>,
+    /// Patterns of the actions, e.g. `users:*`.
+    pub actions: Vec<String>,
+    /// Patterns of the resources, e.g. `user:*`.
+    pub resources: Vec<String>,
+    /// Conditions on the attributes of requests, which must all hold.
+    #[serde(default)]
+    pub conditions: Vec<PolicyConditionConfig>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PolicyConditionConfig {
+    /// Attribute of the request, e.g. `subject` or `resource.email_domain`.
+    pub attribute: String,
+    /// `in`, `not_in` or `same_as`.
+    pub operator: String,
+    pub values: Vec<String>,
+}
+
+impl AuthorizationConfig {
+    pub fn policies(&self) -> Result<Vec<AuthorizationPolicy>, String> {
+        let policies = self
+            .policies
+            .iter()
+            .map(AuthorizationPolicyConfig::policy)
+            .collect::<Result<Vec<_>, _>>()?;
+        AuthorizationPolicies::new(policies.clone())
+            .map_err(|e| format!("authorization.policies: {e}"))?;
+
+        Ok(policies)
+    }
+}
+
+impl AuthorizationPolicyConfig {
+    fn policy(&self) -> Result<AuthorizationPolicy, String> {
+        let error = |e: DomainError| {
+            format!("authorization.policies.{}: {e}", self.name)
+        };
+        let conditions = self
+            .conditions
+            .iter()
+            .map(|condition| {
+                Ok(PolicyCondition {
+                    attribute: condition.attribute.clone(),
+                    operator: condition.operator.parse().map_err(error)?,
+                    values: condition.values.clone(),
+                })
+            })
+            .collect::<Result<_, String>>()?;
+
+        AuthorizationPolicy::new(
+            self.name.clone(),
+            self.effect.parse().map_err(error)?,
+            self.subjects.clone(),
+            self.actions.clone(),
+            self.resources.clone(),
+            conditions,
+        )
+        .map_err(error)
+    }
+}
+
+/// Limits of the request rate of every caller.
+///
+/// Callers are identified by their API key, or by their IP address if they don't have one. Every
+/// client IP address is also limited before its requests are authenticated.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct RateLimitConfig {
+    /// Whether requests are rate-limited at all.
+    pub enabled: bool,
+    /// Where the token buckets are kept.
+    pub store: RateLimitStoreKind,
+    /// Number of shards of the `sharded` store, rounded up to a power of two. Defaults to a
+    /// few shards per CPU.
+    pub shards: Option<usize>,
+    /// Limits of regular routes.
+    pub default: RateLimitBucketConfig,
+    /// Limits of sensitive routes, e.g. the ones that deal with credentials.
+    pub strict: RateLimitBucketConfig,
+    /// Limits of every client IP address, enforced before the requests are authenticated, so
+    /// that requests with invalid API keys are limited too.
+    pub clients: RateLimitBucketConfig,
+    /// Networks of the proxies trusted to report the addresses of clients in `X-Forwarded-For`,
+    /// in CIDR notation, e.g. `10.0.0.0/8`. The header is ignored in requests of other peers.
+    pub trusted_proxies: Vec<String>,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        RateLimitConfig {
+            enabled: true,
+            store: RateLimitStoreKind::default(),
+            shards: None,
+            default: RateLimitBucketConfig {
+                capacity: 100,
+                refill_per_second: 10.0,
+            },
+            strict: RateLimitBucketConfig {
+                capacity: 10,
+                refill_per_second: 0.1,
+            },
+            clients: RateLimitBucketConfig {
+                capacity: 300,
+                refill_per_second: 30.0,
+            },
+            trusted_proxies: Vec::new(),
+        }
+    }
+}
+
+/// Token bucket every caller gets.
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct RateLimitBucketConfig {
+    /// Maximum number of requests in a burst.
+    pub capacity: u32,
+    /// Number of requests allowed every second on average.
+    pub refill_per_second: f64,
+}
+
+/// Where the token buckets of the rate limiter are kept.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RateLimitStoreKind {
+    /// In the memory of the process, the fastest option for a single process.
+    #[default]
+    Memory,
+    /// In the memory of the process, split into shards that are locked independently, for
+    /// servers that handle many requests per second.
+    Sharded,
+    /// In the database, so that the limits are shared by all the processes that use it.
+    Sqlite,
+    /// In Redis, so that the limits are shared by all the processes that use the server.
+    Redis,
+}
+
+/// Client SDKs that identify themselves in the `X-Identify-SDK` header.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct SdkConfig {
+    /// Minimum versions of the SDKs still allowed to call the API, by their names, e.g.
+    /// `identify-js = "1.2.0"`. Older versions are refused with `426 Upgrade Required`.
+    pub min_versions: HashMap<String, String>,
+    /// SDKs whose adoption is tracked by name, besides the ones with minimum versions. The
+    /// others are tracked under a single `other` label.
+    pub names: Vec<String>,
+}
+
+impl SdkConfig {
+    pub fn policy(&self) -> Result<SdkPolicy, String> {
+        let minimum_versions = self
+            .min_versions
+            .iter()
+            .map(|(name, version)| {
+                let version = Version::parse(version.trim()).map_err(|e| {
+                    format!(
+                        "sdk.min_versions.{name} is not a valid version: {e}"
+                    )
+                })?;
+
+                Ok((name.clone(), version))
+            })
+            .collect::<Result<_, String>>()?;
+
+        Ok(SdkPolicy::new(minimum_versions)
+            .with_known_names(self.names.iter().cloned()))
+    }
+}
+
+/// Emails sent to users, e.g. by re-verification campaigns.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct EmailConfig {
+    /// How the emails are sent.
+    pub transport: EmailTransportKind,
+    /// Sender of the emails, e.g. `Identify <no-reply@example.com>`.
+    pub from: String,
+    /// Directory of templates that replace the built-in ones, named after the kind of the email
+    /// and the part they render, e.g. `verification.subject`, `verification.txt` and
+    /// `verification.html`. Missing files keep the built-in templates.
+    ///
+    /// Translations are kept in subdirectories named after their locales, e.g.
+    /// `pt-BR/verification.subject`, and are sent to the users who prefer the locales.
+    pub templates_dir: Option<PathBuf>,
+    pub smtp: SmtpConfig,
+}
+
+impl Default for EmailConfig {
+    fn default() -> Self {
+        EmailConfig {
+            transport: EmailTransportKind::default(),
+            from: "Identify <no-reply@localhost>".to_owned(),
+            templates_dir: None,
+            smtp: SmtpConfig::default(),
+        }
+    }
+}
+
+impl EmailConfig {
+    /// Creates the sender of the emails. Must be called within the runtime, as the SMTP sender
+    /// manages its connections in the background.
+    pub fn sender(&self) -> Result<Arc<dyn EmailSender>, String> {
+        match self.transport {
+            EmailTransportKind::Log => Ok(Arc::new(LogEmailSender)),
+            EmailTransportKind::Smtp => {
+                let sender = SmtpEmailSender::new(&self.smtp_settings())
+                    .map_err(|e| format!("email.smtp: {e:#}"))?;
+
+                Ok(Arc::new(sender))
+            }
+        }
+    }
+
+    pub fn smtp_settings(&self) -> SmtpSettings {
+        SmtpSettings {
+            host: self.smtp.host.clone(),
+            port: self.smtp.port,
+            security: self.smtp.security,
+            credentials: self.smtp.username.clone().zip(
+                self.smtp
+                    .password
+                    .as_ref()
+                    .map(|password| password.expose().to_owned()),
+            ),
+            timeout: Duration::from_secs(self.smtp.timeout_secs),
+            from: self.from.clone(),
+        }
+    }
+
+    /// Returns the built-in templates, replaced by the ones in the templates directory, and the
+    /// translations in its subdirectories.
+    pub fn templates(&self) -> Result<EmailTemplates, String> {
+        let mut templates = EmailTemplates::default();
+        let Some(dir) = &self.templates_dir else {
+            return Ok(templates);
+        };
+        if !dir.is_dir() {
+            return Err(format!(
+                "email.templates_dir '{}' is not a directory",
+                dir.display()
+            ));
+        }
+
+        read_templates(dir, |kind, part, source| {
+            templates.set(kind, part, source)
+        })?;
+
+        let entries = std::fs::read_dir(dir)
+            .map_err(|e| format!("failed to read '{}': {e}", dir.display()))?;
+        for entry in entries {
+            let path = entry
+                .map_err(|e| {
+                    format!("failed to read '{}': {e}", dir.display())
+                })?
+                .path();
+            if !path.is_dir() {
+                continue;
+            }
+
+            let locale = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| Locale::parse(name).ok())
+                .ok_or_else(|| {
+                    format!(
+                        "'{}' is not named after a locale, e.g. 'pt-BR'",
+                        path.display()
+                    )
+                })?;
+            read_templates(&path, |kind, part, source| {
+                templates.translate(locale.clone(), kind, part, source)
+            })?;
+        }
+
+        Ok(templates)
+    }
+}
+
+/// Reads the templates in the directory, named after the kind of the email and the part they
+/// render.
+fn read_templates(
+    dir: &Path,
+    mut add: impl FnMut(EmailKind, EmailPart, &str) -> Result<(), String>,
+) -> Result<(), String> {
+    for kind in EmailKind::ALL {
+        for part in EmailPart::ALL {
+            let path = dir.join(format!("{kind}.{}", part.extension()));
+            if !path.exists() {
+                continue;
+            }
+
+            let source = std::fs::read_to_string(&path).map_err(|e| {
+                format!("failed to read '{}': {e}", path.display())
+            })?;
+            add(kind, part, &source)
+                .map_err(|e| format!("'{}': {e}", path.display()))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// How the emails of the [EmailConfig] are sent.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EmailTransportKind {
+    /// Written to the log instead of being sent, e.g. in development.
+    #[default]
+    Log,
+    /// Sent through the SMTP server of `email.smtp`.
+    Smtp,
+}
+
+/// SMTP server the emails are sent through.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct SmtpConfig {
+    pub host: String,
+    pub port: u16,
+    /// `tls`, `starttls` or `none`.
+    #[serde(deserialize_with = "deserialize_smtp_security")]
+    pub security: SmtpSecurity,
+    /// Username the connection is authenticated with, together with the password.
+    pub username: Option<String>,
+    pub password: Option<Secret>,
+    /// How long to wait for the server to answer, in seconds.
+    pub timeout_secs: u64,
+}
+
+impl Default for SmtpConfig {
+    fn default() -> Self {
+        SmtpConfig {
+            host: "localhost".to_owned(),
+            port: 587,
+            security: SmtpSecurity::StartTls,
+            username: None,
+            password: None,
+            timeout_secs: 10,
+        }
+    }
+}
+
+fn deserialize_smtp_security<'de, D>(
+    deserializer: D,
+) -> Result<SmtpSecurity, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let security = String::deserialize(deserializer)?;
+    match security.as_str() {
+        "tls" => Ok(SmtpSecurity::Tls),
+        "starttls" => Ok(SmtpSecurity::StartTls),
+        "none" => Ok(SmtpSecurity::None),
+        _ => Err(serde::de::Error::unknown_variant(
+            &security,
+            &["tls", "starttls", "none"],
+        )),
+    }
+}
+
+/// Text messages sent to users, e.g. the codes their phone numbers are verified with.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct SmsConfig {
+    /// How the messages are sent.
+    pub transport: SmsTransportKind,
+    /// How long users have to enter the codes they were texted, in minutes.
+    pub verification_valid_for_minutes: u32,
+}
+
+impl Default for SmsConfig {
+    fn default() -> Self {
+        SmsConfig {
+            transport: SmsTransportKind::default(),
+            verification_valid_for_minutes: 10,
+        }
+    }
+}
+
+impl SmsConfig {
+    pub fn sender(&self) -> Arc<dyn SmsSender> {
+        match self.transport {
+            SmsTransportKind::Log => Arc::new(LogSmsSender),
+        }
+    }
+
+    pub fn phone_verification_settings(&self) -> PhoneVerificationSettings {
+        PhoneVerificationSettings {
+            valid_for: TimeDelta::minutes(i64::from(
+                self.verification_valid_for_minutes,
+            )),
+        }
+    }
+}
+
+/// How the messages of the [SmsConfig] are sent.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SmsTransportKind {
+    /// Written to the log instead of being sent, until a gateway is supported.
+    #[default]
+    Log,
+}
+
+/// Storage of blobs, e.g. the avatars of users.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct BlobStorageConfig {
+    /// Where the blobs are stored.
+    pub backend: BlobStorageKind,
+    /// Directory of the blobs stored on the filesystem.
+    pub path: PathBuf,
+    pub s3: S3Config,
+}
+
+impl Default for BlobStorageConfig {
+    fn default() -> Self {
+        BlobStorageConfig {
+            backend: BlobStorageKind::default(),
+            path: PathBuf::from("blobs"),
+            s3: S3Config::default(),
+        }
+    }
+}
+
+impl BlobStorageConfig {
+    pub fn storage(&self) -> Result<Arc<dyn BlobStorage>, String> {
+        match self.backend {
+            BlobStorageKind::Filesystem => {
+                Ok(Arc::new(FilesystemBlobStorage::new(&self.path)))
+            }
+            BlobStorageKind::S3 => self.s3_storage(),
+        }
+    }
+
+    #[cfg(feature = "s3")]
+    fn s3_storage(&self) -> Result<Arc<dyn BlobStorage>, String> {
+        use identify_infrastructure::blobs::s3::{S3BlobStorage, S3Settings};
+
+        let storage = S3BlobStorage::new(S3Settings {
+            endpoint: self.s3.endpoint.clone(),
+            bucket: self.s3.bucket.clone(),
+            region: self.s3.region.clone(),
+            access_key_id: self.s3.access_key_id.clone().unwrap_or_default(),
+            secret_access_key: self
+                .s3
+                .secret_access_key
+                .as_ref()
+                .map(|secret| secret.expose().to_owned())
+                .unwrap_or_default(),
+            path_style: self.s3.path_style,
+            timeout: Duration::from_secs(self.s3.timeout_secs),
+        })
+        .map_err(|e| format!("blob_storage.s3: {e:#}"))?;
+
+        Ok(Arc::new(storage))
+    }
+
+    #[cfg(not(feature = "s3"))]
+    fn s3_storage(&self) -> Result<Arc<dyn BlobStorage>, String> {
+        Err("blob_storage.backend is 's3', but the server is built without the 's3' feature".to_owned())
+    }
+}
+
+/// Where the blobs of the [BlobStorageConfig] are stored.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BlobStorageKind {
+    /// In files under `blob_storage.path`, which replicas must share.
+    #[default]
+    Filesystem,
+    /// In the S3-compatible bucket of `blob_storage.s3`. Requires the `s3` feature.
+    S3,
+}
+
+/// S3-compatible bucket the blobs are stored in.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct S3Config {
+    /// URL of the service, e.g. `https://s3.eu-west-1.amazonaws.com`.
+    pub endpoint: String,
+    pub bucket: String,
+    pub region: String,
+    pub access_key_id: Option<String>,
+    pub secret_access_key: Option<Secret>,
+    /// Whether to address the bucket in the path of the URLs rather than in their host, as
+    /// most self-hosted services (e.g. MinIO) require.
+    pub path_style: bool,
+    /// How long to wait for the service to answer, in seconds.
+    pub timeout_secs: u64,
+}
+
+impl Default for S3Config {
+    fn default() -> Self {
+        S3Config {
+            endpoint: String::new(),
+            bucket: String::new(),
+            region: "us-east-1".to_owned(),
+            access_key_id: None,
+            secret_access_key: None,
+            path_style: false,
+            timeout_secs: 10,
+        }
+    }
+}
+
+/// Encryption of the emails and names of users at rest, see
+/// [identify_infrastructure::encryption].
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct EncryptionConfig {
+    /// ID of the key new values are encrypted with. The encryption is disabled if it's not set.
+    pub current_key: Option<String>,
+    /// Keys by their IDs, each 32 bytes in base64. Keys must be kept as long as values are
+    /// encrypted with them, i.e. until `identify-admin reencrypt-users` has run with a new
+    /// current key.
+    pub keys: HashMap<String, Secret>,
+    /// Key of the blind indexes of emails, 32 bytes in base64. Changing it breaks the lookups by
+    /// email until the indexes are rebuilt by `identify-admin reencrypt-users`.
+    pub index_key: Option<Secret>,
+}
+
+impl EncryptionConfig {
+    /// Returns the provider of the keys, or `None` if the encryption is disabled.
+    pub fn key_provider(&self) -> Result<Option<StaticKeyProvider>, String> {
+        let Some(current_key) = &self.current_key else {
+            if !self.keys.is_empty() {
+                return Err(
+                    "encryption.current_key must be set when encryption.keys are"
+                        .to_owned(),
+                );
+            }
+            return Ok(None);
+        };
+        let Some(index_key) = &self.index_key else {
+            return Err("encryption.index_key must be set when encryption.current_key is".to_owned());
+        };
+
+        let decode = |name: &str, secret: &Secret| {
+            EncryptionKey::from_base64(secret.expose())
+                .map_err(|e| format!("encryption.{name}: {e}"))
+        };
+        let keys = self
+            .keys
+            .iter()
+            .map(|(id, key)| {
+                Ok((id.clone(), decode(&format!("keys.{id}"), key)?))
+            })
+            .collect::<Result<_, String>>()?;
+        let ring = KeyRing::new(
+            current_key.clone(),
+            keys,
+            decode("index_key", index_key)?,
+        )
+        .map_err(|e| format!("encryption: {e}"))?;
+
+        Ok(Some(StaticKeyProvider::new(ring)))
+    }
+}
+
+/// Periodic digest of notable events, e.g. suspended users or failed operations, sent to
+/// admins.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct DigestConfig {
+    /// Whether digests are sent at all.
+    pub enabled: bool,
+    /// How often digests are sent. Every digest covers the period since the previous one.
+    pub period: DigestPeriod,
+    /// URL digests are posted to as JSON.
+    pub webhook_url: Option<String>,
+    /// Email addresses of the admins digests are sent to, through the configured email
+    /// transport.
+    pub recipients: Vec<String>,
+    /// Categories of events left out of the digests, e.g. `verifications`.
+    pub opt_out: Vec<String>,
+}
+
+impl DigestConfig {
+    /// Returns the categories digests include, i.e. all but the opted out ones.
+    pub fn categories(&self) -> Result<Vec<DigestCategory>, String> {
+        let opted_out = self
+            .opt_out
+            .iter()
+            .map(|category| {
+                category.parse().map_err(|e| format!("digest.opt_out: {e}"))
+            })
+            .collect::<Result<HashSet<DigestCategory>, _>>()?;
+
+        Ok(DigestCategory::ALL
+            .into_iter()
+            .filter(|category| !opted_out.contains(category))
+            .collect())
+    }
+}
+
+/// How often admin digests are sent.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DigestPeriod {
+    /// Every day at midnight UTC.
+    #[default]
+    Daily,
+    /// Every Monday at midnight UTC.
+    Weekly,
+}
+
+/// Users of the service.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct UsersConfig {
+    /// How usernames are generated for the users created without one, e.g.
+    /// `email_local_part`.
+    pub username_strategy: String,
+}
+
+impl Default for UsersConfig {
+    fn default() -> Self {
+        UsersConfig {
+            username_strategy: UsernameStrategy::default().to_string(),
+        }
+    }
+}
+
+impl UsersConfig {
+    pub fn username_strategy(&self) -> Result<UsernameStrategy, String> {
+        self.username_strategy
+            .parse()
+            .map_err(|e| format!("users.username_strategy: {e}"))
+    }
+}
+
+/// Identifiers nobody can choose, e.g. usernames like `admin` or offensive names of
+/// organizations.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct IdentifiersConfig {
+    /// File with the words to reserve in addition to the built-in ones, one per line.
+    pub reserved_file: Option<PathBuf>,
+    /// File with the words identifiers must not contain, one per line. Nothing is filtered if
+    /// it's not set.
+    pub profanity_file: Option<PathBuf>,
+    /// How often to check the files for changes, or `0` to load them only on startup.
+    pub reload_interval_secs: u64,
+}
+
+impl Default for IdentifiersConfig {
+    fn default() -> Self {
+        IdentifiersConfig {
+            reserved_file: None,
+            profanity_file: None,
+            reload_interval_secs: 30,
+        }
+    }
+}
+
+impl IdentifiersConfig {
+    pub fn reload_interval(&self) -> Option<Duration> {
+        (self.reload_interval_secs > 0)
+            .then(|| Duration::from_secs(self.reload_interval_secs))
+    }
+}
+
+/// Devices users log in from.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct DevicesConfig {
+    /// Table of networks the locations of the devices are looked up in, see
+    /// [NetworkGeoResolver]. Locations are unknown if it's not set.
+    pub geo_file: Option<PathBuf>,
+}
+
+impl DevicesConfig {
+    pub fn geo_resolver(&self) -> Result<Arc<dyn GeoResolver>, String> {
+        match &self.geo_file {
+            Some(path) => NetworkGeoResolver::from_file(path)
+                .map(|resolver| Arc::new(resolver) as Arc<dyn GeoResolver>)
+                .map_err(|e| format!("devices.geo_file: {e:#}")),
+            None => Ok(Arc::new(NoGeoResolver)),
+        }
+    }
+}
+
+/// Policies users have to accept before they can log in, e.g. the terms of service.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct ConsentsConfig {
+    /// Current versions of the policies by their names, e.g. `terms = "2026-10"`. Users who
+    /// accepted an older version have to accept the current one again.
+    pub policies: HashMap<String, String>,
+}
+
+impl ConsentsConfig {
+    pub fn policies(&self) -> Result<ConsentPolicies, String> {
+        ConsentPolicies::new(self.policies.clone())
+            .map_err(|e| format!("consents.policies: {e}"))
+    }
+}
+
+/// Invitations for people without an account to join organizations.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct InvitationsConfig {
+    /// URL of the page invitees accept invitations on, which the emailed links point to. The
+    /// ID of the invitation and its token are appended as the `invitation` and `token` query
+    /// parameters.
+    pub accept_url: String,
+    /// How long invitations can be accepted for, in hours.
+    pub valid_for_hours: u32,
+}
+
+impl Default for InvitationsConfig {
+    fn default() -> Self {
+        InvitationsConfig {
+            accept_url: "http://localhost:3000/invitations/accept".to_owned(),
+            valid_for_hours: 7 * 24,
+        }
+    }
+}
+
+impl InvitationsConfig {
+    pub fn settings(&self) -> InvitationSettings {
+        InvitationSettings {
+            accept_url: self.accept_url.clone(),
+            valid_for: TimeDelta::hours(i64::from(self.valid_for_hours)),
+        }
+    }
+}
+
+/// Changes of the emails of users, confirmed from the new addresses.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct EmailChangesConfig {
+    /// URL of the page users confirm the changes of their emails on, which the links emailed to
+    /// the new addresses point to. The ID of the change and its token are appended as the
+    /// `change` and `token` query parameters.
+    pub confirm_url: String,
+    /// How long changes can be confirmed for, in hours.
+    pub valid_for_hours: u32,
+}
+
+impl Default for EmailChangesConfig {
+    fn default() -> Self {
+        EmailChangesConfig {
+            confirm_url: "http://localhost:3000/confirm-email".to_owned(),
+            valid_for_hours: 24,
+        }
+    }
+}
+
+impl EmailChangesConfig {
+    pub fn settings(&self) -> EmailChangeSettings {
+        EmailChangeSettings {
+            confirm_url: self.confirm_url.clone(),
+            valid_for: TimeDelta::hours(i64::from(self.valid_for_hours)),
+        }
+    }
+}
+
+/// Limits of what organizations can have.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct QuotasConfig {
+    /// Most users an organization can have, unless it has a quota of its own. Unlimited if
+    /// it's not set.
+    pub max_users_per_organization: Option<u32>,
+}
+
+impl QuotasConfig {
+    pub fn policy(&self) -> QuotaPolicy {
+        QuotaPolicy::new(self.max_users_per_organization)
+    }
+}
+
+/// Registration of users by themselves, e.g. through a sign-up page.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct RegistrationConfig {
+    /// Who can register: `open` for anyone, `invite_only` for nobody, or `allowed_domains` for
+    /// the people whose emails are in one of the allowed domains.
+    pub mode: String,
+    /// Domains of the emails that can register in the `allowed_domains` mode, e.g. `acme.test`.
+    pub allowed_domains: Vec<String>,
+    /// URL of the page users verify their emails on, which the emailed links point to. The ID
+    /// of the verification and its token are appended as the `verification` and `token` query
+    /// parameters.
+    pub verify_url: String,
+    /// How long users have to verify their emails, in hours.
+    pub verification_valid_for_hours: u32,
+}
+
+impl Default for RegistrationConfig {
+    fn default() -> Self {
+        RegistrationConfig {
+            mode: "invite_only".to_owned(),
+            allowed_domains: Vec::new(),
+            verify_url: "http://localhost:3000/verify-email".to_owned(),
+            verification_valid_for_hours: 48,
+        }
+    }
+}
+
+impl RegistrationConfig {
+    pub fn policy(&self) -> Result<RegistrationPolicy, String> {
+        let mode = self
+            .mode
+            .parse()
+            .map_err(|e| format!("registration.mode: {e}"))?;
+
+        RegistrationPolicy::new(mode, self.allowed_domains.clone())
+            .map_err(|e| format!("registration.allowed_domains: {e}"))
+    }
+
+    pub fn settings(&self) -> Result<RegistrationSettings, String> {
+        Ok(RegistrationSettings {
+            policy: Arc::new(self.policy()?),
+            verify_url: self.verify_url.clone(),
+            verification_valid_for: TimeDelta::hours(i64::from(
+                self.verification_valid_for_hours,
+            )),
+        })
+    }
+}
+
+/// Delivery of user events to the webhook endpoints registered through the API.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct WebhooksConfig {
+    /// Whether events are delivered at all. Events recorded while it's disabled are delivered
+    /// once it's enabled.
+    pub enabled: bool,
+    /// How often new events and due retries are looked for, in seconds.
+    pub poll_interval_secs: u64,
+    /// How long to wait for an endpoint to accept an event, in seconds.
+    pub timeout_secs: u64,
+    /// Attempts after which a delivery gives up and becomes a dead letter.
+    pub max_attempts: u32,
+    /// Delay after the first failed attempt, in seconds. Every following delay is twice as
+    /// long.
+    pub initial_backoff_secs: u64,
+    /// Longest delay between two attempts, in seconds.
+    pub max_backoff_secs: u64,
+    /// Whether endpoints can have plain HTTP URLs, e.g. of receivers in a trusted network.
+    /// Events are only delivered to HTTPS ones otherwise.
+    pub allow_http: bool,
+    /// Whether events can be delivered to loopback, private or otherwise non-public addresses,
+    /// e.g. of receivers in the same network. Endpoints could reach the internal services
+    /// around the server otherwise.
+    pub allow_private_networks: bool,
+}
+
+impl Default for WebhooksConfig {
+    fn default() -> Self {
+        WebhooksConfig {
+            enabled: true,
+            poll_interval_secs: 5,
+            timeout_secs: 10,
+            max_attempts: 8,
+            initial_backoff_secs: 30,
+            max_backoff_secs: 6 * 60 * 60,
+            allow_http: false,
+            allow_private_networks: false,
+        }
+    }
+}
+
+impl WebhooksConfig {
+    pub fn poll_interval(&self) -> Duration {
+        Duration::from_secs(self.poll_interval_secs)
+    }
+
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout_secs)
+    }
+
+    pub fn url_policy(&self) -> WebhookUrlPolicy {
+        WebhookUrlPolicy {
+            allow_http: self.allow_http,
+        }
+    }
+
+    pub fn retry_policy(&self) -> WebhookRetryPolicy {
+        WebhookRetryPolicy {
+            max_attempts: self.max_attempts,
+            initial_backoff: TimeDelta::seconds(
+                self.initial_backoff_secs.min(i64::MAX as u64) as i64,
+            ),
+            max_backoff: TimeDelta::seconds(
+                self.max_backoff_secs.min(i64::MAX as u64) as i64,
+            ),
+        }
+    }
+}
+
+/// Where the data of every organization is kept, see [tenancy](crate::api::tenancy).
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct TenancyConfig {
+    pub strategy: TenancyStrategy,
+    /// Directory of the databases of the organizations.
+    pub directory: PathBuf,
+    /// How requests name the organization they're made for.
+    pub resolution: TenantResolution,
+    /// Header naming the organization by its slug, with the `header` resolution.
+    pub header: String,
+    /// Domain whose subdomains are the slugs of the organizations, with the `subdomain`
+    /// resolution, e.g. `identify.example.com`.
+    pub base_domain: Option<String>,
+    /// Maximum number of connections to the database of every organization.
+    pub max_connections: u32,
+}
+
+impl Default for TenancyConfig {
+    fn default() -> Self {
+        TenancyConfig {
+            strategy: TenancyStrategy::default(),
+            directory: PathBuf::from("tenants"),
+            resolution: TenantResolution::default(),
+            header: "x-identify-tenant".to_owned(),
+            base_domain: None,
+            max_connections: 4,
+        }
+    }
+}
+
+impl TenancyConfig {
+    pub fn resolver(&self) -> Result<TenantResolver, String> {
+        match self.resolution {
+            TenantResolution::Header => HeaderName::from_bytes(
+                self.header.as_bytes(),
+            )
+            .map(TenantResolver::Header)
+            .map_err(|_| {
+                format!("tenancy.header is invalid: '{}'", self.header)
+            }),
+            TenantResolution::Subdomain => self
+                .base_domain
+                .as_deref()
+                .map(|domain| domain.trim_matches('.').to_ascii_lowercase())
+                .filter(|domain| !domain.is_empty())
+                .map(TenantResolver::Subdomain)
+                .ok_or_else(|| {
+                    "tenancy.base_domain must be set with the 'subdomain' resolution"
+                        .to_owned()
+                }),
+        }
+    }
+
+    /// Returns the registry of the databases of the organizations, which are opened like the
+    /// primary database, with fewer connections that are closed once they're idle.
+    pub fn registry(&self, database: &DatabaseConfig) -> TenantRegistry {
+        let defaults = database.pool_config();
+
+        TenantRegistry::new(
+            &self.directory,
+            PoolConfig {
+                min_connections: 0,
+                max_connections: self.max_connections,
+                ..defaults
+            },
+        )
+    }
+}
+
+/// How the data of the organizations is isolated.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TenancyStrategy {
+    /// All organizations share the database of `[database]`.
+    #[default]
+    Shared,
+    /// Every organization has a SQLite database of its own in `tenancy.directory`, while the
+    /// database of `[database]` keeps the organizations themselves.
+    DatabasePerOrganization,
+}
+
+/// Where requests name their organization, see [TenantResolver].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TenantResolution {
+    #[default]
+    Header,
+    Subdomain,
+}
+
+/// Projection of user events into the read model that serves user listings and searches.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ReadModelConfig {
+    /// How often new events are looked for, in milliseconds. Listings lag behind changes of
+    /// users by up to this long.
+    pub poll_interval_ms: u64,
+}
+
+impl Default for ReadModelConfig {
+    fn default() -> Self {
+        ReadModelConfig {
+            poll_interval_ms: 500,
+        }
+    }
+}
+
+impl ReadModelConfig {
+    pub fn poll_interval(&self) -> Duration {
+        Duration::from_millis(self.poll_interval_ms)
+    }
+}
+
+/// Statistics of the logins of users, see [logins](crate::logins), and the funnel of the
+/// login flow, see [funnel](crate::funnel).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct LoginsConfig {
+    /// How often the logins counted by the process are added to the statistics of users, in
+    /// milliseconds. The statistics lag behind logins by up to this long.
+    pub flush_interval_ms: u64,
+    /// How long a login attempt can take before it's reported as abandoned, in seconds.
+    pub abandon_after_secs: u64,
+}
+
+impl Default for LoginsConfig {
+    fn default() -> Self {
+        LoginsConfig {
+            flush_interval_ms: 5000,
+            abandon_after_secs: 600,
+        }
+    }
+}
+
+impl LoginsConfig {
+    pub fn flush_interval(&self) -> Duration {
+        Duration::from_millis(self.flush_interval_ms)
+    }
+
+    pub fn abandon_after(&self) -> Duration {
+        Duration::from_secs(self.abandon_after_secs)
+    }
+
+    pub fn funnel(&self) -> LoginFunnel {
+        LoginFunnel::new(self.abandon_after())
+    }
+}
+
+/// Live feeds of user events and session activity to admin dashboards, see
+/// [event_feed](crate::event_feed) and [session_monitor](crate::session_monitor).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct EventStreamConfig {
+    /// How often new events are looked for while anyone is subscribed, in milliseconds.
+    pub poll_interval_ms: u64,
+    /// How often a comment, or a ping over WebSockets, is sent to idle subscribers, so that
+    /// proxies don't close their connections, in seconds.
+    pub keep_alive_secs: u64,
+    /// Most events a subscriber can fall behind by. Subscribers that fall further behind are
+    /// disconnected. Subscribers of user events catch up from the outbox once they reconnect,
+    /// while the session activity they've missed is lost.
+    pub buffer: usize,
+}
+
+impl Default for EventStreamConfig {
+    fn default() -> Self {
+        EventStreamConfig {
+            poll_interval_ms: 1000,
+            keep_alive_secs: 15,
+            buffer: 1024,
+        }
+    }
+}
+
+impl EventStreamConfig {
+    pub fn poll_interval(&self) -> Duration {
+        Duration::from_millis(self.poll_interval_ms)
+    }
+
+    pub fn keep_alive(&self) -> Duration {
+        Duration::from_secs(self.keep_alive_secs)
+    }
+}
+
+/// Periodic cleanup of the data that isn't needed anymore.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct MaintenanceConfig {
+    /// Whether the maintenance tasks run at all.
+    pub enabled: bool,
+    /// Deletion of the user events that have been published and projected.
+    pub prune_user_events: MaintenanceTaskConfig,
+    /// Deletion of the webhook deliveries that have been delivered.
+    pub prune_webhook_deliveries: MaintenanceTaskConfig,
+    /// Rotation of the key tokens are signed with. Retired keys are published for the retention
+    /// period, so it must be longer than the tokens are valid.
+    pub rotate_signing_keys: MaintenanceTaskConfig,
+}
+
+impl Default for MaintenanceConfig {
+    fn default() -> Self {
+        MaintenanceConfig {
+            enabled: true,
+            prune_user_events: MaintenanceTaskConfig::default(),
+            prune_webhook_deliveries: MaintenanceTaskConfig {
+                schedule: "30 3 * * *".to_owned(),
+                ..MaintenanceTaskConfig::default()
+            },
+            rotate_signing_keys: MaintenanceTaskConfig {
+                schedule: "0 4 1 * *".to_owned(),
+                ..MaintenanceTaskConfig::default()
+            },
+        }
+    }
+}
+
+impl MaintenanceConfig {
+    pub fn task(&self, task: MaintenanceTask) -> &MaintenanceTaskConfig {
+        match task {
+            MaintenanceTask::PruneUserEvents => &self.prune_user_events,
+            MaintenanceTask::PruneWebhookDeliveries => {
+                &self.prune_webhook_deliveries
+            }
+            MaintenanceTask::RotateSigningKeys => &self.rotate_signing_keys,
+        }
+    }
+}
+
+/// Schedule of a maintenance task.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct MaintenanceTaskConfig {
+    /// Cron expression of when the task runs, in UTC, e.g. `0 3 * * *` for every day at 03:00.
+    pub schedule: String,
+    /// Longest random delay of a run after its scheduled time, in seconds.
+    pub jitter_secs: u64,
+    /// How old data must be to be deleted, in days.
+    pub retention_days: u64,
+}
+
+impl Default for MaintenanceTaskConfig {
+    fn default() -> Self {
+        MaintenanceTaskConfig {
+            schedule: "0 3 * * *".to_owned(),
+            jitter_secs: 5 * 60,
+            retention_days: 30,
+        }
+    }
+}
+
+impl MaintenanceTaskConfig {
+    pub fn schedule(&self) -> Result<Schedule, String> {
+        self.schedule.parse()
+    }
+
+    pub fn jitter(&self) -> Duration {
+        Duration::from_secs(self.jitter_secs)
+    }
+
+    pub fn retention(&self) -> TimeDelta {
+        TimeDelta::days(self.retention_days.min(i32::MAX as u64) as i64)
+    }
+}
+
+/// Backups of the database uploaded to the blob storage on a schedule.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct BackupConfig {
+    /// Whether backups are taken at all.
+    pub enabled: bool,
+    /// Cron expression of when backups are taken, in UTC.
+    pub schedule: String,
+    /// Longest random delay of a backup after its scheduled time, in seconds.
+    pub jitter_secs: u64,
+    /// Blob key the backups are stored under, followed by the time they were scheduled at.
+    pub key_prefix: String,
+}
+
+impl Default for BackupConfig {
+    fn default() -> Self {
+        BackupConfig {
+            enabled: false,
+            schedule: "0 2 * * *".to_owned(),
+            jitter_secs: 5 * 60,
+            key_prefix: "backups".to_owned(),
+        }
+    }
+}
+
+impl BackupConfig {
+    pub fn schedule(&self) -> Result<Schedule, String> {
+        self.schedule.parse()
+    }
+
+    pub fn jitter(&self) -> Duration {
+        Duration::from_secs(self.jitter_secs)
+    }
+}
+
+/// Cache of the users looked up by their IDs.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct UserCacheConfig {
+    /// Whether to cache users. Lookups may return stale users for a short while after they
+    /// change, see [UserCache](identify_infrastructure::storage::users::cache::UserCache).
+    pub enabled: bool,
+    /// Where the users are cached.
+    pub store: UserCacheStoreKind,
+    /// Most users kept in the `memory` store.
+    pub capacity: usize,
+    /// How long a user is kept in the cache, in seconds.
+    pub ttl_secs: u64,
+}
+
+impl Default for UserCacheConfig {
+    fn default() -> Self {
+        UserCacheConfig {
+            enabled: false,
+            store: UserCacheStoreKind::default(),
+            capacity: 10_000,
+            ttl_secs: 30,
+        }
+    }
+}
+
+impl UserCacheConfig {
+    pub fn cache(&self, redis: Option<&Redis>) -> Result<UserCache, String> {
+        if !self.enabled {
+            return Ok(UserCache::disabled());
+        }
+
+        let ttl = Duration::from_secs(self.ttl_secs);
+        match self.store {
+            UserCacheStoreKind::Memory => {
+                Ok(UserCache::new(self.capacity, ttl))
+            }
+            UserCacheStoreKind::Redis => {
+                let redis = redis.ok_or(
+                    "user_cache.store is 'redis', but redis.url isn't set",
+                )?;
+
+                Ok(redis.user_cache(ttl))
+            }
+        }
+    }
+}
+
+/// Where the users of the [UserCacheConfig] are cached.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UserCacheStoreKind {
+    /// In the memory of the process.
+    #[default]
+    Memory,
+    /// In Redis, so that the users are shared by all the processes that use the server.
+    Redis,
+}
+
+/// Sessions issued to users when they log in, see
+/// [SessionStore](identify_application::SessionStore).
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct SessionsConfig {
+    /// Where the sessions are kept.
+    pub store: SessionStoreKind,
+    /// How long a session lasts, in hours.
+    pub valid_for_hours: u32,
+}
+
+impl Default for SessionsConfig {
+    fn default() -> Self {
+        SessionsConfig {
+            store: SessionStoreKind::default(),
+            valid_for_hours: 30 * 24,
+        }
+    }
+}
+
+impl SessionsConfig {
+    pub fn settings(
+        &self,
+        redis: Option<&Redis>,
+    ) -> Result<SessionSettings, String> {
+        let store = match self.store {
+            SessionStoreKind::Memory => {
+                Arc::new(MemorySessionStore::new()) as Arc<dyn SessionStore>
+            }
+            SessionStoreKind::Redis => redis
+                .ok_or("sessions.store is 'redis', but redis.url isn't set")?
+                .session_store(),
+        };
+
+        Ok(SessionSettings {
+            store,
+            valid_for: TimeDelta::hours(i64::from(self.valid_for_hours)),
+        })
+    }
+}
+
+/// Where the sessions of the [SessionsConfig] are kept.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionStoreKind {
+    /// In the memory of the process, so that they're lost when it exits.
+    #[default]
+    Memory,
+    /// In Redis, so that the sessions are shared by all the processes that use the server.
+    Redis,
+}
+
+/// Redis server shared by the processes of a deployment, see [crate::redis].
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct RedisConfig {
+    /// URL of the server, e.g. `redis://127.0.0.1:6379/0`, or `rediss://...` to connect over
+    /// TLS. Redis is disabled if it's not set.
+    pub url: Option<String>,
+    /// Password the connection is authenticated with.
+    pub password: Option<Secret>,
+    /// Prefix of all the keys, so that several deployments can share a server.
+    pub key_prefix: String,
+    /// How long to wait for the connection to open, or for the reply to a command, in
+    /// milliseconds.
+    pub timeout_ms: u64,
+}
+
+impl Default for RedisConfig {
+    fn default() -> Self {
+        RedisConfig {
+            url: None,
+            password: None,
+            key_prefix: "identify".to_owned(),
+            timeout_ms: 500,
+        }
+    }
+}
+
+impl RedisConfig {
+    pub fn timeout(&self) -> Duration {
+        Duration::from_millis(self.timeout_ms)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct LoggingConfig {
+    /// Log filter in the [EnvFilter] format.
+    ///
+    /// The [LOGGING_ENV](crate::logging::LOGGING_ENV) env variable takes precedence over this.
+    pub filter: String,
+    /// Output format of the logs.
+    ///
+    /// The [LOG_FORMAT_ENV](crate::logging::LOG_FORMAT_ENV) env variable takes precedence
+    /// over this.
+    pub format: LogFormat,
+    /// Writing logs to files in addition to stdout.
+    pub file: FileLoggingConfig,
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        LoggingConfig {
+            filter: "identify=info".into(),
+            format: LogFormat::default(),
+            file: FileLoggingConfig::default(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct FileLoggingConfig {
+    /// Directory to write log files to. File logging is disabled if it's not set.
+    ///
+    /// The [LOGGING_FILE_ENV](crate::logging::LOGGING_FILE_ENV) env variable takes
+    /// precedence over this.
+    pub directory: Option<PathBuf>,
+    /// Prefix of the log file names, followed by the date of the rotation period.
+    pub prefix: String,
+    /// How often to start a new file.
+    pub rotation: LogRotation,
+    /// How many files to keep, older ones are deleted. All files are kept if it's not set.
+    pub max_files: Option<usize>,
+}
+
+impl Default for FileLoggingConfig {
+    fn default() -> Self {
+        FileLoggingConfig {
+            directory: None,
+            prefix: "identify.log".into(),
+            rotation: LogRotation::default(),
+            max_files: Some(7),
+        }
+    }
+}
+
+/// How often log files are rotated.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogRotation {
+    Minutely,
+    Hourly,
+    #[default]
+    Daily,
+    Never,
+}
+
+/// Output format of the logs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// Human-readable text.
+    #[default]
+    Text,
+    /// One JSON object per line, for log aggregators like Loki or ELK.
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            _ => Err(format!("expected 'text' or 'json', got '{s}'")),
+        }
+    }
+}
+
+/// Streaming of user events to other services through NATS JetStream.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct StreamingConfig {
+    /// URL of the NATS server, e.g. `nats://127.0.0.1:4222`, or `tls://...` to connect over
+    /// TLS. Streaming is disabled if it's not set.
+    pub nats_url: Option<String>,
+    /// Token the connection to NATS is authenticated with.
+    pub nats_token: Option<Secret>,
+    /// Prefix of the subjects the events are published to. A stream capturing
+    /// `{subject_prefix}.>` has to exist.
+    pub subject_prefix: String,
+    /// How long to wait for the connection to open, or for JetStream to acknowledge an event,
+    /// in seconds.
+    pub timeout_secs: u64,
+}
+
+impl Default for StreamingConfig {
+    fn default() -> Self {
+        StreamingConfig {
+            nats_url: None,
+            nats_token: None,
+            subject_prefix: "identify.events".to_owned(),
+            timeout_secs: 5,
+        }
+    }
+}
+
+impl StreamingConfig {
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout_secs)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct ErrorReportingConfig {
+    /// Sentry DSN. Error reporting is disabled if it's not set.
+    pub dsn: Option<Secret>,
+    /// Name of the environment the server runs in, e.g. `production`.
+    pub environment: Option<String>,
+    /// Share of errors that are reported, from 0.0 to 1.0.
+    pub sample_rate: f32,
+    /// Share of requests that are traced, from 0.0 to 1.0.
+    pub traces_sample_rate: f32,
+}
+
+impl Default for ErrorReportingConfig {
+    fn default() -> Self {
+        ErrorReportingConfig {
+            dsn: None,
+            environment: None,
+            sample_rate: 1.0,
+            traces_sample_rate: 0.0,
+        }
+    }
+}
+
+/// A sensitive value that is never printed.
+#[derive(Clone, Deserialize)]
+#[serde(transparent)]
+pub struct Secret(String);
+
+impl Secret {
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Secret(***)")
+    }
+}
+
+impl Config {
+    /// Loads and validates the configuration from all the sources.
+    pub fn load() -> Result<Self, ConfigError> {
+        let (path, required) = match std::env::var(CONFIG_FILE_ENV) {
+            Ok(path) => (path, true),
+            Err(_) => (DEFAULT_CONFIG_FILE.to_owned(), false),
+        };
+
+        let config: Config = config::Config::builder()
+            .add_source(File::new(&path, FileFormat::Toml).required(required))
+            .add_source(
+                Environment::with_prefix(ENV_PREFIX)
+                    .prefix_separator("_")
+                    .separator(ENV_SEPARATOR)
+                    .try_parsing(true),
+            )
+            .build()?
+            .try_deserialize()?;
+
+        config.validate()?;
+
+        Ok(config)
+    }
+
+    /// Checks the configuration for errors and reports all of them at once.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let mut errors = Vec::new();
+
+        let cors = [
+            self.cors.origins().err(),
+            self.cors.methods().err(),
+            self.cors.headers().err(),
+        ];
+        errors.extend(cors.into_iter().flatten());
+        if self.cors.allows_any_origin() && self.cors.allowed_origins.len() > 1
+        {
+            errors.push(
+                "cors.allowed_origins must not list other origins along with '*'"
+                    .to_owned(),
+            );
+        }
+
+        if self.database.url.trim().is_empty() {
+            errors.push("database.url must not be empty".to_owned());
+        }
+
+        let pool = self.database.pool_config();
+        if pool.max_connections == 0 {
+            errors.push("database.max_connections must be positive".to_owned());
+        }
+        if pool.min_connections > pool.max_connections {
+            errors.push(format!(
+                "database.min_connections ({}) must not exceed database.max_connections ({})",
+                pool.min_connections, pool.max_connections
+            ));
+        }
+        if pool.acquire_timeout.is_zero() {
+            errors.push(
+                "database.acquire_timeout_secs must be positive".to_owned(),
+            );
+        }
+        if pool.reconnect.max_attempts == 0 {
+            errors
+                .push("database.connect_attempts must be positive".to_owned());
+        }
+        let retry = self.database.retry_policy();
+        if retry.max_attempts == 0 {
+            errors.push("database.retry_attempts must be positive".to_owned());
+        }
+        if !(retry.budget_ratio >= 0.0 && retry.budget_ratio.is_finite()) {
+            errors.push(
+                "database.retry_budget_ratio must not be negative".to_owned(),
+            );
+        }
+        if connection::is_in_memory(&self.database.url) {
+            if pool.pragmas.wal {
+                errors.push(
+                    "database.wal is not supported by in-memory databases"
+                        .to_owned(),
+                );
+            }
+            if pool.max_connections > 1 {
+                errors.push(
+                    "in-memory databases support only a single connection, set database.max_connections to 1".to_owned(),
+                );
+            }
+        }
+        if let Some(read_url) = &self.database.read_url
+            && (connection::is_in_memory(read_url)
+                || connection::is_in_memory(&self.database.url))
+        {
+            errors.push(
+                "in-memory databases can't be replicated, unset database.read_url"
+                    .to_owned(),
+            );
+        }
+
+        let mut api_keys = HashSet::new();
+        for api_key in &self.auth.api_keys {
+            if api_key.key.expose().len() < MIN_SECRET_LENGTH {
+                errors.push(format!(
+                    "API key of '{}' must be at least {MIN_SECRET_LENGTH} characters long",
+                    api_key.subject
+                ));
+            }
+            if !api_keys.insert(api_key.key.expose()) {
+                errors.push(format!(
+                    "API key of '{}' is used more than once",
+                    api_key.subject
+                ));
+            }
+            if api_key.organization_id.is_some()
+                && self.tenancy.strategy
+                    != TenancyStrategy::DatabasePerOrganization
+            {
+                errors.push(format!(
+                    "API key of '{}' is limited to an organization, which requires tenancy.strategy = \"database_per_organization\"",
+                    api_key.subject
+                ));
+            }
+        }
+        if let Err(e) = self.auth.authenticator() {
+            errors.push(e);
+        }
+
+        if self.rate_limit.shards == Some(0) {
+            errors.push("rate_limit.shards must be positive".to_owned());
+        }
+        for (name, bucket) in [
+            ("default", self.rate_limit.default),
+            ("strict", self.rate_limit.strict),
+        ] {
+            if bucket.capacity == 0 {
+                errors.push(format!(
+                    "rate_limit.{name}.capacity must be positive"
+                ));
+            }
+            if !bucket.refill_per_second.is_finite()
+                || bucket.refill_per_second <= 0.0
+            {
+                errors.push(format!(
+                    "rate_limit.{name}.refill_per_second must be positive"
+                ));
+            }
+        }
+
+        if let Err(e) = self.sdk.policy() {
+            errors.push(e);
+        }
+
+        if let Err(e) = self.users.username_strategy() {
+            errors.push(e);
+        }
+
+        if let Err(e) = self.consents.policies() {
+            errors.push(e);
+        }
+
+        if let Err(e) = self.authorization.policies() {
+            errors.push(e);
+        }
+
+        let accept_url = &self.invitations.accept_url;
+        if !reqwest::Url::parse(accept_url).is_ok_and(|url| {
+            matches!(url.scheme(), "http" | "https") && url.has_host()
+        }) {
+            errors.push(format!(
+                "invitations.accept_url is not a valid HTTP(S) URL: '{accept_url}'"
+            ));
+        }
+        if self.invitations.valid_for_hours == 0 {
+            errors.push(
+                "invitations.valid_for_hours must be positive".to_owned(),
+            );
+        }
+
+        let confirm_url = &self.email_changes.confirm_url;
+        if !reqwest::Url::parse(confirm_url).is_ok_and(|url| {
+            matches!(url.scheme(), "http" | "https") && url.has_host()
+        }) {
+            errors.push(format!(
+                "email_changes.confirm_url is not a valid HTTP(S) URL: '{confirm_url}'"
+            ));
+        }
+        if self.email_changes.valid_for_hours == 0 {
+            errors.push(
+                "email_changes.valid_for_hours must be positive".to_owned(),
+            );
+        }
+
+        if let Err(e) = self.registration.policy() {
+            errors.push(e);
+        }
+        let verify_url = &self.registration.verify_url;
+        if !reqwest::Url::parse(verify_url).is_ok_and(|url| {
+            matches!(url.scheme(), "http" | "https") && url.has_host()
+        }) {
+            errors.push(format!(
+                "registration.verify_url is not a valid HTTP(S) URL: '{verify_url}'"
+            ));
+        }
+        if self.registration.verification_valid_for_hours == 0 {
+            errors.push(
+                "registration.verification_valid_for_hours must be positive"
+                    .to_owned(),
+            );
+        }
+
+        if self.sms.verification_valid_for_minutes == 0 {
+            errors.push(
+                "sms.verification_valid_for_minutes must be positive"
+                    .to_owned(),
+            );
+        }
+
+        if self.email.transport == EmailTransportKind::Smtp {
+            if self.email.smtp.host.is_empty() {
+                errors.push("email.smtp.host must not be empty".to_owned());
+            }
+            if self.email.smtp.port == 0 {
+                errors.push("email.smtp.port must be positive".to_owned());
+            }
+            if self.email.smtp.timeout_secs == 0 {
+                errors.push(
+                    "email.smtp.timeout_secs must be positive".to_owned(),
+                );
+            }
+            if self.email.smtp.username.is_some()
+                != self.email.smtp.password.is_some()
+            {
+                errors.push(
+                    "email.smtp.username and email.smtp.password must be set together"
+                        .to_owned(),
+                );
+            }
+        }
+        if let Err(e) = self.email.smtp_settings().validate() {
+            errors.push(format!("email.from is invalid: {e:#}"));
+        }
+        if let Err(e) = self.email.templates() {
+            errors.push(e);
+        }
+
+        let blob_errors = errors.len();
+        match self.blob_storage.backend {
+            BlobStorageKind::Filesystem => {
+                if self.blob_storage.path.as_os_str().is_empty() {
+                    errors
+                        .push("blob_storage.path must not be empty".to_owned());
+                }
+            }
+            BlobStorageKind::S3 => {
+                let s3 = &self.blob_storage.s3;
+                for (name, value) in [
+                    ("endpoint", &s3.endpoint),
+                    ("bucket", &s3.bucket),
+                    ("region", &s3.region),
+                ] {
+                    if value.trim().is_empty() {
+                        errors.push(format!(
+                            "blob_storage.s3.{name} must not be empty"
+                        ));
+                    }
+                }
+                if s3.access_key_id.is_none() || s3.secret_access_key.is_none()
+                {
+                    errors.push(
+                        "blob_storage.s3.access_key_id and blob_storage.s3.secret_access_key must be set"
+                            .to_owned(),
+                    );
+                }
+                if s3.timeout_secs == 0 {
+                    errors.push(
+                        "blob_storage.s3.timeout_secs must be positive"
+                            .to_owned(),
+                    );
+                }
+            }
+        }
+        if errors.len() == blob_errors
+            && let Err(e) = self.blob_storage.storage()
+        {
+            errors.push(e);
+        }
+
+        if let Err(e) = self.encryption.key_provider() {
+            errors.push(e);
+        }
+
+        if let Err(e) = self.digest.categories() {
+            errors.push(e);
+        }
+        if self.digest.enabled {
+            match &self.digest.webhook_url {
+                None if self.digest.recipients.is_empty() => errors.push(
+                    "digest.webhook_url or digest.recipients must be set when digests are enabled"
+                        .to_owned(),
+                ),
+                Some(url) if reqwest::Url::parse(url).is_err() => errors.push(
+                    format!("digest.webhook_url is not a valid URL: '{url}'"),
+                ),
+                _ => {}
+            }
+            for recipient in &self.digest.recipients {
+                if !recipient.contains('@') {
+                    errors.push(format!(
+                        "digest.recipients contains an invalid email address: '{recipient}'"
+                    ));
+                }
+            }
+        }
+
+        for (name, value) in [
+            ("poll_interval_secs", self.webhooks.poll_interval_secs),
+            ("timeout_secs", self.webhooks.timeout_secs),
+            ("max_attempts", u64::from(self.webhooks.max_attempts)),
+            ("initial_backoff_secs", self.webhooks.initial_backoff_secs),
+        ] {
+            if value == 0 {
+                errors.push(format!("webhooks.{name} must be positive"));
+            }
+        }
+        if self.webhooks.max_backoff_secs < self.webhooks.initial_backoff_secs {
+            errors.push(
+                "webhooks.max_backoff_secs must not be shorter than webhooks.initial_backoff_secs"
+                    .to_owned(),
+            );
+        }
+
+        for task in MaintenanceTask::ALL {
+            let config = self.maintenance.task(task);
+            let name = task.as_str();
+            match config.schedule() {
+                Err(e) => errors.push(format!(
+                    "maintenance.{name}.schedule is invalid: {e}"
+                )),
+                Ok(schedule) if schedule.next_after(Utc::now()).is_none() => {
+                    errors.push(format!(
+                        "maintenance.{name}.schedule never fires: '{schedule}'"
+                    ))
+                }
+                Ok(_) => {}
+            }
+            if config.retention_days == 0 {
+                errors.push(format!(
+                    "maintenance.{name}.retention_days must be positive"
+                ));
+            }
+        }
+        if self.maintenance.rotate_signing_keys.jitter_secs as i64
+            >= MIN_SIGNING_KEY_AGE.num_seconds()
+        {
+            errors.push(format!(
+                "maintenance.rotate_signing_keys.jitter_secs must be shorter than {} seconds",
+                MIN_SIGNING_KEY_AGE.num_seconds()
+            ));
+        }
+        if self.maintenance.enabled
+            && self.digest.enabled
+            && self.maintenance.prune_user_events.retention()
+                <= self.digest.period.length()
+        {
+            errors.push(
+                "maintenance.prune_user_events.retention_days must be longer than the digest period"
+                    .to_owned(),
+            );
+        }
+
+        if self.backup.enabled {
+            match self.backup.schedule() {
+                Err(e) => {
+                    errors.push(format!("backup.schedule is invalid: {e}"))
+                }
+                Ok(schedule) if schedule.next_after(Utc::now()).is_none() => {
+                    errors.push(format!(
+                        "backup.schedule never fires: '{schedule}'"
+                    ))
+                }
+                Ok(_) => {}
+            }
+            if !backup::is_valid_key_prefix(&self.backup.key_prefix) {
+                errors.push(
+                    "backup.key_prefix must be made of '/'-separated segments of letters, digits, '.', '_' and '-'"
+                        .to_owned(),
+                );
+            }
+        }
+
+        if self.tenancy.strategy == TenancyStrategy::DatabasePerOrganization {
+            if let Err(e) = self.tenancy.resolver() {
+                errors.push(e);
+            }
+            if self.tenancy.max_connections == 0 {
+                errors.push(
+                    "tenancy.max_connections must be positive".to_owned(),
+                );
+            }
+        }
+
+        if self.read_model.poll_interval_ms == 0 {
+            errors.push(
+                "read_model.poll_interval_ms must be positive".to_owned(),
+            );
+        }
+
+        if self.logins.flush_interval_ms == 0 {
+            errors.push("logins.flush_interval_ms must be positive".to_owned());
+        }
+        if self.logins.abandon_after_secs == 0 {
+            errors
+                .push("logins.abandon_after_secs must be positive".to_owned());
+        }
+
+        for (name, value) in [
+            ("poll_interval_ms", self.event_stream.poll_interval_ms),
+            ("keep_alive_secs", self.event_stream.keep_alive_secs),
+            ("buffer", self.event_stream.buffer as u64),
+        ] {
+            if value == 0 {
+                errors.push(format!("event_stream.{name} must be positive"));
+            }
+        }
+
+        if self.user_cache.enabled {
+            if self.user_cache.store == UserCacheStoreKind::Memory
+                && self.user_cache.capacity == 0
+            {
+                errors.push("user_cache.capacity must be positive".to_owned());
+            }
+            if self.user_cache.ttl_secs == 0 {
+                errors.push("user_cache.ttl_secs must be positive".to_owned());
+            }
+        }
+
+        let redis_users = [
+            (
+                "rate_limit.store",
+                self.rate_limit.enabled
+                    && self.rate_limit.store == RateLimitStoreKind::Redis,
+            ),
+            (
+                "user_cache.store",
+                self.user_cache.enabled
+                    && self.user_cache.store == UserCacheStoreKind::Redis,
+            ),
+        ];
+        for (name, _) in redis_users.iter().filter(|(_, uses)| *uses) {
+            if self.redis.url.is_none() {
+                errors.push(format!(
+                    "{name} is 'redis', but redis.url isn't set"
+                ));
+            }
+            if !cfg!(feature = "redis") {
+                errors.push(format!(
+                    "{name} is 'redis', but the server is built without the 'redis' feature"
+                ));
+            }
+        }
+        if self.redis.key_prefix.trim().is_empty() {
+            errors.push("redis.key_prefix must not be empty".to_owned());
+        }
+        if self.redis.timeout_ms == 0 {
+            errors.push("redis.timeout_ms must be positive".to_owned());
+        }
+
+        let subject_prefix = &self.streaming.subject_prefix;
+        if subject_prefix.is_empty()
+            || subject_prefix.split('.').any(|token| {
+                token.is_empty()
+                    || token.contains(|c: char| {
+                        c.is_whitespace() || c == '*' || c == '>'
+                    })
+            })
+        {
+            errors.push(format!(
+                "streaming.subject_prefix is not a valid NATS subject: '{subject_prefix}'"
+            ));
+        }
+        if self.streaming.timeout_secs == 0 {
+            errors.push("streaming.timeout_secs must be positive".to_owned());
+        }
+
+        if let Err(e) = EnvFilter::try_new(&self.logging.filter) {
+            errors.push(format!("logging.filter is invalid: {e}"));
+        }
+        if self.logging.file.prefix.trim().is_empty() {
+            errors.push("logging.file.prefix must not be empty".to_owned());
+        }
+        if self.logging.file.max_files == Some(0) {
+            errors.push("logging.file.max_files must be positive".to_owned());
+        }
+
+        for (name, rate) in [
+            ("sample_rate", self.error_reporting.sample_rate),
+            (
+                "traces_sample_rate",
+                self.error_reporting.traces_sample_rate,
+            ),
+        ] {
+            if !(0.0..=1.0).contains(&rate) {
+                errors.push(format!(
+                    "error_reporting.{name} must be between 0.0 and 1.0, got {rate}"
+                ));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigError::Invalid(errors))
+        }
+    }
+}
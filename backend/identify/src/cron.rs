@@ -0,0 +1,166 @@
+//! Cron expressions of the schedules of periodic jobs.
+
+use std::{fmt, str::FromStr};
+
+use chrono::{DateTime, Datelike, Days, NaiveDate, TimeDelta, Timelike, Utc};
+
+/// How far ahead the next time of a schedule is looked for. Schedules that don't fire within it,
+/// e.g. `0 0 30 2 *`, never fire at all.
+const MAX_LOOKAHEAD_DAYS: u64 = 5 * 366;
+
+/// A schedule in the classic five-field cron format: minute, hour, day of month, month and day
+/// of week, e.g. `30 3 * * 1-5` for 03:30 UTC on weekdays. Times are always in UTC.
+///
+/// Every field is `*`, a number, a range (`1-5`), a step (`*/15` or `0-30/10`), or a comma-separated
+/// list of those. Days of week go from 0 (Sunday) to 6, and 7 is Sunday too. Like in cron, if
+/// both the day of month and the day of week are restricted, a day matching either of them fires.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schedule {
+    expression: String,
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    /// Whether the day of month or the day of week field isn't `*`.
+    days_restricted: bool,
+    weekdays_restricted: bool,
+}
+
+impl Schedule {
+    /// Returns the first time after `after` the schedule fires at, or `None` if it never does.
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let limit = after + Days::new(MAX_LOOKAHEAD_DAYS);
+        let mut time = after
+            .with_second(0)
+            .and_then(|time| time.with_nanosecond(0))?
+            + TimeDelta::minutes(1);
+
+        while time <= limit {
+            if !has(self.months, time.month()) {
+                let (year, month) = match time.month() {
+                    12 => (time.year() + 1, 1),
+                    month => (time.year(), month + 1),
+                };
+                time = start_of_day(NaiveDate::from_ymd_opt(year, month, 1)?);
+            } else if !self.matches_day(time) {
+                time = start_of_day(time.date_naive() + Days::new(1));
+            } else if !has(self.hours, time.hour()) {
+                time = time.with_minute(0)? + TimeDelta::hours(1);
+            } else if !has(self.minutes, time.minute()) {
+                time += TimeDelta::minutes(1);
+            } else {
+                return Some(time);
+            }
+        }
+
+        None
+    }
+
+    fn matches_day(&self, time: DateTime<Utc>) -> bool {
+        let day = has(self.days, time.day());
+        let weekday = has(self.weekdays, time.weekday().num_days_from_sunday());
+
+        match (self.days_restricted, self.weekdays_restricted) {
+            (true, true) => day || weekday,
+            (true, false) => day,
+            (false, true) => weekday,
+            (false, false) => true,
+        }
+    }
+}
+
+fn has(field: u64, value: u32) -> bool {
+    field & (1 << value) != 0
+}
+
+fn start_of_day(date: NaiveDate) -> DateTime<Utc> {
+    date.and_time(Default::default()).and_utc()
+}
+
+impl FromStr for Schedule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<_> = s.split_whitespace().collect();
+        let [minutes, hours, days, months, weekdays] = fields[..] else {
+            return Err(format!(
+                "'{s}' must have 5 fields: minute, hour, day of month, month and day of week"
+            ));
+        };
+
+        let mut weekdays_field = parse_field(weekdays, "day of week", 0, 7)?;
+        // Both 0 and 7 are Sunday.
+        if has(weekdays_field, 7) {
+            weekdays_field = (weekdays_field | 1) & !(1 << 7);
+        }
+
+        Ok(Schedule {
+            expression: fields.join(" "),
+            minutes: parse_field(minutes, "minute", 0, 59)?,
+            hours: parse_field(hours, "hour", 0, 23)?,
+            days: parse_field(days, "day of month", 1, 31)?,
+            months: parse_field(months, "month", 1, 12)?,
+            weekdays: weekdays_field,
+            days_restricted: days != "*",
+            weekdays_restricted: weekdays != "*",
+        })
+    }
+}
+
+/// Parses a field into a bit set of the values it matches.
+fn parse_field(
+    field: &str,
+    name: &str,
+    min: u32,
+    max: u32,
+) -> Result<u64, String> {
+    let invalid = || {
+        format!("invalid {name} '{field}', expected values from {min} to {max}")
+    };
+    let value = |s: &str| {
+        s.parse::<u32>()
+            .ok()
+            .filter(|value| (min..=max).contains(value))
+            .ok_or_else(invalid)
+    };
+
+    let mut values = 0;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step = step
+                    .parse::<u32>()
+                    .ok()
+                    .filter(|step| *step > 0)
+                    .ok_or_else(invalid)?;
+                (range, step)
+            }
+            None => (part, 1),
+        };
+        let (start, end) = match range {
+            "*" => (min, max),
+            range => match range.split_once('-') {
+                Some((start, end)) => (value(start)?, value(end)?),
+                // `5/15` is short for `5-<max>/15`.
+                None if step > 1 => (value(range)?, max),
+                None => (value(range)?, value(range)?),
+            },
+        };
+        if start > end {
+            return Err(invalid());
+        }
+
+        for value in (start..=end).step_by(step as usize) {
+            values |= 1 << value;
+        }
+    }
+
+    Ok(values)
+}
+
+impl fmt::Display for Schedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.expression)
+    }
+}
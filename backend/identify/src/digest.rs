@@ -0,0 +1,268 @@
+//! Periodic digest of notable events for admins.
+//!
+//! At the end of every period (see [DigestPeriod]) the events of the period are summarized,
+//! posted to the configured webhook and emailed to the configured admins. Periods without any
+//! notable events are skipped.
+
+use std::{fmt::Write as _, sync::Arc, time::Duration};
+
+use chrono::{DateTime, Datelike, Days, TimeDelta, Utc};
+use eyre::{Context, Result, eyre};
+use identify_application::{
+    AdminDigest, BuildAdminDigestParams, DigestCategory, DigestUseCaseDeps,
+    Email, EmailSender, build_admin_digest,
+};
+use identify_domain::QuotaPolicy;
+use identify_infrastructure::{
+    encryption::FieldEncryption, storage::unit_of_work::SqliteUnitOfWork,
+};
+use serde::Serialize;
+use sqlx::SqlitePool;
+use tokio::task::JoinHandle;
+use tracing::{Instrument, debug, error, info, info_span, warn};
+
+use crate::{
+    config::{DigestConfig, DigestPeriod},
+    status::{Component, StatusBoard},
+};
+
+/// How long to wait for the webhook to accept a digest.
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+impl DigestPeriod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DigestPeriod::Daily => "daily",
+            DigestPeriod::Weekly => "weekly",
+        }
+    }
+
+    /// Returns when the period that `now` falls into ends.
+    pub fn end_of(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        let days = match self {
+            DigestPeriod::Daily => 1,
+            DigestPeriod::Weekly => {
+                7 - u64::from(now.weekday().num_days_from_monday())
+            }
+        };
+
+        (now.date_naive() + Days::new(days))
+            .and_time(Default::default())
+            .and_utc()
+    }
+
+    pub fn length(&self) -> TimeDelta {
+        match self {
+            DigestPeriod::Daily => TimeDelta::days(1),
+            DigestPeriod::Weekly => TimeDelta::weeks(1),
+        }
+    }
+}
+
+/// Digest in the form it's posted to the webhook.
+#[derive(Debug, Serialize)]
+struct DigestPayload {
+    period: &'static str,
+    since: DateTime<Utc>,
+    until: DateTime<Utc>,
+    entries: Vec<DigestEntry>,
+}
+
+#[derive(Debug, Serialize)]
+struct DigestEntry {
+    category: &'static str,
+    count: u64,
+}
+
+/// Sends a digest at the end of every period.
+pub struct DigestJob {
+    pool: SqlitePool,
+    encryption: FieldEncryption,
+    period: DigestPeriod,
+    categories: Vec<DigestCategory>,
+    quotas: QuotaPolicy,
+    webhook_url: Option<reqwest::Url>,
+    client: reqwest::Client,
+    recipients: Vec<String>,
+    sender: Arc<dyn EmailSender>,
+    status: StatusBoard,
+}
+
+impl DigestJob {
+    /// Creates the job, unless digests are disabled.
+    ///
+    /// Deliveries to the webhook are reported to the status board.
+    pub fn from_config(
+        pool: SqlitePool,
+        encryption: FieldEncryption,
+        config: &DigestConfig,
+        quotas: QuotaPolicy,
+        sender: Arc<dyn EmailSender>,
+        status: StatusBoard,
+    ) -> Result<Option<Self>> {
+        if !config.enabled {
+            return Ok(None);
+        }
+        if config.webhook_url.is_none() && config.recipients.is_empty() {
+            return Err(eyre!(
+                "neither the digest webhook URL nor recipients are set"
+            ));
+        }
+
+        let webhook_url = config
+            .webhook_url
+            .as_deref()
+            .map(str::parse)
+            .transpose()
+            .wrap_err("invalid digest webhook URL")?;
+        let client = reqwest::Client::builder()
+            .timeout(WEBHOOK_TIMEOUT)
+            .build()
+            .wrap_err("failed to create the digest webhook client")?;
+
+        let categories = config.categories().map_err(|e| eyre!(e))?;
+        if webhook_url.is_some() {
+            status.configure(Component::WebhookDelivery);
+        }
+
+        Ok(Some(DigestJob {
+            pool,
+            encryption,
+            period: config.period,
+            categories,
+            quotas,
+            webhook_url,
+            client,
+            recipients: config.recipients.clone(),
+            sender,
+            status,
+        }))
+    }
+
+    /// Runs the job in the background until the process exits.
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                let until = self.period.end_of(Utc::now());
+                let wait = (until - Utc::now()).to_std().unwrap_or_default();
+                tokio::time::sleep(wait).await;
+
+                let since = until - self.period.length();
+                let span = info_span!(
+                    "digest",
+                    period = self.period.as_str(),
+                    %since
+                );
+                if let Err(e) = self.send(since, until).instrument(span).await {
+                    error!(error = ?e, "Failed to send the admin digest");
+                }
+            }
+        })
+    }
+
+    /// Summarizes the events of the period, emails the digest to the recipients and posts it to
+    /// the webhook.
+    ///
+    /// A recipient the digest can't be emailed to doesn't keep it from the others, nor from the
+    /// webhook.
+    pub async fn send(
+        &self,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> Result<()> {
+        let digest = self.build(since, until).await?;
+        if digest.is_empty() {
+            debug!("Nothing notable happened, skipping the admin digest");
+            return Ok(());
+        }
+
+        self.email(&digest).await;
+        if let Some(webhook_url) = &self.webhook_url {
+            self.post(webhook_url, &digest).await?;
+        }
+        info!("Sent the admin digest");
+
+        Ok(())
+    }
+
+    async fn email(&self, digest: &AdminDigest) {
+        let mut text = format!(
+            "Notable events from {} to {}:\n\n",
+            digest.since.to_rfc3339(),
+            digest.until.to_rfc3339()
+        );
+        for (category, count) in &digest.entries {
+            let _ = writeln!(text, "- {category}: {count}");
+        }
+
+        for recipient in &self.recipients {
+            let email = Email {
+                to: recipient.clone(),
+                subject: format!(
+                    "Identify {} digest for {}",
+                    self.period.as_str(),
+                    digest.since.date_naive()
+                ),
+                text: text.clone(),
+                html: None,
+            };
+            if let Err(e) = self.sender.send(&email).await {
+                warn!(error = %e, recipient, "Failed to email the admin digest");
+            }
+        }
+    }
+
+    async fn post(
+        &self,
+        webhook_url: &reqwest::Url,
+        digest: &AdminDigest,
+    ) -> Result<()> {
+        let payload = DigestPayload {
+            period: self.period.as_str(),
+            since: digest.since,
+            until: digest.until,
+            entries: digest
+                .entries
+                .iter()
+                .map(|(category, count)| DigestEntry {
+                    category: category.as_str(),
+                    count: *count,
+                })
+                .collect(),
+        };
+
+        let result = self
+            .client
+            .post(webhook_url.clone())
+            .json(&payload)
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status);
+        self.status
+            .record(Component::WebhookDelivery, result)
+            .wrap_err("failed to post the digest to the webhook")?;
+
+        Ok(())
+    }
+
+    async fn build(
+        &self,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> Result<AdminDigest> {
+        let mut uow =
+            SqliteUnitOfWork::begin(&self.pool, &self.encryption).await?;
+
+        let digest = build_admin_digest(
+            DigestUseCaseDeps::new(&mut uow, &self.quotas),
+            BuildAdminDigestParams {
+                since,
+                until,
+                categories: self.categories.clone(),
+            },
+        )
+        .await?;
+
+        Ok(digest)
+    }
+}
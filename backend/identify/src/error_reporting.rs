@@ -0,0 +1,136 @@
+//! Reporting of internal errors and panics to Sentry.
+//!
+//! Reporting is available only if the binary is built with the `sentry` feature and a DSN is
+//! configured. Errors logged with the `ERROR` level become Sentry events, and the fields of
+//! the enclosing spans (e.g. the request ID, route and actor) are attached to them. Panics are
+//! reported as well.
+
+use eyre::Result;
+
+use crate::config::ErrorReportingConfig;
+
+/// Keeps the error reporting alive and flushes pending events when dropped.
+#[must_use = "events are not flushed if the guard is dropped right away"]
+pub struct ErrorReportingGuard {
+    #[cfg(feature = "sentry")]
+    _guard: Option<sentry::ClientInitGuard>,
+}
+
+#[cfg(feature = "sentry")]
+pub fn init(config: &ErrorReportingConfig) -> Result<ErrorReportingGuard> {
+    use std::sync::Arc;
+
+    use eyre::WrapErr;
+    use tracing::info;
+
+    let Some(dsn) = &config.dsn else {
+        return Ok(ErrorReportingGuard { _guard: None });
+    };
+
+    let dsn = dsn
+        .expose()
+        .parse()
+        .wrap_err("error_reporting.dsn is invalid")?;
+
+    let guard = sentry::init(sentry::ClientOptions {
+        dsn: Some(dsn),
+        release: sentry::release_name!(),
+        environment: config.environment.clone().map(Into::into),
+        sample_rate: config.sample_rate,
+        traces_sample_rate: config.traces_sample_rate,
+        send_default_pii: false,
+        before_send: Some(Arc::new(|event| Some(redaction::scrub(event)))),
+        ..Default::default()
+    });
+
+    info!("Error reporting is enabled");
+
+    Ok(ErrorReportingGuard {
+        _guard: Some(guard),
+    })
+}
+
+#[cfg(not(feature = "sentry"))]
+pub fn init(config: &ErrorReportingConfig) -> Result<ErrorReportingGuard> {
+    if config.dsn.is_some() {
+        tracing::warn!(
+            "error_reporting.dsn is set, but the server is built without the 'sentry' feature"
+        );
+    }
+
+    Ok(ErrorReportingGuard {})
+}
+
+/// Removes personal data and credentials from events before they leave the server.
+#[cfg(feature = "sentry")]
+pub mod redaction {
+    use sentry::protocol::{Context, Event, Value};
+
+    /// Parts of field names that mark their values as sensitive.
+    const SENSITIVE_KEYS: &[&str] = &[
+        "authorization",
+        "cookie",
+        "password",
+        "secret",
+        "token",
+        "key",
+        "email",
+    ];
+
+    const REDACTED: &str = "[redacted]";
+
+    pub fn scrub(mut event: Event<'static>) -> Event<'static> {
+        if let Some(user) = &mut event.user {
+            user.email = None;
+            user.ip_address = None;
+            scrub_fields(&mut user.other);
+        }
+
+        if let Some(request) = &mut event.request {
+            request.cookies = None;
+            request.query_string = None;
+            request.data = None;
+
+            for (name, value) in &mut request.headers {
+                if is_sensitive(name) {
+                    *value = REDACTED.to_owned();
+                }
+            }
+        }
+
+        for context in event.contexts.values_mut() {
+            if let Context::Other(fields) = context {
+                scrub_fields(fields);
+            }
+        }
+
+        scrub_fields(&mut event.extra);
+
+        event
+    }
+
+    fn scrub_fields<'a>(
+        fields: impl IntoIterator<Item = (&'a String, &'a mut Value)>,
+    ) {
+        for (name, value) in fields {
+            if is_sensitive(name) {
+                *value = Value::String(REDACTED.to_owned());
+            } else {
+                scrub_value(value);
+            }
+        }
+    }
+
+    fn scrub_value(value: &mut Value) {
+        match value {
+            Value::Object(fields) => scrub_fields(fields),
+            Value::Array(values) => values.iter_mut().for_each(scrub_value),
+            _ => {}
+        }
+    }
+
+    fn is_sensitive(name: &str) -> bool {
+        let name = name.to_ascii_lowercase();
+        SENSITIVE_KEYS.iter().any(|key| name.contains(key))
+    }
+}
@@ -0,0 +1,183 @@
+//! Live feed of user events to admin dashboards, see the `/events/stream` route.
+//!
+//! Every process tails the outbox on its own and broadcasts the new events to its subscribers,
+//! so they see the events recorded by all the processes that share the database. Subscribers
+//! that fall behind are disconnected, and catch up from the outbox once they reconnect with the
+//! position of the last event they've seen, see [UserEventFeed::subscribe].
+
+use std::{collections::VecDeque, sync::Arc, time::Duration};
+
+use eyre::Result;
+use futures_util::{StreamExt as _, stream, stream::BoxStream};
+use identify_application::{
+    ApplicationError, EventFeedUseCaseDeps, TailUserEventsParams,
+    UserEventTail, tail_user_events, user_event_contracts::RecordedUserEvent,
+};
+use identify_infrastructure::storage::{
+    self, connection::ReadPool, outbox::OutboxRepository,
+};
+use tokio::{
+    sync::broadcast::{self, error::RecvError},
+    task::JoinHandle,
+};
+use tracing::{Instrument, error, info_span, warn};
+
+use crate::config::EventStreamConfig;
+
+/// Most events read from the outbox at once.
+const BATCH_SIZE: u32 = 100;
+
+/// Events as they're streamed to subscribers. The stream ends after the first error.
+pub type UserEventStream =
+    BoxStream<'static, Result<Arc<RecordedUserEvent>, ApplicationError>>;
+
+/// Broadcasts the user events recorded in the outbox to the subscribers of the process.
+#[derive(Clone)]
+pub struct UserEventFeed {
+    pool: ReadPool,
+    sender: broadcast::Sender<Arc<RecordedUserEvent>>,
+    poll_interval: Duration,
+    keep_alive: Duration,
+}
+
+impl UserEventFeed {
+    pub fn from_config(pool: ReadPool, config: &EventStreamConfig) -> Self {
+        UserEventFeed {
+            pool,
+            sender: broadcast::channel(config.buffer).0,
+            poll_interval: config.poll_interval(),
+            keep_alive: config.keep_alive(),
+        }
+    }
+
+    /// How often idle subscribers are sent a comment to keep their connections open.
+    pub fn keep_alive(&self) -> Duration {
+        self.keep_alive
+    }
+
+    /// Tails the outbox in the background until the process exits, starting with the events
+    /// recorded from now on.
+    pub fn spawn(&self) -> JoinHandle<()> {
+        let feed = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(feed.poll_interval);
+            let mut sequence = None;
+            loop {
+                interval.tick().await;
+                let span = info_span!("event_feed");
+                match feed.run_once(sequence).instrument(span).await {
+                    Ok(next) => sequence = Some(next),
+                    Err(e) => error!(error = ?e, "Failed to tail user events"),
+                }
+            }
+        })
+    }
+
+    /// Broadcasts the events recorded after the one at `after`, or none if it's `None`.
+    ///
+    /// Returns the position of the last event, to be passed to the next run.
+    pub async fn run_once(&self, mut after: Option<u64>) -> Result<u64> {
+        loop {
+            let tail = tail(&self.pool, after).await?;
+            let done = tail.events.len() < BATCH_SIZE as usize;
+            for event in tail.events {
+                // Sending fails only if nobody is subscribed.
+                let _ = self.sender.send(Arc::new(event));
+            }
+            if done {
+                return Ok(tail.sequence);
+            }
+
+            after = Some(tail.sequence);
+        }
+    }
+
+    /// Subscribes to the events broadcast from now on.
+    ///
+    /// Subscribers that pass the position of the last event they've seen first receive the
+    /// events recorded since then that are still in the outbox. Events are never received
+    /// twice, since the live ones are subscribed to before the missed ones are read.
+    pub fn subscribe(&self, after: Option<u64>) -> UserEventStream {
+        let subscription = Subscription {
+            pool: self.pool.clone(),
+            receiver: self.sender.subscribe(),
+            replay_after: after,
+            replayed_through: after.unwrap_or(0),
+            pending: VecDeque::new(),
+        };
+
+        stream::unfold(Some(subscription), |subscription| async move {
+            let mut subscription = subscription?;
+            match subscription.next().await {
+                Ok(Some(event)) => Some((Ok(event), Some(subscription))),
+                Ok(None) => None,
+                Err(e) => Some((Err(e), None)),
+            }
+        })
+        .boxed()
+    }
+}
+
+async fn tail(
+    pool: &ReadPool,
+    after: Option<u64>,
+) -> Result<UserEventTail, ApplicationError> {
+    let mut conn = storage::acquire(pool).await?;
+    let mut repository = OutboxRepository::new(&mut conn);
+
+    tail_user_events(
+        EventFeedUseCaseDeps::new(&mut repository),
+        TailUserEventsParams {
+            after,
+            limit: BATCH_SIZE,
+        },
+    )
+    .await
+}
+
+struct Subscription {
+    pool: ReadPool,
+    receiver: broadcast::Receiver<Arc<RecordedUserEvent>>,
+    /// Position the missed events are read after, until they're all read.
+    replay_after: Option<u64>,
+    /// Position of the last missed event, live events up to which have already been received.
+    replayed_through: u64,
+    pending: VecDeque<Arc<RecordedUserEvent>>,
+}
+
+impl Subscription {
+    /// Returns the next event, or `None` once the subscriber has to reconnect.
+    async fn next(
+        &mut self,
+    ) -> Result<Option<Arc<RecordedUserEvent>>, ApplicationError> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Ok(Some(event));
+            }
+
+            if let Some(after) = self.replay_after {
+                let tail = tail(&self.pool, Some(after)).await?;
+                self.replay_after =
+                    (!tail.events.is_empty()).then_some(tail.sequence);
+                self.replayed_through = tail.sequence;
+                self.pending.extend(tail.events.into_iter().map(Arc::new));
+                continue;
+            }
+
+            match self.receiver.recv().await {
+                Ok(event) if event.sequence > self.replayed_through => {
+                    return Ok(Some(event));
+                }
+                Ok(_) => {}
+                Err(RecvError::Lagged(missed)) => {
+                    warn!(
+                        missed,
+                        "Disconnecting a subscriber that fell behind"
+                    );
+                    return Ok(None);
+                }
+                Err(RecvError::Closed) => return Ok(None),
+            }
+        }
+    }
+}
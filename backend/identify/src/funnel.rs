@@ -0,0 +1,178 @@
+//! Funnel of the login flow for product analytics.
+//!
+//! Every login attempt gets a random correlation ID that ties its steps together. Events only
+//! carry the step, the correlation ID and the time since the attempt started, never who is
+//! logging in or from where, so drop-off can be measured without any client-side tracking.
+//!
+//! Events are counted in [LOGIN_FUNNEL_EVENTS_METRIC] and logged with the `identify::funnel`
+//! target, which can be routed to the analytics event stream.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+
+use metrics::counter;
+use tokio::task::JoinHandle;
+use tracing::{debug, info};
+use uuid::Uuid;
+
+use crate::metrics::{
+    LOGIN_FUNNEL_ABANDONED_METRIC, LOGIN_FUNNEL_EVENTS_METRIC,
+};
+
+/// Attempts tracked at the same time. Steps of attempts started beyond it are still counted,
+/// but they are never reported as abandoned.
+const MAX_ATTEMPTS: usize = 100_000;
+
+/// How often attempts are checked for abandonment.
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// A step of the login flow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoginStep {
+    LoginStarted,
+    PasswordOk,
+    TwoFactorPrompted,
+    TwoFactorOk,
+    SessionIssued,
+    /// The attempt didn't get a session in time.
+    Abandoned,
+}
+
+impl LoginStep {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LoginStep::LoginStarted => "login_started",
+            LoginStep::PasswordOk => "password_ok",
+            LoginStep::TwoFactorPrompted => "2fa_prompted",
+            LoginStep::TwoFactorOk => "2fa_ok",
+            LoginStep::SessionIssued => "session_issued",
+            LoginStep::Abandoned => "abandoned",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Attempt {
+    started: Instant,
+    last_step: LoginStep,
+}
+
+/// Records the steps of login attempts, and reports the attempts that don't finish in time as
+/// abandoned. Cloning it is cheap, clones share the same attempts.
+#[derive(Debug, Clone)]
+pub struct LoginFunnel {
+    attempts: Arc<Mutex<HashMap<Uuid, Attempt>>>,
+    abandon_after: Duration,
+}
+
+impl LoginFunnel {
+    /// Creates a funnel that considers attempts without a session after `abandon_after`
+    /// abandoned.
+    pub fn new(abandon_after: Duration) -> Self {
+        LoginFunnel {
+            attempts: Arc::default(),
+            abandon_after,
+        }
+    }
+
+    /// Starts a new attempt and returns its correlation ID, which must be passed along to the
+    /// following steps (e.g. in the login form).
+    pub fn start(&self) -> Uuid {
+        let id = Uuid::new_v4();
+        let now = Instant::now();
+
+        let mut attempts = self.lock();
+        if attempts.len() < MAX_ATTEMPTS {
+            attempts.insert(
+                id,
+                Attempt {
+                    started: now,
+                    last_step: LoginStep::LoginStarted,
+                },
+            );
+        }
+        drop(attempts);
+
+        emit(LoginStep::LoginStarted, id, Duration::ZERO);
+
+        id
+    }
+
+    /// Records a step of the attempt. Issuing a session finishes it.
+    pub fn record(&self, id: Uuid, step: LoginStep) {
+        let mut attempts = self.lock();
+        let elapsed = match attempts.get_mut(&id) {
+            Some(attempt) => {
+                attempt.last_step = step;
+                attempt.started.elapsed()
+            }
+            None => {
+                debug!(attempt = %id, step = step.as_str(), "Unknown login attempt");
+                Duration::ZERO
+            }
+        };
+        if step == LoginStep::SessionIssued {
+            attempts.remove(&id);
+        }
+        drop(attempts);
+
+        emit(step, id, elapsed);
+    }
+
+    /// Reports the attempts that have been going on for too long as abandoned and forgets
+    /// them. Returns their number.
+    pub fn sweep(&self, now: Instant) -> usize {
+        let mut abandoned = Vec::new();
+        self.lock().retain(|id, attempt| {
+            let elapsed = now.saturating_duration_since(attempt.started);
+            if elapsed < self.abandon_after {
+                return true;
+            }
+
+            abandoned.push((*id, *attempt, elapsed));
+            false
+        });
+
+        for (id, attempt, elapsed) in &abandoned {
+            counter!(
+                LOGIN_FUNNEL_ABANDONED_METRIC,
+                "last_step" => attempt.last_step.as_str()
+            )
+            .increment(1);
+            emit(LoginStep::Abandoned, *id, *elapsed);
+        }
+
+        abandoned.len()
+    }
+
+    /// Sweeps abandoned attempts in the background until the process exits.
+    pub fn spawn_sweeper(&self) -> JoinHandle<()> {
+        let funnel = self.clone();
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(SWEEP_INTERVAL);
+            loop {
+                interval.tick().await;
+                funnel.sweep(Instant::now());
+            }
+        })
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<Uuid, Attempt>> {
+        self.attempts.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+fn emit(step: LoginStep, id: Uuid, elapsed: Duration) {
+    counter!(LOGIN_FUNNEL_EVENTS_METRIC, "step" => step.as_str()).increment(1);
+    info!(
+        target: "identify::funnel",
+        event = step.as_str(),
+        attempt = %id,
+        elapsed_ms = elapsed.as_millis() as u64,
+        "Login funnel event"
+    );
+}
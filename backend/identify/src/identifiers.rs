@@ -0,0 +1,135 @@
+//! Lists of the identifiers nobody can choose, loaded from the configured files.
+//!
+//! The files are checked for changes periodically, so that the lists can be updated without
+//! restarting the server. A list that fails to load keeps the previous version in use.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, PoisonError, RwLock},
+    time::SystemTime,
+};
+
+use eyre::{Context, Result};
+use identify_domain::IdentifierPolicy;
+use tokio::task::JoinHandle;
+use tracing::{error, info};
+
+use crate::config::IdentifiersConfig;
+
+/// The current [IdentifierPolicy], shared by everything that lets users choose identifiers.
+#[derive(Clone)]
+pub struct IdentifierLists {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    reserved_file: Option<PathBuf>,
+    profanity_file: Option<PathBuf>,
+    policy: RwLock<Arc<IdentifierPolicy>>,
+    /// Modification times of the files the policy was loaded from.
+    loaded: Mutex<[Option<SystemTime>; 2]>,
+}
+
+impl IdentifierLists {
+    /// Loads the configured lists, failing if any of the files can't be read.
+    pub fn from_config(config: &IdentifiersConfig) -> Result<Self> {
+        let lists = IdentifierLists {
+            inner: Arc::new(Inner {
+                reserved_file: config.reserved_file.clone(),
+                profanity_file: config.profanity_file.clone(),
+                policy: RwLock::new(Arc::default()),
+                loaded: Mutex::new([None; 2]),
+            }),
+        };
+        lists.reload()?;
+
+        Ok(lists)
+    }
+
+    /// Lists with only the built-in reserved words, e.g. for tests.
+    pub fn builtin() -> Self {
+        IdentifierLists::from_config(&IdentifiersConfig::default())
+            .expect("no files to load")
+    }
+
+    /// Returns the current policy. Later reloads don't affect the returned one.
+    pub fn policy(&self) -> Arc<IdentifierPolicy> {
+        self.inner
+            .policy
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Loads the files again if any of them has changed since the last load.
+    ///
+    /// Returns whether the policy has been replaced.
+    pub fn reload(&self) -> Result<bool> {
+        let inner = &self.inner;
+        let files = [&inner.reserved_file, &inner.profanity_file];
+        let modified = files.map(|file| file.as_deref().and_then(modified_at));
+
+        let mut loaded =
+            inner.loaded.lock().unwrap_or_else(PoisonError::into_inner);
+        if *loaded == modified && modified.iter().any(Option::is_some) {
+            return Ok(false);
+        }
+
+        let policy = IdentifierPolicy::new(
+            read_list(inner.reserved_file.as_deref())?,
+            read_list(inner.profanity_file.as_deref())?,
+        );
+        *inner.policy.write().unwrap_or_else(PoisonError::into_inner) =
+            Arc::new(policy);
+        *loaded = modified;
+
+        Ok(true)
+    }
+
+    /// Checks the files for changes in the background until the process exits.
+    ///
+    /// Nothing is spawned if there are no files to check.
+    pub fn spawn_reloader(
+        &self,
+        config: &IdentifiersConfig,
+    ) -> Option<JoinHandle<()>> {
+        let interval = config.reload_interval()?;
+        if self.inner.reserved_file.is_none()
+            && self.inner.profanity_file.is_none()
+        {
+            return None;
+        }
+
+        let lists = self.clone();
+        Some(tokio::spawn(async move {
+            let mut ticks = tokio::time::interval(interval);
+            ticks.tick().await;
+            loop {
+                ticks.tick().await;
+                match lists.reload() {
+                    Ok(true) => info!("Reloaded the identifier lists"),
+                    Ok(false) => {}
+                    Err(e) => {
+                        error!(error = ?e, "Failed to reload the identifier lists");
+                    }
+                }
+            }
+        }))
+    }
+}
+
+fn read_list(path: Option<&Path>) -> Result<Vec<String>> {
+    let Some(path) = path else {
+        return Ok(Vec::new());
+    };
+
+    let text = std::fs::read_to_string(path).wrap_err_with(|| {
+        format!("failed to read the word list '{}'", path.display())
+    })?;
+
+    Ok(IdentifierPolicy::parse_list(&text))
+}
+
+fn modified_at(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
@@ -0,0 +1,195 @@
+//! Breakdown of request latencies by the stage of the request handling.
+//!
+//! Stages are measured with the spans they already run in: the handler span created by the
+//! API, the spans of use cases and the spans of database queries. [LatencyLayer] attributes
+//! the time spent in every such span, minus the time spent in the spans nested in it, to the
+//! request span, so the stages add up to the latency of the whole request. Whatever is left
+//! is the time spent in the middleware.
+//!
+//! Spans are only measured if the log filter enables them, which the default `identify=info`
+//! filter does.
+
+use std::time::{Duration, Instant};
+
+use tracing::{Metadata, Span, Subscriber, span};
+use tracing_subscriber::{
+    Layer, Registry, layer::Context, registry::LookupSpan,
+};
+
+/// Name of the span every request is handled in.
+pub const REQUEST_SPAN: &str = "request";
+/// Name of the span handlers run in.
+pub const HANDLER_SPAN: &str = "handler";
+/// Name of the spans of database queries.
+pub const QUERY_SPAN: &str = "db.query";
+
+/// Target prefix of the spans of use cases.
+const USE_CASE_TARGET: &str = "identify_application";
+
+/// A stage of the request handling.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    Middleware,
+    Handler,
+    UseCase,
+    Repository,
+}
+
+impl Stage {
+    pub const ALL: [Stage; 4] = [
+        Stage::Middleware,
+        Stage::Handler,
+        Stage::UseCase,
+        Stage::Repository,
+    ];
+
+    /// Name used in metric labels.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Stage::Middleware => "middleware",
+            Stage::Handler => "handler",
+            Stage::UseCase => "use_case",
+            Stage::Repository => "repository",
+        }
+    }
+
+    /// Short name used in the `Server-Timing` header.
+    pub fn server_timing_name(&self) -> &'static str {
+        match self {
+            Stage::Middleware => "mw",
+            Stage::Handler => "handler",
+            Stage::UseCase => "usecase",
+            Stage::Repository => "db",
+        }
+    }
+
+    fn of(metadata: &Metadata<'_>) -> Option<Self> {
+        match metadata.name() {
+            HANDLER_SPAN => Some(Stage::Handler),
+            QUERY_SPAN => Some(Stage::Repository),
+            _ if metadata.target().starts_with(USE_CASE_TARGET) => {
+                Some(Stage::UseCase)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Time spent in every stage of a single request, excluding the stages nested in it.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Breakdown {
+    handler: Duration,
+    use_case: Duration,
+    repository: Duration,
+}
+
+impl Breakdown {
+    /// Returns the time spent in the stage, with the middleware getting whatever is left of
+    /// the total.
+    pub fn get(&self, stage: Stage, total: Duration) -> Duration {
+        match stage {
+            Stage::Middleware => total
+                .saturating_sub(self.handler)
+                .saturating_sub(self.use_case)
+                .saturating_sub(self.repository),
+            Stage::Handler => self.handler,
+            Stage::UseCase => self.use_case,
+            Stage::Repository => self.repository,
+        }
+    }
+
+    fn add(&mut self, stage: Stage, duration: Duration) {
+        let slot = match stage {
+            Stage::Handler => &mut self.handler,
+            Stage::UseCase => &mut self.use_case,
+            Stage::Repository => &mut self.repository,
+            Stage::Middleware => return,
+        };
+
+        *slot += duration;
+    }
+
+    /// Returns the breakdown collected so far for the request handled in the current span.
+    pub fn current() -> Option<Breakdown> {
+        Span::current()
+            .with_subscriber(|(id, dispatch)| {
+                dispatch
+                    .downcast_ref::<Registry>()?
+                    .span(id)?
+                    .extensions()
+                    .get::<Breakdown>()
+                    .copied()
+            })
+            .flatten()
+    }
+}
+
+/// Timing of a single span of a [Stage].
+struct SpanTiming {
+    stage: Stage,
+    started_at: Instant,
+    /// Time spent in the spans of other stages nested in this one.
+    nested: Duration,
+}
+
+/// Layer that collects the [Breakdown] of every request.
+pub struct LatencyLayer;
+
+impl<S> Layer<S> for LatencyLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(
+        &self,
+        attrs: &span::Attributes<'_>,
+        id: &span::Id,
+        ctx: Context<'_, S>,
+    ) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+
+        if attrs.metadata().name() == REQUEST_SPAN {
+            span.extensions_mut().insert(Breakdown::default());
+        } else if let Some(stage) = Stage::of(attrs.metadata()) {
+            span.extensions_mut().insert(SpanTiming {
+                stage,
+                started_at: Instant::now(),
+                nested: Duration::ZERO,
+            });
+        }
+    }
+
+    fn on_close(&self, id: span::Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+
+        let Some(SpanTiming {
+            stage,
+            started_at,
+            nested,
+        }) = span.extensions_mut().remove::<SpanTiming>()
+        else {
+            return;
+        };
+        let elapsed = started_at.elapsed();
+
+        let mut is_nested = false;
+        for ancestor in span.scope().skip(1) {
+            let mut extensions = ancestor.extensions_mut();
+
+            if !is_nested
+                && let Some(parent) = extensions.get_mut::<SpanTiming>()
+            {
+                parent.nested += elapsed;
+                is_nested = true;
+            }
+
+            if let Some(breakdown) = extensions.get_mut::<Breakdown>() {
+                breakdown.add(stage, elapsed.saturating_sub(nested));
+                return;
+            }
+        }
+    }
+}
@@ -1,2 +1,25 @@
+pub mod admin;
 pub mod api;
+pub mod backup;
+pub mod config;
+pub mod cron;
+pub mod digest;
+pub mod error_reporting;
+pub mod event_feed;
+pub mod funnel;
+pub mod identifiers;
+pub mod latency;
 pub mod logging;
+pub mod logins;
+pub mod maintenance;
+pub mod metrics;
+pub mod operations;
+pub mod read_model;
+pub mod redis;
+pub mod reverification;
+pub mod seed;
+pub mod session_monitor;
+pub mod shutdown;
+pub mod status;
+pub mod streaming;
+pub mod webhooks;
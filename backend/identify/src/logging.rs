@@ -1,19 +1,182 @@
-use eyre::{Result, eyre};
-use tracing_subscriber::EnvFilter;
+use std::path::PathBuf;
+
+use eyre::{Result, WrapErr, eyre};
+use tracing::Subscriber;
+use tracing_appender::{
+    non_blocking::WorkerGuard,
+    rolling::{RollingFileAppender, Rotation},
+};
+use tracing_subscriber::{
+    EnvFilter, Layer, fmt, fmt::MakeWriter, prelude::*, registry::LookupSpan,
+};
+
+use crate::{
+    config::{FileLoggingConfig, LogFormat, LogRotation, LoggingConfig},
+    latency::LatencyLayer,
+};
 
 pub const LOGGING_ENV: &str = "IDENTIFY_LOG";
+pub const LOG_FORMAT_ENV: &str = "IDENTIFY_LOG_FORMAT";
+/// Directory to write log files to, enables file logging.
 pub const LOGGING_FILE_ENV: &str = "IDENTIFY_FILE_LOG";
 
-pub fn init() -> Result<()> {
+/// Keeps the logging alive and flushes buffered logs when dropped.
+#[must_use = "buffered logs are lost if the guard is dropped right away"]
+pub struct LoggingGuard {
+    _file_guard: Option<WorkerGuard>,
+}
+
+pub fn init(config: &LoggingConfig) -> Result<LoggingGuard> {
     let env_filter = EnvFilter::builder()
         .with_env_var(LOGGING_ENV)
         .try_from_env()
-        .unwrap_or_else(|_| "identify=info".into());
+        .unwrap_or_else(|_| config.filter.as_str().into());
+
+    let format = match std::env::var(LOG_FORMAT_ENV) {
+        Ok(format) => format
+            .parse::<LogFormat>()
+            .map_err(|e| eyre!(e))
+            .wrap_err_with(|| format!("{LOG_FORMAT_ENV} is invalid"))?,
+        Err(_) => config.format,
+    };
+
+    let (file_writer, file_guard) = match file_appender(&config.file)? {
+        Some(appender) => {
+            let (writer, guard) = tracing_appender::non_blocking(appender);
+            (Some(writer), Some(guard))
+        }
+        None => (None, None),
+    };
+
+    let subscriber = tracing_subscriber::registry()
+        .with(env_filter)
+        .with(format_layer(format, std::io::stdout, true))
+        .with(file_writer.map(|writer| format_layer(format, writer, false)))
+        .with(LatencyLayer);
+
+    #[cfg(feature = "tracing-error")]
+    let subscriber = subscriber.with(tracing_error::ErrorLayer::default());
 
-    tracing_subscriber::fmt()
+    #[cfg(feature = "sentry")]
+    let subscriber = subscriber.with(sentry::integrations::tracing::layer());
+
+    subscriber.try_init().map_err(|e| eyre!(e))?;
+
+    #[cfg(feature = "tracing-error")]
+    span_trace::install_hook()?;
+
+    Ok(LoggingGuard {
+        _file_guard: file_guard,
+    })
+}
+
+/// Builds the formatting layer that writes logs to the provided writer.
+///
+/// Request context (e.g. `request_id` and `trace_id`) is recorded in the spans, which are
+/// included in every JSON line.
+fn format_layer<S, W>(
+    format: LogFormat,
+    writer: W,
+    ansi: bool,
+) -> Box<dyn Layer<S> + Send + Sync>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    let layer = fmt::layer()
+        .with_writer(writer)
+        .with_ansi(ansi)
         .with_file(true)
-        .with_line_number(true)
-        .with_env_filter(env_filter)
-        .try_init()
-        .map_err(|e| eyre!(e))
+        .with_line_number(true);
+
+    match format {
+        LogFormat::Text => layer.boxed(),
+        LogFormat::Json => layer
+            .json()
+            .with_current_span(true)
+            .with_span_list(true)
+            .boxed(),
+    }
+}
+
+/// Creates the appender of rotated log files if file logging is enabled.
+fn file_appender(
+    config: &FileLoggingConfig,
+) -> Result<Option<RollingFileAppender>> {
+    let Some(directory) = std::env::var_os(LOGGING_FILE_ENV)
+        .map(PathBuf::from)
+        .or_else(|| config.directory.clone())
+    else {
+        return Ok(None);
+    };
+
+    let rotation = match config.rotation {
+        LogRotation::Minutely => Rotation::MINUTELY,
+        LogRotation::Hourly => Rotation::HOURLY,
+        LogRotation::Daily => Rotation::DAILY,
+        LogRotation::Never => Rotation::NEVER,
+    };
+
+    let mut builder = RollingFileAppender::builder()
+        .rotation(rotation)
+        .filename_prefix(&config.prefix);
+    if let Some(max_files) = config.max_files {
+        builder = builder.max_log_files(max_files);
+    }
+
+    let appender = builder.build(&directory).wrap_err_with(|| {
+        format!("failed to create log files in '{}'", directory.display())
+    })?;
+
+    Ok(Some(appender))
+}
+
+/// Captures span traces of all [eyre::Report]s.
+///
+/// Internal errors are usually created deep inside use cases and repositories, so the span
+/// trace tells which request and which use case they came from.
+#[cfg(feature = "tracing-error")]
+mod span_trace {
+    use std::{error::Error, fmt};
+
+    use eyre::{Chain, EyreHandler, Result};
+    use tracing_error::{SpanTrace, SpanTraceStatus};
+
+    struct SpanTraceHandler {
+        span_trace: SpanTrace,
+    }
+
+    impl EyreHandler for SpanTraceHandler {
+        fn debug(
+            &self,
+            error: &(dyn Error + 'static),
+            f: &mut fmt::Formatter<'_>,
+        ) -> fmt::Result {
+            if f.alternate() {
+                return fmt::Debug::fmt(error, f);
+            }
+
+            write!(f, "{error}")?;
+
+            for (idx, cause) in Chain::new(error).skip(1).enumerate() {
+                write!(f, "\n  {idx}: {cause}")?;
+            }
+
+            if self.span_trace.status() == SpanTraceStatus::CAPTURED {
+                write!(f, "\n\nSpan trace:\n{}", self.span_trace)?;
+            }
+
+            Ok(())
+        }
+    }
+
+    pub fn install_hook() -> Result<()> {
+        eyre::set_hook(Box::new(|_| {
+            Box::new(SpanTraceHandler {
+                span_trace: SpanTrace::capture(),
+            })
+        }))?;
+
+        Ok(())
+    }
 }
@@ -0,0 +1,178 @@
+//! Statistics of the logins of users, see [User::login_count](identify_domain::User::login_count).
+//!
+//! Logins are counted in the memory of the process, and added to the statistics of their users
+//! in batches, so that every login doesn't write to the user it belongs to. The statistics lag
+//! behind logins by up to
+//! [LoginsConfig::flush_interval_ms](crate::config::LoginsConfig::flush_interval_ms).
+//!
+//! The logins still pending when the server shuts down are added once more, for the primary
+//! database and for every tenant database that was opened. The ones pending when the process
+//! crashes are lost.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use eyre::Result;
+use identify_application::{
+    LoginRecorder,
+    user_contracts::{LoginTally, RecordLogins as _},
+};
+use identify_infrastructure::{
+    encryption::FieldEncryption,
+    storage::{self, users::UsersRepository},
+};
+use sqlx::SqlitePool;
+use tokio::task::JoinHandle;
+use tracing::{Instrument, debug, error, info_span};
+use uuid::Uuid;
+
+use crate::{config::LoginsConfig, shutdown::Shutdown};
+
+/// Counts the logins of users and adds them to their statistics in batches. Cloning it is
+/// cheap, clones share the same pending logins.
+#[derive(Clone)]
+pub struct LoginTracker {
+    pool: SqlitePool,
+    encryption: FieldEncryption,
+    flush_interval: Duration,
+    /// Logins that haven't been added to the statistics yet, by user ID.
+    pending: Arc<Mutex<HashMap<Uuid, LoginTally>>>,
+    /// Jobs that add the logins of the tracker, and of the trackers created from it.
+    jobs: Arc<Mutex<Vec<JoinHandle<()>>>>,
+}
+
+impl LoginTracker {
+    pub fn from_config(
+        pool: SqlitePool,
+        encryption: FieldEncryption,
+        config: &LoginsConfig,
+    ) -> Self {
+        LoginTracker {
+            pool,
+            encryption,
+            flush_interval: config.flush_interval(),
+            pending: Arc::default(),
+            jobs: Arc::default(),
+        }
+    }
+
+    /// Creates a tracker of the logins to another database, e.g. the one of a tenant, that
+    /// adds them just as often. [finished](Self::finished) waits for it too.
+    pub fn with_pool(&self, pool: SqlitePool) -> Self {
+        LoginTracker {
+            pool,
+            encryption: self.encryption.clone(),
+            flush_interval: self.flush_interval,
+            pending: Arc::default(),
+            jobs: self.jobs.clone(),
+        }
+    }
+
+    /// Adds the pending logins in the background until the shutdown starts, and once more
+    /// then.
+    pub fn spawn(&self, mut shutdown: Shutdown) {
+        let tracker = self.clone();
+        let job = tokio::spawn(async move {
+            let mut interval = tokio::time::interval(tracker.flush_interval);
+            loop {
+                let stopping = tokio::select! {
+                    _ = interval.tick() => false,
+                    () = shutdown.wait() => true,
+                };
+
+                let span = info_span!("logins");
+                if let Err(e) = tracker.flush().instrument(span).await {
+                    error!(error = ?e, "Failed to record the logins of users");
+                }
+                if stopping {
+                    return;
+                }
+            }
+        });
+
+        self.jobs
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(job);
+    }
+
+    /// Waits until the logins pending when the shutdown started have been added, by this
+    /// tracker and by every tracker created from it.
+    pub async fn finished(&self) -> Result<()> {
+        let jobs = std::mem::take(
+            &mut *self.jobs.lock().unwrap_or_else(PoisonError::into_inner),
+        );
+        for job in jobs {
+            job.await?;
+        }
+
+        Ok(())
+    }
+
+    /// Adds all the pending logins to the statistics of their users.
+    ///
+    /// Returns the number of users whose statistics changed. The logins are kept pending if
+    /// they can't be added, so that the next flush tries again.
+    pub async fn flush(&self) -> Result<usize> {
+        let tallies = std::mem::take(&mut *self.lock())
+            .into_values()
+            .collect::<Vec<_>>();
+        if tallies.is_empty() {
+            return Ok(0);
+        }
+
+        if let Err(e) = self.write(&tallies).await {
+            let mut pending = self.lock();
+            for tally in tallies {
+                add(&mut pending, tally);
+            }
+            return Err(e);
+        }
+        debug!(users = tallies.len(), "Recorded the logins of users");
+
+        Ok(tallies.len())
+    }
+
+    async fn write(&self, tallies: &[LoginTally]) -> Result<()> {
+        let mut tx = storage::begin(&self.pool).await?;
+        UsersRepository::new(&mut tx, &self.encryption)
+            .record_logins(tallies)
+            .await?;
+        storage::commit(tx).await?;
+
+        Ok(())
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<Uuid, LoginTally>> {
+        self.pending.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl LoginRecorder for LoginTracker {
+    fn record(&self, user_id: Uuid, logged_in_at: DateTime<Utc>) {
+        add(
+            &mut self.lock(),
+            LoginTally {
+                user_id,
+                count: 1,
+                last_login_at: logged_in_at,
+            },
+        );
+    }
+}
+
+/// Adds the tally to the pending one of the same user, if any.
+fn add(pending: &mut HashMap<Uuid, LoginTally>, tally: LoginTally) {
+    pending
+        .entry(tally.user_id)
+        .and_modify(|pending| {
+            pending.count = pending.count.saturating_add(tally.count);
+            pending.last_login_at =
+                pending.last_login_at.max(tally.last_login_at);
+        })
+        .or_insert(tally);
+}
@@ -1,18 +1,332 @@
-use axum::{Router, routing::get};
-use eyre::{Context, Result};
-use identify::logging;
+use std::{net::SocketAddr, sync::Arc};
+
+use eyre::{Context, Result, eyre};
+use identify::{
+    api::{
+        self, ApiState,
+        middleware::{latency::ServerTiming, sdk::SdkTracker, security},
+        rate_limit::RateLimiter,
+        tenancy::{self, Tenants},
+    },
+    backup::BackupScheduler,
+    config::{Config, TenancyStrategy},
+    digest::DigestJob,
+    error_reporting,
+    event_feed::UserEventFeed,
+    identifiers::IdentifierLists,
+    logging,
+    logins::LoginTracker,
+    maintenance::MaintenanceScheduler,
+    metrics::Metrics,
+    operations::OperationRunner,
+    read_model::ReadModelProjector,
+    redis::Redis,
+    session_monitor::SessionMonitor,
+    shutdown,
+    status::StatusBoard,
+    streaming,
+    webhooks::WebhookWorker,
+};
+use identify_application::authorization_policy_contracts::ListAll as _;
+use identify_application::{
+    EnsureSigningKeyParams, SigningKeyRotationDeps, ensure_signing_key,
+};
+use identify_domain::AuthorizationPolicies;
+use identify_infrastructure::{
+    encryption::FieldEncryption,
+    passwords::Argon2Hasher,
+    signing::Ed25519KeyGenerator,
+    storage::{
+        self,
+        authorization_policies::AuthorizationPoliciesRepository,
+        connection::{self, ReadPool},
+        retry::Retrier,
+        signing_keys::SigningKeysRepository,
+        timing,
+    },
+    tokens::RandomTokenGenerator,
+};
+use sqlx::SqlitePool;
 use tracing::info;
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    logging::init().wrap_err("error while initializing the logging")?;
+    let config = Config::load()?;
+
+    let _logging = logging::init(&config.logging)
+        .wrap_err("error while initializing the logging")?;
+    let _error_reporting = error_reporting::init(&config.error_reporting)
+        .wrap_err("error while initializing the error reporting")?;
 
     info!("Initializing!");
 
-    let app = Router::new().route("/", get(|| async { "Hello, World!" }));
+    let metrics =
+        Metrics::install().wrap_err("error while initializing the metrics")?;
+
+    let sdk_policy = config.sdk.policy().map_err(|e| eyre!(e))?;
+
+    let encryption =
+        match config.encryption.key_provider().map_err(|e| eyre!(e))? {
+            Some(keys) => FieldEncryption::from_provider(&keys)
+                .await
+                .wrap_err("error while loading the encryption keys")?,
+            None => FieldEncryption::disabled(),
+        };
+
+    timing::set_slow_query_threshold(config.database.slow_query_threshold());
+    let pool_config = config.database.pool_config();
+    let pool = connection::get_pool(&config.database.url, &pool_config)
+        .await
+        .wrap_err("error while connecting to the database")?;
+    connection::migrate(&pool)
+        .await
+        .wrap_err("error while applying migrations")?;
+    ensure_signing_key_exists(&pool, &encryption)
+        .await
+        .wrap_err("error while generating the first signing key")?;
+    let read_pool = connection::get_read_pool(
+        config.database.read_url(),
+        &pool_config,
+        &pool,
+    )
+    .await
+    .wrap_err("error while connecting to the read database")?;
+
+    let authenticator = config.auth.authenticator().map_err(|e| eyre!(e))?;
+
+    let status = StatusBoard::new();
+    let email_sender = config.email.sender().map_err(|e| eyre!(e))?;
+
+    if let Some(digest) = DigestJob::from_config(
+        pool.clone(),
+        encryption.clone(),
+        &config.digest,
+        config.quotas.policy(),
+        email_sender.clone(),
+        status.clone(),
+    )
+    .wrap_err("error while initializing the admin digest")?
+    {
+        digest.spawn();
+    }
+
+    let publisher = streaming::publisher(&config.streaming)
+        .wrap_err("error while initializing the streaming")?;
+    let webhooks = WebhookWorker::from_config(
+        pool.clone(),
+        encryption.clone(),
+        &config.webhooks,
+        status.clone(),
+        publisher,
+    )
+    .wrap_err("error while initializing the webhook delivery")?;
+    if let Some(webhooks) = &webhooks {
+        webhooks.with_pool(pool.clone()).spawn();
+    }
+
+    let (shutdown_trigger, shutdown) = shutdown::channel();
+    let maintenance_scheduler = MaintenanceScheduler::from_config(
+        pool.clone(),
+        encryption.clone(),
+        &config.maintenance,
+    )
+    .wrap_err("error while initializing the maintenance")?;
+    let maintenance = maintenance_scheduler.as_ref().map(|maintenance| {
+        maintenance.with_pool(pool.clone()).spawn(shutdown.clone())
+    });
+    let tenant_shutdown = shutdown.clone();
+
+    let blob_storage = config.blob_storage.storage().map_err(|e| eyre!(e))?;
+    let backups = BackupScheduler::from_config(
+        pool.clone(),
+        blob_storage.clone(),
+        &config.backup,
+    )
+    .wrap_err("error while initializing the backups")?
+    .map(|backups| backups.spawn(shutdown.clone()));
+
+    let redis = Redis::connect(&config.redis)
+        .wrap_err("error while initializing Redis")?;
+
+    let user_cache = config
+        .user_cache
+        .cache(redis.as_ref())
+        .map_err(|e| eyre!(e))?;
+    ReadModelProjector::from_config(
+        pool.clone(),
+        encryption.clone(),
+        &config.read_model,
+        user_cache.clone(),
+    )
+    .spawn();
+
+    let logins = LoginTracker::from_config(
+        pool.clone(),
+        encryption.clone(),
+        &config.logins,
+    );
+    logins.spawn(shutdown);
+    let funnel = config.logins.funnel();
+    funnel.spawn_sweeper();
+
+    let user_events =
+        UserEventFeed::from_config(read_pool.clone(), &config.event_stream);
+    user_events.spawn();
+    let sessions = SessionMonitor::from_config(&config.event_stream);
+    sessions.spawn(user_events.clone());
 
-    let listener = tokio::net::TcpListener::bind("0.0.0.0:3000").await.unwrap();
-    axum::serve(listener, app).await.unwrap();
+    let identifiers = IdentifierLists::from_config(&config.identifiers)
+        .wrap_err("error while loading the identifier lists")?;
+    identifiers.spawn_reloader(&config.identifiers);
+
+    let rate_limiter =
+        RateLimiter::from_config(&config.rate_limit, &pool, redis.as_ref())
+            .wrap_err("error while initializing the rate limiter")?;
+
+    let authorization_policies =
+        load_authorization_policies(&config, &read_pool)
+            .await
+            .wrap_err("error while loading the authorization policies")?;
+
+    let state = ApiState {
+        operation_runner: OperationRunner::new(pool.clone(), status.clone()),
+        pool,
+        read_pool,
+        encryption,
+        user_cache,
+        sdk_tracker: SdkTracker::new(sdk_policy),
+        authenticator,
+        metrics,
+        server_timing: ServerTiming(config.server.server_timing),
+        rate_limiter,
+        status,
+        username_strategy: config
+            .users
+            .username_strategy()
+            .map_err(|e| eyre!(e))?,
+        identifiers,
+        blob_storage,
+        email_sender,
+        email_templates: Arc::new(
+            config.email.templates().map_err(|e| eyre!(e))?,
+        ),
+        geo_resolver: config.devices.geo_resolver().map_err(|e| eyre!(e))?,
+        consent_policies: Arc::new(
+            config.consents.policies().map_err(|e| eyre!(e))?,
+        ),
+        authorization_policies: Arc::new(authorization_policies),
+        token_generator: Arc::new(RandomTokenGenerator::new()),
+        invitations: config.invitations.settings(),
+        email_changes: config.email_changes.settings(),
+        password_hasher: Arc::new(Argon2Hasher::new()),
+        registration: config.registration.settings().map_err(|e| eyre!(e))?,
+        quotas: config.quotas.policy(),
+        webhook_urls: config.webhooks.url_policy(),
+        sms_sender: config.sms.sender(),
+        phone_verifications: config.sms.phone_verification_settings(),
+        user_events,
+        sessions,
+        session_tokens: config
+            .sessions
+            .settings(redis.as_ref())
+            .map_err(|e| eyre!(e))?,
+        logins: logins.clone(),
+        funnel,
+        retrier: Retrier::new(config.database.retry_policy()),
+    };
+    let tenants = match config.tenancy.strategy {
+        TenancyStrategy::Shared => None,
+        TenancyStrategy::DatabasePerOrganization => {
+            let mut tenants = Tenants::new(
+                config.tenancy.resolver().map_err(|e| eyre!(e))?,
+                config.tenancy.registry(&config.database),
+                config.read_model.clone(),
+                config.event_stream.clone(),
+                state.clone(),
+                tenant_shutdown,
+            );
+            if let Some(webhooks) = webhooks {
+                tenants = tenants.with_webhooks(webhooks);
+            }
+            if let Some(maintenance) = maintenance_scheduler {
+                tenants = tenants.with_maintenance(maintenance);
+            }
+
+            Some(tenants)
+        }
+    };
+    let app = match tenants {
+        Some(tenants) => tenancy::router(tenants),
+        None => api::router(state),
+    };
+    let app = security::protect(app, &config.cors, &config.security_headers)
+        .wrap_err("error while configuring CORS")?;
+
+    let listener = tokio::net::TcpListener::bind(config.server.bind_address)
+        .await
+        .wrap_err("error while binding the server address")?;
+    info!(address = %config.server.bind_address, "Listening");
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .with_graceful_shutdown(shutdown::signal())
+    .await
+    .wrap_err("error while serving requests")?;
+
+    shutdown_trigger.trigger();
+    logins
+        .finished()
+        .await
+        .wrap_err("error while recording the pending logins")?;
+    if let Some(maintenance) = maintenance {
+        maintenance
+            .await
+            .wrap_err("error while finishing the maintenance")?;
+    }
+    if let Some(backups) = backups {
+        backups
+            .await
+            .wrap_err("error while finishing the backups")?;
+    }
+
+    Ok(())
+}
+
+/// Generates a signing key on the first start, so that the JWKS is never empty.
+async fn ensure_signing_key_exists(
+    pool: &SqlitePool,
+    encryption: &FieldEncryption,
+) -> Result<()> {
+    let mut tx = storage::begin(pool).await?;
+    let key = ensure_signing_key(
+        SigningKeyRotationDeps::new(
+            &mut SigningKeysRepository::new(&mut tx, encryption),
+            &Ed25519KeyGenerator::new(),
+        ),
+        EnsureSigningKeyParams,
+    )
+    .await?;
+    storage::commit(tx).await?;
+
+    info!(key_id = %key.id(), "Signing tokens with the active key");
 
     Ok(())
 }
+
+/// Loads the configured authorization policies along with the ones stored in the database.
+async fn load_authorization_policies(
+    config: &Config,
+    read_pool: &ReadPool,
+) -> Result<AuthorizationPolicies> {
+    let configured = config.authorization.policies().map_err(|e| eyre!(e))?;
+    let mut conn = storage::acquire(read_pool).await?;
+    let stored = AuthorizationPoliciesRepository::new(&mut conn)
+        .list_all()
+        .await?;
+    let policies =
+        AuthorizationPolicies::new(configured.into_iter().chain(stored))?;
+    info!(count = policies.len(), "Loaded the authorization policies");
+
+    Ok(policies)
+}
@@ -0,0 +1,239 @@
+//! Periodic maintenance of the database.
+//!
+//! Every task runs on its own cron schedule (see [Schedule]), delayed by a random jitter so that
+//! replicas sharing a database don't all run it at once. Tasks wait for their next run until the
+//! shutdown starts, and a run in progress is finished before the scheduler stops.
+
+use std::time::Duration;
+
+use chrono::{DateTime, TimeDelta, Utc};
+use eyre::{Result, eyre};
+use identify_application::{
+    MaintenanceUseCaseDeps, PruneSigningKeysParams, PruneUserEventsParams,
+    PruneWebhookDeliveriesParams, RotateSigningKeyParams,
+    SigningKeyRotationDeps, UnitOfWork as _, prune_signing_keys,
+    prune_user_events, prune_webhook_deliveries, rotate_signing_key,
+};
+use identify_infrastructure::{
+    encryption::FieldEncryption, signing::Ed25519KeyGenerator,
+    storage::unit_of_work::SqliteUnitOfWork,
+};
+use sqlx::SqlitePool;
+use tokio::task::{JoinHandle, JoinSet};
+use tracing::{Instrument, debug, error, info, info_span};
+use uuid::Uuid;
+
+use crate::{
+    config::{MaintenanceConfig, MaintenanceTaskConfig},
+    cron::Schedule,
+    shutdown::Shutdown,
+};
+
+/// Signing keys younger than this aren't rotated on schedule, so that replicas sharing a
+/// database rotate them only once per run. Runs are delayed by less, see
+/// [Config::validate](crate::config::Config::validate).
+pub const MIN_SIGNING_KEY_AGE: TimeDelta = TimeDelta::hours(1);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MaintenanceTask {
+    /// Deletes the user events that have been published and projected.
+    PruneUserEvents,
+    /// Deletes the webhook deliveries that have been delivered. Dead letters are kept until
+    /// they are redelivered.
+    PruneWebhookDeliveries,
+    /// Replaces the active signing key with a new one, and deletes the keys that were retired
+    /// before the retention period.
+    RotateSigningKeys,
+}
+
+impl MaintenanceTask {
+    pub const ALL: [MaintenanceTask; 3] = [
+        MaintenanceTask::PruneUserEvents,
+        MaintenanceTask::PruneWebhookDeliveries,
+        MaintenanceTask::RotateSigningKeys,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MaintenanceTask::PruneUserEvents => "prune_user_events",
+            MaintenanceTask::PruneWebhookDeliveries => {
+                "prune_webhook_deliveries"
+            }
+            MaintenanceTask::RotateSigningKeys => "rotate_signing_keys",
+        }
+    }
+}
+
+/// When a task runs and what it deletes.
+#[derive(Debug, Clone)]
+struct ScheduledTask {
+    task: MaintenanceTask,
+    schedule: Schedule,
+    jitter: Duration,
+    retention: TimeDelta,
+}
+
+impl ScheduledTask {
+    fn from_config(
+        task: MaintenanceTask,
+        config: &MaintenanceTaskConfig,
+    ) -> Result<Self> {
+        let schedule = config
+            .schedule()
+            .map_err(|e| eyre!("maintenance.{}: {e}", task.as_str()))?;
+
+        Ok(ScheduledTask {
+            task,
+            schedule,
+            jitter: config.jitter(),
+            retention: config.retention(),
+        })
+    }
+
+    /// Returns how long to wait for the next run, including a random jitter.
+    fn wait(&self, now: DateTime<Utc>) -> Option<Duration> {
+        let next = self.schedule.next_after(now)?;
+        let jitter = match self.jitter.as_millis() {
+            0 => Duration::ZERO,
+            jitter => Duration::from_millis(
+                (Uuid::new_v4().as_u128() % jitter) as u64,
+            ),
+        };
+
+        Some((next - now).to_std().unwrap_or_default() + jitter)
+    }
+}
+
+/// Runs the maintenance tasks on their schedules.
+pub struct MaintenanceScheduler {
+    pool: SqlitePool,
+    encryption: FieldEncryption,
+    tasks: Vec<ScheduledTask>,
+}
+
+impl MaintenanceScheduler {
+    /// Creates the scheduler, unless maintenance is disabled.
+    pub fn from_config(
+        pool: SqlitePool,
+        encryption: FieldEncryption,
+        config: &MaintenanceConfig,
+    ) -> Result<Option<Self>> {
+        if !config.enabled {
+            return Ok(None);
+        }
+
+        let tasks = MaintenanceTask::ALL
+            .into_iter()
+            .map(|task| ScheduledTask::from_config(task, config.task(task)))
+            .collect::<Result<_>>()?;
+
+        Ok(Some(MaintenanceScheduler {
+            pool,
+            encryption,
+            tasks,
+        }))
+    }
+
+    /// Creates a scheduler that maintains another database, e.g. the one of a tenant, on the
+    /// same schedules.
+    pub fn with_pool(&self, pool: SqlitePool) -> Self {
+        MaintenanceScheduler {
+            pool,
+            encryption: self.encryption.clone(),
+            tasks: self.tasks.clone(),
+        }
+    }
+
+    /// Runs the tasks in the background until the shutdown starts.
+    pub fn spawn(self, shutdown: Shutdown) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut loops = JoinSet::new();
+            for task in self.tasks {
+                let pool = self.pool.clone();
+                let encryption = self.encryption.clone();
+                let mut shutdown = shutdown.clone();
+
+                loops.spawn(async move {
+                    while let Some(wait) = task.wait(Utc::now()) {
+                        tokio::select! {
+                            () = tokio::time::sleep(wait) => {}
+                            () = shutdown.wait() => return,
+                        }
+
+                        let span =
+                            info_span!("maintenance", task = task.task.as_str());
+                        let before = Utc::now() - task.retention;
+                        if let Err(e) =
+                            run(&pool, &encryption, task.task, before)
+                                .instrument(span)
+                                .await
+                        {
+                            error!(
+                                error = ?e,
+                                task = task.task.as_str(),
+                                "Failed to run a maintenance task"
+                            );
+                        }
+                    }
+                    debug!(
+                        task = task.task.as_str(),
+                        "The schedule never fires again, stopping the maintenance task"
+                    );
+                });
+            }
+
+            while loops.join_next().await.is_some() {}
+        })
+    }
+}
+
+/// Runs a task once, deleting what became obsolete before `before`. Returns the number of
+/// deleted rows.
+pub async fn run(
+    pool: &SqlitePool,
+    encryption: &FieldEncryption,
+    task: MaintenanceTask,
+    before: DateTime<Utc>,
+) -> Result<u64> {
+    let mut unit_of_work = SqliteUnitOfWork::begin(pool, encryption).await?;
+    let deleted = match task {
+        MaintenanceTask::PruneUserEvents => {
+            prune_user_events(
+                MaintenanceUseCaseDeps::new(&mut unit_of_work),
+                PruneUserEventsParams { before },
+            )
+            .await?
+        }
+        MaintenanceTask::PruneWebhookDeliveries => {
+            prune_webhook_deliveries(
+                MaintenanceUseCaseDeps::new(&mut unit_of_work),
+                PruneWebhookDeliveriesParams { before },
+            )
+            .await?
+        }
+        MaintenanceTask::RotateSigningKeys => {
+            let key = rotate_signing_key(
+                SigningKeyRotationDeps::new(
+                    &mut unit_of_work.signing_keys(),
+                    &Ed25519KeyGenerator::new(),
+                ),
+                RotateSigningKeyParams {
+                    min_age: Some(MIN_SIGNING_KEY_AGE),
+                },
+            )
+            .await?;
+            info!(key_id = %key.id(), "The signing key is up to date");
+
+            prune_signing_keys(
+                MaintenanceUseCaseDeps::new(&mut unit_of_work),
+                PruneSigningKeysParams { before },
+            )
+            .await?
+        }
+    };
+    unit_of_work.commit().await?;
+
+    info!(deleted, %before, "Ran the maintenance task");
+
+    Ok(deleted)
+}
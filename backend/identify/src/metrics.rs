@@ -0,0 +1,125 @@
+//! Prometheus metrics of the server.
+//!
+//! Metrics are recorded with the [metrics](::metrics) facade all over the codebase and
+//! exported in the Prometheus text format at `/metrics`.
+
+use std::time::Duration;
+
+use eyre::{Result, WrapErr};
+use identify_application::UseCaseMetrics;
+use metrics::{gauge, histogram};
+use metrics_exporter_prometheus::{
+    Matcher, PrometheusBuilder, PrometheusHandle,
+};
+use sqlx::SqlitePool;
+
+/// Counter of handled HTTP requests, labeled by the method, route and status.
+pub const HTTP_REQUESTS_METRIC: &str = "http_requests_total";
+/// Histogram of HTTP request latencies, labeled by the method, route and status.
+pub const HTTP_REQUEST_DURATION_METRIC: &str = "http_request_duration_seconds";
+/// Histogram of the time requests spent in every stage of their handling, labeled by the
+/// route and stage.
+pub const HTTP_REQUEST_STAGE_DURATION_METRIC: &str =
+    "http_request_stage_duration_seconds";
+/// Counter of requests that took longer than the latency budget of their route, labeled by the
+/// route.
+pub const HTTP_LATENCY_BUDGET_EXCEEDED_METRIC: &str =
+    "http_latency_budget_exceeded_total";
+/// Counter of requests rejected by the rate limiter, labeled by the rate limit class.
+pub const HTTP_RATE_LIMITED_METRIC: &str = "http_rate_limited_total";
+/// Counter of times the rate limiter waited for a shard of the `sharded` store to be unlocked.
+pub const RATE_LIMIT_SHARD_CONTENTION_METRIC: &str =
+    "rate_limit_shard_contention_total";
+/// Number of buckets kept by the `sharded` store after idle ones were forgotten.
+pub const RATE_LIMIT_BUCKETS_METRIC: &str = "rate_limit_buckets";
+/// Counter of request handlers that panicked.
+pub const HTTP_PANICS_METRIC: &str = "http_panics_total";
+/// Counter of login funnel events, labeled by the step.
+pub const LOGIN_FUNNEL_EVENTS_METRIC: &str = "login_funnel_events_total";
+/// Counter of abandoned login attempts, labeled by the last step they got to.
+pub const LOGIN_FUNNEL_ABANDONED_METRIC: &str = "login_funnel_abandoned_total";
+/// Counter of attempts to deliver events to webhook endpoints, labeled by the outcome
+/// (`delivered`, `retrying` or `dead`).
+pub const WEBHOOK_DELIVERIES_METRIC: &str = "webhook_deliveries_total";
+/// Histogram of use case durations, labeled by the use case and the outcome (`ok` or the kind
+/// of the error).
+pub const USE_CASE_DURATION_METRIC: &str = "use_case_duration_seconds";
+/// Number of open connections in the database pool.
+pub const DB_POOL_CONNECTIONS_METRIC: &str = "db_pool_connections";
+/// Number of idle connections in the database pool.
+pub const DB_POOL_IDLE_CONNECTIONS_METRIC: &str = "db_pool_idle_connections";
+
+/// Buckets of all latency histograms, in seconds.
+const LATENCY_BUCKETS: &[f64] = &[
+    0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0,
+    10.0,
+];
+
+/// How often recorded histograms are compacted.
+const UPKEEP_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Handle of the global metrics recorder.
+#[derive(Clone)]
+pub struct Metrics {
+    handle: PrometheusHandle,
+}
+
+impl Metrics {
+    /// Installs the global Prometheus recorder.
+    ///
+    /// Must be called from within the Tokio runtime and only once.
+    pub fn install() -> Result<Self> {
+        let handle = PrometheusBuilder::new()
+            .set_buckets_for_metric(
+                Matcher::Suffix("duration_seconds".into()),
+                LATENCY_BUCKETS,
+            )?
+            .install_recorder()
+            .wrap_err("failed to install the metrics recorder")?;
+
+        let upkeep_handle = handle.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(UPKEEP_INTERVAL);
+            loop {
+                interval.tick().await;
+                upkeep_handle.run_upkeep();
+            }
+        });
+
+        Ok(Metrics { handle })
+    }
+
+    /// Creates a handle of a recorder that is not installed globally, so it never receives
+    /// any metrics.
+    ///
+    /// Meant for tests, which can't install the global recorder more than once.
+    pub fn detached() -> Self {
+        let handle = PrometheusBuilder::new().build_recorder().handle();
+
+        Metrics { handle }
+    }
+
+    /// Renders all metrics in the Prometheus text format.
+    pub fn render(&self, pool: &SqlitePool) -> String {
+        gauge!(DB_POOL_CONNECTIONS_METRIC).set(pool.size());
+        gauge!(DB_POOL_IDLE_CONNECTIONS_METRIC).set(pool.num_idle() as f64);
+
+        self.handle.render()
+    }
+}
+
+impl UseCaseMetrics for Metrics {
+    fn record(
+        &self,
+        use_case: &'static str,
+        outcome: &'static str,
+        elapsed: Duration,
+    ) {
+        histogram!(
+            USE_CASE_DURATION_METRIC,
+            "use_case" => use_case,
+            "outcome" => outcome,
+        )
+        .record(elapsed);
+    }
+}
@@ -0,0 +1,161 @@
+use std::future::Future;
+
+use eyre::Result;
+use identify_application::{
+    AdvanceOperationParams, OperationTransition, OperationUseCaseDeps,
+    StartOperationParams, advance_operation, start_operation,
+};
+use identify_domain::{NewOperationAttrs, Operation};
+use identify_infrastructure::storage::{
+    self, operations::OperationsRepository,
+};
+use sqlx::SqlitePool;
+use tracing::{Instrument, error, info, info_span};
+use uuid::Uuid;
+
+use crate::status::{Component, StatusBoard};
+
+/// How a long-running task has finished.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OperationOutcome {
+    /// The task has done all its work.
+    Completed,
+    /// The task has stopped early because cancellation was requested.
+    Cancelled,
+}
+
+/// Handle passed to a long-running task that allows it to report its progress.
+pub struct OperationContext {
+    id: Uuid,
+    pool: SqlitePool,
+    status: StatusBoard,
+}
+
+impl OperationContext {
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
+
+    /// Persists the progress of the operation.
+    ///
+    /// Returns `false` if a client has requested cancellation, in which case the task should
+    /// stop and return [OperationOutcome::Cancelled].
+    pub async fn report_progress(&self, progress: u8) -> Result<bool> {
+        let operation = advance(
+            &self.pool,
+            self.id,
+            OperationTransition::Progress(progress),
+        )
+        .await?;
+
+        Ok(!operation.cancel_requested())
+    }
+}
+
+/// Starts long-running operations and executes them in the background.
+#[derive(Clone)]
+pub struct OperationRunner {
+    pool: SqlitePool,
+    status: StatusBoard,
+}
+
+impl OperationRunner {
+    /// Creates a runner that reports whether it manages to finish operations to the status
+    /// board.
+    pub fn new(pool: SqlitePool, status: StatusBoard) -> Self {
+        status.configure(Component::JobRunner);
+
+        OperationRunner { pool, status }
+    }
+
+    /// Persists a new operation of the provided kind and spawns the task that executes it.
+    ///
+    /// The returned operation is still pending, so it can be used to build the
+    /// [accepted](crate::api::services::operations::accepted) response right away.
+    pub async fn start<F, Fut>(
+        &self,
+        kind: impl Into<String>,
+        task: F,
+    ) -> Result<Operation>
+    where
+        F: FnOnce(OperationContext) -> Fut + Send + 'static,
+        Fut: Future<Output = Result<OperationOutcome>> + Send + 'static,
+    {
+        let mut tx = storage::begin(&self.pool).await?;
+        let mut repository = OperationsRepository::new(&mut tx);
+
+        let operation = start_operation(
+            OperationUseCaseDeps::new(&mut repository),
+            StartOperationParams {
+                operation_attrs: NewOperationAttrs { kind: kind.into() },
+            },
+        )
+        .await?;
+
+        storage::commit(tx).await?;
+
+        let ctx = OperationContext {
+            id: operation.id(),
+            pool: self.pool.clone(),
+            status: self.status.clone(),
+        };
+        let span = info_span!(
+            "operation",
+            id = %operation.id(),
+            kind = %operation.kind()
+        );
+        tokio::spawn(execute(ctx, task).instrument(span));
+
+        Ok(operation)
+    }
+}
+
+async fn execute<F, Fut>(ctx: OperationContext, task: F)
+where
+    F: FnOnce(OperationContext) -> Fut,
+    Fut: Future<Output = Result<OperationOutcome>>,
+{
+    let pool = ctx.pool.clone();
+    let status = ctx.status.clone();
+    let id = ctx.id;
+
+    if let Err(e) = advance(&pool, id, OperationTransition::Start).await {
+        // The operation might have been cancelled before it got a chance to start.
+        info!(error = %e, "Operation wasn't started");
+        return;
+    }
+
+    let transition = match task(ctx).await {
+        Ok(OperationOutcome::Completed) => OperationTransition::Succeed,
+        Ok(OperationOutcome::Cancelled) => OperationTransition::Cancel,
+        Err(e) => {
+            error!(error = ?e, "Operation failed");
+            OperationTransition::Fail(e.to_string())
+        }
+    };
+
+    // Failing tasks are fine, the runner is only failing if it can't record their outcome.
+    let result = advance(&pool, id, transition).await;
+    if let Err(e) = status.record(Component::JobRunner, result) {
+        error!(error = ?e, "Failed to persist the outcome of the operation");
+    }
+}
+
+async fn advance(
+    pool: &SqlitePool,
+    id: Uuid,
+    transition: OperationTransition,
+) -> Result<Operation> {
+    let mut tx = storage::begin(pool).await?;
+    let mut repository = OperationsRepository::new(&mut tx);
+
+    let operation = advance_operation(
+        OperationUseCaseDeps::new(&mut repository),
+        AdvanceOperationParams { id, transition },
+    )
+    .await?;
+
+    storage::commit(tx).await?;
+
+    Ok(operation)
+}
@@ -0,0 +1,102 @@
+//! Projection of user events into the read model that serves user listings and searches.
+//!
+//! Events are taken from the outbox independently of their publishing, so the read model
+//! stays up to date even if webhooks and streaming are disabled. Listings lag behind changes
+//! of users until their events are projected, see
+//! [ReadModelConfig::poll_interval_ms](crate::config::ReadModelConfig::poll_interval_ms).
+//!
+//! The users changed by the projected events are forgotten by the [UserCache] of the process.
+
+use std::time::Duration;
+
+use eyre::Result;
+use identify_application::{
+    ProjectUserSummariesParams, ReadModelProjectionDeps, UnitOfWork as _,
+    project_user_summaries,
+};
+use identify_infrastructure::{
+    encryption::FieldEncryption,
+    storage::{unit_of_work::SqliteUnitOfWork, users::cache::UserCache},
+};
+use sqlx::SqlitePool;
+use tokio::task::JoinHandle;
+use tracing::{Instrument, debug, error, info_span};
+
+use crate::config::ReadModelConfig;
+
+/// Most events projected in a single batch.
+const BATCH_SIZE: u32 = 100;
+
+/// Projects user events into the summaries of users.
+pub struct ReadModelProjector {
+    pool: SqlitePool,
+    encryption: FieldEncryption,
+    poll_interval: Duration,
+    cache: UserCache,
+}
+
+impl ReadModelProjector {
+    pub fn from_config(
+        pool: SqlitePool,
+        encryption: FieldEncryption,
+        config: &ReadModelConfig,
+        cache: UserCache,
+    ) -> Self {
+        ReadModelProjector {
+            pool,
+            encryption,
+            poll_interval: config.poll_interval(),
+            cache,
+        }
+    }
+
+    /// Runs the projector in the background until the process exits.
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(self.poll_interval);
+            loop {
+                interval.tick().await;
+                let span = info_span!("read_model");
+                if let Err(e) = self.run_once().instrument(span).await {
+                    error!(error = ?e, "Failed to project user events");
+                }
+            }
+        })
+    }
+
+    /// Projects all the events that haven't been projected yet.
+    ///
+    /// Returns the number of projected events.
+    pub async fn run_once(&self) -> Result<usize> {
+        let mut projected = 0;
+        loop {
+            let batch = self.project().await?;
+            projected += batch;
+            if batch < BATCH_SIZE as usize {
+                break;
+            }
+        }
+
+        if projected > 0 {
+            debug!(events = projected, "Projected user events");
+        }
+
+        Ok(projected)
+    }
+
+    async fn project(&self) -> Result<usize> {
+        let mut uow =
+            SqliteUnitOfWork::begin(&self.pool, &self.encryption).await?;
+
+        let events = project_user_summaries(
+            ReadModelProjectionDeps::new(&mut uow),
+            ProjectUserSummariesParams { limit: BATCH_SIZE },
+        )
+        .await?;
+
+        uow.commit().await?;
+        self.cache.invalidate_events(&events).await;
+
+        Ok(events.len())
+    }
+}
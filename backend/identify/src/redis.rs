@@ -0,0 +1,103 @@
+//! Redis server shared by the processes of a deployment.
+//!
+//! Redis is available only if the binary is built with the `redis` feature and a Redis URL is
+//! configured. It keeps the state the processes must share to behave as one: the token buckets
+//! of the [RateLimiter], the [UserCache], so that a user changed through one process isn't
+//! served stale by the others, and the sessions of users, so that every process accepts them.
+
+use std::{sync::Arc, time::Duration};
+
+use eyre::Result;
+use identify_application::SessionStore;
+use identify_infrastructure::storage::users::cache::UserCache;
+
+use crate::{
+    api::rate_limit::{Bucket, RateLimiter},
+    config::RedisConfig,
+};
+
+#[cfg(feature = "redis")]
+type Client = identify_infrastructure::redis::RedisClient;
+#[cfg(not(feature = "redis"))]
+type Client = std::convert::Infallible;
+
+/// Handle to the configured Redis server, which can't exist without the `redis` feature.
+#[derive(Clone)]
+pub struct Redis(Client);
+
+#[cfg(feature = "redis")]
+impl Redis {
+    /// Returns the configured server, if any. The connection is opened by the first command.
+    pub fn connect(config: &RedisConfig) -> Result<Option<Redis>> {
+        use eyre::WrapErr;
+        use identify_infrastructure::redis::{self, RedisClient};
+        use tracing::info;
+
+        let Some(url) = &config.url else {
+            return Ok(None);
+        };
+
+        let client = RedisClient::new(redis::RedisConfig {
+            url: url.clone(),
+            password: config
+                .password
+                .as_ref()
+                .map(|password| password.expose().to_owned()),
+            key_prefix: config.key_prefix.clone(),
+            timeout: config.timeout(),
+        })
+        .wrap_err("redis.url is invalid")?;
+
+        info!(key_prefix = config.key_prefix, "Redis is enabled");
+
+        Ok(Some(Redis(client)))
+    }
+
+    /// Creates a rate limiter whose buckets are kept in Redis.
+    pub fn rate_limiter(&self, default: Bucket, strict: Bucket) -> RateLimiter {
+        use crate::api::rate_limit::RedisStore;
+
+        RateLimiter::new(RedisStore::new(self.0.clone()), default, strict)
+    }
+
+    /// Creates a cache whose users are kept in Redis for `ttl`.
+    pub fn user_cache(&self, ttl: Duration) -> UserCache {
+        UserCache::redis(self.0.clone(), ttl)
+    }
+
+    /// Creates a store whose sessions are kept in Redis.
+    pub fn session_store(&self) -> Arc<dyn SessionStore> {
+        use identify_infrastructure::redis::sessions::RedisSessionStore;
+
+        Arc::new(RedisSessionStore::new(self.0.clone()))
+    }
+}
+
+#[cfg(not(feature = "redis"))]
+impl Redis {
+    pub fn connect(config: &RedisConfig) -> Result<Option<Redis>> {
+        if config.url.is_some() {
+            tracing::warn!(
+                "redis.url is set, but the server is built without the 'redis' feature"
+            );
+        }
+
+        Ok(None)
+    }
+
+    pub fn rate_limiter(
+        &self,
+        _default: Bucket,
+        _strict: Bucket,
+    ) -> RateLimiter {
+        match self.0 {}
+    }
+
+    pub fn user_cache(&self, _ttl: Duration) -> UserCache {
+        match self.0 {}
+    }
+
+    pub fn session_store(&self) -> Arc<dyn SessionStore> {
+        match self.0 {}
+    }
+}
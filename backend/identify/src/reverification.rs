@@ -0,0 +1,104 @@
+//! Notification of the users flagged by email re-verification campaigns.
+//!
+//! Campaigns are notified by [operations](crate::operations), so their progress can be polled
+//! and they can be cancelled like any other long-running task. The users are notified in
+//! batches with a pause between them, so that neither the mail server nor the providers of the
+//! users get flooded.
+
+use std::{sync::Arc, time::Duration};
+
+use eyre::Result;
+use identify_application::{
+    EmailSender, EmailTemplates, NotifyReverificationBatchParams,
+    ReverificationNotificationDeps, notify_reverification_batch,
+};
+use identify_domain::Operation;
+use identify_infrastructure::{
+    encryption::FieldEncryption,
+    storage::{self, reverifications::ReverificationsRepository},
+};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::operations::{OperationContext, OperationOutcome, OperationRunner};
+
+/// Kind of the operations that notify campaigns.
+pub const OPERATION_KIND: &str = "reverification_campaign";
+
+/// How fast the users of a campaign are notified.
+#[derive(Debug, Clone, Copy)]
+pub struct CampaignSchedule {
+    /// Most users notified at once.
+    pub batch_size: u32,
+    /// Time between two batches.
+    pub pause: Duration,
+}
+
+/// Starts notifying the users of the campaign that haven't been notified yet in the
+/// background.
+///
+/// Notifying a campaign that has been cancelled or has failed again resumes it, as the users
+/// that have been notified are skipped.
+pub async fn notify_campaign(
+    runner: &OperationRunner,
+    pool: SqlitePool,
+    encryption: FieldEncryption,
+    sender: Arc<dyn EmailSender>,
+    templates: Arc<EmailTemplates>,
+    campaign_id: Uuid,
+    schedule: CampaignSchedule,
+) -> Result<Operation> {
+    runner
+        .start(OPERATION_KIND, move |ctx| {
+            notify_batches(
+                ctx,
+                pool,
+                encryption,
+                sender,
+                templates,
+                campaign_id,
+                schedule,
+            )
+        })
+        .await
+}
+
+async fn notify_batches(
+    ctx: OperationContext,
+    pool: SqlitePool,
+    encryption: FieldEncryption,
+    sender: Arc<dyn EmailSender>,
+    templates: Arc<EmailTemplates>,
+    campaign_id: Uuid,
+    schedule: CampaignSchedule,
+) -> Result<OperationOutcome> {
+    loop {
+        // Users are marked as notified one at a time, outside of any transaction.
+        let mut conn = storage::connect(&pool).await?;
+        let mut repository =
+            ReverificationsRepository::new(&mut conn, &encryption);
+
+        let progress = notify_reverification_batch(
+            ReverificationNotificationDeps::new(
+                &mut repository,
+                &sender,
+                &templates,
+            ),
+            NotifyReverificationBatchParams {
+                campaign_id,
+                batch_size: schedule.batch_size,
+            },
+        )
+        .await?;
+        drop(conn);
+
+        if !ctx.report_progress(progress.percent()).await? {
+            return Ok(OperationOutcome::Cancelled);
+        }
+        if progress.is_complete() {
+            return Ok(OperationOutcome::Completed);
+        }
+
+        tokio::time::sleep(schedule.pause).await;
+    }
+}
@@ -0,0 +1,388 @@
+//! Declarative fixtures for development and demo environments, see `identify-admin seed`.
+//!
+//! A seed file describes organizations and users together with their roles in the
+//! organizations. Loading it creates whatever is missing through the use cases, so that loading
+//! the same file again changes nothing:
+//!
+//! ```yaml
+//! organizations:
+//!   - external_id: acme
+//!     name: Acme Corp.
+//! users:
+//!   - email: jane@acme.test
+//!     first_name: Jane
+//!     password: correct horse battery
+//!     memberships:
+//!       - organization: acme
+//!         role: owner
+//! ```
+
+use std::{fmt, path::Path, str::FromStr};
+
+use eyre::{Context, Result, eyre};
+use identify_application::{
+    AddOrganizationMemberParams, ApplicationError, ChangeMemberRoleParams,
+    CreateUsersParams, GetOrganizationByExternalIdParams, ListUsersParams,
+    OrganizationUseCaseDeps, PageRequest, PutOrganizationParams, PutOutcome,
+    SetUserPasswordParams, UserLifecycleUseCaseDeps, UserPasswordUseCaseDeps,
+    UserUseCaseDeps, add_organization_member, change_member_role, create_users,
+    get_organization_by_external_id, list_users, put_organization,
+    set_user_password,
+    user_contracts::{UserFilter, UserListQuery},
+};
+use identify_domain::{MembershipRole, NewUserAttrs, Password, User};
+use identify_infrastructure::{
+    passwords::Argon2Hasher,
+    storage::{self, unit_of_work::SqliteUnitOfWork, users::UsersRepository},
+};
+use serde::{Deserialize, Deserializer, de};
+use uuid::Uuid;
+
+use crate::{
+    admin::{self, AdminContext},
+    api::services::{
+        organizations::PutOrganizationRequest, users::CreateUserRequest,
+    },
+};
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SeedFile {
+    #[serde(default)]
+    pub organizations: Vec<SeedOrganization>,
+    #[serde(default)]
+    pub users: Vec<SeedUser>,
+}
+
+/// An organization, identified by its external ID. Existing ones are renamed if their names
+/// differ, like with `PUT /organizations/{external_id}`.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SeedOrganization {
+    pub external_id: String,
+    pub name: String,
+    pub slug: Option<String>,
+}
+
+/// A user, identified by their email. Existing users are left as they are, apart from their
+/// memberships.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SeedUser {
+    pub email: String,
+    pub first_name: String,
+    pub last_name: Option<String>,
+    pub username: Option<String>,
+    /// Set only when the user is created, so that passwords changed since are kept.
+    pub password: Option<String>,
+    #[serde(default)]
+    pub memberships: Vec<SeedMembership>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SeedMembership {
+    /// External ID of the organization, which may come from the same file.
+    pub organization: String,
+    #[serde(default = "default_role", deserialize_with = "deserialize_role")]
+    pub role: MembershipRole,
+}
+
+/// What loading a seed file has changed.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SeedReport {
+    pub organizations_created: usize,
+    pub organizations_updated: usize,
+    pub users_created: usize,
+    pub memberships_created: usize,
+}
+
+impl fmt::Display for SeedReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Organizations: {} created, {} updated. Users: {} created. Memberships: {} created.",
+            self.organizations_created,
+            self.organizations_updated,
+            self.users_created,
+            self.memberships_created
+        )
+    }
+}
+
+impl SeedFile {
+    pub fn parse(yaml: &str) -> Result<Self> {
+        serde_yaml_ng::from_str(yaml).wrap_err("invalid seed file")
+    }
+
+    pub fn read(path: &Path) -> Result<Self> {
+        let yaml = std::fs::read_to_string(path).wrap_err_with(|| {
+            format!("failed to read the seed file '{}'", path.display())
+        })?;
+
+        SeedFile::parse(&yaml)
+    }
+
+    /// Applies the rules of the API to all entries, so that nothing is written if any of them
+    /// is invalid.
+    fn validate(&self) -> Result<()> {
+        for organization in &self.organizations {
+            admin::validate(&PutOrganizationRequest {
+                name: organization.name.clone(),
+                slug: organization.slug.clone(),
+            })
+            .wrap_err_with(|| {
+                format!("invalid organization '{}'", organization.external_id)
+            })?;
+        }
+
+        for user in &self.users {
+            admin::validate(&CreateUserRequest {
+                email: user.email.clone(),
+                username: user.username.clone(),
+                first_name: user.first_name.clone(),
+                last_name: user.last_name.clone(),
+            })
+            .and_then(|()| {
+                if let Some(password) = &user.password {
+                    Password::parse(password)?;
+                }
+                Ok(())
+            })
+            .wrap_err_with(|| format!("invalid user '{}'", user.email))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Creates the entities of the seed file that don't exist yet.
+///
+/// Every organization and membership is written in its own transaction, and the missing users
+/// are created together in another one, so a failure leaves the entities before it in place.
+/// Loading the file again after fixing the problem completes the rest.
+pub async fn load(
+    seed: &SeedFile,
+    context: &AdminContext<'_>,
+) -> Result<SeedReport> {
+    seed.validate()?;
+
+    let mut report = SeedReport::default();
+
+    for organization in &seed.organizations {
+        let unit_of_work =
+            SqliteUnitOfWork::begin(context.pool, &context.encryption).await?;
+        let (_, outcome) = put_organization(
+            OrganizationUseCaseDeps::new(unit_of_work),
+            PutOrganizationParams {
+                external_id: organization.external_id.clone(),
+                name: organization.name.clone(),
+                slug: organization.slug.clone(),
+                identifier_policy: context.identifier_policy.clone(),
+            },
+        )
+        .await
+        .wrap_err_with(|| {
+            format!(
+                "failed to seed the organization '{}'",
+                organization.external_id
+            )
+        })?;
+
+        match outcome {
+            PutOutcome::Created => report.organizations_created += 1,
+            PutOutcome::Updated => report.organizations_updated += 1,
+            PutOutcome::Unchanged => {}
+        }
+    }
+
+    let mut existing = Vec::with_capacity(seed.users.len());
+    let mut missing = Vec::new();
+    for user in &seed.users {
+        let found = find_user(context, &user.email).await?;
+        if found.is_none() {
+            missing.push(user);
+        }
+        existing.push(found);
+    }
+    let mut created = create_seed_users(&missing, context)
+        .await
+        .wrap_err("failed to seed the users")?
+        .into_iter();
+    report.users_created = created.len();
+
+    for (seed_user, user) in seed.users.iter().zip(existing) {
+        // The created users come in the order of the missing ones.
+        let user = user.or_else(|| created.next()).ok_or_else(|| {
+            eyre!("the user '{}' hasn't been created", seed_user.email)
+        })?;
+        seed_memberships(seed_user, &user, context, &mut report)
+            .await
+            .wrap_err_with(|| {
+                format!("failed to seed the user '{}'", seed_user.email)
+            })?;
+    }
+
+    Ok(report)
+}
+
+async fn seed_memberships(
+    seed: &SeedUser,
+    user: &User,
+    context: &AdminContext<'_>,
+    report: &mut SeedReport,
+) -> Result<()> {
+    for membership in &seed.memberships {
+        let unit_of_work =
+            SqliteUnitOfWork::begin(context.pool, &context.encryption).await?;
+        let organization = get_organization_by_external_id(
+            OrganizationUseCaseDeps::new(unit_of_work),
+            GetOrganizationByExternalIdParams {
+                external_id: membership.organization.clone(),
+            },
+        )
+        .await?;
+
+        if seed_membership(
+            context,
+            organization.id(),
+            user.id(),
+            membership.role,
+        )
+        .await?
+        {
+            report.memberships_created += 1;
+        }
+    }
+
+    Ok(())
+}
+
+async fn find_user(
+    context: &AdminContext<'_>,
+    email: &str,
+) -> Result<Option<User>> {
+    let mut tx = storage::begin(context.pool).await?;
+    let mut repository = UsersRepository::new(&mut tx, &context.encryption);
+    let users = list_users(
+        UserUseCaseDeps::new(&mut repository),
+        ListUsersParams {
+            query: UserListQuery {
+                filter: UserFilter {
+                    email: Some(email.to_owned()),
+                    ..Default::default()
+                },
+                sort: Vec::new(),
+                page: PageRequest { number: 1, size: 1 },
+            },
+        },
+    )
+    .await?;
+
+    Ok(users.items.into_iter().next())
+}
+
+/// Creates the users in one batch, and sets the passwords of the ones that have them.
+async fn create_seed_users(
+    seeds: &[&SeedUser],
+    context: &AdminContext<'_>,
+) -> Result<Vec<User>> {
+    if seeds.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let unit_of_work =
+        SqliteUnitOfWork::begin(context.pool, &context.encryption).await?;
+    let users = create_users(
+        UserLifecycleUseCaseDeps::new(unit_of_work),
+        CreateUsersParams {
+            users: seeds
+                .iter()
+                .map(|seed| {
+                    let attrs = NewUserAttrs {
+                        email: seed.email.clone(),
+                        first_name: seed.first_name.clone(),
+                        last_name: seed.last_name.clone(),
+                    };
+                    (attrs, seed.username.clone())
+                })
+                .collect(),
+            username_strategy: context.username_strategy,
+            identifier_policy: context.identifier_policy.clone(),
+        },
+    )
+    .await?;
+
+    for (seed, user) in seeds.iter().zip(&users) {
+        let Some(password) = &seed.password else {
+            continue;
+        };
+        let unit_of_work =
+            SqliteUnitOfWork::begin(context.pool, &context.encryption).await?;
+        set_user_password(
+            UserPasswordUseCaseDeps::new(unit_of_work, &Argon2Hasher::new()),
+            SetUserPasswordParams {
+                id: user.id(),
+                password: password.clone(),
+            },
+        )
+        .await?;
+    }
+
+    Ok(users)
+}
+
+/// Gives the user the role in the organization. Returns whether the user has become a member,
+/// as existing members only get their role changed if needed.
+async fn seed_membership(
+    context: &AdminContext<'_>,
+    organization_id: Uuid,
+    user_id: Uuid,
+    role: MembershipRole,
+) -> Result<bool> {
+    let unit_of_work =
+        SqliteUnitOfWork::begin(context.pool, &context.encryption).await?;
+    let changed = change_member_role(
+        OrganizationUseCaseDeps::new(unit_of_work),
+        ChangeMemberRoleParams {
+            organization_id,
+            user_id,
+            role,
+        },
+    )
+    .await;
+
+    match changed {
+        Ok(_) => Ok(false),
+        Err(ApplicationError::EntityNotFound { .. }) => {
+            let unit_of_work =
+                SqliteUnitOfWork::begin(context.pool, &context.encryption)
+                    .await?;
+            add_organization_member(
+                OrganizationUseCaseDeps::new(unit_of_work),
+                AddOrganizationMemberParams {
+                    organization_id,
+                    user_id,
+                    role,
+                    quota_policy: context.quota_policy,
+                },
+            )
+            .await?;
+
+            Ok(true)
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn default_role() -> MembershipRole {
+    MembershipRole::Member
+}
+
+fn deserialize_role<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<MembershipRole, D::Error> {
+    let role = String::deserialize(deserializer)?;
+
+    MembershipRole::from_str(&role).map_err(de::Error::custom)
+}
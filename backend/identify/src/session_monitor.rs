@@ -0,0 +1,130 @@
+//! Live activity of the sessions of users for admin dashboards, see the `/sessions/live` route.
+//!
+//! Logins and logouts are broadcast by the process that records them, so subscribers only see
+//! the ones handled by the process they're connected to. Lockouts come from the suspensions
+//! recorded in the outbox, which every process tails through its [UserEventFeed], so they're
+//! seen by all the subscribers.
+
+use std::{sync::Arc, time::Duration};
+
+use chrono::{DateTime, Utc};
+use futures_util::StreamExt as _;
+use identify_domain::{Device, UserLifecycleTransition};
+use tokio::{sync::broadcast, task::JoinHandle};
+use tracing::error;
+use uuid::Uuid;
+
+use crate::{config::EventStreamConfig, event_feed::UserEventFeed};
+
+/// How long to wait before following the user events again after a failure.
+const RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// Something that happened to the session of a user.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SessionActivity {
+    /// The user logged in from a device.
+    Login {
+        user_id: Uuid,
+        device_id: Uuid,
+        /// Whether the user had never logged in from the device before.
+        new_device: bool,
+        occurred_at: DateTime<Utc>,
+    },
+    /// A device of the user was revoked, which signs it out.
+    Logout {
+        user_id: Uuid,
+        device_id: Uuid,
+        occurred_at: DateTime<Utc>,
+    },
+    /// The user was suspended and can't log in anymore.
+    Lockout {
+        user_id: Uuid,
+        occurred_at: DateTime<Utc>,
+    },
+}
+
+impl SessionActivity {
+    pub fn login(device: &Device, new_device: bool) -> Self {
+        SessionActivity::Login {
+            user_id: device.user_id(),
+            device_id: device.id(),
+            new_device,
+            occurred_at: *device.last_seen_at(),
+        }
+    }
+
+    pub fn logout(device: &Device) -> Self {
+        SessionActivity::Logout {
+            user_id: device.user_id(),
+            device_id: device.id(),
+            occurred_at: (*device.revoked_at()).unwrap_or_else(Utc::now),
+        }
+    }
+}
+
+/// Broadcasts the activity of sessions to the subscribers of the process. Cloning it is cheap,
+/// clones share the same subscribers.
+#[derive(Debug, Clone)]
+pub struct SessionMonitor {
+    sender: broadcast::Sender<Arc<SessionActivity>>,
+    keep_alive: Duration,
+}
+
+impl SessionMonitor {
+    pub fn from_config(config: &EventStreamConfig) -> Self {
+        SessionMonitor {
+            sender: broadcast::channel(config.buffer).0,
+            keep_alive: config.keep_alive(),
+        }
+    }
+
+    /// How often idle subscribers are pinged to keep their connections open.
+    pub fn keep_alive(&self) -> Duration {
+        self.keep_alive
+    }
+
+    /// Broadcasts the activity to the current subscribers.
+    pub fn publish(&self, activity: SessionActivity) {
+        // Sending fails only if nobody is subscribed.
+        let _ = self.sender.send(Arc::new(activity));
+    }
+
+    /// Subscribes to the activity broadcast from now on. Subscribers that fall too far behind
+    /// get [RecvError::Lagged](broadcast::error::RecvError::Lagged).
+    pub fn subscribe(&self) -> broadcast::Receiver<Arc<SessionActivity>> {
+        self.sender.subscribe()
+    }
+
+    /// Broadcasts the suspensions of users as lockouts until the process exits, starting with
+    /// the ones recorded from now on.
+    pub fn spawn(&self, events: UserEventFeed) -> JoinHandle<()> {
+        let monitor = self.clone();
+        tokio::spawn(async move {
+            let mut after = None;
+            loop {
+                let mut stream = events.subscribe(after);
+                while let Some(event) = stream.next().await {
+                    let recorded = match event {
+                        Ok(recorded) => recorded,
+                        Err(e) => {
+                            error!(error = %e, "Failed to follow user events");
+                            tokio::time::sleep(RETRY_DELAY).await;
+                            break;
+                        }
+                    };
+
+                    after = Some(recorded.sequence);
+                    let event = &recorded.event;
+                    if event.transition() == UserLifecycleTransition::Suspended
+                    {
+                        monitor.publish(SessionActivity::Lockout {
+                            user_id: event.user_id(),
+                            occurred_at: *event.occurred_at(),
+                        });
+                    }
+                }
+                // The stream also ends when it falls behind, and then resumes from the outbox.
+            }
+        })
+    }
+}
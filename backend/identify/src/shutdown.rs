@@ -0,0 +1,67 @@
+//! Graceful shutdown of the server and its background jobs.
+
+use tokio::sync::watch;
+use tracing::{error, info};
+
+/// Creates the trigger of a shutdown and the signal background jobs wait for.
+pub fn channel() -> (ShutdownTrigger, Shutdown) {
+    let (sender, receiver) = watch::channel(false);
+
+    (ShutdownTrigger(sender), Shutdown(receiver))
+}
+
+/// Starts the shutdown, e.g. once the server stopped accepting requests.
+#[derive(Debug)]
+pub struct ShutdownTrigger(watch::Sender<bool>);
+
+impl ShutdownTrigger {
+    pub fn trigger(&self) {
+        self.0.send_replace(true);
+    }
+}
+
+/// Signal of a shutdown, which background jobs wait for between their runs.
+#[derive(Debug, Clone)]
+pub struct Shutdown(watch::Receiver<bool>);
+
+impl Shutdown {
+    /// Waits until the shutdown starts. Returns immediately if it has already started, or if the
+    /// trigger was dropped.
+    pub async fn wait(&mut self) {
+        // Fails only once the trigger is dropped, which shuts down just the same.
+        let _ = self.0.wait_for(|started| *started).await;
+    }
+}
+
+/// Waits for Ctrl+C or, on Unix, `SIGTERM`.
+pub async fn signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            error!(error = ?e, "Failed to listen for Ctrl+C");
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{SignalKind, signal};
+
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                terminate.recv().await;
+            }
+            Err(e) => {
+                error!(error = ?e, "Failed to listen for SIGTERM");
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        () = ctrl_c => {}
+        () = terminate => {}
+    }
+    info!("Shutting down");
+}
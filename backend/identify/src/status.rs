@@ -0,0 +1,147 @@
+//! Coarse health of the components of the service, shown on its status page.
+//!
+//! Background components (e.g. the job runner) record the outcome of their work on the
+//! [StatusBoard] as they go. Their health is derived from whichever outcome was more recent.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use chrono::{DateTime, Utc};
+
+/// A part of the service whose health is reported separately.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Component {
+    Database,
+    JobRunner,
+    OutboundEmail,
+    WebhookDelivery,
+}
+
+impl Component {
+    pub const ALL: [Component; 4] = [
+        Component::Database,
+        Component::JobRunner,
+        Component::OutboundEmail,
+        Component::WebhookDelivery,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Component::Database => "database",
+            Component::JobRunner => "job_runner",
+            Component::OutboundEmail => "outbound_email",
+            Component::WebhookDelivery => "webhook_delivery",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Health {
+    /// The last attempt has succeeded.
+    Operational,
+    /// The last attempt has failed.
+    Failing,
+    /// Nothing has been attempted since the start of the process.
+    Unknown,
+    /// The component is disabled, or the service doesn't have it at all.
+    NotConfigured,
+}
+
+impl Health {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Health::Operational => "operational",
+            Health::Failing => "failing",
+            Health::Unknown => "unknown",
+            Health::NotConfigured => "not_configured",
+        }
+    }
+}
+
+/// Outcomes of the work of a single component.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ComponentStatus {
+    pub configured: bool,
+    pub last_success: Option<DateTime<Utc>>,
+    pub last_failure: Option<DateTime<Utc>>,
+}
+
+impl ComponentStatus {
+    pub fn health(&self) -> Health {
+        if !self.configured {
+            return Health::NotConfigured;
+        }
+
+        match (self.last_success, self.last_failure) {
+            (None, None) => Health::Unknown,
+            (Some(_), None) => Health::Operational,
+            (None, Some(_)) => Health::Failing,
+            (Some(success), Some(failure)) if success >= failure => {
+                Health::Operational
+            }
+            (Some(_), Some(_)) => Health::Failing,
+        }
+    }
+}
+
+/// Collects the outcomes reported by the components. Cloning it is cheap, clones share the
+/// same board.
+#[derive(Debug, Clone, Default)]
+pub struct StatusBoard {
+    components: Arc<Mutex<HashMap<Component, ComponentStatus>>>,
+}
+
+impl StatusBoard {
+    pub fn new() -> Self {
+        StatusBoard::default()
+    }
+
+    /// Marks the component as present, so that its health is reported even before it does
+    /// any work.
+    pub fn configure(&self, component: Component) {
+        self.update(component, |status| status.configured = true);
+    }
+
+    pub fn record_success(&self, component: Component) {
+        self.update(component, |status| status.last_success = Some(Utc::now()));
+    }
+
+    pub fn record_failure(&self, component: Component) {
+        self.update(component, |status| status.last_failure = Some(Utc::now()));
+    }
+
+    /// Records the outcome of a piece of work and passes it through.
+    pub fn record<T, E>(
+        &self,
+        component: Component,
+        result: Result<T, E>,
+    ) -> Result<T, E> {
+        match &result {
+            Ok(_) => self.record_success(component),
+            Err(_) => self.record_failure(component),
+        }
+
+        result
+    }
+
+    pub fn get(&self, component: Component) -> ComponentStatus {
+        self.lock().get(&component).copied().unwrap_or_default()
+    }
+
+    fn update(
+        &self,
+        component: Component,
+        update: impl FnOnce(&mut ComponentStatus),
+    ) {
+        let mut components = self.lock();
+        let status = components.entry(component).or_default();
+        status.configured = true;
+        update(status);
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<Component, ComponentStatus>> {
+        self.components.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
@@ -0,0 +1,56 @@
+//! Streaming of user events to other services through NATS JetStream.
+//!
+//! Streaming is available only if the binary is built with the `nats` feature and a NATS URL is
+//! configured. Events are published by the [WebhookWorker](crate::webhooks::WebhookWorker) as it
+//! drains the outbox, before they are turned into webhook deliveries.
+
+use std::sync::Arc;
+
+use eyre::Result;
+use identify_application::EventPublisher;
+
+use crate::config::StreamingConfig;
+
+#[cfg(feature = "nats")]
+pub fn publisher(
+    config: &StreamingConfig,
+) -> Result<Option<Arc<dyn EventPublisher>>> {
+    use eyre::WrapErr;
+    use identify_infrastructure::nats::{NatsConfig, NatsPublisher};
+    use tracing::info;
+
+    let Some(url) = &config.nats_url else {
+        return Ok(None);
+    };
+
+    let publisher = NatsPublisher::new(NatsConfig {
+        url: url.clone(),
+        token: config
+            .nats_token
+            .as_ref()
+            .map(|token| token.expose().to_owned()),
+        subject_prefix: config.subject_prefix.clone(),
+        timeout: config.timeout(),
+    })
+    .wrap_err("streaming.nats_url is invalid")?;
+
+    info!(
+        subject_prefix = config.subject_prefix,
+        "Streaming is enabled"
+    );
+
+    Ok(Some(Arc::new(publisher)))
+}
+
+#[cfg(not(feature = "nats"))]
+pub fn publisher(
+    config: &StreamingConfig,
+) -> Result<Option<Arc<dyn EventPublisher>>> {
+    if config.nats_url.is_some() {
+        tracing::warn!(
+            "streaming.nats_url is set, but the server is built without the 'nats' feature"
+        );
+    }
+
+    Ok(None)
+}
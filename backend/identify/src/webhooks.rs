@@ -0,0 +1,397 @@
+//! Delivery of user events to the webhook endpoints registered through the API.
+//!
+//! Events are relayed from the outbox. Every poll turns the unpublished events into a delivery
+//! per subscribed endpoint, and then attempts the deliveries that are due. Failed attempts are
+//! retried with an exponential backoff until the delivery gives up and becomes a dead letter,
+//! which can be redelivered through the API.
+//!
+//! Events are delivered at least once, so receivers should deduplicate them by their ID. Every
+//! payload is signed with the secret of its endpoint, see [sign].
+//!
+//! Endpoints are registered through the API, so their URLs aren't trusted: unless the
+//! configuration allows it, events are only posted over HTTPS, to hosts that only resolve to
+//! public addresses, see [PublicResolver]. Redirects are never followed, since they could lead
+//! anywhere.
+//!
+//! The same poll streams the events through the [EventPublisher] if one is configured, see
+//! [streaming](crate::streaming). Streaming works even if deliveries are disabled, in which
+//! case the deliveries are only created, and attempted once they are enabled again.
+
+use std::{net::IpAddr, sync::Arc, time::Duration};
+
+use chrono::{DateTime, Utc};
+use eyre::{Context, Result};
+use hmac::{Hmac, Mac};
+use identify_application::{
+    DispatchUserEventsParams, DueWebhookDelivery, EventPublisher,
+    ListDueWebhookDeliveriesParams, RecordWebhookAttemptParams,
+    UnitOfWork as _, WebhookDeliveryUseCaseDeps, WebhookDispatchUseCaseDeps,
+    dispatch_user_events, list_due_webhook_deliveries, record_webhook_attempt,
+};
+use identify_domain::{
+    UserLifecycleTransition, WebhookDelivery, WebhookDeliveryStatus,
+    WebhookEndpoint, WebhookRetryPolicy, WebhookUrlPolicy,
+};
+use identify_infrastructure::{
+    encryption::FieldEncryption,
+    network,
+    storage::unit_of_work::{SqliteUnitOfWork, SqliteUnitOfWorkFactory},
+};
+use metrics::counter;
+use reqwest::{
+    Url,
+    dns::{Addrs, Name, Resolve, Resolving},
+    redirect,
+};
+use serde::Serialize;
+use sha2::Sha256;
+use sqlx::SqlitePool;
+use tokio::task::JoinHandle;
+use tracing::{Instrument, debug, error, info_span, warn};
+use uuid::Uuid;
+
+use crate::{
+    config::WebhooksConfig,
+    metrics::WEBHOOK_DELIVERIES_METRIC,
+    status::{Component, StatusBoard},
+};
+
+/// Header with the signature of the payload.
+pub const SIGNATURE_HEADER: &str = "x-identify-signature";
+/// Header with the Unix time the payload was signed at, which is a part of the signature.
+pub const TIMESTAMP_HEADER: &str = "x-identify-timestamp";
+/// Header with the type of the event, e.g. `user.created`.
+pub const EVENT_HEADER: &str = "x-identify-event";
+/// Header with the ID of the delivery, which stays the same across retries.
+pub const DELIVERY_HEADER: &str = "x-identify-delivery";
+
+/// Most events dispatched or deliveries attempted in a single batch.
+const BATCH_SIZE: u32 = 100;
+
+/// Longest error message kept from a failed attempt.
+const MAX_ERROR_LENGTH: usize = 500;
+
+/// Signs a payload with the secret of its endpoint.
+///
+/// The signature is `sha256=` followed by the hex-encoded HMAC-SHA256 of
+/// `{timestamp}.{body}`. Receivers should recompute it and reject payloads whose timestamp is
+/// too old, so that captured payloads can't be replayed.
+pub fn sign(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    // HMAC accepts keys of any length.
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .unwrap_or_else(|_| unreachable!());
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Returns the type of the events announcing the transition, e.g. `user.created`.
+pub fn event_type(transition: UserLifecycleTransition) -> String {
+    format!("user.{transition}")
+}
+
+/// Event in the form it's posted to the endpoints.
+#[derive(Debug, Serialize)]
+struct WebhookPayload {
+    /// ID of the event, the same for all the endpoints and retries.
+    id: Uuid,
+    #[serde(rename = "type")]
+    event_type: String,
+    occurred_at: DateTime<Utc>,
+    data: WebhookPayloadData,
+}
+
+/// Only the ID of the user is sent, receivers fetch the rest through the API if they need it.
+#[derive(Debug, Serialize)]
+struct WebhookPayloadData {
+    user_id: Uuid,
+}
+
+impl From<&WebhookDelivery> for WebhookPayload {
+    fn from(value: &WebhookDelivery) -> Self {
+        WebhookPayload {
+            id: value.event_id(),
+            event_type: event_type(value.transition()),
+            occurred_at: *value.occurred_at(),
+            data: WebhookPayloadData {
+                user_id: value.user_id(),
+            },
+        }
+    }
+}
+
+/// Resolves the hosts of the endpoints to their public addresses only, and fails for the hosts
+/// that have none, so that endpoints can't reach the internal services around the server.
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let addresses = tokio::net::lookup_host((name.as_str(), 0))
+                .await?
+                .filter(|address| network::is_public(address.ip()))
+                .collect::<Vec<_>>();
+            if addresses.is_empty() {
+                return Err(
+                    format!("{} has no public address", name.as_str()).into()
+                );
+            }
+
+            Ok(Box::new(addresses.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// Relays user events to the webhook endpoints and the event publisher.
+pub struct WebhookWorker {
+    pool: SqlitePool,
+    encryption: FieldEncryption,
+    client: reqwest::Client,
+    publisher: Option<Arc<dyn EventPublisher>>,
+    deliver: bool,
+    poll_interval: Duration,
+    retry_policy: WebhookRetryPolicy,
+    /// Checked again before every attempt, for the endpoints registered before the policy
+    /// changed.
+    url_policy: WebhookUrlPolicy,
+    allow_private_networks: bool,
+    status: StatusBoard,
+}
+
+impl WebhookWorker {
+    /// Creates the worker, unless deliveries are disabled and there is no publisher either.
+    ///
+    /// Outcomes of the deliveries are reported to the status board.
+    pub fn from_config(
+        pool: SqlitePool,
+        encryption: FieldEncryption,
+        config: &WebhooksConfig,
+        status: StatusBoard,
+        publisher: Option<Arc<dyn EventPublisher>>,
+    ) -> Result<Option<Self>> {
+        if !config.enabled && publisher.is_none() {
+            return Ok(None);
+        }
+
+        let mut client = reqwest::Client::builder()
+            .timeout(config.timeout())
+            .redirect(redirect::Policy::none());
+        if !config.allow_private_networks {
+            client = client.dns_resolver(Arc::new(PublicResolver));
+        }
+        let client = client
+            .build()
+            .wrap_err("failed to create the webhook client")?;
+        if config.enabled {
+            status.configure(Component::WebhookDelivery);
+        }
+
+        Ok(Some(WebhookWorker {
+            pool,
+            encryption,
+            client,
+            publisher,
+            deliver: config.enabled,
+            poll_interval: config.poll_interval(),
+            retry_policy: config.retry_policy(),
+            url_policy: config.url_policy(),
+            allow_private_networks: config.allow_private_networks,
+            status,
+        }))
+    }
+
+    /// Creates a worker that relays the events of another database, e.g. the one of a tenant,
+    /// just the same way.
+    pub fn with_pool(&self, pool: SqlitePool) -> Self {
+        WebhookWorker {
+            pool,
+            encryption: self.encryption.clone(),
+            client: self.client.clone(),
+            publisher: self.publisher.clone(),
+            deliver: self.deliver,
+            poll_interval: self.poll_interval,
+            retry_policy: self.retry_policy,
+            url_policy: self.url_policy,
+            allow_private_networks: self.allow_private_networks,
+            status: self.status.clone(),
+        }
+    }
+
+    /// Runs the worker in the background until the process exits.
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(self.poll_interval);
+            loop {
+                interval.tick().await;
+                let span = info_span!("webhooks");
+                if let Err(e) = self.run_once().instrument(span).await {
+                    error!(error = ?e, "Failed to deliver webhooks");
+                }
+            }
+        })
+    }
+
+    /// Dispatches all the unpublished events and attempts a batch of the deliveries that are
+    /// due. The rest of them is attempted by the following polls.
+    pub async fn run_once(&self) -> Result<()> {
+        while self.dispatch().await? == BATCH_SIZE as usize {}
+
+        if !self.deliver {
+            return Ok(());
+        }
+
+        for due in self.list_due().await? {
+            let outcome = self.attempt(&due).await;
+            self.record(&due.delivery, outcome).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn dispatch(&self) -> Result<usize> {
+        let dispatched = dispatch_user_events(
+            WebhookDispatchUseCaseDeps::new(
+                SqliteUnitOfWorkFactory::new(
+                    self.pool.clone(),
+                    self.encryption.clone(),
+                ),
+                &self.publisher,
+            ),
+            DispatchUserEventsParams { limit: BATCH_SIZE },
+        )
+        .await?;
+
+        if dispatched > 0 {
+            debug!(events = dispatched, "Dispatched user events");
+        }
+
+        Ok(dispatched)
+    }
+
+    async fn list_due(&self) -> Result<Vec<DueWebhookDelivery>> {
+        let mut uow =
+            SqliteUnitOfWork::begin(&self.pool, &self.encryption).await?;
+
+        let due = list_due_webhook_deliveries(
+            WebhookDeliveryUseCaseDeps::new(&mut uow),
+            ListDueWebhookDeliveriesParams {
+                now: Utc::now(),
+                limit: BATCH_SIZE,
+            },
+        )
+        .await?;
+
+        Ok(due)
+    }
+
+    /// Posts the event to the endpoint. Any response but a 2xx one is a failure, redirects
+    /// included.
+    async fn attempt(
+        &self,
+        due: &DueWebhookDelivery,
+    ) -> std::result::Result<(), String> {
+        let DueWebhookDelivery { endpoint, delivery } = due;
+        self.check_destination(endpoint)?;
+
+        let payload = WebhookPayload::from(delivery);
+        let body = serde_json::to_vec(&payload).map_err(|e| e.to_string())?;
+        let timestamp = Utc::now().timestamp();
+
+        let response = self
+            .client
+            .post(endpoint.url())
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(SIGNATURE_HEADER, sign(endpoint.secret(), timestamp, &body))
+            .header(TIMESTAMP_HEADER, timestamp)
+            .header(EVENT_HEADER, &payload.event_type)
+            .header(DELIVERY_HEADER, delivery.id().to_string())
+            .body(body)
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(|e| e.without_url().to_string())
+            .and_then(|response| {
+                let status = response.status();
+                if status.is_success() {
+                    Ok(response)
+                } else {
+                    Err(format!("the endpoint responded with {status}"))
+                }
+            });
+
+        self.status
+            .record(Component::WebhookDelivery, response)
+            .map(|_| ())
+            .map_err(|e| e.chars().take(MAX_ERROR_LENGTH).collect())
+    }
+
+    /// Fails if the policy doesn't allow the URL of the endpoint, or if its host is an address
+    /// that isn't public. Hosts that are names are checked once they're resolved, see
+    /// [PublicResolver].
+    fn check_destination(
+        &self,
+        endpoint: &WebhookEndpoint,
+    ) -> std::result::Result<(), String> {
+        self.url_policy.check(endpoint).map_err(|e| e.to_string())?;
+        if self.allow_private_networks {
+            return Ok(());
+        }
+
+        let url = Url::parse(endpoint.url()).map_err(|e| e.to_string())?;
+        // IPv6 hosts are enclosed in brackets.
+        let host = url.host_str().unwrap_or_default();
+        let Ok(ip) = host
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .parse::<IpAddr>()
+        else {
+            return Ok(());
+        };
+        if !network::is_public(ip) {
+            return Err(format!("{ip} isn't a public address"));
+        }
+
+        Ok(())
+    }
+
+    async fn record(
+        &self,
+        delivery: &WebhookDelivery,
+        outcome: std::result::Result<(), String>,
+    ) -> Result<()> {
+        let mut uow =
+            SqliteUnitOfWork::begin(&self.pool, &self.encryption).await?;
+
+        let delivery = record_webhook_attempt(
+            WebhookDeliveryUseCaseDeps::new(&mut uow),
+            RecordWebhookAttemptParams {
+                delivery_id: delivery.id(),
+                outcome,
+                retry_policy: self.retry_policy,
+            },
+        )
+        .await?;
+
+        uow.commit().await?;
+
+        let outcome = match delivery.status() {
+            WebhookDeliveryStatus::Delivered => "delivered",
+            WebhookDeliveryStatus::Pending => "retrying",
+            WebhookDeliveryStatus::Dead => {
+                warn!(
+                    delivery_id = %delivery.id(),
+                    endpoint_id = %delivery.endpoint_id(),
+                    attempts = delivery.attempts(),
+                    error = delivery.last_error().as_deref(),
+                    "Giving up on a webhook delivery"
+                );
+                "dead"
+            }
+        };
+        counter!(WEBHOOK_DELIVERIES_METRIC, "outcome" => outcome).increment(1);
+
+        Ok(())
+    }
+}
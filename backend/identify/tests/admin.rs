@@ -0,0 +1,252 @@
+use std::sync::Arc;
+
+use clap::Parser;
+use identify::admin::{self, AdminContext, Cli};
+use identify_application::{PasswordHasher, membership_contracts::Get as _};
+use identify_domain::{MembershipRole, QuotaPolicy, UsernameStrategy};
+use identify_infrastructure::{
+    email::LogEmailSender,
+    passwords::Argon2Hasher,
+    storage::{self, memberships::MembershipsRepository},
+};
+use identify_testkit::{TestDatabase, Testkit};
+use serde_json::Value;
+
+/// Runs the command line against the database of the testkit and returns what it printed.
+async fn run(
+    kit: &Testkit,
+    args: &[&str],
+    input: &str,
+) -> eyre::Result<String> {
+    let cli = Cli::try_parse_from(
+        std::iter::once("identify-admin").chain(args.iter().copied()),
+    )?;
+    let context = AdminContext {
+        pool: kit.pool(),
+        encryption: kit.encryption().clone(),
+        username_strategy: UsernameStrategy::default(),
+        identifier_policy: Arc::default(),
+        quota_policy: QuotaPolicy::default(),
+        email_sender: Arc::new(LogEmailSender),
+        email_templates: Arc::default(),
+    };
+
+    let mut output = Vec::new();
+    admin::run(cli.command, &context, &mut input.as_bytes(), &mut output)
+        .await?;
+
+    Ok(String::from_utf8(output)?)
+}
+
+#[tokio::test]
+async fn created_users_are_exported() {
+    let kit = Testkit::new().await.unwrap();
+    kit.fixtures().user().create().await.unwrap();
+
+    let created = run(
+        &kit,
+        &[
+            "create-user",
+            "--email",
+            "jane.doe@example.test",
+            "--first-name",
+            "Jane",
+        ],
+        "",
+    )
+    .await
+    .unwrap();
+    let created: Value = serde_json::from_str(&created).unwrap();
+    assert_eq!(created["username"], "jane.doe");
+
+    let exported = run(&kit, &["export-users"], "").await.unwrap();
+    let exported: Vec<Value> = exported
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(exported.len(), 2);
+    assert_eq!(exported[1], created);
+}
+
+#[tokio::test]
+async fn invalid_users_are_rejected() {
+    let kit = Testkit::new().await.unwrap();
+
+    let result = run(
+        &kit,
+        &["create-user", "--email", "jane", "--first-name", "Jane"],
+        "",
+    )
+    .await;
+
+    assert!(result.unwrap_err().to_string().contains("email"));
+}
+
+#[tokio::test]
+async fn passwords_are_stored_as_hashes() {
+    let kit = Testkit::new().await.unwrap();
+    let user = kit.fixtures().user().create().await.unwrap();
+    let user_id = user.id().to_string();
+
+    run(&kit, &["set-password", &user_id], "correct horse battery\n")
+        .await
+        .unwrap();
+
+    let hash: String =
+        sqlx::query_scalar("select password_hash from users where id = ?")
+            .bind(user.id())
+            .fetch_one(kit.pool())
+            .await
+            .unwrap();
+    assert!(hash.starts_with("$argon2id$"));
+    let hasher = Argon2Hasher::new();
+    assert!(hasher.verify("correct horse battery", &hash).unwrap());
+    assert!(!hasher.verify("correct horse", &hash).unwrap());
+}
+
+#[tokio::test]
+async fn short_passwords_are_rejected() {
+    let kit = Testkit::new().await.unwrap();
+    let user = kit.fixtures().user().create().await.unwrap();
+
+    let result =
+        run(&kit, &["set-password", &user.id().to_string()], "hunter2\n").await;
+
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn members_are_promoted() {
+    let kit = Testkit::new().await.unwrap();
+    let (_, organization) =
+        kit.fixtures().organization().create().await.unwrap();
+    let member = kit
+        .fixtures()
+        .user()
+        .in_org(&organization)
+        .create()
+        .await
+        .unwrap();
+
+    run(
+        &kit,
+        &[
+            "promote",
+            "--organization",
+            &organization.id().to_string(),
+            &member.id().to_string(),
+        ],
+        "",
+    )
+    .await
+    .unwrap();
+
+    let mut tx = storage::begin(kit.pool()).await.unwrap();
+    let membership = MembershipsRepository::new(&mut tx)
+        .get(organization.id(), member.id())
+        .await
+        .unwrap();
+    assert_eq!(membership.role(), MembershipRole::Admin);
+}
+
+#[tokio::test]
+async fn only_members_can_be_promoted() {
+    let kit = Testkit::new().await.unwrap();
+    let (_, organization) =
+        kit.fixtures().organization().create().await.unwrap();
+    let user = kit.fixtures().user().create().await.unwrap();
+
+    let result = run(
+        &kit,
+        &[
+            "promote",
+            "--organization",
+            &organization.id().to_string(),
+            "--role",
+            "owner",
+            &user.id().to_string(),
+        ],
+        "",
+    )
+    .await;
+
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn last_owners_cannot_be_demoted() {
+    let kit = Testkit::new().await.unwrap();
+    let (owner, organization) =
+        kit.fixtures().organization().create().await.unwrap();
+    let demote = |user_id: String| {
+        let organization_id = organization.id().to_string();
+        let kit = &kit;
+        async move {
+            run(
+                kit,
+                &[
+                    "promote",
+                    "--organization",
+                    &organization_id,
+                    "--role",
+                    "member",
+                    &user_id,
+                ],
+                "",
+            )
+            .await
+        }
+    };
+
+    assert!(demote(owner.id().to_string()).await.is_err());
+
+    kit.fixtures()
+        .user()
+        .in_org(&organization)
+        .with_role(MembershipRole::Owner)
+        .create()
+        .await
+        .unwrap();
+    demote(owner.id().to_string()).await.unwrap();
+
+    let mut tx = storage::begin(kit.pool()).await.unwrap();
+    let membership = MembershipsRepository::new(&mut tx)
+        .get(organization.id(), owner.id())
+        .await
+        .unwrap();
+    assert_eq!(membership.role(), MembershipRole::Member);
+}
+
+#[tokio::test]
+async fn signing_keys_are_rotated_right_away() {
+    let kit = Testkit::new().await.unwrap();
+
+    let first = run(&kit, &["rotate-signing-key"], "").await.unwrap();
+    let second = run(&kit, &["rotate-signing-key"], "").await.unwrap();
+
+    assert!(first.starts_with("Signing key "));
+    assert_ne!(first, second);
+    let statuses: Vec<String> = sqlx::query_scalar(
+        "select status from signing_keys order by created_at",
+    )
+    .fetch_all(kit.pool())
+    .await
+    .unwrap();
+    assert_eq!(statuses, ["retired", "active"]);
+}
+
+#[tokio::test]
+async fn backups_of_in_memory_databases_cannot_be_restored_into_them() {
+    let kit = Testkit::with_database(TestDatabase::Memory).await.unwrap();
+    let dir = tempfile::TempDir::new().unwrap();
+    let path = dir.path().join("backup.db");
+    let path = path.to_str().unwrap();
+
+    let backed_up = run(&kit, &["backup", "--out", path], "").await.unwrap();
+    let restored = run(&kit, &["restore", "--from", path], "").await;
+
+    assert!(backed_up.starts_with("Backed up the database"));
+    assert!(
+        format!("{:#}", restored.unwrap_err()).contains("in-memory databases")
+    );
+}
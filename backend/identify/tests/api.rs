@@ -0,0 +1,116 @@
+//! Black-box tests of the HTTP API, which talk to a served [TestApp] like any other client.
+
+use identify_testkit::api::{TestApp, json_of};
+use serde_json::{Value, json};
+
+const READER_KEY: &str = "reader-key";
+
+#[tokio::test]
+async fn created_users_can_be_fetched() {
+    let app = TestApp::builder().start().await.unwrap();
+    let user = app.create_test_user().await.unwrap();
+
+    let response = app
+        .get(&format!("/api/v1/users/{}", user.id))
+        .send()
+        .await
+        .unwrap();
+    let body: Value = json_of(response).await.unwrap();
+
+    assert_eq!(body["email"], user.email);
+    assert_eq!(body["username"], user.username);
+}
+
+#[tokio::test]
+async fn test_users_are_unique() {
+    let app = TestApp::builder().start().await.unwrap();
+    let first = app.create_test_user().await.unwrap();
+    let second = app.create_test_user().await.unwrap();
+
+    let response = app.get("/api/v1/users").send().await.unwrap();
+    let body: Value = json_of(response).await.unwrap();
+
+    assert_ne!(first.email, second.email);
+    assert_eq!(body["page"]["total_items"], 2);
+}
+
+#[tokio::test]
+async fn permissions_of_api_keys_are_enforced() {
+    let app = TestApp::builder()
+        .with_api_key(READER_KEY, &["users:read"])
+        .start()
+        .await
+        .unwrap();
+    let user = app.create_test_user().await.unwrap();
+
+    let read = app
+        .client()
+        .get(format!("{}/api/v1/users/{}", app.base_url(), user.id))
+        .bearer_auth(READER_KEY)
+        .send()
+        .await
+        .unwrap();
+    let write = app
+        .client()
+        .post(format!("{}/api/v1/users", app.base_url()))
+        .bearer_auth(READER_KEY)
+        .json(&json!({ "email": "jane@acme.test", "first_name": "Jane" }))
+        .send()
+        .await
+        .unwrap();
+    let anonymous = app
+        .client()
+        .get(format!("{}/api/v1/users", app.base_url()))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(read.status(), 200);
+    assert_eq!(write.status(), 403);
+    assert_eq!(anonymous.status(), 401);
+}
+
+#[tokio::test]
+async fn previous_slugs_redirect_to_organizations() {
+    let app = TestApp::builder().start().await.unwrap();
+    for slug in ["acme", "acme-corp"] {
+        let response = app
+            .put("/api/v1/organizations/acme")
+            .json(&json!({ "name": "Acme Corp.", "slug": slug }))
+            .send()
+            .await
+            .unwrap();
+        json_of::<Value>(response).await.unwrap();
+    }
+
+    let response = app
+        .get("/api/v1/organizations/by-slug/acme")
+        .send()
+        .await
+        .unwrap();
+
+    assert!(response.url().path().ends_with("/by-slug/acme-corp"));
+    let body: Value = json_of(response).await.unwrap();
+    assert_eq!(body["slug"], "acme-corp");
+}
+
+#[tokio::test]
+async fn fixtures_share_the_database_of_the_api() {
+    let app = TestApp::builder().start().await.unwrap();
+    let user = app
+        .fixtures()
+        .user()
+        .with_email("jane@acme.test")
+        .create()
+        .await
+        .unwrap();
+
+    let response = app
+        .get(&format!("/api/v1/users/{}", user.id()))
+        .send()
+        .await
+        .unwrap();
+    let body: Value = json_of(response).await.unwrap();
+
+    assert_eq!(body["email"], "jane@acme.test");
+}
@@ -0,0 +1,100 @@
+use identify_domain::{
+    AuthorizationPolicies, AuthorizationPolicy, ConditionOperator,
+    PolicyCondition, PolicyEffect,
+};
+use identify_testkit::api::{TestApp, json_of};
+use reqwest::{Response, header::IF_MATCH};
+use serde_json::{Value, json};
+
+const SUPPORT_KEY: &str = "support-eu";
+const DPO_KEY: &str = "dpo";
+
+fn strings(values: &[&str]) -> Vec<String> {
+    values.iter().map(|&value| value.to_owned()).collect()
+}
+
+/// Support may only create users of Acme, and the data protection officer may erase anyone
+/// without the `users:erase` permission.
+fn policies() -> AuthorizationPolicies {
+    AuthorizationPolicies::new([
+        AuthorizationPolicy::new(
+            "support-creates-acme-users".to_owned(),
+            PolicyEffect::Deny,
+            strings(&["support-*"]),
+            strings(&["users:write"]),
+            strings(&["user:*"]),
+            vec![PolicyCondition {
+                attribute: "resource.email_domain".to_owned(),
+                operator: ConditionOperator::NotIn,
+                values: strings(&["acme.test"]),
+            }],
+        )
+        .unwrap(),
+        AuthorizationPolicy::new(
+            "dpo-erases-users".to_owned(),
+            PolicyEffect::Allow,
+            strings(&[DPO_KEY]),
+            strings(&["users:erase"]),
+            strings(&["user:*"]),
+            vec![],
+        )
+        .unwrap(),
+    ])
+    .unwrap()
+}
+
+async fn app() -> TestApp {
+    TestApp::builder()
+        .with_api_key(SUPPORT_KEY, &["users:write"])
+        .with_api_key(DPO_KEY, &[])
+        .with_authorization_policies(policies())
+        .start()
+        .await
+        .unwrap()
+}
+
+async fn create_user(app: &TestApp, key: &str, email: &str) -> Response {
+    app.client()
+        .post(format!("{}/api/v1/users", app.base_url()))
+        .bearer_auth(key)
+        .json(&json!({ "email": email, "first_name": "Jane" }))
+        .send()
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn policies_deny_actions_the_permissions_allow() {
+    let app = app().await;
+
+    let acme = create_user(&app, SUPPORT_KEY, "jane@acme.test").await;
+    let globex = create_user(&app, SUPPORT_KEY, "jane@globex.test").await;
+
+    assert_eq!(acme.status(), 201);
+    assert_eq!(globex.status(), 403);
+}
+
+#[tokio::test]
+async fn policies_allow_actions_the_permissions_dont() {
+    let app = app().await;
+    let user = app.create_test_user().await.unwrap();
+    let erase = |key: &'static str| {
+        app.client()
+            .post(format!(
+                "{}/api/v1/users/{}/erasure",
+                app.base_url(),
+                user.id
+            ))
+            .bearer_auth(key)
+            .header(IF_MATCH, "*")
+            .json(&json!({ "confirmation": user.email }))
+            .send()
+    };
+
+    let support = erase(SUPPORT_KEY).await.unwrap();
+    let dpo = erase(DPO_KEY).await.unwrap();
+
+    assert_eq!(support.status(), 403);
+    let body: Value = json_of(dpo).await.unwrap();
+    assert_eq!(body["id"], user.id.to_string());
+}
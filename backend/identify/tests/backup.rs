@@ -0,0 +1,48 @@
+use std::sync::Arc;
+
+use chrono::{TimeZone, Utc};
+use identify::{
+    backup::{self, BACKUP_CONTENT_TYPE, BackupScheduler},
+    config::BackupConfig,
+};
+use identify_application::BlobStorage;
+use identify_infrastructure::{blobs::FilesystemBlobStorage, storage};
+use identify_testkit::Testkit;
+use tempfile::TempDir;
+
+#[tokio::test]
+async fn backups_are_uploaded_under_their_scheduled_time() {
+    let kit = Testkit::new().await.unwrap();
+    kit.fixtures().user().create().await.unwrap();
+    let dir = TempDir::new().unwrap();
+    let blobs = Arc::new(FilesystemBlobStorage::new(dir.path().join("blobs")));
+    let scheduler = BackupScheduler::from_config(
+        kit.pool().clone(),
+        blobs.clone(),
+        &BackupConfig {
+            enabled: true,
+            ..BackupConfig::default()
+        },
+    )
+    .unwrap()
+    .unwrap();
+    let scheduled_at = Utc.with_ymd_and_hms(2026, 10, 16, 2, 0, 0).unwrap();
+
+    let key = scheduler.run(scheduled_at).await.unwrap();
+
+    assert_eq!(key, "backups/identify-20261016T020000Z.db");
+    let blob = blobs.get(&key).await.unwrap().unwrap();
+    assert_eq!(blob.content_type, BACKUP_CONTENT_TYPE);
+    let path = dir.path().join("backup.db");
+    std::fs::write(&path, blob.bytes).unwrap();
+    assert!(storage::backup::check(&path).await.unwrap().is_empty());
+}
+
+#[test]
+fn key_prefixes_must_make_valid_blob_keys() {
+    assert!(backup::is_valid_key_prefix("backups"));
+    assert!(backup::is_valid_key_prefix("/identify/backups/"));
+    assert!(!backup::is_valid_key_prefix(""));
+    assert!(!backup::is_valid_key_prefix("../backups"));
+    assert!(!backup::is_valid_key_prefix("back ups"));
+}
@@ -0,0 +1,237 @@
+use config::{File, FileFormat};
+use std::path::Path;
+
+use identify::{
+    api::middleware::sdk::SdkVersion,
+    config::{Config, ConfigError, LogFormat, LogRotation},
+};
+
+/// Parses the configuration from TOML over the built-in defaults, without validating it.
+fn parse(toml: &str) -> Config {
+    config::Config::builder()
+        .add_source(File::from_str(toml, FileFormat::Toml))
+        .build()
+        .unwrap()
+        .try_deserialize()
+        .unwrap()
+}
+
+/// Returns the errors reported by the validation of the configuration.
+fn errors(toml: &str) -> Vec<String> {
+    match parse(toml).validate() {
+        Ok(()) => Vec::new(),
+        Err(ConfigError::Invalid(errors)) => errors,
+        Err(e) => panic!("unexpected error: {e}"),
+    }
+}
+
+#[test]
+fn defaults_are_valid() {
+    assert_eq!(errors(""), Vec::<String>::new());
+}
+
+#[test]
+fn sample_is_valid() {
+    let sample = include_str!("../../identify.sample.toml");
+
+    assert_eq!(errors(sample), Vec::<String>::new());
+}
+
+#[test]
+fn all_errors_are_reported_at_once() {
+    let errors = errors(
+        r#"
+        [database]
+        min_connections = 4
+        max_connections = 2
+        retry_attempts = 0
+
+        [invitations]
+        accept_url = "ftp://example.com/accept"
+
+        [rate_limit.strict]
+        capacity = 0
+        refill_per_second = 1.0
+        "#,
+    );
+
+    assert_eq!(
+        errors,
+        [
+            "database.min_connections (4) must not exceed database.max_connections (2)",
+            "database.retry_attempts must be positive",
+            "rate_limit.strict.capacity must be positive",
+            "invitations.accept_url is not a valid HTTP(S) URL: 'ftp://example.com/accept'",
+        ]
+    );
+}
+
+#[test]
+fn in_memory_databases_are_not_replicated() {
+    let errors = errors(
+        r#"
+        [database]
+        url = "sqlite::memory:"
+        max_connections = 1
+        wal = true
+        read_url = "sqlite://replica.db"
+        "#,
+    );
+
+    assert_eq!(
+        errors,
+        [
+            "database.wal is not supported by in-memory databases",
+            "in-memory databases can't be replicated, unset database.read_url",
+        ]
+    );
+}
+
+#[test]
+fn api_keys_are_long_and_unique() {
+    let errors = errors(
+        r#"
+        [[auth.api_keys]]
+        key = "short"
+        subject = "ci"
+
+        [[auth.api_keys]]
+        key = "0123456789abcdef0123456789abcdef"
+        subject = "backend"
+        admin = true
+
+        [[auth.api_keys]]
+        key = "0123456789abcdef0123456789abcdef"
+        subject = "frontend"
+        "#,
+    );
+
+    assert_eq!(
+        errors,
+        [
+            "API key of 'ci' must be at least 32 characters long",
+            "API key of 'frontend' is used more than once",
+        ]
+    );
+}
+
+#[test]
+fn sdk_policy_is_loaded_with_the_rest() {
+    let config = parse(
+        r#"
+        [sdk]
+        names = ["identify-py"]
+
+        [sdk.min_versions]
+        identify-js = "1.2.0"
+        "#,
+    );
+
+    let policy = config.sdk.policy().unwrap();
+
+    assert!(policy.is_known("identify-js"));
+    assert!(policy.is_known("identify-py"));
+    assert!(!policy.is_known("identify-go"));
+    let outdated: SdkVersion = "identify-js/1.1.9".parse().unwrap();
+    assert_eq!(
+        policy.required_upgrade(&outdated).map(ToString::to_string),
+        Some("1.2.0".to_owned())
+    );
+}
+
+#[test]
+fn invalid_sdk_versions_are_reported() {
+    let errors = errors(
+        r#"
+        [sdk.min_versions]
+        identify-js = "latest"
+        "#,
+    );
+
+    assert_eq!(errors.len(), 1, "{errors:?}");
+    assert!(
+        errors[0]
+            .starts_with("sdk.min_versions.identify-js is not a valid version"),
+        "{errors:?}"
+    );
+}
+
+#[test]
+fn logs_are_written_as_json_to_rotated_files() {
+    let config = parse(
+        r#"
+        [logging]
+        format = "json"
+
+        [logging.file]
+        directory = "/var/log/identify"
+        prefix = "server.log"
+        rotation = "hourly"
+        max_files = 24
+        "#,
+    );
+
+    assert_eq!(config.logging.format, LogFormat::Json);
+    let file = &config.logging.file;
+    assert_eq!(
+        file.directory.as_deref(),
+        Some(Path::new("/var/log/identify"))
+    );
+    assert_eq!(file.prefix, "server.log");
+    assert_eq!(file.rotation, LogRotation::Hourly);
+    assert_eq!(file.max_files, Some(24));
+}
+
+#[test]
+fn logs_are_written_as_text_to_stdout_by_default() {
+    let config = parse("");
+
+    assert_eq!(config.logging.format, LogFormat::Text);
+    let file = &config.logging.file;
+    assert_eq!(file.directory, None);
+    assert_eq!(file.rotation, LogRotation::Daily);
+    assert_eq!(file.max_files, Some(7));
+}
+
+#[test]
+fn log_formats_are_case_insensitive() {
+    assert_eq!(" JSON ".parse::<LogFormat>(), Ok(LogFormat::Json));
+    assert_eq!("Text".parse::<LogFormat>(), Ok(LogFormat::Text));
+    assert_eq!(
+        "yaml".parse::<LogFormat>(),
+        Err("expected 'text' or 'json', got 'yaml'".to_owned())
+    );
+}
+
+#[test]
+fn unknown_log_rotations_are_rejected() {
+    let result = config::Config::builder()
+        .add_source(File::from_str(
+            "[logging.file]\nrotation = \"weekly\"",
+            FileFormat::Toml,
+        ))
+        .build()
+        .unwrap()
+        .try_deserialize::<Config>();
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn invalid_logging_is_reported() {
+    let errors = errors(
+        r#"
+        [logging.file]
+        prefix = " "
+        max_files = 0
+        "#,
+    );
+
+    assert_eq!(
+        errors,
+        [
+            "logging.file.prefix must not be empty",
+            "logging.file.max_files must be positive",
+        ]
+    );
+}
@@ -0,0 +1,47 @@
+//! Tests of the statistics of the database, which operators read through the admin API.
+
+use identify_testkit::api::{TestApp, json_of};
+use serde_json::Value;
+
+const READER_KEY: &str = "reader-key";
+
+#[tokio::test]
+async fn stats_count_the_rows_of_every_table() {
+    let app = TestApp::builder().start().await.unwrap();
+    app.create_test_user().await.unwrap();
+    app.create_test_user().await.unwrap();
+
+    let response = app.get("/admin/db/stats").send().await.unwrap();
+    let body: Value = json_of(response).await.unwrap();
+
+    let users = body["tables"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|table| table["name"] == "users")
+        .unwrap();
+    assert_eq!(users["rows"], 2);
+    assert!(body["pool"]["size"].as_u64().unwrap() >= 1);
+    assert!(body["storage"]["size_bytes"].as_i64().unwrap() > 0);
+    assert_eq!(body["storage"]["journal_mode"], "wal");
+    assert!(body["last_migration"]["version"].as_i64().unwrap() > 0);
+}
+
+#[tokio::test]
+async fn stats_are_only_shown_to_admins() {
+    let app = TestApp::builder()
+        .with_api_key(READER_KEY, &["users:read"])
+        .start()
+        .await
+        .unwrap();
+
+    let response = app
+        .client()
+        .get(format!("{}/admin/db/stats", app.base_url()))
+        .bearer_auth(READER_KEY)
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), 403);
+}
@@ -0,0 +1,159 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use axum::{
+    Extension, Router,
+    body::Body,
+    extract::{FromRef, State},
+    http::{Request, StatusCode},
+    response::Response,
+    routing::get,
+};
+use identify::api::{
+    auth::Principal,
+    policy::{self, RoutePolicy},
+    rate_limit::RateLimiter,
+    use_case::UseCaseContext,
+};
+use identify_infrastructure::{
+    encryption::FieldEncryption,
+    storage::{
+        deadline,
+        retry::{Retrier, RetryPolicy},
+    },
+};
+use identify_testkit::Testkit;
+use sqlx::SqlitePool;
+use tokio::{task::JoinHandle, time::Instant};
+use tower::ServiceExt as _;
+
+/// Timeout of the routes under test.
+const TIMEOUT: Duration = Duration::from_millis(100);
+
+#[derive(Clone)]
+struct TestState {
+    pool: SqlitePool,
+    encryption: FieldEncryption,
+    retrier: Retrier,
+    /// Use case the last request left running in the background, which returns the status of
+    /// the error it failed with, if it did.
+    background: Arc<Mutex<Option<JoinHandle<Option<StatusCode>>>>>,
+}
+
+impl FromRef<TestState> for SqlitePool {
+    fn from_ref(state: &TestState) -> Self {
+        state.pool.clone()
+    }
+}
+
+impl FromRef<TestState> for FieldEncryption {
+    fn from_ref(state: &TestState) -> Self {
+        state.encryption.clone()
+    }
+}
+
+impl FromRef<TestState> for Retrier {
+    fn from_ref(state: &TestState) -> Self {
+        state.retrier.clone()
+    }
+}
+
+async fn slow() -> StatusCode {
+    tokio::time::sleep(TIMEOUT * 10).await;
+
+    StatusCode::NO_CONTENT
+}
+
+/// Returns how long is left until the deadline of the request, in milliseconds.
+async fn remaining() -> String {
+    let deadline = deadline::current().expect("the request has a deadline");
+
+    (deadline - Instant::now()).as_millis().to_string()
+}
+
+/// Leaves a use case running in the background under the deadline of the request, like the
+/// GraphQL loader does, until after the request has been cut off.
+async fn background_use_case(
+    State(state): State<TestState>,
+    mut context: UseCaseContext,
+) -> StatusCode {
+    let deadline = deadline::current().expect("the request has a deadline");
+    let use_case = tokio::spawn(deadline::scope(deadline, async move {
+        tokio::time::sleep(TIMEOUT * 2).await;
+
+        context.users().await.err().map(|e| e.status())
+    }));
+    *state.background.lock().unwrap() = Some(use_case);
+
+    slow().await
+}
+
+/// Serves the handlers with the middleware that enforces the policy of every route of the API.
+async fn get_route(path: &str) -> (Response, TestState) {
+    let kit = Testkit::new().await.unwrap();
+    let state = TestState {
+        pool: kit.pool().clone(),
+        encryption: kit.encryption().clone(),
+        retrier: Retrier::new(RetryPolicy::default()),
+        background: Arc::default(),
+    };
+    let policy = RoutePolicy::public().timeout(TIMEOUT);
+    let app = Router::new()
+        .route("/slow", policy::protect(get(slow), policy))
+        .route("/remaining", policy::protect(get(remaining), policy))
+        .route(
+            "/background",
+            policy::protect(get(background_use_case), policy),
+        )
+        .layer(Extension(RateLimiter::disabled()))
+        .layer(Extension(Principal {
+            subject: "test".to_owned(),
+            admin: true,
+            permissions: Vec::new(),
+            organization_id: None,
+            user_id: None,
+        }))
+        .with_state(state.clone());
+
+    let response = app
+        .oneshot(Request::get(path).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+
+    (response, state)
+}
+
+#[tokio::test]
+async fn requests_over_the_timeout_are_cut_off() {
+    let started = Instant::now();
+
+    let (response, _) = get_route("/slow").await;
+
+    assert_eq!(response.status(), StatusCode::REQUEST_TIMEOUT);
+    assert!(started.elapsed() < TIMEOUT * 10, "{:?}", started.elapsed());
+}
+
+#[tokio::test]
+async fn the_timeout_is_the_deadline_of_the_request() {
+    let (response, _) = get_route("/remaining").await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let remaining: u128 = std::str::from_utf8(&body).unwrap().parse().unwrap();
+    assert!(remaining <= TIMEOUT.as_millis(), "{remaining}");
+    assert!(remaining > 0, "{remaining}");
+}
+
+#[tokio::test]
+async fn use_cases_past_the_deadline_are_refused() {
+    let (response, state) = get_route("/background").await;
+    assert_eq!(response.status(), StatusCode::REQUEST_TIMEOUT);
+
+    let use_case = state.background.lock().unwrap().take().unwrap();
+
+    assert_eq!(use_case.await.unwrap(), Some(StatusCode::REQUEST_TIMEOUT));
+}
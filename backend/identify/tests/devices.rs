@@ -0,0 +1,351 @@
+use std::{net::IpAddr, sync::Mutex};
+
+use async_trait::async_trait;
+use chrono::TimeDelta;
+use identify::{config::LoginsConfig, logins::LoginTracker, shutdown};
+use identify_application::{
+    DeviceLogin, DeviceLoginDeps, DeviceUseCaseDeps, Email, EmailKind,
+    EmailPart, EmailSender, EmailTemplates, IssueSessionParams,
+    ListDevicesParams, RecordDeviceLoginParams, RevokeDeviceParams,
+    SessionIssuanceDeps, UnitOfWork as _, issue_session, list_devices,
+    record_device_login, revoke_device,
+    user_contracts::{Get as _, Update as _},
+};
+use identify_domain::{ConsentPolicies, Locale, TimeZone, User};
+use identify_infrastructure::{
+    geo::NetworkGeoResolver,
+    sessions::MemorySessionStore,
+    storage::unit_of_work::{SqliteUnitOfWork, SqliteUnitOfWorkFactory},
+    tokens::RandomTokenGenerator,
+};
+use identify_testkit::Testkit;
+
+const FIREFOX: &str =
+    "Mozilla/5.0 (X11; Linux x86_64; rv:131.0) Gecko/20100101 Firefox/131.0";
+const SAFARI: &str = "Mozilla/5.0 (iPhone; CPU iPhone OS 18_0 like Mac OS X) Version/18.0 Safari/604.1";
+
+/// A sender that remembers the emails it sent.
+#[derive(Default)]
+struct RecordingSender {
+    emails: Mutex<Vec<Email>>,
+}
+
+#[async_trait]
+impl EmailSender for RecordingSender {
+    async fn send(&self, email: &Email) -> identify_application::Result<()> {
+        self.emails.lock().unwrap().push(email.clone());
+        Ok(())
+    }
+}
+
+struct Fixture {
+    kit: Testkit,
+    user: User,
+    geo_resolver: NetworkGeoResolver,
+    sender: RecordingSender,
+    templates: EmailTemplates,
+    policies: ConsentPolicies,
+    sessions: MemorySessionStore,
+    logins: LoginTracker,
+}
+
+impl Fixture {
+    async fn new() -> Self {
+        let kit = Testkit::new().await.unwrap();
+        let user = kit
+            .fixtures()
+            .user()
+            .with_email("jane@acme.test")
+            .create()
+            .await
+            .unwrap();
+
+        Fixture {
+            user,
+            geo_resolver: NetworkGeoResolver::parse(
+                "203.0.113.0/24,Paris, France",
+            )
+            .unwrap(),
+            sender: RecordingSender::default(),
+            templates: EmailTemplates::default(),
+            policies: ConsentPolicies::default(),
+            sessions: MemorySessionStore::new(),
+            logins: LoginTracker::from_config(
+                kit.pool().clone(),
+                kit.encryption().clone(),
+                &LoginsConfig::default(),
+            ),
+            kit,
+        }
+    }
+
+    async fn login(
+        &self,
+        fingerprint: Option<&str>,
+        user_agent: &str,
+        ip: &str,
+    ) -> DeviceLogin {
+        self.try_login(fingerprint, user_agent, ip).await.unwrap()
+    }
+
+    async fn try_login(
+        &self,
+        fingerprint: Option<&str>,
+        user_agent: &str,
+        ip: &str,
+    ) -> identify_application::Result<DeviceLogin> {
+        let login = record_device_login(
+            DeviceLoginDeps::new(
+                SqliteUnitOfWorkFactory::new(
+                    self.kit.pool().clone(),
+                    self.kit.encryption().clone(),
+                ),
+                &self.geo_resolver,
+                &self.sender,
+                &self.templates,
+                &self.policies,
+            ),
+            RecordDeviceLoginParams {
+                user_id: self.user.id(),
+                fingerprint: fingerprint.map(str::to_owned),
+                user_agent: user_agent.to_owned(),
+                ip: ip.parse::<IpAddr>().unwrap(),
+            },
+        )
+        .await?;
+        // Logins are counted once their sessions are issued, like the API does.
+        issue_session(
+            SessionIssuanceDeps::new(
+                &self.sessions,
+                &RandomTokenGenerator::new(),
+                &self.logins,
+            ),
+            IssueSessionParams {
+                user_id: self.user.id(),
+                device_id: login.device.id(),
+                ttl: TimeDelta::hours(1),
+            },
+        )
+        .await?;
+
+        Ok(login)
+    }
+
+    async fn stored_user(&self) -> User {
+        let mut uow =
+            SqliteUnitOfWork::begin(self.kit.pool(), self.kit.encryption())
+                .await
+                .unwrap();
+        uow.users().get(self.user.id()).await.unwrap()
+    }
+
+    fn emails(&self) -> Vec<Email> {
+        self.sender.emails.lock().unwrap().clone()
+    }
+}
+
+#[tokio::test]
+async fn first_device_is_not_notified() {
+    let fixture = Fixture::new().await;
+
+    let login = fixture.login(None, FIREFOX, "203.0.113.7").await;
+
+    assert!(login.new_device);
+    assert_eq!(login.device.user_agent(), FIREFOX);
+    assert_eq!(login.device.location().as_deref(), Some("Paris, France"));
+    assert!(fixture.emails().is_empty());
+}
+
+#[tokio::test]
+async fn new_devices_are_notified() {
+    let fixture = Fixture::new().await;
+    fixture.login(None, FIREFOX, "203.0.113.7").await;
+
+    let login = fixture.login(None, SAFARI, "198.51.100.1").await;
+
+    assert!(login.new_device);
+    assert_eq!(login.device.location(), &None);
+    let emails = fixture.emails();
+    assert_eq!(emails.len(), 1);
+    assert_eq!(emails[0].to, "jane@acme.test");
+    assert!(emails[0].text.contains(SAFARI), "{}", emails[0].text);
+    assert!(
+        emails[0].text.contains("198.51.100.1"),
+        "{}",
+        emails[0].text
+    );
+    assert!(emails[0].text.contains("Unknown"), "{}", emails[0].text);
+}
+
+#[tokio::test]
+async fn new_devices_are_notified_in_the_locale_of_the_user() {
+    let mut fixture = Fixture::new().await;
+    let locale = Locale::parse("de").unwrap();
+    for (part, source) in [
+        (EmailPart::Subject, "Neue Anmeldung"),
+        (
+            EmailPart::Text,
+            "Angemeldet mit {{ user_agent }} am {{ time }}",
+        ),
+    ] {
+        fixture
+            .templates
+            .translate(locale.clone(), EmailKind::NewDevice, part, source)
+            .unwrap();
+    }
+    // The translation of the language is used for its regions as well.
+    fixture.user.localize(
+        Some(Locale::parse("de-AT").unwrap()),
+        Some(TimeZone::parse("Europe/Vienna").unwrap()),
+    );
+    let mut uow =
+        SqliteUnitOfWork::begin(fixture.kit.pool(), fixture.kit.encryption())
+            .await
+            .unwrap();
+    uow.users().update(&fixture.user).await.unwrap();
+    uow.commit().await.unwrap();
+    fixture.login(None, FIREFOX, "203.0.113.7").await;
+
+    let login = fixture.login(None, SAFARI, "198.51.100.1").await;
+
+    let emails = fixture.emails();
+    assert_eq!(emails.len(), 1);
+    assert_eq!(emails[0].subject, "Neue Anmeldung");
+    let time = TimeZone::parse("Europe/Vienna")
+        .unwrap()
+        .format(*login.device.last_seen_at());
+    assert_eq!(emails[0].text, format!("Angemeldet mit {SAFARI} am {time}"));
+    assert!(time.ends_with("CET") || time.ends_with("CEST"), "{time}");
+    // Parts without a translation are rendered from the default templates.
+    assert!(
+        emails[0].html.as_deref().unwrap().contains("198.51.100.1"),
+        "{:?}",
+        emails[0].html
+    );
+}
+
+#[tokio::test]
+async fn logins_are_counted_once_their_sessions_are_issued() {
+    let fixture = Fixture::new().await;
+
+    fixture.login(None, FIREFOX, "203.0.113.7").await;
+    let last = fixture.login(None, SAFARI, "198.51.100.1").await;
+
+    assert_eq!(fixture.stored_user().await.login_count(), 0);
+
+    assert_eq!(fixture.logins.flush().await.unwrap(), 1);
+
+    let user = fixture.stored_user().await;
+    assert_eq!(user.login_count(), 2);
+    assert!(user.last_login_at().unwrap() >= *last.device.last_seen_at());
+    assert_eq!(user.version(), fixture.user.version());
+}
+
+#[tokio::test]
+async fn refused_logins_are_neither_counted_nor_notified() {
+    let mut fixture = Fixture::new().await;
+    fixture.login(None, FIREFOX, "203.0.113.7").await;
+    fixture.policies = ConsentPolicies::new([(
+        String::from("terms"),
+        String::from("2026-10"),
+    )])
+    .unwrap();
+
+    let refused = fixture.try_login(None, SAFARI, "198.51.100.1").await;
+
+    assert!(refused.is_err());
+    assert!(fixture.emails().is_empty());
+    assert_eq!(fixture.logins.flush().await.unwrap(), 1);
+    assert_eq!(fixture.stored_user().await.login_count(), 1);
+}
+
+#[tokio::test]
+async fn pending_logins_are_counted_when_the_server_shuts_down() {
+    let mut fixture = Fixture::new().await;
+    // Like the tracker of a tenant, which is created from the one of the primary database.
+    let primary = fixture.logins.clone();
+    fixture.logins = primary.with_pool(fixture.kit.pool().clone());
+    let (trigger, shutdown) = shutdown::channel();
+    fixture.logins.spawn(shutdown);
+    fixture.login(None, FIREFOX, "203.0.113.7").await;
+
+    trigger.trigger();
+    primary.finished().await.unwrap();
+
+    assert_eq!(fixture.stored_user().await.login_count(), 1);
+}
+
+#[tokio::test]
+async fn known_devices_are_updated() {
+    let fixture = Fixture::new().await;
+    let first = fixture.login(Some("cookie"), FIREFOX, "198.51.100.1").await;
+
+    let login = fixture.login(Some("cookie"), SAFARI, "203.0.113.7").await;
+
+    assert!(!login.new_device);
+    assert_eq!(login.device.id(), first.device.id());
+    assert_eq!(login.device.user_agent(), SAFARI);
+    assert_eq!(login.device.ip().to_string(), "203.0.113.7");
+    assert_eq!(login.device.location().as_deref(), Some("Paris, France"));
+    assert!(login.device.last_seen_at() >= first.device.last_seen_at());
+    assert!(fixture.emails().is_empty());
+}
+
+#[tokio::test]
+async fn revoked_devices_are_new_on_the_next_login() {
+    let fixture = Fixture::new().await;
+    fixture.login(Some("laptop"), FIREFOX, "203.0.113.7").await;
+    let revoked = fixture.login(Some("phone"), SAFARI, "203.0.113.8").await;
+
+    let mut uow =
+        SqliteUnitOfWork::begin(fixture.kit.pool(), fixture.kit.encryption())
+            .await
+            .unwrap();
+    let device = revoke_device(
+        DeviceUseCaseDeps::new(&mut uow),
+        RevokeDeviceParams {
+            user_id: fixture.user.id(),
+            device_id: revoked.device.id(),
+        },
+    )
+    .await
+    .unwrap();
+    assert!(device.is_revoked());
+    assert!(
+        revoke_device(
+            DeviceUseCaseDeps::new(&mut uow),
+            RevokeDeviceParams {
+                user_id: fixture.user.id(),
+                device_id: revoked.device.id(),
+            },
+        )
+        .await
+        .is_err()
+    );
+    uow.commit().await.unwrap();
+
+    let login = fixture.login(Some("phone"), SAFARI, "203.0.113.8").await;
+
+    assert!(login.new_device);
+    assert_ne!(login.device.id(), revoked.device.id());
+    assert_eq!(fixture.emails().len(), 2);
+
+    let mut uow =
+        SqliteUnitOfWork::begin(fixture.kit.pool(), fixture.kit.encryption())
+            .await
+            .unwrap();
+    let devices = list_devices(
+        DeviceUseCaseDeps::new(&mut uow),
+        ListDevicesParams {
+            user_id: fixture.user.id(),
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(devices.len(), 3);
+    assert_eq!(devices[0].id(), login.device.id());
+    assert_eq!(
+        devices.iter().filter(|device| device.is_revoked()).count(),
+        1
+    );
+}
@@ -0,0 +1,226 @@
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use chrono::{TimeDelta, Utc};
+use identify::{
+    config::{DigestConfig, DigestPeriod},
+    digest::DigestJob,
+    status::StatusBoard,
+};
+use identify_application::{
+    ApplicationError, BuildAdminDigestParams, DigestCategory,
+    DigestUseCaseDeps, Email, EmailSender, build_admin_digest,
+    user_event_contracts::Emit,
+    webhook_delivery_contracts::{Insert as _, Update as _},
+    webhook_endpoint_contracts::Insert as _,
+};
+use identify_domain::{
+    MembershipRole, NewUserEventAttrs, NewWebhookDeliveryAttrs,
+    NewWebhookEndpointAttrs, QuotaPolicy, UserEvent, UserLifecycleTransition,
+    WebhookDelivery, WebhookEndpoint, WebhookRetryPolicy,
+};
+use identify_infrastructure::storage::{
+    self,
+    outbox::OutboxRepository,
+    unit_of_work::SqliteUnitOfWork,
+    webhooks::{WebhookDeliveriesRepository, WebhookEndpointsRepository},
+};
+use identify_testkit::Testkit;
+use uuid::Uuid;
+
+/// A sender that remembers the emails, and fails for the recipients it's told to.
+#[derive(Default)]
+struct RecordingSender {
+    emails: Mutex<Vec<Email>>,
+    failing: Vec<String>,
+}
+
+#[async_trait]
+impl EmailSender for RecordingSender {
+    async fn send(&self, email: &Email) -> identify_application::Result<()> {
+        if self.failing.contains(&email.to) {
+            return Err(ApplicationError::Unavailable);
+        }
+
+        self.emails.lock().unwrap().push(email.clone());
+        Ok(())
+    }
+}
+
+fn config(recipients: &[&str]) -> DigestConfig {
+    DigestConfig {
+        enabled: true,
+        period: DigestPeriod::Daily,
+        webhook_url: None,
+        recipients: recipients.iter().map(|r| (*r).to_owned()).collect(),
+        opt_out: vec!["verifications".to_owned()],
+    }
+}
+
+async fn suspend_user(kit: &Testkit) {
+    let mut tx = storage::begin(kit.pool()).await.unwrap();
+    let event = UserEvent::new(NewUserEventAttrs {
+        user_id: Uuid::new_v4(),
+        transition: UserLifecycleTransition::Suspended,
+        version: 2,
+    })
+    .unwrap();
+    OutboxRepository::new(&mut tx).emit(&event).await.unwrap();
+    storage::commit(tx).await.unwrap();
+}
+
+/// Records a delivery that gave up after its only attempt failed.
+async fn give_up_delivery(kit: &Testkit) {
+    let endpoint = WebhookEndpoint::new(NewWebhookEndpointAttrs {
+        url: "https://hooks.acme.test/identify".to_owned(),
+        secret: "0123456789abcdef".to_owned(),
+        events: vec![],
+        external_id: None,
+    })
+    .unwrap();
+    let mut delivery = WebhookDelivery::new(NewWebhookDeliveryAttrs {
+        endpoint_id: endpoint.id(),
+        event_id: Uuid::new_v4(),
+        user_id: Uuid::new_v4(),
+        transition: UserLifecycleTransition::Created,
+        occurred_at: Utc::now(),
+    });
+
+    let mut tx = storage::begin(kit.pool()).await.unwrap();
+    WebhookEndpointsRepository::new(&mut tx, kit.encryption())
+        .insert(&endpoint)
+        .await
+        .unwrap();
+    let mut deliveries = WebhookDeliveriesRepository::new(&mut tx);
+    deliveries.insert(&delivery).await.unwrap();
+    delivery
+        .fail(
+            "connection refused",
+            &WebhookRetryPolicy {
+                max_attempts: 1,
+                initial_backoff: TimeDelta::seconds(1),
+                max_backoff: TimeDelta::seconds(1),
+            },
+        )
+        .unwrap();
+    deliveries.update(&delivery).await.unwrap();
+    storage::commit(tx).await.unwrap();
+}
+
+#[tokio::test]
+async fn digest_counts_admins_failed_deliveries_and_quota_crossings() {
+    let kit = Testkit::new().await.unwrap();
+    let (_, full) = kit.fixtures().organization().create().await.unwrap();
+    kit.fixtures()
+        .user()
+        .in_org(&full)
+        .with_role(MembershipRole::Admin)
+        .create()
+        .await
+        .unwrap();
+    kit.fixtures().user().in_org(&full).create().await.unwrap();
+    kit.fixtures().organization().create().await.unwrap();
+    give_up_delivery(&kit).await;
+
+    let quotas = QuotaPolicy::new(Some(3));
+    let mut uow = SqliteUnitOfWork::begin(kit.pool(), kit.encryption())
+        .await
+        .unwrap();
+    let now = Utc::now();
+    let digest = build_admin_digest(
+        DigestUseCaseDeps::new(&mut uow, &quotas),
+        BuildAdminDigestParams {
+            since: now - TimeDelta::hours(1),
+            until: now + TimeDelta::hours(1),
+            categories: vec![
+                DigestCategory::NewAdmins,
+                DigestCategory::FailedWebhookDeliveries,
+                DigestCategory::QuotaCrossings,
+            ],
+        },
+    )
+    .await
+    .unwrap();
+
+    // Both owners and the admin.
+    assert_eq!(
+        digest.entries,
+        [
+            (DigestCategory::NewAdmins, 3),
+            (DigestCategory::FailedWebhookDeliveries, 1),
+            (DigestCategory::QuotaCrossings, 1),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn digest_is_emailed_to_every_recipient() {
+    let kit = Testkit::new().await.unwrap();
+    suspend_user(&kit).await;
+    let sender = Arc::new(RecordingSender {
+        failing: vec!["down@example.com".to_owned()],
+        ..Default::default()
+    });
+
+    let job = DigestJob::from_config(
+        kit.pool().clone(),
+        kit.encryption().clone(),
+        &config(&["down@example.com", "admin@example.com"]),
+        QuotaPolicy::default(),
+        sender.clone(),
+        StatusBoard::new(),
+    )
+    .unwrap()
+    .unwrap();
+    let now = Utc::now();
+    job.send(now - TimeDelta::hours(1), now + TimeDelta::hours(1))
+        .await
+        .unwrap();
+
+    let emails = sender.emails.lock().unwrap();
+    assert_eq!(emails.len(), 1);
+    assert_eq!(emails[0].to, "admin@example.com");
+    assert!(emails[0].subject.contains("daily digest"));
+    assert!(emails[0].text.contains("- lockouts: 1\n"));
+    assert!(emails[0].text.contains("- quota_crossings: 0\n"));
+    assert!(!emails[0].text.contains("verifications"));
+}
+
+#[tokio::test]
+async fn empty_digest_is_not_emailed() {
+    let kit = Testkit::new().await.unwrap();
+    let sender = Arc::new(RecordingSender::default());
+
+    let job = DigestJob::from_config(
+        kit.pool().clone(),
+        kit.encryption().clone(),
+        &config(&["admin@example.com"]),
+        QuotaPolicy::default(),
+        sender.clone(),
+        StatusBoard::new(),
+    )
+    .unwrap()
+    .unwrap();
+    let now = Utc::now();
+    job.send(now - TimeDelta::hours(1), now + TimeDelta::hours(1))
+        .await
+        .unwrap();
+
+    assert!(sender.emails.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn digest_needs_a_webhook_or_recipients() {
+    let kit = Testkit::new().await.unwrap();
+
+    let result = DigestJob::from_config(
+        kit.pool().clone(),
+        kit.encryption().clone(),
+        &config(&[]),
+        QuotaPolicy::default(),
+        Arc::new(RecordingSender::default()),
+        StatusBoard::new(),
+    );
+
+    assert!(result.is_err());
+}
@@ -0,0 +1,95 @@
+#![cfg(feature = "sentry")]
+
+use std::net::Ipv4Addr;
+
+use identify::error_reporting::redaction;
+use sentry::protocol::{Context, Event, IpAddress, Map, Request, User, Value};
+use serde_json::json;
+
+fn fields(value: Value) -> Map<String, Value> {
+    serde_json::from_value(value).unwrap()
+}
+
+fn value(fields: &Map<String, Value>) -> Value {
+    serde_json::to_value(fields).unwrap()
+}
+
+#[test]
+fn personal_data_and_credentials_are_scrubbed() {
+    let event = Event {
+        user: Some(User {
+            id: Some("7f1c".to_owned()),
+            email: Some("jane@acme.test".to_owned()),
+            ip_address: Some(IpAddress::Exact(Ipv4Addr::LOCALHOST.into())),
+            other: fields(json!({ "email_alias": "jd@acme.test" })),
+            ..User::default()
+        }),
+        request: Some(Request {
+            method: Some("POST".to_owned()),
+            query_string: Some("token=secret".to_owned()),
+            cookies: Some("session=secret".to_owned()),
+            data: Some(r#"{"password":"secret"}"#.to_owned()),
+            headers: [
+                ("Authorization", "Bearer secret"),
+                ("X-Api-Key", "secret"),
+                ("Accept", "application/json"),
+            ]
+            .map(|(name, value)| (name.to_owned(), value.to_owned()))
+            .into(),
+            ..Request::default()
+        }),
+        contexts: [(
+            "request".to_owned(),
+            Context::Other(fields(json!({
+                "route": "/api/v1/users/{id}",
+                "actor_email": "admin@acme.test",
+            }))),
+        )]
+        .into(),
+        extra: fields(json!({
+            "user_id": "7f1c",
+            "session_token": "secret",
+            "changes": [{ "field": "email", "new_email": "jane@acme.test" }],
+            "payload": { "first_name": "Jane", "password": "secret" },
+        })),
+        ..Event::default()
+    };
+
+    let event = redaction::scrub(event);
+
+    let user = event.user.unwrap();
+    assert_eq!(user.id.as_deref(), Some("7f1c"));
+    assert_eq!(user.email, None);
+    assert_eq!(user.ip_address, None);
+    assert_eq!(value(&user.other), json!({ "email_alias": "[redacted]" }));
+
+    let request = event.request.unwrap();
+    assert_eq!(request.method.as_deref(), Some("POST"));
+    assert_eq!(request.query_string, None);
+    assert_eq!(request.cookies, None);
+    assert_eq!(request.data, None);
+    assert_eq!(request.headers["Authorization"], "[redacted]");
+    assert_eq!(request.headers["X-Api-Key"], "[redacted]");
+    assert_eq!(request.headers["Accept"], "application/json");
+
+    let Context::Other(context) = &event.contexts["request"] else {
+        panic!("the context changed its kind");
+    };
+    assert_eq!(
+        value(context),
+        json!({
+            "route": "/api/v1/users/{id}",
+            "actor_email": "[redacted]",
+        })
+    );
+
+    assert_eq!(
+        value(&event.extra),
+        json!({
+            "user_id": "7f1c",
+            "session_token": "[redacted]",
+            "changes": [{ "field": "email", "new_email": "[redacted]" }],
+            "payload": { "first_name": "Jane", "password": "[redacted]" },
+        })
+    );
+}
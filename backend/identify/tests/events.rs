@@ -0,0 +1,158 @@
+//! Tests of the live feed of user events, which subscribers read as Server-Sent Events.
+
+use std::time::Duration;
+
+use identify_testkit::api::TestApp;
+use reqwest::{Response, StatusCode};
+use serde_json::{Value, json};
+use tokio::time::timeout;
+
+/// Longest time a test waits for an event before it fails.
+const EVENT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// An event as it's received by subscribers.
+#[derive(Debug)]
+struct Received {
+    id: String,
+    name: String,
+    data: Value,
+}
+
+/// Reads Server-Sent Events from a response, skipping the keep-alive comments.
+struct Subscriber {
+    response: Response,
+    buffer: String,
+}
+
+impl Subscriber {
+    async fn connect(
+        app: &TestApp,
+        path: &str,
+        last_event_id: Option<&str>,
+    ) -> Self {
+        let mut request = app.get(path);
+        if let Some(id) = last_event_id {
+            request = request.header("last-event-id", id);
+        }
+        let response = request.send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        Subscriber {
+            response,
+            buffer: String::new(),
+        }
+    }
+
+    async fn next(&mut self) -> Received {
+        timeout(EVENT_TIMEOUT, async {
+            loop {
+                if let Some(end) = self.buffer.find("\n\n") {
+                    let block = self.buffer[..end].to_owned();
+                    self.buffer.drain(..end + 2);
+                    if let Some(event) = parse(&block) {
+                        return event;
+                    }
+                    continue;
+                }
+
+                let chunk = self
+                    .response
+                    .chunk()
+                    .await
+                    .unwrap()
+                    .expect("the stream ended");
+                self.buffer.push_str(std::str::from_utf8(&chunk).unwrap());
+            }
+        })
+        .await
+        .expect("no event was received in time")
+    }
+}
+
+fn parse(block: &str) -> Option<Received> {
+    let field = |name: &str| {
+        block
+            .lines()
+            .find_map(|line| line.strip_prefix(name)?.strip_prefix(':'))
+            .map(|value| value.trim_start().to_owned())
+    };
+
+    Some(Received {
+        id: field("id")?,
+        name: field("event")?,
+        data: serde_json::from_str(&field("data")?).unwrap(),
+    })
+}
+
+#[tokio::test]
+async fn created_users_are_streamed_to_subscribers() {
+    let app = TestApp::builder().start().await.unwrap();
+    let mut subscriber =
+        Subscriber::connect(&app, "/api/v1/events/stream", None).await;
+
+    let user = app.create_test_user().await.unwrap();
+
+    let event = subscriber.next().await;
+    assert_eq!(event.name, "user.created");
+    assert_eq!(event.data["type"], "user.created");
+    assert_eq!(event.data["data"]["user_id"], user.id.to_string());
+}
+
+#[tokio::test]
+async fn subscribers_receive_only_the_requested_types() {
+    let app = TestApp::builder().start().await.unwrap();
+    let mut subscriber = Subscriber::connect(
+        &app,
+        "/api/v1/events/stream?types=user.updated",
+        None,
+    )
+    .await;
+
+    let user = app.create_test_user().await.unwrap();
+    app.request(
+        reqwest::Method::PATCH,
+        &format!("/api/v1/users/{}", user.id),
+    )
+    .header("if-match", "*")
+    .json(&json!({ "first_name": "Jane" }))
+    .send()
+    .await
+    .unwrap()
+    .error_for_status()
+    .unwrap();
+
+    let event = subscriber.next().await;
+    assert_eq!(event.name, "user.updated");
+    assert_eq!(event.data["data"]["user_id"], user.id.to_string());
+}
+
+#[tokio::test]
+async fn reconnecting_subscribers_receive_the_events_they_missed() {
+    let app = TestApp::builder().start().await.unwrap();
+    let first = app.create_test_user().await.unwrap();
+    let second = app.create_test_user().await.unwrap();
+
+    let mut subscriber =
+        Subscriber::connect(&app, "/api/v1/events/stream", Some("0")).await;
+    let missed = [subscriber.next().await, subscriber.next().await];
+    let mut reconnected =
+        Subscriber::connect(&app, "/api/v1/events/stream", Some(&missed[0].id))
+            .await;
+
+    assert_eq!(missed[0].data["data"]["user_id"], first.id.to_string());
+    assert_eq!(missed[1].data["data"]["user_id"], second.id.to_string());
+    assert_eq!(reconnected.next().await.id, missed[1].id);
+}
+
+#[tokio::test]
+async fn unknown_event_types_are_rejected() {
+    let app = TestApp::builder().start().await.unwrap();
+
+    let response = app
+        .get("/api/v1/events/stream?types=user.created,user.renamed")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
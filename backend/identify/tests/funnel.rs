@@ -0,0 +1,30 @@
+use std::time::{Duration, Instant};
+
+use identify::funnel::{LoginFunnel, LoginStep};
+
+const ABANDON_AFTER: Duration = Duration::from_secs(600);
+
+#[test]
+fn unfinished_attempts_are_abandoned() {
+    let funnel = LoginFunnel::new(ABANDON_AFTER);
+
+    let finished = funnel.start();
+    funnel.record(finished, LoginStep::PasswordOk);
+    funnel.record(finished, LoginStep::SessionIssued);
+
+    let unfinished = funnel.start();
+    funnel.record(unfinished, LoginStep::TwoFactorPrompted);
+
+    assert_eq!(funnel.sweep(Instant::now()), 0);
+    assert_eq!(funnel.sweep(Instant::now() + ABANDON_AFTER), 1);
+    assert_eq!(funnel.sweep(Instant::now() + ABANDON_AFTER), 0);
+}
+
+#[test]
+fn steps_of_unknown_attempts_are_ignored() {
+    let funnel = LoginFunnel::new(ABANDON_AFTER);
+
+    funnel.record(uuid::Uuid::new_v4(), LoginStep::PasswordOk);
+
+    assert_eq!(funnel.sweep(Instant::now() + ABANDON_AFTER), 0);
+}
@@ -0,0 +1,2821 @@
+//! Golden snapshots of the HTTP API.
+//!
+//! Every endpoint and its error cases are recorded as canonical JSON documents, so that any
+//! change of the API contract shows up in review as a changed snapshot. Random IDs and
+//! timestamps are normalized, while deterministic IDs (e.g. of users) are kept as they are.
+//!
+//! Run `cargo insta review` (or `INSTA_UPDATE=always cargo test`) to update the snapshots.
+
+use std::{sync::Arc, time::Instant};
+
+use axum::{
+    Router,
+    body::{Body, to_bytes},
+    http::{
+        HeaderName, Method, Request,
+        header::{
+            ACCEPT, AUTHORIZATION, CONTENT_TYPE, ETAG, IF_MATCH, IF_NONE_MATCH,
+            LOCATION,
+        },
+    },
+};
+use chrono::DateTime;
+use identify::{
+    api::{
+        self, ApiState,
+        auth::{Authenticator, Principal},
+        middleware::{
+            latency::ServerTiming,
+            sdk::{SdkPolicy, SdkTracker},
+        },
+        rate_limit::{Bucket, MemoryStore, RateLimiter},
+    },
+    config::{
+        EmailChangesConfig, EventStreamConfig, InvitationsConfig, LoginsConfig,
+        RegistrationConfig, SessionsConfig, SmsConfig,
+    },
+    event_feed::UserEventFeed,
+    funnel::LoginFunnel,
+    identifiers::IdentifierLists,
+    logins::LoginTracker,
+    metrics::Metrics,
+    operations::OperationRunner,
+    session_monitor::SessionMonitor,
+    status::StatusBoard,
+};
+use identify_application::{
+    OperationUseCaseDeps, RegisterWebhookEndpointParams, StartOperationParams,
+    TokenGenerator, WebhookEndpointUseCaseDeps,
+    device_contracts::ListByUser as _, register_webhook_endpoint,
+    start_operation, user_contracts::Get as _,
+};
+use identify_domain::{
+    ConsentPolicies, EmailAlias, NewOperationAttrs, NewWebhookEndpointAttrs,
+    Operation, QuotaPolicy, User, UsernameStrategy, WebhookEndpoint,
+    WebhookUrlPolicy,
+};
+use identify_infrastructure::{
+    blobs::FilesystemBlobStorage,
+    email::LogEmailSender,
+    geo::NetworkGeoResolver,
+    passwords::Argon2Hasher,
+    sms::LogSmsSender,
+    storage::{
+        self,
+        devices::DevicesRepository,
+        operations::OperationsRepository,
+        retry::{Retrier, RetryPolicy},
+        users::{UsersRepository, cache::UserCache},
+        webhooks::WebhookEndpointsRepository,
+    },
+    tokens::RandomTokenGenerator,
+};
+use identify_testkit::Testkit;
+use insta::assert_json_snapshot;
+use serde::Serialize;
+use serde_json::{Value, json};
+use tempfile::TempDir;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+const ADMIN_KEY: &str = "admin-key";
+const READER_KEY: &str = "reader-key";
+/// Token every invitation and email verification is sent with, so that snapshots can use them.
+const FIXED_TOKEN: &str = "fixed-token";
+/// Code every phone verification is sent with.
+const FIXED_CODE: &str = "123456";
+
+/// Generates [FIXED_TOKEN] and [FIXED_CODE] every time, and hashes them like the real generator.
+struct FixedTokenGenerator;
+
+impl TokenGenerator for FixedTokenGenerator {
+    fn generate(&self) -> String {
+        FIXED_TOKEN.to_owned()
+    }
+
+    fn generate_code(&self) -> String {
+        FIXED_CODE.to_owned()
+    }
+
+    fn hash(&self, token: &str) -> String {
+        RandomTokenGenerator::new().hash(token)
+    }
+}
+
+/// A response in the form it's recorded in snapshots.
+#[derive(Debug, Serialize)]
+struct Golden {
+    status: u16,
+    content_type: Option<String>,
+    location: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    etag: Option<String>,
+    body: Value,
+}
+
+struct TestApi {
+    kit: Testkit,
+    app: Router,
+    /// Recorded logins, which tests add to the statistics of users by flushing them.
+    logins: LoginTracker,
+    /// Tracked login attempts, which tests report as abandoned by sweeping them.
+    funnel: LoginFunnel,
+    _blobs: TempDir,
+}
+
+impl TestApi {
+    async fn new() -> Self {
+        TestApi::with_rate_limiter(RateLimiter::disabled()).await
+    }
+
+    async fn with_rate_limiter(rate_limiter: RateLimiter) -> Self {
+        TestApi::build(rate_limiter, ConsentPolicies::default()).await
+    }
+
+    async fn with_consent_policies(policies: ConsentPolicies) -> Self {
+        TestApi::build(RateLimiter::disabled(), policies).await
+    }
+
+    async fn build(
+        rate_limiter: RateLimiter,
+        consent_policies: ConsentPolicies,
+    ) -> Self {
+        let kit = Testkit::new().await.unwrap();
+        let pool = kit.pool().clone();
+        let blobs = tempfile::tempdir().unwrap();
+
+        let authenticator = Authenticator::new([
+            (
+                ADMIN_KEY.to_owned(),
+                Principal {
+                    subject: "admin".to_owned(),
+                    admin: true,
+                    permissions: Vec::new(),
+                    organization_id: None,
+                    user_id: None,
+                },
+            ),
+            (
+                READER_KEY.to_owned(),
+                Principal {
+                    subject: "reader".to_owned(),
+                    admin: false,
+                    permissions: vec!["users:read".to_owned()],
+                    organization_id: None,
+                    user_id: None,
+                },
+            ),
+        ]);
+
+        let logins = LoginTracker::from_config(
+            pool.clone(),
+            kit.encryption().clone(),
+            &LoginsConfig::default(),
+        );
+        let funnel = LoginsConfig::default().funnel();
+        let status = StatusBoard::new();
+        let app = api::router(ApiState {
+            operation_runner: OperationRunner::new(
+                pool.clone(),
+                status.clone(),
+            ),
+            pool,
+            read_pool: kit.read_pool().clone(),
+            encryption: kit.encryption().clone(),
+            user_cache: UserCache::disabled(),
+            sdk_tracker: SdkTracker::new(SdkPolicy::default()),
+            authenticator,
+            metrics: Metrics::detached(),
+            server_timing: ServerTiming(false),
+            rate_limiter,
+            status,
+            username_strategy: UsernameStrategy::default(),
+            identifiers: IdentifierLists::builtin(),
+            blob_storage: Arc::new(FilesystemBlobStorage::new(blobs.path())),
+            email_sender: Arc::new(LogEmailSender),
+            email_templates: Arc::default(),
+            geo_resolver: Arc::new(
+                NetworkGeoResolver::parse("203.0.113.0/24,Paris, France")
+                    .unwrap(),
+            ),
+            consent_policies: Arc::new(consent_policies),
+            authorization_policies: Arc::default(),
+            token_generator: Arc::new(FixedTokenGenerator),
+            invitations: InvitationsConfig::default().settings(),
+            email_changes: EmailChangesConfig::default().settings(),
+            password_hasher: Arc::new(Argon2Hasher::new()),
+            registration: RegistrationConfig {
+                mode: "allowed_domains".to_owned(),
+                allowed_domains: vec!["acme.test".to_owned()],
+                ..RegistrationConfig::default()
+            }
+            .settings()
+            .unwrap(),
+            quotas: QuotaPolicy::default(),
+            webhook_urls: WebhookUrlPolicy::default(),
+            sms_sender: Arc::new(LogSmsSender),
+            phone_verifications: SmsConfig::default()
+                .phone_verification_settings(),
+            user_events: UserEventFeed::from_config(
+                kit.read_pool().clone(),
+                &EventStreamConfig::default(),
+            ),
+            sessions: SessionMonitor::from_config(&EventStreamConfig::default()),
+            session_tokens: SessionsConfig::default().settings(None).unwrap(),
+            logins: logins.clone(),
+            funnel: funnel.clone(),
+            retrier: Retrier::new(RetryPolicy::default()),
+        });
+
+        TestApi {
+            kit,
+            app,
+            logins,
+            funnel,
+            _blobs: blobs,
+        }
+    }
+
+    async fn send(
+        &self,
+        method: Method,
+        uri: &str,
+        key: Option<&str>,
+        headers: &[(HeaderName, &str)],
+        body: Option<Value>,
+    ) -> Golden {
+        let mut headers = headers.to_vec();
+        if body.is_some()
+            && !headers.iter().any(|(name, _)| name == CONTENT_TYPE)
+        {
+            headers.push((CONTENT_TYPE, "application/json"));
+        }
+
+        self.send_bytes(
+            method,
+            uri,
+            key,
+            &headers,
+            body.map(|body| body.to_string().into_bytes()),
+        )
+        .await
+    }
+
+    async fn send_bytes(
+        &self,
+        method: Method,
+        uri: &str,
+        key: Option<&str>,
+        headers: &[(HeaderName, &str)],
+        body: Option<Vec<u8>>,
+    ) -> Golden {
+        let mut request = Request::builder().method(method).uri(uri);
+        if let Some(key) = key {
+            request = request.header(AUTHORIZATION, format!("Bearer {key}"));
+        }
+        for (name, value) in headers {
+            request = request.header(name, *value);
+        }
+        let request = match body {
+            Some(body) => request.body(Body::from(body)),
+            None => request.body(Body::empty()),
+        }
+        .unwrap();
+
+        let response = self.app.clone().oneshot(request).await.unwrap();
+
+        let header = |name| {
+            response
+                .headers()
+                .get(name)
+                .map(|value| normalize_str(value.to_str().unwrap()))
+        };
+        let status = response.status().as_u16();
+        let content_type = header(CONTENT_TYPE);
+        let location = header(LOCATION);
+        // Entity tags end with the time of the last change.
+        let etag = header(ETAG).map(|etag| match etag.split_once('-') {
+            Some((version, _)) => format!("{version}-[timestamp]\""),
+            None => etag,
+        });
+
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        // Exports are recorded line by line, and binary bodies, e.g. images, only with their
+        // size.
+        let export = content_type.as_deref().and_then(|content_type| {
+            let text = str::from_utf8(&bytes).ok()?;
+            let lines = text.lines();
+            match content_type.split(';').next()? {
+                "application/x-ndjson" => Some(
+                    lines
+                        .map(|line| serde_json::from_str(line).unwrap())
+                        .collect(),
+                ),
+                "text/csv" => Some(lines.map(csv_record).collect()),
+                _ => None,
+            }
+        });
+        let body = match (
+            export,
+            serde_json::from_slice(&bytes),
+            str::from_utf8(&bytes),
+        ) {
+            (Some(export), _, _) => normalize(Value::Array(export)),
+            (None, Ok(body), _) => normalize(body),
+            (None, Err(_), Ok(text)) => Value::String(text.to_owned()),
+            (None, Err(_), Err(_)) => {
+                Value::String(format!("[{} bytes]", bytes.len()))
+            }
+        };
+
+        Golden {
+            status,
+            content_type,
+            location,
+            etag,
+            body,
+        }
+    }
+
+    async fn get(&self, uri: &str, key: Option<&str>) -> Golden {
+        self.send(Method::GET, uri, key, &[], None).await
+    }
+
+    async fn post(&self, uri: &str, key: Option<&str>, body: Value) -> Golden {
+        self.send(Method::POST, uri, key, &[], Some(body)).await
+    }
+
+    async fn put(&self, uri: &str, key: Option<&str>, body: Value) -> Golden {
+        self.send(Method::PUT, uri, key, &[], Some(body)).await
+    }
+
+    async fn start_operation(&self) -> Operation {
+        let mut tx = storage::begin(self.kit.pool()).await.unwrap();
+        let mut repository = OperationsRepository::new(&mut tx);
+
+        let operation = start_operation(
+            OperationUseCaseDeps::new(&mut repository),
+            StartOperationParams {
+                operation_attrs: NewOperationAttrs {
+                    kind: "test".to_owned(),
+                },
+            },
+        )
+        .await
+        .unwrap();
+
+        storage::commit(tx).await.unwrap();
+
+        operation
+    }
+
+    async fn register_webhook(&self) -> WebhookEndpoint {
+        let mut tx = storage::begin(self.kit.pool()).await.unwrap();
+        let mut repository =
+            WebhookEndpointsRepository::new(&mut tx, self.kit.encryption());
+
+        let endpoint = register_webhook_endpoint(
+            WebhookEndpointUseCaseDeps::new(&mut repository),
+            RegisterWebhookEndpointParams {
+                endpoint_attrs: NewWebhookEndpointAttrs {
+                    url: "https://hooks.example.com/identify".to_owned(),
+                    secret: "0123456789abcdef".to_owned(),
+                    events: Vec::new(),
+                    external_id: None,
+                },
+                url_policy: WebhookUrlPolicy::default(),
+            },
+        )
+        .await
+        .unwrap();
+
+        storage::commit(tx).await.unwrap();
+
+        endpoint
+    }
+}
+
+/// Replaces random values that differ between runs with placeholders.
+/// Splits a line of CSV into its fields, unquoting the quoted ones.
+fn csv_record(line: &str) -> Value {
+    let mut fields = vec![String::new()];
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                fields.last_mut().unwrap().push('"');
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(String::new()),
+            c => fields.last_mut().unwrap().push(c),
+        }
+    }
+
+    fields.into_iter().map(Value::from).collect()
+}
+
+fn normalize(value: Value) -> Value {
+    match value {
+        Value::String(s) => Value::String(normalize_str(&s)),
+        Value::Array(items) => {
+            Value::Array(items.into_iter().map(normalize).collect())
+        }
+        Value::Object(fields) => Value::Object(
+            fields.into_iter().map(|(k, v)| (k, normalize(v))).collect(),
+        ),
+        value => value,
+    }
+}
+
+fn normalize_str(s: &str) -> String {
+    if DateTime::parse_from_rfc3339(s).is_ok() {
+        return "[timestamp]".to_owned();
+    }
+
+    // Random IDs can also be a part of a path, e.g. in the `Location` header.
+    s.split('/')
+        .map(|segment| match Uuid::parse_str(segment) {
+            Ok(id) if id.get_version_num() == 4 => "[uuid]",
+            _ => segment,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[tokio::test]
+async fn root() {
+    let api = TestApi::new().await;
+
+    assert_json_snapshot!(api.get("/", None).await);
+}
+
+#[tokio::test]
+async fn openapi() {
+    let api = TestApi::new().await;
+
+    assert_json_snapshot!(api.get("/api/v1/openapi.json", None).await);
+}
+
+#[tokio::test]
+async fn openapi_unversioned() {
+    let api = TestApi::new().await;
+
+    assert_json_snapshot!(api.get("/openapi.json", None).await);
+}
+
+#[tokio::test]
+async fn unknown_route() {
+    let api = TestApi::new().await;
+
+    assert_json_snapshot!(api.get("/unknown", Some(ADMIN_KEY)).await);
+}
+
+#[tokio::test]
+async fn invalid_credentials() {
+    let api = TestApi::new().await;
+
+    assert_json_snapshot!(api.get("/api/v1/users", Some("wrong-key")).await);
+}
+
+#[tokio::test]
+async fn missing_credentials() {
+    let api = TestApi::new().await;
+
+    assert_json_snapshot!(api.get("/api/v1/users", None).await);
+}
+
+#[tokio::test]
+async fn missing_permission() {
+    let api = TestApi::new().await;
+
+    let body = json!({ "email": "jane@example.test", "first_name": "Jane" });
+    assert_json_snapshot!(
+        api.post("/api/v1/users", Some(READER_KEY), body).await
+    );
+}
+
+#[tokio::test]
+async fn admin_only() {
+    let api = TestApi::new().await;
+
+    assert_json_snapshot!(
+        api.get("/api/v1/sdk/adoption", Some(READER_KEY)).await
+    );
+}
+
+#[tokio::test]
+async fn sdk_adoption() {
+    let api = TestApi::new().await;
+
+    assert_json_snapshot!(
+        api.get("/api/v1/sdk/adoption", Some(ADMIN_KEY)).await
+    );
+}
+
+#[tokio::test]
+async fn metrics() {
+    let api = TestApi::new().await;
+
+    assert_json_snapshot!(api.get("/metrics", Some(ADMIN_KEY)).await);
+}
+
+#[tokio::test]
+async fn rate_limited() {
+    let bucket = Bucket {
+        capacity: 1.0,
+        refill_per_second: 0.001,
+    };
+    let api = TestApi::with_rate_limiter(RateLimiter::new(
+        MemoryStore::default(),
+        bucket,
+        bucket,
+    ))
+    .await;
+
+    api.get("/api/v1/users", Some(READER_KEY)).await;
+    assert_json_snapshot!(api.get("/api/v1/users", Some(READER_KEY)).await);
+}
+
+#[tokio::test]
+async fn create_user() {
+    let api = TestApi::new().await;
+
+    let body = json!({
+        "email": "jane@example.test",
+        "first_name": "Jane",
+        "last_name": "Doe",
+    });
+    assert_json_snapshot!(
+        api.post("/api/v1/users", Some(ADMIN_KEY), body).await
+    );
+}
+
+#[tokio::test]
+async fn create_user_conflict() {
+    let api = TestApi::new().await;
+    api.kit
+        .fixtures()
+        .user()
+        .with_email("jane@example.test")
+        .create()
+        .await
+        .unwrap();
+
+    let body = json!({ "email": "jane@example.test", "first_name": "Jane" });
+    assert_json_snapshot!(
+        api.post("/api/v1/users", Some(ADMIN_KEY), body).await
+    );
+}
+
+#[tokio::test]
+async fn create_user_generated_username_collision() {
+    let api = TestApi::new().await;
+    api.kit
+        .fixtures()
+        .user()
+        .with_email("jane@acme.test")
+        .create()
+        .await
+        .unwrap();
+
+    let body = json!({ "email": "jane@example.test", "first_name": "Jane" });
+    assert_json_snapshot!(
+        api.post("/api/v1/users", Some(ADMIN_KEY), body).await
+    );
+}
+
+#[tokio::test]
+async fn create_user_username_taken() {
+    let api = TestApi::new().await;
+    api.kit
+        .fixtures()
+        .user()
+        .with_username("jane")
+        .create()
+        .await
+        .unwrap();
+
+    let body = json!({
+        "email": "jane@example.test",
+        "username": "Jane",
+        "first_name": "Jane",
+    });
+    assert_json_snapshot!(
+        api.post("/api/v1/users", Some(ADMIN_KEY), body).await
+    );
+}
+
+#[tokio::test]
+async fn create_user_reserved_username() {
+    let api = TestApi::new().await;
+
+    let body = json!({
+        "email": "jane@example.test",
+        "username": "Ad-Min",
+        "first_name": "Jane",
+    });
+    assert_json_snapshot!(
+        api.post("/api/v1/users", Some(ADMIN_KEY), body).await
+    );
+}
+
+#[tokio::test]
+async fn create_user_generated_username_not_reserved() {
+    let api = TestApi::new().await;
+
+    let body = json!({
+        "email": "support@example.test",
+        "first_name": "Sam",
+    });
+    let response = api.post("/api/v1/users", Some(ADMIN_KEY), body).await;
+    assert_eq!(response.body["username"], "support2");
+}
+
+#[tokio::test]
+async fn create_user_malformed() {
+    let api = TestApi::new().await;
+
+    let body = json!({ "email": "jane@example.test" });
+    assert_json_snapshot!(
+        api.post("/api/v1/users", Some(ADMIN_KEY), body).await
+    );
+}
+
+#[tokio::test]
+async fn create_user_invalid() {
+    let api = TestApi::new().await;
+
+    let body = json!({
+        "email": "jane.example.test",
+        "username": "-jane",
+        "first_name": " ",
+        "last_name": "D".repeat(101),
+    });
+    assert_json_snapshot!(
+        api.post("/api/v1/users", Some(ADMIN_KEY), body).await
+    );
+}
+
+#[tokio::test]
+async fn create_user_not_json() {
+    let api = TestApi::new().await;
+
+    let body = Value::String("jane@example.test".to_owned());
+    assert_json_snapshot!(
+        api.post("/api/v1/users", Some(ADMIN_KEY), body).await
+    );
+}
+
+#[tokio::test]
+async fn get_user() {
+    let api = TestApi::new().await;
+    let user = api
+        .kit
+        .fixtures()
+        .user()
+        .with_email("jane@example.test")
+        .with_name("Jane", Some("Doe"))
+        .create()
+        .await
+        .unwrap();
+
+    let uri = format!("/api/v1/users/{}", user.id());
+    assert_json_snapshot!(api.get(&uri, Some(READER_KEY)).await);
+}
+
+#[tokio::test]
+async fn get_user_not_modified() {
+    let api = TestApi::new().await;
+    let user = api.kit.fixtures().user().create().await.unwrap();
+
+    let uri = format!("/api/v1/users/{}", user.id());
+    let headers = [(IF_NONE_MATCH, &*etag(&user))];
+    assert_json_snapshot!(
+        api.send(Method::GET, &uri, Some(READER_KEY), &headers, None)
+            .await
+    );
+}
+
+#[tokio::test]
+async fn get_user_by_username() {
+    let api = TestApi::new().await;
+    api.kit
+        .fixtures()
+        .user()
+        .with_email("jane@example.test")
+        .with_username("jane.doe")
+        .with_name("Jane", Some("Doe"))
+        .create()
+        .await
+        .unwrap();
+
+    assert_json_snapshot!(
+        api.get("/api/v1/users/by-username/Jane.Doe", Some(READER_KEY))
+            .await
+    );
+}
+
+#[tokio::test]
+async fn get_user_by_username_not_found() {
+    let api = TestApi::new().await;
+
+    assert_json_snapshot!(
+        api.get("/api/v1/users/by-username/nobody", Some(READER_KEY))
+            .await
+    );
+}
+
+#[tokio::test]
+async fn update_user() {
+    let api = TestApi::new().await;
+    let user = api
+        .kit
+        .fixtures()
+        .user()
+        .with_email("jane@example.test")
+        .with_name("Jane", None)
+        .create()
+        .await
+        .unwrap();
+
+    let uri = format!("/api/v1/users/{}", user.id());
+    let body = json!({ "first_name": "Janet", "last_name": "Doe" });
+    let headers = [(IF_MATCH, &*etag(&user))];
+    assert_json_snapshot!(
+        api.send(Method::PATCH, &uri, Some(ADMIN_KEY), &headers, Some(body))
+            .await
+    );
+}
+
+#[tokio::test]
+async fn update_user_locale() {
+    let api = TestApi::new().await;
+    let user = api.kit.fixtures().user().create().await.unwrap();
+
+    let uri = format!("/api/v1/users/{}", user.id());
+    let body = json!({
+        "first_name": user.first_name(),
+        "locale": "pt_br",
+        "timezone": "America/Sao_Paulo",
+    });
+    let headers = [(IF_MATCH, &*etag(&user))];
+    assert_json_snapshot!(
+        api.send(Method::PATCH, &uri, Some(ADMIN_KEY), &headers, Some(body))
+            .await
+    );
+}
+
+#[tokio::test]
+async fn update_user_invalid_locale() {
+    let api = TestApi::new().await;
+    let user = api.kit.fixtures().user().create().await.unwrap();
+
+    let uri = format!("/api/v1/users/{}", user.id());
+    let body = json!({
+        "first_name": user.first_name(),
+        "locale": "en-US-Hant",
+        "timezone": "CEST",
+    });
+    let headers = [(IF_MATCH, &*etag(&user))];
+    assert_json_snapshot!(
+        api.send(Method::PATCH, &uri, Some(ADMIN_KEY), &headers, Some(body))
+            .await
+    );
+}
+
+#[tokio::test]
+async fn update_user_stale() {
+    let api = TestApi::new().await;
+    let user = api.kit.fixtures().user().create().await.unwrap();
+
+    let uri = format!("/api/v1/users/{}", user.id());
+    let headers = [(IF_MATCH, &*etag(&user))];
+    let body = json!({ "first_name": "Janet" });
+    api.send(Method::PATCH, &uri, Some(ADMIN_KEY), &headers, Some(body))
+        .await;
+
+    // The change is based on the version the previous one replaced.
+    let body = json!({ "first_name": "Janine" });
+    assert_json_snapshot!(
+        api.send(Method::PATCH, &uri, Some(ADMIN_KEY), &headers, Some(body))
+            .await
+    );
+}
+
+#[tokio::test]
+async fn update_user_forged_etag() {
+    let api = TestApi::new().await;
+    let user = api.kit.fixtures().user().create().await.unwrap();
+
+    let uri = format!("/api/v1/users/{}", user.id());
+    // The current version, but not the time of its last change.
+    let etag = format!("\"{}-0\"", user.version());
+    let headers = [(IF_MATCH, etag.as_str())];
+    let body = json!({ "first_name": "Janet" });
+    assert_json_snapshot!(
+        api.send(Method::PATCH, &uri, Some(ADMIN_KEY), &headers, Some(body))
+            .await
+    );
+}
+
+#[tokio::test]
+async fn update_user_without_if_match() {
+    let api = TestApi::new().await;
+    let user = api.kit.fixtures().user().create().await.unwrap();
+
+    let uri = format!("/api/v1/users/{}", user.id());
+    let body = json!({ "first_name": "Janet" });
+    assert_json_snapshot!(
+        api.send(Method::PATCH, &uri, Some(ADMIN_KEY), &[], Some(body))
+            .await
+    );
+}
+
+#[tokio::test]
+async fn update_user_merge_patch() {
+    let api = TestApi::new().await;
+    let user = api
+        .kit
+        .fixtures()
+        .user()
+        .with_email("jane@example.test")
+        .with_name("Jane", Some("Doe"))
+        .create()
+        .await
+        .unwrap();
+
+    let uri = format!("/api/v1/users/{}", user.id());
+    let headers = [
+        (IF_MATCH, &*etag(&user)),
+        (CONTENT_TYPE, "application/merge-patch+json"),
+    ];
+    let body = json!({ "first_name": "Janet", "last_name": null });
+    assert_json_snapshot!(
+        api.send(Method::PATCH, &uri, Some(ADMIN_KEY), &headers, Some(body))
+            .await
+    );
+}
+
+#[tokio::test]
+async fn update_user_json_patch() {
+    let api = TestApi::new().await;
+    let user = api
+        .kit
+        .fixtures()
+        .user()
+        .with_email("jane@example.test")
+        .with_name("Jane", None)
+        .create()
+        .await
+        .unwrap();
+
+    let uri = format!("/api/v1/users/{}", user.id());
+    let headers = [
+        (IF_MATCH, "*"),
+        (CONTENT_TYPE, "application/json-patch+json"),
+    ];
+    let body = json!([
+        { "op": "test", "path": "/first_name", "value": "Jane" },
+        { "op": "copy", "from": "/first_name", "path": "/last_name" },
+        { "op": "replace", "path": "/first_name", "value": "Janet" },
+    ]);
+    assert_json_snapshot!(
+        api.send(Method::PATCH, &uri, Some(ADMIN_KEY), &headers, Some(body))
+            .await
+    );
+}
+
+#[tokio::test]
+async fn update_user_json_patch_test_failed() {
+    let api = TestApi::new().await;
+    let user = api.kit.fixtures().user().create().await.unwrap();
+
+    let uri = format!("/api/v1/users/{}", user.id());
+    let headers = [
+        (IF_MATCH, &*etag(&user)),
+        (CONTENT_TYPE, "application/json-patch+json"),
+    ];
+    let body = json!([
+        { "op": "test", "path": "/first_name", "value": "Someone else" },
+        { "op": "replace", "path": "/first_name", "value": "Janet" },
+    ]);
+    assert_json_snapshot!(
+        api.send(Method::PATCH, &uri, Some(ADMIN_KEY), &headers, Some(body))
+            .await
+    );
+}
+
+#[tokio::test]
+async fn update_user_invalid_patch() {
+    let api = TestApi::new().await;
+    let user = api.kit.fixtures().user().create().await.unwrap();
+
+    let uri = format!("/api/v1/users/{}", user.id());
+    let headers = [
+        (IF_MATCH, &*etag(&user)),
+        (CONTENT_TYPE, "application/json-patch+json"),
+    ];
+    let body = json!([
+        { "op": "replace", "path": "/email", "value": "janet@example.test" },
+        { "op": "remove", "path": "/id" },
+    ]);
+    assert_json_snapshot!(
+        api.send(Method::PATCH, &uri, Some(ADMIN_KEY), &headers, Some(body))
+            .await
+    );
+
+    let headers = [
+        (IF_MATCH, &*etag(&user)),
+        (CONTENT_TYPE, "application/merge-patch+json"),
+    ];
+    let body = json!({ "first_name": "", "nickname": "Jan" });
+    assert_json_snapshot!(
+        api.send(Method::PATCH, &uri, Some(ADMIN_KEY), &headers, Some(body))
+            .await
+    );
+}
+
+/// Returns the entity tag of the current version of the user.
+fn etag(user: &User) -> String {
+    format!(
+        "\"{}-{}\"",
+        user.version(),
+        user.updated_at().timestamp_micros()
+    )
+}
+
+#[tokio::test]
+async fn get_user_not_found() {
+    let api = TestApi::new().await;
+
+    let uri = format!("/api/v1/users/{}", Uuid::nil());
+    assert_json_snapshot!(api.get(&uri, Some(READER_KEY)).await);
+}
+
+#[tokio::test]
+async fn get_user_invalid_id() {
+    let api = TestApi::new().await;
+
+    assert_json_snapshot!(
+        api.get("/api/v1/users/not-a-uuid", Some(READER_KEY)).await
+    );
+}
+
+#[tokio::test]
+async fn list_users() {
+    let api = TestApi::new().await;
+    let fixtures = api.kit.fixtures();
+    for (email, first_name) in [
+        ("ann@example.test", "Ann"),
+        ("bob@example.test", "Bob"),
+        ("cid@example.test", "Cid"),
+    ] {
+        fixtures
+            .user()
+            .with_email(email)
+            .with_name(first_name, None)
+            .create()
+            .await
+            .unwrap();
+    }
+
+    let uri = "/api/v1/users?sort=-first_name&page[size]=2";
+    assert_json_snapshot!(api.get(uri, Some(READER_KEY)).await);
+}
+
+#[tokio::test]
+async fn list_users_csv() {
+    let api = TestApi::new().await;
+    let fixtures = api.kit.fixtures();
+    for (email, first_name, last_name) in [
+        ("ann@example.test", "Ann", Some("Doe, Jr.")),
+        (
+            "bob@example.test",
+            "=HYPERLINK(\"https://evil.test\")",
+            None,
+        ),
+        ("cid@other.test", "Cid", None),
+    ] {
+        fixtures
+            .user()
+            .with_email(email)
+            .with_name(first_name, last_name)
+            .create()
+            .await
+            .unwrap();
+    }
+
+    // Exports aren't paginated.
+    let uri = "/api/v1/users?filter[email_domain]=example.test&sort=email&page[size]=1";
+    let headers = [(ACCEPT, "text/csv, application/json;q=0.9")];
+    assert_json_snapshot!(
+        api.send(Method::GET, uri, Some(READER_KEY), &headers, None)
+            .await
+    );
+}
+
+#[tokio::test]
+async fn list_users_ndjson() {
+    let api = TestApi::new().await;
+    let fixtures = api.kit.fixtures();
+    for email in ["ann@example.test", "bob@example.test"] {
+        fixtures.user().with_email(email).create().await.unwrap();
+    }
+
+    let uri = "/api/v1/users?sort=-email";
+    let headers = [(ACCEPT, "application/x-ndjson")];
+    assert_json_snapshot!(
+        api.send(Method::GET, uri, Some(READER_KEY), &headers, None)
+            .await
+    );
+}
+
+#[tokio::test]
+async fn list_users_filtered() {
+    let api = TestApi::new().await;
+    let fixtures = api.kit.fixtures();
+    fixtures
+        .user()
+        .with_email("ann@example.test")
+        .create()
+        .await
+        .unwrap();
+    fixtures
+        .user()
+        .with_email("bob@example.test")
+        .create()
+        .await
+        .unwrap();
+
+    let uri = "/api/v1/users?filter[email]=bob@example.test";
+    assert_json_snapshot!(api.get(uri, Some(READER_KEY)).await);
+}
+
+#[tokio::test]
+async fn list_users_by_email_domain_and_creation() {
+    let api = TestApi::new().await;
+    let fixtures = api.kit.fixtures();
+    for email in ["cid@acme.test", "ann@ACME.test", "bob@globex.test"] {
+        fixtures.user().with_email(email).create().await.unwrap();
+    }
+
+    let uri = "/api/v1/users?filter[email_domain]=@acme.test\
+               &filter[created_after]=2000-01-01T00:00:00Z\
+               &filter[created_before]=2999-01-01T00:00:00%2B02:00&sort=-username";
+    assert_json_snapshot!(api.get(uri, Some(READER_KEY)).await);
+}
+
+#[tokio::test]
+async fn list_users_invalid_filters() {
+    let api = TestApi::new().await;
+
+    let uri = "/api/v1/users?filter[email_domain]=%25.test\
+               &filter[created_after]=yesterday";
+    assert_json_snapshot!(api.get(uri, Some(READER_KEY)).await);
+
+    let uri = "/api/v1/users?filter[created_after]=2026-02-01T00:00:00Z\
+               &filter[created_before]=2026-01-01T00:00:00Z";
+    assert_json_snapshot!(api.get(uri, Some(READER_KEY)).await);
+
+    let uri = "/api/v1/users?filter[email]=ann@globex.test\
+               &filter[email_domain]=acme.test";
+    assert_json_snapshot!(api.get(uri, Some(READER_KEY)).await);
+}
+
+#[tokio::test]
+async fn list_users_invalid_query() {
+    let api = TestApi::new().await;
+
+    let uri =
+        "/api/v1/users?sort=password&page[size]=1000&filter[age]=42&foo=bar";
+    assert_json_snapshot!(api.get(uri, Some(READER_KEY)).await);
+}
+
+#[tokio::test]
+async fn search_users() {
+    let api = TestApi::new().await;
+    let fixtures = api.kit.fixtures();
+    for (email, first_name, last_name) in [
+        ("ann@example.test", "Ann", "Doe"),
+        ("annabel@example.test", "Annabel", "Smith"),
+        ("bob@example.test", "Bob", "Doe"),
+    ] {
+        fixtures
+            .user()
+            .with_email(email)
+            .with_name(first_name, Some(last_name))
+            .create()
+            .await
+            .unwrap();
+    }
+
+    let uri = "/api/v1/users/search?q=ann&page[size]=1";
+    assert_json_snapshot!(api.get(uri, Some(READER_KEY)).await);
+}
+
+#[tokio::test]
+async fn search_users_invalid_query() {
+    let api = TestApi::new().await;
+
+    let uri = "/api/v1/users/search?q=%20&sort=email";
+    assert_json_snapshot!(api.get(uri, Some(READER_KEY)).await);
+}
+
+/// The smallest PNG file, a single transparent pixel.
+const PNG: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR\0\0\0\x01\0\0\0\x01\x08\x06\0\0\0\x1f\x15\xc4\x89\0\0\0\rIDATx\x9cc\0\x01\0\0\x05\0\x01\r\n-\xb4\0\0\0\0IEND\xaeB`\x82";
+
+impl TestApi {
+    async fn put_avatar(
+        &self,
+        user: &User,
+        content_type: &str,
+        avatar: &[u8],
+    ) -> Golden {
+        let uri = format!("/api/v1/users/{}/avatar", user.id());
+        self.send_bytes(
+            Method::PUT,
+            &uri,
+            Some(ADMIN_KEY),
+            &[(CONTENT_TYPE, content_type)],
+            Some(avatar.to_vec()),
+        )
+        .await
+    }
+}
+
+#[tokio::test]
+async fn put_user_avatar() {
+    let api = TestApi::new().await;
+    let user = api.kit.fixtures().user().create().await.unwrap();
+
+    assert_json_snapshot!(api.put_avatar(&user, "image/png", PNG).await);
+}
+
+#[tokio::test]
+async fn put_user_avatar_invalid() {
+    let api = TestApi::new().await;
+    let user = api.kit.fixtures().user().create().await.unwrap();
+
+    assert_json_snapshot!(
+        "put_user_avatar_unsupported_type",
+        api.put_avatar(&user, "image/svg+xml", b"<svg/>").await
+    );
+    assert_json_snapshot!(
+        "put_user_avatar_mismatched_type",
+        api.put_avatar(&user, "image/jpeg", PNG).await
+    );
+    assert_json_snapshot!(
+        "put_user_avatar_too_large",
+        api.put_avatar(&user, "image/png", &[0; 1024 * 1024 + 1])
+            .await
+    );
+}
+
+#[tokio::test]
+async fn get_user_avatar() {
+    let api = TestApi::new().await;
+    let user = api.kit.fixtures().user().create().await.unwrap();
+    let uri = format!("/api/v1/users/{}/avatar", user.id());
+
+    assert_json_snapshot!(
+        "get_user_avatar_missing",
+        api.get(&uri, Some(READER_KEY)).await
+    );
+    let etag = api.put_avatar(&user, "image/png", PNG).await.etag.unwrap();
+    assert_json_snapshot!(
+        "get_user_avatar",
+        api.get(&uri, Some(READER_KEY)).await
+    );
+    let headers = [(IF_NONE_MATCH, etag.as_str())];
+    assert_json_snapshot!(
+        "get_user_avatar_not_modified",
+        api.send(Method::GET, &uri, Some(READER_KEY), &headers, None)
+            .await
+    );
+}
+
+#[tokio::test]
+async fn put_user_attributes() {
+    let api = TestApi::new().await;
+    let user = api.kit.fixtures().user().create().await.unwrap();
+
+    let uri = format!("/api/v1/users/{}/attributes", user.id());
+    let body = json!({
+        "locale": "en-GB",
+        "seats": 5,
+        "newsletter": true,
+        "address": { "city": "Lyon", "lines": ["1 Rue de la Paix"] },
+    });
+    api.put(&uri, Some(ADMIN_KEY), body).await;
+    // Replaces all the attributes, leaving one out deletes it.
+    let body = json!({ "locale": "fr-FR", "seats": 5.5, "newsletter": true });
+    assert_json_snapshot!(api.put(&uri, Some(ADMIN_KEY), body).await);
+}
+
+#[tokio::test]
+async fn get_user_attributes() {
+    let api = TestApi::new().await;
+    let user = api.kit.fixtures().user().create().await.unwrap();
+
+    let uri = format!("/api/v1/users/{}/attributes", user.id());
+    let body = json!({ "locale": "en-GB", "tags": ["beta"] });
+    api.put(&uri, Some(ADMIN_KEY), body).await;
+    assert_json_snapshot!(api.get(&uri, Some(READER_KEY)).await);
+}
+
+#[tokio::test]
+async fn put_user_attributes_invalid() {
+    let api = TestApi::new().await;
+    let user = api.kit.fixtures().user().create().await.unwrap();
+
+    let uri = format!("/api/v1/users/{}/attributes", user.id());
+    let invalid_key = json!({ "1st-login": true });
+    let null = json!({ "locale": null });
+    assert_json_snapshot!(
+        "put_user_attributes_invalid_key",
+        api.put(&uri, Some(ADMIN_KEY), invalid_key).await
+    );
+    assert_json_snapshot!(
+        "put_user_attributes_null",
+        api.put(&uri, Some(ADMIN_KEY), null).await
+    );
+}
+
+#[tokio::test]
+async fn put_user_attributes_user_not_found() {
+    let api = TestApi::new().await;
+
+    let uri = format!("/api/v1/users/{}/attributes", Uuid::nil());
+    let body = json!({ "locale": "en-GB" });
+    assert_json_snapshot!(api.put(&uri, Some(ADMIN_KEY), body).await);
+}
+
+#[tokio::test]
+async fn delete_user_attribute() {
+    let api = TestApi::new().await;
+    let user = api.kit.fixtures().user().create().await.unwrap();
+
+    let uri = format!("/api/v1/users/{}/attributes", user.id());
+    api.put(&uri, Some(ADMIN_KEY), json!({ "locale": "en-GB" }))
+        .await;
+    let attribute_uri = format!("{uri}/locale");
+    assert_json_snapshot!(
+        "delete_user_attribute",
+        api.send(Method::DELETE, &attribute_uri, Some(ADMIN_KEY), &[], None)
+            .await
+    );
+    assert_json_snapshot!(
+        "delete_user_attribute_not_found",
+        api.send(Method::DELETE, &attribute_uri, Some(ADMIN_KEY), &[], None)
+            .await
+    );
+}
+
+const USER_AGENT: &str =
+    "Mozilla/5.0 (X11; Linux x86_64; rv:131.0) Gecko/20100101 Firefox/131.0";
+
+fn login(ip: &str, fingerprint: Option<&str>) -> Value {
+    json!({ "user_agent": USER_AGENT, "ip": ip, "fingerprint": fingerprint })
+}
+
+#[tokio::test]
+async fn login_attempts() {
+    let api = TestApi::new().await;
+    let user = api.kit.fixtures().user().create().await.unwrap();
+
+    assert_json_snapshot!(
+        "login_attempt_started",
+        api.post("/api/v1/login-attempts", Some(ADMIN_KEY), json!({}))
+            .await
+    );
+    // Random IDs are normalized in the responses, so the finished attempt is started directly.
+    let finished = api.funnel.start();
+    let steps_uri = format!("/api/v1/login-attempts/{finished}/steps");
+    assert_json_snapshot!(
+        "login_attempt_step",
+        api.post(
+            &steps_uri,
+            Some(ADMIN_KEY),
+            json!({ "step": "password_ok" })
+        )
+        .await
+    );
+    assert_json_snapshot!(
+        "login_attempt_unknown_step",
+        api.post(&steps_uri, Some(ADMIN_KEY), json!({ "step": "abandoned" }))
+            .await
+    );
+    let mut body = login("203.0.113.7", None);
+    body["attempt_id"] = json!(finished);
+    let uri = format!("/api/v1/users/{}/logins", user.id());
+    assert_eq!(api.post(&uri, Some(ADMIN_KEY), body).await.status, 201);
+
+    // Only the attempt that didn't end with a login is abandoned.
+    let later = Instant::now() + LoginsConfig::default().abandon_after();
+    assert_eq!(api.funnel.sweep(later), 1);
+}
+
+#[tokio::test]
+async fn record_user_login() {
+    let api = TestApi::new().await;
+    let user = api.kit.fixtures().user().create().await.unwrap();
+    let uri = format!("/api/v1/users/{}/logins", user.id());
+
+    assert_json_snapshot!(
+        "record_user_login_new_device",
+        api.post(&uri, Some(ADMIN_KEY), login("203.0.113.7", Some("laptop")))
+            .await
+    );
+    assert_json_snapshot!(
+        "record_user_login_known_device",
+        api.post(&uri, Some(ADMIN_KEY), login("198.51.100.1", Some("laptop")))
+            .await
+    );
+}
+
+#[tokio::test]
+async fn record_user_login_invalid() {
+    let api = TestApi::new().await;
+    let user = api.kit.fixtures().user().create().await.unwrap();
+    let uri = format!("/api/v1/users/{}/logins", user.id());
+
+    assert_json_snapshot!(
+        "record_user_login_invalid",
+        api.post(
+            &uri,
+            Some(ADMIN_KEY),
+            json!({ "ip": "localhost", "fingerprint": "" })
+        )
+        .await
+    );
+    assert_json_snapshot!(
+        "record_user_login_user_not_found",
+        api.post(
+            &format!("/api/v1/users/{}/logins", Uuid::nil()),
+            Some(ADMIN_KEY),
+            login("203.0.113.7", None)
+        )
+        .await
+    );
+}
+
+#[tokio::test]
+async fn login_statistics() {
+    let api = TestApi::new().await;
+    let fixtures = api.kit.fixtures();
+    fixtures
+        .user()
+        .with_email("ann@example.test")
+        .create()
+        .await
+        .unwrap();
+    let bob = fixtures
+        .user()
+        .with_email("bob@example.test")
+        .create()
+        .await
+        .unwrap();
+    let logins_uri = format!("/api/v1/users/{}/logins", bob.id());
+    for ip in ["203.0.113.7", "198.51.100.1"] {
+        api.post(&logins_uri, Some(ADMIN_KEY), login(ip, None))
+            .await;
+    }
+    api.logins.flush().await.unwrap();
+
+    assert_json_snapshot!(
+        "login_statistics_user",
+        api.get(&format!("/api/v1/users/{}", bob.id()), Some(READER_KEY))
+            .await
+    );
+    // Users who never logged in are inactive too.
+    assert_json_snapshot!(
+        "login_statistics_inactive_users",
+        api.get(
+            "/api/v1/users?filter[inactive_since]=2000-01-01T00:00:00Z",
+            Some(READER_KEY)
+        )
+        .await
+    );
+}
+
+#[tokio::test]
+async fn list_user_devices() {
+    let api = TestApi::new().await;
+    let user = api.kit.fixtures().user().create().await.unwrap();
+    let logins_uri = format!("/api/v1/users/{}/logins", user.id());
+    api.post(
+        &logins_uri,
+        Some(ADMIN_KEY),
+        login("203.0.113.7", Some("laptop")),
+    )
+    .await;
+    api.post(
+        &logins_uri,
+        Some(ADMIN_KEY),
+        login("198.51.100.1", Some("phone")),
+    )
+    .await;
+
+    assert_json_snapshot!(
+        "list_user_devices",
+        api.get(
+            &format!("/api/v1/users/{}/devices", user.id()),
+            Some(READER_KEY)
+        )
+        .await
+    );
+}
+
+#[tokio::test]
+async fn revoke_user_device() {
+    let api = TestApi::new().await;
+    let user = api.kit.fixtures().user().create().await.unwrap();
+    let logins_uri = format!("/api/v1/users/{}/logins", user.id());
+    api.post(&logins_uri, Some(ADMIN_KEY), login("203.0.113.7", None))
+        .await;
+    // The body has its IDs normalized, so the device is looked up instead.
+    let mut tx = storage::begin(api.kit.pool()).await.unwrap();
+    let devices = DevicesRepository::new(&mut tx)
+        .list_by_user(user.id())
+        .await
+        .unwrap();
+    drop(tx);
+    let uri = format!(
+        "/api/v1/users/{}/devices/{}/revoke",
+        user.id(),
+        devices[0].id()
+    );
+
+    assert_json_snapshot!(
+        "revoke_user_device",
+        api.post(&uri, Some(ADMIN_KEY), json!({})).await
+    );
+    assert_json_snapshot!(
+        "revoke_user_device_already_revoked",
+        api.post(&uri, Some(ADMIN_KEY), json!({})).await
+    );
+    assert_json_snapshot!(
+        "revoke_user_device_not_found",
+        api.post(
+            &format!(
+                "/api/v1/users/{}/devices/{}/revoke",
+                user.id(),
+                Uuid::nil()
+            ),
+            Some(ADMIN_KEY),
+            json!({})
+        )
+        .await
+    );
+}
+
+fn terms() -> ConsentPolicies {
+    ConsentPolicies::new([("terms".to_owned(), "2026-10".to_owned())]).unwrap()
+}
+
+#[tokio::test]
+async fn record_user_login_consent_required() {
+    let api = TestApi::with_consent_policies(terms()).await;
+    let user = api.kit.fixtures().user().create().await.unwrap();
+    let logins_uri = format!("/api/v1/users/{}/logins", user.id());
+    let consents_uri = format!("/api/v1/users/{}/consents", user.id());
+
+    assert_json_snapshot!(
+        "record_user_login_consent_required",
+        api.post(&logins_uri, Some(ADMIN_KEY), login("203.0.113.7", None))
+            .await
+    );
+
+    let accept = json!({ "policy": "terms", "version": "2026-10" });
+    api.post(&consents_uri, Some(ADMIN_KEY), accept).await;
+    let login = api
+        .post(&logins_uri, Some(ADMIN_KEY), login("203.0.113.7", None))
+        .await;
+    assert_eq!(login.status, 201);
+}
+
+#[tokio::test]
+async fn accept_user_policy() {
+    let api = TestApi::with_consent_policies(terms()).await;
+    let user = api.kit.fixtures().user().create().await.unwrap();
+    let uri = format!("/api/v1/users/{}/consents", user.id());
+    let accept = json!({ "policy": "terms", "version": "2026-10" });
+
+    assert_json_snapshot!(
+        "accept_user_policy",
+        api.post(&uri, Some(ADMIN_KEY), accept.clone()).await
+    );
+    assert_json_snapshot!(
+        "accept_user_policy_again",
+        api.post(&uri, Some(ADMIN_KEY), accept).await
+    );
+    assert_json_snapshot!(
+        "accept_user_policy_outdated",
+        api.post(
+            &uri,
+            Some(ADMIN_KEY),
+            json!({ "policy": "terms", "version": "2026-09" })
+        )
+        .await
+    );
+    assert_json_snapshot!(
+        "accept_user_policy_unknown",
+        api.post(
+            &uri,
+            Some(ADMIN_KEY),
+            json!({ "policy": "cookies", "version": "1" })
+        )
+        .await
+    );
+    assert_json_snapshot!(
+        "accept_user_policy_invalid",
+        api.post(&uri, Some(ADMIN_KEY), json!({ "policy": "terms" }))
+            .await
+    );
+}
+
+#[tokio::test]
+async fn list_user_consents() {
+    let api = TestApi::with_consent_policies(terms()).await;
+    let user = api.kit.fixtures().user().create().await.unwrap();
+    let uri = format!("/api/v1/users/{}/consents", user.id());
+
+    assert_json_snapshot!(
+        "list_user_consents_pending",
+        api.get(&uri, Some(READER_KEY)).await
+    );
+
+    api.post(
+        &uri,
+        Some(ADMIN_KEY),
+        json!({ "policy": "terms", "version": "2026-10" }),
+    )
+    .await;
+    assert_json_snapshot!(
+        "list_user_consents",
+        api.get(&uri, Some(READER_KEY)).await
+    );
+}
+
+/// Returns the ID of the only invitation, which responses normalize.
+async fn invitation_id(api: &TestApi) -> Uuid {
+    let mut tx = storage::begin(api.kit.pool()).await.unwrap();
+    let id = sqlx::query_scalar("select id from invitations")
+        .fetch_one(&mut *tx)
+        .await
+        .unwrap();
+    drop(tx);
+
+    id
+}
+
+#[tokio::test]
+async fn create_invitation() {
+    let api = TestApi::new().await;
+    let (owner, organization) = api
+        .kit
+        .fixtures()
+        .organization()
+        .with_name("Acme")
+        .owned_by("owner@acme.test")
+        .create()
+        .await
+        .unwrap();
+    let member = api
+        .kit
+        .fixtures()
+        .user()
+        .with_email("member@acme.test")
+        .in_org(&organization)
+        .create()
+        .await
+        .unwrap();
+    let invite = |email: &str, inviter: &User| {
+        json!({
+            "email": email,
+            "inviter_id": inviter.id(),
+            "organization_id": organization.id(),
+            "role": "admin",
+        })
+    };
+
+    assert_json_snapshot!(
+        "create_invitation",
+        api.post(
+            "/api/v1/invitations",
+            Some(ADMIN_KEY),
+            invite("jane@acme.test", &owner)
+        )
+        .await
+    );
+    assert_json_snapshot!(
+        "create_invitation_not_admin",
+        api.post(
+            "/api/v1/invitations",
+            Some(ADMIN_KEY),
+            invite("john@acme.test", &member)
+        )
+        .await
+    );
+    assert_json_snapshot!(
+        "create_invitation_existing_user",
+        api.post(
+            "/api/v1/invitations",
+            Some(ADMIN_KEY),
+            invite("member@acme.test", &owner)
+        )
+        .await
+    );
+    assert_json_snapshot!(
+        "create_invitation_invalid",
+        api.post(
+            "/api/v1/invitations",
+            Some(ADMIN_KEY),
+            json!({ "email": "jane" })
+        )
+        .await
+    );
+}
+
+#[tokio::test]
+async fn accept_invitation() {
+    let api = TestApi::new().await;
+    let (owner, organization) = api
+        .kit
+        .fixtures()
+        .organization()
+        .owned_by("owner@acme.test")
+        .create()
+        .await
+        .unwrap();
+    api.post(
+        "/api/v1/invitations",
+        Some(ADMIN_KEY),
+        json!({
+            "email": "jane@acme.test",
+            "inviter_id": owner.id(),
+            "organization_id": organization.id(),
+        }),
+    )
+    .await;
+    let id = invitation_id(&api).await;
+    let uri = format!("/api/v1/invitations/{id}/accept");
+
+    assert_json_snapshot!(
+        "get_invitation",
+        api.get(&format!("/api/v1/invitations/{id}"), Some(READER_KEY))
+            .await
+    );
+    // The detail names the random ID of the invitation, so only the status is checked.
+    let wrong_token = api
+        .post(
+            &uri,
+            Some(ADMIN_KEY),
+            json!({ "token": "guess", "first_name": "Jane" }),
+        )
+        .await;
+    assert_eq!(wrong_token.status, 404);
+    let accept = json!({
+        "token": FIXED_TOKEN,
+        "first_name": "Jane",
+        "last_name": "Doe",
+    });
+    assert_json_snapshot!(
+        "accept_invitation",
+        api.post(&uri, Some(ADMIN_KEY), accept.clone()).await
+    );
+    assert_json_snapshot!(
+        "accept_invitation_again",
+        api.post(&uri, Some(ADMIN_KEY), accept).await
+    );
+}
+
+#[tokio::test]
+async fn organization_quotas() {
+    let api = TestApi::new().await;
+    let (owner, organization) = api
+        .kit
+        .fixtures()
+        .organization()
+        .with_external_id("acme")
+        .owned_by("owner@acme.test")
+        .create()
+        .await
+        .unwrap();
+    let uri = "/api/v1/organizations/acme/quota";
+
+    assert_json_snapshot!(
+        "get_organization_quota",
+        api.get(uri, Some(ADMIN_KEY)).await
+    );
+    assert_json_snapshot!(
+        "put_organization_quota",
+        api.put(uri, Some(ADMIN_KEY), json!({ "max_users": 1 }))
+            .await
+    );
+
+    api.post(
+        "/api/v1/invitations",
+        Some(ADMIN_KEY),
+        json!({
+            "email": "jane@acme.test",
+            "inviter_id": owner.id(),
+            "organization_id": organization.id(),
+        }),
+    )
+    .await;
+    let accept_uri =
+        format!("/api/v1/invitations/{}/accept", invitation_id(&api).await);
+    let accept = json!({ "token": FIXED_TOKEN, "first_name": "Jane" });
+    assert_json_snapshot!(
+        "accept_invitation_quota_exceeded",
+        api.post(&accept_uri, Some(ADMIN_KEY), accept.clone()).await
+    );
+
+    assert_json_snapshot!(
+        "reset_organization_quota",
+        api.send(Method::DELETE, uri, Some(ADMIN_KEY), &[], None)
+            .await
+    );
+    assert_eq!(
+        api.post(&accept_uri, Some(ADMIN_KEY), accept).await.status,
+        201
+    );
+    assert_json_snapshot!(
+        "get_organization_quota_not_found",
+        api.get("/api/v1/organizations/unknown/quota", Some(ADMIN_KEY))
+            .await
+    );
+}
+
+/// Returns the ID of the only change of email, which responses normalize.
+async fn email_change_id(api: &TestApi) -> Uuid {
+    let mut tx = storage::begin(api.kit.pool()).await.unwrap();
+    let id = sqlx::query_scalar("select id from email_changes")
+        .fetch_one(&mut *tx)
+        .await
+        .unwrap();
+    drop(tx);
+
+    id
+}
+
+#[tokio::test]
+async fn change_email() {
+    let api = TestApi::new().await;
+    let jane = api
+        .kit
+        .fixtures()
+        .user()
+        .with_email("jane@acme.test")
+        .create()
+        .await
+        .unwrap();
+    api.kit
+        .fixtures()
+        .user()
+        .with_email("john@acme.test")
+        .create()
+        .await
+        .unwrap();
+    let uri = format!("/api/v1/users/{}/email-changes", jane.id());
+
+    assert_json_snapshot!(
+        "request_email_change_taken",
+        api.post(&uri, Some(ADMIN_KEY), json!({ "email": "john@acme.test" }))
+            .await
+    );
+    assert_json_snapshot!(
+        "request_email_change_current",
+        api.post(&uri, Some(ADMIN_KEY), json!({ "email": "jane@acme.test" }))
+            .await
+    );
+    assert_json_snapshot!(
+        "request_email_change",
+        api.post(
+            &uri,
+            Some(ADMIN_KEY),
+            json!({ "email": "jane.doe@acme.test" })
+        )
+        .await
+    );
+    let id = email_change_id(&api).await;
+    let confirm = format!("/api/v1/email-changes/{id}/confirm");
+
+    // The detail names the random ID of the change, so only the status is checked.
+    let wrong_token = api
+        .post(&confirm, Some(ADMIN_KEY), json!({ "token": "guess" }))
+        .await;
+    assert_eq!(wrong_token.status, 404);
+    assert_json_snapshot!(
+        "confirm_email_change",
+        api.post(&confirm, Some(ADMIN_KEY), json!({ "token": FIXED_TOKEN }))
+            .await
+    );
+    assert_json_snapshot!(
+        "confirm_email_change_again",
+        api.post(&confirm, Some(ADMIN_KEY), json!({ "token": FIXED_TOKEN }))
+            .await
+    );
+    // The original email isn't an alias, since the ID of Jane is generated from it.
+    assert_json_snapshot!(
+        "list_email_aliases_after_change",
+        api.get(
+            &format!("/api/v1/users/{}/email-aliases", jane.id()),
+            Some(READER_KEY)
+        )
+        .await
+    );
+    // The original email still identifies Jane, so nobody else can register with it.
+    assert_json_snapshot!(
+        "register_user_changed_email",
+        api.post(
+            "/api/v1/auth/register",
+            Some(ADMIN_KEY),
+            json!({
+                "email": "jane@acme.test",
+                "password": "correct horse battery",
+                "first_name": "Jane",
+            })
+        )
+        .await
+    );
+    // Neither can anyone register with the new email.
+    let register_new = api
+        .post(
+            "/api/v1/auth/register",
+            Some(ADMIN_KEY),
+            json!({
+                "email": "Jane.Doe@acme.test",
+                "password": "correct horse battery",
+                "first_name": "Jane",
+            }),
+        )
+        .await;
+    assert_eq!(register_new.status, 409);
+}
+
+#[tokio::test]
+async fn email_aliases() {
+    let api = TestApi::new().await;
+    let jane = api
+        .kit
+        .fixtures()
+        .user()
+        .with_email("jane@acme.test")
+        .create()
+        .await
+        .unwrap();
+    api.kit
+        .fixtures()
+        .user()
+        .with_email("john@acme.test")
+        .create()
+        .await
+        .unwrap();
+    let uri = format!("/api/v1/users/{}/email-aliases", jane.id());
+    let register = |email: &str| {
+        json!({
+            "email": email,
+            "password": "correct horse battery",
+            "first_name": "Jane",
+        })
+    };
+
+    assert_json_snapshot!(
+        "add_email_alias",
+        api.post(
+            &uri,
+            Some(ADMIN_KEY),
+            json!({ "email": "jane+news@acme.test" })
+        )
+        .await
+    );
+    assert_json_snapshot!(
+        "add_email_alias_taken",
+        api.post(&uri, Some(ADMIN_KEY), json!({ "email": "john@acme.test" }))
+            .await
+    );
+    assert_json_snapshot!(
+        "add_email_alias_own",
+        api.post(&uri, Some(ADMIN_KEY), json!({ "email": "Jane@acme.test" }))
+            .await
+    );
+    assert_json_snapshot!(
+        "list_email_aliases",
+        api.get(&uri, Some(READER_KEY)).await
+    );
+    // Nobody can register with an alias of someone else.
+    assert_json_snapshot!(
+        "register_user_alias",
+        api.post(
+            "/api/v1/auth/register",
+            Some(ADMIN_KEY),
+            register("jane+news@acme.test")
+        )
+        .await
+    );
+
+    let alias_id = EmailAlias::id_of("jane+news@acme.test");
+    let remove = format!("{uri}/{alias_id}");
+    let removed = api
+        .send(Method::DELETE, &remove, Some(ADMIN_KEY), &[], None)
+        .await;
+    assert_eq!(removed.status, 204);
+    let removed_again = api
+        .send(Method::DELETE, &remove, Some(ADMIN_KEY), &[], None)
+        .await;
+    assert_eq!(removed_again.status, 404);
+    // The email is free again once the alias is removed.
+    assert_eq!(
+        api.post(
+            "/api/v1/auth/register",
+            Some(ADMIN_KEY),
+            register("jane+news@acme.test")
+        )
+        .await
+        .status,
+        201
+    );
+}
+
+#[tokio::test]
+async fn register_user() {
+    let api = TestApi::new().await;
+    let body = json!({
+        "email": "Jane@acme.test",
+        "password": "correct horse battery",
+        "first_name": "Jane",
+        "last_name": "Doe",
+    });
+
+    // People register themselves, without credentials.
+    assert_json_snapshot!(
+        "register_user",
+        api.post("/api/v1/auth/register", None, body.clone()).await
+    );
+    assert_json_snapshot!(
+        "register_user_again",
+        api.post("/api/v1/auth/register", None, body).await
+    );
+    assert_json_snapshot!(
+        "register_user_domain_not_allowed",
+        api.post(
+            "/api/v1/auth/register",
+            None,
+            json!({
+                "email": "jane@example.test",
+                "password": "correct horse battery",
+                "first_name": "Jane",
+            }),
+        )
+        .await
+    );
+    assert_json_snapshot!(
+        "register_user_invalid",
+        api.post(
+            "/api/v1/auth/register",
+            None,
+            json!({ "email": "jane@acme.test", "password": "short" }),
+        )
+        .await
+    );
+}
+
+#[tokio::test]
+async fn verify_email() {
+    let api = TestApi::new().await;
+    api.post(
+        "/api/v1/auth/register",
+        None,
+        json!({
+            "email": "jane@acme.test",
+            "password": "correct horse battery",
+            "first_name": "Jane",
+        }),
+    )
+    .await;
+    let mut tx = storage::begin(api.kit.pool()).await.unwrap();
+    let id: Uuid = sqlx::query_scalar("select id from email_verifications")
+        .fetch_one(&mut *tx)
+        .await
+        .unwrap();
+    drop(tx);
+
+    // The detail names the random ID of the verification, so only the status is checked.
+    let wrong_token = api
+        .post(
+            "/api/v1/auth/verify-email",
+            None,
+            json!({ "verification": id, "token": "guess" }),
+        )
+        .await;
+    assert_eq!(wrong_token.status, 404);
+    let body = json!({ "verification": id, "token": FIXED_TOKEN });
+    assert_json_snapshot!(
+        "verify_email",
+        api.post("/api/v1/auth/verify-email", None, body.clone())
+            .await
+    );
+    assert_json_snapshot!(
+        "verify_email_again",
+        api.post("/api/v1/auth/verify-email", None, body).await
+    );
+}
+
+#[tokio::test]
+async fn start_phone_verification() {
+    let api = TestApi::new().await;
+    let user = api.kit.fixtures().user().create().await.unwrap();
+    let uri = format!("/api/v1/users/{}/phone-verifications", user.id());
+
+    assert_json_snapshot!(
+        "start_phone_verification",
+        api.post(
+            &uri,
+            Some(ADMIN_KEY),
+            json!({ "phone_number": "+1 (415) 555-0123" })
+        )
+        .await
+    );
+    assert_json_snapshot!(
+        "start_phone_verification_invalid",
+        api.post(&uri, Some(ADMIN_KEY), json!({ "phone_number": "555-0123" }))
+            .await
+    );
+}
+
+#[tokio::test]
+async fn verify_phone() {
+    let api = TestApi::new().await;
+    let user = api.kit.fixtures().user().create().await.unwrap();
+    api.post(
+        &format!("/api/v1/users/{}/phone-verifications", user.id()),
+        Some(ADMIN_KEY),
+        json!({ "phone_number": "+14155550123" }),
+    )
+    .await;
+    let mut tx = storage::begin(api.kit.pool()).await.unwrap();
+    let id: Uuid = sqlx::query_scalar("select id from phone_verifications")
+        .fetch_one(&mut *tx)
+        .await
+        .unwrap();
+    drop(tx);
+    let uri = format!("/api/v1/phone-verifications/{id}/verify");
+
+    assert_json_snapshot!(
+        "verify_phone_wrong_code",
+        api.post(&uri, Some(ADMIN_KEY), json!({ "code": "000000" }))
+            .await
+    );
+    let body = json!({ "code": FIXED_CODE });
+    assert_json_snapshot!(
+        "verify_phone",
+        api.post(&uri, Some(ADMIN_KEY), body.clone()).await
+    );
+    assert_json_snapshot!(
+        "verify_phone_again",
+        api.post(&uri, Some(ADMIN_KEY), body).await
+    );
+}
+
+#[tokio::test]
+async fn erase_user() {
+    let api = TestApi::new().await;
+    let user = api
+        .kit
+        .fixtures()
+        .user()
+        .with_email("jane@example.test")
+        .with_name("Jane", Some("Doe"))
+        .create()
+        .await
+        .unwrap();
+    let attributes_uri = format!("/api/v1/users/{}/attributes", user.id());
+    api.put(
+        &attributes_uri,
+        Some(ADMIN_KEY),
+        json!({ "locale": "en-GB" }),
+    )
+    .await;
+    api.put_avatar(&user, "image/png", PNG).await;
+    let logins_uri = format!("/api/v1/users/{}/logins", user.id());
+    api.post(&logins_uri, Some(ADMIN_KEY), login("203.0.113.7", None))
+        .await;
+    api.post(
+        &format!("/api/v1/users/{}/phone-verifications", user.id()),
+        Some(ADMIN_KEY),
+        json!({ "phone_number": "+14155550123" }),
+    )
+    .await;
+
+    // The erasure is based on the user as they are after all of that. The ETags of responses
+    // are normalized, so the user is looked up instead.
+    let mut tx = storage::begin(api.kit.pool()).await.unwrap();
+    let user = UsersRepository::new(&mut tx, api.kit.encryption())
+        .get(user.id())
+        .await
+        .unwrap();
+    drop(tx);
+    let etag = etag(&user);
+    let uri = format!("/api/v1/users/{}/erasure", user.id());
+    let body = json!({ "confirmation": "Jane@Example.test" });
+    let headers = [(IF_MATCH, etag.as_str())];
+    assert_json_snapshot!(
+        "erase_user",
+        api.send(
+            Method::POST,
+            &uri,
+            Some(ADMIN_KEY),
+            &headers,
+            Some(body.clone())
+        )
+        .await
+    );
+    let headers = [(IF_MATCH, "*")];
+    assert_json_snapshot!(
+        "erase_user_already_erased",
+        api.send(Method::POST, &uri, Some(ADMIN_KEY), &headers, Some(body))
+            .await
+    );
+
+    // Everything else the user had is gone.
+    let user_uri = format!("/api/v1/users/{}", user.id());
+    assert_json_snapshot!(
+        "erase_user_get",
+        api.get(&user_uri, Some(READER_KEY)).await
+    );
+    let attributes = api.get(&attributes_uri, Some(READER_KEY)).await;
+    assert_eq!(attributes.body, json!({}));
+    let avatar_uri = format!("/api/v1/users/{}/avatar", user.id());
+    assert_eq!(api.get(&avatar_uri, Some(READER_KEY)).await.status, 404);
+    let devices_uri = format!("/api/v1/users/{}/devices", user.id());
+    let devices = api.get(&devices_uri, Some(READER_KEY)).await;
+    assert_eq!(devices.body, json!({ "items": [] }));
+    let phone_verifications: i64 =
+        sqlx::query_scalar("select count(*) from phone_verifications")
+            .fetch_one(api.kit.pool())
+            .await
+            .unwrap();
+    assert_eq!(phone_verifications, 0);
+}
+
+#[tokio::test]
+async fn erase_user_unconfirmed() {
+    let api = TestApi::new().await;
+    let user = api
+        .kit
+        .fixtures()
+        .user()
+        .with_email("jane@example.test")
+        .create()
+        .await
+        .unwrap();
+
+    let uri = format!("/api/v1/users/{}/erasure", user.id());
+    let headers = [(IF_MATCH, &*etag(&user))];
+    assert_json_snapshot!(
+        "erase_user_wrong_confirmation",
+        api.send(
+            Method::POST,
+            &uri,
+            Some(ADMIN_KEY),
+            &headers,
+            Some(json!({ "confirmation": "john@example.test" }))
+        )
+        .await
+    );
+    assert_json_snapshot!(
+        "erase_user_missing_confirmation",
+        api.send(
+            Method::POST,
+            &uri,
+            Some(ADMIN_KEY),
+            &headers,
+            Some(json!({}))
+        )
+        .await
+    );
+}
+
+#[tokio::test]
+async fn erase_user_stale() {
+    let api = TestApi::new().await;
+    let user = api
+        .kit
+        .fixtures()
+        .user()
+        .with_email("jane@example.test")
+        .create()
+        .await
+        .unwrap();
+    let user_uri = format!("/api/v1/users/{}", user.id());
+    let headers = [(IF_MATCH, &*etag(&user))];
+    api.send(
+        Method::PATCH,
+        &user_uri,
+        Some(ADMIN_KEY),
+        &headers,
+        Some(json!({ "first_name": "Janet" })),
+    )
+    .await;
+
+    // The erasure is based on the version the change replaced.
+    let uri = format!("/api/v1/users/{}/erasure", user.id());
+    let body = json!({ "confirmation": "jane@example.test" });
+    assert_json_snapshot!(
+        api.send(Method::POST, &uri, Some(ADMIN_KEY), &headers, Some(body))
+            .await
+    );
+}
+
+#[tokio::test]
+async fn erase_user_without_if_match() {
+    let api = TestApi::new().await;
+    let user = api
+        .kit
+        .fixtures()
+        .user()
+        .with_email("jane@example.test")
+        .create()
+        .await
+        .unwrap();
+
+    let uri = format!("/api/v1/users/{}/erasure", user.id());
+    let body = json!({ "confirmation": "jane@example.test" });
+    assert_json_snapshot!(api.post(&uri, Some(ADMIN_KEY), body).await);
+}
+
+#[tokio::test]
+async fn merge_users() {
+    let api = TestApi::new().await;
+    let primary = api
+        .kit
+        .fixtures()
+        .user()
+        .with_email("jane@example.test")
+        .with_name("Jane", Some("Doe"))
+        .create()
+        .await
+        .unwrap();
+    let duplicate = api
+        .kit
+        .fixtures()
+        .user()
+        .with_email("jane.doe@example.test")
+        .with_name("Jane", None)
+        .create()
+        .await
+        .unwrap();
+    for (user, attributes) in [
+        (&primary, json!({ "locale": "en-GB" })),
+        (&duplicate, json!({ "locale": "fr-FR", "seats": 5 })),
+    ] {
+        let uri = format!("/api/v1/users/{}/attributes", user.id());
+        api.put(&uri, Some(ADMIN_KEY), attributes).await;
+    }
+    let logins_uri = format!("/api/v1/users/{}/logins", duplicate.id());
+    let duplicate_login = api
+        .post(&logins_uri, Some(ADMIN_KEY), login("203.0.113.7", None))
+        .await;
+    api.post(
+        &format!("/api/v1/users/{}/phone-verifications", duplicate.id()),
+        Some(ADMIN_KEY),
+        json!({ "phone_number": "+14155550123" }),
+    )
+    .await;
+
+    let uri = format!("/api/v1/admin/users/{}/merge", primary.id());
+    let body = json!({ "duplicate_id": duplicate.id() });
+    assert_json_snapshot!(
+        "merge_users",
+        api.post(&uri, Some(ADMIN_KEY), body.clone()).await
+    );
+    assert_json_snapshot!(
+        "merge_users_already_merged",
+        api.post(&uri, Some(ADMIN_KEY), body).await
+    );
+
+    // The primary user keeps their own attributes, and takes over the rest.
+    let attributes_uri = format!("/api/v1/users/{}/attributes", primary.id());
+    let attributes = api.get(&attributes_uri, Some(READER_KEY)).await;
+    assert_eq!(attributes.body, json!({ "locale": "en-GB", "seats": 5.0 }));
+    let devices_uri = format!("/api/v1/users/{}/devices", primary.id());
+    let devices = api.get(&devices_uri, Some(READER_KEY)).await;
+    assert_eq!(devices.body["items"].as_array().unwrap().len(), 1);
+    // So does the session the duplicate logged in with.
+    let token = &duplicate_login.body["session"]["token"];
+    let session = api
+        .post(
+            "/api/v1/sessions/verify",
+            Some(READER_KEY),
+            json!({ "token": token }),
+        )
+        .await;
+    assert_eq!(session.body["user_id"], primary.id().to_string());
+    // The phone numbers of the duplicate are erased along with it.
+    let phone_verifications: i64 =
+        sqlx::query_scalar("select count(*) from phone_verifications")
+            .fetch_one(api.kit.pool())
+            .await
+            .unwrap();
+    assert_eq!(phone_verifications, 0);
+}
+
+#[tokio::test]
+async fn merge_users_into_themselves() {
+    let api = TestApi::new().await;
+    let user = api.kit.fixtures().user().create().await.unwrap();
+
+    let uri = format!("/api/v1/admin/users/{}/merge", user.id());
+    assert_json_snapshot!(
+        api.post(&uri, Some(ADMIN_KEY), json!({ "duplicate_id": user.id() }))
+            .await
+    );
+}
+
+#[tokio::test]
+async fn get_operation() {
+    let api = TestApi::new().await;
+    let operation = api.start_operation().await;
+
+    let uri = format!("/api/v1/operations/{}", operation.id());
+    assert_json_snapshot!(api.get(&uri, Some(ADMIN_KEY)).await);
+}
+
+#[tokio::test]
+async fn get_operation_not_found() {
+    let api = TestApi::new().await;
+
+    let uri = format!("/api/v1/operations/{}", Uuid::nil());
+    assert_json_snapshot!(api.get(&uri, Some(ADMIN_KEY)).await);
+}
+
+#[tokio::test]
+async fn cancel_operation() {
+    let api = TestApi::new().await;
+    let operation = api.start_operation().await;
+
+    let uri = format!("/api/v1/operations/{}/cancel", operation.id());
+    assert_json_snapshot!(api.post(&uri, Some(ADMIN_KEY), json!({})).await);
+}
+
+#[tokio::test]
+async fn cancel_finished_operation() {
+    let api = TestApi::new().await;
+    let operation = api.start_operation().await;
+
+    let uri = format!("/api/v1/operations/{}/cancel", operation.id());
+    api.post(&uri, Some(ADMIN_KEY), json!({})).await;
+    assert_json_snapshot!(api.post(&uri, Some(ADMIN_KEY), json!({})).await);
+}
+
+#[tokio::test]
+async fn put_organization() {
+    let api = TestApi::new().await;
+
+    let uri = "/api/v1/organizations/acme";
+    let body = json!({ "name": "Acme" });
+    assert_json_snapshot!(api.put(uri, Some(ADMIN_KEY), body).await);
+}
+
+#[tokio::test]
+async fn put_organization_unchanged() {
+    let api = TestApi::new().await;
+
+    let uri = "/api/v1/organizations/acme";
+    let body = json!({ "name": "Acme" });
+    api.put(uri, Some(ADMIN_KEY), body.clone()).await;
+    assert_json_snapshot!(api.put(uri, Some(ADMIN_KEY), body).await);
+}
+
+#[tokio::test]
+async fn put_organization_renamed() {
+    let api = TestApi::new().await;
+
+    let uri = "/api/v1/organizations/acme";
+    api.put(uri, Some(ADMIN_KEY), json!({ "name": "Acme" }))
+        .await;
+    assert_json_snapshot!(
+        api.put(uri, Some(ADMIN_KEY), json!({ "name": "Acme Inc." }))
+            .await
+    );
+}
+
+#[tokio::test]
+async fn put_organization_invalid() {
+    let api = TestApi::new().await;
+
+    let uri = "/api/v1/organizations/acme";
+    assert_json_snapshot!(api.put(uri, Some(ADMIN_KEY), json!({})).await);
+}
+
+#[tokio::test]
+async fn get_organization() {
+    let api = TestApi::new().await;
+
+    let uri = "/api/v1/organizations/acme";
+    api.put(uri, Some(ADMIN_KEY), json!({ "name": "Acme" }))
+        .await;
+    assert_json_snapshot!(api.get(uri, Some(ADMIN_KEY)).await);
+}
+
+#[tokio::test]
+async fn get_organization_not_found() {
+    let api = TestApi::new().await;
+
+    let uri = "/api/v1/organizations/acme";
+    assert_json_snapshot!(api.get(uri, Some(ADMIN_KEY)).await);
+}
+
+#[tokio::test]
+async fn put_organization_slug_taken() {
+    let api = TestApi::new().await;
+
+    api.put(
+        "/api/v1/organizations/acme",
+        Some(ADMIN_KEY),
+        json!({ "name": "Acme" }),
+    )
+    .await;
+    assert_json_snapshot!(
+        api.put(
+            "/api/v1/organizations/other",
+            Some(ADMIN_KEY),
+            json!({ "name": "Other", "slug": "acme" }),
+        )
+        .await
+    );
+}
+
+#[tokio::test]
+async fn get_organization_by_slug() {
+    let api = TestApi::new().await;
+
+    let uri = "/api/v1/organizations/acme";
+    api.put(uri, Some(ADMIN_KEY), json!({ "name": "Acme Corp." }))
+        .await;
+    assert_json_snapshot!(
+        api.get("/api/v1/organizations/by-slug/acme-corp", Some(ADMIN_KEY))
+            .await
+    );
+}
+
+#[tokio::test]
+async fn get_organization_by_previous_slug() {
+    let api = TestApi::new().await;
+
+    let uri = "/api/v1/organizations/acme";
+    api.put(uri, Some(ADMIN_KEY), json!({ "name": "Acme" }))
+        .await;
+    api.put(
+        uri,
+        Some(ADMIN_KEY),
+        json!({ "name": "Acme", "slug": "acme-inc" }),
+    )
+    .await;
+    assert_json_snapshot!(
+        api.get("/api/v1/organizations/by-slug/acme", Some(ADMIN_KEY))
+            .await
+    );
+}
+
+#[tokio::test]
+async fn get_organization_by_slug_not_found() {
+    let api = TestApi::new().await;
+
+    assert_json_snapshot!(
+        api.get("/api/v1/organizations/by-slug/acme", Some(ADMIN_KEY))
+            .await
+    );
+}
+
+#[tokio::test]
+async fn graphql_users() {
+    let api = TestApi::new().await;
+    let fixtures = api.kit.fixtures();
+    let jane = fixtures
+        .user()
+        .with_email("jane@example.test")
+        .create()
+        .await
+        .unwrap();
+    fixtures
+        .user()
+        .with_email("john@example.test")
+        .create()
+        .await
+        .unwrap();
+
+    let query = r#"
+        query ($id: UUID!, $missing: UUID!) {
+            jane: user(id: $id) { email firstName }
+            missing: user(id: $missing) { email }
+            users(pageSize: 1) { items { email } page pageSize totalItems totalPages }
+        }
+    "#;
+    let body = json!({
+        "query": query,
+        "variables": { "id": jane.id(), "missing": Uuid::nil() },
+    });
+    assert_json_snapshot!(api.post("/graphql", Some(READER_KEY), body).await);
+}
+
+#[tokio::test]
+async fn graphql_create_user() {
+    let api = TestApi::new().await;
+
+    let query = r#"
+        mutation {
+            createUser(input: { email: "jane@example.test", firstName: "Jane" }) {
+                id email firstName lastName
+            }
+        }
+    "#;
+    let body = json!({ "query": query });
+    assert_json_snapshot!(api.post("/graphql", Some(ADMIN_KEY), body).await);
+}
+
+#[tokio::test]
+async fn graphql_create_user_invalid() {
+    let api = TestApi::new().await;
+
+    let query = r#"
+        mutation {
+            createUser(input: { email: "jane", firstName: "" }) { id }
+        }
+    "#;
+    let body = json!({ "query": query });
+    assert_json_snapshot!(api.post("/graphql", Some(ADMIN_KEY), body).await);
+}
+
+#[tokio::test]
+async fn graphql_update_user() {
+    let api = TestApi::new().await;
+    let user = api
+        .kit
+        .fixtures()
+        .user()
+        .with_email("jane@example.test")
+        .with_name("Jane", None)
+        .create()
+        .await
+        .unwrap();
+
+    let query = r#"
+        mutation ($id: UUID!) {
+            updateUser(id: $id, input: { firstName: "Janet", lastName: "Doe" }) {
+                email firstName lastName
+            }
+        }
+    "#;
+    let body = json!({ "query": query, "variables": { "id": user.id() } });
+    api.post("/graphql", Some(ADMIN_KEY), body).await;
+
+    let uri = format!("/api/v1/users/{}", user.id());
+    assert_json_snapshot!(api.get(&uri, Some(READER_KEY)).await);
+}
+
+#[tokio::test]
+async fn graphql_missing_permission() {
+    let api = TestApi::new().await;
+
+    let query = r#"
+        mutation {
+            createUser(input: { email: "jane@example.test", firstName: "Jane" }) { id }
+        }
+    "#;
+    let body = json!({ "query": query });
+    assert_json_snapshot!(api.post("/graphql", Some(READER_KEY), body).await);
+}
+
+#[tokio::test]
+async fn graphql_limits() {
+    let api = TestApi::new().await;
+
+    let page = "users(pageSize: 100) { items { id email username firstName lastName } }";
+    let query = format!("{{ a: {page} b: {page} c: {page} d: {page} }}");
+    let body = json!({ "query": query });
+    assert_json_snapshot!(
+        "graphql_too_complex",
+        api.post("/graphql", Some(READER_KEY), body).await
+    );
+
+    let type_ref = (0..20).fold(String::from("name"), |inner, _| {
+        format!("ofType {{ {inner} }}")
+    });
+    let query = format!(
+        "{{ __type(name: \"User\") {{ fields {{ type {{ {type_ref} }} }} }} }}"
+    );
+    let body = json!({ "query": query });
+    assert_json_snapshot!(
+        "graphql_too_deep",
+        api.post("/graphql", Some(READER_KEY), body).await
+    );
+}
+
+#[tokio::test]
+async fn graphql_schema() {
+    insta::assert_snapshot!(identify::api::graphql::schema().sdl());
+}
+
+#[tokio::test]
+async fn status() {
+    let api = TestApi::new().await;
+
+    assert_json_snapshot!(api.get("/status", None).await);
+}
+
+#[tokio::test]
+async fn status_incident() {
+    let api = TestApi::new().await;
+
+    let body = json!({ "message": "Logins are slow" });
+    assert_json_snapshot!(
+        api.put("/status/incident", Some(ADMIN_KEY), body).await
+    );
+    assert_json_snapshot!(api.get("/status", None).await);
+}
+
+#[tokio::test]
+async fn status_incident_resolved() {
+    let api = TestApi::new().await;
+
+    let body = json!({ "message": "Logins are slow" });
+    api.put("/status/incident", Some(ADMIN_KEY), body).await;
+    let response = api
+        .send(
+            Method::DELETE,
+            "/status/incident",
+            Some(ADMIN_KEY),
+            &[],
+            None,
+        )
+        .await;
+    assert_eq!(response.status, 204);
+
+    assert_json_snapshot!(api.get("/status", None).await);
+}
+
+#[tokio::test]
+async fn create_webhook_endpoint() {
+    let api = TestApi::new().await;
+
+    let body = json!({
+        "url": "https://hooks.example.com/identify",
+        "secret": "0123456789abcdef",
+        "events": ["user.created", "user.deleted"],
+    });
+    assert_json_snapshot!(
+        api.post("/api/v1/webhooks", Some(ADMIN_KEY), body).await
+    );
+}
+
+#[tokio::test]
+async fn create_webhook_endpoint_invalid() {
+    let api = TestApi::new().await;
+
+    let body = json!({ "url": "ftp://hooks.example.com", "secret": "short" });
+    assert_json_snapshot!(
+        api.post("/api/v1/webhooks", Some(ADMIN_KEY), body).await
+    );
+}
+
+#[tokio::test]
+async fn put_webhook_endpoint() {
+    let api = TestApi::new().await;
+
+    let uri = "/api/v1/webhooks/by-external-id/crm";
+    let body = json!({
+        "url": "https://hooks.example.com/identify",
+        "secret": "0123456789abcdef",
+    });
+    assert_json_snapshot!(api.put(uri, Some(ADMIN_KEY), body).await);
+}
+
+#[tokio::test]
+async fn put_webhook_endpoint_unchanged() {
+    let api = TestApi::new().await;
+
+    let uri = "/api/v1/webhooks/by-external-id/crm";
+    let body = json!({
+        "url": "https://hooks.example.com/identify",
+        "secret": "0123456789abcdef",
+    });
+    api.put(uri, Some(ADMIN_KEY), body.clone()).await;
+    assert_json_snapshot!(api.put(uri, Some(ADMIN_KEY), body).await);
+}
+
+#[tokio::test]
+async fn put_webhook_endpoint_reconfigured() {
+    let api = TestApi::new().await;
+
+    let uri = "/api/v1/webhooks/by-external-id/crm";
+    let body = json!({
+        "url": "https://hooks.example.com/identify",
+        "secret": "0123456789abcdef",
+    });
+    api.put(uri, Some(ADMIN_KEY), body).await;
+    let body = json!({
+        "url": "https://hooks.example.com/identify/v2",
+        "secret": "0123456789abcdef",
+        "events": ["user.deleted"],
+    });
+    assert_json_snapshot!(api.put(uri, Some(ADMIN_KEY), body).await);
+}
+
+#[tokio::test]
+async fn get_webhook_endpoint_by_external_id_not_found() {
+    let api = TestApi::new().await;
+
+    let uri = "/api/v1/webhooks/by-external-id/crm";
+    assert_json_snapshot!(api.get(uri, Some(ADMIN_KEY)).await);
+}
+
+#[tokio::test]
+async fn list_webhook_endpoints() {
+    let api = TestApi::new().await;
+
+    api.register_webhook().await;
+    assert_json_snapshot!(api.get("/api/v1/webhooks", Some(ADMIN_KEY)).await);
+}
+
+#[tokio::test]
+async fn delete_webhook_endpoint() {
+    let api = TestApi::new().await;
+
+    let endpoint = api.register_webhook().await;
+    let uri = format!("/api/v1/webhooks/{}", endpoint.id());
+    let response = api
+        .send(Method::DELETE, &uri, Some(ADMIN_KEY), &[], None)
+        .await;
+    assert_eq!(response.status, 204);
+
+    assert_json_snapshot!(api.get("/api/v1/webhooks", Some(ADMIN_KEY)).await);
+}
+
+#[tokio::test]
+async fn list_webhook_dead_letters() {
+    let api = TestApi::new().await;
+
+    let endpoint = api.register_webhook().await;
+    let uri = format!("/api/v1/webhooks/{}/dead-letters", endpoint.id());
+    assert_json_snapshot!(api.get(&uri, Some(ADMIN_KEY)).await);
+}
+
+#[tokio::test]
+async fn redeliver_webhook_not_found() {
+    let api = TestApi::new().await;
+
+    let endpoint = api.register_webhook().await;
+    let uri = format!(
+        "/api/v1/webhooks/{}/dead-letters/{}/redeliver",
+        endpoint.id(),
+        Uuid::nil()
+    );
+    assert_json_snapshot!(api.post(&uri, Some(ADMIN_KEY), json!({})).await);
+}
+
+#[tokio::test]
+async fn rotate_signing_key() {
+    let api = TestApi::new().await;
+
+    assert_json_snapshot!(
+        api.send(
+            Method::POST,
+            "/api/v1/signing-keys/rotate",
+            Some(ADMIN_KEY),
+            &[],
+            None,
+        )
+        .await
+    );
+}
+
+#[tokio::test]
+async fn jwks() {
+    let api = TestApi::new().await;
+
+    for _ in 0..2 {
+        let response = api
+            .send(
+                Method::POST,
+                "/api/v1/signing-keys/rotate",
+                Some(ADMIN_KEY),
+                &[],
+                None,
+            )
+            .await;
+        assert_eq!(response.status, 201);
+    }
+
+    let mut response = api.get("/.well-known/jwks.json", None).await;
+    // Public keys are random, so only their length is recorded.
+    for key in response.body["keys"].as_array_mut().unwrap() {
+        let x = key["x"].as_str().unwrap();
+        key["x"] = Value::String(format!("[{} chars]", x.len()));
+    }
+    assert_json_snapshot!(response);
+}
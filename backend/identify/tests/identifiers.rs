@@ -0,0 +1,63 @@
+use std::{fs, path::PathBuf};
+
+use identify::{config::IdentifiersConfig, identifiers::IdentifierLists};
+use uuid::Uuid;
+
+/// A word list in a file that is removed when the test ends.
+struct ListFile(PathBuf);
+
+impl ListFile {
+    fn new(words: &str) -> ListFile {
+        let path = std::env::temp_dir()
+            .join(format!("identify-{}.txt", Uuid::new_v4().simple()));
+        fs::write(&path, words).unwrap();
+
+        ListFile(path)
+    }
+}
+
+impl Drop for ListFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+#[test]
+fn lists_are_reloaded_when_they_change() {
+    let reserved = ListFile::new("acme\n");
+    let lists = IdentifierLists::from_config(&IdentifiersConfig {
+        reserved_file: Some(reserved.0.clone()),
+        ..Default::default()
+    })
+    .unwrap();
+    let before = lists.policy();
+    assert!(before.is_reserved("acme"));
+    assert!(!lists.reload().unwrap());
+
+    fs::write(&reserved.0, "globex\n").unwrap();
+    let modified = fs::metadata(&reserved.0).unwrap().modified().unwrap();
+    fs::File::options()
+        .write(true)
+        .open(&reserved.0)
+        .unwrap()
+        .set_modified(modified + std::time::Duration::from_secs(1))
+        .unwrap();
+    assert!(lists.reload().unwrap());
+
+    let after = lists.policy();
+    assert!(after.is_reserved("globex"));
+    assert!(!after.is_reserved("acme"));
+    assert!(after.is_reserved("admin"));
+    // Policies handed out before the reload stay as they were.
+    assert!(before.is_reserved("acme"));
+}
+
+#[test]
+fn missing_lists_are_reported() {
+    let result = IdentifierLists::from_config(&IdentifiersConfig {
+        profanity_file: Some(PathBuf::from("/nonexistent/profanity.txt")),
+        ..Default::default()
+    });
+
+    assert!(result.is_err());
+}
@@ -0,0 +1,310 @@
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use chrono::{TimeDelta, Utc};
+use identify_application::{
+    AcceptInvitationParams, AcceptedInvitation, ApplicationError,
+    CreateInvitationParams, Email, EmailSender, EmailTemplates,
+    InvitationAcceptanceDeps, InvitationDeliveryDeps, TokenGenerator as _,
+    UnitOfWork as _, accept_invitation, create_invitation,
+    invitation_contracts::Insert as _, membership_contracts::Get as _,
+};
+use identify_domain::{
+    DomainError, Invitation, InvitationStatus, MembershipRole,
+    NewInvitationAttrs, Organization, QuotaExceeded, QuotaPolicy, User,
+    UsernameStrategy,
+};
+use identify_infrastructure::{
+    storage::unit_of_work::SqliteUnitOfWork, tokens::RandomTokenGenerator,
+};
+use identify_testkit::Testkit;
+
+const ACCEPT_URL: &str = "https://app.acme.test/invitations/accept";
+
+/// A sender that remembers the emails it sent.
+#[derive(Default)]
+struct RecordingSender {
+    emails: Mutex<Vec<Email>>,
+}
+
+#[async_trait]
+impl EmailSender for RecordingSender {
+    async fn send(&self, email: &Email) -> identify_application::Result<()> {
+        self.emails.lock().unwrap().push(email.clone());
+        Ok(())
+    }
+}
+
+struct Fixture {
+    kit: Testkit,
+    owner: User,
+    organization: Organization,
+    tokens: RandomTokenGenerator,
+    sender: RecordingSender,
+    templates: EmailTemplates,
+    quota_policy: QuotaPolicy,
+}
+
+impl Fixture {
+    async fn new() -> Self {
+        let kit = Testkit::new().await.unwrap();
+        let (owner, organization) = kit
+            .fixtures()
+            .organization()
+            .with_name("Acme")
+            .owned_by("owner@acme.test")
+            .create()
+            .await
+            .unwrap();
+
+        Fixture {
+            kit,
+            owner,
+            organization,
+            tokens: RandomTokenGenerator::new(),
+            sender: RecordingSender::default(),
+            templates: EmailTemplates::default(),
+            quota_policy: QuotaPolicy::default(),
+        }
+    }
+
+    async fn invite(
+        &self,
+        email: &str,
+        inviter: &User,
+    ) -> identify_application::Result<Invitation> {
+        let mut uow =
+            SqliteUnitOfWork::begin(self.kit.pool(), self.kit.encryption())
+                .await
+                .unwrap();
+        let invitation = create_invitation(
+            InvitationDeliveryDeps::new(
+                &mut uow,
+                &self.tokens,
+                &self.sender,
+                &self.templates,
+            ),
+            CreateInvitationParams {
+                email: email.to_owned(),
+                inviter_id: inviter.id(),
+                organization_id: self.organization.id(),
+                role: MembershipRole::Admin,
+                accept_url: ACCEPT_URL.to_owned(),
+                valid_for: TimeDelta::days(7),
+            },
+        )
+        .await?;
+        uow.commit().await.unwrap();
+
+        Ok(invitation)
+    }
+
+    async fn accept(
+        &self,
+        invitation: &Invitation,
+        token: &str,
+    ) -> identify_application::Result<AcceptedInvitation> {
+        let mut uow =
+            SqliteUnitOfWork::begin(self.kit.pool(), self.kit.encryption())
+                .await
+                .unwrap();
+        let accepted = accept_invitation(
+            InvitationAcceptanceDeps::new(&mut uow, &self.tokens),
+            AcceptInvitationParams {
+                id: invitation.id(),
+                token: token.to_owned(),
+                first_name: "Jane".to_owned(),
+                last_name: None,
+                username: None,
+                username_strategy: UsernameStrategy::default(),
+                identifier_policy: Default::default(),
+                quota_policy: self.quota_policy,
+            },
+        )
+        .await?;
+        uow.commit().await.unwrap();
+
+        Ok(accepted)
+    }
+
+    /// Returns the token from the link of the latest email.
+    fn token(&self) -> String {
+        let emails = self.sender.emails.lock().unwrap();
+        let text = &emails.last().expect("no email was sent").text;
+        let (_, token) =
+            text.split_once("&token=").expect("the email has no link");
+
+        token.split_whitespace().next().unwrap().to_owned()
+    }
+}
+
+#[tokio::test]
+async fn invitations_are_emailed() {
+    let fixture = Fixture::new().await;
+
+    let invitation = fixture
+        .invite("jane@acme.test", &fixture.owner)
+        .await
+        .unwrap();
+
+    let emails = fixture.sender.emails.lock().unwrap().clone();
+    assert_eq!(emails.len(), 1);
+    assert_eq!(emails[0].to, "jane@acme.test");
+    let link = format!("{ACCEPT_URL}?invitation={}&token=", invitation.id());
+    assert!(emails[0].text.contains(&link), "{}", emails[0].text);
+    assert!(emails[0].text.contains("Acme"), "{}", emails[0].text);
+    assert_ne!(invitation.token_hash(), fixture.token());
+}
+
+#[tokio::test]
+async fn accepting_creates_the_member() {
+    let fixture = Fixture::new().await;
+    let invitation = fixture
+        .invite("jane@acme.test", &fixture.owner)
+        .await
+        .unwrap();
+
+    let accepted = fixture.accept(&invitation, &fixture.token()).await.unwrap();
+
+    assert_eq!(accepted.user.email(), "jane@acme.test");
+    assert_eq!(accepted.invitation.status(), InvitationStatus::Accepted);
+    assert_eq!(*accepted.invitation.user_id(), Some(accepted.user.id()));
+    let mut uow =
+        SqliteUnitOfWork::begin(fixture.kit.pool(), fixture.kit.encryption())
+            .await
+            .unwrap();
+    let membership = uow
+        .memberships()
+        .get(fixture.organization.id(), accepted.user.id())
+        .await
+        .unwrap();
+    assert_eq!(membership.role(), MembershipRole::Admin);
+    drop(uow);
+
+    let again = fixture.accept(&invitation, &fixture.token()).await;
+    assert!(
+        matches!(
+            again,
+            Err(ApplicationError::Domain(
+                DomainError::InvalidStateTransition { .. }
+            ))
+        ),
+        "{again:?}"
+    );
+}
+
+#[tokio::test]
+async fn full_organizations_cannot_be_joined() {
+    let mut fixture = Fixture::new().await;
+    // The owner is the only user the organization can have.
+    fixture.quota_policy = QuotaPolicy::new(Some(1));
+    let invitation = fixture
+        .invite("jane@acme.test", &fixture.owner)
+        .await
+        .unwrap();
+
+    let accepted = fixture.accept(&invitation, &fixture.token()).await;
+
+    let expected = QuotaExceeded {
+        organization_id: fixture.organization.id(),
+        max_users: 1,
+    };
+    assert!(
+        matches!(&accepted, Err(ApplicationError::QuotaExceeded(e)) if *e == expected),
+        "{accepted:?}"
+    );
+
+    fixture.quota_policy = QuotaPolicy::new(Some(2));
+    let accepted = fixture.accept(&invitation, &fixture.token()).await;
+    assert!(accepted.is_ok(), "{accepted:?}");
+}
+
+#[tokio::test]
+async fn wrong_tokens_are_rejected() {
+    let fixture = Fixture::new().await;
+    let invitation = fixture
+        .invite("jane@acme.test", &fixture.owner)
+        .await
+        .unwrap();
+
+    let accepted = fixture.accept(&invitation, "guess").await;
+
+    assert!(
+        matches!(accepted, Err(ApplicationError::EntityNotFound { .. })),
+        "{accepted:?}"
+    );
+}
+
+#[tokio::test]
+async fn expired_invitations_cannot_be_accepted() {
+    let fixture = Fixture::new().await;
+    let token = fixture.tokens.generate();
+    let mut attrs = Invitation::new(NewInvitationAttrs {
+        email: "jane@acme.test".to_owned(),
+        inviter_id: fixture.owner.id(),
+        organization_id: fixture.organization.id(),
+        role: MembershipRole::Member,
+        token_hash: fixture.tokens.hash(&token),
+        expires_at: Utc::now() + TimeDelta::days(7),
+    })
+    .unwrap()
+    .to_attributes();
+    attrs.expires_at = Utc::now() - TimeDelta::hours(1);
+    let invitation = Invitation::load(attrs).unwrap();
+    let mut uow =
+        SqliteUnitOfWork::begin(fixture.kit.pool(), fixture.kit.encryption())
+            .await
+            .unwrap();
+    uow.invitations().insert(&invitation).await.unwrap();
+    uow.commit().await.unwrap();
+
+    let accepted = fixture.accept(&invitation, &token).await;
+
+    assert!(
+        matches!(
+            accepted,
+            Err(ApplicationError::Domain(
+                DomainError::InvalidStateTransition { .. }
+            ))
+        ),
+        "{accepted:?}"
+    );
+}
+
+#[tokio::test]
+async fn only_admins_can_invite() {
+    let fixture = Fixture::new().await;
+    let member = fixture
+        .kit
+        .fixtures()
+        .user()
+        .with_email("member@acme.test")
+        .in_org(&fixture.organization)
+        .with_role(MembershipRole::Member)
+        .create()
+        .await
+        .unwrap();
+
+    let invited = fixture.invite("jane@acme.test", &member).await;
+
+    assert!(
+        matches!(
+            invited,
+            Err(ApplicationError::Domain(DomainError::Validation { .. }))
+        ),
+        "{invited:?}"
+    );
+    assert!(fixture.sender.emails.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn existing_users_cannot_be_invited() {
+    let fixture = Fixture::new().await;
+
+    let invited = fixture.invite("Owner@acme.test", &fixture.owner).await;
+
+    assert!(
+        matches!(invited, Err(ApplicationError::EntityAlreadyExists { .. })),
+        "{invited:?}"
+    );
+}
@@ -0,0 +1,87 @@
+use std::time::Duration;
+
+use axum::{
+    Router,
+    body::Body,
+    http::Request,
+    middleware::{from_fn, from_fn_with_state},
+    routing::get,
+};
+use identify::{
+    api::{
+        middleware::{
+            context,
+            latency::{self, SERVER_TIMING_HEADER, ServerTiming},
+        },
+        policy::RoutePolicy,
+    },
+    latency::LatencyLayer,
+};
+use tower::ServiceExt as _;
+use tracing_subscriber::{Registry, layer::SubscriberExt as _};
+
+/// Serves a route that takes 20 ms with the budget, with the middleware of the API that
+/// measures it, and returns its `Server-Timing` header.
+async fn server_timing(budget: Duration) -> String {
+    let _subscriber = tracing::subscriber::set_default(
+        Registry::default().with(LatencyLayer),
+    );
+    let handler = get(|| async {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    })
+    .layer(from_fn(latency::handler_span))
+    .layer(from_fn_with_state(
+        RoutePolicy::public().latency_budget(budget),
+        latency::attach_budget,
+    ));
+    let app = Router::new()
+        .route("/slow", handler)
+        .layer(from_fn_with_state(
+            ServerTiming(true),
+            latency::track_latency,
+        ))
+        .layer(from_fn(context::propagate_context));
+
+    let response = app
+        .oneshot(Request::get("/slow").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+
+    response.headers()[SERVER_TIMING_HEADER]
+        .to_str()
+        .unwrap()
+        .to_owned()
+}
+
+/// Returns the entry of the metric in the `Server-Timing` header.
+fn entry<'a>(header: &'a str, name: &str) -> &'a str {
+    header
+        .split(", ")
+        .find(|entry| entry.split(';').next() == Some(name))
+        .unwrap_or_else(|| panic!("no {name} in {header}"))
+}
+
+#[tokio::test]
+async fn server_timing_breaks_the_latency_down() {
+    let header = server_timing(Duration::from_secs(10)).await;
+
+    for name in ["mw", "handler", "usecase", "db", "total"] {
+        assert!(entry(&header, name).starts_with(&format!("{name};dur=")));
+    }
+    let handler: f64 = entry(&header, "handler")
+        .trim_start_matches("handler;dur=")
+        .parse()
+        .unwrap();
+    assert!(handler >= 20.0, "{header}");
+    assert_eq!(entry(&header, "budget"), "budget;dur=10000.000");
+}
+
+#[tokio::test]
+async fn requests_over_the_budget_are_reported() {
+    let header = server_timing(Duration::from_millis(1)).await;
+
+    assert_eq!(
+        entry(&header, "budget"),
+        "budget;dur=1.000;desc=\"exceeded\""
+    );
+}
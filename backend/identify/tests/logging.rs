@@ -0,0 +1,44 @@
+use identify::{
+    config::{LogFormat, LoggingConfig},
+    logging,
+};
+use serde_json::Value;
+
+// Logging is initialized once per process, so everything is checked in a single test.
+#[test]
+fn json_logs_are_written_to_files() {
+    let directory = tempfile::tempdir().unwrap();
+    let mut config = LoggingConfig {
+        filter: "logging=info".to_owned(),
+        format: LogFormat::Json,
+        ..LoggingConfig::default()
+    };
+    config.file.directory = Some(directory.path().to_owned());
+    config.file.prefix = "test.log".to_owned();
+
+    let guard = logging::init(&config).unwrap();
+    tracing::info_span!("request", request_id = "abc").in_scope(|| {
+        tracing::info!(user_id = 42, "user created");
+    });
+    // Dropping the guard flushes the buffered logs.
+    drop(guard);
+
+    let files: Vec<_> = std::fs::read_dir(directory.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    assert_eq!(files.len(), 1, "{files:?}");
+    let name = files[0].file_name().unwrap().to_str().unwrap();
+    assert!(name.starts_with("test.log."), "{name}");
+
+    let logs = std::fs::read_to_string(&files[0]).unwrap();
+    let lines: Vec<Value> = logs
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(lines.len(), 1, "{logs}");
+    assert_eq!(lines[0]["level"], "INFO");
+    assert_eq!(lines[0]["fields"]["message"], "user created");
+    assert_eq!(lines[0]["fields"]["user_id"], 42);
+    assert_eq!(lines[0]["span"]["request_id"], "abc");
+}
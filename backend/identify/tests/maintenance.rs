@@ -0,0 +1,243 @@
+use axum::{Router, http::StatusCode, routing::post};
+use chrono::{DateTime, TimeDelta, Utc};
+use identify::{
+    config::WebhooksConfig,
+    cron::Schedule,
+    maintenance::{self, MaintenanceTask},
+    status::StatusBoard,
+    webhooks::WebhookWorker,
+};
+use identify_application::{
+    RegisterWebhookEndpointParams, WebhookEndpointUseCaseDeps,
+    register_webhook_endpoint,
+};
+use identify_domain::{NewWebhookEndpointAttrs, WebhookUrlPolicy};
+use identify_infrastructure::storage::{
+    self, webhooks::WebhookEndpointsRepository,
+};
+use identify_testkit::Testkit;
+use sqlx::SqlitePool;
+
+/// Registers an endpoint that answers every delivery with `status`.
+async fn register(kit: &Testkit, status: StatusCode) {
+    let app = Router::new().route("/hook", post(move || async move { status }));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/hook", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await });
+
+    let mut tx = storage::begin(kit.pool()).await.unwrap();
+    let mut repository =
+        WebhookEndpointsRepository::new(&mut tx, kit.encryption());
+    register_webhook_endpoint(
+        WebhookEndpointUseCaseDeps::new(&mut repository),
+        RegisterWebhookEndpointParams {
+            endpoint_attrs: NewWebhookEndpointAttrs {
+                url,
+                secret: "0123456789abcdef".to_owned(),
+                events: vec![],
+                external_id: None,
+            },
+            // The receiver is served over plain HTTP on the loopback interface.
+            url_policy: WebhookUrlPolicy { allow_http: true },
+        },
+    )
+    .await
+    .unwrap();
+    storage::commit(tx).await.unwrap();
+}
+
+/// Publishes the recorded events and delivers them to the endpoints.
+async fn deliver(kit: &Testkit) {
+    WebhookWorker::from_config(
+        kit.pool().clone(),
+        kit.encryption().clone(),
+        &WebhooksConfig {
+            allow_http: true,
+            allow_private_networks: true,
+            ..WebhooksConfig::default()
+        },
+        StatusBoard::new(),
+        None,
+    )
+    .unwrap()
+    .unwrap()
+    .run_once()
+    .await
+    .unwrap();
+}
+
+async fn count(pool: &SqlitePool, table: &str) -> i64 {
+    sqlx::query_scalar(&format!("select count(*) from {table}"))
+        .fetch_one(pool)
+        .await
+        .unwrap()
+}
+
+fn tomorrow() -> DateTime<Utc> {
+    Utc::now() + TimeDelta::days(1)
+}
+
+#[tokio::test]
+async fn prunes_only_handled_user_events() {
+    let kit = Testkit::new().await.unwrap();
+    // Fixtures project their events right away, but they aren't published yet.
+    kit.fixtures().user().create().await.unwrap();
+    let deleted = maintenance::run(
+        kit.pool(),
+        kit.encryption(),
+        MaintenanceTask::PruneUserEvents,
+        tomorrow(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(deleted, 0);
+
+    deliver(&kit).await;
+    // Handled, but within the retention.
+    let deleted = maintenance::run(
+        kit.pool(),
+        kit.encryption(),
+        MaintenanceTask::PruneUserEvents,
+        Utc::now() - TimeDelta::days(1),
+    )
+    .await
+    .unwrap();
+    assert_eq!(deleted, 0);
+
+    let deleted = maintenance::run(
+        kit.pool(),
+        kit.encryption(),
+        MaintenanceTask::PruneUserEvents,
+        tomorrow(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(deleted, 1);
+    assert_eq!(count(kit.pool(), "outbox").await, 0);
+}
+
+#[tokio::test]
+async fn prunes_only_delivered_webhook_deliveries() {
+    let kit = Testkit::new().await.unwrap();
+    register(&kit, StatusCode::NO_CONTENT).await;
+    register(&kit, StatusCode::INTERNAL_SERVER_ERROR).await;
+    kit.fixtures().user().create().await.unwrap();
+    deliver(&kit).await;
+
+    let deleted = maintenance::run(
+        kit.pool(),
+        kit.encryption(),
+        MaintenanceTask::PruneWebhookDeliveries,
+        tomorrow(),
+    )
+    .await
+    .unwrap();
+
+    // The failed delivery is still being retried.
+    assert_eq!(deleted, 1);
+    assert_eq!(count(kit.pool(), "webhook_deliveries").await, 1);
+}
+
+#[tokio::test]
+async fn rotates_signing_keys_once_per_schedule() {
+    let kit = Testkit::new().await.unwrap();
+    let rotate = || {
+        maintenance::run(
+            kit.pool(),
+            kit.encryption(),
+            MaintenanceTask::RotateSigningKeys,
+            Utc::now() - TimeDelta::days(30),
+        )
+    };
+
+    assert_eq!(rotate().await.unwrap(), 0);
+    let first = active_signing_key(kit.pool()).await;
+    // Another replica running the same schedule keeps the new key.
+    assert_eq!(rotate().await.unwrap(), 0);
+    assert_eq!(active_signing_key(kit.pool()).await, first);
+
+    // As if the key was created before the previous run.
+    sqlx::query("update signing_keys set created_at = ?")
+        .bind(Utc::now() - TimeDelta::days(31))
+        .execute(kit.pool())
+        .await
+        .unwrap();
+    assert_eq!(rotate().await.unwrap(), 0);
+    assert_ne!(active_signing_key(kit.pool()).await, first);
+    assert_eq!(count(kit.pool(), "signing_keys").await, 2);
+
+    // Retired keys are deleted once they're retired for longer than the retention.
+    let deleted = maintenance::run(
+        kit.pool(),
+        kit.encryption(),
+        MaintenanceTask::RotateSigningKeys,
+        tomorrow(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(deleted, 1);
+    assert_eq!(count(kit.pool(), "signing_keys").await, 1);
+}
+
+async fn active_signing_key(pool: &SqlitePool) -> String {
+    sqlx::query_scalar(
+        "select hex(id) from signing_keys where status = 'active'",
+    )
+    .fetch_one(pool)
+    .await
+    .unwrap()
+}
+
+fn at(time: &str) -> DateTime<Utc> {
+    time.parse().unwrap()
+}
+
+fn next(schedule: &str, after: &str) -> Option<DateTime<Utc>> {
+    schedule.parse::<Schedule>().unwrap().next_after(at(after))
+}
+
+#[test]
+fn schedules_fire_at_matching_times() {
+    let after = "2026-10-16T10:17:30Z";
+
+    assert_eq!(next("* * * * *", after), Some(at("2026-10-16T10:18:00Z")));
+    assert_eq!(
+        next("*/15 * * * *", after),
+        Some(at("2026-10-16T10:30:00Z"))
+    );
+    assert_eq!(next("0 3 * * *", after), Some(at("2026-10-17T03:00:00Z")));
+    assert_eq!(
+        next("5,40 9-11 * * *", after),
+        Some(at("2026-10-16T10:40:00Z"))
+    );
+    // 2026-10-16 is a Friday.
+    assert_eq!(next("0 0 * * 1-5", after), Some(at("2026-10-19T00:00:00Z")));
+    assert_eq!(next("0 0 * * 7", after), Some(at("2026-10-18T00:00:00Z")));
+    assert_eq!(next("0 0 1 1 *", after), Some(at("2027-01-01T00:00:00Z")));
+    // Either the day of month or the day of week.
+    assert_eq!(next("0 0 20 * 0", after), Some(at("2026-10-18T00:00:00Z")));
+    assert_eq!(next("0 0 29 2 *", after), Some(at("2028-02-29T00:00:00Z")));
+    assert_eq!(next("0 0 30 2 *", after), None);
+}
+
+#[test]
+fn invalid_schedules_are_rejected() {
+    for schedule in [
+        "",
+        "* * * *",
+        "* * * * * *",
+        "60 * * * *",
+        "* 24 * * *",
+        "* * 0 * *",
+        "* * * 13 *",
+        "* * * * 8",
+        "*/0 * * * *",
+        "30-10 * * * *",
+        "a * * * *",
+    ] {
+        assert!(
+            schedule.parse::<Schedule>().is_err(),
+            "'{schedule}' should be invalid"
+        );
+    }
+}
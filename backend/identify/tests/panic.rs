@@ -0,0 +1,75 @@
+use std::net::SocketAddr;
+
+use axum::{Router, routing::get};
+use identify::api::middleware::panic;
+use reqwest::StatusCode;
+use serde_json::{Value, json};
+use tokio::net::TcpListener;
+use tower_http::catch_panic::CatchPanicLayer;
+
+async fn panicking() -> &'static str {
+    panic!("the handler failed with a secret");
+}
+
+/// Serves a route that panics next to one that doesn't, behind the panic boundary of the API,
+/// and returns the address of the server.
+async fn serve() -> SocketAddr {
+    let app = Router::new()
+        .route("/panic", get(panicking))
+        .route("/ok", get(|| async { "ok" }))
+        .layer(CatchPanicLayer::custom(panic::handle_panic));
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    address
+}
+
+#[tokio::test]
+async fn panics_are_internal_server_errors() {
+    let address = serve().await;
+
+    let response = reqwest::get(format!("http://{address}/panic"))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(
+        response.headers()["content-type"],
+        "application/problem+json"
+    );
+    // The panic message isn't returned to the caller.
+    assert_eq!(
+        response.json::<Value>().await.unwrap(),
+        json!({
+            "type": "about:blank",
+            "title": "Internal Server Error",
+            "status": 500,
+            "detail": "The server failed to process the request",
+        })
+    );
+}
+
+#[tokio::test]
+async fn the_server_keeps_serving_after_a_panic() {
+    let address = serve().await;
+    let client = reqwest::Client::new();
+
+    for _ in 0..3 {
+        let response = client
+            .get(format!("http://{address}/panic"))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    let response = client
+        .get(format!("http://{address}/ok"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.text().await.unwrap(), "ok");
+}
@@ -0,0 +1,70 @@
+use std::sync::Arc;
+
+use identify_application::{
+    ApplicationError, CreateUserParams, UserLifecycleUseCaseDeps, create_user,
+};
+use identify_domain::{
+    NewUserAttrs, Organization, QuotaExceeded, QuotaPolicy, User,
+    UsernameStrategy,
+};
+use identify_infrastructure::storage::unit_of_work::SqliteUnitOfWork;
+use identify_testkit::Testkit;
+
+/// Creates a user to join the organization, whose quota is limited by the policy.
+async fn create(
+    kit: &Testkit,
+    organization: &Organization,
+    quota_policy: QuotaPolicy,
+) -> identify_application::Result<User> {
+    create_user(
+        UserLifecycleUseCaseDeps::new(
+            SqliteUnitOfWork::begin(kit.pool(), kit.encryption())
+                .await
+                .unwrap(),
+        ),
+        CreateUserParams {
+            user_attrs: NewUserAttrs {
+                email: "jane@acme.test".to_owned(),
+                first_name: "Jane".to_owned(),
+                last_name: None,
+            },
+            username: None,
+            username_strategy: UsernameStrategy::default(),
+            identifier_policy: Arc::default(),
+            organization_id: Some(organization.id()),
+            quota_policy,
+        },
+    )
+    .await
+}
+
+async fn count_users(kit: &Testkit) -> i64 {
+    sqlx::query_scalar("select count(*) from users")
+        .fetch_one(kit.pool())
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn users_are_not_created_for_full_organizations() {
+    let kit = Testkit::new().await.unwrap();
+    let (_, organization) =
+        kit.fixtures().organization().create().await.unwrap();
+
+    // The owner is the only user the organization can have.
+    let created = create(&kit, &organization, QuotaPolicy::new(Some(1))).await;
+
+    let expected = QuotaExceeded {
+        organization_id: organization.id(),
+        max_users: 1,
+    };
+    assert!(
+        matches!(&created, Err(ApplicationError::QuotaExceeded(e)) if *e == expected),
+        "{created:?}"
+    );
+    assert_eq!(count_users(&kit).await, 1);
+
+    let created = create(&kit, &organization, QuotaPolicy::new(Some(2))).await;
+    assert!(created.is_ok(), "{created:?}");
+    assert_eq!(count_users(&kit).await, 2);
+}
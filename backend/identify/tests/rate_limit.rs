@@ -0,0 +1,211 @@
+//! Behavior shared by all the in-memory rate limit stores, and the limits of clients.
+
+use std::net::IpAddr;
+
+use axum::http::{HeaderMap, HeaderValue, StatusCode};
+use identify::api::rate_limit::{
+    Bucket, MemoryStore, RateLimitStore, RateLimiter, ShardedStore, client_ip,
+};
+use identify_infrastructure::network::Network;
+use identify_testkit::api::TestApp;
+
+const BUCKET: Bucket = Bucket {
+    capacity: 2.0,
+    refill_per_second: 0.5,
+};
+
+/// Unix timestamp the tests start at.
+const NOW: f64 = 1_700_000_000.0;
+
+fn stores() -> Vec<(&'static str, Box<dyn RateLimitStore>)> {
+    vec![
+        ("memory", Box::new(MemoryStore::default())),
+        ("sharded", Box::new(ShardedStore::new(Some(4)))),
+    ]
+}
+
+#[tokio::test]
+async fn buckets_start_full_and_run_out() {
+    for (name, store) in stores() {
+        let mut allowed = Vec::new();
+        for _ in 0..3 {
+            allowed
+                .push(store.take("caller", BUCKET, NOW).await.unwrap().allowed);
+        }
+        assert_eq!(allowed, [true, true, false], "{name}");
+
+        let other = store.take("other", BUCKET, NOW).await.unwrap();
+        assert!(other.allowed, "{name}");
+    }
+}
+
+#[tokio::test]
+async fn buckets_are_refilled_up_to_the_capacity() {
+    for (name, store) in stores() {
+        for _ in 0..3 {
+            store.take("caller", BUCKET, NOW).await.unwrap();
+        }
+
+        let denied = store.take("caller", BUCKET, NOW + 1.0).await.unwrap();
+        assert!(!denied.allowed, "{name}");
+        assert!((denied.tokens - 0.5).abs() < 1e-6, "{name}: {denied:?}");
+
+        let taken = store.take("caller", BUCKET, NOW + 2.0).await.unwrap();
+        assert!(taken.allowed, "{name}");
+        assert!(taken.tokens.abs() < 1e-6, "{name}: {taken:?}");
+
+        let taken = store.take("caller", BUCKET, NOW + 100.0).await.unwrap();
+        assert!(taken.allowed, "{name}");
+        assert!((taken.tokens - 1.0).abs() < 1e-6, "{name}: {taken:?}");
+    }
+}
+
+#[tokio::test]
+async fn forgotten_buckets_are_full_again() {
+    for (name, store) in stores() {
+        for _ in 0..2 {
+            store.take("caller", BUCKET, NOW).await.unwrap();
+        }
+
+        store.forget_idle(NOW + 10.0).await.unwrap();
+
+        let taken = store.take("caller", BUCKET, NOW).await.unwrap();
+        assert!(taken.allowed, "{name}");
+    }
+}
+
+fn ip(address: &str) -> IpAddr {
+    address.parse().unwrap()
+}
+
+fn forwarded_for(values: &[&str]) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    for value in values {
+        headers
+            .append("x-forwarded-for", HeaderValue::from_str(value).unwrap());
+    }
+
+    headers
+}
+
+#[test]
+fn clients_are_their_peers_unless_they_are_trusted_proxies() {
+    let proxies = [Network::parse("10.0.0.0/8").unwrap()];
+    let headers = forwarded_for(&["198.51.100.7"]);
+
+    assert_eq!(
+        client_ip(ip("203.0.113.1"), &headers, &proxies),
+        ip("203.0.113.1")
+    );
+    assert_eq!(
+        client_ip(ip("10.0.0.1"), &headers, &proxies),
+        ip("198.51.100.7")
+    );
+    assert_eq!(
+        client_ip(ip("10.0.0.1"), &HeaderMap::new(), &proxies),
+        ip("10.0.0.1")
+    );
+}
+
+#[test]
+fn forged_addresses_before_the_trusted_proxies_are_ignored() {
+    let proxies = [Network::parse("10.0.0.0/8").unwrap()];
+    let headers = forwarded_for(&["192.0.2.1, 198.51.100.7", "10.0.0.2"]);
+
+    assert_eq!(
+        client_ip(ip("10.0.0.1"), &headers, &proxies),
+        ip("198.51.100.7")
+    );
+    assert_eq!(
+        client_ip(ip("10.0.0.1"), &forwarded_for(&["forged"]), &proxies),
+        ip("10.0.0.1")
+    );
+}
+
+#[tokio::test]
+async fn clients_are_limited_before_they_are_authenticated() {
+    let unlimited = Bucket {
+        capacity: 100.0,
+        refill_per_second: 100.0,
+    };
+    let clients = Bucket {
+        capacity: 2.0,
+        refill_per_second: 0.001,
+    };
+    let app = TestApp::builder()
+        .with_rate_limiter(
+            RateLimiter::new(MemoryStore::default(), unlimited, unlimited)
+                .with_clients(clients, Vec::new()),
+        )
+        .start()
+        .await
+        .unwrap();
+
+    let mut statuses = Vec::new();
+    for _ in 0..3 {
+        let response = app
+            .client()
+            .get(format!("{}/api/v1/users", app.base_url()))
+            .bearer_auth("guessed-key")
+            .send()
+            .await
+            .unwrap();
+        statuses.push(response.status());
+    }
+
+    assert_eq!(
+        statuses,
+        [
+            StatusCode::UNAUTHORIZED,
+            StatusCode::UNAUTHORIZED,
+            StatusCode::TOO_MANY_REQUESTS
+        ]
+    );
+}
+
+#[tokio::test]
+async fn anonymous_registrations_are_limited_strictly() {
+    let unlimited = Bucket {
+        capacity: 100.0,
+        refill_per_second: 100.0,
+    };
+    let strict = Bucket {
+        capacity: 2.0,
+        refill_per_second: 0.001,
+    };
+    let app = TestApp::builder()
+        .with_rate_limiter(RateLimiter::new(
+            MemoryStore::default(),
+            unlimited,
+            strict,
+        ))
+        .start()
+        .await
+        .unwrap();
+
+    let mut statuses = Vec::new();
+    for _ in 0..3 {
+        let response = app
+            .client()
+            .post(format!("{}/api/v1/auth/register", app.base_url()))
+            .json(&serde_json::json!({
+                "email": "jane@acme.test",
+                "password": "correct horse battery",
+                "first_name": "Jane",
+            }))
+            .send()
+            .await
+            .unwrap();
+        statuses.push(response.status());
+    }
+
+    // Registration is by invitation only by default, which is only checked within the limit.
+    assert_eq!(
+        statuses,
+        [
+            StatusCode::FORBIDDEN,
+            StatusCode::FORBIDDEN,
+            StatusCode::TOO_MANY_REQUESTS
+        ]
+    );
+}
@@ -0,0 +1,196 @@
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use chrono::TimeDelta;
+use identify_application::{
+    ApplicationError, Email, EmailSender, EmailTemplates,
+    EmailVerificationDeps, PasswordHasher as _, RegisterUserParams,
+    SelfRegistrationDeps, UnitOfWork as _, VerifyEmailParams, register_user,
+    verify_email,
+};
+use identify_domain::{
+    NewUserAttrs, Password, RegistrationMode, RegistrationPolicy,
+    RegistrationRefusal, User, UsernameStrategy,
+};
+use identify_infrastructure::{
+    passwords::Argon2Hasher, storage::unit_of_work::SqliteUnitOfWork,
+    tokens::RandomTokenGenerator,
+};
+use identify_testkit::Testkit;
+use uuid::Uuid;
+
+const VERIFY_URL: &str = "https://app.acme.test/verify-email";
+const PASSWORD: &str = "correct horse battery";
+
+/// A sender that remembers the emails it sent.
+#[derive(Default)]
+struct RecordingSender {
+    emails: Mutex<Vec<Email>>,
+}
+
+#[async_trait]
+impl EmailSender for RecordingSender {
+    async fn send(&self, email: &Email) -> identify_application::Result<()> {
+        self.emails.lock().unwrap().push(email.clone());
+        Ok(())
+    }
+}
+
+struct Fixture {
+    kit: Testkit,
+    hasher: Argon2Hasher,
+    tokens: RandomTokenGenerator,
+    sender: RecordingSender,
+    templates: EmailTemplates,
+}
+
+impl Fixture {
+    async fn new() -> Self {
+        Fixture {
+            kit: Testkit::new().await.unwrap(),
+            hasher: Argon2Hasher::new(),
+            tokens: RandomTokenGenerator::new(),
+            sender: RecordingSender::default(),
+            templates: EmailTemplates::default(),
+        }
+    }
+
+    async fn register(
+        &self,
+        policy: &RegistrationPolicy,
+        email: &str,
+    ) -> identify_application::Result<User> {
+        let mut uow =
+            SqliteUnitOfWork::begin(self.kit.pool(), self.kit.encryption())
+                .await
+                .unwrap();
+        let user = register_user(
+            SelfRegistrationDeps::new(
+                &mut uow,
+                policy,
+                &self.hasher,
+                &self.tokens,
+                &self.sender,
+                &self.templates,
+            ),
+            RegisterUserParams {
+                user_attrs: NewUserAttrs {
+                    email: email.to_owned(),
+                    first_name: "Jane".to_owned(),
+                    last_name: None,
+                },
+                password: Password::parse(PASSWORD).unwrap(),
+                username: None,
+                username_strategy: UsernameStrategy::default(),
+                identifier_policy: Default::default(),
+                verify_url: VERIFY_URL.to_owned(),
+                verification_valid_for: TimeDelta::hours(48),
+            },
+        )
+        .await?;
+        uow.commit().await.unwrap();
+
+        Ok(user)
+    }
+
+    /// Returns the ID of the verification and the token from the link of the latest email.
+    fn link(&self) -> (Uuid, String) {
+        let emails = self.sender.emails.lock().unwrap();
+        let text = &emails.last().expect("no email was sent").text;
+        let (_, query) = text
+            .split_once(&format!("{VERIFY_URL}?verification="))
+            .expect("the email has no link");
+        let (id, token) = query.split_once("&token=").unwrap();
+
+        (
+            id.parse().unwrap(),
+            token.split_whitespace().next().unwrap().to_owned(),
+        )
+    }
+}
+
+fn open() -> RegistrationPolicy {
+    RegistrationPolicy::new(RegistrationMode::Open, []).unwrap()
+}
+
+#[tokio::test]
+async fn registered_users_verify_their_emails() {
+    let fixture = Fixture::new().await;
+
+    let user = fixture.register(&open(), "jane@acme.test").await.unwrap();
+
+    let hash: String =
+        sqlx::query_scalar("select password_hash from users where id = ?")
+            .bind(user.id())
+            .fetch_one(fixture.kit.pool())
+            .await
+            .unwrap();
+    assert!(fixture.hasher.verify(PASSWORD, &hash).unwrap());
+
+    let (id, token) = fixture.link();
+    let mut uow =
+        SqliteUnitOfWork::begin(fixture.kit.pool(), fixture.kit.encryption())
+            .await
+            .unwrap();
+    let wrong = verify_email(
+        EmailVerificationDeps::new(&mut uow, &fixture.tokens),
+        VerifyEmailParams {
+            id,
+            token: "guess".to_owned(),
+        },
+    )
+    .await;
+    assert!(
+        matches!(wrong, Err(ApplicationError::EntityNotFound { .. })),
+        "{wrong:?}"
+    );
+    let verification = verify_email(
+        EmailVerificationDeps::new(&mut uow, &fixture.tokens),
+        VerifyEmailParams { id, token },
+    )
+    .await
+    .unwrap();
+    uow.commit().await.unwrap();
+
+    assert!(verification.is_verified());
+    assert_eq!(verification.user_id(), user.id());
+}
+
+#[tokio::test]
+async fn refused_registrations_create_nothing() {
+    let fixture = Fixture::new().await;
+    let domains = RegistrationPolicy::new(
+        RegistrationMode::AllowedDomains,
+        ["acme.test".to_owned()],
+    )
+    .unwrap();
+
+    for (policy, email, refusal) in [
+        (
+            RegistrationPolicy::default(),
+            "jane@acme.test",
+            RegistrationRefusal::InviteOnly,
+        ),
+        (
+            domains,
+            "jane@example.test",
+            RegistrationRefusal::DomainNotAllowed("example.test".to_owned()),
+        ),
+    ] {
+        let registered = fixture.register(&policy, email).await;
+
+        assert!(
+            matches!(
+                &registered,
+                Err(ApplicationError::RegistrationRefused(r)) if *r == refusal
+            ),
+            "{registered:?}"
+        );
+    }
+    let users: i64 = sqlx::query_scalar("select count(*) from users")
+        .fetch_one(fixture.kit.pool())
+        .await
+        .unwrap();
+    assert_eq!(users, 0);
+    assert!(fixture.sender.emails.lock().unwrap().is_empty());
+}
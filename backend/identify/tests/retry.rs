@@ -0,0 +1,141 @@
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicU32, Ordering},
+    },
+    time::Duration,
+};
+
+use axum::{
+    Extension, Router,
+    body::Body,
+    extract::{FromRef, State},
+    http::{Request, StatusCode},
+    routing::post,
+};
+use identify::api::{
+    auth::Principal, error::ApiError, use_case::UseCaseContext,
+};
+use identify_application::ApplicationError;
+use identify_infrastructure::{
+    encryption::FieldEncryption,
+    storage::retry::{Retrier, RetryPolicy},
+};
+use identify_testkit::Testkit;
+use sqlx::SqlitePool;
+use tower::ServiceExt as _;
+
+#[derive(Clone)]
+struct TestState {
+    pool: SqlitePool,
+    encryption: FieldEncryption,
+    retrier: Retrier,
+    /// Attempts of the changes made so far.
+    attempts: Arc<AtomicU32>,
+    /// Error the changes fail with until the attempt that succeeds.
+    error: fn() -> ApplicationError,
+    succeeding_attempt: u32,
+}
+
+impl FromRef<TestState> for SqlitePool {
+    fn from_ref(state: &TestState) -> Self {
+        state.pool.clone()
+    }
+}
+
+impl FromRef<TestState> for FieldEncryption {
+    fn from_ref(state: &TestState) -> Self {
+        state.encryption.clone()
+    }
+}
+
+impl FromRef<TestState> for Retrier {
+    fn from_ref(state: &TestState) -> Self {
+        state.retrier.clone()
+    }
+}
+
+/// Makes changes in the transaction of the context, which fail until the succeeding attempt.
+async fn handler(
+    State(state): State<TestState>,
+    context: UseCaseContext,
+) -> Result<StatusCode, ApiError> {
+    context
+        .retried("test", |mut context| async {
+            let attempt = state.attempts.fetch_add(1, Ordering::SeqCst) + 1;
+            // The transaction begins like it would for a use case.
+            let _ = context.users().await?;
+            if attempt < state.succeeding_attempt {
+                return Err((state.error)().into());
+            }
+
+            context.commit().await
+        })
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Makes a request whose changes fail with the error until the succeeding attempt, and returns
+/// its status along with the number of attempts.
+async fn request(
+    error: fn() -> ApplicationError,
+    succeeding_attempt: u32,
+) -> (StatusCode, u32) {
+    let kit = Testkit::new().await.unwrap();
+    let state = TestState {
+        pool: kit.pool().clone(),
+        encryption: kit.encryption().clone(),
+        retrier: Retrier::new(RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(5),
+            ..RetryPolicy::default()
+        }),
+        attempts: Arc::default(),
+        error,
+        succeeding_attempt,
+    };
+    let app = Router::new()
+        .route("/changes", post(handler))
+        .layer(Extension(Principal {
+            subject: "test".to_owned(),
+            admin: true,
+            permissions: Vec::new(),
+            organization_id: None,
+            user_id: None,
+        }))
+        .with_state(state.clone());
+
+    let response = app
+        .oneshot(Request::post("/changes").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+
+    (response.status(), state.attempts.load(Ordering::SeqCst))
+}
+
+#[tokio::test]
+async fn changes_are_made_again_while_the_database_is_busy() {
+    let (status, attempts) = request(|| ApplicationError::Unavailable, 3).await;
+
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    assert_eq!(attempts, 3);
+}
+
+#[tokio::test]
+async fn changes_are_made_again_up_to_the_last_attempt() {
+    let (status, attempts) = request(|| ApplicationError::Unavailable, 4).await;
+
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(attempts, 3);
+}
+
+#[tokio::test]
+async fn changes_that_fail_otherwise_are_not_made_again() {
+    let (status, attempts) =
+        request(|| ApplicationError::ReferenceViolation, 2).await;
+
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(attempts, 1);
+}
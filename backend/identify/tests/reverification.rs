@@ -0,0 +1,228 @@
+use std::sync::{
+    Arc, Mutex,
+    atomic::{AtomicBool, Ordering},
+};
+
+use async_trait::async_trait;
+use clap::Parser;
+use identify::admin::{self, AdminContext, Cli};
+use identify_application::{ApplicationError, Email, EmailSender};
+use identify_domain::{QuotaPolicy, UsernameStrategy};
+use identify_testkit::Testkit;
+use uuid::Uuid;
+
+/// A sender that remembers the recipients, and fails while it's told to or once it has sent
+/// as many emails as it's allowed to.
+#[derive(Default)]
+struct RecordingSender {
+    recipients: Mutex<Vec<String>>,
+    failing: AtomicBool,
+    limit: Mutex<Option<usize>>,
+}
+
+#[async_trait]
+impl EmailSender for RecordingSender {
+    async fn send(&self, email: &Email) -> identify_application::Result<()> {
+        let mut recipients = self.recipients.lock().unwrap();
+        let exhausted = self
+            .limit
+            .lock()
+            .unwrap()
+            .is_some_and(|limit| recipients.len() >= limit);
+        if self.failing.load(Ordering::SeqCst) || exhausted {
+            return Err(ApplicationError::Unavailable);
+        }
+
+        recipients.push(email.to.clone());
+        Ok(())
+    }
+}
+
+async fn run(
+    kit: &Testkit,
+    sender: &Arc<RecordingSender>,
+    args: &[&str],
+) -> eyre::Result<String> {
+    let cli = Cli::try_parse_from(
+        std::iter::once("identify-admin").chain(args.iter().copied()),
+    )?;
+    let context = AdminContext {
+        pool: kit.pool(),
+        encryption: kit.encryption().clone(),
+        username_strategy: UsernameStrategy::default(),
+        identifier_policy: Arc::default(),
+        quota_policy: QuotaPolicy::default(),
+        email_sender: sender.clone(),
+        email_templates: Arc::default(),
+    };
+
+    let mut output = Vec::new();
+    admin::run(cli.command, &context, &mut "".as_bytes(), &mut output).await?;
+
+    Ok(String::from_utf8(output)?)
+}
+
+/// Extracts the campaign ID from the output of `start-reverification`.
+fn campaign_id(output: &str) -> &str {
+    output
+        .split_whitespace()
+        .nth(1)
+        .expect("the output starts with the campaign")
+}
+
+#[tokio::test]
+async fn cohort_is_notified_in_batches() {
+    let kit = Testkit::new().await.unwrap();
+    for email in ["jane@acme.test", "john@ACME.test", "jim@other.test"] {
+        kit.fixtures()
+            .user()
+            .with_email(email)
+            .create()
+            .await
+            .unwrap();
+    }
+    let sender = Arc::new(RecordingSender::default());
+
+    let output = run(
+        &kit,
+        &sender,
+        &[
+            "start-reverification",
+            "--reason",
+            "Our email domain has moved.",
+            "--email-domain",
+            "acme.test",
+            "--batch-size",
+            "1",
+            "--pause-secs",
+            "0",
+        ],
+    )
+    .await
+    .unwrap();
+
+    assert!(output.contains("flagged 2 users"), "{output}");
+    assert!(output.ends_with("Notified 2 of 2 users\n"), "{output}");
+    let mut recipients = sender.recipients.lock().unwrap().clone();
+    recipients.sort();
+    assert_eq!(recipients, ["jane@acme.test", "john@ACME.test"]);
+
+    let progress = run(
+        &kit,
+        &sender,
+        &["reverification-progress", campaign_id(&output)],
+    )
+    .await
+    .unwrap();
+    assert_eq!(progress, "Notified 2 of 2 users\n");
+}
+
+#[tokio::test]
+async fn failed_campaigns_are_resumed() {
+    let kit = Testkit::new().await.unwrap();
+    for email in ["jane@acme.test", "john@acme.test"] {
+        kit.fixtures()
+            .user()
+            .with_email(email)
+            .create()
+            .await
+            .unwrap();
+    }
+    let sender = Arc::new(RecordingSender::default());
+    sender.failing.store(true, Ordering::SeqCst);
+
+    let error = run(
+        &kit,
+        &sender,
+        &["start-reverification", "--reason", "Data cleanup"],
+    )
+    .await
+    .unwrap_err();
+    assert!(error.to_string().contains("failed"), "{error}");
+
+    let id: Uuid =
+        sqlx::query_scalar("select id from reverification_campaigns")
+            .fetch_one(kit.pool())
+            .await
+            .unwrap();
+    sender.failing.store(false, Ordering::SeqCst);
+
+    let output = run(
+        &kit,
+        &sender,
+        &[
+            "resume-reverification",
+            &id.to_string(),
+            "--pause-secs",
+            "0",
+        ],
+    )
+    .await
+    .unwrap();
+
+    assert!(output.ends_with("Notified 2 of 2 users\n"), "{output}");
+    assert_eq!(sender.recipients.lock().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn users_emailed_before_a_failure_are_not_emailed_again() {
+    let kit = Testkit::new().await.unwrap();
+    for email in ["jane@acme.test", "john@acme.test"] {
+        kit.fixtures()
+            .user()
+            .with_email(email)
+            .create()
+            .await
+            .unwrap();
+    }
+    let sender = Arc::new(RecordingSender::default());
+    *sender.limit.lock().unwrap() = Some(1);
+
+    let error = run(
+        &kit,
+        &sender,
+        &["start-reverification", "--reason", "Data cleanup"],
+    )
+    .await
+    .unwrap_err();
+    assert!(error.to_string().contains("failed"), "{error}");
+    let id: Uuid =
+        sqlx::query_scalar("select id from reverification_campaigns")
+            .fetch_one(kit.pool())
+            .await
+            .unwrap();
+    *sender.limit.lock().unwrap() = None;
+
+    let output = run(
+        &kit,
+        &sender,
+        &[
+            "resume-reverification",
+            &id.to_string(),
+            "--pause-secs",
+            "0",
+        ],
+    )
+    .await
+    .unwrap();
+
+    assert!(output.ends_with("Notified 2 of 2 users\n"), "{output}");
+    let mut recipients = sender.recipients.lock().unwrap().clone();
+    recipients.sort();
+    assert_eq!(recipients, ["jane@acme.test", "john@acme.test"]);
+}
+
+#[tokio::test]
+async fn unknown_campaigns_are_rejected() {
+    let kit = Testkit::new().await.unwrap();
+    let sender = Arc::new(RecordingSender::default());
+
+    let result = run(
+        &kit,
+        &sender,
+        &["resume-reverification", &Uuid::new_v4().to_string()],
+    )
+    .await;
+
+    assert!(result.is_err());
+}
@@ -0,0 +1,280 @@
+use identify_domain::{UserId, UserIdAttrs};
+use identify_testkit::api::{TestApp, json_of};
+use reqwest::Method;
+use serde_json::{Value, json};
+
+const SUPPORT_KEY: &str = "support";
+
+async fn app() -> TestApp {
+    TestApp::builder()
+        .with_api_key(SUPPORT_KEY, &["roles:read"])
+        .start()
+        .await
+        .unwrap()
+}
+
+async fn create_role(app: &TestApp, name: &str) -> Value {
+    let response = app
+        .post("/api/v1/admin/roles")
+        .json(&json!({
+            "name": name,
+            "description": "Helps users",
+            "permissions": ["users:read"],
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 201);
+
+    response.json().await.unwrap()
+}
+
+#[tokio::test]
+async fn roles_are_created_updated_and_deleted() {
+    let app = app().await;
+    let role = create_role(&app, "support").await;
+    let path = format!("/api/v1/admin/roles/{}", role["id"].as_str().unwrap());
+
+    let response = app
+        .put(&path)
+        .json(&json!({ "name": "helpdesk" }))
+        .send()
+        .await
+        .unwrap();
+    let updated: Value = json_of(response).await.unwrap();
+    let response = app
+        .put(&format!("{path}/permissions/users:write"))
+        .send()
+        .await
+        .unwrap();
+    let granted: Value = json_of(response).await.unwrap();
+    let response = app.get("/api/v1/admin/roles").send().await.unwrap();
+    let listed: Value = json_of(response).await.unwrap();
+
+    assert_eq!(updated["name"], "helpdesk");
+    assert_eq!(updated["description"], Value::Null);
+    assert_eq!(granted["permissions"], json!(["users:read", "users:write"]));
+    assert_eq!(listed["items"].as_array().unwrap().len(), 1);
+    assert_eq!(listed["items"][0]["name"], "helpdesk");
+
+    let deleted = app.request(Method::DELETE, &path).send().await.unwrap();
+    let missing = app.get(&path).send().await.unwrap();
+
+    assert_eq!(deleted.status(), 204);
+    assert_eq!(missing.status(), 404);
+}
+
+#[tokio::test]
+async fn roles_are_assigned_to_users() {
+    let app = app().await;
+    let role = create_role(&app, "support").await;
+    let user = app.create_test_user().await.unwrap();
+    let members = format!(
+        "/api/v1/admin/roles/{}/members",
+        role["id"].as_str().unwrap()
+    );
+    let member = format!("{members}/{}", user.id);
+
+    let assigned = app.put(&member).send().await.unwrap();
+    let again = app.put(&member).send().await.unwrap();
+    let response = app.get(&members).send().await.unwrap();
+    let listed: Value = json_of(response).await.unwrap();
+    let response = app
+        .get(&format!("/api/v1/admin/users/{}/roles", user.id))
+        .send()
+        .await
+        .unwrap();
+    let roles: Value = json_of(response).await.unwrap();
+
+    assert_eq!(assigned.status(), 204);
+    assert_eq!(again.status(), 204);
+    assert_eq!(listed["items"][0]["user_id"], user.id.to_string());
+    assert_eq!(roles["items"][0]["name"], "support");
+
+    let unassigned = app.request(Method::DELETE, &member).send().await;
+    let not_assigned = app.request(Method::DELETE, &member).send().await;
+
+    assert_eq!(unassigned.unwrap().status(), 204);
+    assert_eq!(not_assigned.unwrap().status(), 404);
+}
+
+#[tokio::test]
+async fn callers_have_the_permissions_of_the_roles_of_their_users() {
+    const BOT_KEY: &str = "support-bot";
+    let email = "bot@acme.test";
+    // IDs of users are derived from their emails, so the key can name the user up front.
+    let user_id = UserId::new(UserIdAttrs {
+        email: email.to_owned(),
+    })
+    .to_uuid();
+    let app = TestApp::builder()
+        .with_user_api_key(BOT_KEY, user_id)
+        .start()
+        .await
+        .unwrap();
+    let response = app
+        .post("/api/v1/users")
+        .json(&json!({ "email": email, "first_name": "Bot" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 201);
+    let role = create_role(&app, "support").await;
+    let member = format!(
+        "/api/v1/admin/roles/{}/members/{user_id}",
+        role["id"].as_str().unwrap()
+    );
+    let get_user = || {
+        app.client()
+            .get(format!("{}/api/v1/users/{user_id}", app.base_url()))
+            .bearer_auth(BOT_KEY)
+            .send()
+    };
+
+    let before = get_user().await.unwrap();
+    app.put(&member).send().await.unwrap();
+    let assigned = get_user().await.unwrap();
+    app.request(Method::DELETE, &member).send().await.unwrap();
+    let unassigned = get_user().await.unwrap();
+
+    assert_eq!(before.status(), 403);
+    assert_eq!(assigned.status(), 200);
+    assert_eq!(unassigned.status(), 403);
+}
+
+#[tokio::test]
+async fn roles_are_put_by_external_id() {
+    let app = app().await;
+    let path = "/api/v1/admin/roles/by-external-id/terraform-support";
+    let put = |permissions: Value| {
+        app.put(path)
+            .json(&json!({ "name": "support", "permissions": permissions }))
+            .send()
+    };
+
+    let created = put(json!(["users:read"])).await.unwrap();
+    assert_eq!(created.status(), 201);
+    let created: Value = created.json().await.unwrap();
+    let unchanged = put(json!(["users:read"])).await.unwrap();
+    assert_eq!(unchanged.status(), 200);
+    let unchanged: Value = unchanged.json().await.unwrap();
+    let response = put(json!(["users:write"])).await.unwrap();
+    let updated: Value = json_of(response).await.unwrap();
+    let response = app.get(path).send().await.unwrap();
+    let fetched: Value = json_of(response).await.unwrap();
+    let missing = app
+        .get("/api/v1/admin/roles/by-external-id/unknown")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(created["external_id"], "terraform-support");
+    assert_eq!(unchanged["id"], created["id"]);
+    assert_eq!(unchanged["updated_at"], created["updated_at"]);
+    assert_eq!(updated["id"], created["id"]);
+    assert_eq!(updated["permissions"], json!(["users:write"]));
+    assert_eq!(fetched, updated);
+    assert_eq!(missing.status(), 404);
+}
+
+#[tokio::test]
+async fn names_of_roles_are_unique() {
+    let app = app().await;
+    create_role(&app, "support").await;
+
+    let response = app
+        .post("/api/v1/admin/roles")
+        .json(&json!({ "name": "support" }))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), 409);
+}
+
+#[tokio::test]
+async fn changes_need_the_write_permission() {
+    let app = app().await;
+    let request = |method, path: &str| {
+        app.client()
+            .request(method, format!("{}/api/v1{path}", app.base_url()))
+            .bearer_auth(SUPPORT_KEY)
+    };
+
+    let listed = request(Method::GET, "/admin/roles").send().await.unwrap();
+    let created = request(Method::POST, "/admin/roles")
+        .json(&json!({ "name": "support" }))
+        .send()
+        .await
+        .unwrap();
+    let put = request(Method::PUT, "/admin/roles/by-external-id/support")
+        .json(&json!({ "name": "support" }))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(listed.status(), 200);
+    assert_eq!(created.status(), 403);
+    assert_eq!(put.status(), 403);
+}
+
+#[tokio::test]
+async fn roles_are_assigned_in_bulk_after_a_dry_run() {
+    let app = app().await;
+    let role = create_role(&app, "support").await;
+    let path = format!(
+        "/api/v1/admin/roles/{}/assign",
+        role["id"].as_str().unwrap()
+    );
+    for email in ["jane@acme.test", "john@ACME.test", "jane@globex.test"] {
+        let response = app
+            .post("/api/v1/users")
+            .json(&json!({ "email": email, "first_name": "Jane" }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 201);
+    }
+    let assign = |dry_run: bool| {
+        app.post(&path)
+            .json(&json!({ "email_domain": "acme.test", "dry_run": dry_run }))
+            .send()
+    };
+
+    let dry_run: Value = json_of(assign(true).await.unwrap()).await.unwrap();
+    let response = app
+        .get(&format!("{}/members", path.trim_end_matches("/assign")))
+        .send()
+        .await
+        .unwrap();
+    let members: Value = json_of(response).await.unwrap();
+
+    assert_eq!(dry_run["applied"], false);
+    assert_eq!(dry_run["users"].as_array().unwrap().len(), 2);
+    assert_eq!(members["items"], json!([]));
+
+    let applied: Value = json_of(assign(false).await.unwrap()).await.unwrap();
+    let again: Value = json_of(assign(false).await.unwrap()).await.unwrap();
+
+    assert_eq!(applied["applied"], true);
+    assert_eq!(applied["users"], dry_run["users"]);
+    assert_eq!(again["users"], json!([]));
+}
+
+#[tokio::test]
+async fn bulk_assignments_need_a_criterion() {
+    let app = app().await;
+    let role = create_role(&app, "support").await;
+
+    let response = app
+        .post(&format!(
+            "/api/v1/admin/roles/{}/assign",
+            role["id"].as_str().unwrap()
+        ))
+        .json(&json!({ "dry_run": true }))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), 422);
+}
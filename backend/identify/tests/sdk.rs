@@ -0,0 +1,60 @@
+//! Adoption metrics of the client SDKs.
+
+use std::collections::HashMap;
+
+use identify::api::middleware::sdk::{
+    SdkAdoption, SdkPolicy, SdkTracker, SdkVersion,
+};
+use semver::Version;
+
+fn adoption(sdk: &str, version: &str, requests: u64) -> SdkAdoption {
+    SdkAdoption {
+        sdk: sdk.to_owned(),
+        version: version.to_owned(),
+        requests,
+    }
+}
+
+fn record(tracker: &SdkTracker, header: &str) {
+    tracker.record(&header.parse::<SdkVersion>().unwrap());
+}
+
+#[test]
+fn versions_of_known_sdks_are_counted_by_minor_version() {
+    let tracker = SdkTracker::new(
+        SdkPolicy::default().with_known_names(["identify-js".to_owned()]),
+    );
+
+    record(&tracker, "identify-js/1.4.2");
+    record(&tracker, "identify-js/1.4.3-beta.1");
+    record(&tracker, "identify-js/1.5.0+build.7");
+
+    assert_eq!(
+        tracker.adoption(),
+        [
+            adoption("identify-js", "1.4", 2),
+            adoption("identify-js", "1.5", 1)
+        ]
+    );
+}
+
+#[test]
+fn unknown_sdks_are_counted_as_others() {
+    let tracker = SdkTracker::new(SdkPolicy::new(HashMap::from([(
+        "identify-py".to_owned(),
+        Version::new(0, 4, 0),
+    )])));
+
+    record(&tracker, "identify-py/0.4.1");
+    for i in 0..3 {
+        record(&tracker, &format!("made-up-{i}/{i}.0.0"));
+    }
+
+    assert_eq!(
+        tracker.adoption(),
+        [
+            adoption("identify-py", "0.4", 1),
+            adoption("other", "other", 3)
+        ]
+    );
+}
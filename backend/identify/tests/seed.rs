@@ -0,0 +1,154 @@
+use std::sync::Arc;
+
+use identify::{
+    admin::AdminContext,
+    seed::{self, SeedFile, SeedReport},
+};
+use identify_application::membership_contracts::Get as _;
+use identify_domain::{MembershipRole, QuotaPolicy, UsernameStrategy};
+use identify_infrastructure::{
+    email::LogEmailSender,
+    storage::{self, memberships::MembershipsRepository},
+};
+use identify_testkit::Testkit;
+use uuid::Uuid;
+
+fn context(kit: &Testkit) -> AdminContext<'_> {
+    AdminContext {
+        pool: kit.pool(),
+        encryption: kit.encryption().clone(),
+        username_strategy: UsernameStrategy::default(),
+        identifier_policy: Arc::default(),
+        quota_policy: QuotaPolicy::default(),
+        email_sender: Arc::new(LogEmailSender),
+        email_templates: Arc::default(),
+    }
+}
+
+#[tokio::test]
+async fn sample_file_is_loaded_once() {
+    let kit = Testkit::new().await.unwrap();
+    let seed = SeedFile::read(
+        &std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../seed.sample.yaml"),
+    )
+    .unwrap();
+
+    let report = seed::load(&seed, &context(&kit)).await.unwrap();
+    assert_eq!(
+        report,
+        SeedReport {
+            organizations_created: 2,
+            organizations_updated: 0,
+            users_created: 3,
+            memberships_created: 4,
+        }
+    );
+
+    let report = seed::load(&seed, &context(&kit)).await.unwrap();
+    assert_eq!(report, SeedReport::default());
+}
+
+fn membership_seed(role: &str) -> SeedFile {
+    SeedFile::parse(&format!(
+        r#"
+organizations:
+  - external_id: acme
+    name: Acme
+users:
+  - email: jane@acme.test
+    first_name: Jane
+    memberships:
+      - organization: acme
+        role: {role}
+"#
+    ))
+    .unwrap()
+}
+
+#[tokio::test]
+async fn roles_are_brought_to_the_seeded_ones() {
+    let kit = Testkit::new().await.unwrap();
+    let context = context(&kit);
+    seed::load(&membership_seed("member"), &context)
+        .await
+        .unwrap();
+
+    let report = seed::load(&membership_seed("admin"), &context)
+        .await
+        .unwrap();
+    assert_eq!(report, SeedReport::default());
+
+    let (organization_id, user_id): (Uuid, Uuid) =
+        sqlx::query_as("select organization_id, user_id from memberships")
+            .fetch_one(kit.pool())
+            .await
+            .unwrap();
+    let mut tx = storage::begin(kit.pool()).await.unwrap();
+    let membership = MembershipsRepository::new(&mut tx)
+        .get(organization_id, user_id)
+        .await
+        .unwrap();
+    assert_eq!(membership.role(), MembershipRole::Admin);
+}
+
+#[tokio::test]
+async fn invalid_files_write_nothing() {
+    let kit = Testkit::new().await.unwrap();
+    let seed = SeedFile::parse(
+        r#"
+organizations:
+  - external_id: acme
+    name: Acme
+users:
+  - email: not-an-email
+    first_name: Jane
+"#,
+    )
+    .unwrap();
+
+    let error = seed::load(&seed, &context(&kit)).await.unwrap_err();
+    assert!(error.to_string().contains("not-an-email"));
+
+    let count: i64 = sqlx::query_scalar("select count(*) from organizations")
+        .fetch_one(kit.pool())
+        .await
+        .unwrap();
+    assert_eq!(count, 0);
+}
+
+#[tokio::test]
+async fn users_of_the_same_file_get_different_usernames() {
+    let kit = Testkit::new().await.unwrap();
+    let seed = SeedFile::parse(
+        r#"
+users:
+  - email: jane@acme.test
+    first_name: Jane
+  - email: jane@globex.test
+    first_name: Jane
+"#,
+    )
+    .unwrap();
+
+    let report = seed::load(&seed, &context(&kit)).await.unwrap();
+    assert_eq!(report.users_created, 2);
+
+    let usernames: Vec<String> =
+        sqlx::query_scalar("select username from users order by username")
+            .fetch_all(kit.pool())
+            .await
+            .unwrap();
+    assert_eq!(usernames, ["jane", "jane2"]);
+}
+
+#[test]
+fn unknown_fields_and_roles_are_rejected() {
+    assert!(SeedFile::parse("groups: []").is_err());
+    assert!(
+        SeedFile::parse(
+            "users:\n  - email: a@b.test\n    first_name: A\n    memberships:\n      - organization: acme\n        role: boss\n"
+        )
+        .is_err()
+    );
+}
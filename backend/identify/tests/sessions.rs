@@ -0,0 +1,185 @@
+//! Tests of the sessions issued by logins, and of their live activity, which monitors receive
+//! over a WebSocket.
+
+use std::time::Duration;
+
+use futures_util::StreamExt as _;
+use identify_application::user_event_contracts::Emit as _;
+use identify_domain::{NewUserEventAttrs, UserEvent, UserLifecycleTransition};
+use identify_infrastructure::storage::outbox::OutboxRepository;
+use identify_testkit::api::{ADMIN_KEY, TestApp};
+use reqwest::StatusCode;
+use serde_json::{Value, json};
+use tokio::{net::TcpStream, time::timeout};
+use tokio_tungstenite::{
+    MaybeTlsStream, WebSocketStream, connect_async,
+    tungstenite::{Error, Message, client::IntoClientRequest as _},
+};
+
+/// Longest time a test waits for a message before it fails.
+const MESSAGE_TIMEOUT: Duration = Duration::from_secs(5);
+
+const PATH: &str = "/api/v1/sessions/live";
+
+type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+async fn connect(app: &TestApp, key: Option<&str>) -> Result<Socket, Error> {
+    let url = format!("{}{PATH}", app.base_url().replacen("http", "ws", 1));
+    let mut request = url.into_client_request().unwrap();
+    if let Some(key) = key {
+        request
+            .headers_mut()
+            .insert("authorization", format!("Bearer {key}").parse().unwrap());
+    }
+
+    Ok(connect_async(request).await?.0)
+}
+
+/// Returns the next activity, skipping the pings.
+async fn next(socket: &mut Socket) -> Value {
+    timeout(MESSAGE_TIMEOUT, async {
+        loop {
+            match socket.next().await.expect("the socket closed").unwrap() {
+                Message::Text(text) => {
+                    return serde_json::from_str(&text).unwrap();
+                }
+                Message::Ping(_) | Message::Pong(_) => {}
+                message => panic!("unexpected message: {message:?}"),
+            }
+        }
+    })
+    .await
+    .expect("no activity was received in time")
+}
+
+async fn login(app: &TestApp, user_id: &str) -> Value {
+    app.post(&format!("/api/v1/users/{user_id}/logins"))
+        .json(&json!({
+            "user_agent": "Mozilla/5.0",
+            "ip": "203.0.113.7",
+            "fingerprint": "laptop",
+        }))
+        .send()
+        .await
+        .unwrap()
+        .error_for_status()
+        .unwrap()
+        .json()
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn logins_and_logouts_are_sent_to_monitors() {
+    let app = TestApp::builder().start().await.unwrap();
+    let user = app.create_test_user().await.unwrap();
+    let mut socket = connect(&app, Some(ADMIN_KEY)).await.unwrap();
+
+    let device = login(&app, &user.id.to_string()).await;
+    let device_id = device["device"]["id"].as_str().unwrap();
+    app.post(&format!(
+        "/api/v1/users/{}/devices/{device_id}/revoke",
+        user.id
+    ))
+    .send()
+    .await
+    .unwrap()
+    .error_for_status()
+    .unwrap();
+
+    let activity = next(&mut socket).await;
+    assert_eq!(activity["type"], "login");
+    assert_eq!(activity["user_id"], user.id.to_string());
+    assert_eq!(activity["device_id"], device_id);
+    assert_eq!(activity["new_device"], true);
+    let activity = next(&mut socket).await;
+    assert_eq!(activity["type"], "logout");
+    assert_eq!(activity["device_id"], device_id);
+}
+
+#[tokio::test]
+async fn suspensions_are_sent_to_monitors_as_lockouts() {
+    let app = TestApp::builder().start().await.unwrap();
+    let user = app.create_test_user().await.unwrap();
+    let mut socket = connect(&app, Some(ADMIN_KEY)).await.unwrap();
+
+    let mut conn = app.kit().pool().acquire().await.unwrap();
+    OutboxRepository::new(&mut conn)
+        .emit(
+            &UserEvent::new(NewUserEventAttrs {
+                user_id: user.id,
+                transition: UserLifecycleTransition::Suspended,
+                version: 2,
+            })
+            .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let activity = next(&mut socket).await;
+    assert_eq!(activity["type"], "lockout");
+    assert_eq!(activity["user_id"], user.id.to_string());
+}
+
+#[tokio::test]
+async fn monitors_must_be_admins() {
+    let app = TestApp::builder().start().await.unwrap();
+
+    let error = connect(&app, None).await.unwrap_err();
+
+    let Error::Http(response) = error else {
+        panic!("unexpected error: {error:?}");
+    };
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+async fn verify(app: &TestApp, token: &str) -> reqwest::Response {
+    app.post("/api/v1/sessions/verify")
+        .json(&json!({ "token": token }))
+        .send()
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn logins_issue_sessions_until_their_devices_are_revoked() {
+    let app = TestApp::builder().start().await.unwrap();
+    let user = app.create_test_user().await.unwrap();
+
+    let login = login(&app, &user.id.to_string()).await;
+    let device_id = login["device"]["id"].as_str().unwrap();
+    let token = login["session"]["token"].as_str().unwrap();
+
+    let response = verify(&app, token).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let session: Value = response.json().await.unwrap();
+    assert_eq!(session["user_id"], user.id.to_string());
+    assert_eq!(session["device_id"], device_id);
+    assert_eq!(session["expires_at"], login["session"]["expires_at"]);
+
+    app.post(&format!(
+        "/api/v1/users/{}/devices/{device_id}/revoke",
+        user.id
+    ))
+    .send()
+    .await
+    .unwrap()
+    .error_for_status()
+    .unwrap();
+
+    assert_eq!(verify(&app, token).await.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn unknown_session_tokens_are_not_found() {
+    let app = TestApp::builder().start().await.unwrap();
+
+    assert_eq!(
+        verify(&app, "unknown").await.status(),
+        StatusCode::NOT_FOUND
+    );
+    assert_eq!(
+        verify(&app, "").await.status(),
+        StatusCode::UNPROCESSABLE_ENTITY
+    );
+}
@@ -0,0 +1,221 @@
+use std::time::Duration;
+
+use axum::http::HeaderName;
+use identify::api::tenancy::TenantResolver;
+use identify_testkit::api::{TestApp, json_of};
+use reqwest::{Response, StatusCode, header::HOST};
+use serde_json::{Value, json};
+use tokio::time::timeout;
+
+const TENANT_HEADER: &str = "x-identify-tenant";
+
+const ACME_KEY: &str = "acme-key-0123456789abcdef0123456789";
+const READER_KEY: &str = "reader-key-0123456789abcdef01234567";
+
+async fn app() -> TestApp {
+    let app = TestApp::builder()
+        .with_tenancy(TenantResolver::Header(HeaderName::from_static(
+            TENANT_HEADER,
+        )))
+        .with_tenant_api_key(ACME_KEY, "acme", &["users:read"])
+        .with_api_key(READER_KEY, &["users:read"])
+        .start()
+        .await
+        .unwrap();
+    for slug in ["acme", "globex"] {
+        let response = app
+            .put(&format!("/api/v1/organizations/{slug}"))
+            .json(&json!({ "name": slug }))
+            .send()
+            .await
+            .unwrap();
+        json_of::<Value>(response).await.unwrap();
+    }
+
+    app
+}
+
+#[tokio::test]
+async fn users_are_only_visible_to_their_tenant() {
+    let app = app().await;
+
+    let response = app
+        .post("/api/v1/users")
+        .header(TENANT_HEADER, "acme")
+        .json(&json!({ "email": "jane@acme.test", "first_name": "Jane" }))
+        .send()
+        .await
+        .unwrap();
+    let user: Value = json_of(response).await.unwrap();
+    let path = format!("/api/v1/users/{}", user["id"].as_str().unwrap());
+
+    let own = app.get(&path).header(TENANT_HEADER, "ACME").send().await;
+    let other = app.get(&path).header(TENANT_HEADER, "globex").send().await;
+    let primary = app.get(&path).send().await;
+
+    assert_eq!(own.unwrap().status(), 200);
+    assert_eq!(other.unwrap().status(), 404);
+    assert_eq!(primary.unwrap().status(), 404);
+}
+
+async fn create_user(
+    app: &TestApp,
+    tenant: Option<&str>,
+    email: &str,
+) -> Value {
+    let mut request = app.post("/api/v1/users");
+    if let Some(tenant) = tenant {
+        request = request.header(TENANT_HEADER, tenant);
+    }
+    let response = request
+        .json(&json!({ "email": email, "first_name": "Jane" }))
+        .send()
+        .await
+        .unwrap();
+
+    json_of(response).await.unwrap()
+}
+
+/// Returns the data of the first Server-Sent Event of the stream.
+async fn first_event(mut response: Response) -> Value {
+    assert_eq!(response.status(), StatusCode::OK);
+    let mut buffer = String::new();
+    timeout(Duration::from_secs(5), async {
+        loop {
+            let data = buffer.split("\n\n").find_map(|block| {
+                block.lines().find_map(|line| line.strip_prefix("data:"))
+            });
+            if let Some(data) = data {
+                return serde_json::from_str(data.trim_start()).unwrap();
+            }
+
+            let chunk = response.chunk().await.unwrap().unwrap();
+            buffer.push_str(std::str::from_utf8(&chunk).unwrap());
+        }
+    })
+    .await
+    .expect("no event was received in time")
+}
+
+#[tokio::test]
+async fn events_are_only_streamed_to_their_tenant() {
+    let app = app().await;
+    let subscribe = |last_event_id: Option<&'static str>| {
+        let mut request = app
+            .get("/api/v1/events/stream")
+            .header(TENANT_HEADER, "acme");
+        if let Some(id) = last_event_id {
+            request = request.header("last-event-id", id);
+        }
+        async move { request.send().await.unwrap() }
+    };
+    let live = subscribe(None).await;
+
+    create_user(&app, None, "jane@primary.test").await;
+    create_user(&app, Some("globex"), "jane@globex.test").await;
+    let user = create_user(&app, Some("acme"), "jane@acme.test").await;
+
+    let streamed = first_event(live).await;
+    let resumed = first_event(subscribe(Some("0")).await).await;
+    for event in [streamed, resumed] {
+        assert_eq!(event["data"]["user_id"], user["id"]);
+    }
+}
+
+#[tokio::test]
+async fn keys_are_only_accepted_for_their_tenant() {
+    let app = app().await;
+    let status = |key: &'static str, tenant: Option<&'static str>| {
+        let mut request = app
+            .client()
+            .get(format!("{}/api/v1/users", app.base_url()))
+            .bearer_auth(key);
+        if let Some(tenant) = tenant {
+            request = request.header(TENANT_HEADER, tenant);
+        }
+        async move { request.send().await.unwrap().status() }
+    };
+
+    assert_eq!(status(ACME_KEY, Some("acme")).await, 200);
+    assert_eq!(status(ACME_KEY, Some("globex")).await, 403);
+    assert_eq!(status(ACME_KEY, None).await, 403);
+    assert_eq!(status(READER_KEY, Some("acme")).await, 403);
+    assert_eq!(status(READER_KEY, None).await, 200);
+}
+
+#[tokio::test]
+async fn keys_keep_their_tenant_when_it_is_renamed() {
+    let app = app().await;
+    let response = app
+        .put("/api/v1/organizations/acme")
+        .json(&json!({ "name": "acme", "slug": "acme-corp" }))
+        .send()
+        .await
+        .unwrap();
+    json_of::<Value>(response).await.unwrap();
+
+    let response = app
+        .client()
+        .get(format!("{}/api/v1/users", app.base_url()))
+        .bearer_auth(ACME_KEY)
+        .header(TENANT_HEADER, "acme-corp")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), 200);
+}
+
+#[tokio::test]
+async fn callers_cannot_tell_which_tenants_exist() {
+    let app = app().await;
+    let status = |key: Option<&'static str>, tenant: &'static str| {
+        let mut request = app
+            .client()
+            .get(format!("{}/api/v1/users", app.base_url()))
+            .header(TENANT_HEADER, tenant);
+        if let Some(key) = key {
+            request = request.bearer_auth(key);
+        }
+        async move { request.send().await.unwrap().status() }
+    };
+
+    for tenant in ["globex", "initech"] {
+        assert_eq!(status(None, tenant).await, 401);
+        assert_eq!(status(Some("invalid-key"), tenant).await, 401);
+        assert_eq!(status(Some(ACME_KEY), tenant).await, 403);
+        assert_eq!(status(Some(READER_KEY), tenant).await, 403);
+    }
+}
+
+#[tokio::test]
+async fn unknown_tenants_are_not_found() {
+    let app = app().await;
+
+    let response = app
+        .get("/api/v1/users")
+        .header(TENANT_HEADER, "initech")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), 404);
+    let body: Value = response.json().await.unwrap();
+    assert!(body["detail"].as_str().unwrap().contains("initech"));
+}
+
+#[test]
+fn tenants_are_resolved_from_subdomains() {
+    let resolver = TenantResolver::Subdomain("identify.test".to_owned());
+    let resolve = |host: &str| {
+        let mut headers = axum::http::HeaderMap::new();
+        headers.insert(HOST, host.parse().unwrap());
+        resolver.resolve(&headers)
+    };
+
+    assert_eq!(resolve("acme.identify.test").as_deref(), Some("acme"));
+    assert_eq!(resolve("Acme.Identify.test:8080").as_deref(), Some("acme"));
+    assert_eq!(resolve("identify.test"), None);
+    assert_eq!(resolve("a.b.identify.test"), None);
+    assert_eq!(resolve("acme.example.test"), None);
+}
@@ -0,0 +1,48 @@
+//! Tests of the databases the testkit gives to the other tests.
+
+use identify_testkit::{TestDatabase, Testkit};
+use sqlx::Row as _;
+
+async fn count_roles(pool: &sqlx::SqlitePool) -> i64 {
+    sqlx::query("select count(*) from roles")
+        .fetch_one(pool)
+        .await
+        .unwrap()
+        .get(0)
+}
+
+#[test]
+fn test_databases_are_parsed() {
+    assert_eq!(
+        "memory".parse::<TestDatabase>().unwrap(),
+        TestDatabase::Memory
+    );
+    assert_eq!(
+        " File ".parse::<TestDatabase>().unwrap(),
+        TestDatabase::File
+    );
+    assert!("postgres".parse::<TestDatabase>().is_err());
+}
+
+#[tokio::test]
+async fn readers_of_file_databases_only_see_committed_transactions() {
+    let kit = Testkit::with_database(TestDatabase::File).await.unwrap();
+    let mut tx = kit.pool().begin().await.unwrap();
+    sqlx::query(
+        r#"
+            insert into roles (id, name, permissions, created_at, updated_at)
+            values ((?), 'support', '', datetime('now'), datetime('now'))
+        "#,
+    )
+    .bind(uuid::Uuid::new_v4())
+    .execute(&mut *tx)
+    .await
+    .unwrap();
+
+    let uncommitted = count_roles(kit.read_pool().pool()).await;
+    tx.commit().await.unwrap();
+    let committed = count_roles(kit.read_pool().pool()).await;
+
+    assert_eq!(uncommitted, 0);
+    assert_eq!(committed, 1);
+}
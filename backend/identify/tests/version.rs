@@ -0,0 +1,65 @@
+use axum::{
+    Router,
+    body::Body,
+    http::{HeaderMap, Request, StatusCode, header::LINK},
+    middleware::from_fn_with_state,
+    routing::get,
+};
+use chrono::{TimeZone as _, Utc};
+use identify::api::version::{
+    self, ApiVersion, DEPRECATION_HEADER, Deprecation, SUNSET_HEADER,
+};
+use tower::ServiceExt as _;
+
+/// Serves a route of a version with the deprecation, and returns the headers of its response.
+async fn headers(deprecation: Deprecation) -> HeaderMap {
+    let app = Router::new().nest(
+        "/api/v0",
+        Router::new().route("/users", get(|| async { "[]" })).layer(
+            from_fn_with_state(deprecation, version::announce_deprecation),
+        ),
+    );
+
+    let response = app
+        .oneshot(Request::get("/api/v0/users").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    response.headers().clone()
+}
+
+#[tokio::test]
+async fn deprecated_versions_announce_their_sunset_and_successor() {
+    let headers = headers(Deprecation {
+        since: Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap(),
+        sunset: Some(Utc.with_ymd_and_hms(2026, 7, 1, 12, 30, 0).unwrap()),
+        successor: ApiVersion::V1,
+    })
+    .await;
+
+    assert_eq!(headers[DEPRECATION_HEADER], "@1767225600");
+    assert_eq!(headers[SUNSET_HEADER], "Wed, 01 Jul 2026 12:30:00 GMT");
+    assert_eq!(headers[LINK], "</api/v1>; rel=\"successor-version\"");
+}
+
+#[tokio::test]
+async fn versions_without_a_sunset_only_announce_their_deprecation() {
+    let headers = headers(Deprecation {
+        since: Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap(),
+        sunset: None,
+        successor: ApiVersion::V1,
+    })
+    .await;
+
+    assert_eq!(headers[DEPRECATION_HEADER], "@1767225600");
+    assert!(!headers.contains_key(SUNSET_HEADER));
+    assert_eq!(headers[LINK], "</api/v1>; rel=\"successor-version\"");
+}
+
+#[test]
+fn the_latest_version_is_not_deprecated() {
+    let latest = ApiVersion::ALL.last().unwrap();
+
+    assert!(latest.deprecation().is_none());
+}
@@ -0,0 +1,436 @@
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use axum::{
+    Router,
+    body::Bytes,
+    extract::State,
+    http::{HeaderMap, StatusCode, header::LOCATION},
+    routing::post,
+};
+use identify::{
+    config::WebhooksConfig,
+    status::StatusBoard,
+    webhooks::{self, WebhookWorker},
+};
+use identify_application::{
+    ApplicationError, EventPublisher, ListWebhookDeliveriesParams,
+    RegisterWebhookEndpointParams, WebhookDeliveryUseCaseDeps,
+    WebhookEndpointUseCaseDeps, list_webhook_deliveries,
+    register_webhook_endpoint,
+};
+use identify_domain::{
+    NewWebhookEndpointAttrs, UserEvent, UserLifecycleTransition,
+    WebhookDelivery, WebhookDeliveryStatus, WebhookUrlPolicy,
+};
+use identify_infrastructure::storage::{
+    self, unit_of_work::SqliteUnitOfWork, webhooks::WebhookEndpointsRepository,
+};
+use identify_testkit::Testkit;
+use uuid::Uuid;
+
+const SECRET: &str = "0123456789abcdef";
+
+/// Requests received by a [Receiver], as their headers and bodies.
+type Received = Arc<Mutex<Vec<(HeaderMap, Bytes)>>>;
+
+/// A local endpoint that records everything posted to it.
+struct Receiver {
+    url: String,
+    received: Received,
+}
+
+impl Receiver {
+    async fn start(status: StatusCode) -> Receiver {
+        let received = Received::default();
+        let app = Router::new()
+            .route(
+                "/hook",
+                post(
+                    move |State(received): State<Received>,
+                          headers: HeaderMap,
+                          body: Bytes| async move {
+                        received.lock().unwrap().push((headers, body));
+                        status
+                    },
+                ),
+            )
+            .with_state(received.clone());
+
+        let listener =
+            tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        Receiver { url, received }
+    }
+
+    fn received(&self) -> Vec<(HeaderMap, Bytes)> {
+        self.received.lock().unwrap().clone()
+    }
+}
+
+/// Starts an endpoint that redirects everything posted to it to the URL.
+async fn redirect_to(url: &str) -> String {
+    let location = url.to_owned();
+    let app = Router::new().route(
+        "/hook",
+        post(move || async move {
+            (StatusCode::TEMPORARY_REDIRECT, [(LOCATION, location)])
+        }),
+    );
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/hook", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await });
+
+    url
+}
+
+async fn try_register(
+    kit: &Testkit,
+    url: &str,
+    url_policy: WebhookUrlPolicy,
+) -> identify_application::Result<Uuid> {
+    let mut tx = storage::begin(kit.pool()).await.unwrap();
+    let mut repository =
+        WebhookEndpointsRepository::new(&mut tx, kit.encryption());
+    let endpoint = register_webhook_endpoint(
+        WebhookEndpointUseCaseDeps::new(&mut repository),
+        RegisterWebhookEndpointParams {
+            endpoint_attrs: NewWebhookEndpointAttrs {
+                url: url.to_owned(),
+                secret: SECRET.to_owned(),
+                events: vec![],
+                external_id: None,
+            },
+            url_policy,
+        },
+    )
+    .await?;
+    storage::commit(tx).await.unwrap();
+
+    Ok(endpoint.id())
+}
+
+async fn register(
+    kit: &Testkit,
+    url: &str,
+    events: Vec<UserLifecycleTransition>,
+) -> Uuid {
+    let mut tx = storage::begin(kit.pool()).await.unwrap();
+    let mut repository =
+        WebhookEndpointsRepository::new(&mut tx, kit.encryption());
+    let endpoint = register_webhook_endpoint(
+        WebhookEndpointUseCaseDeps::new(&mut repository),
+        RegisterWebhookEndpointParams {
+            endpoint_attrs: NewWebhookEndpointAttrs {
+                url: url.to_owned(),
+                secret: SECRET.to_owned(),
+                events,
+                external_id: None,
+            },
+            url_policy: WebhookUrlPolicy { allow_http: true },
+        },
+    )
+    .await
+    .unwrap();
+    storage::commit(tx).await.unwrap();
+
+    endpoint.id()
+}
+
+/// Returns the deliveries to the endpoint that are still pending.
+async fn pending(kit: &Testkit, endpoint_id: Uuid) -> Vec<WebhookDelivery> {
+    let mut uow = SqliteUnitOfWork::begin(kit.pool(), kit.encryption())
+        .await
+        .unwrap();
+
+    list_webhook_deliveries(
+        WebhookDeliveryUseCaseDeps::new(&mut uow),
+        ListWebhookDeliveriesParams {
+            endpoint_id,
+            status: WebhookDeliveryStatus::Pending,
+            limit: 10,
+        },
+    )
+    .await
+    .unwrap()
+}
+
+/// Configuration that lets events be delivered to the [Receivers](Receiver), which are served
+/// over plain HTTP on the loopback interface.
+fn local() -> WebhooksConfig {
+    WebhooksConfig {
+        allow_http: true,
+        allow_private_networks: true,
+        ..WebhooksConfig::default()
+    }
+}
+
+fn worker(kit: &Testkit, config: WebhooksConfig) -> WebhookWorker {
+    WebhookWorker::from_config(
+        kit.pool().clone(),
+        kit.encryption().clone(),
+        &config,
+        StatusBoard::new(),
+        None,
+    )
+    .unwrap()
+    .unwrap()
+}
+
+/// A publisher that remembers the IDs of the published events.
+#[derive(Clone, Default)]
+struct RecordingPublisher(Arc<Mutex<Vec<Uuid>>>);
+
+#[async_trait]
+impl EventPublisher for RecordingPublisher {
+    async fn publish(
+        &self,
+        event: &UserEvent,
+    ) -> identify_application::Result<()> {
+        self.0.lock().unwrap().push(event.id());
+        Ok(())
+    }
+}
+
+/// A publisher that is always unavailable.
+struct FailingPublisher;
+
+#[async_trait]
+impl EventPublisher for FailingPublisher {
+    async fn publish(
+        &self,
+        _event: &UserEvent,
+    ) -> identify_application::Result<()> {
+        Err(ApplicationError::Unavailable)
+    }
+}
+
+#[tokio::test]
+async fn delivers_signed_events_to_subscribed_endpoints() {
+    let kit = Testkit::new().await.unwrap();
+    let receiver = Receiver::start(StatusCode::NO_CONTENT).await;
+    register(&kit, &receiver.url, vec![]).await;
+    let unsubscribed = Receiver::start(StatusCode::NO_CONTENT).await;
+    register(
+        &kit,
+        &unsubscribed.url,
+        vec![UserLifecycleTransition::Deleted],
+    )
+    .await;
+
+    let user = kit.fixtures().user().create().await.unwrap();
+    let worker = worker(&kit, local());
+    worker.run_once().await.unwrap();
+    // Delivered events aren't delivered again.
+    worker.run_once().await.unwrap();
+
+    let received = receiver.received();
+    assert_eq!(received.len(), 1);
+    let (headers, body) = &received[0];
+
+    let timestamp: i64 = headers[webhooks::TIMESTAMP_HEADER]
+        .to_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert_eq!(
+        headers[webhooks::SIGNATURE_HEADER],
+        webhooks::sign(SECRET, timestamp, body),
+    );
+    assert_eq!(headers[webhooks::EVENT_HEADER], "user.created");
+
+    let payload: serde_json::Value = serde_json::from_slice(body).unwrap();
+    assert_eq!(payload["type"], "user.created");
+    assert_eq!(payload["data"]["user_id"], user.id().to_string());
+
+    assert!(unsubscribed.received().is_empty());
+}
+
+#[tokio::test]
+async fn failing_deliveries_become_dead_letters() {
+    let kit = Testkit::new().await.unwrap();
+    let receiver = Receiver::start(StatusCode::INTERNAL_SERVER_ERROR).await;
+    let endpoint_id = register(&kit, &receiver.url, vec![]).await;
+
+    kit.fixtures().user().create().await.unwrap();
+    let worker = worker(
+        &kit,
+        WebhooksConfig {
+            max_attempts: 2,
+            initial_backoff_secs: 0,
+            max_backoff_secs: 0,
+            ..local()
+        },
+    );
+    for _ in 0..3 {
+        worker.run_once().await.unwrap();
+    }
+
+    assert_eq!(receiver.received().len(), 2);
+
+    let mut uow = SqliteUnitOfWork::begin(kit.pool(), kit.encryption())
+        .await
+        .unwrap();
+    let dead = list_webhook_deliveries(
+        WebhookDeliveryUseCaseDeps::new(&mut uow),
+        ListWebhookDeliveriesParams {
+            endpoint_id,
+            status: WebhookDeliveryStatus::Dead,
+            limit: 10,
+        },
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(dead.len(), 1);
+    assert_eq!(dead[0].attempts(), 2);
+    assert!(dead[0].last_error().is_some());
+}
+
+#[tokio::test]
+async fn streams_events_even_if_deliveries_are_disabled() {
+    let kit = Testkit::new().await.unwrap();
+    let receiver = Receiver::start(StatusCode::NO_CONTENT).await;
+    register(&kit, &receiver.url, vec![]).await;
+
+    kit.fixtures().user().create().await.unwrap();
+    let publisher = RecordingPublisher::default();
+    let worker = WebhookWorker::from_config(
+        kit.pool().clone(),
+        kit.encryption().clone(),
+        &WebhooksConfig {
+            enabled: false,
+            ..local()
+        },
+        StatusBoard::new(),
+        Some(Arc::new(publisher.clone())),
+    )
+    .unwrap()
+    .unwrap();
+    worker.run_once().await.unwrap();
+    worker.run_once().await.unwrap();
+
+    assert_eq!(publisher.0.lock().unwrap().len(), 1);
+    assert!(receiver.received().is_empty());
+}
+
+#[tokio::test]
+async fn events_that_fail_to_be_published_are_dispatched_again() {
+    let kit = Testkit::new().await.unwrap();
+    kit.fixtures().user().create().await.unwrap();
+    let failing = WebhookWorker::from_config(
+        kit.pool().clone(),
+        kit.encryption().clone(),
+        &local(),
+        StatusBoard::new(),
+        Some(Arc::new(FailingPublisher)),
+    )
+    .unwrap()
+    .unwrap();
+
+    assert!(failing.run_once().await.is_err());
+
+    let publisher = RecordingPublisher::default();
+    let worker = WebhookWorker::from_config(
+        kit.pool().clone(),
+        kit.encryption().clone(),
+        &local(),
+        StatusBoard::new(),
+        Some(Arc::new(publisher.clone())),
+    )
+    .unwrap()
+    .unwrap();
+    worker.run_once().await.unwrap();
+
+    assert_eq!(publisher.0.lock().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn plain_http_endpoints_are_refused_unless_allowed() {
+    let kit = Testkit::new().await.unwrap();
+
+    let refused = try_register(
+        &kit,
+        "http://hooks.example.com/identify",
+        WebhookUrlPolicy::default(),
+    )
+    .await;
+    assert!(matches!(refused, Err(ApplicationError::Domain(_))));
+
+    let receiver = Receiver::start(StatusCode::NO_CONTENT).await;
+    let endpoint_id = register(&kit, &receiver.url, vec![]).await;
+    kit.fixtures().user().create().await.unwrap();
+    worker(
+        &kit,
+        WebhooksConfig {
+            allow_http: false,
+            ..local()
+        },
+    )
+    .run_once()
+    .await
+    .unwrap();
+
+    assert!(receiver.received().is_empty());
+    let pending = pending(&kit, endpoint_id).await;
+    assert!(
+        pending[0]
+            .last_error()
+            .as_deref()
+            .unwrap()
+            .contains("HTTPS")
+    );
+}
+
+#[tokio::test]
+async fn events_are_not_delivered_to_private_addresses() {
+    let kit = Testkit::new().await.unwrap();
+    let receiver = Receiver::start(StatusCode::NO_CONTENT).await;
+    let by_address = register(&kit, &receiver.url, vec![]).await;
+    let by_name = register(
+        &kit,
+        &receiver.url.replace("127.0.0.1", "localhost"),
+        vec![],
+    )
+    .await;
+
+    kit.fixtures().user().create().await.unwrap();
+    worker(
+        &kit,
+        WebhooksConfig {
+            allow_private_networks: false,
+            ..local()
+        },
+    )
+    .run_once()
+    .await
+    .unwrap();
+
+    assert!(receiver.received().is_empty());
+    let pending_by_address = pending(&kit, by_address).await;
+    assert!(
+        pending_by_address[0]
+            .last_error()
+            .as_deref()
+            .unwrap()
+            .contains("isn't a public address")
+    );
+    assert_eq!(pending(&kit, by_name).await.len(), 1);
+}
+
+#[tokio::test]
+async fn redirects_are_not_followed() {
+    let kit = Testkit::new().await.unwrap();
+    let receiver = Receiver::start(StatusCode::NO_CONTENT).await;
+    let endpoint_id =
+        register(&kit, &redirect_to(&receiver.url).await, vec![]).await;
+
+    kit.fixtures().user().create().await.unwrap();
+    worker(&kit, local()).run_once().await.unwrap();
+
+    assert!(receiver.received().is_empty());
+    assert_eq!(pending(&kit, endpoint_id).await.len(), 1);
+}